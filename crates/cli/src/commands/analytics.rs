@@ -0,0 +1,206 @@
+use crate::commands::CommandResult;
+use quotey_core::chrono::NaiveDate;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_core::{AnalyticsFilter, AnalyticsQuerySpec};
+use quotey_db::analytics::{AnalyticsCache, AnalyticsResult, AnalyticsService};
+use quotey_db::{connect_with_settings, migrations};
+use serde::Serialize;
+
+const COMMAND: &str = "analytics";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn parse_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Command-line shape of an analytics query; `spec_json` replaces every other field.
+#[derive(Clone, Debug, Default)]
+pub struct AnalyticsArgs {
+    pub metrics: Vec<String>,
+    pub dimensions: Vec<String>,
+    pub lookback_days: u32,
+    pub finalized_only: bool,
+    /// `dimension=value[,value…]`, repeatable.
+    pub filters: Vec<String>,
+    pub end_date: Option<String>,
+    pub limit: Option<u32>,
+    pub spec_json: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct QueryOutput {
+    command: &'static str,
+    status: &'static str,
+    #[serde(flatten)]
+    result: AnalyticsResult,
+}
+
+/// Runs an analytics query and prints the result as JSON or CSV.
+pub fn run(args: AnalyticsArgs, format: String) -> CommandResult {
+    let Some(format) = OutputFormat::parse_label(&format) else {
+        return CommandResult::failure(
+            COMMAND,
+            "invalid_input",
+            format!("unknown format `{format}`; expected json or csv"),
+            2,
+        );
+    };
+    let spec = match build_spec(args) {
+        Ok(spec) => spec,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_input", message, 2),
+    };
+
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        // A one-shot process gains nothing from caching.
+        let result = AnalyticsService::new(pool.clone())
+            .with_cache(AnalyticsCache::with_ttl(std::time::Duration::ZERO))
+            .run(&spec)
+            .await
+            .map_err(|error| ("analytics_query", error.to_string(), 6u8));
+        pool.close().await;
+        result
+    });
+
+    match result {
+        Ok(result) if format == OutputFormat::Csv => {
+            CommandResult { exit_code: 0, output: result.to_csv().trim_end().to_string() }
+        }
+        Ok(result) => {
+            let payload = QueryOutput { command: COMMAND, status: "ok", result };
+            match serde_json::to_string_pretty(&payload) {
+                Ok(output) => CommandResult { exit_code: 0, output },
+                Err(error) => {
+                    CommandResult::failure(COMMAND, "serialization", error.to_string(), 8)
+                }
+            }
+        }
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(COMMAND, error_class, message, exit_code)
+        }
+    }
+}
+
+fn build_spec(args: AnalyticsArgs) -> Result<AnalyticsQuerySpec, String> {
+    if let Some(raw) = args.spec_json {
+        let spec: AnalyticsQuerySpec =
+            serde_json::from_str(&raw).map_err(|error| format!("invalid --spec-json: {error}"))?;
+        spec.validate().map_err(|error| error.to_string())?;
+        return Ok(spec);
+    }
+
+    let mut spec =
+        AnalyticsQuerySpec::from_labels(&args.metrics, &args.dimensions, args.lookback_days)
+            .map_err(|error| error.to_string())?;
+    spec.include_only_finalized = args.finalized_only;
+    for raw in &args.filters {
+        let (dimension, values) = raw
+            .split_once('=')
+            .ok_or_else(|| format!("filter `{raw}` must look like dimension=value[,value]"))?;
+        let values = values.split(',').map(|value| value.trim().to_string()).collect();
+        spec.filters.push(
+            AnalyticsFilter::from_label(dimension, values).map_err(|error| error.to_string())?,
+        );
+    }
+    if let Some(raw) = args.end_date {
+        spec.end_date = Some(
+            NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("--end-date `{raw}` must be YYYY-MM-DD"))?,
+        );
+    }
+    spec.limit = args.limit;
+    spec.validate().map_err(|error| error.to_string())?;
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::{DimensionKind, MetricKind};
+
+    use super::*;
+
+    #[test]
+    fn build_spec_parses_flags_and_filters() {
+        let spec = build_spec(AnalyticsArgs {
+            metrics: vec!["quote_count".to_string(), "win_rate_pct".to_string()],
+            dimensions: vec!["month".to_string()],
+            lookback_days: 90,
+            filters: vec!["region=emea, amer".to_string()],
+            end_date: Some("2026-09-30".to_string()),
+            limit: Some(25),
+            ..AnalyticsArgs::default()
+        })
+        .expect("spec");
+        assert_eq!(spec.metrics, vec![MetricKind::QuoteCount, MetricKind::WinRatePct]);
+        assert_eq!(spec.dimensions, vec![DimensionKind::Month]);
+        assert_eq!(spec.filters[0].dimension, DimensionKind::Region);
+        assert_eq!(spec.filters[0].values, vec!["emea".to_string(), "amer".to_string()]);
+        assert_eq!(spec.end_date, NaiveDate::from_ymd_opt(2026, 9, 30));
+        assert_eq!(spec.limit, Some(25));
+    }
+
+    #[test]
+    fn build_spec_rejects_malformed_input() {
+        let args = |filters: Vec<&str>| AnalyticsArgs {
+            metrics: vec!["quote_count".to_string()],
+            lookback_days: 30,
+            filters: filters.into_iter().map(str::to_string).collect(),
+            ..AnalyticsArgs::default()
+        };
+        assert!(build_spec(args(vec!["region"])).unwrap_err().contains("dimension=value"));
+        assert!(build_spec(args(vec!["planet=mars"])).unwrap_err().contains("planet"));
+        assert!(build_spec(AnalyticsArgs { lookback_days: 30, ..AnalyticsArgs::default() })
+            .unwrap_err()
+            .contains("at least one metric"));
+        assert!(build_spec(AnalyticsArgs {
+            spec_json: Some(r#"{"schema_version":"v0"}"#.to_string()),
+            ..AnalyticsArgs::default()
+        })
+        .is_err());
+    }
+}
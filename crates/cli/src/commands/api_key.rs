@@ -0,0 +1,321 @@
+use crate::commands::CommandResult;
+use quotey_core::chrono::Utc;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_core::{
+    generate_api_key_secret, hash_api_key, ApiKeyGrants, ApiKeyRecord, ApiScope, API_KEY_PREFIX_LEN,
+};
+use quotey_db::repositories::{ApiKeyRepository, SqlApiKeyRepository};
+use quotey_db::{connect_with_settings, migrations, DbPool};
+use serde::Serialize;
+
+type CommandError = (&'static str, String, u8);
+
+/// Key metadata printed by every subcommand. Never includes the secret.
+#[derive(Debug, Serialize)]
+struct ApiKeySummary {
+    id: String,
+    name: String,
+    key_prefix: String,
+    scopes: Vec<ApiScope>,
+    account_ids: Vec<String>,
+    team_ids: Vec<String>,
+    requests_per_minute: u32,
+    active: bool,
+    rotated_from: Option<String>,
+    created_at: String,
+    revoked_at: Option<String>,
+    last_used_at: Option<String>,
+}
+
+impl From<&ApiKeyRecord> for ApiKeySummary {
+    fn from(record: &ApiKeyRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            name: record.name.clone(),
+            key_prefix: record.key_prefix.clone(),
+            scopes: record.grants.scopes.clone(),
+            account_ids: record.grants.account_ids.clone(),
+            team_ids: record.grants.team_ids.clone(),
+            requests_per_minute: record.requests_per_minute,
+            active: record.active,
+            rotated_from: record.rotated_from.clone(),
+            created_at: record.created_at.to_rfc3339(),
+            revoked_at: record.revoked_at.map(|ts| ts.to_rfc3339()),
+            last_used_at: record.last_used_at.map(|ts| ts.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct IssuedKeyOutput {
+    command: &'static str,
+    status: &'static str,
+    /// Plaintext secret. Shown once; only its hash is stored.
+    api_key: String,
+    key: ApiKeySummary,
+}
+
+#[derive(Debug, Serialize)]
+struct KeyListOutput {
+    command: &'static str,
+    status: &'static str,
+    keys: Vec<ApiKeySummary>,
+}
+
+pub fn run_create(
+    name: String,
+    scopes: Vec<String>,
+    account_ids: Vec<String>,
+    team_ids: Vec<String>,
+    requests_per_minute: u32,
+) -> CommandResult {
+    const COMMAND: &str = "api-key-create";
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return CommandResult::failure(COMMAND, "invalid_argument", "--name must not be blank", 2);
+    }
+    if requests_per_minute == 0 {
+        return CommandResult::failure(
+            COMMAND,
+            "invalid_argument",
+            "--requests-per-minute must be greater than zero",
+            2,
+        );
+    }
+    let grants = match parse_grants(scopes, account_ids, team_ids) {
+        Ok(grants) => grants,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_argument", message, 2),
+    };
+
+    with_repository(COMMAND, |repo| async move {
+        let (secret, record) = issue_key(name, grants, requests_per_minute, None);
+        repo.create(&record).await.map_err(|error| ("api_key_store", error.to_string(), 6u8))?;
+        Ok(issued_output(COMMAND, secret, &record))
+    })
+}
+
+pub fn run_list(include_revoked: bool) -> CommandResult {
+    const COMMAND: &str = "api-key-list";
+
+    with_repository(COMMAND, |repo| async move {
+        let keys = repo
+            .list(include_revoked)
+            .await
+            .map_err(|error| ("api_key_store", error.to_string(), 6u8))?;
+        let payload = KeyListOutput {
+            command: COMMAND,
+            status: "ok",
+            keys: keys.iter().map(ApiKeySummary::from).collect(),
+        };
+        Ok(to_json(COMMAND, &payload))
+    })
+}
+
+pub fn run_rotate(id: String) -> CommandResult {
+    const COMMAND: &str = "api-key-rotate";
+
+    with_repository(COMMAND, |repo| async move {
+        let existing = repo
+            .find_by_id(id.trim())
+            .await
+            .map_err(|error| ("api_key_store", error.to_string(), 6u8))?
+            .filter(|record| record.active)
+            .ok_or_else(|| ("not_found", format!("no active API key with id `{id}`"), 7u8))?;
+
+        let (secret, replacement) = issue_key(
+            existing.name.clone(),
+            existing.grants.clone(),
+            existing.requests_per_minute,
+            Some(existing.id.clone()),
+        );
+        let rotated = repo
+            .rotate(&existing.id, &replacement)
+            .await
+            .map_err(|error| ("api_key_store", error.to_string(), 6u8))?;
+        if !rotated {
+            return Err(("not_found", format!("API key `{id}` was revoked concurrently"), 7u8));
+        }
+        Ok(issued_output(COMMAND, secret, &replacement))
+    })
+}
+
+pub fn run_revoke(id: String) -> CommandResult {
+    const COMMAND: &str = "api-key-revoke";
+
+    with_repository(COMMAND, |repo| async move {
+        let revoked = repo
+            .revoke(id.trim(), Utc::now())
+            .await
+            .map_err(|error| ("api_key_store", error.to_string(), 6u8))?;
+        if !revoked {
+            return Err(("not_found", format!("no active API key with id `{id}`"), 7u8));
+        }
+        Ok(CommandResult::success(COMMAND, format!("revoked API key {}", id.trim())))
+    })
+}
+
+fn parse_grants(
+    scopes: Vec<String>,
+    account_ids: Vec<String>,
+    team_ids: Vec<String>,
+) -> Result<ApiKeyGrants, String> {
+    if scopes.is_empty() {
+        return Err("at least one --scope is required".to_string());
+    }
+    let mut parsed = Vec::with_capacity(scopes.len());
+    for label in scopes.iter().flat_map(|raw| raw.split(',')) {
+        let scope = ApiScope::parse_label(label).ok_or_else(|| {
+            let known: Vec<&str> = ApiScope::ALL_SCOPES.iter().map(|s| s.as_str()).collect();
+            format!("unknown scope `{}`; expected one of {}", label.trim(), known.join(", "))
+        })?;
+        if !parsed.contains(&scope) {
+            parsed.push(scope);
+        }
+    }
+    parsed.sort();
+
+    let clean = |values: Vec<String>| -> Vec<String> {
+        let mut values: Vec<String> = values
+            .into_iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect();
+        values.sort();
+        values.dedup();
+        values
+    };
+
+    Ok(ApiKeyGrants { scopes: parsed, account_ids: clean(account_ids), team_ids: clean(team_ids) })
+}
+
+fn issue_key(
+    name: String,
+    grants: ApiKeyGrants,
+    requests_per_minute: u32,
+    rotated_from: Option<String>,
+) -> (String, ApiKeyRecord) {
+    let secret = generate_api_key_secret();
+    let key_hash = hash_api_key(&secret);
+    let record = ApiKeyRecord {
+        id: format!("KEY-{}", &key_hash[..12]),
+        name,
+        key_prefix: secret.chars().take(API_KEY_PREFIX_LEN).collect(),
+        key_hash,
+        grants,
+        requests_per_minute,
+        active: true,
+        rotated_from,
+        created_at: Utc::now(),
+        revoked_at: None,
+        last_used_at: None,
+    };
+    (secret, record)
+}
+
+fn issued_output(command: &'static str, secret: String, record: &ApiKeyRecord) -> CommandResult {
+    let payload = IssuedKeyOutput {
+        command,
+        status: "ok",
+        api_key: secret,
+        key: ApiKeySummary::from(record),
+    };
+    to_json(command, &payload)
+}
+
+fn to_json<T: Serialize>(command: &str, payload: &T) -> CommandResult {
+    match serde_json::to_string_pretty(payload) {
+        Ok(output) => CommandResult { exit_code: 0, output },
+        Err(error) => CommandResult::failure(command, "serialization", error.to_string(), 8),
+    }
+}
+
+fn with_repository<F, Fut>(command: &str, action: F) -> CommandResult
+where
+    F: FnOnce(SqlApiKeyRepository) -> Fut,
+    Fut: std::future::Future<Output = Result<CommandResult, CommandError>>,
+{
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool: DbPool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let outcome = action(SqlApiKeyRepository::new(pool.clone())).await;
+        pool.close().await;
+        outcome
+    });
+
+    match result {
+        Ok(output) => output,
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(command, error_class, message, exit_code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_grants_accepts_repeated_and_comma_separated_scopes() {
+        let grants = parse_grants(
+            vec!["quote:write,quote:read".to_string(), "catalog:read".to_string()],
+            vec![" acct-2 ".to_string(), "acct-1".to_string(), "acct-1".to_string()],
+            Vec::new(),
+        )
+        .expect("grants");
+        assert_eq!(
+            grants.scopes,
+            vec![ApiScope::CatalogRead, ApiScope::QuoteRead, ApiScope::QuoteWrite]
+        );
+        assert_eq!(grants.account_ids, vec!["acct-1".to_string(), "acct-2".to_string()]);
+    }
+
+    #[test]
+    fn parse_grants_rejects_missing_and_unknown_scopes() {
+        assert!(parse_grants(Vec::new(), Vec::new(), Vec::new()).is_err());
+        let err = parse_grants(vec!["quote:delete".to_string()], Vec::new(), Vec::new())
+            .expect_err("unknown scope");
+        assert!(err.contains("quote:delete"));
+    }
+
+    #[test]
+    fn issued_key_stores_only_hash_and_prefix() {
+        let (secret, record) = issue_key("ci".to_string(), ApiKeyGrants::unrestricted(), 60, None);
+        assert_eq!(record.key_hash, hash_api_key(&secret));
+        assert!(secret.starts_with(&record.key_prefix));
+        assert_eq!(record.key_prefix.len(), API_KEY_PREFIX_LEN);
+        assert!(!record.id.contains(&secret));
+    }
+}
@@ -0,0 +1,237 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use crate::commands::CommandResult;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_db::catalog::{
+    CatalogDiff, CatalogDocument, CatalogError, CatalogFormat, CatalogIssue, CatalogService,
+    ImportRequest,
+};
+use quotey_db::{connect_with_settings, migrations};
+use serde::Serialize;
+
+type CommandError = (&'static str, String, u8);
+
+const CLI_ACTOR: &str = "cli-operator";
+
+#[derive(Debug, Serialize)]
+struct ImportOutput {
+    command: &'static str,
+    status: &'static str,
+    file: String,
+    format: &'static str,
+    /// `true` unless `--apply` was given; nothing was written.
+    dry_run: bool,
+    import_id: Option<String>,
+    #[serde(flatten)]
+    diff: CatalogDiff,
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidOutput {
+    command: &'static str,
+    status: &'static str,
+    error_class: &'static str,
+    message: String,
+    issues: Vec<CatalogIssue>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportOutput {
+    command: &'static str,
+    status: &'static str,
+    out: String,
+    format: &'static str,
+    families: usize,
+    products: usize,
+    inactive_products: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportArgs {
+    pub file: PathBuf,
+    /// `json` or `csv`; guessed from the file extension when `None`.
+    pub format: Option<String>,
+    /// Write the changes; without it the import is a dry run that only reports the diff.
+    pub apply: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportArgs {
+    pub out: PathBuf,
+    pub format: Option<String>,
+}
+
+/// Diffs a catalog file against the stored catalog and, with `--apply`, imports it.
+pub fn run_import(args: ImportArgs) -> CommandResult {
+    const COMMAND: &str = "catalog-import";
+
+    let format = match resolve_format(args.format.as_deref(), &args.file) {
+        Ok(format) => format,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_format", message, 2),
+    };
+    let document = match std::fs::read_to_string(&args.file)
+        .map_err(|error| format!("cannot read {}: {error}", args.file.display()))
+        .and_then(|raw| CatalogDocument::parse(format, &raw).map_err(|error| error.to_string()))
+    {
+        Ok(document) => document,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_catalog_file", message, 2),
+    };
+
+    with_service(COMMAND, |service| async move {
+        let request = ImportRequest {
+            document,
+            dry_run: !args.apply,
+            actor: CLI_ACTOR.to_string(),
+            source: args.file.display().to_string(),
+        };
+        let report = match service.import(request).await {
+            Ok(report) => report,
+            Err(CatalogError::Invalid(issues)) => {
+                let payload = InvalidOutput {
+                    command: COMMAND,
+                    status: "error",
+                    error_class: "invalid_catalog",
+                    message: CatalogError::Invalid(issues.clone()).to_string(),
+                    issues,
+                };
+                return Ok(to_json(COMMAND, &payload, 6));
+            }
+            Err(error) => return Err(("catalog_import", error.to_string(), 6u8)),
+        };
+        let payload = ImportOutput {
+            command: COMMAND,
+            status: "ok",
+            file: args.file.display().to_string(),
+            format: format.as_str(),
+            dry_run: report.dry_run,
+            import_id: report.import_id,
+            diff: report.diff,
+        };
+        Ok(to_json(COMMAND, &payload, 0))
+    })
+}
+
+/// Writes the stored catalog, inactive products included, as JSON or CSV.
+pub fn run_export(args: ExportArgs) -> CommandResult {
+    const COMMAND: &str = "catalog-export";
+
+    let format = match resolve_format(args.format.as_deref(), &args.out) {
+        Ok(format) => format,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_format", message, 2),
+    };
+
+    with_service(COMMAND, |service| async move {
+        let document =
+            service.export().await.map_err(|error| ("catalog_export", error.to_string(), 6u8))?;
+        std::fs::write(&args.out, document.render(format)).map_err(|error| {
+            ("catalog_write", format!("cannot write {}: {error}", args.out.display()), 6u8)
+        })?;
+        let payload = ExportOutput {
+            command: COMMAND,
+            status: "ok",
+            out: args.out.display().to_string(),
+            format: format.as_str(),
+            families: document.families.len(),
+            products: document.products.len(),
+            inactive_products: document.products.iter().filter(|product| !product.active).count(),
+        };
+        Ok(to_json(COMMAND, &payload, 0))
+    })
+}
+
+fn resolve_format(format: Option<&str>, path: &Path) -> Result<CatalogFormat, String> {
+    match format {
+        Some(label) => CatalogFormat::parse_label(label)
+            .ok_or_else(|| format!("format must be json or csv, not '{label}'")),
+        None => CatalogFormat::from_path(path).ok_or_else(|| {
+            format!("cannot tell the format of {}; pass --format json|csv", path.display())
+        }),
+    }
+}
+
+fn to_json<T: Serialize>(command: &str, payload: &T, exit_code: u8) -> CommandResult {
+    match serde_json::to_string_pretty(payload) {
+        Ok(output) => CommandResult { exit_code, output },
+        Err(error) => CommandResult::failure(command, "serialization", error.to_string(), 8),
+    }
+}
+
+fn with_service<F, Fut>(command: &str, action: F) -> CommandResult
+where
+    F: FnOnce(CatalogService) -> Fut,
+    Fut: Future<Output = Result<CommandResult, CommandError>>,
+{
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let outcome = action(CatalogService::new(pool.clone())).await;
+        pool.close().await;
+        outcome
+    });
+
+    match result {
+        Ok(output) => output,
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(command, error_class, message, exit_code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_comes_from_the_flag_or_the_file_extension() {
+        assert_eq!(resolve_format(None, Path::new("catalog.CSV")), Ok(CatalogFormat::Csv));
+        assert_eq!(resolve_format(Some("json"), Path::new("catalog.txt")), Ok(CatalogFormat::Json));
+        assert!(resolve_format(None, Path::new("catalog")).is_err());
+        assert!(resolve_format(Some("xlsx"), Path::new("catalog.csv")).is_err());
+    }
+
+    #[test]
+    fn import_rejects_unreadable_files_before_touching_the_database() {
+        let path =
+            std::env::temp_dir().join(format!("quotey-catalog-{}-bad.csv", std::process::id()));
+        std::fs::write(&path, "record,sku\nwidget,W-1\n").expect("write sheet");
+        let result = run_import(ImportArgs { file: path.clone(), format: None, apply: false });
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(result.exit_code, 2, "{}", result.output);
+        assert!(result.output.contains("invalid_catalog_file"));
+        assert!(result.output.contains("line 2"));
+    }
+}
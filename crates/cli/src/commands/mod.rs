@@ -1,10 +1,17 @@
+pub mod analytics;
+pub mod api_key;
+pub mod catalog;
 pub mod config;
 pub mod doctor;
 pub mod genome;
 pub mod migrate;
+pub mod model;
+pub mod policy;
 pub mod policy_packet;
+pub mod replay;
 pub mod rule_preview;
 pub mod seed;
+pub mod similarity;
 pub mod smoke;
 pub mod start;
 
@@ -0,0 +1,161 @@
+use crate::commands::CommandResult;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_core::ml::TrainingConfig;
+use quotey_db::win_probability::{
+    ModelVersionRecord, TrainingRun, WinProbabilityError, WinProbabilityService,
+};
+use quotey_db::{connect_with_settings, migrations, DbPool};
+use serde::Serialize;
+
+type CommandError = (&'static str, String, u8);
+
+#[derive(Debug, Serialize)]
+struct TrainOutput {
+    command: &'static str,
+    status: &'static str,
+    promoted: bool,
+    #[serde(flatten)]
+    run: TrainingRun,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelOutput {
+    command: &'static str,
+    status: &'static str,
+    model: ModelVersionRecord,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelListOutput {
+    command: &'static str,
+    status: &'static str,
+    models: Vec<ModelVersionRecord>,
+}
+
+/// Trains a new win-probability version from recorded deal outcomes, optionally promoting it.
+pub fn run_train(holdout_fraction: f64, min_samples: usize, promote: bool) -> CommandResult {
+    const COMMAND: &str = "model-train";
+
+    if !(holdout_fraction > 0.0 && holdout_fraction < 1.0) {
+        return CommandResult::failure(
+            COMMAND,
+            "invalid_argument",
+            "--holdout must be between 0 and 1",
+            2,
+        );
+    }
+    let config = TrainingConfig { holdout_fraction, min_samples, ..TrainingConfig::default() };
+
+    with_service(COMMAND, |service| async move {
+        let mut run = service.train(&config).await.map_err(model_error)?;
+        if promote {
+            run.model = service.promote(&run.model.version).await.map_err(model_error)?;
+        }
+        Ok(to_json(
+            COMMAND,
+            &TrainOutput { command: COMMAND, status: "ok", promoted: promote, run },
+        ))
+    })
+}
+
+pub fn run_list() -> CommandResult {
+    const COMMAND: &str = "model-list";
+
+    with_service(COMMAND, |service| async move {
+        let models = service.list_versions().await.map_err(model_error)?;
+        Ok(to_json(COMMAND, &ModelListOutput { command: COMMAND, status: "ok", models }))
+    })
+}
+
+pub fn run_promote(version: String) -> CommandResult {
+    const COMMAND: &str = "model-promote";
+
+    with_service(COMMAND, |service| async move {
+        let model = service.promote(version.trim()).await.map_err(model_error)?;
+        Ok(to_json(COMMAND, &ModelOutput { command: COMMAND, status: "ok", model }))
+    })
+}
+
+/// Retires the active version and reinstates the one it replaced.
+pub fn run_rollback() -> CommandResult {
+    const COMMAND: &str = "model-rollback";
+
+    with_service(COMMAND, |service| async move {
+        let model = service.rollback().await.map_err(model_error)?;
+        Ok(to_json(COMMAND, &ModelOutput { command: COMMAND, status: "ok", model }))
+    })
+}
+
+fn model_error(error: WinProbabilityError) -> CommandError {
+    let error_class = match &error {
+        WinProbabilityError::Training(_) => "training",
+        WinProbabilityError::VersionNotFound(_) => "not_found",
+        WinProbabilityError::NoActiveModel | WinProbabilityError::NoPreviousVersion(_) => {
+            "rollback"
+        }
+        WinProbabilityError::CorruptModel { .. } | WinProbabilityError::Repository(_) => {
+            return ("model_store", error.to_string(), 6);
+        }
+    };
+    (error_class, error.to_string(), 7)
+}
+
+fn to_json<T: Serialize>(command: &str, payload: &T) -> CommandResult {
+    match serde_json::to_string_pretty(payload) {
+        Ok(output) => CommandResult { exit_code: 0, output },
+        Err(error) => CommandResult::failure(command, "serialization", error.to_string(), 8),
+    }
+}
+
+fn with_service<F, Fut>(command: &str, action: F) -> CommandResult
+where
+    F: FnOnce(WinProbabilityService) -> Fut,
+    Fut: std::future::Future<Output = Result<CommandResult, CommandError>>,
+{
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool: DbPool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let outcome = action(WinProbabilityService::new(pool.clone())).await;
+        pool.close().await;
+        outcome
+    });
+
+    match result {
+        Ok(output) => output,
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(command, error_class, message, exit_code)
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::commands::CommandResult;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_core::policy::optimizer::{ApprovalPacket, ApprovalPacketActionPayload};
+use quotey_core::VisualRuleDefinition;
+use quotey_db::policy_apply::{
+    CanaryApplyRequest, CanaryCohort, CanaryEvaluation, PolicyApplyError, PolicyApplyService,
+    PolicyCanary, PolicySetVersion, PolicySigner,
+};
+use quotey_db::policy_rules::{
+    PolicyRuleSetError, PolicyRuleSetService, PolicyRuleSetVersion, PublishRuleSet,
+};
+use quotey_db::{connect_with_settings, migrations, DbPool};
+use serde::Serialize;
+
+type CommandError = (&'static str, String, u8);
+
+const CLI_ACTOR: &str = "cli-operator";
+
+#[derive(Debug, Serialize)]
+struct CanaryOutput {
+    command: &'static str,
+    status: &'static str,
+    canary: PolicyCanary,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusOutput {
+    command: &'static str,
+    status: &'static str,
+    active: PolicySetVersion,
+    canary: Option<PolicyCanary>,
+    versions: Vec<PolicySetVersion>,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleSetOutput {
+    command: &'static str,
+    status: &'static str,
+    rule_set: PolicyRuleSetVersion,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleSetListOutput {
+    command: &'static str,
+    status: &'static str,
+    rule_sets: Vec<PolicyRuleSetVersion>,
+}
+
+#[derive(Debug, Serialize)]
+struct EvaluateOutput {
+    command: &'static str,
+    status: &'static str,
+    evaluation: Option<CanaryEvaluation>,
+}
+
+/// Input for [`run_canary`], mirroring the `policy canary` flags.
+#[derive(Debug)]
+pub struct CanaryArgs {
+    pub packet_json: String,
+    pub action_json: String,
+    pub rep_ids: Vec<String>,
+    pub segment_keys: Vec<String>,
+    pub min_sessions: usize,
+}
+
+/// Applies an approved packet to its canary cohort as a new canary policy version.
+pub fn run_canary(args: CanaryArgs) -> CommandResult {
+    const COMMAND: &str = "policy-canary";
+
+    let packet: ApprovalPacket = match serde_json::from_str(&args.packet_json) {
+        Ok(packet) => packet,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "packet_parse",
+                format!("invalid packet json: {error}"),
+                2,
+            );
+        }
+    };
+    let action: ApprovalPacketActionPayload = match serde_json::from_str(&args.action_json) {
+        Ok(action) => action,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "action_parse",
+                format!("invalid action json: {error}"),
+                2,
+            );
+        }
+    };
+    let Some(signer) = PolicySigner::from_env() else {
+        return CommandResult::failure(
+            COMMAND,
+            "signing_key",
+            "QUOTEY_POLICY_SIGNING_KEY must be set to sign policy apply records",
+            2,
+        );
+    };
+    let request = CanaryApplyRequest {
+        packet,
+        action,
+        cohort: CanaryCohort { rep_ids: args.rep_ids, segment_keys: args.segment_keys },
+        min_sessions: args.min_sessions,
+        actor_id: CLI_ACTOR.to_string(),
+    };
+
+    with_pool(COMMAND, |pool| async move {
+        let service = PolicyApplyService::new(pool);
+        let canary = service.apply_canary(request, &signer).await.map_err(policy_error)?;
+        Ok(to_json(COMMAND, &CanaryOutput { command: COMMAND, status: "ok", canary }))
+    })
+}
+
+/// Shows the active version, the monitoring canary and the version history.
+pub fn run_status() -> CommandResult {
+    const COMMAND: &str = "policy-status";
+
+    with_pool(COMMAND, |pool| async move {
+        let service = PolicyApplyService::new(pool);
+        let active = service.active_version().await.map_err(policy_error)?;
+        let canary = service.monitoring_canary().await.map_err(policy_error)?;
+        let versions = service.list_versions().await.map_err(policy_error)?;
+        Ok(to_json(
+            COMMAND,
+            &StatusOutput { command: COMMAND, status: "ok", active, canary, versions },
+        ))
+    })
+}
+
+/// Runs the canary monitor once instead of waiting for the server's next pass.
+pub fn run_evaluate() -> CommandResult {
+    const COMMAND: &str = "policy-evaluate";
+
+    let signer = PolicySigner::from_env();
+    with_pool(COMMAND, |pool| async move {
+        let service = PolicyApplyService::new(pool);
+        let evaluation = service.evaluate_canary(signer.as_ref()).await.map_err(policy_error)?;
+        Ok(to_json(COMMAND, &EvaluateOutput { command: COMMAND, status: "ok", evaluation }))
+    })
+}
+
+pub fn run_promote(reason: String) -> CommandResult {
+    const COMMAND: &str = "policy-promote";
+
+    with_pool(COMMAND, |pool| async move {
+        let service = PolicyApplyService::new(pool);
+        let canary = service.promote(CLI_ACTOR, reason.trim()).await.map_err(policy_error)?;
+        Ok(to_json(COMMAND, &CanaryOutput { command: COMMAND, status: "ok", canary }))
+    })
+}
+
+pub fn run_rollback(reason: String) -> CommandResult {
+    const COMMAND: &str = "policy-rollback";
+
+    let Some(signer) = PolicySigner::from_env() else {
+        return CommandResult::failure(
+            COMMAND,
+            "signing_key",
+            "QUOTEY_POLICY_SIGNING_KEY must be set to sign policy rollback records",
+            2,
+        );
+    };
+    with_pool(COMMAND, |pool| async move {
+        let service = PolicyApplyService::new(pool);
+        let canary =
+            service.rollback(CLI_ACTOR, reason.trim(), &signer).await.map_err(policy_error)?;
+        Ok(to_json(COMMAND, &CanaryOutput { command: COMMAND, status: "ok", canary }))
+    })
+}
+
+/// Publishes visual rules as the next immutable policy rule set version.
+pub fn run_publish_rules(name: String, visual_rules_json: String) -> CommandResult {
+    const COMMAND: &str = "policy-publish-rules";
+
+    let visual_rules: Vec<VisualRuleDefinition> = match serde_json::from_str(&visual_rules_json) {
+        Ok(rules) => rules,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "visual_rules_parse",
+                format!("invalid visual rules json: {error}"),
+                2,
+            );
+        }
+    };
+    let request = PublishRuleSet { name, visual_rules, created_by: CLI_ACTOR.to_string() };
+
+    with_pool(COMMAND, |pool| async move {
+        let rule_set =
+            PolicyRuleSetService::new(pool).publish(request).await.map_err(rule_set_error)?;
+        Ok(to_json(COMMAND, &RuleSetOutput { command: COMMAND, status: "ok", rule_set }))
+    })
+}
+
+/// Lists published policy rule set versions, newest first.
+pub fn run_rules() -> CommandResult {
+    const COMMAND: &str = "policy-rules";
+
+    with_pool(COMMAND, |pool| async move {
+        let rule_sets =
+            PolicyRuleSetService::new(pool).list_versions().await.map_err(rule_set_error)?;
+        Ok(to_json(COMMAND, &RuleSetListOutput { command: COMMAND, status: "ok", rule_sets }))
+    })
+}
+
+fn rule_set_error(error: PolicyRuleSetError) -> CommandError {
+    match &error {
+        PolicyRuleSetError::Corrupt(_) | PolicyRuleSetError::Repository(_) => {
+            ("policy_store", error.to_string(), 6)
+        }
+        _ => ("invalid_argument", error.to_string(), 7),
+    }
+}
+
+fn policy_error(error: PolicyApplyError) -> CommandError {
+    let error_class = match &error {
+        PolicyApplyError::Lifecycle(_) => "lifecycle",
+        PolicyApplyError::Thresholds(_) => "thresholds",
+        PolicyApplyError::EmptyCohort | PolicyApplyError::StaleProposedVersion { .. } => {
+            "invalid_argument"
+        }
+        PolicyApplyError::CanaryInProgress(_) | PolicyApplyError::NoActiveCanary => "canary_state",
+        PolicyApplyError::SignerRequired(_) => "signing_key",
+        PolicyApplyError::Corrupt(_) | PolicyApplyError::Repository(_) => {
+            return ("policy_store", error.to_string(), 6);
+        }
+    };
+    (error_class, error.to_string(), 7)
+}
+
+fn to_json<T: Serialize>(command: &str, payload: &T) -> CommandResult {
+    match serde_json::to_string_pretty(payload) {
+        Ok(output) => CommandResult { exit_code: 0, output },
+        Err(error) => CommandResult::failure(command, "serialization", error.to_string(), 8),
+    }
+}
+
+fn with_pool<F, Fut>(command: &str, action: F) -> CommandResult
+where
+    F: FnOnce(DbPool) -> Fut,
+    Fut: std::future::Future<Output = Result<CommandResult, CommandError>>,
+{
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool: DbPool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let outcome = action(pool.clone()).await;
+        pool.close().await;
+        outcome
+    });
+
+    match result {
+        Ok(output) => output,
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(command, error_class, message, exit_code)
+        }
+    }
+}
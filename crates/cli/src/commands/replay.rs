@@ -0,0 +1,300 @@
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::commands::CommandResult;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_core::cpq::replay::{
+    BundleReplayReport, ReplayBundle, ReplayHarness, ReplayPolicy, SkippedRecord,
+    REPLAY_BUNDLE_SCHEMA_VERSION,
+};
+use quotey_db::replay::{ExportQuery, ReplayService};
+use quotey_db::{connect_with_settings, migrations};
+use serde::Serialize;
+
+type CommandError = (&'static str, String, u8);
+
+/// Exit code when a replayed fixture no longer matches its recorded outcome.
+pub const DRIFT_EXIT_CODE: u8 = 7;
+
+#[derive(Debug, Serialize)]
+struct ExportOutput {
+    command: &'static str,
+    status: &'static str,
+    out: String,
+    negotiations: usize,
+    counters: usize,
+    pricing_runs: usize,
+    skipped: Vec<SkippedRecord>,
+}
+
+#[derive(Debug, Serialize)]
+struct RunOutput {
+    command: &'static str,
+    /// `ok`, or `drift` when any fixture changed outcome.
+    status: &'static str,
+    fixtures: String,
+    #[serde(flatten)]
+    report: BundleReplayReport,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportArgs {
+    pub out: PathBuf,
+    pub quote_id: Option<String>,
+    pub since: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunArgs {
+    pub fixtures: PathBuf,
+    /// Policy set version to replay against; the active version when `None`.
+    pub policy_version: Option<i32>,
+    /// Replay against the built-in default policy without opening the database.
+    pub default_policy: bool,
+}
+
+/// Exports recorded negotiation counters and pricing runs to a replay fixture file.
+pub fn run_export(args: ExportArgs) -> CommandResult {
+    const COMMAND: &str = "replay-export";
+
+    with_service(COMMAND, |service| async move {
+        let bundle = service
+            .export(ExportQuery { quote_id: args.quote_id, since: args.since, limit: args.limit })
+            .await
+            .map_err(|error| ("replay_export", error.to_string(), 6u8))?;
+        let json = serde_json::to_string_pretty(&bundle)
+            .map_err(|error| ("serialization", error.to_string(), 8u8))?;
+        std::fs::write(&args.out, json).map_err(|error| {
+            ("fixture_write", format!("cannot write {}: {error}", args.out.display()), 6u8)
+        })?;
+        let payload = ExportOutput {
+            command: COMMAND,
+            status: "ok",
+            out: args.out.display().to_string(),
+            negotiations: bundle.negotiations.len(),
+            counters: bundle.negotiations.iter().map(|session| session.counters.len()).sum(),
+            pricing_runs: bundle.pricing_runs.len(),
+            skipped: bundle.skipped,
+        };
+        Ok(to_json(COMMAND, &payload, 0))
+    })
+}
+
+/// Replays a fixture file against the current engine and policy; exits with
+/// [`DRIFT_EXIT_CODE`] when any outcome drifted so CI can block the deploy.
+pub fn run_replay(args: RunArgs) -> CommandResult {
+    const COMMAND: &str = "replay-run";
+
+    let bundle = match load_bundle(&args.fixtures) {
+        Ok(bundle) => bundle,
+        Err(message) => return CommandResult::failure(COMMAND, "invalid_fixtures", message, 2),
+    };
+    if args.default_policy {
+        return replay_output(COMMAND, &args, &bundle, &ReplayPolicy::default());
+    }
+
+    with_service(COMMAND, |service| async move {
+        let policy = service
+            .policy(args.policy_version)
+            .await
+            .map_err(|error| ("replay_policy", error.to_string(), 6u8))?;
+        Ok(replay_output(COMMAND, &args, &bundle, &policy))
+    })
+}
+
+fn replay_output(
+    command: &'static str,
+    args: &RunArgs,
+    bundle: &ReplayBundle,
+    policy: &ReplayPolicy,
+) -> CommandResult {
+    let report = ReplayHarness.replay_bundle(bundle, policy);
+    let (status, exit_code) =
+        if report.deterministic { ("ok", 0) } else { ("drift", DRIFT_EXIT_CODE) };
+    let payload =
+        RunOutput { command, status, fixtures: args.fixtures.display().to_string(), report };
+    to_json(command, &payload, exit_code)
+}
+
+fn load_bundle(path: &PathBuf) -> Result<ReplayBundle, String> {
+    let raw = std::fs::read_to_string(path)
+        .map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+    let bundle: ReplayBundle = serde_json::from_str(&raw)
+        .map_err(|error| format!("{} is not a replay fixture file: {error}", path.display()))?;
+    if bundle.schema_version != REPLAY_BUNDLE_SCHEMA_VERSION {
+        return Err(format!(
+            "fixture schema `{}` is not supported (expected `{REPLAY_BUNDLE_SCHEMA_VERSION}`)",
+            bundle.schema_version
+        ));
+    }
+    Ok(bundle)
+}
+
+fn to_json<T: Serialize>(command: &str, payload: &T, exit_code: u8) -> CommandResult {
+    match serde_json::to_string_pretty(payload) {
+        Ok(output) => CommandResult { exit_code, output },
+        Err(error) => CommandResult::failure(command, "serialization", error.to_string(), 8),
+    }
+}
+
+fn with_service<F, Fut>(command: &str, action: F) -> CommandResult
+where
+    F: FnOnce(ReplayService) -> Fut,
+    Fut: Future<Output = Result<CommandResult, CommandError>>,
+{
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                command,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let outcome = action(ReplayService::new(pool.clone())).await;
+        pool.close().await;
+        outcome
+    });
+
+    match result {
+        Ok(output) => output,
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(command, error_class, message, exit_code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures_path(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("quotey-replay-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).expect("write fixtures");
+        path
+    }
+
+    #[test]
+    fn run_with_default_policy_passes_for_an_empty_bundle() {
+        let bundle = ReplayBundle::new("2026-03-06T00:00:00Z");
+        let path = fixtures_path("empty.json", &serde_json::to_string(&bundle).expect("encode"));
+
+        let result = run_replay(RunArgs {
+            fixtures: path.clone(),
+            policy_version: None,
+            default_policy: true,
+        });
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(result.exit_code, 0, "{}", result.output);
+        let output: serde_json::Value = serde_json::from_str(&result.output).expect("json output");
+        assert_eq!(output["status"], "ok");
+        assert_eq!(output["total_cases"], 0);
+    }
+
+    #[test]
+    fn run_rejects_unreadable_and_unsupported_fixture_files() {
+        let path = fixtures_path("bad.json", "{\"not\":\"a bundle\"}");
+        let result = run_replay(RunArgs {
+            fixtures: path.clone(),
+            policy_version: None,
+            default_policy: true,
+        });
+        std::fs::remove_file(path).ok();
+        assert_eq!(result.exit_code, 2);
+        assert!(result.output.contains("invalid_fixtures"));
+
+        let mut bundle = ReplayBundle::new("2026-03-06T00:00:00Z");
+        bundle.schema_version = "replay.v0".to_string();
+        let path = fixtures_path("old.json", &serde_json::to_string(&bundle).expect("encode"));
+        let result = run_replay(RunArgs {
+            fixtures: path.clone(),
+            policy_version: None,
+            default_policy: true,
+        });
+        std::fs::remove_file(path).ok();
+        assert_eq!(result.exit_code, 2);
+        assert!(result.output.contains("replay.v0"));
+    }
+
+    #[test]
+    fn run_exits_with_drift_code_when_a_recorded_outcome_changed() {
+        use quotey_core::chrono::NaiveDate;
+        use quotey_core::cpq::policy_rules::PolicyRuleContext;
+        use quotey_core::cpq::replay::{
+            replay_pricing_run, PricingOutcome, RecordedLine, RecordedPricingRun,
+        };
+        use rust_decimal::Decimal;
+
+        let mut run = RecordedPricingRun {
+            quote_id: "Q-CLI-1".to_string(),
+            version: 3,
+            currency: "USD".to_string(),
+            policy_version: Some(1),
+            lines: vec![RecordedLine {
+                line_id: "Q-CLI-1-ql-1".to_string(),
+                product_id: "plan-pro".to_string(),
+                quantity: 4,
+                unit_price: Decimal::new(250, 0),
+                discount_pct: Decimal::new(10, 0),
+            }],
+            unit_costs: Default::default(),
+            context: PolicyRuleContext::new(NaiveDate::from_ymd_opt(2026, 3, 6).expect("date")),
+            expected: PricingOutcome {
+                subtotal: Decimal::ZERO,
+                discount_total: Decimal::ZERO,
+                total: Decimal::ZERO,
+                policy_status: String::new(),
+                violations: Vec::new(),
+            },
+        };
+        run.expected = replay_pricing_run(&run, &ReplayPolicy::default());
+        run.expected.total = Decimal::new(901, 0);
+        let mut bundle = ReplayBundle::new("2026-03-06T00:00:00Z");
+        bundle.pricing_runs.push(run);
+        let path = fixtures_path("drift.json", &serde_json::to_string(&bundle).expect("encode"));
+
+        let result = run_replay(RunArgs {
+            fixtures: path.clone(),
+            policy_version: None,
+            default_policy: true,
+        });
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(result.exit_code, DRIFT_EXIT_CODE, "{}", result.output);
+        let output: serde_json::Value = serde_json::from_str(&result.output).expect("json output");
+        assert_eq!(output["status"], "drift");
+        assert_eq!(output["cases"][0]["fixture"], "pricing:Q-CLI-1@v3");
+        assert_eq!(output["cases"][0]["drifts"][0]["field"], "total");
+        assert_eq!(output["cases"][0]["drifts"][0]["original_value"], "901");
+        assert_eq!(output["cases"][0]["drifts"][0]["replayed_value"], "900");
+    }
+}
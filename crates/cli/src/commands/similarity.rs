@@ -0,0 +1,77 @@
+use crate::commands::CommandResult;
+use quotey_core::config::{AppConfig, LoadOptions};
+use quotey_db::similarity::{DealSimilarityService, IndexRebuildReport};
+use quotey_db::{connect_with_settings, migrations};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct RebuildOutput {
+    command: &'static str,
+    status: &'static str,
+    #[serde(flatten)]
+    report: IndexRebuildReport,
+}
+
+/// Rebuilds `deal_similarity_index` from stored fingerprints, fingerprinting closed quotes
+/// that were never indexed.
+pub fn run_rebuild() -> CommandResult {
+    const COMMAND: &str = "similarity-rebuild";
+
+    let config = match AppConfig::load(LoadOptions::default()) {
+        Ok(config) => config,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "config_validation",
+                format!("configuration issue: {error}"),
+                2,
+            );
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            return CommandResult::failure(
+                COMMAND,
+                "runtime_init",
+                format!("failed to initialize async runtime: {error}"),
+                3,
+            );
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let pool = connect_with_settings(
+            &config.database.url,
+            config.database.max_connections,
+            config.database.timeout_secs,
+        )
+        .await
+        .map_err(|error| ("db_connectivity", error.to_string(), 4u8))?;
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|error| ("migration", error.to_string(), 5u8))?;
+        let report = DealSimilarityService::new(pool.clone())
+            .rebuild()
+            .await
+            .map_err(|error| ("similarity_index", error.to_string(), 6u8));
+        pool.close().await;
+        report
+    });
+
+    match result {
+        Ok(report) => {
+            let payload = RebuildOutput { command: COMMAND, status: "ok", report };
+            match serde_json::to_string_pretty(&payload) {
+                Ok(output) => CommandResult { exit_code: 0, output },
+                Err(error) => {
+                    CommandResult::failure(COMMAND, "serialization", error.to_string(), 8)
+                }
+            }
+        }
+        Err((error_class, message, exit_code)) => {
+            CommandResult::failure(COMMAND, error_class, message, exit_code)
+        }
+    }
+}
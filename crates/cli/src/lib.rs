@@ -8,7 +8,7 @@ use std::process::ExitCode;
     name = "quotey",
     about = "Quotey operator CLI",
     long_about = "Operate Quotey runtime readiness, migrations, config inspection, and smoke validation.",
-    after_help = "Examples:\n  quotey doctor --json\n  quotey config\n  quotey smoke"
+    after_help = "Examples:\n  quotey doctor --json\n  quotey config\n  quotey smoke\n  quotey api-key create --name ci --scope quote:read\n  quotey analytics --metric win_rate_pct --dimension month --format csv"
 )]
 pub struct Cli {
     #[command(subcommand)]
@@ -39,6 +39,13 @@ enum Command {
         #[command(subcommand)]
         command: PolicyPacketCommand,
     },
+    #[command(
+        about = "Version policy thresholds and rule sets; roll candidates out through canaries"
+    )]
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommand,
+    },
     #[command(about = "Preview pricing rule SQL and before/after sample quote impacts")]
     RulePreview {
         #[arg(long, help = "Visual rule JSON payload (VisualRuleDefinition)")]
@@ -53,6 +60,187 @@ enum Command {
         #[command(subcommand)]
         command: GenomeCommand,
     },
+    #[command(about = "Create, list, rotate and revoke scoped MCP API keys")]
+    ApiKey {
+        #[command(subcommand)]
+        command: ApiKeyCommand,
+    },
+    #[command(about = "Maintain the persistent similar-deal index")]
+    Similarity {
+        #[command(subcommand)]
+        command: SimilarityCommand,
+    },
+    #[command(
+        about = "Export recorded negotiations and pricing runs and replay them to detect drift",
+        after_help = "Examples:\n  quotey replay export --out replay-fixtures.json --since 2026-01-01T00:00:00Z\n  quotey replay run --fixtures replay-fixtures.json\n\nreplay run exits with code 7 when any recorded outcome drifted."
+    )]
+    Replay {
+        #[command(subcommand)]
+        command: ReplayCommand,
+    },
+    #[command(
+        about = "Import and export the product catalog as CSV or JSON",
+        after_help = "Examples:\n  quotey catalog export --out catalog.csv\n  quotey catalog import --file catalog.csv\n  quotey catalog import --file catalog.csv --apply\n\nImports cover the whole catalog: products missing from the file are deactivated.\nWithout --apply the import is a dry run that prints the diff."
+    )]
+    Catalog {
+        #[command(subcommand)]
+        command: CatalogCommand,
+    },
+    #[command(about = "Train, promote and roll back win-probability models")]
+    Model {
+        #[command(subcommand)]
+        command: ModelCommand,
+    },
+    #[command(
+        about = "Aggregate quote metrics by dimensions and print them as JSON or CSV",
+        after_help = "Examples:\n  quotey analytics --metric quote_count --metric win_rate_pct --dimension month\n  quotey analytics --metric avg_discount_pct --dimension sales_rep --filter region=emea --format csv"
+    )]
+    Analytics {
+        #[arg(
+            long = "metric",
+            help = "Metric to compute, repeatable (quote_count, win_rate_pct, avg_discount_pct, avg_deal_value, approval_cycle_hours, time_to_finalize_hours, anomaly_rate_pct)"
+        )]
+        metrics: Vec<String>,
+        #[arg(
+            long = "dimension",
+            help = "Dimension to group by, repeatable (day, week, month, quarter, customer_segment, industry, region, sales_rep, product_family, approval_role)"
+        )]
+        dimensions: Vec<String>,
+        #[arg(long, default_value_t = 30, help = "Days back from today to include")]
+        lookback_days: u32,
+        #[arg(long, help = "Only count approved, finalized, sent and accepted quotes")]
+        finalized_only: bool,
+        #[arg(long = "filter", help = "Keep rows matching dimension=value[,value], repeatable")]
+        filters: Vec<String>,
+        #[arg(long, help = "Last creation date counted (YYYY-MM-DD, inclusive)")]
+        end_date: Option<String>,
+        #[arg(long, help = "Maximum rows returned (default 1000)")]
+        limit: Option<u32>,
+        #[arg(
+            long,
+            conflicts_with_all = ["metrics", "dimensions", "filters", "end_date", "limit"],
+            help = "Full AnalyticsQuerySpec JSON payload instead of the flags above"
+        )]
+        spec_json: Option<String>,
+        #[arg(long, default_value = "json", help = "Output format: json or csv")]
+        format: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ModelCommand {
+    #[command(about = "Train a candidate version from recorded deal outcomes")]
+    Train {
+        #[arg(
+            long = "holdout",
+            default_value_t = 0.2,
+            help = "Share of the most recently closed deals held out for evaluation"
+        )]
+        holdout_fraction: f64,
+        #[arg(long, default_value_t = 10, help = "Minimum closed deals required to train")]
+        min_samples: usize,
+        #[arg(long, help = "Promote the new version once trained")]
+        promote: bool,
+    },
+    #[command(about = "List model versions with status and holdout metrics")]
+    List,
+    #[command(about = "Make a version the one serving predictions")]
+    Promote {
+        #[arg(long, help = "Model version, e.g. v3")]
+        version: String,
+    },
+    #[command(about = "Reinstate the version the active model replaced")]
+    Rollback,
+}
+
+#[derive(Debug, Subcommand)]
+enum ReplayCommand {
+    #[command(about = "Export recorded negotiation counters and pricing runs as replay fixtures")]
+    Export {
+        #[arg(long, help = "Fixture file to write")]
+        out: std::path::PathBuf,
+        #[arg(long, help = "Only export this quote's sessions and pricing runs")]
+        quote_id: Option<String>,
+        #[arg(long, help = "Only export records created at or after this RFC 3339 timestamp")]
+        since: Option<String>,
+        #[arg(long, help = "Maximum sessions and pricing runs read, each (default 5000)")]
+        limit: Option<u32>,
+    },
+    #[command(about = "Replay a fixture file against the current engine and report drift")]
+    Run {
+        #[arg(long, help = "Fixture file written by replay export")]
+        fixtures: std::path::PathBuf,
+        #[arg(long, help = "Policy set version to replay against (default: the active version)")]
+        policy_version: Option<i32>,
+        #[arg(
+            long,
+            conflicts_with = "policy_version",
+            help = "Replay against the built-in default policy without opening the database"
+        )]
+        default_policy: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CatalogCommand {
+    #[command(about = "Validate a catalog file and diff it against the stored catalog")]
+    Import {
+        #[arg(long, help = "CSV or JSON catalog file")]
+        file: std::path::PathBuf,
+        #[arg(long, help = "json or csv (default: from the file extension)")]
+        format: Option<String>,
+        #[arg(long, help = "Write the changes instead of only reporting them")]
+        apply: bool,
+    },
+    #[command(about = "Write the stored catalog, inactive products included")]
+    Export {
+        #[arg(long, help = "File to write")]
+        out: std::path::PathBuf,
+        #[arg(long, help = "json or csv (default: from the file extension)")]
+        format: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SimilarityCommand {
+    #[command(about = "Rebuild the index from stored fingerprints and backfill closed quotes")]
+    Rebuild,
+}
+
+#[derive(Debug, Subcommand)]
+enum ApiKeyCommand {
+    #[command(about = "Create a key; the secret is printed once and only its hash is stored")]
+    Create {
+        #[arg(long, help = "Human-readable key name")]
+        name: String,
+        #[arg(
+            long = "scope",
+            required = true,
+            help = "Granted scope, repeatable or comma-separated (e.g. quote:read,quote:write)"
+        )]
+        scopes: Vec<String>,
+        #[arg(long = "account", help = "Restrict the key to this account id (repeatable)")]
+        accounts: Vec<String>,
+        #[arg(long = "team", help = "Restrict the key to this team id (repeatable)")]
+        teams: Vec<String>,
+        #[arg(long, default_value_t = 60, help = "Rate limit for this key")]
+        requests_per_minute: u32,
+    },
+    #[command(about = "List keys without secrets")]
+    List {
+        #[arg(long, help = "Include revoked and rotated keys")]
+        include_revoked: bool,
+    },
+    #[command(about = "Replace a key with a new secret carrying the same grants")]
+    Rotate {
+        #[arg(long, help = "Key id to rotate")]
+        id: String,
+    },
+    #[command(about = "Revoke a key")]
+    Revoke {
+        #[arg(long, help = "Key id to revoke")]
+        id: String,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -85,6 +273,53 @@ enum PolicyPacketCommand {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    #[command(about = "Apply an approved packet to a canary cohort of reps and segments")]
+    Canary {
+        #[arg(long, help = "Approved approval packet JSON payload")]
+        packet_json: String,
+        #[arg(long, help = "Approve action JSON payload (from policy-packet action)")]
+        action_json: String,
+        #[arg(long = "rep", help = "Sales rep id in the canary cohort (repeatable)")]
+        rep_ids: Vec<String>,
+        #[arg(
+            long = "segment",
+            help = "Customer segment in the canary cohort (repeatable; defaults to the candidate's)"
+        )]
+        segment_keys: Vec<String>,
+        #[arg(
+            long,
+            default_value_t = quotey_db::policy_apply::DEFAULT_CANARY_MIN_SESSIONS,
+            help = "Negotiation sessions required before the canary may be promoted"
+        )]
+        min_sessions: usize,
+    },
+    #[command(about = "Show the active policy version, monitoring canary and version history")]
+    Status,
+    #[command(about = "Score the monitoring canary now and promote or roll it back")]
+    Evaluate,
+    #[command(about = "Promote the monitoring canary to the active version")]
+    Promote {
+        #[arg(long, help = "Reason recorded in the policy audit trail")]
+        reason: String,
+    },
+    #[command(about = "Roll back the monitoring canary with a signed rollback record")]
+    Rollback {
+        #[arg(long, help = "Reason recorded in the policy audit trail")]
+        reason: String,
+    },
+    #[command(about = "Publish visual rules as the next immutable policy rule set version")]
+    PublishRules {
+        #[arg(long, help = "Rule set name, e.g. FY26 H1")]
+        name: String,
+        #[arg(long, help = "JSON array of discount_policy/approval_threshold visual rules")]
+        visual_rules_json: String,
+    },
+    #[command(about = "List published policy rule set versions, newest first")]
+    Rules,
+}
+
 #[derive(Debug, Subcommand)]
 enum GenomeCommand {
     #[command(about = "Perform a deal autopsy on a terminal quote")]
@@ -149,6 +384,29 @@ pub fn run() -> ExitCode {
                 commands::policy_packet::run_action(packet_json, decision, reason)
             }
         },
+        Command::Policy { command } => match command {
+            PolicyCommand::Canary {
+                packet_json,
+                action_json,
+                rep_ids,
+                segment_keys,
+                min_sessions,
+            } => commands::policy::run_canary(commands::policy::CanaryArgs {
+                packet_json,
+                action_json,
+                rep_ids,
+                segment_keys,
+                min_sessions,
+            }),
+            PolicyCommand::Status => commands::policy::run_status(),
+            PolicyCommand::Evaluate => commands::policy::run_evaluate(),
+            PolicyCommand::Promote { reason } => commands::policy::run_promote(reason),
+            PolicyCommand::Rollback { reason } => commands::policy::run_rollback(reason),
+            PolicyCommand::PublishRules { name, visual_rules_json } => {
+                commands::policy::run_publish_rules(name, visual_rules_json)
+            }
+            PolicyCommand::Rules => commands::policy::run_rules(),
+        },
         Command::RulePreview { rule_json, samples_json } => {
             commands::rule_preview::run(rule_json, samples_json)
         }
@@ -164,6 +422,73 @@ pub fn run() -> ExitCode {
                 commands::genome::run_build_graph(reports_json)
             }
         },
+        Command::ApiKey { command } => match command {
+            ApiKeyCommand::Create { name, scopes, accounts, teams, requests_per_minute } => {
+                commands::api_key::run_create(name, scopes, accounts, teams, requests_per_minute)
+            }
+            ApiKeyCommand::List { include_revoked } => commands::api_key::run_list(include_revoked),
+            ApiKeyCommand::Rotate { id } => commands::api_key::run_rotate(id),
+            ApiKeyCommand::Revoke { id } => commands::api_key::run_revoke(id),
+        },
+        Command::Similarity { command } => match command {
+            SimilarityCommand::Rebuild => commands::similarity::run_rebuild(),
+        },
+        Command::Replay { command } => match command {
+            ReplayCommand::Export { out, quote_id, since, limit } => {
+                commands::replay::run_export(commands::replay::ExportArgs {
+                    out,
+                    quote_id,
+                    since,
+                    limit,
+                })
+            }
+            ReplayCommand::Run { fixtures, policy_version, default_policy } => {
+                commands::replay::run_replay(commands::replay::RunArgs {
+                    fixtures,
+                    policy_version,
+                    default_policy,
+                })
+            }
+        },
+        Command::Catalog { command } => match command {
+            CatalogCommand::Import { file, format, apply } => {
+                commands::catalog::run_import(commands::catalog::ImportArgs { file, format, apply })
+            }
+            CatalogCommand::Export { out, format } => {
+                commands::catalog::run_export(commands::catalog::ExportArgs { out, format })
+            }
+        },
+        Command::Model { command } => match command {
+            ModelCommand::Train { holdout_fraction, min_samples, promote } => {
+                commands::model::run_train(holdout_fraction, min_samples, promote)
+            }
+            ModelCommand::List => commands::model::run_list(),
+            ModelCommand::Promote { version } => commands::model::run_promote(version),
+            ModelCommand::Rollback => commands::model::run_rollback(),
+        },
+        Command::Analytics {
+            metrics,
+            dimensions,
+            lookback_days,
+            finalized_only,
+            filters,
+            end_date,
+            limit,
+            spec_json,
+            format,
+        } => commands::analytics::run(
+            commands::analytics::AnalyticsArgs {
+                metrics,
+                dimensions,
+                lookback_days,
+                finalized_only,
+                filters,
+                end_date,
+                limit,
+                spec_json,
+            },
+            format,
+        ),
     };
 
     println!("{}", result.output);
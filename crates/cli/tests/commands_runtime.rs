@@ -1,7 +1,7 @@
 use std::env;
 use std::sync::{Mutex, OnceLock};
 
-use quotey_cli::commands::{migrate, seed, smoke, start};
+use quotey_cli::commands::{analytics, api_key, migrate, model, seed, similarity, smoke, start};
 use serde_json::Value;
 
 #[test]
@@ -161,6 +161,150 @@ fn smoke_returns_failure_when_config_invalid() {
     });
 }
 
+#[test]
+fn api_key_create_rotate_revoke_lifecycle_persists_hashed_keys() {
+    let db_path = env::temp_dir().join(format!("quotey-api-key-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&db_path);
+    let database_url = format!("sqlite://{}?mode=rwc", db_path.display());
+
+    with_env(
+        &[
+            ("QUOTEY_SLACK_APP_TOKEN", "xapp-test"),
+            ("QUOTEY_SLACK_BOT_TOKEN", "xoxb-test"),
+            ("QUOTEY_DATABASE_URL", &database_url),
+        ],
+        || {
+            let created = api_key::run_create(
+                "ci-agent".to_string(),
+                vec!["quote:read,catalog:read".to_string()],
+                vec!["acct-1".to_string()],
+                Vec::new(),
+                30,
+            );
+            assert_eq!(created.exit_code, 0, "create failed: {}", created.output);
+            let created = parse_payload(&created.output);
+            let secret = created["api_key"].as_str().expect("secret").to_string();
+            let key_id = created["key"]["id"].as_str().expect("id").to_string();
+            assert_eq!(created["key"]["scopes"], serde_json::json!(["catalog:read", "quote:read"]));
+
+            let listed = parse_payload(&api_key::run_list(false).output);
+            assert_eq!(listed["keys"].as_array().map(Vec::len), Some(1));
+            assert!(!listed.to_string().contains(&secret), "list must not expose secrets");
+
+            let rotated = api_key::run_rotate(key_id.clone());
+            assert_eq!(rotated.exit_code, 0, "rotate failed: {}", rotated.output);
+            let rotated = parse_payload(&rotated.output);
+            assert_ne!(rotated["api_key"].as_str(), Some(secret.as_str()));
+            assert_eq!(rotated["key"]["rotated_from"].as_str(), Some(key_id.as_str()));
+            let new_id = rotated["key"]["id"].as_str().expect("new id").to_string();
+
+            let revoke_old = api_key::run_revoke(key_id);
+            assert_eq!(parse_payload(&revoke_old.output)["error_class"], "not_found");
+
+            let revoke_new = api_key::run_revoke(new_id);
+            assert_eq!(revoke_new.exit_code, 0, "revoke failed: {}", revoke_new.output);
+
+            let listed = parse_payload(&api_key::run_list(false).output);
+            assert_eq!(listed["keys"].as_array().map(Vec::len), Some(0));
+            let all = parse_payload(&api_key::run_list(true).output);
+            assert_eq!(all["keys"].as_array().map(Vec::len), Some(2));
+        },
+    );
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn similarity_rebuild_reports_indexed_deals() {
+    with_env(
+        &[
+            ("QUOTEY_SLACK_APP_TOKEN", "xapp-test"),
+            ("QUOTEY_SLACK_BOT_TOKEN", "xoxb-test"),
+            ("QUOTEY_DATABASE_URL", "sqlite::memory:"),
+        ],
+        || {
+            let result = similarity::run_rebuild();
+            assert_eq!(result.exit_code, 0, "rebuild failed: {}", result.output);
+
+            let payload = parse_payload(&result.output);
+            assert_eq!(payload["command"], "similarity-rebuild");
+            assert_eq!(payload["status"], "ok");
+            assert_eq!(payload["indexed"], 0);
+            assert_eq!(payload["backfilled"], 0);
+        },
+    );
+}
+
+#[test]
+fn model_commands_report_thin_history_and_empty_registry() {
+    with_env(
+        &[
+            ("QUOTEY_SLACK_APP_TOKEN", "xapp-test"),
+            ("QUOTEY_SLACK_BOT_TOKEN", "xoxb-test"),
+            ("QUOTEY_DATABASE_URL", "sqlite::memory:"),
+        ],
+        || {
+            let result = model::run_train(0.2, 10, true);
+            assert_eq!(result.exit_code, 7, "training without outcomes: {}", result.output);
+            let payload = parse_payload(last_line(&result.output));
+            assert_eq!(payload["command"], "model-train");
+            assert_eq!(payload["error_class"], "training");
+
+            assert_eq!(model::run_train(1.5, 10, false).exit_code, 2);
+
+            let result = model::run_list();
+            assert_eq!(result.exit_code, 0, "list failed: {}", result.output);
+            let payload = parse_payload(&result.output);
+            assert_eq!(payload["command"], "model-list");
+            assert_eq!(payload["models"], serde_json::json!([]));
+
+            let result = model::run_rollback();
+            assert_eq!(result.exit_code, 7);
+            assert_eq!(parse_payload(last_line(&result.output))["error_class"], "rollback");
+        },
+    );
+}
+
+#[test]
+fn analytics_prints_json_and_csv_and_rejects_unknown_metrics() {
+    with_env(
+        &[
+            ("QUOTEY_SLACK_APP_TOKEN", "xapp-test"),
+            ("QUOTEY_SLACK_BOT_TOKEN", "xoxb-test"),
+            ("QUOTEY_DATABASE_URL", "sqlite::memory:"),
+        ],
+        || {
+            let args = analytics::AnalyticsArgs {
+                metrics: vec!["quote_count".to_string(), "win_rate_pct".to_string()],
+                lookback_days: 30,
+                ..analytics::AnalyticsArgs::default()
+            };
+
+            let result = analytics::run(args.clone(), "json".to_string());
+            assert_eq!(result.exit_code, 0, "analytics failed: {}", result.output);
+            let payload = parse_payload(&result.output);
+            assert_eq!(payload["command"], "analytics");
+            assert_eq!(payload["rows"][0]["quote_count"], 0);
+            assert_eq!(payload["truncated"], false);
+
+            let result = analytics::run(args, "csv".to_string());
+            assert_eq!(result.exit_code, 0);
+            assert_eq!(result.output, "quote_count,win_rate_pct\n0,");
+
+            let result = analytics::run(
+                analytics::AnalyticsArgs {
+                    metrics: vec!["revenue".to_string()],
+                    lookback_days: 30,
+                    ..analytics::AnalyticsArgs::default()
+                },
+                "json".to_string(),
+            );
+            assert_eq!(result.exit_code, 2);
+            assert_eq!(parse_payload(&result.output)["error_class"], "invalid_input");
+        },
+    );
+}
+
 fn parse_payload(output: &str) -> Value {
     serde_json::from_str(output).expect("command output should be valid JSON")
 }
@@ -54,6 +54,7 @@ pub enum EntityType {
     Approval,
     Negotiation,
     Product,
+    ProductFamily,
     SalesRep,
     Integration,
     OrgSetting,
@@ -69,6 +70,7 @@ impl EntityType {
             Self::Approval => "approval",
             Self::Negotiation => "negotiation",
             Self::Product => "product",
+            Self::ProductFamily => "product_family",
             Self::SalesRep => "sales_rep",
             Self::Integration => "integration",
             Self::OrgSetting => "org_setting",
@@ -84,6 +86,7 @@ impl EntityType {
             "approval" => Some(Self::Approval),
             "negotiation" => Some(Self::Negotiation),
             "product" => Some(Self::Product),
+            "product_family" => Some(Self::ProductFamily),
             "sales_rep" => Some(Self::SalesRep),
             "integration" => Some(Self::Integration),
             "org_setting" => Some(Self::OrgSetting),
@@ -334,6 +337,8 @@ pub mod funnel {
     pub const SESSION_COMPLETED: &str = "funnel.session_completed";
     /// Session dropped (user abandoned or session expired without completion).
     pub const SESSION_DROPPED: &str = "funnel.session_dropped";
+    /// Customer e-signed the quote in the portal (closed-won).
+    pub const QUOTE_ACCEPTED: &str = "funnel.quote_accepted";
 
     /// Ordinal positions in the happy-path funnel.
     /// Used for measuring drop-off between consecutive steps.
@@ -344,6 +349,7 @@ pub mod funnel {
             PRICING_RENDERED => 3,
             APPROVAL_ACTION => 4,
             SESSION_COMPLETED => 5,
+            QUOTE_ACCEPTED => 6,
             // Non-linear steps get ordinal 0 (out-of-band)
             _ => 0,
         }
@@ -446,6 +452,7 @@ mod tests {
         assert_eq!(funnel::step_ordinal(funnel::PRICING_RENDERED), 3);
         assert_eq!(funnel::step_ordinal(funnel::APPROVAL_ACTION), 4);
         assert_eq!(funnel::step_ordinal(funnel::SESSION_COMPLETED), 5);
+        assert_eq!(funnel::step_ordinal(funnel::QUOTE_ACCEPTED), 6);
         // Non-linear events get ordinal 0
         assert_eq!(funnel::step_ordinal(funnel::COMMENT_ADDED), 0);
         assert_eq!(funnel::step_ordinal(funnel::SESSION_RESUMED), 0);
@@ -534,6 +541,7 @@ mod tests {
             EntityType::Approval,
             EntityType::Negotiation,
             EntityType::Product,
+            EntityType::ProductFamily,
             EntityType::SalesRep,
             EntityType::Integration,
             EntityType::OrgSetting,
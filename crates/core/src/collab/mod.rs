@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::domain::product::ProductId;
 use crate::domain::quote::{Quote, QuoteId, QuoteLine};
+use crate::domain::sales_rep::SalesRepRole;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -16,12 +17,21 @@ pub enum OperationType {
 }
 
 impl OperationType {
-    fn target_key(&self) -> String {
+    /// Product line the operation edits; operations on the same key conflict.
+    pub fn target_key(&self) -> String {
         match self {
             Self::Insert { line } => line.product_id.0.clone(),
             Self::Update { product_id, .. } | Self::Delete { product_id } => product_id.0.clone(),
         }
     }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Insert { .. } => "insert",
+            Self::Update { .. } => "update",
+            Self::Delete { .. } => "delete",
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +40,40 @@ pub struct OperationAuthority {
     pub rank: u8,
 }
 
+impl OperationAuthority {
+    /// Authority of a collaborator role; deal desk and finance outrank the quote's reps.
+    pub fn for_role(role: &str) -> Option<Self> {
+        let rank = match role {
+            "sales_rep" => 1,
+            "sales_manager" => 2,
+            "deal_desk" => 3,
+            "finance" | "vp_sales" => 4,
+            _ => return None,
+        };
+        Some(Self { role: role.to_string(), rank })
+    }
+
+    /// Highest authority a `sales_rep` record entitles its holder to.
+    pub fn for_rep_role(role: SalesRepRole) -> Self {
+        let (role, rank) = match role {
+            SalesRepRole::Ae | SalesRepRole::Se => ("sales_rep", 1),
+            SalesRepRole::Manager => ("sales_manager", 2),
+            SalesRepRole::Ops => ("deal_desk", 3),
+            SalesRepRole::Vp | SalesRepRole::Cro => ("vp_sales", 4),
+        };
+        Self { role: role.to_string(), rank }
+    }
+
+    /// The lower of two authorities, so a requested role can only narrow an entitlement.
+    pub fn lower(self, other: Self) -> Self {
+        if other.rank < self.rank {
+            other
+        } else {
+            self
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct QuoteOperation {
     pub operation_id: String,
@@ -40,6 +84,13 @@ pub struct QuoteOperation {
     pub operation: OperationType,
 }
 
+impl QuoteOperation {
+    /// True when this operation wins a conflict with `other` on the same target.
+    pub fn outranks(&self, other: &QuoteOperation) -> bool {
+        operation_precedence_compare(self, other) == Ordering::Greater
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum OperationStatus {
@@ -48,6 +99,25 @@ pub enum OperationStatus {
     Rejected,
 }
 
+impl OperationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Applied => "applied",
+            Self::Overridden => "overridden",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse_label(s: &str) -> Option<Self> {
+        match s {
+            "applied" => Some(Self::Applied),
+            "overridden" => Some(Self::Overridden),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OperationHistoryEntry {
     pub operation_id: String,
@@ -280,6 +350,7 @@ mod tests {
     };
     use crate::domain::product::ProductId;
     use crate::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use crate::domain::sales_rep::SalesRepRole;
 
     #[test]
     fn higher_authority_update_wins_conflict() {
@@ -376,6 +447,23 @@ mod tests {
         assert_eq!(engine.history()[1].status, OperationStatus::Applied);
     }
 
+    #[test]
+    fn role_authority_decides_precedence() {
+        let mut rep = update_operation("op-rep", 1, 2000, "starter", Some(2), None);
+        rep.authority = OperationAuthority::for_role("sales_rep").expect("rep role");
+        let mut desk = update_operation("op-desk", 1, 1000, "starter", Some(3), None);
+        desk.authority = OperationAuthority::for_role("deal_desk").expect("desk role");
+
+        assert!(desk.outranks(&rep));
+        assert!(!rep.outranks(&desk));
+        assert!(OperationAuthority::for_role("customer").is_none());
+
+        let ops = OperationAuthority::for_rep_role(SalesRepRole::Ops);
+        assert_eq!(Some(ops.clone()), OperationAuthority::for_role("deal_desk"));
+        let requested = OperationAuthority::for_role("vp_sales").expect("vp role");
+        assert_eq!(requested.lower(ops).role, "deal_desk");
+    }
+
     fn quote_with_lines(lines: Vec<QuoteLine>) -> Quote {
         let now = Utc::now();
         Quote {
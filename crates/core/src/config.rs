@@ -56,6 +56,11 @@ pub struct CrmConfig {
     pub salesforce_client_secret: Option<String>,
     pub hubspot_client_id: Option<String>,
     pub hubspot_client_secret: Option<String>,
+    /// Salesforce login host used for OAuth; defaults to `https://login.salesforce.com`.
+    /// Point it at `https://test.salesforce.com` for sandboxes.
+    pub salesforce_login_url: Option<String>,
+    /// HubSpot API host; defaults to `https://api.hubapi.com`.
+    pub hubspot_api_base_url: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -153,6 +158,8 @@ impl Default for AppConfig {
                 salesforce_client_secret: None,
                 hubspot_client_id: None,
                 hubspot_client_secret: None,
+                salesforce_login_url: None,
+                hubspot_api_base_url: None,
             },
             logging: LoggingConfig { level: "info".to_string(), format: LogFormat::Compact },
         }
@@ -293,6 +300,12 @@ impl AppConfig {
             if let Some(hubspot_client_secret) = crm.hubspot_client_secret {
                 self.crm.hubspot_client_secret = Some(hubspot_client_secret);
             }
+            if let Some(salesforce_login_url) = crm.salesforce_login_url {
+                self.crm.salesforce_login_url = Some(salesforce_login_url);
+            }
+            if let Some(hubspot_api_base_url) = crm.hubspot_api_base_url {
+                self.crm.hubspot_api_base_url = Some(hubspot_api_base_url);
+            }
         }
 
         if let Some(logging) = patch.logging {
@@ -377,6 +390,12 @@ impl AppConfig {
         if let Some(value) = read_env("QUOTEY_CRM_HUBSPOT_CLIENT_SECRET") {
             self.crm.hubspot_client_secret = Some(value);
         }
+        if let Some(value) = read_env("QUOTEY_CRM_SALESFORCE_LOGIN_URL") {
+            self.crm.salesforce_login_url = Some(value);
+        }
+        if let Some(value) = read_env("QUOTEY_CRM_HUBSPOT_API_BASE_URL") {
+            self.crm.hubspot_api_base_url = Some(value);
+        }
 
         let log_level = read_env("QUOTEY_LOGGING_LEVEL").or_else(|| read_env("QUOTEY_LOG_LEVEL"));
         if let Some(value) = log_level {
@@ -655,6 +674,19 @@ fn validate_crm(crm: &CrmConfig) -> Result<(), ConfigError> {
         }
     }
 
+    for (key, value) in [
+        ("crm.salesforce_login_url", &crm.salesforce_login_url),
+        ("crm.hubspot_api_base_url", &crm.hubspot_api_base_url),
+    ] {
+        if let Some(url) = value {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::Validation(format!(
+                    "{key} must start with http:// or https://"
+                )));
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -746,6 +778,8 @@ struct CrmPatch {
     salesforce_client_secret: Option<String>,
     hubspot_client_id: Option<String>,
     hubspot_client_secret: Option<String>,
+    salesforce_login_url: Option<String>,
+    hubspot_api_base_url: Option<String>,
 }
 
 #[cfg(test)]
@@ -7,6 +7,8 @@
 //! Integration: anomaly flags surface as `PolicyViolation`s that can trigger
 //! approval escalation through the existing policy engine.
 
+use std::collections::BTreeMap;
+
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -101,6 +103,73 @@ impl DistributionStats {
         }
         ((value - self.mean) / self.std_dev).abs()
     }
+
+    /// Summarise `samples` with the given estimator. Empty input yields all zeros, which
+    /// [`Self::z_score`] treats as "not enough data".
+    pub fn from_samples(samples: &[f64], method: BaselineMethod) -> Self {
+        let sample_count = u32::try_from(samples.len()).unwrap_or(u32::MAX);
+        if samples.is_empty() {
+            return Self { mean: 0.0, std_dev: 0.0, sample_count };
+        }
+        match method {
+            BaselineMethod::MeanStdDev => {
+                let n = samples.len() as f64;
+                let mean = samples.iter().sum::<f64>() / n;
+                let std_dev = if samples.len() < 2 {
+                    0.0
+                } else {
+                    (samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+                };
+                Self { mean, std_dev, sample_count }
+            }
+            BaselineMethod::MedianMad => {
+                let center = median(samples.to_vec());
+                let mad = median(samples.iter().map(|x| (x - center).abs()).collect());
+                Self { mean: center, std_dev: mad * MAD_TO_STD_DEV, sample_count }
+            }
+        }
+    }
+}
+
+/// Scales a median absolute deviation so it estimates σ for normally distributed data.
+const MAD_TO_STD_DEV: f64 = 1.4826;
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// How [`build_baselines`] summarises historical values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BaselineMethod {
+    /// Windowed mean and sample standard deviation.
+    #[default]
+    MeanStdDev,
+    /// Median and scaled median absolute deviation; robust to a few extreme deals.
+    MedianMad,
+}
+
+impl BaselineMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MeanStdDev => "mean_std_dev",
+            Self::MedianMad => "median_mad",
+        }
+    }
+
+    pub fn parse_label(s: &str) -> Option<Self> {
+        match s {
+            "mean_std_dev" => Some(Self::MeanStdDev),
+            "median_mad" => Some(Self::MedianMad),
+            _ => None,
+        }
+    }
 }
 
 /// Baseline statistics for a product within an optional customer segment.
@@ -114,6 +183,48 @@ pub struct PricingBaseline {
     pub deal_total: DistributionStats,
 }
 
+/// One historical quote line used to build baselines. `deal_total` is the whole quote's total.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSample {
+    pub product_id: String,
+    pub segment: Option<String>,
+    pub unit_price: f64,
+    pub discount_pct: f64,
+    pub quantity: f64,
+    pub deal_total: f64,
+}
+
+/// Aggregate historical lines into one baseline per product across all segments, plus one per
+/// product and segment for samples that carry a segment. Output is sorted by product, with the
+/// all-segment baseline first.
+pub fn build_baselines(samples: &[BaselineSample], method: BaselineMethod) -> Vec<PricingBaseline> {
+    let mut groups: BTreeMap<(&str, Option<&str>), Vec<&BaselineSample>> = BTreeMap::new();
+    for sample in samples {
+        groups.entry((&sample.product_id, None)).or_default().push(sample);
+        if let Some(segment) = sample.segment.as_deref() {
+            groups.entry((&sample.product_id, Some(segment))).or_default().push(sample);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((product_id, segment), group)| {
+            let stats = |value: fn(&BaselineSample) -> f64| {
+                let values: Vec<f64> = group.iter().map(|sample| value(sample)).collect();
+                DistributionStats::from_samples(&values, method)
+            };
+            PricingBaseline {
+                product_id: product_id.to_string(),
+                segment: segment.map(str::to_string),
+                unit_price: stats(|sample| sample.unit_price),
+                discount_pct: stats(|sample| sample.discount_pct),
+                quantity: stats(|sample| sample.quantity),
+                deal_total: stats(|sample| sample.deal_total),
+            }
+        })
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Anomaly results
 // ---------------------------------------------------------------------------
@@ -335,18 +446,26 @@ impl AnomalyDetector {
     ///
     /// Products without baselines are silently skipped (insufficient data).
     pub fn score_quote(&self, quote: &Quote, baselines: &[PricingBaseline]) -> AnomalyScore {
+        self.score_lines(&quote.lines, None, baselines)
+    }
+
+    /// Score quote lines for a customer segment. Each product uses its segment baseline when
+    /// one exists, otherwise its all-segment baseline.
+    pub fn score_lines(
+        &self,
+        lines: &[QuoteLine],
+        segment: Option<&str>,
+        baselines: &[PricingBaseline],
+    ) -> AnomalyScore {
         let mut all_flags = Vec::new();
         let mut weighted_z_sum = 0.0;
         let mut weight_total = 0.0;
-        let mut matched_any_baseline = false;
+        let mut first_baseline = None;
 
         // Per-line anomaly checks
-        for line in &quote.lines {
-            let product_id = &line.product_id.0;
-            let baseline = baselines.iter().find(|b| b.product_id == *product_id);
-
-            if let Some(bl) = baseline {
-                matched_any_baseline = true;
+        for line in lines {
+            if let Some(bl) = find_baseline(baselines, &line.product_id.0, segment) {
+                first_baseline.get_or_insert(bl);
                 let line_flags = self.score_line(line, bl);
                 for flag in &line_flags {
                     let w = match flag.rule_name.as_str() {
@@ -364,25 +483,23 @@ impl AnomalyDetector {
 
         // Deal-size check — only if at least one product had a baseline
         let deal_total: f64 =
-            quote.lines.iter().map(|l| decimal_to_f64(l.unit_price) * l.quantity as f64).sum();
-
-        if matched_any_baseline {
-            if let Some(bl) = baselines.first() {
-                let z_deal = bl.deal_total.z_score(deal_total, MIN_SAMPLES);
-                if z_deal >= self.thresholds.component_flag {
-                    all_flags.push(AnomalyFlag {
-                        rule_name: "deal_size_outlier".to_string(),
-                        z_score: z_deal,
-                        observed_value: deal_total,
-                        expected_mean: bl.deal_total.mean,
-                        explanation: format!(
-                            "Deal total ${:.2} is {:.1}σ from typical ${:.2}",
-                            deal_total, z_deal, bl.deal_total.mean
-                        ),
-                    });
-                    weighted_z_sum += z_deal * self.weights.deal_size;
-                    weight_total += self.weights.deal_size;
-                }
+            lines.iter().map(|l| decimal_to_f64(l.unit_price) * l.quantity as f64).sum();
+
+        if let Some(bl) = first_baseline {
+            let z_deal = bl.deal_total.z_score(deal_total, MIN_SAMPLES);
+            if z_deal >= self.thresholds.component_flag {
+                all_flags.push(AnomalyFlag {
+                    rule_name: "deal_size_outlier".to_string(),
+                    z_score: z_deal,
+                    observed_value: deal_total,
+                    expected_mean: bl.deal_total.mean,
+                    explanation: format!(
+                        "Deal total ${:.2} is {:.1}σ from typical ${:.2}",
+                        deal_total, z_deal, bl.deal_total.mean
+                    ),
+                });
+                weighted_z_sum += z_deal * self.weights.deal_size;
+                weight_total += self.weights.deal_size;
             }
         }
 
@@ -569,6 +686,19 @@ impl AnomalyRuleSet {
     }
 }
 
+/// Segment baseline for the product if present, then the all-segment one, then any.
+fn find_baseline<'a>(
+    baselines: &'a [PricingBaseline],
+    product_id: &str,
+    segment: Option<&str>,
+) -> Option<&'a PricingBaseline> {
+    let for_product = || baselines.iter().filter(move |b| b.product_id == product_id);
+    segment
+        .and_then(|segment| for_product().find(|b| b.segment.as_deref() == Some(segment)))
+        .or_else(|| for_product().find(|b| b.segment.is_none()))
+        .or_else(|| for_product().next())
+}
+
 /// Map a raw z-score-based value into [0, 1] using a sigmoid: 2 / (1 + e^(-x)) - 1
 /// This maps 0 → 0, and grows towards 1 for large positive values.
 fn sigmoid_transform(x: f64) -> f64 {
@@ -861,4 +991,83 @@ mod tests {
         assert!(hits.iter().any(|hit| hit.rule == AnomalyRuleKind::Quantity));
         assert!(hits.iter().any(|hit| hit.rule == AnomalyRuleKind::Price));
     }
+
+    fn sample(product: &str, segment: Option<&str>, unit_price: f64) -> BaselineSample {
+        BaselineSample {
+            product_id: product.to_string(),
+            segment: segment.map(str::to_string),
+            unit_price,
+            discount_pct: 5.0,
+            quantity: 10.0,
+            deal_total: unit_price * 10.0,
+        }
+    }
+
+    #[test]
+    fn distribution_stats_from_samples_supports_both_estimators() {
+        let values = [10.0, 12.0, 11.0, 13.0, 100.0];
+
+        let mean = DistributionStats::from_samples(&values, BaselineMethod::MeanStdDev);
+        assert_eq!(mean.sample_count, 5);
+        assert!((mean.mean - 29.2).abs() < 1e-9);
+        assert!(mean.std_dev > 39.0 && mean.std_dev < 40.0);
+
+        // The outlier barely moves the robust centre or spread.
+        let robust = DistributionStats::from_samples(&values, BaselineMethod::MedianMad);
+        assert_eq!(robust.mean, 12.0);
+        assert!((robust.std_dev - 1.4826).abs() < 1e-9);
+
+        let empty = DistributionStats::from_samples(&[], BaselineMethod::MedianMad);
+        assert_eq!(empty, DistributionStats { mean: 0.0, std_dev: 0.0, sample_count: 0 });
+        assert_eq!(BaselineMethod::parse_label("median_mad"), Some(BaselineMethod::MedianMad));
+        assert_eq!(BaselineMethod::MeanStdDev.as_str(), "mean_std_dev");
+    }
+
+    #[test]
+    fn build_baselines_groups_by_product_and_segment() {
+        let samples = vec![
+            sample("SKU-B", None, 50.0),
+            sample("SKU-A", Some("smb"), 90.0),
+            sample("SKU-A", Some("smb"), 110.0),
+            sample("SKU-A", Some("enterprise"), 300.0),
+        ];
+
+        let baselines = build_baselines(&samples, BaselineMethod::MeanStdDev);
+        let keys: Vec<(&str, Option<&str>, u32)> = baselines
+            .iter()
+            .map(|b| (b.product_id.as_str(), b.segment.as_deref(), b.unit_price.sample_count))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("SKU-A", None, 3),
+                ("SKU-A", Some("enterprise"), 1),
+                ("SKU-A", Some("smb"), 2),
+                ("SKU-B", None, 1),
+            ]
+        );
+        assert_eq!(baselines[2].unit_price.mean, 100.0);
+        assert_eq!(baselines[2].deal_total.mean, 1000.0);
+    }
+
+    #[test]
+    fn score_lines_prefers_the_segment_baseline() {
+        let all_segments = baseline_for("SKU-001");
+        let enterprise = PricingBaseline {
+            segment: Some("enterprise".to_string()),
+            unit_price: DistributionStats { mean: 300.0, std_dev: 20.0, sample_count: 20 },
+            deal_total: DistributionStats { mean: 3000.0, std_dev: 500.0, sample_count: 20 },
+            ..baseline_for("SKU-001")
+        };
+        let baselines = vec![enterprise, all_segments];
+        let lines = vec![line("SKU-001", 30_000, 10, 5.0)];
+        let detector = AnomalyDetector::default();
+
+        let in_segment = detector.score_lines(&lines, Some("enterprise"), &baselines);
+        assert!(in_segment.flags.is_empty(), "{:?}", in_segment.flags);
+
+        let elsewhere = detector.score_lines(&lines, Some("smb"), &baselines);
+        assert!(elsewhere.flags.iter().any(|flag| flag.rule_name == "unit_price_outlier"));
+        assert_eq!(elsewhere, detector.score_lines(&lines, None, &baselines));
+    }
 }
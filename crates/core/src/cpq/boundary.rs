@@ -113,6 +113,60 @@ impl BoundaryCalculator {
         Self { margin_policies, discount_policies }
     }
 
+    /// Check a margin against the floor policy of `category`.
+    ///
+    /// Returns a `PricingFloor` reason below the hard floor, an `ApprovalRequired` reason below
+    /// the soft floor, and `None` when the margin clears both or the category has no policy.
+    pub fn check_margin_floor(&self, category: &str, margin_pct: f64) -> Option<StopReason> {
+        let margin_policy = self.margin_policies.iter().find(|p| p.category == category)?;
+        if margin_pct < margin_policy.hard_floor_pct {
+            Some(StopReason {
+                category: StopReasonCategory::PricingFloor,
+                message: format!(
+                    "margin {:.1}% below hard floor {:.1}% for category {}",
+                    margin_pct, margin_policy.hard_floor_pct, category
+                ),
+            })
+        } else if margin_pct < margin_policy.soft_floor_pct {
+            Some(StopReason {
+                category: StopReasonCategory::ApprovalRequired,
+                message: format!(
+                    "margin {:.1}% below soft floor {:.1}% for category {}",
+                    margin_pct, margin_policy.soft_floor_pct, category
+                ),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check a discount against the ceiling policy of `category`.
+    ///
+    /// Returns a `PricingFloor` reason above the hard ceiling, an `ApprovalRequired` reason above
+    /// the soft ceiling, and `None` when the discount is within both or the category has no policy.
+    pub fn check_discount_ceiling(&self, category: &str, discount_pct: f64) -> Option<StopReason> {
+        let discount_policy = self.discount_policies.iter().find(|p| p.category == category)?;
+        if discount_pct > discount_policy.hard_ceiling_pct {
+            Some(StopReason {
+                category: StopReasonCategory::PricingFloor,
+                message: format!(
+                    "discount {:.1}% exceeds hard ceiling {:.1}% for category {}",
+                    discount_pct, discount_policy.hard_ceiling_pct, category
+                ),
+            })
+        } else if discount_pct > discount_policy.soft_ceiling_pct {
+            Some(StopReason {
+                category: StopReasonCategory::ApprovalRequired,
+                message: format!(
+                    "discount {:.1}% exceeds soft ceiling {:.1}% for category {}",
+                    discount_pct, discount_policy.soft_ceiling_pct, category
+                ),
+            })
+        } else {
+            None
+        }
+    }
+
     /// Evaluate boundaries for a negotiation position.
     pub fn evaluate(&self, input: &BoundaryInput) -> (BoundaryEvaluation, Vec<StopReason>) {
         let mut stop_reasons = Vec::new();
@@ -122,58 +176,26 @@ impl BoundaryCalculator {
         let mut requires_approval = false;
 
         // Check margin floor
-        if let Some(margin_policy) =
-            self.margin_policies.iter().find(|p| p.category == input.product_category)
-        {
-            if input.margin_pct < margin_policy.hard_floor_pct {
+        if let Some(reason) = self.check_margin_floor(&input.product_category, input.margin_pct) {
+            if reason.category == StopReasonCategory::PricingFloor {
                 floor_breached = true;
                 walk_away = true;
-                stop_reasons.push(StopReason {
-                    category: StopReasonCategory::PricingFloor,
-                    message: format!(
-                        "margin {:.1}% below hard floor {:.1}% for category {}",
-                        input.margin_pct, margin_policy.hard_floor_pct, input.product_category
-                    ),
-                });
-            } else if input.margin_pct < margin_policy.soft_floor_pct {
+            } else {
                 requires_approval = true;
-                stop_reasons.push(StopReason {
-                    category: StopReasonCategory::ApprovalRequired,
-                    message: format!(
-                        "margin {:.1}% below soft floor {:.1}% for category {}",
-                        input.margin_pct, margin_policy.soft_floor_pct, input.product_category
-                    ),
-                });
             }
+            stop_reasons.push(reason);
         }
 
         // Check discount ceiling
-        if let Some(discount_policy) =
-            self.discount_policies.iter().find(|p| p.category == input.product_category)
+        if let Some(reason) =
+            self.check_discount_ceiling(&input.product_category, input.discount_pct)
         {
-            if input.discount_pct > discount_policy.hard_ceiling_pct {
+            if reason.category == StopReasonCategory::PricingFloor {
                 ceiling_breached = true;
-                stop_reasons.push(StopReason {
-                    category: StopReasonCategory::PricingFloor,
-                    message: format!(
-                        "discount {:.1}% exceeds hard ceiling {:.1}% for category {}",
-                        input.discount_pct,
-                        discount_policy.hard_ceiling_pct,
-                        input.product_category
-                    ),
-                });
-            } else if input.discount_pct > discount_policy.soft_ceiling_pct {
+            } else {
                 requires_approval = true;
-                stop_reasons.push(StopReason {
-                    category: StopReasonCategory::ApprovalRequired,
-                    message: format!(
-                        "discount {:.1}% exceeds soft ceiling {:.1}% for category {}",
-                        input.discount_pct,
-                        discount_policy.soft_ceiling_pct,
-                        input.product_category
-                    ),
-                });
             }
+            stop_reasons.push(reason);
         }
 
         // Check max turns
@@ -32,8 +32,8 @@ pub fn check_discount_budget(rep: &SalesRep, proposed_discount_cents: i64) -> Bu
     }
 
     let total_after = rep.spent_discount_cents.saturating_add(proposed_discount_cents);
-    let used_pct_raw = ((total_after as f64 / budget as f64) * 100.0).round() as u8;
-    let used_pct = used_pct_raw.min(255);
+    // `as u8` saturates, so anything above 255% clamps to 255.
+    let used_pct = ((total_after as f64 / budget as f64) * 100.0).round() as u8;
     let remaining = (budget - total_after).max(0);
 
     if used_pct >= 100 {
@@ -1,9 +1,13 @@
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
-use crate::{VisualActionType, VisualRuleDefinition, VisualRuleType};
+use crate::cpq::policy_rules::{PolicyRule, PolicyRuleAction, PolicyRuleScope};
+use crate::{
+    LogicalConnector, VisualActionType, VisualOperator, VisualRuleDefinition, VisualRuleType,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DiscountPolicyDraft {
@@ -98,6 +102,230 @@ pub fn build_discount_policy(
     })
 }
 
+/// Builds a scoped, effective-dated [`PolicyRule`] from a `discount_policy` or
+/// `approval_threshold` visual rule.
+///
+/// Conditions are ANDed and set the scope: `customer_segment`, `product_family` (or
+/// `product_category`), `region` and `rep_role` take `equals`/`in`; `deal_value` sets the minimum
+/// deal value; `effective_date` with `>=`/`>` and `<`/`<=` bounds the effective window. Exactly
+/// one action sets what the rule checks: `apply_discount_cap` (per line with
+/// `"applies_to": "line"`), `require_minimum_deal_size`, `restrict_payment_terms` or
+/// `flag_non_standard_terms`. `route_approval_role` names the approver.
+pub fn build_policy_rule(
+    visual_rule: &VisualRuleDefinition,
+) -> Result<PolicyRule, DiscountPolicyBuilderError> {
+    visual_rule.validate().map_err(DiscountPolicyBuilderError::InvalidVisualRule)?;
+
+    if !matches!(
+        visual_rule.rule_type,
+        VisualRuleType::DiscountPolicy | VisualRuleType::ApprovalThreshold
+    ) {
+        return Err(DiscountPolicyBuilderError::WrongRuleType { actual: visual_rule.rule_type });
+    }
+
+    let mut scope = PolicyRuleScope::default();
+    let mut effective_from = None;
+    let mut expires_on = None;
+
+    for condition in &visual_rule.conditions {
+        if condition.connector == Some(LogicalConnector::Or) {
+            return Err(DiscountPolicyBuilderError::UnsupportedConnector {
+                key: condition.field_key.clone(),
+            });
+        }
+        let key = condition.field_key.as_str();
+        let list = match key {
+            "customer_segment" => Some(&mut scope.customer_segments),
+            "product_family" | "product_category" => Some(&mut scope.product_families),
+            "region" => Some(&mut scope.regions),
+            "rep_role" => Some(&mut scope.rep_roles),
+            _ => None,
+        };
+        if let Some(list) = list {
+            if !matches!(condition.operator, VisualOperator::Equals | VisualOperator::In) {
+                return Err(DiscountPolicyBuilderError::UnsupportedOperator {
+                    key: key.to_string(),
+                    operator: condition.operator,
+                });
+            }
+            list.extend(string_list(&condition.value, key)?);
+            continue;
+        }
+
+        match key {
+            "deal_value" => {
+                scope.min_deal_value = Some(decimal_from_json(&condition.value).map_err(|_| {
+                    DiscountPolicyBuilderError::InvalidDecimal {
+                        key: key.to_string(),
+                        value: condition.value.to_string(),
+                    }
+                })?);
+            }
+            "effective_date" => {
+                let date = date_from_json(&condition.value, key)?;
+                match condition.operator {
+                    VisualOperator::GreaterOrEqual => effective_from = Some(date),
+                    VisualOperator::GreaterThan => effective_from = date.succ_opt(),
+                    VisualOperator::LessThan => expires_on = Some(date),
+                    VisualOperator::LessOrEqual => expires_on = date.succ_opt(),
+                    operator => {
+                        return Err(DiscountPolicyBuilderError::UnsupportedOperator {
+                            key: key.to_string(),
+                            operator,
+                        })
+                    }
+                }
+            }
+            _ => {
+                return Err(DiscountPolicyBuilderError::UnsupportedCondition {
+                    key: key.to_string(),
+                })
+            }
+        }
+    }
+
+    if let (Some(from), Some(until)) = (effective_from, expires_on) {
+        if until <= from {
+            return Err(DiscountPolicyBuilderError::InvalidEffectiveWindow { from, until });
+        }
+    }
+
+    let mut approver_role = None;
+    let mut approval_max_pct = None;
+    for action in &visual_rule.actions {
+        match action.action_type {
+            VisualActionType::RouteApprovalRole => {
+                approver_role =
+                    Some(required_string_parameter(&action.parameters, "approver_role")?);
+                if action.parameters.contains_key("max_discount_with_approval_pct") {
+                    approval_max_pct = Some(decimal_from_parameter(
+                        &action.parameters,
+                        "max_discount_with_approval_pct",
+                    )?);
+                }
+            }
+            VisualActionType::SetApprovalThreshold => {
+                approval_max_pct =
+                    Some(decimal_from_parameter(&action.parameters, "threshold_pct")?);
+            }
+            _ => {}
+        }
+    }
+    let approver = |default: &str| approver_role.clone().unwrap_or_else(|| default.to_string());
+
+    let mut policy_action = None;
+    for action in &visual_rule.actions {
+        let parameters = &action.parameters;
+        let built = match action.action_type {
+            VisualActionType::ApplyDiscountCap => {
+                let max_discount_pct = decimal_from_parameter(parameters, "max_discount_pct")?;
+                if parameters.get("applies_to").and_then(Value::as_str) == Some("line") {
+                    PolicyRuleAction::LineDiscountCap {
+                        max_discount_pct,
+                        approver_role: approver("sales_manager"),
+                    }
+                } else {
+                    PolicyRuleAction::DiscountApproval {
+                        auto_approve_max_pct: max_discount_pct,
+                        approval_max_pct,
+                        approver_role: approver("sales_manager"),
+                    }
+                }
+            }
+            VisualActionType::RequireMinimumDealSize => PolicyRuleAction::MinimumDealSize {
+                min_deal_value: decimal_from_parameter(parameters, "min_deal_value")?,
+                approver_role: approver("sales_manager"),
+            },
+            VisualActionType::RestrictPaymentTerms => {
+                let terms = parameters.get("terms").ok_or_else(|| {
+                    DiscountPolicyBuilderError::MissingParameter { key: "terms".to_string() }
+                })?;
+                PolicyRuleAction::RestrictedPaymentTerms {
+                    terms: string_list(terms, "terms")?,
+                    approver_role: approver("finance"),
+                }
+            }
+            VisualActionType::FlagNonStandardTerms => {
+                let optional_list = |key: &str| match parameters.get(key) {
+                    Some(value) => string_list(value, key),
+                    None => Ok(Vec::new()),
+                };
+                let standard_payment_terms = optional_list("standard_payment_terms")?;
+                let keywords = optional_list("keywords")?;
+                let max_term_months = match parameters.get("max_term_months") {
+                    Some(value) => Some(
+                        value.as_u64().and_then(|months| u32::try_from(months).ok()).ok_or_else(
+                            || DiscountPolicyBuilderError::InvalidDecimal {
+                                key: "max_term_months".to_string(),
+                                value: value.to_string(),
+                            },
+                        )?,
+                    ),
+                    None => None,
+                };
+                if standard_payment_terms.is_empty()
+                    && keywords.is_empty()
+                    && max_term_months.is_none()
+                {
+                    return Err(DiscountPolicyBuilderError::MissingParameter {
+                        key: "standard_payment_terms".to_string(),
+                    });
+                }
+                PolicyRuleAction::NonStandardTerms {
+                    standard_payment_terms,
+                    max_term_months,
+                    keywords,
+                    approver_role: approver("legal"),
+                }
+            }
+            _ => continue,
+        };
+        if policy_action.replace(built).is_some() {
+            return Err(DiscountPolicyBuilderError::ConflictingActions);
+        }
+    }
+    let action = policy_action.ok_or(DiscountPolicyBuilderError::MissingPolicyAction)?;
+
+    Ok(PolicyRule {
+        id: visual_rule.id.clone(),
+        name: visual_rule.name.clone(),
+        priority: visual_rule.priority,
+        scope,
+        effective_from,
+        expires_on,
+        action,
+    })
+}
+
+fn string_list(value: &Value, key: &str) -> Result<Vec<String>, DiscountPolicyBuilderError> {
+    let invalid = || DiscountPolicyBuilderError::InvalidList {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let entries = match value {
+        Value::String(text) => vec![text.trim().to_string()],
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().map(|text| text.trim().to_string()).ok_or_else(invalid))
+            .collect::<Result<_, _>>()?,
+        _ => return Err(invalid()),
+    };
+    if entries.is_empty() || entries.iter().any(String::is_empty) {
+        return Err(invalid());
+    }
+    Ok(entries)
+}
+
+fn date_from_json(value: &Value, key: &str) -> Result<NaiveDate, DiscountPolicyBuilderError> {
+    value
+        .as_str()
+        .and_then(|text| NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok())
+        .ok_or_else(|| DiscountPolicyBuilderError::InvalidDate {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+}
+
 fn required_string_parameter(
     parameters: &std::collections::BTreeMap<String, Value>,
     key: &str,
@@ -150,6 +378,22 @@ pub enum DiscountPolicyBuilderError {
     MissingParameter { key: String },
     #[error("invalid decimal for `{key}`: `{value}`")]
     InvalidDecimal { key: String, value: String },
+    #[error("`{key}` must be a non-empty string or list of strings, got `{value}`")]
+    InvalidList { key: String, value: String },
+    #[error("invalid date for `{key}`: `{value}` (expected YYYY-MM-DD)")]
+    InvalidDate { key: String, value: String },
+    #[error("condition `{key}` does not support operator `{operator:?}`")]
+    UnsupportedOperator { key: String, operator: VisualOperator },
+    #[error("policy rules cannot be scoped by `{key}`")]
+    UnsupportedCondition { key: String },
+    #[error("policy rule conditions are ANDed; `{key}` uses `or`")]
+    UnsupportedConnector { key: String },
+    #[error("effective window is empty: expires {until} is not after {from}")]
+    InvalidEffectiveWindow { from: NaiveDate, until: NaiveDate },
+    #[error("policy rule needs one discount cap, minimum deal size, payment terms or non-standard terms action")]
+    MissingPolicyAction,
+    #[error("policy rule has more than one discount cap, minimum deal size, payment terms or non-standard terms action")]
+    ConflictingActions,
 }
 
 #[cfg(test)]
@@ -158,7 +402,8 @@ mod tests {
 
     use serde_json::json;
 
-    use super::{build_discount_policy, DiscountPolicyBuilderError};
+    use super::{build_discount_policy, build_policy_rule, DiscountPolicyBuilderError};
+    use crate::cpq::policy_rules::PolicyRuleAction;
     use crate::{
         LogicalConnector, VisualActionType, VisualOperator, VisualRuleAction, VisualRuleCondition,
         VisualRuleDefinition, VisualRuleMetadata, VisualRuleType, VISUAL_RULE_SCHEMA_VERSION,
@@ -240,4 +485,56 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn builds_scoped_policy_rule_from_discount_policy() {
+        let rule = build_policy_rule(&fixture()).expect("should build");
+
+        assert_eq!(rule.scope.customer_segments, vec!["smb".to_string()]);
+        assert_eq!(rule.scope.min_deal_value, Some("5000".parse().expect("min deal value")));
+        assert_eq!(
+            rule.action,
+            PolicyRuleAction::DiscountApproval {
+                auto_approve_max_pct: "10.0".parse().expect("auto threshold"),
+                approval_max_pct: Some("20.0".parse().expect("approval threshold")),
+                approver_role: "sales_manager".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn builds_effective_dated_line_cap_and_rejects_unknown_scope() {
+        let mut rule = fixture();
+        rule.conditions.push(VisualRuleCondition {
+            field_key: "product_family".to_string(),
+            operator: VisualOperator::In,
+            value: json!(["hardware", "appliances"]),
+            connector: Some(LogicalConnector::And),
+        });
+        rule.conditions.push(VisualRuleCondition {
+            field_key: "effective_date".to_string(),
+            operator: VisualOperator::GreaterOrEqual,
+            value: json!("2026-01-01"),
+            connector: Some(LogicalConnector::And),
+        });
+        rule.conditions.push(VisualRuleCondition {
+            field_key: "effective_date".to_string(),
+            operator: VisualOperator::LessOrEqual,
+            value: json!("2026-06-30"),
+            connector: Some(LogicalConnector::And),
+        });
+        rule.actions[0].parameters.insert("applies_to".to_string(), json!("line"));
+
+        let built = build_policy_rule(&rule).expect("should build");
+        assert_eq!(built.scope.product_families, vec!["hardware", "appliances"]);
+        assert_eq!(built.effective_from.map(|d| d.to_string()).as_deref(), Some("2026-01-01"));
+        assert_eq!(built.expires_on.map(|d| d.to_string()).as_deref(), Some("2026-07-01"));
+        assert!(matches!(built.action, PolicyRuleAction::LineDiscountCap { .. }));
+
+        rule.conditions[2].field_key = "industry".to_string();
+        assert_eq!(
+            build_policy_rule(&rule),
+            Err(DiscountPolicyBuilderError::UnsupportedCondition { key: "industry".to_string() })
+        );
+    }
 }
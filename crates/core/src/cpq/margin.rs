@@ -0,0 +1,209 @@
+//! Line and quote margin computed from product cost records.
+//!
+//! Margin is internal pricing data: it feeds the policy margin floor and the per-category floors
+//! of [`BoundaryCalculator`], and is shown in internal views only — never on customer-facing
+//! portal pages or PDFs. A line whose product has no cost in the quote currency is reported as
+//! uncosted and left out of the quote margin instead of being treated as free.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::cpq::boundary::{BoundaryCalculator, StopReasonCategory};
+use crate::cpq::policy::{PolicyDecision, PolicyViolation};
+use crate::domain::approval::ApprovalStatus;
+
+/// Policy id of the violations added by [`apply_category_margin_floors`].
+pub const CATEGORY_MARGIN_FLOOR_POLICY_ID: &str = "category-margin-floor";
+
+/// A priced line, as margin computation needs it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarginLine {
+    pub line_id: String,
+    pub product_id: String,
+    pub quantity: u32,
+    /// Line revenue after discounts.
+    pub net_amount: Decimal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineMargin {
+    pub line_id: String,
+    pub product_id: String,
+    pub revenue: Decimal,
+    /// Unit cost × quantity; `None` when the product has no cost record in the quote currency.
+    pub cost: Option<Decimal>,
+    pub margin: Option<Decimal>,
+    pub margin_pct: Option<Decimal>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteMargin {
+    pub lines: Vec<LineMargin>,
+    /// Revenue of the costed lines; the base of `margin_pct`.
+    pub costed_revenue: Decimal,
+    pub cost: Decimal,
+    pub margin: Decimal,
+    /// `None` when no line is costed.
+    pub margin_pct: Option<Decimal>,
+    pub uncosted_line_ids: Vec<String>,
+}
+
+impl QuoteMargin {
+    /// Computes margin from the unit cost of each line's product, keyed by product id.
+    pub fn compute(lines: &[MarginLine], unit_costs: &BTreeMap<String, Decimal>) -> Self {
+        let mut margin = Self::default();
+        for line in lines {
+            let cost = unit_costs
+                .get(&line.product_id)
+                .map(|unit_cost| (*unit_cost * Decimal::from(line.quantity)).round_dp(2));
+            match cost {
+                Some(cost) => {
+                    margin.costed_revenue += line.net_amount;
+                    margin.cost += cost;
+                }
+                None => margin.uncosted_line_ids.push(line.line_id.clone()),
+            }
+            margin.lines.push(LineMargin {
+                line_id: line.line_id.clone(),
+                product_id: line.product_id.clone(),
+                revenue: line.net_amount,
+                cost,
+                margin: cost.map(|cost| line.net_amount - cost),
+                margin_pct: cost.and_then(|cost| margin_pct(line.net_amount, cost)),
+            });
+        }
+        margin.margin = margin.costed_revenue - margin.cost;
+        if margin.uncosted_line_ids.len() < lines.len() {
+            margin.margin_pct = margin_pct(margin.costed_revenue, margin.cost);
+        }
+        margin
+    }
+
+    /// Whether every line had a cost record.
+    pub fn is_complete(&self) -> bool {
+        self.uncosted_line_ids.is_empty()
+    }
+}
+
+/// Margin as a percentage of revenue. A costed line given away for free is a -100% margin.
+fn margin_pct(revenue: Decimal, cost: Decimal) -> Option<Decimal> {
+    if revenue > Decimal::ZERO {
+        Some(((revenue - cost) * Decimal::from(100) / revenue).round_dp(2))
+    } else if cost > Decimal::ZERO {
+        Some(Decimal::from(-100))
+    } else {
+        None
+    }
+}
+
+/// Checks every costed line against the margin floor of its product family and records the
+/// breaches on `decision`: a hard-floor breach needs `vp_finance`, a soft-floor breach `finance`.
+///
+/// `families` maps product id to family name; names match floor categories case-insensitively.
+pub fn apply_category_margin_floors(
+    decision: &mut PolicyDecision,
+    calculator: &BoundaryCalculator,
+    margin: &QuoteMargin,
+    families: &BTreeMap<String, String>,
+) {
+    for line in &margin.lines {
+        let (Some(margin_pct), Some(family)) =
+            (line.margin_pct.and_then(|pct| pct.to_f64()), families.get(&line.product_id))
+        else {
+            continue;
+        };
+        let Some(reason) = calculator.check_margin_floor(&family.to_ascii_lowercase(), margin_pct)
+        else {
+            continue;
+        };
+        let approver = if reason.category == StopReasonCategory::PricingFloor {
+            "vp_finance"
+        } else {
+            "finance"
+        };
+        decision.reasons.push(format!("Line {}: {}", line.line_id, reason.message));
+        decision.violations.push(PolicyViolation {
+            policy_id: CATEGORY_MARGIN_FLOOR_POLICY_ID.to_string(),
+            reason: format!("Line {}: {}", line.line_id, reason.message),
+            required_approval: Some(approver.to_string()),
+        });
+        decision.approval_required = true;
+        decision.approval_status = ApprovalStatus::Pending;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpq::policy::evaluate_policy;
+
+    fn line(id: &str, product_id: &str, quantity: u32, net_amount: i64) -> MarginLine {
+        MarginLine {
+            line_id: id.to_string(),
+            product_id: product_id.to_string(),
+            quantity,
+            net_amount: Decimal::from(net_amount),
+        }
+    }
+
+    #[test]
+    fn quote_margin_covers_costed_lines_and_reports_uncosted_ones() {
+        let costs = BTreeMap::from([
+            ("plan".to_string(), Decimal::from(30)),
+            ("addon".to_string(), Decimal::from(45)),
+        ]);
+        let margin = QuoteMargin::compute(
+            &[
+                line("ql-1", "plan", 10, 1000),
+                line("ql-2", "addon", 2, 80),
+                line("ql-3", "svc", 1, 500),
+            ],
+            &costs,
+        );
+
+        assert_eq!(margin.lines[0].margin_pct, Some(Decimal::from(70)));
+        assert_eq!(margin.lines[1].margin, Some(Decimal::from(-10)));
+        assert_eq!(margin.lines[2].cost, None);
+        assert_eq!(margin.costed_revenue, Decimal::from(1080));
+        assert_eq!(margin.cost, Decimal::from(390));
+        assert_eq!(margin.margin_pct, Some(Decimal::new(6389, 2)));
+        assert_eq!(margin.uncosted_line_ids, vec!["ql-3".to_string()]);
+        assert!(!margin.is_complete());
+
+        let uncosted = QuoteMargin::compute(&[line("ql-1", "svc", 1, 500)], &costs);
+        assert_eq!(uncosted.margin_pct, None);
+    }
+
+    #[test]
+    fn category_floors_use_line_margin_of_the_product_family() {
+        let costs = BTreeMap::from([
+            ("plan".to_string(), Decimal::from(90)),
+            ("svc".to_string(), Decimal::from(75)),
+        ]);
+        let margin = QuoteMargin::compute(
+            &[line("ql-1", "plan", 1, 100), line("ql-2", "svc", 1, 100)],
+            &costs,
+        );
+        let families = BTreeMap::from([
+            ("plan".to_string(), "Software".to_string()),
+            ("svc".to_string(), "Services".to_string()),
+        ]);
+        let mut decision = evaluate_policy();
+        apply_category_margin_floors(
+            &mut decision,
+            &BoundaryCalculator::default(),
+            &margin,
+            &families,
+        );
+
+        assert!(decision.approval_required);
+        let approvers: Vec<_> =
+            decision.violations.iter().map(|v| v.required_approval.as_deref()).collect();
+        // Software at 10% is below its 15% hard floor; services at 25% only below the 30% soft floor.
+        assert_eq!(approvers, vec![Some("vp_finance"), Some("finance")]);
+        assert!(decision.violations[0].reason.starts_with("Line ql-1: margin 10.0%"));
+    }
+}
@@ -10,8 +10,11 @@ pub mod discount_policy_builder;
 pub mod draft_quote_builder;
 pub mod escalation;
 pub mod hierarchy;
+pub mod margin;
 pub mod negotiation_audit;
+pub mod negotiation_turn;
 pub mod policy;
+pub mod policy_rules;
 pub mod precedent;
 pub mod pricing;
 pub mod product_matcher;
@@ -131,7 +134,7 @@ mod tests {
             policy_input: PolicyInput {
                 requested_discount_pct: Decimal::new(500, 2),
                 deal_value: Decimal::new(100_000, 2),
-                minimum_margin_pct: Decimal::new(4000, 2),
+                minimum_margin_pct: Some(Decimal::new(4000, 2)),
             },
         });
 
@@ -183,7 +186,7 @@ mod tests {
             policy_input: PolicyInput {
                 requested_discount_pct: Decimal::ZERO,
                 deal_value: Decimal::new(100_000, 2),
-                minimum_margin_pct: Decimal::new(4000, 2),
+                minimum_margin_pct: Some(Decimal::new(4000, 2)),
             },
         });
 
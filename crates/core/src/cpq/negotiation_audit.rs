@@ -150,7 +150,7 @@ pub fn reconstruct_transcript(events: &[AuditEvent], session_id: &str) -> Vec<Tr
         .collect();
 
     // Stable sort by timestamp for deterministic ordering
-    filtered.sort_by(|a, b| a.occurred_at.cmp(&b.occurred_at));
+    filtered.sort_by_key(|a| a.occurred_at);
 
     filtered
         .iter()
@@ -0,0 +1,337 @@
+//! Turn recording rules for NXT negotiation sessions: counters, accepts and rejects.
+//!
+//! A counter is checked against the [`ConcessionPolicyEngine`] envelope and against the
+//! per-family margin floors and discount ceilings of [`BoundaryCalculator`] before it is
+//! recorded. Counters outside the hard bounds are recorded as rejected turns and leave the
+//! session where it was; counters in the soft zone send the session to approval. Accepting a
+//! counter applies its terms to the quote.
+//!
+//! Everything here is pure; persistence lives in `quotey_db::negotiation`.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::cpq::boundary::{BoundaryCalculator, StopReasonCategory};
+use crate::cpq::concession::{
+    ConcessionPolicy, ConcessionPolicyEngine, ConcessionRequest, ConcessionRequestValue,
+};
+use crate::cpq::margin::QuoteMargin;
+use crate::domain::negotiation::{
+    BoundaryEvaluation, ConcessionEnvelope, NegotiationState, TurnRequestType,
+};
+use crate::domain::quote::Quote;
+
+/// Who recorded a turn.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnParty {
+    Customer,
+    Rep,
+}
+
+impl TurnParty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Customer => "customer",
+            Self::Rep => "rep",
+        }
+    }
+
+    pub fn parse_label(s: &str) -> Option<Self> {
+        match s {
+            "customer" => Some(Self::Customer),
+            "rep" => Some(Self::Rep),
+            _ => None,
+        }
+    }
+}
+
+/// Terms proposed by a counter. The discount applies to every line of the quote.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CounterTerms {
+    pub discount_pct: f64,
+    #[serde(default)]
+    pub term_months: Option<u32>,
+}
+
+impl CounterTerms {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.discount_pct.is_finite() || !(0.0..=100.0).contains(&self.discount_pct) {
+            return Err("discount_pct must be between 0 and 100".to_string());
+        }
+        if self.term_months == Some(0) {
+            return Err("term_months must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CounterVerdict {
+    /// Inside every bound; the counter waits for the other party.
+    Offered,
+    /// Inside the hard bounds but in a soft zone; the session goes to approval.
+    NeedsApproval,
+    /// Outside a hard bound; the counter is recorded as rejected.
+    Blocked,
+}
+
+/// Result of checking a counter before it is recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CounterCheck {
+    pub verdict: CounterVerdict,
+    pub envelope: ConcessionEnvelope,
+    pub boundary: BoundaryEvaluation,
+    /// Quote margin under the counter's discount; `None` without product cost data.
+    pub margin_pct: Option<f64>,
+}
+
+/// Checks counter terms against the concession envelope and the boundary floors.
+///
+/// `margin` is the quote margin with the counter's discount applied; `families` maps product id
+/// to family name. Margin is left out of the envelope and checked per product family by the
+/// boundary calculator instead, since families carry their own floors.
+pub fn check_counter(
+    policy: &ConcessionPolicy,
+    calculator: &BoundaryCalculator,
+    session_id: &str,
+    terms: &CounterTerms,
+    margin: &QuoteMargin,
+    families: &BTreeMap<String, String>,
+) -> CounterCheck {
+    let mut values = vec![ConcessionRequestValue {
+        dimension: "discount_pct".to_string(),
+        value: terms.discount_pct,
+    }];
+    if let Some(term_months) = terms.term_months {
+        values.push(ConcessionRequestValue {
+            dimension: "term_months".to_string(),
+            value: f64::from(term_months),
+        });
+    }
+    let request = ConcessionRequest { session_id: session_id.to_string(), values };
+    let (envelope, mut boundary) = ConcessionPolicyEngine.evaluate(policy, &request);
+
+    apply_family_bounds(&mut boundary, calculator, terms, margin, families);
+    boundary.within_bounds = !boundary.floor_breached && !boundary.ceiling_breached;
+
+    let verdict = if !boundary.within_bounds || boundary.walk_away {
+        CounterVerdict::Blocked
+    } else if boundary.requires_approval {
+        CounterVerdict::NeedsApproval
+    } else {
+        CounterVerdict::Offered
+    };
+    CounterCheck {
+        verdict,
+        envelope,
+        boundary,
+        margin_pct: margin.margin_pct.and_then(|pct| pct.to_f64()),
+    }
+}
+
+/// Adds the margin floor and discount ceiling of each product family on the quote to `boundary`.
+fn apply_family_bounds(
+    boundary: &mut BoundaryEvaluation,
+    calculator: &BoundaryCalculator,
+    terms: &CounterTerms,
+    margin: &QuoteMargin,
+    families: &BTreeMap<String, String>,
+) {
+    // (costed revenue, cost) per family, lower-cased to match the floor categories.
+    let mut by_family: BTreeMap<String, (Decimal, Decimal)> = BTreeMap::new();
+    for line in &margin.lines {
+        let Some(family) = families.get(&line.product_id) else {
+            continue;
+        };
+        let totals = by_family.entry(family.to_ascii_lowercase()).or_default();
+        if let Some(cost) = line.cost {
+            totals.0 += line.revenue;
+            totals.1 += cost;
+        }
+    }
+
+    for (family, (revenue, cost)) in by_family {
+        let margin_pct = (revenue > Decimal::ZERO)
+            .then(|| ((revenue - cost) * Decimal::from(100) / revenue).to_f64())
+            .flatten();
+        if let Some(reason) = margin_pct.and_then(|pct| calculator.check_margin_floor(&family, pct))
+        {
+            if reason.category == StopReasonCategory::PricingFloor {
+                boundary.floor_breached = true;
+                boundary.walk_away = true;
+            } else {
+                boundary.requires_approval = true;
+            }
+            boundary.stop_reasons.push(reason.message);
+        }
+        if let Some(reason) = calculator.check_discount_ceiling(&family, terms.discount_pct) {
+            if reason.category == StopReasonCategory::PricingFloor {
+                boundary.ceiling_breached = true;
+            } else {
+                boundary.requires_approval = true;
+            }
+            boundary.stop_reasons.push(reason.message);
+        }
+    }
+}
+
+/// States a session passes through when a turn is recorded, in order; empty when it stays put.
+///
+/// `verdict` is the counter check result and is only consulted for counters. A new counter
+/// replaces a pending one, and a counter after approval reopens the negotiation.
+pub fn turn_transitions(
+    current: &NegotiationState,
+    request: &TurnRequestType,
+    verdict: Option<&CounterVerdict>,
+) -> Result<Vec<NegotiationState>, String> {
+    use NegotiationState::{Accepted, Active, ApprovalPending, CounterPending, Draft, Rejected};
+
+    if current.is_terminal() {
+        return Err(format!("session is {} and takes no more turns", current.as_str()));
+    }
+    let path = match request {
+        TurnRequestType::Counter => {
+            let reopen = match current {
+                Draft | CounterPending | NegotiationState::Approved => vec![Active],
+                Active => Vec::new(),
+                _ => {
+                    return Err(format!(
+                        "session is {} and cannot take a counter until it is decided",
+                        current.as_str()
+                    ))
+                }
+            };
+            match verdict {
+                Some(CounterVerdict::Offered) => [reopen, vec![CounterPending]].concat(),
+                Some(CounterVerdict::NeedsApproval) => {
+                    [reopen, vec![CounterPending, ApprovalPending]].concat()
+                }
+                // A blocked counter still replaces any pending one, so nothing is left to accept.
+                Some(CounterVerdict::Blocked) => reopen,
+                None => return Err("a counter needs a verdict".to_string()),
+            }
+        }
+        TurnRequestType::Accept => {
+            if !matches!(current, CounterPending | NegotiationState::Approved) {
+                return Err(format!(
+                    "session is {} and has no counter that can be accepted",
+                    current.as_str()
+                ));
+            }
+            vec![Accepted]
+        }
+        TurnRequestType::Reject => {
+            if !current.can_transition_to(&Rejected) {
+                return Err(format!("session is {} and cannot be rejected", current.as_str()));
+            }
+            vec![Rejected]
+        }
+        other => return Err(format!("'{}' turns are not recorded here", other.as_str())),
+    };
+
+    let mut from = current;
+    for next in &path {
+        if !from.can_transition_to(next) {
+            return Err(format!("invalid transition from {} to {}", from.as_str(), next.as_str()));
+        }
+        from = next;
+    }
+    Ok(path)
+}
+
+/// Applies accepted counter terms to every line of the quote. Versioning is up to the caller.
+pub fn apply_counter_terms(quote: &mut Quote, terms: &CounterTerms) {
+    for line in &mut quote.lines {
+        line.discount_pct = terms.discount_pct;
+    }
+    if let Some(term_months) = terms.term_months {
+        quote.term_months = Some(term_months);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpq::margin::MarginLine;
+
+    fn margin(net_amount: i64, unit_cost: i64) -> QuoteMargin {
+        let lines = [MarginLine {
+            line_id: "ql-1".to_string(),
+            product_id: "plan".to_string(),
+            quantity: 1,
+            net_amount: Decimal::from(net_amount),
+        }];
+        QuoteMargin::compute(&lines, &BTreeMap::from([("plan".to_string(), unit_cost.into())]))
+    }
+
+    fn check(discount_pct: f64, margin: &QuoteMargin) -> CounterCheck {
+        check_counter(
+            &ConcessionPolicy::default(),
+            &BoundaryCalculator::default(),
+            "NXT-1",
+            &CounterTerms { discount_pct, term_months: Some(12) },
+            margin,
+            &BTreeMap::from([("plan".to_string(), "Software".to_string())]),
+        )
+    }
+
+    #[test]
+    fn counter_verdict_follows_envelope_and_family_bounds() {
+        assert_eq!(check(10.0, &margin(90, 30)).verdict, CounterVerdict::Offered);
+
+        let soft_discount = check(30.0, &margin(70, 30));
+        assert_eq!(soft_discount.verdict, CounterVerdict::NeedsApproval);
+        assert!(soft_discount.boundary.stop_reasons.iter().any(|r| r.contains("soft ceiling")));
+
+        let below_floor = check(10.0, &margin(90, 80));
+        assert_eq!(below_floor.verdict, CounterVerdict::Blocked);
+        assert!(below_floor.boundary.walk_away);
+        assert_eq!(below_floor.margin_pct, Some(11.11));
+
+        let over_ceiling = check(45.0, &margin(55, 10));
+        assert_eq!(over_ceiling.verdict, CounterVerdict::Blocked);
+        assert!(over_ceiling.boundary.ceiling_breached);
+        assert!(!over_ceiling.boundary.walk_away);
+    }
+
+    #[test]
+    fn transitions_walk_the_lifecycle() {
+        use NegotiationState::*;
+        let counter = TurnRequestType::Counter;
+
+        assert_eq!(
+            turn_transitions(&Draft, &counter, Some(&CounterVerdict::Offered)),
+            Ok(vec![Active, CounterPending])
+        );
+        assert_eq!(
+            turn_transitions(&CounterPending, &counter, Some(&CounterVerdict::NeedsApproval)),
+            Ok(vec![Active, CounterPending, ApprovalPending])
+        );
+        assert_eq!(
+            turn_transitions(&Draft, &counter, Some(&CounterVerdict::Blocked)),
+            Ok(vec![Active])
+        );
+        assert_eq!(turn_transitions(&Active, &counter, Some(&CounterVerdict::Blocked)), Ok(vec![]));
+        assert_eq!(
+            turn_transitions(&CounterPending, &counter, Some(&CounterVerdict::Blocked)),
+            Ok(vec![Active])
+        );
+        assert!(
+            turn_transitions(&ApprovalPending, &counter, Some(&CounterVerdict::Offered)).is_err()
+        );
+
+        assert_eq!(
+            turn_transitions(&CounterPending, &TurnRequestType::Accept, None),
+            Ok(vec![Accepted])
+        );
+        assert_eq!(turn_transitions(&Approved, &TurnRequestType::Accept, None), Ok(vec![Accepted]));
+        assert!(turn_transitions(&Active, &TurnRequestType::Accept, None).is_err());
+
+        assert_eq!(turn_transitions(&Active, &TurnRequestType::Reject, None), Ok(vec![Rejected]));
+        assert!(turn_transitions(&Draft, &TurnRequestType::Reject, None).is_err());
+        assert!(turn_transitions(&Accepted, &counter, Some(&CounterVerdict::Offered)).is_err());
+    }
+}
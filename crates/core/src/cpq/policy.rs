@@ -1,7 +1,12 @@
+use std::str::FromStr;
+
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
 
 use crate::domain::approval::ApprovalStatus;
+use crate::policy::optimizer::{CandidateRuleDiff, CandidateRuleOperation};
 
 /// Configurable policy thresholds loaded from org_settings.
 /// Defaults match the original hardcoded values.
@@ -31,11 +36,108 @@ impl Default for PolicyThresholds {
     }
 }
 
+/// Why a candidate rule diff could not be applied to [`PolicyThresholds`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ThresholdDiffError {
+    #[error("rule diff `{rule_id}.{field}` does not target a policy threshold")]
+    UnknownThreshold { rule_id: String, field: String },
+    #[error("rule diff `{rule_id}.{field}` has an unusable value `{value}`")]
+    InvalidValue { rule_id: String, field: String, value: String },
+}
+
+impl PolicyThresholds {
+    /// Returns a copy with each rule diff applied in order.
+    ///
+    /// A diff targets a threshold by its field name (`manager_discount_pct`, `vp_discount_pct`,
+    /// `margin_floor_pct`, `finance_deal_value_cents`, `auto_approve_clean`) or, for generic
+    /// fields such as `threshold`, by the policy id the engine reports (`discount-cap`,
+    /// `deal-value-cap`, `margin-floor`). Values are JSON scalars or `{"value": ...}`; `remove`
+    /// restores the default.
+    pub fn with_rule_diffs(&self, diffs: &[CandidateRuleDiff]) -> Result<Self, ThresholdDiffError> {
+        let defaults = Self::default();
+        let mut next = self.clone();
+        for diff in diffs {
+            let field = diff.field.trim().to_ascii_lowercase().replace('-', "_");
+            let target = match field.as_str() {
+                "manager_discount_pct"
+                | "vp_discount_pct"
+                | "margin_floor_pct"
+                | "finance_deal_value_cents"
+                | "auto_approve_clean" => field.clone(),
+                _ => match diff.rule_id.trim() {
+                    "discount-cap" => "manager_discount_pct".to_string(),
+                    "margin-floor" => "margin_floor_pct".to_string(),
+                    "deal-value-cap" => "finance_deal_value_cents".to_string(),
+                    _ => {
+                        return Err(ThresholdDiffError::UnknownThreshold {
+                            rule_id: diff.rule_id.clone(),
+                            field: diff.field.clone(),
+                        })
+                    }
+                },
+            };
+
+            if diff.operation == CandidateRuleOperation::Remove {
+                match target.as_str() {
+                    "manager_discount_pct" => {
+                        next.manager_discount_pct = defaults.manager_discount_pct
+                    }
+                    "vp_discount_pct" => next.vp_discount_pct = defaults.vp_discount_pct,
+                    "margin_floor_pct" => next.margin_floor_pct = defaults.margin_floor_pct,
+                    "finance_deal_value_cents" => next.finance_deal_value_cents = None,
+                    _ => next.auto_approve_clean = defaults.auto_approve_clean,
+                }
+                continue;
+            }
+
+            let raw = diff.to_value_json.as_deref().unwrap_or_default();
+            let invalid = || ThresholdDiffError::InvalidValue {
+                rule_id: diff.rule_id.clone(),
+                field: diff.field.clone(),
+                value: raw.to_string(),
+            };
+            let value = match serde_json::from_str::<Value>(raw).map_err(|_| invalid())? {
+                Value::Object(mut map) => map.remove("value").ok_or_else(invalid)?,
+                value => value,
+            };
+            match target.as_str() {
+                "auto_approve_clean" => {
+                    next.auto_approve_clean = value.as_bool().ok_or_else(invalid)?;
+                }
+                "finance_deal_value_cents" => {
+                    next.finance_deal_value_cents = match value {
+                        Value::Null => None,
+                        value => Some(value.as_i64().ok_or_else(invalid)?),
+                    };
+                }
+                pct_field => {
+                    let pct = match &value {
+                        Value::Number(number) => Decimal::from_str(&number.to_string()).ok(),
+                        Value::String(text) => Decimal::from_str(text.trim()).ok(),
+                        _ => None,
+                    }
+                    .filter(|pct| *pct >= Decimal::ZERO && *pct <= Decimal::ONE_HUNDRED)
+                    .ok_or_else(invalid)?;
+                    match pct_field {
+                        "manager_discount_pct" => next.manager_discount_pct = pct,
+                        "vp_discount_pct" => next.vp_discount_pct = pct,
+                        _ => next.margin_floor_pct = pct,
+                    }
+                }
+            }
+        }
+        Ok(next)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PolicyInput {
     pub requested_discount_pct: Decimal,
     pub deal_value: Decimal,
-    pub minimum_margin_pct: Decimal,
+    /// Quote margin computed from product cost records; `None` when no line has a cost, in which
+    /// case the margin floor is not evaluated. May be negative for deals priced below cost.
+    #[serde(default)]
+    pub minimum_margin_pct: Option<Decimal>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -66,6 +168,24 @@ impl PolicyEngine for DeterministicPolicyEngine {
     }
 }
 
+/// Policy engine that applies org-configured [`PolicyThresholds`].
+#[derive(Clone, Debug, Default)]
+pub struct ThresholdPolicyEngine {
+    thresholds: PolicyThresholds,
+}
+
+impl ThresholdPolicyEngine {
+    pub fn new(thresholds: PolicyThresholds) -> Self {
+        Self { thresholds }
+    }
+}
+
+impl PolicyEngine for ThresholdPolicyEngine {
+    fn evaluate(&self, input: &PolicyInput) -> PolicyDecision {
+        evaluate_policy_with_thresholds(input, &self.thresholds)
+    }
+}
+
 pub fn evaluate_policy() -> PolicyDecision {
     PolicyDecision {
         approval_required: false,
@@ -98,15 +218,6 @@ pub fn evaluate_policy_with_thresholds(
         });
     }
 
-    if input.minimum_margin_pct < Decimal::ZERO {
-        reasons.push("Minimum margin cannot be negative".to_string());
-        violations.push(PolicyViolation {
-            policy_id: "invalid-input".to_string(),
-            reason: "minimum_margin_pct must be >= 0".to_string(),
-            required_approval: Some("risk".to_string()),
-        });
-    }
-
     if input.deal_value < Decimal::ZERO {
         reasons.push("Deal value cannot be negative".to_string());
         violations.push(PolicyViolation {
@@ -150,11 +261,17 @@ pub fn evaluate_policy_with_thresholds(
         }
     }
 
-    if input.minimum_margin_pct < thresholds.margin_floor_pct {
+    if let Some(margin_pct) =
+        input.minimum_margin_pct.filter(|margin_pct| *margin_pct < thresholds.margin_floor_pct)
+    {
         reasons.push(format!("Margin floor breached (below {}%)", thresholds.margin_floor_pct));
         violations.push(PolicyViolation {
             policy_id: "margin-floor".to_string(),
-            reason: format!("Minimum margin is below {}%", thresholds.margin_floor_pct),
+            reason: format!(
+                "Quote margin {}% is below {}%",
+                margin_pct.round_dp(2),
+                thresholds.margin_floor_pct
+            ),
             required_approval: Some("finance".to_string()),
         });
     }
@@ -175,15 +292,17 @@ mod tests {
     use rust_decimal::Decimal;
 
     use super::{
-        evaluate_policy_input, evaluate_policy_with_thresholds, PolicyInput, PolicyThresholds,
+        evaluate_policy_input, evaluate_policy_with_thresholds, PolicyEngine, PolicyInput,
+        PolicyThresholds, ThresholdDiffError, ThresholdPolicyEngine,
     };
+    use crate::policy::optimizer::{CandidateRuleDiff, CandidateRuleOperation};
 
     #[test]
     fn policy_requires_approver_above_thresholds() {
         let high_discount = evaluate_policy_input(&PolicyInput {
             requested_discount_pct: Decimal::new(3001, 2),
             deal_value: Decimal::new(50_000, 2),
-            minimum_margin_pct: Decimal::new(1200, 2),
+            minimum_margin_pct: Some(Decimal::new(1200, 2)),
         });
         assert!(high_discount.approval_required);
         assert!(high_discount.violations.iter().any(|v| v.policy_id == "discount-cap"
@@ -192,7 +311,7 @@ mod tests {
         let low_margin = evaluate_policy_input(&PolicyInput {
             requested_discount_pct: Decimal::new(1000, 2),
             deal_value: Decimal::new(50_000, 2),
-            minimum_margin_pct: Decimal::new(50, 2),
+            minimum_margin_pct: Some(Decimal::new(50, 2)),
         });
         assert!(low_margin.approval_required);
         assert!(low_margin.violations.iter().any(|v| v.policy_id == "margin-floor"));
@@ -203,11 +322,34 @@ mod tests {
         let normal = evaluate_policy_input(&PolicyInput {
             requested_discount_pct: Decimal::new(1500, 2),
             deal_value: Decimal::new(50_000, 2),
-            minimum_margin_pct: Decimal::new(2000, 2),
+            minimum_margin_pct: Some(Decimal::new(2000, 2)),
         });
         assert!(!normal.approval_required);
     }
 
+    #[test]
+    fn margin_floor_uses_computed_margin_and_skips_unknown_margin() {
+        let below_cost = evaluate_policy_input(&PolicyInput {
+            requested_discount_pct: Decimal::ZERO,
+            deal_value: Decimal::new(50_000, 2),
+            minimum_margin_pct: Some(Decimal::new(-500, 2)),
+        });
+        let violation = below_cost
+            .violations
+            .iter()
+            .find(|v| v.policy_id == "margin-floor")
+            .expect("negative margin breaches the floor");
+        assert!(violation.reason.contains("-5"));
+        assert!(!below_cost.violations.iter().any(|v| v.policy_id == "invalid-input"));
+
+        let uncosted = evaluate_policy_input(&PolicyInput {
+            requested_discount_pct: Decimal::ZERO,
+            deal_value: Decimal::new(50_000, 2),
+            minimum_margin_pct: None,
+        });
+        assert!(!uncosted.approval_required);
+    }
+
     #[test]
     fn custom_thresholds_lower_manager_trigger() {
         // Lower manager threshold to 10% — a 15% discount should now trigger
@@ -219,7 +361,7 @@ mod tests {
             &PolicyInput {
                 requested_discount_pct: Decimal::new(1500, 2),
                 deal_value: Decimal::new(50_000, 2),
-                minimum_margin_pct: Decimal::new(2000, 2),
+                minimum_margin_pct: Some(Decimal::new(2000, 2)),
             },
             &thresholds,
         );
@@ -239,7 +381,7 @@ mod tests {
             &PolicyInput {
                 requested_discount_pct: Decimal::new(3500, 2),
                 deal_value: Decimal::new(50_000, 2),
-                minimum_margin_pct: Decimal::new(2000, 2),
+                minimum_margin_pct: Some(Decimal::new(2000, 2)),
             },
             &thresholds,
         );
@@ -264,11 +406,99 @@ mod tests {
             &PolicyInput {
                 requested_discount_pct: Decimal::new(500, 2),
                 deal_value: Decimal::new(200_000, 2), // $2,000.00
-                minimum_margin_pct: Decimal::new(2000, 2),
+                minimum_margin_pct: Some(Decimal::new(2000, 2)),
             },
             &thresholds,
         );
         assert!(result.approval_required);
         assert!(result.violations.iter().any(|v| v.policy_id == "deal-value-cap"));
     }
+
+    #[test]
+    fn threshold_policy_engine_applies_configured_thresholds() {
+        let input = PolicyInput {
+            requested_discount_pct: Decimal::new(1500, 2),
+            deal_value: Decimal::new(50_000, 2),
+            minimum_margin_pct: Some(Decimal::new(4000, 2)),
+        };
+        let strict = ThresholdPolicyEngine::new(PolicyThresholds {
+            manager_discount_pct: Decimal::new(1000, 2),
+            ..PolicyThresholds::default()
+        });
+
+        assert!(!ThresholdPolicyEngine::default().evaluate(&input).approval_required);
+        assert!(strict.evaluate(&input).approval_required);
+    }
+
+    fn diff(
+        rule_id: &str,
+        field: &str,
+        operation: CandidateRuleOperation,
+        to: Option<&str>,
+    ) -> CandidateRuleDiff {
+        CandidateRuleDiff {
+            rule_id: rule_id.to_string(),
+            operation,
+            field: field.to_string(),
+            from_value_json: None,
+            to_value_json: to.map(str::to_string),
+            rationale: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn rule_diffs_update_thresholds_by_field_or_policy_id() {
+        let current = PolicyThresholds {
+            finance_deal_value_cents: Some(5_000_000),
+            ..PolicyThresholds::default()
+        };
+        let next = current
+            .with_rule_diffs(&[
+                diff(
+                    "discount-cap",
+                    "threshold",
+                    CandidateRuleOperation::Update,
+                    Some("{\"value\":18}"),
+                ),
+                diff(
+                    "margin-floor",
+                    "min_margin_pct",
+                    CandidateRuleOperation::Update,
+                    Some("\"12.5\""),
+                ),
+                diff("vp", "vp_discount_pct", CandidateRuleOperation::Update, Some("35")),
+                diff("auto", "auto_approve_clean", CandidateRuleOperation::Add, Some("false")),
+                diff("deal-value-cap", "threshold", CandidateRuleOperation::Remove, None),
+            ])
+            .expect("diffs apply");
+
+        assert_eq!(next.manager_discount_pct, Decimal::new(18, 0));
+        assert_eq!(next.margin_floor_pct, Decimal::new(125, 1));
+        assert_eq!(next.vp_discount_pct, Decimal::new(35, 0));
+        assert!(!next.auto_approve_clean);
+        assert_eq!(next.finance_deal_value_cents, None);
+    }
+
+    #[test]
+    fn rule_diffs_reject_unknown_targets_and_bad_values() {
+        let current = PolicyThresholds::default();
+        assert!(matches!(
+            current.with_rule_diffs(&[diff(
+                "bundle-rule",
+                "enabled",
+                CandidateRuleOperation::Update,
+                Some("true")
+            )]),
+            Err(ThresholdDiffError::UnknownThreshold { .. })
+        ));
+        assert!(matches!(
+            current.with_rule_diffs(&[diff(
+                "discount-cap",
+                "threshold",
+                CandidateRuleOperation::Update,
+                Some("140")
+            )]),
+            Err(ThresholdDiffError::InvalidValue { .. })
+        ));
+    }
 }
@@ -0,0 +1,491 @@
+//! Scoped, effective-dated policy rules evaluated alongside [`PolicyThresholds`].
+//!
+//! A [`PolicyRuleSet`] is authored as visual rules (see
+//! [`build_policy_rule`](crate::cpq::discount_policy_builder::build_policy_rule)) and stored as an
+//! immutable version. Each rule applies to quotes matching its scope (product families, customer
+//! segments, regions, rep roles and a minimum deal value) on dates inside its effective window.
+//! Evaluation is deterministic: rules run in `(priority, id)` order and every violation names the
+//! rule that raised it.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::policy::{
+    evaluate_policy_with_thresholds, PolicyDecision, PolicyInput, PolicyThresholds, PolicyViolation,
+};
+use crate::domain::approval::ApprovalStatus;
+
+/// Who and what a rule applies to. Empty lists match everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRuleScope {
+    #[serde(default)]
+    pub product_families: Vec<String>,
+    #[serde(default)]
+    pub customer_segments: Vec<String>,
+    #[serde(default)]
+    pub regions: Vec<String>,
+    #[serde(default)]
+    pub rep_roles: Vec<String>,
+    /// Only deals at or above this value are in scope.
+    #[serde(default)]
+    pub min_deal_value: Option<Decimal>,
+}
+
+impl PolicyRuleScope {
+    fn matches_quote(&self, context: &PolicyRuleContext, deal_value: Decimal) -> bool {
+        matches_any(&self.customer_segments, context.customer_segment.as_deref())
+            && matches_any(&self.regions, context.region.as_deref())
+            && matches_any(&self.rep_roles, context.rep_role.as_deref())
+            && self.min_deal_value.map_or(true, |min| deal_value >= min)
+    }
+
+    fn matches_family(&self, family: Option<&str>) -> bool {
+        matches_any(&self.product_families, family)
+    }
+}
+
+fn matches_any(allowed: &[String], value: Option<&str>) -> bool {
+    allowed.is_empty()
+        || value.is_some_and(|value| {
+            let value = value.trim();
+            allowed.iter().any(|entry| entry.trim().eq_ignore_ascii_case(value))
+        })
+}
+
+/// What a rule checks once it is in scope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyRuleAction {
+    /// Quote-level discount: above `auto_approve_max_pct` needs `approver_role`, above
+    /// `approval_max_pct` escalates to `vp_finance`.
+    DiscountApproval {
+        auto_approve_max_pct: Decimal,
+        approval_max_pct: Option<Decimal>,
+        approver_role: String,
+    },
+    /// Per-line discount cap for lines in the scoped product families.
+    LineDiscountCap { max_discount_pct: Decimal, approver_role: String },
+    /// Deals below `min_deal_value` need sign-off.
+    MinimumDealSize { min_deal_value: Decimal, approver_role: String },
+    /// Payment terms that may only be offered with approval.
+    RestrictedPaymentTerms { terms: Vec<String>, approver_role: String },
+    /// Flags terms outside the standard contract: payment terms not in `standard_payment_terms`,
+    /// a term longer than `max_term_months`, or quote notes mentioning any of `keywords`.
+    NonStandardTerms {
+        standard_payment_terms: Vec<String>,
+        max_term_months: Option<u32>,
+        keywords: Vec<String>,
+        approver_role: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    pub name: String,
+    pub priority: i32,
+    pub scope: PolicyRuleScope,
+    /// First day the rule applies (inclusive). `None` means always.
+    pub effective_from: Option<NaiveDate>,
+    /// Day the rule stops applying (exclusive). `None` means never.
+    pub expires_on: Option<NaiveDate>,
+    pub action: PolicyRuleAction,
+}
+
+impl PolicyRule {
+    pub fn is_effective_on(&self, date: NaiveDate) -> bool {
+        self.effective_from.map_or(true, |from| date >= from)
+            && self.expires_on.map_or(true, |until| date < until)
+    }
+}
+
+/// Quote attributes rules are scoped and checked against.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRuleContext {
+    /// Date the quote is evaluated on; selects which rules are in effect.
+    pub as_of: NaiveDate,
+    pub customer_segment: Option<String>,
+    pub region: Option<String>,
+    pub rep_role: Option<String>,
+    pub payment_terms: Option<String>,
+    pub term_months: Option<u32>,
+    pub notes: Option<String>,
+    /// Product family of each product on the quote, keyed by product id.
+    pub product_families: BTreeMap<String, String>,
+}
+
+impl PolicyRuleContext {
+    pub fn new(as_of: NaiveDate) -> Self {
+        Self {
+            as_of,
+            customer_segment: None,
+            region: None,
+            rep_role: None,
+            payment_terms: None,
+            term_months: None,
+            notes: None,
+            product_families: BTreeMap::new(),
+        }
+    }
+}
+
+/// A priced line as the rules see it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRuleLine {
+    pub line_id: String,
+    pub product_id: String,
+    pub discount_pct: Decimal,
+}
+
+/// Rules of one policy version, kept in evaluation order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRuleSet {
+    rules: Vec<PolicyRule>,
+}
+
+impl PolicyRuleSet {
+    pub fn new(mut rules: Vec<PolicyRule>) -> Self {
+        rules.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &[PolicyRule] {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// `sha256:<hex>` over the canonical JSON of the rules, in evaluation order.
+    pub fn checksum(&self) -> String {
+        let canonical = serde_json::to_string(&self.rules).unwrap_or_default();
+        format!("sha256:{:x}", Sha256::digest(canonical.as_bytes()))
+    }
+
+    /// Violations raised by the rules in effect on `context.as_of`.
+    pub fn evaluate(
+        &self,
+        input: &PolicyInput,
+        context: &PolicyRuleContext,
+        lines: &[PolicyRuleLine],
+    ) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        for rule in &self.rules {
+            if !rule.is_effective_on(context.as_of)
+                || !rule.scope.matches_quote(context, input.deal_value)
+            {
+                continue;
+            }
+            let family_of =
+                |line: &PolicyRuleLine| context.product_families.get(&line.product_id).cloned();
+            let in_scope_lines: Vec<&PolicyRuleLine> = lines
+                .iter()
+                .filter(|line| rule.scope.matches_family(family_of(line).as_deref()))
+                .collect();
+            if !rule.scope.product_families.is_empty() && in_scope_lines.is_empty() {
+                continue;
+            }
+            evaluate_rule(rule, input, context, &in_scope_lines, &mut violations);
+        }
+        violations
+    }
+}
+
+fn evaluate_rule(
+    rule: &PolicyRule,
+    input: &PolicyInput,
+    context: &PolicyRuleContext,
+    lines: &[&PolicyRuleLine],
+    violations: &mut Vec<PolicyViolation>,
+) {
+    let mut violate = |reason: String, approver: &str| {
+        violations.push(PolicyViolation {
+            policy_id: rule.id.clone(),
+            reason: format!("{}: {reason}", rule.name),
+            required_approval: Some(approver.to_string()),
+        });
+    };
+
+    match &rule.action {
+        PolicyRuleAction::DiscountApproval {
+            auto_approve_max_pct,
+            approval_max_pct,
+            approver_role,
+        } => {
+            let discount = input.requested_discount_pct;
+            match approval_max_pct {
+                Some(max) if discount > *max => {
+                    violate(format!("discount {discount}% is above {max}%"), "vp_finance")
+                }
+                _ if discount > *auto_approve_max_pct => violate(
+                    format!("discount {discount}% is above {auto_approve_max_pct}%"),
+                    approver_role,
+                ),
+                _ => {}
+            }
+        }
+        PolicyRuleAction::LineDiscountCap { max_discount_pct, approver_role } => {
+            for line in lines.iter().filter(|line| line.discount_pct > *max_discount_pct) {
+                violate(
+                    format!(
+                        "line {} discount {}% is above {max_discount_pct}%",
+                        line.line_id, line.discount_pct
+                    ),
+                    approver_role,
+                );
+            }
+        }
+        PolicyRuleAction::MinimumDealSize { min_deal_value, approver_role } => {
+            if input.deal_value < *min_deal_value {
+                violate(
+                    format!(
+                        "deal value {} is below the {min_deal_value} minimum",
+                        input.deal_value
+                    ),
+                    approver_role,
+                );
+            }
+        }
+        PolicyRuleAction::RestrictedPaymentTerms { terms, approver_role } => {
+            if let Some(payment_terms) = context.payment_terms.as_deref() {
+                if matches_listed(terms, payment_terms) {
+                    violate(
+                        format!("payment terms `{payment_terms}` are restricted"),
+                        approver_role,
+                    );
+                }
+            }
+        }
+        PolicyRuleAction::NonStandardTerms {
+            standard_payment_terms,
+            max_term_months,
+            keywords,
+            approver_role,
+        } => {
+            if let Some(payment_terms) = context.payment_terms.as_deref() {
+                if !standard_payment_terms.is_empty()
+                    && !matches_listed(standard_payment_terms, payment_terms)
+                {
+                    violate(
+                        format!("payment terms `{payment_terms}` are non-standard"),
+                        approver_role,
+                    );
+                }
+            }
+            if let (Some(max), Some(term)) = (max_term_months, context.term_months) {
+                if term > *max {
+                    violate(
+                        format!("{term}-month term is longer than the {max}-month standard"),
+                        approver_role,
+                    );
+                }
+            }
+            let notes = context.notes.as_deref().unwrap_or_default().to_ascii_lowercase();
+            let mut mentioned: Vec<&str> = keywords
+                .iter()
+                .map(|keyword| keyword.trim())
+                .filter(|keyword| {
+                    !keyword.is_empty() && notes.contains(&keyword.to_ascii_lowercase())
+                })
+                .collect();
+            mentioned.sort_unstable();
+            mentioned.dedup();
+            if !mentioned.is_empty() {
+                violate(
+                    format!("quote notes mention non-standard terms ({})", mentioned.join(", ")),
+                    approver_role,
+                );
+            }
+        }
+    }
+}
+
+fn matches_listed(listed: &[String], value: &str) -> bool {
+    listed.iter().any(|entry| entry.trim().eq_ignore_ascii_case(value.trim()))
+}
+
+/// [`evaluate_policy_with_thresholds`] plus the violations raised by `rule_set`.
+pub fn evaluate_policy_with_rule_set(
+    input: &PolicyInput,
+    thresholds: &PolicyThresholds,
+    rule_set: &PolicyRuleSet,
+    context: &PolicyRuleContext,
+    lines: &[PolicyRuleLine],
+) -> PolicyDecision {
+    let mut decision = evaluate_policy_with_thresholds(input, thresholds);
+    let violations = rule_set.evaluate(input, context, lines);
+    if violations.is_empty() {
+        return decision;
+    }
+    decision.approval_required = true;
+    decision.approval_status = ApprovalStatus::Pending;
+    decision.reasons.extend(violations.iter().map(|violation| violation.reason.clone()));
+    decision.violations.extend(violations);
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("date")
+    }
+
+    fn rule(id: &str, priority: i32, action: PolicyRuleAction) -> PolicyRule {
+        PolicyRule {
+            id: id.to_string(),
+            name: id.replace('-', " "),
+            priority,
+            scope: PolicyRuleScope::default(),
+            effective_from: None,
+            expires_on: None,
+            action,
+        }
+    }
+
+    fn input(discount: i64, deal_value: i64) -> PolicyInput {
+        PolicyInput {
+            requested_discount_pct: Decimal::from(discount),
+            deal_value: Decimal::from(deal_value),
+            minimum_margin_pct: Some(Decimal::from(100 - discount)),
+        }
+    }
+
+    fn line(id: &str, product: &str, discount: i64) -> PolicyRuleLine {
+        PolicyRuleLine {
+            line_id: id.to_string(),
+            product_id: product.to_string(),
+            discount_pct: Decimal::from(discount),
+        }
+    }
+
+    #[test]
+    fn scoped_rules_only_apply_inside_their_scope_and_window() {
+        let mut cap = rule(
+            "smb-line-cap",
+            10,
+            PolicyRuleAction::LineDiscountCap {
+                max_discount_pct: Decimal::from(5),
+                approver_role: "sales_manager".to_string(),
+            },
+        );
+        cap.scope.customer_segments = vec!["SMB".to_string()];
+        cap.scope.product_families = vec!["hardware".to_string()];
+        cap.effective_from = Some(date("2026-01-01"));
+        cap.expires_on = Some(date("2026-07-01"));
+        let rules = PolicyRuleSet::new(vec![cap]);
+
+        let mut context = PolicyRuleContext::new(date("2026-03-01"));
+        context.customer_segment = Some("smb".to_string());
+        context.product_families.insert("p-router".to_string(), "hardware".to_string());
+        context.product_families.insert("p-support".to_string(), "services".to_string());
+        let lines = [line("q-ql-1", "p-router", 8), line("q-ql-2", "p-support", 8)];
+
+        let violations = rules.evaluate(&input(8, 10_000), &context, &lines);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].policy_id, "smb-line-cap");
+        assert!(violations[0].reason.contains("q-ql-1"));
+
+        let mut enterprise = context.clone();
+        enterprise.customer_segment = Some("enterprise".to_string());
+        assert!(rules.evaluate(&input(8, 10_000), &enterprise, &lines).is_empty());
+
+        let mut expired = context.clone();
+        expired.as_of = date("2026-07-01");
+        assert!(rules.evaluate(&input(8, 10_000), &expired, &lines).is_empty());
+    }
+
+    #[test]
+    fn rule_set_violations_merge_into_the_threshold_decision_in_rule_order() {
+        let rules = PolicyRuleSet::new(vec![
+            rule(
+                "restricted-terms",
+                20,
+                PolicyRuleAction::RestrictedPaymentTerms {
+                    terms: vec!["net_90".to_string()],
+                    approver_role: "finance".to_string(),
+                },
+            ),
+            rule(
+                "minimum-deal",
+                10,
+                PolicyRuleAction::MinimumDealSize {
+                    min_deal_value: Decimal::from(5_000),
+                    approver_role: "sales_manager".to_string(),
+                },
+            ),
+            rule(
+                "non-standard",
+                30,
+                PolicyRuleAction::NonStandardTerms {
+                    standard_payment_terms: vec!["net_30".to_string()],
+                    max_term_months: Some(36),
+                    keywords: vec!["MFN".to_string()],
+                    approver_role: "legal".to_string(),
+                },
+            ),
+        ]);
+        let mut context = PolicyRuleContext::new(date("2026-03-01"));
+        context.payment_terms = Some("NET_90".to_string());
+        context.term_months = Some(48);
+        context.notes = Some("Customer asks for an mfn clause".to_string());
+
+        let decision = evaluate_policy_with_rule_set(
+            &input(5, 1_000),
+            &PolicyThresholds::default(),
+            &rules,
+            &context,
+            &[],
+        );
+        assert!(decision.approval_required);
+        let ids: Vec<&str> = decision.violations.iter().map(|v| v.policy_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["minimum-deal", "restricted-terms", "non-standard", "non-standard", "non-standard"]
+        );
+
+        let clean = evaluate_policy_with_rule_set(
+            &input(5, 10_000),
+            &PolicyThresholds::default(),
+            &PolicyRuleSet::default(),
+            &context,
+            &[],
+        );
+        assert!(!clean.approval_required);
+    }
+
+    #[test]
+    fn discount_approval_escalates_above_the_approval_cap() {
+        let mut discount = rule(
+            "emea-discount",
+            10,
+            PolicyRuleAction::DiscountApproval {
+                auto_approve_max_pct: Decimal::from(10),
+                approval_max_pct: Some(Decimal::from(15)),
+                approver_role: "sales_manager".to_string(),
+            },
+        );
+        discount.scope.regions = vec!["emea".to_string()];
+        discount.scope.rep_roles = vec!["ae".to_string()];
+        let rules = PolicyRuleSet::new(vec![discount]);
+        let mut context = PolicyRuleContext::new(date("2026-03-01"));
+        context.region = Some("emea".to_string());
+        context.rep_role = Some("ae".to_string());
+
+        let manager = rules.evaluate(&input(12, 10_000), &context, &[]);
+        assert_eq!(manager[0].required_approval.as_deref(), Some("sales_manager"));
+        let vp = rules.evaluate(&input(18, 10_000), &context, &[]);
+        assert_eq!(vp[0].required_approval.as_deref(), Some("vp_finance"));
+
+        context.rep_role = Some("manager".to_string());
+        assert!(rules.evaluate(&input(18, 10_000), &context, &[]).is_empty());
+    }
+}
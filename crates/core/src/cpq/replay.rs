@@ -4,16 +4,33 @@
 //! configuration inputs, then compares outputs to detect determinism drift.
 //! This is the core of the NXT replay guarantee: identical inputs with
 //! identical policy versions must produce identical outputs.
+//!
+//! Recorded negotiation counters and quote pricing runs are exported into a
+//! [`ReplayBundle`] and re-run with [`ReplayHarness::replay_bundle`], so a build
+//! that changes historical outcomes shows up as field-level drift.
 
 use std::collections::BTreeMap;
 
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::cpq::boundary::BoundaryCalculator;
 use crate::cpq::concession::{
     ConcessionPolicy, ConcessionPolicyEngine, ConcessionRequest, ConcessionRequestValue,
 };
 use crate::cpq::counteroffer::{CounterofferConfig, CounterofferPlanner};
+use crate::cpq::margin::{apply_category_margin_floors, MarginLine, QuoteMargin};
 use crate::cpq::negotiation_audit::{self, TranscriptEntry};
+use crate::cpq::negotiation_turn::{check_counter, CounterCheck, CounterTerms, CounterVerdict};
+use crate::cpq::policy::{PolicyInput, PolicyThresholds};
+use crate::cpq::policy_rules::{
+    evaluate_policy_with_rule_set, PolicyRuleContext, PolicyRuleLine, PolicyRuleSet,
+};
+use crate::domain::negotiation::{BoundaryEvaluation, ConcessionEnvelope};
+use crate::domain::quote::QuoteId;
+use crate::explanation::{policy_evaluation_from_decision, PolicyEvaluation};
 
 // ---------------------------------------------------------------------------
 // Replay step types
@@ -502,6 +519,402 @@ pub fn standard_fixture() -> ReplayFixture {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Recorded fixtures (exported from negotiation sessions and pricing runs)
+// ---------------------------------------------------------------------------
+
+/// Schema tag written on every exported [`ReplayBundle`].
+pub const REPLAY_BUNDLE_SCHEMA_VERSION: &str = "replay.v1";
+
+/// Engine build stamped on exported bundles and replay reports.
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Replay fixtures exported from recorded negotiation sessions and quote pricing runs.
+///
+/// Each fixture carries the inputs the engine saw (lines, unit costs, product families, rule
+/// context) and the outcome it produced, so a later build can re-run it without the database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayBundle {
+    pub schema_version: String,
+    pub exported_at: String,
+    /// Engine build the outcomes were recorded with.
+    pub engine_version: String,
+    #[serde(default)]
+    pub negotiations: Vec<RecordedNegotiation>,
+    #[serde(default)]
+    pub pricing_runs: Vec<RecordedPricingRun>,
+    /// Records found in the audit log that could not be turned into fixtures.
+    #[serde(default)]
+    pub skipped: Vec<SkippedRecord>,
+}
+
+impl ReplayBundle {
+    pub fn new(exported_at: impl Into<String>) -> Self {
+        Self {
+            schema_version: REPLAY_BUNDLE_SCHEMA_VERSION.to_string(),
+            exported_at: exported_at.into(),
+            engine_version: ENGINE_VERSION.to_string(),
+            negotiations: Vec::new(),
+            pricing_runs: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Number of replayable fixtures: one per counter and one per pricing run.
+    pub fn fixture_count(&self) -> usize {
+        self.negotiations.iter().map(|session| session.counters.len()).sum::<usize>()
+            + self.pricing_runs.len()
+    }
+}
+
+/// A record left out of a bundle, with the reason.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedRecord {
+    pub record: String,
+    pub reason: String,
+}
+
+/// A quote line as it stood when the recorded outcome was produced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedLine {
+    pub line_id: String,
+    pub product_id: String,
+    pub quantity: u32,
+    pub unit_price: Decimal,
+    pub discount_pct: Decimal,
+}
+
+/// Counters recorded on one negotiation session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedNegotiation {
+    pub session_id: String,
+    pub quote_id: String,
+    /// Policy version label the session was opened under.
+    pub policy_version: String,
+    pub counters: Vec<RecordedCounter>,
+}
+
+/// One counter turn with the inputs [`check_counter`] saw and the outcome it recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCounter {
+    pub turn_number: u32,
+    pub terms: CounterTerms,
+    /// Quote lines before the counter's discount is applied.
+    pub lines: Vec<RecordedLine>,
+    pub unit_costs: BTreeMap<String, Decimal>,
+    /// Product family of each product, keyed by product id.
+    pub families: BTreeMap<String, String>,
+    pub expected: CounterOutcome,
+}
+
+/// Outcome fields of a counter check compared on replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CounterOutcome {
+    pub verdict: CounterVerdict,
+    pub envelope: ConcessionEnvelope,
+    pub boundary: BoundaryEvaluation,
+}
+
+impl From<CounterCheck> for CounterOutcome {
+    fn from(check: CounterCheck) -> Self {
+        Self { verdict: check.verdict, envelope: check.envelope, boundary: check.boundary }
+    }
+}
+
+/// One quote pricing run with its inputs and the totals and policy verdict it recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPricingRun {
+    pub quote_id: String,
+    pub version: i32,
+    pub currency: String,
+    /// Policy set version the run was evaluated against, when it was recorded.
+    pub policy_version: Option<i32>,
+    pub lines: Vec<RecordedLine>,
+    pub unit_costs: BTreeMap<String, Decimal>,
+    pub context: PolicyRuleContext,
+    pub expected: PricingOutcome,
+}
+
+/// Outcome fields of a pricing run compared on replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingOutcome {
+    pub subtotal: Decimal,
+    pub discount_total: Decimal,
+    pub total: Decimal,
+    /// `approved` or `violation`.
+    pub policy_status: String,
+    /// Ids of the policies that raised a violation, in evaluation order.
+    pub violations: Vec<String>,
+}
+
+impl PricingOutcome {
+    pub fn new(
+        subtotal: Decimal,
+        discount_total: Decimal,
+        total: Decimal,
+        evaluation: &PolicyEvaluation,
+    ) -> Self {
+        // Normalized so amounts read back from storage (`750.0`) compare equal to `750`.
+        Self {
+            subtotal: subtotal.normalize(),
+            discount_total: discount_total.normalize(),
+            total: total.normalize(),
+            policy_status: evaluation.overall_status.clone(),
+            violations: evaluation
+                .violations
+                .iter()
+                .map(|violation| violation.policy_id.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Engine configuration recorded fixtures are re-run against.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayPolicy {
+    /// Policy set version `thresholds` belong to; `None` when none is active.
+    pub version: Option<i32>,
+    pub thresholds: PolicyThresholds,
+    pub rules: PolicyRuleSet,
+    pub concession: ConcessionPolicy,
+    pub calculator: BoundaryCalculator,
+}
+
+/// Replay result of one recorded fixture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayCase {
+    /// `negotiation:<session>#<turn>` or `pricing:<quote>@v<version>`.
+    pub fixture: String,
+    pub passed: bool,
+    pub drifts: Vec<DriftDiagnostic>,
+}
+
+/// Replay report for a whole [`ReplayBundle`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleReplayReport {
+    pub recorded_engine_version: String,
+    pub engine_version: String,
+    pub policy_version: Option<i32>,
+    pub total_cases: usize,
+    pub passed_cases: usize,
+    pub failed_cases: usize,
+    pub deterministic: bool,
+    pub cases: Vec<ReplayCase>,
+}
+
+impl ReplayHarness {
+    /// Re-runs every fixture in `bundle` against `policy` and reports field-level drift.
+    pub fn replay_bundle(
+        &self,
+        bundle: &ReplayBundle,
+        policy: &ReplayPolicy,
+    ) -> BundleReplayReport {
+        let mut cases = Vec::with_capacity(bundle.fixture_count());
+        for session in &bundle.negotiations {
+            for counter in &session.counters {
+                let replayed = replay_counter(&session.session_id, counter, policy);
+                cases.push(compare_outcomes(
+                    format!("negotiation:{}#{}", session.session_id, counter.turn_number),
+                    counter.turn_number as usize,
+                    &counter.expected,
+                    &replayed,
+                ));
+            }
+        }
+        for run in &bundle.pricing_runs {
+            let replayed = replay_pricing_run(run, policy);
+            cases.push(compare_outcomes(
+                format!("pricing:{}@v{}", run.quote_id, run.version),
+                usize::try_from(run.version).unwrap_or_default(),
+                &run.expected,
+                &replayed,
+            ));
+        }
+
+        let passed = cases.iter().filter(|case| case.passed).count();
+        BundleReplayReport {
+            recorded_engine_version: bundle.engine_version.clone(),
+            engine_version: ENGINE_VERSION.to_string(),
+            policy_version: policy.version,
+            total_cases: cases.len(),
+            passed_cases: passed,
+            failed_cases: cases.len() - passed,
+            deterministic: passed == cases.len(),
+            cases,
+        }
+    }
+}
+
+/// Checks a recorded counter again, applying its discount to every line as the turn service does.
+pub fn replay_counter(
+    session_id: &str,
+    counter: &RecordedCounter,
+    policy: &ReplayPolicy,
+) -> CounterOutcome {
+    let discount_pct = Decimal::from_f64(counter.terms.discount_pct).unwrap_or(Decimal::ZERO);
+    let margin_lines: Vec<MarginLine> = counter
+        .lines
+        .iter()
+        .map(|line| {
+            let subtotal = line.unit_price * Decimal::from(line.quantity);
+            MarginLine {
+                line_id: line.line_id.clone(),
+                product_id: line.product_id.clone(),
+                quantity: line.quantity,
+                net_amount: subtotal - subtotal * discount_pct / Decimal::from(100),
+            }
+        })
+        .collect();
+    check_counter(
+        &policy.concession,
+        &policy.calculator,
+        session_id,
+        &counter.terms,
+        &QuoteMargin::compute(&margin_lines, &counter.unit_costs),
+        &counter.families,
+    )
+    .into()
+}
+
+/// Prices a recorded run's lines again and evaluates the result against `policy`.
+pub fn replay_pricing_run(run: &RecordedPricingRun, policy: &ReplayPolicy) -> PricingOutcome {
+    let mut subtotal = Decimal::ZERO;
+    let mut discount_total = Decimal::ZERO;
+    let mut margin_lines = Vec::with_capacity(run.lines.len());
+    let mut rule_lines = Vec::with_capacity(run.lines.len());
+    for line in &run.lines {
+        let line_subtotal = line.unit_price * Decimal::from(line.quantity);
+        let discount_amount = (line_subtotal * line.discount_pct / Decimal::from(100)).round_dp(2);
+        subtotal += line_subtotal;
+        discount_total += discount_amount;
+        margin_lines.push(MarginLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            quantity: line.quantity,
+            net_amount: line_subtotal - discount_amount,
+        });
+        rule_lines.push(PolicyRuleLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            discount_pct: line.discount_pct,
+        });
+    }
+
+    let margin = QuoteMargin::compute(&margin_lines, &run.unit_costs);
+    let effective_discount_pct = if subtotal > Decimal::ZERO {
+        discount_total * Decimal::from(100) / subtotal
+    } else {
+        Decimal::ZERO
+    };
+    let input = PolicyInput {
+        requested_discount_pct: effective_discount_pct.round_dp(4),
+        deal_value: subtotal,
+        minimum_margin_pct: margin.margin_pct,
+    };
+    let mut decision = evaluate_policy_with_rule_set(
+        &input,
+        &policy.thresholds,
+        &policy.rules,
+        &run.context,
+        &rule_lines,
+    );
+    apply_category_margin_floors(
+        &mut decision,
+        &policy.calculator,
+        &margin,
+        &run.context.product_families,
+    );
+    let evaluation = policy_evaluation_from_decision(
+        &QuoteId(run.quote_id.clone()),
+        run.version,
+        &input,
+        &policy.thresholds,
+        &decision,
+        String::new(),
+    );
+    PricingOutcome::new(subtotal, discount_total, subtotal - discount_total, &evaluation)
+}
+
+fn compare_outcomes<T: Serialize>(
+    fixture: String,
+    sequence: usize,
+    expected: &T,
+    replayed: &T,
+) -> ReplayCase {
+    let mut drifts = Vec::new();
+    match (serde_json::to_value(expected), serde_json::to_value(replayed)) {
+        (Ok(original), Ok(replayed)) => {
+            diff_values(sequence, "", &original, &replayed, &mut drifts);
+        }
+        (Err(error), _) | (_, Err(error)) => drifts.push(DriftDiagnostic {
+            step_sequence: sequence,
+            field: "<outcome>".to_string(),
+            original_value: "<serializable>".to_string(),
+            replayed_value: error.to_string(),
+        }),
+    }
+    ReplayCase { fixture, passed: drifts.is_empty(), drifts }
+}
+
+/// Records one drift per differing leaf of two JSON documents; `path` is dotted, with `[i]`
+/// for array positions.
+fn diff_values(
+    sequence: usize,
+    path: &str,
+    original: &Value,
+    replayed: &Value,
+    drifts: &mut Vec<DriftDiagnostic>,
+) {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (original, replayed) {
+        (Value::Object(left), Value::Object(right)) => {
+            let keys: std::collections::BTreeSet<&String> =
+                left.keys().chain(right.keys()).collect();
+            for key in keys {
+                diff_values(
+                    sequence,
+                    &child(key),
+                    left.get(key).unwrap_or(&Value::Null),
+                    right.get(key).unwrap_or(&Value::Null),
+                    drifts,
+                );
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                diff_values(
+                    sequence,
+                    &format!("{path}[{index}]"),
+                    left.get(index).unwrap_or(&Value::Null),
+                    right.get(index).unwrap_or(&Value::Null),
+                    drifts,
+                );
+            }
+        }
+        _ if original != replayed => drifts.push(DriftDiagnostic {
+            step_sequence: sequence,
+            field: path.to_string(),
+            original_value: leaf(original),
+            replayed_value: leaf(replayed),
+        }),
+        _ => {}
+    }
+}
+
+fn leaf(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => "<missing>".to_string(),
+        other => other.to_string(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -687,4 +1100,116 @@ mod tests {
         assert_eq!(report.total_steps, 0);
         assert_eq!(report.replayed_steps, 0);
     }
+
+    fn recorded_bundle() -> ReplayBundle {
+        let policy = ReplayPolicy::default();
+        let line = RecordedLine {
+            line_id: "Q-1-ql-1".to_string(),
+            product_id: "prod-1".to_string(),
+            quantity: 10,
+            unit_price: Decimal::new(10000, 2),
+            discount_pct: Decimal::new(25, 0),
+        };
+        let mut counter = RecordedCounter {
+            turn_number: 1,
+            terms: CounterTerms { discount_pct: 15.0, term_months: Some(12) },
+            lines: vec![line.clone()],
+            unit_costs: BTreeMap::from([("prod-1".to_string(), Decimal::new(40, 0))]),
+            families: BTreeMap::from([("prod-1".to_string(), "software".to_string())]),
+            expected: CounterOutcome {
+                verdict: CounterVerdict::Offered,
+                envelope: ConcessionEnvelope {
+                    session_id: crate::domain::negotiation::NegotiationSessionId(String::new()),
+                    ranges: Vec::new(),
+                    blocking_reasons: Vec::new(),
+                },
+                boundary: BoundaryEvaluation {
+                    within_bounds: true,
+                    floor_breached: false,
+                    ceiling_breached: false,
+                    walk_away: false,
+                    requires_approval: false,
+                    stop_reasons: Vec::new(),
+                },
+            },
+        };
+        counter.expected = replay_counter("NXT-1", &counter, &policy);
+        let mut run = RecordedPricingRun {
+            quote_id: "Q-1".to_string(),
+            version: 2,
+            currency: "USD".to_string(),
+            policy_version: None,
+            lines: vec![line],
+            unit_costs: BTreeMap::new(),
+            context: PolicyRuleContext::new(
+                chrono::NaiveDate::from_ymd_opt(2026, 3, 6).expect("date"),
+            ),
+            expected: PricingOutcome {
+                subtotal: Decimal::ZERO,
+                discount_total: Decimal::ZERO,
+                total: Decimal::ZERO,
+                policy_status: String::new(),
+                violations: Vec::new(),
+            },
+        };
+        run.expected = replay_pricing_run(&run, &policy);
+
+        let mut bundle = ReplayBundle::new("2026-03-06T00:00:00Z");
+        bundle.negotiations.push(RecordedNegotiation {
+            session_id: "NXT-1".to_string(),
+            quote_id: "Q-1".to_string(),
+            policy_version: "policy-v1".to_string(),
+            counters: vec![counter],
+        });
+        bundle.pricing_runs.push(run);
+        bundle
+    }
+
+    #[test]
+    fn recorded_bundle_replays_without_drift_on_the_same_engine() {
+        let bundle = recorded_bundle();
+        let pricing = &bundle.pricing_runs[0].expected;
+        assert_eq!(pricing.subtotal, Decimal::new(1000, 0));
+        assert_eq!(pricing.discount_total, Decimal::new(250, 0));
+        assert_eq!(pricing.total, Decimal::new(750, 0));
+        assert_eq!(pricing.policy_status, "violation");
+        assert_eq!(pricing.violations, vec!["discount-cap".to_string()]);
+
+        let json = serde_json::to_string(&bundle).expect("encode bundle");
+        let decoded: ReplayBundle = serde_json::from_str(&json).expect("decode bundle");
+        let report = ReplayHarness.replay_bundle(&decoded, &ReplayPolicy::default());
+
+        assert!(report.deterministic, "{:?}", report.cases);
+        assert_eq!(report.total_cases, 2);
+        assert_eq!(report.cases[0].fixture, "negotiation:NXT-1#1");
+        assert_eq!(report.cases[1].fixture, "pricing:Q-1@v2");
+        assert_eq!(report.engine_version, bundle.engine_version);
+    }
+
+    #[test]
+    fn recorded_bundle_reports_field_level_drift_under_a_new_policy() {
+        let bundle = recorded_bundle();
+        let policy = ReplayPolicy {
+            version: Some(7),
+            thresholds: PolicyThresholds {
+                manager_discount_pct: Decimal::new(30, 0),
+                ..PolicyThresholds::default()
+            },
+            ..ReplayPolicy::default()
+        };
+
+        let report = ReplayHarness.replay_bundle(&bundle, &policy);
+
+        assert!(!report.deterministic);
+        assert_eq!(report.policy_version, Some(7));
+        assert_eq!((report.passed_cases, report.failed_cases), (1, 1));
+        let drifts = &report.cases[1].drifts;
+        assert!(drifts.iter().any(|drift| drift.field == "policy_status"
+            && drift.original_value == "violation"
+            && drift.replayed_value == "approved"));
+        assert!(drifts.iter().any(|drift| drift.field == "violations[0]"
+            && drift.original_value == "discount-cap"
+            && drift.replayed_value == "<missing>"));
+        assert!(drifts.iter().all(|drift| drift.step_sequence == 2));
+    }
 }
@@ -288,7 +288,7 @@ fn render_where_clause(conditions: &[PricingRuleCondition]) -> String {
 }
 
 /// Renders a pricing rule condition as SQL.
-/// 
+///
 /// # Security Note
 /// This function assumes field_key has already been validated against ALLOWED_PRICING_FIELD_KEYS.
 /// The is_safe_sql_identifier check here is defense-in-depth.
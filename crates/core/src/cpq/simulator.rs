@@ -512,7 +512,7 @@ impl<R: CpqRuntime> DealFlightSimulator<R> {
                     .unwrap_or(baseline_policy_input.deal_value),
                 minimum_margin_pct: variation
                     .minimum_margin_pct_override
-                    .unwrap_or(baseline_policy_input.minimum_margin_pct),
+                    .or(baseline_policy_input.minimum_margin_pct),
             };
 
             let evaluation = self.cpq_runtime.evaluate_quote(CpqEvaluationInput {
@@ -850,7 +850,7 @@ mod tests {
         let policy_input = PolicyInput {
             requested_discount_pct: Decimal::new(1000, 2),
             deal_value: Decimal::new(100_000, 2),
-            minimum_margin_pct: Decimal::new(4000, 2),
+            minimum_margin_pct: Some(Decimal::new(4000, 2)),
         };
 
         let variations = vec![
@@ -911,7 +911,7 @@ mod tests {
         let policy_input = PolicyInput {
             requested_discount_pct: Decimal::ZERO,
             deal_value: Decimal::new(100_000, 2),
-            minimum_margin_pct: Decimal::new(4000, 2),
+            minimum_margin_pct: Some(Decimal::new(4000, 2)),
         };
 
         let result = simulator.simulate(
@@ -969,7 +969,7 @@ mod tests {
         let policy_input = PolicyInput {
             requested_discount_pct: Decimal::ZERO,
             deal_value: Decimal::new(100_000, 2),
-            minimum_margin_pct: Decimal::new(4000, 2),
+            minimum_margin_pct: Some(Decimal::new(4000, 2)),
         };
 
         let comparison = simulator
@@ -1029,7 +1029,7 @@ mod tests {
         let policy_input = PolicyInput {
             requested_discount_pct: Decimal::new(500, 2),
             deal_value: Decimal::new(90_000, 2),
-            minimum_margin_pct: Decimal::new(3500, 2),
+            minimum_margin_pct: Some(Decimal::new(3500, 2)),
         };
         let telemetry_sink = RecordingTelemetrySink::default();
 
@@ -1090,7 +1090,7 @@ mod tests {
         let policy_input = PolicyInput {
             requested_discount_pct: Decimal::ZERO,
             deal_value: Decimal::new(50_000, 2),
-            minimum_margin_pct: Decimal::new(4000, 2),
+            minimum_margin_pct: Some(Decimal::new(4000, 2)),
         };
         let telemetry_sink = RecordingTelemetrySink::default();
 
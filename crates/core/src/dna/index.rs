@@ -0,0 +1,344 @@
+//! Multi-index hashing over 128-bit configuration fingerprints.
+//!
+//! Each fingerprint is split into [`INDEX_CHUNKS`] chunks of [`CHUNK_BITS`] bits. Two
+//! fingerprints within Hamming distance `r` must agree on at least one chunk to within
+//! `r / INDEX_CHUNKS` bits, so a lookup only has to visit the buckets of chunk values near the
+//! query instead of every stored deal. The same chunking backs the persisted
+//! `deal_similarity_index` table.
+
+use std::collections::{BTreeSet, HashMap};
+
+use rust_decimal::Decimal;
+
+use super::{
+    DealOutcomeMetadata, DealOutcomeStatus, SimilarDeal, FINGERPRINT_BITS, FINGERPRINT_BYTES,
+};
+
+pub const INDEX_CHUNKS: usize = 8;
+pub const CHUNK_BITS: usize = FINGERPRINT_BITS / INDEX_CHUNKS;
+/// Largest per-chunk radius worth probing; wider searches fall back to a filtered scan.
+pub const MAX_PROBE_RADIUS: usize = 4;
+
+/// A closed deal as stored in the similarity index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarityIndexEntry {
+    pub quote_id: String,
+    pub fingerprint: [u8; FINGERPRINT_BYTES],
+    pub outcome: DealOutcomeStatus,
+    pub customer_segment: Option<String>,
+    pub final_price: Decimal,
+    pub close_date: Option<String>,
+}
+
+impl SimilarityIndexEntry {
+    pub fn chunks(&self) -> [u16; INDEX_CHUNKS] {
+        fingerprint_chunks(&self.fingerprint)
+    }
+
+    pub fn to_similar_deal(&self, hamming_distance: usize) -> SimilarDeal {
+        SimilarDeal {
+            outcome: DealOutcomeMetadata {
+                quote_id: self.quote_id.clone(),
+                outcome_status: self.outcome.clone(),
+                final_price: self.final_price,
+                close_date: self.close_date.clone(),
+            },
+            similarity_score: similarity_from_distance(hamming_distance),
+            hamming_distance,
+        }
+    }
+}
+
+/// Narrows a similarity search to a segment, outcome and close-date window.
+///
+/// Date bounds are inclusive and compare the `YYYY-MM-DD` prefix of `close_date`; deals
+/// without a close date are excluded once either bound is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimilarityFilter {
+    pub customer_segment: Option<String>,
+    pub outcome: Option<DealOutcomeStatus>,
+    pub closed_from: Option<String>,
+    pub closed_to: Option<String>,
+}
+
+impl SimilarityFilter {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn matches(&self, entry: &SimilarityIndexEntry) -> bool {
+        if let Some(segment) = &self.customer_segment {
+            let same_segment = entry
+                .customer_segment
+                .as_deref()
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(segment));
+            if !same_segment {
+                return false;
+            }
+        }
+        if self.outcome.as_ref().is_some_and(|outcome| outcome != &entry.outcome) {
+            return false;
+        }
+        if self.closed_from.is_none() && self.closed_to.is_none() {
+            return true;
+        }
+        let Some(closed) = entry.close_date.as_deref().map(date_prefix) else {
+            return false;
+        };
+        self.closed_from.as_deref().map_or(true, |from| closed >= date_prefix(from))
+            && self.closed_to.as_deref().map_or(true, |to| closed <= date_prefix(to))
+    }
+}
+
+/// Splits a fingerprint into big-endian 16-bit chunks.
+pub fn fingerprint_chunks(fingerprint: &[u8; FINGERPRINT_BYTES]) -> [u16; INDEX_CHUNKS] {
+    let mut chunks = [0u16; INDEX_CHUNKS];
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+        *chunk = u16::from_be_bytes([fingerprint[index * 2], fingerprint[index * 2 + 1]]);
+    }
+    chunks
+}
+
+pub fn hamming_distance_bytes(
+    left: &[u8; FINGERPRINT_BYTES],
+    right: &[u8; FINGERPRINT_BYTES],
+) -> usize {
+    left.iter().zip(right.iter()).map(|(a, b)| (a ^ b).count_ones() as usize).sum()
+}
+
+pub fn similarity_from_distance(hamming_distance: usize) -> f32 {
+    1.0 - hamming_distance as f32 / FINGERPRINT_BITS as f32
+}
+
+/// Largest Hamming distance that still scores at least `min_similarity`.
+pub fn max_hamming_distance(min_similarity: f32) -> usize {
+    let slack = (1.0 - min_similarity.clamp(0.0, 1.0)) * FINGERPRINT_BITS as f32;
+    (slack + 1e-4).floor() as usize
+}
+
+/// Per-chunk radius to probe for `min_similarity`, or `None` when a scan is cheaper.
+pub fn probe_radius(min_similarity: f32) -> Option<usize> {
+    let radius = max_hamming_distance(min_similarity) / INDEX_CHUNKS;
+    (radius <= MAX_PROBE_RADIUS).then_some(radius)
+}
+
+/// Every chunk value within `radius` flipped bits of `chunk`, including `chunk` itself.
+pub fn chunk_neighbours(chunk: u16, radius: usize) -> Vec<u16> {
+    let mut neighbours = vec![chunk];
+    let mut frontier = vec![(chunk, 0usize)];
+    for _ in 0..radius.min(CHUNK_BITS) {
+        let mut next = Vec::new();
+        for (value, lowest_bit) in frontier {
+            for bit in lowest_bit..CHUNK_BITS {
+                let flipped = value ^ (1 << bit);
+                neighbours.push(flipped);
+                next.push((flipped, bit + 1));
+            }
+        }
+        frontier = next;
+    }
+    neighbours
+}
+
+/// Best first: highest similarity, then smallest distance, then quote id.
+pub fn rank_similar_deals(matches: &mut Vec<SimilarDeal>, limit: usize) {
+    matches.sort_by(|left, right| {
+        right
+            .similarity_score
+            .total_cmp(&left.similarity_score)
+            .then_with(|| left.hamming_distance.cmp(&right.hamming_distance))
+            .then_with(|| left.outcome.quote_id.cmp(&right.outcome.quote_id))
+    });
+    matches.truncate(limit);
+}
+
+/// In-memory multi-index hash keyed by quote id; the latest entry per quote wins.
+#[derive(Clone, Debug)]
+pub struct DealSimilarityIndex {
+    entries: HashMap<String, SimilarityIndexEntry>,
+    buckets: Vec<HashMap<u16, BTreeSet<String>>>,
+}
+
+impl Default for DealSimilarityIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DealSimilarityIndex {
+    pub fn new() -> Self {
+        Self { entries: HashMap::new(), buckets: vec![HashMap::new(); INDEX_CHUNKS] }
+    }
+
+    pub fn from_entries(entries: impl IntoIterator<Item = SimilarityIndexEntry>) -> Self {
+        let mut index = Self::new();
+        for entry in entries {
+            index.upsert(entry);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, quote_id: &str) -> Option<&SimilarityIndexEntry> {
+        self.entries.get(quote_id)
+    }
+
+    pub fn upsert(&mut self, entry: SimilarityIndexEntry) {
+        self.remove(&entry.quote_id);
+        for (bucket, chunk) in self.buckets.iter_mut().zip(entry.chunks()) {
+            bucket.entry(chunk).or_default().insert(entry.quote_id.clone());
+        }
+        self.entries.insert(entry.quote_id.clone(), entry);
+    }
+
+    pub fn remove(&mut self, quote_id: &str) -> Option<SimilarityIndexEntry> {
+        let entry = self.entries.remove(quote_id)?;
+        for (bucket, chunk) in self.buckets.iter_mut().zip(entry.chunks()) {
+            if let Some(ids) = bucket.get_mut(&chunk) {
+                ids.remove(quote_id);
+                if ids.is_empty() {
+                    bucket.remove(&chunk);
+                }
+            }
+        }
+        Some(entry)
+    }
+
+    pub fn query(
+        &self,
+        fingerprint: &[u8; FINGERPRINT_BYTES],
+        min_similarity: f32,
+        filter: &SimilarityFilter,
+        limit: usize,
+    ) -> Vec<SimilarDeal> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        let max_distance = max_hamming_distance(min_similarity);
+        let score = |entry: &SimilarityIndexEntry| {
+            let distance = hamming_distance_bytes(fingerprint, &entry.fingerprint);
+            (distance <= max_distance && filter.matches(entry))
+                .then(|| entry.to_similar_deal(distance))
+        };
+
+        let mut matches: Vec<SimilarDeal> = match probe_radius(min_similarity) {
+            Some(radius) => {
+                let mut candidates = BTreeSet::new();
+                for (bucket, chunk) in self.buckets.iter().zip(fingerprint_chunks(fingerprint)) {
+                    for neighbour in chunk_neighbours(chunk, radius) {
+                        if let Some(ids) = bucket.get(&neighbour) {
+                            candidates.extend(ids.iter().map(String::as_str));
+                        }
+                    }
+                }
+                candidates
+                    .into_iter()
+                    .filter_map(|id| self.entries.get(id))
+                    .filter_map(score)
+                    .collect()
+            }
+            None => self.entries.values().filter_map(score).collect(),
+        };
+        rank_similar_deals(&mut matches, limit);
+        matches
+    }
+}
+
+fn date_prefix(value: &str) -> &str {
+    value.get(..10).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::{
+        chunk_neighbours, fingerprint_chunks, max_hamming_distance, probe_radius,
+        DealSimilarityIndex, SimilarityFilter, SimilarityIndexEntry,
+    };
+    use crate::dna::{DealOutcomeStatus, FINGERPRINT_BYTES};
+
+    fn entry(quote_id: &str, fingerprint: [u8; FINGERPRINT_BYTES]) -> SimilarityIndexEntry {
+        SimilarityIndexEntry {
+            quote_id: quote_id.to_owned(),
+            fingerprint,
+            outcome: DealOutcomeStatus::Won,
+            customer_segment: Some("enterprise".to_owned()),
+            final_price: Decimal::new(1_000, 0),
+            close_date: Some("2026-02-01".to_owned()),
+        }
+    }
+
+    fn flip(mut fingerprint: [u8; FINGERPRINT_BYTES], bits: &[usize]) -> [u8; FINGERPRINT_BYTES] {
+        for bit in bits {
+            fingerprint[bit / 8] ^= 1 << (7 - bit % 8);
+        }
+        fingerprint
+    }
+
+    #[test]
+    fn chunk_neighbours_enumerate_every_value_within_radius() {
+        assert_eq!(chunk_neighbours(0, 0), vec![0]);
+        assert_eq!(chunk_neighbours(0, 1).len(), 17);
+        let within_two = chunk_neighbours(0b1010, 2);
+        assert_eq!(within_two.len(), 1 + 16 + 120);
+        assert!(within_two.iter().all(|value| (value ^ 0b1010).count_ones() <= 2));
+        assert_eq!(fingerprint_chunks(&[0xAB; FINGERPRINT_BYTES]), [0xABAB; 8]);
+    }
+
+    #[test]
+    fn probe_radius_follows_pigeonhole_bound_and_gives_up_on_wide_searches() {
+        assert_eq!(max_hamming_distance(1.0), 0);
+        assert_eq!(max_hamming_distance(0.8), 25);
+        assert_eq!(probe_radius(0.8), Some(3));
+        assert_eq!(probe_radius(0.7), Some(4));
+        assert_eq!(probe_radius(0.5), None);
+    }
+
+    #[test]
+    fn index_query_matches_linear_scan_and_applies_filters() {
+        let base = [0x5Au8; FINGERPRINT_BYTES];
+        // Spread 24 flipped bits evenly so no single chunk is an exact match.
+        let spread: Vec<usize> = (0..24).map(|i| (i % 8) * 16 + i / 8).collect();
+        let mut lost = entry("Q-SPREAD", flip(base, &spread));
+        lost.outcome = DealOutcomeStatus::Lost;
+        lost.close_date = Some("2025-06-30".to_owned());
+        let mut index = DealSimilarityIndex::from_entries([
+            entry("Q-EXACT", base),
+            entry("Q-NEAR", flip(base, &[0, 40, 90])),
+            lost,
+            entry("Q-FAR", flip(base, &(0..64).collect::<Vec<_>>())),
+        ]);
+
+        let all = index.query(&base, 0.8, &SimilarityFilter::default(), 10);
+        let ids: Vec<&str> = all.iter().map(|deal| deal.outcome.quote_id.as_str()).collect();
+        assert_eq!(ids, ["Q-EXACT", "Q-NEAR", "Q-SPREAD"]);
+        assert_eq!(all[2].hamming_distance, 24);
+
+        let won_in_2026 = SimilarityFilter {
+            customer_segment: Some("Enterprise".to_owned()),
+            outcome: Some(DealOutcomeStatus::Won),
+            closed_from: Some("2026-01-01".to_owned()),
+            closed_to: Some("2026-12-31T00:00:00Z".to_owned()),
+        };
+        let filtered = index.query(&base, 0.8, &won_in_2026, 10);
+        assert_eq!(filtered.len(), 2);
+
+        index.upsert(entry("Q-NEAR", flip(base, &(0..64).collect::<Vec<_>>())));
+        assert!(index.remove("Q-EXACT").is_some());
+        let ids: Vec<String> = index
+            .query(&base, 0.8, &SimilarityFilter::default(), 10)
+            .into_iter()
+            .map(|deal| deal.outcome.quote_id)
+            .collect();
+        assert_eq!(ids, ["Q-SPREAD"]);
+        assert_eq!(index.query(&base, 0.0, &SimilarityFilter::default(), 10).len(), 3);
+    }
+}
@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+pub mod index;
 
 use rust_decimal::Decimal;
 use serde_json::{json, Value};
@@ -7,6 +7,8 @@ use thiserror::Error;
 use crate::domain::quote::{Quote, QuoteLine, QuoteStatus};
 use crate::flows::states::{FlowAction, FlowState, TransitionOutcome};
 
+pub use index::{DealSimilarityIndex, SimilarityFilter, SimilarityIndexEntry};
+
 pub const FINGERPRINT_BITS: usize = 128;
 pub const FINGERPRINT_BYTES: usize = FINGERPRINT_BITS / 8;
 
@@ -117,17 +119,31 @@ impl FingerprintGenerator {
     }
 }
 
+/// Similar-deal lookup over a [`DealSimilarityIndex`]; candidates are keyed by quote id.
 #[derive(Clone, Debug)]
 pub struct SimilarityEngine {
     min_similarity: f32,
-    candidates: Vec<SimilarityCandidate>,
+    index: DealSimilarityIndex,
 }
 
 impl SimilarityEngine {
     const DEFAULT_MIN_SIMILARITY: f32 = 0.8;
 
     pub fn new(candidates: Vec<SimilarityCandidate>) -> Self {
-        Self { min_similarity: Self::DEFAULT_MIN_SIMILARITY, candidates }
+        Self::from_index(DealSimilarityIndex::from_entries(candidates.into_iter().map(
+            |candidate| SimilarityIndexEntry {
+                quote_id: candidate.outcome.quote_id,
+                fingerprint: candidate.fingerprint.hash_bytes,
+                outcome: candidate.outcome.outcome_status,
+                customer_segment: None,
+                final_price: candidate.outcome.final_price,
+                close_date: candidate.outcome.close_date,
+            },
+        )))
+    }
+
+    pub fn from_index(index: DealSimilarityIndex) -> Self {
+        Self { min_similarity: Self::DEFAULT_MIN_SIMILARITY, index }
     }
 
     pub fn with_min_similarity(mut self, min_similarity: f32) -> Self {
@@ -139,49 +155,29 @@ impl SimilarityEngine {
         self.min_similarity
     }
 
+    pub fn index(&self) -> &DealSimilarityIndex {
+        &self.index
+    }
+
+    pub fn index_mut(&mut self) -> &mut DealSimilarityIndex {
+        &mut self.index
+    }
+
     pub fn find_similar(
         &self,
         fingerprint: &ConfigurationFingerprint,
         limit: usize,
     ) -> Vec<SimilarDeal> {
-        if limit == 0 {
-            return Vec::new();
-        }
-
-        let generator = FingerprintGenerator::new();
-        let mut matches: Vec<SimilarDeal> = self
-            .candidates
-            .iter()
-            .filter_map(|candidate| {
-                let hamming_distance =
-                    generator.hamming_distance(fingerprint, &candidate.fingerprint);
-                let similarity_score = 1.0 - hamming_distance as f32 / FINGERPRINT_BITS as f32;
-                if similarity_score >= self.min_similarity {
-                    Some(SimilarDeal {
-                        outcome: candidate.outcome.clone(),
-                        similarity_score,
-                        hamming_distance,
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        matches.sort_by(|left, right| {
-            right
-                .similarity_score
-                .partial_cmp(&left.similarity_score)
-                .unwrap_or(Ordering::Equal)
-                .then_with(|| left.hamming_distance.cmp(&right.hamming_distance))
-                .then_with(|| left.outcome.quote_id.cmp(&right.outcome.quote_id))
-        });
-
-        if matches.len() > limit {
-            matches.truncate(limit);
-        }
+        self.find_similar_filtered(fingerprint, &SimilarityFilter::default(), limit)
+    }
 
-        matches
+    pub fn find_similar_filtered(
+        &self,
+        fingerprint: &ConfigurationFingerprint,
+        filter: &SimilarityFilter,
+        limit: usize,
+    ) -> Vec<SimilarDeal> {
+        self.index.query(&fingerprint.hash_bytes, self.min_similarity, filter, limit)
     }
 }
 
@@ -212,6 +208,13 @@ impl From<ClosedDealOutcome> for DealOutcomeStatus {
 pub trait DnaLifecycleStore {
     fn upsert_fingerprint_snapshot(&mut self, snapshot: FingerprintSnapshot) -> Result<(), String>;
     fn upsert_deal_outcome(&mut self, outcome: DealOutcomeMetadata) -> Result<(), String>;
+
+    /// Keeps a similarity index in step with snapshots and outcomes. Stores without one
+    /// can ignore it.
+    fn upsert_similarity_entry(&mut self, entry: SimilarityIndexEntry) -> Result<(), String> {
+        let _ = entry;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Error, PartialEq, Eq)]
@@ -220,6 +223,8 @@ pub enum DnaLifecycleError {
     PersistFingerprintSnapshot { quote_id: String, details: String },
     #[error("failed to persist deal outcome for quote {quote_id}: {details}")]
     PersistDealOutcome { quote_id: String, details: String },
+    #[error("failed to update similarity index for quote {quote_id}: {details}")]
+    PersistSimilarityIndex { quote_id: String, details: String },
 }
 
 #[derive(Clone, Debug, Default)]
@@ -245,6 +250,13 @@ impl DealDnaLifecycleService {
                 details,
             }
         })?;
+        self.index(
+            quote,
+            DealOutcomeStatus::Pending,
+            snapshot.final_price,
+            snapshot.close_date.clone(),
+            store,
+        )?;
 
         Ok(snapshot)
     }
@@ -274,6 +286,13 @@ impl DealDnaLifecycleService {
         store.upsert_deal_outcome(metadata.clone()).map_err(|details| {
             DnaLifecycleError::PersistDealOutcome { quote_id: metadata.quote_id.clone(), details }
         })?;
+        self.index(
+            quote,
+            metadata.outcome_status.clone(),
+            metadata.final_price,
+            metadata.close_date.clone(),
+            store,
+        )?;
 
         Ok(metadata)
     }
@@ -312,6 +331,27 @@ impl DealDnaLifecycleService {
         }
     }
 
+    fn index<S: DnaLifecycleStore>(
+        &self,
+        quote: &Quote,
+        outcome: DealOutcomeStatus,
+        final_price: Decimal,
+        close_date: Option<String>,
+        store: &mut S,
+    ) -> Result<(), DnaLifecycleError> {
+        let entry = SimilarityIndexEntry {
+            quote_id: quote.id.0.clone(),
+            fingerprint: self.generator.generate_from_quote(quote).hash_bytes,
+            outcome,
+            customer_segment: None,
+            final_price,
+            close_date,
+        };
+        store.upsert_similarity_entry(entry).map_err(|details| {
+            DnaLifecycleError::PersistSimilarityIndex { quote_id: quote.id.0.clone(), details }
+        })
+    }
+
     fn snapshot_for(&self, quote: &Quote, close_date: Option<String>) -> FingerprintSnapshot {
         let fingerprint = self.generator.generate_from_quote(quote);
         FingerprintSnapshot {
@@ -396,7 +436,7 @@ fn bytes_to_hex(bytes: &[u8; FINGERPRINT_BYTES]) -> String {
 }
 
 fn is_closed_quote_status(status: &QuoteStatus) -> bool {
-    matches!(status, QuoteStatus::Finalized | QuoteStatus::Sent)
+    matches!(status, QuoteStatus::Finalized | QuoteStatus::Sent | QuoteStatus::Accepted)
 }
 
 fn quote_total(quote: &Quote) -> Decimal {
@@ -424,8 +464,9 @@ mod tests {
 
     use super::{
         configuration_from_lines, ClosedDealOutcome, DealDnaLifecycleService, DealOutcomeMetadata,
-        DealOutcomeStatus, DnaLifecycleStore, FingerprintGenerator, SimilarityCandidate,
-        SimilarityEngine, FINGERPRINT_BITS, FINGERPRINT_BYTES,
+        DealOutcomeStatus, DealSimilarityIndex, DnaLifecycleStore, FingerprintGenerator,
+        SimilarityCandidate, SimilarityEngine, SimilarityFilter, SimilarityIndexEntry,
+        FINGERPRINT_BITS, FINGERPRINT_BYTES,
     };
 
     #[test]
@@ -862,6 +903,53 @@ mod tests {
             || snapshot.quote_id == "Q-2026-BACKFILL-3"));
     }
 
+    #[test]
+    fn lifecycle_keeps_similarity_index_current_across_close_outcome_and_reopen() {
+        let service = DealDnaLifecycleService::default();
+        let mut store = InMemoryLifecycleStore::default();
+        let line = |quantity| QuoteLine {
+            product_id: ProductId("plan-pro".to_owned()),
+            quantity,
+            unit_price: Decimal::new(12_000, 2),
+            discount_pct: 0.0,
+            notes: None,
+        };
+        let closed = quote_with_status("Q-2026-INDEX-1", QuoteStatus::Sent, vec![line(5)]);
+        let fingerprint = FingerprintGenerator::new().generate_from_quote(&closed);
+
+        service.on_quote_closed(&closed, None, &mut store).expect("close");
+        assert_eq!(
+            store.index.get("Q-2026-INDEX-1").map(|entry| entry.outcome.clone()),
+            Some(DealOutcomeStatus::Pending)
+        );
+
+        service
+            .record_closed_deal_outcome(
+                &closed,
+                ClosedDealOutcome::Won,
+                "2026-03-01".to_owned(),
+                &mut store,
+            )
+            .expect("outcome");
+        let won = SimilarityFilter {
+            outcome: Some(DealOutcomeStatus::Won),
+            closed_from: Some("2026-03-01".to_owned()),
+            ..SimilarityFilter::default()
+        };
+        let engine = SimilarityEngine::from_index(store.index.clone());
+        let matches = engine.find_similar_filtered(&fingerprint, &won, 5);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].hamming_distance, 0);
+
+        let reopened = quote_with_status("Q-2026-INDEX-1", QuoteStatus::Revised, vec![line(500)]);
+        service.on_quote_reopened_or_modified(&reopened, &mut store).expect("reopen");
+        assert_eq!(store.index.len(), 1);
+        assert!(SimilarityEngine::from_index(store.index.clone())
+            .with_min_similarity(1.0)
+            .find_similar(&fingerprint, 5)
+            .is_empty());
+    }
+
     fn quote_fixture(lines: Vec<QuoteLine>) -> Quote {
         quote_with_status("Q-2026-2001", QuoteStatus::Draft, lines)
     }
@@ -904,6 +992,7 @@ mod tests {
     struct InMemoryLifecycleStore {
         snapshots: Vec<super::FingerprintSnapshot>,
         outcomes: Vec<DealOutcomeMetadata>,
+        index: DealSimilarityIndex,
     }
 
     impl DnaLifecycleStore for InMemoryLifecycleStore {
@@ -931,5 +1020,10 @@ mod tests {
             }
             Ok(())
         }
+
+        fn upsert_similarity_entry(&mut self, entry: SimilarityIndexEntry) -> Result<(), String> {
+            self.index.upsert(entry);
+            Ok(())
+        }
     }
 }
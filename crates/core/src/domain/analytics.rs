@@ -1,7 +1,14 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub const ANALYTICS_SCHEMA_VERSION: &str = "analytics_contract.v1";
+/// Hard cap on rows a single query may return.
+pub const MAX_ANALYTICS_ROWS: u32 = 10_000;
+/// Rows returned when a spec does not set `limit`.
+pub const DEFAULT_ANALYTICS_ROWS: u32 = 1_000;
+/// Values a single filter may list.
+pub const MAX_FILTER_VALUES: usize = 100;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +22,35 @@ pub enum MetricKind {
     AnomalyRatePct,
 }
 
+impl MetricKind {
+    pub const ALL: [MetricKind; 7] = [
+        Self::QuoteCount,
+        Self::WinRatePct,
+        Self::AvgDiscountPct,
+        Self::AvgDealValue,
+        Self::ApprovalCycleHours,
+        Self::TimeToFinalizeHours,
+        Self::AnomalyRatePct,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::QuoteCount => "quote_count",
+            Self::WinRatePct => "win_rate_pct",
+            Self::AvgDiscountPct => "avg_discount_pct",
+            Self::AvgDealValue => "avg_deal_value",
+            Self::ApprovalCycleHours => "approval_cycle_hours",
+            Self::TimeToFinalizeHours => "time_to_finalize_hours",
+            Self::AnomalyRatePct => "anomaly_rate_pct",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        let label = label.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|metric| metric.as_str() == label)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum DimensionKind {
@@ -30,6 +66,61 @@ pub enum DimensionKind {
     ApprovalRole,
 }
 
+impl DimensionKind {
+    pub const ALL: [DimensionKind; 10] = [
+        Self::Day,
+        Self::Week,
+        Self::Month,
+        Self::Quarter,
+        Self::CustomerSegment,
+        Self::Industry,
+        Self::Region,
+        Self::SalesRep,
+        Self::ProductFamily,
+        Self::ApprovalRole,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Quarter => "quarter",
+            Self::CustomerSegment => "customer_segment",
+            Self::Industry => "industry",
+            Self::Region => "region",
+            Self::SalesRep => "sales_rep",
+            Self::ProductFamily => "product_family",
+            Self::ApprovalRole => "approval_role",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        let label = label.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|dimension| dimension.as_str() == label)
+    }
+}
+
+/// Keeps only rows whose `dimension` value is one of `values`. The dimension does not have to
+/// be grouped on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticsFilter {
+    pub dimension: DimensionKind,
+    pub values: Vec<String>,
+}
+
+impl AnalyticsFilter {
+    pub fn from_label(
+        dimension: &str,
+        values: Vec<String>,
+    ) -> Result<Self, AnalyticsContractError> {
+        let dimension = DimensionKind::parse_label(dimension).ok_or_else(|| {
+            AnalyticsContractError::UnknownDimension(dimension.trim().to_string())
+        })?;
+        Ok(Self { dimension, values })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AnalyticsQuerySpec {
     pub schema_version: String,
@@ -37,9 +128,64 @@ pub struct AnalyticsQuerySpec {
     pub dimensions: Vec<DimensionKind>,
     pub lookback_days: u32,
     pub include_only_finalized: bool,
+    #[serde(default)]
+    pub filters: Vec<AnalyticsFilter>,
+    /// Last creation date (inclusive) counted; the window still starts `lookback_days` ago.
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    /// Row cap, defaulting to [`DEFAULT_ANALYTICS_ROWS`].
+    #[serde(default)]
+    pub limit: Option<u32>,
 }
 
 impl AnalyticsQuerySpec {
+    pub fn new(
+        metrics: Vec<MetricKind>,
+        dimensions: Vec<DimensionKind>,
+        lookback_days: u32,
+    ) -> Self {
+        Self {
+            schema_version: ANALYTICS_SCHEMA_VERSION.to_string(),
+            metrics,
+            dimensions,
+            lookback_days,
+            include_only_finalized: false,
+            filters: Vec::new(),
+            end_date: None,
+            limit: None,
+        }
+    }
+
+    /// Builds a spec from labels such as `win_rate_pct` and `sales_rep`.
+    pub fn from_labels<M: AsRef<str>, D: AsRef<str>>(
+        metrics: &[M],
+        dimensions: &[D],
+        lookback_days: u32,
+    ) -> Result<Self, AnalyticsContractError> {
+        let metrics = metrics
+            .iter()
+            .map(|label| {
+                MetricKind::parse_label(label.as_ref()).ok_or_else(|| {
+                    AnalyticsContractError::UnknownMetric(label.as_ref().trim().to_string())
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let dimensions = dimensions
+            .iter()
+            .map(|label| {
+                DimensionKind::parse_label(label.as_ref()).ok_or_else(|| {
+                    AnalyticsContractError::UnknownDimension(label.as_ref().trim().to_string())
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(metrics, dimensions, lookback_days))
+    }
+
+    /// Effective row cap.
+    pub fn row_limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_ANALYTICS_ROWS)
+    }
+
     pub fn validate(&self) -> Result<(), AnalyticsContractError> {
         if self.schema_version != ANALYTICS_SCHEMA_VERSION {
             return Err(AnalyticsContractError::UnsupportedSchemaVersion {
@@ -56,6 +202,34 @@ impl AnalyticsQuerySpec {
             return Err(AnalyticsContractError::InvalidLookbackDays { value: self.lookback_days });
         }
 
+        if let Some(metric) =
+            self.metrics.iter().enumerate().find(|(i, m)| self.metrics[..*i].contains(m))
+        {
+            return Err(AnalyticsContractError::DuplicateField { field: metric.1.as_str() });
+        }
+        if let Some(dimension) =
+            self.dimensions.iter().enumerate().find(|(i, d)| self.dimensions[..*i].contains(d))
+        {
+            return Err(AnalyticsContractError::DuplicateField { field: dimension.1.as_str() });
+        }
+
+        for filter in &self.filters {
+            if filter.values.is_empty()
+                || filter.values.len() > MAX_FILTER_VALUES
+                || filter.values.iter().any(|value| value.trim().is_empty())
+            {
+                return Err(AnalyticsContractError::InvalidFilter {
+                    dimension: filter.dimension.as_str(),
+                });
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_ANALYTICS_ROWS {
+                return Err(AnalyticsContractError::InvalidLimit { value: limit });
+            }
+        }
+
         Ok(())
     }
 }
@@ -64,16 +238,26 @@ impl AnalyticsQuerySpec {
 pub enum AnalyticsContractError {
     #[error("unsupported schema version: expected `{expected}`, got `{actual}`")]
     UnsupportedSchemaVersion { expected: String, actual: String },
+    #[error("unknown metric `{0}`")]
+    UnknownMetric(String),
+    #[error("unknown dimension `{0}`")]
+    UnknownDimension(String),
     #[error("analytics query must include at least one metric")]
     MissingMetrics,
     #[error("lookback_days must be in range 1..=3650, got {value}")]
     InvalidLookbackDays { value: u32 },
+    #[error("`{field}` is listed more than once")]
+    DuplicateField { field: &'static str },
+    #[error("filter on `{dimension}` needs 1..={MAX_FILTER_VALUES} non-empty values")]
+    InvalidFilter { dimension: &'static str },
+    #[error("limit must be in range 1..={MAX_ANALYTICS_ROWS}, got {value}")]
+    InvalidLimit { value: u32 },
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        AnalyticsContractError, AnalyticsQuerySpec, DimensionKind, MetricKind,
+        AnalyticsContractError, AnalyticsFilter, AnalyticsQuerySpec, DimensionKind, MetricKind,
         ANALYTICS_SCHEMA_VERSION,
     };
 
@@ -84,6 +268,9 @@ mod tests {
             dimensions: vec![DimensionKind::Month, DimensionKind::Region],
             lookback_days: 90,
             include_only_finalized: true,
+            filters: Vec::new(),
+            end_date: None,
+            limit: None,
         }
     }
 
@@ -106,4 +293,58 @@ mod tests {
         spec.lookback_days = 0;
         assert_eq!(spec.validate(), Err(AnalyticsContractError::InvalidLookbackDays { value: 0 }));
     }
+
+    #[test]
+    fn validate_rejects_duplicates_empty_filters_and_bad_limits() {
+        let mut spec = valid_spec();
+        spec.dimensions.push(DimensionKind::Month);
+        assert_eq!(spec.validate(), Err(AnalyticsContractError::DuplicateField { field: "month" }));
+
+        let mut spec = valid_spec();
+        spec.filters.push(AnalyticsFilter { dimension: DimensionKind::Region, values: vec![] });
+        assert_eq!(
+            spec.validate(),
+            Err(AnalyticsContractError::InvalidFilter { dimension: "region" })
+        );
+
+        let mut spec = valid_spec();
+        spec.limit = Some(0);
+        assert_eq!(spec.validate(), Err(AnalyticsContractError::InvalidLimit { value: 0 }));
+        spec.limit = Some(500);
+        assert_eq!(spec.validate(), Ok(()));
+        assert_eq!(spec.row_limit(), 500);
+    }
+
+    #[test]
+    fn labels_round_trip_through_serde_names() {
+        for metric in MetricKind::ALL {
+            assert_eq!(MetricKind::parse_label(metric.as_str()), Some(metric));
+            assert_eq!(serde_json::to_value(metric).unwrap(), metric.as_str());
+        }
+        for dimension in DimensionKind::ALL {
+            assert_eq!(DimensionKind::parse_label(dimension.as_str()), Some(dimension));
+            assert_eq!(serde_json::to_value(dimension).unwrap(), dimension.as_str());
+        }
+        let spec: AnalyticsQuerySpec = serde_json::from_value(serde_json::json!({
+            "schema_version": ANALYTICS_SCHEMA_VERSION,
+            "metrics": ["quote_count"],
+            "dimensions": [],
+            "lookback_days": 30,
+            "include_only_finalized": false,
+        }))
+        .unwrap();
+        assert!(spec.filters.is_empty() && spec.limit.is_none());
+
+        let spec = AnalyticsQuerySpec::from_labels(&["Win_Rate_Pct"], &[" sales_rep"], 30).unwrap();
+        assert_eq!(spec.metrics, vec![MetricKind::WinRatePct]);
+        assert_eq!(spec.dimensions, vec![DimensionKind::SalesRep]);
+        assert_eq!(
+            AnalyticsQuerySpec::from_labels(&["revenue"], &[] as &[&str], 30),
+            Err(AnalyticsContractError::UnknownMetric("revenue".to_string()))
+        );
+        assert_eq!(
+            AnalyticsFilter::from_label("planet", vec!["mars".to_string()]),
+            Err(AnalyticsContractError::UnknownDimension("planet".to_string()))
+        );
+    }
 }
@@ -128,6 +128,192 @@ impl AuthError {
     }
 }
 
+/// Permission granted to an API key, written as `<resource>:<level>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ApiScope {
+    /// Every permission; assigned to legacy config-file keys.
+    All,
+    CatalogRead,
+    /// Catalog maintenance, including confidential product cost records.
+    CatalogAdmin,
+    QuoteRead,
+    QuoteWrite,
+    /// Administrative quote operations such as force-unlocking another actor's lock.
+    QuoteAdmin,
+    ApprovalRead,
+    ApprovalRequest,
+    ApprovalDecide,
+    OrgRead,
+    OrgAdmin,
+    AuditRead,
+    SettingsRead,
+    SettingsAdmin,
+}
+
+impl ApiScope {
+    pub const ALL_SCOPES: [ApiScope; 14] = [
+        Self::All,
+        Self::CatalogRead,
+        Self::CatalogAdmin,
+        Self::QuoteRead,
+        Self::QuoteWrite,
+        Self::QuoteAdmin,
+        Self::ApprovalRead,
+        Self::ApprovalRequest,
+        Self::ApprovalDecide,
+        Self::OrgRead,
+        Self::OrgAdmin,
+        Self::AuditRead,
+        Self::SettingsRead,
+        Self::SettingsAdmin,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "*",
+            Self::CatalogRead => "catalog:read",
+            Self::CatalogAdmin => "catalog:admin",
+            Self::QuoteRead => "quote:read",
+            Self::QuoteWrite => "quote:write",
+            Self::QuoteAdmin => "quote:admin",
+            Self::ApprovalRead => "approval:read",
+            Self::ApprovalRequest => "approval:request",
+            Self::ApprovalDecide => "approval:decide",
+            Self::OrgRead => "org:read",
+            Self::OrgAdmin => "org:admin",
+            Self::AuditRead => "audit:read",
+            Self::SettingsRead => "settings:read",
+            Self::SettingsAdmin => "settings:admin",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        Self::ALL_SCOPES.into_iter().find(|scope| scope.as_str() == label)
+    }
+
+    /// Whether holding `self` is sufficient for an operation requiring `required`.
+    ///
+    /// Higher levels of a resource imply the lower ones (`quote:admin` grants
+    /// `quote:write` and `quote:read`); `*` grants everything.
+    pub fn implies(self, required: ApiScope) -> bool {
+        if self == required || self == Self::All {
+            return true;
+        }
+        matches!(
+            (self, required),
+            (Self::CatalogAdmin, Self::CatalogRead)
+                | (Self::QuoteWrite, Self::QuoteRead)
+                | (Self::QuoteAdmin, Self::QuoteWrite | Self::QuoteRead)
+                | (Self::ApprovalRequest | Self::ApprovalDecide, Self::ApprovalRead)
+                | (Self::OrgAdmin, Self::OrgRead)
+                | (Self::SettingsAdmin, Self::SettingsRead)
+        )
+    }
+}
+
+impl std::fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ApiScope> for String {
+    fn from(scope: ApiScope) -> Self {
+        scope.as_str().to_string()
+    }
+}
+
+impl TryFrom<String> for ApiScope {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse_label(&value).ok_or_else(|| format!("unknown API scope '{value}'"))
+    }
+}
+
+/// What an API key may do: its scopes plus optional account/team restrictions.
+///
+/// Empty `account_ids` / `team_ids` mean the key is not restricted on that axis.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyGrants {
+    pub scopes: Vec<ApiScope>,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    #[serde(default)]
+    pub team_ids: Vec<String>,
+}
+
+impl ApiKeyGrants {
+    /// Full access with no restrictions.
+    pub fn unrestricted() -> Self {
+        Self { scopes: vec![ApiScope::All], account_ids: Vec::new(), team_ids: Vec::new() }
+    }
+
+    pub fn allows_scope(&self, required: ApiScope) -> bool {
+        self.scopes.iter().any(|scope| scope.implies(required))
+    }
+
+    pub fn allows_account(&self, account_id: &str) -> bool {
+        self.account_ids.is_empty() || self.account_ids.iter().any(|id| id == account_id)
+    }
+
+    pub fn allows_team(&self, team_id: &str) -> bool {
+        self.team_ids.is_empty() || self.team_ids.iter().any(|id| id == team_id)
+    }
+
+    pub fn is_account_restricted(&self) -> bool {
+        !self.account_ids.is_empty()
+    }
+
+    pub fn is_team_restricted(&self) -> bool {
+        !self.team_ids.is_empty()
+    }
+}
+
+/// Persisted API key. Only the SHA-256 hash of the secret is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyRecord {
+    pub id: String,
+    pub name: String,
+    pub key_hash: String,
+    /// First characters of the secret, kept so operators can recognise a key.
+    pub key_prefix: String,
+    pub grants: ApiKeyGrants,
+    pub requests_per_minute: u32,
+    pub active: bool,
+    /// Key this one replaced through rotation.
+    pub rotated_from: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Number of leading secret characters kept in [`ApiKeyRecord::key_prefix`].
+pub const API_KEY_PREFIX_LEN: usize = 8;
+
+/// Generate a new cryptographically secure API key secret (32 alphanumerics).
+pub fn generate_api_key_secret() -> String {
+    use rand::rngs::OsRng;
+    use rand::RngCore;
+
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    const KEY_LEN: usize = 32;
+
+    let mut bytes = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| CHARSET[*b as usize % CHARSET.len()] as char).collect()
+}
+
+/// Hash an API key secret for storage and lookup.
+pub fn hash_api_key(secret: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(secret.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +356,49 @@ mod tests {
         assert_eq!(err.retry_after_seconds, Some(1));
         assert!(err.is_retryable());
     }
+    #[test]
+    fn api_scopes_round_trip_and_reject_unknown_labels() {
+        for scope in ApiScope::ALL_SCOPES {
+            assert_eq!(ApiScope::parse_label(scope.as_str()), Some(scope));
+        }
+        assert_eq!(ApiScope::parse_label("quote:delete"), None);
+        let json = serde_json::to_string(&ApiScope::ApprovalDecide).expect("serialize");
+        assert_eq!(json, "\"approval:decide\"");
+    }
+
+    #[test]
+    fn higher_scope_levels_imply_lower_ones() {
+        assert!(ApiScope::QuoteAdmin.implies(ApiScope::QuoteRead));
+        assert!(ApiScope::QuoteWrite.implies(ApiScope::QuoteRead));
+        assert!(!ApiScope::QuoteRead.implies(ApiScope::QuoteWrite));
+        assert!(!ApiScope::QuoteWrite.implies(ApiScope::QuoteAdmin));
+        assert!(!ApiScope::ApprovalRequest.implies(ApiScope::ApprovalDecide));
+        assert!(ApiScope::All.implies(ApiScope::SettingsAdmin));
+    }
+
+    #[test]
+    fn grants_restrict_accounts_and_teams_only_when_listed() {
+        let open = ApiKeyGrants::unrestricted();
+        assert!(open.allows_account("acct-1"));
+        assert!(open.allows_team("team-a"));
+
+        let scoped = ApiKeyGrants {
+            scopes: vec![ApiScope::QuoteRead],
+            account_ids: vec!["acct-1".to_string()],
+            team_ids: vec!["team-a".to_string()],
+        };
+        assert!(scoped.allows_scope(ApiScope::QuoteRead));
+        assert!(!scoped.allows_scope(ApiScope::SettingsAdmin));
+        assert!(scoped.allows_account("acct-1"));
+        assert!(!scoped.allows_account("acct-2"));
+        assert!(!scoped.allows_team("team-b"));
+    }
+
+    #[test]
+    fn api_key_hash_is_stable_hex_and_ignores_surrounding_whitespace() {
+        let hash = hash_api_key("secret-key");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key("  secret-key\n"));
+        assert_ne!(hash, hash_api_key("secret-key-2"));
+    }
 }
@@ -10,6 +10,7 @@ pub mod explanation;
 pub mod integration;
 pub mod negotiation;
 pub mod optimizer;
+pub mod order_form;
 pub mod org_settings;
 pub mod outbox;
 pub mod precedent;
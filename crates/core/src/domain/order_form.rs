@@ -0,0 +1,453 @@
+//! Order forms: the contract document generated from an accepted quote.
+//!
+//! An order form freezes the quote's lines, pricing and terms when it is generated, adds a
+//! billing schedule, signatory blocks and the selected clauses, and then follows its own
+//! lifecycle ([`OrderFormStatus`]). Amendments are new order forms that reference the order
+//! they amend.
+
+use std::fmt;
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::pdf::template::ResolvedDocument;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderFormId(pub String);
+
+impl fmt::Display for OrderFormId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderFormStatus {
+    Draft,
+    Sent,
+    /// Signed by the customer.
+    Signed,
+    /// Signed by both parties; the order is in effect.
+    Countersigned,
+}
+
+impl OrderFormStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Sent => "sent",
+            Self::Signed => "signed",
+            Self::Countersigned => "countersigned",
+        }
+    }
+
+    pub fn parse_label(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "sent" => Some(Self::Sent),
+            "signed" => Some(Self::Signed),
+            "countersigned" => Some(Self::Countersigned),
+            _ => None,
+        }
+    }
+
+    /// Order forms only move forward, one step at a time.
+    pub fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Draft, Self::Sent)
+                | (Self::Sent, Self::Signed)
+                | (Self::Signed, Self::Countersigned)
+        )
+    }
+
+    /// The signatory party whose signature the transition into this status records.
+    pub fn signing_party(self) -> Option<SignatoryParty> {
+        match self {
+            Self::Signed => Some(SignatoryParty::Customer),
+            Self::Countersigned => Some(SignatoryParty::Provider),
+            Self::Draft | Self::Sent => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingFrequency {
+    Upfront,
+    #[default]
+    Annual,
+    Quarterly,
+    Monthly,
+}
+
+impl BillingFrequency {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Upfront => "upfront",
+            Self::Annual => "annual",
+            Self::Quarterly => "quarterly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse_label(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "upfront" | "one_time" => Some(Self::Upfront),
+            "annual" | "annually" | "yearly" => Some(Self::Annual),
+            "quarterly" => Some(Self::Quarterly),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Upfront => "Upfront",
+            Self::Annual => "Annual",
+            Self::Quarterly => "Quarterly",
+            Self::Monthly => "Monthly",
+        }
+    }
+
+    /// Months covered by one invoice; `None` when the whole term is billed at once.
+    fn period_months(self) -> Option<u32> {
+        match self {
+            Self::Upfront => None,
+            Self::Annual => Some(12),
+            Self::Quarterly => Some(3),
+            Self::Monthly => Some(1),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BillingInstallment {
+    pub sequence: u32,
+    pub description: String,
+    pub due_date: NaiveDate,
+    pub amount: Decimal,
+}
+
+/// Splits `total` into invoices over `term_months` starting on `start`.
+///
+/// Installments are rounded to cents and the last one absorbs the rounding difference, so
+/// they always add up to `total`. A final partial period is billed as a full installment.
+pub fn billing_schedule(
+    total: Decimal,
+    term_months: u32,
+    frequency: BillingFrequency,
+    start: NaiveDate,
+) -> Vec<BillingInstallment> {
+    let (count, period) = match frequency.period_months() {
+        Some(period) if term_months > period => (term_months.div_ceil(period), period),
+        _ => (1, term_months),
+    };
+    let installment = (total / Decimal::from(count)).round_dp(2);
+    (0..count)
+        .map(|index| {
+            let sequence = index + 1;
+            let amount = if sequence == count {
+                total - installment * Decimal::from(count - 1)
+            } else {
+                installment
+            };
+            BillingInstallment {
+                sequence,
+                description: match count {
+                    1 => format!("{} invoice", frequency.label()),
+                    _ => format!("{} invoice {sequence} of {count}", frequency.label()),
+                },
+                due_date: start.checked_add_months(Months::new(index * period)).unwrap_or(start),
+                amount,
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatoryParty {
+    Customer,
+    Provider,
+}
+
+impl SignatoryParty {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Customer => "customer",
+            Self::Provider => "provider",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signatory {
+    pub party: SignatoryParty,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OrderFormError {
+    #[error("an order form needs at least one line item")]
+    NoLines,
+    #[error("term must be at least one month")]
+    InvalidTerm,
+    #[error("signatory names must not be blank")]
+    BlankSignatory,
+    #[error("only one {} signatory is allowed", .0.as_str())]
+    DuplicateSignatory(SignatoryParty),
+    #[error("a {} signatory is required", .0.as_str())]
+    MissingSignatory(SignatoryParty),
+    #[error("order form cannot move from `{}` to `{}`", .from.as_str(), .to.as_str())]
+    InvalidTransition { from: OrderFormStatus, to: OrderFormStatus },
+}
+
+/// Checks the signatory blocks: named, at most one per party.
+pub fn validate_signatories(signatories: &[Signatory]) -> Result<(), OrderFormError> {
+    for (index, signatory) in signatories.iter().enumerate() {
+        if signatory.name.trim().is_empty() {
+            return Err(OrderFormError::BlankSignatory);
+        }
+        if signatories[..index].iter().any(|other| other.party == signatory.party) {
+            return Err(OrderFormError::DuplicateSignatory(signatory.party));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderLine {
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    pub name: String,
+    pub quantity: u32,
+    pub unit_price: Decimal,
+    pub discount_pct: Decimal,
+    pub subtotal: Decimal,
+    pub total: Decimal,
+}
+
+/// Everything an order form states, captured from the quote when it is generated. It does not
+/// change after the order form leaves draft.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderFormContent {
+    pub quote_id: String,
+    pub quote_version: u32,
+    pub account_id: Option<String>,
+    pub currency: String,
+    pub lines: Vec<OrderLine>,
+    pub subtotal: Decimal,
+    pub discount_total: Decimal,
+    pub total: Decimal,
+    pub payment_terms: String,
+    pub billing_frequency: BillingFrequency,
+    pub term_months: u32,
+    pub start_date: NaiveDate,
+    pub billing_schedule: Vec<BillingInstallment>,
+    /// The template sections and selected clauses, with clause text inlined.
+    pub document: ResolvedDocument,
+}
+
+impl OrderFormContent {
+    pub fn validate(&self) -> Result<(), OrderFormError> {
+        if self.lines.is_empty() {
+            return Err(OrderFormError::NoLines);
+        }
+        if self.term_months == 0 {
+            return Err(OrderFormError::InvalidTerm);
+        }
+        Ok(())
+    }
+
+    /// The payload the quote PDF layouts render the order form from.
+    pub fn render_payload(
+        &self,
+        order_id: &OrderFormId,
+        status: OrderFormStatus,
+        signatories: &[Signatory],
+        company_name: &str,
+    ) -> Value {
+        let lines: Vec<Value> = self
+            .lines
+            .iter()
+            .map(|line| {
+                json!({
+                    "product_id": line.product_id,
+                    "product_name": line.name,
+                    "product_sku": line.sku,
+                    "quantity": line.quantity,
+                    "unit_price": line.unit_price.to_string(),
+                    "subtotal": line.subtotal.to_string(),
+                    "discount_pct": line.discount_pct.to_string(),
+                    "total_price": line.total.to_string(),
+                })
+            })
+            .collect();
+        let mut payload = json!({
+            "id": order_id.0,
+            "status": status.as_str(),
+            "quote_id": self.quote_id,
+            "version": self.quote_version,
+            "currency": self.currency,
+            "account": { "id": self.account_id, "name": self.account_id },
+            "lines": lines,
+            "pricing": {
+                "subtotal": self.subtotal.to_string(),
+                "discount_total": self.discount_total.to_string(),
+                "total": self.total.to_string(),
+            },
+            "payment_terms": self.payment_terms,
+            "billing_frequency": self.billing_frequency.label(),
+            "term_months": self.term_months,
+            "start_date": self.start_date.to_string(),
+            "billing_schedule": self.billing_schedule,
+            "signatories": signatories,
+            "company_name": company_name,
+        });
+        self.document.insert_into(&mut payload);
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn date(raw: &str) -> NaiveDate {
+        NaiveDate::from_str(raw).expect("date")
+    }
+
+    #[test]
+    fn billing_schedule_splits_the_total_and_absorbs_rounding_in_the_last_invoice() {
+        let total = Decimal::from_str("1000.00").expect("decimal");
+        let schedule = billing_schedule(total, 12, BillingFrequency::Quarterly, date("2026-01-31"));
+        let due: Vec<String> = schedule.iter().map(|item| item.due_date.to_string()).collect();
+        assert_eq!(due, ["2026-01-31", "2026-04-30", "2026-07-31", "2026-10-31"]);
+        assert_eq!(schedule[0].amount, Decimal::from_str("250.00").expect("decimal"));
+
+        let total = Decimal::from_str("100.00").expect("decimal");
+        let schedule = billing_schedule(total, 3, BillingFrequency::Monthly, date("2026-01-01"));
+        let amounts: Vec<String> = schedule.iter().map(|item| item.amount.to_string()).collect();
+        assert_eq!(amounts, ["33.33", "33.33", "33.34"]);
+        assert_eq!(schedule[2].description, "Monthly invoice 3 of 3");
+
+        let partial = billing_schedule(total, 18, BillingFrequency::Annual, date("2026-01-01"));
+        assert_eq!(partial.len(), 2);
+        let upfront = billing_schedule(total, 36, BillingFrequency::Upfront, date("2026-01-01"));
+        assert_eq!((upfront.len(), upfront[0].amount), (1, total));
+        assert_eq!(upfront[0].description, "Upfront invoice");
+    }
+
+    #[test]
+    fn lifecycle_moves_forward_one_step_at_a_time() {
+        use OrderFormStatus::*;
+        assert!(Draft.can_transition_to(Sent));
+        assert!(Sent.can_transition_to(Signed));
+        assert!(Signed.can_transition_to(Countersigned));
+        assert!(!Draft.can_transition_to(Signed));
+        assert!(!Countersigned.can_transition_to(Draft));
+        assert!(!Signed.can_transition_to(Sent));
+        assert_eq!(Signed.signing_party(), Some(SignatoryParty::Customer));
+        assert_eq!(Countersigned.signing_party(), Some(SignatoryParty::Provider));
+        for status in [Draft, Sent, Signed, Countersigned] {
+            assert_eq!(OrderFormStatus::parse_label(status.as_str()), Some(status));
+        }
+    }
+
+    #[test]
+    fn order_forms_render_through_the_quote_layouts() {
+        use crate::pdf::template::{QuoteDocumentTemplate, SectionContent, TemplateSection};
+        use crate::pdf::{render_quote_pdf, PdfBranding, PdfTemplate};
+
+        let mut template = QuoteDocumentTemplate::builtin(PdfTemplate::Detailed);
+        template.sections.push(TemplateSection::always(SectionContent::BillingSchedule));
+        template.sections.push(TemplateSection::always(SectionContent::Signatures));
+        let mut document =
+            template.resolve("detailed", None, &Default::default(), &[]).expect("resolve");
+        document.title = Some("Order Form".to_owned());
+        let total = Decimal::from(24_000);
+        let content = OrderFormContent {
+            quote_id: "Q-1".to_owned(),
+            quote_version: 2,
+            account_id: Some("acct-1".to_owned()),
+            currency: "USD".to_owned(),
+            lines: vec![OrderLine {
+                product_id: "plan-pro".to_owned(),
+                sku: Some("PRO".to_owned()),
+                name: "Pro plan".to_owned(),
+                quantity: 10,
+                unit_price: Decimal::from(2_400),
+                discount_pct: Decimal::ZERO,
+                subtotal: total,
+                total,
+            }],
+            subtotal: total,
+            discount_total: Decimal::ZERO,
+            total,
+            payment_terms: "net_30".to_owned(),
+            billing_frequency: BillingFrequency::Monthly,
+            term_months: 24,
+            start_date: date("2026-01-01"),
+            billing_schedule: billing_schedule(
+                total,
+                24,
+                BillingFrequency::Monthly,
+                date("2026-01-01"),
+            ),
+            document,
+        };
+        assert_eq!(content.validate(), Ok(()));
+        let signatories = [Signatory {
+            party: SignatoryParty::Customer,
+            name: "Dana Reyes".to_owned(),
+            title: Some("VP Procurement".to_owned()),
+            email: None,
+            signed_at: None,
+        }];
+        let id = OrderFormId("ORD-1".to_owned());
+        let branding = PdfBranding::default();
+        let render = |signatories: &[Signatory]| {
+            let payload = content.render_payload(&id, OrderFormStatus::Draft, signatories, "Acme");
+            render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render")
+        };
+        let signed = render(&signatories);
+        assert_eq!(signed, render(&signatories), "rendering is deterministic");
+        assert_ne!(signed, render(&[]), "signature blocks are printed");
+    }
+
+    #[test]
+    fn signatories_need_names_and_one_block_per_party() {
+        let signatory = |party, name: &str| Signatory {
+            party,
+            name: name.to_owned(),
+            title: None,
+            email: None,
+            signed_at: None,
+        };
+        let customer = signatory(SignatoryParty::Customer, "Dana Reyes");
+        let provider = signatory(SignatoryParty::Provider, "Sam Ortiz");
+        assert_eq!(validate_signatories(&[customer.clone(), provider]), Ok(()));
+        assert_eq!(
+            validate_signatories(&[customer.clone(), customer]),
+            Err(OrderFormError::DuplicateSignatory(SignatoryParty::Customer))
+        );
+        assert_eq!(
+            validate_signatories(&[signatory(SignatoryParty::Provider, " ")]),
+            Err(OrderFormError::BlankSignatory)
+        );
+    }
+}
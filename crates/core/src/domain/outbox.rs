@@ -63,6 +63,13 @@ pub enum OutboxOperation {
         body_html: Option<String>,
         attachment_path: Option<String>,
     },
+    /// One-time code for a customer e-signature request. Only the request is referenced here:
+    /// the code is minted and rendered into the email when the task is delivered.
+    SignatureCodeSend {
+        signature_request_id: String,
+        to: Vec<String>,
+        subject: String,
+    },
 
     // Custom operations
     WebhookCall {
@@ -84,6 +91,7 @@ impl OutboxOperation {
             Self::CrmCreateDeal { .. } => "crm.create_deal",
             Self::PdfGenerate { .. } => "pdf.generate",
             Self::EmailSend { .. } => "email.send",
+            Self::SignatureCodeSend { .. } => "email.signature_code",
             Self::WebhookCall { .. } => "webhook.call",
         }
     }
@@ -119,7 +127,7 @@ impl OutboxOperation {
             Self::PdfGenerate { .. } => RetryPolicy::default(),
 
             // Email: standard retry
-            Self::EmailSend { .. } => RetryPolicy::default(),
+            Self::EmailSend { .. } | Self::SignatureCodeSend { .. } => RetryPolicy::default(),
 
             // Webhook: longer backoff (external dependency)
             Self::WebhookCall { .. } => RetryPolicy {
@@ -310,12 +318,12 @@ mod tests {
         // First retry: ~30s
         let t1 = policy.next_retry_at(0).unwrap();
         let diff1 = (t1 - Utc::now()).num_seconds();
-        assert!(diff1 >= 24 && diff1 <= 36, "First retry should be ~30s");
+        assert!((24..=36).contains(&diff1), "First retry should be ~30s");
 
         // Second retry: ~60s
         let t2 = policy.next_retry_at(1).unwrap();
         let diff2 = (t2 - Utc::now()).num_seconds();
-        assert!(diff2 >= 48 && diff2 <= 72, "Second retry should be ~60s");
+        assert!((48..=72).contains(&diff2), "Second retry should be ~60s");
     }
 
     #[test]
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -82,6 +82,74 @@ pub struct ProductAttribute {
     pub default_value: Option<String>,
 }
 
+impl AttributeValueType {
+    /// Checks the schema itself is usable: bounds in order, enums with distinct values.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Integer { min: Some(min), max: Some(max) } if min > max => {
+                Err(format!("integer min {min} is greater than max {max}"))
+            }
+            Self::Decimal { min: Some(min), max: Some(max) } if min > max => {
+                Err(format!("decimal min {min} is greater than max {max}"))
+            }
+            Self::Enum { allowed_values } => {
+                if allowed_values.is_empty() {
+                    return Err("enum needs at least one allowed value".to_string());
+                }
+                if allowed_values.iter().any(|value| value.trim().is_empty()) {
+                    return Err("enum allowed values must not be blank".to_string());
+                }
+                let mut seen = std::collections::BTreeSet::new();
+                match allowed_values.iter().find(|value| !seen.insert(value.as_str())) {
+                    Some(value) => Err(format!("enum allowed value '{value}' is repeated")),
+                    None => Ok(()),
+                }
+            }
+            Self::Text { max_length: Some(0) } => {
+                Err("text max_length must be at least 1".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `value` is a valid value of this type.
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            Self::Integer { min, max } => value.trim().parse::<i64>().is_ok_and(|number| {
+                min.map_or(true, |min| number >= min) && max.map_or(true, |max| number <= max)
+            }),
+            Self::Decimal { min, max } => value.trim().parse::<Decimal>().is_ok_and(|number| {
+                min.map_or(true, |min| number >= min) && max.map_or(true, |max| number <= max)
+            }),
+            Self::Enum { allowed_values } => allowed_values.iter().any(|allowed| allowed == value),
+            Self::Boolean => matches!(value, "true" | "false"),
+            Self::Text { max_length } => {
+                max_length.map_or(true, |max| value.chars().count() <= max as usize)
+            }
+        }
+    }
+}
+
+impl ProductAttribute {
+    /// Checks the key, the value type schema and that the default fits the type.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.key.trim().is_empty() || self.key.chars().any(char::is_whitespace) {
+            return Err(format!("attribute key '{}' must be non-empty without spaces", self.key));
+        }
+        if self.display_name.trim().is_empty() {
+            return Err(format!("attribute '{}' needs a display_name", self.key));
+        }
+        self.value_type.validate().map_err(|error| format!("attribute '{}': {error}", self.key))?;
+        match &self.default_value {
+            Some(default) if !self.value_type.accepts(default) => Err(format!(
+                "attribute '{}' default '{default}' does not fit its value type",
+                self.key
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Product (enriched)
 // ---------------------------------------------------------------------------
@@ -123,6 +191,80 @@ impl Product {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Product cost — what one unit costs to deliver, for margin computation
+// ---------------------------------------------------------------------------
+
+/// One named part of a unit cost (hosting, support, licence fees, ...).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostComponent {
+    pub name: String,
+    pub unit_cost: Decimal,
+}
+
+/// Unit cost of a product in one currency over an effective window.
+///
+/// `effective_until` is exclusive. When several records cover the same day, the one with the
+/// latest `effective_from` applies, so a new record supersedes older ones from its start date.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductCost {
+    pub id: String,
+    pub product_id: ProductId,
+    pub unit_cost: Decimal,
+    pub currency: String,
+    pub effective_from: NaiveDate,
+    pub effective_until: Option<NaiveDate>,
+    /// Optional COGS breakdown; when present it sums to `unit_cost`.
+    #[serde(default)]
+    pub components: Vec<CostComponent>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ProductCost {
+    pub fn is_effective_on(&self, date: NaiveDate) -> bool {
+        self.effective_from <= date && self.effective_until.map_or(true, |until| date < until)
+    }
+
+    /// Checks the record is internally consistent before it is stored.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.unit_cost < Decimal::ZERO {
+            return Err("unit_cost must be >= 0".to_string());
+        }
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(format!("currency '{}' must be a 3-letter ISO code", self.currency));
+        }
+        if let Some(until) = self.effective_until {
+            if until <= self.effective_from {
+                return Err(format!(
+                    "effective_until {until} must be after effective_from {}",
+                    self.effective_from
+                ));
+            }
+        }
+        if self.components.is_empty() {
+            return Ok(());
+        }
+        if let Some(component) = self.components.iter().find(|component| {
+            component.name.trim().is_empty() || component.unit_cost < Decimal::ZERO
+        }) {
+            return Err(format!(
+                "cost component '{}' needs a name and a unit_cost >= 0",
+                component.name
+            ));
+        }
+        let components_total: Decimal =
+            self.components.iter().map(|component| component.unit_cost).sum();
+        if components_total != self.unit_cost {
+            return Err(format!(
+                "cost components sum to {components_total}, not unit_cost {}",
+                self.unit_cost
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +285,58 @@ mod tests {
         assert!(p.attributes.is_empty());
         assert_eq!(p.currency, "USD");
     }
+
+    #[test]
+    fn product_cost_validation_and_effective_window() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").expect("date");
+        let mut cost = ProductCost {
+            id: "cost-1".to_string(),
+            product_id: ProductId("prod-1".to_string()),
+            unit_cost: Decimal::new(4000, 2),
+            currency: "USD".to_string(),
+            effective_from: date("2026-01-01"),
+            effective_until: Some(date("2026-07-01")),
+            components: vec![
+                CostComponent { name: "hosting".to_string(), unit_cost: Decimal::new(2500, 2) },
+                CostComponent { name: "support".to_string(), unit_cost: Decimal::new(1500, 2) },
+            ],
+            created_by: "test".to_string(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(cost.validate(), Ok(()));
+        assert!(cost.is_effective_on(date("2026-01-01")));
+        assert!(!cost.is_effective_on(date("2026-07-01")));
+
+        cost.components[1].unit_cost = Decimal::new(1000, 2);
+        assert!(cost.validate().expect_err("mismatched components").contains("sum to 35.00"));
+        cost.components.clear();
+        cost.effective_until = Some(date("2025-12-31"));
+        assert!(cost.validate().is_err());
+    }
+
+    #[test]
+    fn attribute_schemas_and_defaults_are_validated() {
+        let mut attr = ProductAttribute {
+            key: "seats".to_string(),
+            display_name: "Seats".to_string(),
+            value_type: AttributeValueType::Integer { min: Some(1), max: Some(1000) },
+            required: true,
+            default_value: Some("10".to_string()),
+        };
+        assert_eq!(attr.validate(), Ok(()));
+        attr.default_value = Some("0".to_string());
+        assert!(attr.validate().expect_err("below min").contains("default '0'"));
+        attr.value_type = AttributeValueType::Integer { min: Some(5), max: Some(1) };
+        assert!(attr.validate().expect_err("inverted bounds").contains("greater than max"));
+
+        let tier = AttributeValueType::Enum {
+            allowed_values: vec!["gold".to_string(), "silver".to_string(), "gold".to_string()],
+        };
+        assert!(tier.validate().expect_err("repeated").contains("'gold' is repeated"));
+        assert!(tier.accepts("silver"));
+        assert!(!tier.accepts("bronze"));
+        assert!(AttributeValueType::Boolean.accepts("false"));
+        assert!(!AttributeValueType::Text { max_length: Some(3) }.accepts("long"));
+        assert!(AttributeValueType::Decimal { min: None, max: Some(Decimal::ONE) }.accepts("0.5"));
+    }
 }
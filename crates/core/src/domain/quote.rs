@@ -21,11 +21,27 @@ pub enum QuoteStatus {
     Rejected,
     Finalized,
     Sent,
+    /// Signed by the customer; the deal is closed-won.
+    Accepted,
     Expired,
     Cancelled,
     Revised,
 }
 
+impl QuoteStatus {
+    /// Whether the quote can still take a new revision. Signed, rejected, cancelled and expired
+    /// quotes are closed; changing them needs a new quote.
+    pub fn is_editable(&self) -> bool {
+        !matches!(
+            self,
+            QuoteStatus::Accepted
+                | QuoteStatus::Rejected
+                | QuoteStatus::Cancelled
+                | QuoteStatus::Expired
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct QuoteLine {
     pub product_id: ProductId,
@@ -68,6 +84,9 @@ impl Quote {
                 | (QuoteStatus::Approval, QuoteStatus::Rejected)
                 | (QuoteStatus::Approved, QuoteStatus::Finalized)
                 | (QuoteStatus::Finalized, QuoteStatus::Sent)
+                | (QuoteStatus::Approved, QuoteStatus::Accepted)
+                | (QuoteStatus::Finalized, QuoteStatus::Accepted)
+                | (QuoteStatus::Sent, QuoteStatus::Accepted)
                 | (QuoteStatus::Revised, QuoteStatus::Validated)
                 | (_, QuoteStatus::Cancelled)
                 | (_, QuoteStatus::Expired)
@@ -135,6 +154,14 @@ mod tests {
         assert!(matches!(error, crate::errors::DomainError::InvalidQuoteTransition { .. }));
     }
 
+    #[test]
+    fn only_customer_facing_quotes_can_be_accepted() {
+        let mut sent = quote(QuoteStatus::Sent);
+        sent.transition_to(QuoteStatus::Accepted).expect("sent -> accepted");
+        assert!(!quote(QuoteStatus::Draft).can_transition_to(QuoteStatus::Accepted));
+        assert!(!quote(QuoteStatus::Rejected).can_transition_to(QuoteStatus::Accepted));
+    }
+
     #[test]
     fn revised_quotes_can_reenter_validation() {
         let mut quote = quote(QuoteStatus::Rejected);
@@ -143,4 +170,19 @@ mod tests {
 
         assert_eq!(quote.status, QuoteStatus::Validated);
     }
+
+    #[test]
+    fn closed_quotes_are_not_editable() {
+        for status in [QuoteStatus::Draft, QuoteStatus::Approved, QuoteStatus::Sent] {
+            assert!(status.is_editable(), "{status:?} should be editable");
+        }
+        for status in [
+            QuoteStatus::Accepted,
+            QuoteStatus::Rejected,
+            QuoteStatus::Cancelled,
+            QuoteStatus::Expired,
+        ] {
+            assert!(!status.is_editable(), "{status:?} should be closed");
+        }
+    }
 }
@@ -110,6 +110,9 @@ pub enum VisualActionType {
     ExcludeProduct,
     RouteApprovalRole,
     SetApprovalThreshold,
+    RequireMinimumDealSize,
+    RestrictPaymentTerms,
+    FlagNonStandardTerms,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -0,0 +1,240 @@
+//! Customer e-signatures for quote acceptance.
+//!
+//! A signer confirms the name, title and email they enter with a short-lived one-time code sent
+//! to that email, then signs by typing their name or drawing on a pad. Drawn signatures are kept
+//! as normalised vector strokes so the certificate page of the signed PDF can redraw them exactly
+//! (see [`crate::pdf::render_signed_quote_pdf`]). Only a salted hash of the code is ever stored.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+pub const CODE_LENGTH: usize = 6;
+pub const CODE_TTL_MINUTES: i64 = 10;
+/// Wrong codes accepted before a signature request is locked. The count carries over to the
+/// requests that supersede it within [`START_WINDOW_MINUTES`].
+pub const MAX_CODE_ATTEMPTS: u32 = 5;
+/// Signature requests one quote, or one signer email, may open per [`START_WINDOW_MINUTES`].
+pub const MAX_STARTS_PER_WINDOW: i64 = 5;
+pub const START_WINDOW_MINUTES: i64 = 60;
+/// Statement the signer agrees to; printed on the certificate page.
+pub const CONSENT_STATEMENT: &str = "I agree that my electronic signature is the legal \
+     equivalent of my handwritten signature, and I accept this quote and its terms.";
+
+const MAX_FIELD_CHARS: usize = 120;
+const MAX_STROKES: usize = 64;
+const MAX_POINTS: usize = 4096;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("invalid signer {field}: {reason}")]
+    InvalidSigner { field: &'static str, reason: String },
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// Who is signing, as confirmed by the one-time code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerIdentity {
+    pub name: String,
+    pub title: String,
+    pub email: String,
+}
+
+impl SignerIdentity {
+    /// Trims every field and lowercases the email.
+    pub fn new(name: &str, title: &str, email: &str) -> Result<Self, SignatureError> {
+        let field = |field: &'static str, value: &str| {
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(SignatureError::InvalidSigner { field, reason: "required".to_owned() });
+            }
+            if value.chars().count() > MAX_FIELD_CHARS || value.chars().any(char::is_control) {
+                return Err(SignatureError::InvalidSigner {
+                    field,
+                    reason: format!("must be at most {MAX_FIELD_CHARS} printable characters"),
+                });
+            }
+            Ok(value.to_owned())
+        };
+        let email = field("email", email)?.to_ascii_lowercase();
+        let valid_email = email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+        });
+        if !valid_email {
+            return Err(SignatureError::InvalidSigner {
+                field: "email",
+                reason: "not an email address".to_owned(),
+            });
+        }
+        Ok(Self { name: field("name", name)?, title: field("title", title)?, email })
+    }
+}
+
+/// The signature mark itself.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignatureCapture {
+    /// Name typed by the signer.
+    Typed { text: String },
+    /// Pen strokes from a signature pad, each a polyline of `[x, y]` points normalised to the
+    /// pad with the origin at its top-left corner and both axes in `0.0..=1.0`.
+    Drawn { strokes: Vec<Vec<[f32; 2]>> },
+}
+
+impl SignatureCapture {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Typed { .. } => "typed",
+            Self::Drawn { .. } => "drawn",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), SignatureError> {
+        match self {
+            Self::Typed { text } => {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err(SignatureError::InvalidSignature(
+                        "typed signature is empty".into(),
+                    ));
+                }
+                if text.chars().count() > MAX_FIELD_CHARS || text.chars().any(char::is_control) {
+                    return Err(SignatureError::InvalidSignature(format!(
+                        "typed signature must be at most {MAX_FIELD_CHARS} printable characters"
+                    )));
+                }
+            }
+            Self::Drawn { strokes } => {
+                let points: usize = strokes.iter().map(Vec::len).sum();
+                if points == 0 {
+                    return Err(SignatureError::InvalidSignature(
+                        "drawn signature is empty".into(),
+                    ));
+                }
+                if strokes.len() > MAX_STROKES || points > MAX_POINTS {
+                    return Err(SignatureError::InvalidSignature(format!(
+                        "drawn signature exceeds {MAX_STROKES} strokes or {MAX_POINTS} points"
+                    )));
+                }
+                let in_range = |value: f32| value.is_finite() && (0.0..=1.0).contains(&value);
+                if !strokes.iter().flatten().all(|[x, y]| in_range(*x) && in_range(*y)) {
+                    return Err(SignatureError::InvalidSignature(
+                        "drawn points must be normalised to 0..1".into(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where and when the signer requested the code and signed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningEvidence {
+    pub code_sent_at: DateTime<Utc>,
+    pub requested_ip: String,
+    pub requested_user_agent: String,
+    pub signed_at: DateTime<Utc>,
+    pub signed_ip: String,
+    pub signed_user_agent: String,
+}
+
+/// Everything printed on the certificate page of a signed quote.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AcceptanceCertificate {
+    pub request_id: String,
+    pub quote_id: String,
+    pub quote_version: u32,
+    /// SHA-256 of the unsigned quote PDF the signer reviewed.
+    pub document_sha256: String,
+    pub signer: SignerIdentity,
+    pub signature: SignatureCapture,
+    pub evidence: SigningEvidence,
+}
+
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect()
+}
+
+/// Hash stored in place of the code, salted with the signature request id.
+pub fn hash_code(request_id: &str, code: &str) -> String {
+    document_sha256(format!("quotey:esign:v1|{request_id}|{}", code.trim()).as_bytes())
+}
+
+/// Lowercase hex SHA-256, as recorded for unsigned and signed quote documents.
+pub fn document_sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Compares in constant time so response timing doesn't leak how much of the code matched.
+pub fn code_matches(request_id: &str, code: &str, expected_hash: &str) -> bool {
+    let actual = hash_code(request_id, code);
+    actual.len() == expected_hash.len()
+        && actual.bytes().zip(expected_hash.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// `d***@example.com`, for telling the signer where the code went.
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first = local.chars().next().map(String::from).unwrap_or_default();
+            format!("{first}***@{domain}")
+        }
+        None => "***".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signer_fields_are_trimmed_and_validated() {
+        let signer = SignerIdentity::new(" Dana Reyes ", "VP Procurement", " Dana@Example.COM ")
+            .expect("valid signer");
+        assert_eq!(signer.name, "Dana Reyes");
+        assert_eq!(signer.email, "dana@example.com");
+
+        let missing = SignerIdentity::new("Dana", " ", "dana@example.com").unwrap_err();
+        assert!(matches!(missing, SignatureError::InvalidSigner { field: "title", .. }));
+        for email in ["dana", "dana@localhost", "@example.com", "dana@example.", "d a@x.io"] {
+            assert!(SignerIdentity::new("Dana", "VP", email).is_err(), "{email}");
+        }
+    }
+
+    #[test]
+    fn signatures_must_be_non_empty_and_normalised() {
+        assert!(SignatureCapture::Typed { text: "Dana Reyes".into() }.validate().is_ok());
+        assert!(SignatureCapture::Typed { text: "  ".into() }.validate().is_err());
+        let drawn = |strokes: Vec<Vec<[f32; 2]>>| SignatureCapture::Drawn { strokes }.validate();
+        assert!(drawn(vec![vec![[0.1, 0.5], [0.4, 0.2], [0.9, 0.7]]]).is_ok());
+        assert!(drawn(vec![vec![]]).is_err());
+        assert!(drawn(vec![vec![[0.1, 1.5]]]).is_err());
+        assert!(drawn(vec![vec![[f32::NAN, 0.5]]]).is_err());
+
+        let json = serde_json::json!({"kind": "drawn", "strokes": [[[0.0, 0.0], [1.0, 1.0]]]});
+        let parsed: SignatureCapture = serde_json::from_value(json).expect("tagged json");
+        assert_eq!(parsed.kind(), "drawn");
+    }
+
+    #[test]
+    fn codes_are_numeric_and_only_match_their_request() {
+        let code = generate_code();
+        assert_eq!(code.len(), CODE_LENGTH);
+        assert!(code.chars().all(|ch| ch.is_ascii_digit()));
+
+        let hash = hash_code("SIGN-1", &code);
+        assert!(code_matches("SIGN-1", &format!(" {code} "), &hash));
+        assert!(!code_matches("SIGN-2", &code, &hash));
+        assert!(!code_matches("SIGN-1", "abcdef", &hash));
+        assert_eq!(mask_email("dana@example.com"), "d***@example.com");
+    }
+}
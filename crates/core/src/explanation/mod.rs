@@ -7,6 +7,7 @@ use chrono::Utc;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 
+use crate::cpq::policy::{PolicyDecision, PolicyInput, PolicyThresholds};
 use crate::domain::explanation::*;
 use crate::domain::quote::{QuoteId, QuoteLineId};
 use async_trait::async_trait;
@@ -251,18 +252,19 @@ impl<P: PricingSnapshotProvider, O: PolicyEvaluationProvider> ExplanationEngine<
         let mut steps = vec![];
         let mut step_order = 1;
 
-        // Step 1: Sum line items
-        let line_items_subtotal: Decimal = pricing.line_items.iter().map(|l| l.line_subtotal).sum();
+        // Step 1: Sum line items before discounts (line subtotals are already net of discount)
+        let line_items_subtotal: Decimal =
+            pricing.line_items.iter().map(|l| l.line_subtotal + l.discount_amount).sum();
         steps.push(ArithmeticStep {
             step_order,
             operation: "sum".to_string(),
             input_values: pricing
                 .line_items
                 .iter()
-                .map(|l| (format!("line_{}", l.line_id), l.line_subtotal))
+                .map(|l| (format!("line_{}", l.line_id), l.line_subtotal + l.discount_amount))
                 .collect(),
             result: line_items_subtotal,
-            description: "Sum of all line item subtotals".to_string(),
+            description: "Sum of all line items before discounts".to_string(),
         });
         step_order += 1;
 
@@ -522,6 +524,178 @@ impl<P: PricingSnapshotProvider, O: PolicyEvaluationProvider> ExplanationEngine<
     }
 }
 
+/// Builds the pricing snapshot persisted when a quote version is priced.
+///
+/// `line_subtotal` on each line is the net amount after its discount; the snapshot subtotal is
+/// the gross sum and `discount_total` the sum of line discounts.
+pub fn pricing_snapshot_from_lines(
+    quote_id: &QuoteId,
+    version: i32,
+    currency: &str,
+    line_items: Vec<PricingLineSnapshot>,
+    tax_total: Decimal,
+    priced_at: String,
+) -> PricingSnapshot {
+    let subtotal: Decimal = line_items.iter().map(|l| l.line_subtotal + l.discount_amount).sum();
+    let discount_total: Decimal = line_items.iter().map(|l| l.discount_amount).sum();
+    let total = subtotal - discount_total + tax_total;
+
+    let mut calculation_steps = vec![CalculationStep {
+        step_order: 1,
+        step_name: "line_subtotals".to_string(),
+        input_values: line_items
+            .iter()
+            .map(|l| (format!("line_{}", l.line_id), l.line_subtotal + l.discount_amount))
+            .collect(),
+        output_value: subtotal,
+        formula: Some("sum(unit_price * quantity)".to_string()),
+    }];
+    calculation_steps.push(CalculationStep {
+        step_order: 2,
+        step_name: "discounts".to_string(),
+        input_values: line_items
+            .iter()
+            .map(|l| (format!("line_{}", l.line_id), l.discount_amount))
+            .collect(),
+        output_value: discount_total,
+        formula: Some("sum(line_gross * discount_percent / 100)".to_string()),
+    });
+    calculation_steps.push(CalculationStep {
+        step_order: 3,
+        step_name: "total".to_string(),
+        input_values: HashMap::from([
+            ("subtotal".to_string(), subtotal),
+            ("discount_total".to_string(), discount_total),
+            ("tax_total".to_string(), tax_total),
+        ]),
+        output_value: total,
+        formula: Some("subtotal - discount_total + tax_total".to_string()),
+    });
+
+    PricingSnapshot {
+        quote_id: quote_id.clone(),
+        version,
+        subtotal,
+        discount_total,
+        tax_total,
+        total,
+        currency: currency.to_string(),
+        line_items,
+        calculation_steps,
+        created_at: priced_at,
+    }
+}
+
+/// Records a policy decision as the evaluation persisted next to the pricing snapshot.
+///
+/// Violations that require an approver are `blocking`; every configured check that did not
+/// fire is listed as an applied rule so explanations can show what passed.
+pub fn policy_evaluation_from_decision(
+    quote_id: &QuoteId,
+    version: i32,
+    input: &PolicyInput,
+    thresholds: &PolicyThresholds,
+    decision: &PolicyDecision,
+    evaluated_at: String,
+) -> PolicyEvaluation {
+    let violations = decision
+        .violations
+        .iter()
+        .map(|violation| {
+            let (threshold_value, actual_value) = match violation.policy_id.as_str() {
+                "discount-cap" => (
+                    Some(if violation.required_approval.as_deref() == Some("vp_finance") {
+                        thresholds.vp_discount_pct
+                    } else {
+                        thresholds.manager_discount_pct
+                    }),
+                    input.requested_discount_pct,
+                ),
+                "margin-floor" => (
+                    Some(thresholds.margin_floor_pct),
+                    input.minimum_margin_pct.unwrap_or_default(),
+                ),
+                "deal-value-cap" => (
+                    thresholds
+                        .finance_deal_value_cents
+                        .map(|cents| Decimal::from(cents) / Decimal::from(100)),
+                    input.deal_value,
+                ),
+                _ => (None, input.requested_discount_pct),
+            };
+            PolicyViolation {
+                policy_id: violation.policy_id.clone(),
+                policy_name: policy_display_name(&violation.policy_id),
+                severity: if violation.required_approval.is_some() {
+                    "blocking".to_string()
+                } else {
+                    "warning".to_string()
+                },
+                threshold_value,
+                actual_value,
+                message: violation.reason.clone(),
+                suggested_resolution: violation
+                    .required_approval
+                    .as_ref()
+                    .map(|role| format!("Request {} approval", role.replace('_', " "))),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut checks = vec![(
+        "discount-cap",
+        format!(
+            "Discounts up to {}% need no approval; above {}% needs VP finance",
+            thresholds.manager_discount_pct, thresholds.vp_discount_pct
+        ),
+    )];
+    // Without cost data the margin floor was not evaluated, so it is not listed as passed.
+    if input.minimum_margin_pct.is_some() {
+        checks.push((
+            "margin-floor",
+            format!("Margin must stay at or above {}%", thresholds.margin_floor_pct),
+        ));
+    }
+    if let Some(cents) = thresholds.finance_deal_value_cents {
+        checks.push((
+            "deal-value-cap",
+            format!(
+                "Deals above {} need finance approval",
+                Decimal::from(cents) / Decimal::from(100)
+            ),
+        ));
+    }
+    let applied_rules = checks
+        .into_iter()
+        .filter(|(policy_id, _)| !violations.iter().any(|v| v.policy_id == *policy_id))
+        .map(|(policy_id, description)| AppliedRule {
+            rule_id: policy_id.to_string(),
+            rule_name: policy_display_name(policy_id),
+            rule_section: "pricing.policy".to_string(),
+            rule_description: description,
+        })
+        .collect();
+
+    PolicyEvaluation {
+        quote_id: quote_id.clone(),
+        version,
+        overall_status: if violations.is_empty() { "approved" } else { "violation" }.to_string(),
+        violations,
+        applied_rules,
+        evaluated_at,
+    }
+}
+
+fn policy_display_name(policy_id: &str) -> String {
+    match policy_id {
+        "discount-cap" => "Discount Cap".to_string(),
+        "margin-floor" => "Margin Floor".to_string(),
+        "deal-value-cap" => "Deal Value Cap".to_string(),
+        "invalid-input" => "Input Validation".to_string(),
+        other => other.replace(['-', '_'], " "),
+    }
+}
+
 /// In-memory implementation of pricing snapshot provider (for testing)
 pub struct InMemoryPricingProvider {
     snapshots: HashMap<(String, i32), PricingSnapshot>,
@@ -845,4 +1019,56 @@ mod tests {
         assert!(has_pricing_ref, "should reference pricing snapshot");
         assert!(has_policy_ref, "should reference policy evaluation");
     }
+
+    #[tokio::test]
+    async fn total_chain_starts_from_gross_line_amounts() {
+        let quote_id = create_test_quote_id("Q-2026-009");
+        let mut pricing_provider = InMemoryPricingProvider::new();
+        let mut policy_provider = InMemoryPolicyProvider::new();
+        pricing_provider.add_snapshot(&quote_id, 1, create_test_pricing_snapshot(&quote_id));
+        policy_provider.add_evaluation(&quote_id, 1, create_test_policy_evaluation(&quote_id));
+
+        let engine = ExplanationEngine::new(pricing_provider, policy_provider);
+        let explanation = engine.explain_total(&quote_id, 1).await.expect("should succeed");
+
+        assert_eq!(explanation.arithmetic_chain[0].result, Decimal::new(20000, 2));
+        assert_eq!(explanation.arithmetic_chain[1].result, Decimal::new(18000, 2));
+    }
+
+    #[test]
+    fn snapshot_and_policy_builders_capture_discounts_and_thresholds() {
+        let quote_id = create_test_quote_id("Q-2026-010");
+        let lines = create_test_pricing_snapshot(&quote_id).line_items;
+        let snapshot =
+            pricing_snapshot_from_lines(&quote_id, 3, "USD", lines, Decimal::ZERO, "now".into());
+        assert_eq!(snapshot.subtotal, Decimal::new(20000, 2));
+        assert_eq!(snapshot.discount_total, Decimal::new(2000, 2));
+        assert_eq!(snapshot.total, Decimal::new(18000, 2));
+        assert_eq!(snapshot.calculation_steps.len(), 3);
+
+        let input = PolicyInput {
+            requested_discount_pct: Decimal::new(25, 0),
+            deal_value: Decimal::new(180, 0),
+            minimum_margin_pct: Some(Decimal::new(75, 0)),
+        };
+        let thresholds = PolicyThresholds::default();
+        let decision = crate::cpq::policy::evaluate_policy_with_thresholds(&input, &thresholds);
+        let evaluation = policy_evaluation_from_decision(
+            &quote_id,
+            3,
+            &input,
+            &thresholds,
+            &decision,
+            "now".into(),
+        );
+
+        assert_eq!(evaluation.overall_status, "violation");
+        let violation = &evaluation.violations[0];
+        assert_eq!(violation.policy_id, "discount-cap");
+        assert_eq!(violation.severity, "blocking");
+        assert_eq!(violation.threshold_value, Some(thresholds.manager_discount_pct));
+        assert_eq!(violation.actual_value, Decimal::new(25, 0));
+        assert!(evaluation.applied_rules.iter().any(|rule| rule.rule_id == "margin-floor"));
+        assert!(!evaluation.applied_rules.iter().any(|rule| rule.rule_id == "discount-cap"));
+    }
 }
@@ -438,7 +438,7 @@ mod tests {
             PolicyInput {
                 requested_discount_pct: Decimal::ZERO,
                 deal_value: Decimal::new(9_998, 2),
-                minimum_margin_pct: Decimal::new(3_000, 2),
+                minimum_margin_pct: Some(Decimal::new(3_000, 2)),
             },
         );
 
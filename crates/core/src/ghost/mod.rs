@@ -46,6 +46,52 @@ impl Default for SignalDetectorConfig {
     }
 }
 
+impl SignalDetectorConfig {
+    /// Lowest threshold calibration may relax to.
+    pub const MIN_CALIBRATED_THRESHOLD: u8 = 50;
+    /// Highest threshold calibration may raise to.
+    pub const MAX_CALIBRATED_THRESHOLD: u8 = 95;
+
+    /// Returns a copy with the confidence threshold tuned by rep feedback: a high dismiss
+    /// rate raises it (up to +20), a low one relaxes it by 5. Sparse feedback leaves it alone.
+    pub fn calibrated(&self, feedback: &GhostFeedback) -> Self {
+        let Some(dismiss_rate) = feedback.dismiss_rate() else {
+            return self.clone();
+        };
+        let threshold = if dismiss_rate > 0.5 {
+            let raise = ((dismiss_rate - 0.5) * 40.0).round() as u8;
+            self.confidence_threshold.saturating_add(raise).min(Self::MAX_CALIBRATED_THRESHOLD)
+        } else if dismiss_rate < 0.2 {
+            self.confidence_threshold.saturating_sub(5).max(Self::MIN_CALIBRATED_THRESHOLD)
+        } else {
+            self.confidence_threshold
+        };
+        Self { confidence_threshold: threshold, ..self.clone() }
+    }
+}
+
+/// Rep responses to ghost drafts, fed back into detector thresholds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GhostFeedback {
+    pub accepted: u32,
+    pub dismissed: u32,
+}
+
+impl GhostFeedback {
+    /// Responses needed before feedback moves the threshold.
+    pub const MIN_RESPONSES: u32 = 5;
+
+    pub fn responses(&self) -> u32 {
+        self.accepted.saturating_add(self.dismissed)
+    }
+
+    /// Share of drafts dismissed, or `None` until [`Self::MIN_RESPONSES`] responses exist.
+    pub fn dismiss_rate(&self) -> Option<f64> {
+        let responses = self.responses();
+        (responses >= Self::MIN_RESPONSES).then(|| f64::from(self.dismissed) / f64::from(responses))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Signal {
     pub confidence: u8,
@@ -57,6 +103,38 @@ pub struct Signal {
     pub above_threshold: bool,
 }
 
+impl Signal {
+    /// Coarse classification persisted with the signal.
+    pub fn signal_type(&self) -> &'static str {
+        let matched = |keywords: &[&str]| {
+            self.keyword_matches.iter().any(|keyword| keywords.contains(&keyword.as_str()))
+        };
+        if !self.competitors.is_empty() {
+            "competitive_evaluation"
+        } else if matched(&["expand", "upgrade", "rollout"]) {
+            "expansion"
+        } else if matched(&["renewal"]) {
+            "renewal"
+        } else {
+            "buying_intent"
+        }
+    }
+
+    /// One-line summary of what was extracted, e.g. `budget, expand · marketing · next quarter`.
+    pub fn intent_summary(&self) -> String {
+        let mut parts = Vec::new();
+        for group in [&self.keyword_matches, &self.departments, &self.timelines] {
+            if !group.is_empty() {
+                parts.push(group.join(", "));
+            }
+        }
+        if !self.competitors.is_empty() {
+            parts.push(format!("vs {}", self.competitors.join(", ")));
+        }
+        parts.join(" · ")
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct SignalDetector {
     config: SignalDetectorConfig,
@@ -426,8 +504,8 @@ mod tests {
     };
 
     use super::{
-        GhostQuoteGenerator, InMemoryCustomerHistoryProvider, InMemoryGhostQuoteStore,
-        SignalDetector, SignalDetectorConfig,
+        GhostFeedback, GhostQuoteGenerator, InMemoryCustomerHistoryProvider,
+        InMemoryGhostQuoteStore, SignalDetector, SignalDetectorConfig,
     };
 
     #[test]
@@ -486,6 +564,38 @@ mod tests {
         assert!(signal.departments.contains(&"engineering".to_string()));
     }
 
+    #[test]
+    fn classifies_signal_type_and_summarizes_intent() {
+        let detector = SignalDetector::default();
+        let competitive = detector.analyze("Acme Corp is evaluating Salesforce pricing.");
+        assert_eq!(competitive.signal_type(), "competitive_evaluation");
+        assert!(competitive.intent_summary().ends_with("vs salesforce"));
+
+        let expansion = detector.analyze("Plan to expand the finance team next quarter.");
+        assert_eq!(expansion.signal_type(), "expansion");
+        assert_eq!(expansion.intent_summary(), "expand · finance, teams · next quarter");
+    }
+
+    #[test]
+    fn feedback_calibrates_threshold_only_with_enough_responses() {
+        let config = SignalDetectorConfig::default();
+        let sparse = GhostFeedback { accepted: 0, dismissed: 4 };
+        assert_eq!(sparse.dismiss_rate(), None);
+        assert_eq!(config.calibrated(&sparse).confidence_threshold, 70);
+
+        let dismissive = GhostFeedback { accepted: 1, dismissed: 9 };
+        assert_eq!(config.calibrated(&dismissive).confidence_threshold, 86);
+
+        let all_dismissed = GhostFeedback { accepted: 0, dismissed: 50 };
+        let strict = SignalDetectorConfig { confidence_threshold: 90, ..config.clone() };
+        assert_eq!(strict.calibrated(&all_dismissed).confidence_threshold, 95);
+
+        let welcomed = GhostFeedback { accepted: 9, dismissed: 1 };
+        assert_eq!(config.calibrated(&welcomed).confidence_threshold, 65);
+        let relaxed = SignalDetectorConfig { confidence_threshold: 52, ..config };
+        assert_eq!(relaxed.calibrated(&welcomed).confidence_threshold, 50);
+    }
+
     /// qa-tag: fake-in-memory-critical-path (bd-3vp2.1)
     #[test]
     fn ghost_quote_generator_creates_discounted_draft_and_persists_it() {
@@ -17,6 +17,7 @@ pub enum LedgerAction {
     Update,
     Approve,
     Reject,
+    Accept,
     Custom(String),
 }
 
@@ -27,6 +28,7 @@ impl LedgerAction {
             Self::Update => "update".to_string(),
             Self::Approve => "approve".to_string(),
             Self::Reject => "reject".to_string(),
+            Self::Accept => "accept".to_string(),
             Self::Custom(value) => value.to_ascii_lowercase(),
         }
     }
@@ -76,31 +78,15 @@ impl LedgerService {
         let chain = self.entries_by_quote.entry(quote.id.0.clone()).or_default();
         let version = u32::try_from(chain.len()).unwrap_or(u32::MAX).saturating_add(1);
         let prev_hash = chain.last().map(|entry| entry.entry_hash.clone());
-        let timestamp = Utc::now();
-        let content_hash = content_hash(quote);
-        let entry_hash = hash_entry_material(
-            &quote.id,
+        let entry = seal_entry(
+            &self.signing_key,
+            quote.id.clone(),
             version,
-            &content_hash,
-            prev_hash.as_deref(),
-            timestamp,
-            &actor_id,
-            &action,
-        );
-        let signature = hmac_hex(&self.signing_key, entry_hash.as_bytes());
-
-        let entry = LedgerEntry {
-            entry_id: Uuid::new_v4().to_string(),
-            quote_id: quote.id.clone(),
-            version,
-            content_hash,
+            content_hash(quote),
             prev_hash,
-            entry_hash,
-            timestamp,
             actor_id,
             action,
-            signature,
-        };
+        );
 
         chain.push(entry.clone());
         entry
@@ -196,6 +182,44 @@ impl LedgerService {
     }
 }
 
+/// Hashes and signs a new entry that follows `prev_hash` in a quote's chain, timestamped now.
+///
+/// `content_hash` is whatever the entry attests to: the canonical quote for
+/// [`LedgerService::append_entry`], or the SHA-256 of a signed document for acceptances.
+pub fn seal_entry(
+    signing_key: &[u8],
+    quote_id: QuoteId,
+    version: u32,
+    content_hash: String,
+    prev_hash: Option<String>,
+    actor_id: String,
+    action: LedgerAction,
+) -> LedgerEntry {
+    let timestamp = Utc::now();
+    let entry_hash = hash_entry_material(
+        &quote_id,
+        version,
+        &content_hash,
+        prev_hash.as_deref(),
+        timestamp,
+        &actor_id,
+        &action,
+    );
+    let signature = hmac_hex(signing_key, entry_hash.as_bytes());
+    LedgerEntry {
+        entry_id: Uuid::new_v4().to_string(),
+        quote_id,
+        version,
+        content_hash,
+        prev_hash,
+        entry_hash,
+        timestamp,
+        actor_id,
+        action,
+        signature,
+    }
+}
+
 fn content_hash(quote: &Quote) -> String {
     let canonical_payload = match serde_json::to_vec(quote) {
         Ok(payload) => payload,
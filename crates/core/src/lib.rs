@@ -19,14 +19,18 @@ pub mod cpq;
 pub mod dna;
 pub mod domain;
 pub mod errors;
+pub mod esign;
 pub mod execution_engine;
 pub mod explanation;
 pub mod flows;
 pub mod ghost;
 pub mod ledger;
+pub mod ml;
+pub mod pdf;
 pub mod policy;
 pub mod services;
 pub mod suggestions;
+pub mod webhooks;
 
 pub use approvals::{
     ApprovalValidationFailure, ApprovalValidationInput, ApprovalValidationResult,
@@ -52,7 +56,7 @@ pub use cpq::constraint_rule_builder::{
     ConstraintRuleCondition, ConstraintRuleDraft, ConstraintRuleOperator,
 };
 pub use cpq::discount_policy_builder::{
-    build_discount_policy, DiscountPolicyBuilderError, DiscountPolicyDraft,
+    build_discount_policy, build_policy_rule, DiscountPolicyBuilderError, DiscountPolicyDraft,
 };
 pub use cpq::draft_quote_builder::{
     DraftQuoteBuildError, DraftQuoteBuildRequest, DraftQuoteBuildResult, DraftQuoteBuilder,
@@ -65,15 +69,19 @@ pub use cpq::rule_builder::{
 };
 pub use dna::{
     ClosedDealOutcome, ConfigurationFingerprint, DealDnaLifecycleService, DealOutcomeMetadata,
-    DealOutcomeStatus, DnaLifecycleError, DnaLifecycleStore, FingerprintGenerator,
-    FingerprintSnapshot, SimilarDeal, SimilarityCandidate, SimilarityEngine,
+    DealOutcomeStatus, DealSimilarityIndex, DnaLifecycleError, DnaLifecycleStore,
+    FingerprintGenerator, FingerprintSnapshot, SimilarDeal, SimilarityCandidate, SimilarityEngine,
+    SimilarityFilter, SimilarityIndexEntry,
 };
 pub use domain::analytics::{
-    AnalyticsContractError, AnalyticsQuerySpec, DimensionKind, MetricKind, ANALYTICS_SCHEMA_VERSION,
+    AnalyticsContractError, AnalyticsFilter, AnalyticsQuerySpec, DimensionKind, MetricKind,
+    ANALYTICS_SCHEMA_VERSION,
 };
 pub use domain::approval::{ApprovalId, ApprovalRequest, ApprovalStatus};
 pub use domain::auth::{
-    AuthChannel, AuthContext, AuthError, AuthErrorCode, AuthMethod, AuthPrincipal, AuthStrength,
+    generate_api_key_secret, hash_api_key, ApiKeyGrants, ApiKeyRecord, ApiScope, AuthChannel,
+    AuthContext, AuthError, AuthErrorCode, AuthMethod, AuthPrincipal, AuthStrength,
+    API_KEY_PREFIX_LEN,
 };
 pub use domain::autopsy::*;
 pub use domain::execution::{
@@ -109,13 +117,14 @@ pub use execution_engine::{
     InMemoryExecutionEngine, RetryPolicy, TransitionResult,
 };
 pub use explanation::{
-    AppliedRule, CalculationStep, ExplanationEngine, ExplanationError, InMemoryPolicyProvider,
-    InMemoryPricingProvider, PolicyEvaluation, PolicyEvaluationProvider, PolicyViolation,
-    PricingLineSnapshot, PricingSnapshot, PricingSnapshotProvider,
+    policy_evaluation_from_decision, pricing_snapshot_from_lines, AppliedRule, CalculationStep,
+    ExplanationEngine, ExplanationError, InMemoryPolicyProvider, InMemoryPricingProvider,
+    PolicyEvaluation, PolicyEvaluationProvider, PolicyViolation, PricingLineSnapshot,
+    PricingSnapshot, PricingSnapshotProvider,
 };
 pub use ghost::{
-    GhostQuote, GhostQuoteGenerator, InMemoryCustomerHistoryProvider, InMemoryGhostQuoteStore,
-    Signal, SignalDetector, SignalDetectorConfig,
+    GhostFeedback, GhostQuote, GhostQuoteGenerator, InMemoryCustomerHistoryProvider,
+    InMemoryGhostQuoteStore, Signal, SignalDetector, SignalDetectorConfig,
 };
 pub use ledger::{LedgerAction, LedgerEntry, LedgerService, VerificationResult};
 pub use policy::optimizer::{
@@ -130,7 +139,8 @@ pub use policy::optimizer::{
 pub use policy::{ExplanationGenerator, ExplanationTemplate, GeneratedExplanation};
 pub use suggestions::{
     BusinessRule, BusinessRuleType, ComponentScores, ConfidenceLevel, CustomerProfile,
-    CustomerSimilarity, FeedbackExperimentVariant, ProductInfo, ProductRelationship,
-    ProductSuggestion, QuoteContext, RelationshipType, ScoreCalculator, ScoringWeights,
-    SeasonalPattern, SuggestionCategory, SuggestionEngine, SuggestionFeedback, SuggestionRequest,
+    CustomerSimilarity, FeedbackExperimentVariant, ProductInfo, ProductPurchase,
+    ProductRelationship, ProductSuggestion, QuoteContext, RelationshipType, ScoreCalculator,
+    ScoringWeights, SeasonalPattern, SuggestionCategory, SuggestionEngine, SuggestionFeedback,
+    SuggestionRequest, SuggestionSnapshot,
 };
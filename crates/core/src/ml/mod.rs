@@ -2,14 +2,42 @@
 //!
 //! Provides deterministic logistic regression for predicting quote win probability
 //! based on historical deal outcomes. All predictions are auditable and reproducible.
+//!
+//! Features come from the catalog and the deal itself: product families (resolved through
+//! [`ProductFamilies`]), line discounts, contract term, customer segment and owning rep.
+//! Categorical features are one-hot encoded against a [`FeatureSchema`] fitted on the training
+//! set and stored with the model, so a persisted model always scores quotes with the same
+//! columns it was trained on.
 
 use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use crate::domain::quote::{Quote, QuoteLine};
-use crate::domain::product::ProductId;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::domain::product::{ProductFamilyId, ProductId};
+use crate::domain::quote::Quote;
+
+/// Catalog lookup from product to its family.
+pub type ProductFamilies = HashMap<ProductId, ProductFamilyId>;
+
+/// Categories seen fewer times than this in the training set get no one-hot column.
+pub const MIN_CATEGORY_SUPPORT: usize = 2;
+
+/// Names of the numeric feature columns, in vector order (bias first).
+const NUMERIC_FEATURE_NAMES: [&str; 10] = [
+    "bias",
+    "line_count",
+    "total_quantity",
+    "total_value",
+    "unique_products",
+    "avg_unit_price",
+    "avg_discount",
+    "max_discount",
+    "term_length",
+    "multi_family",
+];
 
 /// Feature vector extracted from a quote for win probability prediction
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -18,53 +46,68 @@ pub struct QuoteFeatures {
     pub line_count: u32,
     /// Total quantity across all lines
     pub total_quantity: u32,
-    /// Total value of the quote (subtotal)
+    /// Total list value of the quote (before line discounts)
     pub total_value: Decimal,
     /// Number of unique products
     pub unique_products: u32,
     /// Average unit price across lines
     pub avg_unit_price: Decimal,
-    /// Has premium products (detected from product IDs)
-    pub has_premium: bool,
-    /// Has enterprise products (detected from product IDs)
-    pub has_enterprise: bool,
-    /// Quote has multiple product tiers
-    pub mixed_tiers: bool,
-    /// Customer segment (if available from external context)
+    /// Line discount percentage (0-100) weighted by line list value
+    pub avg_discount_pct: f64,
+    /// Deepest line discount percentage (0-100)
+    pub max_discount_pct: f64,
+    /// Contract term, when the quote has one
+    pub term_months: Option<u32>,
+    /// Catalog families on the quote, sorted and de-duplicated
+    pub product_families: Vec<String>,
+    /// Customer segment, lowercased (if known)
     pub customer_segment: Option<String>,
+    /// Rep who owns the quote
+    pub sales_rep_id: Option<String>,
 }
 
 impl QuoteFeatures {
-    /// Extract features from a quote
-    pub fn from_quote(quote: &Quote) -> Self {
+    /// Extract features from a quote, resolving product families through the catalog.
+    ///
+    /// The owning rep defaults to `quote.created_by`; segment is unknown until set with
+    /// [`QuoteFeatures::with_customer_segment`].
+    pub fn from_quote(quote: &Quote, families: &ProductFamilies) -> Self {
         let line_count = quote.lines.len() as u32;
         let total_quantity: u32 = quote.lines.iter().map(|l| l.quantity).sum();
-        let total_value: Decimal = quote.lines.iter()
-            .map(|l| l.unit_price * Decimal::from(l.quantity))
-            .sum();
-        
-        let unique_products = quote.lines.iter()
-            .map(|l| &l.product_id)
-            .collect::<std::collections::HashSet<_>>()
-            .len() as u32;
-        
-        let avg_unit_price = if line_count > 0 {
-            total_value / Decimal::from(line_count)
+        let line_values: Vec<Decimal> =
+            quote.lines.iter().map(|l| l.unit_price * Decimal::from(l.quantity)).collect();
+        let total_value: Decimal = line_values.iter().copied().sum();
+
+        let unique_products =
+            quote.lines.iter().map(|l| &l.product_id).collect::<HashSet<_>>().len() as u32;
+
+        let avg_unit_price =
+            if line_count > 0 { total_value / Decimal::from(line_count) } else { Decimal::ZERO };
+
+        let total_value_f64 = total_value.to_f64().unwrap_or(0.0);
+        let avg_discount_pct = if total_value_f64 > 0.0 {
+            quote
+                .lines
+                .iter()
+                .zip(&line_values)
+                .map(|(line, value)| line.discount_pct * value.to_f64().unwrap_or(0.0))
+                .sum::<f64>()
+                / total_value_f64
+        } else if line_count > 0 {
+            quote.lines.iter().map(|l| l.discount_pct).sum::<f64>() / f64::from(line_count)
         } else {
-            Decimal::ZERO
+            0.0
         };
-
-        let has_premium = quote.lines.iter()
-            .any(|l| l.product_id.0.to_lowercase().contains("premium"));
-        
-        let has_enterprise = quote.lines.iter()
-            .any(|l| l.product_id.0.to_lowercase().contains("enterprise"));
-
-        let has_standard = quote.lines.iter()
-            .any(|l| l.product_id.0.to_lowercase().contains("standard") 
-                || l.product_id.0.to_lowercase().contains("starter"));
-        
-        let mixed_tiers = (has_premium || has_enterprise) && has_standard;
+        let max_discount_pct = quote.lines.iter().map(|l| l.discount_pct).fold(0.0, f64::max);
+
+        let product_families = quote
+            .lines
+            .iter()
+            .filter_map(|l| families.get(&l.product_id))
+            .map(|family| family.0.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
 
         Self {
             line_count,
@@ -72,44 +115,142 @@ impl QuoteFeatures {
             total_value,
             unique_products,
             avg_unit_price,
-            has_premium,
-            has_enterprise,
-            mixed_tiers,
+            avg_discount_pct,
+            max_discount_pct,
+            term_months: quote.term_months,
+            product_families,
             customer_segment: None,
+            sales_rep_id: normalize_category(Some(quote.created_by.as_str())),
         }
     }
 
+    pub fn with_customer_segment(mut self, segment: Option<&str>) -> Self {
+        self.customer_segment = normalize_category(segment).map(|s| s.to_lowercase());
+        self
+    }
+
+    pub fn with_sales_rep(mut self, rep_id: Option<&str>) -> Self {
+        self.sales_rep_id = normalize_category(rep_id);
+        self
+    }
+
+    /// Stable hash of the features, used to tell whether a cached prediction is still current.
+    pub fn cache_key(&self) -> String {
+        let payload = serde_json::to_string(self).unwrap_or_default();
+        format!("{:x}", Sha256::digest(payload.as_bytes()))
+    }
+
     /// Convert features to a normalized feature vector for model input
-    /// 
-    /// Features are normalized to roughly [0, 1] range:
+    ///
+    /// Numeric features are normalized to roughly [0, 1] range:
     /// - line_count: log(1 + x) / 3 (max ~20 lines -> ~1.0)
     /// - total_quantity: log(1 + x) / 5 (max ~150 qty -> ~1.0)
     /// - total_value: min(x / 100000, 1.0) ($100k max -> 1.0)
     /// - unique_products: x / 10.0 (max 10 products -> 1.0)
     /// - avg_unit_price: min(x / 1000, 1.0) ($1000 max -> 1.0)
-    /// - has_premium: 1.0 or 0.0
-    /// - has_enterprise: 1.0 or 0.0
-    /// - mixed_tiers: 1.0 or 0.0
-    pub fn to_normalized_vector(&self) -> Vec<f64> {
-        let line_count_norm = ((1.0 + self.line_count as f64).ln()) / 3.0;
-        let total_qty_norm = ((1.0 + self.total_quantity as f64).ln()) / 5.0;
+    /// - avg_discount / max_discount: pct / 100
+    /// - term_length: months / 36 (0 when no term)
+    /// - multi_family: 1.0 when lines span more than one family
+    ///
+    /// followed by one-hot columns for every family, segment and rep in `schema`.
+    pub fn to_normalized_vector(&self, schema: &FeatureSchema) -> Vec<f64> {
+        let line_count_norm = (1.0 + self.line_count as f64).ln() / 3.0;
+        let total_qty_norm = (1.0 + self.total_quantity as f64).ln() / 5.0;
         let total_value_f64: f64 = self.total_value.try_into().unwrap_or(0.0);
-        let total_value_norm = (total_value_f64 / 100_000.0).min(1.0);
-        let unique_products_norm = (self.unique_products as f64 / 10.0).min(1.0);
+        let total_value_norm = total_value_f64 / 100_000.0;
+        let unique_products_norm = self.unique_products as f64 / 10.0;
         let avg_price_f64: f64 = self.avg_unit_price.try_into().unwrap_or(0.0);
-        let avg_price_norm = (avg_price_f64 / 1000.0).min(1.0);
-        
-        vec![
+        let avg_price_norm = avg_price_f64 / 1000.0;
+        let term_norm = self.term_months.map(|months| months as f64 / 36.0).unwrap_or(0.0);
+
+        let mut vector = vec![
             1.0, // bias term
             line_count_norm.clamp(0.0, 1.0),
             total_qty_norm.clamp(0.0, 1.0),
             total_value_norm.clamp(0.0, 1.0),
             unique_products_norm.clamp(0.0, 1.0),
             avg_price_norm.clamp(0.0, 1.0),
-            if self.has_premium { 1.0 } else { 0.0 },
-            if self.has_enterprise { 1.0 } else { 0.0 },
-            if self.mixed_tiers { 1.0 } else { 0.0 },
-        ]
+            (self.avg_discount_pct / 100.0).clamp(0.0, 1.0),
+            (self.max_discount_pct / 100.0).clamp(0.0, 1.0),
+            term_norm.clamp(0.0, 1.0),
+            if self.product_families.len() > 1 { 1.0 } else { 0.0 },
+        ];
+        vector.extend(schema.families.iter().map(|family| {
+            if self.product_families.contains(family) {
+                1.0
+            } else {
+                0.0
+            }
+        }));
+        vector.extend(one_hot(&schema.segments, self.customer_segment.as_deref()));
+        vector.extend(one_hot(&schema.sales_reps, self.sales_rep_id.as_deref()));
+        vector
+    }
+}
+
+fn normalize_category(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+fn one_hot<'a>(categories: &'a [String], value: Option<&'a str>) -> impl Iterator<Item = f64> + 'a {
+    categories.iter().map(move |category| if value == Some(category) { 1.0 } else { 0.0 })
+}
+
+/// Categorical vocabulary a model was trained with; decides the one-hot columns.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSchema {
+    pub families: Vec<String>,
+    pub segments: Vec<String>,
+    pub sales_reps: Vec<String>,
+}
+
+impl FeatureSchema {
+    /// Collects every family, segment and rep seen at least [`MIN_CATEGORY_SUPPORT`] times.
+    pub fn fit(samples: &[QuoteFeatures]) -> Self {
+        let mut families = BTreeMap::<&str, usize>::new();
+        let mut segments = BTreeMap::<&str, usize>::new();
+        let mut reps = BTreeMap::<&str, usize>::new();
+        for sample in samples {
+            for family in &sample.product_families {
+                *families.entry(family).or_default() += 1;
+            }
+            if let Some(segment) = &sample.customer_segment {
+                *segments.entry(segment).or_default() += 1;
+            }
+            if let Some(rep) = &sample.sales_rep_id {
+                *reps.entry(rep).or_default() += 1;
+            }
+        }
+        let supported = |counts: BTreeMap<&str, usize>| -> Vec<String> {
+            counts
+                .into_iter()
+                .filter(|(_, count)| *count >= MIN_CATEGORY_SUPPORT)
+                .map(|(value, _)| value.to_string())
+                .collect()
+        };
+        Self {
+            families: supported(families),
+            segments: supported(segments),
+            sales_reps: supported(reps),
+        }
+    }
+
+    /// Length of the vector produced by [`QuoteFeatures::to_normalized_vector`].
+    pub fn dimension(&self) -> usize {
+        WinProbabilityModel::FEATURE_DIM
+            + self.families.len()
+            + self.segments.len()
+            + self.sales_reps.len()
+    }
+
+    pub fn feature_names(&self) -> Vec<String> {
+        NUMERIC_FEATURE_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(self.families.iter().map(|family| format!("family:{family}")))
+            .chain(self.segments.iter().map(|segment| format!("segment:{segment}")))
+            .chain(self.sales_reps.iter().map(|rep| format!("rep:{rep}")))
+            .collect()
     }
 }
 
@@ -123,6 +264,34 @@ pub struct DealOutcome {
     pub close_date: DateTime<Utc>,
 }
 
+/// How [`WinProbabilityModel::train_with_holdout`] splits and scores the data.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrainingConfig {
+    /// Share of the most recently closed deals held out for evaluation.
+    pub holdout_fraction: f64,
+    /// Refuse to train on fewer closed deals than this.
+    pub min_samples: usize,
+    /// Equal-width probability buckets used for the calibration table.
+    pub calibration_bins: usize,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self { holdout_fraction: 0.2, min_samples: 10, calibration_bins: 5 }
+    }
+}
+
+/// Outcome of a train/holdout run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrainingReport {
+    pub version: String,
+    pub training_samples: usize,
+    pub holdout_samples: usize,
+    pub training_accuracy: f64,
+    pub holdout: ModelMetrics,
+    pub calibration: CalibrationMetrics,
+}
+
 /// Trained win probability model with version and metadata
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WinProbabilityModel {
@@ -138,12 +307,15 @@ pub struct WinProbabilityModel {
     pub training_samples: usize,
     /// Feature names for interpretability
     pub feature_names: Vec<String>,
+    /// Categorical vocabulary the weights line up with
+    #[serde(default)]
+    pub schema: FeatureSchema,
 }
 
 impl WinProbabilityModel {
-    /// Feature dimension (including bias)
-    pub const FEATURE_DIM: usize = 9;
-    
+    /// Numeric feature dimension (including bias); one-hot columns follow per [`FeatureSchema`]
+    pub const FEATURE_DIM: usize = NUMERIC_FEATURE_NAMES.len();
+
     /// Learning rate for gradient descent
     pub const LEARNING_RATE: f64 = 0.1;
     /// Number of training epochs
@@ -151,55 +323,38 @@ impl WinProbabilityModel {
     /// L2 regularization parameter
     pub const REGULARIZATION: f64 = 0.01;
 
-    /// Create a new untrained model with random initialization
+    /// Create a new untrained model with zero weights and an empty schema
     pub fn new(version: impl Into<String>) -> Self {
+        let schema = FeatureSchema::default();
         Self {
             version: version.into(),
             trained_at: Utc::now(),
-            weights: vec![0.0; Self::FEATURE_DIM],
+            weights: vec![0.0; schema.dimension()],
             accuracy: 0.0,
             training_samples: 0,
-            feature_names: vec![
-                "bias".to_string(),
-                "line_count".to_string(),
-                "total_quantity".to_string(),
-                "total_value".to_string(),
-                "unique_products".to_string(),
-                "avg_unit_price".to_string(),
-                "has_premium".to_string(),
-                "has_enterprise".to_string(),
-                "mixed_tiers".to_string(),
-            ],
+            feature_names: schema.feature_names(),
+            schema,
         }
     }
 
     /// Create a model with pre-trained weights (for testing/loading persisted models)
-    pub fn with_weights(version: impl Into<String>, weights: Vec<f64>) -> Result<Self, String> {
-        if weights.len() != Self::FEATURE_DIM {
-            return Err(format!(
-                "Expected {} weights, got {}",
-                Self::FEATURE_DIM,
-                weights.len()
-            ));
+    pub fn with_weights(
+        version: impl Into<String>,
+        schema: FeatureSchema,
+        weights: Vec<f64>,
+    ) -> Result<Self, String> {
+        if weights.len() != schema.dimension() {
+            return Err(format!("Expected {} weights, got {}", schema.dimension(), weights.len()));
         }
-        
+
         Ok(Self {
             version: version.into(),
             trained_at: Utc::now(),
             weights,
             accuracy: 0.0,
             training_samples: 0,
-            feature_names: vec![
-                "bias".to_string(),
-                "line_count".to_string(),
-                "total_quantity".to_string(),
-                "total_value".to_string(),
-                "unique_products".to_string(),
-                "avg_unit_price".to_string(),
-                "has_premium".to_string(),
-                "has_enterprise".to_string(),
-                "mixed_tiers".to_string(),
-            ],
+            feature_names: schema.feature_names(),
+            schema,
         })
     }
 
@@ -210,95 +365,132 @@ impl WinProbabilityModel {
         1.0 / (1.0 + (-z).exp())
     }
 
+    fn score(&self, x: &[f64]) -> f64 {
+        let z: f64 = self.weights.iter().zip(x).map(|(w, xi)| w * xi).sum();
+        Self::sigmoid(z)
+    }
+
     /// Predict win probability for a quote (0.0 to 1.0)
-    pub fn predict_win_probability(&self, quote: &Quote) -> f64 {
-        let features = QuoteFeatures::from_quote(quote);
+    pub fn predict_win_probability(&self, quote: &Quote, families: &ProductFamilies) -> f64 {
+        let features = QuoteFeatures::from_quote(quote, families);
         self.predict_from_features(&features)
     }
 
     /// Predict from pre-extracted features
     pub fn predict_from_features(&self, features: &QuoteFeatures) -> f64 {
-        let x = features.to_normalized_vector();
-        let z: f64 = self.weights.iter().zip(x.iter()).map(|(w, xi)| w * xi).sum();
-        Self::sigmoid(z)
+        self.score(&features.to_normalized_vector(&self.schema))
     }
 
     /// Train the model on historical deal outcomes
-    /// 
-    /// Uses batch gradient descent with L2 regularization
+    ///
+    /// Fits the categorical schema on `outcomes`, then runs batch gradient descent with L2
+    /// regularization. Returns accuracy on the training data itself.
     pub fn train(&mut self, outcomes: &[DealOutcome]) -> Result<f64, String> {
         if outcomes.is_empty() {
             return Err("Cannot train on empty dataset".to_string());
         }
 
+        let features: Vec<QuoteFeatures> = outcomes.iter().map(|o| o.features.clone()).collect();
+        self.schema = FeatureSchema::fit(&features);
+        self.feature_names = self.schema.feature_names();
+        let dim = self.schema.dimension();
+        self.weights = vec![0.0; dim];
+
         let n = outcomes.len() as f64;
         self.training_samples = outcomes.len();
-        
-        // Prepare training data
-        let x: Vec<Vec<f64>> = outcomes.iter()
-            .map(|o| o.features.to_normalized_vector())
-            .collect();
-        let y: Vec<f64> = outcomes.iter()
-            .map(|o| if o.outcome { 1.0 } else { 0.0 })
-            .collect();
-
-        // Gradient descent
-        for epoch in 0..Self::EPOCHS {
-            let mut gradients = vec![0.0; Self::FEATURE_DIM];
-            
-            // Compute gradients
-            for i in 0..outcomes.len() {
-                let z: f64 = self.weights.iter().zip(x[i].iter()).map(|(w, xi)| w * xi).sum();
-                let pred = Self::sigmoid(z);
-                let error = pred - y[i];
-                
-                for j in 0..Self::FEATURE_DIM {
-                    gradients[j] += error * x[i][j];
+        let (x, y) = self.design_matrix(outcomes);
+
+        for _ in 0..Self::EPOCHS {
+            let mut gradients = vec![0.0; dim];
+            for (xi, yi) in x.iter().zip(&y) {
+                let error = self.score(xi) - yi;
+                for (gradient, value) in gradients.iter_mut().zip(xi) {
+                    *gradient += error * value;
                 }
             }
-            
-            // Average and add regularization (skip bias term)
-            for j in 0..Self::FEATURE_DIM {
-                gradients[j] /= n;
-                if j > 0 { // Don't regularize bias
-                    gradients[j] += Self::REGULARIZATION * self.weights[j];
+
+            for (j, (weight, gradient)) in self.weights.iter_mut().zip(&gradients).enumerate() {
+                let mut step = gradient / n;
+                // Don't regularize bias
+                if j > 0 {
+                    step += Self::REGULARIZATION * *weight;
                 }
-            }
-            
-            // Update weights
-            for j in 0..Self::FEATURE_DIM {
-                self.weights[j] -= Self::LEARNING_RATE * gradients[j];
-            }
-            
-            // Log progress every 100 epochs (only in debug builds)
-            #[cfg(debug_assertions)]
-            if epoch % 100 == 0 {
-                let _loss = self.compute_loss(&x, &y);
-                // Training progress logged at epoch intervals
+                *weight -= Self::LEARNING_RATE * step;
             }
         }
 
-        // Compute final accuracy
         self.accuracy = self.compute_accuracy(&x, &y);
         self.trained_at = Utc::now();
-        
+
         Ok(self.accuracy)
     }
 
+    /// Trains on the older deals and scores the most recently closed ones.
+    ///
+    /// The split is by close date rather than random so the holdout measures how the model
+    /// does on deals it could not have seen, and so the same data always yields the same model.
+    /// [`WinProbabilityModel::accuracy`] ends up as the holdout accuracy.
+    pub fn train_with_holdout(
+        &mut self,
+        outcomes: &[DealOutcome],
+        config: &TrainingConfig,
+    ) -> Result<TrainingReport, String> {
+        if outcomes.len() < config.min_samples.max(2) {
+            return Err(format!(
+                "need at least {} closed deals to train, found {}",
+                config.min_samples.max(2),
+                outcomes.len()
+            ));
+        }
+        if !(config.holdout_fraction > 0.0 && config.holdout_fraction < 1.0) {
+            return Err("holdout fraction must be between 0 and 1".to_string());
+        }
+
+        let (training, holdout) = holdout_split(outcomes, config.holdout_fraction);
+        let has_won = training.iter().any(|o| o.outcome);
+        let has_lost = training.iter().any(|o| !o.outcome);
+        if !(has_won && has_lost) {
+            return Err("training set needs both won and lost deals".to_string());
+        }
+
+        let training_accuracy = self.train(&training)?;
+        let metrics = self.evaluate(&holdout);
+        let calibration = self.calibration(&holdout, config.calibration_bins);
+        self.accuracy = metrics.accuracy;
+
+        Ok(TrainingReport {
+            version: self.version.clone(),
+            training_samples: training.len(),
+            holdout_samples: holdout.len(),
+            training_accuracy,
+            holdout: metrics,
+            calibration,
+        })
+    }
+
+    fn design_matrix(&self, outcomes: &[DealOutcome]) -> (Vec<Vec<f64>>, Vec<f64>) {
+        let x = outcomes.iter().map(|o| o.features.to_normalized_vector(&self.schema)).collect();
+        let y = outcomes.iter().map(|o| if o.outcome { 1.0 } else { 0.0 }).collect();
+        (x, y)
+    }
+
     /// Compute binary cross-entropy loss
     fn compute_loss(&self, x: &[Vec<f64>], y: &[f64]) -> f64 {
-        let n = x.len() as f64;
-        let mut loss = 0.0;
-        
-        for i in 0..x.len() {
-            let z: f64 = self.weights.iter().zip(x[i].iter()).map(|(w, xi)| w * xi).sum();
-            let pred = Self::sigmoid(z);
-            // Add small epsilon to avoid log(0)
-            let pred = pred.clamp(1e-15, 1.0 - 1e-15);
-            loss -= y[i] * pred.ln() + (1.0 - y[i]) * (1.0 - pred).ln();
+        if x.is_empty() {
+            return 0.0;
         }
-        
-        loss / n
+
+        let loss: f64 = x
+            .iter()
+            .zip(y)
+            .map(|(xi, yi)| {
+                // Add small epsilon to avoid log(0)
+                let pred = self.score(xi).clamp(1e-15, 1.0 - 1e-15);
+                -(yi * pred.ln() + (1.0 - yi) * (1.0 - pred).ln())
+            })
+            .sum();
+
+        loss / x.len() as f64
     }
 
     /// Compute classification accuracy
@@ -307,18 +499,8 @@ impl WinProbabilityModel {
             return 0.0;
         }
 
-        let mut correct = 0;
-
-        for i in 0..x.len() {
-            let z: f64 = self.weights.iter().zip(x[i].iter()).map(|(w, xi)| w * xi).sum();
-            let pred = Self::sigmoid(z);
-            let pred_class = pred >= 0.5;
-            let true_class = y[i] >= 0.5;
-
-            if pred_class == true_class {
-                correct += 1;
-            }
-        }
+        let correct =
+            x.iter().zip(y).filter(|(xi, yi)| (self.score(xi) >= 0.5) == (**yi >= 0.5)).count();
 
         correct as f64 / x.len() as f64
     }
@@ -335,62 +517,109 @@ impl WinProbabilityModel {
             };
         }
 
-        let x: Vec<Vec<f64>> = outcomes.iter()
-            .map(|o| o.features.to_normalized_vector())
-            .collect();
-        let y: Vec<f64> = outcomes.iter()
-            .map(|o| if o.outcome { 1.0 } else { 0.0 })
-            .collect();
-
+        let (x, y) = self.design_matrix(outcomes);
         let accuracy = self.compute_accuracy(&x, &y);
-        
+
         // Compute precision and recall
         let mut true_positives = 0;
         let mut false_positives = 0;
         let mut false_negatives = 0;
-        
-        for i in 0..outcomes.len() {
-            let prob = self.predict_from_features(&outcomes[i].features);
-            let pred = prob >= 0.5;
-            let actual = outcomes[i].outcome;
-            
-            if pred && actual {
-                true_positives += 1;
-            } else if pred && !actual {
-                false_positives += 1;
-            } else if !pred && actual {
-                false_negatives += 1;
+
+        for outcome in outcomes {
+            let pred = self.predict_from_features(&outcome.features) >= 0.5;
+            match (pred, outcome.outcome) {
+                (true, true) => true_positives += 1,
+                (true, false) => false_positives += 1,
+                (false, true) => false_negatives += 1,
+                (false, false) => {}
             }
         }
-        
+
         let precision = if true_positives + false_positives > 0 {
             true_positives as f64 / (true_positives + false_positives) as f64
         } else {
             0.0
         };
-        
+
         let recall = if true_positives + false_negatives > 0 {
             true_positives as f64 / (true_positives + false_negatives) as f64
         } else {
             0.0
         };
-        
+
         let f1 = if precision + recall > 0.0 {
             2.0 * precision * recall / (precision + recall)
         } else {
             0.0
         };
 
-        ModelMetrics {
-            accuracy,
-            precision,
-            recall,
-            f1_score: f1,
-            sample_count: outcomes.len(),
+        ModelMetrics { accuracy, precision, recall, f1_score: f1, sample_count: outcomes.len() }
+    }
+
+    /// How well predicted probabilities match observed win rates on `outcomes`.
+    pub fn calibration(&self, outcomes: &[DealOutcome], bins: usize) -> CalibrationMetrics {
+        let bins = bins.max(1);
+        let (x, y) = self.design_matrix(outcomes);
+        let predictions: Vec<f64> = x.iter().map(|xi| self.score(xi)).collect();
+        if predictions.is_empty() {
+            return CalibrationMetrics::default();
+        }
+
+        let n = predictions.len() as f64;
+        let brier_score =
+            predictions.iter().zip(&y).map(|(p, yi)| (p - yi).powi(2)).sum::<f64>() / n;
+
+        let mut buckets = vec![(0usize, 0.0f64, 0.0f64); bins];
+        for (p, yi) in predictions.iter().zip(&y) {
+            let index = ((p * bins as f64) as usize).min(bins - 1);
+            let bucket = &mut buckets[index];
+            bucket.0 += 1;
+            bucket.1 += p;
+            bucket.2 += yi;
+        }
+
+        let mut expected_calibration_error = 0.0;
+        let mut table = Vec::new();
+        for (index, (count, predicted_sum, won_sum)) in buckets.into_iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let mean_predicted = predicted_sum / count as f64;
+            let observed_win_rate = won_sum / count as f64;
+            expected_calibration_error +=
+                (count as f64 / n) * (mean_predicted - observed_win_rate).abs();
+            table.push(CalibrationBin {
+                lower: index as f64 / bins as f64,
+                upper: (index + 1) as f64 / bins as f64,
+                count,
+                mean_predicted,
+                observed_win_rate,
+            });
+        }
+
+        CalibrationMetrics {
+            brier_score,
+            log_loss: self.compute_loss(&x, &y),
+            expected_calibration_error,
+            bins: table,
         }
     }
 }
 
+/// Orders outcomes by close date (then quote id) and holds out the newest share.
+pub fn holdout_split(
+    outcomes: &[DealOutcome],
+    holdout_fraction: f64,
+) -> (Vec<DealOutcome>, Vec<DealOutcome>) {
+    let mut ordered = outcomes.to_vec();
+    ordered
+        .sort_by(|a, b| a.close_date.cmp(&b.close_date).then_with(|| a.quote_id.cmp(&b.quote_id)));
+    let holdout_len = ((ordered.len() as f64 * holdout_fraction).ceil() as usize)
+        .clamp(1, ordered.len().saturating_sub(1));
+    let holdout = ordered.split_off(ordered.len() - holdout_len);
+    (ordered, holdout)
+}
+
 /// Model performance metrics
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ModelMetrics {
@@ -401,6 +630,28 @@ pub struct ModelMetrics {
     pub sample_count: usize,
 }
 
+/// Probability calibration on a scored set; lower is better for every score.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationMetrics {
+    /// Mean squared error between predicted probability and outcome
+    pub brier_score: f64,
+    /// Binary cross-entropy
+    pub log_loss: f64,
+    /// Count-weighted gap between predicted and observed win rate across bins
+    pub expected_calibration_error: f64,
+    /// Non-empty probability buckets
+    pub bins: Vec<CalibrationBin>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    pub observed_win_rate: f64,
+}
+
 /// Model versioning and persistence
 #[derive(Clone, Debug, Default)]
 pub struct ModelRegistry {
@@ -426,8 +677,7 @@ impl ModelRegistry {
 
     /// Get the current (latest) model
     pub fn current(&self) -> Option<&WinProbabilityModel> {
-        self.current_version.as_ref()
-            .and_then(|v| self.models.get(v))
+        self.current_version.as_ref().and_then(|v| self.models.get(v))
     }
 
     /// List all registered versions
@@ -445,79 +695,149 @@ impl ModelRegistry {
 
     /// Save a model to JSON
     pub fn save(&self, version: &str) -> Result<String, String> {
-        let model = self.models.get(version)
+        let model = self
+            .models
+            .get(version)
             .ok_or_else(|| format!("Model version {} not found", version))?;
-        serde_json::to_string_pretty(model)
-            .map_err(|e| format!("Failed to serialize model: {}", e))
+        serde_json::to_string_pretty(model).map_err(|e| format!("Failed to serialize model: {}", e))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use crate::domain::quote::{QuoteId, QuoteLine, QuoteStatus};
+    use chrono::{Duration, TimeZone, Utc};
     use rust_decimal::Decimal;
 
-    fn create_test_quote(product_id: &str, quantity: u32, unit_price: i64) -> Quote {
+    fn catalog() -> ProductFamilies {
+        [
+            ("plan-ent", "platform"),
+            ("plan-starter", "platform"),
+            ("support-24x7", "support"),
+            ("addon-sso", "security"),
+        ]
+        .into_iter()
+        .map(|(product, family)| {
+            (ProductId(product.to_string()), ProductFamilyId(family.to_string()))
+        })
+        .collect()
+    }
+
+    fn line(product_id: &str, quantity: u32, unit_price: i64, discount_pct: f64) -> QuoteLine {
+        QuoteLine {
+            product_id: ProductId(product_id.to_string()),
+            quantity,
+            unit_price: Decimal::new(unit_price, 2),
+            discount_pct,
+            notes: None,
+        }
+    }
+
+    fn create_test_quote(lines: Vec<QuoteLine>, term_months: Option<u32>, rep: &str) -> Quote {
         let now = Utc::now();
         Quote {
-            id: crate::domain::quote::QuoteId("Q-TEST-001".to_string()),
+            id: QuoteId("Q-TEST-001".to_string()),
             version: 1,
-            status: crate::domain::quote::QuoteStatus::Draft,
+            status: QuoteStatus::Draft,
             account_id: None,
             deal_id: None,
             currency: "USD".to_string(),
-            term_months: None,
+            term_months,
             start_date: None,
             end_date: None,
             valid_until: None,
             notes: None,
-            created_by: "system".to_string(),
-            lines: vec![QuoteLine {
-                product_id: ProductId(product_id.to_string()),
-                quantity,
-                unit_price: Decimal::new(unit_price, 2),
-                discount_pct: 0.0,
-                notes: None,
-            }],
+            created_by: rep.to_string(),
+            lines,
             created_at: now,
             updated_at: now,
         }
     }
 
-    fn create_test_outcome(product_id: &str, quantity: u32, price: i64, won: bool) -> DealOutcome {
-        let quote = create_test_quote(product_id, quantity, price);
+    fn create_test_outcome(index: usize, won: bool) -> DealOutcome {
+        // Won deals: multi-year enterprise platform with support, shallow discount, rep-a.
+        // Lost deals: monthly starter, deep discount, rep-b.
+        let (lines, term, rep, segment) = if won {
+            (
+                vec![
+                    line("plan-ent", 20 + index as u32, 100_000, 5.0),
+                    line("support-24x7", 1, 50_000, 0.0),
+                ],
+                Some(36),
+                "rep-a",
+                "enterprise",
+            )
+        } else {
+            (vec![line("plan-starter", 2 + index as u32, 10_000, 30.0)], Some(1), "rep-b", "smb")
+        };
+        let quote = create_test_quote(lines, term, rep);
         DealOutcome {
-            quote_id: "Q-TEST-001".to_string(),
-            features: QuoteFeatures::from_quote(&quote),
+            quote_id: format!("Q-{}-{index:03}", if won { "W" } else { "L" }),
+            features: QuoteFeatures::from_quote(&quote, &catalog())
+                .with_customer_segment(Some(segment)),
             outcome: won,
-            final_price: Decimal::new(price * quantity as i64, 2),
-            close_date: Utc::now(),
+            final_price: Decimal::new(100_000, 2),
+            close_date: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+                + Duration::days(index as i64),
         }
     }
 
+    fn training_set() -> Vec<DealOutcome> {
+        (0..30)
+            .flat_map(|i| [create_test_outcome(i, true), create_test_outcome(i, false)])
+            .collect()
+    }
+
     #[test]
-    fn quote_features_extracts_correctly() {
-        let quote = create_test_quote("enterprise-plan", 10, 50000); // $500.00
-        let features = QuoteFeatures::from_quote(&quote);
-        
-        assert_eq!(features.line_count, 1);
-        assert_eq!(features.total_quantity, 10);
-        assert!(features.has_enterprise);
-        assert!(!features.has_premium);
-        assert!(!features.mixed_tiers);
+    fn quote_features_come_from_catalog_discount_term_and_rep() {
+        let quote = create_test_quote(
+            vec![
+                line("plan-ent", 10, 50_000, 10.0),
+                line("support-24x7", 1, 100_000, 0.0),
+                line("unlisted", 1, 1_000, 50.0),
+            ],
+            Some(24),
+            "rep-a",
+        );
+        let features = QuoteFeatures::from_quote(&quote, &catalog())
+            .with_customer_segment(Some(" Mid-Market "));
+
+        assert_eq!(features.line_count, 3);
+        assert_eq!(features.total_quantity, 12);
+        assert_eq!(features.product_families, vec!["platform".to_string(), "support".to_string()]);
+        assert_eq!(features.term_months, Some(24));
+        assert_eq!(features.max_discount_pct, 50.0);
+        // 10% on $5,000 and 50% on $10, weighted by list value.
+        assert!((features.avg_discount_pct - (500.0 + 5.0) / 6010.0 * 100.0).abs() < 1e-9);
+        assert_eq!(features.customer_segment.as_deref(), Some("mid-market"));
+        assert_eq!(features.sales_rep_id.as_deref(), Some("rep-a"));
+        assert_eq!(
+            features.clone().with_sales_rep(Some("REP-7")).sales_rep_id.as_deref(),
+            Some("REP-7")
+        );
     }
 
     #[test]
-    fn feature_normalization_produces_correct_dimensions() {
-        let quote = create_test_quote("premium-plan", 5, 25000);
-        let features = QuoteFeatures::from_quote(&quote);
-        let vector = features.to_normalized_vector();
-        
-        assert_eq!(vector.len(), WinProbabilityModel::FEATURE_DIM);
+    fn feature_schema_one_hot_encodes_supported_categories() {
+        let samples: Vec<QuoteFeatures> =
+            training_set().into_iter().map(|outcome| outcome.features).collect();
+        let schema = FeatureSchema::fit(&samples);
+        assert_eq!(schema.families, vec!["platform".to_string(), "support".to_string()]);
+        assert_eq!(schema.segments, vec!["enterprise".to_string(), "smb".to_string()]);
+        assert_eq!(schema.sales_reps, vec!["rep-a".to_string(), "rep-b".to_string()]);
+
+        let vector = samples[0].to_normalized_vector(&schema);
+        assert_eq!(vector.len(), schema.dimension());
+        assert_eq!(schema.feature_names().len(), schema.dimension());
         assert_eq!(vector[0], 1.0); // bias
-        assert!(vector[1] >= 0.0 && vector[1] <= 1.0); // line_count
-        assert!(vector[6] == 1.0); // has_premium
+        assert!(vector.iter().all(|value| (0.0..=1.0).contains(value)));
+        let column =
+            |name: &str| schema.feature_names().iter().position(|n| n == name).expect("column");
+        assert_eq!(vector[column("family:support")], 1.0);
+        assert_eq!(vector[column("segment:enterprise")], 1.0);
+        assert_eq!(vector[column("segment:smb")], 0.0);
+        assert_eq!(vector[column("rep:rep-a")], 1.0);
     }
 
     #[test]
@@ -530,54 +850,70 @@ mod tests {
     #[test]
     fn model_prediction_returns_probability_between_0_and_1() {
         let model = WinProbabilityModel::new("v1.0.0-test");
-        let quote = create_test_quote("enterprise", 10, 50000);
-        
-        let prob = model.predict_win_probability(&quote);
-        assert!(prob >= 0.0 && prob <= 1.0);
+        let quote = create_test_quote(vec![line("plan-ent", 10, 50_000, 0.0)], None, "rep-a");
+
+        let prob = model.predict_win_probability(&quote, &catalog());
+        assert!((0.0..=1.0).contains(&prob));
     }
 
     #[test]
     fn model_trains_and_achieves_minimum_accuracy() {
-        // Create synthetic training data
-        // Pattern: enterprise + high quantity = more likely to win
-        let mut outcomes = vec![];
-        
-        // Won deals: enterprise with high quantity
-        for i in 0..30 {
-            outcomes.push(create_test_outcome("enterprise-plan", 20 + i as u32, 100000, true));
-        }
-        
-        // Lost deals: starter with low quantity  
-        for i in 0..30 {
-            outcomes.push(create_test_outcome("starter-plan", 2 + i as u32, 10000, false));
-        }
-        
         let mut model = WinProbabilityModel::new("v1.0.0-test");
-        let accuracy = model.train(&outcomes).expect("Training should succeed");
-        
+        let accuracy = model.train(&training_set()).expect("Training should succeed");
+
         // Model should achieve >70% accuracy on this separable data
-        assert!(
-            accuracy >= 0.70,
-            "Model accuracy {:.2}% should be >= 70%", 
-            accuracy * 100.0
-        );
-        
-        println!("Training accuracy: {:.2}%", accuracy * 100.0);
+        assert!(accuracy >= 0.70, "Model accuracy {:.2}% should be >= 70%", accuracy * 100.0);
+        assert_eq!(model.weights.len(), model.schema.dimension());
+    }
+
+    #[test]
+    fn holdout_training_scores_newest_deals_with_calibration() {
+        let outcomes = training_set();
+        let mut model = WinProbabilityModel::new("v2");
+        let report =
+            model.train_with_holdout(&outcomes, &TrainingConfig::default()).expect("train");
+
+        assert_eq!(report.training_samples + report.holdout_samples, outcomes.len());
+        assert_eq!(report.holdout_samples, 12);
+        assert_eq!(report.holdout.sample_count, 12);
+        assert!(report.holdout.accuracy >= 0.9, "holdout accuracy {}", report.holdout.accuracy);
+        assert_eq!(model.accuracy, report.holdout.accuracy);
+
+        let calibration = &report.calibration;
+        assert!(calibration.brier_score < 0.1, "brier {}", calibration.brier_score);
+        assert!(calibration.expected_calibration_error < 0.3);
+        assert_eq!(calibration.bins.iter().map(|bin| bin.count).sum::<usize>(), 12);
+
+        // Holdout is the most recently closed share.
+        let (training, holdout) = holdout_split(&outcomes, 0.2);
+        let newest_training = training.iter().map(|o| o.close_date).max().expect("training");
+        assert!(holdout.iter().all(|o| o.close_date >= newest_training));
+    }
+
+    #[test]
+    fn holdout_training_rejects_thin_or_one_sided_data() {
+        let mut model = WinProbabilityModel::new("v1");
+        let config = TrainingConfig::default();
+        let few: Vec<DealOutcome> = (0..3).map(|i| create_test_outcome(i, true)).collect();
+        assert!(model.train_with_holdout(&few, &config).unwrap_err().contains("at least 10"));
+
+        let all_won: Vec<DealOutcome> = (0..12).map(|i| create_test_outcome(i, true)).collect();
+        assert!(model.train_with_holdout(&all_won, &config).unwrap_err().contains("won and lost"));
     }
 
     #[test]
     fn model_registry_manages_versions() {
         let mut registry = ModelRegistry::new();
-        
+
         let model1 = WinProbabilityModel::new("v1.0.0");
         let model2 = WinProbabilityModel::new("v1.1.0");
-        
+
         registry.register(model1);
         assert_eq!(registry.current().unwrap().version, "v1.0.0");
-        
+
         registry.register(model2);
         assert_eq!(registry.current().unwrap().version, "v1.1.0");
-        
+
         assert!(registry.get("v1.0.0").is_some());
         assert!(registry.get("v1.1.0").is_some());
         assert_eq!(registry.versions().len(), 2);
@@ -585,58 +921,44 @@ mod tests {
 
     #[test]
     fn model_serialization_roundtrip() {
+        let mut model = WinProbabilityModel::new("v1.0.0-test");
+        model.train(&training_set()).expect("train");
         let mut registry = ModelRegistry::new();
-        let model = WinProbabilityModel::new("v1.0.0-test");
-        registry.register(model);
-        
+        registry.register(model.clone());
+
         let json = registry.save("v1.0.0-test").expect("Save should succeed");
-        
+
         let mut new_registry = ModelRegistry::new();
         new_registry.load(&json).expect("Load should succeed");
-        
+
         let loaded = new_registry.get("v1.0.0-test").unwrap();
         assert_eq!(loaded.version, "v1.0.0-test");
-        assert_eq!(loaded.weights.len(), WinProbabilityModel::FEATURE_DIM);
+        assert_eq!(loaded.schema, model.schema);
+        assert_eq!(loaded.weights.len(), loaded.schema.dimension());
+        let sample = &training_set()[0].features;
+        assert_eq!(loaded.predict_from_features(sample), model.predict_from_features(sample));
     }
 
     #[test]
-    fn mixed_tiers_detection_works() {
-        let now = Utc::now();
-        let quote = Quote {
-            id: crate::domain::quote::QuoteId("Q-MIXED".to_string()),
-            version: 1,
-            status: crate::domain::quote::QuoteStatus::Draft,
-            account_id: None,
-            deal_id: None,
-            currency: "USD".to_string(),
-            term_months: None,
-            start_date: None,
-            end_date: None,
-            valid_until: None,
-            notes: None,
-            created_by: "system".to_string(),
-            lines: vec![
-                QuoteLine {
-                    product_id: ProductId("enterprise".to_string()),
-                    quantity: 1,
-                    unit_price: Decimal::new(100000, 2),
-                    discount_pct: 0.0,
-                    notes: None,
-                },
-                QuoteLine {
-                    product_id: ProductId("starter".to_string()),
-                    quantity: 1,
-                    unit_price: Decimal::new(10000, 2),
-                    discount_pct: 0.0,
-                    notes: None,
-                },
-            ],
-            created_at: now,
-            updated_at: now,
+    fn with_weights_checks_schema_dimension() {
+        let schema = FeatureSchema {
+            families: vec!["platform".to_string()],
+            segments: Vec::new(),
+            sales_reps: Vec::new(),
         };
-        
-        let features = QuoteFeatures::from_quote(&quote);
-        assert!(features.has_enterprise);
-        assert!(features.mixed_tiers);
+        assert!(WinProbabilityModel::with_weights("v1", schema.clone(), vec![0.0; 3]).is_err());
+        let model = WinProbabilityModel::with_weights("v1", schema, vec![0.0; 11]).expect("model");
+        assert_eq!(model.feature_names.last().map(String::as_str), Some("family:platform"));
+    }
+
+    #[test]
+    fn cache_key_changes_with_features() {
+        let quote = create_test_quote(vec![line("plan-ent", 10, 50_000, 0.0)], None, "rep-a");
+        let features = QuoteFeatures::from_quote(&quote, &catalog());
+        assert_eq!(features.cache_key(), features.clone().cache_key());
+        assert_ne!(
+            features.cache_key(),
+            features.clone().with_customer_segment(Some("smb")).cache_key()
+        );
     }
 }
@@ -0,0 +1,433 @@
+//! TrueType parsing and subsetting for embedded PDF fonts.
+//!
+//! Only what the renderer needs: character-to-glyph lookup (`cmap` formats 4 and 12), advance
+//! widths, the metrics a PDF font descriptor asks for, and a subsetter that keeps glyph ids
+//! stable (unused outlines are emptied rather than renumbered) so content streams can address
+//! glyphs directly through `Identity-H`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use super::PdfRenderError;
+
+const REGULAR_BYTES: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const BOLD_BYTES: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Tables a PDF viewer needs from an embedded TrueType program.
+const SUBSET_TABLES: [&[u8; 4]; 9] =
+    [b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep"];
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum FontStyle {
+    Regular,
+    Bold,
+}
+
+impl FontStyle {
+    pub(crate) fn font(self) -> &'static TrueTypeFont {
+        static REGULAR: OnceLock<TrueTypeFont> = OnceLock::new();
+        static BOLD: OnceLock<TrueTypeFont> = OnceLock::new();
+        match self {
+            Self::Regular => REGULAR.get_or_init(|| {
+                TrueTypeFont::parse("DejaVuSans", REGULAR_BYTES).expect("bundled regular font")
+            }),
+            Self::Bold => BOLD.get_or_init(|| {
+                TrueTypeFont::parse("DejaVuSans-Bold", BOLD_BYTES).expect("bundled bold font")
+            }),
+        }
+    }
+
+    pub(crate) fn resource_name(self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Cmap {
+    /// Offset of a format 4 (BMP) subtable.
+    Segmented(usize),
+    /// Offset of a format 12 (full Unicode) subtable.
+    Groups(usize),
+}
+
+#[derive(Debug)]
+pub(crate) struct TrueTypeFont {
+    pub(crate) postscript_name: &'static str,
+    data: &'static [u8],
+    tables: BTreeMap<[u8; 4], (usize, usize)>,
+    pub(crate) units_per_em: u16,
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    long_loca: bool,
+    pub(crate) ascender: i16,
+    pub(crate) descender: i16,
+    pub(crate) cap_height: i16,
+    pub(crate) bbox: [i16; 4],
+    cmap: Cmap,
+}
+
+impl TrueTypeFont {
+    fn parse(postscript_name: &'static str, data: &'static [u8]) -> Result<Self, PdfRenderError> {
+        let tables = table_directory(data)?;
+        let table = |tag: &[u8; 4]| {
+            tables.get(tag).map(|(offset, _)| *offset).ok_or_else(|| {
+                font_error(&format!("missing `{}` table", String::from_utf8_lossy(tag)))
+            })
+        };
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let maxp = table(b"maxp")?;
+        let ascender = read_i16(data, hhea + 4)?;
+        let cap_height = match tables.get(b"OS/2") {
+            Some((os2, _)) if read_u16(data, *os2)? >= 2 => read_i16(data, os2 + 88)?,
+            _ => ascender,
+        };
+
+        Ok(Self {
+            postscript_name,
+            data,
+            units_per_em: read_u16(data, head + 18)?,
+            num_glyphs: read_u16(data, maxp + 4)?,
+            num_h_metrics: read_u16(data, hhea + 34)?,
+            long_loca: read_i16(data, head + 50)? == 1,
+            ascender,
+            descender: read_i16(data, hhea + 6)?,
+            cap_height,
+            bbox: [
+                read_i16(data, head + 36)?,
+                read_i16(data, head + 38)?,
+                read_i16(data, head + 40)?,
+                read_i16(data, head + 42)?,
+            ],
+            cmap: find_cmap(data, table(b"cmap")?)?,
+            tables,
+        })
+    }
+
+    /// Glyph for `ch`, or `.notdef` (0) when the font has none.
+    pub(crate) fn glyph_id(&self, ch: char) -> u16 {
+        let code = u32::from(ch);
+        let data = self.data;
+        let lookup = match self.cmap {
+            Cmap::Segmented(offset) => lookup_format4(data, offset, code),
+            Cmap::Groups(offset) => lookup_format12(data, offset, code),
+        };
+        lookup.filter(|glyph| *glyph < self.num_glyphs).unwrap_or(0)
+    }
+
+    /// Advance width in font units.
+    pub(crate) fn advance(&self, glyph: u16) -> u16 {
+        let Some((hmtx, _)) = self.tables.get(b"hmtx") else {
+            return 0;
+        };
+        let index = glyph.min(self.num_h_metrics.saturating_sub(1)) as usize;
+        read_u16(self.data, hmtx + index * 4).unwrap_or(0)
+    }
+
+    /// Advance width in PDF glyph space (thousandths of an em).
+    pub(crate) fn advance_per_mille(&self, glyph: u16) -> u32 {
+        let units = u32::from(self.advance(glyph)) * 1000;
+        let em = u32::from(self.units_per_em.max(1));
+        (units + em / 2) / em
+    }
+
+    /// Width of `text` set at `size` points.
+    pub(crate) fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text.chars().map(|ch| u32::from(self.advance(self.glyph_id(ch)))).sum();
+        units as f32 * size / f32::from(self.units_per_em.max(1))
+    }
+
+    pub(crate) fn scale(&self, value: i16) -> i32 {
+        (i32::from(value) * 1000) / i32::from(self.units_per_em.max(1))
+    }
+
+    /// A TrueType program keeping every glyph id but only the outlines of `glyphs` (plus
+    /// `.notdef` and any composite components they reference).
+    pub(crate) fn subset(&self, glyphs: &BTreeSet<u16>) -> Result<Vec<u8>, PdfRenderError> {
+        let mut keep: BTreeSet<u16> =
+            glyphs.iter().copied().filter(|g| *g < self.num_glyphs).collect();
+        keep.insert(0);
+        let mut pending: Vec<u16> = keep.iter().copied().collect();
+        while let Some(glyph) = pending.pop() {
+            for component in self.components(glyph)? {
+                if component < self.num_glyphs && keep.insert(component) {
+                    pending.push(component);
+                }
+            }
+        }
+
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity((usize::from(self.num_glyphs) + 1) * 4);
+        for glyph in 0..self.num_glyphs {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            if keep.contains(&glyph) {
+                glyf.extend_from_slice(self.glyph_data(glyph)?);
+                while glyf.len() % 4 != 0 {
+                    glyf.push(0);
+                }
+            }
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let mut tables: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+        for tag in SUBSET_TABLES {
+            let body = match tag {
+                b"glyf" => glyf.clone(),
+                b"loca" => loca.clone(),
+                _ => {
+                    let Some((offset, length)) = self.tables.get(tag) else {
+                        continue;
+                    };
+                    let mut body = self.data[*offset..offset + length].to_vec();
+                    if tag == b"head" {
+                        // Zero checkSumAdjustment and switch to long loca offsets.
+                        if let Some(slot) = body.get_mut(8..12) {
+                            slot.copy_from_slice(&[0; 4]);
+                        }
+                        if let Some(slot) = body.get_mut(50..52) {
+                            slot.copy_from_slice(&1i16.to_be_bytes());
+                        }
+                    }
+                    body
+                }
+            };
+            tables.push((*tag, body));
+        }
+        Ok(assemble_font(&tables))
+    }
+
+    fn glyph_data(&self, glyph: u16) -> Result<&'static [u8], PdfRenderError> {
+        let (loca, _) = *self.tables.get(b"loca").ok_or_else(|| font_error("missing `loca`"))?;
+        let (glyf, glyf_len) =
+            *self.tables.get(b"glyf").ok_or_else(|| font_error("missing `glyf`"))?;
+        let index = usize::from(glyph);
+        let (start, end) = if self.long_loca {
+            (
+                read_u32(self.data, loca + index * 4)? as usize,
+                read_u32(self.data, loca + index * 4 + 4)? as usize,
+            )
+        } else {
+            (
+                read_u16(self.data, loca + index * 2)? as usize * 2,
+                read_u16(self.data, loca + index * 2 + 2)? as usize * 2,
+            )
+        };
+        if start > end || end > glyf_len {
+            return Err(font_error("glyph offsets out of range"));
+        }
+        Ok(&self.data[glyf + start..glyf + end])
+    }
+
+    fn components(&self, glyph: u16) -> Result<Vec<u16>, PdfRenderError> {
+        let data = self.glyph_data(glyph)?;
+        if data.len() < 10 || read_i16(data, 0)? >= 0 {
+            return Ok(Vec::new());
+        }
+        let mut components = Vec::new();
+        let mut cursor = 10;
+        loop {
+            let flags = read_u16(data, cursor)?;
+            components.push(read_u16(data, cursor + 2)?);
+            cursor += 4;
+            cursor += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+            if flags & WE_HAVE_A_SCALE != 0 {
+                cursor += 2;
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                cursor += 4;
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                cursor += 8;
+            }
+            if flags & MORE_COMPONENTS == 0 {
+                break;
+            }
+        }
+        Ok(components)
+    }
+}
+
+fn table_directory(data: &[u8]) -> Result<BTreeMap<[u8; 4], (usize, usize)>, PdfRenderError> {
+    let num_tables = read_u16(data, 4)? as usize;
+    let mut tables = BTreeMap::new();
+    for index in 0..num_tables {
+        let record = 12 + index * 16;
+        let tag: [u8; 4] = data
+            .get(record..record + 4)
+            .and_then(|tag| tag.try_into().ok())
+            .ok_or_else(|| font_error("truncated table directory"))?;
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        if offset.checked_add(length).map_or(true, |end| end > data.len()) {
+            return Err(font_error("table extends past end of file"));
+        }
+        tables.insert(tag, (offset, length));
+    }
+    Ok(tables)
+}
+
+fn find_cmap(data: &[u8], cmap: usize) -> Result<Cmap, PdfRenderError> {
+    let count = read_u16(data, cmap + 2)? as usize;
+    let mut segmented = None;
+    for index in 0..count {
+        let record = cmap + 4 + index * 8;
+        let platform = read_u16(data, record)?;
+        let encoding = read_u16(data, record + 2)?;
+        let offset = cmap + read_u32(data, record + 4)? as usize;
+        match (platform, encoding, read_u16(data, offset)?) {
+            (3, 10, 12) | (0, 4, 12) => return Ok(Cmap::Groups(offset)),
+            (3, 1, 4) | (0, 3, 4) => segmented = segmented.or(Some(offset)),
+            _ => {}
+        }
+    }
+    segmented.map(Cmap::Segmented).ok_or_else(|| font_error("no Unicode cmap subtable"))
+}
+
+fn lookup_format4(data: &[u8], offset: usize, code: u32) -> Option<u16> {
+    let code = u16::try_from(code).ok()?;
+    let seg_count = usize::from(read_u16(data, offset + 6).ok()? / 2);
+    let ends = offset + 14;
+    let starts = ends + seg_count * 2 + 2;
+    let deltas = starts + seg_count * 2;
+    let range_offsets = deltas + seg_count * 2;
+    for segment in 0..seg_count {
+        if read_u16(data, ends + segment * 2).ok()? < code {
+            continue;
+        }
+        let start = read_u16(data, starts + segment * 2).ok()?;
+        if start > code {
+            return None;
+        }
+        let delta = read_u16(data, deltas + segment * 2).ok()?;
+        let range_offset_at = range_offsets + segment * 2;
+        let range_offset = read_u16(data, range_offset_at).ok()?;
+        if range_offset == 0 {
+            return Some(code.wrapping_add(delta));
+        }
+        let glyph_at = range_offset_at + usize::from(range_offset) + usize::from(code - start) * 2;
+        let glyph = read_u16(data, glyph_at).ok()?;
+        return (glyph != 0).then(|| glyph.wrapping_add(delta));
+    }
+    None
+}
+
+fn lookup_format12(data: &[u8], offset: usize, code: u32) -> Option<u16> {
+    let groups = read_u32(data, offset + 12).ok()? as usize;
+    for group in 0..groups {
+        let record = offset + 16 + group * 12;
+        let start = read_u32(data, record).ok()?;
+        let end = read_u32(data, record + 4).ok()?;
+        if (start..=end).contains(&code) {
+            let glyph = read_u32(data, record + 8).ok()? + (code - start);
+            return u16::try_from(glyph).ok();
+        }
+    }
+    None
+}
+
+/// Writes an sfnt with tables in tag order, each 4-byte aligned and checksummed.
+fn assemble_font(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let mut entry_selector = 0u16;
+    while (1u16 << (entry_selector + 1)) <= count {
+        entry_selector += 1;
+    }
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&count.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&(count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut bodies = Vec::new();
+    for (tag, body) in tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&table_checksum(body).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        let padded = (body.len() + 3) & !3;
+        bodies.extend_from_slice(body);
+        bodies.resize(bodies.len() + padded - body.len(), 0);
+        offset += padded;
+    }
+    out.extend_from_slice(&bodies);
+    out
+}
+
+fn table_checksum(body: &[u8]) -> u32 {
+    body.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn font_error(reason: &str) -> PdfRenderError {
+    PdfRenderError::Font(reason.to_owned())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, PdfRenderError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| font_error("unexpected end of font data"))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Result<i16, PdfRenderError> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, PdfRenderError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| font_error("unexpected end of font data"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_fonts_map_latin_text_and_currency_symbols() {
+        let font = FontStyle::Regular.font();
+        assert_eq!(font.units_per_em, 2048);
+        for ch in ['A', 'z', '0', '$', '€', '£', '—', '•'] {
+            assert_ne!(font.glyph_id(ch), 0, "missing glyph for {ch}");
+        }
+        assert!(font.text_width("WWW", 10.0) > font.text_width("iii", 10.0));
+        assert!(FontStyle::Bold.font().text_width("Total", 10.0) > font.text_width("Total", 10.0));
+    }
+
+    #[test]
+    fn subset_keeps_glyph_ids_and_drops_unused_outlines() {
+        let font = FontStyle::Regular.font();
+        let used: BTreeSet<u16> = "Quote".chars().map(|ch| font.glyph_id(ch)).collect();
+        let subset = font.subset(&used).expect("subset");
+        assert!(subset.len() < REGULAR_BYTES.len() / 4, "subset is {} bytes", subset.len());
+        assert_eq!(
+            subset,
+            font.subset(&used).expect("subset again"),
+            "subsetting is deterministic"
+        );
+
+        let leaked: &'static [u8] = Box::leak(subset.into_boxed_slice());
+        let reparsed = TrueTypeFont {
+            data: leaked,
+            tables: table_directory(leaked).expect("subset tables"),
+            long_loca: true,
+            ..TrueTypeFont::parse("DejaVuSans", REGULAR_BYTES).expect("font")
+        };
+        let q = font.glyph_id('Q');
+        assert_eq!(reparsed.glyph_data(q).expect("kept"), font.glyph_data(q).expect("original"));
+        assert!(reparsed.glyph_data(font.glyph_id('Z')).expect("emptied").is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! Branding logos embedded as image XObjects.
+//!
+//! Logos are only taken from `data:` URIs; the renderer never fetches remote URLs. JPEG data is
+//! passed through as `DCTDecode`. Non-interlaced PNGs without an alpha channel are embedded by
+//! concatenating their `IDAT` chunks, which are already a zlib stream PDF can read through
+//! `FlateDecode` with the PNG predictor. Anything else yields `None` and the header falls back
+//! to the company name.
+
+use base64::Engine as _;
+
+use super::writer::{hex, ObjectWriter};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogoImage {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    dictionary: String,
+    data: Vec<u8>,
+}
+
+impl LogoImage {
+    pub(crate) fn from_data_uri(uri: &str) -> Option<Self> {
+        let rest = uri.trim().strip_prefix("data:")?;
+        let (meta, payload) = rest.split_once(',')?;
+        let mut meta = meta.split(';');
+        let mime = meta.next()?.trim().to_ascii_lowercase();
+        if !meta.any(|part| part.trim().eq_ignore_ascii_case("base64")) {
+            return None;
+        }
+        let compact: String = payload.chars().filter(|ch| !ch.is_ascii_whitespace()).collect();
+        let bytes = base64::engine::general_purpose::STANDARD.decode(compact).ok()?;
+        match mime.as_str() {
+            "image/jpeg" | "image/jpg" => Self::jpeg(bytes),
+            "image/png" => Self::png(&bytes),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut ObjectWriter) -> usize {
+        writer.add_stream(&self.dictionary, &self.data)
+    }
+
+    fn jpeg(bytes: Vec<u8>) -> Option<Self> {
+        if !bytes.starts_with(&[0xFF, 0xD8]) {
+            return None;
+        }
+        let mut cursor = 2;
+        while cursor + 4 <= bytes.len() {
+            if bytes[cursor] != 0xFF {
+                return None;
+            }
+            let marker = bytes[cursor + 1];
+            let length = usize::from(u16::from_be_bytes([bytes[cursor + 2], bytes[cursor + 3]]));
+            let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame {
+                let frame = bytes.get(cursor + 4..cursor + 10)?;
+                let height = u32::from(u16::from_be_bytes([frame[1], frame[2]]));
+                let width = u32::from(u16::from_be_bytes([frame[3], frame[4]]));
+                let color_space = match frame[5] {
+                    1 => "/DeviceGray",
+                    3 => "/DeviceRGB",
+                    4 => "/DeviceCMYK /Decode [1 0 1 0 1 0 1 0]",
+                    _ => return None,
+                };
+                if width == 0 || height == 0 {
+                    return None;
+                }
+                let dictionary = format!(
+                    "/Type /XObject /Subtype /Image /Width {width} /Height {height} \
+                     /ColorSpace {color_space} /BitsPerComponent 8 /Filter /DCTDecode"
+                );
+                return Some(Self { width, height, dictionary, data: bytes });
+            }
+            cursor += 2 + length;
+        }
+        None
+    }
+
+    fn png(bytes: &[u8]) -> Option<Self> {
+        let mut cursor = PNG_SIGNATURE.len();
+        if !bytes.starts_with(PNG_SIGNATURE) {
+            return None;
+        }
+        let mut header = None;
+        let mut palette = None;
+        let mut data = Vec::new();
+        while cursor + 8 <= bytes.len() {
+            let length = u32::from_be_bytes(bytes[cursor..cursor + 4].try_into().ok()?) as usize;
+            let kind = &bytes[cursor + 4..cursor + 8];
+            let body = bytes.get(cursor + 8..cursor + 8 + length)?;
+            match kind {
+                b"IHDR" => header = Some(body),
+                b"PLTE" => palette = Some(body),
+                b"IDAT" => data.extend_from_slice(body),
+                b"IEND" => break,
+                _ => {}
+            }
+            cursor += 12 + length;
+        }
+
+        let header = header.filter(|header| header.len() >= 13)?;
+        let width = u32::from_be_bytes(header[0..4].try_into().ok()?);
+        let height = u32::from_be_bytes(header[4..8].try_into().ok()?);
+        let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+        if width == 0 || height == 0 || interlace != 0 || data.is_empty() {
+            return None;
+        }
+        let (color_space, colors) = match (color_type, bit_depth) {
+            (0, 1 | 2 | 4 | 8 | 16) => ("/DeviceGray".to_owned(), 1),
+            (2, 8 | 16) => ("/DeviceRGB".to_owned(), 3),
+            (3, 1 | 2 | 4 | 8) => {
+                let palette = palette.filter(|p| !p.is_empty() && p.len() % 3 == 0)?;
+                (format!("[/Indexed /DeviceRGB {} <{}>]", palette.len() / 3 - 1, hex(palette)), 1)
+            }
+            _ => return None,
+        };
+        let dictionary = format!(
+            "/Type /XObject /Subtype /Image /Width {width} /Height {height} \
+             /ColorSpace {color_space} /BitsPerComponent {bit_depth} /Filter /FlateDecode \
+             /DecodeParms << /Predictor 15 /Colors {colors} /BitsPerComponent {bit_depth} \
+             /Columns {width} >>"
+        );
+        Some(Self { width, height, dictionary, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut out = (body.len() as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        out.extend_from_slice(&[0; 4]);
+        out
+    }
+
+    fn png(color_type: u8, interlace: u8) -> String {
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        header.extend_from_slice(&4u32.to_be_bytes());
+        header.extend_from_slice(&2u32.to_be_bytes());
+        header.extend_from_slice(&[8, color_type, 0, 0, interlace]);
+        bytes.extend(chunk(b"IHDR", &header));
+        bytes.extend(chunk(b"IDAT", &[0x78, 0x9C, 0x01]));
+        bytes.extend(chunk(b"IDAT", &[0x02, 0x03]));
+        bytes.extend(chunk(b"IEND", &[]));
+        format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    #[test]
+    fn png_idat_chunks_are_embedded_with_the_png_predictor() {
+        let logo = LogoImage::from_data_uri(&png(2, 0)).expect("rgb png");
+        assert_eq!((logo.width, logo.height), (4, 2));
+        assert_eq!(logo.data, vec![0x78, 0x9C, 0x01, 0x02, 0x03]);
+        assert!(logo.dictionary.contains("/Predictor 15 /Colors 3"));
+    }
+
+    #[test]
+    fn unsupported_logos_are_rejected() {
+        assert!(LogoImage::from_data_uri(&png(6, 0)).is_none(), "alpha channel");
+        assert!(LogoImage::from_data_uri(&png(2, 1)).is_none(), "interlaced");
+        assert!(LogoImage::from_data_uri("https://cdn.example.com/logo.png").is_none());
+        assert!(LogoImage::from_data_uri("data:image/svg+xml;base64,PHN2Zy8+").is_none());
+        assert!(LogoImage::from_data_uri("data:image/png;base64,abc123").is_none());
+    }
+}
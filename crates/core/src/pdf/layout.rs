@@ -0,0 +1,1362 @@
+//! Page layout for the quote templates.
+//!
+//! Coordinates here run top-down from the page's top edge; `Canvas` flips them into PDF user
+//! space when emitting operators. Everything is laid out on A4 with fixed margins, and every
+//! page gets a footer once the page count is known.
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+
+use super::font::FontStyle;
+use super::image::LogoImage;
+use super::template::{DocumentSection, QuoteDocumentTemplate, ResolvedDocument};
+use super::writer::{number, GlyphUsage};
+use super::{PdfBranding, PdfTemplate};
+use crate::esign::{AcceptanceCertificate, SignatureCapture, CONSENT_STATEMENT};
+
+pub(crate) const PAGE_WIDTH: f32 = 595.28;
+pub(crate) const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 42.0;
+const FOOTER_HEIGHT: f32 = 44.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const CONTENT_BOTTOM: f32 = PAGE_HEIGHT - MARGIN - FOOTER_HEIGHT;
+pub(crate) const LOGO_RESOURCE: &str = "Logo";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rgb(f32, f32, f32);
+
+const TEXT: Rgb = Rgb(0.12, 0.16, 0.22);
+const MUTED: Rgb = Rgb(0.42, 0.45, 0.5);
+const RULE: Rgb = Rgb(0.85, 0.87, 0.9);
+const STRIPE: Rgb = Rgb(0.97, 0.98, 0.99);
+const WHITE: Rgb = Rgb(1.0, 1.0, 1.0);
+
+impl Rgb {
+    /// Parses `#rgb` / `#rrggbb`, falling back to `fallback` for anything else.
+    fn parse(value: &str, fallback: &str) -> Self {
+        Self::from_hex(value).or_else(|| Self::from_hex(fallback)).unwrap_or(TEXT)
+    }
+
+    fn from_hex(value: &str) -> Option<Self> {
+        let hex = value.trim().strip_prefix('#')?;
+        let expanded: String = match hex.len() {
+            3 => hex.chars().flat_map(|ch| [ch, ch]).collect(),
+            6 => hex.to_owned(),
+            _ => return None,
+        };
+        let channel = |index: usize| {
+            u8::from_str_radix(expanded.get(index..index + 2)?, 16)
+                .ok()
+                .map(|c| f32::from(c) / 255.0)
+        };
+        Some(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    fn operands(self) -> String {
+        format!("{} {} {}", number(self.0), number(self.1), number(self.2))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Font {
+    style: FontStyle,
+    size: f32,
+}
+
+impl Font {
+    const fn regular(size: f32) -> Self {
+        Self { style: FontStyle::Regular, size }
+    }
+
+    const fn bold(size: f32) -> Self {
+        Self { style: FontStyle::Bold, size }
+    }
+
+    fn width(self, text: &str) -> f32 {
+        self.style.font().text_width(text, self.size)
+    }
+
+    fn leading(self) -> f32 {
+        self.size * 1.35
+    }
+}
+
+/// Drawing surface collecting one content stream per page.
+pub(crate) struct Canvas {
+    pub(crate) pages: Vec<String>,
+    current: usize,
+    y: f32,
+    pub(crate) regular: GlyphUsage,
+    pub(crate) bold: GlyphUsage,
+    pub(crate) uses_logo: bool,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Self {
+            pages: vec![String::new()],
+            current: 0,
+            y: MARGIN,
+            regular: GlyphUsage::default(),
+            bold: GlyphUsage::default(),
+            uses_logo: false,
+        }
+    }
+
+    fn ops(&mut self) -> &mut String {
+        &mut self.pages[self.current]
+    }
+
+    fn fits(&self, height: f32) -> bool {
+        self.y + height <= CONTENT_BOTTOM
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(String::new());
+        self.current = self.pages.len() - 1;
+        self.y = MARGIN;
+    }
+
+    fn fill_rect(&mut self, x: f32, top: f32, width: f32, height: f32, color: Rgb) {
+        let op = format!(
+            "{} rg {} {} {} {} re f\n",
+            color.operands(),
+            number(x),
+            number(PAGE_HEIGHT - top - height),
+            number(width),
+            number(height)
+        );
+        self.ops().push_str(&op);
+    }
+
+    fn rule(&mut self, x: f32, top: f32, width: f32, thickness: f32, color: Rgb) {
+        self.fill_rect(x, top, width, thickness, color);
+    }
+
+    fn text(&mut self, x: f32, baseline: f32, font: Font, color: Rgb, text: &str) {
+        let ttf = font.style.font();
+        let usage = match font.style {
+            FontStyle::Regular => &mut self.regular,
+            FontStyle::Bold => &mut self.bold,
+        };
+        let mut glyphs = String::with_capacity(text.len() * 4);
+        for ch in text.chars() {
+            let ch = if ch.is_control() { ' ' } else { ch };
+            let glyph = ttf.glyph_id(ch);
+            usage.record(glyph, ch);
+            let _ = write!(glyphs, "{glyph:04X}");
+        }
+        if glyphs.is_empty() {
+            return;
+        }
+        let op = format!(
+            "BT /{} {} Tf {} rg {} {} Td <{glyphs}> Tj ET\n",
+            font.style.resource_name(),
+            number(font.size),
+            color.operands(),
+            number(x),
+            number(PAGE_HEIGHT - baseline)
+        );
+        self.ops().push_str(&op);
+    }
+
+    fn text_right(&mut self, right: f32, baseline: f32, font: Font, color: Rgb, text: &str) {
+        self.text(right - font.width(text), baseline, font, color, text);
+    }
+
+    fn image(&mut self, x: f32, top: f32, width: f32, height: f32) {
+        self.uses_logo = true;
+        let op = format!(
+            "q {} 0 0 {} {} {} cm /{LOGO_RESOURCE} Do Q\n",
+            number(width),
+            number(height),
+            number(x),
+            number(PAGE_HEIGHT - top - height)
+        );
+        self.ops().push_str(&op);
+    }
+
+    /// Strokes a polyline with round caps, so a single point leaves a dot.
+    fn polyline(&mut self, points: &[(f32, f32)], width: f32, color: Rgb) {
+        let Some(((first_x, first_y), rest)) = points.split_first() else {
+            return;
+        };
+        let mut op = format!(
+            "q {} RG {} w 1 J 1 j {} {} m",
+            color.operands(),
+            number(width),
+            number(*first_x),
+            number(PAGE_HEIGHT - first_y)
+        );
+        let tail = if rest.is_empty() { &points[..1] } else { rest };
+        for (x, y) in tail {
+            let _ = write!(op, " {} {} l", number(*x), number(PAGE_HEIGHT - y));
+        }
+        op.push_str(" S Q\n");
+        self.ops().push_str(&op);
+    }
+
+    /// Wrapped paragraph at the cursor, breaking pages between lines.
+    fn paragraph(&mut self, x: f32, width: f32, font: Font, color: Rgb, text: &str) {
+        for line in wrap(text, font, width) {
+            if !self.fits(font.leading()) {
+                self.new_page();
+            }
+            self.text(x, self.y + font.size, font, color, &line);
+            self.y += font.leading();
+        }
+    }
+}
+
+/// Greedy word wrap; words wider than `width` are broken between characters.
+fn wrap(text: &str, font: Font, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate =
+                if line.is_empty() { word.to_owned() } else { format!("{line} {word}") };
+            if font.width(&candidate) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for ch in word.chars() {
+                line.push(ch);
+                if font.width(&line) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, ch.to_string()));
+                }
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Quote fields the templates read, normalized from the JSON payload.
+struct QuoteView {
+    id: String,
+    status: String,
+    version: Option<String>,
+    created_at: Option<String>,
+    valid_until: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    currency: String,
+    currency_explicit: bool,
+    payment_terms: String,
+    billing_frequency: String,
+    billing_country: Option<String>,
+    term_months: Option<f64>,
+    valid_days: String,
+    notes: Option<String>,
+    account_name: String,
+    account_details: Vec<String>,
+    prepared_by: Option<String>,
+    lines: Vec<LineView>,
+    subtotal: f64,
+    discount_total: f64,
+    tax_rate: f64,
+    tax_total: f64,
+    total: f64,
+    assumptions: Vec<(String, String, Option<String>)>,
+    /// Installments of an order form: label, due date, amount.
+    billing_schedule: Vec<(String, Option<String>, f64)>,
+    signatories: Vec<SignatoryView>,
+}
+
+struct SignatoryView {
+    party: String,
+    name: String,
+    title: Option<String>,
+    email: Option<String>,
+    signed_at: Option<String>,
+}
+
+struct LineView {
+    name: String,
+    detail: Option<String>,
+    quantity: String,
+    unit_price: f64,
+    discount_pct: f64,
+    total: f64,
+}
+
+impl QuoteView {
+    fn from_payload(payload: &Value) -> Self {
+        let pricing = payload.get("pricing");
+        let price = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| pricing.and_then(|p| number_at(p, &[key])))
+                .or_else(|| keys.iter().find_map(|key| number_at(payload, &[key])))
+                .unwrap_or(0.0)
+        };
+        let lines: Vec<LineView> = payload
+            .get("lines")
+            .and_then(Value::as_array)
+            .map(|lines| lines.iter().map(LineView::from_payload).collect())
+            .unwrap_or_default();
+        let subtotal = price(&["subtotal"]);
+        let discount_total = price(&["discount_total", "total_discount"]);
+        let tax_total = price(&["tax_total", "tax"]);
+        let total = match price(&["total"]) {
+            total if total != 0.0 => total,
+            _ => subtotal - discount_total + tax_total,
+        };
+
+        let account = payload.get("account").unwrap_or(&Value::Null);
+        let account_details = ["industry", "domain", "region"]
+            .iter()
+            .filter_map(|key| string_at(account, &[key]))
+            .chain(string_at(payload, &["billing_country"]))
+            .collect();
+
+        let assumptions = payload
+            .get("assumptions")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| {
+                        let label = string_at(item, &["label"])?;
+                        let value = string_at(item, &["value"]).unwrap_or_default();
+                        Some((label, value, string_at(item, &["description"])))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let billing_schedule = payload
+            .get("billing_schedule")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let label = string_at(item, &["description"])
+                            .unwrap_or_else(|| format!("Installment {}", index + 1));
+                        let due = string_at(item, &["due_date"]).map(|d| format_date(&d));
+                        (label, due, number_at(item, &["amount"]).unwrap_or(0.0))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let signatories = payload
+            .get("signatories")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| {
+                        Some(SignatoryView {
+                            party: string_at(item, &["party"]).unwrap_or_default(),
+                            name: string_at(item, &["name"])?,
+                            title: string_at(item, &["title"]),
+                            email: string_at(item, &["email"]),
+                            signed_at: string_at(item, &["signed_at"]).map(|d| format_date(&d)),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let currency = string_at(payload, &["currency"]);
+        Self {
+            id: string_at(payload, &["id"])
+                .or_else(|| string_at(payload, &["quote_id"]))
+                .unwrap_or_else(|| "Quote".to_owned()),
+            status: string_at(payload, &["status"]).unwrap_or_else(|| "draft".to_owned()),
+            version: value_text(payload.get("version")),
+            created_at: string_at(payload, &["created_at"]).map(|d| format_date(&d)),
+            valid_until: string_at(payload, &["valid_until"]).map(|d| format_date(&d)),
+            start_date: string_at(payload, &["start_date"]).map(|d| format_date(&d)),
+            end_date: string_at(payload, &["end_date"]).map(|d| format_date(&d)),
+            currency_explicit: payload
+                .get("currency_explicit")
+                .and_then(Value::as_bool)
+                .unwrap_or(currency.is_some()),
+            currency: currency.unwrap_or_else(|| "USD".to_owned()),
+            payment_terms: string_at(payload, &["payment_terms"])
+                .unwrap_or_else(|| "Net 30".to_owned()),
+            billing_frequency: string_at(payload, &["billing_frequency"])
+                .unwrap_or_else(|| "Annual".to_owned()),
+            billing_country: string_at(payload, &["billing_country"]),
+            term_months: number_at(payload, &["term_months"]).filter(|months| *months > 0.0),
+            valid_days: value_text(payload.get("valid_days")).unwrap_or_else(|| "30".to_owned()),
+            notes: string_at(payload, &["notes"]),
+            account_name: string_at(account, &["name"])
+                .or_else(|| string_at(payload, &["account_id"]))
+                .unwrap_or_else(|| "Customer".to_owned()),
+            account_details,
+            prepared_by: string_at(payload, &["sales_rep", "name"]),
+            lines,
+            subtotal,
+            discount_total,
+            tax_rate: price(&["tax_rate"]),
+            tax_total,
+            total,
+            assumptions,
+            billing_schedule,
+            signatories,
+        }
+    }
+}
+
+impl LineView {
+    fn from_payload(line: &Value) -> Self {
+        let quantity = number_at(line, &["quantity"]).unwrap_or(0.0);
+        let unit_price = number_at(line, &["unit_price"]).unwrap_or(0.0);
+        let discount_pct = number_at(line, &["discount_pct"]).unwrap_or(0.0).clamp(0.0, 100.0);
+        let subtotal = number_at(line, &["subtotal"]).unwrap_or(unit_price * quantity);
+        let total = number_at(line, &["total_price"]).unwrap_or_else(|| {
+            number_at(line, &["discount_amount"])
+                .map(|discount| subtotal - discount)
+                .unwrap_or(subtotal * (1.0 - discount_pct / 100.0))
+        });
+        let detail = [string_at(line, &["product_sku"]), string_at(line, &["description"])]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        Self {
+            name: string_at(line, &["product_name"])
+                .or_else(|| string_at(line, &["product_id"]))
+                .unwrap_or_else(|| "Item".to_owned()),
+            detail: (!detail.is_empty()).then(|| detail.join(" — ")),
+            quantity: format_quantity(quantity),
+            unit_price,
+            discount_pct,
+            total,
+        }
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |current, key| current.get(key))
+}
+
+fn string_at(value: &Value, path: &[&str]) -> Option<String> {
+    value_text(lookup(value, path))
+}
+
+fn value_text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(text) => Some(text.trim().to_owned()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn number_at(value: &Value, path: &[&str]) -> Option<f64> {
+    match lookup(value, path)? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|number: &f64| number.is_finite())
+}
+
+/// `%B %d, %Y` for RFC 3339 timestamps and ISO dates; anything else is shown verbatim.
+fn format_date(raw: &str) -> String {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(raw) {
+        return timestamp.format("%B %d, %Y").to_string();
+    }
+    match NaiveDate::parse_from_str(raw.get(..10).unwrap_or(raw), "%Y-%m-%d") {
+        Ok(date) => date.format("%B %d, %Y").to_string(),
+        Err(_) => raw.to_owned(),
+    }
+}
+
+pub(crate) fn format_money(amount: f64, currency: &str) -> String {
+    let cents = (amount.abs() * 100.0).round() as u64;
+    let whole = (cents / 100).to_string();
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if amount < 0.0 && cents > 0 { "-" } else { "" };
+    let digits = format!("{grouped}.{:02}", cents % 100);
+    match currency.to_ascii_uppercase().as_str() {
+        "USD" | "$" => format!("{sign}${digits}"),
+        "EUR" => format!("{sign}€{digits}"),
+        "GBP" => format!("{sign}£{digits}"),
+        code => format!("{sign}{code} {digits}"),
+    }
+}
+
+fn format_quantity(quantity: f64) -> String {
+    if quantity.fract() == 0.0 {
+        format!("{quantity:.0}")
+    } else {
+        format!("{quantity:.2}").trim_end_matches('0').to_owned()
+    }
+}
+
+fn format_percent(value: f64) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    format!("{text}%")
+}
+
+fn title_case(status: &str) -> String {
+    status
+        .split(|ch: char| ch == '_' || ch.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Column {
+    title: &'static str,
+    width: f32,
+    numeric: bool,
+}
+
+struct Layout<'a> {
+    canvas: Canvas,
+    template: PdfTemplate,
+    branding: &'a PdfBranding,
+    logo: Option<&'a LogoImage>,
+    /// Document heading from the resolved template; the layout's own when `None`.
+    title: Option<String>,
+    quote: QuoteView,
+    primary: Rgb,
+    secondary: Rgb,
+    accent: Rgb,
+}
+
+/// Lays out every page of the quote, plus a closing certificate page for signed quotes, and
+/// returns the canvas ready for serialization.
+pub(crate) fn layout_quote(
+    payload: &Value,
+    template: PdfTemplate,
+    branding: &PdfBranding,
+    logo: Option<&LogoImage>,
+    certificate: Option<&AcceptanceCertificate>,
+) -> Canvas {
+    let defaults = PdfBranding::default();
+    let document = ResolvedDocument::from_payload(payload);
+    let mut layout = Layout {
+        canvas: Canvas::new(),
+        template,
+        branding,
+        logo,
+        title: document.as_ref().and_then(|document| document.title.clone()),
+        quote: QuoteView::from_payload(payload),
+        primary: Rgb::parse(&branding.primary_color, &defaults.primary_color),
+        secondary: Rgb::parse(&branding.secondary_color, &defaults.secondary_color),
+        accent: Rgb::parse(&branding.accent_color, &defaults.accent_color),
+    };
+
+    let sections = match document {
+        Some(document) => document.sections,
+        None => QuoteDocumentTemplate::builtin(template)
+            .resolve(template.as_str(), None, &Default::default(), &[])
+            .map(|document| document.sections)
+            .unwrap_or_default(),
+    };
+    match template {
+        PdfTemplate::Detailed => {
+            layout.banner_header();
+            layout.parties();
+            layout.line_items();
+            layout.totals();
+            layout.sections(&sections);
+        }
+        PdfTemplate::ExecutiveSummary => {
+            layout.banner_header();
+            layout.parties();
+            layout.summary_highlight();
+            layout.included_products();
+            layout.sections(&sections);
+            layout.whats_next();
+        }
+        PdfTemplate::Compact => {
+            layout.compact_header();
+            layout.line_items();
+            layout.totals();
+            layout.sections(&sections);
+        }
+    }
+    if let Some(certificate) = certificate {
+        layout.certificate_page(certificate);
+    }
+    layout.footers();
+    layout.canvas
+}
+
+impl Layout<'_> {
+    fn money(&self, amount: f64) -> String {
+        format_money(amount, &self.quote.currency)
+    }
+
+    fn body_font(&self) -> Font {
+        match self.template {
+            PdfTemplate::Compact => Font::regular(8.5),
+            _ => Font::regular(9.5),
+        }
+    }
+
+    /// Draws the logo (scaled into `max_width` × `max_height`) or the company name.
+    fn brand_mark(&mut self, x: f32, top: f32, max_width: f32, max_height: f32, color: Rgb) {
+        if let Some(logo) = self.logo {
+            let scale = (max_width / logo.width as f32).min(max_height / logo.height as f32);
+            let (width, height) = (logo.width as f32 * scale, logo.height as f32 * scale);
+            self.canvas.image(x, top + (max_height - height) / 2.0, width, height);
+        } else {
+            let name = self.branding.company_name.clone();
+            self.canvas.text(x, top + max_height / 2.0 + 7.0, Font::bold(20.0), color, &name);
+        }
+    }
+
+    fn banner_header(&mut self) {
+        const BAND: f32 = 92.0;
+        let right = PAGE_WIDTH - MARGIN;
+        self.canvas.fill_rect(0.0, 0.0, PAGE_WIDTH, BAND, self.primary);
+        self.brand_mark(MARGIN, 22.0, 220.0, 48.0, WHITE);
+
+        let title = match (&self.title, self.template) {
+            (Some(title), _) => title.to_uppercase(),
+            (None, PdfTemplate::ExecutiveSummary) => "EXECUTIVE SUMMARY".to_owned(),
+            (None, _) => "QUOTE".to_owned(),
+        };
+        self.canvas.text_right(right, 34.0, Font::bold(16.0), WHITE, &title);
+        let mut reference = self.quote.id.clone();
+        if let Some(version) = &self.quote.version {
+            let _ = write!(reference, "  ·  Version {version}");
+        }
+        self.canvas.text_right(right, 52.0, Font::regular(10.0), WHITE, &reference);
+        let mut meta = title_case(&self.quote.status);
+        if let Some(created) = &self.quote.created_at {
+            let _ = write!(meta, "  ·  Issued {created}");
+        }
+        self.canvas.text_right(right, 68.0, Font::regular(9.0), WHITE, &meta);
+
+        self.canvas.y = BAND + 18.0;
+        let contact: Vec<String> = [
+            Some(self.branding.company_name.clone()).filter(|_| self.logo.is_some()),
+            self.branding.company_address.clone(),
+            self.branding.company_email.clone(),
+            self.branding.company_phone.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !contact.is_empty() {
+            let font = Font::regular(8.5);
+            self.canvas.paragraph(MARGIN, CONTENT_WIDTH, font, MUTED, &contact.join("  ·  "));
+            self.canvas.y += 8.0;
+        }
+    }
+
+    fn compact_header(&mut self) {
+        let right = PAGE_WIDTH - MARGIN;
+        self.brand_mark(MARGIN, MARGIN, 180.0, 32.0, self.primary);
+        self.canvas.text_right(
+            right,
+            MARGIN + 12.0,
+            Font::bold(12.0),
+            self.primary,
+            &self.quote.id.clone(),
+        );
+        let mut meta = title_case(&self.quote.status);
+        if let Some(created) = &self.quote.created_at {
+            let _ = write!(meta, "  ·  {created}");
+        }
+        self.canvas.text_right(right, MARGIN + 26.0, Font::regular(8.5), MUTED, &meta);
+        self.canvas.rule(MARGIN, MARGIN + 40.0, CONTENT_WIDTH, 2.0, self.primary);
+        self.canvas.y = MARGIN + 56.0;
+
+        let heading = self.title.as_deref().map_or_else(|| "Quote".to_owned(), title_case);
+        let mut line = format!("{heading} for {}", self.quote.account_name);
+        if let Some(valid_until) = &self.quote.valid_until {
+            let _ = write!(line, "  ·  Valid until {valid_until}");
+        }
+        let _ = write!(line, "  ·  {}", self.quote.payment_terms);
+        self.canvas.paragraph(MARGIN, CONTENT_WIDTH, Font::bold(9.5), TEXT, &line);
+        self.canvas.y += 10.0;
+    }
+
+    fn section_label(&mut self, x: f32, label: &str) {
+        self.canvas.text(x, self.canvas.y + 8.0, Font::bold(8.0), self.secondary, label);
+    }
+
+    fn section_heading(&mut self, title: &str) {
+        let font = Font::bold(11.0);
+        if !self.canvas.fits(font.leading() + 30.0) {
+            self.canvas.new_page();
+            self.continuation_header();
+        }
+        self.canvas.y += 6.0;
+        self.canvas.text(MARGIN, self.canvas.y + font.size, font, self.secondary, title);
+        self.canvas.y += font.leading() + 2.0;
+        self.canvas.rule(MARGIN, self.canvas.y, CONTENT_WIDTH, 0.75, RULE);
+        self.canvas.y += 6.0;
+    }
+
+    fn parties(&mut self) {
+        let column = (CONTENT_WIDTH - 24.0) / 2.0;
+        let right_x = MARGIN + column + 24.0;
+        let top = self.canvas.y;
+        let body = self.body_font();
+
+        let bill_to = match self.template {
+            PdfTemplate::ExecutiveSummary => "PREPARED FOR",
+            _ => "BILL TO",
+        };
+        self.section_label(MARGIN, bill_to);
+        self.canvas.y += 16.0;
+        let name = self.quote.account_name.clone();
+        self.canvas.paragraph(MARGIN, column, Font::bold(11.0), TEXT, &name);
+        for detail in self.quote.account_details.clone() {
+            self.canvas.paragraph(MARGIN, column, body, MUTED, &detail);
+        }
+        let left_bottom = self.canvas.y;
+
+        self.canvas.y = top;
+        let details = match self.template {
+            PdfTemplate::ExecutiveSummary => "QUOTE DETAILS",
+            _ => "QUOTE INFORMATION",
+        };
+        self.section_label(right_x, details);
+        self.canvas.y += 16.0;
+        for (label, value) in self.quote_facts() {
+            let baseline = self.canvas.y + body.size;
+            self.canvas.text(right_x, baseline, body, MUTED, label);
+            self.canvas.text_right(right_x + column, baseline, Font::bold(body.size), TEXT, &value);
+            self.canvas.y += body.leading();
+        }
+        self.canvas.y = self.canvas.y.max(left_bottom) + 14.0;
+    }
+
+    fn quote_facts(&self) -> Vec<(&'static str, String)> {
+        let quote = &self.quote;
+        let assumed = |explicit: bool, value: &str| {
+            if explicit {
+                value.to_owned()
+            } else {
+                format!("{value} (assumed)")
+            }
+        };
+        let mut facts = Vec::new();
+        if self.template == PdfTemplate::Detailed {
+            facts.push(("Quote ID", quote.id.clone()));
+        }
+        if let Some(valid_until) = &quote.valid_until {
+            facts.push(("Valid Until", valid_until.clone()));
+        }
+        facts.push(("Currency", assumed(quote.currency_explicit, &quote.currency)));
+        facts.push(("Payment Terms", quote.payment_terms.clone()));
+        if self.template == PdfTemplate::Detailed {
+            if let Some(months) = quote.term_months {
+                facts.push(("Term", format!("{} months", format_quantity(months))));
+            }
+            facts.push(("Billing", quote.billing_frequency.clone()));
+            if let Some(start) = &quote.start_date {
+                facts.push(("Start Date", start.clone()));
+            }
+            if let Some(end) = &quote.end_date {
+                facts.push(("End Date", end.clone()));
+            }
+            if let Some(rep) = &quote.prepared_by {
+                facts.push(("Prepared By", rep.clone()));
+            }
+        }
+        facts
+    }
+
+    fn columns(&self) -> Vec<Column> {
+        let fixed: &[(&'static str, f32)] = match self.template {
+            PdfTemplate::Compact => &[("Qty", 40.0), ("Price", 80.0), ("Total", 86.0)],
+            _ => &[("Qty", 40.0), ("Unit Price", 78.0), ("Discount", 56.0), ("Total", 86.0)],
+        };
+        let fixed_width: f32 = fixed.iter().map(|(_, width)| width).sum();
+        let mut columns = vec![Column {
+            title: if self.template == PdfTemplate::Compact { "Item" } else { "Product" },
+            width: CONTENT_WIDTH - fixed_width,
+            numeric: false,
+        }];
+        columns.extend(fixed.iter().map(|(title, width)| Column {
+            title,
+            width: *width,
+            numeric: true,
+        }));
+        columns
+    }
+
+    fn table_header(&mut self, columns: &[Column]) {
+        let font = Font::bold(self.body_font().size - 0.5);
+        let height = font.size + 10.0;
+        self.canvas.fill_rect(MARGIN, self.canvas.y, CONTENT_WIDTH, height, self.secondary);
+        let baseline = self.canvas.y + 5.0 + font.size * 0.85;
+        let mut x = MARGIN;
+        for column in columns {
+            if column.numeric {
+                self.canvas.text_right(x + column.width - 6.0, baseline, font, WHITE, column.title);
+            } else {
+                self.canvas.text(x + 6.0, baseline, font, WHITE, column.title);
+            }
+            x += column.width;
+        }
+        self.canvas.y += height;
+    }
+
+    fn continuation_header(&mut self) {
+        let label =
+            format!("{}  ·  Quote {} (continued)", self.branding.company_name, self.quote.id);
+        self.canvas.text(MARGIN, self.canvas.y + 8.0, Font::regular(8.0), MUTED, &label);
+        self.canvas.y += 14.0;
+        self.canvas.rule(MARGIN, self.canvas.y, CONTENT_WIDTH, 1.0, self.primary);
+        self.canvas.y += 10.0;
+    }
+
+    fn line_items(&mut self) {
+        if self.template != PdfTemplate::Compact {
+            self.section_heading("Line Items");
+        }
+        let columns = self.columns();
+        let body = self.body_font();
+        let detail_font = Font::regular(body.size - 1.5);
+        let name_font = Font::bold(body.size);
+        let name_width = columns[0].width - 12.0;
+        if !self.canvas.fits(body.size + 10.0 + 2.0 * body.leading()) {
+            self.canvas.new_page();
+            self.continuation_header();
+        }
+        self.table_header(&columns);
+
+        if self.quote.lines.is_empty() {
+            self.canvas.y += 6.0;
+            self.canvas.paragraph(MARGIN + 6.0, name_width, body, MUTED, "No line items.");
+        }
+
+        for index in 0..self.quote.lines.len() {
+            let line = &self.quote.lines[index];
+            let name = wrap(&line.name, name_font, name_width);
+            let detail = match (&line.detail, self.template) {
+                (Some(detail), PdfTemplate::Detailed) => wrap(detail, detail_font, name_width),
+                _ => Vec::new(),
+            };
+            let mut values = vec![line.quantity.clone(), self.money(line.unit_price)];
+            if self.template != PdfTemplate::Compact {
+                values.push(if line.discount_pct > 0.0 {
+                    format_percent(line.discount_pct)
+                } else {
+                    "—".to_owned()
+                });
+            }
+            values.push(self.money(line.total));
+
+            let height = name.len() as f32 * name_font.leading()
+                + detail.len() as f32 * detail_font.leading()
+                + 10.0;
+            if !self.canvas.fits(height) {
+                self.canvas.new_page();
+                self.continuation_header();
+                self.table_header(&columns);
+            }
+            let top = self.canvas.y;
+            if index % 2 == 1 {
+                self.canvas.fill_rect(MARGIN, top, CONTENT_WIDTH, height, STRIPE);
+            }
+            let mut baseline = top + 5.0 + body.size;
+            for text in &name {
+                self.canvas.text(MARGIN + 6.0, baseline, name_font, TEXT, text);
+                baseline += name_font.leading();
+            }
+            for text in &detail {
+                self.canvas.text(MARGIN + 6.0, baseline - 1.0, detail_font, MUTED, text);
+                baseline += detail_font.leading();
+            }
+            let mut x = MARGIN + columns[0].width;
+            for (column, value) in columns[1..].iter().zip(&values) {
+                x += column.width;
+                self.canvas.text_right(x - 6.0, top + 5.0 + body.size, body, TEXT, value);
+            }
+            self.canvas.y = top + height;
+            self.canvas.rule(MARGIN, self.canvas.y - 0.5, CONTENT_WIDTH, 0.5, RULE);
+        }
+        self.canvas.y += 12.0;
+    }
+
+    fn totals(&mut self) {
+        let body = self.body_font();
+        let mut rows = vec![("Subtotal".to_owned(), self.money(self.quote.subtotal))];
+        if self.quote.discount_total > 0.0 {
+            rows.push((
+                "Discount".to_owned(),
+                format!("-{}", self.money(self.quote.discount_total)),
+            ));
+        }
+        if self.quote.tax_total > 0.0 {
+            let rate = format_percent(self.quote.tax_rate * 100.0);
+            rows.push((format!("Tax ({rate})"), self.money(self.quote.tax_total)));
+        }
+        let total_font = Font::bold(body.size + 3.0);
+        let height = rows.len() as f32 * body.leading() + total_font.leading() + 22.0;
+        if !self.canvas.fits(height) {
+            self.canvas.new_page();
+            self.continuation_header();
+        }
+
+        let width = 230.0;
+        let x = PAGE_WIDTH - MARGIN - width;
+        let right = PAGE_WIDTH - MARGIN - 10.0;
+        let top = self.canvas.y;
+        self.canvas.fill_rect(x, top, width, height, STRIPE);
+        self.canvas.y += 8.0;
+        for (label, value) in rows {
+            let baseline = self.canvas.y + body.size;
+            self.canvas.text(x + 10.0, baseline, body, MUTED, &label);
+            self.canvas.text_right(right, baseline, body, TEXT, &value);
+            self.canvas.y += body.leading();
+        }
+        self.canvas.y += 3.0;
+        self.canvas.rule(x + 10.0, self.canvas.y, width - 20.0, 1.0, self.accent);
+        self.canvas.y += 5.0;
+        let baseline = self.canvas.y + total_font.size;
+        self.canvas.text(x + 10.0, baseline, total_font, TEXT, "Total");
+        let total = self.money(self.quote.total);
+        self.canvas.text_right(right, baseline, total_font, self.accent, &total);
+        self.canvas.y = top + height + 16.0;
+    }
+
+    fn summary_highlight(&mut self) {
+        let height = 84.0;
+        if !self.canvas.fits(height + 12.0) {
+            self.canvas.new_page();
+            self.continuation_header();
+        }
+        let top = self.canvas.y;
+        self.canvas.fill_rect(MARGIN, top, CONTENT_WIDTH, height, STRIPE);
+        self.canvas.rule(MARGIN, top, 4.0, height, self.accent);
+        self.canvas.text(
+            MARGIN + 18.0,
+            top + 20.0,
+            Font::bold(8.0),
+            self.secondary,
+            "TOTAL CONTRACT VALUE",
+        );
+        let total = self.money(self.quote.total);
+        self.canvas.text(MARGIN + 18.0, top + 48.0, Font::bold(24.0), self.accent, &total);
+        let mut term = match self.quote.term_months {
+            Some(months) => format!("{} month term", format_quantity(months)),
+            None => self.quote.billing_frequency.clone(),
+        };
+        if let Some(start) = &self.quote.start_date {
+            let _ = write!(term, " starting {start}");
+        }
+        self.canvas.text(MARGIN + 18.0, top + 68.0, Font::regular(9.0), MUTED, &term);
+
+        let mut metrics = vec![
+            (self.quote.lines.len().to_string(), "Products"),
+            (self.money(self.quote.discount_total), "Discount Applied"),
+        ];
+        if let Some(months) = self.quote.term_months {
+            metrics.push((format!("{}/mo", self.money(self.quote.total / months)), "Monthly"));
+        }
+        let mut right = PAGE_WIDTH - MARGIN - 18.0;
+        for (value, label) in metrics.iter().rev() {
+            let width = Font::bold(13.0).width(value).max(Font::regular(8.0).width(label));
+            self.canvas.text_right(right, top + 40.0, Font::bold(13.0), TEXT, value);
+            self.canvas.text_right(right, top + 56.0, Font::regular(8.0), MUTED, label);
+            right -= width + 24.0;
+        }
+        self.canvas.y = top + height + 16.0;
+    }
+
+    fn included_products(&mut self) {
+        self.section_heading("Included Products");
+        let body = self.body_font();
+        let shown = self.quote.lines.len().min(5);
+        for index in 0..shown {
+            let line = &self.quote.lines[index];
+            let text = format!("{} × {}", line.quantity, line.name);
+            self.canvas.paragraph(
+                MARGIN + 6.0,
+                CONTENT_WIDTH - 12.0,
+                body,
+                TEXT,
+                &format!("•  {text}"),
+            );
+        }
+        if self.quote.lines.len() > shown {
+            let more = format!("+{} more", self.quote.lines.len() - shown);
+            self.canvas.paragraph(MARGIN + 6.0, CONTENT_WIDTH - 12.0, body, MUTED, &more);
+        }
+        if self.quote.lines.is_empty() {
+            self.canvas.paragraph(
+                MARGIN + 6.0,
+                CONTENT_WIDTH - 12.0,
+                body,
+                MUTED,
+                "No products yet.",
+            );
+        }
+        self.canvas.y += 10.0;
+    }
+
+    fn sections(&mut self, sections: &[DocumentSection]) {
+        for section in sections {
+            match section {
+                DocumentSection::Assumptions => self.assumptions(),
+                DocumentSection::Notes => self.notes(),
+                DocumentSection::Terms => self.terms(),
+                DocumentSection::BillingSchedule => self.billing_schedule(),
+                DocumentSection::Signatures => self.signatures(),
+                DocumentSection::Clause { title, body, .. } => self.clause(title, body),
+            }
+        }
+    }
+
+    fn clause(&mut self, title: &str, body: &str) {
+        self.section_heading(title);
+        let font = self.body_font();
+        for paragraph in body.split("\n\n").map(str::trim).filter(|text| !text.is_empty()) {
+            self.canvas.paragraph(MARGIN, CONTENT_WIDTH, font, TEXT, &paragraph.replace('\n', " "));
+            self.canvas.y += 4.0;
+        }
+        self.canvas.y += 6.0;
+    }
+
+    fn billing_schedule(&mut self) {
+        if self.quote.billing_schedule.is_empty() {
+            return;
+        }
+        self.section_heading("Billing Schedule");
+        let body = self.body_font();
+        let right = PAGE_WIDTH - MARGIN - 6.0;
+        for (label, due, amount) in self.quote.billing_schedule.clone() {
+            if !self.canvas.fits(body.leading() + 4.0) {
+                self.canvas.new_page();
+                self.continuation_header();
+            }
+            let baseline = self.canvas.y + body.size;
+            self.canvas.text(MARGIN + 6.0, baseline, body, TEXT, &label);
+            if let Some(due) = due {
+                self.canvas.text(MARGIN + CONTENT_WIDTH * 0.5, baseline, body, MUTED, &due);
+            }
+            let amount = self.money(amount);
+            self.canvas.text_right(right, baseline, Font::bold(body.size), TEXT, &amount);
+            self.canvas.y += body.leading() + 2.0;
+            self.canvas.rule(MARGIN, self.canvas.y, CONTENT_WIDTH, 0.5, RULE);
+            self.canvas.y += 2.0;
+        }
+        self.canvas.y += 10.0;
+    }
+
+    fn signatures(&mut self) {
+        const BLOCK_HEIGHT: f32 = 96.0;
+        if self.quote.signatories.is_empty() {
+            return;
+        }
+        self.section_heading("Signatures");
+        let column = (CONTENT_WIDTH - 24.0) / 2.0;
+        let body = self.body_font();
+        let small = Font::regular(8.0);
+        let signatories = std::mem::take(&mut self.quote.signatories);
+        for pair in signatories.chunks(2) {
+            if !self.canvas.fits(BLOCK_HEIGHT) {
+                self.canvas.new_page();
+                self.continuation_header();
+            }
+            let top = self.canvas.y;
+            for (index, signatory) in pair.iter().enumerate() {
+                let x = MARGIN + index as f32 * (column + 24.0);
+                let label = match signatory.party.as_str() {
+                    "provider" => self.branding.company_name.to_uppercase(),
+                    "" => "SIGNATORY".to_owned(),
+                    party => party.to_uppercase(),
+                };
+                self.canvas.text(x, top + 8.0, Font::bold(8.0), self.secondary, &label);
+                self.canvas.rule(x, top + 44.0, column, 0.75, MUTED);
+                let mut baseline = top + 44.0 + body.size + 4.0;
+                self.canvas.text(x, baseline, Font::bold(body.size), TEXT, &signatory.name);
+                for detail in [&signatory.title, &signatory.email].into_iter().flatten() {
+                    baseline += small.leading();
+                    self.canvas.text(x, baseline, small, MUTED, detail);
+                }
+                let date = match &signatory.signed_at {
+                    Some(signed_at) => format!("Signed {signed_at}"),
+                    None => "Date: ____________________".to_owned(),
+                };
+                self.canvas.text(x, baseline + small.leading(), small, MUTED, &date);
+            }
+            self.canvas.y = top + BLOCK_HEIGHT;
+        }
+        self.quote.signatories = signatories;
+        self.canvas.y += 6.0;
+    }
+
+    fn assumptions(&mut self) {
+        if self.quote.assumptions.is_empty() {
+            return;
+        }
+        self.section_heading("Assumptions Made");
+        let body = self.body_font();
+        let intro = "The following values were assumed and may need confirmation:";
+        self.canvas.paragraph(MARGIN, CONTENT_WIDTH, body, MUTED, intro);
+        for (label, value, description) in self.quote.assumptions.clone() {
+            let mut text = format!("•  {label}: {value} (assumed)");
+            if let Some(description) =
+                description.filter(|_| self.template == PdfTemplate::Detailed)
+            {
+                let _ = write!(text, " — {description}");
+            }
+            self.canvas.paragraph(MARGIN + 6.0, CONTENT_WIDTH - 6.0, body, TEXT, &text);
+        }
+        self.canvas.y += 10.0;
+    }
+
+    fn notes(&mut self) {
+        let Some(notes) = self.quote.notes.clone() else {
+            return;
+        };
+        self.section_heading("Notes");
+        self.canvas.paragraph(MARGIN, CONTENT_WIDTH, self.body_font(), TEXT, &notes);
+        self.canvas.y += 10.0;
+    }
+
+    fn terms(&mut self) {
+        let quote = &self.quote;
+        let assumed = |explicit: bool| if explicit { "" } else { " (assumed)" };
+        let taxes = match &quote.billing_country {
+            Some(country) => format!("exclude applicable taxes for {country}"),
+            None => "exclude applicable taxes".to_owned(),
+        };
+        let mut items = vec![
+            format!(
+                "Quote valid for {} days from issue date unless otherwise specified.",
+                quote.valid_days
+            ),
+            format!("Payment terms: {} from invoice date.", quote.payment_terms),
+            format!(
+                "Prices are in {}{} and {taxes}.",
+                quote.currency,
+                assumed(quote.currency_explicit)
+            ),
+        ];
+        if self.template == PdfTemplate::Detailed {
+            items.push(
+                "Service commencement is subject to signed agreement and initial payment."
+                    .to_owned(),
+            );
+            items.push("Any changes to this quote may result in price adjustments.".to_owned());
+        }
+
+        let body = self.body_font();
+        match self.template {
+            PdfTemplate::Compact => self.canvas.y += 4.0,
+            _ => self.section_heading("Terms & Conditions"),
+        }
+        for item in items {
+            self.canvas.paragraph(
+                MARGIN + 6.0,
+                CONTENT_WIDTH - 6.0,
+                body,
+                TEXT,
+                &format!("•  {item}"),
+            );
+        }
+        if let Some(footer) = self.branding.terms_footer.clone() {
+            self.canvas.y += 6.0;
+            self.canvas.paragraph(MARGIN, CONTENT_WIDTH, body, MUTED, &footer);
+        }
+    }
+
+    fn whats_next(&mut self) {
+        self.section_heading("What's Next");
+        let body = self.body_font();
+        let mut paragraphs = vec![
+            "This executive summary provides a high-level overview of your quote. A detailed \
+             breakdown with full line items and terms is available upon request."
+                .to_owned(),
+            "To accept this quote, please sign and return the detailed quote document or contact \
+             your sales representative."
+                .to_owned(),
+        ];
+        if let Some(valid_until) = &self.quote.valid_until {
+            paragraphs.push(format!(
+                "This quote is valid until {valid_until}. Prices and availability subject to \
+                 change thereafter."
+            ));
+        }
+        for paragraph in paragraphs {
+            self.canvas.paragraph(MARGIN, CONTENT_WIDTH, body, TEXT, &paragraph);
+            self.canvas.y += 4.0;
+        }
+    }
+
+    fn certificate_page(&mut self, certificate: &AcceptanceCertificate) {
+        const LABEL_WIDTH: f32 = 132.0;
+        const PAD_WIDTH: f32 = 260.0;
+        const PAD_HEIGHT: f32 = 86.0;
+
+        self.canvas.new_page();
+        let title = Font::bold(16.0);
+        self.canvas.text(
+            MARGIN,
+            self.canvas.y + title.size,
+            title,
+            self.primary,
+            "Certificate of Acceptance",
+        );
+        self.canvas.y += title.leading() + 4.0;
+        let body = Font::regular(9.5);
+        let intro = format!(
+            "{} accepted quote {} (version {}) electronically. The details below were captured \
+             when the quote was signed.",
+            certificate.signer.name, certificate.quote_id, certificate.quote_version
+        );
+        self.canvas.paragraph(MARGIN, CONTENT_WIDTH, body, TEXT, &intro);
+        self.canvas.y += 4.0;
+
+        let evidence = &certificate.evidence;
+        let timestamp = |at: &DateTime<Utc>| at.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+        let rows = [
+            ("Signer", certificate.signer.name.clone()),
+            ("Title", certificate.signer.title.clone()),
+            ("Email", certificate.signer.email.clone()),
+            (
+                "Email verification",
+                format!(
+                    "One-time code sent {} and confirmed {}",
+                    timestamp(&evidence.code_sent_at),
+                    timestamp(&evidence.signed_at)
+                ),
+            ),
+            ("Signed at", timestamp(&evidence.signed_at)),
+            ("Signing IP address", evidence.signed_ip.clone()),
+            ("Signing browser", evidence.signed_user_agent.clone()),
+            ("Code requested from", evidence.requested_ip.clone()),
+            ("Signature method", title_case(certificate.signature.kind())),
+            ("Quote", format!("{} (version {})", certificate.quote_id, certificate.quote_version)),
+            ("Document SHA-256", certificate.document_sha256.clone()),
+            ("Signature request", certificate.request_id.clone()),
+        ];
+        self.section_heading("Signing Evidence");
+        let label_font = Font::bold(8.5);
+        let value_font = Font::regular(8.5);
+        for (label, value) in rows {
+            let lines = wrap(&value, value_font, CONTENT_WIDTH - LABEL_WIDTH);
+            let top = self.canvas.y;
+            self.canvas.text(MARGIN, top + label_font.size, label_font, MUTED, label);
+            for (index, line) in lines.iter().enumerate() {
+                let baseline = top + value_font.size + index as f32 * value_font.leading();
+                self.canvas.text(MARGIN + LABEL_WIDTH, baseline, value_font, TEXT, line);
+            }
+            self.canvas.y += value_font.leading() * lines.len().max(1) as f32 + 3.0;
+        }
+
+        self.section_heading("Signature");
+        let top = self.canvas.y;
+        self.canvas.fill_rect(MARGIN, top, PAD_WIDTH, PAD_HEIGHT, STRIPE);
+        match &certificate.signature {
+            SignatureCapture::Typed { text } => {
+                let font = Font::bold(22.0);
+                let baseline = top + PAD_HEIGHT / 2.0 + font.size / 3.0;
+                self.canvas.text(MARGIN + 12.0, baseline, font, TEXT, text.trim());
+            }
+            SignatureCapture::Drawn { strokes } => {
+                for stroke in strokes {
+                    let points: Vec<(f32, f32)> = stroke
+                        .iter()
+                        .map(|[x, y]| {
+                            (
+                                MARGIN + 6.0 + x * (PAD_WIDTH - 12.0),
+                                top + 6.0 + y * (PAD_HEIGHT - 12.0),
+                            )
+                        })
+                        .collect();
+                    self.canvas.polyline(&points, 1.4, TEXT);
+                }
+            }
+        }
+        self.canvas.y = top + PAD_HEIGHT;
+        self.canvas.rule(MARGIN, self.canvas.y, PAD_WIDTH, 0.75, MUTED);
+        self.canvas.y += 4.0;
+        let caption = format!("{}, {}", certificate.signer.name, certificate.signer.title);
+        self.canvas.paragraph(MARGIN, PAD_WIDTH, Font::regular(8.0), MUTED, &caption);
+        self.canvas.y += 10.0;
+
+        self.canvas.paragraph(MARGIN, CONTENT_WIDTH, body, TEXT, CONSENT_STATEMENT);
+        self.canvas.y += 6.0;
+        self.canvas.paragraph(
+            MARGIN,
+            CONTENT_WIDTH,
+            Font::regular(8.0),
+            MUTED,
+            "The document SHA-256 identifies the quote pages exactly as they were shown to the \
+             signer. The SHA-256 of this signed file is recorded in the quote ledger.",
+        );
+    }
+
+    fn footers(&mut self) {
+        let count = self.canvas.pages.len();
+        let contact = format!(
+            "Questions? Contact {} at {}.",
+            self.branding.support_contact_name, self.branding.support_contact_email
+        );
+        let font = Font::regular(7.5);
+        let top = PAGE_HEIGHT - MARGIN - FOOTER_HEIGHT + 14.0;
+        for page in 0..count {
+            self.canvas.current = page;
+            self.canvas.rule(MARGIN, top, CONTENT_WIDTH, 0.5, RULE);
+            self.canvas.text(MARGIN, top + 14.0, font, MUTED, &contact);
+            let label = format!("Page {} of {count}", page + 1);
+            self.canvas.text_right(PAGE_WIDTH - MARGIN, top + 14.0, font, MUTED, &label);
+            if !self.branding.white_label {
+                self.canvas.text(MARGIN, top + 25.0, font, MUTED, "Generated by Quotey");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn money_uses_symbols_and_thousands_separators() {
+        assert_eq!(format_money(1_234_567.891, "USD"), "$1,234,567.89");
+        assert_eq!(format_money(-50.0, "eur"), "-€50.00");
+        assert_eq!(format_money(999.995, "GBP"), "£1,000.00");
+        assert_eq!(format_money(12.5, "JPY"), "JPY 12.50");
+        assert_eq!(format_money(-0.001, "USD"), "$0.00");
+    }
+
+    #[test]
+    fn dates_render_long_form_and_pass_through_unparseable_values() {
+        assert_eq!(format_date("2024-01-15T10:00:00Z"), "January 15, 2024");
+        assert_eq!(format_date("2024-02-05"), "February 05, 2024");
+        assert_eq!(format_date("end of quarter"), "end of quarter");
+    }
+
+    #[test]
+    fn wrap_breaks_on_words_and_splits_overlong_tokens() {
+        let font = Font::regular(10.0);
+        let lines = wrap("Enterprise platform license with premium support", font, 120.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| font.width(line) <= 120.0));
+
+        let long = wrap(&"X".repeat(60), font, 100.0);
+        assert!(long.len() > 1);
+        assert_eq!(long.concat(), "X".repeat(60));
+    }
+}
@@ -0,0 +1,412 @@
+//! In-process PDF rendering for customer-facing quote documents.
+//!
+//! Lays out the `detailed`, `executive_summary` and `compact` quote templates directly to PDF,
+//! embedding subsetted DejaVu Sans fonts (see `assets/fonts/LICENSE`) so no external converter
+//! is needed. Output contains no timestamps or random identifiers: rendering the same payload
+//! with the same template and branding yields byte-identical documents, so the SHA-256 of a
+//! rendered quote version can be recorded in the ledger.
+//!
+//! Signed quotes ([`render_signed_quote_pdf`]) are the same pages followed by a certificate page
+//! carrying the signer, the signature and the evidence captured by [`crate::esign`].
+//!
+//! The optional sections after the pricing (assumptions, notes, terms and clause blocks) follow
+//! the [`template::ResolvedDocument`] stored in the payload, when there is one.
+
+mod font;
+mod image;
+mod layout;
+pub mod template;
+mod writer;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use self::font::FontStyle;
+use self::image::LogoImage;
+use self::layout::{layout_quote, LOGO_RESOURCE, PAGE_HEIGHT, PAGE_WIDTH};
+use self::writer::{number, write_font, ObjectWriter};
+use crate::esign::AcceptanceCertificate;
+
+#[derive(Debug, Error)]
+pub enum PdfRenderError {
+    #[error("embedded font error: {0}")]
+    Font(String),
+    #[error("layout error: {0}")]
+    Layout(String),
+}
+
+/// Quote document layouts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfTemplate {
+    Detailed,
+    ExecutiveSummary,
+    Compact,
+}
+
+impl PdfTemplate {
+    pub const ALL: [Self; 3] = [Self::Detailed, Self::ExecutiveSummary, Self::Compact];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Detailed => "detailed",
+            Self::ExecutiveSummary => "executive_summary",
+            Self::Compact => "compact",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|template| template.as_str() == name.trim())
+    }
+}
+
+/// Branding applied to a rendered quote.
+///
+/// Colours are CSS hex strings (`#rgb` or `#rrggbb`); invalid values fall back to the defaults.
+/// `company_logo` is only drawn when it is a base64 `data:` URI holding a JPEG or an opaque PNG;
+/// otherwise the company name is shown instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfBranding {
+    pub company_name: String,
+    pub company_logo: Option<String>,
+    pub company_address: Option<String>,
+    pub company_email: Option<String>,
+    pub company_phone: Option<String>,
+    pub primary_color: String,
+    pub secondary_color: String,
+    pub accent_color: String,
+    pub support_contact_name: String,
+    pub support_contact_email: String,
+    pub terms_footer: Option<String>,
+    pub white_label: bool,
+}
+
+impl Default for PdfBranding {
+    fn default() -> Self {
+        Self {
+            company_name: "Quotey".to_owned(),
+            company_logo: None,
+            company_address: None,
+            company_email: None,
+            company_phone: None,
+            primary_color: "#2563eb".to_owned(),
+            secondary_color: "#1e40af".to_owned(),
+            accent_color: "#3b82f6".to_owned(),
+            support_contact_name: "your sales representative".to_owned(),
+            support_contact_email: "sales@example.com".to_owned(),
+            terms_footer: None,
+            white_label: false,
+        }
+    }
+}
+
+impl PdfBranding {
+    /// Resolves branding from a quote payload, preferring a nested `branding` object over
+    /// top-level keys and falling back to the sales rep for support contact details.
+    pub fn from_payload(payload: &Value) -> Self {
+        let nested = payload.get("branding");
+        let read = |key: &str| {
+            nested
+                .and_then(|value| value.get(key))
+                .or_else(|| payload.get(key))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        };
+        let sales_rep = |key: &str| {
+            payload
+                .get("sales_rep")
+                .and_then(|rep| rep.get(key))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        };
+
+        let defaults = Self::default();
+        let company_email = read("company_email");
+        let support_email = read("support_email")
+            .or_else(|| read("contact_email"))
+            .or_else(|| company_email.clone());
+        Self {
+            company_name: read("company_name").unwrap_or(defaults.company_name),
+            company_logo: read("company_logo"),
+            company_address: read("company_address"),
+            company_phone: read("company_phone"),
+            primary_color: read("primary_color").unwrap_or(defaults.primary_color),
+            secondary_color: read("secondary_color").unwrap_or(defaults.secondary_color),
+            accent_color: read("accent_color").unwrap_or(defaults.accent_color),
+            support_contact_name: read("sender_name")
+                .or_else(|| read("contact_name"))
+                .or_else(|| sales_rep("name"))
+                .unwrap_or(defaults.support_contact_name),
+            support_contact_email: support_email
+                .or_else(|| sales_rep("email"))
+                .unwrap_or(defaults.support_contact_email),
+            terms_footer: read("terms_footer")
+                .or_else(|| read("custom_terms_footer"))
+                .or_else(|| read("footer_text")),
+            white_label: nested
+                .and_then(|value| value.get("white_label"))
+                .or_else(|| payload.get("white_label"))
+                .and_then(Value::as_bool)
+                .unwrap_or(defaults.white_label),
+            company_email,
+        }
+    }
+}
+
+/// Renders a quote payload (the JSON shape the HTML quote templates consume) to PDF bytes.
+pub fn render_quote_pdf(
+    payload: &Value,
+    template: PdfTemplate,
+    branding: &PdfBranding,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render(payload, template, branding, None)
+}
+
+/// Renders the quote followed by a certificate page recording its acceptance.
+pub fn render_signed_quote_pdf(
+    payload: &Value,
+    template: PdfTemplate,
+    branding: &PdfBranding,
+    certificate: &AcceptanceCertificate,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render(payload, template, branding, Some(certificate))
+}
+
+fn render(
+    payload: &Value,
+    template: PdfTemplate,
+    branding: &PdfBranding,
+    certificate: Option<&AcceptanceCertificate>,
+) -> Result<Vec<u8>, PdfRenderError> {
+    let logo = branding.company_logo.as_deref().and_then(LogoImage::from_data_uri);
+    let canvas = layout_quote(payload, template, branding, logo.as_ref(), certificate);
+
+    let mut writer = ObjectWriter::default();
+    let pages_id = writer.reserve();
+
+    let mut fonts = String::new();
+    for (style, usage) in [(FontStyle::Regular, &canvas.regular), (FontStyle::Bold, &canvas.bold)] {
+        if !usage.is_empty() {
+            let id = write_font(&mut writer, style, usage)?;
+            fonts.push_str(&format!("/{} {id} 0 R ", style.resource_name()));
+        }
+    }
+    let mut resources = format!("<< /Font << {}>>", fonts);
+    if let Some(logo) = logo.as_ref().filter(|_| canvas.uses_logo) {
+        let id = logo.write(&mut writer);
+        resources.push_str(&format!(" /XObject << /{LOGO_RESOURCE} {id} 0 R >>"));
+    }
+    resources.push_str(" >>");
+
+    let media_box = format!("[0 0 {} {}]", number(PAGE_WIDTH), number(PAGE_HEIGHT));
+    let mut kids = Vec::with_capacity(canvas.pages.len());
+    for content in &canvas.pages {
+        let stream = writer.add_stream("", content.as_bytes());
+        kids.push(format!(
+            "{} 0 R",
+            writer.add(format!(
+                "<< /Type /Page /Parent {pages_id} 0 R /MediaBox {media_box} \
+                 /Resources {resources} /Contents {stream} 0 R >>"
+            ))
+        ));
+    }
+    writer.set(
+        pages_id,
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len()),
+    );
+    let catalog = writer.add(format!("<< /Type /Catalog /Pages {pages_id} 0 R >>"));
+    writer.finish(catalog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(lines: usize) -> Value {
+        let lines: Vec<Value> = (0..lines)
+            .map(|index| {
+                serde_json::json!({
+                    "product_name": format!("Platform Seat {index}"),
+                    "product_sku": format!("SKU-{index:03}"),
+                    "description": "Annual subscription including standard support and onboarding",
+                    "quantity": 10,
+                    "unit_price": 1250.0,
+                    "discount_pct": 10.0,
+                    "discount_amount": 1250.0,
+                    "subtotal": 12500.0,
+                    "total_price": 11250.0,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "id": "Q-2026-0042",
+            "version": 3,
+            "status": "approved",
+            "created_at": "2026-03-02T09:30:00Z",
+            "valid_until": "2026-04-01",
+            "currency": "EUR",
+            "term_months": 12,
+            "account": {"name": "Northwind Traders", "industry": "Logistics"},
+            "sales_rep": {"name": "Dana Reyes", "email": "dana@quotey.example"},
+            "lines": lines,
+            "pricing": {"subtotal": 25000.0, "discount_total": 2500.0, "tax_total": 0.0, "total": 22500.0},
+            "notes": "Pricing honours the 2025 framework agreement.",
+        })
+    }
+
+    fn count(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack.windows(needle.len()).filter(|window| *window == needle).count()
+    }
+
+    #[test]
+    fn rendering_is_byte_for_byte_deterministic() {
+        let payload = payload(2);
+        let branding = PdfBranding::from_payload(&payload);
+        let first = render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render");
+        let second = render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render");
+        assert_eq!(first, second);
+        assert!(first.starts_with(b"%PDF-1.7"));
+        assert!(first.ends_with(b"%%EOF\n"));
+        assert_eq!(count(&first, b"/FontFile2"), 2, "regular and bold fonts are embedded");
+        assert_eq!(count(&first, b"/Type /Page "), 1);
+
+        let mut revised = payload.clone();
+        revised["version"] = serde_json::json!(4);
+        let third = render_quote_pdf(&revised, PdfTemplate::Detailed, &branding).expect("render");
+        assert_ne!(first, third, "a new quote version changes the document");
+    }
+
+    #[test]
+    fn templates_produce_distinct_documents_and_long_quotes_paginate() {
+        let payload = payload(40);
+        let branding = PdfBranding::from_payload(&payload);
+        let rendered: Vec<Vec<u8>> = PdfTemplate::ALL
+            .into_iter()
+            .map(|template| render_quote_pdf(&payload, template, &branding).expect("render"))
+            .collect();
+        assert_ne!(rendered[0], rendered[1]);
+        assert_ne!(rendered[0], rendered[2]);
+        assert!(count(&rendered[0], b"/Type /Page ") > 2, "40 detailed lines span several pages");
+        assert_eq!(
+            count(&rendered[1], b"/Type /Page "),
+            1,
+            "executive summary lists five products"
+        );
+    }
+
+    #[test]
+    fn branding_colours_and_logo_are_applied() {
+        let mut payload = payload(1);
+        payload["branding"] = serde_json::json!({
+            "company_name": "Acme CPQ",
+            "primary_color": "#ff0000",
+            "company_logo": "https://cdn.example.com/logo.png",
+            "white_label": true,
+        });
+        let branding = PdfBranding::from_payload(&payload);
+        assert_eq!(branding.support_contact_name, "Dana Reyes");
+        let pdf = render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render");
+        assert!(count(&pdf, b"1 0 0 rg") > 0, "primary colour fills the header band");
+        assert_eq!(count(&pdf, b"/XObject"), 0, "remote logos fall back to the company name");
+    }
+
+    #[test]
+    fn signed_quotes_append_a_certificate_page() {
+        use crate::esign::{SignatureCapture, SignerIdentity, SigningEvidence};
+        use chrono::TimeZone;
+
+        let payload = payload(2);
+        let branding = PdfBranding::from_payload(&payload);
+        let unsigned =
+            render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render");
+        let signed_at = chrono::Utc.with_ymd_and_hms(2026, 3, 10, 15, 4, 5).unwrap();
+        let mut certificate = AcceptanceCertificate {
+            request_id: "SIGN-0001".into(),
+            quote_id: "Q-2026-0042".into(),
+            quote_version: 3,
+            document_sha256: "ab".repeat(32),
+            signer: SignerIdentity::new("Dana Reyes", "VP Procurement", "dana@northwind.example")
+                .expect("signer"),
+            signature: SignatureCapture::Drawn {
+                strokes: vec![vec![[0.05, 0.8], [0.3, 0.2], [0.6, 0.7]], vec![[0.9, 0.5]]],
+            },
+            evidence: SigningEvidence {
+                code_sent_at: signed_at - chrono::Duration::minutes(3),
+                requested_ip: "203.0.113.7".into(),
+                requested_user_agent: "Mozilla/5.0".into(),
+                signed_at,
+                signed_ip: "203.0.113.7".into(),
+                signed_user_agent: "Mozilla/5.0".into(),
+            },
+        };
+
+        let drawn =
+            render_signed_quote_pdf(&payload, PdfTemplate::Detailed, &branding, &certificate)
+                .expect("render signed");
+        assert_eq!(count(&drawn, b"/Type /Page "), count(&unsigned, b"/Type /Page ") + 1);
+        assert_eq!(count(&drawn, b" S Q\n"), 2, "one stroked path per pen stroke");
+        assert_eq!(
+            drawn,
+            render_signed_quote_pdf(&payload, PdfTemplate::Detailed, &branding, &certificate)
+                .expect("render signed")
+        );
+
+        certificate.signature = SignatureCapture::Typed { text: "Dana Reyes".into() };
+        let typed =
+            render_signed_quote_pdf(&payload, PdfTemplate::Detailed, &branding, &certificate)
+                .expect("render signed");
+        assert_eq!(count(&typed, b" S Q\n"), 0);
+        assert_ne!(typed, drawn);
+    }
+
+    #[test]
+    fn resolved_documents_choose_the_sections_after_pricing() {
+        use self::template::{DocumentSection, QuoteDocumentTemplate, ResolvedDocument};
+
+        let payload = payload(2);
+        let branding = PdfBranding::from_payload(&payload);
+        let plain = render_quote_pdf(&payload, PdfTemplate::Detailed, &branding).expect("render");
+
+        let builtin = QuoteDocumentTemplate::builtin(PdfTemplate::Detailed)
+            .resolve("detailed", None, &Default::default(), &[])
+            .expect("resolve");
+        let mut explicit = payload.clone();
+        builtin.insert_into(&mut explicit);
+        let rendered =
+            render_quote_pdf(&explicit, PdfTemplate::Detailed, &branding).expect("render");
+        assert_eq!(rendered, plain, "the built-in template matches the legacy layout");
+
+        let mut legal = payload.clone();
+        ResolvedDocument {
+            sections: vec![
+                DocumentSection::Terms,
+                DocumentSection::Clause {
+                    key: "msa".into(),
+                    version: 1,
+                    title: "Master Services Agreement".into(),
+                    body: "Customer agrees to the master services agreement. ".repeat(40)
+                        + "\n\n"
+                        + &"Liability is capped at fees paid. ".repeat(400),
+                },
+            ],
+            ..builtin
+        }
+        .insert_into(&mut legal);
+        let rendered = render_quote_pdf(&legal, PdfTemplate::Detailed, &branding).expect("render");
+        assert_ne!(rendered, plain);
+        assert!(count(&rendered, b"/Type /Page ") > count(&plain, b"/Type /Page "));
+    }
+
+    #[test]
+    fn template_names_round_trip() {
+        for template in PdfTemplate::ALL {
+            assert_eq!(PdfTemplate::parse(template.as_str()), Some(template));
+        }
+        assert_eq!(PdfTemplate::parse("invoice"), None);
+    }
+}
@@ -0,0 +1,435 @@
+//! Quote document templates: a base layout plus conditional sections and reusable clauses.
+//!
+//! A [`QuoteDocumentTemplate`] picks one of the [`PdfTemplate`] layouts and lists, in order, the
+//! optional sections rendered after the pricing. Each section may carry a [`SectionCondition`]
+//! (customer segment, region, minimum total, minimum term) so that, for example, legal terms
+//! only appear on enterprise quotes. Clause sections reference a [`ClauseBlock`] by key, so
+//! wording shared by many templates is maintained in one place.
+//!
+//! [`QuoteDocumentTemplate::resolve`] evaluates the conditions for one quote; the resulting
+//! [`ResolvedDocument`] is written into the quote payload under [`DOCUMENT_PAYLOAD_KEY`], which
+//! the PDF layout reads. Payloads without it render the layout's built-in sections.
+//!
+//! Order forms render through the same pipeline: their payload adds a `billing_schedule` and
+//! `signatories`, which the [`SectionContent::BillingSchedule`] and
+//! [`SectionContent::Signatures`] sections print. On quotes without them those sections are
+//! empty.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::PdfTemplate;
+
+/// Payload key holding the [`ResolvedDocument`] a quote is rendered with.
+pub const DOCUMENT_PAYLOAD_KEY: &str = "document";
+
+const MAX_KEY_LEN: usize = 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("`{0}` is not a valid key: use 1-64 lowercase letters, digits, `-` or `_`")]
+    InvalidKey(String),
+    #[error("section `{0}` appears more than once")]
+    DuplicateSection(&'static str),
+    #[error("clause `{key}` is listed more than once")]
+    DuplicateClause { key: String },
+    #[error("section condition is invalid: {0}")]
+    InvalidCondition(String),
+    #[error("clause `{key}` does not exist")]
+    UnknownClause { key: String },
+    #[error("clause `{key}` has no version {version}")]
+    UnknownClauseVersion { key: String, version: i32 },
+}
+
+/// Checks a template or clause key.
+pub fn validate_key(key: &str) -> Result<(), TemplateError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || "-_".contains(ch));
+    if valid {
+        Ok(())
+    } else {
+        Err(TemplateError::InvalidKey(key.to_owned()))
+    }
+}
+
+/// Layout plus the ordered optional sections of a quote document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuoteDocumentTemplate {
+    pub layout: PdfTemplate,
+    #[serde(default)]
+    pub sections: Vec<TemplateSection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TemplateSection {
+    #[serde(flatten)]
+    pub content: SectionContent,
+    /// Rendered only when the quote matches; an empty condition always matches.
+    #[serde(default, skip_serializing_if = "SectionCondition::is_always")]
+    pub when: SectionCondition,
+}
+
+impl TemplateSection {
+    pub fn always(content: SectionContent) -> Self {
+        Self { content, when: SectionCondition::default() }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SectionContent {
+    Assumptions,
+    Notes,
+    Terms,
+    BillingSchedule,
+    Signatures,
+    /// A clause block; the newest version is used unless `version` pins one.
+    Clause {
+        key: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<i32>,
+    },
+}
+
+impl SectionContent {
+    fn builtin_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Assumptions => Some("assumptions"),
+            Self::Notes => Some("notes"),
+            Self::Terms => Some("terms"),
+            Self::BillingSchedule => Some("billing_schedule"),
+            Self::Signatures => Some("signatures"),
+            Self::Clause { .. } => None,
+        }
+    }
+}
+
+/// When a section applies. Every non-empty criterion must match; lists match any entry,
+/// case-insensitively.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SectionCondition {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_total: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_term_months: Option<u32>,
+}
+
+impl SectionCondition {
+    pub fn is_always(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, context: &DocumentContext) -> bool {
+        let listed = |allowed: &[String], value: &Option<String>| {
+            allowed.is_empty()
+                || value.as_deref().is_some_and(|value| {
+                    allowed.iter().any(|entry| entry.trim().eq_ignore_ascii_case(value.trim()))
+                })
+        };
+        listed(&self.segments, &context.customer_segment)
+            && listed(&self.regions, &context.region)
+            && self.min_total.map_or(true, |minimum| context.total >= minimum)
+            && self
+                .min_term_months
+                .map_or(true, |minimum| context.term_months.is_some_and(|term| term >= minimum))
+    }
+}
+
+/// The facts about a quote that section conditions are evaluated against.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocumentContext {
+    pub customer_segment: Option<String>,
+    pub region: Option<String>,
+    pub total: Decimal,
+    pub term_months: Option<u32>,
+}
+
+/// One version of a reusable clause.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClauseBlock {
+    pub key: String,
+    pub version: i32,
+    pub title: String,
+    /// Plain text; blank lines separate paragraphs.
+    pub body: String,
+}
+
+/// A section as rendered, with clause text inlined.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DocumentSection {
+    Assumptions,
+    Notes,
+    Terms,
+    BillingSchedule,
+    Signatures,
+    Clause { key: String, version: i32, title: String, body: String },
+}
+
+/// A template evaluated for one quote.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedDocument {
+    pub template_key: String,
+    /// Registry version; `None` for the built-in layouts.
+    pub template_version: Option<i32>,
+    pub layout: PdfTemplate,
+    pub sections: Vec<DocumentSection>,
+    /// Heading printed instead of the layout's own, such as "ORDER FORM".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl ResolvedDocument {
+    /// Stores the document in the quote payload so the PDF layout renders these sections.
+    pub fn insert_into(&self, payload: &mut Value) {
+        if let (Value::Object(map), Ok(document)) = (payload, serde_json::to_value(self)) {
+            map.insert(DOCUMENT_PAYLOAD_KEY.to_owned(), document);
+        }
+    }
+
+    /// The document stored in a payload by [`Self::insert_into`], if any.
+    pub fn from_payload(payload: &Value) -> Option<Self> {
+        serde_json::from_value(payload.get(DOCUMENT_PAYLOAD_KEY)?.clone()).ok()
+    }
+}
+
+impl QuoteDocumentTemplate {
+    /// The sections each layout has always rendered.
+    pub fn builtin(layout: PdfTemplate) -> Self {
+        let sections = match layout {
+            PdfTemplate::Detailed => {
+                vec![SectionContent::Assumptions, SectionContent::Notes, SectionContent::Terms]
+            }
+            PdfTemplate::ExecutiveSummary => vec![SectionContent::Assumptions],
+            PdfTemplate::Compact => vec![SectionContent::Terms],
+        };
+        Self { layout, sections: sections.into_iter().map(TemplateSection::always).collect() }
+    }
+
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        let mut builtins = Vec::new();
+        let mut clauses = Vec::new();
+        for section in &self.sections {
+            match &section.content {
+                SectionContent::Clause { key, version } => {
+                    validate_key(key)?;
+                    if let Some(version) = version.filter(|version| *version < 1) {
+                        return Err(TemplateError::UnknownClauseVersion {
+                            key: key.clone(),
+                            version,
+                        });
+                    }
+                    if clauses.contains(&key) {
+                        return Err(TemplateError::DuplicateClause { key: key.clone() });
+                    }
+                    clauses.push(key);
+                }
+                content => {
+                    let name = content.builtin_name().unwrap_or_default();
+                    if builtins.contains(&name) {
+                        return Err(TemplateError::DuplicateSection(name));
+                    }
+                    builtins.push(name);
+                }
+            }
+            let when = &section.when;
+            if when.min_total.is_some_and(|total| total.is_sign_negative()) {
+                return Err(TemplateError::InvalidCondition(
+                    "min_total must not be negative".into(),
+                ));
+            }
+            if when.segments.iter().chain(&when.regions).any(|entry| entry.trim().is_empty()) {
+                return Err(TemplateError::InvalidCondition(
+                    "segments and regions must not contain blank entries".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Clause keys the template references, in section order.
+    pub fn clause_keys(&self) -> Vec<&str> {
+        self.sections
+            .iter()
+            .filter_map(|section| match &section.content {
+                SectionContent::Clause { key, .. } => Some(key.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Keeps the sections whose conditions match `context` and inlines their clauses.
+    ///
+    /// `clauses` holds every available version of the referenced clauses; unpinned sections
+    /// use the newest. A referenced clause that is missing is an error even when its section
+    /// would be skipped, so a template never silently depends on a deleted clause.
+    pub fn resolve(
+        &self,
+        template_key: &str,
+        template_version: Option<i32>,
+        context: &DocumentContext,
+        clauses: &[ClauseBlock],
+    ) -> Result<ResolvedDocument, TemplateError> {
+        let mut sections = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let resolved = match &section.content {
+                SectionContent::Assumptions => DocumentSection::Assumptions,
+                SectionContent::Notes => DocumentSection::Notes,
+                SectionContent::Terms => DocumentSection::Terms,
+                SectionContent::BillingSchedule => DocumentSection::BillingSchedule,
+                SectionContent::Signatures => DocumentSection::Signatures,
+                SectionContent::Clause { key, version } => {
+                    let clause = clauses
+                        .iter()
+                        .filter(|clause| clause.key == *key)
+                        .filter(|clause| version.map_or(true, |pinned| clause.version == pinned))
+                        .max_by_key(|clause| clause.version)
+                        .ok_or_else(|| match version {
+                            Some(version) => TemplateError::UnknownClauseVersion {
+                                key: key.clone(),
+                                version: *version,
+                            },
+                            None => TemplateError::UnknownClause { key: key.clone() },
+                        })?;
+                    DocumentSection::Clause {
+                        key: clause.key.clone(),
+                        version: clause.version,
+                        title: clause.title.clone(),
+                        body: clause.body.clone(),
+                    }
+                }
+            };
+            if section.when.matches(context) {
+                sections.push(resolved);
+            }
+        }
+        Ok(ResolvedDocument {
+            template_key: template_key.to_owned(),
+            template_version,
+            layout: self.layout,
+            sections,
+            title: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn clause(key: &str, version: i32, title: &str) -> ClauseBlock {
+        ClauseBlock {
+            key: key.to_owned(),
+            version,
+            title: title.to_owned(),
+            body: format!("{title} body"),
+        }
+    }
+
+    fn enterprise_template() -> QuoteDocumentTemplate {
+        serde_json::from_value(json!({
+            "layout": "detailed",
+            "sections": [
+                {"kind": "notes"},
+                {"kind": "clause", "key": "msa-terms", "when": {"segments": ["Enterprise"]}},
+                {"kind": "clause", "key": "sla", "version": 1,
+                 "when": {"min_total": "50000", "min_term_months": 24}},
+                {"kind": "terms"}
+            ]
+        }))
+        .expect("template json")
+    }
+
+    #[test]
+    fn sections_round_trip_through_json() {
+        let template = enterprise_template();
+        assert_eq!(template.sections.len(), 4);
+        assert_eq!(
+            template.sections[2].content,
+            SectionContent::Clause { key: "sla".into(), version: Some(1) }
+        );
+        assert!(template.sections[0].when.is_always());
+
+        let encoded = serde_json::to_value(&template).expect("encode");
+        assert_eq!(encoded["sections"][0], json!({"kind": "notes"}));
+        assert_eq!(serde_json::from_value::<QuoteDocumentTemplate>(encoded).unwrap(), template);
+    }
+
+    #[test]
+    fn conditional_sections_follow_segment_total_and_term() {
+        let template = enterprise_template();
+        let clauses = [
+            clause("msa-terms", 1, "MSA"),
+            clause("msa-terms", 2, "MSA v2"),
+            clause("sla", 1, "SLA"),
+        ];
+
+        let smb = DocumentContext {
+            customer_segment: Some("smb".into()),
+            total: Decimal::new(10_000, 0),
+            ..DocumentContext::default()
+        };
+        let resolved = template.resolve("enterprise", Some(3), &smb, &clauses).expect("resolve");
+        assert_eq!(resolved.sections, vec![DocumentSection::Notes, DocumentSection::Terms]);
+
+        let enterprise = DocumentContext {
+            customer_segment: Some("enterprise".into()),
+            region: Some("emea".into()),
+            total: Decimal::new(80_000, 0),
+            term_months: Some(36),
+        };
+        let resolved =
+            template.resolve("enterprise", Some(3), &enterprise, &clauses).expect("resolve");
+        assert_eq!(resolved.sections.len(), 4);
+        assert!(matches!(
+            &resolved.sections[1],
+            DocumentSection::Clause { key, version: 2, title, .. } if key == "msa-terms" && title == "MSA v2"
+        ));
+        assert!(matches!(&resolved.sections[2], DocumentSection::Clause { version: 1, .. }));
+    }
+
+    #[test]
+    fn missing_clauses_and_invalid_definitions_are_rejected() {
+        let template = enterprise_template();
+        assert_eq!(template.validate(), Ok(()));
+        assert_eq!(template.clause_keys(), vec!["msa-terms", "sla"]);
+        let error = template
+            .resolve("enterprise", None, &DocumentContext::default(), &[clause("sla", 1, "SLA")])
+            .unwrap_err();
+        assert_eq!(error, TemplateError::UnknownClause { key: "msa-terms".into() });
+
+        let mut duplicate = template.clone();
+        duplicate.sections.push(TemplateSection::always(SectionContent::Notes));
+        assert_eq!(duplicate.validate(), Err(TemplateError::DuplicateSection("notes")));
+
+        let mut bad_key = template;
+        bad_key.sections[1].content =
+            SectionContent::Clause { key: "MSA Terms".into(), version: None };
+        assert!(matches!(bad_key.validate(), Err(TemplateError::InvalidKey(_))));
+        assert!(validate_key("enterprise_detailed-v2").is_ok());
+        assert!(validate_key("").is_err());
+    }
+
+    #[test]
+    fn resolved_documents_round_trip_through_the_payload() {
+        let resolved = QuoteDocumentTemplate::builtin(PdfTemplate::Compact)
+            .resolve("compact", None, &DocumentContext::default(), &[])
+            .expect("resolve");
+        let mut payload = json!({"id": "Q-1"});
+        resolved.insert_into(&mut payload);
+        assert_eq!(payload["document"]["layout"], "compact");
+        assert_eq!(ResolvedDocument::from_payload(&payload), Some(resolved));
+        assert_eq!(ResolvedDocument::from_payload(&json!({"id": "Q-1"})), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Low-level PDF object serialization.
+//!
+//! Objects are numbered in allocation order and written uncompressed with no creation dates, so
+//! the same sequence of calls always yields the same bytes. The trailer `/ID` is derived from the
+//! body for the same reason.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use sha2::{Digest, Sha256};
+
+use super::font::FontStyle;
+use super::PdfRenderError;
+
+#[derive(Debug, Default)]
+pub(crate) struct ObjectWriter {
+    objects: Vec<Option<Vec<u8>>>,
+}
+
+impl ObjectWriter {
+    /// Reserve an object number before its body is known (e.g. the page tree).
+    pub(crate) fn reserve(&mut self) -> usize {
+        self.objects.push(None);
+        self.objects.len()
+    }
+
+    pub(crate) fn set(&mut self, id: usize, body: impl Into<Vec<u8>>) {
+        self.objects[id - 1] = Some(body.into());
+    }
+
+    pub(crate) fn add(&mut self, body: impl Into<Vec<u8>>) -> usize {
+        let id = self.reserve();
+        self.set(id, body);
+        id
+    }
+
+    pub(crate) fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> usize {
+        let mut body = format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.add(body)
+    }
+
+    pub(crate) fn finish(self, catalog: usize) -> Result<Vec<u8>, PdfRenderError> {
+        let mut out = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (index, body) in self.objects.into_iter().enumerate() {
+            let body = body.ok_or_else(|| {
+                PdfRenderError::Layout(format!(
+                    "object {} was reserved but never written",
+                    index + 1
+                ))
+            })?;
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            out.extend_from_slice(&body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let id = hex(&Sha256::digest(&out)[..16]);
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in &offsets {
+            let _ = writeln!(table, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {catalog} 0 R /ID [<{id}> <{id}>] >>\nstartxref\n{xref}\n%%EOF\n",
+            offsets.len() + 1
+        );
+        out.extend_from_slice(table.as_bytes());
+        Ok(out)
+    }
+}
+
+/// Glyphs drawn with one font style, and the text they stand for (for `/ToUnicode`).
+#[derive(Debug, Default)]
+pub(crate) struct GlyphUsage {
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl GlyphUsage {
+    pub(crate) fn record(&mut self, glyph: u16, ch: char) {
+        self.glyphs.entry(glyph).or_insert(ch);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+}
+
+/// Writes the Type0/CIDFontType2 object graph for one font style and returns the Type0 font id.
+pub(crate) fn write_font(
+    writer: &mut ObjectWriter,
+    style: FontStyle,
+    usage: &GlyphUsage,
+) -> Result<usize, PdfRenderError> {
+    let font = style.font();
+    let glyph_set: BTreeSet<u16> = usage.glyphs.keys().copied().collect();
+    let base_font = format!("{}+{}", subset_tag(&glyph_set), font.postscript_name);
+
+    let program = font.subset(&glyph_set)?;
+    let font_file = writer.add_stream(&format!("/Length1 {}", program.len()), &program);
+
+    let [x_min, y_min, x_max, y_max] = font.bbox;
+    let descriptor = writer.add(format!(
+        "<< /Type /FontDescriptor /FontName /{base_font} /Flags 32 /FontBBox [{} {} {} {}] \
+         /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV {} /FontFile2 {font_file} 0 R >>",
+        font.scale(x_min),
+        font.scale(y_min),
+        font.scale(x_max),
+        font.scale(y_max),
+        font.scale(font.ascender),
+        font.scale(font.descender),
+        font.scale(font.cap_height),
+        if style == FontStyle::Bold { 140 } else { 80 },
+    ));
+
+    let mut widths = String::new();
+    for glyph in &glyph_set {
+        let _ = write!(widths, "{glyph} [{}] ", font.advance_per_mille(*glyph));
+    }
+    let cid_font = writer.add(format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{base_font} \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /FontDescriptor {descriptor} 0 R /CIDToGIDMap /Identity /DW 1000 /W [{}] >>",
+        widths.trim_end()
+    ));
+
+    let to_unicode = writer.add_stream("", to_unicode_cmap(usage).as_bytes());
+    Ok(writer.add(format!(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /{base_font} /Encoding /Identity-H \
+         /DescendantFonts [{cid_font} 0 R] /ToUnicode {to_unicode} 0 R >>"
+    )))
+}
+
+/// Six uppercase letters identifying the subset, stable for a given glyph set.
+fn subset_tag(glyphs: &BTreeSet<u16>) -> String {
+    let mut hasher = Sha256::new();
+    for glyph in glyphs {
+        hasher.update(glyph.to_be_bytes());
+    }
+    hasher.finalize().iter().take(6).map(|byte| char::from(b'A' + byte % 26)).collect()
+}
+
+fn to_unicode_cmap(usage: &GlyphUsage) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<_> = usage.glyphs.iter().collect();
+    for chunk in entries.chunks(100) {
+        let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+        for (glyph, ch) in chunk {
+            let mut units = [0u16; 2];
+            let utf16: String =
+                ch.encode_utf16(&mut units).iter().map(|unit| format!("{unit:04X}")).collect();
+            let _ = writeln!(cmap, "<{glyph:04X}> <{utf16}>");
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend");
+    cmap
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+/// A coordinate or size with at most two decimals and no trailing zeros.
+pub(crate) fn number(value: f32) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let text = format!("{rounded:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_owned()
+    } else {
+        text.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xref_offsets_point_at_their_objects() {
+        let mut writer = ObjectWriter::default();
+        let pages = writer.reserve();
+        let catalog = writer.add(format!("<< /Type /Catalog /Pages {pages} 0 R >>"));
+        writer.set(pages, "<< /Type /Pages /Kids [] /Count 0 >>");
+        let pdf = writer.finish(catalog).expect("pdf");
+
+        let tail = pdf.len() - pdf.windows(10).rev().position(|w| w == b"startxref\n").unwrap();
+        let xref_at: usize =
+            std::str::from_utf8(&pdf[tail..]).unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref_at..].starts_with(b"xref\n0 3\n"));
+        let first = std::str::from_utf8(&pdf[xref_at..]).unwrap().lines().nth(3).unwrap();
+        let offset: usize = first[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with(b"1 0 obj\n<< /Type /Pages"));
+    }
+
+    #[test]
+    fn numbers_are_canonical() {
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(12.5), "12.5");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(number(841.889), "841.89");
+    }
+}
@@ -2,204 +2,12 @@
 
 use std::collections::{HashMap, HashSet};
 
-use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono::{DateTime, Datelike, Utc};
 
 use super::scoring::{FeedbackExperimentVariant, ScoreCalculator, ScoringWeights};
 use super::types::*;
 use super::SuggestionResult;
 
-/// Lightweight catalog seed used by the current deterministic suggestion engine.
-#[derive(Debug, Clone, Copy)]
-struct ProductSeed {
-    id: &'static str,
-    sku: &'static str,
-    name: &'static str,
-    category: &'static str,
-    unit_price: f64,
-}
-
-const PRODUCT_SEEDS: &[ProductSeed] = &[
-    ProductSeed {
-        id: "prod_pro_v2",
-        sku: "PLAN-PRO-001",
-        name: "Pro Plan",
-        category: "plan",
-        unit_price: 12_000.0,
-    },
-    ProductSeed {
-        id: "prod_enterprise",
-        sku: "PLAN-ENT-001",
-        name: "Enterprise Plan",
-        category: "plan",
-        unit_price: 18_000.0,
-    },
-    ProductSeed {
-        id: "prod_sso",
-        sku: "ADDON-SSO-001",
-        name: "SSO Add-on",
-        category: "addon",
-        unit_price: 2_400.0,
-    },
-    ProductSeed {
-        id: "prod_support",
-        sku: "ADDON-SUP-001",
-        name: "Premium Support",
-        category: "addon",
-        unit_price: 6_500.0,
-    },
-    ProductSeed {
-        id: "prod_onboarding",
-        sku: "ADDON-ONB-001",
-        name: "Onboarding Pack",
-        category: "service",
-        unit_price: 2_900.0,
-    },
-    ProductSeed {
-        id: "prod_backup",
-        sku: "ADDON-BKP-001",
-        name: "Automated Backups",
-        category: "addon",
-        unit_price: 4_000.0,
-    },
-    ProductSeed {
-        id: "prod_analytics",
-        sku: "ANALYTICS-001",
-        name: "Advanced Analytics",
-        category: "addon",
-        unit_price: 7_800.0,
-    },
-];
-
-#[derive(Debug, Clone)]
-struct CustomerSeed {
-    id: &'static str,
-    segment: &'static str,
-    industry: Option<&'static str>,
-    employee_count: Option<u32>,
-    region: &'static str,
-    avg_deal_size: f64,
-}
-
-const SIMILAR_CUSTOMER_SEEDS: &[CustomerSeed] = &[
-    CustomerSeed {
-        id: "acme_enterprise_saas",
-        segment: "enterprise",
-        industry: Some("fintech"),
-        employee_count: Some(1200),
-        region: "us",
-        avg_deal_size: 82_000.0,
-    },
-    CustomerSeed {
-        id: "northbridge_healthcare",
-        segment: "enterprise",
-        industry: Some("healthcare"),
-        employee_count: Some(950),
-        region: "us",
-        avg_deal_size: 74_000.0,
-    },
-    CustomerSeed {
-        id: "pivot_smb_saas",
-        segment: "mid_market",
-        industry: Some("saas"),
-        employee_count: Some(180),
-        region: "us",
-        avg_deal_size: 31_000.0,
-    },
-    CustomerSeed {
-        id: "aurora_mid_market",
-        segment: "mid_market",
-        industry: Some("education"),
-        employee_count: Some(240),
-        region: "eu",
-        avg_deal_size: 33_000.0,
-    },
-    CustomerSeed {
-        id: "founder_smb",
-        segment: "smb",
-        industry: Some("saas"),
-        employee_count: Some(75),
-        region: "us",
-        avg_deal_size: 9_500.0,
-    },
-    CustomerSeed {
-        id: "local_service_smb",
-        segment: "smb",
-        industry: Some("services"),
-        employee_count: Some(40),
-        region: "apac",
-        avg_deal_size: 7_800.0,
-    },
-    CustomerSeed {
-        id: "atlas_global",
-        segment: "enterprise",
-        industry: Some("technology"),
-        employee_count: Some(2000),
-        region: "eu",
-        avg_deal_size: 98_000.0,
-    },
-];
-
-#[derive(Debug, Clone)]
-struct RelationshipSeed {
-    source_product_id: &'static str,
-    target_product_id: &'static str,
-    relationship_type: RelationshipType,
-    confidence: f64,
-    co_occurrence_count: u32,
-}
-
-const PRODUCT_RELATIONSHIP_SEEDS: &[RelationshipSeed] = &[
-    RelationshipSeed {
-        source_product_id: "prod_enterprise",
-        target_product_id: "prod_sso",
-        relationship_type: RelationshipType::Bundle,
-        confidence: 0.90,
-        co_occurrence_count: 142,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_enterprise",
-        target_product_id: "prod_support",
-        relationship_type: RelationshipType::Bundle,
-        confidence: 0.93,
-        co_occurrence_count: 198,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_pro_v2",
-        target_product_id: "prod_sso",
-        relationship_type: RelationshipType::AddOn,
-        confidence: 0.81,
-        co_occurrence_count: 118,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_pro_v2",
-        target_product_id: "prod_backup",
-        relationship_type: RelationshipType::AddOn,
-        confidence: 0.76,
-        co_occurrence_count: 77,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_pro_v2",
-        target_product_id: "prod_onboarding",
-        relationship_type: RelationshipType::Upgrade,
-        confidence: 0.84,
-        co_occurrence_count: 95,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_support",
-        target_product_id: "prod_analytics",
-        relationship_type: RelationshipType::CrossSell,
-        confidence: 0.58,
-        co_occurrence_count: 64,
-    },
-    RelationshipSeed {
-        source_product_id: "prod_enterprise",
-        target_product_id: "prod_analytics",
-        relationship_type: RelationshipType::CrossSell,
-        confidence: 0.61,
-        co_occurrence_count: 52,
-    },
-];
-
 fn normalize_identifier(value: &str) -> String {
     value
         .to_ascii_lowercase()
@@ -290,47 +98,6 @@ fn infer_avg_deal_size(segment: &str, employee_count: u32, region: &str) -> f64
     (base * (1.0 + employee_factor.min(0.35))).max(5_000.0) * region_factor
 }
 
-fn build_product_catalog() -> Vec<ProductInfo> {
-    PRODUCT_SEEDS
-        .iter()
-        .map(|seed| ProductInfo {
-            id: seed.id.to_owned(),
-            sku: seed.sku.to_owned(),
-            name: seed.name.to_owned(),
-            category: seed.category.to_owned(),
-            unit_price: seed.unit_price,
-            active: true,
-        })
-        .collect()
-}
-
-fn resolve_product(product_id: &str) -> Option<ProductInfo> {
-    PRODUCT_SEEDS.iter().find(|seed| seed.id.eq_ignore_ascii_case(product_id)).map(|seed| {
-        ProductInfo {
-            id: seed.id.to_owned(),
-            sku: seed.sku.to_owned(),
-            name: seed.name.to_owned(),
-            category: seed.category.to_owned(),
-            unit_price: seed.unit_price,
-            active: true,
-        }
-    })
-}
-
-fn synthetic_peer_profiles() -> Vec<CustomerProfile> {
-    SIMILAR_CUSTOMER_SEEDS
-        .iter()
-        .map(|seed| CustomerProfile {
-            id: seed.id.to_owned(),
-            segment: seed.segment.to_owned(),
-            industry: seed.industry.map(std::string::ToString::to_string),
-            employee_count: seed.employee_count,
-            region: seed.region.to_owned(),
-            avg_deal_size: seed.avg_deal_size,
-        })
-        .collect()
-}
-
 fn profile_similarity(a: &CustomerProfile, b: &CustomerProfile) -> f64 {
     let mut score = 0.0;
 
@@ -358,157 +125,38 @@ fn profile_similarity(a: &CustomerProfile, b: &CustomerProfile) -> f64 {
     score.min(1.0)
 }
 
-fn customer_profile_preferences(product_id: &str) -> (Vec<&'static str>, Vec<&'static str>) {
-    match product_id {
-        "prod_enterprise" => (vec!["enterprise"], vec!["enterprise", "high-compliance"]),
-        "prod_sso" => (vec!["enterprise", "mid_market"], vec!["security", "identity"]),
-        "prod_support" => (vec!["enterprise", "mid_market", "smb"], vec!["service", "retention"]),
-        "prod_onboarding" => (vec!["enterprise", "mid_market"], vec!["growth", "new"]),
-        "prod_backup" => (vec!["enterprise", "mid_market", "smb"], vec!["operations"]),
-        "prod_analytics" => (vec!["enterprise", "mid_market"], vec!["insight"]),
-        _ => (vec!["smb", "mid_market", "enterprise"], vec!["baseline"]),
-    }
-}
-
-fn recent_purchase_days(product_id: &str) -> [i64; 3] {
-    match product_id {
-        "prod_analytics" => [11, 49, 89],
-        "prod_support" => [14, 28, 63],
-        "prod_sso" => [17, 32, 77],
-        "prod_enterprise" => [20, 75, 140],
-        "prod_backup" => [31, 58, 108],
-        "prod_onboarding" => [9, 27, 92],
-        _ => [20, 55, 95],
-    }
-}
-
-fn seasonal_intensity(product_id: &str, quarter: u8) -> f64 {
-    // Tuned synthetic trend: Q4 is strongest for onboarding, Q2 for security/add-ons.
-    match (product_id, quarter) {
-        ("prod_enterprise", 1) => 1.15,
-        ("prod_enterprise", 4) => 1.28,
-        ("prod_sso", 2) => 1.16,
-        ("prod_sso", 4) => 1.20,
-        ("prod_support", 3) => 1.18,
-        ("prod_support", 4) => 1.04,
-        ("prod_analytics", 4) => 1.35,
-        ("prod_onboarding", 1) => 1.22,
-        ("prod_backup", 2) => 1.20,
-        ("prod_backup", 3) => 0.95,
-        _ => 1.0,
-    }
-}
-
-fn build_business_rules() -> Vec<BusinessRule> {
-    let now = Utc::now();
-
-    vec![
-        BusinessRule {
-            id: "rule_always_enterprise_support".to_owned(),
-            rule_type: BusinessRuleType::AlwaysSuggestForSegment {
-                segment: "enterprise".to_owned(),
-                product_id: "prod_support".to_owned(),
-                boost: 0.24,
-            },
-            active: true,
-            priority: 100,
-        },
-        BusinessRule {
-            id: "rule_mid_market_onboarding".to_owned(),
-            rule_type: BusinessRuleType::AlwaysSuggestForSegment {
-                segment: "mid_market".to_owned(),
-                product_id: "prod_onboarding".to_owned(),
-                boost: 0.16,
-            },
-            active: true,
-            priority: 90,
-        },
-        BusinessRule {
-            id: "rule_smb_sso".to_owned(),
-            rule_type: BusinessRuleType::AlwaysSuggestForSegment {
-                segment: "smb".to_owned(),
-                product_id: "prod_sso".to_owned(),
-                boost: 0.10,
-            },
-            active: true,
-            priority: 80,
-        },
-        BusinessRule {
-            id: "rule_min_deal_size_backup".to_owned(),
-            rule_type: BusinessRuleType::MinimumDealSize {
-                threshold: 20_000.0,
-                product_id: "prod_backup".to_owned(),
-                boost: 0.11,
-            },
-            active: true,
-            priority: 70,
-        },
-        BusinessRule {
-            id: "rule_analytics_promo".to_owned(),
-            rule_type: BusinessRuleType::NewProductPromotion {
-                product_id: "prod_analytics".to_owned(),
-                launch_date: now - Duration::days(14),
-                promotion_days: 45,
-                boost: 0.12,
-            },
-            active: true,
-            priority: 95,
-        },
-    ]
-}
-
 fn now_quarter(now: DateTime<Utc>) -> u8 {
     ((now.month() - 1) / 3 + 1) as u8
 }
 
-fn apply_context_boosts(
-    catalog: Vec<ProductInfo>,
-    customer: &CustomerProfile,
-    context: Option<&QuoteContext>,
-) -> Vec<ProductInfo> {
-    if let Some(context) = context {
-        let quote_value = context.quote_value.unwrap_or(customer.avg_deal_size);
-
-        catalog
-            .into_iter()
-            .map(|product| {
-                let is_enterprise = product.id == "prod_enterprise";
-                let is_backup = product.id == "prod_backup";
-                let is_analytics = product.id == "prod_analytics";
-
-                // Prefer higher ACV products for larger deals and keep core service fit.
-                let unit_price = if quote_value >= 60_000.0 {
-                    if is_enterprise || is_analytics || is_backup {
-                        product.unit_price * 1.0
-                    } else {
-                        product.unit_price * 1.1
-                    }
-                } else if quote_value < 20_000.0 {
-                    if is_backup {
-                        product.unit_price * 0.9
-                    } else {
-                        product.unit_price
-                    }
-                } else {
-                    product.unit_price
-                };
-
-                ProductInfo { unit_price, ..product }
-            })
-            .collect()
-    } else {
-        catalog
-    }
-}
+/// Most recent purchases considered for the time-decay score.
+const RECENT_PURCHASE_WINDOW: usize = 10;
+/// Peers kept for the similar-customer score.
+const MAX_SIMILAR_CUSTOMERS: usize = 4;
+/// Peers below this profile similarity are ignored.
+const MIN_PEER_SIMILARITY: f64 = 0.40;
 
 /// The main suggestion engine
+///
+/// Candidates, peers, purchase history and relationships come from a [`SuggestionSnapshot`]
+/// loaded from the database; an engine without a snapshot has nothing to suggest.
 #[derive(Debug, Clone)]
 pub struct SuggestionEngine {
     /// Scoring calculator
     calculator: ScoreCalculator,
+    /// Catalog products in snapshot order
+    catalog: Vec<ProductInfo>,
+    /// Known account profiles keyed by normalized id
+    customers: HashMap<String, CustomerProfile>,
+    /// Purchase history keyed by product id
+    purchases: HashMap<String, Vec<ProductPurchase>>,
+    /// Mined relationships keyed by every product they touch
+    relationships: HashMap<String, Vec<ProductRelationship>>,
+    /// Active business rules
+    business_rules: Vec<BusinessRule>,
     /// In-memory cache for customer similarities
     customer_cache: HashMap<String, Vec<(CustomerProfile, f64)>>,
-    /// In-memory cache for product relationships  
+    /// In-memory cache for product relationships, consulted before mined relationships
     relationship_cache: HashMap<String, Vec<ProductRelationship>>,
     /// In-memory feedback cache used for deterministic score re-ranking.
     feedback_cache: HashMap<String, ProductAcceptanceRate>,
@@ -519,19 +167,18 @@ pub struct SuggestionEngine {
 impl SuggestionEngine {
     /// Create a new suggestion engine with default weights
     pub fn new() -> Self {
-        Self {
-            calculator: ScoreCalculator::new(),
-            customer_cache: HashMap::new(),
-            relationship_cache: HashMap::new(),
-            feedback_cache: HashMap::new(),
-            feedback_variant_override: None,
-        }
+        Self::with_weights(ScoringWeights::default())
     }
 
     /// Create with custom weights
     pub fn with_weights(weights: ScoringWeights) -> Self {
         Self {
             calculator: ScoreCalculator::with_weights(weights),
+            catalog: Vec::new(),
+            customers: HashMap::new(),
+            purchases: HashMap::new(),
+            relationships: HashMap::new(),
+            business_rules: Vec::new(),
             customer_cache: HashMap::new(),
             relationship_cache: HashMap::new(),
             feedback_cache: HashMap::new(),
@@ -539,6 +186,53 @@ impl SuggestionEngine {
         }
     }
 
+    /// Create an engine with default weights scoring against `snapshot`
+    pub fn from_snapshot(snapshot: SuggestionSnapshot) -> Self {
+        let mut engine = Self::new();
+        engine.load_snapshot(snapshot);
+        engine
+    }
+
+    /// Replace catalog, accounts, history and relationships with `snapshot`.
+    ///
+    /// The snapshot's acceptance rates replace the feedback cache and cached customer
+    /// similarities are dropped since the peer pool changed.
+    pub fn load_snapshot(&mut self, snapshot: SuggestionSnapshot) {
+        let SuggestionSnapshot { products, customers, purchases, relationships, acceptance } =
+            snapshot;
+
+        self.catalog = products;
+        self.customers = customers
+            .into_iter()
+            .map(|profile| (normalize_identifier(&profile.id), profile))
+            .collect();
+
+        self.purchases.clear();
+        for purchase in purchases {
+            self.purchases.entry(purchase.product_id.clone()).or_default().push(purchase);
+        }
+        for history in self.purchases.values_mut() {
+            history.sort_by_key(|purchase| std::cmp::Reverse(purchase.purchased_at));
+        }
+
+        self.relationships.clear();
+        for relationship in relationships {
+            if relationship.source_product_id != relationship.target_product_id {
+                self.relationships
+                    .entry(relationship.target_product_id.clone())
+                    .or_default()
+                    .push(relationship.clone());
+            }
+            self.relationships
+                .entry(relationship.source_product_id.clone())
+                .or_default()
+                .push(relationship);
+        }
+
+        self.feedback_cache = acceptance;
+        self.customer_cache.clear();
+    }
+
     /// Determine the deterministic feedback experiment variant for a suggestion request.
     pub fn feedback_variant_for_request(
         &self,
@@ -563,7 +257,8 @@ impl SuggestionEngine {
     ) -> SuggestionResult<Vec<ProductSuggestion>> {
         let feedback_variant = self.feedback_variant_for_request(&request);
         // Get customer profile
-        let customer = self.get_customer_profile(&request.customer_id).await?;
+        let customer =
+            self.get_customer_profile(&request.customer_id, request.quote_context.as_ref()).await?;
 
         // Get current products (if any)
         let current_products = if request.current_products.is_empty() {
@@ -578,9 +273,7 @@ impl SuggestionEngine {
 
         // Score candidate products
         let mut suggestions = Vec::new();
-        let candidate_products = self
-            .get_candidate_products(&customer, request.quote_context.as_ref(), &current_products)
-            .await?;
+        let candidate_products = self.get_candidate_products(&current_products).await?;
 
         for candidate in candidate_products {
             let scores = self
@@ -693,11 +386,21 @@ impl SuggestionEngine {
     }
 
     // -------------------------------------------------------------------------
-    // Data Access (Deterministic in-memory implementation)
+    // Data Access (snapshot-backed)
     // -------------------------------------------------------------------------
 
-    async fn get_customer_profile(&self, customer_id: &str) -> SuggestionResult<CustomerProfile> {
+    /// Known accounts come from the snapshot; anything else (a prospect named in Slack, say)
+    /// gets a profile inferred from its name and the quote context.
+    async fn get_customer_profile(
+        &self,
+        customer_id: &str,
+        quote_context: Option<&QuoteContext>,
+    ) -> SuggestionResult<CustomerProfile> {
         let normalized = normalize_identifier(customer_id);
+        if let Some(known) = self.customers.get(&normalized) {
+            return Ok(known.clone());
+        }
+
         let segment = infer_segment(&normalized).to_owned();
         let region = infer_region(&normalized);
 
@@ -717,7 +420,10 @@ impl SuggestionEngine {
             }
         };
 
-        let avg_deal_size = infer_avg_deal_size(&segment, employee_count, region);
+        let avg_deal_size = quote_context
+            .and_then(|context| context.quote_value)
+            .filter(|value| *value > 0.0)
+            .unwrap_or_else(|| infer_avg_deal_size(&segment, employee_count, region));
 
         Ok(CustomerProfile {
             id: customer_id.to_string(),
@@ -733,12 +439,14 @@ impl SuggestionEngine {
         let mut products = Vec::with_capacity(product_ids.len());
 
         for id in product_ids {
-            if let Some(product) = resolve_product(id) {
-                products.push(product);
+            if let Some(product) =
+                self.catalog.iter().find(|product| product.id.eq_ignore_ascii_case(id))
+            {
+                products.push(product.clone());
                 continue;
             }
 
-            // Deterministic fallback for unknown product identifiers.
+            // Deterministic fallback for products missing from the catalog.
             products.push(ProductInfo {
                 id: id.clone(),
                 sku: format!("SKU-{id}"),
@@ -754,70 +462,17 @@ impl SuggestionEngine {
 
     async fn get_candidate_products(
         &self,
-        customer: &CustomerProfile,
-        quote_context: Option<&QuoteContext>,
         current_products: &[ProductInfo],
     ) -> SuggestionResult<Vec<ProductInfo>> {
-        let mut candidates = build_product_catalog();
         let current_ids: HashSet<&str> =
             current_products.iter().map(|product| product.id.as_str()).collect();
 
-        candidates
-            .retain(|product| current_ids.is_empty() || !current_ids.contains(product.id.as_str()));
-
-        let quote_value =
-            quote_context.and_then(|context| context.quote_value).unwrap_or(customer.avg_deal_size);
-        let term = quote_context.and_then(|context| context.term_months).unwrap_or(12);
-
-        // Region-aware and segment-aware ranking:
-        //  - enterprise customers are suggested enterprise-first
-        //  - mid-market customers favor onboarding and security add-ons
-        //  - SMB customers prefer lighter add-ons first
-        candidates.sort_by(|a, b| {
-            let (a_preferred, a_tags) = customer_profile_preferences(&a.id);
-            let (b_preferred, b_tags) = customer_profile_preferences(&b.id);
-
-            let a_match = a_preferred.iter().filter(|segment| **segment == customer.segment).count()
-                as i32
-                + a_tags.iter().filter(|tag| tag == &&"growth").count() as i32;
-            let b_match = b_preferred.iter().filter(|segment| **segment == customer.segment).count()
-                as i32
-                + b_tags.iter().filter(|tag| tag == &&"growth").count() as i32;
-
-            // Prefer products with a price that aligns with the quote context.
-            let a_price_signal = if quote_value < 20_000.0 {
-                (a.unit_price <= 5_000.0) as i32
-            } else if quote_value > 60_000.0 {
-                (a.unit_price >= 4_000.0) as i32
-            } else {
-                1
-            };
-            let b_price_signal = if quote_value < 20_000.0 {
-                (b.unit_price <= 5_000.0) as i32
-            } else if quote_value > 60_000.0 {
-                (b.unit_price >= 4_000.0) as i32
-            } else {
-                1
-            };
-
-            let a_term_signal = if term >= 12 {
-                (a.id == "prod_support") as i32 + (a.id == "prod_analytics") as i32
-            } else {
-                0
-            };
-            let b_term_signal = if term >= 12 {
-                (b.id == "prod_support") as i32 + (b.id == "prod_analytics") as i32
-            } else {
-                0
-            };
-
-            let a_score = a_match + a_price_signal + a_term_signal;
-            let b_score = b_match + b_price_signal + b_term_signal;
-
-            b_score.cmp(&a_score).then_with(|| a.id.cmp(&b.id))
-        });
-
-        Ok(apply_context_boosts(candidates, customer, quote_context))
+        Ok(self
+            .catalog
+            .iter()
+            .filter(|product| product.active && !current_ids.contains(product.id.as_str()))
+            .cloned()
+            .collect())
     }
 
     async fn get_similar_customers(
@@ -828,19 +483,22 @@ impl SuggestionEngine {
             return Ok(cached.clone());
         }
 
-        let mut peers = synthetic_peer_profiles()
-            .into_iter()
-            .filter(|peer| peer.id != customer.id)
-            .map(|peer| {
-                let similarity = profile_similarity(customer, &peer);
-                (peer, similarity)
-            })
-            .filter(|(_, similarity)| *similarity >= 0.40)
+        let own_id = normalize_identifier(&customer.id);
+        let mut peers = self
+            .customers
+            .iter()
+            .filter(|(id, _)| **id != own_id)
+            .map(|(_, peer)| (peer.clone(), profile_similarity(customer, peer)))
+            .filter(|(_, similarity)| *similarity >= MIN_PEER_SIMILARITY)
             .collect::<Vec<_>>();
 
-        peers.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        peers.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
 
-        peers.truncate(4);
+        peers.truncate(MAX_SIMILAR_CUSTOMERS);
         Ok(peers)
     }
 
@@ -851,36 +509,27 @@ impl SuggestionEngine {
         Ok(similar_customers.iter().map(|(customer, _)| customer.id.clone()).collect())
     }
 
+    /// Number of distinct quotes on which each peer bought `product`.
     async fn get_purchase_counts(
         &self,
         customers: &[(CustomerProfile, f64)],
         product: &ProductInfo,
     ) -> SuggestionResult<HashMap<String, u32>> {
         let mut counts = HashMap::new();
+        let Some(history) = self.purchases.get(&product.id) else {
+            return Ok(counts);
+        };
 
-        for (similar_customer, similarity) in customers {
-            let mut count = 1.0f64;
-
-            if customer_profile_preferences(&product.id)
-                .0
+        for (similar_customer, _) in customers {
+            let peer_id = normalize_identifier(&similar_customer.id);
+            let quotes = history
                 .iter()
-                .any(|segment| *segment == similar_customer.segment)
-            {
-                count += 1.5;
+                .filter(|purchase| normalize_identifier(&purchase.customer_id) == peer_id)
+                .map(|purchase| purchase.quote_id.as_str())
+                .collect::<HashSet<_>>();
+            if !quotes.is_empty() {
+                counts.insert(similar_customer.id.clone(), quotes.len() as u32);
             }
-
-            if similar_customer.industry.is_some()
-                && customer_profile_preferences(&product.id).1.iter().any(|tag| tag == &"growth")
-            {
-                count += 0.5;
-            }
-
-            if let Some(employees) = similar_customer.employee_count {
-                count += (employees as f64 / 1000.0).min(1.0);
-            }
-
-            count *= similarity.max(0.25);
-            counts.insert(similar_customer.id.clone(), (count.round() as u32).clamp(1, 5));
         }
 
         Ok(counts)
@@ -894,73 +543,55 @@ impl SuggestionEngine {
             return Ok(cached.clone());
         }
 
-        let mut relationships = PRODUCT_RELATIONSHIP_SEEDS
-            .iter()
-            .filter(|seed| {
-                seed.source_product_id == product.id || seed.target_product_id == product.id
-            })
-            .map(|seed| ProductRelationship {
-                id: format!("rel:{}:{}", seed.source_product_id, seed.target_product_id),
-                source_product_id: seed.source_product_id.to_string(),
-                target_product_id: seed.target_product_id.to_string(),
-                relationship_type: seed.relationship_type,
-                confidence: seed.confidence,
-                co_occurrence_count: seed.co_occurrence_count,
-            })
-            .collect::<Vec<_>>();
-
-        if relationships.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        relationships.sort_by(|a, b| {
-            b.co_occurrence_count.cmp(&a.co_occurrence_count).then_with(|| {
-                b.relationship_type
-                    .base_score()
-                    .partial_cmp(&a.relationship_type.base_score())
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-        });
-
-        Ok(relationships)
+        Ok(self.relationships.get(&product.id).cloned().unwrap_or_default())
     }
 
     async fn get_recent_purchases(
         &self,
         product: &ProductInfo,
     ) -> SuggestionResult<Vec<chrono::DateTime<chrono::Utc>>> {
-        let now = chrono::Utc::now();
-        let mut purchases = Vec::new();
-        for age_days in recent_purchase_days(&product.id) {
-            purchases.push(now - Duration::days(age_days));
-        }
-
-        // Add mild regional recency spread for non-US regions.
-        if product.id == "prod_analytics" {
-            purchases.push(now - Duration::days(120));
-        }
-
-        Ok(purchases)
+        Ok(self
+            .purchases
+            .get(&product.id)
+            .map(|history| {
+                history
+                    .iter()
+                    .take(RECENT_PURCHASE_WINDOW)
+                    .map(|purchase| purchase.purchased_at)
+                    .collect()
+            })
+            .unwrap_or_default())
     }
 
+    /// Compares this quarter's purchase count against the product's per-quarter average.
     async fn get_seasonal_pattern(
         &self,
         product: &ProductInfo,
     ) -> SuggestionResult<Option<SeasonalPattern>> {
+        let Some(history) = self.purchases.get(&product.id).filter(|history| !history.is_empty())
+        else {
+            return Ok(None);
+        };
+
         let now = Utc::now();
         let quarter = now_quarter(now);
-        let avg = seasonal_intensity(&product.id, quarter) * 1.0;
+        let mut per_quarter = [0u32; 4];
+        for purchase in history {
+            per_quarter[usize::from(now_quarter(purchase.purchased_at) - 1)] += 1;
+        }
+        let average = history.len() as f64 / 4.0;
+        let intensity = f64::from(per_quarter[usize::from(quarter - 1)]) / average;
 
         Ok(Some(SeasonalPattern {
             product_id: product.id.clone(),
             quarter,
-            avg_purchases: avg,
+            avg_purchases: intensity,
             year: now.year() as u32,
         }))
     }
 
     async fn get_business_rules(&self) -> SuggestionResult<Vec<BusinessRule>> {
-        Ok(build_business_rules())
+        Ok(self.business_rules.clone())
     }
 
     // -------------------------------------------------------------------------
@@ -982,6 +613,11 @@ impl SuggestionEngine {
         self.feedback_cache = data;
     }
 
+    /// Replace the business rules used for the rule boost.
+    pub fn warm_business_rules(&mut self, rules: Vec<BusinessRule>) {
+        self.business_rules = rules;
+    }
+
     /// Clear all caches
     pub fn clear_caches(&mut self) {
         self.customer_cache.clear();
@@ -998,11 +634,93 @@ impl Default for SuggestionEngine {
 
 #[cfg(test)]
 mod tests {
+    use chrono::Duration;
+
+    use super::super::mining::{CoOccurrenceStats, MiningThresholds};
     use super::*;
 
+    fn product(id: &str, name: &str, unit_price: f64, active: bool) -> ProductInfo {
+        ProductInfo {
+            id: id.to_owned(),
+            sku: id.to_ascii_uppercase(),
+            name: name.to_owned(),
+            category: "addon".to_owned(),
+            unit_price,
+            active,
+        }
+    }
+
+    fn account(
+        id: &str,
+        segment: &str,
+        region: &str,
+        employees: u32,
+        deal: f64,
+    ) -> CustomerProfile {
+        CustomerProfile {
+            id: id.to_owned(),
+            segment: segment.to_owned(),
+            industry: (segment == "enterprise").then(|| "saas".to_owned()),
+            employee_count: Some(employees),
+            region: region.to_owned(),
+            avg_deal_size: deal,
+        }
+    }
+
+    /// Two enterprise accounts that keep buying the plan with SSO, one SMB account that only
+    /// bought backups (alongside a product that has since been retired).
+    fn fixture_snapshot() -> SuggestionSnapshot {
+        let quotes: [(&str, &str, &[&str]); 7] = [
+            ("Q-1", "acme_enterprise", &["prod_pro_v2", "prod_sso"]),
+            ("Q-2", "acme_enterprise", &["prod_pro_v2", "prod_sso"]),
+            ("Q-3", "acme_enterprise", &["prod_pro_v2", "prod_sso"]),
+            ("Q-4", "northbridge", &["prod_pro_v2", "prod_sso", "prod_support"]),
+            ("Q-5", "northbridge", &["prod_pro_v2", "prod_sso", "prod_support"]),
+            ("Q-6", "northbridge", &["prod_pro_v2", "prod_sso"]),
+            ("Q-7", "founder_smb", &["prod_backup", "prod_legacy"]),
+        ];
+
+        let now = Utc::now();
+        let mut stats = CoOccurrenceStats::new();
+        let mut purchases = Vec::new();
+        for (index, (quote_id, customer_id, products)) in quotes.iter().enumerate() {
+            stats.add_basket(products);
+            for product_id in products.iter() {
+                purchases.push(ProductPurchase {
+                    customer_id: (*customer_id).to_owned(),
+                    product_id: (*product_id).to_owned(),
+                    quote_id: (*quote_id).to_owned(),
+                    purchased_at: now - Duration::days(5 * index as i64),
+                });
+            }
+        }
+
+        SuggestionSnapshot {
+            products: vec![
+                product("prod_pro_v2", "Pro Plan", 12_000.0, true),
+                product("prod_sso", "SSO Add-on", 2_400.0, true),
+                product("prod_support", "Premium Support", 6_500.0, true),
+                product("prod_backup", "Automated Backups", 4_000.0, true),
+                product("prod_legacy", "Legacy Connector", 900.0, false),
+            ],
+            customers: vec![
+                account("acme_enterprise", "enterprise", "us", 1200, 95_000.0),
+                account("northbridge", "enterprise", "us", 1000, 90_000.0),
+                account("founder_smb", "smb", "apac", 40, 8_000.0),
+            ],
+            purchases,
+            relationships: stats.relationships(&MiningThresholds::default()),
+            acceptance: HashMap::new(),
+        }
+    }
+
+    fn fixture_engine() -> SuggestionEngine {
+        SuggestionEngine::from_snapshot(fixture_snapshot())
+    }
+
     #[tokio::test]
     async fn test_get_suggestions() {
-        let engine = SuggestionEngine::new();
+        let engine = fixture_engine();
 
         let request = SuggestionRequest::new("test_customer")
             .with_current_products(vec!["prod_pro_v2".to_string()])
@@ -1010,7 +728,7 @@ mod tests {
 
         let suggestions = engine.get_suggestions(request).await.unwrap();
 
-        // Should return suggestions from deterministic engine input.
+        assert!(!suggestions.is_empty());
         for suggestion in &suggestions {
             assert!(suggestion.score >= super::super::MIN_SUGGESTION_SCORE);
             assert!(!suggestion.product_name.is_empty());
@@ -1018,6 +736,54 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn engine_without_snapshot_suggests_nothing() {
+        let suggestions = SuggestionEngine::new()
+            .get_suggestions(SuggestionRequest::new("test_customer"))
+            .await
+            .expect("suggestions");
+        assert!(suggestions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn candidates_come_from_active_catalog_products_not_on_the_quote() {
+        let engine = fixture_engine();
+        let request = SuggestionRequest::new("test_customer")
+            .with_current_products(vec!["prod_pro_v2".to_string()])
+            .with_max_suggestions(10);
+
+        let suggestions = engine.get_suggestions(request).await.expect("suggestions");
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.product_id.as_str()).collect();
+        assert!(ids.contains(&"prod_sso"), "{ids:?}");
+        assert!(!ids.contains(&"prod_pro_v2"), "products on the quote are never suggested");
+        assert!(!ids.contains(&"prod_legacy"), "inactive products are never suggested");
+    }
+
+    #[tokio::test]
+    async fn similar_accounts_and_mined_relationships_drive_scores() {
+        let engine = fixture_engine();
+        let request = SuggestionRequest::new("test_customer")
+            .with_current_products(vec!["prod_pro_v2".to_string()]);
+
+        let suggestions = engine.get_suggestions(request).await.expect("suggestions");
+        let sso = suggestions
+            .iter()
+            .find(|suggestion| suggestion.product_id == "prod_sso")
+            .expect("prod_sso suggested");
+
+        // Plan and SSO appear on the same six quotes, so the mined rule is a full-confidence
+        // bundle; the SMB account is too far from an enterprise prospect to count as a peer.
+        assert!((sso.component_scores.product_relationship - 1.0).abs() < 1e-9);
+        assert!(sso.component_scores.similar_customer > 0.0);
+        let peers = sso
+            .reasoning
+            .iter()
+            .find(|line| line.starts_with("Similar customers"))
+            .expect("peer reasoning");
+        assert!(peers.contains("acme_enterprise") && peers.contains("northbridge"), "{peers}");
+        assert!(!peers.contains("founder_smb"), "{peers}");
+    }
+
     #[test]
     fn test_default_weights() {
         let engine = SuggestionEngine::new();
@@ -1034,7 +800,7 @@ mod tests {
 
     #[tokio::test]
     async fn feedback_cache_adjusts_scores_and_reasoning() {
-        let mut engine = SuggestionEngine::new();
+        let mut engine = fixture_engine();
         let request = SuggestionRequest::new("test_customer")
             .with_current_products(vec!["prod_pro_v2".to_string()]);
 
@@ -1073,7 +839,7 @@ mod tests {
 
     #[tokio::test]
     async fn clear_caches_clears_feedback_adjustments() {
-        let mut engine = SuggestionEngine::new();
+        let mut engine = fixture_engine();
         let request = SuggestionRequest::new("test_customer")
             .with_current_products(vec!["prod_pro_v2".to_string()]);
 
@@ -1137,7 +903,7 @@ mod tests {
             },
         );
 
-        let mut control = SuggestionEngine::new();
+        let mut control = fixture_engine();
         control.warm_feedback_cache(feedback.clone());
         control.set_feedback_variant_override(Some(FeedbackExperimentVariant::Control));
         let control_sso = control
@@ -1149,7 +915,7 @@ mod tests {
             .expect("control should include prod_sso")
             .score;
 
-        let mut treatment = SuggestionEngine::new();
+        let mut treatment = fixture_engine();
         treatment.warm_feedback_cache(feedback);
         treatment.set_feedback_variant_override(Some(FeedbackExperimentVariant::Treatment));
         let treatment_sso = treatment
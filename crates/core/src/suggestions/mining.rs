@@ -0,0 +1,262 @@
+//! Association-rule mining over quote baskets
+//!
+//! Each quote contributes one basket (its distinct product ids). Counts are kept per product and
+//! per unordered product pair so a changed quote can be applied as a remove/add delta instead of
+//! rescanning every quote; support, confidence and lift are derived from the counts on demand.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::types::{ProductRelationship, RelationshipType};
+
+/// Confidence both ways at or above this marks a pair as a bundle.
+const BUNDLE_CONFIDENCE: f64 = 0.60;
+/// One-way confidence at or above this marks the target as an add-on of the source.
+const ADD_ON_CONFIDENCE: f64 = 0.40;
+
+/// Thresholds a pair must clear before it is emitted as a relationship.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiningThresholds {
+    /// Minimum number of baskets containing both products.
+    pub min_co_occurrence: u32,
+    /// Minimum share of all baskets containing both products.
+    pub min_support: f64,
+    /// Minimum P(target | source).
+    pub min_confidence: f64,
+    /// Minimum lift; values above 1.0 mean the pair appears together more than chance.
+    pub min_lift: f64,
+}
+
+impl Default for MiningThresholds {
+    fn default() -> Self {
+        Self { min_co_occurrence: 2, min_support: 0.01, min_confidence: 0.20, min_lift: 1.0 }
+    }
+}
+
+/// Product and pair counts across all indexed baskets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoOccurrenceStats {
+    basket_count: u32,
+    product_counts: HashMap<String, u32>,
+    /// Keyed by `(a, b)` with `a < b`.
+    pair_counts: HashMap<(String, String), u32>,
+}
+
+impl CoOccurrenceStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild stats from persisted counts. Pair keys are normalized to `a < b`.
+    pub fn from_counts(
+        basket_count: u32,
+        product_counts: HashMap<String, u32>,
+        pair_counts: impl IntoIterator<Item = ((String, String), u32)>,
+    ) -> Self {
+        let mut stats = Self { basket_count, product_counts, pair_counts: HashMap::new() };
+        for ((a, b), count) in pair_counts {
+            if a != b && count > 0 {
+                *stats.pair_counts.entry(ordered_pair(a, b)).or_insert(0) += count;
+            }
+        }
+        stats
+    }
+
+    pub fn basket_count(&self) -> u32 {
+        self.basket_count
+    }
+
+    pub fn product_count(&self, product_id: &str) -> u32 {
+        self.product_counts.get(product_id).copied().unwrap_or(0)
+    }
+
+    pub fn pair_count(&self, a: &str, b: &str) -> u32 {
+        let key = ordered_pair(a.to_owned(), b.to_owned());
+        self.pair_counts.get(&key).copied().unwrap_or(0)
+    }
+
+    /// Count one basket. Duplicate ids within the basket are counted once; empty baskets are
+    /// ignored.
+    pub fn add_basket<S: AsRef<str>>(&mut self, products: &[S]) {
+        let items = basket_items(products);
+        if items.is_empty() {
+            return;
+        }
+        self.basket_count += 1;
+        for item in &items {
+            *self.product_counts.entry((*item).to_owned()).or_insert(0) += 1;
+        }
+        for (a, b) in basket_pairs(&items) {
+            *self.pair_counts.entry((a.to_owned(), b.to_owned())).or_insert(0) += 1;
+        }
+    }
+
+    /// Undo a previously added basket, e.g. before re-adding a quote whose lines changed.
+    pub fn remove_basket<S: AsRef<str>>(&mut self, products: &[S]) {
+        let items = basket_items(products);
+        if items.is_empty() {
+            return;
+        }
+        self.basket_count = self.basket_count.saturating_sub(1);
+        for item in &items {
+            decrement(&mut self.product_counts, (*item).to_owned());
+        }
+        for (a, b) in basket_pairs(&items) {
+            decrement(&mut self.pair_counts, (a.to_owned(), b.to_owned()));
+        }
+    }
+
+    /// Directed relationships for every pair clearing `thresholds`, strongest first.
+    pub fn relationships(&self, thresholds: &MiningThresholds) -> Vec<ProductRelationship> {
+        if self.basket_count == 0 {
+            return Vec::new();
+        }
+        let total = f64::from(self.basket_count);
+        let mut relationships = Vec::new();
+
+        for ((a, b), &pair_count) in &self.pair_counts {
+            if pair_count < thresholds.min_co_occurrence.max(1) {
+                continue;
+            }
+            let support = f64::from(pair_count) / total;
+            if support < thresholds.min_support {
+                continue;
+            }
+            let count_a = f64::from(self.product_count(a).max(pair_count));
+            let count_b = f64::from(self.product_count(b).max(pair_count));
+            let lift = support / ((count_a / total) * (count_b / total));
+            if lift < thresholds.min_lift {
+                continue;
+            }
+
+            let a_to_b = f64::from(pair_count) / count_a;
+            let b_to_a = f64::from(pair_count) / count_b;
+            let relationship_type = |forward: f64| {
+                if a_to_b >= BUNDLE_CONFIDENCE && b_to_a >= BUNDLE_CONFIDENCE {
+                    RelationshipType::Bundle
+                } else if forward >= ADD_ON_CONFIDENCE {
+                    RelationshipType::AddOn
+                } else {
+                    RelationshipType::CrossSell
+                }
+            };
+
+            for (source, target, confidence) in [(a, b, a_to_b), (b, a, b_to_a)] {
+                if confidence < thresholds.min_confidence {
+                    continue;
+                }
+                relationships.push(ProductRelationship {
+                    id: format!("rel:{source}:{target}"),
+                    source_product_id: source.clone(),
+                    target_product_id: target.clone(),
+                    relationship_type: relationship_type(confidence),
+                    confidence,
+                    co_occurrence_count: pair_count,
+                    support,
+                    lift,
+                });
+            }
+        }
+
+        relationships.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.co_occurrence_count.cmp(&a.co_occurrence_count))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        relationships
+    }
+}
+
+fn ordered_pair(a: String, b: String) -> (String, String) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn basket_items<S: AsRef<str>>(products: &[S]) -> BTreeSet<&str> {
+    products.iter().map(AsRef::as_ref).filter(|id| !id.trim().is_empty()).collect()
+}
+
+fn basket_pairs<'a>(items: &BTreeSet<&'a str>) -> Vec<(&'a str, &'a str)> {
+    let items: Vec<&str> = items.iter().copied().collect();
+    let mut pairs = Vec::new();
+    for (index, a) in items.iter().enumerate() {
+        for b in &items[index + 1..] {
+            pairs.push((*a, *b));
+        }
+    }
+    pairs
+}
+
+fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, u32>, key: K) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(baskets: &[&[&str]]) -> CoOccurrenceStats {
+        let mut stats = CoOccurrenceStats::new();
+        for basket in baskets {
+            stats.add_basket(basket);
+        }
+        stats
+    }
+
+    #[test]
+    fn relationships_report_support_confidence_and_lift() {
+        let stats = stats(&[
+            &["plan", "sso"],
+            &["plan", "sso"],
+            &["plan", "sso", "support"],
+            &["plan"],
+            &["support"],
+        ]);
+
+        let relationships = stats.relationships(&MiningThresholds::default());
+        let plan_to_sso = relationships
+            .iter()
+            .find(|rel| rel.source_product_id == "plan" && rel.target_product_id == "sso")
+            .expect("plan -> sso");
+        assert_eq!(plan_to_sso.co_occurrence_count, 3);
+        assert!((plan_to_sso.support - 0.6).abs() < 1e-9);
+        assert!((plan_to_sso.confidence - 0.75).abs() < 1e-9);
+        assert!((plan_to_sso.lift - 0.6 / (0.8 * 0.6)).abs() < 1e-9);
+        assert_eq!(plan_to_sso.relationship_type, RelationshipType::Bundle);
+
+        // plan/support co-occur once: below the default co-occurrence floor.
+        assert!(!relationships.iter().any(|rel| rel.target_product_id == "support"));
+    }
+
+    #[test]
+    fn removing_a_basket_matches_a_fresh_count() {
+        let mut incremental = stats(&[&["plan", "sso"], &["plan", "backup"]]);
+        incremental.remove_basket(&["plan", "backup"]);
+        incremental.add_basket(&["plan", "sso", "sso"]);
+
+        let fresh = stats(&[&["plan", "sso"], &["sso", "plan"]]);
+        assert_eq!(incremental, fresh);
+        assert_eq!(incremental.product_count("backup"), 0);
+        assert_eq!(incremental.pair_count("sso", "plan"), 2);
+    }
+
+    #[test]
+    fn from_counts_normalizes_pair_order() {
+        let stats = CoOccurrenceStats::from_counts(
+            4,
+            HashMap::from([("a".to_owned(), 3), ("b".to_owned(), 2)]),
+            [(("b".to_owned(), "a".to_owned()), 2)],
+        );
+        assert_eq!(stats.pair_count("a", "b"), 2);
+        assert_eq!(stats.relationships(&MiningThresholds::default()).len(), 2);
+    }
+}
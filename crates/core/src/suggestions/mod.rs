@@ -1,13 +1,15 @@
 //! Smart Product Suggestions Engine
 //!
-//! Provides AI-powered product recommendations based on customer similarity,
-//! product relationships, temporal patterns, and business rules.
+//! Provides product recommendations from the live catalog based on customer similarity,
+//! product relationships mined from quote co-occurrence, temporal patterns, and business rules.
 
 mod engine;
+mod mining;
 mod scoring;
 mod types;
 
 pub use engine::SuggestionEngine;
+pub use mining::{CoOccurrenceStats, MiningThresholds};
 pub use scoring::{FeedbackExperimentVariant, ScoreCalculator, ScoringWeights};
 pub use types::*;
 
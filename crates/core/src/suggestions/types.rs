@@ -1,5 +1,7 @@
 //! Types for the Suggestion Engine
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -166,6 +168,10 @@ pub struct ProductRelationship {
     pub relationship_type: RelationshipType,
     pub confidence: f64,
     pub co_occurrence_count: u32,
+    /// Share of all quotes containing both products.
+    pub support: f64,
+    /// How much more often the pair appears together than if bought independently.
+    pub lift: f64,
 }
 
 /// Type of product relationship
@@ -290,3 +296,27 @@ pub struct ProductInfo {
     pub unit_price: f64,
     pub active: bool,
 }
+
+/// A product appearing on one of a customer's quotes
+#[derive(Debug, Clone)]
+pub struct ProductPurchase {
+    pub customer_id: String,
+    pub product_id: String,
+    pub quote_id: String,
+    pub purchased_at: DateTime<Utc>,
+}
+
+/// Catalog and history the engine scores against, loaded from the database
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionSnapshot {
+    /// Candidate products (inactive products are never suggested)
+    pub products: Vec<ProductInfo>,
+    /// Known accounts, used as the peer pool for similarity
+    pub customers: Vec<CustomerProfile>,
+    /// Quote line history per account
+    pub purchases: Vec<ProductPurchase>,
+    /// Relationships mined from quote co-occurrence
+    pub relationships: Vec<ProductRelationship>,
+    /// Acceptance rates from suggestion feedback, keyed by product id
+    pub acceptance: HashMap<String, ProductAcceptanceRate>,
+}
@@ -0,0 +1,432 @@
+//! Outbound webhooks for quote lifecycle events.
+//!
+//! Subscribers pick the events they want with patterns over the dotted event type (an exact
+//! type, a `namespace.*` wildcard, or `*`). Every delivery carries a
+//! `Quotey-Signature: t=<unix seconds>,v1=<hex>` header, where `v1` is the HMAC-SHA256 of
+//! `"{t}.{body}"` keyed with the subscription secret. Receivers recompute it with
+//! [`verify_signature`] and reject timestamps outside [`SIGNATURE_TOLERANCE_SECS`], so a captured
+//! request cannot be replayed later. The timestamp is taken when each attempt is sent, not when
+//! the event happened, so retries hours later still verify.
+//!
+//! Subscription URLs must point at public hosts: `localhost` and loopback, link-local, private
+//! and unspecified addresses are refused when a subscription is registered and again, after DNS
+//! resolution, before every delivery (see [`TargetPolicy`]).
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "Quotey-Signature";
+pub const EVENT_TYPE_HEADER: &str = "Quotey-Event";
+pub const EVENT_ID_HEADER: &str = "Quotey-Event-Id";
+pub const DELIVERY_ID_HEADER: &str = "Quotey-Delivery";
+/// How far a signature timestamp may drift from the receiver's clock.
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+pub const SECRET_PREFIX: &str = "whsec_";
+
+const MAX_EVENT_PATTERNS: usize = 32;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum WebhookError {
+    #[error("invalid webhook url: {0}")]
+    InvalidUrl(String),
+    #[error("webhook url points at a non-public address: {0}")]
+    BlockedTarget(String),
+    #[error("webhook host `{0}` could not be resolved")]
+    UnresolvedTarget(String),
+    #[error("unknown webhook event pattern `{0}`")]
+    UnknownEvent(String),
+    #[error("a subscription needs between 1 and {MAX_EVENT_PATTERNS} event patterns")]
+    EventCount,
+    #[error("malformed {SIGNATURE_HEADER} header")]
+    MalformedSignature,
+    #[error("signature timestamp is {age_secs}s away from now")]
+    StaleTimestamp { age_secs: i64 },
+    #[error("signature does not match the payload")]
+    SignatureMismatch,
+}
+
+/// Quote lifecycle events a subscription can receive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "quote.finalized")]
+    QuoteFinalized,
+    #[serde(rename = "quote.accepted")]
+    QuoteAccepted,
+    #[serde(rename = "approval.decided")]
+    ApprovalDecided,
+    #[serde(rename = "portal.comment_added")]
+    PortalCommentAdded,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 4] = [
+        Self::QuoteFinalized,
+        Self::QuoteAccepted,
+        Self::ApprovalDecided,
+        Self::PortalCommentAdded,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::QuoteFinalized => "quote.finalized",
+            Self::QuoteAccepted => "quote.accepted",
+            Self::ApprovalDecided => "approval.decided",
+            Self::PortalCommentAdded => "portal.comment_added",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        Self::ALL.into_iter().find(|event| event.as_str() == label)
+    }
+}
+
+/// Event patterns a subscription listens to, validated against [`WebhookEventType::ALL`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter(Vec<String>);
+
+impl EventFilter {
+    /// Trims and deduplicates `patterns`. Each must be `*`, a known event type, or
+    /// `namespace.*` for a namespace with at least one known event.
+    pub fn parse<S: AsRef<str>>(patterns: &[S]) -> Result<Self, WebhookError> {
+        let mut parsed: Vec<String> = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim().to_ascii_lowercase();
+            let known = pattern == "*"
+                || match pattern.strip_suffix(".*") {
+                    Some(namespace) => WebhookEventType::ALL
+                        .iter()
+                        .any(|event| event.as_str().starts_with(&format!("{namespace}."))),
+                    None => WebhookEventType::parse_label(&pattern).is_some(),
+                };
+            if !known {
+                return Err(WebhookError::UnknownEvent(pattern));
+            }
+            if !parsed.contains(&pattern) {
+                parsed.push(pattern);
+            }
+        }
+        if parsed.is_empty() || parsed.len() > MAX_EVENT_PATTERNS {
+            return Err(WebhookError::EventCount);
+        }
+        Ok(Self(parsed))
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn matches(&self, event: WebhookEventType) -> bool {
+        let event = event.as_str();
+        self.0.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => event.starts_with(prefix),
+            None => pattern == event,
+        })
+    }
+}
+
+/// Body posted to subscribers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub quote_id: String,
+    pub occurred_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: WebhookEventType, quote_id: impl Into<String>, data: Value) -> Self {
+        Self {
+            id: format!("EVT-{}", uuid::Uuid::new_v4().simple()),
+            event_type,
+            quote_id: quote_id.into(),
+            occurred_at: Utc::now(),
+            data,
+        }
+    }
+}
+
+/// Which hosts webhook URLs may point at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetPolicy {
+    /// Public hosts only; the default everywhere outside tests.
+    #[default]
+    PublicOnly,
+    /// Any host, including loopback receivers in tests.
+    AllowPrivate,
+}
+
+/// Checks that `url` is an absolute http(s) URL with a host that `policy` allows.
+pub fn validate_url(url: &str, policy: TargetPolicy) -> Result<String, WebhookError> {
+    let url = url.trim();
+    let (host, _) = target_host(url)?;
+    if policy == TargetPolicy::PublicOnly {
+        if host == "localhost" || host.ends_with(".localhost") {
+            return Err(WebhookError::BlockedTarget(host));
+        }
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            check_address(ip)?;
+        }
+    }
+    Ok(url.to_string())
+}
+
+/// Refuses loopback, link-local, private (RFC 1918, unique local, shared) and unspecified
+/// addresses, including IPv4 addresses mapped into IPv6.
+pub fn check_address(ip: IpAddr) -> Result<(), WebhookError> {
+    let blocked = match ip {
+        IpAddr::V4(ip) => is_blocked_v4(ip),
+        IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or_else(|| is_blocked_v6(ip), is_blocked_v4),
+    };
+    if blocked {
+        return Err(WebhookError::BlockedTarget(ip.to_string()));
+    }
+    Ok(())
+}
+
+fn is_blocked_v4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || first == 0
+        || (first == 100 && (64..128).contains(&second))
+}
+
+fn is_blocked_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
+
+/// Lowercased host (IPv6 without brackets) and port of an absolute http(s) URL.
+pub fn target_host(url: &str) -> Result<(String, u16), WebhookError> {
+    let (rest, default_port) = match url.strip_prefix("https://") {
+        Some(rest) => (rest, 443),
+        None => url.strip_prefix("http://").map(|rest| (rest, 80)).ok_or_else(|| {
+            WebhookError::InvalidUrl("must start with http:// or https://".into())
+        })?,
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.contains('@') {
+        return Err(WebhookError::InvalidUrl("must not carry credentials".into()));
+    }
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, after) = bracketed
+                .split_once(']')
+                .ok_or_else(|| WebhookError::InvalidUrl("unterminated IPv6 host".into()))?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    if host.is_empty() || url.chars().any(char::is_whitespace) {
+        return Err(WebhookError::InvalidUrl("must name a host".into()));
+    }
+    let port = match port {
+        Some(port) => {
+            port.parse().map_err(|_| WebhookError::InvalidUrl(format!("invalid port `{port}`")))?
+        }
+        None => default_port,
+    };
+    Ok((host.trim_end_matches('.').to_ascii_lowercase(), port))
+}
+
+/// New random subscription secret.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{SECRET_PREFIX}{}", encode_hex(&bytes))
+}
+
+/// `Quotey-Signature` header value for `body` sent at `timestamp`.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    format!(
+        "t={timestamp},v1={}",
+        encode_hex(&mac(secret, timestamp, body).finalize().into_bytes())
+    )
+}
+
+/// Verifies a `Quotey-Signature` header against `body` as a receiver would, rejecting
+/// timestamps more than `tolerance_secs` away from `now`.
+pub fn verify_signature(
+    secret: &str,
+    header: &str,
+    body: &str,
+    now: DateTime<Utc>,
+    tolerance_secs: i64,
+) -> Result<(), WebhookError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(decode_hex(value)),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or(WebhookError::MalformedSignature)?;
+    if signatures.is_empty() {
+        return Err(WebhookError::MalformedSignature);
+    }
+    let age_secs = now.timestamp() - timestamp;
+    if age_secs.abs() > tolerance_secs {
+        return Err(WebhookError::StaleTimestamp { age_secs });
+    }
+    let expected = mac(secret, timestamp, body);
+    signatures
+        .into_iter()
+        .flatten()
+        .any(|signature| expected.clone().verify_slice(&signature).is_ok())
+        .then_some(())
+        .ok_or(WebhookError::SignatureMismatch)
+}
+
+fn mac(secret: &str, timestamp: i64, body: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body.as_bytes());
+    mac
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn filters_match_exact_types_namespaces_and_everything() {
+        let filter = EventFilter::parse(&["quote.*", " approval.decided ", "quote.*"]).unwrap();
+        assert_eq!(filter.patterns(), ["quote.*", "approval.decided"]);
+        assert!(filter.matches(WebhookEventType::QuoteFinalized));
+        assert!(filter.matches(WebhookEventType::QuoteAccepted));
+        assert!(filter.matches(WebhookEventType::ApprovalDecided));
+        assert!(!filter.matches(WebhookEventType::PortalCommentAdded));
+
+        let all = EventFilter::parse(&["*"]).unwrap();
+        assert!(WebhookEventType::ALL.into_iter().all(|event| all.matches(event)));
+
+        assert_eq!(
+            EventFilter::parse(&["quote.deleted"]),
+            Err(WebhookError::UnknownEvent("quote.deleted".into()))
+        );
+        assert_eq!(
+            EventFilter::parse(&["invoice.*"]),
+            Err(WebhookError::UnknownEvent("invoice.*".into()))
+        );
+        assert_eq!(EventFilter::parse::<&str>(&[]), Err(WebhookError::EventCount));
+    }
+
+    #[test]
+    fn signatures_verify_within_tolerance_and_reject_replays_and_tampering() {
+        let secret = generate_secret();
+        assert!(secret.starts_with(SECRET_PREFIX));
+        let body = r#"{"type":"quote.finalized"}"#;
+        let sent_at = Utc::now();
+        let header = sign_payload(&secret, sent_at.timestamp(), body);
+
+        assert_eq!(verify_signature(&secret, &header, body, sent_at, 300), Ok(()));
+        assert_eq!(
+            verify_signature(&secret, &header, body, sent_at + Duration::seconds(301), 300),
+            Err(WebhookError::StaleTimestamp { age_secs: 301 })
+        );
+        assert_eq!(
+            verify_signature(&secret, &header, r#"{"type":"quote.accepted"}"#, sent_at, 300),
+            Err(WebhookError::SignatureMismatch)
+        );
+        assert_eq!(
+            verify_signature("whsec_other", &header, body, sent_at, 300),
+            Err(WebhookError::SignatureMismatch)
+        );
+        // Moving the timestamp forward invalidates the signature it was computed with.
+        let forged = header.replacen(
+            &format!("t={}", sent_at.timestamp()),
+            &format!("t={}", sent_at.timestamp() + 60),
+            1,
+        );
+        assert_eq!(
+            verify_signature(&secret, &forged, body, sent_at + Duration::seconds(60), 300),
+            Err(WebhookError::SignatureMismatch)
+        );
+        assert_eq!(
+            verify_signature(&secret, "v1=abcd", body, sent_at, 300),
+            Err(WebhookError::MalformedSignature)
+        );
+    }
+
+    #[test]
+    fn urls_must_be_absolute_http() {
+        let public = TargetPolicy::PublicOnly;
+        assert_eq!(
+            validate_url(" https://hooks.example.com/q ", public).unwrap(),
+            "https://hooks.example.com/q"
+        );
+        assert!(validate_url("https://hooks.example.com:8443/q", public).is_ok());
+        assert!(validate_url("ftp://example.com", public).is_err());
+        assert!(validate_url("https:///path", public).is_err());
+        assert!(validate_url("https://hooks.example.com:http/q", public).is_err());
+        assert!(validate_url("https://user:pw@hooks.example.com/q", public).is_err());
+    }
+
+    #[test]
+    fn urls_must_not_point_at_internal_hosts() {
+        let public = TargetPolicy::PublicOnly;
+        for url in [
+            "http://localhost:9000/hook",
+            "http://LOCALHOST./hook",
+            "http://api.localhost/hook",
+            "http://127.0.0.1:9000",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5/hook",
+            "http://172.16.3.4/hook",
+            "http://192.168.1.10/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]:8080/hook",
+            "http://[fe80::1]/hook",
+            "http://[fd00::2]/hook",
+            "http://[::ffff:169.254.169.254]/hook",
+        ] {
+            assert!(
+                matches!(validate_url(url, public), Err(WebhookError::BlockedTarget(_))),
+                "{url}"
+            );
+        }
+        assert!(validate_url("http://172.32.0.1/hook", public).is_ok());
+        assert!(validate_url("http://[2001:db8::1]/hook", public).is_ok());
+        assert!(validate_url("http://127.0.0.1:9000", TargetPolicy::AllowPrivate).is_ok());
+    }
+}
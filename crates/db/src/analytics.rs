@@ -0,0 +1,375 @@
+//! Runs [`AnalyticsQuerySpec`]s for the REST API, MCP, CLI and analytics dashboard.
+//!
+//! Specs are validated and compiled by [`SqlAnalyticsQueryBuilder`] with every caller value bound
+//! as a parameter. Results are capped at the spec's row limit and flagged when truncated.
+//! Identical specs are answered from an [`AnalyticsCache`] for a short TTL, because dashboards
+//! re-issue the same handful of queries on every refresh.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use quotey_core::chrono::{DateTime, Utc};
+use quotey_core::{AnalyticsQuerySpec, MetricKind};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::Row;
+use thiserror::Error;
+
+use crate::repositories::{
+    AnalyticsParam, AnalyticsQueryError, RepositoryError, SqlAnalyticsQueryBuilder,
+};
+use crate::DbPool;
+
+/// How long a cached result is served before the query runs again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Cached results kept at once; the oldest is dropped past this.
+const MAX_CACHE_ENTRIES: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsColumnKind {
+    Dimension,
+    Metric,
+}
+
+impl AnalyticsColumnKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Dimension => "dimension",
+            Self::Metric => "metric",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AnalyticsColumn {
+    pub name: &'static str,
+    pub kind: AnalyticsColumnKind,
+}
+
+/// Rows keyed by column name; `columns` gives the order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AnalyticsResult {
+    pub schema_version: String,
+    pub columns: Vec<AnalyticsColumn>,
+    pub rows: Vec<Map<String, Value>>,
+    /// More rows matched than the spec's limit allowed.
+    pub truncated: bool,
+    pub generated_at: DateTime<Utc>,
+    /// Served from the cache rather than the database.
+    pub cached: bool,
+}
+
+impl AnalyticsResult {
+    /// CSV with a header row. Missing metric values are empty cells.
+    pub fn to_csv(&self) -> String {
+        let mut csv = self.columns.iter().map(|column| column.name).collect::<Vec<_>>().join(",");
+        csv.push('\n');
+        for row in &self.rows {
+            let cells: Vec<String> = self
+                .columns
+                .iter()
+                .map(|column| match row.get(column.name) {
+                    Some(Value::String(text)) => csv_field(text),
+                    Some(Value::Null) | None => String::new(),
+                    Some(other) => other.to_string(),
+                })
+                .collect();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnalyticsServiceError {
+    #[error(transparent)]
+    Invalid(#[from] AnalyticsQueryError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for AnalyticsServiceError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+/// Result cache shared by every [`AnalyticsService`] given a clone of it.
+#[derive(Clone, Debug)]
+pub struct AnalyticsCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (Instant, AnalyticsResult)>>>,
+}
+
+impl Default for AnalyticsCache {
+    fn default() -> Self {
+        Self::with_ttl(DEFAULT_CACHE_TTL)
+    }
+}
+
+impl AnalyticsCache {
+    /// A zero TTL disables caching.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self { ttl, entries: Arc::default() }
+    }
+
+    fn get(&self, key: &str) -> Option<AnalyticsResult> {
+        let entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < self.ttl)
+            .map(|(_, result)| result.clone())
+    }
+
+    fn put(&self, key: String, result: &AnalyticsResult) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        if entries.len() >= MAX_CACHE_ENTRIES {
+            if let Some(oldest) =
+                entries.iter().min_by_key(|(_, (stored_at, _))| *stored_at).map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (Instant::now(), result.clone()));
+    }
+}
+
+pub struct AnalyticsService {
+    pool: DbPool,
+    builder: SqlAnalyticsQueryBuilder,
+    cache: AnalyticsCache,
+}
+
+impl AnalyticsService {
+    /// A service with its own cache; use [`Self::with_cache`] to share one across requests.
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool, builder: SqlAnalyticsQueryBuilder, cache: AnalyticsCache::default() }
+    }
+
+    pub fn with_cache(mut self, cache: AnalyticsCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub async fn run(
+        &self,
+        spec: &AnalyticsQuerySpec,
+    ) -> Result<AnalyticsResult, AnalyticsServiceError> {
+        let query = self.builder.build_query(spec)?;
+        let key = serde_json::to_string(spec).unwrap_or_default();
+        if let Some(mut result) = self.cache.get(&key) {
+            result.cached = true;
+            return Ok(result);
+        }
+
+        let mut statement = sqlx::query(&query.sql);
+        for param in &query.params {
+            statement = match param {
+                AnalyticsParam::Text(value) => statement.bind(value.clone()),
+                AnalyticsParam::Integer(value) => statement.bind(*value),
+            };
+        }
+        let mut fetched = statement.fetch_all(&self.pool).await?;
+
+        let limit = spec.row_limit() as usize;
+        let truncated = fetched.len() > limit;
+        fetched.truncate(limit);
+
+        let mut columns: Vec<AnalyticsColumn> = spec
+            .dimensions
+            .iter()
+            .map(|dimension| AnalyticsColumn {
+                name: dimension.as_str(),
+                kind: AnalyticsColumnKind::Dimension,
+            })
+            .collect();
+        columns.extend(spec.metrics.iter().map(|metric| AnalyticsColumn {
+            name: metric.as_str(),
+            kind: AnalyticsColumnKind::Metric,
+        }));
+
+        let mut rows = Vec::with_capacity(fetched.len());
+        for record in &fetched {
+            let mut row = Map::new();
+            for (index, dimension) in spec.dimensions.iter().enumerate() {
+                let value: Option<String> = record.try_get(index)?;
+                row.insert(dimension.as_str().to_string(), value.map_or(Value::Null, Value::from));
+            }
+            for (offset, metric) in spec.metrics.iter().enumerate() {
+                let index = spec.dimensions.len() + offset;
+                let value = if *metric == MetricKind::QuoteCount {
+                    record.try_get::<Option<i64>, _>(index)?.map_or(Value::Null, Value::from)
+                } else {
+                    record.try_get::<Option<f64>, _>(index)?.map_or(Value::Null, Value::from)
+                };
+                row.insert(metric.as_str().to_string(), value);
+            }
+            rows.push(row);
+        }
+
+        let result = AnalyticsResult {
+            schema_version: spec.schema_version.clone(),
+            columns,
+            rows,
+            truncated,
+            generated_at: Utc::now(),
+            cached: false,
+        };
+        self.cache.put(key, &result);
+        Ok(result)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::{AnalyticsFilter, DimensionKind};
+
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+
+    type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+    async fn setup() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await?;
+        migrations::run_pending(&pool).await?;
+        let now = Utc::now().to_rfc3339();
+        for (id, status, rep, notes, discount) in [
+            ("Q-A1", "accepted", "rep-ana", r#"{"region":"emea"}"#, 10.0),
+            ("Q-A2", "draft", "rep-ana", "call back next week, pricing, \"urgent\"", 0.0),
+            ("Q-B1", "finalized", "rep-ben", r#"{"region":"amer"}"#, 20.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO quote (id, status, currency, created_by, notes, created_at, updated_at)
+                 VALUES (?, ?, 'USD', ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(status)
+            .bind(rep)
+            .bind(notes)
+            .bind(&now)
+            .bind(&now)
+            .execute(&pool)
+            .await?;
+            sqlx::query(
+                "INSERT INTO quote_line
+                    (id, quote_id, product_id, quantity, unit_price, discount_pct, created_at, updated_at)
+                 VALUES (?, ?, 'plan-pro', 2, 100.0, ?, ?, ?)",
+            )
+            .bind(format!("{id}-L1"))
+            .bind(id)
+            .bind(discount)
+            .bind(&now)
+            .bind(&now)
+            .execute(&pool)
+            .await?;
+        }
+        Ok(pool)
+    }
+
+    #[tokio::test]
+    async fn every_metric_and_dimension_executes_against_the_schema() -> TestResult<()> {
+        let pool = setup().await?;
+        let spec =
+            AnalyticsQuerySpec::new(MetricKind::ALL.to_vec(), DimensionKind::ALL.to_vec(), 30);
+        let result = AnalyticsService::new(pool).run(&spec).await?;
+        assert_eq!(result.columns.len(), MetricKind::ALL.len() + DimensionKind::ALL.len());
+        assert_eq!(result.rows.len(), 3);
+        assert!(!result.truncated);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn groups_filter_and_cap_rows() -> TestResult<()> {
+        let pool = setup().await?;
+        let service = AnalyticsService::new(pool);
+        let mut spec = AnalyticsQuerySpec::new(
+            vec![MetricKind::QuoteCount, MetricKind::AvgDiscountPct],
+            vec![DimensionKind::SalesRep],
+            30,
+        );
+        let result = service.run(&spec).await?;
+        assert_eq!(result.rows[0]["sales_rep"], "rep-ana");
+        assert_eq!(result.rows[0]["quote_count"], 2);
+        assert_eq!(result.rows[0]["avg_discount_pct"], 5.0);
+        assert_eq!(result.rows[1]["sales_rep"], "rep-ben");
+
+        spec.filters.push(AnalyticsFilter {
+            dimension: DimensionKind::Region,
+            values: vec!["unknown".to_string()],
+        });
+        let unknown_region = service.run(&spec).await?;
+        assert_eq!(unknown_region.rows.len(), 1);
+        assert_eq!(unknown_region.rows[0]["quote_count"], 1);
+
+        spec.filters.clear();
+        spec.limit = Some(1);
+        let capped = service.run(&spec).await?;
+        assert_eq!(capped.rows.len(), 1);
+        assert!(capped.truncated);
+
+        spec.limit = Some(0);
+        assert!(matches!(service.run(&spec).await, Err(AnalyticsServiceError::Invalid(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn identical_specs_are_served_from_a_shared_cache() -> TestResult<()> {
+        let pool = setup().await?;
+        let cache = AnalyticsCache::default();
+        let spec = AnalyticsQuerySpec::new(vec![MetricKind::QuoteCount], Vec::new(), 30);
+
+        let first =
+            AnalyticsService::new(pool.clone()).with_cache(cache.clone()).run(&spec).await?;
+        assert!(!first.cached);
+        sqlx::query("DELETE FROM quote").execute(&pool).await?;
+        let second = AnalyticsService::new(pool.clone()).with_cache(cache).run(&spec).await?;
+        assert!(second.cached);
+        assert_eq!(second.rows, first.rows);
+
+        let uncached = AnalyticsService::new(pool)
+            .with_cache(AnalyticsCache::with_ttl(Duration::ZERO))
+            .run(&spec)
+            .await?;
+        assert_eq!(uncached.rows[0]["quote_count"], 0);
+        Ok(())
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let mut row = Map::new();
+        row.insert("region".to_string(), Value::from("emea, \"north\""));
+        row.insert("quote_count".to_string(), Value::from(3));
+        row.insert("avg_discount_pct".to_string(), Value::Null);
+        let result = AnalyticsResult {
+            schema_version: quotey_core::ANALYTICS_SCHEMA_VERSION.to_string(),
+            columns: vec![
+                AnalyticsColumn { name: "region", kind: AnalyticsColumnKind::Dimension },
+                AnalyticsColumn { name: "quote_count", kind: AnalyticsColumnKind::Metric },
+                AnalyticsColumn { name: "avg_discount_pct", kind: AnalyticsColumnKind::Metric },
+            ],
+            rows: vec![row],
+            truncated: false,
+            generated_at: Utc::now(),
+            cached: false,
+        };
+        assert_eq!(
+            result.to_csv(),
+            "region,quote_count,avg_discount_pct\n\"emea, \"\"north\"\"\",3,\n"
+        );
+    }
+}
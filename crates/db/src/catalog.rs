@@ -0,0 +1,1453 @@
+//! Catalog import and export for `quotey catalog` and the catalog admin API.
+//!
+//! A [`CatalogDocument`] is the whole product catalog — families, products, their attributes and
+//! bundle members — as JSON or as a single CSV sheet whose `record` column says what each row
+//! holds (see [`CSV_COLUMNS`]). Imports are full-catalog: products missing from the document are
+//! deactivated, never deleted, so quotes and order forms that reference them keep resolving.
+//!
+//! [`CatalogService::import`] validates a document, diffs it against the stored catalog and, unless
+//! it is a dry run, re-plans and writes the changes in one transaction together with an audit event
+//! per changed family and product plus a `catalog.imported` summary event.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::Utc;
+use quotey_core::audit::{
+    ActorType, AuditAction, AuditCategory, AuditEvent, AuditOutcome, EntityType,
+};
+use quotey_core::domain::product::{AttributeValueType, ProductAttribute, ProductType};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqliteConnection;
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::{RepositoryError, SqlAuditEventRepository};
+use crate::DbPool;
+
+pub const CATALOG_SCHEMA_VERSION: &str = "catalog.v1";
+
+/// Header of the CSV form. `record` is `family`, `product`, `attribute` or `bundle_member`;
+/// attribute and bundle member rows name their product in `sku`, and an attribute's display name
+/// goes in `name`. Columns a sheet does not use may be left out.
+pub const CSV_COLUMNS: [&str; 16] = [
+    "record",
+    "id",
+    "sku",
+    "name",
+    "description",
+    "product_type",
+    "family_id",
+    "base_price",
+    "currency",
+    "active",
+    "attribute_key",
+    "value_type",
+    "required",
+    "default_value",
+    "member_sku",
+    "quantity",
+];
+
+/// Longest SKU an import accepts.
+pub const MAX_SKU_LENGTH: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogFormat {
+    Json,
+    Csv,
+}
+
+impl CatalogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension().and_then(|extension| extension.to_str()).and_then(Self::parse_label)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CatalogDocument {
+    #[serde(default = "default_schema_version")]
+    pub schema_version: String,
+    #[serde(default)]
+    pub families: Vec<FamilyRecord>,
+    #[serde(default)]
+    pub products: Vec<ProductRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FamilyRecord {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProductRecord {
+    /// Stable product id. When absent the product keeps the id stored for its SKU, and new
+    /// products get the lower-cased SKU.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub sku: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `simple`, `configurable` or `bundle`.
+    #[serde(default = "default_product_type")]
+    pub product_type: String,
+    #[serde(default)]
+    pub family_id: Option<String>,
+    /// Decimal string; absent for products priced by rule only.
+    #[serde(default)]
+    pub base_price: Option<String>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default = "default_active")]
+    pub active: bool,
+    #[serde(default)]
+    pub attributes: Vec<ProductAttribute>,
+    /// Only on bundles; members are referenced by SKU and must be in the same document.
+    #[serde(default)]
+    pub bundle_members: Vec<BundleMemberRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleMemberRecord {
+    pub sku: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i64,
+}
+
+fn default_schema_version() -> String {
+    CATALOG_SCHEMA_VERSION.to_string()
+}
+
+fn default_product_type() -> String {
+    ProductType::Simple.as_str().to_string()
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn default_quantity() -> i64 {
+    1
+}
+
+/// One problem found while validating a document; `location` names the family, product or CSV
+/// line it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogIssue {
+    pub location: String,
+    pub message: String,
+}
+
+impl CatalogIssue {
+    fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { location: location.into(), message: message.into() }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CatalogError {
+    #[error("{0}")]
+    Parse(String),
+    #[error("{}", describe_issues(.0))]
+    Invalid(Vec<CatalogIssue>),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl CatalogError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Parse(_) => "invalid_catalog_file",
+            Self::Invalid(_) => "invalid_catalog",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for CatalogError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+fn describe_issues(issues: &[CatalogIssue]) -> String {
+    match issues.first() {
+        Some(first) => format!(
+            "catalog has {} problem(s); first: {}: {}",
+            issues.len(),
+            first.location,
+            first.message
+        ),
+        None => "catalog is invalid".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing and serialization
+// ---------------------------------------------------------------------------
+
+impl CatalogDocument {
+    pub fn parse(format: CatalogFormat, input: &str) -> Result<Self, CatalogError> {
+        match format {
+            CatalogFormat::Json => serde_json::from_str(input.trim_start_matches('\u{feff}'))
+                .map_err(|error| CatalogError::Parse(format!("invalid catalog JSON: {error}"))),
+            CatalogFormat::Csv => Self::from_csv(input),
+        }
+    }
+
+    pub fn render(&self, format: CatalogFormat) -> String {
+        match format {
+            CatalogFormat::Json => {
+                serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+            }
+            CatalogFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn from_csv(input: &str) -> Result<Self, CatalogError> {
+        let mut rows = parse_csv(input).map_err(CatalogError::Parse)?.into_iter();
+        let Some((_, header)) = rows.next() else {
+            return Err(CatalogError::Parse("catalog CSV is empty".to_string()));
+        };
+        let mut columns = BTreeMap::new();
+        for (index, name) in header.iter().enumerate() {
+            let name = name.trim().to_ascii_lowercase();
+            if !CSV_COLUMNS.contains(&name.as_str()) {
+                return Err(CatalogError::Parse(format!("line 1: unknown column '{name}'")));
+            }
+            if columns.insert(name.clone(), index).is_some() {
+                return Err(CatalogError::Parse(format!("line 1: column '{name}' is repeated")));
+            }
+        }
+        if !columns.contains_key("record") {
+            return Err(CatalogError::Parse("line 1: missing the 'record' column".to_string()));
+        }
+
+        let mut document = CatalogDocument {
+            schema_version: default_schema_version(),
+            families: Vec::new(),
+            products: Vec::new(),
+        };
+        // Attribute and bundle member rows may come before their product row.
+        let mut attributes = Vec::new();
+        let mut members = Vec::new();
+        for (line, cells) in rows {
+            let cell = |column: &str| -> Option<String> {
+                columns
+                    .get(column)
+                    .and_then(|index| cells.get(*index))
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            let required = |column: &str| -> Result<String, CatalogError> {
+                cell(column).ok_or_else(|| {
+                    CatalogError::Parse(format!("line {line}: '{column}' is required"))
+                })
+            };
+            let record = cell("record").unwrap_or_default().to_ascii_lowercase();
+            match record.as_str() {
+                "family" => document.families.push(FamilyRecord {
+                    id: required("id")?,
+                    name: required("name")?,
+                    description: cell("description"),
+                }),
+                "product" => document.products.push(ProductRecord {
+                    id: cell("id"),
+                    sku: required("sku")?,
+                    name: required("name")?,
+                    description: cell("description"),
+                    product_type: cell("product_type").unwrap_or_else(default_product_type),
+                    family_id: cell("family_id"),
+                    base_price: cell("base_price"),
+                    currency: cell("currency").unwrap_or_else(default_currency),
+                    active: parse_flag(cell("active"), true, line, "active")?,
+                    attributes: Vec::new(),
+                    bundle_members: Vec::new(),
+                }),
+                "attribute" => {
+                    let value_type = parse_value_type(&required("value_type")?)
+                        .map_err(|error| CatalogError::Parse(format!("line {line}: {error}")))?;
+                    let key = required("attribute_key")?;
+                    attributes.push((
+                        line,
+                        required("sku")?,
+                        ProductAttribute {
+                            display_name: cell("name").unwrap_or_else(|| key.clone()),
+                            key,
+                            value_type,
+                            required: parse_flag(cell("required"), false, line, "required")?,
+                            default_value: cell("default_value"),
+                        },
+                    ));
+                }
+                "bundle_member" => {
+                    let quantity = match cell("quantity") {
+                        Some(quantity) => quantity.parse::<i64>().map_err(|_| {
+                            CatalogError::Parse(format!(
+                                "line {line}: quantity '{quantity}' is not a whole number"
+                            ))
+                        })?,
+                        None => default_quantity(),
+                    };
+                    members.push((
+                        line,
+                        required("sku")?,
+                        BundleMemberRecord { sku: required("member_sku")?, quantity },
+                    ));
+                }
+                other => {
+                    return Err(CatalogError::Parse(format!(
+                        "line {line}: record must be family, product, attribute or bundle_member, \
+                         not '{other}'"
+                    )))
+                }
+            }
+        }
+
+        let owner = |document: &CatalogDocument, line: usize, sku: &str| {
+            let found = document.products.iter().position(|product| product.sku.trim() == sku);
+            found.ok_or_else(|| {
+                CatalogError::Parse(format!("line {line}: no product row has SKU '{sku}'"))
+            })
+        };
+        for (line, sku, attribute) in attributes {
+            let index = owner(&document, line, &sku)?;
+            document.products[index].attributes.push(attribute);
+        }
+        for (line, sku, member) in members {
+            let index = owner(&document, line, &sku)?;
+            document.products[index].bundle_members.push(member);
+        }
+        Ok(document)
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = CSV_COLUMNS.join(",");
+        csv.push('\n');
+        let mut push = |cells: &[(&str, Option<&str>)]| {
+            let row: Vec<String> = CSV_COLUMNS
+                .iter()
+                .map(|column| {
+                    cells
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .and_then(|(_, value)| value.map(csv_field))
+                        .unwrap_or_default()
+                })
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        };
+        for family in &self.families {
+            push(&[
+                ("record", Some("family")),
+                ("id", Some(&family.id)),
+                ("name", Some(&family.name)),
+                ("description", family.description.as_deref()),
+            ]);
+        }
+        for product in &self.products {
+            push(&[
+                ("record", Some("product")),
+                ("id", product.id.as_deref()),
+                ("sku", Some(&product.sku)),
+                ("name", Some(&product.name)),
+                ("description", product.description.as_deref()),
+                ("product_type", Some(&product.product_type)),
+                ("family_id", product.family_id.as_deref()),
+                ("base_price", product.base_price.as_deref()),
+                ("currency", Some(&product.currency)),
+                ("active", Some(if product.active { "true" } else { "false" })),
+            ]);
+            for attribute in &product.attributes {
+                let value_type = render_value_type(&attribute.value_type);
+                push(&[
+                    ("record", Some("attribute")),
+                    ("sku", Some(&product.sku)),
+                    ("attribute_key", Some(&attribute.key)),
+                    ("name", Some(&attribute.display_name)),
+                    ("value_type", Some(&value_type)),
+                    ("required", Some(if attribute.required { "true" } else { "false" })),
+                    ("default_value", attribute.default_value.as_deref()),
+                ]);
+            }
+            for member in &product.bundle_members {
+                let quantity = member.quantity.to_string();
+                push(&[
+                    ("record", Some("bundle_member")),
+                    ("sku", Some(&product.sku)),
+                    ("member_sku", Some(&member.sku)),
+                    ("quantity", Some(&quantity)),
+                ]);
+            }
+        }
+        csv
+    }
+}
+
+/// Splits RFC 4180 CSV into rows, each with the line it starts on. Blank lines are skipped.
+fn parse_csv(input: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = input.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("line {row_line}: unterminated quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    rows.retain(|(_, cells)| cells.iter().any(|cell| !cell.trim().is_empty()));
+    Ok(rows)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_flag(
+    value: Option<String>,
+    default: bool,
+    line: usize,
+    column: &str,
+) -> Result<bool, CatalogError> {
+    match value.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        None => Ok(default),
+        Some("true" | "yes" | "1") => Ok(true),
+        Some("false" | "no" | "0") => Ok(false),
+        Some(other) => Err(CatalogError::Parse(format!(
+            "line {line}: {column} must be true or false, not '{other}'"
+        ))),
+    }
+}
+
+/// Value types are the stored JSON (`{"Integer":{"min":1,"max":1000}}`); the unit variant may be
+/// written bare (`Boolean`).
+fn parse_value_type(cell: &str) -> Result<AttributeValueType, String> {
+    serde_json::from_str(cell)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(cell.to_string())))
+        .map_err(|error| format!("value_type '{cell}' is not an attribute value type: {error}"))
+}
+
+fn render_value_type(value_type: &AttributeValueType) -> String {
+    match serde_json::to_value(value_type) {
+        Ok(serde_json::Value::String(unit)) => unit,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Create,
+    Update,
+    Deactivate,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Deactivate => "deactivate",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FamilyChange {
+    pub family_id: String,
+    pub kind: ChangeKind,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductChange {
+    pub product_id: String,
+    pub sku: String,
+    pub kind: ChangeKind,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub families_created: usize,
+    pub families_updated: usize,
+    pub products_created: usize,
+    pub products_updated: usize,
+    pub products_deactivated: usize,
+    pub products_unchanged: usize,
+}
+
+/// What an import changes, family by family and product by product. Unchanged entries are only
+/// counted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogDiff {
+    pub summary: ImportSummary,
+    pub families: Vec<FamilyChange>,
+    pub products: Vec<ProductChange>,
+}
+
+impl CatalogDiff {
+    pub fn is_empty(&self) -> bool {
+        self.families.is_empty() && self.products.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Correlation id of the audit events; `None` for dry runs.
+    pub import_id: Option<String>,
+    pub diff: CatalogDiff,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportRequest {
+    pub document: CatalogDocument,
+    pub dry_run: bool,
+    pub actor: String,
+    /// Where the document came from (file name, `api`), kept on the summary audit event.
+    pub source: String,
+}
+
+/// A product as stored or as it will be stored, in comparable form.
+#[derive(Clone, Debug, PartialEq)]
+struct CatalogProduct {
+    id: String,
+    sku: String,
+    name: String,
+    description: Option<String>,
+    product_type: ProductType,
+    family_id: Option<String>,
+    base_price: Option<Decimal>,
+    currency: String,
+    active: bool,
+    attributes: Vec<ProductAttribute>,
+    /// `(member id, member SKU, quantity)` in document order.
+    members: Vec<(String, String, i64)>,
+}
+
+impl CatalogProduct {
+    fn fields(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("sku", Some(self.sku.clone())),
+            ("name", Some(self.name.clone())),
+            ("description", self.description.clone()),
+            ("product_type", Some(self.product_type.as_str().to_string())),
+            ("family_id", self.family_id.clone()),
+            ("base_price", self.base_price.map(|price| price.normalize().to_string())),
+            ("currency", Some(self.currency.clone())),
+            ("active", Some(self.active.to_string())),
+            (
+                "attributes",
+                Some(&self.attributes)
+                    .filter(|attributes| !attributes.is_empty())
+                    .and_then(|attributes| serde_json::to_string(attributes).ok()),
+            ),
+            (
+                "bundle_members",
+                Some(&self.members).filter(|members| !members.is_empty()).map(|members| {
+                    members
+                        .iter()
+                        .map(|(_, sku, quantity)| format!("{sku} x{quantity}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }),
+            ),
+        ]
+    }
+
+    fn record(&self) -> ProductRecord {
+        ProductRecord {
+            id: Some(self.id.clone()),
+            sku: self.sku.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            product_type: self.product_type.as_str().to_string(),
+            family_id: self.family_id.clone(),
+            base_price: self.base_price.map(|price| price.to_string()),
+            currency: self.currency.clone(),
+            active: self.active,
+            attributes: self.attributes.clone(),
+            bundle_members: self
+                .members
+                .iter()
+                .map(|(_, sku, quantity)| BundleMemberRecord {
+                    sku: sku.clone(),
+                    quantity: *quantity,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn field_changes(
+    before: Vec<(&'static str, Option<String>)>,
+    after: Vec<(&'static str, Option<String>)>,
+) -> Vec<FieldChange> {
+    before
+        .into_iter()
+        .zip(after)
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((field, before), (_, after))| FieldChange {
+            field: field.to_string(),
+            before,
+            after,
+        })
+        .collect()
+}
+
+fn family_fields(family: &FamilyRecord) -> Vec<(&'static str, Option<String>)> {
+    vec![("name", Some(family.name.clone())), ("description", family.description.clone())]
+}
+
+/// The stored catalog a document is diffed against.
+#[derive(Debug, Default)]
+struct StoredCatalog {
+    families: BTreeMap<String, FamilyRecord>,
+    products: BTreeMap<String, CatalogProduct>,
+}
+
+impl StoredCatalog {
+    async fn load(conn: &mut SqliteConnection) -> Result<Self, CatalogError> {
+        let mut catalog = Self::default();
+        let families = sqlx::query_as::<_, (String, String, Option<String>)>(
+            "SELECT id, name, description FROM product_family ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        for (id, name, description) in families {
+            catalog.families.insert(id.clone(), FamilyRecord { id, name, description });
+        }
+
+        type ProductColumns = (
+            String,
+            String,
+            String,
+            Option<String>,
+            String,
+            Option<String>,
+            Option<String>,
+            String,
+            bool,
+        );
+        let products = sqlx::query_as::<_, ProductColumns>(
+            "SELECT id, sku, name, description, product_type, family_id, base_price, currency, \
+             active FROM product ORDER BY sku",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        for (id, sku, name, description, product_type, family_id, base_price, currency, active) in
+            products
+        {
+            let product_type =
+                ProductType::from_str(&product_type).map_err(RepositoryError::Decode)?;
+            let base_price =
+                base_price.as_deref().map(Decimal::from_str).transpose().map_err(|error| {
+                    RepositoryError::Decode(format!("invalid base_price: {error}"))
+                })?;
+            catalog.products.insert(
+                id.clone(),
+                CatalogProduct {
+                    id,
+                    sku,
+                    name,
+                    description,
+                    product_type,
+                    family_id,
+                    base_price,
+                    currency,
+                    active,
+                    attributes: Vec::new(),
+                    members: Vec::new(),
+                },
+            );
+        }
+
+        let attributes =
+            sqlx::query_as::<_, (String, String, String, String, bool, Option<String>)>(
+                "SELECT product_id, key, display_name, value_type, required, default_value \
+                 FROM product_attribute ORDER BY product_id, sort_order, id",
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+        for (product_id, key, display_name, value_type, required, default_value) in attributes {
+            let value_type = serde_json::from_str(&value_type).map_err(|error| {
+                RepositoryError::Decode(format!("invalid attribute value_type JSON: {error}"))
+            })?;
+            if let Some(product) = catalog.products.get_mut(&product_id) {
+                product.attributes.push(ProductAttribute {
+                    key,
+                    display_name,
+                    value_type,
+                    required,
+                    default_value,
+                });
+            }
+        }
+
+        let members = sqlx::query_as::<_, (String, String, String, i64)>(
+            "SELECT m.bundle_id, m.member_id, p.sku, m.quantity FROM product_bundle_member m \
+             JOIN product p ON p.id = m.member_id ORDER BY m.bundle_id, m.sort_order, p.sku",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        for (bundle_id, member_id, sku, quantity) in members {
+            if let Some(bundle) = catalog.products.get_mut(&bundle_id) {
+                bundle.members.push((member_id, sku, quantity));
+            }
+        }
+        Ok(catalog)
+    }
+
+    fn document(&self) -> CatalogDocument {
+        CatalogDocument {
+            schema_version: default_schema_version(),
+            families: self.families.values().cloned().collect(),
+            products: {
+                let mut products: Vec<_> = self.products.values().collect();
+                products.sort_by(|left, right| left.sku.cmp(&right.sku));
+                products.into_iter().map(CatalogProduct::record).collect()
+            },
+        }
+    }
+}
+
+/// A validated document and the writes that bring the stored catalog in line with it.
+#[derive(Debug)]
+struct ImportPlan {
+    diff: CatalogDiff,
+    families: Vec<(ChangeKind, FamilyRecord, Option<FamilyRecord>)>,
+    products: Vec<(ChangeKind, CatalogProduct, Option<CatalogProduct>)>,
+}
+
+fn plan(document: &CatalogDocument, stored: &StoredCatalog) -> Result<ImportPlan, CatalogError> {
+    let mut issues = Vec::new();
+    if document.schema_version != CATALOG_SCHEMA_VERSION {
+        issues.push(CatalogIssue::new(
+            "schema_version",
+            format!(
+                "'{}' is not supported (expected '{CATALOG_SCHEMA_VERSION}')",
+                document.schema_version
+            ),
+        ));
+    }
+    if document.products.is_empty() {
+        // A full-catalog import with no products would deactivate everything.
+        issues.push(CatalogIssue::new("products", "the catalog has no products"));
+    }
+
+    let mut families = BTreeMap::new();
+    for (index, family) in document.families.iter().enumerate() {
+        let family = FamilyRecord {
+            id: family.id.trim().to_string(),
+            name: family.name.trim().to_string(),
+            description: trimmed(family.description.as_deref()),
+        };
+        let location = if family.id.is_empty() {
+            format!("families[{index}]")
+        } else {
+            format!("family '{}'", family.id)
+        };
+        if family.id.is_empty() {
+            issues.push(CatalogIssue::new(&location, "id is required"));
+        }
+        if family.name.is_empty() {
+            issues.push(CatalogIssue::new(&location, "name is required"));
+        }
+        if families.insert(family.id.clone(), family).is_some() {
+            issues.push(CatalogIssue::new(&location, "family id is repeated"));
+        }
+    }
+
+    let stored_ids_by_sku: BTreeMap<&str, &str> = stored
+        .products
+        .values()
+        .map(|product| (product.sku.as_str(), product.id.as_str()))
+        .collect();
+    let mut products: Vec<(String, CatalogProduct)> = Vec::new();
+    let mut ids_by_sku = BTreeMap::new();
+    let mut seen_ids = BTreeSet::new();
+    for (index, record) in document.products.iter().enumerate() {
+        let sku = record.sku.trim().to_string();
+        let location =
+            if sku.is_empty() { format!("products[{index}]") } else { format!("product '{sku}'") };
+        let mut issue = |message: String| issues.push(CatalogIssue::new(&location, message));
+
+        if sku.is_empty() {
+            issue("sku is required".to_string());
+        } else if sku.len() > MAX_SKU_LENGTH
+            || !sku.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            issue(format!("sku must be at most {MAX_SKU_LENGTH} letters, digits, '-', '_' or '.'"));
+        }
+        let id = match trimmed(record.id.as_deref()) {
+            Some(id) => id,
+            None => stored_ids_by_sku
+                .get(sku.as_str())
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| sku.to_ascii_lowercase()),
+        };
+        if id.chars().any(char::is_whitespace) {
+            issue(format!("id '{id}' must not contain spaces"));
+        }
+        if let Some(owner) = stored_ids_by_sku.get(sku.as_str()).filter(|owner| **owner != id) {
+            issue(format!("sku already belongs to product '{owner}'"));
+        }
+        if !sku.is_empty() && ids_by_sku.insert(sku.to_ascii_uppercase(), id.clone()).is_some() {
+            issue("sku is repeated".to_string());
+        }
+        if !seen_ids.insert(id.clone()) {
+            issue(format!("product id '{id}' is repeated"));
+        }
+        if record.name.trim().is_empty() {
+            issue("name is required".to_string());
+        }
+        let product_type = ProductType::from_str(&record.product_type.trim().to_ascii_lowercase())
+            .unwrap_or_else(|error| {
+                issue(error);
+                ProductType::Simple
+            });
+        let family_id = trimmed(record.family_id.as_deref());
+        if let Some(family_id) = &family_id {
+            if !families.contains_key(family_id) && !stored.families.contains_key(family_id) {
+                issue(format!("family '{family_id}' is neither in the file nor the catalog"));
+            }
+        }
+        let base_price = match trimmed(record.base_price.as_deref()) {
+            Some(price) => match Decimal::from_str(&price) {
+                Ok(price) if price >= Decimal::ZERO => Some(price),
+                _ => {
+                    issue(format!("base_price '{price}' must be a decimal >= 0"));
+                    None
+                }
+            },
+            None => None,
+        };
+        let currency = record.currency.trim().to_ascii_uppercase();
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+            issue(format!("currency '{currency}' must be a 3-letter ISO code"));
+        }
+        let mut keys = BTreeSet::new();
+        for attribute in &record.attributes {
+            if let Err(error) = attribute.validate() {
+                issue(error);
+            }
+            if !keys.insert(attribute.key.as_str()) {
+                issue(format!("attribute '{}' is repeated", attribute.key));
+            }
+        }
+        if !record.bundle_members.is_empty() && product_type != ProductType::Bundle {
+            issue("only bundle products can have bundle members".to_string());
+        }
+
+        products.push((
+            location,
+            CatalogProduct {
+                id,
+                sku,
+                name: record.name.trim().to_string(),
+                description: trimmed(record.description.as_deref()),
+                product_type,
+                family_id,
+                base_price,
+                currency,
+                active: record.active,
+                attributes: record.attributes.clone(),
+                members: Vec::new(),
+            },
+        ));
+    }
+
+    // Members are resolved once every product has its id; they must be in the same document
+    // because anything missing from it is deactivated.
+    let active_by_id: BTreeMap<String, bool> =
+        products.iter().map(|(_, product)| (product.id.clone(), product.active)).collect();
+    for ((location, product), record) in products.iter_mut().zip(&document.products) {
+        let mut seen = BTreeSet::new();
+        for member in &record.bundle_members {
+            let sku = member.sku.trim();
+            let Some(member_id) = ids_by_sku.get(&sku.to_ascii_uppercase()).cloned() else {
+                issues.push(CatalogIssue::new(
+                    location.as_str(),
+                    format!("bundle member '{sku}' is not in the catalog file"),
+                ));
+                continue;
+            };
+            if member_id == product.id {
+                issues.push(CatalogIssue::new(location.as_str(), "a bundle cannot contain itself"));
+            } else if !seen.insert(member_id.clone()) {
+                issues.push(CatalogIssue::new(
+                    location.as_str(),
+                    format!("bundle member '{sku}' is repeated"),
+                ));
+            } else if member.quantity < 1 {
+                issues.push(CatalogIssue::new(
+                    location.as_str(),
+                    format!("bundle member '{sku}' needs a quantity of at least 1"),
+                ));
+            } else if product.active && !active_by_id.get(&member_id).copied().unwrap_or(false) {
+                issues.push(CatalogIssue::new(
+                    location.as_str(),
+                    format!("active bundle includes inactive member '{sku}'"),
+                ));
+            }
+            let sku = products_sku(&document.products, sku);
+            product.members.push((member_id, sku, member.quantity));
+        }
+    }
+    if let Some(location) = bundle_cycle(&products) {
+        issues.push(CatalogIssue::new(location, "bundle members form a cycle"));
+    }
+    if !issues.is_empty() {
+        return Err(CatalogError::Invalid(issues));
+    }
+
+    let mut plan =
+        ImportPlan { diff: CatalogDiff::default(), families: Vec::new(), products: Vec::new() };
+    for family in families.into_values() {
+        let before = stored.families.get(&family.id).cloned();
+        let changes = field_changes(
+            before.as_ref().map(family_fields).unwrap_or_else(|| {
+                family_fields(&family).into_iter().map(|(field, _)| (field, None)).collect()
+            }),
+            family_fields(&family),
+        );
+        let kind = match &before {
+            None => ChangeKind::Create,
+            Some(_) if changes.is_empty() => continue,
+            Some(_) => ChangeKind::Update,
+        };
+        match kind {
+            ChangeKind::Create => plan.diff.summary.families_created += 1,
+            _ => plan.diff.summary.families_updated += 1,
+        }
+        plan.diff.families.push(FamilyChange { family_id: family.id.clone(), kind, changes });
+        plan.families.push((kind, family, before));
+    }
+
+    let mut listed = BTreeSet::new();
+    for (_, product) in products {
+        listed.insert(product.id.clone());
+        let before = stored.products.get(&product.id).cloned();
+        let changes = field_changes(
+            before.as_ref().map(CatalogProduct::fields).unwrap_or_else(|| {
+                product.fields().into_iter().map(|(field, _)| (field, None)).collect()
+            }),
+            product.fields(),
+        );
+        let kind = match &before {
+            None => ChangeKind::Create,
+            Some(_) if changes.is_empty() => {
+                plan.diff.summary.products_unchanged += 1;
+                continue;
+            }
+            Some(_) => ChangeKind::Update,
+        };
+        match kind {
+            ChangeKind::Create => plan.diff.summary.products_created += 1,
+            _ => plan.diff.summary.products_updated += 1,
+        }
+        plan.diff.products.push(ProductChange {
+            product_id: product.id.clone(),
+            sku: product.sku.clone(),
+            kind,
+            changes,
+        });
+        plan.products.push((kind, product, before));
+    }
+    for stored_product in stored.products.values() {
+        if listed.contains(&stored_product.id) || !stored_product.active {
+            continue;
+        }
+        let mut deactivated = stored_product.clone();
+        deactivated.active = false;
+        plan.diff.summary.products_deactivated += 1;
+        plan.diff.products.push(ProductChange {
+            product_id: deactivated.id.clone(),
+            sku: deactivated.sku.clone(),
+            kind: ChangeKind::Deactivate,
+            changes: field_changes(stored_product.fields(), deactivated.fields()),
+        });
+        plan.products.push((ChangeKind::Deactivate, deactivated, Some(stored_product.clone())));
+    }
+    plan.diff.products.sort_by(|left, right| left.sku.cmp(&right.sku));
+    Ok(plan)
+}
+
+fn trimmed(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// The SKU exactly as its product row spells it, so member references are case-insensitive.
+fn products_sku(records: &[ProductRecord], sku: &str) -> String {
+    records
+        .iter()
+        .map(|record| record.sku.trim())
+        .find(|candidate| candidate.eq_ignore_ascii_case(sku))
+        .unwrap_or(sku)
+        .to_string()
+}
+
+/// Location of a bundle that (transitively) contains itself, if any.
+fn bundle_cycle(products: &[(String, CatalogProduct)]) -> Option<String> {
+    let edges: BTreeMap<&str, Vec<&str>> = products
+        .iter()
+        .map(|(_, product)| {
+            (product.id.as_str(), product.members.iter().map(|(id, _, _)| id.as_str()).collect())
+        })
+        .collect();
+    for (location, product) in products {
+        let mut stack: Vec<&str> = edges.get(product.id.as_str()).cloned().unwrap_or_default();
+        let mut visited = BTreeSet::new();
+        while let Some(next) = stack.pop() {
+            if next == product.id {
+                return Some(location.clone());
+            }
+            if visited.insert(next) {
+                stack.extend(edges.get(next).into_iter().flatten());
+            }
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Service
+// ---------------------------------------------------------------------------
+
+pub struct CatalogService {
+    pool: DbPool,
+}
+
+impl CatalogService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// The whole stored catalog, inactive products included, ordered by family id and SKU.
+    pub async fn export(&self) -> Result<CatalogDocument, CatalogError> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(StoredCatalog::load(&mut conn).await?.document())
+    }
+
+    /// Validates and diffs `request.document`; unless it is a dry run, applies the diff in one
+    /// transaction. The diff is recomputed inside the transaction so a concurrent edit cannot be
+    /// overwritten by a stale plan.
+    pub async fn import(&self, request: ImportRequest) -> Result<ImportReport, CatalogError> {
+        if request.dry_run {
+            let mut conn = self.pool.acquire().await?;
+            let stored = StoredCatalog::load(&mut conn).await?;
+            let diff = plan(&request.document, &stored)?.diff;
+            return Ok(ImportReport { dry_run: true, import_id: None, diff });
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let stored = StoredCatalog::load(&mut tx).await?;
+        let plan = plan(&request.document, &stored)?;
+        let import_id = format!("CATIMP-{}", short_id());
+        let now = Utc::now().to_rfc3339();
+
+        for (kind, family, before) in &plan.families {
+            sqlx::query(
+                "INSERT INTO product_family (id, name, description, created_at, updated_at) \
+                 VALUES (?, ?, ?, ?, ?) \
+                 ON CONFLICT(id) DO UPDATE SET name = excluded.name, \
+                 description = excluded.description, updated_at = excluded.updated_at",
+            )
+            .bind(&family.id)
+            .bind(&family.name)
+            .bind(&family.description)
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            let event = change_event(&request, &import_id, *kind, EntityType::ProductFamily)
+                .with_entity(EntityType::ProductFamily, family.id.clone())
+                .with_after(json!(family).to_string());
+            let event = match before {
+                Some(before) => event.with_before(json!(before).to_string()),
+                None => event,
+            };
+            SqlAuditEventRepository::save_with(&mut *tx, &event).await?;
+        }
+
+        for (kind, product, _) in &plan.products {
+            sqlx::query(
+                "INSERT INTO product (id, sku, name, description, product_type, family_id, \
+                 base_price, currency, active, created_at, updated_at) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+                 ON CONFLICT(id) DO UPDATE SET \
+                 sku = excluded.sku, name = excluded.name, description = excluded.description, \
+                 product_type = excluded.product_type, family_id = excluded.family_id, \
+                 base_price = excluded.base_price, currency = excluded.currency, \
+                 active = excluded.active, updated_at = excluded.updated_at",
+            )
+            .bind(&product.id)
+            .bind(&product.sku)
+            .bind(&product.name)
+            .bind(&product.description)
+            .bind(product.product_type.as_str())
+            .bind(&product.family_id)
+            .bind(product.base_price.map(|price| price.to_string()))
+            .bind(&product.currency)
+            .bind(product.active)
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            if *kind == ChangeKind::Deactivate {
+                continue;
+            }
+            sqlx::query("DELETE FROM product_attribute WHERE product_id = ?")
+                .bind(&product.id)
+                .execute(&mut *tx)
+                .await?;
+            for (sort_order, attribute) in product.attributes.iter().enumerate() {
+                let value_type = serde_json::to_string(&attribute.value_type).map_err(|error| {
+                    RepositoryError::Decode(format!("serialize attribute type: {error}"))
+                })?;
+                sqlx::query(
+                    "INSERT INTO product_attribute (product_id, key, display_name, value_type, \
+                     required, default_value, sort_order) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&product.id)
+                .bind(&attribute.key)
+                .bind(&attribute.display_name)
+                .bind(value_type)
+                .bind(attribute.required)
+                .bind(&attribute.default_value)
+                .bind(sort_order as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        // Members last: every product they point at exists by now.
+        for (kind, product, _) in &plan.products {
+            if *kind == ChangeKind::Deactivate {
+                continue;
+            }
+            sqlx::query("DELETE FROM product_bundle_member WHERE bundle_id = ?")
+                .bind(&product.id)
+                .execute(&mut *tx)
+                .await?;
+            for (sort_order, (member_id, _, quantity)) in product.members.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO product_bundle_member (bundle_id, member_id, quantity, sort_order) \
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(&product.id)
+                .bind(member_id)
+                .bind(quantity)
+                .bind(sort_order as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        for (kind, product, before) in &plan.products {
+            let mut event = change_event(&request, &import_id, *kind, EntityType::Product)
+                .with_entity(EntityType::Product, product.id.clone())
+                .with_after(json!(product.record()).to_string())
+                .with_metadata("sku", product.sku.clone());
+            if let Some(before) = before {
+                event = event.with_before(json!(before.record()).to_string());
+            }
+            SqlAuditEventRepository::save_with(&mut *tx, &event).await?;
+        }
+
+        let summary = &plan.diff.summary;
+        let event = AuditEvent::new(
+            None,
+            None,
+            import_id.clone(),
+            "catalog.imported",
+            AuditCategory::Persistence,
+            request.actor.clone(),
+            AuditOutcome::Success,
+        )
+        .with_actor_type(ActorType::User)
+        .with_action(AuditAction::Updated)
+        .with_after(json!(summary).to_string())
+        .with_metadata("import_id", import_id.clone())
+        .with_metadata("source", request.source.clone());
+        SqlAuditEventRepository::save_with(&mut *tx, &event).await?;
+
+        tx.commit().await?;
+        Ok(ImportReport { dry_run: false, import_id: Some(import_id), diff: plan.diff })
+    }
+}
+
+fn change_event(
+    request: &ImportRequest,
+    import_id: &str,
+    kind: ChangeKind,
+    entity: EntityType,
+) -> AuditEvent {
+    let (event_type, action) = match (&entity, kind) {
+        (EntityType::ProductFamily, ChangeKind::Create) => {
+            ("catalog.family_created", AuditAction::Created)
+        }
+        (EntityType::ProductFamily, _) => ("catalog.family_updated", AuditAction::Updated),
+        (_, ChangeKind::Create) => ("catalog.product_created", AuditAction::Created),
+        (_, ChangeKind::Update) => ("catalog.product_updated", AuditAction::Updated),
+        (_, ChangeKind::Deactivate) => ("catalog.product_deactivated", AuditAction::Updated),
+    };
+    AuditEvent::new(
+        None,
+        None,
+        import_id,
+        event_type,
+        AuditCategory::Persistence,
+        request.actor.clone(),
+        AuditOutcome::Success,
+    )
+    .with_actor_type(ActorType::User)
+    .with_action(action)
+    .with_metadata("import_id", import_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+
+    const SHEET: &str = "\
+record,id,sku,name,description,product_type,family_id,base_price,currency,active,attribute_key,value_type,required,default_value,member_sku,quantity
+family,fam-core,,Core,,,,,,,,,,,,
+attribute,,SEATS-PRO,Seats,,,,,,,seats,\"{\"\"Integer\"\":{\"\"min\"\":1,\"\"max\"\":500}}\",true,10,,
+product,,SEATS-PRO,Pro seats,\"Per seat, billed annually\",configurable,fam-core,40.00,usd,,,,,,,
+product,,SUPPORT,Premium support,,simple,fam-core,500,USD,true,,,,,,
+product,,SUITE,Suite,,bundle,fam-core,,USD,,,,,,,
+bundle_member,,SUITE,,,,,,,,,,,,seats-pro,5
+bundle_member,,SUITE,,,,,,,,,,,,SUPPORT,
+";
+
+    async fn pool() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    fn request(document: CatalogDocument, dry_run: bool) -> ImportRequest {
+        ImportRequest { document, dry_run, actor: "ops".to_string(), source: "test".to_string() }
+    }
+
+    #[test]
+    fn csv_sheets_parse_and_render_back_to_the_same_document() {
+        let document = CatalogDocument::parse(CatalogFormat::Csv, SHEET).expect("parse");
+        assert_eq!(document.families.len(), 1);
+        let seats = &document.products[0];
+        assert_eq!(seats.description.as_deref(), Some("Per seat, billed annually"));
+        assert_eq!(seats.attributes[0].default_value.as_deref(), Some("10"));
+        assert_eq!(
+            seats.attributes[0].value_type,
+            AttributeValueType::Integer { min: Some(1), max: Some(500) }
+        );
+        assert_eq!(document.products[2].bundle_members[1].quantity, 1);
+
+        let rendered = document.render(CatalogFormat::Csv);
+        assert_eq!(
+            CatalogDocument::parse(CatalogFormat::Csv, &rendered).expect("reparse"),
+            document
+        );
+        let json = document.render(CatalogFormat::Json);
+        assert_eq!(CatalogDocument::parse(CatalogFormat::Json, &json).expect("json"), document);
+
+        let error = CatalogDocument::parse(
+            CatalogFormat::Csv,
+            "record,sku,member_sku\nbundle_member,NOPE,SUPPORT\n",
+        )
+        .expect_err("orphan member");
+        assert_eq!(error.to_string(), "line 2: no product row has SKU 'NOPE'");
+    }
+
+    #[test]
+    fn invalid_skus_schemas_and_bundle_references_are_all_reported() {
+        let mut document = CatalogDocument::parse(CatalogFormat::Csv, SHEET).expect("parse");
+        document.products[0].attributes[0].value_type =
+            AttributeValueType::Enum { allowed_values: Vec::new() };
+        document.products[1].sku = "SUPPORT PLUS".to_string();
+        document.products[2]
+            .bundle_members
+            .push(BundleMemberRecord { sku: "MISSING".to_string(), quantity: 1 });
+        document.products[1]
+            .bundle_members
+            .push(BundleMemberRecord { sku: "SUITE".to_string(), quantity: 1 });
+
+        let Err(CatalogError::Invalid(issues)) = plan(&document, &StoredCatalog::default()) else {
+            panic!("document should be rejected");
+        };
+        let messages: Vec<String> =
+            issues.iter().map(|issue| format!("{}: {}", issue.location, issue.message)).collect();
+        assert!(messages.contains(
+            &"product 'SEATS-PRO': attribute 'seats': enum needs at least one allowed value"
+                .to_string()
+        ));
+        assert!(messages
+            .iter()
+            .any(|message| message.starts_with("product 'SUPPORT PLUS': sku must")));
+        assert!(messages.contains(
+            &"product 'SUPPORT PLUS': only bundle products can have bundle members".to_string()
+        ));
+        assert!(messages.contains(
+            &"product 'SUITE': bundle member 'MISSING' is not in the catalog file".to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn imports_diff_apply_deactivate_and_audit_in_one_transaction() {
+        let pool = pool().await;
+        let service = CatalogService::new(pool.clone());
+        let document = CatalogDocument::parse(CatalogFormat::Csv, SHEET).expect("parse");
+
+        let dry_run = service.import(request(document.clone(), true)).await.expect("dry run");
+        assert_eq!(dry_run.import_id, None);
+        assert_eq!(dry_run.diff.summary.products_created, 3);
+        assert_eq!(dry_run.diff.summary.families_created, 1);
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM product")
+            .fetch_one(&pool)
+            .await
+            .expect("count");
+        assert_eq!(stored, 0, "dry runs write nothing");
+
+        let applied = service.import(request(document.clone(), false)).await.expect("apply");
+        assert!(applied.import_id.is_some());
+        let exported = service.export().await.expect("export");
+        let suite = exported.products.iter().find(|product| product.sku == "SUITE").expect("suite");
+        assert_eq!(suite.id.as_deref(), Some("suite"));
+        assert_eq!(suite.bundle_members[0].sku, "SEATS-PRO");
+        let replay = service.import(request(exported, true)).await.expect("re-import");
+        assert!(replay.diff.is_empty(), "an export re-imports as a no-op: {:?}", replay.diff);
+        assert_eq!(replay.diff.summary.products_unchanged, 3);
+
+        let mut next = document;
+        next.products.retain(|product| product.sku != "SUPPORT");
+        next.products[1].bundle_members.retain(|member| member.sku != "SUPPORT");
+        next.products[0].base_price = Some("45".to_string());
+        let report = service.import(request(next, false)).await.expect("second import");
+        assert_eq!(report.diff.summary.products_updated, 2);
+        assert_eq!(report.diff.summary.products_deactivated, 1);
+        let seats = &report.diff.products[0];
+        assert_eq!((seats.sku.as_str(), seats.kind), ("SEATS-PRO", ChangeKind::Update));
+        assert_eq!(
+            seats.changes,
+            vec![FieldChange {
+                field: "base_price".to_string(),
+                before: Some("40".to_string()),
+                after: Some("45".to_string()),
+            }]
+        );
+
+        let support: (bool,) = sqlx::query_as("SELECT active FROM product WHERE id = 'support'")
+            .fetch_one(&pool)
+            .await
+            .expect("support kept");
+        assert!(!support.0);
+        let events: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT event_type, entity_id FROM audit_event WHERE json_extract(payload_json, \
+             '$.correlation_id') = ? ORDER BY event_type",
+        )
+        .bind(report.import_id.as_deref())
+        .fetch_all(&pool)
+        .await
+        .expect("audit");
+        assert_eq!(
+            events,
+            vec![
+                ("catalog.imported".to_string(), None),
+                ("catalog.product_deactivated".to_string(), Some("support".to_string())),
+                ("catalog.product_updated".to_string(), Some("seats-pro".to_string())),
+                ("catalog.product_updated".to_string(), Some("suite".to_string())),
+            ]
+        );
+
+        // A rejected import leaves the catalog as it was.
+        let mut broken = service.export().await.expect("export");
+        broken.products[0].currency = "dollars".to_string();
+        assert!(matches!(
+            service.import(request(broken, false)).await,
+            Err(CatalogError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,1106 @@
+//! Live collaborative quote editing sessions.
+//!
+//! Reps and deal desk join a session on a quote, submit line operations against the sequence
+//! number they last saw, and get every operation back with the outcome [`OperationalTransform`]
+//! gave it. Operations are stored in `session_operations` with a per-session sequence, so the
+//! working lines of a session are the quote's lines plus its applied operations replayed in
+//! order. Committing a session writes the converged lines as a new quote revision.
+//!
+//! An operation is concurrent with every operation applied after the sequence its author based
+//! it on. When a concurrent operation from someone else on the same line outranks it, it is
+//! recorded as overridden instead of being applied; operations in one batch are resolved by the
+//! transform itself. Applied operations are never rewritten, so later winning edits land on top.
+
+use std::collections::HashMap;
+
+use quotey_core::audit::{
+    ActorType, AuditAction, AuditCategory, AuditEvent, AuditOutcome, EntityType,
+};
+use quotey_core::chrono::{DateTime, Duration, Utc};
+use quotey_core::collab::{
+    OperationAuthority, OperationStatus, OperationType, OperationalTransform, QuoteOperation,
+};
+use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine};
+use quotey_core::domain::sales_rep::SalesRepRole;
+use serde::Serialize;
+use serde_json::json;
+use sqlx::Row;
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::quote::quote_status_as_str;
+use crate::repositories::{
+    QuoteRepository, RepositoryError, RevisionSave, SqlAuditEventRepository, SqlQuoteRepository,
+};
+use crate::DbPool;
+
+/// How long a session stays open without being committed.
+pub const SESSION_TTL_HOURS: i64 = 8;
+/// Participants seen within this window are reported as online.
+pub const PRESENCE_WINDOW_SECS: i64 = 60;
+/// Most operations accepted in one submission.
+pub const MAX_BATCH_OPERATIONS: usize = 50;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CollabSession {
+    pub id: String,
+    pub quote_id: String,
+    pub status: String,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+    /// Quote version the session forked from; commit fails once the quote moves on.
+    pub base_version: u32,
+    pub committed_version: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Participant {
+    pub user_id: String,
+    pub role: String,
+    pub joined_at: String,
+    pub last_activity: String,
+    pub online: bool,
+}
+
+/// An operation as stored, with the outcome of resolving it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResolvedOperation {
+    pub seq: u64,
+    pub operation_id: String,
+    pub actor_user_id: String,
+    pub role: String,
+    pub status: OperationStatus,
+    pub reason: String,
+    /// Operation that won the conflict, for overridden operations.
+    pub superseded_by: Option<String>,
+    pub operation: OperationType,
+    pub recorded_at: String,
+}
+
+/// Working state of a session: its lines with every applied operation replayed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SessionState {
+    pub session: CollabSession,
+    /// Sequence of the newest operation; submissions based on it see every earlier edit.
+    pub head_seq: u64,
+    pub lines: Vec<QuoteLine>,
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SubmitOperations {
+    pub session_id: String,
+    pub user_id: String,
+    /// Newest sequence the author had seen when making these edits.
+    pub base_seq: u64,
+    pub operations: Vec<OperationType>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SubmitOutcome {
+    pub session_id: String,
+    pub head_seq: u64,
+    /// The submitted operations in order, each with its outcome.
+    pub operations: Vec<ResolvedOperation>,
+    pub lines: Vec<QuoteLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CommittedRevision {
+    pub session_id: String,
+    pub quote_id: String,
+    pub previous_version: u32,
+    pub version: u32,
+    pub status: &'static str,
+    pub applied_operations: usize,
+    pub audit_event_id: String,
+}
+
+/// What participants are told about a session as it changes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabEvent {
+    Joined {
+        participant: Participant,
+    },
+    Left {
+        user_id: String,
+    },
+    Operations {
+        head_seq: u64,
+        operations: Vec<ResolvedOperation>,
+    },
+    /// Sent to the author of an operation that lost a conflict.
+    Overridden {
+        author: String,
+        operation: ResolvedOperation,
+    },
+    Committed {
+        revision: CommittedRevision,
+    },
+}
+
+impl CollabEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Joined { .. } => "joined",
+            Self::Left { .. } => "left",
+            Self::Operations { .. } => "operations",
+            Self::Overridden { .. } => "overridden",
+            Self::Committed { .. } => "committed",
+        }
+    }
+
+    /// Events for everyone in the session after a submission, plus one per overridden operation
+    /// for its author.
+    pub fn for_submission(outcome: &SubmitOutcome) -> Vec<Self> {
+        Self::for_operations(outcome.head_seq, outcome.operations.clone())
+    }
+
+    pub fn for_operations(head_seq: u64, operations: Vec<ResolvedOperation>) -> Vec<Self> {
+        let overridden: Vec<Self> = operations
+            .iter()
+            .filter(|operation| operation.status == OperationStatus::Overridden)
+            .map(|operation| Self::Overridden {
+                author: operation.actor_user_id.clone(),
+                operation: operation.clone(),
+            })
+            .collect();
+        let mut events = vec![Self::Operations { head_seq, operations }];
+        events.extend(overridden);
+        events
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CollabError {
+    #[error("collaboration session `{0}` not found")]
+    SessionNotFound(String),
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("`{user_id}` has not joined session `{session_id}`")]
+    NotParticipant { session_id: String, user_id: String },
+    #[error("{0}")]
+    Invalid(String),
+    #[error("collaboration session `{0}` is closed")]
+    SessionClosed(String),
+    #[error("quote moved from version {expected} to {current} since the session opened")]
+    StaleSession { expected: u32, current: u32 },
+    #[error("quote is `{0}` and cannot be edited")]
+    QuoteClosed(&'static str),
+    #[error("another submission was recorded at the same time; retry with the new head")]
+    SequenceConflict,
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl CollabError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::SessionNotFound(_) => "session_not_found",
+            Self::QuoteNotFound(_) => "quote_not_found",
+            Self::NotParticipant { .. } => "not_participant",
+            Self::Invalid(_) => "invalid_request",
+            Self::SessionClosed(_) => "session_closed",
+            Self::StaleSession { .. } => "stale_session",
+            Self::QuoteClosed(_) => "quote_closed",
+            Self::SequenceConflict => "sequence_conflict",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for CollabError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct CollabService {
+    pool: DbPool,
+}
+
+impl CollabService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Joins the quote's open session, starting one when there is none.
+    pub async fn open(
+        &self,
+        quote_id: &str,
+        user_id: &str,
+        requested_role: Option<&str>,
+    ) -> Result<(CollabSession, Participant), CollabError> {
+        let user_id = required(user_id, "user_id")?;
+        requested_role.map(authority).transpose()?;
+        let quote = self.load_quote(quote_id).await?;
+        ensure_editable(&quote)?;
+
+        let now = Utc::now();
+        let open: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM quote_sessions
+             WHERE quote_id = ? AND status = 'active' AND expires_at > ?
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(&quote.id.0)
+        .bind(now.to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await?;
+        let session_id = match open {
+            Some(session_id) => session_id,
+            None => {
+                let session_id = format!("COL-{}", short_id());
+                sqlx::query(
+                    "INSERT INTO quote_sessions
+                        (id, quote_id, status, created_by, created_at, expires_at, base_version)
+                     VALUES (?, ?, 'active', ?, ?, ?, ?)",
+                )
+                .bind(&session_id)
+                .bind(&quote.id.0)
+                .bind(user_id)
+                .bind(now.to_rfc3339())
+                .bind((now + Duration::hours(SESSION_TTL_HOURS)).to_rfc3339())
+                .bind(i64::from(quote.version))
+                .execute(&self.pool)
+                .await?;
+                session_id
+            }
+        };
+        let participant = self.join(&session_id, user_id, requested_role).await?;
+        Ok((self.load_session(&session_id).await?, participant))
+    }
+
+    /// Adds `user_id` to the session, or refreshes their presence if already in it.
+    ///
+    /// The role comes from the user's active `sales_rep` record, `sales_rep` when there is none.
+    /// A `requested_role` can only lower it, and re-joining never raises the role a participant
+    /// already holds.
+    pub async fn join(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        requested_role: Option<&str>,
+    ) -> Result<Participant, CollabError> {
+        let user_id = required(user_id, "user_id")?;
+        let requested = requested_role.map(authority).transpose()?;
+        let session = self.load_open_session(session_id).await?;
+
+        let mut role = self.entitled_authority(user_id).await?;
+        if let Some(requested) = requested {
+            role = role.lower(requested);
+        }
+        let current: Option<String> = sqlx::query_scalar(
+            "SELECT role FROM session_participants WHERE session_id = ? AND user_id = ?",
+        )
+        .bind(&session.id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(current) = current.as_deref().and_then(OperationAuthority::for_role) {
+            role = role.lower(current);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO session_participants (session_id, user_id, role, joined_at, last_activity)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT (session_id, user_id)
+             DO UPDATE SET role = excluded.role, last_activity = excluded.last_activity",
+        )
+        .bind(&session.id)
+        .bind(user_id)
+        .bind(&role.role)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+        self.participant(&session.id, user_id).await
+    }
+
+    pub async fn leave(&self, session_id: &str, user_id: &str) -> Result<(), CollabError> {
+        let session = self.load_session(session_id).await?;
+        let removed =
+            sqlx::query("DELETE FROM session_participants WHERE session_id = ? AND user_id = ?")
+                .bind(&session.id)
+                .bind(user_id.trim())
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        if removed == 0 {
+            return Err(not_participant(&session.id, user_id));
+        }
+        Ok(())
+    }
+
+    /// Marks the participant as active now.
+    pub async fn heartbeat(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> Result<Participant, CollabError> {
+        let session = self.load_open_session(session_id).await?;
+        self.touch(&session.id, user_id).await?;
+        self.participant(&session.id, user_id.trim()).await
+    }
+
+    pub async fn participants(&self, session_id: &str) -> Result<Vec<Participant>, CollabError> {
+        let rows = sqlx::query(
+            "SELECT user_id, role, joined_at, last_activity FROM session_participants
+             WHERE session_id = ? ORDER BY joined_at, user_id",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let now = Utc::now();
+        Ok(rows.iter().map(|row| participant_from_row(row, now)).collect())
+    }
+
+    pub async fn state(&self, session_id: &str) -> Result<SessionState, CollabError> {
+        let session = self.load_session(session_id).await?;
+        let quote = self.load_quote(&session.quote_id).await?;
+        let operations = self.operations_since(&session.id, 0).await?;
+        let head_seq = operations.last().map(|operation| operation.seq).unwrap_or(0);
+        let lines = replay(&quote, &operations)?.lines;
+        let participants = self.participants(&session.id).await?;
+        Ok(SessionState { session, head_seq, lines, participants })
+    }
+
+    /// Operations recorded after `after_seq`, oldest first.
+    pub async fn operations_since(
+        &self,
+        session_id: &str,
+        after_seq: u64,
+    ) -> Result<Vec<ResolvedOperation>, CollabError> {
+        let rows = sqlx::query(
+            "SELECT o.seq, o.id, o.user_id, o.payload_json, o.status, o.reason, o.superseded_by,
+                    o.timestamp
+             FROM session_operations o
+             WHERE o.session_id = ? AND o.seq > ?
+             ORDER BY o.seq",
+        )
+        .bind(session_id)
+        .bind(i64::try_from(after_seq).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(operation_from_row).collect()
+    }
+
+    /// Resolves and records a batch of operations from one participant.
+    pub async fn submit(&self, request: SubmitOperations) -> Result<SubmitOutcome, CollabError> {
+        if request.operations.is_empty() {
+            return Err(CollabError::Invalid("at least one operation is required".to_string()));
+        }
+        if request.operations.len() > MAX_BATCH_OPERATIONS {
+            return Err(CollabError::Invalid(format!(
+                "at most {MAX_BATCH_OPERATIONS} operations can be submitted at once"
+            )));
+        }
+        let session = self.load_open_session(&request.session_id).await?;
+        let user_id = request.user_id.trim().to_string();
+        let participant = self.participant(&session.id, &user_id).await?;
+        let authority = authority(&participant.role)?;
+        let quote = self.load_quote(&session.quote_id).await?;
+        ensure_editable(&quote)?;
+
+        let history = self.operations_since(&session.id, 0).await?;
+        let head_seq = history.last().map(|operation| operation.seq).unwrap_or(0);
+        if request.base_seq > head_seq {
+            return Err(CollabError::Invalid(format!(
+                "base_seq {} is ahead of the session head {head_seq}",
+                request.base_seq
+            )));
+        }
+        let mut working = replay(&quote, &history)?;
+
+        // Applied edits from others the author had not seen, by the line they touched.
+        let mut concurrent: HashMap<String, Vec<QuoteOperation>> = HashMap::new();
+        for resolved in history.iter().filter(|resolved| {
+            resolved.seq > request.base_seq
+                && resolved.status == OperationStatus::Applied
+                && resolved.actor_user_id != user_id
+        }) {
+            concurrent
+                .entry(resolved.operation.target_key())
+                .or_default()
+                .push(quote_operation(&quote.id, resolved)?);
+        }
+
+        let timestamp_ms = Utc::now().timestamp_millis();
+        let mut resolved = Vec::with_capacity(request.operations.len());
+        let mut contenders = Vec::new();
+        for operation in request.operations {
+            let candidate = QuoteOperation {
+                operation_id: format!("cop-{}", short_id()),
+                quote_id: quote.id.clone(),
+                actor_user_id: user_id.clone(),
+                authority: authority.clone(),
+                timestamp_ms,
+                operation,
+            };
+            let winner = concurrent
+                .get(&candidate.operation.target_key())
+                .and_then(|applied| applied.iter().find(|applied| applied.outranks(&candidate)));
+            let (status, reason, superseded_by) = match winner {
+                Some(winner) => (
+                    OperationStatus::Overridden,
+                    format!(
+                        "concurrent edit by {} ({}) outranks this one",
+                        winner.actor_user_id, winner.authority.role
+                    ),
+                    Some(winner.operation_id.clone()),
+                ),
+                None => {
+                    contenders.push(candidate.clone());
+                    (OperationStatus::Applied, String::new(), None)
+                }
+            };
+            resolved.push((candidate, status, reason, superseded_by));
+        }
+
+        let result = OperationalTransform::new().transform(&mut working, contenders);
+        for entry in result.history_entries {
+            if let Some((_, status, reason, superseded_by)) = resolved
+                .iter_mut()
+                .find(|(candidate, ..)| candidate.operation_id == entry.operation_id)
+            {
+                *status = entry.status;
+                *reason = entry.reason;
+                *superseded_by = entry.superseded_by;
+            }
+        }
+
+        let recorded_at = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        let mut seq = head_seq;
+        let mut operations = Vec::with_capacity(resolved.len());
+        for (candidate, status, reason, superseded_by) in resolved {
+            seq += 1;
+            let inserted = sqlx::query(
+                "INSERT INTO session_operations
+                    (id, session_id, user_id, operation_type, payload_json, timestamp, seq,
+                     status, reason, superseded_by)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&candidate.operation_id)
+            .bind(&session.id)
+            .bind(&user_id)
+            .bind(candidate.operation.kind())
+            .bind(encode(&candidate, "session operation")?)
+            .bind(&recorded_at)
+            .bind(i64::try_from(seq).unwrap_or(i64::MAX))
+            .bind(status.as_str())
+            .bind(&reason)
+            .bind(&superseded_by)
+            .execute(&mut *tx)
+            .await;
+            match inserted {
+                Ok(_) => {}
+                Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                    return Err(CollabError::SequenceConflict)
+                }
+                Err(error) => return Err(error.into()),
+            }
+            operations.push(ResolvedOperation {
+                seq,
+                operation_id: candidate.operation_id,
+                actor_user_id: user_id.clone(),
+                role: candidate.authority.role,
+                status,
+                reason,
+                superseded_by,
+                operation: candidate.operation,
+                recorded_at: recorded_at.clone(),
+            });
+        }
+        sqlx::query(
+            "UPDATE session_participants SET last_activity = ? WHERE session_id = ? AND user_id = ?",
+        )
+        .bind(&recorded_at)
+        .bind(&session.id)
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(SubmitOutcome {
+            session_id: session.id,
+            head_seq: seq,
+            operations,
+            lines: working.lines,
+        })
+    }
+
+    /// Writes the session's converged lines to the quote as a new revision and closes it.
+    pub async fn commit(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        correlation_id: &str,
+    ) -> Result<CommittedRevision, CollabError> {
+        let session = self.load_open_session(session_id).await?;
+        let user_id = user_id.trim();
+        self.participant(&session.id, user_id).await?;
+        let mut quote = self.load_quote(&session.quote_id).await?;
+        ensure_editable(&quote)?;
+        if quote.version != session.base_version {
+            return Err(CollabError::StaleSession {
+                expected: session.base_version,
+                current: quote.version,
+            });
+        }
+
+        let operations = self.operations_since(&session.id, 0).await?;
+        let applied = operations
+            .iter()
+            .filter(|operation| operation.status == OperationStatus::Applied)
+            .count();
+        if applied == 0 {
+            return Err(CollabError::Invalid("the session has no applied edits to commit".into()));
+        }
+
+        let previous_version = quote.version;
+        quote.lines = replay(&quote, &operations)?.lines;
+
+        // The revision and the session close land together: a concurrent commit or edit either
+        // sees the new version or fails its own version/status guard.
+        let mut tx = self.pool.begin().await?;
+        if let RevisionSave::Stale { current } =
+            SqlQuoteRepository::save_revision(&mut tx, &mut quote).await?
+        {
+            return Err(CollabError::StaleSession { expected: session.base_version, current });
+        }
+        let closed = sqlx::query(
+            "UPDATE quote_sessions SET status = 'closed', committed_version = ?, closed_at = ?
+             WHERE id = ? AND status = 'active'",
+        )
+        .bind(i64::from(quote.version))
+        .bind(Utc::now().to_rfc3339())
+        .bind(&session.id)
+        .execute(&mut *tx)
+        .await?;
+        if closed.rows_affected() == 0 {
+            return Err(CollabError::SessionClosed(session.id));
+        }
+        tx.commit().await?;
+
+        let participants = self.participants(&session.id).await?;
+        let event = AuditEvent::new(
+            Some(quote.id.clone()),
+            None,
+            correlation_id.to_string(),
+            "quote.collab_session_committed",
+            AuditCategory::Flow,
+            user_id.to_string(),
+            AuditOutcome::Success,
+        )
+        .with_actor_type(ActorType::User)
+        .with_entity(EntityType::Quote, quote.id.0.clone())
+        .with_action(AuditAction::Updated)
+        .with_before(json!({ "version": previous_version }).to_string())
+        .with_after(json!({ "version": quote.version, "lines": quote.lines.len() }).to_string())
+        .with_metadata("collab_session_id", session.id.clone())
+        .with_metadata("applied_operations", applied.to_string())
+        .with_metadata(
+            "participants",
+            participants
+                .iter()
+                .map(|participant| participant.user_id.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        SqlAuditEventRepository::new(self.pool.clone()).save(&event).await?;
+
+        Ok(CommittedRevision {
+            session_id: session.id,
+            quote_id: quote.id.0,
+            previous_version,
+            version: quote.version,
+            status: quote_status_as_str(&quote.status),
+            applied_operations: applied,
+            audit_event_id: event.event_id,
+        })
+    }
+
+    pub async fn load_session(&self, session_id: &str) -> Result<CollabSession, CollabError> {
+        let session_id = session_id.trim();
+        let row = sqlx::query(
+            "SELECT id, quote_id, status, created_by, created_at, expires_at, base_version,
+                    committed_version
+             FROM quote_sessions WHERE id = ?",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| CollabError::SessionNotFound(session_id.to_string()))?;
+        Ok(CollabSession {
+            id: row.try_get("id")?,
+            quote_id: row.try_get("quote_id")?,
+            status: row.try_get("status")?,
+            created_by: row.try_get("created_by")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+            base_version: u32::try_from(row.try_get::<i64, _>("base_version")?).unwrap_or(1),
+            committed_version: row
+                .try_get::<Option<i64>, _>("committed_version")?
+                .and_then(|version| u32::try_from(version).ok()),
+        })
+    }
+
+    async fn load_open_session(&self, session_id: &str) -> Result<CollabSession, CollabError> {
+        let session = self.load_session(session_id).await?;
+        let expired = DateTime::parse_from_rfc3339(&session.expires_at)
+            .map(|expires_at| expires_at.with_timezone(&Utc) <= Utc::now())
+            .unwrap_or(false);
+        if session.status != "active" || expired {
+            return Err(CollabError::SessionClosed(session.id));
+        }
+        Ok(session)
+    }
+
+    /// Authority granted by the user's active `sales_rep` record, matched by id or external ref.
+    async fn entitled_authority(&self, user_id: &str) -> Result<OperationAuthority, CollabError> {
+        let rep_role: Option<String> = sqlx::query_scalar(
+            "SELECT role FROM sales_rep
+             WHERE (id = ? OR external_user_ref = ?) AND status = 'active'
+             ORDER BY id = ? DESC LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let rep_role = rep_role
+            .map(|role| role.parse::<SalesRepRole>())
+            .transpose()
+            .map_err(|error| CollabError::Repository(RepositoryError::Decode(error.to_string())))?;
+        Ok(rep_role.map_or_else(
+            || OperationAuthority::for_rep_role(SalesRepRole::Ae),
+            OperationAuthority::for_rep_role,
+        ))
+    }
+
+    async fn participant(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> Result<Participant, CollabError> {
+        let row = sqlx::query(
+            "SELECT user_id, role, joined_at, last_activity FROM session_participants
+             WHERE session_id = ? AND user_id = ?",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| not_participant(session_id, user_id))?;
+        Ok(participant_from_row(&row, Utc::now()))
+    }
+
+    async fn touch(&self, session_id: &str, user_id: &str) -> Result<(), CollabError> {
+        let touched = sqlx::query(
+            "UPDATE session_participants SET last_activity = ? WHERE session_id = ? AND user_id = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(session_id)
+        .bind(user_id.trim())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if touched == 0 {
+            return Err(not_participant(session_id, user_id));
+        }
+        Ok(())
+    }
+
+    async fn load_quote(&self, quote_id: &str) -> Result<Quote, CollabError> {
+        SqlQuoteRepository::new(self.pool.clone())
+            .find_by_id(&QuoteId(quote_id.trim().to_string()))
+            .await?
+            .ok_or_else(|| CollabError::QuoteNotFound(quote_id.trim().to_string()))
+    }
+}
+
+/// The quote with every applied operation replayed in sequence order.
+fn replay(quote: &Quote, operations: &[ResolvedOperation]) -> Result<Quote, CollabError> {
+    let mut working = quote.clone();
+    let mut transform = OperationalTransform::new();
+    for resolved in operations.iter().filter(|op| op.status == OperationStatus::Applied) {
+        transform.transform(&mut working, vec![quote_operation(&quote.id, resolved)?]);
+    }
+    Ok(working)
+}
+
+fn quote_operation(
+    quote_id: &QuoteId,
+    resolved: &ResolvedOperation,
+) -> Result<QuoteOperation, CollabError> {
+    Ok(QuoteOperation {
+        operation_id: resolved.operation_id.clone(),
+        quote_id: quote_id.clone(),
+        actor_user_id: resolved.actor_user_id.clone(),
+        authority: authority(&resolved.role)?,
+        timestamp_ms: DateTime::parse_from_rfc3339(&resolved.recorded_at)
+            .map(|recorded_at| recorded_at.timestamp_millis())
+            .unwrap_or_default(),
+        operation: resolved.operation.clone(),
+    })
+}
+
+fn operation_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<ResolvedOperation, CollabError> {
+    let payload: String = row.try_get("payload_json")?;
+    let operation: QuoteOperation = serde_json::from_str(&payload).map_err(|error| {
+        RepositoryError::Decode(format!("decode session operation payload: {error}"))
+    })?;
+    let status: String = row.try_get("status")?;
+    Ok(ResolvedOperation {
+        seq: u64::try_from(row.try_get::<i64, _>("seq")?).unwrap_or(0),
+        operation_id: row.try_get("id")?,
+        actor_user_id: row.try_get("user_id")?,
+        role: operation.authority.role,
+        status: OperationStatus::parse_label(&status).ok_or_else(|| {
+            RepositoryError::Decode(format!("unknown session operation status `{status}`"))
+        })?,
+        reason: row.try_get("reason")?,
+        superseded_by: row.try_get("superseded_by")?,
+        operation: operation.operation,
+        recorded_at: row.try_get("timestamp")?,
+    })
+}
+
+fn participant_from_row(row: &sqlx::sqlite::SqliteRow, now: DateTime<Utc>) -> Participant {
+    let last_activity: String = row.try_get("last_activity").unwrap_or_default();
+    let online = DateTime::parse_from_rfc3339(&last_activity)
+        .map(|seen| now - seen.with_timezone(&Utc) <= Duration::seconds(PRESENCE_WINDOW_SECS))
+        .unwrap_or(false);
+    Participant {
+        user_id: row.try_get("user_id").unwrap_or_default(),
+        role: row.try_get("role").unwrap_or_default(),
+        joined_at: row.try_get("joined_at").unwrap_or_default(),
+        last_activity,
+        online,
+    }
+}
+
+fn authority(role: &str) -> Result<OperationAuthority, CollabError> {
+    OperationAuthority::for_role(role.trim()).ok_or_else(|| {
+        CollabError::Invalid(format!(
+            "unknown role `{role}`; expected sales_rep, sales_manager, deal_desk, finance or vp_sales"
+        ))
+    })
+}
+
+fn ensure_editable(quote: &Quote) -> Result<(), CollabError> {
+    if !quote.status.is_editable() {
+        return Err(CollabError::QuoteClosed(quote_status_as_str(&quote.status)));
+    }
+    Ok(())
+}
+
+fn required<'a>(value: &'a str, field: &str) -> Result<&'a str, CollabError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(CollabError::Invalid(format!("{field} is required")));
+    }
+    Ok(value)
+}
+
+fn not_participant(session_id: &str, user_id: &str) -> CollabError {
+    CollabError::NotParticipant {
+        session_id: session_id.to_string(),
+        user_id: user_id.trim().to_string(),
+    }
+}
+
+fn encode<T: Serialize>(value: &T, what: &str) -> Result<String, CollabError> {
+    serde_json::to_string(value)
+        .map_err(|error| RepositoryError::Decode(format!("encode {what}: {error}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::collab::{OperationStatus, OperationType};
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use rust_decimal::Decimal;
+
+    use super::{CollabError, CollabEvent, CollabService, SubmitOperations};
+    use crate::repositories::{QuoteRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    type TestResult<T> = Result<T, String>;
+
+    #[tokio::test]
+    async fn concurrent_edits_resolve_by_authority_and_commit_as_revision() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-COLLAB-1").await?;
+        insert_rep(&pool, "U-DESK", "ops").await?;
+        let service = CollabService::new(pool.clone());
+
+        let (session, _) = service
+            .open(&quote_id.0, "U-REP", None)
+            .await
+            .map_err(|error| format!("rep opens: {error}"))?;
+        let (joined, desk) = service
+            .open(&quote_id.0, "U-DESK", None)
+            .await
+            .map_err(|error| format!("desk joins: {error}"))?;
+        assert_eq!(joined.id, session.id);
+        assert!(desk.online);
+        assert_eq!(desk.role, "deal_desk");
+
+        let rep_edit = service
+            .submit(update(&session.id, "U-REP", 0, 20))
+            .await
+            .map_err(|error| format!("rep edit: {error}"))?;
+        assert_eq!(rep_edit.head_seq, 1);
+        assert_eq!(rep_edit.operations[0].status, OperationStatus::Applied);
+
+        // Deal desk had not seen the rep's edit, but outranks it.
+        let desk_edit = service
+            .submit(update(&session.id, "U-DESK", 0, 30))
+            .await
+            .map_err(|error| format!("desk edit: {error}"))?;
+        assert_eq!(desk_edit.operations[0].status, OperationStatus::Applied);
+        assert_eq!(desk_edit.lines[0].quantity, 30);
+
+        // The rep's next edit is concurrent with the desk's and loses.
+        let overridden = service
+            .submit(update(&session.id, "U-REP", 1, 15))
+            .await
+            .map_err(|error| format!("overridden edit: {error}"))?;
+        let lost = &overridden.operations[0];
+        assert_eq!(lost.status, OperationStatus::Overridden);
+        assert_eq!(
+            lost.superseded_by.as_deref(),
+            Some(desk_edit.operations[0].operation_id.as_str())
+        );
+        let events = CollabEvent::for_submission(&overridden);
+        assert!(matches!(
+            &events[1],
+            CollabEvent::Overridden { author, .. } if author == "U-REP"
+        ));
+
+        service
+            .submit(SubmitOperations {
+                operations: vec![OperationType::Insert { line: line("addon-sso", 5) }],
+                ..update(&session.id, "U-REP", 3, 0)
+            })
+            .await
+            .map_err(|error| format!("insert: {error}"))?;
+        let state = service.state(&session.id).await.map_err(|error| format!("state: {error}"))?;
+        assert_eq!(state.head_seq, 4);
+        assert_eq!(state.participants.len(), 2);
+        assert_eq!(
+            state
+                .lines
+                .iter()
+                .map(|line| (line.product_id.0.as_str(), line.quantity))
+                .collect::<Vec<_>>(),
+            vec![("plan-pro", 30), ("addon-sso", 5)]
+        );
+
+        let revision = service
+            .commit(&session.id, "U-DESK", "corr-collab")
+            .await
+            .map_err(|error| format!("commit: {error}"))?;
+        assert_eq!((revision.previous_version, revision.version), (1, 2));
+        assert_eq!(revision.applied_operations, 3);
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("load quote: {error}"))?
+            .ok_or("quote exists")?;
+        assert_eq!(quote.version, 2);
+        assert_eq!(quote.lines.len(), 2);
+        assert_eq!(quote.status, QuoteStatus::Revised);
+
+        let after_commit = service.submit(update(&session.id, "U-REP", 4, 40)).await;
+        assert!(matches!(after_commit, Err(CollabError::SessionClosed(_))));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn commit_against_a_moved_quote_writes_nothing_and_keeps_the_session_open(
+    ) -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-COLLAB-4").await?;
+        let service = CollabService::new(pool.clone());
+        let (session, _) = service
+            .open(&quote_id.0, "U-REP", None)
+            .await
+            .map_err(|error| format!("open: {error}"))?;
+        service
+            .submit(update(&session.id, "U-REP", 0, 20))
+            .await
+            .map_err(|error| format!("edit: {error}"))?;
+
+        // Another writer revises the quote after the session opened.
+        let repo = SqlQuoteRepository::new(pool.clone());
+        let mut moved = repo
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("load quote: {error}"))?
+            .ok_or("quote exists")?;
+        moved.version = 2;
+        repo.save(moved).await.map_err(|error| format!("move quote: {error}"))?;
+
+        let stale = service.commit(&session.id, "U-REP", "corr-stale").await;
+        assert!(
+            matches!(stale, Err(CollabError::StaleSession { expected: 1, current: 2 })),
+            "{stale:?}"
+        );
+        let quote = repo
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("reload quote: {error}"))?
+            .ok_or("quote exists")?;
+        assert_eq!((quote.version, quote.lines[0].quantity), (2, 10));
+        let session =
+            service.load_session(&session.id).await.map_err(|error| format!("session: {error}"))?;
+        assert_eq!(session.status, "active");
+        assert_eq!(session.committed_version, None);
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_participants_with_known_roles_can_edit() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-COLLAB-2").await?;
+        let service = CollabService::new(pool.clone());
+
+        let unknown_role = service.open(&quote_id.0, "U-CUST", Some("customer")).await;
+        assert!(matches!(unknown_role, Err(CollabError::Invalid(_))));
+
+        let (session, _) = service
+            .open(&quote_id.0, "U-REP", Some("sales_rep"))
+            .await
+            .map_err(|error| format!("open: {error}"))?;
+        let outsider = service.submit(update(&session.id, "U-OTHER", 0, 2)).await;
+        assert!(matches!(outsider, Err(CollabError::NotParticipant { .. })));
+
+        service.leave(&session.id, "U-REP").await.map_err(|error| format!("leave: {error}"))?;
+        let left = service.submit(update(&session.id, "U-REP", 0, 2)).await;
+        assert!(matches!(left, Err(CollabError::NotParticipant { .. })));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roles_come_from_the_rep_record_and_never_rise_on_rejoin() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-COLLAB-3").await?;
+        insert_rep(&pool, "U-MGR", "manager").await?;
+        let service = CollabService::new(pool.clone());
+
+        let (session, manager) = service
+            .open(&quote_id.0, "U-MGR", None)
+            .await
+            .map_err(|error| format!("manager opens: {error}"))?;
+        assert_eq!(manager.role, "sales_manager");
+
+        let unlisted = service
+            .join(&session.id, "U-REP", Some("deal_desk"))
+            .await
+            .map_err(|error| format!("rep joins: {error}"))?;
+        assert_eq!(unlisted.role, "sales_rep", "users without a rep record get the lowest role");
+
+        let lowered = service
+            .join(&session.id, "U-MGR", Some("sales_rep"))
+            .await
+            .map_err(|error| format!("manager lowers role: {error}"))?;
+        assert_eq!(lowered.role, "sales_rep");
+        for requested in [Some("sales_manager"), Some("vp_sales"), None] {
+            let rejoined = service
+                .join(&session.id, "U-MGR", requested)
+                .await
+                .map_err(|error| format!("manager re-joins: {error}"))?;
+            assert_eq!(rejoined.role, "sales_rep", "re-join asking for {requested:?}");
+        }
+
+        pool.close().await;
+        Ok(())
+    }
+
+    fn update(session_id: &str, user_id: &str, base_seq: u64, quantity: u32) -> SubmitOperations {
+        SubmitOperations {
+            session_id: session_id.to_string(),
+            user_id: user_id.to_string(),
+            base_seq,
+            operations: vec![OperationType::Update {
+                product_id: ProductId("plan-pro".to_string()),
+                quantity: Some(quantity),
+                unit_price: None,
+            }],
+        }
+    }
+
+    fn line(product_id: &str, quantity: u32) -> QuoteLine {
+        QuoteLine {
+            product_id: ProductId(product_id.to_string()),
+            quantity,
+            unit_price: Decimal::new(100, 0),
+            discount_pct: 0.0,
+            notes: None,
+        }
+    }
+
+    async fn setup_pool() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30)
+            .await
+            .map_err(|error| format!("connect test pool: {error}"))?;
+        migrations::run_pending(&pool).await.map_err(|error| format!("run migrations: {error}"))?;
+        Ok(pool)
+    }
+
+    async fn insert_rep(pool: &DbPool, id: &str, role: &str) -> TestResult<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO sales_rep (id, name, role, status, created_at, updated_at)
+             VALUES (?, ?, ?, 'active', ?, ?)",
+        )
+        .bind(id)
+        .bind(format!("Rep {id}"))
+        .bind(role)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .map_err(|error| format!("insert sales_rep: {error}"))?;
+        Ok(())
+    }
+
+    async fn insert_quote(pool: &DbPool, id: &str) -> TestResult<QuoteId> {
+        let now = Utc::now();
+        let quote = Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status: QuoteStatus::Sent,
+            account_id: Some("acct-collab".to_string()),
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: Some(12),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: "U-REP".to_string(),
+            lines: vec![line("plan-pro", 10)],
+            created_at: now,
+            updated_at: now,
+        };
+        SqlQuoteRepository::new(pool.clone())
+            .save(quote.clone())
+            .await
+            .map_err(|error| format!("save quote fixture {id}: {error}"))?;
+        Ok(quote.id)
+    }
+}
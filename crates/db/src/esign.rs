@@ -0,0 +1,874 @@
+//! Customer e-signature requests and quote acceptance.
+//!
+//! Starting a request queues an `email.signature_code` outbox task that names the request but
+//! carries no code. The delivering worker calls [`QuoteSignatureService::issue_code`], which
+//! stores a salted hash of a fresh one-time code, and renders the code into the email in memory,
+//! so the plaintext never reaches the database. Verifying the code yields a
+//! [`VerifiedSignatureRequest`], the only way to record an acceptance: that moves the quote to
+//! `accepted`, stores the signed PDF with its evidence, and appends an `accept` entry carrying
+//! the signed document's SHA-256 to `quote_ledger`, all in one transaction.
+//!
+//! Starts are limited per quote and per signer email, and a new request inherits the wrong-code
+//! count of the requests it replaces, so restarting neither resets the lockout nor turns the
+//! portal into a mail cannon.
+
+use quotey_core::chrono::{DateTime, Duration, Utc};
+use quotey_core::domain::outbox::OutboxOperation;
+use quotey_core::domain::quote::{QuoteId, QuoteStatus};
+use quotey_core::esign::{
+    code_matches, document_sha256, generate_code, hash_code, AcceptanceCertificate, SignatureError,
+    SignerIdentity, CODE_TTL_MINUTES, MAX_CODE_ATTEMPTS, MAX_STARTS_PER_WINDOW,
+    START_WINDOW_MINUTES,
+};
+use quotey_core::ledger::{seal_entry, LedgerAction};
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row, SqliteConnection};
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::quote::parse_quote_status;
+use crate::repositories::RepositoryError;
+use crate::DbPool;
+
+/// Everything known about the signer and document when a request is opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureStart {
+    pub quote_id: String,
+    pub template: String,
+    /// SHA-256 of the unsigned quote PDF shown to the signer.
+    pub document_sha256: String,
+    pub signer: SignerIdentity,
+    pub ip_address: String,
+    pub user_agent: String,
+}
+
+/// A new request and the queued task that will deliver its code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IssuedSignatureRequest {
+    pub request: SignatureRequest,
+    pub email_task_id: String,
+}
+
+/// A freshly minted code for a pending request. Only held in memory, long enough to send it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IssuedSignatureCode {
+    pub request: SignatureRequest,
+    pub code: String,
+}
+
+impl IssuedSignatureCode {
+    /// The email handed to the email adapter; never queued or stored.
+    pub fn email(&self) -> OutboxOperation {
+        OutboxOperation::EmailSend {
+            to: vec![self.request.signer.email.clone()],
+            subject: signature_code_subject(&self.request.quote_id),
+            body_text: format!(
+                "Hello {},\n\nUse code {} to confirm your signature on quote {}. The code \
+                 expires in {CODE_TTL_MINUTES} minutes.\n\nIf you did not ask to sign this \
+                 quote, you can ignore this email.\n",
+                self.request.signer.name, self.code, self.request.quote_id
+            ),
+            body_html: None,
+            attachment_path: None,
+        }
+    }
+}
+
+/// Row in `quote_signature_request`, without the code hash or signed document.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SignatureRequest {
+    pub id: String,
+    pub quote_id: String,
+    pub quote_version: u32,
+    pub template: String,
+    pub document_sha256: String,
+    pub signer: SignerIdentity,
+    /// `pending`, `signed`, `locked` or `superseded`.
+    pub status: String,
+    pub failed_attempts: u32,
+    pub requested_ip: String,
+    pub requested_user_agent: String,
+    pub created_at: DateTime<Utc>,
+    pub code_expires_at: DateTime<Utc>,
+}
+
+/// A pending request whose one-time code has just been confirmed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedSignatureRequest {
+    request: SignatureRequest,
+}
+
+impl VerifiedSignatureRequest {
+    pub fn request(&self) -> &SignatureRequest {
+        &self.request
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct QuoteAcceptance {
+    pub request_id: String,
+    pub quote_id: String,
+    pub signed_pdf_sha256: String,
+    pub ledger_entry_id: String,
+    pub ledger_version: u32,
+    pub signed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum SignatureServiceError {
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("quote `{quote_id}` is `{status}` and cannot be signed")]
+    NotSignable { quote_id: String, status: String },
+    #[error("signature request `{0}` not found")]
+    RequestNotFound(String),
+    #[error("signature request `{request_id}` is already {status}")]
+    RequestClosed { request_id: String, status: String },
+    #[error("the signing code for request `{0}` has expired")]
+    CodeExpired(String),
+    #[error("incorrect signing code; {remaining} attempts left")]
+    InvalidCode { remaining: u32 },
+    #[error("signature request `{0}` is locked after too many incorrect codes")]
+    Locked(String),
+    #[error("too many signing requests; try again in {retry_after_secs}s")]
+    TooManyStarts { retry_after_secs: u64 },
+    #[error("certificate does not match signature request `{0}`")]
+    CertificateMismatch(String),
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for SignatureServiceError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct QuoteSignatureService {
+    pool: DbPool,
+}
+
+impl QuoteSignatureService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fails with [`SignatureServiceError::TooManyStarts`] when `quote_id` or `signer_email`
+    /// may not open another request yet. Lets callers refuse before rendering anything;
+    /// [`Self::start`] checks again.
+    pub async fn check_start_allowed(
+        &self,
+        quote_id: &str,
+        signer_email: &str,
+    ) -> Result<(), SignatureServiceError> {
+        let mut conn = self.pool.acquire().await?;
+        start_allowance(&mut conn, quote_id, signer_email, Utc::now()).await.map(|_| ())
+    }
+
+    /// Opens a request for a quote that can still be accepted, superseding any pending request
+    /// for it, and queues the email carrying the one-time code.
+    pub async fn start(
+        &self,
+        start: SignatureStart,
+    ) -> Result<IssuedSignatureRequest, SignatureServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query("SELECT status, version FROM quote WHERE id = ?")
+            .bind(&start.quote_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| SignatureServiceError::QuoteNotFound(start.quote_id.clone()))?;
+        let status: String = row.try_get("status")?;
+        if !is_signable(&status) {
+            return Err(SignatureServiceError::NotSignable { quote_id: start.quote_id, status });
+        }
+        let quote_version = u32::try_from(row.try_get::<i64, _>("version")?).unwrap_or(1).max(1);
+
+        let now = Utc::now();
+        let failed_attempts =
+            start_allowance(&mut tx, &start.quote_id, &start.signer.email, now).await?;
+        let request = SignatureRequest {
+            id: format!("SIGN-{}", short_id()),
+            quote_id: start.quote_id,
+            quote_version,
+            template: start.template,
+            document_sha256: start.document_sha256,
+            signer: start.signer,
+            status: "pending".to_owned(),
+            failed_attempts,
+            requested_ip: start.ip_address,
+            requested_user_agent: start.user_agent,
+            created_at: now,
+            code_expires_at: now + Duration::minutes(CODE_TTL_MINUTES),
+        };
+
+        sqlx::query(
+            "UPDATE quote_signature_request SET status = 'superseded'
+             WHERE quote_id = ? AND status = 'pending'",
+        )
+        .bind(&request.quote_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO quote_signature_request
+                (id, quote_id, quote_version, template, document_sha256, signer_name,
+                 signer_title, signer_email, code_hash, code_expires_at, failed_attempts,
+                 requested_ip, requested_user_agent, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&request.id)
+        .bind(&request.quote_id)
+        .bind(i64::from(request.quote_version))
+        .bind(&request.template)
+        .bind(&request.document_sha256)
+        .bind(&request.signer.name)
+        .bind(&request.signer.title)
+        .bind(&request.signer.email)
+        .bind("")
+        .bind(request.code_expires_at.to_rfc3339())
+        .bind(i64::from(request.failed_attempts))
+        .bind(&request.requested_ip)
+        .bind(&request.requested_user_agent)
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        let email = OutboxOperation::SignatureCodeSend {
+            signature_request_id: request.id.clone(),
+            to: vec![request.signer.email.clone()],
+            subject: signature_code_subject(&request.quote_id),
+        };
+        let email_task_id = format!("email-{}", sqlx::types::Uuid::new_v4().simple());
+        let quote_id = QuoteId(request.quote_id.clone());
+        sqlx::query(
+            "INSERT INTO execution_queue_task
+                (id, quote_id, operation_kind, payload_json, idempotency_key, state,
+                 retry_count, max_retries, available_at, state_version, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, 'queued', 0, ?, ?, 1, ?, ?)",
+        )
+        .bind(&email_task_id)
+        .bind(&request.quote_id)
+        .bind(email.kind())
+        .bind(serde_json::to_string(&email).map_err(|error| {
+            RepositoryError::Decode(format!("cannot encode signing email: {error}"))
+        })?)
+        .bind(email.idempotency_key(&quote_id).0)
+        .bind(i64::from(email.retry_policy().max_retries))
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(IssuedSignatureRequest { request, email_task_id })
+    }
+
+    /// Mints the one-time code for a pending request, replacing any earlier code and restarting
+    /// its expiry; called by the email worker right before it sends the code.
+    pub async fn issue_code(
+        &self,
+        request_id: &str,
+    ) -> Result<IssuedSignatureCode, SignatureServiceError> {
+        let code = generate_code();
+        let expires_at = Utc::now() + Duration::minutes(CODE_TTL_MINUTES);
+        let updated = sqlx::query(
+            "UPDATE quote_signature_request SET code_hash = ?, code_expires_at = ?
+             WHERE id = ? AND status = 'pending'",
+        )
+        .bind(hash_code(request_id, &code))
+        .bind(expires_at.to_rfc3339())
+        .bind(request_id)
+        .execute(&self.pool)
+        .await?;
+
+        let row = sqlx::query(&format!("{REQUEST_SELECT} WHERE id = ?"))
+            .bind(request_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SignatureServiceError::RequestNotFound(request_id.to_owned()))?;
+        let request = request_from_row(&row)?;
+        if updated.rows_affected() == 0 {
+            return Err(SignatureServiceError::RequestClosed {
+                request_id: request.id,
+                status: request.status,
+            });
+        }
+        Ok(IssuedSignatureCode { request, code })
+    }
+
+    /// Checks the one-time code of a pending request on `quote_id`. Each wrong code counts
+    /// against [`MAX_CODE_ATTEMPTS`]; the last one locks the request.
+    pub async fn verify_code(
+        &self,
+        quote_id: &str,
+        request_id: &str,
+        code: &str,
+    ) -> Result<VerifiedSignatureRequest, SignatureServiceError> {
+        let row = sqlx::query(&format!("{REQUEST_SELECT} WHERE id = ? AND quote_id = ?"))
+            .bind(request_id)
+            .bind(quote_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SignatureServiceError::RequestNotFound(request_id.to_owned()))?;
+        let request = request_from_row(&row)?;
+        if request.status != "pending" {
+            return Err(SignatureServiceError::RequestClosed {
+                request_id: request.id,
+                status: request.status,
+            });
+        }
+        if request.code_expires_at <= Utc::now() {
+            return Err(SignatureServiceError::CodeExpired(request.id));
+        }
+
+        let code_hash: String = row.try_get("code_hash")?;
+        if code_matches(&request.id, code, &code_hash) {
+            return Ok(VerifiedSignatureRequest { request });
+        }
+
+        let failed_attempts: i64 = sqlx::query_scalar(
+            "UPDATE quote_signature_request
+             SET failed_attempts = failed_attempts + 1,
+                 status = CASE WHEN failed_attempts + 1 >= ? THEN 'locked' ELSE status END
+             WHERE id = ? AND status = 'pending'
+             RETURNING failed_attempts",
+        )
+        .bind(i64::from(MAX_CODE_ATTEMPTS))
+        .bind(&request.id)
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(i64::from(MAX_CODE_ATTEMPTS));
+        let remaining = u32::try_from(i64::from(MAX_CODE_ATTEMPTS) - failed_attempts).unwrap_or(0);
+        if remaining == 0 {
+            Err(SignatureServiceError::Locked(request.id))
+        } else {
+            Err(SignatureServiceError::InvalidCode { remaining })
+        }
+    }
+
+    /// Records the signed quote: the request becomes `signed`, the quote `accepted`, and the
+    /// SHA-256 of `signed_pdf` is appended to the quote's ledger chain signed with `ledger_key`.
+    pub async fn record_acceptance(
+        &self,
+        verified: VerifiedSignatureRequest,
+        certificate: &AcceptanceCertificate,
+        signed_pdf: &[u8],
+        ledger_key: &[u8],
+    ) -> Result<QuoteAcceptance, SignatureServiceError> {
+        let request = verified.request;
+        if certificate.request_id != request.id
+            || certificate.quote_id != request.quote_id
+            || certificate.document_sha256 != request.document_sha256
+            || certificate.signer != request.signer
+        {
+            return Err(SignatureServiceError::CertificateMismatch(request.id));
+        }
+        certificate.signature.validate()?;
+
+        let signed_pdf_sha256 = document_sha256(signed_pdf);
+        let signed_at = certificate.evidence.signed_at;
+        let encode = |value: serde_json::Result<String>| {
+            value.map_err(|error| {
+                RepositoryError::Decode(format!("cannot encode signature evidence: {error}"))
+            })
+        };
+        let signature_json = encode(serde_json::to_string(&certificate.signature))?;
+        let evidence_json = encode(serde_json::to_string(&certificate.evidence))?;
+
+        let mut tx = self.pool.begin().await?;
+        let quote_updated = sqlx::query(
+            "UPDATE quote SET status = 'accepted', updated_at = ?
+             WHERE id = ? AND status IN ('approved', 'finalized', 'sent')",
+        )
+        .bind(signed_at.to_rfc3339())
+        .bind(&request.quote_id)
+        .execute(&mut *tx)
+        .await?;
+        if quote_updated.rows_affected() == 0 {
+            let status: Option<String> =
+                sqlx::query_scalar("SELECT status FROM quote WHERE id = ?")
+                    .bind(&request.quote_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            return Err(match status {
+                Some(status) => {
+                    SignatureServiceError::NotSignable { quote_id: request.quote_id, status }
+                }
+                None => SignatureServiceError::QuoteNotFound(request.quote_id),
+            });
+        }
+
+        let previous = sqlx::query(
+            "SELECT version_number, metadata_json FROM quote_ledger
+             WHERE quote_id = ? ORDER BY version_number DESC LIMIT 1",
+        )
+        .bind(&request.quote_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (ledger_version, prev_hash) = match previous {
+            Some(row) => {
+                let version: i64 = row.try_get("version_number")?;
+                let metadata: String = row.try_get("metadata_json")?;
+                let prev_hash = serde_json::from_str::<serde_json::Value>(&metadata)
+                    .ok()
+                    .and_then(|value| value.get("entry_hash")?.as_str().map(str::to_owned));
+                (u32::try_from(version).unwrap_or(u32::MAX).saturating_add(1), prev_hash)
+            }
+            None => (1, None),
+        };
+        let entry = seal_entry(
+            ledger_key,
+            QuoteId(request.quote_id.clone()),
+            ledger_version,
+            signed_pdf_sha256.clone(),
+            prev_hash,
+            format!("customer:{}", request.signer.email),
+            LedgerAction::Accept,
+        );
+        let metadata = serde_json::json!({
+            "entry_hash": entry.entry_hash,
+            "document_sha256": request.document_sha256,
+            "quote_version": request.quote_version,
+            "signature_request_id": request.id,
+            "signature_kind": certificate.signature.kind(),
+            "template": request.template,
+        });
+        sqlx::query(
+            "INSERT INTO quote_ledger
+                (entry_id, quote_id, version_number, content_hash, prev_hash, actor_id,
+                 action_type, timestamp, signature, metadata_json)
+             VALUES (?, ?, ?, ?, ?, ?, 'accept', ?, ?, ?)",
+        )
+        .bind(&entry.entry_id)
+        .bind(&request.quote_id)
+        .bind(i64::from(entry.version))
+        .bind(&entry.content_hash)
+        .bind(entry.prev_hash.as_deref())
+        .bind(&entry.actor_id)
+        .bind(entry.timestamp.to_rfc3339())
+        .bind(&entry.signature)
+        .bind(metadata.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        let signed = sqlx::query(
+            "UPDATE quote_signature_request
+             SET status = 'signed', signature_json = ?, evidence_json = ?, signed_pdf = ?,
+                 signed_pdf_sha256 = ?, ledger_entry_id = ?, signed_at = ?
+             WHERE id = ? AND status = 'pending'",
+        )
+        .bind(&signature_json)
+        .bind(&evidence_json)
+        .bind(signed_pdf)
+        .bind(&signed_pdf_sha256)
+        .bind(&entry.entry_id)
+        .bind(signed_at.to_rfc3339())
+        .bind(&request.id)
+        .execute(&mut *tx)
+        .await?;
+        if signed.rows_affected() == 0 {
+            return Err(SignatureServiceError::RequestClosed {
+                request_id: request.id,
+                status: "no longer pending".to_owned(),
+            });
+        }
+        tx.commit().await?;
+
+        Ok(QuoteAcceptance {
+            request_id: request.id,
+            quote_id: request.quote_id,
+            signed_pdf_sha256,
+            ledger_entry_id: entry.entry_id,
+            ledger_version: entry.version,
+            signed_at,
+        })
+    }
+
+    /// The most recently signed PDF of a quote, with the request it came from.
+    pub async fn signed_pdf(
+        &self,
+        quote_id: &str,
+    ) -> Result<Option<(SignatureRequest, Vec<u8>)>, SignatureServiceError> {
+        let row = sqlx::query(&format!(
+            "{REQUEST_SELECT} WHERE quote_id = ? AND status = 'signed' AND signed_pdf IS NOT NULL
+             ORDER BY signed_at DESC LIMIT 1"
+        ))
+        .bind(quote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| Ok((request_from_row(&row)?, row.try_get("signed_pdf")?))).transpose()
+    }
+}
+
+const REQUEST_SELECT: &str = "SELECT id, quote_id, quote_version, template, document_sha256,
+        signer_name, signer_title, signer_email, code_hash, code_expires_at, failed_attempts,
+        status, requested_ip, requested_user_agent, created_at, signed_pdf
+     FROM quote_signature_request";
+
+/// Wrong codes a new request on `quote_id` inherits, after checking that neither the quote nor
+/// `signer_email` has used up its starts and that the quote is not locked out. Both limits and
+/// the carried count look back [`START_WINDOW_MINUTES`].
+async fn start_allowance(
+    conn: &mut SqliteConnection,
+    quote_id: &str,
+    signer_email: &str,
+    now: DateTime<Utc>,
+) -> Result<u32, SignatureServiceError> {
+    let window = Duration::minutes(START_WINDOW_MINUTES);
+    let since = (now - window).to_rfc3339();
+    let retry_after = |oldest: &str| {
+        let reopens = DateTime::parse_from_rfc3339(oldest)
+            .map(|oldest| oldest.with_timezone(&Utc) + window)
+            .unwrap_or(now + window);
+        u64::try_from((reopens - now).num_seconds()).unwrap_or(0).max(1)
+    };
+
+    let carried: Option<(i64, String)> = sqlx::query_as(
+        "SELECT failed_attempts, created_at FROM quote_signature_request
+         WHERE quote_id = ? AND created_at > ? AND status != 'signed'
+         ORDER BY failed_attempts DESC, created_at ASC
+         LIMIT 1",
+    )
+    .bind(quote_id)
+    .bind(&since)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let failed_attempts = carried.as_ref().map_or(0, |(failed, _)| *failed);
+    if let Some((_, created_at)) =
+        carried.filter(|_| failed_attempts >= i64::from(MAX_CODE_ATTEMPTS))
+    {
+        return Err(SignatureServiceError::TooManyStarts {
+            retry_after_secs: retry_after(&created_at),
+        });
+    }
+
+    for (filter, value) in
+        [("quote_id = ?", quote_id), ("lower(signer_email) = lower(?)", signer_email)]
+    {
+        let (count, oldest): (i64, Option<String>) = sqlx::query_as(&format!(
+            "SELECT COUNT(*), MIN(created_at) FROM quote_signature_request
+             WHERE {filter} AND created_at > ?"
+        ))
+        .bind(value)
+        .bind(&since)
+        .fetch_one(&mut *conn)
+        .await?;
+        if count >= MAX_STARTS_PER_WINDOW {
+            return Err(SignatureServiceError::TooManyStarts {
+                retry_after_secs: retry_after(oldest.as_deref().unwrap_or_default()),
+            });
+        }
+    }
+    Ok(u32::try_from(failed_attempts).unwrap_or(0))
+}
+
+fn signature_code_subject(quote_id: &str) -> String {
+    format!("Your signing code for quote {quote_id}")
+}
+
+fn is_signable(status: &str) -> bool {
+    parse_quote_status(status).is_ok_and(|status| {
+        matches!(status, QuoteStatus::Approved | QuoteStatus::Finalized | QuoteStatus::Sent)
+    })
+}
+
+fn request_from_row(row: &SqliteRow) -> Result<SignatureRequest, SignatureServiceError> {
+    let timestamp = |column: &str| -> Result<DateTime<Utc>, SignatureServiceError> {
+        let raw: String = row.try_get(column)?;
+        DateTime::parse_from_rfc3339(&raw).map(|value| value.with_timezone(&Utc)).map_err(|error| {
+            RepositoryError::Decode(format!("invalid {column} `{raw}`: {error}")).into()
+        })
+    };
+    let count = |column: &str| -> Result<u32, SignatureServiceError> {
+        Ok(u32::try_from(row.try_get::<i64, _>(column)?).unwrap_or(0))
+    };
+    Ok(SignatureRequest {
+        id: row.try_get("id")?,
+        quote_id: row.try_get("quote_id")?,
+        quote_version: count("quote_version")?,
+        template: row.try_get("template")?,
+        document_sha256: row.try_get("document_sha256")?,
+        signer: SignerIdentity {
+            name: row.try_get("signer_name")?,
+            title: row.try_get("signer_title")?,
+            email: row.try_get("signer_email")?,
+        },
+        status: row.try_get("status")?,
+        failed_attempts: count("failed_attempts")?,
+        requested_ip: row.try_get("requested_ip")?,
+        requested_user_agent: row.try_get("requested_user_agent")?,
+        created_at: timestamp("created_at")?,
+        code_expires_at: timestamp("code_expires_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::esign::{SignatureCapture, SigningEvidence};
+
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+
+    type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+    const LEDGER_KEY: &[u8] = b"test-ledger-key";
+
+    async fn setup(status: &str) -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await?;
+        migrations::run_pending(&pool).await?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, version, currency, created_by, created_at, updated_at)
+             VALUES ('Q-SIGN', ?, 3, 'USD', 'rep', ?, ?)",
+        )
+        .bind(status)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await?;
+        Ok(pool)
+    }
+
+    fn start() -> SignatureStart {
+        SignatureStart {
+            quote_id: "Q-SIGN".to_owned(),
+            template: "detailed".to_owned(),
+            document_sha256: "ab".repeat(32),
+            signer: SignerIdentity::new("Dana Reyes", "VP Procurement", "dana@northwind.example")
+                .expect("signer"),
+            ip_address: "203.0.113.7".to_owned(),
+            user_agent: "Mozilla/5.0".to_owned(),
+        }
+    }
+
+    fn certificate(request: &SignatureRequest) -> AcceptanceCertificate {
+        AcceptanceCertificate {
+            request_id: request.id.clone(),
+            quote_id: request.quote_id.clone(),
+            quote_version: request.quote_version,
+            document_sha256: request.document_sha256.clone(),
+            signer: request.signer.clone(),
+            signature: SignatureCapture::Typed { text: "Dana Reyes".to_owned() },
+            evidence: SigningEvidence {
+                code_sent_at: request.created_at,
+                requested_ip: request.requested_ip.clone(),
+                requested_user_agent: request.requested_user_agent.clone(),
+                signed_at: Utc::now(),
+                signed_ip: "203.0.113.9".to_owned(),
+                signed_user_agent: "Mozilla/5.0".to_owned(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn accepting_a_quote_records_the_signed_document_in_the_ledger() -> TestResult<()> {
+        let pool = setup("sent").await?;
+        let service = QuoteSignatureService::new(pool.clone());
+        let issued = service.start(start()).await?;
+        assert_eq!(issued.request.quote_version, 3);
+
+        let (kind, payload): (String, String) = sqlx::query_as(
+            "SELECT operation_kind, payload_json FROM execution_queue_task WHERE id = ?",
+        )
+        .bind(&issued.email_task_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(kind, "email.signature_code");
+        assert!(payload.contains(&issued.request.id) && payload.contains("dana@northwind.example"));
+
+        let code = service.issue_code(&issued.request.id).await?;
+        let OutboxOperation::EmailSend { to, body_text, .. } = code.email() else {
+            panic!("signing code email");
+        };
+        assert_eq!(to, vec!["dana@northwind.example".to_owned()]);
+        assert!(body_text.contains(&format!("Use code {} ", code.code)));
+
+        let verified = service.verify_code("Q-SIGN", &issued.request.id, &code.code).await?;
+        let certificate = certificate(verified.request());
+        let acceptance =
+            service.record_acceptance(verified, &certificate, b"%PDF-signed", LEDGER_KEY).await?;
+        assert_eq!(acceptance.ledger_version, 1);
+        assert_eq!(acceptance.signed_pdf_sha256, document_sha256(b"%PDF-signed"));
+
+        let status: String = sqlx::query_scalar("SELECT status FROM quote WHERE id = 'Q-SIGN'")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(status, "accepted");
+        let (content_hash, action, metadata): (String, String, String) = sqlx::query_as(
+            "SELECT content_hash, action_type, metadata_json FROM quote_ledger WHERE entry_id = ?",
+        )
+        .bind(&acceptance.ledger_entry_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(content_hash, acceptance.signed_pdf_sha256);
+        assert_eq!(action, "accept");
+        assert!(metadata.contains(&"ab".repeat(32)), "unsigned document hash is kept too");
+
+        let (request, pdf) = service.signed_pdf("Q-SIGN").await?.expect("signed pdf");
+        assert_eq!((request.status.as_str(), pdf.as_slice()), ("signed", &b"%PDF-signed"[..]));
+        let replay = service.verify_code("Q-SIGN", &issued.request.id, &code.code).await;
+        assert!(matches!(replay, Err(SignatureServiceError::RequestClosed { .. })));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn signing_codes_are_never_written_to_the_database() -> TestResult<()> {
+        let pool = setup("sent").await?;
+        let service = QuoteSignatureService::new(pool.clone());
+        let issued = service.start(start()).await?;
+        let code = service.issue_code(&issued.request.id).await?;
+        service.verify_code("Q-SIGN", &issued.request.id, &code.code).await?;
+
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .fetch_all(&pool)
+        .await?;
+        for table in tables {
+            let columns: Vec<String> =
+                sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{table}')"))
+                    .fetch_all(&pool)
+                    .await?;
+            for column in columns {
+                let values: Vec<Option<Vec<u8>>> = sqlx::query_scalar(&format!(
+                    "SELECT CAST(\"{column}\" AS BLOB) FROM \"{table}\""
+                ))
+                .fetch_all(&pool)
+                .await?;
+                for value in values.into_iter().flatten() {
+                    let value = String::from_utf8_lossy(&value);
+                    assert!(
+                        !contains_code(&value, &code.code),
+                        "signing code found in {table}.{column}: {value}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `code` appears as a standalone run of digits, so longer numbers such as
+    /// timestamps don't count.
+    fn contains_code(value: &str, code: &str) -> bool {
+        value.split(|ch: char| !ch.is_ascii_digit()).any(|digits| digits == code)
+    }
+
+    #[tokio::test]
+    async fn wrong_codes_lock_the_request_and_new_requests_supersede_old_ones() -> TestResult<()> {
+        let pool = setup("approved").await?;
+        let service = QuoteSignatureService::new(pool.clone());
+        let first = service.start(start()).await?;
+        let first_code = service.issue_code(&first.request.id).await?.code;
+        let second = service.start(start()).await?;
+        let superseded = service.verify_code("Q-SIGN", &first.request.id, &first_code).await;
+        assert!(matches!(superseded, Err(SignatureServiceError::RequestClosed { .. })));
+        let reissued = service.issue_code(&first.request.id).await;
+        assert!(matches!(reissued, Err(SignatureServiceError::RequestClosed { .. })));
+
+        let second_code = service.issue_code(&second.request.id).await?.code;
+        let wrong = if second_code == "000000" { "111111" } else { "000000" };
+        for remaining in (1..MAX_CODE_ATTEMPTS).rev() {
+            let result = service.verify_code("Q-SIGN", &second.request.id, wrong).await;
+            assert!(
+                matches!(result, Err(SignatureServiceError::InvalidCode { remaining: left }) if left == remaining)
+            );
+        }
+        let locked = service.verify_code("Q-SIGN", &second.request.id, wrong).await;
+        assert!(matches!(locked, Err(SignatureServiceError::Locked(_))));
+        let after = service.verify_code("Q-SIGN", &second.request.id, &second_code).await;
+        assert!(matches!(after, Err(SignatureServiceError::RequestClosed { .. })));
+
+        // The lockout outlives restarts until the start window has passed.
+        let restarted = service.start(start()).await;
+        assert!(matches!(restarted, Err(SignatureServiceError::TooManyStarts { .. })));
+        sqlx::query("UPDATE quote_signature_request SET created_at = ?")
+            .bind((Utc::now() - Duration::minutes(START_WINDOW_MINUTES + 1)).to_rfc3339())
+            .execute(&pool)
+            .await?;
+        let third = service.start(start()).await?;
+        assert_eq!(third.request.failed_attempts, 0);
+        let third_code = service.issue_code(&third.request.id).await?.code;
+        sqlx::query("UPDATE quote_signature_request SET code_expires_at = ? WHERE id = ?")
+            .bind((Utc::now() - Duration::minutes(1)).to_rfc3339())
+            .bind(&third.request.id)
+            .execute(&pool)
+            .await?;
+        let expired = service.verify_code("Q-SIGN", &third.request.id, &third_code).await;
+        assert!(matches!(expired, Err(SignatureServiceError::CodeExpired(_))));
+        let other_quote = service.verify_code("Q-OTHER", &third.request.id, &third_code).await;
+        assert!(matches!(other_quote, Err(SignatureServiceError::RequestNotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restarts_keep_failed_attempts_and_starts_are_limited_per_quote_and_email(
+    ) -> TestResult<()> {
+        let pool = setup("approved").await?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, version, currency, created_by, created_at, updated_at)
+             VALUES ('Q-SIGN-2', 'approved', 1, 'USD', 'rep', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await?;
+        let service = QuoteSignatureService::new(pool.clone());
+
+        let first = service.start(start()).await?;
+        let code = service.issue_code(&first.request.id).await?.code;
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+        for _ in 0..2 {
+            service.verify_code("Q-SIGN", &first.request.id, wrong).await.unwrap_err();
+        }
+        let second = service.start(start()).await?;
+        assert_eq!(second.request.failed_attempts, 2);
+        service.issue_code(&second.request.id).await?;
+        let result = service.verify_code("Q-SIGN", &second.request.id, wrong).await;
+        assert!(matches!(
+            result,
+            Err(SignatureServiceError::InvalidCode { remaining }) if remaining == MAX_CODE_ATTEMPTS - 3
+        ));
+
+        for _ in 2..MAX_STARTS_PER_WINDOW {
+            service.start(start()).await?;
+        }
+        let per_quote = service.check_start_allowed("Q-SIGN", "someone@else.example").await;
+        assert!(matches!(
+            per_quote,
+            Err(SignatureServiceError::TooManyStarts { retry_after_secs })
+                if retry_after_secs > 0 && retry_after_secs <= 3600
+        ));
+        let emails: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM execution_queue_task WHERE operation_kind = 'email.signature_code'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(emails, MAX_STARTS_PER_WINDOW);
+
+        // The same signer cannot move on to another quote, whatever the case of their address.
+        let mut other = start();
+        other.quote_id = "Q-SIGN-2".to_owned();
+        other.signer =
+            SignerIdentity::new("Dana Reyes", "VP Procurement", "DANA@Northwind.example")
+                .expect("signer");
+        assert!(matches!(
+            service.start(other.clone()).await,
+            Err(SignatureServiceError::TooManyStarts { .. })
+        ));
+        other.signer =
+            SignerIdentity::new("Lee Park", "CFO", "lee@northwind.example").expect("signer");
+        service.start(other).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_customer_facing_quotes_can_be_signed() -> TestResult<()> {
+        let pool = setup("draft").await?;
+        let result = QuoteSignatureService::new(pool).start(start()).await;
+        assert!(matches!(result, Err(SignatureServiceError::NotSignable { .. })));
+        Ok(())
+    }
+}
@@ -0,0 +1,722 @@
+//! Explain Any Number service shared by the MCP, Slack and portal surfaces.
+//!
+//! Wraps [`ExplanationEngine`] with the persisted pricing snapshots and policy evaluations
+//! from [`SqlPricingSnapshotRepository`], records every request with its evidence and audit
+//! trail, and caches responses per pricing snapshot so repeated questions are served without
+//! re-deriving the arithmetic.
+
+use std::time::Instant;
+
+use quotey_core::chrono::{Duration, Utc};
+use quotey_core::domain::explanation::{
+    CreateExplanationRequest, EvidenceType, ExplanationCache, ExplanationEventType,
+    ExplanationRequestId, ExplanationRequestType, ExplanationResponse, ExplanationStatus,
+    LineItemEvidence,
+};
+use quotey_core::domain::quote::{QuoteId, QuoteLineId};
+use quotey_core::{
+    DeterministicExecutionEngine, ExplanationEngine, ExplanationError, PricingSnapshotProvider,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::repositories::{
+    ExplanationRepository, RepositoryError, SqlExplanationRepository, SqlPricingSnapshotRepository,
+};
+use crate::DbPool;
+
+/// How long a cached explanation stays valid for an unchanged pricing snapshot.
+const CACHE_TTL_HOURS: i64 = 24;
+
+/// What number the caller wants explained.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExplainTarget {
+    Total,
+    Line(String),
+    Policy,
+}
+
+impl ExplainTarget {
+    /// Parses a target kind (`total`, `line`, `policy`) plus optional line id.
+    ///
+    /// A missing kind defaults to `line` when a line id is given and `total` otherwise.
+    pub fn parse(kind: Option<&str>, line_id: Option<&str>) -> Option<Self> {
+        let line_id = line_id.map(str::trim).filter(|id| !id.is_empty());
+        match kind.map(|k| k.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") => Some(line_id.map_or(Self::Total, |id| Self::Line(id.to_string()))),
+            Some("total") => Some(Self::Total),
+            Some("policy") => Some(Self::Policy),
+            Some("line") => line_id.map(|id| Self::Line(id.to_string())),
+            Some(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.request_type().as_str()
+    }
+
+    pub fn line_id(&self) -> Option<&str> {
+        match self {
+            Self::Line(line_id) => Some(line_id),
+            _ => None,
+        }
+    }
+
+    fn request_type(&self) -> ExplanationRequestType {
+        match self {
+            Self::Total => ExplanationRequestType::Total,
+            Self::Line(_) => ExplanationRequestType::Line,
+            Self::Policy => ExplanationRequestType::Policy,
+        }
+    }
+}
+
+/// A single explanation request from one of the user-facing surfaces.
+#[derive(Clone, Debug)]
+pub struct ExplainQuery {
+    pub quote_id: QuoteId,
+    pub target: ExplainTarget,
+    /// Quote version to explain; defaults to the quote's current version.
+    pub version: Option<i32>,
+    /// Audit actor type: `user`, `system` or `agent`.
+    pub actor_type: String,
+    pub actor_id: String,
+    /// Conversation the question came from (Slack thread, MCP session, portal token).
+    pub thread_id: String,
+    pub correlation_id: String,
+}
+
+/// Evidence row backing an explanation, with a stable reference to its source artifact.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceLink {
+    pub evidence_type: String,
+    pub key: String,
+    pub source_reference: String,
+    pub payload: serde_json::Value,
+}
+
+/// Explanation returned to callers, tied to the persisted request row.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Explanation {
+    pub request_id: String,
+    pub quote_id: String,
+    pub quote_version: i32,
+    pub pricing_snapshot_id: Option<String>,
+    pub target: &'static str,
+    pub line_id: Option<String>,
+    pub cached: bool,
+    pub response: ExplanationResponse,
+    pub evidence: Vec<EvidenceLink>,
+}
+
+#[derive(Debug, Error)]
+pub enum ExplainError {
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("line `{line_id}` not found on quote `{quote_id}`")]
+    LineNotFound { quote_id: String, line_id: String },
+    #[error("no pricing evidence recorded for this quote version: {0}")]
+    MissingEvidence(String),
+    #[error("explanation failed: {0}")]
+    Failed(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl ExplainError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::QuoteNotFound(_) => "quote_not_found",
+            Self::LineNotFound { .. } => "line_not_found",
+            Self::MissingEvidence(_) => "missing_evidence",
+            Self::Failed(_) => "explanation_failed",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+/// Cached payload stored in `explanation_response_cache.evidence_refs_json`.
+#[derive(Serialize, Deserialize)]
+struct CachedExplanation {
+    response: ExplanationResponse,
+    evidence: Vec<EvidenceLink>,
+}
+
+pub struct ExplainService {
+    pool: DbPool,
+    requests: SqlExplanationRepository,
+}
+
+impl ExplainService {
+    pub fn new(pool: DbPool) -> Self {
+        let requests = SqlExplanationRepository::new(pool.clone());
+        Self { pool, requests }
+    }
+
+    /// Explains a total, line or policy verdict, recording the request, evidence and audit
+    /// trail. Served from cache when the same snapshot was explained recently.
+    pub async fn explain(&self, query: ExplainQuery) -> Result<Explanation, ExplainError> {
+        let started = Instant::now();
+        let version = match query.version {
+            Some(version) => version,
+            None => self.current_version(&query.quote_id).await?,
+        };
+        let snapshots = SqlPricingSnapshotRepository::new(self.pool.clone());
+
+        let request = self
+            .requests
+            .create_request(CreateExplanationRequest {
+                quote_id: query.quote_id.clone(),
+                line_id: query.target.line_id().map(|id| QuoteLineId(id.to_string())),
+                request_type: query.target.request_type(),
+                thread_id: query.thread_id.clone(),
+                actor_id: query.actor_id.clone(),
+                correlation_id: query.correlation_id.clone(),
+                quote_version: version,
+            })
+            .await?;
+        self.audit(
+            &request.id,
+            ExplanationEventType::RequestReceived,
+            json!({
+                "target": query.target.as_str(),
+                "line_id": query.target.line_id(),
+                "quote_version": version,
+            }),
+            &query,
+        )
+        .await?;
+
+        let snapshot_id = snapshots.snapshot_id(&query.quote_id, version).await?;
+        let cache_key = snapshot_id.as_deref().map(|snapshot_id| {
+            explanation_cache_key(&query.quote_id, &query.target, version, snapshot_id)
+        });
+
+        if let Some(cache_key) = cache_key.as_deref() {
+            if let Some(entry) = self.requests.get_cached_response(cache_key, Utc::now()).await? {
+                if let Ok(cached) =
+                    serde_json::from_str::<CachedExplanation>(&entry.evidence_refs_json)
+                {
+                    return self
+                        .deliver(
+                            &query,
+                            &request.id,
+                            version,
+                            Some(entry.pricing_snapshot_id),
+                            cached.response,
+                            cached.evidence,
+                            true,
+                            started,
+                        )
+                        .await;
+                }
+            }
+        }
+
+        let engine = ExplanationEngine::new(
+            SqlPricingSnapshotRepository::new(self.pool.clone()),
+            SqlPricingSnapshotRepository::new(self.pool.clone()),
+        );
+        let outcome = match &query.target {
+            ExplainTarget::Total => engine.explain_total(&query.quote_id, version).await,
+            ExplainTarget::Line(line_id) => {
+                engine.explain_line(&query.quote_id, &QuoteLineId(line_id.clone()), version).await
+            }
+            ExplainTarget::Policy => engine.explain_policy(&query.quote_id, version).await,
+        };
+
+        let response = match outcome {
+            Ok(response) => response,
+            Err(error) => return Err(self.fail(&query, &request.id, error, started).await?),
+        };
+
+        // The fallback path may have persisted a snapshot while the engine ran.
+        let snapshot_id = match snapshot_id {
+            Some(snapshot_id) => Some(snapshot_id),
+            None => snapshots.snapshot_id(&query.quote_id, version).await?,
+        };
+        let evidence = self
+            .collect_evidence(&snapshots, &query, version, snapshot_id.as_deref(), &response)
+            .await;
+        self.audit(
+            &request.id,
+            ExplanationEventType::EvidenceGathered,
+            json!({ "evidence_count": evidence.len() }),
+            &query,
+        )
+        .await?;
+        self.audit(
+            &request.id,
+            ExplanationEventType::ExplanationGenerated,
+            json!({ "summary": response.user_summary }),
+            &query,
+        )
+        .await?;
+
+        if let Some(snapshot_id) = snapshot_id.as_deref() {
+            let now = Utc::now();
+            let payload =
+                CachedExplanation { response: response.clone(), evidence: evidence.clone() };
+            let payload = serde_json::to_string(&payload)
+                .map_err(|error| ExplainError::Failed(format!("encode cache entry: {error}")))?;
+            self.requests
+                .put_cached_response(ExplanationCache {
+                    id: format!("exp-cache-{}", sqlx::types::Uuid::new_v4()),
+                    cache_key: explanation_cache_key(
+                        &query.quote_id,
+                        &query.target,
+                        version,
+                        snapshot_id,
+                    ),
+                    quote_id: query.quote_id.clone(),
+                    line_id: query.target.line_id().map(|id| QuoteLineId(id.to_string())),
+                    quote_version: version,
+                    pricing_snapshot_id: snapshot_id.to_string(),
+                    explanation_summary: response.user_summary.clone(),
+                    evidence_refs_json: payload,
+                    hit_count: 0,
+                    last_hit_at: None,
+                    created_at: now,
+                    expires_at: now + Duration::hours(CACHE_TTL_HOURS),
+                })
+                .await?;
+        }
+
+        self.deliver(&query, &request.id, version, snapshot_id, response, evidence, false, started)
+            .await
+    }
+
+    async fn current_version(&self, quote_id: &QuoteId) -> Result<i32, ExplainError> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT version FROM quote WHERE id = ?")
+            .bind(&quote_id.0)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(RepositoryError::from)?;
+        let version = version.ok_or_else(|| ExplainError::QuoteNotFound(quote_id.0.clone()))?;
+        i32::try_from(version)
+            .map_err(|_| ExplainError::Failed(format!("quote version `{version}` out of range")))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn deliver(
+        &self,
+        query: &ExplainQuery,
+        request_id: &ExplanationRequestId,
+        version: i32,
+        snapshot_id: Option<String>,
+        mut response: ExplanationResponse,
+        evidence: Vec<EvidenceLink>,
+        cached: bool,
+        started: Instant,
+    ) -> Result<Explanation, ExplainError> {
+        response.request_id = request_id.clone();
+        for (order, link) in evidence.iter().enumerate() {
+            self.requests
+                .add_evidence(
+                    request_id,
+                    EvidenceType::parse(&link.evidence_type).unwrap_or(EvidenceType::PricingTrace),
+                    link.key.clone(),
+                    link.payload.to_string(),
+                    link.source_reference.clone(),
+                    order as i32,
+                )
+                .await?;
+        }
+        if let Some(snapshot_id) = snapshot_id.as_deref() {
+            self.requests.attach_pricing_snapshot(request_id, snapshot_id).await?;
+        }
+        self.requests
+            .update_request_status(
+                request_id,
+                ExplanationStatus::Success,
+                None,
+                None,
+                Some(elapsed_ms(started)),
+            )
+            .await?;
+        self.audit(
+            request_id,
+            ExplanationEventType::ExplanationDelivered,
+            json!({ "cache_hit": cached, "evidence_count": evidence.len() }),
+            query,
+        )
+        .await?;
+
+        Ok(Explanation {
+            request_id: request_id.0.clone(),
+            quote_id: query.quote_id.0.clone(),
+            quote_version: version,
+            pricing_snapshot_id: snapshot_id,
+            target: query.target.as_str(),
+            line_id: query.target.line_id().map(str::to_string),
+            cached,
+            response,
+            evidence,
+        })
+    }
+
+    async fn fail(
+        &self,
+        query: &ExplainQuery,
+        request_id: &ExplanationRequestId,
+        error: ExplanationError,
+        started: Instant,
+    ) -> Result<ExplainError, ExplainError> {
+        let (status, event, mapped) = match &error {
+            ExplanationError::MissingPricingSnapshot { .. }
+            | ExplanationError::MissingPolicyEvaluation { .. }
+            | ExplanationError::VersionMismatch { .. } => (
+                ExplanationStatus::MissingEvidence,
+                ExplanationEventType::EvidenceMissing,
+                ExplainError::MissingEvidence(error.to_string()),
+            ),
+            ExplanationError::QuoteNotFound { quote_id } => (
+                ExplanationStatus::Error,
+                ExplanationEventType::ErrorOccurred,
+                ExplainError::QuoteNotFound(quote_id.0.clone()),
+            ),
+            ExplanationError::InvalidLineId { quote_id, line_id } => (
+                ExplanationStatus::Error,
+                ExplanationEventType::ErrorOccurred,
+                ExplainError::LineNotFound {
+                    quote_id: quote_id.0.clone(),
+                    line_id: line_id.0.clone(),
+                },
+            ),
+            ExplanationError::EvidenceGatheringFailed { .. } => (
+                ExplanationStatus::Error,
+                ExplanationEventType::ErrorOccurred,
+                ExplainError::Failed(error.to_string()),
+            ),
+        };
+
+        self.requests
+            .update_request_status(
+                request_id,
+                status,
+                Some(mapped.code().to_string()),
+                Some(error.to_string()),
+                Some(elapsed_ms(started)),
+            )
+            .await?;
+        self.audit(
+            request_id,
+            event,
+            json!({ "error_code": mapped.code(), "message": error.to_string() }),
+            query,
+        )
+        .await?;
+        Ok(mapped)
+    }
+
+    async fn collect_evidence(
+        &self,
+        snapshots: &SqlPricingSnapshotRepository,
+        query: &ExplainQuery,
+        version: i32,
+        snapshot_id: Option<&str>,
+        response: &ExplanationResponse,
+    ) -> Vec<EvidenceLink> {
+        let source = snapshot_id.map_or_else(
+            || format!("quote:{}:v{version}", query.quote_id.0),
+            |snapshot_id| format!("quote_pricing_snapshot:{snapshot_id}"),
+        );
+        let mut evidence = Vec::new();
+
+        if let ExplainTarget::Line(line_id) = &query.target {
+            let line =
+                snapshots.get_snapshot(&query.quote_id, version).await.ok().and_then(|snapshot| {
+                    snapshot.line_items.into_iter().find(|line| &line.line_id == line_id)
+                });
+            if let Some(line) = line {
+                let payload = LineItemEvidence {
+                    line_id: line.line_id.clone(),
+                    product_id: line.product_id,
+                    product_name: line.product_name,
+                    quantity: line.quantity,
+                    unit_price: line.unit_price,
+                    discount_percent: line.discount_percent,
+                    subtotal: line.line_subtotal,
+                };
+                evidence.push(EvidenceLink {
+                    evidence_type: EvidenceType::LineItem.as_str().to_string(),
+                    key: format!("line:{}", line.line_id),
+                    source_reference: format!("{source}:line:{}", line.line_id),
+                    payload: serde_json::to_value(payload).unwrap_or_default(),
+                });
+            }
+        }
+
+        for step in &response.arithmetic_chain {
+            evidence.push(EvidenceLink {
+                evidence_type: EvidenceType::PricingTrace.as_str().to_string(),
+                key: format!("step:{}:{}", step.step_order, step.operation),
+                source_reference: format!("{source}:step_{}", step.step_order),
+                payload: serde_json::to_value(step).unwrap_or_default(),
+            });
+        }
+
+        let policy_source = snapshot_id.map_or_else(
+            || format!("policy_evaluation:{}:v{version}", query.quote_id.0),
+            |snapshot_id| format!("policy_evaluation:{snapshot_id}"),
+        );
+        for policy in &response.policy_evidence {
+            evidence.push(EvidenceLink {
+                evidence_type: EvidenceType::PolicyEvaluation.as_str().to_string(),
+                key: format!("policy:{}", policy.policy_id),
+                source_reference: format!("{policy_source}:{}", policy.policy_id),
+                payload: serde_json::to_value(policy).unwrap_or_default(),
+            });
+        }
+
+        evidence
+    }
+
+    async fn audit(
+        &self,
+        request_id: &ExplanationRequestId,
+        event_type: ExplanationEventType,
+        payload: serde_json::Value,
+        query: &ExplainQuery,
+    ) -> Result<(), ExplainError> {
+        self.requests
+            .append_audit_event(
+                request_id,
+                event_type,
+                payload.to_string(),
+                query.actor_type.clone(),
+                query.actor_id.clone(),
+                query.correlation_id.clone(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn explanation_cache_key(
+    quote_id: &QuoteId,
+    target: &ExplainTarget,
+    version: i32,
+    snapshot_id: &str,
+) -> String {
+    DeterministicExecutionEngine::hash_payload(&format!(
+        "{}|{}|{}|{}|{}",
+        quote_id.0,
+        target.as_str(),
+        target.line_id().unwrap_or(""),
+        version,
+        snapshot_id
+    ))
+}
+
+fn elapsed_ms(started: Instant) -> i32 {
+    i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::cpq::policy::{
+        evaluate_policy_with_thresholds, PolicyInput, PolicyThresholds,
+    };
+    use quotey_core::domain::explanation::{
+        ExplanationEventType, ExplanationRequestId, ExplanationStatus,
+    };
+    use quotey_core::domain::quote::QuoteId;
+    use quotey_core::PricingLineSnapshot;
+    use quotey_core::{policy_evaluation_from_decision, pricing_snapshot_from_lines};
+    use rust_decimal::Decimal;
+
+    use super::{ExplainError, ExplainQuery, ExplainService, ExplainTarget};
+    use crate::repositories::{
+        ExplanationRepository, SqlExplanationRepository, SqlPricingSnapshotRepository,
+    };
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    type TestResult<T> = Result<T, String>;
+
+    #[test]
+    fn explain_target_parses_kind_and_line_id() {
+        assert_eq!(ExplainTarget::parse(None, None), Some(ExplainTarget::Total));
+        assert_eq!(
+            ExplainTarget::parse(None, Some("Q-1-ql-1")),
+            Some(ExplainTarget::Line("Q-1-ql-1".to_string()))
+        );
+        assert_eq!(ExplainTarget::parse(Some("Policy"), None), Some(ExplainTarget::Policy));
+        assert_eq!(ExplainTarget::parse(Some("line"), None), None);
+        assert_eq!(ExplainTarget::parse(Some("margin"), None), None);
+    }
+
+    #[tokio::test]
+    async fn explain_persists_evidence_and_serves_repeat_requests_from_cache() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = QuoteId("Q-EXPLAIN-1".to_string());
+        insert_quote(&pool, &quote_id).await?;
+        record_priced_quote(&pool, &quote_id).await?;
+        let service = ExplainService::new(pool.clone());
+
+        let first = service
+            .explain(query(&quote_id, ExplainTarget::Total))
+            .await
+            .map_err(|error| format!("explain total: {error}"))?;
+        assert!(!first.cached);
+        assert_eq!(first.response.amount, Decimal::new(1800, 0));
+        assert_eq!(first.response.request_id.0, first.request_id);
+        assert!(first.pricing_snapshot_id.is_some());
+        assert!(first.evidence.iter().any(|link| link.source_reference.ends_with(":step_1")
+            && link.evidence_type == "pricing_trace"));
+        assert!(first.evidence.iter().any(|link| link.evidence_type == "policy_evaluation"));
+
+        let second = service
+            .explain(query(&quote_id, ExplainTarget::Total))
+            .await
+            .map_err(|error| format!("explain total again: {error}"))?;
+        assert!(second.cached);
+        assert_ne!(second.request_id, first.request_id);
+        assert_eq!(second.response.user_summary, first.response.user_summary);
+
+        let repo = SqlExplanationRepository::new(pool.clone());
+        let requests = repo
+            .list_requests_for_quote(&quote_id, 10)
+            .await
+            .map_err(|error| format!("list requests: {error}"))?;
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.status == ExplanationStatus::Success
+            && request.pricing_snapshot_id == first.pricing_snapshot_id));
+
+        let stored = repo
+            .get_evidence_for_request(&ExplanationRequestId(second.request_id.clone()))
+            .await
+            .map_err(|error| format!("get evidence: {error}"))?;
+        assert_eq!(stored.len(), second.evidence.len());
+
+        let line = service
+            .explain(query(&quote_id, ExplainTarget::Line("Q-EXPLAIN-1-ql-1".to_string())))
+            .await
+            .map_err(|error| format!("explain line: {error}"))?;
+        assert_eq!(line.response.amount, Decimal::new(1800, 0));
+        assert_eq!(line.evidence[0].evidence_type, "line_item");
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn explain_records_missing_evidence_and_unknown_lines() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = QuoteId("Q-EXPLAIN-2".to_string());
+        insert_quote(&pool, &quote_id).await?;
+        let service = ExplainService::new(pool.clone());
+
+        let missing = service.explain(query(&quote_id, ExplainTarget::Policy)).await;
+        assert!(matches!(missing, Err(ExplainError::MissingEvidence(_))));
+
+        record_priced_quote(&pool, &quote_id).await?;
+        let unknown =
+            service.explain(query(&quote_id, ExplainTarget::Line("nope".to_string()))).await;
+        assert!(matches!(unknown, Err(ExplainError::LineNotFound { .. })));
+
+        let quote =
+            service.explain(query(&QuoteId("Q-MISSING".to_string()), ExplainTarget::Total)).await;
+        assert!(matches!(quote, Err(ExplainError::QuoteNotFound(_))));
+
+        let repo = SqlExplanationRepository::new(pool.clone());
+        let requests = repo
+            .list_requests_for_quote(&quote_id, 10)
+            .await
+            .map_err(|error| format!("list requests: {error}"))?;
+        let missing_request = requests
+            .iter()
+            .find(|request| request.status == ExplanationStatus::MissingEvidence)
+            .ok_or_else(|| "missing-evidence request should be recorded".to_string())?;
+        let audit = repo
+            .get_audit_trail(&missing_request.id)
+            .await
+            .map_err(|error| format!("audit trail: {error}"))?;
+        assert_eq!(
+            audit.last().map(|event| event.event_type.clone()),
+            Some(ExplanationEventType::EvidenceMissing)
+        );
+        assert!(requests.iter().any(|request| request.status == ExplanationStatus::Error
+            && request.error_code.as_deref() == Some("line_not_found")));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    fn query(quote_id: &QuoteId, target: ExplainTarget) -> ExplainQuery {
+        ExplainQuery {
+            quote_id: quote_id.clone(),
+            target,
+            version: None,
+            actor_type: "user".to_string(),
+            actor_id: "U-EXPLAIN".to_string(),
+            thread_id: "T-EXPLAIN".to_string(),
+            correlation_id: "corr-explain".to_string(),
+        }
+    }
+
+    async fn record_priced_quote(pool: &DbPool, quote_id: &QuoteId) -> TestResult<()> {
+        let line = PricingLineSnapshot {
+            line_id: format!("{}-ql-1", quote_id.0),
+            product_id: "plan-pro".to_string(),
+            product_name: "Pro Plan".to_string(),
+            quantity: 20,
+            unit_price: Decimal::new(100, 0),
+            discount_percent: Decimal::new(10, 0),
+            discount_amount: Decimal::new(200, 0),
+            line_subtotal: Decimal::new(1800, 0),
+        };
+        let snapshot = pricing_snapshot_from_lines(
+            quote_id,
+            1,
+            "USD",
+            vec![line],
+            Decimal::ZERO,
+            "2026-02-24T00:00:00Z".to_string(),
+        );
+        let input = PolicyInput {
+            requested_discount_pct: Decimal::new(10, 0),
+            deal_value: Decimal::new(1800, 0),
+            minimum_margin_pct: Some(Decimal::new(40, 0)),
+        };
+        let thresholds = PolicyThresholds::default();
+        let decision = evaluate_policy_with_thresholds(&input, &thresholds);
+        let policy = policy_evaluation_from_decision(
+            quote_id,
+            1,
+            &input,
+            &thresholds,
+            &decision,
+            "2026-02-24T00:00:00Z".to_string(),
+        );
+        SqlPricingSnapshotRepository::new(pool.clone())
+            .record_snapshot(&snapshot, Some(&policy))
+            .await
+            .map_err(|error| format!("record snapshot: {error}"))?;
+        Ok(())
+    }
+
+    async fn setup_pool() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 30)
+            .await
+            .map_err(|error| format!("connect test pool: {error}"))?;
+        migrations::run_pending(&pool).await.map_err(|error| format!("run migrations: {error}"))?;
+        Ok(pool)
+    }
+
+    async fn insert_quote(pool: &DbPool, quote_id: &QuoteId) -> TestResult<()> {
+        let timestamp = "2026-02-24T00:00:00Z";
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
+             VALUES (?, 'draft', 'USD', 'U-EXP', ?, ?)",
+        )
+        .bind(&quote_id.0)
+        .bind(timestamp)
+        .bind(timestamp)
+        .execute(pool)
+        .await
+        .map_err(|error| format!("insert quote fixture {}: {error}", quote_id.0))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,620 @@
+//! Ghost quote pipeline: buying signals observed in Slack become persisted signals and, when the
+//! company has quote history, auto-drafted quotes that reps accept, dismiss or convert.
+//!
+//! Rep responses are counted back into [`GhostFeedback`] so the detector threshold rises when
+//! reps mostly dismiss drafts and relaxes when they mostly keep them.
+
+use std::collections::HashMap;
+
+use quotey_core::audit::{
+    ActorType, AuditAction, AuditCategory, AuditEvent, AuditOutcome, EntityType,
+};
+use quotey_core::chrono::Utc;
+use quotey_core::domain::quote::{Quote, QuoteId, QuoteStatus};
+use quotey_core::ghost::{
+    CustomerHistoryProvider, GhostFeedback, GhostQuoteGenerator, InMemoryGhostQuoteStore,
+    SignalDetector, SignalDetectorConfig,
+};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::{
+    BuyingSignalRecord, GhostQuoteRecord, QuoteRepository, RepositoryError,
+    SqlAuditEventRepository, SqlGhostQuoteRepository, SqlQuoteRepository,
+};
+use crate::DbPool;
+
+/// Actor recorded on ghost drafts until a rep converts them.
+pub const GHOST_ACTOR: &str = "ghost-engine";
+/// Most recent quotes per company handed to the generator.
+const HISTORY_LIMIT: i64 = 25;
+
+/// Slack message seen by the pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObservedMessage {
+    pub channel_id: String,
+    /// Slack `ts` of the message; together with the channel it dedupes redeliveries.
+    pub message_id: String,
+    pub author_id: String,
+    pub text: String,
+}
+
+/// Ghost draft produced from a signal, ready to show a rep.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GhostDraft {
+    pub ghost_id: String,
+    pub signal_id: String,
+    pub quote_id: String,
+    pub company: String,
+    pub signal_type: String,
+    pub intent: String,
+    pub confidence: u8,
+    pub suggested_discount_pct: u8,
+    pub similar_quote_id: Option<String>,
+    pub currency: String,
+    pub total: Decimal,
+    pub line_count: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostAction {
+    /// Keep the draft for follow-up; counts as positive feedback.
+    Accept,
+    /// Discard the draft; counts as negative feedback and cancels it.
+    Dismiss,
+    /// Copy the draft into a rep-owned quote and retire the ghost draft.
+    Convert,
+}
+
+impl GhostAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Dismiss => "dismiss",
+            Self::Convert => "convert",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "accept" | "accepted" => Some(Self::Accept),
+            "dismiss" | "dismissed" => Some(Self::Dismiss),
+            "convert" | "converted" => Some(Self::Convert),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a rep action on a ghost draft.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GhostResolution {
+    pub ghost_id: String,
+    pub action: GhostAction,
+    pub draft_quote_id: Option<String>,
+    /// Rep-owned quote created by [`GhostAction::Convert`].
+    pub quote_id: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum GhostError {
+    #[error("ghost quote `{0}` not found")]
+    NotFound(String),
+    #[error("ghost quote `{ghost_id}` was already {response}")]
+    AlreadyResolved { ghost_id: String, response: String },
+    #[error("ghost quote generation failed: {0}")]
+    Generation(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+/// Company history loaded from persisted quotes, matched on a normalized `account_id`.
+///
+/// [`CustomerHistoryProvider`] is synchronous, so history is loaded up front for the
+/// companies a signal names.
+#[derive(Clone, Debug, Default)]
+pub struct SqlCustomerHistoryProvider {
+    histories: HashMap<String, Vec<Quote>>,
+}
+
+impl SqlCustomerHistoryProvider {
+    pub async fn load(pool: &DbPool, companies: &[String]) -> Result<Self, RepositoryError> {
+        let repo = SqlQuoteRepository::new(pool.clone());
+        let mut histories = HashMap::new();
+        for company in companies {
+            let keys = account_keys(company);
+            let Some(full) = keys.first() else {
+                continue;
+            };
+            let stem = keys.get(1).unwrap_or(full);
+            let ids: Vec<String> = sqlx::query_scalar(
+                "SELECT id FROM (
+                    SELECT id, created_at FROM quote
+                    WHERE account_id IS NOT NULL
+                      AND created_by != ?
+                      AND status != 'cancelled'
+                      AND lower(replace(replace(replace(replace(account_id, ' ', ''), '-', ''), '_', ''), '.', ''))
+                          IN (?, ?)
+                    ORDER BY created_at DESC
+                    LIMIT ?
+                 ) ORDER BY created_at ASC",
+            )
+            .bind(GHOST_ACTOR)
+            .bind(full)
+            .bind(stem)
+            .bind(HISTORY_LIMIT)
+            .fetch_all(pool)
+            .await?;
+
+            let mut quotes = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(quote) = repo.find_by_id(&QuoteId(id)).await? {
+                    quotes.push(quote);
+                }
+            }
+            if !quotes.is_empty() {
+                histories.insert(company.clone(), quotes);
+            }
+        }
+        Ok(Self { histories })
+    }
+}
+
+impl CustomerHistoryProvider for SqlCustomerHistoryProvider {
+    fn history_for_company(&self, company: &str) -> Option<Vec<Quote>> {
+        self.histories.get(company).cloned()
+    }
+}
+
+pub struct GhostPipeline {
+    pool: DbPool,
+    repo: SqlGhostQuoteRepository,
+    config: SignalDetectorConfig,
+}
+
+impl GhostPipeline {
+    pub fn new(pool: DbPool) -> Self {
+        Self::with_config(pool, SignalDetectorConfig::default())
+    }
+
+    pub fn with_config(pool: DbPool, config: SignalDetectorConfig) -> Self {
+        Self { repo: SqlGhostQuoteRepository::new(pool.clone()), pool, config }
+    }
+
+    /// Detector tuned by the rep feedback recorded so far.
+    pub async fn detector(&self) -> Result<SignalDetector, GhostError> {
+        let feedback = self.repo.feedback().await?;
+        Ok(SignalDetector::new(self.config.calibrated(&feedback)))
+    }
+
+    pub async fn feedback(&self) -> Result<GhostFeedback, GhostError> {
+        Ok(self.repo.feedback().await?)
+    }
+
+    /// Runs a message through the detector, persisting any signal and ghost draft.
+    ///
+    /// Returns `None` for messages below threshold, redelivered messages, and signals whose
+    /// company has no quote history to draft from.
+    pub async fn observe(
+        &self,
+        message: &ObservedMessage,
+    ) -> Result<Option<GhostDraft>, GhostError> {
+        let detector = self.detector().await?;
+        let Some(signal) = detector.detect(&message.text) else {
+            return Ok(None);
+        };
+        if self
+            .repo
+            .find_signal_by_message(&message.channel_id, &message.message_id)
+            .await?
+            .is_some()
+        {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let record = BuyingSignalRecord {
+            id: format!("SIG-{}", short_id()),
+            slack_channel_id: message.channel_id.clone(),
+            slack_message_id: message.message_id.clone(),
+            signal_type: signal.signal_type().to_string(),
+            confidence_score: f64::from(signal.confidence) / 100.0,
+            detected_company: signal.companies.first().cloned(),
+            extracted_intent: signal.intent_summary(),
+            matched_rep_id: Some(message.author_id.clone()).filter(|id| !id.trim().is_empty()),
+            status: "new".to_string(),
+            created_at: now.to_rfc3339(),
+        };
+        self.repo.insert_signal(&record).await?;
+
+        let history = SqlCustomerHistoryProvider::load(&self.pool, &signal.companies).await?;
+        let mut store = InMemoryGhostQuoteStore::default();
+        let quote_id = format!("Q-GHOST-{}", short_id());
+        let generated = GhostQuoteGenerator::new(detector.config().confidence_threshold)
+            .generate(&quote_id, &signal, &history, &mut store)
+            .map_err(GhostError::Generation)?;
+        let Some(ghost) = generated else {
+            return Ok(None);
+        };
+
+        let mut draft = ghost.draft_quote.clone();
+        draft.created_by = GHOST_ACTOR.to_string();
+        SqlQuoteRepository::new(self.pool.clone()).save(draft.clone()).await?;
+
+        let ghost_record = GhostQuoteRecord {
+            id: format!("GQ-{}", short_id()),
+            signal_id: record.id.clone(),
+            draft_quote_id: Some(draft.id.0.clone()),
+            confidence_score: f64::from(ghost.confidence) / 100.0,
+            rep_notified_at: None,
+            rep_response: None,
+            created_at: now.to_rfc3339(),
+        };
+        self.repo.insert_ghost_quote(&ghost_record).await?;
+        self.repo.set_signal_status(&record.id, "processed").await?;
+
+        let event = AuditEvent::new(
+            Some(draft.id.clone()),
+            Some(message.channel_id.clone()),
+            format!("ghost-{}", ghost_record.id),
+            "ghost_quote.drafted",
+            AuditCategory::Flow,
+            GHOST_ACTOR,
+            AuditOutcome::Success,
+        )
+        .with_actor_type(ActorType::System)
+        .with_entity(EntityType::Quote, draft.id.0.clone())
+        .with_action(AuditAction::Created)
+        .with_metadata("ghost_quote_id", ghost_record.id.clone())
+        .with_metadata("buying_signal_id", record.id.clone())
+        .with_metadata("company", ghost.company.clone());
+        SqlAuditEventRepository::new(self.pool.clone()).save(&event).await?;
+
+        Ok(Some(GhostDraft {
+            ghost_id: ghost_record.id,
+            signal_id: record.id,
+            quote_id: draft.id.0.clone(),
+            company: ghost.company,
+            signal_type: record.signal_type,
+            intent: record.extracted_intent,
+            confidence: ghost.confidence,
+            suggested_discount_pct: ghost.suggested_discount_pct,
+            similar_quote_id: ghost.similar_quote_id,
+            currency: draft.currency.clone(),
+            total: draft
+                .lines
+                .iter()
+                .map(|line| line.unit_price * Decimal::from(line.quantity))
+                .sum(),
+            line_count: draft.lines.len(),
+        }))
+    }
+
+    /// Drafts awaiting a rep response, newest first.
+    pub async fn pending(&self, limit: u32) -> Result<Vec<GhostQuoteRecord>, GhostError> {
+        Ok(self.repo.list_pending(limit).await?)
+    }
+
+    pub async fn mark_notified(&self, ghost_id: &str) -> Result<(), GhostError> {
+        Ok(self.repo.mark_notified(ghost_id).await?)
+    }
+
+    /// Applies a rep action. Accepted drafts may still be converted; everything else is final.
+    pub async fn resolve(
+        &self,
+        ghost_id: &str,
+        action: GhostAction,
+        rep_id: &str,
+    ) -> Result<GhostResolution, GhostError> {
+        let ghost = self
+            .repo
+            .find_ghost_quote(ghost_id)
+            .await?
+            .ok_or_else(|| GhostError::NotFound(ghost_id.to_string()))?;
+        let draft = match &ghost.draft_quote_id {
+            Some(id) => {
+                SqlQuoteRepository::new(self.pool.clone()).find_by_id(&QuoteId(id.clone())).await?
+            }
+            None => None,
+        };
+        if let Some(response) = ghost.rep_response.as_deref() {
+            let draft_open = draft.as_ref().is_some_and(|quote| quote.status == QuoteStatus::Draft);
+            if !(response == "accepted" && action == GhostAction::Convert && draft_open) {
+                let response =
+                    if response == "accepted" && !draft_open { "converted" } else { response };
+                return Err(GhostError::AlreadyResolved {
+                    ghost_id: ghost.id.clone(),
+                    response: response.to_string(),
+                });
+            }
+        }
+
+        let mut converted = None;
+        match action {
+            GhostAction::Accept => {
+                self.repo.record_response(&ghost.id, "accepted").await?;
+            }
+            GhostAction::Dismiss => {
+                self.repo.record_response(&ghost.id, "dismissed").await?;
+                self.repo.set_signal_status(&ghost.signal_id, "dismissed").await?;
+                if let Some(draft) = draft.clone() {
+                    self.retire_draft(draft).await?;
+                }
+            }
+            GhostAction::Convert => {
+                let draft = draft.clone().ok_or_else(|| {
+                    GhostError::Generation(format!(
+                        "ghost quote `{}` has no draft to convert",
+                        ghost.id
+                    ))
+                })?;
+                self.repo.record_response(&ghost.id, "accepted").await?;
+                let now = Utc::now();
+                let quote = Quote {
+                    id: QuoteId(format!("Q-{}", short_id())),
+                    version: 1,
+                    status: QuoteStatus::Draft,
+                    notes: Some(format!("Converted from ghost quote {}", ghost.id)),
+                    created_by: rep_id.to_string(),
+                    created_at: now,
+                    updated_at: now,
+                    ..draft.clone()
+                };
+                SqlQuoteRepository::new(self.pool.clone()).save(quote.clone()).await?;
+                self.retire_draft(draft).await?;
+                converted = Some(quote.id.0);
+            }
+        }
+
+        let audited_quote = converted.clone().or_else(|| ghost.draft_quote_id.clone()).map(QuoteId);
+        let mut event = AuditEvent::new(
+            audited_quote.clone(),
+            None,
+            format!("ghost-{}", ghost.id),
+            format!("ghost_quote.{}", action.as_str()),
+            AuditCategory::Flow,
+            rep_id,
+            AuditOutcome::Success,
+        )
+        .with_actor_type(ActorType::User)
+        .with_action(match action {
+            GhostAction::Convert => AuditAction::Created,
+            GhostAction::Accept | GhostAction::Dismiss => AuditAction::Updated,
+        })
+        .with_metadata("ghost_quote_id", ghost.id.clone())
+        .with_metadata("buying_signal_id", ghost.signal_id.clone());
+        if let Some(quote_id) = &audited_quote {
+            event = event.with_entity(EntityType::Quote, quote_id.0.clone());
+        }
+        if let Some(draft_id) = &ghost.draft_quote_id {
+            event = event.with_metadata("draft_quote_id", draft_id.clone());
+        }
+        SqlAuditEventRepository::new(self.pool.clone()).save(&event).await?;
+
+        Ok(GhostResolution {
+            ghost_id: ghost.id,
+            action,
+            draft_quote_id: ghost.draft_quote_id,
+            quote_id: converted,
+        })
+    }
+
+    async fn retire_draft(&self, mut draft: Quote) -> Result<(), GhostError> {
+        if draft.status == QuoteStatus::Cancelled {
+            return Ok(());
+        }
+        draft.status = QuoteStatus::Cancelled;
+        draft.version += 1;
+        draft.updated_at = Utc::now();
+        Ok(SqlQuoteRepository::new(self.pool.clone()).save(draft).await?)
+    }
+}
+
+/// Lower-cased alphanumeric forms of a company name: the full name, then the name without a
+/// legal suffix (`Acme Corp` -> `acmecorp`, `acme`).
+fn account_keys(company: &str) -> Vec<String> {
+    let normalize = |value: &str| {
+        value.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_ascii_lowercase()
+    };
+    let full = normalize(company);
+    if full.is_empty() {
+        return Vec::new();
+    }
+    let mut words = company.split_whitespace().collect::<Vec<_>>();
+    if words.len() > 1 {
+        words.pop();
+    }
+    let stem = normalize(&words.join(" "));
+    if stem.is_empty() || stem == full {
+        vec![full]
+    } else {
+        vec![full, stem]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use rust_decimal::Decimal;
+
+    use super::{account_keys, GhostAction, GhostError, GhostPipeline, ObservedMessage};
+    use crate::repositories::{
+        QuoteRepository, SqlAuditEventRepository, SqlGhostQuoteRepository, SqlQuoteRepository,
+    };
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    const SIGNAL_TEXT: &str =
+        "Acme Corp plans to expand operations next quarter and is evaluating Salesforce pricing.";
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn seed_history(pool: &DbPool, id: &str, account_id: &str, quantity: u32) {
+        let now = Utc::now();
+        SqlQuoteRepository::new(pool.clone())
+            .save(Quote {
+                id: QuoteId(id.to_string()),
+                version: 1,
+                status: QuoteStatus::Approved,
+                account_id: Some(account_id.to_string()),
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: Some(12),
+                start_date: None,
+                end_date: None,
+                valid_until: None,
+                notes: None,
+                created_by: "U-REP".to_string(),
+                lines: vec![QuoteLine {
+                    product_id: ProductId("plan-pro".to_string()),
+                    quantity,
+                    unit_price: Decimal::new(10_000, 2),
+                    discount_pct: 0.0,
+                    notes: None,
+                }],
+                created_at: now,
+                updated_at: now,
+            })
+            .await
+            .expect("seed history quote");
+    }
+
+    fn message(message_id: &str, text: &str) -> ObservedMessage {
+        ObservedMessage {
+            channel_id: "C-DEALS".to_string(),
+            message_id: message_id.to_string(),
+            author_id: "U-REP".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn account_keys_strip_legal_suffix() {
+        assert_eq!(account_keys("Acme Corp"), ["acmecorp", "acme"]);
+        assert_eq!(account_keys("Initech"), ["initech"]);
+        assert!(account_keys("  ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn observed_signal_drafts_ghost_quote_from_account_history() {
+        let pool = setup().await;
+        seed_history(&pool, "Q-HIST-1", "acme", 10).await;
+        seed_history(&pool, "Q-HIST-2", "acme-corp", 12).await;
+        let pipeline = GhostPipeline::new(pool.clone());
+
+        let draft = pipeline
+            .observe(&message("1700000000.0001", SIGNAL_TEXT))
+            .await
+            .expect("observe")
+            .expect("ghost draft");
+        assert_eq!(draft.company, "Acme Corp");
+        assert_eq!(draft.signal_type, "competitive_evaluation");
+        assert!(draft.suggested_discount_pct > 0);
+        assert_eq!(draft.line_count, 1);
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&QuoteId(draft.quote_id.clone()))
+            .await
+            .expect("load draft")
+            .expect("draft persisted");
+        assert_eq!(quote.created_by, "ghost-engine");
+        assert_eq!(quote.lines[0].quantity, 12, "most recent history quote is the template");
+        assert!(quote.lines[0].unit_price < Decimal::new(10_000, 2));
+
+        let repo = SqlGhostQuoteRepository::new(pool.clone());
+        let signal = repo.find_signal(&draft.signal_id).await.expect("signal").expect("persisted");
+        assert_eq!(signal.status, "processed");
+        assert_eq!(signal.matched_rep_id.as_deref(), Some("U-REP"));
+        assert_eq!(repo.list_pending(10).await.expect("pending").len(), 1);
+
+        let redelivered =
+            pipeline.observe(&message("1700000000.0001", SIGNAL_TEXT)).await.expect("observe");
+        assert!(redelivered.is_none());
+
+        let unknown = pipeline
+            .observe(&message(
+                "1700000000.0002",
+                "Globex Inc plans to expand operations next quarter and is evaluating Salesforce pricing.",
+            ))
+            .await
+            .expect("observe");
+        assert!(unknown.is_none(), "no history means no draft");
+        let unknown_signal = repo
+            .find_signal_by_message("C-DEALS", "1700000000.0002")
+            .await
+            .expect("signal")
+            .expect("signal is still recorded");
+        assert_eq!(unknown_signal.status, "new");
+        assert_eq!(unknown_signal.detected_company.as_deref(), Some("Globex Inc"));
+    }
+
+    #[tokio::test]
+    async fn rep_actions_convert_dismiss_and_feed_back_into_threshold() {
+        let pool = setup().await;
+        seed_history(&pool, "Q-HIST-1", "Acme Corp", 10).await;
+        let pipeline = GhostPipeline::new(pool.clone());
+        let quotes = SqlQuoteRepository::new(pool.clone());
+
+        let first = pipeline
+            .observe(&message("1.0", SIGNAL_TEXT))
+            .await
+            .expect("observe")
+            .expect("ghost draft");
+        let accepted =
+            pipeline.resolve(&first.ghost_id, GhostAction::Accept, "U-REP").await.expect("accept");
+        assert!(accepted.quote_id.is_none());
+
+        let converted = pipeline
+            .resolve(&first.ghost_id, GhostAction::Convert, "U-REP")
+            .await
+            .expect("convert after accept");
+        let quote_id = converted.quote_id.expect("converted quote");
+        let quote =
+            quotes.find_by_id(&QuoteId(quote_id.clone())).await.expect("load").expect("exists");
+        assert_eq!(quote.created_by, "U-REP");
+        assert_eq!(quote.status, QuoteStatus::Draft);
+        let draft = quotes
+            .find_by_id(&QuoteId(first.quote_id.clone()))
+            .await
+            .expect("load")
+            .expect("draft kept");
+        assert_eq!(draft.status, QuoteStatus::Cancelled);
+        assert!(matches!(
+            pipeline.resolve(&first.ghost_id, GhostAction::Convert, "U-REP").await,
+            Err(GhostError::AlreadyResolved { ref response, .. }) if response == "converted"
+        ));
+        let audit = SqlAuditEventRepository::new(pool.clone())
+            .find_by_quote_id(&QuoteId(quote_id.clone()))
+            .await
+            .expect("audit");
+        assert!(audit.iter().any(|event| event.event_type == "ghost_quote.convert"));
+
+        assert_eq!(pipeline.detector().await.expect("detector").config().confidence_threshold, 70);
+        for index in 0..5 {
+            let ghost = pipeline
+                .observe(&message(&format!("2.{index}"), SIGNAL_TEXT))
+                .await
+                .expect("observe")
+                .expect("ghost draft");
+            pipeline
+                .resolve(&ghost.ghost_id, GhostAction::Dismiss, "U-REP")
+                .await
+                .expect("dismiss");
+        }
+        assert!(matches!(
+            pipeline.resolve("GQ-missing", GhostAction::Accept, "U-REP").await,
+            Err(GhostError::NotFound(_))
+        ));
+        let feedback = pipeline.feedback().await.expect("feedback");
+        assert_eq!((feedback.accepted, feedback.dismissed), (1, 5));
+        let threshold = pipeline.detector().await.expect("detector").config().confidence_threshold;
+        assert!(threshold > 70, "dismissals should raise the threshold, got {threshold}");
+    }
+}
@@ -0,0 +1,6 @@
+//! Identifier helpers shared by the db services.
+
+/// Returns a random 12-character hex suffix for generated row ids such as `GHOST-<short_id>`.
+pub(crate) fn short_id() -> String {
+    sqlx::types::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
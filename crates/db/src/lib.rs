@@ -1,7 +1,25 @@
+pub mod analytics;
+pub mod catalog;
+pub mod collab;
 pub mod connection;
+pub mod esign;
+pub mod explain;
 pub mod fixtures;
+pub mod ghost;
+mod ids;
 pub mod migrations;
+pub mod negotiation;
+pub mod order_forms;
+pub mod policy_apply;
+pub mod policy_rules;
+pub mod quote_templates;
+pub mod replay;
 pub mod repositories;
+pub mod similarity;
+pub mod simulate;
+pub mod suggestions;
+pub mod webhooks;
+pub mod win_probability;
 
 pub use connection::{connect, connect_with_settings, DbPool};
 pub use fixtures::{E2ESeedDataset, FlowSeedInfo, SeedResult, VerificationResult};
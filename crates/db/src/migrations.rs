@@ -278,6 +278,75 @@ mod tests {
         "idx_audit_event_entity",
         "idx_audit_event_action",
         "idx_audit_event_actor_type",
+        // 0043 — api keys
+        "api_key",
+        "idx_api_key_name",
+        "idx_api_key_active",
+        // 0044 — deal similarity index
+        "deal_similarity_index",
+        "idx_deal_similarity_index_chunk_0",
+        "idx_deal_similarity_index_chunk_1",
+        "idx_deal_similarity_index_chunk_2",
+        "idx_deal_similarity_index_chunk_3",
+        "idx_deal_similarity_index_chunk_4",
+        "idx_deal_similarity_index_chunk_5",
+        "idx_deal_similarity_index_chunk_6",
+        "idx_deal_similarity_index_chunk_7",
+        "idx_deal_similarity_index_filters",
+        // 0045 — win probability registry
+        "idx_win_probability_models_active",
+        // 0046 — product co-occurrence
+        "product_cooccurrence_basket",
+        "idx_product_cooccurrence_basket_account",
+        "product_cooccurrence_item",
+        "product_cooccurrence_pair",
+        "idx_product_cooccurrence_pair_b",
+        // 0047 — quote signatures
+        "quote_signature_request",
+        "idx_quote_signature_request_quote",
+        // 0048 — CRM remote object links
+        "crm_remote_object",
+        "idx_crm_remote_object_quote",
+        "idx_crm_remote_object_remote_id",
+        // 0049 — email messages
+        "email_message",
+        "idx_email_message_thread",
+        "idx_email_message_quote",
+        // 0050 — webhook subscriptions and delivery log
+        "webhook_subscription",
+        "webhook_delivery",
+        "idx_webhook_delivery_subscription",
+        "idx_webhook_delivery_event",
+        "webhook_delivery_attempt",
+        "idx_webhook_delivery_attempt_delivery",
+        // 0051 — versioned pricing baselines
+        "pricing_baseline_version",
+        "pricing_baseline",
+        "idx_pricing_baseline_product",
+        // 0052 — versioned policy sets and canaries
+        "policy_set_version",
+        "idx_policy_set_version_active",
+        "policy_canary",
+        "idx_policy_canary_monitoring",
+        "idx_quote_pricing_snapshot_policy_version",
+        // 0053 — immutable policy rule set versions
+        "policy_rule_set_version",
+        // 0054 — effective-dated product costs
+        "product_cost",
+        "idx_product_cost_lookup",
+        // 0055 — collaborative operation sequencing
+        "idx_session_operations_seq",
+        // 0056 — quote document templates
+        "quote_template_version",
+        "idx_quote_template_version_published",
+        "quote_clause_block",
+        "quote_template_default",
+        // 0057 — order forms
+        "order_form",
+        "idx_order_form_account",
+        "idx_order_form_amendment",
+        // 0058 — REST idempotency key reservations
+        "rest_idempotency_reservation",
     ];
 
     async fn managed_object_count(pool: &sqlx::SqlitePool, object_name: &str) -> TestResult<i64> {
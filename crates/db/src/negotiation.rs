@@ -0,0 +1,819 @@
+//! Negotiation turn service shared by the MCP tools and the customer portal.
+//!
+//! Records counters, accepts and rejects on a negotiation session: checks each counter with
+//! [`check_counter`], walks the session state machine, persists the turn with its envelope and
+//! boundary evaluation, and applies an accepted counter to the quote as a new revision.
+
+use std::collections::BTreeMap;
+
+use quotey_core::audit::{
+    ActorType, AuditAction, AuditCategory, AuditEvent, AuditOutcome, EntityType,
+};
+use quotey_core::chrono::{DateTime, Utc};
+use quotey_core::cpq::boundary::BoundaryCalculator;
+use quotey_core::cpq::concession::ConcessionPolicy;
+use quotey_core::cpq::margin::{MarginLine, QuoteMargin};
+use quotey_core::cpq::negotiation_audit;
+use quotey_core::cpq::negotiation_turn::{
+    apply_counter_terms, check_counter, turn_transitions, CounterCheck, CounterTerms,
+    CounterVerdict, TurnParty,
+};
+use quotey_core::domain::negotiation::{
+    NegotiationSession, NegotiationSessionId, NegotiationState, NegotiationTurn, NegotiationTurnId,
+    TurnOutcome, TurnRequestType,
+};
+use quotey_core::domain::quote::{Quote, QuoteId};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::quote::quote_status_as_str;
+use crate::repositories::{
+    QuoteRepository, RepositoryError, RevisionSave, SqlAuditEventRepository,
+    SqlNegotiationRepository, SqlProductCostRepository, SqlQuoteRepository,
+};
+use crate::DbPool;
+
+/// Turns a session allows before it must be escalated or closed.
+pub const DEFAULT_MAX_TURNS: u32 = 20;
+
+/// What a turn asks for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurnAction {
+    Counter(CounterTerms),
+    /// Accept the pending counter.
+    Accept,
+    /// Reject the negotiation; the session ends.
+    Reject {
+        reason: String,
+    },
+}
+
+impl TurnAction {
+    fn request_type(&self) -> TurnRequestType {
+        match self {
+            Self::Counter(_) => TurnRequestType::Counter,
+            Self::Accept => TurnRequestType::Accept,
+            Self::Reject { .. } => TurnRequestType::Reject,
+        }
+    }
+}
+
+/// Request to record one turn on a session.
+#[derive(Clone, Debug)]
+pub struct RecordTurn {
+    pub session_id: String,
+    pub action: TurnAction,
+    pub party: TurnParty,
+    pub actor_id: String,
+    pub note: Option<String>,
+    /// Replaying a key returns the turn it first recorded instead of recording another.
+    pub idempotency_key: Option<String>,
+    pub correlation_id: String,
+}
+
+/// Payload stored on every recorded turn.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TurnPayload {
+    pub party: TurnParty,
+    pub actor_id: String,
+    #[serde(default)]
+    pub terms: Option<CounterTerms>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Quote version the turn was recorded against.
+    pub quote_version: u32,
+}
+
+/// Quote revision created by accepting a counter.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AppliedRevision {
+    pub quote_id: String,
+    pub previous_version: u32,
+    pub version: u32,
+    pub status: &'static str,
+    pub audit_event_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RecordedTurn {
+    pub session_id: String,
+    pub quote_id: String,
+    pub turn_id: String,
+    pub turn_number: u32,
+    pub request_type: &'static str,
+    pub outcome: &'static str,
+    /// Session state after the turn.
+    pub state: &'static str,
+    /// Counter check; only set for counters recorded by this call.
+    pub check: Option<CounterCheck>,
+    pub revision: Option<AppliedRevision>,
+    /// True when an idempotency key matched an earlier turn and nothing was recorded.
+    pub replayed: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum NegotiationError {
+    #[error("negotiation session `{0}` not found")]
+    SessionNotFound(String),
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("session reached its limit of {0} turns")]
+    MaxTurnsReached(u32),
+    #[error("negotiation session `{0}` has expired")]
+    SessionExpired(String),
+    #[error("quote moved from version {expected} to {current} since the counter was made")]
+    StaleCounter { expected: u32, current: u32 },
+    #[error("quote is `{0}` and cannot be negotiated")]
+    QuoteClosed(&'static str),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl NegotiationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::SessionNotFound(_) => "session_not_found",
+            Self::QuoteNotFound(_) => "quote_not_found",
+            Self::Invalid(_) => "invalid_turn",
+            Self::InvalidState(_) => "invalid_state",
+            Self::MaxTurnsReached(_) => "max_turns_reached",
+            Self::SessionExpired(_) => "session_expired",
+            Self::StaleCounter { .. } => "stale_counter",
+            Self::QuoteClosed(_) => "quote_closed",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for NegotiationError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct NegotiationService {
+    pool: DbPool,
+    policy: ConcessionPolicy,
+    calculator: BoundaryCalculator,
+}
+
+impl NegotiationService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            policy: ConcessionPolicy::default(),
+            calculator: BoundaryCalculator::default(),
+        }
+    }
+
+    /// Returns the quote's open session, or starts a draft one for `actor_id`.
+    pub async fn open_session(
+        &self,
+        quote_id: &str,
+        actor_id: &str,
+        policy_version: &str,
+    ) -> Result<NegotiationSession, NegotiationError> {
+        if let Some(session) =
+            SqlNegotiationRepository::find_active_session_for_quote(&self.pool, quote_id).await?
+        {
+            return Ok(session);
+        }
+        let quote = self.load_quote(quote_id).await?;
+        let now = Utc::now();
+        let session = NegotiationSession {
+            id: NegotiationSessionId(format!("NXT-{}", short_id())),
+            quote_id: quote.id.0,
+            actor_id: actor_id.to_string(),
+            state: NegotiationState::Draft,
+            policy_version: policy_version.to_string(),
+            pricing_version: "pricing-v1".to_string(),
+            idempotency_key: format!("{quote_id}-{actor_id}-{}", now.timestamp()),
+            max_turns: DEFAULT_MAX_TURNS,
+            expires_at: None,
+            created_at: now.to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        };
+        SqlNegotiationRepository::save_session(&self.pool, &session).await?;
+        SqlAuditEventRepository::new(self.pool.clone())
+            .save(&negotiation_audit::session_created(&session))
+            .await?;
+        Ok(session)
+    }
+
+    /// Checks and records one turn, advancing the session and, on accept, revising the quote.
+    pub async fn record(&self, request: RecordTurn) -> Result<RecordedTurn, NegotiationError> {
+        let session_id = request.session_id.trim().to_string();
+        let mut session = self.load_session(&session_id).await?;
+        let transition_key = request
+            .idempotency_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| format!("{session_id}:{key}"));
+        if let Some(key) = &transition_key {
+            if let Some(turn) =
+                SqlNegotiationRepository::find_turn_by_transition_key(&self.pool, key).await?
+            {
+                return Ok(RecordedTurn {
+                    session_id: session.id.0,
+                    quote_id: session.quote_id,
+                    turn_id: turn.id.0,
+                    turn_number: turn.turn_number,
+                    request_type: turn.request_type.as_str(),
+                    outcome: turn.outcome.as_str(),
+                    state: session.state.as_str(),
+                    check: None,
+                    revision: None,
+                    replayed: true,
+                });
+            }
+        }
+
+        if session.state.is_terminal() {
+            return Err(NegotiationError::InvalidState(format!(
+                "session is {} and takes no more turns",
+                session.state.as_str()
+            )));
+        }
+        if is_expired(&session) {
+            self.advance(&mut session, &[NegotiationState::Expired], &request.correlation_id)
+                .await?;
+            return Err(NegotiationError::SessionExpired(session.id.0));
+        }
+        let turns =
+            SqlNegotiationRepository::find_turns_by_session(&self.pool, &session_id).await?;
+        let turn_number = u32::try_from(turns.len()).unwrap_or(u32::MAX).saturating_add(1);
+        if turn_number > session.max_turns {
+            return Err(NegotiationError::MaxTurnsReached(session.max_turns));
+        }
+
+        let mut quote = self.load_quote(&session.quote_id).await?;
+        if !quote.status.is_editable() {
+            return Err(NegotiationError::QuoteClosed(quote_status_as_str(&quote.status)));
+        }
+
+        let pending = pending_counter(&turns);
+        let mut payload = TurnPayload {
+            party: request.party.clone(),
+            actor_id: request.actor_id.clone(),
+            terms: None,
+            reason: None,
+            note: request.note.clone().filter(|note| !note.trim().is_empty()),
+            quote_version: quote.version,
+        };
+        let mut check = None;
+        let mut chosen_offer_id = None;
+        let (outcome, path) = match &request.action {
+            TurnAction::Counter(terms) => {
+                terms.validate().map_err(NegotiationError::Invalid)?;
+                let counter = self.check(&session, &quote, terms).await?;
+                let path = transitions(&session, &TurnRequestType::Counter, Some(&counter))?;
+                let outcome = match counter.verdict {
+                    CounterVerdict::Offered => TurnOutcome::Offered,
+                    CounterVerdict::NeedsApproval => TurnOutcome::Escalated,
+                    CounterVerdict::Blocked => TurnOutcome::Rejected,
+                };
+                payload.terms = Some(terms.clone());
+                check = Some(counter);
+                (outcome, path)
+            }
+            TurnAction::Accept => {
+                let path = transitions(&session, &TurnRequestType::Accept, None)?;
+                let (counter, counter_payload) = pending.ok_or_else(|| {
+                    NegotiationError::InvalidState("session has no pending counter".to_string())
+                })?;
+                if counter_payload.quote_version != quote.version {
+                    return Err(NegotiationError::StaleCounter {
+                        expected: counter_payload.quote_version,
+                        current: quote.version,
+                    });
+                }
+                payload.terms = counter_payload.terms;
+                chosen_offer_id = Some(counter.id.0.clone());
+                (TurnOutcome::Accepted, path)
+            }
+            TurnAction::Reject { reason } => {
+                let reason = reason.trim();
+                if reason.is_empty() {
+                    return Err(NegotiationError::Invalid("reason is required".to_string()));
+                }
+                let path = transitions(&session, &TurnRequestType::Reject, None)?;
+                payload.reason = Some(reason.to_string());
+                chosen_offer_id = pending.map(|(counter, _)| counter.id.0.clone());
+                (TurnOutcome::Rejected, path)
+            }
+        };
+
+        let turn = NegotiationTurn {
+            id: NegotiationTurnId(format!("NXT-TURN-{}", short_id())),
+            session_id: session.id.clone(),
+            turn_number,
+            request_type: request.action.request_type(),
+            request_payload: encode(&payload, "turn payload")?,
+            envelope_json: check
+                .as_ref()
+                .map(|check| encode(&check.envelope, "concession envelope"))
+                .transpose()?,
+            plan_json: None,
+            chosen_offer_id,
+            outcome,
+            boundary_json: check
+                .as_ref()
+                .map(|check| encode(&check.boundary, "boundary evaluation"))
+                .transpose()?,
+            transition_key: transition_key
+                .unwrap_or_else(|| format!("{}:turn-{turn_number}", session.id.0)),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        // An accepted counter is written onto the quote before the turn, so a stale accept
+        // leaves no accepted turn behind.
+        let previous_version = quote.version;
+        if let (TurnAction::Accept, Some(terms)) = (&request.action, &payload.terms) {
+            self.revise_quote(&mut quote, terms).await?;
+        }
+        SqlNegotiationRepository::save_turn(&self.pool, &turn).await?;
+
+        let audit = SqlAuditEventRepository::new(self.pool.clone());
+        audit
+            .save(&with_correlation(
+                negotiation_audit::turn_recorded(&session, &turn),
+                &request.correlation_id,
+            ))
+            .await?;
+        if let Some(check) = &check {
+            let boundary = &check.boundary;
+            let events = [
+                Some(negotiation_audit::envelope_evaluated(
+                    &session,
+                    check.envelope.blocking_reasons.len(),
+                )),
+                Some(negotiation_audit::boundary_evaluated(
+                    &session,
+                    boundary.within_bounds,
+                    boundary.walk_away,
+                    boundary.requires_approval,
+                )),
+                (check.verdict == CounterVerdict::NeedsApproval).then(|| {
+                    negotiation_audit::escalation_triggered(
+                        &session,
+                        &boundary.stop_reasons.join("; "),
+                    )
+                }),
+                boundary.walk_away.then(|| {
+                    negotiation_audit::walk_away_triggered(
+                        &session,
+                        &boundary.stop_reasons.join("; "),
+                    )
+                }),
+            ];
+            for event in events.into_iter().flatten() {
+                audit.save(&with_correlation(event, &request.correlation_id)).await?;
+            }
+        }
+
+        let revision = match (&request.action, &payload.terms) {
+            (TurnAction::Accept, Some(terms)) => {
+                audit
+                    .save(&with_correlation(
+                        negotiation_audit::offer_selected(
+                            &session,
+                            turn.chosen_offer_id.as_deref().unwrap_or_default(),
+                            turn_number,
+                        ),
+                        &request.correlation_id,
+                    ))
+                    .await?;
+                Some(
+                    self.audit_revision(&quote, previous_version, terms, &session, &turn, &request)
+                        .await?,
+                )
+            }
+            _ => None,
+        };
+        self.advance(&mut session, &path, &request.correlation_id).await?;
+
+        Ok(RecordedTurn {
+            session_id: session.id.0,
+            quote_id: session.quote_id,
+            turn_id: turn.id.0,
+            turn_number,
+            request_type: turn.request_type.as_str(),
+            outcome: turn.outcome.as_str(),
+            state: session.state.as_str(),
+            check,
+            revision,
+            replayed: false,
+        })
+    }
+
+    /// Checks counter terms with the counter's discount applied to every line.
+    async fn check(
+        &self,
+        session: &NegotiationSession,
+        quote: &Quote,
+        terms: &CounterTerms,
+    ) -> Result<CounterCheck, NegotiationError> {
+        let discount_pct = Decimal::from_f64(terms.discount_pct).unwrap_or(Decimal::ZERO);
+        let margin_lines: Vec<MarginLine> = quote
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let subtotal = line.unit_price * Decimal::from(line.quantity);
+                MarginLine {
+                    line_id: format!("{}-ql-{}", quote.id.0, index + 1),
+                    product_id: line.product_id.0.clone(),
+                    quantity: line.quantity,
+                    net_amount: subtotal - subtotal * discount_pct / Decimal::from(100),
+                }
+            })
+            .collect();
+        let unit_costs = SqlProductCostRepository::new(self.pool.clone())
+            .unit_costs_for_quote(&quote.id.0, Utc::now().date_naive())
+            .await?;
+        let families: BTreeMap<String, String> = sqlx::query_as(
+            "SELECT DISTINCT ql.product_id, pf.name
+             FROM quote_line ql
+             JOIN product p ON p.id = ql.product_id
+             JOIN product_family pf ON pf.id = p.family_id
+             WHERE ql.quote_id = ?",
+        )
+        .bind(&quote.id.0)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        Ok(check_counter(
+            &self.policy,
+            &self.calculator,
+            &session.id.0,
+            terms,
+            &QuoteMargin::compute(&margin_lines, &unit_costs),
+            &families,
+        ))
+    }
+
+    /// Writes the accepted counter onto the quote as its next revision.
+    async fn revise_quote(
+        &self,
+        quote: &mut Quote,
+        terms: &CounterTerms,
+    ) -> Result<(), NegotiationError> {
+        let expected = quote.version;
+        apply_counter_terms(quote, terms);
+        let mut tx = self.pool.begin().await?;
+        if let RevisionSave::Stale { current } =
+            SqlQuoteRepository::save_revision(&mut tx, quote).await?
+        {
+            return Err(NegotiationError::StaleCounter { expected, current });
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn audit_revision(
+        &self,
+        quote: &Quote,
+        previous_version: u32,
+        terms: &CounterTerms,
+        session: &NegotiationSession,
+        turn: &NegotiationTurn,
+        request: &RecordTurn,
+    ) -> Result<AppliedRevision, NegotiationError> {
+        let event = AuditEvent::new(
+            Some(quote.id.clone()),
+            None,
+            request.correlation_id.clone(),
+            "quote.negotiation_counter_accepted",
+            AuditCategory::Flow,
+            request.actor_id.clone(),
+            AuditOutcome::Success,
+        )
+        .with_actor_type(ActorType::User)
+        .with_entity(EntityType::Quote, quote.id.0.clone())
+        .with_action(AuditAction::Updated)
+        .with_before(json!({ "version": previous_version }).to_string())
+        .with_after(
+            json!({
+                "version": quote.version,
+                "discount_pct": terms.discount_pct,
+                "term_months": quote.term_months,
+            })
+            .to_string(),
+        )
+        .with_metadata("negotiation_session_id", session.id.0.clone())
+        .with_metadata("negotiation_turn_id", turn.id.0.clone())
+        .with_metadata("counter_turn_id", turn.chosen_offer_id.clone().unwrap_or_default());
+        SqlAuditEventRepository::new(self.pool.clone()).save(&event).await?;
+
+        Ok(AppliedRevision {
+            quote_id: quote.id.0.clone(),
+            previous_version,
+            version: quote.version,
+            status: quote_status_as_str(&quote.status),
+            audit_event_id: event.event_id,
+        })
+    }
+
+    /// Moves the session through `path`, auditing each step.
+    async fn advance(
+        &self,
+        session: &mut NegotiationSession,
+        path: &[NegotiationState],
+        correlation_id: &str,
+    ) -> Result<(), NegotiationError> {
+        let audit = SqlAuditEventRepository::new(self.pool.clone());
+        for next in path {
+            SqlNegotiationRepository::advance_session_state(
+                &self.pool,
+                &session.id.0,
+                next.clone(),
+            )
+            .await?;
+            let event = negotiation_audit::session_state_changed(session, &session.state, next);
+            audit.save(&with_correlation(event, correlation_id)).await?;
+            session.state = next.clone();
+        }
+        Ok(())
+    }
+
+    async fn load_session(&self, session_id: &str) -> Result<NegotiationSession, NegotiationError> {
+        SqlNegotiationRepository::find_session_by_id(&self.pool, session_id)
+            .await?
+            .ok_or_else(|| NegotiationError::SessionNotFound(session_id.to_string()))
+    }
+
+    async fn load_quote(&self, quote_id: &str) -> Result<Quote, NegotiationError> {
+        SqlQuoteRepository::new(self.pool.clone())
+            .find_by_id(&QuoteId(quote_id.to_string()))
+            .await?
+            .ok_or_else(|| NegotiationError::QuoteNotFound(quote_id.to_string()))
+    }
+}
+
+/// The newest counter with its payload, when it is still open for a decision.
+///
+/// Every counter voids the ones before it, so an older offer is never returned once a newer
+/// counter (even a blocked one) was made.
+fn pending_counter(turns: &[NegotiationTurn]) -> Option<(&NegotiationTurn, TurnPayload)> {
+    turns
+        .iter()
+        .rev()
+        .find(|turn| turn.request_type == TurnRequestType::Counter)
+        .filter(|turn| matches!(turn.outcome, TurnOutcome::Offered | TurnOutcome::Escalated))
+        .and_then(|turn| {
+            serde_json::from_str(&turn.request_payload).ok().map(|payload| (turn, payload))
+        })
+}
+
+fn transitions(
+    session: &NegotiationSession,
+    request: &TurnRequestType,
+    counter: Option<&CounterCheck>,
+) -> Result<Vec<NegotiationState>, NegotiationError> {
+    turn_transitions(&session.state, request, counter.map(|check| &check.verdict))
+        .map_err(NegotiationError::InvalidState)
+}
+
+fn is_expired(session: &NegotiationSession) -> bool {
+    session
+        .expires_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_some_and(|at| at <= Utc::now())
+}
+
+/// Negotiation audit events key on the session; tie them to the request that caused them.
+fn with_correlation(event: AuditEvent, correlation_id: &str) -> AuditEvent {
+    event.with_metadata("correlation_id", correlation_id.to_string())
+}
+
+fn encode<T: Serialize>(value: &T, what: &str) -> Result<String, NegotiationError> {
+    serde_json::to_string(value)
+        .map_err(|error| RepositoryError::Decode(format!("encode {what}: {error}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::cpq::negotiation_turn::{CounterTerms, CounterVerdict, TurnParty};
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use rust_decimal::Decimal;
+
+    use super::{pending_counter, NegotiationError, NegotiationService, RecordTurn, TurnAction};
+    use crate::repositories::{QuoteRepository, SqlNegotiationRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    type TestResult<T> = Result<T, String>;
+
+    #[tokio::test]
+    async fn accepted_counter_revises_quote_and_closes_session() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-NEG-1").await?;
+        let service = NegotiationService::new(pool.clone());
+        let session = service
+            .open_session(&quote_id.0, "portal:customer", "policy-v1")
+            .await
+            .map_err(|error| format!("open session: {error}"))?;
+
+        let blocked = service
+            .record(counter(&session.id.0, 45.0, None))
+            .await
+            .map_err(|error| format!("blocked counter: {error}"))?;
+        assert_eq!(blocked.outcome, "rejected");
+        assert_eq!(blocked.state, "active");
+        let check = blocked.check.ok_or("blocked counter carries its check")?;
+        assert_eq!(check.verdict, CounterVerdict::Blocked);
+        assert!(check.boundary.ceiling_breached);
+
+        let offered = service
+            .record(counter(&session.id.0, 12.5, Some("counter-1")))
+            .await
+            .map_err(|error| format!("counter: {error}"))?;
+        assert_eq!((offered.outcome, offered.state), ("offered", "counter_pending"));
+        let replay = service
+            .record(counter(&session.id.0, 30.0, Some("counter-1")))
+            .await
+            .map_err(|error| format!("replay: {error}"))?;
+        assert!(replay.replayed);
+        assert_eq!(replay.turn_id, offered.turn_id);
+
+        let accepted = service
+            .record(RecordTurn { action: TurnAction::Accept, ..counter(&session.id.0, 0.0, None) })
+            .await
+            .map_err(|error| format!("accept: {error}"))?;
+        assert_eq!((accepted.outcome, accepted.state), ("accepted", "accepted"));
+        let revision = accepted.revision.ok_or("accept applies the counter")?;
+        assert_eq!((revision.previous_version, revision.version), (1, 2));
+        assert_eq!(revision.status, "revised");
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("load quote: {error}"))?
+            .ok_or("quote exists")?;
+        assert_eq!(quote.version, 2);
+        assert_eq!(quote.term_months, Some(24));
+        assert!(quote.lines.iter().all(|line| (line.discount_pct - 12.5).abs() < f64::EPSILON));
+
+        let turns = SqlNegotiationRepository::find_turns_by_session(&pool, &session.id.0)
+            .await
+            .map_err(|error| format!("turns: {error}"))?;
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[2].chosen_offer_id.as_deref(), Some(offered.turn_id.as_str()));
+
+        let closed = service.record(counter(&session.id.0, 10.0, None)).await;
+        assert!(matches!(closed, Err(NegotiationError::InvalidState(_))));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn soft_zone_counter_waits_for_approval_and_reject_ends_session() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-NEG-2").await?;
+        let service = NegotiationService::new(pool.clone());
+        let session = service
+            .open_session(&quote_id.0, "U-REP", "policy-v1")
+            .await
+            .map_err(|error| format!("open session: {error}"))?;
+
+        let escalated = service
+            .record(counter(&session.id.0, 39.0, None))
+            .await
+            .map_err(|error| format!("counter: {error}"))?;
+        assert_eq!((escalated.outcome, escalated.state), ("escalated", "approval_pending"));
+
+        let accept = service
+            .record(RecordTurn { action: TurnAction::Accept, ..counter(&session.id.0, 0.0, None) })
+            .await;
+        assert!(matches!(accept, Err(NegotiationError::InvalidState(_))));
+
+        let missing_reason = service
+            .record(RecordTurn {
+                action: TurnAction::Reject { reason: " ".to_string() },
+                ..counter(&session.id.0, 0.0, None)
+            })
+            .await;
+        assert!(matches!(missing_reason, Err(NegotiationError::Invalid(_))));
+        let rejected = service
+            .record(RecordTurn {
+                action: TurnAction::Reject { reason: "Budget frozen".to_string() },
+                party: TurnParty::Rep,
+                ..counter(&session.id.0, 0.0, None)
+            })
+            .await
+            .map_err(|error| format!("reject: {error}"))?;
+        assert_eq!((rejected.outcome, rejected.state), ("rejected", "rejected"));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blocked_counter_voids_the_offer_before_it() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-NEG-3").await?;
+        let service = NegotiationService::new(pool.clone());
+        let session = service
+            .open_session(&quote_id.0, "portal:customer", "policy-v1")
+            .await
+            .map_err(|error| format!("open session: {error}"))?;
+
+        let offered = service
+            .record(counter(&session.id.0, 12.5, None))
+            .await
+            .map_err(|error| format!("counter A: {error}"))?;
+        assert_eq!((offered.outcome, offered.state), ("offered", "counter_pending"));
+        let blocked = service
+            .record(counter(&session.id.0, 45.0, None))
+            .await
+            .map_err(|error| format!("counter B: {error}"))?;
+        assert_eq!((blocked.outcome, blocked.state), ("rejected", "active"));
+
+        let accept = service
+            .record(RecordTurn { action: TurnAction::Accept, ..counter(&session.id.0, 0.0, None) })
+            .await;
+        assert!(matches!(accept, Err(NegotiationError::InvalidState(_))), "{accept:?}");
+        let turns = SqlNegotiationRepository::find_turns_by_session(&pool, &session.id.0)
+            .await
+            .map_err(|error| format!("turns: {error}"))?;
+        assert!(pending_counter(&turns).is_none());
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("load quote: {error}"))?
+            .ok_or("quote exists")?;
+        assert_eq!(quote.version, 1);
+        assert!(quote.lines.iter().all(|line| line.discount_pct == 0.0));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    fn counter(session_id: &str, discount_pct: f64, key: Option<&str>) -> RecordTurn {
+        RecordTurn {
+            session_id: session_id.to_string(),
+            action: TurnAction::Counter(CounterTerms { discount_pct, term_months: Some(24) }),
+            party: TurnParty::Customer,
+            actor_id: "portal:customer".to_string(),
+            note: None,
+            idempotency_key: key.map(str::to_string),
+            correlation_id: "corr-neg".to_string(),
+        }
+    }
+
+    async fn setup_pool() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30)
+            .await
+            .map_err(|error| format!("connect test pool: {error}"))?;
+        migrations::run_pending(&pool).await.map_err(|error| format!("run migrations: {error}"))?;
+        Ok(pool)
+    }
+
+    async fn insert_quote(pool: &DbPool, id: &str) -> TestResult<QuoteId> {
+        let now = Utc::now();
+        let quote = Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status: QuoteStatus::Sent,
+            account_id: Some("acct-neg".to_string()),
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: Some(12),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: "U-REP".to_string(),
+            lines: vec![QuoteLine {
+                product_id: ProductId("plan-pro".to_string()),
+                quantity: 10,
+                unit_price: Decimal::new(100, 0),
+                discount_pct: 0.0,
+                notes: None,
+            }],
+            created_at: now,
+            updated_at: now,
+        };
+        SqlQuoteRepository::new(pool.clone())
+            .save(quote.clone())
+            .await
+            .map_err(|error| format!("save quote fixture {id}: {error}"))?;
+        Ok(quote.id)
+    }
+}
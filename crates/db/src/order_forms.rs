@@ -0,0 +1,687 @@
+//! Order forms generated from accepted quotes.
+//!
+//! [`OrderFormService::generate`] snapshots an accepted quote into an [`OrderFormContent`]:
+//! its lines and pricing, a billing schedule, and the quote template (resolved the same way as
+//! the quote PDF) followed by the selected clause blocks, the billing schedule and the signature
+//! blocks. The order form records the quote's newest ledger entry and a SHA-256 of the
+//! snapshot, then moves draft → sent → signed → countersigned. Amendments are generated from a
+//! later accepted quote and always reference the original order form.
+
+use quotey_core::chrono::{NaiveDate, Utc};
+use quotey_core::domain::order_form::{
+    billing_schedule, validate_signatories, BillingFrequency, OrderFormContent, OrderFormError,
+    OrderFormId, OrderFormStatus, OrderLine, Signatory,
+};
+use quotey_core::esign::document_sha256;
+use quotey_core::pdf::template::DocumentSection;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row};
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::quote_templates::{QuoteTemplateError, QuoteTemplateService, TemplateSelection};
+use crate::repositories::RepositoryError;
+use crate::DbPool;
+
+/// Term used when neither the request nor the quote sets one.
+const DEFAULT_TERM_MONTHS: u32 = 12;
+
+const ORDER_SELECT: &str = "SELECT id, quote_id, account_id, amends_order_id, amendment_number,
+        status, content_json, content_sha256, signatories_json, quote_ledger_entry_id,
+        created_by, created_at, updated_at, sent_at, signed_at, countersigned_at
+    FROM order_form";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OrderForm {
+    pub id: OrderFormId,
+    pub quote_id: String,
+    pub account_id: Option<String>,
+    /// The original order form this one amends.
+    pub amends_order_id: Option<OrderFormId>,
+    /// `0` for an original order form, then `1, 2, ...` for its amendments.
+    pub amendment_number: u32,
+    pub status: OrderFormStatus,
+    pub content: OrderFormContent,
+    pub content_sha256: String,
+    pub signatories: Vec<Signatory>,
+    pub quote_ledger_entry_id: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub sent_at: Option<String>,
+    pub signed_at: Option<String>,
+    pub countersigned_at: Option<String>,
+}
+
+/// A clause block to print on the order form after the template's own sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClauseSelection {
+    pub key: String,
+    /// The newest version when `None`.
+    pub version: Option<i32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GenerateOrderForm {
+    pub quote_id: String,
+    pub template: TemplateSelection,
+    pub billing_frequency: BillingFrequency,
+    /// Defaults to the quote's start date, else today.
+    pub start_date: Option<NaiveDate>,
+    /// Defaults to the quote's term, else 12 months.
+    pub term_months: Option<u32>,
+    pub signatories: Vec<Signatory>,
+    pub clauses: Vec<ClauseSelection>,
+    pub actor: String,
+}
+
+#[derive(Debug, Error)]
+pub enum OrderFormServiceError {
+    #[error(transparent)]
+    Invalid(#[from] OrderFormError),
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("quote `{quote_id}` is `{status}`; order forms are generated from accepted quotes")]
+    QuoteNotAccepted { quote_id: String, status: String },
+    #[error("quote `{quote_id}` already has order form `{order_id}`")]
+    AlreadyGenerated { quote_id: String, order_id: String },
+    #[error("order form `{0}` not found")]
+    OrderNotFound(String),
+    #[error("order form `{order_id}` is `{status}`; only countersigned orders can be amended")]
+    NotAmendable { order_id: String, status: &'static str },
+    #[error("quote `{quote_id}` belongs to a different account than order form `{order_id}`")]
+    AccountMismatch { quote_id: String, order_id: String },
+    #[error("clause `{0}` is selected more than once")]
+    DuplicateClause(String),
+    #[error("order form `{0}` was changed concurrently")]
+    Conflict(String),
+    #[error(transparent)]
+    Template(#[from] QuoteTemplateError),
+    #[error("stored order form is unreadable: {0}")]
+    Corrupt(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl OrderFormServiceError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid(OrderFormError::InvalidTransition { .. })
+            | Self::QuoteNotAccepted { .. }
+            | Self::AlreadyGenerated { .. }
+            | Self::NotAmendable { .. }
+            | Self::Conflict(_) => "conflict",
+            Self::Invalid(_) | Self::AccountMismatch { .. } | Self::DuplicateClause(_) => {
+                "invalid_order_form"
+            }
+            Self::QuoteNotFound(_) | Self::OrderNotFound(_) => "not_found",
+            Self::Template(error) => error.code(),
+            Self::Corrupt(_) | Self::Repository(_) => "storage",
+        }
+    }
+}
+
+impl From<sqlx::Error> for OrderFormServiceError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct OrderFormService {
+    pool: DbPool,
+}
+
+impl OrderFormService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Generates the draft order form of an accepted quote.
+    pub async fn generate(
+        &self,
+        request: GenerateOrderForm,
+    ) -> Result<OrderForm, OrderFormServiceError> {
+        self.insert(request, None).await
+    }
+
+    /// Generates an order form for `request.quote_id` that amends `order_id`, or the order form
+    /// `order_id` itself amends. The original must be countersigned and for the same account.
+    pub async fn amend(
+        &self,
+        order_id: &str,
+        request: GenerateOrderForm,
+    ) -> Result<OrderForm, OrderFormServiceError> {
+        let amended = self.require(order_id).await?;
+        let original = match &amended.amends_order_id {
+            Some(original) => self.require(&original.0).await?,
+            None => amended,
+        };
+        if original.status != OrderFormStatus::Countersigned {
+            return Err(OrderFormServiceError::NotAmendable {
+                order_id: original.id.0,
+                status: original.status.as_str(),
+            });
+        }
+        self.insert(request, Some(original)).await
+    }
+
+    pub async fn find(&self, id: &str) -> Result<Option<OrderForm>, OrderFormServiceError> {
+        let row = sqlx::query(&format!("{ORDER_SELECT} WHERE id = ?"))
+            .bind(id.trim())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(order_from_row).transpose()
+    }
+
+    pub async fn find_for_quote(
+        &self,
+        quote_id: &str,
+    ) -> Result<Option<OrderForm>, OrderFormServiceError> {
+        let row = sqlx::query(&format!("{ORDER_SELECT} WHERE quote_id = ?"))
+            .bind(quote_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(order_from_row).transpose()
+    }
+
+    /// Amendments of an original order form, oldest first.
+    pub async fn amendments(
+        &self,
+        order_id: &str,
+    ) -> Result<Vec<OrderForm>, OrderFormServiceError> {
+        let rows = sqlx::query(&format!(
+            "{ORDER_SELECT} WHERE amends_order_id = ? ORDER BY amendment_number"
+        ))
+        .bind(order_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(order_from_row).collect()
+    }
+
+    /// Moves an order form one step along its lifecycle.
+    ///
+    /// Signing and countersigning stamp the customer or provider signatory block; `signer`
+    /// replaces that block first, for when the person signing was not known at generation.
+    pub async fn transition(
+        &self,
+        id: &str,
+        to: OrderFormStatus,
+        signer: Option<Signatory>,
+    ) -> Result<OrderForm, OrderFormServiceError> {
+        let order = self.require(id).await?;
+        if !order.status.can_transition_to(to) {
+            return Err(OrderFormError::InvalidTransition { from: order.status, to }.into());
+        }
+
+        let now = Utc::now();
+        let mut signatories = order.signatories.clone();
+        if let Some(party) = to.signing_party() {
+            if let Some(signer) = signer {
+                if signer.party != party {
+                    return Err(OrderFormError::MissingSignatory(party).into());
+                }
+                signatories.retain(|signatory| signatory.party != party);
+                signatories.push(signer);
+                validate_signatories(&signatories)?;
+            }
+            let signatory = signatories
+                .iter_mut()
+                .find(|signatory| signatory.party == party)
+                .ok_or(OrderFormError::MissingSignatory(party))?;
+            signatory.signed_at = Some(now);
+        }
+
+        let column = match to {
+            OrderFormStatus::Sent => "sent_at",
+            OrderFormStatus::Signed => "signed_at",
+            OrderFormStatus::Countersigned => "countersigned_at",
+            OrderFormStatus::Draft => "updated_at",
+        };
+        let updated = sqlx::query(&format!(
+            "UPDATE order_form SET status = ?, signatories_json = ?, {column} = ?, updated_at = ?
+             WHERE id = ? AND status = ?"
+        ))
+        .bind(to.as_str())
+        .bind(encode(&signatories)?)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(&order.id.0)
+        .bind(order.status.as_str())
+        .execute(&self.pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(OrderFormServiceError::Conflict(order.id.0));
+        }
+        self.require(&order.id.0).await
+    }
+
+    async fn insert(
+        &self,
+        request: GenerateOrderForm,
+        original: Option<OrderForm>,
+    ) -> Result<OrderForm, OrderFormServiceError> {
+        validate_signatories(&request.signatories)?;
+        let quote_id = request.quote_id.trim().to_owned();
+        let quote = sqlx::query(
+            "SELECT status, version, currency, account_id, payment_terms, term_months, start_date
+             FROM quote WHERE id = ?",
+        )
+        .bind(&quote_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| OrderFormServiceError::QuoteNotFound(quote_id.clone()))?;
+        let status: String = quote.try_get("status")?;
+        if status != "accepted" {
+            return Err(OrderFormServiceError::QuoteNotAccepted { quote_id, status });
+        }
+        if let Some(existing) = self.find_for_quote(&quote_id).await? {
+            return Err(OrderFormServiceError::AlreadyGenerated {
+                quote_id,
+                order_id: existing.id.0,
+            });
+        }
+        let account_id: Option<String> = quote.try_get("account_id")?;
+        if let Some(original) = &original {
+            if original.account_id != account_id {
+                return Err(OrderFormServiceError::AccountMismatch {
+                    quote_id,
+                    order_id: original.id.0.clone(),
+                });
+            }
+        }
+
+        let lines = self.order_lines(&quote_id).await?;
+        let subtotal: Decimal = lines.iter().map(|line| line.subtotal).sum();
+        let total: Decimal = lines.iter().map(|line| line.total).sum();
+        let term_months = match request.term_months {
+            Some(term) => term,
+            None => quote
+                .try_get::<Option<i64>, _>("term_months")?
+                .and_then(|term| u32::try_from(term).ok())
+                .filter(|term| *term > 0)
+                .unwrap_or(DEFAULT_TERM_MONTHS),
+        };
+        let start_date = match request.start_date {
+            Some(start) => start,
+            None => quote
+                .try_get::<Option<String>, _>("start_date")?
+                .and_then(|raw| raw.get(..10).and_then(|date| date.parse().ok()))
+                .unwrap_or_else(|| Utc::now().date_naive()),
+        };
+
+        let amendment_number = match &original {
+            Some(original) => {
+                let latest: Option<i64> = sqlx::query_scalar(
+                    "SELECT MAX(amendment_number) FROM order_form WHERE amends_order_id = ?",
+                )
+                .bind(&original.id.0)
+                .fetch_one(&self.pool)
+                .await?;
+                u32::try_from(latest.unwrap_or(0)).unwrap_or(0) + 1
+            }
+            None => 0,
+        };
+
+        let templates = QuoteTemplateService::new(self.pool.clone());
+        let mut document = templates.resolve_for_quote(&quote_id, request.template).await?;
+        for selection in &request.clauses {
+            let clause = templates.clause(&selection.key, selection.version).await?;
+            let listed = document.sections.iter().any(|section| {
+                matches!(section, DocumentSection::Clause { key, .. } if *key == clause.key)
+            });
+            if listed {
+                return Err(OrderFormServiceError::DuplicateClause(clause.key));
+            }
+            document.sections.push(DocumentSection::Clause {
+                key: clause.key,
+                version: clause.version,
+                title: clause.title,
+                body: clause.body,
+            });
+        }
+        for section in [DocumentSection::BillingSchedule, DocumentSection::Signatures] {
+            if !document.sections.contains(&section) {
+                document.sections.push(section);
+            }
+        }
+        document.title = Some(match amendment_number {
+            0 => "Order Form".to_owned(),
+            number => format!("Order Form Amendment {number}"),
+        });
+
+        let content = OrderFormContent {
+            quote_version: u32::try_from(quote.try_get::<i64, _>("version")?).unwrap_or(1),
+            account_id: account_id.clone(),
+            currency: quote.try_get("currency")?,
+            subtotal,
+            discount_total: subtotal - total,
+            total,
+            payment_terms: quote
+                .try_get::<Option<String>, _>("payment_terms")?
+                .unwrap_or_else(|| "net_30".to_owned()),
+            billing_frequency: request.billing_frequency,
+            term_months,
+            start_date,
+            billing_schedule: billing_schedule(
+                total,
+                term_months,
+                request.billing_frequency,
+                start_date,
+            ),
+            document,
+            quote_id,
+            lines,
+        };
+        content.validate()?;
+        let content_json = encode(&content)?;
+        let content_sha256 = document_sha256(content_json.as_bytes());
+
+        let ledger_entry_id: Option<String> = sqlx::query_scalar(
+            "SELECT entry_id FROM quote_ledger WHERE quote_id = ?
+             ORDER BY version_number DESC LIMIT 1",
+        )
+        .bind(&content.quote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let id = format!("ORD-{}", short_id());
+        let now = Utc::now().to_rfc3339();
+        let inserted = sqlx::query(
+            "INSERT INTO order_form
+                (id, quote_id, account_id, amends_order_id, amendment_number, content_json,
+                 content_sha256, signatories_json, template_key, template_version,
+                 quote_ledger_entry_id, created_by, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(&content.quote_id)
+        .bind(&account_id)
+        .bind(original.as_ref().map(|original| original.id.0.as_str()))
+        .bind(i64::from(amendment_number))
+        .bind(&content_json)
+        .bind(&content_sha256)
+        .bind(encode(&request.signatories)?)
+        .bind(&content.document.template_key)
+        .bind(content.document.template_version)
+        .bind(&ledger_entry_id)
+        .bind(request.actor.trim())
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await;
+        match inserted {
+            Ok(_) => {}
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                return Err(OrderFormServiceError::Conflict(id));
+            }
+            Err(error) => return Err(error.into()),
+        }
+        self.require(&id).await
+    }
+
+    async fn order_lines(&self, quote_id: &str) -> Result<Vec<OrderLine>, OrderFormServiceError> {
+        let rows = sqlx::query(
+            "SELECT ql.product_id, ql.quantity, ql.unit_price, ql.subtotal, ql.discount_pct,
+                    p.name AS product_name, p.sku AS product_sku
+             FROM quote_line ql
+             LEFT JOIN product p ON p.id = ql.product_id
+             WHERE ql.quote_id = ?
+             ORDER BY ql.id",
+        )
+        .bind(quote_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let money = |value: f64| Decimal::from_f64(value).unwrap_or_default().round_dp(2);
+        rows.iter()
+            .map(|row| {
+                let product_id: String = row.try_get("product_id")?;
+                let quantity = u32::try_from(row.try_get::<i64, _>("quantity")?).unwrap_or(0);
+                let unit_price = money(row.try_get::<Option<f64>, _>("unit_price")?.unwrap_or(0.0));
+                let subtotal = match row.try_get::<Option<f64>, _>("subtotal")? {
+                    Some(subtotal) => money(subtotal),
+                    None => unit_price * Decimal::from(quantity),
+                };
+                let discount_pct = Decimal::from_f64(
+                    row.try_get::<Option<f64>, _>("discount_pct")?.unwrap_or(0.0).clamp(0.0, 100.0),
+                )
+                .unwrap_or_default();
+                let total = (subtotal * (Decimal::ONE_HUNDRED - discount_pct)
+                    / Decimal::ONE_HUNDRED)
+                    .round_dp(2);
+                Ok(OrderLine {
+                    name: row
+                        .try_get::<Option<String>, _>("product_name")?
+                        .unwrap_or_else(|| product_id.clone()),
+                    sku: row.try_get("product_sku")?,
+                    product_id,
+                    quantity,
+                    unit_price,
+                    discount_pct,
+                    subtotal,
+                    total,
+                })
+            })
+            .collect()
+    }
+
+    async fn require(&self, id: &str) -> Result<OrderForm, OrderFormServiceError> {
+        self.find(id).await?.ok_or_else(|| OrderFormServiceError::OrderNotFound(id.to_owned()))
+    }
+}
+
+fn order_from_row(row: &SqliteRow) -> Result<OrderForm, OrderFormServiceError> {
+    let status: String = row.try_get("status")?;
+    Ok(OrderForm {
+        id: OrderFormId(row.try_get("id")?),
+        quote_id: row.try_get("quote_id")?,
+        account_id: row.try_get("account_id")?,
+        amends_order_id: row.try_get::<Option<String>, _>("amends_order_id")?.map(OrderFormId),
+        amendment_number: u32::try_from(row.try_get::<i64, _>("amendment_number")?).unwrap_or(0),
+        status: OrderFormStatus::parse_label(&status)
+            .ok_or_else(|| OrderFormServiceError::Corrupt(format!("unknown status `{status}`")))?,
+        content: decode(&row.try_get::<String, _>("content_json")?)?,
+        content_sha256: row.try_get("content_sha256")?,
+        signatories: decode(&row.try_get::<String, _>("signatories_json")?)?,
+        quote_ledger_entry_id: row.try_get("quote_ledger_entry_id")?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        sent_at: row.try_get("sent_at")?,
+        signed_at: row.try_get("signed_at")?,
+        countersigned_at: row.try_get("countersigned_at")?,
+    })
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<String, OrderFormServiceError> {
+    serde_json::to_string(value).map_err(|error| OrderFormServiceError::Corrupt(error.to_string()))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, OrderFormServiceError> {
+    serde_json::from_str(json).map_err(|error| OrderFormServiceError::Corrupt(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::domain::order_form::SignatoryParty;
+
+    use super::*;
+    use crate::quote_templates::SaveClause;
+    use crate::{connect_with_settings, migrations};
+
+    async fn pool() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn seed_quote(pool: &DbPool, quote_id: &str, status: &str, account_id: &str) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, version, currency, account_id, term_months,
+                                start_date, created_by, created_at, updated_at)
+             VALUES (?, ?, 2, 'USD', ?, 12, '2026-03-01', 'rep', ?, ?)",
+        )
+        .bind(quote_id)
+        .bind(status)
+        .bind(account_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("quote");
+        sqlx::query(
+            "INSERT INTO quote_line (id, quote_id, product_id, quantity, unit_price,
+                                     discount_pct, created_at, updated_at)
+             VALUES (?, ?, 'plan-pro', 10, 1000, 10, ?, ?)",
+        )
+        .bind(format!("{quote_id}-ql-1"))
+        .bind(quote_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("line");
+        sqlx::query(
+            "INSERT INTO quote_ledger
+                (entry_id, quote_id, version_number, content_hash, actor_id, action_type,
+                 timestamp, signature)
+             VALUES (?, ?, 1, 'hash', 'customer:dana@example.com', 'accept', ?, 'sig')",
+        )
+        .bind(format!("{quote_id}-accept"))
+        .bind(quote_id)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("ledger");
+    }
+
+    fn signatory(party: SignatoryParty, name: &str) -> Signatory {
+        Signatory { party, name: name.to_owned(), title: None, email: None, signed_at: None }
+    }
+
+    fn request(quote_id: &str) -> GenerateOrderForm {
+        GenerateOrderForm {
+            quote_id: quote_id.to_owned(),
+            template: TemplateSelection::default(),
+            billing_frequency: BillingFrequency::Quarterly,
+            start_date: None,
+            term_months: None,
+            signatories: vec![signatory(SignatoryParty::Customer, "Dana Reyes")],
+            clauses: vec![ClauseSelection { key: "msa".to_owned(), version: None }],
+            actor: "rep".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn accepted_quotes_generate_order_forms_that_are_signed_in_order() {
+        let pool = pool().await;
+        seed_quote(&pool, "Q-SENT", "sent", "acct-1").await;
+        seed_quote(&pool, "Q-1", "accepted", "acct-1").await;
+        let service = OrderFormService::new(pool.clone());
+
+        let unsigned = service.generate(request("Q-SENT")).await;
+        assert!(matches!(unsigned, Err(OrderFormServiceError::QuoteNotAccepted { .. })));
+        let unknown_clause = service.generate(request("Q-1")).await;
+        assert_eq!(unknown_clause.expect_err("no clause yet").code(), "invalid_template");
+
+        QuoteTemplateService::new(pool.clone())
+            .save_clause(SaveClause {
+                key: "msa".into(),
+                title: "Master Services Agreement".into(),
+                body: "Governed by the MSA.".into(),
+                actor: "legal".into(),
+            })
+            .await
+            .expect("clause");
+        let order = service.generate(request("Q-1")).await.expect("generate");
+        assert_eq!(order.status, OrderFormStatus::Draft);
+        assert_eq!(order.quote_ledger_entry_id.as_deref(), Some("Q-1-accept"));
+        assert_eq!(order.content.quote_version, 2);
+        assert_eq!(order.content.total, Decimal::from(9_000));
+        assert_eq!(order.content.start_date.to_string(), "2026-03-01");
+        assert_eq!(order.content.billing_schedule.len(), 4);
+        assert_eq!(order.content.document.title.as_deref(), Some("Order Form"));
+        assert!(matches!(
+            order.content.document.sections.as_slice(),
+            [.., DocumentSection::Clause { key, .. }, DocumentSection::BillingSchedule,
+                DocumentSection::Signatures] if key == "msa"
+        ));
+        let again = service.generate(request("Q-1")).await;
+        assert!(matches!(again, Err(OrderFormServiceError::AlreadyGenerated { .. })));
+
+        let early = service.transition(&order.id.0, OrderFormStatus::Signed, None).await;
+        assert!(matches!(
+            early,
+            Err(OrderFormServiceError::Invalid(OrderFormError::InvalidTransition { .. }))
+        ));
+        service.transition(&order.id.0, OrderFormStatus::Sent, None).await.expect("send");
+        let edit = sqlx::query("UPDATE order_form SET content_json = '{}' WHERE id = ?")
+            .bind(&order.id.0)
+            .execute(&pool)
+            .await;
+        assert!(edit.is_err(), "content is frozen once sent");
+
+        let signed =
+            service.transition(&order.id.0, OrderFormStatus::Signed, None).await.expect("sign");
+        assert!(signed.signatories[0].signed_at.is_some() && signed.signed_at.is_some());
+        let no_provider =
+            service.transition(&order.id.0, OrderFormStatus::Countersigned, None).await;
+        assert!(matches!(
+            no_provider,
+            Err(OrderFormServiceError::Invalid(OrderFormError::MissingSignatory(
+                SignatoryParty::Provider
+            )))
+        ));
+        let countersigned = service
+            .transition(
+                &order.id.0,
+                OrderFormStatus::Countersigned,
+                Some(signatory(SignatoryParty::Provider, "Sam Ortiz")),
+            )
+            .await
+            .expect("countersign");
+        assert_eq!(countersigned.signatories.len(), 2);
+        assert_eq!(countersigned.content_sha256, order.content_sha256);
+    }
+
+    #[tokio::test]
+    async fn amendments_reference_the_original_countersigned_order() {
+        let pool = pool().await;
+        seed_quote(&pool, "Q-1", "accepted", "acct-1").await;
+        seed_quote(&pool, "Q-2", "accepted", "acct-1").await;
+        seed_quote(&pool, "Q-3", "accepted", "acct-1").await;
+        seed_quote(&pool, "Q-OTHER", "accepted", "acct-2").await;
+        let service = OrderFormService::new(pool.clone());
+        let no_clauses =
+            |quote_id: &str| GenerateOrderForm { clauses: Vec::new(), ..request(quote_id) };
+
+        let original = service.generate(no_clauses("Q-1")).await.expect("generate");
+        let early = service.amend(&original.id.0, no_clauses("Q-2")).await;
+        assert!(matches!(early, Err(OrderFormServiceError::NotAmendable { .. })));
+        for (status, signer) in [
+            (OrderFormStatus::Sent, None),
+            (OrderFormStatus::Signed, None),
+            (OrderFormStatus::Countersigned, Some(signatory(SignatoryParty::Provider, "Sam"))),
+        ] {
+            service.transition(&original.id.0, status, signer).await.expect("transition");
+        }
+
+        let other = service.amend(&original.id.0, no_clauses("Q-OTHER")).await;
+        assert!(matches!(other, Err(OrderFormServiceError::AccountMismatch { .. })));
+        let first = service.amend(&original.id.0, no_clauses("Q-2")).await.expect("amend");
+        assert_eq!(first.amends_order_id.as_ref(), Some(&original.id));
+        assert_eq!(first.amendment_number, 1);
+        assert_eq!(first.content.document.title.as_deref(), Some("Order Form Amendment 1"));
+
+        let second = service.amend(&first.id.0, no_clauses("Q-3")).await.expect("amend again");
+        assert_eq!(second.amends_order_id.as_ref(), Some(&original.id));
+        assert_eq!(second.amendment_number, 2);
+        let amendments = service.amendments(&original.id.0).await.expect("amendments");
+        assert_eq!(
+            amendments.iter().map(|order| order.id.clone()).collect::<Vec<_>>(),
+            [first.id, second.id]
+        );
+    }
+}
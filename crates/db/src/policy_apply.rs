@@ -0,0 +1,1317 @@
+//! Versioned live policy thresholds and canary rollout of approved optimizer candidates.
+//!
+//! Every distinct set of live [`PolicyThresholds`] is a row in `policy_set_version`, and exactly
+//! one row is `active`. The threshold org settings remain the place admins edit policy: whenever
+//! their raw values differ from the ones the active version was built from, the next read folds
+//! them into a new active version. Approved candidates never go straight to the live set.
+//! [`PolicyApplyService::apply_canary`] signs the apply through the optimizer lifecycle engine and
+//! stores the candidate's thresholds as a `canary` version that only the canary cohort (reps and
+//! customer segments) is evaluated against. [`PolicyApplyService::evaluate_canary`] then scores
+//! the negotiation sessions opened under the canary version with [`compute_kpis`] and
+//! [`run_rollout_gate`]; critical pricing anomalies on quotes priced under it count as policy
+//! breach incidents. A passing gate with enough sessions promotes the canary (writing its
+//! thresholds back to org settings), a failing one rolls it back with a signed rollback record.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use quotey_core::chrono::Utc;
+use quotey_core::cpq::policy::{PolicyThresholds, ThresholdDiffError};
+use quotey_core::cpq::rollout::{run_rollout_gate, RolloutReadinessReport};
+use quotey_core::cpq::telemetry::{compute_kpis, GoNoGoDecision, KpiReport};
+use quotey_core::domain::optimizer::{
+    ApprovalDecisionKind, PolicyApplyRecordId, PolicyApprovalDecision, PolicyCandidate,
+    PolicyCandidateId, PolicyCandidateStatus, PolicyLifecycleAuditEvent,
+    PolicyLifecycleAuditEventType, PolicyLifecycleAuditId,
+};
+use quotey_core::policy::optimizer::{
+    ApprovalPacket, ApprovalPacketActionPayload, InMemoryPolicyLifecycleEngine, PolicyApplyRequest,
+    PolicyLifecycleError, PolicyRollbackRequest,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{sqlite::SqliteRow, Row};
+use thiserror::Error;
+
+use crate::repositories::{
+    OrgSettingsRepository, PolicyOptimizerRepository, RepositoryError, SqlNegotiationRepository,
+    SqlOrgSettingsRepository, SqlPolicyOptimizerRepository, SqlPricingBaselineRepository,
+};
+use crate::DbPool;
+
+const MANAGER_DISCOUNT_SETTING: &str = "require_manager_approval_above_discount_pct";
+const FINANCE_DEAL_VALUE_SETTING: &str = "require_finance_approval_above_deal_value_cents";
+const AUTO_APPROVE_SETTING: &str = "auto_approve_standard_pricing";
+const THRESHOLD_SETTINGS: [&str; 3] =
+    [MANAGER_DISCOUNT_SETTING, FINANCE_DEAL_VALUE_SETTING, AUTO_APPROVE_SETTING];
+
+/// Negotiation sessions a canary needs before the gate may promote it.
+pub const DEFAULT_CANARY_MIN_SESSIONS: usize = 20;
+/// Actor recorded for decisions taken by [`PolicyApplyService::evaluate_canary`].
+pub const CANARY_MONITOR_ACTOR: &str = "policy-canary-monitor";
+
+const VERSION_SELECT: &str = "SELECT version, status, source, thresholds_json, org_settings_json,
+        candidate_id, parent_version, created_by, created_at, activated_at, retired_at
+    FROM policy_set_version";
+const CANARY_SELECT: &str = "SELECT id, candidate_id, apply_record_id, base_version,
+        canary_version, rep_ids_json, segment_keys_json, min_sessions, status, packet_json,
+        action_json, signature_key_id, kpi_report_json, readiness_json, decision_reason,
+        decided_by, started_at, last_evaluated_at, decided_at
+    FROM policy_canary";
+
+/// Row in `policy_set_version`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PolicySetVersion {
+    pub version: i32,
+    /// `active`, `canary`, `superseded` or `rolled_back`.
+    pub status: String,
+    /// `org_settings` or `candidate`.
+    pub source: String,
+    pub thresholds: PolicyThresholds,
+    pub candidate_id: Option<String>,
+    /// Version that was active when this one was created.
+    pub parent_version: Option<i32>,
+    pub created_by: String,
+    pub created_at: String,
+    pub activated_at: Option<String>,
+    pub retired_at: Option<String>,
+}
+
+/// Reps and customer segments evaluated against a canary version.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CanaryCohort {
+    pub rep_ids: Vec<String>,
+    pub segment_keys: Vec<String>,
+}
+
+impl CanaryCohort {
+    fn normalized(self) -> Self {
+        let mut rep_ids: Vec<String> = self
+            .rep_ids
+            .iter()
+            .map(|rep| rep.trim().to_string())
+            .filter(|rep| !rep.is_empty())
+            .collect();
+        rep_ids.sort();
+        rep_ids.dedup();
+        let mut segment_keys: Vec<String> = self
+            .segment_keys
+            .iter()
+            .map(|segment| normalize_segment(segment))
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        segment_keys.sort();
+        segment_keys.dedup();
+        Self { rep_ids, segment_keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rep_ids.is_empty() && self.segment_keys.is_empty()
+    }
+
+    /// Whether a quote owned by `rep_id` for an account in `segment` belongs to the cohort.
+    pub fn contains(&self, rep_id: Option<&str>, segment: Option<&str>) -> bool {
+        rep_id.is_some_and(|rep| self.rep_ids.iter().any(|member| member == rep.trim()))
+            || segment.is_some_and(|segment| {
+                let segment = normalize_segment(segment);
+                self.segment_keys.contains(&segment)
+            })
+    }
+}
+
+/// Row in `policy_canary`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PolicyCanary {
+    pub id: String,
+    pub candidate_id: String,
+    pub apply_record_id: String,
+    pub base_version: i32,
+    pub canary_version: i32,
+    pub cohort: CanaryCohort,
+    pub min_sessions: usize,
+    /// `monitoring`, `promoted` or `rolled_back`.
+    pub status: String,
+    pub signature_key_id: String,
+    /// KPI report from the latest evaluation.
+    pub kpi_report: Option<KpiReport>,
+    /// Rollout gate result from the latest evaluation.
+    pub readiness: Option<RolloutReadinessReport>,
+    pub decision_reason: Option<String>,
+    pub decided_by: Option<String>,
+    pub started_at: String,
+    pub last_evaluated_at: Option<String>,
+    pub decided_at: Option<String>,
+}
+
+/// Thresholds a quote is evaluated against and the version they belong to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResolvedPolicy {
+    pub version: i32,
+    pub thresholds: PolicyThresholds,
+    /// Set when the quote's rep or segment is enrolled in the monitoring canary.
+    pub canary_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryDecision {
+    /// Not enough sessions yet; the canary keeps running.
+    Monitoring,
+    Promoted,
+    RolledBack,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CanaryEvaluation {
+    pub decision: CanaryDecision,
+    pub canary: PolicyCanary,
+}
+
+/// Key that signs apply and rollback records.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PolicySigner {
+    pub key_id: String,
+    pub secret: String,
+}
+
+impl PolicySigner {
+    pub fn new(key_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self { key_id: key_id.into(), secret: secret.into() }
+    }
+
+    /// Reads `QUOTEY_POLICY_SIGNING_KEY` and, optionally, `QUOTEY_POLICY_SIGNING_KEY_ID`
+    /// (default `quotey-policy`). `None` when no signing key is configured.
+    pub fn from_env() -> Option<Self> {
+        let secret = std::env::var("QUOTEY_POLICY_SIGNING_KEY")
+            .ok()
+            .filter(|secret| !secret.trim().is_empty())?;
+        let key_id = std::env::var("QUOTEY_POLICY_SIGNING_KEY_ID")
+            .ok()
+            .filter(|key_id| !key_id.trim().is_empty())
+            .unwrap_or_else(|| "quotey-policy".to_string());
+        Some(Self { key_id, secret })
+    }
+}
+
+impl fmt::Debug for PolicySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolicySigner").field("key_id", &self.key_id).finish_non_exhaustive()
+    }
+}
+
+/// An approved candidate and the cohort it should be tried on.
+#[derive(Clone, Debug)]
+pub struct CanaryApplyRequest {
+    pub packet: ApprovalPacket,
+    pub action: ApprovalPacketActionPayload,
+    /// Empty means the candidate's own cohort segments.
+    pub cohort: CanaryCohort,
+    pub min_sessions: usize,
+    pub actor_id: String,
+}
+
+#[derive(Debug, Error)]
+pub enum PolicyApplyError {
+    #[error(transparent)]
+    Lifecycle(#[from] PolicyLifecycleError),
+    #[error(transparent)]
+    Thresholds(#[from] ThresholdDiffError),
+    #[error("canary cohort needs at least one rep or customer segment")]
+    EmptyCohort,
+    #[error("proposed policy version {proposed} must be greater than the latest version {latest}")]
+    StaleProposedVersion { proposed: i32, latest: i32 },
+    #[error("canary `{0}` is still monitoring; promote or roll it back first")]
+    CanaryInProgress(String),
+    #[error("no policy canary is monitoring")]
+    NoActiveCanary,
+    #[error("canary `{0}` failed its rollout gate but no policy signing key is configured")]
+    SignerRequired(String),
+    #[error("stored policy data is unreadable: {0}")]
+    Corrupt(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for PolicyApplyError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+/// Label recorded as `policy_version` on negotiation sessions evaluated against `version`.
+pub fn policy_version_label(version: i32) -> String {
+    format!("policy-v{version}")
+}
+
+pub struct PolicyApplyService {
+    pool: DbPool,
+}
+
+impl PolicyApplyService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// The live policy version, after folding in any edits to the threshold org settings.
+    pub async fn active_version(&self) -> Result<PolicySetVersion, PolicyApplyError> {
+        let settings = self.threshold_settings().await?;
+        let settings_json = encode(&settings)?;
+
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(&format!("{VERSION_SELECT} WHERE status = 'active'"))
+            .fetch_optional(&mut *tx)
+            .await?;
+        let previous = match &row {
+            Some(row) => {
+                let recorded: String = row.try_get("org_settings_json")?;
+                let version = version_from_row(row)?;
+                if recorded == settings_json {
+                    return Ok(version);
+                }
+                Some(version)
+            }
+            None => None,
+        };
+
+        let base = previous.as_ref().map(|version| version.thresholds.clone()).unwrap_or_default();
+        let thresholds = thresholds_from_settings(base, &settings);
+        let version: i32 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) + 1 FROM policy_set_version")
+                .fetch_one(&mut *tx)
+                .await?;
+        let now = Utc::now().to_rfc3339();
+        if let Some(previous) = &previous {
+            sqlx::query(
+                "UPDATE policy_set_version SET status = 'superseded', retired_at = ?
+                 WHERE version = ?",
+            )
+            .bind(&now)
+            .bind(previous.version)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            "INSERT INTO policy_set_version
+                (version, status, source, thresholds_json, org_settings_json, parent_version,
+                 created_by, created_at, activated_at)
+             VALUES (?, 'active', 'org_settings', ?, ?, ?, 'org_settings', ?, ?)",
+        )
+        .bind(version)
+        .bind(encode(&thresholds)?)
+        .bind(&settings_json)
+        .bind(previous.as_ref().map(|previous| previous.version))
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.find_version(version).await?.ok_or_else(|| missing_version(version))
+    }
+
+    /// All versions, newest first.
+    pub async fn list_versions(&self) -> Result<Vec<PolicySetVersion>, PolicyApplyError> {
+        let rows = sqlx::query(&format!("{VERSION_SELECT} ORDER BY version DESC"))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(version_from_row).collect()
+    }
+
+    pub async fn find_version(
+        &self,
+        version: i32,
+    ) -> Result<Option<PolicySetVersion>, PolicyApplyError> {
+        let row = sqlx::query(&format!("{VERSION_SELECT} WHERE version = ?"))
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    /// The canary currently being monitored, if any.
+    pub async fn monitoring_canary(&self) -> Result<Option<PolicyCanary>, PolicyApplyError> {
+        let row = sqlx::query(&format!("{CANARY_SELECT} WHERE status = 'monitoring'"))
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(canary_from_row).transpose()
+    }
+
+    /// Most recent canaries, newest first.
+    pub async fn list_canaries(&self, limit: u32) -> Result<Vec<PolicyCanary>, PolicyApplyError> {
+        let rows =
+            sqlx::query(&format!("{CANARY_SELECT} ORDER BY started_at DESC, id DESC LIMIT ?"))
+                .bind(i64::from(limit))
+                .fetch_all(&self.pool)
+                .await?;
+        rows.iter().map(canary_from_row).collect()
+    }
+
+    /// Thresholds for a quote owned by `rep_id` on an account in `segment`: the canary's when
+    /// either is enrolled in the monitoring canary, the active version's otherwise.
+    pub async fn resolve(
+        &self,
+        rep_id: Option<&str>,
+        segment: Option<&str>,
+    ) -> Result<ResolvedPolicy, PolicyApplyError> {
+        let active = self.active_version().await?;
+        if let Some(canary) = self.monitoring_canary().await? {
+            if canary.cohort.contains(rep_id, segment) {
+                let version = self
+                    .find_version(canary.canary_version)
+                    .await?
+                    .ok_or_else(|| missing_version(canary.canary_version))?;
+                return Ok(ResolvedPolicy {
+                    version: version.version,
+                    thresholds: version.thresholds,
+                    canary_id: Some(canary.id),
+                });
+            }
+        }
+        Ok(ResolvedPolicy {
+            version: active.version,
+            thresholds: active.thresholds,
+            canary_id: None,
+        })
+    }
+
+    /// [`Self::resolve`] for a stored quote: its owning rep and its account's customer segment.
+    pub async fn resolve_for_quote(
+        &self,
+        quote_id: &str,
+    ) -> Result<ResolvedPolicy, PolicyApplyError> {
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT COALESCE(created_by_sales_rep_id, created_by), account_id
+             FROM quote WHERE id = ?",
+        )
+        .bind(quote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (rep_id, account_id) = row.unwrap_or_default();
+        let segment = match account_id {
+            Some(account_id) => {
+                SqlPricingBaselineRepository::new(self.pool.clone())
+                    .segment_for_account(&account_id)
+                    .await?
+            }
+            None => None,
+        };
+        self.resolve(rep_id.as_deref(), segment.as_deref()).await
+    }
+
+    /// Applies an approved candidate to its canary cohort as a new `canary` version.
+    ///
+    /// The packet must be based on the active version and propose a version number that has not
+    /// been used. The signed apply record, approval decision and lifecycle audit events are
+    /// stored through [`SqlPolicyOptimizerRepository`] and the candidate moves to `monitoring`.
+    pub async fn apply_canary(
+        &self,
+        request: CanaryApplyRequest,
+        signer: &PolicySigner,
+    ) -> Result<PolicyCanary, PolicyApplyError> {
+        let CanaryApplyRequest { packet, action, cohort, min_sessions, actor_id } = request;
+        let cohort = if cohort.is_empty() {
+            CanaryCohort {
+                rep_ids: Vec::new(),
+                segment_keys: packet.candidate_diff.cohort_scope.segment_keys.clone(),
+            }
+        } else {
+            cohort
+        }
+        .normalized();
+        if cohort.is_empty() {
+            return Err(PolicyApplyError::EmptyCohort);
+        }
+        if let Some(canary) = self.monitoring_canary().await? {
+            return Err(PolicyApplyError::CanaryInProgress(canary.id));
+        }
+
+        let active = self.active_version().await?;
+        let latest: i32 =
+            sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM policy_set_version")
+                .fetch_one(&self.pool)
+                .await?;
+        if packet.proposed_policy_version <= latest {
+            return Err(PolicyApplyError::StaleProposedVersion {
+                proposed: packet.proposed_policy_version,
+                latest,
+            });
+        }
+        let thresholds = active.thresholds.with_rule_diffs(&packet.candidate_diff.rule_diffs)?;
+
+        let outcome =
+            InMemoryPolicyLifecycleEngine::new(active.version).apply(PolicyApplyRequest {
+                packet: packet.clone(),
+                action: action.clone(),
+                actor_id: actor_id.clone(),
+                signature_key_id: signer.key_id.clone(),
+                signing_secret: signer.secret.clone(),
+                idempotency_key_override: None,
+            })?;
+        let apply_record = outcome.apply_record;
+        let now = Utc::now();
+
+        let optimizer = SqlPolicyOptimizerRepository::new(self.pool.clone());
+        let mut candidate = match optimizer.get_candidate(&packet.candidate_id).await? {
+            Some(candidate) => candidate,
+            None => candidate_from_packet(&packet, &apply_record.actor_id)?,
+        };
+        candidate.status = PolicyCandidateStatus::Monitoring;
+        candidate.latest_replay_checksum = Some(apply_record.replay_checksum.clone());
+        candidate.approved_at = candidate.approved_at.or(Some(now));
+        candidate.applied_at = Some(now);
+        candidate.monitoring_started_at = Some(now);
+        candidate.rolled_back_at = None;
+        candidate.updated_at = now;
+        optimizer.save_candidate(candidate).await?;
+        optimizer
+            .save_approval_decision(PolicyApprovalDecision {
+                id: apply_record.approval_decision_id.clone(),
+                candidate_id: packet.candidate_id.clone(),
+                replay_evaluation_id: None,
+                decision: ApprovalDecisionKind::Approved,
+                reason: action.reason.clone(),
+                decision_payload_json: apply_record.apply_audit_json.clone(),
+                actor_id: apply_record.actor_id.clone(),
+                actor_role: "policy_reviewer".to_string(),
+                channel_ref: None,
+                signature: None,
+                signature_key_id: Some(apply_record.signature_key_id.clone()),
+                idempotency_key: action.idempotency_key.clone(),
+                decided_at: now,
+                expires_at: None,
+                is_stale: false,
+            })
+            .await?;
+        optimizer.save_apply_record(apply_record.clone()).await?;
+        optimizer
+            .append_lifecycle_audit_event(PolicyLifecycleAuditEvent {
+                actor_type: "user".to_string(),
+                ..outcome.audit_event
+            })
+            .await?;
+        optimizer
+            .append_lifecycle_audit_event(PolicyLifecycleAuditEvent {
+                id: PolicyLifecycleAuditId(format!(
+                    "audit:monitoring:{}",
+                    apply_record.idempotency_key
+                )),
+                candidate_id: packet.candidate_id.clone(),
+                replay_evaluation_id: None,
+                approval_decision_id: None,
+                apply_record_id: Some(apply_record.id.clone()),
+                rollback_record_id: None,
+                event_type: PolicyLifecycleAuditEventType::MonitoringStarted,
+                event_payload_json: serde_json::json!({
+                    "canary_version": packet.proposed_policy_version,
+                    "base_version": active.version,
+                    "rep_ids": cohort.rep_ids,
+                    "segment_keys": cohort.segment_keys,
+                    "min_sessions": min_sessions,
+                })
+                .to_string(),
+                actor_type: "user".to_string(),
+                actor_id: apply_record.actor_id.clone(),
+                correlation_id: format!("apply:{}", packet.packet_id),
+                idempotency_key: Some(apply_record.idempotency_key.clone()),
+                occurred_at: now,
+            })
+            .await?;
+
+        let canary_id = format!("canary-{}", sqlx::types::Uuid::new_v4());
+        let started_at = now.to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO policy_set_version
+                (version, status, source, thresholds_json, candidate_id, parent_version,
+                 created_by, created_at)
+             VALUES (?, 'canary', 'candidate', ?, ?, ?, ?, ?)",
+        )
+        .bind(packet.proposed_policy_version)
+        .bind(encode(&thresholds)?)
+        .bind(&packet.candidate_id.0)
+        .bind(active.version)
+        .bind(&apply_record.actor_id)
+        .bind(&started_at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO policy_canary
+                (id, candidate_id, apply_record_id, base_version, canary_version, rep_ids_json,
+                 segment_keys_json, min_sessions, status, packet_json, action_json,
+                 signature_key_id, started_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'monitoring', ?, ?, ?, ?)",
+        )
+        .bind(&canary_id)
+        .bind(&packet.candidate_id.0)
+        .bind(&apply_record.id.0)
+        .bind(active.version)
+        .bind(packet.proposed_policy_version)
+        .bind(encode(&cohort.rep_ids)?)
+        .bind(encode(&cohort.segment_keys)?)
+        .bind(i64::try_from(min_sessions.max(1)).unwrap_or(i64::MAX))
+        .bind(encode(&packet)?)
+        .bind(&apply_record.apply_audit_json)
+        .bind(&apply_record.signature_key_id)
+        .bind(&started_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.find_canary(&canary_id).await
+    }
+
+    /// Scores the monitoring canary and promotes or rolls it back when the evidence is in.
+    ///
+    /// A `NoGo` KPI decision rolls back immediately. Otherwise the canary keeps monitoring until
+    /// it has `min_sessions` sessions, then promotes if the rollout gate is ready and rolls back
+    /// if not. Rolling back needs `signer`; without one the evaluation is stored and
+    /// [`PolicyApplyError::SignerRequired`] returned.
+    pub async fn evaluate_canary(
+        &self,
+        signer: Option<&PolicySigner>,
+    ) -> Result<Option<CanaryEvaluation>, PolicyApplyError> {
+        let Some(canary) = self.monitoring_canary().await? else {
+            return Ok(None);
+        };
+        let kpi_report = self.canary_kpis(&canary).await?;
+        let readiness = run_rollout_gate(Some(&kpi_report));
+        sqlx::query(
+            "UPDATE policy_canary
+             SET kpi_report_json = ?, readiness_json = ?, last_evaluated_at = ?
+             WHERE id = ?",
+        )
+        .bind(encode(&kpi_report)?)
+        .bind(encode(&readiness)?)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&canary.id)
+        .execute(&self.pool)
+        .await?;
+
+        let failure = if kpi_report.go_no_go == GoNoGoDecision::NoGo {
+            let missed: Vec<&str> = kpi_report
+                .measurements
+                .iter()
+                .filter(|measurement| !measurement.meets_target)
+                .map(|measurement| measurement.metric.as_str())
+                .collect();
+            Some(format!("KPI gate returned no-go ({})", missed.join(", ")))
+        } else if kpi_report.session_count < canary.min_sessions {
+            let canary = self.find_canary(&canary.id).await?;
+            return Ok(Some(CanaryEvaluation { decision: CanaryDecision::Monitoring, canary }));
+        } else if readiness.ready {
+            None
+        } else {
+            let failed: Vec<&str> = readiness
+                .checks
+                .iter()
+                .filter(|check| !check.passed)
+                .map(|check| check.name.as_str())
+                .collect();
+            Some(format!("rollout gate not ready ({})", failed.join(", ")))
+        };
+
+        let (decision, canary) = match failure {
+            None => {
+                let reason =
+                    format!("rollout gate ready after {} sessions", kpi_report.session_count);
+                let canary =
+                    self.finish_promotion(canary, CANARY_MONITOR_ACTOR, "system", &reason).await?;
+                (CanaryDecision::Promoted, canary)
+            }
+            Some(reason) => {
+                let signer =
+                    signer.ok_or_else(|| PolicyApplyError::SignerRequired(canary.id.clone()))?;
+                let canary = self
+                    .finish_rollback(canary, CANARY_MONITOR_ACTOR, "system", &reason, signer)
+                    .await?;
+                (CanaryDecision::RolledBack, canary)
+            }
+        };
+        Ok(Some(CanaryEvaluation { decision, canary }))
+    }
+
+    /// Promotes the monitoring canary to the active version regardless of its KPIs.
+    pub async fn promote(
+        &self,
+        actor_id: &str,
+        reason: &str,
+    ) -> Result<PolicyCanary, PolicyApplyError> {
+        let canary = self.monitoring_canary().await?.ok_or(PolicyApplyError::NoActiveCanary)?;
+        self.finish_promotion(canary, actor_id, "user", reason).await
+    }
+
+    /// Rolls back the monitoring canary; its cohort returns to the active version.
+    pub async fn rollback(
+        &self,
+        actor_id: &str,
+        reason: &str,
+        signer: &PolicySigner,
+    ) -> Result<PolicyCanary, PolicyApplyError> {
+        let canary = self.monitoring_canary().await?.ok_or(PolicyApplyError::NoActiveCanary)?;
+        self.finish_rollback(canary, actor_id, "user", reason, signer).await
+    }
+
+    async fn find_canary(&self, id: &str) -> Result<PolicyCanary, PolicyApplyError> {
+        let row = sqlx::query(&format!("{CANARY_SELECT} WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| PolicyApplyError::Corrupt(format!("canary `{id}` disappeared")))?;
+        canary_from_row(&row)
+    }
+
+    async fn canary_kpis(&self, canary: &PolicyCanary) -> Result<KpiReport, PolicyApplyError> {
+        let sessions = SqlNegotiationRepository::find_sessions_by_policy_version(
+            &self.pool,
+            &policy_version_label(canary.canary_version),
+        )
+        .await?;
+        let mut turns_by_session = Vec::with_capacity(sessions.len());
+        for session in &sessions {
+            turns_by_session.push(
+                SqlNegotiationRepository::find_turns_by_session(&self.pool, &session.id.0).await?,
+            );
+        }
+
+        let packet_json: String =
+            sqlx::query_scalar("SELECT packet_json FROM policy_canary WHERE id = ?")
+                .bind(&canary.id)
+                .fetch_one(&self.pool)
+                .await?;
+        let packet: ApprovalPacket = decode(&packet_json, "approval packet")?;
+        let replay_determinism_rate =
+            if packet.replay_report.deterministic_pass { 100.0 } else { 0.0 };
+        let breach_incidents: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM quote_pricing_snapshot
+             WHERE policy_version = ? AND json_extract(anomaly_json, '$.severity') = 'Critical'",
+        )
+        .bind(canary.canary_version)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(compute_kpis(
+            &sessions,
+            &turns_by_session,
+            replay_determinism_rate,
+            u32::try_from(breach_incidents).unwrap_or(u32::MAX),
+        ))
+    }
+
+    async fn finish_promotion(
+        &self,
+        canary: PolicyCanary,
+        actor_id: &str,
+        actor_type: &str,
+        reason: &str,
+    ) -> Result<PolicyCanary, PolicyApplyError> {
+        let version = self
+            .find_version(canary.canary_version)
+            .await?
+            .ok_or_else(|| missing_version(canary.canary_version))?;
+        let settings = settings_from_thresholds(&version.thresholds);
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+        for (key, value_json) in &settings {
+            sqlx::query(
+                "INSERT INTO org_settings (key, value_json, updated_at, updated_by)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT(key) DO UPDATE SET
+                    value_json = excluded.value_json,
+                    updated_at = excluded.updated_at,
+                    updated_by = excluded.updated_by",
+            )
+            .bind(key)
+            .bind(value_json)
+            .bind(&now)
+            .bind(actor_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            "UPDATE policy_set_version SET status = 'superseded', retired_at = ?
+             WHERE status = 'active'",
+        )
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE policy_set_version
+             SET status = 'active', activated_at = ?, org_settings_json = ?
+             WHERE version = ?",
+        )
+        .bind(&now)
+        .bind(encode(&settings)?)
+        .bind(canary.canary_version)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE policy_canary
+             SET status = 'promoted', decision_reason = ?, decided_by = ?, decided_at = ?
+             WHERE id = ?",
+        )
+        .bind(reason)
+        .bind(actor_id)
+        .bind(&now)
+        .bind(&canary.id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let optimizer = SqlPolicyOptimizerRepository::new(self.pool.clone());
+        let candidate_id = PolicyCandidateId(canary.candidate_id.clone());
+        if let Some(mut candidate) = optimizer.get_candidate(&candidate_id).await? {
+            candidate.status = PolicyCandidateStatus::Applied;
+            candidate.updated_at = Utc::now();
+            optimizer.save_candidate(candidate).await?;
+        }
+        let idempotency_key = format!("{}:promote", canary.apply_record_id);
+        optimizer
+            .append_lifecycle_audit_event(PolicyLifecycleAuditEvent {
+                id: PolicyLifecycleAuditId(format!("audit:promote:{}", canary.apply_record_id)),
+                candidate_id,
+                replay_evaluation_id: None,
+                approval_decision_id: None,
+                apply_record_id: Some(PolicyApplyRecordId(canary.apply_record_id.clone())),
+                rollback_record_id: None,
+                event_type: PolicyLifecycleAuditEventType::Applied,
+                event_payload_json: serde_json::json!({
+                    "promoted_version": canary.canary_version,
+                    "canary_id": canary.id,
+                    "reason": reason,
+                })
+                .to_string(),
+                actor_type: actor_type.to_string(),
+                actor_id: actor_id.to_string(),
+                correlation_id: format!("canary:{}", canary.id),
+                idempotency_key: Some(idempotency_key),
+                occurred_at: Utc::now(),
+            })
+            .await?;
+
+        self.find_canary(&canary.id).await
+    }
+
+    async fn finish_rollback(
+        &self,
+        canary: PolicyCanary,
+        actor_id: &str,
+        actor_type: &str,
+        reason: &str,
+        signer: &PolicySigner,
+    ) -> Result<PolicyCanary, PolicyApplyError> {
+        let (packet_json, action_json): (String, String) =
+            sqlx::query_as("SELECT packet_json, action_json FROM policy_canary WHERE id = ?")
+                .bind(&canary.id)
+                .fetch_one(&self.pool)
+                .await?;
+        let packet: ApprovalPacket = decode(&packet_json, "approval packet")?;
+        let action = ApprovalPacketActionPayload::parse_json(&action_json)
+            .map_err(|error| PolicyApplyError::Corrupt(format!("approval action: {error}")))?;
+
+        // The lifecycle engine only rolls back applies it has seen, so replay the stored apply
+        // against the base version first; it reproduces the same apply record id.
+        let mut engine = InMemoryPolicyLifecycleEngine::new(canary.base_version);
+        engine.apply(PolicyApplyRequest {
+            packet,
+            action,
+            actor_id: actor_id.to_string(),
+            signature_key_id: signer.key_id.clone(),
+            signing_secret: signer.secret.clone(),
+            idempotency_key_override: None,
+        })?;
+        let outcome = engine.rollback(PolicyRollbackRequest {
+            apply_record_id: PolicyApplyRecordId(canary.apply_record_id.clone()),
+            candidate_id: PolicyCandidateId(canary.candidate_id.clone()),
+            rollback_reason: reason.to_string(),
+            actor_id: actor_id.to_string(),
+            signature_key_id: signer.key_id.clone(),
+            signing_secret: signer.secret.clone(),
+            idempotency_key: format!("{}:rollback", canary.apply_record_id),
+        })?;
+
+        let optimizer = SqlPolicyOptimizerRepository::new(self.pool.clone());
+        optimizer.save_rollback_record(outcome.rollback_record.clone()).await?;
+        optimizer
+            .append_lifecycle_audit_event(PolicyLifecycleAuditEvent {
+                actor_type: actor_type.to_string(),
+                ..outcome.audit_event
+            })
+            .await?;
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE policy_set_version SET status = 'rolled_back', retired_at = ? WHERE version = ?",
+        )
+        .bind(now.to_rfc3339())
+        .bind(canary.canary_version)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE policy_canary
+             SET status = 'rolled_back', decision_reason = ?, decided_by = ?, decided_at = ?
+             WHERE id = ?",
+        )
+        .bind(reason)
+        .bind(actor_id)
+        .bind(now.to_rfc3339())
+        .bind(&canary.id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if let Some(mut candidate) =
+            optimizer.get_candidate(&PolicyCandidateId(canary.candidate_id.clone())).await?
+        {
+            candidate.status = PolicyCandidateStatus::RolledBack;
+            candidate.rolled_back_at = Some(now);
+            candidate.updated_at = now;
+            optimizer.save_candidate(candidate).await?;
+        }
+
+        self.find_canary(&canary.id).await
+    }
+
+    /// Raw `value_json` of the threshold org settings that are set.
+    async fn threshold_settings(&self) -> Result<BTreeMap<String, String>, PolicyApplyError> {
+        let repo = SqlOrgSettingsRepository::new(self.pool.clone());
+        let mut settings = BTreeMap::new();
+        for key in THRESHOLD_SETTINGS {
+            if let Some(setting) = repo.get(key).await? {
+                settings.insert(key.to_string(), setting.value_json);
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// Overlays the threshold org settings on `base`. Unparseable values are ignored.
+fn thresholds_from_settings(
+    base: PolicyThresholds,
+    settings: &BTreeMap<String, String>,
+) -> PolicyThresholds {
+    let value =
+        |key: &str| settings.get(key).and_then(|raw| serde_json::from_str::<Value>(raw).ok());
+    let mut thresholds = base;
+    // Stored as a fraction (0.10 = 10%); the engine works in percent.
+    if let Some(pct) = value(MANAGER_DISCOUNT_SETTING)
+        .and_then(|value| value.as_f64())
+        .and_then(|fraction| Decimal::from_f64(fraction * 100.0))
+    {
+        thresholds.manager_discount_pct = pct;
+    }
+    match value(FINANCE_DEAL_VALUE_SETTING) {
+        Some(Value::Null) => thresholds.finance_deal_value_cents = None,
+        Some(value) => {
+            if let Some(cents) = value.as_i64() {
+                thresholds.finance_deal_value_cents = Some(cents);
+            }
+        }
+        None => {}
+    }
+    if let Some(auto_approve) = value(AUTO_APPROVE_SETTING).and_then(|value| value.as_bool()) {
+        thresholds.auto_approve_clean = auto_approve;
+    }
+    thresholds
+}
+
+/// Raw org setting values that carry `thresholds`.
+fn settings_from_thresholds(thresholds: &PolicyThresholds) -> BTreeMap<String, String> {
+    let fraction = (thresholds.manager_discount_pct / Decimal::ONE_HUNDRED).normalize();
+    BTreeMap::from([
+        (MANAGER_DISCOUNT_SETTING.to_string(), fraction.to_string()),
+        (
+            FINANCE_DEAL_VALUE_SETTING.to_string(),
+            thresholds
+                .finance_deal_value_cents
+                .map_or_else(|| "null".to_string(), |cents| cents.to_string()),
+        ),
+        (AUTO_APPROVE_SETTING.to_string(), thresholds.auto_approve_clean.to_string()),
+    ])
+}
+
+fn candidate_from_packet(
+    packet: &ApprovalPacket,
+    actor_id: &str,
+) -> Result<PolicyCandidate, PolicyApplyError> {
+    let now = Utc::now();
+    let diff = &packet.candidate_diff;
+    Ok(PolicyCandidate {
+        id: packet.candidate_id.clone(),
+        base_policy_version: packet.base_policy_version,
+        proposed_policy_version: packet.proposed_policy_version,
+        status: PolicyCandidateStatus::Approved,
+        policy_diff_json: encode(diff)?,
+        provenance_json: encode(&diff.provenance)?,
+        confidence_score: diff.confidence_bounds.confidence_score().clamp(0.0, 1.0),
+        cohort_scope_json: encode(&diff.cohort_scope)?,
+        latest_replay_checksum: Some(packet.replay_report.input_checksum.clone()),
+        idempotency_key: format!("candidate:{}", packet.candidate_id.0),
+        created_by_actor_id: actor_id.to_string(),
+        created_at: now,
+        updated_at: now,
+        review_ready_at: None,
+        approved_at: Some(now),
+        applied_at: None,
+        monitoring_started_at: None,
+        rolled_back_at: None,
+    })
+}
+
+fn version_from_row(row: &SqliteRow) -> Result<PolicySetVersion, PolicyApplyError> {
+    let thresholds_json: String = row.try_get("thresholds_json")?;
+    Ok(PolicySetVersion {
+        version: row.try_get("version")?,
+        status: row.try_get("status")?,
+        source: row.try_get("source")?,
+        thresholds: decode(&thresholds_json, "policy thresholds")?,
+        candidate_id: row.try_get("candidate_id")?,
+        parent_version: row.try_get("parent_version")?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+        activated_at: row.try_get("activated_at")?,
+        retired_at: row.try_get("retired_at")?,
+    })
+}
+
+fn canary_from_row(row: &SqliteRow) -> Result<PolicyCanary, PolicyApplyError> {
+    let rep_ids: String = row.try_get("rep_ids_json")?;
+    let segment_keys: String = row.try_get("segment_keys_json")?;
+    let kpi_report: Option<String> = row.try_get("kpi_report_json")?;
+    let readiness: Option<String> = row.try_get("readiness_json")?;
+    let min_sessions: i64 = row.try_get("min_sessions")?;
+    Ok(PolicyCanary {
+        id: row.try_get("id")?,
+        candidate_id: row.try_get("candidate_id")?,
+        apply_record_id: row.try_get("apply_record_id")?,
+        base_version: row.try_get("base_version")?,
+        canary_version: row.try_get("canary_version")?,
+        cohort: CanaryCohort {
+            rep_ids: decode(&rep_ids, "canary rep ids")?,
+            segment_keys: decode(&segment_keys, "canary segment keys")?,
+        },
+        min_sessions: usize::try_from(min_sessions).unwrap_or(1),
+        status: row.try_get("status")?,
+        signature_key_id: row.try_get("signature_key_id")?,
+        kpi_report: kpi_report.map(|raw| decode(&raw, "canary KPI report")).transpose()?,
+        readiness: readiness.map(|raw| decode(&raw, "canary readiness report")).transpose()?,
+        decision_reason: row.try_get("decision_reason")?,
+        decided_by: row.try_get("decided_by")?,
+        started_at: row.try_get("started_at")?,
+        last_evaluated_at: row.try_get("last_evaluated_at")?,
+        decided_at: row.try_get("decided_at")?,
+    })
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<String, PolicyApplyError> {
+    serde_json::to_string(value).map_err(|error| PolicyApplyError::Corrupt(error.to_string()))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(raw: &str, what: &str) -> Result<T, PolicyApplyError> {
+    serde_json::from_str(raw).map_err(|error| PolicyApplyError::Corrupt(format!("{what}: {error}")))
+}
+
+fn missing_version(version: i32) -> PolicyApplyError {
+    PolicyApplyError::Corrupt(format!("policy version {version} is missing"))
+}
+
+fn normalize_segment(raw: &str) -> String {
+    raw.trim().to_ascii_lowercase().replace(['-', ' '], "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::domain::negotiation::{
+        NegotiationSession, NegotiationSessionId, NegotiationState,
+    };
+    use quotey_core::domain::optimizer::{
+        PolicyApplyRecordId, PolicyCandidateId, PolicyCandidateStatus,
+    };
+    use quotey_core::policy::optimizer::{
+        ApprovalPacket, ApprovalPacketActionPayload, ApprovalPacketDecision, PolicyCandidateDiffV1,
+        PolicyReplayEngine, ReplayImpactRequest, ReplayQuoteSnapshot,
+    };
+    use quotey_core::{PricingSnapshot, QuoteId};
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::repositories::SqlPricingSnapshotRepository;
+    use crate::{connect_with_settings, migrations};
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn seed_quote(pool: &DbPool, id: &str, rep: &str) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, account_id, created_by, created_at, updated_at)
+             VALUES (?, 'draft', 'USD', 'acct-1', ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(rep)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("insert quote");
+    }
+
+    async fn seed_session(pool: &DbPool, id: &str, quote_id: &str, policy_version: i32) {
+        let now = Utc::now().to_rfc3339();
+        let session = NegotiationSession {
+            id: NegotiationSessionId(id.to_string()),
+            quote_id: quote_id.to_string(),
+            actor_id: "rep-canary".to_string(),
+            state: NegotiationState::Accepted,
+            policy_version: policy_version_label(policy_version),
+            pricing_version: "pricing-v1".to_string(),
+            idempotency_key: format!("key-{id}"),
+            max_turns: 20,
+            expires_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        SqlNegotiationRepository::save_session(pool, &session).await.expect("save session");
+    }
+
+    /// Approved packet that lowers the manager discount threshold to 18% for SMB.
+    fn approved_packet(base: i32, proposed: i32) -> (ApprovalPacket, ApprovalPacketActionPayload) {
+        let replay_report = PolicyReplayEngine::default()
+            .evaluate(ReplayImpactRequest {
+                candidate_id: PolicyCandidateId("cand-canary".to_string()),
+                base_policy_version: base,
+                proposed_policy_version: proposed,
+                policy_diff_json: "{\"rule\":\"discount-cap\"}".to_string(),
+                cohort_scope_json: "{\"segments\":[\"smb\"]}".to_string(),
+                engine_version: "optimizer-v1".to_string(),
+                expected_input_checksum: None,
+                snapshots: vec![ReplayQuoteSnapshot {
+                    quote_id: "q-1".to_string(),
+                    cohort_id: "cohort-a".to_string(),
+                    segment_key: "smb".to_string(),
+                    impacted_rule_ids: vec!["discount-cap".to_string()],
+                    baseline_margin_bps: 3000,
+                    candidate_margin_bps: 3050,
+                    baseline_win_rate_proxy_bps: 5200,
+                    candidate_win_rate_proxy_bps: 5220,
+                    baseline_approval_required: false,
+                    candidate_approval_required: false,
+                    baseline_hard_violation_count: 0,
+                    candidate_hard_violation_count: 0,
+                }],
+            })
+            .expect("replay");
+        let mut diff: PolicyCandidateDiffV1 = serde_json::from_value(serde_json::json!({
+            "schema_version": "clo_candidate_diff.v1",
+            "candidate_id": "cand-canary",
+            "rule_diffs": [{
+                "rule_id": "discount-cap",
+                "operation": "update",
+                "field": "threshold",
+                "from_value_json": "{\"value\":10}",
+                "to_value_json": "{\"value\":18}",
+                "rationale": "loosen the manager approval trigger"
+            }],
+            "cohort_scope": {
+                "segment_keys": ["smb"],
+                "region_keys": [],
+                "quote_ids": [],
+                "time_window_days": 90
+            },
+            "projected_impact": {
+                "replay_checksum": "",
+                "replay_deterministic_pass": true,
+                "projected_margin_delta_bps": 55,
+                "projected_win_rate_proxy_delta_bps": 20,
+                "projected_approval_load_delta_bps": 0,
+                "projected_hard_violation_delta": 0
+            },
+            "confidence_bounds": { "lower_bps": 6200, "point_estimate_bps": 7100, "upper_bps": 7800 },
+            "provenance": {
+                "source_replay_evaluation_ids": ["replay-1"],
+                "source_outcome_window": "2026-Q3",
+                "generated_by": "test"
+            },
+            "rationale_summary": "canary fixture"
+        }))
+        .expect("candidate diff");
+        diff.projected_impact.replay_checksum = replay_report.input_checksum.clone();
+        let packet =
+            ApprovalPacket::build(diff, replay_report, base, proposed, 1200, "roll back").unwrap();
+        let action =
+            ApprovalPacketActionPayload::new(&packet, ApprovalPacketDecision::Approve, None)
+                .unwrap();
+        (packet, action)
+    }
+
+    fn canary_request(base: i32, proposed: i32, min_sessions: usize) -> CanaryApplyRequest {
+        let (packet, action) = approved_packet(base, proposed);
+        CanaryApplyRequest {
+            packet,
+            action,
+            cohort: CanaryCohort::default(),
+            min_sessions,
+            actor_id: "ops-lead".to_string(),
+        }
+    }
+
+    fn signer() -> PolicySigner {
+        PolicySigner::new("kms-test", "test-secret")
+    }
+
+    #[tokio::test]
+    async fn org_setting_edits_version_the_policy_and_canaries_only_reach_their_cohort() {
+        let pool = setup().await;
+        let service = PolicyApplyService::new(pool.clone());
+
+        let first = service.active_version().await.expect("active");
+        assert_eq!((first.version, first.parent_version), (1, None));
+        assert_eq!(first.thresholds.manager_discount_pct, Decimal::new(10, 0));
+        assert_eq!(first.thresholds.finance_deal_value_cents, Some(10_000_000));
+        assert_eq!(service.active_version().await.expect("active").version, 1);
+
+        SqlOrgSettingsRepository::new(pool.clone())
+            .set(MANAGER_DISCOUNT_SETTING, "0.15", Some("admin"))
+            .await
+            .expect("set");
+        let second = service.active_version().await.expect("active");
+        assert_eq!((second.version, second.parent_version), (2, Some(1)));
+        assert_eq!(second.thresholds.manager_discount_pct, Decimal::new(15, 0));
+        assert_eq!(service.find_version(1).await.unwrap().unwrap().status, "superseded");
+
+        let stale = service.apply_canary(canary_request(2, 2, 5), &signer()).await;
+        assert!(matches!(stale, Err(PolicyApplyError::StaleProposedVersion { .. })));
+        let mut request = canary_request(2, 3, 5);
+        request.cohort.rep_ids = vec!["rep-pilot".to_string()];
+        request.cohort.segment_keys = vec!["SMB".to_string()];
+        let canary = service.apply_canary(request, &signer()).await.expect("apply canary");
+        assert_eq!((canary.base_version, canary.canary_version), (2, 3));
+        assert_eq!(canary.cohort.segment_keys, vec!["smb".to_string()]);
+        assert!(matches!(
+            service.apply_canary(canary_request(2, 4, 5), &signer()).await,
+            Err(PolicyApplyError::CanaryInProgress(_))
+        ));
+
+        let enrolled = service.resolve(Some("rep-other"), Some("smb")).await.expect("resolve");
+        assert_eq!((enrolled.version, enrolled.canary_id.as_ref()), (3, Some(&canary.id)));
+        assert_eq!(enrolled.thresholds.manager_discount_pct, Decimal::new(18, 0));
+        seed_quote(&pool, "Q-PILOT", "rep-pilot").await;
+        assert_eq!(service.resolve_for_quote("Q-PILOT").await.expect("resolve").version, 3);
+        let control = service.resolve(Some("rep-other"), Some("enterprise")).await.unwrap();
+        assert_eq!((control.version, control.canary_id), (2, None));
+
+        let optimizer = SqlPolicyOptimizerRepository::new(pool.clone());
+        let candidate = optimizer
+            .get_candidate(&PolicyCandidateId("cand-canary".to_string()))
+            .await
+            .unwrap()
+            .expect("candidate stored");
+        assert_eq!(candidate.status, PolicyCandidateStatus::Monitoring);
+        let apply = optimizer
+            .get_apply_record(&PolicyApplyRecordId(canary.apply_record_id.clone()))
+            .await
+            .unwrap()
+            .expect("apply record stored");
+        assert_eq!((apply.prior_policy_version, apply.applied_policy_version), (2, 3));
+    }
+
+    #[tokio::test]
+    async fn canary_promotes_once_enough_sessions_pass_the_gate() {
+        let pool = setup().await;
+        let service = PolicyApplyService::new(pool.clone());
+        service.apply_canary(canary_request(1, 2, 2), &signer()).await.expect("apply canary");
+
+        let waiting = service.evaluate_canary(None).await.unwrap().expect("evaluation");
+        assert_eq!(waiting.decision, CanaryDecision::Monitoring);
+        assert_eq!(waiting.canary.kpi_report.as_ref().map(|report| report.session_count), Some(0));
+
+        seed_quote(&pool, "Q-1", "rep-1").await;
+        seed_session(&pool, "S-1", "Q-1", 2).await;
+        seed_session(&pool, "S-2", "Q-1", 2).await;
+        seed_session(&pool, "S-3", "Q-1", 1).await;
+        let promoted = service.evaluate_canary(None).await.unwrap().expect("evaluation");
+        assert_eq!(promoted.decision, CanaryDecision::Promoted);
+        assert_eq!(promoted.canary.status, "promoted");
+        assert_eq!(promoted.canary.kpi_report.as_ref().map(|report| report.session_count), Some(2));
+
+        // Promotion writes the thresholds back to org settings without minting another version.
+        let active = service.active_version().await.expect("active");
+        assert_eq!(active.version, 2);
+        assert_eq!(active.thresholds.manager_discount_pct, Decimal::new(18, 0));
+        let setting = SqlOrgSettingsRepository::new(pool.clone())
+            .get(MANAGER_DISCOUNT_SETTING)
+            .await
+            .unwrap()
+            .expect("setting");
+        assert_eq!(setting.value_json, "0.18");
+        assert!(service.evaluate_canary(None).await.unwrap().is_none());
+        let candidate = SqlPolicyOptimizerRepository::new(pool.clone())
+            .get_candidate(&PolicyCandidateId("cand-canary".to_string()))
+            .await
+            .unwrap()
+            .expect("candidate");
+        assert_eq!(candidate.status, PolicyCandidateStatus::Applied);
+    }
+
+    #[tokio::test]
+    async fn canary_rolls_back_when_priced_quotes_breach_policy() {
+        let pool = setup().await;
+        let service = PolicyApplyService::new(pool.clone());
+        let canary =
+            service.apply_canary(canary_request(1, 2, 50), &signer()).await.expect("apply canary");
+
+        seed_quote(&pool, "Q-BREACH", "rep-1").await;
+        seed_session(&pool, "S-BREACH", "Q-BREACH", 2).await;
+        let snapshot = PricingSnapshot {
+            quote_id: QuoteId("Q-BREACH".to_string()),
+            version: 1,
+            subtotal: Decimal::new(1000, 0),
+            discount_total: Decimal::ZERO,
+            tax_total: Decimal::ZERO,
+            total: Decimal::new(1000, 0),
+            currency: "USD".to_string(),
+            line_items: vec![],
+            calculation_steps: vec![],
+            created_at: Utc::now().to_rfc3339(),
+        };
+        let snapshots =
+            SqlPricingSnapshotRepository::new(pool.clone()).with_policy_version(Some(2));
+        snapshots.record_snapshot(&snapshot, None).await.expect("record snapshot");
+        assert_eq!(
+            snapshots.snapshot_policy_version(&snapshot.quote_id, 1).await.unwrap(),
+            Some(2)
+        );
+        sqlx::query(
+            "UPDATE quote_pricing_snapshot SET anomaly_json = '{\"severity\":\"Critical\"}'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            service.evaluate_canary(None).await,
+            Err(PolicyApplyError::SignerRequired(_))
+        ));
+        let evaluation =
+            service.evaluate_canary(Some(&signer())).await.unwrap().expect("evaluation");
+        assert_eq!(evaluation.decision, CanaryDecision::RolledBack);
+        assert!(evaluation.canary.decision_reason.unwrap().contains("policy_breach_incidents"));
+        assert_eq!(evaluation.canary.decided_by.as_deref(), Some(CANARY_MONITOR_ACTOR));
+
+        assert_eq!(service.find_version(2).await.unwrap().unwrap().status, "rolled_back");
+        assert_eq!(service.resolve(None, Some("smb")).await.unwrap().version, 1);
+        let chain = SqlPolicyOptimizerRepository::new(pool.clone())
+            .list_rollback_chain_for_apply(&PolicyApplyRecordId(canary.apply_record_id))
+            .await
+            .unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].rollback_target_version, 1);
+    }
+}
@@ -0,0 +1,389 @@
+//! Immutable, versioned policy rule sets and the quote context they are evaluated against.
+//!
+//! Rule sets are authored as visual rules and compiled with [`build_policy_rule`] when they are
+//! published. Each publish stores a new `policy_rule_set_version` row holding both the authored
+//! rules and the compiled [`PolicyRule`]s; rows are never changed afterwards. Quotes are evaluated
+//! against the newest version, with [`PolicyRuleSetService::context_for_quote`] supplying the
+//! segment, region, rep role, terms and product families the rules are scoped by.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use quotey_core::chrono::{NaiveDate, Utc};
+use quotey_core::cpq::policy_rules::{PolicyRule, PolicyRuleContext, PolicyRuleSet};
+use quotey_core::{build_policy_rule, DiscountPolicyBuilderError, VisualRuleDefinition};
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row};
+use thiserror::Error;
+
+use crate::repositories::{RepositoryError, SqlPricingBaselineRepository};
+use crate::DbPool;
+
+const VERSION_SELECT: &str = "SELECT version, name, visual_rules_json, rules_json, checksum,
+        parent_version, created_by, created_at
+    FROM policy_rule_set_version";
+
+/// Row in `policy_rule_set_version`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PolicyRuleSetVersion {
+    pub version: i32,
+    pub name: String,
+    pub rules: PolicyRuleSet,
+    /// The visual rules the version was compiled from, as authored.
+    pub visual_rules: Vec<VisualRuleDefinition>,
+    pub checksum: String,
+    pub parent_version: Option<i32>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct PublishRuleSet {
+    pub name: String,
+    /// An empty list publishes a version with no rules, retiring the previous ones.
+    pub visual_rules: Vec<VisualRuleDefinition>,
+    pub created_by: String,
+}
+
+#[derive(Debug, Error)]
+pub enum PolicyRuleSetError {
+    #[error("policy rule set name is required")]
+    EmptyName,
+    #[error("policy rule `{0}` appears more than once")]
+    DuplicateRuleId(String),
+    #[error("policy rule `{rule_id}` is invalid: {source}")]
+    InvalidRule {
+        rule_id: String,
+        #[source]
+        source: DiscountPolicyBuilderError,
+    },
+    #[error("stored policy rule set is unreadable: {0}")]
+    Corrupt(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for PolicyRuleSetError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct PolicyRuleSetService {
+    pool: DbPool,
+}
+
+impl PolicyRuleSetService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Compiles `request.visual_rules` and stores them as the next version.
+    pub async fn publish(
+        &self,
+        request: PublishRuleSet,
+    ) -> Result<PolicyRuleSetVersion, PolicyRuleSetError> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(PolicyRuleSetError::EmptyName);
+        }
+        let mut seen = BTreeSet::new();
+        let mut rules = Vec::with_capacity(request.visual_rules.len());
+        for visual_rule in &request.visual_rules {
+            if !seen.insert(visual_rule.id.trim().to_string()) {
+                return Err(PolicyRuleSetError::DuplicateRuleId(visual_rule.id.clone()));
+            }
+            let rule = build_policy_rule(visual_rule).map_err(|source| {
+                PolicyRuleSetError::InvalidRule { rule_id: visual_rule.id.clone(), source }
+            })?;
+            rules.push(rule);
+        }
+        let rules = PolicyRuleSet::new(rules);
+
+        let mut tx = self.pool.begin().await?;
+        let latest: Option<i32> =
+            sqlx::query_scalar("SELECT MAX(version) FROM policy_rule_set_version")
+                .fetch_one(&mut *tx)
+                .await?;
+        let version = latest.unwrap_or(0) + 1;
+        sqlx::query(
+            "INSERT INTO policy_rule_set_version
+                (version, name, visual_rules_json, rules_json, checksum, parent_version,
+                 created_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(version)
+        .bind(name)
+        .bind(encode(&request.visual_rules)?)
+        .bind(encode(rules.rules())?)
+        .bind(rules.checksum())
+        .bind(latest)
+        .bind(request.created_by.trim())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.find_version(version).await?.ok_or_else(|| {
+            PolicyRuleSetError::Corrupt(format!("policy rule set version {version} vanished"))
+        })
+    }
+
+    /// The version quotes are evaluated against, if any has been published.
+    pub async fn latest(&self) -> Result<Option<PolicyRuleSetVersion>, PolicyRuleSetError> {
+        let row = sqlx::query(&format!("{VERSION_SELECT} ORDER BY version DESC LIMIT 1"))
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    pub async fn find_version(
+        &self,
+        version: i32,
+    ) -> Result<Option<PolicyRuleSetVersion>, PolicyRuleSetError> {
+        let row = sqlx::query(&format!("{VERSION_SELECT} WHERE version = ?"))
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    /// All versions, newest first.
+    pub async fn list_versions(&self) -> Result<Vec<PolicyRuleSetVersion>, PolicyRuleSetError> {
+        let rows = sqlx::query(&format!("{VERSION_SELECT} ORDER BY version DESC"))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(version_from_row).collect()
+    }
+
+    /// Scope and terms of a stored quote as of `as_of`. Unknown quotes get an empty context.
+    ///
+    /// The region comes from the quote notes' JSON `region` key (as in analytics), the rep role
+    /// from the owning sales rep and the segment from the account's latest deal outcome.
+    pub async fn context_for_quote(
+        &self,
+        quote_id: &str,
+        as_of: NaiveDate,
+    ) -> Result<PolicyRuleContext, PolicyRuleSetError> {
+        let mut context = PolicyRuleContext::new(as_of);
+        let Some(row) = sqlx::query(
+            "SELECT q.account_id, q.payment_terms, q.term_months, q.notes,
+                    CASE WHEN json_valid(q.notes) THEN json_extract(q.notes, '$.region') END
+                        AS region,
+                    sr.role AS rep_role
+             FROM quote q
+             LEFT JOIN sales_rep sr ON sr.id = q.created_by_sales_rep_id
+             WHERE q.id = ?",
+        )
+        .bind(quote_id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(context);
+        };
+
+        let account_id: Option<String> = row.try_get("account_id")?;
+        context.payment_terms = row.try_get("payment_terms")?;
+        context.term_months =
+            row.try_get::<Option<i64>, _>("term_months")?.and_then(|m| u32::try_from(m).ok());
+        context.notes = row.try_get("notes")?;
+        context.region = row.try_get("region")?;
+        context.rep_role = row.try_get("rep_role")?;
+        if let Some(account_id) = account_id {
+            context.customer_segment = SqlPricingBaselineRepository::new(self.pool.clone())
+                .segment_for_account(&account_id)
+                .await?;
+        }
+
+        let families: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT ql.product_id, pf.name
+             FROM quote_line ql
+             JOIN product p ON p.id = ql.product_id
+             JOIN product_family pf ON pf.id = p.family_id
+             WHERE ql.quote_id = ?",
+        )
+        .bind(quote_id)
+        .fetch_all(&self.pool)
+        .await?;
+        context.product_families = families.into_iter().collect::<BTreeMap<_, _>>();
+        Ok(context)
+    }
+}
+
+fn version_from_row(row: &SqliteRow) -> Result<PolicyRuleSetVersion, PolicyRuleSetError> {
+    let rules: Vec<PolicyRule> = decode(&row.try_get::<String, _>("rules_json")?)?;
+    Ok(PolicyRuleSetVersion {
+        version: row.try_get("version")?,
+        name: row.try_get("name")?,
+        rules: PolicyRuleSet::new(rules),
+        visual_rules: decode(&row.try_get::<String, _>("visual_rules_json")?)?,
+        checksum: row.try_get("checksum")?,
+        parent_version: row.try_get("parent_version")?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<String, PolicyRuleSetError> {
+    serde_json::to_string(value).map_err(|error| PolicyRuleSetError::Corrupt(error.to_string()))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, PolicyRuleSetError> {
+    serde_json::from_str(json).map_err(|error| PolicyRuleSetError::Corrupt(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use quotey_core::{
+        VisualActionType, VisualOperator, VisualRuleAction, VisualRuleCondition,
+        VisualRuleMetadata, VisualRuleType, VISUAL_RULE_SCHEMA_VERSION,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+
+    async fn pool() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    fn visual_rule(id: &str, field: &str, value: serde_json::Value) -> VisualRuleDefinition {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("terms".to_string(), json!(["net_90"]));
+        VisualRuleDefinition {
+            schema_version: VISUAL_RULE_SCHEMA_VERSION.to_string(),
+            id: id.to_string(),
+            name: "Restricted terms".to_string(),
+            description: None,
+            rule_type: VisualRuleType::ApprovalThreshold,
+            enabled: true,
+            priority: 10,
+            conditions: vec![VisualRuleCondition {
+                field_key: field.to_string(),
+                operator: VisualOperator::Equals,
+                value,
+                connector: None,
+            }],
+            actions: vec![VisualRuleAction {
+                action_type: VisualActionType::RestrictPaymentTerms,
+                parameters,
+            }],
+            metadata: VisualRuleMetadata {
+                created_by: "salesops".to_string(),
+                updated_by: "salesops".to_string(),
+                tags: Vec::new(),
+                rationale: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn publishing_appends_immutable_versions() {
+        let pool = pool().await;
+        let service = PolicyRuleSetService::new(pool.clone());
+        assert!(service.latest().await.expect("latest").is_none());
+
+        let first = service
+            .publish(PublishRuleSet {
+                name: "FY26".to_string(),
+                visual_rules: vec![visual_rule("emea-terms", "region", json!("emea"))],
+                created_by: "salesops".to_string(),
+            })
+            .await
+            .expect("publish");
+        assert_eq!(first.version, 1);
+        assert_eq!(first.rules.rules()[0].scope.regions, vec!["emea".to_string()]);
+
+        let second = service
+            .publish(PublishRuleSet {
+                name: "FY26 retired".to_string(),
+                visual_rules: Vec::new(),
+                created_by: "salesops".to_string(),
+            })
+            .await
+            .expect("publish");
+        assert_eq!(second.parent_version, Some(1));
+        assert!(service.latest().await.expect("latest").expect("version").rules.is_empty());
+
+        let update =
+            sqlx::query("UPDATE policy_rule_set_version SET name = 'edited'").execute(&pool).await;
+        assert!(update.is_err());
+        assert_eq!(service.find_version(1).await.expect("find"), Some(first));
+
+        let invalid = service
+            .publish(PublishRuleSet {
+                name: "bad".to_string(),
+                visual_rules: vec![visual_rule("by-industry", "industry", json!("retail"))],
+                created_by: "salesops".to_string(),
+            })
+            .await;
+        assert!(matches!(invalid, Err(PolicyRuleSetError::InvalidRule { .. })));
+        assert_eq!(service.list_versions().await.expect("list").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn quote_context_reads_terms_region_rep_role_and_families() {
+        let pool = pool().await;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO sales_rep (id, name, role, status, created_at, updated_at)
+             VALUES ('rep-1', 'Rep', 'ae', 'active', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("rep");
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, term_months, notes, payment_terms,
+                                created_by, created_by_sales_rep_id, created_at, updated_at)
+             VALUES ('Q-1', 'draft', 'USD', 24, '{\"region\":\"emea\"}', 'net_60', 'rep-1',
+                     'rep-1', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("quote");
+        sqlx::query(
+            "INSERT INTO product_family (id, name, created_at, updated_at)
+             VALUES ('fam-hw', 'hardware', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("family");
+        sqlx::query(
+            "INSERT INTO product (id, sku, name, family_id, created_at, updated_at)
+             VALUES ('p-router', 'SKU-R', 'Router', 'fam-hw', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("product");
+        sqlx::query(
+            "INSERT INTO quote_line (id, quote_id, product_id, quantity, unit_price, created_at,
+                                     updated_at)
+             VALUES ('Q-1-ql-1', 'Q-1', 'p-router', 1, 100, ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("line");
+
+        let as_of = NaiveDate::from_ymd_opt(2026, 3, 1).expect("date");
+        let context =
+            PolicyRuleSetService::new(pool).context_for_quote("Q-1", as_of).await.expect("context");
+        assert_eq!(context.region.as_deref(), Some("emea"));
+        assert_eq!(context.rep_role.as_deref(), Some("ae"));
+        assert_eq!(context.payment_terms.as_deref(), Some("net_60"));
+        assert_eq!(context.term_months, Some(24));
+        assert_eq!(context.product_families.get("p-router").map(String::as_str), Some("hardware"));
+    }
+}
@@ -0,0 +1,875 @@
+//! Quote document template registry: versioned templates, clause blocks and defaults.
+//!
+//! Saving a template edits its open draft, or opens the next version as a draft when the newest
+//! one is already published. [`QuoteTemplateService::publish`] freezes a draft and retires the
+//! version it replaces. The built-in layouts (`detailed`, `executive_summary`, `compact`) are
+//! always available under their own names, so quotes render before anything is published.
+//!
+//! [`QuoteTemplateService::resolve_for_quote`] picks the template for a quote (an explicit
+//! choice, else the account default, the segment default, the global default and finally
+//! `detailed`) and evaluates its section conditions against the quote.
+
+use quotey_core::chrono::Utc;
+use quotey_core::pdf::template::{
+    validate_key, ClauseBlock, DocumentContext, QuoteDocumentTemplate, ResolvedDocument,
+    TemplateError,
+};
+use quotey_core::pdf::PdfTemplate;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row};
+use thiserror::Error;
+
+use crate::policy_rules::{PolicyRuleSetError, PolicyRuleSetService};
+use crate::repositories::RepositoryError;
+use crate::DbPool;
+
+/// Template used when neither the request nor any default names one.
+pub const FALLBACK_TEMPLATE: &str = "detailed";
+
+const VERSION_SELECT: &str = "SELECT template_key, version, label, definition_json, status,
+        created_by, created_at, updated_at, published_by, published_at
+    FROM quote_template_version";
+
+const CLAUSE_SELECT: &str =
+    "SELECT clause_key, version, title, body, created_by, created_at FROM quote_clause_block";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateStatus {
+    Draft,
+    Published,
+    Retired,
+}
+
+impl TemplateStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Published => "published",
+            Self::Retired => "retired",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::Draft, Self::Published, Self::Retired]
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+}
+
+/// Row in `quote_template_version`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QuoteTemplateVersion {
+    pub key: String,
+    pub version: i32,
+    pub label: String,
+    pub definition: QuoteDocumentTemplate,
+    pub status: TemplateStatus,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub published_by: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// Row in `quote_clause_block`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClauseBlockVersion {
+    #[serde(flatten)]
+    pub clause: ClauseBlock,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveTemplateDraft {
+    pub key: String,
+    pub label: String,
+    pub definition: QuoteDocumentTemplate,
+    pub actor: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveClause {
+    pub key: String,
+    pub title: String,
+    pub body: String,
+    pub actor: String,
+}
+
+/// What a default template applies to; the most specific match wins.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "scope", content = "value", rename_all = "snake_case")]
+pub enum TemplateScope {
+    Global,
+    Segment(String),
+    Account(String),
+}
+
+impl TemplateScope {
+    fn columns(&self) -> (&'static str, &str) {
+        match self {
+            Self::Global => ("global", ""),
+            Self::Segment(segment) => ("segment", segment.as_str()),
+            Self::Account(account) => ("account", account.as_str()),
+        }
+    }
+}
+
+/// Row in `quote_template_default`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TemplateDefault {
+    pub scope: TemplateScope,
+    pub template_key: String,
+    pub updated_by: String,
+    pub updated_at: String,
+}
+
+/// Which template to render a quote with; unset fields fall back to the defaults.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateSelection {
+    pub key: Option<String>,
+    /// A specific version, including drafts; the published version when `None`.
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Error)]
+pub enum QuoteTemplateError {
+    #[error("template label is required")]
+    EmptyLabel,
+    #[error("clause title and body are required")]
+    EmptyClause,
+    #[error(transparent)]
+    Invalid(#[from] TemplateError),
+    #[error("`{0}` is a built-in layout and cannot be redefined")]
+    ReservedKey(String),
+    #[error("quote template `{0}` not found")]
+    TemplateNotFound(String),
+    #[error("quote template `{key}` has no version {version}")]
+    VersionNotFound { key: String, version: i32 },
+    #[error("quote template `{key}` version {version} is {status}, only drafts can be published")]
+    NotDraft { key: String, version: i32, status: &'static str },
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("stored quote template is unreadable: {0}")]
+    Corrupt(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl QuoteTemplateError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EmptyLabel | Self::EmptyClause | Self::Invalid(_) | Self::ReservedKey(_) => {
+                "invalid_template"
+            }
+            Self::TemplateNotFound(_) | Self::VersionNotFound { .. } | Self::QuoteNotFound(_) => {
+                "not_found"
+            }
+            Self::NotDraft { .. } => "not_draft",
+            Self::Corrupt(_) | Self::Repository(_) => "storage",
+        }
+    }
+}
+
+impl From<sqlx::Error> for QuoteTemplateError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+impl From<PolicyRuleSetError> for QuoteTemplateError {
+    fn from(error: PolicyRuleSetError) -> Self {
+        match error {
+            PolicyRuleSetError::Repository(error) => Self::Repository(error),
+            other => Self::Corrupt(other.to_string()),
+        }
+    }
+}
+
+pub struct QuoteTemplateService {
+    pool: DbPool,
+}
+
+impl QuoteTemplateService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores `request` as the template's open draft, opening a new version if needed.
+    pub async fn save_draft(
+        &self,
+        request: SaveTemplateDraft,
+    ) -> Result<QuoteTemplateVersion, QuoteTemplateError> {
+        let key = request.key.trim();
+        validate_key(key)?;
+        if PdfTemplate::parse(key).is_some() {
+            return Err(QuoteTemplateError::ReservedKey(key.to_owned()));
+        }
+        let label = request.label.trim();
+        if label.is_empty() {
+            return Err(QuoteTemplateError::EmptyLabel);
+        }
+        request.definition.validate()?;
+        // Resolving checks every referenced clause (and pinned version) exists.
+        let clauses: Vec<ClauseBlock> = self
+            .clause_versions(&request.definition.clause_keys())
+            .await?
+            .into_iter()
+            .map(|version| version.clause)
+            .collect();
+        request.definition.resolve(key, None, &DocumentContext::default(), &clauses)?;
+
+        let now = Utc::now().to_rfc3339();
+        let definition = encode(&request.definition)?;
+        let layout = request.definition.layout.as_str();
+        let mut tx = self.pool.begin().await?;
+        let latest: Option<(i32, String)> = sqlx::query_as(
+            "SELECT version, status FROM quote_template_version
+             WHERE template_key = ? ORDER BY version DESC LIMIT 1",
+        )
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let version = match latest {
+            Some((version, status)) if status == TemplateStatus::Draft.as_str() => {
+                sqlx::query(
+                    "UPDATE quote_template_version
+                     SET label = ?, layout = ?, definition_json = ?, created_by = ?,
+                         updated_at = ?
+                     WHERE template_key = ? AND version = ?",
+                )
+                .bind(label)
+                .bind(layout)
+                .bind(&definition)
+                .bind(request.actor.trim())
+                .bind(&now)
+                .bind(key)
+                .bind(version)
+                .execute(&mut *tx)
+                .await?;
+                version
+            }
+            latest => {
+                let version = latest.map_or(1, |(version, _)| version + 1);
+                sqlx::query(
+                    "INSERT INTO quote_template_version
+                        (template_key, version, label, layout, definition_json, status,
+                         created_by, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, 'draft', ?, ?, ?)",
+                )
+                .bind(key)
+                .bind(version)
+                .bind(label)
+                .bind(layout)
+                .bind(&definition)
+                .bind(request.actor.trim())
+                .bind(&now)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+                version
+            }
+        };
+        tx.commit().await?;
+        self.require_version(key, version).await
+    }
+
+    /// Publishes a draft and retires the previously published version.
+    pub async fn publish(
+        &self,
+        key: &str,
+        version: i32,
+        actor: &str,
+    ) -> Result<QuoteTemplateVersion, QuoteTemplateError> {
+        let key = key.trim();
+        let current = self.require_version(key, version).await?;
+        if current.status != TemplateStatus::Draft {
+            return Err(QuoteTemplateError::NotDraft {
+                key: key.to_owned(),
+                version,
+                status: current.status.as_str(),
+            });
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE quote_template_version SET status = 'retired', updated_at = ?
+             WHERE template_key = ? AND status = 'published'",
+        )
+        .bind(&now)
+        .bind(key)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE quote_template_version
+             SET status = 'published', published_by = ?, published_at = ?, updated_at = ?
+             WHERE template_key = ? AND version = ?",
+        )
+        .bind(actor.trim())
+        .bind(&now)
+        .bind(&now)
+        .bind(key)
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        self.require_version(key, version).await
+    }
+
+    /// The newest version of every template, ordered by key.
+    pub async fn list_templates(&self) -> Result<Vec<QuoteTemplateVersion>, QuoteTemplateError> {
+        let rows = sqlx::query(&format!(
+            "{VERSION_SELECT} t WHERE version =
+                (SELECT MAX(version) FROM quote_template_version WHERE template_key = t.template_key)
+             ORDER BY template_key"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(version_from_row).collect()
+    }
+
+    /// All versions of a template, newest first.
+    pub async fn list_versions(
+        &self,
+        key: &str,
+    ) -> Result<Vec<QuoteTemplateVersion>, QuoteTemplateError> {
+        let rows =
+            sqlx::query(&format!("{VERSION_SELECT} WHERE template_key = ? ORDER BY version DESC"))
+                .bind(key.trim())
+                .fetch_all(&self.pool)
+                .await?;
+        rows.iter().map(version_from_row).collect()
+    }
+
+    pub async fn find_version(
+        &self,
+        key: &str,
+        version: i32,
+    ) -> Result<Option<QuoteTemplateVersion>, QuoteTemplateError> {
+        let row = sqlx::query(&format!("{VERSION_SELECT} WHERE template_key = ? AND version = ?"))
+            .bind(key.trim())
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    pub async fn published(
+        &self,
+        key: &str,
+    ) -> Result<Option<QuoteTemplateVersion>, QuoteTemplateError> {
+        let row = sqlx::query(&format!(
+            "{VERSION_SELECT} WHERE template_key = ? AND status = 'published'"
+        ))
+        .bind(key.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    /// Stores the next version of a clause block.
+    pub async fn save_clause(
+        &self,
+        request: SaveClause,
+    ) -> Result<ClauseBlockVersion, QuoteTemplateError> {
+        let key = request.key.trim();
+        validate_key(key)?;
+        let (title, body) = (request.title.trim(), request.body.trim());
+        if title.is_empty() || body.is_empty() {
+            return Err(QuoteTemplateError::EmptyClause);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let latest: Option<i32> =
+            sqlx::query_scalar("SELECT MAX(version) FROM quote_clause_block WHERE clause_key = ?")
+                .bind(key)
+                .fetch_one(&mut *tx)
+                .await?;
+        let version = latest.unwrap_or(0) + 1;
+        sqlx::query(
+            "INSERT INTO quote_clause_block
+                (clause_key, version, title, body, created_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(key)
+        .bind(version)
+        .bind(title)
+        .bind(body)
+        .bind(request.actor.trim())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut versions = self.clause_versions(&[key]).await?;
+        versions.retain(|clause| clause.clause.version == version);
+        versions.pop().ok_or_else(|| {
+            QuoteTemplateError::Corrupt(format!("clause `{key}` version {version} vanished"))
+        })
+    }
+
+    /// The newest version of every clause block, ordered by key.
+    pub async fn list_clauses(&self) -> Result<Vec<ClauseBlockVersion>, QuoteTemplateError> {
+        let rows = sqlx::query(&format!(
+            "{CLAUSE_SELECT} c WHERE version =
+                (SELECT MAX(version) FROM quote_clause_block WHERE clause_key = c.clause_key)
+             ORDER BY clause_key"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(clause_from_row).collect()
+    }
+
+    /// One clause block: the given version, or the newest when `version` is `None`.
+    pub async fn clause(
+        &self,
+        key: &str,
+        version: Option<i32>,
+    ) -> Result<ClauseBlock, QuoteTemplateError> {
+        let key = key.trim();
+        self.clause_versions(&[key])
+            .await?
+            .into_iter()
+            .map(|version| version.clause)
+            .filter(|clause| version.map_or(true, |pinned| clause.version == pinned))
+            .max_by_key(|clause| clause.version)
+            .ok_or_else(|| {
+                let key = key.to_owned();
+                match version {
+                    Some(version) => TemplateError::UnknownClauseVersion { key, version },
+                    None => TemplateError::UnknownClause { key },
+                }
+                .into()
+            })
+    }
+
+    /// Makes `template_key` the default for `scope`.
+    pub async fn set_default(
+        &self,
+        scope: &TemplateScope,
+        template_key: &str,
+        actor: &str,
+    ) -> Result<(), QuoteTemplateError> {
+        let template_key = template_key.trim();
+        if PdfTemplate::parse(template_key).is_none()
+            && self.published(template_key).await?.is_none()
+        {
+            return Err(QuoteTemplateError::TemplateNotFound(template_key.to_owned()));
+        }
+        let (scope_type, scope_value) = scope.columns();
+        sqlx::query(
+            "INSERT INTO quote_template_default
+                (scope_type, scope_value, template_key, updated_by, updated_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT (scope_type, scope_value) DO UPDATE SET
+                template_key = excluded.template_key,
+                updated_by = excluded.updated_by,
+                updated_at = excluded.updated_at",
+        )
+        .bind(scope_type)
+        .bind(scope_value.trim())
+        .bind(template_key)
+        .bind(actor.trim())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Every configured default, global first, then segments and accounts.
+    pub async fn list_defaults(&self) -> Result<Vec<TemplateDefault>, QuoteTemplateError> {
+        let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+            "SELECT scope_type, scope_value, template_key, updated_by, updated_at
+             FROM quote_template_default
+             ORDER BY CASE scope_type WHEN 'global' THEN 0 WHEN 'segment' THEN 1 ELSE 2 END,
+                      scope_value",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(scope_type, scope_value, template_key, updated_by, updated_at)| {
+                let scope = match scope_type.as_str() {
+                    "global" => TemplateScope::Global,
+                    "segment" => TemplateScope::Segment(scope_value),
+                    "account" => TemplateScope::Account(scope_value),
+                    other => {
+                        return Err(QuoteTemplateError::Corrupt(format!(
+                            "unknown default scope `{other}`"
+                        )))
+                    }
+                };
+                Ok(TemplateDefault { scope, template_key, updated_by, updated_at })
+            })
+            .collect()
+    }
+
+    /// The default template key for an account, checking account, segment and global defaults.
+    pub async fn default_for(
+        &self,
+        account_id: Option<&str>,
+        segment: Option<&str>,
+    ) -> Result<Option<String>, QuoteTemplateError> {
+        let scopes = [
+            account_id.map(|account| TemplateScope::Account(account.to_owned())),
+            segment.map(|segment| TemplateScope::Segment(segment.to_owned())),
+            Some(TemplateScope::Global),
+        ];
+        for scope in scopes.into_iter().flatten() {
+            let (scope_type, scope_value) = scope.columns();
+            let key: Option<String> = sqlx::query_scalar(
+                "SELECT template_key FROM quote_template_default
+                 WHERE scope_type = ? AND scope_value = ? COLLATE NOCASE",
+            )
+            .bind(scope_type)
+            .bind(scope_value)
+            .fetch_optional(&self.pool)
+            .await?;
+            if key.is_some() {
+                return Ok(key);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Picks the template for a quote and evaluates its sections against it.
+    pub async fn resolve_for_quote(
+        &self,
+        quote_id: &str,
+        selection: TemplateSelection,
+    ) -> Result<ResolvedDocument, QuoteTemplateError> {
+        let row: Option<(Option<String>, f64)> = sqlx::query_as(
+            "SELECT q.account_id,
+                    COALESCE(SUM(
+                        COALESCE(ql.subtotal, COALESCE(ql.unit_price, 0.0) * ql.quantity)
+                        * (1.0 - MAX(0.0, MIN(COALESCE(ql.discount_pct, 0.0), 100.0)) / 100.0)
+                    ), 0.0)
+             FROM quote q
+             LEFT JOIN quote_line ql ON ql.quote_id = q.id
+             WHERE q.id = ?
+             GROUP BY q.id",
+        )
+        .bind(quote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((account_id, total)) = row else {
+            return Err(QuoteTemplateError::QuoteNotFound(quote_id.to_owned()));
+        };
+
+        let today = Utc::now().date_naive();
+        let scope =
+            PolicyRuleSetService::new(self.pool.clone()).context_for_quote(quote_id, today).await?;
+        let context = DocumentContext {
+            customer_segment: scope.customer_segment,
+            region: scope.region,
+            total: Decimal::from_f64(total).unwrap_or_default().round_dp(2),
+            term_months: scope.term_months,
+        };
+
+        let key = match selection.key.map(|key| key.trim().to_owned()) {
+            Some(key) if !key.is_empty() => key,
+            _ => self
+                .default_for(account_id.as_deref(), context.customer_segment.as_deref())
+                .await?
+                .unwrap_or_else(|| FALLBACK_TEMPLATE.to_owned()),
+        };
+        self.resolve(&key, selection.version, &context).await
+    }
+
+    /// Evaluates a template version (published when `version` is `None`) for `context`.
+    pub async fn resolve(
+        &self,
+        key: &str,
+        version: Option<i32>,
+        context: &DocumentContext,
+    ) -> Result<ResolvedDocument, QuoteTemplateError> {
+        let (definition, version) = match version {
+            Some(version) => (self.require_version(key, version).await?.definition, Some(version)),
+            None => match self.published(key).await? {
+                Some(published) => (published.definition, Some(published.version)),
+                None => {
+                    let layout = PdfTemplate::parse(key)
+                        .ok_or_else(|| QuoteTemplateError::TemplateNotFound(key.to_owned()))?;
+                    (QuoteDocumentTemplate::builtin(layout), None)
+                }
+            },
+        };
+        let clauses: Vec<ClauseBlock> = self
+            .clause_versions(&definition.clause_keys())
+            .await?
+            .into_iter()
+            .map(|version| version.clause)
+            .collect();
+        Ok(definition.resolve(key, version, context, &clauses)?)
+    }
+
+    async fn clause_versions(
+        &self,
+        keys: &[&str],
+    ) -> Result<Vec<ClauseBlockVersion>, QuoteTemplateError> {
+        let mut versions = Vec::new();
+        for key in keys {
+            let rows =
+                sqlx::query(&format!("{CLAUSE_SELECT} WHERE clause_key = ? ORDER BY version"))
+                    .bind(key)
+                    .fetch_all(&self.pool)
+                    .await?;
+            for row in &rows {
+                versions.push(clause_from_row(row)?);
+            }
+        }
+        Ok(versions)
+    }
+
+    async fn require_version(
+        &self,
+        key: &str,
+        version: i32,
+    ) -> Result<QuoteTemplateVersion, QuoteTemplateError> {
+        self.find_version(key, version).await?.ok_or_else(|| QuoteTemplateError::VersionNotFound {
+            key: key.trim().to_owned(),
+            version,
+        })
+    }
+}
+
+fn version_from_row(row: &SqliteRow) -> Result<QuoteTemplateVersion, QuoteTemplateError> {
+    let status: String = row.try_get("status")?;
+    Ok(QuoteTemplateVersion {
+        key: row.try_get("template_key")?,
+        version: row.try_get("version")?,
+        label: row.try_get("label")?,
+        definition: decode(&row.try_get::<String, _>("definition_json")?)?,
+        status: TemplateStatus::parse(&status)
+            .ok_or_else(|| QuoteTemplateError::Corrupt(format!("unknown status `{status}`")))?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        published_by: row.try_get("published_by")?,
+        published_at: row.try_get("published_at")?,
+    })
+}
+
+fn clause_from_row(row: &SqliteRow) -> Result<ClauseBlockVersion, QuoteTemplateError> {
+    Ok(ClauseBlockVersion {
+        clause: ClauseBlock {
+            key: row.try_get("clause_key")?,
+            version: row.try_get("version")?,
+            title: row.try_get("title")?,
+            body: row.try_get("body")?,
+        },
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<String, QuoteTemplateError> {
+    serde_json::to_string(value).map_err(|error| QuoteTemplateError::Corrupt(error.to_string()))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, QuoteTemplateError> {
+    serde_json::from_str(json).map_err(|error| QuoteTemplateError::Corrupt(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::pdf::template::DocumentSection;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+
+    async fn pool() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    fn legal_template() -> QuoteDocumentTemplate {
+        serde_json::from_value(json!({
+            "layout": "detailed",
+            "sections": [
+                {"kind": "notes"},
+                {"kind": "clause", "key": "msa", "when": {"segments": ["enterprise"]}},
+                {"kind": "terms"}
+            ]
+        }))
+        .expect("template")
+    }
+
+    async fn seed_quote(pool: &DbPool, quote_id: &str, account_id: &str) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, account_id, created_by, created_at,
+                                updated_at)
+             VALUES (?, 'draft', 'USD', ?, 'rep', ?, ?)",
+        )
+        .bind(quote_id)
+        .bind(account_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("quote");
+        sqlx::query(
+            "INSERT INTO quote_line (id, quote_id, product_id, quantity, unit_price,
+                                     discount_pct, created_at, updated_at)
+             VALUES (?, ?, 'plan-pro', 10, 1000, 10, ?, ?)",
+        )
+        .bind(format!("{quote_id}-ql-1"))
+        .bind(quote_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("line");
+    }
+
+    #[tokio::test]
+    async fn drafts_are_edited_in_place_until_published() {
+        let service = QuoteTemplateService::new(pool().await);
+        let draft = SaveTemplateDraft {
+            key: "enterprise".into(),
+            label: "Enterprise".into(),
+            definition: legal_template(),
+            actor: "legal".into(),
+        };
+        let missing_clause = service.save_draft(draft.clone()).await;
+        assert!(matches!(
+            missing_clause,
+            Err(QuoteTemplateError::Invalid(TemplateError::UnknownClause { .. }))
+        ));
+
+        service
+            .save_clause(SaveClause {
+                key: "msa".into(),
+                title: "Master Services Agreement".into(),
+                body: "Governed by the MSA.".into(),
+                actor: "legal".into(),
+            })
+            .await
+            .expect("clause");
+        let first = service.save_draft(draft.clone()).await.expect("draft");
+        assert_eq!((first.version, first.status), (1, TemplateStatus::Draft));
+        let edited = service
+            .save_draft(SaveTemplateDraft { label: "Enterprise (legal)".into(), ..draft.clone() })
+            .await
+            .expect("edit draft");
+        assert_eq!((edited.version, edited.label.as_str()), (1, "Enterprise (legal)"));
+
+        let published = service.publish("enterprise", 1, "legal").await.expect("publish");
+        assert_eq!(published.status, TemplateStatus::Published);
+        assert!(matches!(
+            service.publish("enterprise", 1, "legal").await,
+            Err(QuoteTemplateError::NotDraft { .. })
+        ));
+        let frozen = sqlx::query(
+            "UPDATE quote_template_version SET label = 'x' WHERE template_key = 'enterprise'",
+        )
+        .execute(&service.pool)
+        .await;
+        assert!(frozen.is_err(), "published versions are immutable");
+
+        let second = service.save_draft(draft).await.expect("next draft");
+        assert_eq!((second.version, second.status), (2, TemplateStatus::Draft));
+        assert_eq!(service.published("enterprise").await.expect("published").unwrap().version, 1);
+        service.publish("enterprise", 2, "legal").await.expect("publish v2");
+        let versions = service.list_versions("enterprise").await.expect("versions");
+        let statuses: Vec<_> = versions.iter().map(|version| version.status).collect();
+        assert_eq!(statuses, vec![TemplateStatus::Published, TemplateStatus::Retired]);
+        assert_eq!(service.list_templates().await.expect("list").len(), 1);
+
+        let reserved = service
+            .save_draft(SaveTemplateDraft {
+                key: "detailed".into(),
+                label: "Detailed".into(),
+                definition: legal_template(),
+                actor: "legal".into(),
+            })
+            .await;
+        assert!(matches!(reserved, Err(QuoteTemplateError::ReservedKey(_))));
+    }
+
+    #[tokio::test]
+    async fn quotes_resolve_account_then_segment_then_global_defaults() {
+        let pool = pool().await;
+        let service = QuoteTemplateService::new(pool.clone());
+        seed_quote(&pool, "Q-1", "acct-1").await;
+        seed_quote(&pool, "Q-2", "acct-2").await;
+
+        let fallback = service.resolve_for_quote("Q-1", TemplateSelection::default()).await;
+        let fallback = fallback.expect("fallback");
+        assert_eq!((fallback.template_key.as_str(), fallback.template_version), ("detailed", None));
+        assert_eq!(
+            fallback.sections,
+            vec![DocumentSection::Assumptions, DocumentSection::Notes, DocumentSection::Terms]
+        );
+
+        service
+            .save_clause(SaveClause {
+                key: "msa".into(),
+                title: "MSA".into(),
+                body: "Governed by the MSA.".into(),
+                actor: "legal".into(),
+            })
+            .await
+            .expect("clause");
+        service
+            .save_draft(SaveTemplateDraft {
+                key: "enterprise".into(),
+                label: "Enterprise".into(),
+                definition: legal_template(),
+                actor: "legal".into(),
+            })
+            .await
+            .expect("draft");
+        assert!(matches!(
+            service.set_default(&TemplateScope::Global, "enterprise", "ops").await,
+            Err(QuoteTemplateError::TemplateNotFound(_))
+        ));
+        service.publish("enterprise", 1, "legal").await.expect("publish");
+        service.set_default(&TemplateScope::Global, "compact", "ops").await.expect("global");
+        service
+            .set_default(&TemplateScope::Account("acct-1".into()), "enterprise", "ops")
+            .await
+            .expect("account");
+
+        let account = service.resolve_for_quote("Q-1", TemplateSelection::default()).await;
+        let account = account.expect("account default");
+        assert_eq!(
+            (account.template_key.as_str(), account.template_version),
+            ("enterprise", Some(1))
+        );
+        assert_eq!(account.sections, vec![DocumentSection::Notes, DocumentSection::Terms]);
+        let global = service.resolve_for_quote("Q-2", TemplateSelection::default()).await;
+        assert_eq!(global.expect("global default").template_key, "compact");
+        let defaults = service.list_defaults().await.expect("defaults");
+        let scopes: Vec<_> = defaults.iter().map(|default| default.scope.clone()).collect();
+        assert_eq!(scopes, vec![TemplateScope::Global, TemplateScope::Account("acct-1".into())]);
+
+        let context = DocumentContext {
+            customer_segment: Some("Enterprise".into()),
+            total: Decimal::new(9_000, 0),
+            ..DocumentContext::default()
+        };
+        let enterprise = service.resolve("enterprise", None, &context).await.expect("resolve");
+        assert!(
+            matches!(&enterprise.sections[1], DocumentSection::Clause { key, .. } if key == "msa")
+        );
+
+        let preview = service
+            .resolve_for_quote(
+                "Q-2",
+                TemplateSelection { key: Some("enterprise".into()), version: Some(7) },
+            )
+            .await;
+        assert!(matches!(preview, Err(QuoteTemplateError::VersionNotFound { version: 7, .. })));
+        assert!(matches!(
+            service.resolve_for_quote("Q-404", TemplateSelection::default()).await,
+            Err(QuoteTemplateError::QuoteNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,602 @@
+//! Replay fixture export for the `quotey replay` CLI.
+//!
+//! Turns recorded negotiation counters (`negotiation_turn`) and quote pricing runs
+//! (`quote_pricing_snapshot`) into a [`ReplayBundle`]: each fixture carries the lines, unit costs,
+//! product families and rule context the engine saw plus the outcome it recorded, so later builds
+//! can re-run it with [`ReplayHarness::replay_bundle`](quotey_core::cpq::replay::ReplayHarness)
+//! and report drift. Also resolves the [`ReplayPolicy`] fixtures are re-run against.
+
+use std::collections::BTreeMap;
+
+use quotey_core::chrono::{DateTime, NaiveDate, Utc};
+use quotey_core::cpq::negotiation_turn::CounterVerdict;
+use quotey_core::cpq::replay::{
+    CounterOutcome, PricingOutcome, RecordedCounter, RecordedLine, RecordedNegotiation,
+    RecordedPricingRun, ReplayBundle, ReplayPolicy, SkippedRecord,
+};
+use quotey_core::domain::negotiation::{
+    BoundaryEvaluation, ConcessionEnvelope, NegotiationTurn, TurnOutcome, TurnRequestType,
+};
+use quotey_core::domain::quote::QuoteId;
+use quotey_core::PricingLineSnapshot;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::negotiation::TurnPayload;
+use crate::policy_apply::{PolicyApplyError, PolicyApplyService};
+use crate::policy_rules::{PolicyRuleSetError, PolicyRuleSetService};
+use crate::repositories::{
+    QuoteRepository, RecordedSnapshot, RepositoryError, SqlNegotiationRepository,
+    SqlPricingSnapshotRepository, SqlProductCostRepository, SqlQuoteRepository,
+};
+use crate::DbPool;
+
+/// Most sessions and pricing runs a single export reads, each.
+pub const MAX_EXPORT_RECORDS: u32 = 5_000;
+
+/// Which recorded sessions and pricing runs to export.
+#[derive(Clone, Debug, Default)]
+pub struct ExportQuery {
+    pub quote_id: Option<String>,
+    /// RFC 3339 timestamp; older records are left out.
+    pub since: Option<String>,
+    /// Per record kind; defaults to [`MAX_EXPORT_RECORDS`].
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("policy version {0} not found")]
+    PolicyVersionNotFound(i32),
+    #[error(transparent)]
+    PolicyApply(#[from] PolicyApplyError),
+    #[error(transparent)]
+    PolicyRules(#[from] PolicyRuleSetError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl ReplayError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid_request",
+            Self::PolicyVersionNotFound(_) => "policy_version_not_found",
+            Self::PolicyApply(_) | Self::PolicyRules(_) => "policy_error",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for ReplayError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct ReplayService {
+    pool: DbPool,
+}
+
+impl ReplayService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Exports recorded counters and pricing runs, oldest first.
+    ///
+    /// Records whose inputs can no longer be reconstructed (for example a counter made on a quote
+    /// version that was never priced) are listed in [`ReplayBundle::skipped`].
+    pub async fn export(&self, query: ExportQuery) -> Result<ReplayBundle, ReplayError> {
+        let limit = query.limit.unwrap_or(MAX_EXPORT_RECORDS);
+        if limit == 0 || limit > MAX_EXPORT_RECORDS {
+            return Err(ReplayError::Invalid(format!(
+                "limit must be between 1 and {MAX_EXPORT_RECORDS}"
+            )));
+        }
+        let since = match query.since.as_deref().map(str::trim).filter(|since| !since.is_empty()) {
+            Some(since) => Some(
+                DateTime::parse_from_rfc3339(since)
+                    .map_err(|_| {
+                        ReplayError::Invalid("since must be an RFC 3339 timestamp".to_string())
+                    })?
+                    .with_timezone(&Utc)
+                    .to_rfc3339(),
+            ),
+            None => None,
+        };
+        let quote_id = query.quote_id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+
+        let mut bundle = ReplayBundle::new(Utc::now().to_rfc3339());
+        self.export_negotiations(&mut bundle, quote_id, since.as_deref(), limit).await?;
+        self.export_pricing_runs(&mut bundle, quote_id, since.as_deref(), limit).await?;
+        Ok(bundle)
+    }
+
+    /// Thresholds of `version` (the active policy set version when `None`) and the newest
+    /// published rule set.
+    pub async fn policy(&self, version: Option<i32>) -> Result<ReplayPolicy, ReplayError> {
+        let policies = PolicyApplyService::new(self.pool.clone());
+        let policy_version = match version {
+            Some(version) => policies
+                .find_version(version)
+                .await?
+                .ok_or(ReplayError::PolicyVersionNotFound(version))?,
+            None => policies.active_version().await?,
+        };
+        let rules = PolicyRuleSetService::new(self.pool.clone())
+            .latest()
+            .await?
+            .map(|version| version.rules)
+            .unwrap_or_default();
+        Ok(ReplayPolicy {
+            version: Some(policy_version.version),
+            thresholds: policy_version.thresholds,
+            rules,
+            ..ReplayPolicy::default()
+        })
+    }
+
+    async fn export_negotiations(
+        &self,
+        bundle: &mut ReplayBundle,
+        quote_id: Option<&str>,
+        since: Option<&str>,
+        limit: u32,
+    ) -> Result<(), ReplayError> {
+        let sessions: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT id, quote_id, policy_version FROM negotiation_session
+             WHERE (?1 IS NULL OR quote_id = ?1) AND (?2 IS NULL OR created_at >= ?2)
+             ORDER BY created_at ASC, id ASC
+             LIMIT ?3",
+        )
+        .bind(quote_id)
+        .bind(since)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (session_id, quote_id, policy_version) in sessions {
+            let turns =
+                SqlNegotiationRepository::find_turns_by_session(&self.pool, &session_id).await?;
+            let mut counters = Vec::new();
+            for turn in turns.iter().filter(|turn| turn.request_type == TurnRequestType::Counter) {
+                let record = format!("negotiation:{session_id}#{}", turn.turn_number);
+                match self.recorded_counter(&quote_id, turn).await? {
+                    Ok(counter) => counters.push(counter),
+                    Err(reason) => bundle.skipped.push(SkippedRecord { record, reason }),
+                }
+            }
+            if !counters.is_empty() {
+                bundle.negotiations.push(RecordedNegotiation {
+                    session_id,
+                    quote_id,
+                    policy_version,
+                    counters,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The counter's fixture, or why it cannot be replayed.
+    async fn recorded_counter(
+        &self,
+        quote_id: &str,
+        turn: &NegotiationTurn,
+    ) -> Result<Result<RecordedCounter, String>, ReplayError> {
+        let (Some(envelope_json), Some(boundary_json)) = (&turn.envelope_json, &turn.boundary_json)
+        else {
+            return Ok(Err("turn has no recorded envelope or boundary".to_string()));
+        };
+        let verdict = match turn.outcome {
+            TurnOutcome::Offered => CounterVerdict::Offered,
+            TurnOutcome::Escalated => CounterVerdict::NeedsApproval,
+            TurnOutcome::Rejected => CounterVerdict::Blocked,
+            _ => {
+                return Ok(Err(format!(
+                    "turn outcome `{}` is not a counter check",
+                    turn.outcome.as_str()
+                )))
+            }
+        };
+        let payload: TurnPayload = decode(&turn.request_payload, "turn payload")?;
+        let Some(terms) = payload.terms else {
+            return Ok(Err("turn payload has no counter terms".to_string()));
+        };
+        let Some(checked_on) = recorded_on(&turn.created_at) else {
+            return Ok(Err(format!("turn timestamp `{}` is unreadable", turn.created_at)));
+        };
+        let Some((currency, lines)) = self.lines_at(quote_id, payload.quote_version).await? else {
+            return Ok(Err(format!(
+                "no pricing snapshot or live lines for quote version {}",
+                payload.quote_version
+            )));
+        };
+        let unit_costs = self.unit_costs(&lines, &currency, checked_on).await?;
+        let families = self.families(&lines).await?;
+
+        Ok(Ok(RecordedCounter {
+            turn_number: turn.turn_number,
+            terms,
+            lines,
+            unit_costs,
+            families,
+            expected: CounterOutcome {
+                verdict,
+                envelope: decode::<ConcessionEnvelope>(envelope_json, "concession envelope")?,
+                boundary: decode::<BoundaryEvaluation>(boundary_json, "boundary evaluation")?,
+            },
+        }))
+    }
+
+    async fn export_pricing_runs(
+        &self,
+        bundle: &mut ReplayBundle,
+        quote_id: Option<&str>,
+        since: Option<&str>,
+        limit: u32,
+    ) -> Result<(), ReplayError> {
+        let snapshots = SqlPricingSnapshotRepository::new(self.pool.clone())
+            .list_recorded(quote_id, since, limit)
+            .await?;
+        let rule_sets = PolicyRuleSetService::new(self.pool.clone());
+        for RecordedSnapshot { snapshot, policy, policy_version } in snapshots {
+            let record = format!("pricing:{}@v{}", snapshot.quote_id.0, snapshot.version);
+            if snapshot.line_items.is_empty() {
+                bundle.skipped.push(SkippedRecord {
+                    record,
+                    reason: "snapshot has no line items".to_string(),
+                });
+                continue;
+            }
+            let Some(priced_on) = recorded_on(&snapshot.created_at) else {
+                bundle.skipped.push(SkippedRecord {
+                    record,
+                    reason: format!("priced_at `{}` is unreadable", snapshot.created_at),
+                });
+                continue;
+            };
+            let lines = recorded_lines(&snapshot.line_items);
+            let unit_costs = self.unit_costs(&lines, &snapshot.currency, priced_on).await?;
+            let context = rule_sets.context_for_quote(&snapshot.quote_id.0, priced_on).await?;
+            bundle.pricing_runs.push(RecordedPricingRun {
+                quote_id: snapshot.quote_id.0.clone(),
+                version: snapshot.version,
+                currency: snapshot.currency.clone(),
+                policy_version,
+                lines,
+                unit_costs,
+                context,
+                expected: PricingOutcome::new(
+                    snapshot.subtotal,
+                    snapshot.discount_total,
+                    snapshot.total,
+                    &policy,
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Currency and lines of a quote version: from its pricing snapshot when one was recorded,
+    /// otherwise from the live quote if it is still at that version.
+    async fn lines_at(
+        &self,
+        quote_id: &str,
+        version: u32,
+    ) -> Result<Option<(String, Vec<RecordedLine>)>, ReplayError> {
+        let snapshot = SqlPricingSnapshotRepository::new(self.pool.clone())
+            .list_recorded(Some(quote_id), None, MAX_EXPORT_RECORDS)
+            .await?
+            .into_iter()
+            .find(|recorded| u32::try_from(recorded.snapshot.version).ok() == Some(version));
+        if let Some(RecordedSnapshot { snapshot, .. }) = snapshot {
+            if !snapshot.line_items.is_empty() {
+                return Ok(Some((snapshot.currency.clone(), recorded_lines(&snapshot.line_items))));
+            }
+        }
+
+        let quote = SqlQuoteRepository::new(self.pool.clone())
+            .find_by_id(&QuoteId(quote_id.into()))
+            .await?;
+        Ok(quote.filter(|quote| quote.version == version).map(|quote| {
+            let lines = quote
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(index, line)| RecordedLine {
+                    line_id: format!("{}-ql-{}", quote.id.0, index + 1),
+                    product_id: line.product_id.0.clone(),
+                    quantity: line.quantity,
+                    unit_price: line.unit_price,
+                    discount_pct: Decimal::from_f64(line.discount_pct).unwrap_or(Decimal::ZERO),
+                })
+                .collect();
+            (quote.currency, lines)
+        }))
+    }
+
+    async fn unit_costs(
+        &self,
+        lines: &[RecordedLine],
+        currency: &str,
+        as_of: NaiveDate,
+    ) -> Result<BTreeMap<String, Decimal>, ReplayError> {
+        Ok(SqlProductCostRepository::new(self.pool.clone())
+            .unit_costs_on(&product_ids(lines), currency, as_of)
+            .await?)
+    }
+
+    async fn families(
+        &self,
+        lines: &[RecordedLine],
+    ) -> Result<BTreeMap<String, String>, ReplayError> {
+        let mut families = BTreeMap::new();
+        for product_id in product_ids(lines) {
+            let family: Option<String> = sqlx::query_scalar(
+                "SELECT pf.name FROM product p
+                 JOIN product_family pf ON pf.id = p.family_id
+                 WHERE p.id = ?",
+            )
+            .bind(&product_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(family) = family {
+                families.insert(product_id, family);
+            }
+        }
+        Ok(families)
+    }
+}
+
+fn recorded_lines(line_items: &[PricingLineSnapshot]) -> Vec<RecordedLine> {
+    line_items
+        .iter()
+        .map(|line| RecordedLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            quantity: u32::try_from(line.quantity).unwrap_or_default(),
+            unit_price: line.unit_price,
+            discount_pct: line.discount_percent,
+        })
+        .collect()
+}
+
+fn product_ids(lines: &[RecordedLine]) -> Vec<String> {
+    let mut ids: Vec<String> = lines.iter().map(|line| line.product_id.clone()).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Calendar day (UTC) of a recorded timestamp.
+fn recorded_on(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|at| at.with_timezone(&Utc).date_naive())
+}
+
+fn decode<T: serde::de::DeserializeOwned>(json: &str, what: &str) -> Result<T, ReplayError> {
+    serde_json::from_str(json)
+        .map_err(|error| RepositoryError::Decode(format!("{what}: {error}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::cpq::negotiation_turn::{CounterTerms, TurnParty};
+    use quotey_core::cpq::policy::{
+        evaluate_policy_with_thresholds, PolicyInput, PolicyThresholds,
+    };
+    use quotey_core::cpq::replay::{ReplayHarness, ReplayPolicy};
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use quotey_core::{
+        policy_evaluation_from_decision, pricing_snapshot_from_lines, PricingLineSnapshot,
+    };
+    use rust_decimal::Decimal;
+
+    use super::{ExportQuery, ReplayError, ReplayService};
+    use crate::negotiation::{NegotiationService, RecordTurn, TurnAction};
+    use crate::repositories::{QuoteRepository, SqlPricingSnapshotRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    type TestResult<T> = Result<T, String>;
+
+    #[tokio::test]
+    async fn exported_counters_and_pricing_runs_replay_and_report_policy_drift() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-REPLAY-1").await?;
+        record_pricing_run(&pool, &quote_id, 25).await?;
+
+        let negotiations = NegotiationService::new(pool.clone());
+        let session = negotiations
+            .open_session(&quote_id.0, "portal:customer", "policy-v1")
+            .await
+            .map_err(|error| format!("open session: {error}"))?;
+        for discount_pct in [45.0, 39.0] {
+            negotiations
+                .record(RecordTurn {
+                    session_id: session.id.0.clone(),
+                    action: TurnAction::Counter(CounterTerms {
+                        discount_pct,
+                        term_months: Some(24),
+                    }),
+                    party: TurnParty::Customer,
+                    actor_id: "portal:customer".to_string(),
+                    note: None,
+                    idempotency_key: None,
+                    correlation_id: "corr-replay".to_string(),
+                })
+                .await
+                .map_err(|error| format!("counter {discount_pct}: {error}"))?;
+        }
+
+        let service = ReplayService::new(pool.clone());
+        let bundle = service
+            .export(ExportQuery { quote_id: Some(quote_id.0.clone()), ..ExportQuery::default() })
+            .await
+            .map_err(|error| format!("export: {error}"))?;
+        assert_eq!(bundle.negotiations.len(), 1);
+        assert_eq!(bundle.negotiations[0].counters.len(), 2);
+        assert_eq!(bundle.pricing_runs.len(), 1);
+        assert_eq!(bundle.pricing_runs[0].expected.violations, vec!["discount-cap".to_string()]);
+        assert!(bundle.skipped.is_empty(), "{:?}", bundle.skipped);
+
+        let policy = service.policy(None).await.map_err(|error| format!("policy: {error}"))?;
+        let report = ReplayHarness.replay_bundle(&bundle, &policy);
+        assert!(report.deterministic, "{:?}", report.cases);
+        assert_eq!(report.total_cases, 3);
+
+        let loosened = ReplayPolicy {
+            thresholds: PolicyThresholds {
+                manager_discount_pct: Decimal::new(30, 0),
+                ..policy.thresholds.clone()
+            },
+            ..policy
+        };
+        let report = ReplayHarness.replay_bundle(&bundle, &loosened);
+        assert_eq!(report.failed_cases, 1);
+        let drift = &report.cases[2];
+        assert_eq!(drift.fixture, "pricing:Q-REPLAY-1@v1");
+        assert!(drift.drifts.iter().any(|drift| drift.field == "policy_status"));
+
+        let missing = service.policy(Some(99)).await;
+        assert!(matches!(missing, Err(ReplayError::PolicyVersionNotFound(99))));
+        let invalid = service
+            .export(ExportQuery { since: Some("yesterday".to_string()), ..ExportQuery::default() })
+            .await;
+        assert!(matches!(invalid, Err(ReplayError::Invalid(_))));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn counters_on_unpriced_past_versions_are_skipped() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-REPLAY-2").await?;
+        let negotiations = NegotiationService::new(pool.clone());
+        let session = negotiations
+            .open_session(&quote_id.0, "U-REP", "policy-v1")
+            .await
+            .map_err(|error| format!("open session: {error}"))?;
+        negotiations
+            .record(RecordTurn {
+                session_id: session.id.0.clone(),
+                action: TurnAction::Counter(CounterTerms { discount_pct: 10.0, term_months: None }),
+                party: TurnParty::Customer,
+                actor_id: "portal:customer".to_string(),
+                note: None,
+                idempotency_key: None,
+                correlation_id: "corr-replay".to_string(),
+            })
+            .await
+            .map_err(|error| format!("counter: {error}"))?;
+
+        let quotes = SqlQuoteRepository::new(pool.clone());
+        let mut quote =
+            quotes.find_by_id(&quote_id).await.map_err(|e| e.to_string())?.ok_or("quote")?;
+        quote.version = 2;
+        quotes.save(quote).await.map_err(|error| format!("revise quote: {error}"))?;
+
+        let bundle = ReplayService::new(pool.clone())
+            .export(ExportQuery::default())
+            .await
+            .map_err(|error| format!("export: {error}"))?;
+        assert!(bundle.negotiations.is_empty());
+        assert_eq!(bundle.skipped.len(), 1);
+        assert_eq!(bundle.skipped[0].record, format!("negotiation:{}#1", session.id.0));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    async fn record_pricing_run(
+        pool: &DbPool,
+        quote_id: &QuoteId,
+        discount: i64,
+    ) -> TestResult<()> {
+        let line_subtotal = Decimal::new(1000, 0);
+        let discount_amount = line_subtotal * Decimal::new(discount, 0) / Decimal::from(100);
+        let snapshot = pricing_snapshot_from_lines(
+            quote_id,
+            1,
+            "USD",
+            vec![PricingLineSnapshot {
+                line_id: format!("{}-ql-1", quote_id.0),
+                product_id: "plan-pro".to_string(),
+                product_name: "plan-pro".to_string(),
+                quantity: 10,
+                unit_price: Decimal::new(100, 0),
+                discount_percent: Decimal::new(discount, 0),
+                discount_amount,
+                line_subtotal: line_subtotal - discount_amount,
+            }],
+            Decimal::ZERO,
+            Utc::now().to_rfc3339(),
+        );
+        let input = PolicyInput {
+            requested_discount_pct: Decimal::new(discount, 0),
+            deal_value: line_subtotal,
+            minimum_margin_pct: None,
+        };
+        let thresholds = PolicyThresholds::default();
+        let decision = evaluate_policy_with_thresholds(&input, &thresholds);
+        let policy = policy_evaluation_from_decision(
+            quote_id,
+            1,
+            &input,
+            &thresholds,
+            &decision,
+            Utc::now().to_rfc3339(),
+        );
+        SqlPricingSnapshotRepository::new(pool.clone())
+            .with_policy_version(Some(1))
+            .record_snapshot(&snapshot, Some(&policy))
+            .await
+            .map_err(|error| format!("record snapshot: {error}"))?;
+        Ok(())
+    }
+
+    async fn setup_pool() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30)
+            .await
+            .map_err(|error| format!("connect test pool: {error}"))?;
+        migrations::run_pending(&pool).await.map_err(|error| format!("run migrations: {error}"))?;
+        Ok(pool)
+    }
+
+    async fn insert_quote(pool: &DbPool, id: &str) -> TestResult<QuoteId> {
+        let now = Utc::now();
+        let quote = Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status: QuoteStatus::Sent,
+            account_id: Some("acct-replay".to_string()),
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: Some(12),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: "U-REP".to_string(),
+            lines: vec![QuoteLine {
+                product_id: ProductId("plan-pro".to_string()),
+                quantity: 10,
+                unit_price: Decimal::new(100, 0),
+                discount_pct: 0.0,
+                notes: None,
+            }],
+            created_at: now,
+            updated_at: now,
+        };
+        SqlQuoteRepository::new(pool.clone())
+            .save(quote.clone())
+            .await
+            .map_err(|error| format!("save quote fixture {id}: {error}"))?;
+        Ok(quote.id)
+    }
+}
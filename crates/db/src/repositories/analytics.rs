@@ -1,11 +1,32 @@
 use quotey_core::{AnalyticsQuerySpec, DimensionKind, MetricKind};
 use thiserror::Error;
 
+/// Bound value for a `?` placeholder in [`AnalyticsQuery::sql`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnalyticsParam {
+    Text(String),
+    Integer(i64),
+}
+
+/// SQL for a spec and its parameters in placeholder order. Dimension columns come first, then
+/// metric columns, each in spec order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalyticsQuery {
+    pub sql: String,
+    pub params: Vec<AnalyticsParam>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SqlAnalyticsQueryBuilder;
 
 impl SqlAnalyticsQueryBuilder {
-    pub fn build_query(&self, spec: &AnalyticsQuerySpec) -> Result<String, AnalyticsQueryError> {
+    /// Builds the query for `spec`. Caller-supplied values (window, filters, end date, limit)
+    /// are always bound, never spliced into the SQL. The query fetches one row past
+    /// [`AnalyticsQuerySpec::row_limit`] so callers can tell the result was truncated.
+    pub fn build_query(
+        &self,
+        spec: &AnalyticsQuerySpec,
+    ) -> Result<AnalyticsQuery, AnalyticsQueryError> {
         spec.validate().map_err(|error| AnalyticsQueryError::InvalidSpec(error.to_string()))?;
 
         let dimension_selects: Vec<String> = spec
@@ -22,14 +43,30 @@ impl SqlAnalyticsQueryBuilder {
         select_parts.extend(dimension_selects.clone());
         select_parts.extend(metric_selects);
 
+        let mut params = vec![AnalyticsParam::Text(format!("-{} days", spec.lookback_days))];
         let mut query = format!(
-            "SELECT\n  {}\nFROM quote q\nLEFT JOIN quote_line ql ON ql.quote_id = q.id\nLEFT JOIN product p ON p.id = ql.product_id\nLEFT JOIN product_family pf ON pf.id = p.family_id\nLEFT JOIN approval_request ar ON ar.quote_id = q.id\nLEFT JOIN audit_event ae ON ae.quote_id = q.id\nWHERE q.created_at >= datetime('now', '-{} days')",
-            select_parts.join(",\n  "),
-            spec.lookback_days
+            "SELECT\n  {}\nFROM quote q\nLEFT JOIN quote_line ql ON ql.quote_id = q.id\nLEFT JOIN product p ON p.id = ql.product_id\nLEFT JOIN product_family pf ON pf.id = p.family_id\nLEFT JOIN approval_request ar ON ar.quote_id = q.id\nWHERE q.created_at >= datetime('now', ?)",
+            select_parts.join(",\n  ")
         );
 
+        if let Some(end_date) = spec.end_date {
+            query.push_str("\n  AND date(q.created_at) <= ?");
+            params.push(AnalyticsParam::Text(end_date.format("%Y-%m-%d").to_string()));
+        }
+
         if spec.include_only_finalized {
-            query.push_str("\n  AND q.status IN ('approved', 'finalized', 'sent')");
+            query.push_str("\n  AND q.status IN ('approved', 'finalized', 'sent', 'accepted')");
+        }
+
+        for filter in &spec.filters {
+            let placeholders = vec!["?"; filter.values.len()].join(", ");
+            query.push_str(&format!(
+                "\n  AND {} IN ({placeholders})",
+                dimension_sql(&filter.dimension)
+            ));
+            params.extend(
+                filter.values.iter().map(|value| AnalyticsParam::Text(value.trim().to_string())),
+            );
         }
 
         if !spec.dimensions.is_empty() {
@@ -39,7 +76,10 @@ impl SqlAnalyticsQueryBuilder {
             query.push_str(&format!("\nORDER BY {group_by}"));
         }
 
-        Ok(query)
+        query.push_str("\nLIMIT ?");
+        params.push(AnalyticsParam::Integer(i64::from(spec.row_limit()) + 1));
+
+        Ok(AnalyticsQuery { sql: query, params })
     }
 }
 
@@ -52,8 +92,13 @@ fn dimension_sql(dimension: &DimensionKind) -> &'static str {
             "printf('%s-Q%d', strftime('%Y', q.created_at), ((cast(strftime('%m', q.created_at) as integer)-1)/3)+1)"
         }
         DimensionKind::CustomerSegment => "COALESCE(q.account_id, 'unknown')",
-        DimensionKind::Industry => "COALESCE(json_extract(q.notes, '$.industry'), 'unknown')",
-        DimensionKind::Region => "COALESCE(json_extract(q.notes, '$.region'), 'unknown')",
+        // `notes` is free text for most quotes; json_extract raises on anything but JSON.
+        DimensionKind::Industry => {
+            "COALESCE(CASE WHEN json_valid(q.notes) THEN json_extract(q.notes, '$.industry') END, 'unknown')"
+        }
+        DimensionKind::Region => {
+            "COALESCE(CASE WHEN json_valid(q.notes) THEN json_extract(q.notes, '$.region') END, 'unknown')"
+        }
         DimensionKind::SalesRep => "COALESCE(q.created_by_sales_rep_id, q.created_by, 'unknown')",
         DimensionKind::ProductFamily => "COALESCE(pf.name, 'unknown')",
         DimensionKind::ApprovalRole => "COALESCE(ar.approver_role, 'none')",
@@ -79,20 +124,21 @@ fn metric_sql(metric: &MetricKind) -> &'static str {
     match metric {
         MetricKind::QuoteCount => "COUNT(DISTINCT q.id) AS metric_quote_count",
         MetricKind::WinRatePct => {
-            "ROUND(AVG(CASE WHEN q.status IN ('approved','finalized','sent') THEN 100.0 ELSE 0.0 END), 2) AS metric_win_rate_pct"
+            "ROUND(AVG(CASE WHEN q.status IN ('approved','finalized','sent','accepted') THEN 100.0 ELSE 0.0 END), 2) AS metric_win_rate_pct"
         }
         MetricKind::AvgDiscountPct => "ROUND(AVG(COALESCE(ql.discount_pct, 0)), 2) AS metric_avg_discount_pct",
         MetricKind::AvgDealValue => {
             "ROUND(AVG(COALESCE(ql.unit_price, 0) * COALESCE(ql.quantity, 0)), 2) AS metric_avg_deal_value"
         }
         MetricKind::ApprovalCycleHours => {
-            "ROUND(AVG((julianday(ar.updated_at) - julianday(ar.created_at)) * 24.0), 2) AS metric_approval_cycle_hours"
+            "ROUND(AVG(CASE WHEN ar.status IN ('approved', 'rejected') THEN (julianday(ar.updated_at) - julianday(ar.created_at)) * 24.0 END), 2) AS metric_approval_cycle_hours"
         }
         MetricKind::TimeToFinalizeHours => {
             "ROUND(AVG((julianday(q.updated_at) - julianday(q.created_at)) * 24.0), 2) AS metric_time_to_finalize_hours"
         }
+        // Checked per quote rather than joined, so audit history does not multiply line rows.
         MetricKind::AnomalyRatePct => {
-            "ROUND(AVG(CASE WHEN ae.event_type LIKE 'anomaly.%' THEN 100.0 ELSE 0.0 END), 2) AS metric_anomaly_rate_pct"
+            "ROUND(AVG(CASE WHEN EXISTS (SELECT 1 FROM audit_event ae WHERE ae.quote_id = q.id AND ae.event_type LIKE 'anomaly.%') THEN 100.0 ELSE 0.0 END), 2) AS metric_anomaly_rate_pct"
         }
     }
 }
@@ -105,9 +151,11 @@ pub enum AnalyticsQueryError {
 
 #[cfg(test)]
 mod tests {
-    use quotey_core::{AnalyticsQuerySpec, DimensionKind, MetricKind, ANALYTICS_SCHEMA_VERSION};
+    use quotey_core::{
+        AnalyticsFilter, AnalyticsQuerySpec, DimensionKind, MetricKind, ANALYTICS_SCHEMA_VERSION,
+    };
 
-    use super::SqlAnalyticsQueryBuilder;
+    use super::{AnalyticsParam, SqlAnalyticsQueryBuilder};
 
     #[test]
     fn build_query_includes_metrics_dimensions_and_filters() {
@@ -118,16 +166,24 @@ mod tests {
             dimensions: vec![DimensionKind::Month, DimensionKind::Region],
             lookback_days: 90,
             include_only_finalized: true,
+            filters: Vec::new(),
+            end_date: None,
+            limit: None,
         };
 
-        let sql = builder.build_query(&spec).expect("query should build");
+        let query = builder.build_query(&spec).expect("query should build");
+        let sql = query.sql;
         assert!(sql.contains("metric_quote_count"));
         assert!(sql.contains("metric_win_rate_pct"));
         assert!(sql.contains("dim_month"));
         assert!(sql.contains("dim_region"));
         assert!(sql.contains("GROUP BY dim_month, dim_region"));
-        assert!(sql.contains("q.status IN ('approved', 'finalized', 'sent')"));
-        assert!(sql.contains("datetime('now', '-90 days')"));
+        assert!(sql.contains("q.status IN ('approved', 'finalized', 'sent', 'accepted')"));
+        assert!(sql.contains("datetime('now', ?)"));
+        assert_eq!(
+            query.params,
+            vec![AnalyticsParam::Text("-90 days".to_string()), AnalyticsParam::Integer(1_001)]
+        );
     }
 
     #[test]
@@ -139,10 +195,43 @@ mod tests {
             dimensions: vec![],
             lookback_days: 30,
             include_only_finalized: false,
+            filters: Vec::new(),
+            end_date: None,
+            limit: None,
         };
 
-        let sql = builder.build_query(&spec).expect("query should build");
+        let sql = builder.build_query(&spec).expect("query should build").sql;
         assert!(sql.contains("metric_avg_discount_pct"));
         assert!(!sql.contains("GROUP BY"));
     }
+
+    #[test]
+    fn filter_values_are_bound_not_interpolated() {
+        let mut spec = AnalyticsQuerySpec::new(
+            vec![MetricKind::QuoteCount],
+            vec![DimensionKind::SalesRep],
+            30,
+        );
+        spec.filters.push(AnalyticsFilter {
+            dimension: DimensionKind::Region,
+            values: vec!["emea".to_string(), "x') OR 1=1 --".to_string()],
+        });
+        spec.end_date = Some("2026-09-30".parse().unwrap());
+        spec.limit = Some(50);
+
+        let query = SqlAnalyticsQueryBuilder.build_query(&spec).expect("query should build");
+        assert!(!query.sql.contains("emea") && !query.sql.contains("OR 1=1"));
+        assert!(query.sql.contains("IN (?, ?)"));
+        assert!(query.sql.contains("date(q.created_at) <= ?"));
+        assert_eq!(
+            query.params,
+            vec![
+                AnalyticsParam::Text("-30 days".to_string()),
+                AnalyticsParam::Text("2026-09-30".to_string()),
+                AnalyticsParam::Text("emea".to_string()),
+                AnalyticsParam::Text("x') OR 1=1 --".to_string()),
+                AnalyticsParam::Integer(51),
+            ]
+        );
+    }
 }
@@ -0,0 +1,317 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use quotey_core::domain::auth::{ApiKeyGrants, ApiKeyRecord, ApiScope};
+
+use crate::DbPool;
+
+use super::RepositoryError;
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, record: &ApiKeyRecord) -> Result<(), RepositoryError>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<ApiKeyRecord>, RepositoryError>;
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyRecord>, RepositoryError>;
+    async fn list(&self, include_revoked: bool) -> Result<Vec<ApiKeyRecord>, RepositoryError>;
+    /// Whether any key was ever created, active or revoked.
+    async fn has_keys(&self) -> Result<bool, RepositoryError>;
+    /// Deactivate a key. Returns `false` when the key does not exist or is already revoked.
+    async fn revoke(&self, id: &str, at: DateTime<Utc>) -> Result<bool, RepositoryError>;
+    /// Atomically revoke `old_id` and insert `replacement` linked to it.
+    ///
+    /// Returns `false` (and writes nothing) when `old_id` is not an active key.
+    async fn rotate(
+        &self,
+        old_id: &str,
+        replacement: &ApiKeyRecord,
+    ) -> Result<bool, RepositoryError>;
+    async fn touch_last_used(&self, id: &str, at: DateTime<Utc>) -> Result<(), RepositoryError>;
+}
+
+// ---------------------------------------------------------------------------
+// SQL implementation
+// ---------------------------------------------------------------------------
+
+pub struct SqlApiKeyRepository {
+    pool: DbPool,
+}
+
+impl SqlApiKeyRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+const SELECT_COLUMNS: &str = "id, name, key_hash, key_prefix, scopes_json, account_ids_json, \
+     team_ids_json, requests_per_minute, active, rotated_from, created_at, revoked_at, last_used_at";
+
+const INSERT_SQL: &str = "INSERT INTO api_key
+        (id, name, key_hash, key_prefix, scopes_json, account_ids_json, team_ids_json,
+         requests_per_minute, active, rotated_from, created_at, revoked_at, last_used_at)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+
+fn encode_list<T: serde::Serialize>(values: &[T]) -> Result<String, RepositoryError> {
+    serde_json::to_string(values).map_err(|e| RepositoryError::Decode(e.to_string()))
+}
+
+fn decode_list<T: serde::de::DeserializeOwned>(
+    column: &str,
+    raw: &str,
+) -> Result<Vec<T>, RepositoryError> {
+    serde_json::from_str(raw).map_err(|e| RepositoryError::Decode(format!("{column}: {e}")))
+}
+
+fn parse_timestamp(column: &str, raw: &str) -> Result<DateTime<Utc>, RepositoryError> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|e| RepositoryError::Decode(format!("{column}: {e}")))
+}
+
+fn parse_optional_timestamp(
+    column: &str,
+    raw: Option<String>,
+) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+    raw.map(|value| parse_timestamp(column, &value)).transpose()
+}
+
+fn row_to_record(row: sqlx::sqlite::SqliteRow) -> Result<ApiKeyRecord, RepositoryError> {
+    let scopes_json: String = row.get("scopes_json");
+    let account_ids_json: String = row.get("account_ids_json");
+    let team_ids_json: String = row.get("team_ids_json");
+    let created_at: String = row.get("created_at");
+    let requests_per_minute: i64 = row.get("requests_per_minute");
+    let active: i64 = row.get("active");
+
+    Ok(ApiKeyRecord {
+        id: row.get("id"),
+        name: row.get("name"),
+        key_hash: row.get("key_hash"),
+        key_prefix: row.get("key_prefix"),
+        grants: ApiKeyGrants {
+            scopes: decode_list::<ApiScope>("scopes_json", &scopes_json)?,
+            account_ids: decode_list("account_ids_json", &account_ids_json)?,
+            team_ids: decode_list("team_ids_json", &team_ids_json)?,
+        },
+        requests_per_minute: u32::try_from(requests_per_minute).map_err(|_| {
+            RepositoryError::Decode(format!(
+                "requests_per_minute out of range: {requests_per_minute}"
+            ))
+        })?,
+        active: active != 0,
+        rotated_from: row.get("rotated_from"),
+        created_at: parse_timestamp("created_at", &created_at)?,
+        revoked_at: parse_optional_timestamp("revoked_at", row.get("revoked_at"))?,
+        last_used_at: parse_optional_timestamp("last_used_at", row.get("last_used_at"))?,
+    })
+}
+
+async fn insert_record<'e, E>(executor: E, record: &ApiKeyRecord) -> Result<(), RepositoryError>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    sqlx::query(INSERT_SQL)
+        .bind(&record.id)
+        .bind(&record.name)
+        .bind(&record.key_hash)
+        .bind(&record.key_prefix)
+        .bind(encode_list(&record.grants.scopes)?)
+        .bind(encode_list(&record.grants.account_ids)?)
+        .bind(encode_list(&record.grants.team_ids)?)
+        .bind(i64::from(record.requests_per_minute))
+        .bind(i64::from(record.active))
+        .bind(&record.rotated_from)
+        .bind(record.created_at.to_rfc3339())
+        .bind(record.revoked_at.map(|ts| ts.to_rfc3339()))
+        .bind(record.last_used_at.map(|ts| ts.to_rfc3339()))
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[async_trait]
+impl ApiKeyRepository for SqlApiKeyRepository {
+    async fn create(&self, record: &ApiKeyRecord) -> Result<(), RepositoryError> {
+        insert_record(&self.pool, record).await
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<ApiKeyRecord>, RepositoryError> {
+        let sql = format!("SELECT {SELECT_COLUMNS} FROM api_key WHERE id = ?");
+        let row = sqlx::query(&sql).bind(id).fetch_optional(&self.pool).await?;
+        row.map(row_to_record).transpose()
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyRecord>, RepositoryError> {
+        let sql = format!("SELECT {SELECT_COLUMNS} FROM api_key WHERE key_hash = ?");
+        let row = sqlx::query(&sql).bind(key_hash).fetch_optional(&self.pool).await?;
+        row.map(row_to_record).transpose()
+    }
+
+    async fn list(&self, include_revoked: bool) -> Result<Vec<ApiKeyRecord>, RepositoryError> {
+        let sql = if include_revoked {
+            format!("SELECT {SELECT_COLUMNS} FROM api_key ORDER BY name, created_at")
+        } else {
+            format!(
+                "SELECT {SELECT_COLUMNS} FROM api_key WHERE active = 1 ORDER BY name, created_at"
+            )
+        };
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        rows.into_iter().map(row_to_record).collect()
+    }
+
+    async fn has_keys(&self) -> Result<bool, RepositoryError> {
+        let exists: i64 = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM api_key)")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists != 0)
+    }
+
+    async fn revoke(&self, id: &str, at: DateTime<Utc>) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE api_key SET active = 0, revoked_at = ? WHERE id = ? AND active = 1",
+        )
+        .bind(at.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn rotate(
+        &self,
+        old_id: &str,
+        replacement: &ApiKeyRecord,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let revoked = sqlx::query(
+            "UPDATE api_key SET active = 0, revoked_at = ? WHERE id = ? AND active = 1",
+        )
+        .bind(replacement.created_at.to_rfc3339())
+        .bind(old_id)
+        .execute(&mut *tx)
+        .await?;
+        if revoked.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+        insert_record(&mut *tx, replacement).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn touch_last_used(&self, id: &str, at: DateTime<Utc>) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE api_key SET last_used_at = ? WHERE id = ?")
+            .bind(at.to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connect_with_settings, migrations};
+    use quotey_core::domain::auth::hash_api_key;
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.unwrap();
+        migrations::run_pending(&pool).await.unwrap();
+        pool
+    }
+
+    fn make_record(id: &str, secret: &str, scopes: Vec<ApiScope>) -> ApiKeyRecord {
+        ApiKeyRecord {
+            id: id.to_string(),
+            name: format!("{id}-name"),
+            key_hash: hash_api_key(secret),
+            key_prefix: secret.chars().take(8).collect(),
+            grants: ApiKeyGrants {
+                scopes,
+                account_ids: vec!["acct-1".to_string()],
+                team_ids: Vec::new(),
+            },
+            requests_per_minute: 30,
+            active: true,
+            rotated_from: None,
+            created_at: Utc::now(),
+            revoked_at: None,
+            last_used_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_and_find_by_hash_round_trips_grants() {
+        let repo = SqlApiKeyRepository::new(setup().await);
+        let record = make_record("key-1", "secret-one", vec![ApiScope::QuoteRead]);
+        repo.create(&record).await.unwrap();
+
+        let found = repo.find_by_hash(&hash_api_key("secret-one")).await.unwrap().unwrap();
+        assert_eq!(found.id, "key-1");
+        assert_eq!(found.grants, record.grants);
+        assert_eq!(found.requests_per_minute, 30);
+        assert!(found.active);
+        assert!(repo.find_by_hash(&hash_api_key("other")).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn duplicate_hash_is_rejected() {
+        let repo = SqlApiKeyRepository::new(setup().await);
+        repo.create(&make_record("key-1", "same", vec![ApiScope::All])).await.unwrap();
+        assert!(repo.create(&make_record("key-2", "same", vec![ApiScope::All])).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn revoke_hides_key_from_active_list() {
+        let repo = SqlApiKeyRepository::new(setup().await);
+        assert!(!repo.has_keys().await.unwrap());
+        repo.create(&make_record("key-1", "secret-one", vec![ApiScope::All])).await.unwrap();
+
+        assert!(repo.revoke("key-1", Utc::now()).await.unwrap());
+        assert!(!repo.revoke("key-1", Utc::now()).await.unwrap());
+        assert!(repo.list(false).await.unwrap().is_empty());
+        assert!(repo.has_keys().await.unwrap(), "revoked keys still count");
+
+        let all = repo.list(true).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert!(!all[0].active);
+        assert!(all[0].revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn rotate_revokes_old_key_and_links_replacement() {
+        let repo = SqlApiKeyRepository::new(setup().await);
+        repo.create(&make_record("key-1", "secret-one", vec![ApiScope::QuoteWrite])).await.unwrap();
+
+        let mut replacement = make_record("key-2", "secret-two", vec![ApiScope::QuoteWrite]);
+        replacement.rotated_from = Some("key-1".to_string());
+        assert!(repo.rotate("key-1", &replacement).await.unwrap());
+
+        let old = repo.find_by_id("key-1").await.unwrap().unwrap();
+        assert!(!old.active);
+        let new = repo.find_by_id("key-2").await.unwrap().unwrap();
+        assert_eq!(new.rotated_from.as_deref(), Some("key-1"));
+
+        let again = make_record("key-3", "secret-three", vec![ApiScope::QuoteWrite]);
+        assert!(!repo.rotate("key-1", &again).await.unwrap());
+        assert!(repo.find_by_id("key-3").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn touch_last_used_records_timestamp() {
+        let repo = SqlApiKeyRepository::new(setup().await);
+        repo.create(&make_record("key-1", "secret-one", vec![ApiScope::All])).await.unwrap();
+        repo.touch_last_used("key-1", Utc::now()).await.unwrap();
+        let found = repo.find_by_id("key-1").await.unwrap().unwrap();
+        assert!(found.last_used_at.is_some());
+    }
+}
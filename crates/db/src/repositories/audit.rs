@@ -18,6 +18,14 @@ impl SqlAuditEventRepository {
 
     /// Persist an audit event to the `audit_event` table.
     pub async fn save(&self, event: &AuditEvent) -> Result<(), RepositoryError> {
+        Self::save_with(&self.pool, event).await
+    }
+
+    /// Persist an audit event on `executor`, e.g. inside the transaction it describes.
+    pub async fn save_with<'e, E>(executor: E, event: &AuditEvent) -> Result<(), RepositoryError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
         let quote_id = event.quote_id.as_ref().map(|q| q.0.as_str());
         let category = format!("{:?}", event.category);
 
@@ -52,7 +60,7 @@ impl SqlAuditEventRepository {
         .bind(event.action.as_ref().map(|a| a.as_str()))
         .bind(&event.before_json)
         .bind(&event.after_json)
-        .execute(&self.pool)
+        .execute(executor)
         .await?;
 
         Ok(())
@@ -5,9 +5,9 @@
 use async_trait::async_trait;
 use quotey_core::chrono::{DateTime, Utc};
 use quotey_core::domain::explanation::{
-    CreateExplanationRequest, EvidenceType, ExplanationAuditEvent, ExplanationEventType,
-    ExplanationEvidence, ExplanationEvidenceId, ExplanationRequest, ExplanationRequestId,
-    ExplanationRequestType, ExplanationStats, ExplanationStatus,
+    CreateExplanationRequest, EvidenceType, ExplanationAuditEvent, ExplanationCache,
+    ExplanationEventType, ExplanationEvidence, ExplanationEvidenceId, ExplanationRequest,
+    ExplanationRequestId, ExplanationRequestType, ExplanationStats, ExplanationStatus,
 };
 use quotey_core::domain::quote::{QuoteId, QuoteLineId};
 use sqlx::{sqlite::SqliteRow, Row};
@@ -47,6 +47,13 @@ pub trait ExplanationRepository: Send + Sync {
         latency_ms: Option<i32>,
     ) -> Result<(), RepositoryError>;
 
+    /// Record which pricing snapshot an explanation was derived from
+    async fn attach_pricing_snapshot(
+        &self,
+        id: &ExplanationRequestId,
+        pricing_snapshot_id: &str,
+    ) -> Result<(), RepositoryError>;
+
     /// Add evidence to an explanation
     async fn add_evidence(
         &self,
@@ -83,6 +90,16 @@ pub trait ExplanationRepository: Send + Sync {
 
     /// Get explanation statistics
     async fn get_stats(&self) -> Result<ExplanationStats, RepositoryError>;
+
+    /// Look up an unexpired cached response, recording the hit
+    async fn get_cached_response(
+        &self,
+        cache_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ExplanationCache>, RepositoryError>;
+
+    /// Store (or replace) a cached response keyed by `cache_key`
+    async fn put_cached_response(&self, entry: ExplanationCache) -> Result<(), RepositoryError>;
 }
 
 /// SQLite implementation of ExplanationRepository
@@ -221,6 +238,20 @@ impl ExplanationRepository for SqlExplanationRepository {
         Ok(())
     }
 
+    async fn attach_pricing_snapshot(
+        &self,
+        id: &ExplanationRequestId,
+        pricing_snapshot_id: &str,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE explanation_requests SET pricing_snapshot_id = ? WHERE id = ?")
+            .bind(pricing_snapshot_id)
+            .bind(&id.0)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     async fn add_evidence(
         &self,
         request_id: &ExplanationRequestId,
@@ -355,6 +386,81 @@ impl ExplanationRepository for SqlExplanationRepository {
 
         Ok(explanation_stats_from_row(&row)?)
     }
+
+    async fn get_cached_response(
+        &self,
+        cache_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ExplanationCache>, RepositoryError> {
+        let now = now.to_rfc3339();
+        let updated = sqlx::query(
+            r#"
+            UPDATE explanation_response_cache
+            SET hit_count = hit_count + 1, last_hit_at = ?
+            WHERE cache_key = ? AND expires_at > ?
+            "#,
+        )
+        .bind(&now)
+        .bind(cache_key)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            r#"
+            SELECT
+                id, cache_key, quote_id, line_id, quote_version, pricing_snapshot_id,
+                explanation_summary, evidence_refs_json, hit_count, last_hit_at,
+                created_at, expires_at
+            FROM explanation_response_cache
+            WHERE cache_key = ?
+            "#,
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| explanation_cache_from_row(&r)).transpose()
+    }
+
+    async fn put_cached_response(&self, entry: ExplanationCache) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO explanation_response_cache (
+                id, cache_key, quote_id, line_id, quote_version, pricing_snapshot_id,
+                explanation_summary, evidence_refs_json, hit_count, last_hit_at,
+                created_at, expires_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(cache_key) DO UPDATE SET
+                pricing_snapshot_id = excluded.pricing_snapshot_id,
+                explanation_summary = excluded.explanation_summary,
+                evidence_refs_json = excluded.evidence_refs_json,
+                hit_count = 0,
+                last_hit_at = NULL,
+                created_at = excluded.created_at,
+                expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(&entry.id)
+        .bind(&entry.cache_key)
+        .bind(&entry.quote_id.0)
+        .bind(entry.line_id.as_ref().map(|l| &l.0))
+        .bind(entry.quote_version)
+        .bind(&entry.pricing_snapshot_id)
+        .bind(&entry.explanation_summary)
+        .bind(&entry.evidence_refs_json)
+        .bind(entry.hit_count)
+        .bind(entry.last_hit_at.map(|ts| ts.to_rfc3339()))
+        .bind(entry.created_at.to_rfc3339())
+        .bind(entry.expires_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
 
 // Helper functions for row mapping
@@ -431,6 +537,29 @@ fn explanation_audit_event_from_row(
     })
 }
 
+fn explanation_cache_from_row(row: &SqliteRow) -> Result<ExplanationCache, RepositoryError> {
+    let quote_id: String = row.try_get("quote_id")?;
+    let line_id: Option<String> = row.try_get("line_id")?;
+    let last_hit_at: Option<String> = row.try_get("last_hit_at")?;
+    let created_at: String = row.try_get("created_at")?;
+    let expires_at: String = row.try_get("expires_at")?;
+
+    Ok(ExplanationCache {
+        id: row.try_get("id")?,
+        cache_key: row.try_get("cache_key")?,
+        quote_id: QuoteId(quote_id),
+        line_id: line_id.map(QuoteLineId),
+        quote_version: row.try_get("quote_version")?,
+        pricing_snapshot_id: row.try_get("pricing_snapshot_id")?,
+        explanation_summary: row.try_get("explanation_summary")?,
+        evidence_refs_json: row.try_get("evidence_refs_json")?,
+        hit_count: row.try_get("hit_count")?,
+        last_hit_at: last_hit_at.map(|ts| parse_timestamp("last_hit_at", ts)).transpose()?,
+        created_at: parse_timestamp("created_at", created_at)?,
+        expires_at: parse_timestamp("expires_at", expires_at)?,
+    })
+}
+
 fn explanation_stats_from_row(row: &SqliteRow) -> Result<ExplanationStats, RepositoryError> {
     let last_updated_at: String = row.try_get("last_updated_at")?;
 
@@ -453,9 +582,10 @@ fn parse_timestamp(column: &str, value: String) -> Result<DateTime<Utc>, Reposit
 
 #[cfg(test)]
 mod tests {
+    use quotey_core::chrono::{Duration, Utc};
     use quotey_core::domain::explanation::{
-        CreateExplanationRequest, EvidenceType, ExplanationEventType, ExplanationRequestType,
-        ExplanationStatus,
+        CreateExplanationRequest, EvidenceType, ExplanationCache, ExplanationEventType,
+        ExplanationRequestType, ExplanationStatus,
     };
     use quotey_core::domain::quote::{QuoteId, QuoteLineId};
 
@@ -684,6 +814,63 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn sql_explanation_repo_cache_counts_hits_and_honours_expiry() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = QuoteId("Q-EXP-REQ-004".to_string());
+        insert_quote(&pool, &quote_id).await?;
+        let repo = SqlExplanationRepository::new(pool.clone());
+        let now = Utc::now();
+
+        repo.put_cached_response(ExplanationCache {
+            id: "exp-cache-1".to_string(),
+            cache_key: "key-1".to_string(),
+            quote_id: quote_id.clone(),
+            line_id: None,
+            quote_version: 1,
+            pricing_snapshot_id: "psnap-1".to_string(),
+            explanation_summary: "Total is $900.00".to_string(),
+            evidence_refs_json: "[]".to_string(),
+            hit_count: 0,
+            last_hit_at: None,
+            created_at: now,
+            expires_at: now + Duration::hours(1),
+        })
+        .await
+        .map_err(|error| format!("put cache: {error}"))?;
+
+        let first = repo
+            .get_cached_response("key-1", now)
+            .await
+            .map_err(|error| format!("get cache: {error}"))?
+            .ok_or_else(|| "cache entry should be present".to_string())?;
+        if first.hit_count != 1 || first.last_hit_at.is_none() {
+            return Err("cache test: first hit should be recorded".to_string());
+        }
+        if first.explanation_summary != "Total is $900.00" {
+            return Err("cache test: summary mismatch".to_string());
+        }
+
+        let expired = repo
+            .get_cached_response("key-1", now + Duration::hours(2))
+            .await
+            .map_err(|error| format!("get expired cache: {error}"))?;
+        if expired.is_some() {
+            return Err("cache test: expired entry should not be returned".to_string());
+        }
+
+        let missing = repo
+            .get_cached_response("key-2", now)
+            .await
+            .map_err(|error| format!("get missing cache: {error}"))?;
+        if missing.is_some() {
+            return Err("cache test: unknown key should miss".to_string());
+        }
+
+        pool.close().await;
+        Ok(())
+    }
+
     async fn setup_pool() -> TestResult<DbPool> {
         let pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 30)
             .await
@@ -0,0 +1,297 @@
+use quotey_core::chrono::Utc;
+use quotey_core::ghost::GhostFeedback;
+use sqlx::{sqlite::SqliteRow, Row};
+
+use super::RepositoryError;
+use crate::DbPool;
+
+/// Row in `buying_signals`: a Slack message the detector scored above threshold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuyingSignalRecord {
+    pub id: String,
+    pub slack_channel_id: String,
+    pub slack_message_id: String,
+    pub signal_type: String,
+    /// Detector confidence scaled to 0.0..=1.0.
+    pub confidence_score: f64,
+    pub detected_company: Option<String>,
+    pub extracted_intent: String,
+    pub matched_rep_id: Option<String>,
+    /// `new`, `processed` or `dismissed`.
+    pub status: String,
+    pub created_at: String,
+}
+
+/// Row in `ghost_quotes`: an auto-drafted quote awaiting rep review.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GhostQuoteRecord {
+    pub id: String,
+    pub signal_id: String,
+    pub draft_quote_id: Option<String>,
+    pub confidence_score: f64,
+    pub rep_notified_at: Option<String>,
+    /// `accepted`, `dismissed`, or `None` while pending.
+    pub rep_response: Option<String>,
+    pub created_at: String,
+}
+
+pub struct SqlGhostQuoteRepository {
+    pool: DbPool,
+}
+
+impl SqlGhostQuoteRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_signal(&self, signal: &BuyingSignalRecord) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO buying_signals
+                (id, slack_channel_id, slack_message_id, signal_type, confidence_score,
+                 detected_company, extracted_intent, matched_rep_id, status, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&signal.id)
+        .bind(&signal.slack_channel_id)
+        .bind(&signal.slack_message_id)
+        .bind(&signal.signal_type)
+        .bind(signal.confidence_score)
+        .bind(&signal.detected_company)
+        .bind(&signal.extracted_intent)
+        .bind(&signal.matched_rep_id)
+        .bind(&signal.status)
+        .bind(&signal.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_signal(
+        &self,
+        id: &str,
+    ) -> Result<Option<BuyingSignalRecord>, RepositoryError> {
+        let row = sqlx::query(&format!("{SIGNAL_SELECT} WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(signal_from_row).transpose()
+    }
+
+    /// Signal already recorded for a Slack message, if any; Slack redelivers events.
+    pub async fn find_signal_by_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<Option<BuyingSignalRecord>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{SIGNAL_SELECT} WHERE slack_channel_id = ? AND slack_message_id = ? LIMIT 1"
+        ))
+        .bind(channel_id)
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(signal_from_row).transpose()
+    }
+
+    pub async fn set_signal_status(&self, id: &str, status: &str) -> Result<(), RepositoryError> {
+        sqlx::query("UPDATE buying_signals SET status = ? WHERE id = ?")
+            .bind(status)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn insert_ghost_quote(
+        &self,
+        ghost: &GhostQuoteRecord,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO ghost_quotes
+                (id, signal_id, draft_quote_id, confidence_score, rep_notified_at, rep_response,
+                 created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&ghost.id)
+        .bind(&ghost.signal_id)
+        .bind(&ghost.draft_quote_id)
+        .bind(ghost.confidence_score)
+        .bind(&ghost.rep_notified_at)
+        .bind(&ghost.rep_response)
+        .bind(&ghost.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_ghost_quote(
+        &self,
+        id: &str,
+    ) -> Result<Option<GhostQuoteRecord>, RepositoryError> {
+        let row = sqlx::query(&format!("{GHOST_SELECT} WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(ghost_from_row).transpose()
+    }
+
+    /// Drafts no rep has responded to yet, newest first.
+    pub async fn list_pending(&self, limit: u32) -> Result<Vec<GhostQuoteRecord>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{GHOST_SELECT} WHERE rep_response IS NULL ORDER BY created_at DESC, id DESC LIMIT ?"
+        ))
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(ghost_from_row).collect()
+    }
+
+    pub async fn mark_notified(&self, id: &str) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE ghost_quotes SET rep_notified_at = ? WHERE id = ? AND rep_notified_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records `accepted` or `dismissed`. Returns `false` when the draft was already answered.
+    pub async fn record_response(&self, id: &str, response: &str) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE ghost_quotes SET rep_response = ? WHERE id = ? AND rep_response IS NULL",
+        )
+        .bind(response)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Accepted vs dismissed counts across all answered drafts.
+    pub async fn feedback(&self) -> Result<GhostFeedback, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT
+                COALESCE(SUM(CASE WHEN rep_response = 'accepted' THEN 1 ELSE 0 END), 0) AS accepted,
+                COALESCE(SUM(CASE WHEN rep_response = 'dismissed' THEN 1 ELSE 0 END), 0) AS dismissed
+             FROM ghost_quotes",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let count = |column: &str| -> Result<u32, RepositoryError> {
+            let value: i64 = row.try_get(column)?;
+            u32::try_from(value).map_err(|_| RepositoryError::Decode(format!("{column} overflow")))
+        };
+        Ok(GhostFeedback { accepted: count("accepted")?, dismissed: count("dismissed")? })
+    }
+}
+
+const SIGNAL_SELECT: &str = "SELECT id, slack_channel_id, slack_message_id, signal_type,
+        confidence_score, detected_company, extracted_intent, matched_rep_id, status, created_at
+     FROM buying_signals";
+
+const GHOST_SELECT: &str = "SELECT id, signal_id, draft_quote_id, confidence_score,
+        rep_notified_at, rep_response, created_at
+     FROM ghost_quotes";
+
+fn signal_from_row(row: &SqliteRow) -> Result<BuyingSignalRecord, RepositoryError> {
+    Ok(BuyingSignalRecord {
+        id: row.try_get("id")?,
+        slack_channel_id: row.try_get("slack_channel_id")?,
+        slack_message_id: row.try_get("slack_message_id")?,
+        signal_type: row.try_get("signal_type")?,
+        confidence_score: row.try_get("confidence_score")?,
+        detected_company: row.try_get("detected_company")?,
+        extracted_intent: row.try_get("extracted_intent")?,
+        matched_rep_id: row.try_get("matched_rep_id")?,
+        status: row.try_get("status")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn ghost_from_row(row: &SqliteRow) -> Result<GhostQuoteRecord, RepositoryError> {
+    Ok(GhostQuoteRecord {
+        id: row.try_get("id")?,
+        signal_id: row.try_get("signal_id")?,
+        draft_quote_id: row.try_get("draft_quote_id")?,
+        confidence_score: row.try_get("confidence_score")?,
+        rep_notified_at: row.try_get("rep_notified_at")?,
+        rep_response: row.try_get("rep_response")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuyingSignalRecord, GhostQuoteRecord, SqlGhostQuoteRepository};
+    use crate::{connect_with_settings, migrations};
+
+    fn signal(id: &str, message_id: &str) -> BuyingSignalRecord {
+        BuyingSignalRecord {
+            id: id.to_string(),
+            slack_channel_id: "C1".to_string(),
+            slack_message_id: message_id.to_string(),
+            signal_type: "expansion".to_string(),
+            confidence_score: 0.8,
+            detected_company: Some("Acme Corp".to_string()),
+            extracted_intent: "expand".to_string(),
+            matched_rep_id: Some("U1".to_string()),
+            status: "new".to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn ghost(id: &str, signal_id: &str) -> GhostQuoteRecord {
+        GhostQuoteRecord {
+            id: id.to_string(),
+            signal_id: signal_id.to_string(),
+            draft_quote_id: None,
+            confidence_score: 0.9,
+            rep_notified_at: None,
+            rep_response: None,
+            created_at: format!("2026-01-01T00:00:0{}Z", id.len() % 10),
+        }
+    }
+
+    #[tokio::test]
+    async fn signals_and_ghost_quotes_round_trip_with_feedback_counts() {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        let repo = SqlGhostQuoteRepository::new(pool);
+
+        for (id, message) in [("SIG-1", "1.1"), ("SIG-2", "1.2")] {
+            repo.insert_signal(&signal(id, message)).await.expect("insert signal");
+        }
+        let found = repo.find_signal_by_message("C1", "1.2").await.expect("find").expect("some");
+        assert_eq!(found, signal("SIG-2", "1.2"));
+        assert!(repo.find_signal_by_message("C2", "1.2").await.expect("find").is_none());
+
+        repo.set_signal_status("SIG-1", "processed").await.expect("status");
+        assert_eq!(
+            repo.find_signal("SIG-1").await.expect("find").expect("some").status,
+            "processed"
+        );
+
+        repo.insert_ghost_quote(&ghost("GQ-1", "SIG-1")).await.expect("insert ghost");
+        repo.insert_ghost_quote(&ghost("GQ-22", "SIG-2")).await.expect("insert ghost");
+        assert_eq!(repo.list_pending(10).await.expect("pending").len(), 2);
+
+        repo.mark_notified("GQ-1").await.expect("notified");
+        assert!(repo
+            .find_ghost_quote("GQ-1")
+            .await
+            .expect("find")
+            .expect("some")
+            .rep_notified_at
+            .is_some());
+
+        assert!(repo.record_response("GQ-1", "dismissed").await.expect("respond"));
+        assert!(!repo.record_response("GQ-1", "accepted").await.expect("respond twice"));
+        let pending = repo.list_pending(10).await.expect("pending");
+        assert_eq!(pending.iter().map(|ghost| ghost.id.as_str()).collect::<Vec<_>>(), ["GQ-22"]);
+
+        let feedback = repo.feedback().await.expect("feedback");
+        assert_eq!((feedback.accepted, feedback.dismissed), (0, 1));
+    }
+}
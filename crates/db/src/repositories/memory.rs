@@ -156,7 +156,7 @@ impl ApprovalRepository for InMemoryApprovalRepository {
         let approvals = self.approvals.read().await;
         let mut results: Vec<ApprovalRequest> =
             approvals.values().filter(|a| a.quote_id == *quote_id).cloned().collect();
-        results.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        results.sort_by_key(|a| std::cmp::Reverse(a.created_at));
         Ok(results)
     }
 
@@ -213,7 +213,7 @@ impl ExecutionQueueRepository for InMemoryExecutionQueueRepository {
             })
             .cloned()
             .collect();
-        entries.sort_by(|left, right| left.available_at.cmp(&right.available_at));
+        entries.sort_by_key(|left| left.available_at);
         Ok(entries)
     }
 
@@ -229,7 +229,7 @@ impl ExecutionQueueRepository for InMemoryExecutionQueueRepository {
     ) -> Result<(), RepositoryError> {
         let mut transitions = self.transitions.write().await;
         transitions.push(transition);
-        transitions.sort_by(|left, right| left.occurred_at.cmp(&right.occurred_at));
+        transitions.sort_by_key(|left| left.occurred_at);
         Ok(())
     }
 
@@ -308,7 +308,7 @@ impl PolicyOptimizerRepository for InMemoryPolicyOptimizerRepository {
             })
             .cloned()
             .collect();
-        entries.sort_by(|left, right| right.created_at.cmp(&left.created_at));
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.created_at));
         entries.truncate(limit.max(0) as usize);
         Ok(entries)
     }
@@ -332,7 +332,7 @@ impl PolicyOptimizerRepository for InMemoryPolicyOptimizerRepository {
             .filter(|replay| replay.candidate_id == *candidate_id)
             .cloned()
             .collect();
-        entries.sort_by(|left, right| left.replayed_at.cmp(&right.replayed_at));
+        entries.sort_by_key(|left| left.replayed_at);
         Ok(entries)
     }
 
@@ -369,7 +369,7 @@ impl PolicyOptimizerRepository for InMemoryPolicyOptimizerRepository {
             .filter(|decision| decision.candidate_id == *candidate_id)
             .cloned()
             .collect();
-        entries.sort_by(|left, right| left.decided_at.cmp(&right.decided_at));
+        entries.sort_by_key(|left| left.decided_at);
         Ok(entries)
     }
 
@@ -386,7 +386,7 @@ impl PolicyOptimizerRepository for InMemoryPolicyOptimizerRepository {
             })
             .cloned()
             .collect();
-        entries.sort_by(|left, right| left.decided_at.cmp(&right.decided_at));
+        entries.sort_by_key(|left| left.decided_at);
         Ok(entries)
     }
 
@@ -437,7 +437,7 @@ impl PolicyOptimizerRepository for InMemoryPolicyOptimizerRepository {
     ) -> Result<(), RepositoryError> {
         let mut lifecycle_events = self.lifecycle_events.write().await;
         lifecycle_events.push(event);
-        lifecycle_events.sort_by(|left, right| left.occurred_at.cmp(&right.occurred_at));
+        lifecycle_events.sort_by_key(|left| left.occurred_at);
         Ok(())
     }
 
@@ -523,7 +523,7 @@ impl SuggestionFeedbackRepository for InMemorySuggestionFeedbackRepository {
         let store = self.feedbacks.read().await;
         let mut results: Vec<SuggestionFeedback> =
             store.values().filter(|fb| fb.product_id == product_id).cloned().collect();
-        results.sort_by(|a, b| b.suggested_at.cmp(&a.suggested_at));
+        results.sort_by_key(|a| std::cmp::Reverse(a.suggested_at));
         results.truncate(limit as usize);
         Ok(results)
     }
@@ -17,20 +17,24 @@ use quotey_core::suggestions::{ProductAcceptanceRate, SuggestionFeedback};
 pub mod ai_cost;
 pub mod analytics;
 pub mod anomaly_override;
+pub mod api_key;
 pub mod approval;
 pub mod audit;
 pub mod customer;
 pub mod dialogue;
 pub mod execution_queue;
 pub mod explanation;
+pub mod ghost;
 pub mod integration_config;
 pub mod memory;
 pub mod negotiation;
 pub mod optimizer;
 pub mod org_settings;
 pub mod precedent;
+pub mod pricing_baseline;
 pub mod pricing_snapshot;
 pub mod product;
+pub mod product_cost;
 pub mod quote;
 pub mod quote_comment;
 pub mod quote_lock;
@@ -39,14 +43,18 @@ pub mod simulation;
 pub mod suggestion_feedback;
 
 pub use ai_cost::{AiCostRepository, SqlAiCostRepository};
-pub use analytics::{AnalyticsQueryError, SqlAnalyticsQueryBuilder};
+pub use analytics::{
+    AnalyticsParam, AnalyticsQuery, AnalyticsQueryError, SqlAnalyticsQueryBuilder,
+};
 pub use anomaly_override::SqlAnomalyOverrideRepository;
+pub use api_key::{ApiKeyRepository, SqlApiKeyRepository};
 pub use approval::SqlApprovalRepository;
 pub use audit::SqlAuditEventRepository;
 pub use customer::SqlCustomerRepository;
 pub use dialogue::{DialogueSessionRepository, SqlDialogueSessionRepository};
 pub use execution_queue::SqlExecutionQueueRepository;
 pub use explanation::{ExplanationRepository, SqlExplanationRepository};
+pub use ghost::{BuyingSignalRecord, GhostQuoteRecord, SqlGhostQuoteRepository};
 pub use integration_config::{IntegrationConfigRepository, SqlIntegrationConfigRepository};
 pub use memory::{
     InMemoryApprovalRepository, InMemoryExecutionQueueRepository, InMemoryIdempotencyRepository,
@@ -57,9 +65,14 @@ pub use negotiation::SqlNegotiationRepository;
 pub use optimizer::{PolicyOptimizerRepository, SqlPolicyOptimizerRepository};
 pub use org_settings::SqlOrgSettingsRepository;
 pub use precedent::{PrecedentRepository, SqlPrecedentRepository};
-pub use pricing_snapshot::SqlPricingSnapshotRepository;
+pub use pricing_baseline::{
+    BaselineRefreshConfig, BaselineSet, BaselineVersion, SnapshotAnomaly,
+    SqlPricingBaselineRepository, DEFAULT_BASELINE_WINDOW_DAYS,
+};
+pub use pricing_snapshot::{RecordedSnapshot, SqlPricingSnapshotRepository};
 pub use product::SqlProductRepository;
-pub use quote::SqlQuoteRepository;
+pub use product_cost::SqlProductCostRepository;
+pub use quote::{RevisionSave, SqlQuoteRepository};
 pub use quote_comment::SqlQuoteCommentRepository;
 pub use quote_lock::SqlQuoteLockRepository;
 pub use sales_rep::SqlSalesRepRepository;
@@ -72,6 +72,23 @@ impl SqlNegotiationRepository {
         Ok(rows.iter().filter_map(row_to_session).collect())
     }
 
+    /// Sessions opened under a policy version (e.g. a canary's), oldest first.
+    pub async fn find_sessions_by_policy_version(
+        pool: &SqlitePool,
+        policy_version: &str,
+    ) -> Result<Vec<NegotiationSession>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, quote_id, actor_id, state, policy_version, pricing_version,
+                    idempotency_key, max_turns, expires_at, created_at, updated_at
+             FROM negotiation_session WHERE policy_version = ? ORDER BY created_at ASC",
+        )
+        .bind(policy_version)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().filter_map(row_to_session).collect())
+    }
+
     /// Advance session state. Returns error if the transition is invalid per lifecycle contract.
     pub async fn advance_session_state(
         pool: &SqlitePool,
@@ -150,6 +167,24 @@ impl SqlNegotiationRepository {
         Ok(rows.iter().filter_map(row_to_turn).collect())
     }
 
+    /// Find a turn by its transition key (used to replay idempotent turn requests).
+    pub async fn find_turn_by_transition_key(
+        pool: &SqlitePool,
+        transition_key: &str,
+    ) -> Result<Option<NegotiationTurn>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, session_id, turn_number, request_type, request_payload,
+                    envelope_json, plan_json, chosen_offer_id, outcome,
+                    boundary_json, transition_key, created_at
+             FROM negotiation_turn WHERE transition_key = ?",
+        )
+        .bind(transition_key)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.and_then(|r| row_to_turn(&r)))
+    }
+
     /// Count turns for a session (used for max-turn enforcement).
     pub async fn count_turns(pool: &SqlitePool, session_id: &str) -> Result<i64, RepositoryError> {
         let count: i64 =
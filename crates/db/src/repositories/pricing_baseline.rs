@@ -0,0 +1,452 @@
+use quotey_core::chrono::{DateTime, Duration, Utc};
+use quotey_core::cpq::anomaly::{
+    build_baselines, AnomalyDetector, AnomalyScore, BaselineMethod, BaselineSample, PricingBaseline,
+};
+use quotey_core::domain::product::ProductId;
+use quotey_core::domain::quote::QuoteLine;
+use quotey_core::PricingSnapshot;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::RepositoryError;
+use crate::DbPool;
+
+/// Trailing window of finalized quotes a refresh aggregates when nothing else is configured.
+pub const DEFAULT_BASELINE_WINDOW_DAYS: u32 = 180;
+/// Baseline versions kept after a refresh; older ones are deleted with their rows.
+const RETAINED_VERSIONS: i64 = 10;
+/// Key stored for the all-segment baseline of a product.
+const ALL_SEGMENTS: &str = "all";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaselineRefreshConfig {
+    pub method: BaselineMethod,
+    pub window_days: u32,
+}
+
+impl Default for BaselineRefreshConfig {
+    fn default() -> Self {
+        Self { method: BaselineMethod::default(), window_days: DEFAULT_BASELINE_WINDOW_DAYS }
+    }
+}
+
+/// Row in `pricing_baseline_version`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BaselineVersion {
+    pub version: i64,
+    pub method: BaselineMethod,
+    pub window_days: u32,
+    pub sample_count: u32,
+    pub baseline_count: u32,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// The baselines written under one version.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineSet {
+    pub version: BaselineVersion,
+    pub baselines: Vec<PricingBaseline>,
+}
+
+/// Anomaly score stored with a pricing snapshot, and the baseline version it was scored against.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotAnomaly {
+    pub baseline_version: i64,
+    pub score: AnomalyScore,
+}
+
+/// Builds, versions and reads the pricing baselines behind anomaly detection.
+///
+/// Samples are the lines of quotes that reached `finalized`, `sent` or `accepted` within the
+/// window, keyed by product and by the account's most recent `deal_outcomes.customer_segment`.
+pub struct SqlPricingBaselineRepository {
+    pool: DbPool,
+}
+
+impl SqlPricingBaselineRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Recompute every baseline from quote history and store them as a new version.
+    pub async fn refresh(
+        &self,
+        config: BaselineRefreshConfig,
+    ) -> Result<BaselineVersion, RepositoryError> {
+        let samples = self.load_samples(config.window_days).await?;
+        let baselines = build_baselines(&samples, config.method);
+        let computed_at = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+        let version = sqlx::query(
+            "INSERT INTO pricing_baseline_version
+                (method, window_days, sample_count, baseline_count, computed_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(config.method.as_str())
+        .bind(i64::from(config.window_days))
+        .bind(samples.len() as i64)
+        .bind(baselines.len() as i64)
+        .bind(computed_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for baseline in &baselines {
+            let baseline_json = serde_json::to_string(baseline)
+                .map_err(|error| RepositoryError::Decode(format!("pricing baseline: {error}")))?;
+            sqlx::query(
+                "INSERT INTO pricing_baseline (version, product_id, segment_key, baseline_json)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(version)
+            .bind(&baseline.product_id)
+            .bind(baseline.segment.as_deref().unwrap_or(ALL_SEGMENTS))
+            .bind(baseline_json)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("DELETE FROM pricing_baseline WHERE version <= ?")
+            .bind(version - RETAINED_VERSIONS)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM pricing_baseline_version WHERE version <= ?")
+            .bind(version - RETAINED_VERSIONS)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(BaselineVersion {
+            version,
+            method: config.method,
+            window_days: config.window_days,
+            sample_count: u32::try_from(samples.len()).unwrap_or(u32::MAX),
+            baseline_count: u32::try_from(baselines.len()).unwrap_or(u32::MAX),
+            computed_at,
+        })
+    }
+
+    /// Refresh when there is no version yet or the newest is older than `max_age`.
+    pub async fn refresh_if_stale(
+        &self,
+        config: BaselineRefreshConfig,
+        max_age: Duration,
+    ) -> Result<Option<BaselineVersion>, RepositoryError> {
+        if let Some(latest) = self.latest_version().await? {
+            if Utc::now() - latest.computed_at < max_age {
+                return Ok(None);
+            }
+        }
+        self.refresh(config).await.map(Some)
+    }
+
+    pub async fn latest_version(&self) -> Result<Option<BaselineVersion>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT version, method, window_days, sample_count, baseline_count, computed_at
+             FROM pricing_baseline_version
+             ORDER BY version DESC
+             LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| {
+            let method: String = row.try_get("method")?;
+            let computed_at: String = row.try_get("computed_at")?;
+            Ok(BaselineVersion {
+                version: row.try_get("version")?,
+                method: BaselineMethod::parse_label(&method).ok_or_else(|| {
+                    RepositoryError::Decode(format!("unknown baseline method `{method}`"))
+                })?,
+                window_days: u32::try_from(row.try_get::<i64, _>("window_days")?).unwrap_or(0),
+                sample_count: u32::try_from(row.try_get::<i64, _>("sample_count")?).unwrap_or(0),
+                baseline_count: u32::try_from(row.try_get::<i64, _>("baseline_count")?)
+                    .unwrap_or(0),
+                computed_at: DateTime::parse_from_rfc3339(&computed_at)
+                    .map_err(|error| {
+                        RepositoryError::Decode(format!("baseline computed_at: {error}"))
+                    })?
+                    .with_timezone(&Utc),
+            })
+        })
+        .transpose()
+    }
+
+    /// The newest baseline version and all of its baselines.
+    pub async fn latest(&self) -> Result<Option<BaselineSet>, RepositoryError> {
+        let Some(version) = self.latest_version().await? else {
+            return Ok(None);
+        };
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT baseline_json FROM pricing_baseline
+             WHERE version = ?
+             ORDER BY product_id, segment_key",
+        )
+        .bind(version.version)
+        .fetch_all(&self.pool)
+        .await?;
+        let baselines = rows
+            .iter()
+            .map(|raw| {
+                serde_json::from_str(raw)
+                    .map_err(|error| RepositoryError::Decode(format!("pricing baseline: {error}")))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(BaselineSet { version, baselines }))
+    }
+
+    /// Score a priced snapshot against the newest baselines. `None` until a refresh has run.
+    pub async fn assess(
+        &self,
+        snapshot: &PricingSnapshot,
+    ) -> Result<Option<SnapshotAnomaly>, RepositoryError> {
+        let Some(set) = self.latest().await? else {
+            return Ok(None);
+        };
+        let account_id: Option<String> =
+            sqlx::query_scalar("SELECT account_id FROM quote WHERE id = ?")
+                .bind(&snapshot.quote_id.0)
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+        let segment = match account_id {
+            Some(account_id) => self.segment_for_account(&account_id).await?,
+            None => None,
+        };
+        let lines: Vec<QuoteLine> = snapshot
+            .line_items
+            .iter()
+            .map(|line| QuoteLine {
+                product_id: ProductId(line.product_id.clone()),
+                quantity: u32::try_from(line.quantity).unwrap_or(0),
+                unit_price: line.unit_price,
+                discount_pct: line.discount_percent.to_f64().unwrap_or(0.0),
+                notes: None,
+            })
+            .collect();
+        let score =
+            AnomalyDetector::default().score_lines(&lines, segment.as_deref(), &set.baselines);
+        Ok(Some(SnapshotAnomaly { baseline_version: set.version.version, score }))
+    }
+
+    /// Customer segment from the account's most recent recorded deal outcome.
+    pub async fn segment_for_account(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<String>, RepositoryError> {
+        let segment: Option<String> = sqlx::query_scalar(
+            "SELECT o.customer_segment
+             FROM deal_outcomes o
+             JOIN quote q ON q.id = o.quote_id
+             WHERE q.account_id = ? AND o.customer_segment IS NOT NULL
+             ORDER BY o.close_date DESC, o.created_at DESC
+             LIMIT 1",
+        )
+        .bind(account_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(segment.map(|segment| normalize_segment(&segment)).filter(|segment| !segment.is_empty()))
+    }
+
+    async fn load_samples(&self, window_days: u32) -> Result<Vec<BaselineSample>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT ql.product_id,
+                    CAST(COALESCE(ql.unit_price, 0) AS REAL) AS unit_price,
+                    CAST(ql.quantity AS REAL) AS quantity,
+                    CAST(COALESCE(ql.discount_pct, 0) AS REAL) AS discount_pct,
+                    t.total AS deal_total,
+                    (SELECT o.customer_segment
+                     FROM deal_outcomes o
+                     JOIN quote q2 ON q2.id = o.quote_id
+                     WHERE q2.account_id = q.account_id AND o.customer_segment IS NOT NULL
+                     ORDER BY o.close_date DESC, o.created_at DESC
+                     LIMIT 1) AS segment
+             FROM quote_line ql
+             JOIN quote q ON q.id = ql.quote_id
+             JOIN (
+                 SELECT quote_id,
+                        SUM(CAST(COALESCE(unit_price, 0) AS REAL) * quantity) AS total
+                 FROM quote_line GROUP BY quote_id
+             ) t ON t.quote_id = q.id
+             WHERE q.status IN ('finalized', 'sent', 'accepted')
+               AND julianday(q.updated_at) >= julianday('now', ?)
+             ORDER BY ql.product_id, q.id",
+        )
+        .bind(format!("-{window_days} days"))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let segment: Option<String> = row.try_get("segment")?;
+                Ok(BaselineSample {
+                    product_id: row.try_get("product_id")?,
+                    segment: segment
+                        .map(|segment| normalize_segment(&segment))
+                        .filter(|segment| !segment.is_empty()),
+                    unit_price: row.try_get("unit_price")?,
+                    discount_pct: row.try_get("discount_pct")?,
+                    quantity: row.try_get("quantity")?,
+                    deal_total: row.try_get("deal_total")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Same normalisation the suggestion engine applies to `deal_outcomes.customer_segment`.
+fn normalize_segment(raw: &str) -> String {
+    raw.trim().to_ascii_lowercase().replace(['-', ' '], "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::{Duration, Utc};
+    use quotey_core::cpq::anomaly::{AnomalySeverity, BaselineMethod};
+    use quotey_core::{PricingLineSnapshot, PricingSnapshot, QuoteId};
+    use rust_decimal::Decimal;
+
+    use super::{BaselineRefreshConfig, SqlPricingBaselineRepository};
+    use crate::repositories::SqlPricingSnapshotRepository;
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn seed_quote(pool: &DbPool, id: &str, status: &str, unit_price: f64, quantity: i64) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, account_id, created_by, created_at, updated_at)
+             VALUES (?, ?, 'USD', 'acct-1', 'rep-1', ?, ?)",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("insert quote");
+        sqlx::query(
+            "INSERT INTO quote_line
+                (id, quote_id, product_id, quantity, unit_price, discount_pct, created_at, updated_at)
+             VALUES (?, ?, 'plan-pro', ?, ?, 5.0, ?, ?)",
+        )
+        .bind(format!("{id}-l1"))
+        .bind(id)
+        .bind(quantity)
+        .bind(unit_price)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("insert quote line");
+    }
+
+    async fn seed_history(pool: &DbPool) {
+        for (index, price) in [95.0, 100.0, 105.0, 98.0, 102.0, 100.0].into_iter().enumerate() {
+            seed_quote(pool, &format!("Q-HIST-{index}"), "finalized", price, 10).await;
+        }
+        // Drafts never feed baselines, however unusual.
+        seed_quote(pool, "Q-DRAFT", "draft", 10_000.0, 1).await;
+        sqlx::query(
+            "INSERT INTO deal_outcomes
+                (id, quote_id, outcome, final_price, close_date, customer_segment, created_at)
+             VALUES ('DO-1', 'Q-HIST-0', 'won', 950.0, '2026-01-15', 'Mid-Market', '2026-01-15')",
+        )
+        .execute(pool)
+        .await
+        .expect("insert deal outcome");
+    }
+
+    fn snapshot(quote_id: &str, unit_price: i64) -> PricingSnapshot {
+        let price = Decimal::new(unit_price, 0);
+        PricingSnapshot {
+            quote_id: QuoteId(quote_id.to_string()),
+            version: 1,
+            subtotal: price * Decimal::from(10),
+            discount_total: Decimal::ZERO,
+            tax_total: Decimal::ZERO,
+            total: price * Decimal::from(10),
+            currency: "USD".to_string(),
+            line_items: vec![PricingLineSnapshot {
+                line_id: "line-1".to_string(),
+                product_id: "plan-pro".to_string(),
+                product_name: "Pro Plan".to_string(),
+                quantity: 10,
+                unit_price: price,
+                discount_percent: Decimal::new(5, 0),
+                discount_amount: Decimal::ZERO,
+                line_subtotal: price * Decimal::from(10),
+            }],
+            calculation_steps: vec![],
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_versions_baselines_from_finalized_history() {
+        let pool = setup().await;
+        seed_history(&pool).await;
+        let repo = SqlPricingBaselineRepository::new(pool.clone());
+        assert!(repo.latest().await.expect("latest").is_none());
+
+        let first = repo.refresh(BaselineRefreshConfig::default()).await.expect("refresh");
+        assert_eq!((first.sample_count, first.baseline_count), (6, 2));
+
+        let config = BaselineRefreshConfig { method: BaselineMethod::MedianMad, window_days: 30 };
+        let second = repo.refresh(config).await.expect("second refresh");
+        assert_eq!(second.version, first.version + 1);
+
+        let set = repo.latest().await.expect("latest").expect("baselines");
+        assert_eq!(set.version.version, second.version);
+        assert_eq!(set.version.method, BaselineMethod::MedianMad);
+        let keys: Vec<_> =
+            set.baselines.iter().map(|b| (b.product_id.as_str(), b.segment.as_deref())).collect();
+        assert_eq!(keys, vec![("plan-pro", None), ("plan-pro", Some("mid_market"))]);
+        assert_eq!(set.baselines[0].unit_price.sample_count, 6);
+        assert_eq!(set.baselines[0].unit_price.mean, 100.0);
+        assert_eq!(set.baselines[0].deal_total.mean, 1000.0);
+
+        let fresh = repo
+            .refresh_if_stale(BaselineRefreshConfig::default(), Duration::hours(1))
+            .await
+            .expect("refresh if stale");
+        assert!(fresh.is_none());
+        let stale = repo
+            .refresh_if_stale(BaselineRefreshConfig::default(), Duration::zero())
+            .await
+            .expect("refresh if stale");
+        assert_eq!(stale.map(|version| version.version), Some(second.version + 1));
+    }
+
+    #[tokio::test]
+    async fn recorded_snapshots_carry_anomaly_scores() {
+        let pool = setup().await;
+        seed_history(&pool).await;
+        seed_quote(&pool, "Q-NEW", "priced", 100.0, 10).await;
+        let snapshots = SqlPricingSnapshotRepository::new(pool.clone());
+        let quote_id = QuoteId("Q-NEW".to_string());
+
+        // Before the first refresh there is nothing to score against.
+        snapshots.record_snapshot(&snapshot("Q-NEW", 100), None).await.expect("record");
+        assert!(snapshots.snapshot_anomaly(&quote_id, 1).await.expect("anomaly").is_none());
+
+        let version = SqlPricingBaselineRepository::new(pool.clone())
+            .refresh(BaselineRefreshConfig::default())
+            .await
+            .expect("refresh");
+        snapshots.record_snapshot(&snapshot("Q-NEW", 100), None).await.expect("record");
+        let typical = snapshots.snapshot_anomaly(&quote_id, 1).await.expect("anomaly").unwrap();
+        assert_eq!(typical.baseline_version, version.version);
+        assert_eq!(typical.score.severity, AnomalySeverity::None);
+
+        snapshots.record_snapshot(&snapshot("Q-NEW", 400), None).await.expect("record");
+        let outlier = snapshots.snapshot_anomaly(&quote_id, 1).await.expect("anomaly").unwrap();
+        assert!(outlier.score.flags.iter().any(|flag| flag.rule_name == "unit_price_outlier"));
+        assert_ne!(outlier.score.severity, AnomalySeverity::None);
+    }
+}
@@ -3,34 +3,47 @@ use std::{collections::HashMap, str::FromStr};
 use async_trait::async_trait;
 use quotey_core::domain::quote::QuoteId;
 use quotey_core::{
-    CalculationStep, ExplanationError, PricingLineSnapshot, PricingSnapshot,
-    PricingSnapshotProvider,
+    AppliedRule, CalculationStep, ExplanationError, PolicyEvaluation, PolicyEvaluationProvider,
+    PolicyViolation, PricingLineSnapshot, PricingSnapshot, PricingSnapshotProvider,
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{sqlite::SqliteRow, Row};
 
+use super::pricing_baseline::{SnapshotAnomaly, SqlPricingBaselineRepository};
+use super::RepositoryError;
 use crate::DbPool;
 
-/// SQLite-backed pricing snapshot provider for Explain Any Number.
+/// SQLite-backed pricing snapshot and policy evaluation provider for Explain Any Number.
 ///
 /// Lookup order:
 /// 1. Validate quote exists.
-/// 2. Validate requested quote version exists in quote_ledger.
-/// 3. Return cached row from quote_pricing_snapshot when present.
-/// 4. Fallback-build snapshot from current quote_line rows and cache it.
+/// 2. Return the persisted row from quote_pricing_snapshot when present, validating its
+///    quote_ledger linkage when the row carries one.
+/// 3. Otherwise validate the requested version exists in quote_ledger and fallback-build a
+///    snapshot from current quote_line rows and cache it.
+///
+/// Policy evaluations are read from the `policy_evaluation_json` column written alongside the
+/// snapshot by [`SqlPricingSnapshotRepository::record_snapshot`].
 pub struct SqlPricingSnapshotRepository {
     pool: DbPool,
     priced_by: String,
+    policy_version: Option<i32>,
 }
 
 impl SqlPricingSnapshotRepository {
     pub fn new(pool: DbPool) -> Self {
-        Self { pool, priced_by: "system".to_string() }
+        Self { pool, priced_by: "system".to_string(), policy_version: None }
     }
 
     pub fn with_priced_by(pool: DbPool, priced_by: impl Into<String>) -> Self {
-        Self { pool, priced_by: priced_by.into() }
+        Self { pool, priced_by: priced_by.into(), policy_version: None }
+    }
+
+    /// Policy set version the recorded snapshots were evaluated against.
+    pub fn with_policy_version(mut self, policy_version: Option<i32>) -> Self {
+        self.policy_version = policy_version;
+        self
     }
 
     async fn ensure_quote_exists(&self, quote_id: &QuoteId) -> Result<String, ExplanationError> {
@@ -356,6 +369,192 @@ impl SqlPricingSnapshotRepository {
         Ok(())
     }
 
+    /// Persists the snapshot (and policy evaluation) for a priced quote version.
+    ///
+    /// Re-pricing the same version replaces the previous row. Returns the snapshot id, which
+    /// changes on every write so explanation cache entries keyed on it go stale automatically.
+    /// The snapshot is also scored against the newest pricing baselines and the
+    /// [`SnapshotAnomaly`] stored on the row; see [`Self::snapshot_anomaly`].
+    pub async fn record_snapshot(
+        &self,
+        snapshot: &PricingSnapshot,
+        policy: Option<&PolicyEvaluation>,
+    ) -> Result<String, RepositoryError> {
+        let trace_json = serde_json::to_string(&PersistedPricingTrace::from_snapshot(snapshot))
+            .map_err(|error| RepositoryError::Decode(format!("pricing trace: {error}")))?;
+        let policy_json = policy
+            .map(|policy| {
+                serde_json::to_string(&PersistedPolicyEvaluation::from_evaluation(policy))
+            })
+            .transpose()
+            .map_err(|error| RepositoryError::Decode(format!("policy evaluation: {error}")))?;
+        let anomaly = SqlPricingBaselineRepository::new(self.pool.clone()).assess(snapshot).await?;
+        let anomaly_json = anomaly
+            .as_ref()
+            .map(|anomaly| serde_json::to_string(&anomaly.score))
+            .transpose()
+            .map_err(|error| RepositoryError::Decode(format!("anomaly score: {error}")))?;
+        let snapshot_id = format!("psnap-{}", sqlx::types::Uuid::new_v4());
+
+        sqlx::query(
+            r#"
+            INSERT INTO quote_pricing_snapshot (
+                id, quote_id, version, subtotal, discount_total, tax_total, total, currency,
+                pricing_trace_json, policy_evaluation_json, priced_at, priced_by, anomaly_json,
+                baseline_version, policy_version
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (quote_id, version) DO UPDATE SET
+                id = excluded.id,
+                ledger_entry_id = NULL,
+                ledger_content_hash = NULL,
+                subtotal = excluded.subtotal,
+                discount_total = excluded.discount_total,
+                tax_total = excluded.tax_total,
+                total = excluded.total,
+                currency = excluded.currency,
+                pricing_trace_json = excluded.pricing_trace_json,
+                policy_evaluation_json = excluded.policy_evaluation_json,
+                priced_at = excluded.priced_at,
+                priced_by = excluded.priced_by,
+                anomaly_json = excluded.anomaly_json,
+                baseline_version = excluded.baseline_version,
+                policy_version = excluded.policy_version
+            "#,
+        )
+        .bind(&snapshot_id)
+        .bind(&snapshot.quote_id.0)
+        .bind(snapshot.version)
+        .bind(snapshot.subtotal.to_string())
+        .bind(snapshot.discount_total.to_string())
+        .bind(snapshot.tax_total.to_string())
+        .bind(snapshot.total.to_string())
+        .bind(&snapshot.currency)
+        .bind(trace_json)
+        .bind(policy_json)
+        .bind(&snapshot.created_at)
+        .bind(&self.priced_by)
+        .bind(anomaly_json)
+        .bind(anomaly.map(|anomaly| anomaly.baseline_version))
+        .bind(self.policy_version)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(snapshot_id)
+    }
+
+    /// Anomaly score recorded with a quote version's snapshot. `None` when there is no snapshot
+    /// or it was priced before any baselines existed.
+    pub async fn snapshot_anomaly(
+        &self,
+        quote_id: &QuoteId,
+        version: i32,
+    ) -> Result<Option<SnapshotAnomaly>, RepositoryError> {
+        let row: Option<(Option<String>, Option<i64>)> = sqlx::query_as(
+            "SELECT anomaly_json, baseline_version FROM quote_pricing_snapshot
+             WHERE quote_id = ? AND version = ?",
+        )
+        .bind(&quote_id.0)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((Some(anomaly_json), Some(baseline_version))) = row else {
+            return Ok(None);
+        };
+        let score = serde_json::from_str(&anomaly_json)
+            .map_err(|error| RepositoryError::Decode(format!("anomaly score: {error}")))?;
+        Ok(Some(SnapshotAnomaly { baseline_version, score }))
+    }
+
+    /// Policy set version a quote version's snapshot was evaluated against, if it was recorded.
+    pub async fn snapshot_policy_version(
+        &self,
+        quote_id: &QuoteId,
+        version: i32,
+    ) -> Result<Option<i32>, RepositoryError> {
+        let policy_version: Option<Option<i32>> = sqlx::query_scalar(
+            "SELECT policy_version FROM quote_pricing_snapshot WHERE quote_id = ? AND version = ?",
+        )
+        .bind(&quote_id.0)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(policy_version.flatten())
+    }
+
+    /// Id of the persisted snapshot for a quote version, if one exists.
+    pub async fn snapshot_id(
+        &self,
+        quote_id: &QuoteId,
+        version: i32,
+    ) -> Result<Option<String>, RepositoryError> {
+        Ok(sqlx::query_scalar(
+            "SELECT id FROM quote_pricing_snapshot WHERE quote_id = ? AND version = ?",
+        )
+        .bind(&quote_id.0)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Snapshots recorded with a policy evaluation, oldest first. Read-only: unlike
+    /// [`PricingSnapshotProvider::get_snapshot`] nothing is rebuilt or cached.
+    ///
+    /// `quote_id` and `priced_since` (an RFC 3339 timestamp) narrow the rows when set.
+    pub async fn list_recorded(
+        &self,
+        quote_id: Option<&str>,
+        priced_since: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<RecordedSnapshot>, RepositoryError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                quote_id,
+                version,
+                CAST(subtotal AS TEXT) AS subtotal_text,
+                CAST(discount_total AS TEXT) AS discount_total_text,
+                CAST(tax_total AS TEXT) AS tax_total_text,
+                CAST(total AS TEXT) AS total_text,
+                currency,
+                pricing_trace_json,
+                policy_evaluation_json,
+                policy_version,
+                priced_at
+            FROM quote_pricing_snapshot
+            WHERE policy_evaluation_json IS NOT NULL
+              AND (?1 IS NULL OR quote_id = ?1)
+              AND (?2 IS NULL OR priced_at >= ?2)
+            ORDER BY priced_at ASC, quote_id ASC, version ASC
+            LIMIT ?3
+            "#,
+        )
+        .bind(quote_id)
+        .bind(priced_since)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let snapshot = Self::snapshot_from_row(row)
+                    .map_err(|error| RepositoryError::Decode(error.to_string()))?;
+                let policy_json: String = row.try_get("policy_evaluation_json")?;
+                let persisted: PersistedPolicyEvaluation = serde_json::from_str(&policy_json)
+                    .map_err(|error| {
+                        RepositoryError::Decode(format!("policy evaluation: {error}"))
+                    })?;
+                let policy = persisted
+                    .try_into_evaluation(&snapshot.quote_id, snapshot.version)
+                    .map_err(|error| RepositoryError::Decode(error.to_string()))?;
+                Ok(RecordedSnapshot {
+                    policy_version: row.try_get("policy_version")?,
+                    snapshot,
+                    policy,
+                })
+            })
+            .collect()
+    }
+
     fn parse_decimal(field: &str, value: &str) -> Result<Decimal, ExplanationError> {
         Decimal::from_str(value).map_err(|error| ExplanationError::EvidenceGatheringFailed {
             reason: format!("invalid decimal value for {field}: {error}"),
@@ -375,13 +574,18 @@ impl PricingSnapshotProvider for SqlPricingSnapshotRepository {
         version: i32,
     ) -> Result<PricingSnapshot, ExplanationError> {
         let currency = self.ensure_quote_exists(quote_id).await?;
-        let ledger = self.load_ledger_version(quote_id, version).await?;
 
         if let Some(row) = self.load_cached_snapshot_row(quote_id, version).await? {
-            Self::validate_ledger_linkage(&row, quote_id, version, &ledger)?;
+            let linked: Option<String> = row.try_get("ledger_entry_id").map_err(Self::db_error)?;
+            if linked.is_some() {
+                let ledger = self.load_ledger_version(quote_id, version).await?;
+                Self::validate_ledger_linkage(&row, quote_id, version, &ledger)?;
+            }
             return Self::snapshot_from_row(&row);
         }
 
+        let ledger = self.load_ledger_version(quote_id, version).await?;
+
         // quote_line rows represent mutable live state. Without a persisted snapshot we can
         // only safely reconstruct the latest ledger version.
         if version != ledger.latest_version {
@@ -402,6 +606,44 @@ impl PricingSnapshotProvider for SqlPricingSnapshotRepository {
     }
 }
 
+#[async_trait]
+impl PolicyEvaluationProvider for SqlPricingSnapshotRepository {
+    async fn get_evaluation(
+        &self,
+        quote_id: &QuoteId,
+        version: i32,
+    ) -> Result<PolicyEvaluation, ExplanationError> {
+        self.ensure_quote_exists(quote_id).await?;
+        let raw: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT policy_evaluation_json FROM quote_pricing_snapshot WHERE quote_id = ? AND version = ?",
+        )
+        .bind(&quote_id.0)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let raw = raw.flatten().ok_or_else(|| ExplanationError::MissingPolicyEvaluation {
+            quote_id: quote_id.clone(),
+        })?;
+        let persisted: PersistedPolicyEvaluation = serde_json::from_str(&raw).map_err(|error| {
+            ExplanationError::EvidenceGatheringFailed {
+                reason: format!("failed to decode policy_evaluation_json: {error}"),
+            }
+        })?;
+        persisted.try_into_evaluation(quote_id, version)
+    }
+}
+
+/// A persisted pricing run with the policy evaluation recorded next to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedSnapshot {
+    pub snapshot: PricingSnapshot,
+    pub policy: PolicyEvaluation,
+    /// Policy set version the run was evaluated against, when it was recorded.
+    pub policy_version: Option<i32>,
+}
+
 #[derive(Clone, Debug)]
 struct LedgerVersion {
     entry_id: String,
@@ -410,9 +652,13 @@ struct LedgerVersion {
     latest_version: i32,
 }
 
+/// Rows written outside the pricing engine (e.g. portal assumption updates) carry a different
+/// trace shape; both lists default to empty so those snapshots still load.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct PersistedPricingTrace {
+    #[serde(default)]
     line_items: Vec<PersistedPricingLineItem>,
+    #[serde(default)]
     calculation_steps: Vec<PersistedCalculationStep>,
 }
 
@@ -544,9 +790,120 @@ impl PersistedCalculationStep {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedPolicyEvaluation {
+    overall_status: String,
+    violations: Vec<PersistedPolicyViolation>,
+    applied_rules: Vec<PersistedAppliedRule>,
+    evaluated_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedPolicyViolation {
+    policy_id: String,
+    policy_name: String,
+    severity: String,
+    threshold_value: Option<String>,
+    actual_value: String,
+    message: String,
+    suggested_resolution: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedAppliedRule {
+    rule_id: String,
+    rule_name: String,
+    rule_section: String,
+    rule_description: String,
+}
+
+impl PersistedPolicyEvaluation {
+    fn from_evaluation(evaluation: &PolicyEvaluation) -> Self {
+        Self {
+            overall_status: evaluation.overall_status.clone(),
+            violations: evaluation
+                .violations
+                .iter()
+                .map(|violation| PersistedPolicyViolation {
+                    policy_id: violation.policy_id.clone(),
+                    policy_name: violation.policy_name.clone(),
+                    severity: violation.severity.clone(),
+                    threshold_value: violation.threshold_value.map(|value| value.to_string()),
+                    actual_value: violation.actual_value.to_string(),
+                    message: violation.message.clone(),
+                    suggested_resolution: violation.suggested_resolution.clone(),
+                })
+                .collect(),
+            applied_rules: evaluation
+                .applied_rules
+                .iter()
+                .map(|rule| PersistedAppliedRule {
+                    rule_id: rule.rule_id.clone(),
+                    rule_name: rule.rule_name.clone(),
+                    rule_section: rule.rule_section.clone(),
+                    rule_description: rule.rule_description.clone(),
+                })
+                .collect(),
+            evaluated_at: evaluation.evaluated_at.clone(),
+        }
+    }
+
+    fn try_into_evaluation(
+        self,
+        quote_id: &QuoteId,
+        version: i32,
+    ) -> Result<PolicyEvaluation, ExplanationError> {
+        let violations = self
+            .violations
+            .into_iter()
+            .map(|violation| {
+                Ok(PolicyViolation {
+                    threshold_value: violation
+                        .threshold_value
+                        .as_deref()
+                        .map(|value| {
+                            SqlPricingSnapshotRepository::parse_decimal("policy.threshold", value)
+                        })
+                        .transpose()?,
+                    actual_value: SqlPricingSnapshotRepository::parse_decimal(
+                        "policy.actual_value",
+                        &violation.actual_value,
+                    )?,
+                    policy_id: violation.policy_id,
+                    policy_name: violation.policy_name,
+                    severity: violation.severity,
+                    message: violation.message,
+                    suggested_resolution: violation.suggested_resolution,
+                })
+            })
+            .collect::<Result<Vec<_>, ExplanationError>>()?;
+
+        Ok(PolicyEvaluation {
+            quote_id: quote_id.clone(),
+            version,
+            overall_status: self.overall_status,
+            violations,
+            applied_rules: self
+                .applied_rules
+                .into_iter()
+                .map(|rule| AppliedRule {
+                    rule_id: rule.rule_id,
+                    rule_name: rule.rule_name,
+                    rule_section: rule.rule_section,
+                    rule_description: rule.rule_description,
+                })
+                .collect(),
+            evaluated_at: self.evaluated_at,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use quotey_core::{PricingSnapshotProvider, QuoteId};
+    use quotey_core::{
+        ExplanationError, PolicyEvaluation, PolicyEvaluationProvider, PolicyViolation,
+        PricingSnapshotProvider, QuoteId,
+    };
     use rust_decimal::Decimal;
 
     use super::{
@@ -874,6 +1231,71 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn recorded_snapshot_and_policy_load_without_ledger_linkage() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = QuoteId("Q-PS-RECORDED-001".to_string());
+        insert_quote(&pool, &quote_id, "USD").await?;
+
+        let repo = SqlPricingSnapshotRepository::with_priced_by(pool.clone(), "mcp");
+        let missing = repo.get_evaluation(&quote_id, 1).await;
+        if !matches!(missing, Err(ExplanationError::MissingPolicyEvaluation { .. })) {
+            return Err(format!("expected missing policy evaluation, got {missing:?}"));
+        }
+
+        let snapshot = sample_snapshot(&quote_id, 1, "USD", "2026-02-24T00:00:00Z");
+        let policy = PolicyEvaluation {
+            quote_id: quote_id.clone(),
+            version: 1,
+            overall_status: "violation".to_string(),
+            violations: vec![PolicyViolation {
+                policy_id: "discount-cap".to_string(),
+                policy_name: "Discount Cap".to_string(),
+                severity: "blocking".to_string(),
+                threshold_value: Some(Decimal::new(20, 0)),
+                actual_value: Decimal::new(25, 0),
+                message: "Requested discount is above 20%".to_string(),
+                suggested_resolution: Some("Request sales manager approval".to_string()),
+            }],
+            applied_rules: Vec::new(),
+            evaluated_at: "2026-02-24T00:00:00Z".to_string(),
+        };
+        let first_id = repo
+            .record_snapshot(&snapshot, Some(&policy))
+            .await
+            .map_err(|error| format!("record snapshot: {error}"))?;
+
+        let fetched = repo
+            .get_snapshot(&quote_id, 1)
+            .await
+            .map_err(|error| format!("fetch recorded snapshot: {error}"))?;
+        if fetched.total != snapshot.total || fetched.line_items.len() != 1 {
+            return Err(format!("recorded snapshot mismatch: {fetched:?}"));
+        }
+        let evaluation = repo
+            .get_evaluation(&quote_id, 1)
+            .await
+            .map_err(|error| format!("fetch recorded policy: {error}"))?;
+        if evaluation != policy {
+            return Err(format!("recorded policy mismatch: {evaluation:?}"));
+        }
+
+        let second_id = repo
+            .record_snapshot(&snapshot, None)
+            .await
+            .map_err(|error| format!("re-record snapshot: {error}"))?;
+        let current = repo
+            .snapshot_id(&quote_id, 1)
+            .await
+            .map_err(|error| format!("snapshot id: {error}"))?;
+        if second_id == first_id || current.as_deref() != Some(second_id.as_str()) {
+            return Err("re-recording should replace the snapshot row".to_string());
+        }
+
+        pool.close().await;
+        Ok(())
+    }
+
     async fn setup_pool() -> TestResult<DbPool> {
         let pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 30)
             .await
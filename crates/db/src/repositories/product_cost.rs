@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use quotey_core::chrono::{DateTime, NaiveDate, Utc};
+use quotey_core::domain::product::{CostComponent, ProductCost, ProductId};
+use rust_decimal::Decimal;
+
+use super::RepositoryError;
+use crate::DbPool;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Effective-dated unit costs behind margin computation.
+///
+/// Records are append-only: a new cost supersedes older ones from its `effective_from`, which
+/// keeps past margins reproducible.
+pub struct SqlProductCostRepository {
+    pool: DbPool,
+}
+
+impl SqlProductCostRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(&self, cost: &ProductCost) -> Result<(), RepositoryError> {
+        let components_json = serde_json::to_string(&cost.components)
+            .map_err(|e| RepositoryError::Decode(format!("serialize cost components: {e}")))?;
+        sqlx::query(
+            "INSERT INTO product_cost (id, product_id, unit_cost, currency, effective_from, \
+             effective_until, components_json, created_by, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&cost.id)
+        .bind(&cost.product_id.0)
+        .bind(cost.unit_cost.to_string())
+        .bind(&cost.currency)
+        .bind(cost.effective_from.format(DATE_FORMAT).to_string())
+        .bind(cost.effective_until.map(|until| until.format(DATE_FORMAT).to_string()))
+        .bind(components_json)
+        .bind(&cost.created_by)
+        .bind(cost.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Every cost record of a product, newest `effective_from` first.
+    pub async fn list_for_product(
+        &self,
+        product_id: &ProductId,
+    ) -> Result<Vec<ProductCost>, RepositoryError> {
+        let rows = sqlx::query_as::<_, ProductCostRow>(
+            "SELECT id, product_id, unit_cost, currency, effective_from, effective_until, \
+             components_json, created_by, created_at \
+             FROM product_cost WHERE product_id = ? \
+             ORDER BY effective_from DESC, created_at DESC, id DESC",
+        )
+        .bind(&product_id.0)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(ProductCostRow::into_cost).collect()
+    }
+
+    /// Unit cost in `currency` applying on `as_of`, keyed by product id. Products without a
+    /// matching record are absent from the map.
+    pub async fn unit_costs_on(
+        &self,
+        product_ids: &[String],
+        currency: &str,
+        as_of: NaiveDate,
+    ) -> Result<BTreeMap<String, Decimal>, RepositoryError> {
+        if product_ids.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        let placeholders = vec!["?"; product_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT product_id, unit_cost FROM product_cost \
+             WHERE currency = ? AND effective_from <= ? \
+             AND (effective_until IS NULL OR effective_until > ?) \
+             AND product_id IN ({placeholders}) \
+             ORDER BY product_id, effective_from DESC, created_at DESC, id DESC"
+        );
+        let as_of = as_of.format(DATE_FORMAT).to_string();
+        let mut query =
+            sqlx::query_as::<_, (String, String)>(&sql).bind(currency).bind(&as_of).bind(&as_of);
+        for product_id in product_ids {
+            query = query.bind(product_id);
+        }
+
+        let mut costs = BTreeMap::new();
+        for (product_id, unit_cost) in query.fetch_all(&self.pool).await? {
+            if costs.contains_key(&product_id) {
+                continue;
+            }
+            let unit_cost = Decimal::from_str(&unit_cost)
+                .map_err(|e| RepositoryError::Decode(format!("invalid unit_cost: {e}")))?;
+            costs.insert(product_id, unit_cost);
+        }
+        Ok(costs)
+    }
+
+    /// Unit costs applying on `as_of` for the products on a quote, in the quote's currency.
+    pub async fn unit_costs_for_quote(
+        &self,
+        quote_id: &str,
+        as_of: NaiveDate,
+    ) -> Result<BTreeMap<String, Decimal>, RepositoryError> {
+        let Some(currency) =
+            sqlx::query_scalar::<_, String>("SELECT currency FROM quote WHERE id = ?")
+                .bind(quote_id)
+                .fetch_optional(&self.pool)
+                .await?
+        else {
+            return Ok(BTreeMap::new());
+        };
+        let product_ids = sqlx::query_scalar::<_, String>(
+            "SELECT DISTINCT product_id FROM quote_line WHERE quote_id = ?",
+        )
+        .bind(quote_id)
+        .fetch_all(&self.pool)
+        .await?;
+        self.unit_costs_on(&product_ids, &currency, as_of).await
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ProductCostRow {
+    id: String,
+    product_id: String,
+    unit_cost: String,
+    currency: String,
+    effective_from: String,
+    effective_until: Option<String>,
+    components_json: String,
+    created_by: String,
+    created_at: String,
+}
+
+impl ProductCostRow {
+    fn into_cost(self) -> Result<ProductCost, RepositoryError> {
+        let date = |value: &str| {
+            NaiveDate::parse_from_str(value, DATE_FORMAT)
+                .map_err(|e| RepositoryError::Decode(format!("invalid cost date '{value}': {e}")))
+        };
+        let components: Vec<CostComponent> = serde_json::from_str(&self.components_json)
+            .map_err(|e| RepositoryError::Decode(format!("invalid cost components JSON: {e}")))?;
+        let created_at: DateTime<Utc> = self
+            .created_at
+            .parse()
+            .map_err(|e| RepositoryError::Decode(format!("invalid created_at: {e}")))?;
+
+        Ok(ProductCost {
+            id: self.id,
+            product_id: ProductId(self.product_id),
+            unit_cost: Decimal::from_str(&self.unit_cost)
+                .map_err(|e| RepositoryError::Decode(format!("invalid unit_cost: {e}")))?,
+            currency: self.currency,
+            effective_from: date(&self.effective_from)?,
+            effective_until: self.effective_until.as_deref().map(date).transpose()?,
+            components,
+            created_by: self.created_by,
+            created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::domain::product::Product;
+
+    use super::*;
+    use crate::repositories::{ProductRepository, SqlProductRepository};
+    use crate::{connect_with_settings, migrations};
+
+    fn cost(
+        id: &str,
+        unit_cost: i64,
+        currency: &str,
+        from: &str,
+        until: Option<&str>,
+    ) -> ProductCost {
+        let date = |value: &str| NaiveDate::parse_from_str(value, DATE_FORMAT).expect("date");
+        ProductCost {
+            id: id.to_string(),
+            product_id: ProductId("prod-cost".to_string()),
+            unit_cost: Decimal::from(unit_cost),
+            currency: currency.to_string(),
+            effective_from: date(from),
+            effective_until: until.map(date),
+            components: Vec::new(),
+            created_by: "test".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_effective_cost_in_the_currency_applies() {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        SqlProductRepository::new(pool.clone())
+            .save(Product::simple("prod-cost", "SKU-COST", "Costed"))
+            .await
+            .expect("product");
+
+        let repo = SqlProductCostRepository::new(pool.clone());
+        repo.record(&cost("c1", 40, "USD", "2026-01-01", None)).await.expect("c1");
+        repo.record(&cost("c2", 45, "USD", "2026-04-01", Some("2026-07-01"))).await.expect("c2");
+        repo.record(&cost("c3", 38, "EUR", "2026-01-01", None)).await.expect("c3");
+
+        let ids = vec!["prod-cost".to_string(), "prod-missing".to_string()];
+        let on = |day: &str| NaiveDate::parse_from_str(day, DATE_FORMAT).expect("date");
+        let march = repo.unit_costs_on(&ids, "USD", on("2026-03-15")).await.expect("march");
+        assert_eq!(march.get("prod-cost"), Some(&Decimal::from(40)));
+        assert!(!march.contains_key("prod-missing"));
+        let may = repo.unit_costs_on(&ids, "USD", on("2026-05-01")).await.expect("may");
+        assert_eq!(may.get("prod-cost"), Some(&Decimal::from(45)));
+        let july = repo.unit_costs_on(&ids, "USD", on("2026-07-01")).await.expect("july");
+        assert_eq!(july.get("prod-cost"), Some(&Decimal::from(40)));
+        let eur = repo.unit_costs_on(&ids, "EUR", on("2025-12-31")).await.expect("eur");
+        assert!(eur.is_empty());
+
+        let history = repo.list_for_product(&ProductId("prod-cost".into())).await.expect("list");
+        assert_eq!(history.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["c2", "c3", "c1"]);
+    }
+}
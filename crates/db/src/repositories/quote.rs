@@ -3,7 +3,7 @@ use quotey_core::domain::product::ProductId;
 use quotey_core::domain::quote::{Quote, QuoteId};
 use quotey_core::domain::quote::{QuoteLine, QuoteStatus};
 use rust_decimal::Decimal;
-use sqlx::Row;
+use sqlx::{Row, SqliteConnection};
 
 use super::{QuoteRepository, RepositoryError};
 use crate::DbPool;
@@ -12,10 +12,73 @@ pub struct SqlQuoteRepository {
     pool: DbPool,
 }
 
+/// Outcome of [`SqlQuoteRepository::save_revision`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevisionSave {
+    Saved,
+    /// The stored quote is no longer at the version the revision was based on (`0` when it is
+    /// gone); nothing was written.
+    Stale {
+        current: u32,
+    },
+}
+
 impl SqlQuoteRepository {
     pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
+
+    /// Writes `quote` as the next revision inside the caller's transaction.
+    ///
+    /// The update only applies while the stored quote is still at `quote.version`, so two
+    /// revisions based on the same version cannot overwrite each other. On success the quote
+    /// is bumped to the new version and moved to `revised` unless it is a draft.
+    pub async fn save_revision(
+        conn: &mut SqliteConnection,
+        quote: &mut Quote,
+    ) -> Result<RevisionSave, RepositoryError> {
+        let base_version = quote.version;
+        let status = if matches!(quote.status, QuoteStatus::Draft | QuoteStatus::Revised) {
+            quote.status.clone()
+        } else {
+            QuoteStatus::Revised
+        };
+        let updated_at = Utc::now();
+
+        let updated = sqlx::query(
+            "UPDATE quote SET status = ?, currency = ?, start_date = ?, end_date = ?,
+                    term_months = ?, valid_until = ?, notes = ?, version = ?, updated_at = ?
+             WHERE id = ? AND version = ?",
+        )
+        .bind(quote_status_as_str(&status))
+        .bind(&quote.currency)
+        .bind(&quote.start_date)
+        .bind(&quote.end_date)
+        .bind(quote.term_months.map(|v| v as i32))
+        .bind(&quote.valid_until)
+        .bind(&quote.notes)
+        .bind(i64::from(base_version) + 1)
+        .bind(updated_at.to_rfc3339())
+        .bind(&quote.id.0)
+        .bind(i64::from(base_version))
+        .execute(&mut *conn)
+        .await?;
+        if updated.rows_affected() == 0 {
+            let current: Option<i64> = sqlx::query_scalar("SELECT version FROM quote WHERE id = ?")
+                .bind(&quote.id.0)
+                .fetch_optional(&mut *conn)
+                .await?;
+            let current = current.and_then(|version| u32::try_from(version).ok()).unwrap_or(0);
+            return Ok(RevisionSave::Stale { current });
+        }
+
+        quote.status = status;
+        quote.version = base_version + 1;
+        quote.updated_at = updated_at;
+        let created_at = quote.created_at.to_rfc3339();
+        write_lines(conn, quote, &created_at, &updated_at.to_rfc3339()).await?;
+        Ok(RevisionSave::Saved)
+    }
 }
 
 #[async_trait::async_trait]
@@ -162,40 +225,7 @@ impl QuoteRepository for SqlQuoteRepository {
         .execute(&mut *tx)
         .await?;
 
-        sqlx::query("DELETE FROM quote_line WHERE quote_id = ?")
-            .bind(&quote.id.0)
-            .execute(&mut *tx)
-            .await?;
-
-        for (index, line) in quote.lines.iter().enumerate() {
-            let line_id = format!("{}-ql-{}", quote.id.0, index + 1);
-            let unit_price = line.unit_price.to_string();
-            let quantity = i64::from(line.quantity);
-            let subtotal = (line.unit_price * Decimal::from(line.quantity)).to_string();
-
-            sqlx::query(
-                r#"
-                INSERT INTO quote_line (
-                    id, quote_id, product_id, quantity,
-                    unit_price, subtotal, discount_pct, notes,
-                    attributes_json, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, NULL, ?, ?)
-                "#,
-            )
-            .bind(&line_id)
-            .bind(&quote.id.0)
-            .bind(&line.product_id.0)
-            .bind(quantity)
-            .bind(&unit_price)
-            .bind(&subtotal)
-            .bind(line.discount_pct)
-            .bind(&line.notes)
-            .bind(&created_at)
-            .bind(&now)
-            .execute(&mut *tx)
-            .await?;
-        }
+        write_lines(&mut tx, &quote, &created_at, &now).await?;
 
         tx.commit().await?;
         Ok(())
@@ -293,6 +323,50 @@ impl QuoteRepository for SqlQuoteRepository {
     }
 }
 
+/// Replaces the quote's stored lines with `quote.lines`.
+async fn write_lines(
+    conn: &mut SqliteConnection,
+    quote: &Quote,
+    created_at: &str,
+    now: &str,
+) -> Result<(), RepositoryError> {
+    sqlx::query("DELETE FROM quote_line WHERE quote_id = ?")
+        .bind(&quote.id.0)
+        .execute(&mut *conn)
+        .await?;
+
+    for (index, line) in quote.lines.iter().enumerate() {
+        let line_id = format!("{}-ql-{}", quote.id.0, index + 1);
+        let unit_price = line.unit_price.to_string();
+        let quantity = i64::from(line.quantity);
+        let subtotal = (line.unit_price * Decimal::from(line.quantity)).to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO quote_line (
+                id, quote_id, product_id, quantity,
+                unit_price, subtotal, discount_pct, notes,
+                attributes_json, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, NULL, ?, ?)
+            "#,
+        )
+        .bind(&line_id)
+        .bind(&quote.id.0)
+        .bind(&line.product_id.0)
+        .bind(quantity)
+        .bind(&unit_price)
+        .bind(&subtotal)
+        .bind(line.discount_pct)
+        .bind(&line.notes)
+        .bind(created_at)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
 async fn load_quote_lines(
     pool: &DbPool,
     quote_id: &str,
@@ -344,7 +418,7 @@ async fn load_quote_lines(
     Ok(lines)
 }
 
-fn parse_quote_status(raw: &str) -> Result<QuoteStatus, RepositoryError> {
+pub fn parse_quote_status(raw: &str) -> Result<QuoteStatus, RepositoryError> {
     match raw.to_lowercase().as_str() {
         "draft" => Ok(QuoteStatus::Draft),
         "validated" => Ok(QuoteStatus::Validated),
@@ -354,6 +428,7 @@ fn parse_quote_status(raw: &str) -> Result<QuoteStatus, RepositoryError> {
         "rejected" => Ok(QuoteStatus::Rejected),
         "finalized" => Ok(QuoteStatus::Finalized),
         "sent" => Ok(QuoteStatus::Sent),
+        "accepted" => Ok(QuoteStatus::Accepted),
         "expired" => Ok(QuoteStatus::Expired),
         "cancelled" => Ok(QuoteStatus::Cancelled),
         "revised" => Ok(QuoteStatus::Revised),
@@ -371,6 +446,7 @@ pub fn quote_status_as_str(status: &QuoteStatus) -> &'static str {
         QuoteStatus::Rejected => "rejected",
         QuoteStatus::Finalized => "finalized",
         QuoteStatus::Sent => "sent",
+        QuoteStatus::Accepted => "accepted",
         QuoteStatus::Expired => "expired",
         QuoteStatus::Cancelled => "cancelled",
         QuoteStatus::Revised => "revised",
@@ -485,6 +561,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn save_revision_refuses_a_second_revision_of_the_same_version() -> Result<(), String> {
+        let pool = in_memory_pool().await.map_err(|error| error.to_string())?;
+        run_pending(&pool).await.map_err(|error| error.to_string())?;
+        let repo = SqlQuoteRepository::new(pool.clone());
+        let mut quote = test_quote("Q-REVISE-001", Some("acct_acme"));
+        quote.status = QuoteStatus::Sent;
+        repo.save(quote.clone()).await.map_err(|error| error.to_string())?;
+
+        let mut first = quote.clone();
+        first.lines.truncate(1);
+        let mut second = quote.clone();
+        second.lines[0].quantity = 99;
+
+        let mut conn = pool.acquire().await.map_err(|error| error.to_string())?;
+        let saved = SqlQuoteRepository::save_revision(&mut conn, &mut first)
+            .await
+            .map_err(|error| error.to_string())?;
+        assert_eq!(saved, RevisionSave::Saved);
+        assert_eq!((first.version, first.status.clone()), (2, QuoteStatus::Revised));
+
+        let stale = SqlQuoteRepository::save_revision(&mut conn, &mut second)
+            .await
+            .map_err(|error| error.to_string())?;
+        assert_eq!(stale, RevisionSave::Stale { current: 2 });
+        assert_eq!(second.version, 1, "a refused revision is not applied to the caller's copy");
+        drop(conn);
+
+        let loaded = repo
+            .find_by_id(&quote.id)
+            .await
+            .map_err(|error| error.to_string())?
+            .ok_or("quote should exist")?;
+        assert_eq!(loaded.version, 2);
+        assert_eq!(loaded.lines.len(), 1);
+        assert_eq!(loaded.lines[0].quantity, 3);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn updating_quote_replaces_lines() -> Result<(), String> {
         let pool = in_memory_pool().await.map_err(|error| error.to_string())?;
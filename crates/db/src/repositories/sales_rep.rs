@@ -195,11 +195,10 @@ impl SqlSalesRepRepository {
             return Ok(None);
         }
 
-        let row: (i64,) =
-            sqlx::query_as("SELECT spent_discount_cents FROM sales_rep WHERE id = ?")
-                .bind(&rep_id.0)
-                .fetch_one(&self.pool)
-                .await?;
+        let row: (i64,) = sqlx::query_as("SELECT spent_discount_cents FROM sales_rep WHERE id = ?")
+            .bind(&rep_id.0)
+            .fetch_one(&self.pool)
+            .await?;
         Ok(Some(row.0))
     }
 
@@ -0,0 +1,701 @@
+//! Persistent similar-deal index shared by the REST API and the CLI.
+//!
+//! Keeps the `deal_similarity_index` table (multi-index hashing, see
+//! [`quotey_core::dna::index`]) in step with the [`DealDnaLifecycleService`] hooks, and answers
+//! filtered similar-deal lookups by probing the chunk columns near the query fingerprint
+//! instead of loading every stored fingerprint.
+
+use std::collections::HashMap;
+
+use quotey_core::chrono::Utc;
+use quotey_core::dna::index::{
+    chunk_neighbours, fingerprint_chunks, hamming_distance_bytes, max_hamming_distance,
+    probe_radius, rank_similar_deals,
+};
+use quotey_core::dna::{
+    ClosedDealOutcome, DealDnaLifecycleService, DealOutcomeMetadata, DealOutcomeStatus,
+    DnaLifecycleError, DnaLifecycleStore, FingerprintGenerator, FingerprintSnapshot, SimilarDeal,
+    SimilarityFilter, SimilarityIndexEntry, FINGERPRINT_BYTES,
+};
+use quotey_core::domain::quote::{Quote, QuoteId};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row, Sqlite, Transaction};
+use thiserror::Error;
+
+use crate::repositories::{QuoteRepository, RepositoryError, SqlQuoteRepository};
+use crate::DbPool;
+
+/// Similarity floor used when callers don't pass one.
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.7;
+/// Chunk values bound per probe statement, well under SQLite's variable limit.
+const PROBE_BATCH: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarDealsQuery {
+    pub min_similarity: f32,
+    pub limit: usize,
+    pub filter: SimilarityFilter,
+}
+
+impl Default for SimilarDealsQuery {
+    fn default() -> Self {
+        Self {
+            min_similarity: DEFAULT_MIN_SIMILARITY,
+            limit: 5,
+            filter: SimilarityFilter::default(),
+        }
+    }
+}
+
+/// A ranked match joined with the quote and segment it came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarDealMatch {
+    pub deal: SimilarDeal,
+    pub customer_name: String,
+    pub customer_segment: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct IndexRebuildReport {
+    /// Closed quotes that had no fingerprint yet and were fingerprinted first.
+    pub backfilled: usize,
+    pub indexed: usize,
+    /// Fingerprints whose stored vector is not a 128-bit hash.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SimilarityIndexError {
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error(transparent)]
+    Lifecycle(#[from] DnaLifecycleError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for SimilarityIndexError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct DealSimilarityService {
+    pool: DbPool,
+    lifecycle: DealDnaLifecycleService,
+}
+
+impl DealSimilarityService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool, lifecycle: DealDnaLifecycleService::new(FingerprintGenerator::new()) }
+    }
+
+    /// Fingerprints a finalized or sent quote and (re)indexes it.
+    pub async fn on_quote_closed(
+        &self,
+        quote: &Quote,
+        close_date: Option<String>,
+    ) -> Result<FingerprintSnapshot, SimilarityIndexError> {
+        let mut batch = DnaWriteBatch::default();
+        let snapshot = self.lifecycle.on_quote_closed(quote, close_date, &mut batch)?;
+        batch.commit(&self.pool).await?;
+        Ok(snapshot)
+    }
+
+    /// Refreshes the fingerprint and index entry of a quote whose configuration changed.
+    pub async fn on_quote_reopened_or_modified(
+        &self,
+        quote: &Quote,
+    ) -> Result<FingerprintSnapshot, SimilarityIndexError> {
+        let mut batch = DnaWriteBatch::default();
+        let snapshot = self.lifecycle.on_quote_reopened_or_modified(quote, &mut batch)?;
+        batch.commit(&self.pool).await?;
+        Ok(snapshot)
+    }
+
+    pub async fn record_outcome(
+        &self,
+        quote: &Quote,
+        outcome: ClosedDealOutcome,
+        close_date: String,
+    ) -> Result<DealOutcomeMetadata, SimilarityIndexError> {
+        let mut batch = DnaWriteBatch::default();
+        let metadata =
+            self.lifecycle.record_closed_deal_outcome(quote, outcome, close_date, &mut batch)?;
+        batch.commit(&self.pool).await?;
+        Ok(metadata)
+    }
+
+    /// Deals similar to `quote_id`, excluding the quote itself. Quotes that are not indexed yet
+    /// are fingerprinted from their current lines.
+    pub async fn find_similar_to_quote(
+        &self,
+        quote_id: &str,
+        query: &SimilarDealsQuery,
+    ) -> Result<Vec<SimilarDealMatch>, SimilarityIndexError> {
+        let indexed: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT fingerprint FROM deal_similarity_index WHERE quote_id = ?")
+                .bind(quote_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let fingerprint = match indexed.as_deref().and_then(fingerprint_from_blob) {
+            Some(fingerprint) => fingerprint,
+            None => {
+                let quote = SqlQuoteRepository::new(self.pool.clone())
+                    .find_by_id(&QuoteId(quote_id.to_string()))
+                    .await?
+                    .ok_or_else(|| SimilarityIndexError::QuoteNotFound(quote_id.to_string()))?;
+                FingerprintGenerator::new().generate_from_quote(&quote).hash_bytes
+            }
+        };
+        self.find_similar(&fingerprint, query, Some(quote_id)).await
+    }
+
+    pub async fn find_similar(
+        &self,
+        fingerprint: &[u8; FINGERPRINT_BYTES],
+        query: &SimilarDealsQuery,
+        exclude_quote_id: Option<&str>,
+    ) -> Result<Vec<SimilarDealMatch>, SimilarityIndexError> {
+        if query.limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates: HashMap<String, IndexedDeal> = HashMap::new();
+        match probe_radius(query.min_similarity) {
+            Some(radius) => {
+                for (column, chunk) in fingerprint_chunks(fingerprint).into_iter().enumerate() {
+                    let neighbours = chunk_neighbours(chunk, radius);
+                    for probe in neighbours.chunks(PROBE_BATCH) {
+                        let placeholders = vec!["?"; probe.len()].join(", ");
+                        let sql = format!(
+                            "{INDEX_SELECT} WHERE i.chunk_{column} IN ({placeholders}) {FILTER_SQL}"
+                        );
+                        let mut statement = sqlx::query(&sql);
+                        for value in probe {
+                            statement = statement.bind(i64::from(*value));
+                        }
+                        for row in
+                            bind_filter(statement, &query.filter).fetch_all(&self.pool).await?
+                        {
+                            let deal = indexed_deal_from_row(&row)?;
+                            candidates.entry(deal.entry.quote_id.clone()).or_insert(deal);
+                        }
+                    }
+                }
+            }
+            None => {
+                let sql = format!("{INDEX_SELECT} WHERE 1 = 1 {FILTER_SQL}");
+                for row in
+                    bind_filter(sqlx::query(&sql), &query.filter).fetch_all(&self.pool).await?
+                {
+                    let deal = indexed_deal_from_row(&row)?;
+                    candidates.insert(deal.entry.quote_id.clone(), deal);
+                }
+            }
+        }
+        if let Some(exclude) = exclude_quote_id {
+            candidates.remove(exclude);
+        }
+
+        let max_distance = max_hamming_distance(query.min_similarity);
+        let mut ranked: Vec<SimilarDeal> = candidates
+            .values()
+            .filter_map(|candidate| {
+                let distance = hamming_distance_bytes(fingerprint, &candidate.entry.fingerprint);
+                (distance <= max_distance).then(|| candidate.entry.to_similar_deal(distance))
+            })
+            .collect();
+        rank_similar_deals(&mut ranked, query.limit);
+
+        Ok(ranked
+            .into_iter()
+            .filter_map(|deal| {
+                let candidate = candidates.remove(&deal.outcome.quote_id)?;
+                Some(SimilarDealMatch {
+                    deal,
+                    customer_name: candidate.customer_name,
+                    customer_segment: candidate.entry.customer_segment,
+                })
+            })
+            .collect())
+    }
+
+    /// Fingerprints closed quotes that were never fingerprinted, then rebuilds the whole index
+    /// from the latest fingerprint and deal outcome of every quote.
+    pub async fn rebuild(&self) -> Result<IndexRebuildReport, SimilarityIndexError> {
+        let mut report = IndexRebuildReport::default();
+
+        let unfingerprinted: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM quote
+             WHERE status IN ('finalized', 'sent', 'accepted')
+               AND id NOT IN (SELECT quote_id FROM configuration_fingerprints)
+             ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let quotes = SqlQuoteRepository::new(self.pool.clone());
+        for quote_id in unfingerprinted {
+            if let Some(quote) = quotes.find_by_id(&QuoteId(quote_id)).await? {
+                self.on_quote_closed(&quote, None).await?;
+                report.backfilled += 1;
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM deal_similarity_index").execute(&mut *tx).await?;
+        let rows = sqlx::query(
+            "SELECT f.quote_id, f.configuration_vector, f.outcome_status, f.final_price, f.close_date
+             FROM configuration_fingerprints f
+             WHERE f.id = (
+                 SELECT latest.id FROM configuration_fingerprints latest
+                 WHERE latest.quote_id = f.quote_id
+                 ORDER BY latest.created_at DESC, latest.id DESC
+                 LIMIT 1
+             )
+             ORDER BY f.quote_id",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in rows {
+            let vector: Vec<u8> = row.try_get("configuration_vector")?;
+            let Some(fingerprint) = fingerprint_from_blob(&vector) else {
+                report.skipped += 1;
+                continue;
+            };
+            let outcome: String = row.try_get("outcome_status")?;
+            let entry = SimilarityIndexEntry {
+                quote_id: row.try_get("quote_id")?,
+                fingerprint,
+                outcome: parse_outcome(&outcome)?,
+                customer_segment: None,
+                final_price: decimal_from_f64(row.try_get("final_price")?),
+                close_date: row.try_get("close_date")?,
+            };
+            upsert_index_entry(&mut tx, &entry).await?;
+            report.indexed += 1;
+        }
+        tx.commit().await?;
+
+        Ok(report)
+    }
+}
+
+/// Lifecycle writes collected synchronously and applied in one transaction.
+#[derive(Default)]
+struct DnaWriteBatch {
+    writes: Vec<DnaWrite>,
+}
+
+enum DnaWrite {
+    Snapshot(FingerprintSnapshot),
+    Outcome(DealOutcomeMetadata),
+    Index(SimilarityIndexEntry),
+}
+
+impl DnaLifecycleStore for DnaWriteBatch {
+    fn upsert_fingerprint_snapshot(&mut self, snapshot: FingerprintSnapshot) -> Result<(), String> {
+        self.writes.push(DnaWrite::Snapshot(snapshot));
+        Ok(())
+    }
+
+    fn upsert_deal_outcome(&mut self, outcome: DealOutcomeMetadata) -> Result<(), String> {
+        self.writes.push(DnaWrite::Outcome(outcome));
+        Ok(())
+    }
+
+    fn upsert_similarity_entry(&mut self, entry: SimilarityIndexEntry) -> Result<(), String> {
+        self.writes.push(DnaWrite::Index(entry));
+        Ok(())
+    }
+}
+
+impl DnaWriteBatch {
+    async fn commit(self, pool: &DbPool) -> Result<(), SimilarityIndexError> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await?;
+        for write in self.writes {
+            match write {
+                DnaWrite::Snapshot(snapshot) => {
+                    sqlx::query(
+                        "INSERT INTO configuration_fingerprints
+                            (id, quote_id, fingerprint_hash, configuration_vector, outcome_status,
+                             final_price, close_date, created_at)
+                         VALUES (?, ?, ?, ?, COALESCE((
+                             SELECT outcome FROM deal_outcomes WHERE quote_id = ?
+                             ORDER BY close_date DESC, created_at DESC LIMIT 1
+                         ), 'pending'), ?, ?, ?)
+                         ON CONFLICT(id) DO UPDATE SET
+                            fingerprint_hash = excluded.fingerprint_hash,
+                            configuration_vector = excluded.configuration_vector,
+                            outcome_status = excluded.outcome_status,
+                            final_price = excluded.final_price,
+                            close_date = excluded.close_date,
+                            created_at = excluded.created_at",
+                    )
+                    .bind(format!("fp-{}", snapshot.quote_id))
+                    .bind(&snapshot.quote_id)
+                    .bind(&snapshot.fingerprint_hash)
+                    .bind(&snapshot.configuration_vector)
+                    .bind(&snapshot.quote_id)
+                    .bind(snapshot.final_price.to_f64().unwrap_or_default())
+                    .bind(&snapshot.close_date)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                DnaWrite::Outcome(outcome) => {
+                    let status = outcome_as_str(&outcome.outcome_status);
+                    let close_date =
+                        outcome.close_date.clone().unwrap_or_else(|| now[..10].to_string());
+                    let final_price = outcome.final_price.to_f64().unwrap_or_default();
+                    sqlx::query(
+                        "INSERT INTO deal_outcomes
+                            (id, quote_id, outcome, final_price, close_date, created_at)
+                         VALUES (?, ?, ?, ?, ?, ?)
+                         ON CONFLICT(id) DO UPDATE SET
+                            outcome = excluded.outcome,
+                            final_price = excluded.final_price,
+                            close_date = excluded.close_date,
+                            created_at = excluded.created_at",
+                    )
+                    .bind(format!("do-{}", outcome.quote_id))
+                    .bind(&outcome.quote_id)
+                    .bind(status)
+                    .bind(final_price)
+                    .bind(&close_date)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+                    sqlx::query(
+                        "UPDATE configuration_fingerprints
+                         SET outcome_status = ?, close_date = ?
+                         WHERE quote_id = ?",
+                    )
+                    .bind(status)
+                    .bind(&close_date)
+                    .bind(&outcome.quote_id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                DnaWrite::Index(entry) => upsert_index_entry(&mut tx, &entry).await?,
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Writes an index row, preferring the latest recorded deal outcome (and its segment) over
+/// the entry's own outcome fields.
+async fn upsert_index_entry(
+    tx: &mut Transaction<'_, Sqlite>,
+    entry: &SimilarityIndexEntry,
+) -> Result<(), SimilarityIndexError> {
+    let recorded = sqlx::query(
+        "SELECT outcome, customer_segment, final_price, close_date
+         FROM deal_outcomes
+         WHERE quote_id = ?
+         ORDER BY close_date DESC, created_at DESC, id DESC
+         LIMIT 1",
+    )
+    .bind(&entry.quote_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    let (outcome, segment, final_price, close_date) = match recorded {
+        Some(row) => (
+            row.try_get::<String, _>("outcome")?,
+            row.try_get::<Option<String>, _>("customer_segment")?,
+            row.try_get::<f64, _>("final_price")?,
+            Some(row.try_get::<String, _>("close_date")?),
+        ),
+        None => (
+            outcome_as_str(&entry.outcome).to_string(),
+            entry.customer_segment.clone(),
+            entry.final_price.to_f64().unwrap_or_default(),
+            entry.close_date.clone(),
+        ),
+    };
+
+    let chunks = fingerprint_chunks(&entry.fingerprint);
+    let mut statement = sqlx::query(
+        "INSERT INTO deal_similarity_index
+            (quote_id, fingerprint, chunk_0, chunk_1, chunk_2, chunk_3, chunk_4, chunk_5,
+             chunk_6, chunk_7, outcome, customer_segment, final_price, close_date, indexed_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(quote_id) DO UPDATE SET
+            fingerprint = excluded.fingerprint,
+            chunk_0 = excluded.chunk_0,
+            chunk_1 = excluded.chunk_1,
+            chunk_2 = excluded.chunk_2,
+            chunk_3 = excluded.chunk_3,
+            chunk_4 = excluded.chunk_4,
+            chunk_5 = excluded.chunk_5,
+            chunk_6 = excluded.chunk_6,
+            chunk_7 = excluded.chunk_7,
+            outcome = excluded.outcome,
+            customer_segment = excluded.customer_segment,
+            final_price = excluded.final_price,
+            close_date = excluded.close_date,
+            indexed_at = excluded.indexed_at",
+    )
+    .bind(&entry.quote_id)
+    .bind(entry.fingerprint.to_vec());
+    for chunk in chunks {
+        statement = statement.bind(i64::from(chunk));
+    }
+    statement
+        .bind(outcome)
+        .bind(segment)
+        .bind(final_price)
+        .bind(close_date)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+struct IndexedDeal {
+    entry: SimilarityIndexEntry,
+    customer_name: String,
+}
+
+const INDEX_SELECT: &str = "SELECT i.quote_id, i.fingerprint, i.outcome, i.customer_segment,
+        i.final_price, i.close_date, COALESCE(q.created_by, 'unknown') AS customer_name
+     FROM deal_similarity_index i
+     LEFT JOIN quote q ON q.id = i.quote_id";
+
+const FILTER_SQL: &str = "AND (? IS NULL OR lower(i.customer_segment) = lower(?))
+     AND (? IS NULL OR i.outcome = ?)
+     AND (? IS NULL OR substr(i.close_date, 1, 10) >= substr(?, 1, 10))
+     AND (? IS NULL OR substr(i.close_date, 1, 10) <= substr(?, 1, 10))";
+
+type SqliteQuery<'q> = sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>;
+
+fn bind_filter<'q>(statement: SqliteQuery<'q>, filter: &'q SimilarityFilter) -> SqliteQuery<'q> {
+    let outcome = filter.outcome.as_ref().map(outcome_as_str);
+    statement
+        .bind(filter.customer_segment.as_deref())
+        .bind(filter.customer_segment.as_deref())
+        .bind(outcome)
+        .bind(outcome)
+        .bind(filter.closed_from.as_deref())
+        .bind(filter.closed_from.as_deref())
+        .bind(filter.closed_to.as_deref())
+        .bind(filter.closed_to.as_deref())
+}
+
+fn indexed_deal_from_row(row: &SqliteRow) -> Result<IndexedDeal, SimilarityIndexError> {
+    let quote_id: String = row.try_get("quote_id")?;
+    let blob: Vec<u8> = row.try_get("fingerprint")?;
+    let fingerprint = fingerprint_from_blob(&blob).ok_or_else(|| {
+        RepositoryError::Decode(format!("index fingerprint for `{quote_id}` is not 16 bytes"))
+    })?;
+    let outcome: String = row.try_get("outcome")?;
+    Ok(IndexedDeal {
+        entry: SimilarityIndexEntry {
+            quote_id,
+            fingerprint,
+            outcome: parse_outcome(&outcome)?,
+            customer_segment: row.try_get("customer_segment")?,
+            final_price: decimal_from_f64(row.try_get("final_price")?),
+            close_date: row.try_get("close_date")?,
+        },
+        customer_name: row.try_get("customer_name")?,
+    })
+}
+
+fn fingerprint_from_blob(blob: &[u8]) -> Option<[u8; FINGERPRINT_BYTES]> {
+    blob.try_into().ok()
+}
+
+fn decimal_from_f64(value: f64) -> Decimal {
+    Decimal::from_f64(value).map(|value| value.round_dp(2)).unwrap_or_default()
+}
+
+pub fn outcome_as_str(outcome: &DealOutcomeStatus) -> &'static str {
+    match outcome {
+        DealOutcomeStatus::Won => "won",
+        DealOutcomeStatus::Lost => "lost",
+        DealOutcomeStatus::Pending => "pending",
+    }
+}
+
+pub fn parse_outcome(value: &str) -> Result<DealOutcomeStatus, RepositoryError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "won" => Ok(DealOutcomeStatus::Won),
+        "lost" => Ok(DealOutcomeStatus::Lost),
+        "pending" => Ok(DealOutcomeStatus::Pending),
+        other => Err(RepositoryError::Decode(format!("unknown deal outcome `{other}`"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::dna::{ClosedDealOutcome, DealOutcomeStatus, SimilarityFilter};
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use rust_decimal::Decimal;
+
+    use super::{DealSimilarityService, SimilarDealsQuery};
+    use crate::repositories::{QuoteRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    fn quote(id: &str, status: QuoteStatus, lines: &[(&str, u32)]) -> Quote {
+        let now = Utc::now();
+        Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status,
+            account_id: None,
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: None,
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: format!("owner-{id}"),
+            lines: lines
+                .iter()
+                .map(|(product, quantity)| QuoteLine {
+                    product_id: ProductId((*product).to_string()),
+                    quantity: *quantity,
+                    unit_price: Decimal::new(10_000, 2),
+                    discount_pct: 0.0,
+                    notes: None,
+                })
+                .collect(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    const BUNDLE: &[(&str, u32)] =
+        &[("plan-pro", 50), ("support-premium", 1), ("addon-sso", 50), ("addon-audit", 50)];
+
+    #[tokio::test]
+    async fn lifecycle_hooks_keep_index_current_and_lookups_apply_filters() {
+        let pool = setup().await;
+        let quotes = SqlQuoteRepository::new(pool.clone());
+        let service = DealSimilarityService::new(pool.clone());
+
+        let source = quote("Q-SRC", QuoteStatus::Draft, BUNDLE);
+        let twin = quote("Q-TWIN", QuoteStatus::Sent, BUNDLE);
+        let other = quote("Q-OTHER", QuoteStatus::Sent, &[("starter", 3)]);
+        for quote in [&source, &twin, &other] {
+            quotes.save(quote.clone()).await.expect("save quote");
+        }
+        service.on_quote_closed(&twin, Some("2026-02-01".to_string())).await.expect("close twin");
+        service.on_quote_closed(&other, None).await.expect("close other");
+        service
+            .record_outcome(&twin, ClosedDealOutcome::Won, "2026-02-01".to_string())
+            .await
+            .expect("twin won");
+
+        let matches = service
+            .find_similar_to_quote("Q-SRC", &SimilarDealsQuery::default())
+            .await
+            .expect("lookup");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].deal.outcome.quote_id, "Q-TWIN");
+        assert_eq!(matches[0].deal.outcome.outcome_status, DealOutcomeStatus::Won);
+        assert_eq!(matches[0].deal.hamming_distance, 0);
+        assert_eq!(matches[0].customer_name, "owner-Q-TWIN");
+
+        let lost_only = SimilarDealsQuery {
+            filter: SimilarityFilter {
+                outcome: Some(DealOutcomeStatus::Lost),
+                ..SimilarityFilter::default()
+            },
+            ..SimilarDealsQuery::default()
+        };
+        assert!(service
+            .find_similar_to_quote("Q-SRC", &lost_only)
+            .await
+            .expect("lookup")
+            .is_empty());
+        let before_close = SimilarDealsQuery {
+            filter: SimilarityFilter {
+                closed_to: Some("2026-01-31".to_string()),
+                ..SimilarityFilter::default()
+            },
+            ..SimilarDealsQuery::default()
+        };
+        assert!(service
+            .find_similar_to_quote("Q-SRC", &before_close)
+            .await
+            .expect("lookup")
+            .is_empty());
+
+        let everything = SimilarDealsQuery { min_similarity: 0.0, ..SimilarDealsQuery::default() };
+        let scanned = service.find_similar_to_quote("Q-SRC", &everything).await.expect("full scan");
+        assert_eq!(scanned.len(), 2);
+        assert!(scanned.iter().all(|deal| deal.deal.outcome.quote_id != "Q-SRC"));
+
+        let mut reworked = twin.clone();
+        reworked.status = QuoteStatus::Revised;
+        reworked.lines = other.lines.clone();
+        service.on_quote_reopened_or_modified(&reworked).await.expect("reopen");
+        let exact = SimilarDealsQuery { min_similarity: 1.0, ..SimilarDealsQuery::default() };
+        let matches = service.find_similar_to_quote("Q-OTHER", &exact).await.expect("lookup");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].deal.outcome.quote_id, "Q-TWIN");
+
+        assert!(matches!(
+            service.find_similar_to_quote("Q-404", &SimilarDealsQuery::default()).await,
+            Err(super::SimilarityIndexError::QuoteNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rebuild_backfills_closed_quotes_and_restores_segments() {
+        let pool = setup().await;
+        let quotes = SqlQuoteRepository::new(pool.clone());
+        for quote in [
+            quote("Q-A", QuoteStatus::Finalized, BUNDLE),
+            quote("Q-B", QuoteStatus::Sent, BUNDLE),
+            quote("Q-DRAFT", QuoteStatus::Draft, BUNDLE),
+        ] {
+            quotes.save(quote).await.expect("save quote");
+        }
+        sqlx::query(
+            "INSERT INTO deal_outcomes
+                (id, quote_id, outcome, final_price, close_date, customer_segment, created_at)
+             VALUES ('deal-b', 'Q-B', 'lost', 900.0, '2026-01-10', 'mid-market', '2026-01-10')",
+        )
+        .execute(&pool)
+        .await
+        .expect("seed outcome");
+
+        let service = DealSimilarityService::new(pool.clone());
+        let report = service.rebuild().await.expect("rebuild");
+        assert_eq!((report.backfilled, report.indexed, report.skipped), (2, 2, 0));
+
+        let mid_market = SimilarDealsQuery {
+            filter: SimilarityFilter {
+                customer_segment: Some("Mid-Market".to_string()),
+                ..SimilarityFilter::default()
+            },
+            ..SimilarDealsQuery::default()
+        };
+        let matches = service.find_similar_to_quote("Q-DRAFT", &mid_market).await.expect("lookup");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].deal.outcome.quote_id, "Q-B");
+        assert_eq!(matches[0].deal.outcome.outcome_status, DealOutcomeStatus::Lost);
+        assert_eq!(matches[0].deal.outcome.final_price, Decimal::new(900, 0));
+        assert_eq!(matches[0].customer_segment.as_deref(), Some("mid-market"));
+
+        let again = service.rebuild().await.expect("rebuild is repeatable");
+        assert_eq!((again.backfilled, again.indexed), (0, 2));
+    }
+}
@@ -0,0 +1,926 @@
+//! Deal flight simulation service shared by the MCP and REST surfaces.
+//!
+//! Runs scenario variants through [`DealFlightSimulator`] on the deterministic CPQ runtime
+//! (with org policy thresholds), persists the run, its ranked variants and per-variant deltas
+//! in the `deal_flight_scenario_*` tables, and promotes a chosen variant into a new quote
+//! revision with audit linkage back to the scenario run.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use quotey_core::audit::{
+    ActorType, AuditAction, AuditCategory, AuditEvent, AuditOutcome, EntityType,
+};
+use quotey_core::chrono::Utc;
+use quotey_core::cpq::constraints::DeterministicConstraintEngine;
+use quotey_core::cpq::margin::{MarginLine, QuoteMargin};
+use quotey_core::cpq::policy::{
+    PolicyDecision, PolicyInput, PolicyThresholds, ThresholdPolicyEngine,
+};
+use quotey_core::cpq::pricing::{DeterministicPricingEngine, PricingResult};
+use quotey_core::cpq::simulator::{
+    DealFlightSimulator, ScenarioDeltaBundle, ScenarioVariation, SimulatedScenario,
+    SimulationTelemetryContext, SimulationTelemetrySink, SimulatorGuardrailError,
+};
+use quotey_core::cpq::DeterministicCpqRuntime;
+use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine};
+use quotey_core::domain::simulation::{
+    CreateScenarioRunRequest, ScenarioAuditEventType, ScenarioDeltaType, ScenarioRun,
+    ScenarioRunId, ScenarioRunStatus, ScenarioTelemetryEvent, ScenarioVariant,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::repositories::quote::quote_status_as_str;
+use crate::repositories::{
+    QuoteRepository, RepositoryError, RevisionSave, ScenarioRepository, SqlAuditEventRepository,
+    SqlProductCostRepository, SqlQuoteRepository, SqlScenarioRepository,
+};
+use crate::DbPool;
+
+/// Most variants a single run may compare.
+pub const MAX_SIMULATION_VARIANTS: usize = 3;
+
+/// Scenario simulation request.
+#[derive(Clone, Debug)]
+pub struct SimulateQuery {
+    pub quote_id: QuoteId,
+    pub variations: Vec<ScenarioVariation>,
+    /// Baseline discount to evaluate policy against; defaults to the quote's line discounts.
+    pub requested_discount_pct: Option<Decimal>,
+    /// Audit actor type: `user`, `system` or `agent`.
+    pub actor_type: String,
+    pub actor_id: String,
+    /// Conversation the request came from (Slack thread, MCP session, API key).
+    pub thread_id: String,
+    pub correlation_id: String,
+}
+
+/// Request to promote one variant of a completed run.
+#[derive(Clone, Debug)]
+pub struct PromoteQuery {
+    /// Quote the run must belong to.
+    pub quote_id: QuoteId,
+    pub run_id: String,
+    /// Variant key (case-insensitive) or variant id.
+    pub variant: String,
+    pub actor_type: String,
+    pub actor_id: String,
+    pub correlation_id: String,
+}
+
+/// Priced and policy-checked result of one scenario (or the baseline).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioOutcome {
+    pub subtotal: Decimal,
+    pub discount_total: Decimal,
+    pub tax_total: Decimal,
+    pub total: Decimal,
+    pub approval_required: bool,
+    pub approval_status: String,
+    pub reasons: Vec<String>,
+}
+
+impl ScenarioOutcome {
+    fn new(pricing: &PricingResult, policy: &PolicyDecision) -> Self {
+        Self {
+            subtotal: pricing.subtotal,
+            discount_total: pricing.discount_total,
+            tax_total: pricing.tax_total,
+            total: pricing.total,
+            approval_required: policy.approval_required,
+            approval_status: format!("{:?}", policy.approval_status),
+            reasons: policy.reasons.clone(),
+        }
+    }
+}
+
+/// One persisted variant, in rank order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulatedVariant {
+    pub variant_id: String,
+    pub variant_key: String,
+    pub rank_order: i32,
+    pub rank_score: f64,
+    pub selected_for_promotion: bool,
+    pub outcome: ScenarioOutcome,
+    pub delta: ScenarioDeltaBundle,
+    pub lines: Vec<QuoteLine>,
+}
+
+/// Persisted simulation run with its baseline and ranked variants.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulationRun {
+    pub run_id: String,
+    pub quote_id: String,
+    pub base_quote_version: i32,
+    pub status: &'static str,
+    pub baseline: ScenarioOutcome,
+    pub variants: Vec<SimulatedVariant>,
+}
+
+/// Result of promoting a variant into a new quote revision.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Promotion {
+    pub run_id: String,
+    pub variant_id: String,
+    pub variant_key: String,
+    pub quote_id: String,
+    pub previous_version: u32,
+    pub version: u32,
+    pub status: &'static str,
+    pub audit_event_id: String,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("quote `{0}` not found")]
+    QuoteNotFound(String),
+    #[error("simulation run `{0}` not found")]
+    RunNotFound(String),
+    #[error("variant `{variant}` not found in simulation run `{run_id}`")]
+    VariantNotFound { run_id: String, variant: String },
+    #[error("{}", .error.user_safe_message())]
+    Guardrail { run_id: Option<String>, error: SimulatorGuardrailError },
+    #[error("simulation run `{run_id}` is `{status}` and cannot be promoted")]
+    NotPromotable { run_id: String, status: &'static str },
+    #[error("quote moved from version {expected} to {current} since the simulation ran")]
+    StaleRun { expected: i32, current: u32 },
+    #[error("quote is `{0}` and cannot be revised")]
+    QuoteClosed(&'static str),
+    #[error("simulation failed: {0}")]
+    Failed(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl SimulationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::QuoteNotFound(_) => "quote_not_found",
+            Self::RunNotFound(_) => "run_not_found",
+            Self::VariantNotFound { .. } => "variant_not_found",
+            Self::Guardrail { .. } => "guardrail_rejected",
+            Self::NotPromotable { .. } => "run_not_promotable",
+            Self::StaleRun { .. } => "stale_run",
+            Self::QuoteClosed(_) => "quote_closed",
+            Self::Failed(_) => "simulation_failed",
+            Self::Repository(_) => "repository_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for SimulationError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+/// Collects simulator telemetry so it can be persisted as scenario audit rows.
+#[derive(Default)]
+struct RecordingSink {
+    events: Mutex<Vec<ScenarioTelemetryEvent>>,
+}
+
+impl RecordingSink {
+    fn take(&self) -> Vec<ScenarioTelemetryEvent> {
+        match self.events.lock() {
+            Ok(mut events) => std::mem::take(&mut *events),
+            Err(poisoned) => std::mem::take(&mut *poisoned.into_inner()),
+        }
+    }
+}
+
+impl SimulationTelemetrySink for RecordingSink {
+    fn emit(&self, event: ScenarioTelemetryEvent) {
+        match self.events.lock() {
+            Ok(mut events) => events.push(event),
+            Err(poisoned) => poisoned.into_inner().push(event),
+        }
+    }
+}
+
+pub struct SimulationService {
+    pool: DbPool,
+    scenarios: SqlScenarioRepository,
+    thresholds: PolicyThresholds,
+}
+
+impl SimulationService {
+    pub fn new(pool: DbPool, thresholds: PolicyThresholds) -> Self {
+        let scenarios = SqlScenarioRepository::new(pool.clone());
+        Self { pool, scenarios, thresholds }
+    }
+
+    /// Simulates the variations against the quote's current version and persists the run.
+    ///
+    /// Guardrail rejections after the run is created are recorded as a `failed` run.
+    pub async fn simulate(&self, query: SimulateQuery) -> Result<SimulationRun, SimulationError> {
+        let quote = self.load_quote(&query.quote_id).await?;
+        if query.variations.is_empty() || query.variations.len() > MAX_SIMULATION_VARIANTS {
+            let error = if query.variations.is_empty() {
+                SimulatorGuardrailError::EmptyVariationSet
+            } else {
+                SimulatorGuardrailError::TooManyVariations {
+                    requested: query.variations.len(),
+                    max_allowed: MAX_SIMULATION_VARIANTS,
+                }
+            };
+            return Err(SimulationError::Guardrail { run_id: None, error });
+        }
+
+        let base_quote_version = i32::try_from(quote.version).unwrap_or(i32::MAX);
+        let params = json!({
+            "variations": &query.variations,
+            "requested_discount_pct": query.requested_discount_pct,
+        });
+        let run = self
+            .scenarios
+            .create_run(CreateScenarioRunRequest {
+                quote_id: quote.id.clone(),
+                thread_id: query.thread_id.clone(),
+                actor_id: query.actor_id.clone(),
+                correlation_id: query.correlation_id.clone(),
+                base_quote_version,
+                request_params_json: params.to_string(),
+                variant_count: query.variations.len() as i32,
+            })
+            .await?;
+
+        let runtime = DeterministicCpqRuntime::new(
+            DeterministicConstraintEngine,
+            DeterministicPricingEngine,
+            ThresholdPolicyEngine::new(self.thresholds.clone()),
+        );
+        let simulator = DealFlightSimulator::with_limits(runtime, MAX_SIMULATION_VARIANTS);
+        let sink = RecordingSink::default();
+        let unit_costs = SqlProductCostRepository::new(self.pool.clone())
+            .unit_costs_for_quote(&quote.id.0, Utc::now().date_naive())
+            .await?;
+        let result = simulator.simulate_with_telemetry(
+            &quote,
+            &quote.currency,
+            baseline_policy_input(&quote, query.requested_discount_pct, &unit_costs),
+            query.variations.clone(),
+            &SimulationTelemetryContext {
+                correlation_id: query.correlation_id.clone(),
+                scenario_run_id: Some(run.id.clone()),
+            },
+            &sink,
+        );
+
+        let comparison = match result {
+            Ok(comparison) => comparison,
+            Err(error) => {
+                self.record_telemetry(&run.id, sink.take(), None, &query).await?;
+                self.scenarios
+                    .update_run_status(
+                        &run.id,
+                        ScenarioRunStatus::Failed,
+                        Some("guardrail_rejected".to_string()),
+                        Some(error.user_safe_message()),
+                    )
+                    .await?;
+                return Err(SimulationError::Guardrail { run_id: Some(run.id.0), error });
+            }
+        };
+
+        let baseline = ScenarioOutcome::new(
+            &comparison.baseline_evaluation.pricing,
+            &comparison.baseline_evaluation.policy,
+        );
+        // Telemetry is written before the variants so the request event leads the trail.
+        self.record_telemetry(&run.id, sink.take(), Some(&baseline), &query).await?;
+
+        let mut variants = Vec::with_capacity(comparison.variants.len());
+        for (order, scenario) in comparison.variants.iter().enumerate() {
+            variants.push(self.persist_variant(&run.id, order as i32, scenario, &query).await?);
+        }
+        self.scenarios.update_run_status(&run.id, ScenarioRunStatus::Success, None, None).await?;
+
+        Ok(SimulationRun {
+            run_id: run.id.0,
+            quote_id: quote.id.0,
+            base_quote_version,
+            status: ScenarioRunStatus::Success.as_str(),
+            baseline,
+            variants,
+        })
+    }
+
+    /// Loads a persisted run with its baseline and ranked variants.
+    pub async fn load(&self, run_id: &str) -> Result<SimulationRun, SimulationError> {
+        let run = self.get_run(run_id).await?;
+        let baseline = self
+            .scenarios
+            .list_audit_for_run(&run.id)
+            .await?
+            .into_iter()
+            .find(|event| event.event_type == ScenarioAuditEventType::ComparisonRendered)
+            .and_then(|event| {
+                serde_json::from_str::<serde_json::Value>(&event.event_payload_json).ok()
+            })
+            .and_then(|payload| serde_json::from_value(payload["baseline"].clone()).ok())
+            .ok_or_else(|| {
+                SimulationError::Failed(format!("run `{}` has no rendered comparison", run.id))
+            })?;
+
+        let mut variants = Vec::new();
+        for variant in self.scenarios.list_variants_for_run(&run.id).await? {
+            variants.push(self.variant_from_record(variant).await?);
+        }
+        variants.sort_by_key(|variant| variant.rank_order);
+
+        Ok(SimulationRun {
+            run_id: run.id.0,
+            quote_id: run.quote_id.0,
+            base_quote_version: run.base_quote_version,
+            status: run.status.as_str(),
+            baseline,
+            variants,
+        })
+    }
+
+    /// Applies a variant's configuration as a new revision of the quote.
+    ///
+    /// Only successful runs whose quote is still editable and at the simulated version can be
+    /// promoted.
+    pub async fn promote(&self, query: PromoteQuery) -> Result<Promotion, SimulationError> {
+        let run = self.get_run(&query.run_id).await?;
+        if run.quote_id != query.quote_id {
+            return Err(SimulationError::RunNotFound(run.id.0));
+        }
+        if run.status != ScenarioRunStatus::Success {
+            return Err(SimulationError::NotPromotable {
+                run_id: run.id.0,
+                status: run.status.as_str(),
+            });
+        }
+
+        let wanted = query.variant.trim();
+        let variant = self
+            .scenarios
+            .list_variants_for_run(&run.id)
+            .await?
+            .into_iter()
+            .find(|variant| {
+                variant.id.0 == wanted || variant.variant_key.eq_ignore_ascii_case(wanted)
+            })
+            .ok_or_else(|| SimulationError::VariantNotFound {
+                run_id: run.id.0.clone(),
+                variant: wanted.to_string(),
+            })?;
+
+        let mut quote = self.load_quote(&run.quote_id).await?;
+        if i64::from(quote.version) != i64::from(run.base_quote_version) {
+            return Err(SimulationError::StaleRun {
+                expected: run.base_quote_version,
+                current: quote.version,
+            });
+        }
+        if !quote.status.is_editable() {
+            return Err(SimulationError::QuoteClosed(quote_status_as_str(&quote.status)));
+        }
+
+        self.scenarios
+            .append_audit_event(
+                &run.id,
+                Some(variant.id.clone()),
+                ScenarioAuditEventType::PromotionRequested,
+                json!({ "variant_key": &variant.variant_key }).to_string(),
+                query.actor_type.clone(),
+                query.actor_id.clone(),
+                query.correlation_id.clone(),
+            )
+            .await?;
+
+        let lines: Vec<QuoteLine> = decode(&variant.configuration_result_json, "configuration")?;
+        let previous_version = quote.version;
+        quote.lines = lines;
+        let mut tx = self.pool.begin().await?;
+        if let RevisionSave::Stale { current } =
+            SqlQuoteRepository::save_revision(&mut tx, &mut quote).await?
+        {
+            return Err(SimulationError::StaleRun { expected: run.base_quote_version, current });
+        }
+        tx.commit().await?;
+        self.scenarios.promote_variant(&run.id, &variant.id).await?;
+
+        self.scenarios
+            .append_audit_event(
+                &run.id,
+                Some(variant.id.clone()),
+                ScenarioAuditEventType::PromotionApplied,
+                json!({
+                    "variant_key": &variant.variant_key,
+                    "previous_version": previous_version,
+                    "version": quote.version,
+                })
+                .to_string(),
+                query.actor_type.clone(),
+                query.actor_id.clone(),
+                query.correlation_id.clone(),
+            )
+            .await?;
+
+        let event = AuditEvent::new(
+            Some(quote.id.clone()),
+            Some(run.thread_id.clone()),
+            query.correlation_id.clone(),
+            "quote.simulation_promoted",
+            AuditCategory::Flow,
+            query.actor_id.clone(),
+            AuditOutcome::Success,
+        )
+        .with_actor_type(actor_type(&query.actor_type))
+        .with_entity(EntityType::Quote, quote.id.0.clone())
+        .with_action(AuditAction::Updated)
+        .with_before(json!({ "version": previous_version }).to_string())
+        .with_after(json!({ "version": quote.version }).to_string())
+        .with_metadata("scenario_run_id", run.id.0.clone())
+        .with_metadata("scenario_variant_id", variant.id.0.clone())
+        .with_metadata("variant_key", variant.variant_key.clone());
+        SqlAuditEventRepository::new(self.pool.clone()).save(&event).await?;
+
+        Ok(Promotion {
+            run_id: run.id.0,
+            variant_id: variant.id.0,
+            variant_key: variant.variant_key,
+            quote_id: quote.id.0,
+            previous_version,
+            version: quote.version,
+            status: quote_status_as_str(&quote.status),
+            audit_event_id: event.event_id,
+        })
+    }
+
+    async fn load_quote(&self, quote_id: &QuoteId) -> Result<Quote, SimulationError> {
+        SqlQuoteRepository::new(self.pool.clone())
+            .find_by_id(quote_id)
+            .await?
+            .ok_or_else(|| SimulationError::QuoteNotFound(quote_id.0.clone()))
+    }
+
+    async fn get_run(&self, run_id: &str) -> Result<ScenarioRun, SimulationError> {
+        self.scenarios
+            .get_run(&ScenarioRunId(run_id.trim().to_string()))
+            .await?
+            .ok_or_else(|| SimulationError::RunNotFound(run_id.trim().to_string()))
+    }
+
+    async fn persist_variant(
+        &self,
+        run_id: &ScenarioRunId,
+        order: i32,
+        scenario: &SimulatedScenario,
+        query: &SimulateQuery,
+    ) -> Result<SimulatedVariant, SimulationError> {
+        let policy = &scenario.evaluation.policy;
+        let approval_route = json!({
+            "approval_required": policy.approval_required,
+            "approval_status": format!("{:?}", policy.approval_status),
+            "required_approvals": policy
+                .violations
+                .iter()
+                .filter_map(|violation| violation.required_approval.clone())
+                .collect::<Vec<_>>(),
+        });
+        let variant = self
+            .scenarios
+            .add_variant(
+                run_id,
+                scenario.variant_key.clone(),
+                order,
+                encode(&scenario.normalized_variation, "variation")?,
+                encode(&scenario.evaluation.pricing, "pricing")?,
+                encode(policy, "policy")?,
+                approval_route.to_string(),
+                encode(&scenario.forked_quote.lines, "configuration")?,
+                scenario.rank_score,
+                scenario.rank_order,
+            )
+            .await?;
+
+        let delta = &scenario.delta;
+        for (delta_type, payload) in [
+            (ScenarioDeltaType::Price, encode(&delta.price, "price delta")?),
+            (ScenarioDeltaType::Policy, encode(&delta.policy, "policy delta")?),
+            (ScenarioDeltaType::Approval, encode(&delta.approval, "approval delta")?),
+            (
+                ScenarioDeltaType::Configuration,
+                encode(&delta.configuration, "configuration delta")?,
+            ),
+        ] {
+            self.scenarios.add_delta(&variant.id, delta_type, payload).await?;
+        }
+        self.scenarios
+            .append_audit_event(
+                run_id,
+                Some(variant.id.clone()),
+                ScenarioAuditEventType::VariantGenerated,
+                json!({
+                    "variant_key": &scenario.variant_key,
+                    "rank_order": scenario.rank_order,
+                    "total": scenario.evaluation.pricing.total,
+                })
+                .to_string(),
+                query.actor_type.clone(),
+                query.actor_id.clone(),
+                query.correlation_id.clone(),
+            )
+            .await?;
+
+        Ok(SimulatedVariant {
+            variant_id: variant.id.0,
+            variant_key: scenario.variant_key.clone(),
+            rank_order: scenario.rank_order,
+            rank_score: scenario.rank_score,
+            selected_for_promotion: false,
+            outcome: ScenarioOutcome::new(&scenario.evaluation.pricing, policy),
+            delta: delta.clone(),
+            lines: scenario.forked_quote.lines.clone(),
+        })
+    }
+
+    async fn variant_from_record(
+        &self,
+        variant: ScenarioVariant,
+    ) -> Result<SimulatedVariant, SimulationError> {
+        let pricing: PricingResult = decode(&variant.pricing_result_json, "pricing")?;
+        let policy: PolicyDecision = decode(&variant.policy_result_json, "policy")?;
+        let lines = decode(&variant.configuration_result_json, "configuration")?;
+
+        let (mut price, mut policy_delta, mut approval, mut configuration) =
+            (None, None, None, None);
+        for stored in self.scenarios.list_deltas_for_variant(&variant.id).await? {
+            let payload = &stored.delta_payload_json;
+            match stored.delta_type {
+                ScenarioDeltaType::Price => price = Some(decode(payload, "price delta")?),
+                ScenarioDeltaType::Policy => policy_delta = Some(decode(payload, "policy delta")?),
+                ScenarioDeltaType::Approval => approval = Some(decode(payload, "approval delta")?),
+                ScenarioDeltaType::Configuration => {
+                    configuration = Some(decode(payload, "configuration delta")?)
+                }
+            }
+        }
+        let missing = || {
+            SimulationError::Failed(format!("variant `{}` is missing stored deltas", variant.id))
+        };
+        let delta = ScenarioDeltaBundle {
+            price: price.ok_or_else(missing)?,
+            policy: policy_delta.ok_or_else(missing)?,
+            approval: approval.ok_or_else(missing)?,
+            configuration: configuration.ok_or_else(missing)?,
+        };
+
+        Ok(SimulatedVariant {
+            variant_id: variant.id.0,
+            variant_key: variant.variant_key,
+            rank_order: variant.rank_order,
+            rank_score: variant.rank_score,
+            selected_for_promotion: variant.selected_for_promotion,
+            outcome: ScenarioOutcome::new(&pricing, &policy),
+            delta,
+            lines,
+        })
+    }
+
+    async fn record_telemetry(
+        &self,
+        run_id: &ScenarioRunId,
+        events: Vec<ScenarioTelemetryEvent>,
+        baseline: Option<&ScenarioOutcome>,
+        query: &SimulateQuery,
+    ) -> Result<(), SimulationError> {
+        for event in events {
+            let mut payload = json!({
+                "variant_count": event.variant_count,
+                "approval_required_variant_count": event.approval_required_variant_count,
+                "latency_ms": event.latency_ms,
+                "outcome": event.outcome.as_str(),
+                "error_code": event.error_code,
+            });
+            if event.event_type == ScenarioAuditEventType::ComparisonRendered {
+                payload["baseline"] = json!(baseline);
+            }
+            self.scenarios
+                .append_audit_event(
+                    run_id,
+                    None,
+                    event.event_type,
+                    payload.to_string(),
+                    query.actor_type.clone(),
+                    query.actor_id.clone(),
+                    query.correlation_id.clone(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Policy input for the quote as it stands: effective line discount against its list value, and
+/// the margin those discounted lines earn over their product costs.
+fn baseline_policy_input(
+    quote: &Quote,
+    requested_discount_pct: Option<Decimal>,
+    unit_costs: &BTreeMap<String, Decimal>,
+) -> PolicyInput {
+    let mut subtotal = Decimal::ZERO;
+    let mut discount_total = Decimal::ZERO;
+    let mut margin_lines = Vec::with_capacity(quote.lines.len());
+    for (index, line) in quote.lines.iter().enumerate() {
+        let line_subtotal = line.unit_price * Decimal::from(line.quantity);
+        let line_discount_pct = requested_discount_pct.unwrap_or_else(|| {
+            Decimal::from_f64(line.discount_pct.clamp(0.0, 100.0)).unwrap_or(Decimal::ZERO)
+        });
+        let line_discount = line_subtotal * line_discount_pct / Decimal::from(100);
+        subtotal += line_subtotal;
+        discount_total += line_discount;
+        margin_lines.push(MarginLine {
+            line_id: format!("{}-ql-{}", quote.id.0, index + 1),
+            product_id: line.product_id.0.clone(),
+            quantity: line.quantity,
+            net_amount: line_subtotal - line_discount,
+        });
+    }
+    let effective_discount_pct = requested_discount_pct.unwrap_or_else(|| {
+        if subtotal > Decimal::ZERO {
+            (discount_total * Decimal::from(100) / subtotal).round_dp(4)
+        } else {
+            Decimal::ZERO
+        }
+    });
+    PolicyInput {
+        requested_discount_pct: effective_discount_pct,
+        deal_value: subtotal,
+        minimum_margin_pct: QuoteMargin::compute(&margin_lines, unit_costs).margin_pct,
+    }
+}
+
+fn actor_type(raw: &str) -> ActorType {
+    match raw {
+        "user" => ActorType::User,
+        "system" => ActorType::System,
+        _ => ActorType::Agent,
+    }
+}
+
+fn encode<T: Serialize>(value: &T, what: &str) -> Result<String, SimulationError> {
+    serde_json::to_string(value)
+        .map_err(|error| SimulationError::Failed(format!("encode {what}: {error}")))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(raw: &str, what: &str) -> Result<T, SimulationError> {
+    serde_json::from_str(raw)
+        .map_err(|error| RepositoryError::Decode(format!("stored {what}: {error}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::cpq::policy::PolicyThresholds;
+    use quotey_core::cpq::simulator::{LineVariation, ScenarioVariation};
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use quotey_core::domain::simulation::{ScenarioAuditEventType, ScenarioRunId};
+    use rust_decimal::Decimal;
+
+    use super::{PromoteQuery, SimulateQuery, SimulationError, SimulationService};
+    use crate::repositories::{
+        QuoteRepository, ScenarioRepository, SqlAuditEventRepository, SqlQuoteRepository,
+        SqlScenarioRepository,
+    };
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    type TestResult<T> = Result<T, String>;
+
+    #[tokio::test]
+    async fn simulate_persists_ranked_variants_and_promotion_creates_revision() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-SIM-1", QuoteStatus::Priced).await?;
+        let service = SimulationService::new(pool.clone(), PolicyThresholds::default());
+
+        let run = service
+            .simulate(query(
+                &quote_id,
+                vec![
+                    variation("more-seats", 10, None),
+                    ScenarioVariation {
+                        requested_discount_pct_override: Some(Decimal::new(35, 0)),
+                        ..variation("deep-discount", 0, Some(Decimal::new(80, 0)))
+                    },
+                ],
+            ))
+            .await
+            .map_err(|error| format!("simulate: {error}"))?;
+        assert_eq!(run.status, "success");
+        assert_eq!(run.base_quote_version, 1);
+        assert_eq!(run.baseline.total, Decimal::new(1000, 0));
+        assert_eq!(run.variants.len(), 2);
+        assert_eq!(run.variants[0].variant_key, "more-seats");
+        assert_eq!(run.variants[0].outcome.total, Decimal::new(2000, 0));
+        assert_eq!(run.variants[0].delta.price.total_delta, Decimal::new(1000, 0));
+        assert!(run.variants[1].outcome.approval_required);
+
+        let loaded = service.load(&run.run_id).await.map_err(|error| format!("load: {error}"))?;
+        assert_eq!(loaded.baseline, run.baseline);
+        assert_eq!(loaded.variants, run.variants);
+
+        let promotion = service
+            .promote(promote(&quote_id, &run.run_id, "MORE-SEATS"))
+            .await
+            .map_err(|error| format!("promote: {error}"))?;
+        assert_eq!((promotion.previous_version, promotion.version), (1, 2));
+        assert_eq!(promotion.status, "revised");
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&quote_id)
+            .await
+            .map_err(|error| format!("load quote: {error}"))?
+            .ok_or("quote should exist")?;
+        assert_eq!(quote.version, 2);
+        assert_eq!(quote.lines[0].quantity, 20);
+
+        let scenarios = SqlScenarioRepository::new(pool.clone());
+        let audit = scenarios
+            .list_audit_for_run(&ScenarioRunId(run.run_id.clone()))
+            .await
+            .map_err(|error| format!("scenario audit: {error}"))?;
+        assert_eq!(
+            audit.last().map(|event| event.event_type.clone()),
+            Some(ScenarioAuditEventType::PromotionApplied)
+        );
+        let quote_audit = SqlAuditEventRepository::new(pool.clone())
+            .find_by_quote_id(&quote_id)
+            .await
+            .map_err(|error| format!("quote audit: {error}"))?;
+        let promoted = quote_audit
+            .iter()
+            .find(|event| event.event_type == "quote.simulation_promoted")
+            .ok_or("promotion should be audited on the quote")?;
+        assert_eq!(promoted.metadata.get("scenario_run_id"), Some(&run.run_id));
+        assert_eq!(promoted.metadata.get("scenario_variant_id"), Some(&promotion.variant_id));
+
+        let again = service.promote(promote(&quote_id, &run.run_id, "deep-discount")).await;
+        assert!(matches!(again, Err(SimulationError::NotPromotable { .. })));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn simulate_records_guardrail_failures_and_rejects_stale_promotions() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let quote_id = insert_quote(&pool, "Q-SIM-2", QuoteStatus::Draft).await?;
+        let service = SimulationService::new(pool.clone(), PolicyThresholds::default());
+
+        let underflow =
+            service.simulate(query(&quote_id, vec![variation("drop", -50, None)])).await;
+        let Err(SimulationError::Guardrail { run_id: Some(run_id), .. }) = underflow else {
+            return Err(format!("expected persisted guardrail failure, got {underflow:?}"));
+        };
+        let failed = service.load(&run_id).await;
+        assert!(matches!(failed, Err(SimulationError::Failed(_))));
+        let run = SqlScenarioRepository::new(pool.clone())
+            .get_run(&ScenarioRunId(run_id))
+            .await
+            .map_err(|error| format!("get run: {error}"))?
+            .ok_or("failed run should be stored")?;
+        assert_eq!(run.status.as_str(), "failed");
+
+        let empty = service.simulate(query(&quote_id, Vec::new())).await;
+        assert!(matches!(empty, Err(SimulationError::Guardrail { run_id: None, .. })));
+
+        let first = service
+            .simulate(query(&quote_id, vec![variation("plus-one", 1, None)]))
+            .await
+            .map_err(|error| format!("simulate first: {error}"))?;
+        let second = service
+            .simulate(query(&quote_id, vec![variation("plus-two", 2, None)]))
+            .await
+            .map_err(|error| format!("simulate second: {error}"))?;
+        service
+            .promote(promote(&quote_id, &second.run_id, "plus-two"))
+            .await
+            .map_err(|error| format!("promote second: {error}"))?;
+
+        let stale = service.promote(promote(&quote_id, &first.run_id, "plus-one")).await;
+        assert!(matches!(stale, Err(SimulationError::StaleRun { expected: 1, current: 2 })));
+        let unknown = service.promote(promote(&quote_id, "sim-run-missing", "plus-one")).await;
+        assert!(matches!(unknown, Err(SimulationError::RunNotFound(_))));
+
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn promote_refuses_accepted_and_rejected_quotes() -> TestResult<()> {
+        let pool = setup_pool().await?;
+        let service = SimulationService::new(pool.clone(), PolicyThresholds::default());
+
+        for (id, status) in
+            [("Q-SIM-ACCEPTED", QuoteStatus::Accepted), ("Q-SIM-REJECTED", QuoteStatus::Rejected)]
+        {
+            let quote_id = insert_quote(&pool, id, status.clone()).await?;
+            let run = service
+                .simulate(query(&quote_id, vec![variation("plus-one", 1, None)]))
+                .await
+                .map_err(|error| format!("simulate {id}: {error}"))?;
+
+            let promoted = service.promote(promote(&quote_id, &run.run_id, "plus-one")).await;
+            assert!(matches!(promoted, Err(SimulationError::QuoteClosed(_))), "{id}: {promoted:?}");
+
+            let quote = SqlQuoteRepository::new(pool.clone())
+                .find_by_id(&quote_id)
+                .await
+                .map_err(|error| format!("load {id}: {error}"))?
+                .ok_or("quote should still exist")?;
+            assert_eq!(quote.status, status);
+            assert_eq!(quote.version, 1);
+        }
+
+        pool.close().await;
+        Ok(())
+    }
+
+    fn variation(key: &str, quantity_delta: i32, unit_price: Option<Decimal>) -> ScenarioVariation {
+        ScenarioVariation {
+            variant_key: key.to_string(),
+            line_variations: vec![LineVariation {
+                product_id: ProductId("plan-pro".to_string()),
+                quantity_delta,
+                unit_price_override: unit_price,
+            }],
+            requested_discount_pct_override: None,
+            minimum_margin_pct_override: None,
+            deal_value_override: None,
+        }
+    }
+
+    fn query(quote_id: &QuoteId, variations: Vec<ScenarioVariation>) -> SimulateQuery {
+        SimulateQuery {
+            quote_id: quote_id.clone(),
+            variations,
+            requested_discount_pct: None,
+            actor_type: "agent".to_string(),
+            actor_id: "mcp".to_string(),
+            thread_id: "T-SIM".to_string(),
+            correlation_id: "corr-sim".to_string(),
+        }
+    }
+
+    fn promote(quote_id: &QuoteId, run_id: &str, variant: &str) -> PromoteQuery {
+        PromoteQuery {
+            quote_id: quote_id.clone(),
+            run_id: run_id.to_string(),
+            variant: variant.to_string(),
+            actor_type: "user".to_string(),
+            actor_id: "U-SIM".to_string(),
+            correlation_id: "corr-sim-promote".to_string(),
+        }
+    }
+
+    async fn setup_pool() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30)
+            .await
+            .map_err(|error| format!("connect test pool: {error}"))?;
+        migrations::run_pending(&pool).await.map_err(|error| format!("run migrations: {error}"))?;
+        Ok(pool)
+    }
+
+    async fn insert_quote(pool: &DbPool, id: &str, status: QuoteStatus) -> TestResult<QuoteId> {
+        let now = Utc::now();
+        let quote = Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status,
+            account_id: Some("acct-sim".to_string()),
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: Some(12),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: "U-SIM".to_string(),
+            lines: vec![QuoteLine {
+                product_id: ProductId("plan-pro".to_string()),
+                quantity: 10,
+                unit_price: Decimal::new(100, 0),
+                discount_pct: 0.0,
+                notes: None,
+            }],
+            created_at: now,
+            updated_at: now,
+        };
+        SqlQuoteRepository::new(pool.clone())
+            .save(quote.clone())
+            .await
+            .map_err(|error| format!("save quote fixture {id}: {error}"))?;
+        Ok(quote.id)
+    }
+}
@@ -0,0 +1,604 @@
+//! Catalog- and history-backed data for the product [`SuggestionEngine`].
+//!
+//! Candidates come from [`ProductRepository`], peers and purchase history from the accounts on
+//! quotes, acceptance rates from `suggestion_feedback`, and product relationships from quote
+//! co-occurrence. Co-occurrence counts live in the `product_cooccurrence_*` tables and are
+//! maintained incrementally: a refresh only re-reads quotes whose lines changed since they were
+//! last counted and applies the basket difference, the same remove/add delta
+//! [`CoOccurrenceStats`] applies in memory.
+
+use std::collections::{BTreeSet, HashMap};
+
+use quotey_core::chrono::{DateTime, NaiveDate, Utc};
+use quotey_core::domain::product::Product;
+use quotey_core::suggestions::{
+    CoOccurrenceStats, CustomerProfile, MiningThresholds, ProductAcceptanceRate, ProductInfo,
+    ProductPurchase, SuggestionEngine, SuggestionSnapshot,
+};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
+use sqlx::{Row, Sqlite, Transaction};
+use thiserror::Error;
+
+use crate::repositories::{ProductRepository, RepositoryError, SqlProductRepository};
+use crate::DbPool;
+
+/// Upper bound on catalog products loaded as suggestion candidates.
+pub const CATALOG_LIMIT: u32 = 5_000;
+/// Profile value used when an account has no recorded segment or billing country.
+const UNKNOWN: &str = "unknown";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RelationshipRefreshReport {
+    /// Quotes compared against their stored basket.
+    pub scanned: usize,
+    /// Quotes whose basket was (re)counted because their lines changed.
+    pub recounted: usize,
+    /// Baskets subtracted because their quote no longer exists.
+    pub removed: usize,
+    /// Non-empty baskets counted after the refresh.
+    pub baskets: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum SuggestionDataError {
+    #[error("stored basket for quote `{quote_id}` is corrupt: {reason}")]
+    CorruptBasket { quote_id: String, reason: String },
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for SuggestionDataError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct SuggestionDataService {
+    pool: DbPool,
+    thresholds: MiningThresholds,
+}
+
+impl SuggestionDataService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool, thresholds: MiningThresholds::default() }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: MiningThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Refreshes co-occurrence counts and loads an engine scoring against the live catalog.
+    pub async fn engine(&self) -> Result<SuggestionEngine, SuggestionDataError> {
+        self.refresh_relationships().await?;
+        Ok(SuggestionEngine::from_snapshot(self.snapshot().await?))
+    }
+
+    /// Brings the co-occurrence counts up to date with the current quotes.
+    pub async fn refresh_relationships(
+        &self,
+    ) -> Result<RelationshipRefreshReport, SuggestionDataError> {
+        let mut report = RelationshipRefreshReport::default();
+        let rows = sqlx::query(
+            "SELECT q.id, q.account_id, q.created_at, q.updated_at,
+                    COUNT(ql.id) AS line_count, MAX(ql.updated_at) AS lines_updated_at,
+                    b.source_marker
+             FROM quote q
+             LEFT JOIN quote_line ql ON ql.quote_id = q.id
+             LEFT JOIN product_cooccurrence_basket b ON b.quote_id = q.id
+             GROUP BY q.id
+             ORDER BY q.id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        for row in rows {
+            report.scanned += 1;
+            let quote_id: String = row.try_get("id")?;
+            let account_id: Option<String> = row.try_get("account_id")?;
+            let quoted_at: String = row.try_get("created_at")?;
+            let updated_at: String = row.try_get("updated_at")?;
+            let line_count: i64 = row.try_get("line_count")?;
+            let lines_updated_at: Option<String> = row.try_get("lines_updated_at")?;
+            let stored_marker: Option<String> = row.try_get("source_marker")?;
+
+            let marker = format!(
+                "{updated_at}|{line_count}|{}|{}",
+                lines_updated_at.unwrap_or_default(),
+                account_id.as_deref().unwrap_or_default()
+            );
+            if stored_marker.as_deref() == Some(marker.as_str()) {
+                continue;
+            }
+
+            let previous = stored_basket(&mut tx, &quote_id).await?;
+            let current: BTreeSet<String> = sqlx::query_scalar(
+                "SELECT DISTINCT product_id FROM quote_line WHERE quote_id = ? ORDER BY product_id",
+            )
+            .bind(&quote_id)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+
+            apply_basket_delta(&mut tx, &previous, &current).await?;
+            let product_ids_json = serde_json::to_string(&current).map_err(|error| {
+                SuggestionDataError::CorruptBasket {
+                    quote_id: quote_id.clone(),
+                    reason: error.to_string(),
+                }
+            })?;
+            sqlx::query(
+                "INSERT INTO product_cooccurrence_basket
+                     (quote_id, account_id, product_ids_json, source_marker, quoted_at, indexed_at)
+                 VALUES (?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(quote_id) DO UPDATE SET
+                     account_id = excluded.account_id,
+                     product_ids_json = excluded.product_ids_json,
+                     source_marker = excluded.source_marker,
+                     quoted_at = excluded.quoted_at,
+                     indexed_at = excluded.indexed_at",
+            )
+            .bind(&quote_id)
+            .bind(account_id.filter(|account| !account.trim().is_empty()))
+            .bind(&product_ids_json)
+            .bind(&marker)
+            .bind(&quoted_at)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+            report.recounted += 1;
+        }
+
+        let orphaned: Vec<String> = sqlx::query_scalar(
+            "SELECT quote_id FROM product_cooccurrence_basket
+             WHERE quote_id NOT IN (SELECT id FROM quote)",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for quote_id in orphaned {
+            let previous = stored_basket(&mut tx, &quote_id).await?;
+            apply_basket_delta(&mut tx, &previous, &BTreeSet::new()).await?;
+            sqlx::query("DELETE FROM product_cooccurrence_basket WHERE quote_id = ?")
+                .bind(&quote_id)
+                .execute(&mut *tx)
+                .await?;
+            report.removed += 1;
+        }
+
+        report.baskets = basket_count(&mut tx).await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Co-occurrence counts as of the last refresh.
+    pub async fn relationship_stats(&self) -> Result<CoOccurrenceStats, SuggestionDataError> {
+        let mut conn = self.pool.acquire().await?;
+        let baskets: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM product_cooccurrence_basket WHERE product_ids_json <> '[]'",
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let items: Vec<(String, i64)> =
+            sqlx::query_as("SELECT product_id, basket_count FROM product_cooccurrence_item")
+                .fetch_all(&mut *conn)
+                .await?;
+        let pairs: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT product_a, product_b, pair_count FROM product_cooccurrence_pair",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(CoOccurrenceStats::from_counts(
+            count_u32(baskets),
+            items.into_iter().map(|(product, count)| (product, count_u32(count))).collect(),
+            pairs.into_iter().map(|(a, b, count)| ((a, b), count_u32(count))),
+        ))
+    }
+
+    /// Catalog, accounts, purchase history, mined relationships and feedback as of the last
+    /// refresh.
+    pub async fn snapshot(&self) -> Result<SuggestionSnapshot, SuggestionDataError> {
+        let products = SqlProductRepository::new(self.pool.clone())
+            .search("", true, CATALOG_LIMIT)
+            .await?
+            .into_iter()
+            .map(product_info)
+            .collect();
+        let relationships = self.relationship_stats().await?.relationships(&self.thresholds);
+
+        Ok(SuggestionSnapshot {
+            products,
+            customers: self.customer_profiles().await?,
+            purchases: self.purchases().await?,
+            relationships,
+            acceptance: self.acceptance_rates().await?,
+        })
+    }
+
+    /// One profile per account seen on a quote. Segment comes from the account's most recent
+    /// recorded deal outcome, region from its billing country, deal size from its quote totals.
+    async fn customer_profiles(&self) -> Result<Vec<CustomerProfile>, SuggestionDataError> {
+        let rows = sqlx::query(
+            "SELECT q.account_id,
+                    MAX(q.billing_country) AS billing_country,
+                    AVG(COALESCE(t.total, 0)) AS avg_total
+             FROM quote q
+             LEFT JOIN (
+                 SELECT quote_id,
+                        SUM(COALESCE(CAST(subtotal AS REAL),
+                                     CAST(unit_price AS REAL) * quantity, 0)) AS total
+                 FROM quote_line GROUP BY quote_id
+             ) t ON t.quote_id = q.id
+             WHERE q.account_id IS NOT NULL AND TRIM(q.account_id) <> ''
+             GROUP BY q.account_id
+             ORDER BY q.account_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let segments: Vec<(String, String)> = sqlx::query_as(
+            "SELECT q.account_id, o.customer_segment
+             FROM deal_outcomes o
+             JOIN quote q ON q.id = o.quote_id
+             WHERE q.account_id IS NOT NULL AND o.customer_segment IS NOT NULL
+             ORDER BY o.close_date DESC, o.created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut latest_segment = HashMap::new();
+        for (account_id, segment) in segments {
+            latest_segment.entry(account_id).or_insert_with(|| normalize_segment(&segment));
+        }
+
+        rows.into_iter()
+            .map(|row| {
+                let account_id: String = row.try_get("account_id")?;
+                let billing_country: Option<String> = row.try_get("billing_country")?;
+                let avg_total: Option<f64> = row.try_get("avg_total")?;
+                Ok(CustomerProfile {
+                    segment: latest_segment
+                        .get(&account_id)
+                        .cloned()
+                        .unwrap_or_else(|| UNKNOWN.to_owned()),
+                    industry: None,
+                    employee_count: None,
+                    region: billing_country
+                        .map(|country| country.trim().to_ascii_lowercase())
+                        .filter(|country| !country.is_empty())
+                        .unwrap_or_else(|| UNKNOWN.to_owned()),
+                    avg_deal_size: avg_total.unwrap_or(0.0),
+                    id: account_id,
+                })
+            })
+            .collect()
+    }
+
+    async fn purchases(&self) -> Result<Vec<ProductPurchase>, SuggestionDataError> {
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT quote_id, account_id, product_ids_json, quoted_at
+             FROM product_cooccurrence_basket
+             WHERE account_id IS NOT NULL AND product_ids_json <> '[]'
+             ORDER BY quoted_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut purchases = Vec::new();
+        for (quote_id, account_id, product_ids_json, quoted_at) in rows {
+            let Some(purchased_at) = parse_timestamp(&quoted_at) else {
+                continue;
+            };
+            for product_id in parse_basket(&quote_id, &product_ids_json)? {
+                purchases.push(ProductPurchase {
+                    customer_id: account_id.clone(),
+                    product_id,
+                    quote_id: quote_id.clone(),
+                    purchased_at,
+                });
+            }
+        }
+        Ok(purchases)
+    }
+
+    async fn acceptance_rates(
+        &self,
+    ) -> Result<HashMap<String, ProductAcceptanceRate>, SuggestionDataError> {
+        let rows: Vec<(String, i64, i64, i64)> = sqlx::query_as(
+            "SELECT product_id,
+                    COUNT(*) AS shown_count,
+                    COALESCE(SUM(was_clicked), 0) AS clicked_count,
+                    COALESCE(SUM(was_added_to_quote), 0) AS added_count
+             FROM suggestion_feedback
+             WHERE was_shown = 1
+             GROUP BY product_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|(_, shown, _, _)| *shown > 0)
+            .map(|(product_id, shown, clicked, added)| {
+                let (shown, clicked, added) =
+                    (count_u32(shown), count_u32(clicked), count_u32(added));
+                let rate = ProductAcceptanceRate {
+                    shown_count: shown,
+                    clicked_count: clicked,
+                    added_count: added,
+                    click_rate: f64::from(clicked) / f64::from(shown),
+                    add_rate: f64::from(added) / f64::from(shown),
+                };
+                (product_id, rate)
+            })
+            .collect())
+    }
+}
+
+fn product_info(product: Product) -> ProductInfo {
+    ProductInfo {
+        category: product
+            .family_id
+            .map(|family| family.0)
+            .unwrap_or_else(|| product.product_type.as_str().to_owned()),
+        unit_price: product.base_price.and_then(|price| price.to_f64()).unwrap_or(0.0),
+        id: product.id.0,
+        sku: product.sku,
+        name: product.name,
+        active: product.active,
+    }
+}
+
+/// `Mid-Market` and `mid market` both become `mid_market`, matching the engine's segments.
+fn normalize_segment(raw: &str) -> String {
+    raw.trim().to_ascii_lowercase().replace(['-', ' '], "_")
+}
+
+fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw).map(|date| date.with_timezone(&Utc)).ok().or_else(|| {
+        let date = NaiveDate::parse_from_str(raw.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+    })
+}
+
+fn parse_basket(quote_id: &str, raw: &str) -> Result<BTreeSet<String>, SuggestionDataError> {
+    serde_json::from_str(raw).map_err(|error| SuggestionDataError::CorruptBasket {
+        quote_id: quote_id.to_owned(),
+        reason: error.to_string(),
+    })
+}
+
+fn count_u32(value: i64) -> u32 {
+    u32::try_from(value.max(0)).unwrap_or(u32::MAX)
+}
+
+async fn stored_basket(
+    tx: &mut Transaction<'_, Sqlite>,
+    quote_id: &str,
+) -> Result<BTreeSet<String>, SuggestionDataError> {
+    let raw: Option<String> = sqlx::query_scalar(
+        "SELECT product_ids_json FROM product_cooccurrence_basket WHERE quote_id = ?",
+    )
+    .bind(quote_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    match raw {
+        Some(raw) => parse_basket(quote_id, &raw),
+        None => Ok(BTreeSet::new()),
+    }
+}
+
+async fn basket_count(tx: &mut Transaction<'_, Sqlite>) -> Result<u32, SuggestionDataError> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM product_cooccurrence_basket WHERE product_ids_json <> '[]'",
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(count_u32(count))
+}
+
+/// Moves the item and pair counts from `previous` to `current`, touching only what changed.
+async fn apply_basket_delta(
+    tx: &mut Transaction<'_, Sqlite>,
+    previous: &BTreeSet<String>,
+    current: &BTreeSet<String>,
+) -> Result<(), SuggestionDataError> {
+    if previous == current {
+        return Ok(());
+    }
+
+    for product in current.difference(previous) {
+        sqlx::query(
+            "INSERT INTO product_cooccurrence_item (product_id, basket_count) VALUES (?, 1)
+             ON CONFLICT(product_id) DO UPDATE SET basket_count = basket_count + 1",
+        )
+        .bind(product)
+        .execute(&mut **tx)
+        .await?;
+    }
+    for product in previous.difference(current) {
+        sqlx::query(
+            "UPDATE product_cooccurrence_item SET basket_count = basket_count - 1
+             WHERE product_id = ? AND basket_count > 0",
+        )
+        .bind(product)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    let previous_pairs = basket_pairs(previous);
+    let current_pairs = basket_pairs(current);
+    for (a, b) in current_pairs.difference(&previous_pairs) {
+        sqlx::query(
+            "INSERT INTO product_cooccurrence_pair (product_a, product_b, pair_count)
+             VALUES (?, ?, 1)
+             ON CONFLICT(product_a, product_b) DO UPDATE SET pair_count = pair_count + 1",
+        )
+        .bind(a)
+        .bind(b)
+        .execute(&mut **tx)
+        .await?;
+    }
+    for (a, b) in previous_pairs.difference(&current_pairs) {
+        sqlx::query(
+            "UPDATE product_cooccurrence_pair SET pair_count = pair_count - 1
+             WHERE product_a = ? AND product_b = ? AND pair_count > 0",
+        )
+        .bind(a)
+        .bind(b)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    sqlx::query("DELETE FROM product_cooccurrence_item WHERE basket_count = 0")
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query("DELETE FROM product_cooccurrence_pair WHERE pair_count = 0")
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Unordered pairs as `(a, b)` with `a < b`; the set is already sorted.
+fn basket_pairs(basket: &BTreeSet<String>) -> BTreeSet<(&str, &str)> {
+    let items: Vec<&str> = basket.iter().map(String::as_str).collect();
+    let mut pairs = BTreeSet::new();
+    for (index, a) in items.iter().enumerate() {
+        for b in &items[index + 1..] {
+            pairs.insert((*a, *b));
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use quotey_core::suggestions::{CoOccurrenceStats, SuggestionRequest};
+    use rust_decimal::Decimal;
+
+    use super::SuggestionDataService;
+    use crate::repositories::{QuoteRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        for (id, name, price, active) in [
+            ("plan-pro", "Pro Plan", "12000", 1),
+            ("addon-sso", "SSO Add-on", "2400", 1),
+            ("support-premium", "Premium Support", "6500", 1),
+            ("legacy-connector", "Legacy Connector", "900", 0),
+        ] {
+            sqlx::query(
+                "INSERT INTO product (id, sku, name, base_price, active, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            )
+            .bind(id)
+            .bind(id.to_ascii_uppercase())
+            .bind(name)
+            .bind(price)
+            .bind(active)
+            .execute(&pool)
+            .await
+            .expect("product");
+        }
+        pool
+    }
+
+    async fn save_quote(pool: &DbPool, id: &str, account: &str, products: &[&str]) {
+        let now = Utc::now();
+        let quote = Quote {
+            id: QuoteId(id.to_owned()),
+            version: 1,
+            status: QuoteStatus::Sent,
+            account_id: Some(account.to_owned()),
+            deal_id: None,
+            currency: "USD".to_owned(),
+            term_months: Some(12),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: "rep".to_owned(),
+            lines: products
+                .iter()
+                .map(|product| QuoteLine {
+                    product_id: ProductId((*product).to_owned()),
+                    quantity: 1,
+                    unit_price: Decimal::new(1_000, 0),
+                    discount_pct: 0.0,
+                    notes: None,
+                })
+                .collect(),
+            created_at: now,
+            updated_at: now,
+        };
+        SqlQuoteRepository::new(pool.clone()).save(quote).await.expect("save quote");
+    }
+
+    #[tokio::test]
+    async fn refresh_counts_only_changed_quotes_and_matches_a_full_recount() {
+        let pool = setup().await;
+        save_quote(&pool, "Q-1", "acme", &["plan-pro", "addon-sso"]).await;
+        save_quote(&pool, "Q-2", "acme", &["plan-pro", "addon-sso"]).await;
+        save_quote(&pool, "Q-3", "globex", &["plan-pro", "support-premium"]).await;
+
+        let service = SuggestionDataService::new(pool.clone());
+        let first = service.refresh_relationships().await.expect("first refresh");
+        assert_eq!((first.scanned, first.recounted, first.baskets), (3, 3, 3));
+
+        let unchanged = service.refresh_relationships().await.expect("no-op refresh");
+        assert_eq!((unchanged.recounted, unchanged.removed), (0, 0));
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        save_quote(&pool, "Q-3", "globex", &["plan-pro", "addon-sso"]).await;
+        sqlx::query("DELETE FROM quote WHERE id = 'Q-1'").execute(&pool).await.expect("delete");
+        let delta = service.refresh_relationships().await.expect("delta refresh");
+        assert_eq!((delta.recounted, delta.removed, delta.baskets), (1, 1, 2));
+
+        let mut expected = CoOccurrenceStats::new();
+        expected.add_basket(&["plan-pro", "addon-sso"]);
+        expected.add_basket(&["plan-pro", "addon-sso"]);
+        assert_eq!(service.relationship_stats().await.expect("stats"), expected);
+    }
+
+    #[tokio::test]
+    async fn engine_suggests_catalog_products_bought_by_similar_accounts() {
+        let pool = setup().await;
+        for (id, account) in [("Q-1", "acme"), ("Q-2", "acme"), ("Q-3", "initech")] {
+            save_quote(&pool, id, account, &["plan-pro", "addon-sso", "legacy-connector"]).await;
+        }
+        sqlx::query(
+            "INSERT INTO suggestion_feedback
+                 (id, request_id, customer_id, product_id, product_sku, score, confidence,
+                  category, suggested_at, was_shown, was_clicked, was_added_to_quote)
+             VALUES ('F-1', 'R-1', 'acme', 'addon-sso', 'ADDON-SSO', 0.7, 'Medium', 'Bundle',
+                     '2026-01-01', 1, 1, 1)",
+        )
+        .execute(&pool)
+        .await
+        .expect("feedback");
+
+        let service = SuggestionDataService::new(pool);
+        let snapshot = service.snapshot().await.expect("snapshot before refresh");
+        assert!(snapshot.relationships.is_empty(), "nothing is counted until a refresh");
+        assert_eq!(snapshot.products.len(), 3, "inactive catalog products are not candidates");
+        assert_eq!(snapshot.acceptance["addon-sso"].added_count, 1);
+
+        let engine = service.engine().await.expect("engine");
+        let suggestions = engine
+            .get_suggestions(
+                SuggestionRequest::new("initech").with_current_products(vec!["plan-pro".into()]),
+            )
+            .await
+            .expect("suggestions");
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.product_id.as_str()).collect();
+        assert_eq!(ids, vec!["addon-sso"]);
+        assert!((suggestions[0].component_scores.product_relationship - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,916 @@
+//! Webhook subscriptions, event fan-out and the delivery log.
+//!
+//! Publishing an event queues one `webhook.call` execution task per active subscription whose
+//! filter matches, alongside a `webhook_delivery` row tying the task to the subscription. The
+//! server's webhook worker signs and sends those tasks, reporting each HTTP attempt back through
+//! [`WebhookService::record_attempt`]; a delivery that exhausts its retries is copied to
+//! `outbox_dead_letter`. Redelivery queues a fresh task carrying the original event body, so
+//! subscribers can deduplicate on the event id.
+
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use quotey_core::chrono::{DateTime, Utc};
+use quotey_core::domain::execution::{ExecutionTask, ExecutionTaskState};
+use quotey_core::domain::outbox::OutboxOperation;
+use quotey_core::domain::quote::QuoteId;
+use quotey_core::webhooks::{
+    check_address, generate_secret, target_host, validate_url, EventFilter, TargetPolicy,
+    WebhookError, WebhookEvent, DELIVERY_ID_HEADER, EVENT_ID_HEADER, EVENT_TYPE_HEADER,
+};
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row, Sqlite, Transaction};
+use thiserror::Error;
+
+use crate::ids::short_id;
+use crate::repositories::RepositoryError;
+use crate::DbPool;
+
+const MAX_RESPONSE_EXCERPT_CHARS: usize = 512;
+/// Longest wait for DNS when checking where a webhook URL points.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Row in `webhook_subscription`. The secret is never read back after creation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+    pub created_by: String,
+}
+
+/// A new subscription and its signing secret, shown to the caller once.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CreatedWebhookSubscription {
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Retrying,
+    Delivered,
+    DeadLettered,
+    Cancelled,
+}
+
+impl DeliveryStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Retrying => "retrying",
+            Self::Delivered => "delivered",
+            Self::DeadLettered => "dead_lettered",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse_label(label: &str) -> Option<Self> {
+        [Self::Pending, Self::Retrying, Self::Delivered, Self::DeadLettered, Self::Cancelled]
+            .into_iter()
+            .find(|status| status.as_str() == label.trim())
+    }
+}
+
+/// Row in `webhook_delivery`: one event sent (or being sent) to one subscription.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub quote_id: String,
+    pub task_id: String,
+    pub redelivery_of: Option<String>,
+    pub status: DeliveryStatus,
+    pub attempt_count: u32,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One HTTP attempt, as reported by the worker.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WebhookDeliveryAttempt {
+    pub attempt: u32,
+    pub status_code: Option<u16>,
+    pub response_excerpt: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// A delivery with every attempt made for it, oldest first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WebhookDeliveryLog {
+    pub delivery: WebhookDelivery,
+    pub attempts: Vec<WebhookDeliveryAttempt>,
+}
+
+/// What the worker needs to sign and send a queued delivery.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeliveryTarget {
+    pub delivery: WebhookDelivery,
+    pub secret: String,
+    pub subscription_active: bool,
+}
+
+/// Filters for [`WebhookService::list_deliveries`], which returns newest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeliveryQuery {
+    pub subscription_id: String,
+    pub status: Option<DeliveryStatus>,
+    /// Keyset position `(created_at, id)` of the last row already seen.
+    pub before: Option<(String, String)>,
+    pub limit: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookServiceError {
+    #[error("webhook subscription `{0}` not found")]
+    SubscriptionNotFound(String),
+    #[error("webhook subscription `{0}` is disabled")]
+    SubscriptionDisabled(String),
+    #[error("webhook delivery `{0}` not found")]
+    DeliveryNotFound(String),
+    #[error(transparent)]
+    Invalid(#[from] WebhookError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for WebhookServiceError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+/// Resolves the host of `url` and refuses it when any address is not public or the lookup
+/// fails.
+pub async fn check_resolved_target(url: &str, policy: TargetPolicy) -> Result<(), WebhookError> {
+    if policy == TargetPolicy::AllowPrivate {
+        return Ok(());
+    }
+    let (host, port) = target_host(url.trim())?;
+    resolve_public(&host, port).await.map(|_| ())
+}
+
+/// Addresses of `host`, provided every one of them is public. Lookup errors, timeouts and
+/// empty answers are refused rather than waved through.
+pub async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>, WebhookError> {
+    let name = host.to_string();
+    let lookup = tokio::task::spawn_blocking(move || {
+        (name.as_str(), port).to_socket_addrs().map(|addrs| addrs.collect::<Vec<_>>())
+    });
+    let addrs = match tokio::time::timeout(RESOLVE_TIMEOUT, lookup).await {
+        Ok(Ok(Ok(addrs))) if !addrs.is_empty() => addrs,
+        _ => return Err(WebhookError::UnresolvedTarget(host.to_string())),
+    };
+    addrs.iter().try_for_each(|addr| check_address(addr.ip()))?;
+    Ok(addrs)
+}
+
+pub struct WebhookService {
+    pool: DbPool,
+    target_policy: TargetPolicy,
+}
+
+impl WebhookService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool, target_policy: TargetPolicy::default() }
+    }
+
+    /// Overrides which hosts new subscriptions may point at; tests use this for loopback
+    /// receivers.
+    pub fn with_target_policy(mut self, target_policy: TargetPolicy) -> Self {
+        self.target_policy = target_policy;
+        self
+    }
+
+    pub async fn subscribe(
+        &self,
+        new: NewWebhookSubscription,
+    ) -> Result<CreatedWebhookSubscription, WebhookServiceError> {
+        let url = validate_url(&new.url, self.target_policy)?;
+        check_resolved_target(&url, self.target_policy).await?;
+        let filter = EventFilter::parse(&new.events)?;
+        let now = Utc::now();
+        let subscription = WebhookSubscription {
+            id: format!("WHS-{}", short_id()),
+            url,
+            events: filter.patterns().to_vec(),
+            description: new
+                .description
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty()),
+            active: true,
+            created_by: new.created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        let secret = generate_secret();
+        sqlx::query(
+            "INSERT INTO webhook_subscription
+                (id, url, secret, events_json, description, active, created_by, created_at,
+                 updated_at)
+             VALUES (?, ?, ?, ?, ?, 1, ?, ?, ?)",
+        )
+        .bind(&subscription.id)
+        .bind(&subscription.url)
+        .bind(&secret)
+        .bind(encode_json(&subscription.events)?)
+        .bind(&subscription.description)
+        .bind(&subscription.created_by)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(CreatedWebhookSubscription { subscription, secret })
+    }
+
+    /// Subscriptions, newest first.
+    pub async fn list_subscriptions(
+        &self,
+        include_disabled: bool,
+    ) -> Result<Vec<WebhookSubscription>, WebhookServiceError> {
+        let rows = sqlx::query(
+            "SELECT id, url, events_json, description, active, created_by, created_at, updated_at
+             FROM webhook_subscription
+             WHERE active = 1 OR ?
+             ORDER BY created_at DESC, id DESC",
+        )
+        .bind(include_disabled)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(subscription_from_row).collect()
+    }
+
+    pub async fn subscription(&self, id: &str) -> Result<WebhookSubscription, WebhookServiceError> {
+        let row = sqlx::query(
+            "SELECT id, url, events_json, description, active, created_by, created_at, updated_at
+             FROM webhook_subscription WHERE id = ?",
+        )
+        .bind(id.trim())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| WebhookServiceError::SubscriptionNotFound(id.trim().to_string()))?;
+        subscription_from_row(&row)
+    }
+
+    /// Stops new deliveries to a subscription. Its delivery log is kept, and tasks already
+    /// queued for it are cancelled when the worker picks them up.
+    pub async fn disable(&self, id: &str) -> Result<WebhookSubscription, WebhookServiceError> {
+        let updated =
+            sqlx::query("UPDATE webhook_subscription SET active = 0, updated_at = ? WHERE id = ?")
+                .bind(Utc::now().to_rfc3339())
+                .bind(id.trim())
+                .execute(&self.pool)
+                .await?;
+        if updated.rows_affected() == 0 {
+            return Err(WebhookServiceError::SubscriptionNotFound(id.trim().to_string()));
+        }
+        self.subscription(id).await
+    }
+
+    /// Queues `event` for every active subscription whose filter matches it.
+    pub async fn publish(
+        &self,
+        event: &WebhookEvent,
+    ) -> Result<Vec<WebhookDelivery>, WebhookServiceError> {
+        let body = encode_json(event)?;
+        let mut tx = self.pool.begin().await?;
+        let subscriptions: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT id, url, events_json FROM webhook_subscription
+             WHERE active = 1
+             ORDER BY created_at ASC, id ASC",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut deliveries = Vec::new();
+        for (subscription_id, url, events_json) in subscriptions {
+            let patterns: Vec<String> = decode_json(&events_json)?;
+            let matches = EventFilter::parse(&patterns)
+                .map(|filter| filter.matches(event.event_type))
+                .unwrap_or(false);
+            if !matches {
+                continue;
+            }
+            let target = QueuedDelivery {
+                subscription_id: &subscription_id,
+                url: &url,
+                event_id: &event.id,
+                event_type: event.event_type.as_str(),
+                quote_id: &event.quote_id,
+                body: &body,
+                redelivery_of: None,
+            };
+            deliveries.push(queue_delivery(&mut tx, target).await?);
+        }
+        tx.commit().await?;
+        Ok(deliveries)
+    }
+
+    pub async fn list_deliveries(
+        &self,
+        query: &DeliveryQuery,
+    ) -> Result<Vec<WebhookDelivery>, WebhookServiceError> {
+        let mut sql = format!(
+            "SELECT {DELIVERY_COLUMNS} FROM webhook_delivery d WHERE d.subscription_id = ?"
+        );
+        if query.status.is_some() {
+            sql.push_str(" AND d.status = ?");
+        }
+        if query.before.is_some() {
+            sql.push_str(" AND (d.created_at < ? OR (d.created_at = ? AND d.id < ?))");
+        }
+        sql.push_str(" ORDER BY d.created_at DESC, d.id DESC LIMIT ?");
+
+        let mut rows = sqlx::query(&sql).bind(query.subscription_id.trim());
+        if let Some(status) = query.status {
+            rows = rows.bind(status.as_str());
+        }
+        if let Some((created_at, id)) = &query.before {
+            rows = rows.bind(created_at).bind(created_at).bind(id);
+        }
+        let rows = rows.bind(i64::from(query.limit)).fetch_all(&self.pool).await?;
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    pub async fn delivery_log(&self, id: &str) -> Result<WebhookDeliveryLog, WebhookServiceError> {
+        let delivery = self.delivery(id).await?;
+        let rows = sqlx::query(
+            "SELECT attempt, status_code, response_excerpt, error, duration_ms, attempted_at
+             FROM webhook_delivery_attempt
+             WHERE delivery_id = ?
+             ORDER BY attempt ASC",
+        )
+        .bind(&delivery.id)
+        .fetch_all(&self.pool)
+        .await?;
+        let attempts = rows
+            .iter()
+            .map(|row| {
+                Ok(WebhookDeliveryAttempt {
+                    attempt: u32::try_from(row.try_get::<i64, _>("attempt")?).unwrap_or(1),
+                    status_code: status_code(row.try_get("status_code")?),
+                    response_excerpt: row.try_get("response_excerpt")?,
+                    error: row.try_get("error")?,
+                    duration_ms: u64::try_from(row.try_get::<i64, _>("duration_ms")?)
+                        .unwrap_or_default(),
+                    attempted_at: parse_timestamp(&row.try_get::<String, _>("attempted_at")?)?,
+                })
+            })
+            .collect::<Result<_, WebhookServiceError>>()?;
+        Ok(WebhookDeliveryLog { delivery, attempts })
+    }
+
+    /// Queues the event of delivery `id` again for its subscription, resolving any
+    /// dead-letter entry the original left behind.
+    pub async fn redeliver(
+        &self,
+        id: &str,
+        actor: &str,
+    ) -> Result<WebhookDelivery, WebhookServiceError> {
+        let original = self.delivery(id).await?;
+        let mut tx = self.pool.begin().await?;
+        let (url, active): (String, bool) =
+            sqlx::query_as("SELECT url, active FROM webhook_subscription WHERE id = ?")
+                .bind(&original.subscription_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if !active {
+            return Err(WebhookServiceError::SubscriptionDisabled(original.subscription_id));
+        }
+        let (payload_json, idempotency_key): (String, String) = sqlx::query_as(
+            "SELECT payload_json, idempotency_key FROM execution_queue_task WHERE id = ?",
+        )
+        .bind(&original.task_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let OutboxOperation::WebhookCall { body, .. } = decode_json(&payload_json)? else {
+            return Err(RepositoryError::Decode(format!(
+                "task `{}` is not a webhook call",
+                original.task_id
+            ))
+            .into());
+        };
+
+        let target = QueuedDelivery {
+            subscription_id: &original.subscription_id,
+            url: &url,
+            event_id: &original.event_id,
+            event_type: &original.event_type,
+            quote_id: &original.quote_id,
+            body: &body,
+            redelivery_of: Some(&original.id),
+        };
+        let delivery = queue_delivery(&mut tx, target).await?;
+        sqlx::query(
+            "UPDATE outbox_dead_letter
+             SET resolution_status = 'redelivered', resolved_by = ?, resolved_at = ?,
+                 resolution_notes = ?
+             WHERE idempotency_key = ? AND resolution_status = 'pending'",
+        )
+        .bind(actor)
+        .bind(Utc::now().to_rfc3339())
+        .bind(format!("redelivered as {}", delivery.id))
+        .bind(&idempotency_key)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(delivery)
+    }
+
+    /// Delivery, secret and subscription state for a queued `webhook.call` task, or `None`
+    /// when the task was not queued by [`Self::publish`] or [`Self::redeliver`].
+    pub async fn target_for_task(
+        &self,
+        task_id: &str,
+    ) -> Result<Option<DeliveryTarget>, WebhookServiceError> {
+        let row = sqlx::query(&format!(
+            "SELECT {DELIVERY_COLUMNS}, s.secret, s.active
+             FROM webhook_delivery d
+             JOIN webhook_subscription s ON s.id = d.subscription_id
+             WHERE d.task_id = ?"
+        ))
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| {
+            Ok(DeliveryTarget {
+                delivery: delivery_from_row(&row)?,
+                secret: row.try_get("secret")?,
+                subscription_active: row.try_get("active")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Logs one HTTP attempt and moves the delivery to match `task`, which the worker has
+    /// already transitioned. A terminal failure copies the task to `outbox_dead_letter`.
+    pub async fn record_attempt(
+        &self,
+        delivery_id: &str,
+        attempt: &WebhookDeliveryAttempt,
+        task: &ExecutionTask,
+    ) -> Result<WebhookDelivery, WebhookServiceError> {
+        let status = match task.state {
+            ExecutionTaskState::Completed => DeliveryStatus::Delivered,
+            ExecutionTaskState::FailedTerminal => DeliveryStatus::DeadLettered,
+            _ => DeliveryStatus::Retrying,
+        };
+        let excerpt = attempt
+            .response_excerpt
+            .as_deref()
+            .map(|text| text.chars().take(MAX_RESPONSE_EXCERPT_CHARS).collect::<String>());
+        let attempted_at = attempt.attempted_at.to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO webhook_delivery_attempt
+                (id, delivery_id, attempt, status_code, response_excerpt, error, duration_ms,
+                 attempted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(format!("WHA-{}", short_id()))
+        .bind(delivery_id)
+        .bind(i64::from(attempt.attempt))
+        .bind(attempt.status_code.map(i64::from))
+        .bind(&excerpt)
+        .bind(&attempt.error)
+        .bind(i64::try_from(attempt.duration_ms).unwrap_or(i64::MAX))
+        .bind(&attempted_at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE webhook_delivery
+             SET status = ?, attempt_count = ?, last_status_code = ?, last_error = ?,
+                 last_attempt_at = ?,
+                 delivered_at = CASE WHEN ? = 'delivered' THEN ? ELSE delivered_at END
+             WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(i64::from(attempt.attempt))
+        .bind(attempt.status_code.map(i64::from))
+        .bind(&attempt.error)
+        .bind(&attempted_at)
+        .bind(status.as_str())
+        .bind(&attempted_at)
+        .bind(delivery_id)
+        .execute(&mut *tx)
+        .await?;
+        if status == DeliveryStatus::DeadLettered {
+            dead_letter(&mut tx, task).await?;
+        }
+        tx.commit().await?;
+        self.delivery(delivery_id).await
+    }
+
+    /// Marks a delivery whose subscription was disabled before it could be sent.
+    pub async fn cancel(&self, delivery_id: &str) -> Result<WebhookDelivery, WebhookServiceError> {
+        sqlx::query(
+            "UPDATE webhook_delivery SET status = 'cancelled', last_error = ?
+             WHERE id = ? AND status IN ('pending', 'retrying')",
+        )
+        .bind("subscription disabled")
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await?;
+        self.delivery(delivery_id).await
+    }
+
+    pub async fn delivery(&self, id: &str) -> Result<WebhookDelivery, WebhookServiceError> {
+        let row = sqlx::query(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM webhook_delivery d WHERE d.id = ?"
+        ))
+        .bind(id.trim())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| WebhookServiceError::DeliveryNotFound(id.trim().to_string()))?;
+        delivery_from_row(&row)
+    }
+}
+
+const DELIVERY_COLUMNS: &str = "d.id, d.subscription_id, d.event_id, d.event_type, d.quote_id, \
+     d.task_id, d.redelivery_of, d.status, d.attempt_count, d.last_status_code, d.last_error, \
+     d.last_attempt_at, d.delivered_at, d.created_at";
+
+struct QueuedDelivery<'a> {
+    subscription_id: &'a str,
+    url: &'a str,
+    event_id: &'a str,
+    event_type: &'a str,
+    quote_id: &'a str,
+    body: &'a str,
+    redelivery_of: Option<&'a str>,
+}
+
+/// Inserts the `webhook.call` task and its delivery row. The delivery id is part of the
+/// payload, so every delivery (redeliveries included) gets its own idempotency key.
+async fn queue_delivery(
+    tx: &mut Transaction<'_, Sqlite>,
+    target: QueuedDelivery<'_>,
+) -> Result<WebhookDelivery, WebhookServiceError> {
+    let now = Utc::now();
+    let delivery_id = format!("WHD-{}", short_id());
+    let task_id = format!("webhook-{}", sqlx::types::Uuid::new_v4().simple());
+    let operation = OutboxOperation::WebhookCall {
+        url: target.url.to_string(),
+        method: "POST".to_string(),
+        headers: HashMap::from([
+            ("Content-Type".to_string(), "application/json".to_string()),
+            (EVENT_TYPE_HEADER.to_string(), target.event_type.to_string()),
+            (EVENT_ID_HEADER.to_string(), target.event_id.to_string()),
+            (DELIVERY_ID_HEADER.to_string(), delivery_id.clone()),
+        ]),
+        body: target.body.to_string(),
+    };
+    sqlx::query(
+        "INSERT INTO execution_queue_task
+            (id, quote_id, operation_kind, payload_json, idempotency_key, state,
+             retry_count, max_retries, available_at, state_version, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, 'queued', 0, ?, ?, 1, ?, ?)",
+    )
+    .bind(&task_id)
+    .bind(target.quote_id)
+    .bind(operation.kind())
+    .bind(encode_json(&operation)?)
+    .bind(operation.idempotency_key(&QuoteId(target.quote_id.to_string())).0)
+    .bind(i64::from(operation.retry_policy().max_retries))
+    .bind(now.to_rfc3339())
+    .bind(now.to_rfc3339())
+    .bind(now.to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        "INSERT INTO webhook_delivery
+            (id, subscription_id, event_id, event_type, quote_id, task_id, redelivery_of,
+             status, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, 'pending', ?)",
+    )
+    .bind(&delivery_id)
+    .bind(target.subscription_id)
+    .bind(target.event_id)
+    .bind(target.event_type)
+    .bind(target.quote_id)
+    .bind(&task_id)
+    .bind(target.redelivery_of)
+    .bind(now.to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(WebhookDelivery {
+        id: delivery_id,
+        subscription_id: target.subscription_id.to_string(),
+        event_id: target.event_id.to_string(),
+        event_type: target.event_type.to_string(),
+        quote_id: target.quote_id.to_string(),
+        task_id,
+        redelivery_of: target.redelivery_of.map(str::to_string),
+        status: DeliveryStatus::Pending,
+        attempt_count: 0,
+        last_status_code: None,
+        last_error: None,
+        last_attempt_at: None,
+        delivered_at: None,
+        created_at: now,
+    })
+}
+
+async fn dead_letter(
+    tx: &mut Transaction<'_, Sqlite>,
+    task: &ExecutionTask,
+) -> Result<(), WebhookServiceError> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO outbox_dead_letter
+            (id, quote_id, operation_kind, payload_json, idempotency_key, failed_at,
+             failure_reason, error_class, retry_count, max_retries, original_created_at,
+             created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, 'webhook_delivery_failed', ?, ?, ?, ?)
+         ON CONFLICT(idempotency_key) DO NOTHING",
+    )
+    .bind(format!("DL-{}", short_id()))
+    .bind(&task.quote_id.0)
+    .bind(&task.operation_kind)
+    .bind(&task.payload_json)
+    .bind(&task.idempotency_key.0)
+    .bind(&now)
+    .bind(task.last_error.as_deref().unwrap_or("delivery failed"))
+    .bind(i64::from(task.retry_count))
+    .bind(i64::from(task.max_retries))
+    .bind(task.created_at.to_rfc3339())
+    .bind(&now)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn subscription_from_row(row: &SqliteRow) -> Result<WebhookSubscription, WebhookServiceError> {
+    Ok(WebhookSubscription {
+        id: row.try_get("id")?,
+        url: row.try_get("url")?,
+        events: decode_json(&row.try_get::<String, _>("events_json")?)?,
+        description: row.try_get("description")?,
+        active: row.try_get("active")?,
+        created_by: row.try_get("created_by")?,
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+        updated_at: parse_timestamp(&row.try_get::<String, _>("updated_at")?)?,
+    })
+}
+
+fn delivery_from_row(row: &SqliteRow) -> Result<WebhookDelivery, WebhookServiceError> {
+    let status: String = row.try_get("status")?;
+    let optional_timestamp = |column: &str| -> Result<_, WebhookServiceError> {
+        row.try_get::<Option<String>, _>(column)?.as_deref().map(parse_timestamp).transpose()
+    };
+    Ok(WebhookDelivery {
+        id: row.try_get("id")?,
+        subscription_id: row.try_get("subscription_id")?,
+        event_id: row.try_get("event_id")?,
+        event_type: row.try_get("event_type")?,
+        quote_id: row.try_get("quote_id")?,
+        task_id: row.try_get("task_id")?,
+        redelivery_of: row.try_get("redelivery_of")?,
+        status: DeliveryStatus::parse_label(&status).ok_or_else(|| {
+            RepositoryError::Decode(format!("unknown webhook delivery status `{status}`"))
+        })?,
+        attempt_count: u32::try_from(row.try_get::<i64, _>("attempt_count")?).unwrap_or_default(),
+        last_status_code: status_code(row.try_get("last_status_code")?),
+        last_error: row.try_get("last_error")?,
+        last_attempt_at: optional_timestamp("last_attempt_at")?,
+        delivered_at: optional_timestamp("delivered_at")?,
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+    })
+}
+
+fn status_code(value: Option<i64>) -> Option<u16> {
+    value.and_then(|code| u16::try_from(code).ok())
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, WebhookServiceError> {
+    DateTime::parse_from_rfc3339(value).map(|at| at.with_timezone(&Utc)).map_err(|error| {
+        RepositoryError::Decode(format!("invalid timestamp `{value}`: {error}")).into()
+    })
+}
+
+fn encode_json<T: Serialize>(value: &T) -> Result<String, WebhookServiceError> {
+    serde_json::to_string(value).map_err(|error| {
+        RepositoryError::Decode(format!("cannot encode webhook data: {error}")).into()
+    })
+}
+
+fn decode_json<T: serde::de::DeserializeOwned>(raw: &str) -> Result<T, WebhookServiceError> {
+    serde_json::from_str(raw)
+        .map_err(|error| RepositoryError::Decode(format!("invalid webhook data: {error}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::domain::execution::ExecutionTaskId;
+    use quotey_core::webhooks::WebhookEventType;
+    use serde_json::json;
+
+    use super::*;
+    use crate::repositories::{ExecutionQueueRepository, SqlExecutionQueueRepository};
+    use crate::{connect_with_settings, migrations};
+
+    type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+    async fn setup() -> TestResult<DbPool> {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await?;
+        migrations::run_pending(&pool).await?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
+             VALUES ('Q-HOOK', 'finalized', 'USD', 'rep', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await?;
+        Ok(pool)
+    }
+
+    fn subscription(url: &str, events: &[&str]) -> NewWebhookSubscription {
+        NewWebhookSubscription {
+            url: url.to_string(),
+            events: events.iter().map(|event| event.to_string()).collect(),
+            description: Some("  ".to_string()),
+            created_by: "api-key:test".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn published_events_fan_out_to_matching_active_subscriptions() -> TestResult<()> {
+        let pool = setup().await?;
+        let service = WebhookService::new(pool.clone());
+        let quotes =
+            service.subscribe(subscription("https://203.0.113.1/hook", &["quote.*"])).await?;
+        let approvals = service
+            .subscribe(subscription("https://203.0.113.2/hook", &["approval.decided"]))
+            .await?;
+        let disabled = service.subscribe(subscription("https://203.0.113.3/hook", &["*"])).await?;
+        service.disable(&disabled.subscription.id).await?;
+        assert!(quotes.secret.starts_with("whsec_"));
+        assert_eq!(quotes.subscription.description, None);
+        assert_eq!(service.list_subscriptions(false).await?.len(), 2);
+        assert_eq!(service.list_subscriptions(true).await?.len(), 3);
+
+        let event =
+            WebhookEvent::new(WebhookEventType::QuoteFinalized, "Q-HOOK", json!({ "version": 2 }));
+        let deliveries = service.publish(&event).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].subscription_id, quotes.subscription.id);
+
+        let (kind, payload): (String, String) = sqlx::query_as(
+            "SELECT operation_kind, payload_json FROM execution_queue_task WHERE id = ?",
+        )
+        .bind(&deliveries[0].task_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(kind, "webhook.call");
+        let OutboxOperation::WebhookCall { url, headers, body, .. } =
+            serde_json::from_str(&payload)?
+        else {
+            panic!("expected a webhook call");
+        };
+        assert_eq!(url, "https://203.0.113.1/hook");
+        assert_eq!(headers[DELIVERY_ID_HEADER], deliveries[0].id);
+        assert_eq!(serde_json::from_str::<WebhookEvent>(&body)?, event);
+
+        let approval = WebhookEvent::new(WebhookEventType::ApprovalDecided, "Q-HOOK", json!({}));
+        let deliveries = service.publish(&approval).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].subscription_id, approvals.subscription.id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn exhausted_deliveries_are_dead_lettered_and_redelivered_with_the_same_event(
+    ) -> TestResult<()> {
+        let pool = setup().await?;
+        let service = WebhookService::new(pool.clone());
+        let created = service.subscribe(subscription("https://203.0.113.1/hook", &["*"])).await?;
+        let event = WebhookEvent::new(WebhookEventType::QuoteAccepted, "Q-HOOK", json!({}));
+        let delivery = service.publish(&event).await?.remove(0);
+
+        let queue = SqlExecutionQueueRepository::new(pool.clone());
+        let mut task =
+            queue.find_task_by_id(&ExecutionTaskId(delivery.task_id.clone())).await?.unwrap();
+        task.state = ExecutionTaskState::FailedTerminal;
+        task.last_error = Some("HTTP 500".to_string());
+        let attempt = WebhookDeliveryAttempt {
+            attempt: 9,
+            status_code: Some(500),
+            response_excerpt: Some("x".repeat(2000)),
+            error: Some("HTTP 500".to_string()),
+            duration_ms: 12,
+            attempted_at: Utc::now(),
+        };
+        let failed = service.record_attempt(&delivery.id, &attempt, &task).await?;
+        assert_eq!(failed.status, DeliveryStatus::DeadLettered);
+        assert_eq!(failed.last_status_code, Some(500));
+
+        let log = service.delivery_log(&delivery.id).await?;
+        assert_eq!(log.attempts.len(), 1);
+        assert_eq!(log.attempts[0].response_excerpt.as_deref().map(str::len), Some(512));
+        let pending: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM outbox_dead_letter WHERE resolution_status = 'pending'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(pending, 1);
+
+        let again = service.redeliver(&delivery.id, "api-key:test").await?;
+        assert_eq!(again.redelivery_of.as_deref(), Some(delivery.id.as_str()));
+        assert_eq!(again.event_id, event.id);
+        assert_ne!(again.task_id, delivery.task_id);
+        let resolution: String =
+            sqlx::query_scalar("SELECT resolution_status FROM outbox_dead_letter")
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(resolution, "redelivered");
+
+        let listed = service
+            .list_deliveries(&DeliveryQuery {
+                subscription_id: created.subscription.id.clone(),
+                limit: 10,
+                ..DeliveryQuery::default()
+            })
+            .await?;
+        assert_eq!(listed.len(), 2);
+        let dead = service
+            .list_deliveries(&DeliveryQuery {
+                subscription_id: created.subscription.id.clone(),
+                status: Some(DeliveryStatus::DeadLettered),
+                limit: 10,
+                ..DeliveryQuery::default()
+            })
+            .await?;
+        assert_eq!(dead, vec![failed]);
+
+        service.disable(&created.subscription.id).await?;
+        assert!(matches!(
+            service.redeliver(&delivery.id, "api-key:test").await,
+            Err(WebhookServiceError::SubscriptionDisabled(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn subscriptions_reject_bad_urls_and_unknown_events() -> TestResult<()> {
+        let service = WebhookService::new(setup().await?);
+        assert!(matches!(
+            service.subscribe(subscription("ftp://a.example", &["*"])).await,
+            Err(WebhookServiceError::Invalid(WebhookError::InvalidUrl(_)))
+        ));
+        assert!(matches!(
+            service.subscribe(subscription("https://203.0.113.1", &["quote.deleted"])).await,
+            Err(WebhookServiceError::Invalid(WebhookError::UnknownEvent(_)))
+        ));
+        for url in ["http://localhost:8080/hook", "http://169.254.169.254/", "http://2130706433/"] {
+            assert!(
+                matches!(
+                    service.subscribe(subscription(url, &["*"])).await,
+                    Err(WebhookServiceError::Invalid(WebhookError::BlockedTarget(_)))
+                ),
+                "{url}"
+            );
+        }
+        // A host that does not resolve cannot be vetted, so it is refused.
+        assert!(matches!(
+            service.subscribe(subscription("https://hooks.invalid/hook", &["*"])).await,
+            Err(WebhookServiceError::Invalid(WebhookError::UnresolvedTarget(_)))
+        ));
+        assert!(service
+            .with_target_policy(TargetPolicy::AllowPrivate)
+            .subscribe(subscription("http://127.0.0.1:9000/hook", &["*"]))
+            .await
+            .is_ok());
+        let service = WebhookService::new(setup().await?);
+        assert!(matches!(
+            service.disable("WHS-missing").await,
+            Err(WebhookServiceError::SubscriptionNotFound(_))
+        ));
+        Ok(())
+    }
+}
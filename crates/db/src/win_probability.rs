@@ -0,0 +1,650 @@
+//! Win-probability training and serving on top of recorded deal outcomes.
+//!
+//! Training builds [`QuoteFeatures`] for every quote with a row in `deal_outcomes` (catalog
+//! families from `product`, line discounts, term, the outcome's customer segment and the owning
+//! rep), splits off the most recently closed deals as a holdout and stores the fitted model as a
+//! `candidate` version in `win_probability_models`. Exactly one version is `active` at a time;
+//! promotion records the version it replaced so a rollback can restore it. Predictions from the
+//! active version are cached in `prediction_cache` per quote, keyed by a hash of the features so
+//! an edited quote is re-scored.
+
+use std::collections::HashMap;
+
+use quotey_core::chrono::{DateTime, NaiveDate, Utc};
+use quotey_core::domain::product::{ProductFamilyId, ProductId};
+use quotey_core::domain::quote::{Quote, QuoteId};
+use quotey_core::ml::{
+    DealOutcome, ProductFamilies, QuoteFeatures, TrainingConfig, TrainingReport,
+    WinProbabilityModel,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, Row};
+use thiserror::Error;
+
+use crate::repositories::{QuoteRepository, RepositoryError, SqlQuoteRepository};
+use crate::DbPool;
+
+/// Row in `win_probability_models`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ModelVersionRecord {
+    pub version: String,
+    /// `candidate`, `active` or `retired`.
+    pub status: String,
+    pub training_date: String,
+    /// Holdout accuracy.
+    pub accuracy_score: f64,
+    /// Holdout Brier score.
+    pub brier_score: Option<f64>,
+    pub training_samples: i64,
+    pub holdout_samples: i64,
+    pub promoted_at: Option<String>,
+    /// Version that was active when this one was last promoted; the rollback target.
+    pub previous_version: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TrainingRun {
+    pub model: ModelVersionRecord,
+    pub report: TrainingReport,
+    /// Outcome rows left out because their quote is gone or the close date is unreadable.
+    pub skipped: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WinPrediction {
+    pub quote_id: String,
+    pub model_version: String,
+    /// 0.0..=1.0
+    pub win_probability: f64,
+    pub predicted_at: String,
+}
+
+#[derive(Debug, Error)]
+pub enum WinProbabilityError {
+    #[error("cannot train win-probability model: {0}")]
+    Training(String),
+    #[error("model version `{0}` not found")]
+    VersionNotFound(String),
+    #[error("no win-probability model is active")]
+    NoActiveModel,
+    #[error("active model `{0}` has no previous version to roll back to")]
+    NoPreviousVersion(String),
+    #[error("stored model `{version}` is unreadable: {reason}")]
+    CorruptModel { version: String, reason: String },
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl From<sqlx::Error> for WinProbabilityError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Repository(error.into())
+    }
+}
+
+pub struct WinProbabilityService {
+    pool: DbPool,
+}
+
+impl WinProbabilityService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Features and labels for every quote with a recorded outcome (latest outcome per quote).
+    pub async fn training_set(&self) -> Result<(Vec<DealOutcome>, usize), WinProbabilityError> {
+        let families = self.product_families().await?;
+        let quotes = SqlQuoteRepository::new(self.pool.clone());
+        let rows = sqlx::query(
+            "SELECT o.quote_id, o.outcome, o.final_price, o.close_date, o.customer_segment,
+                    COALESCE(q.created_by_sales_rep_id, q.created_by) AS sales_rep_id
+             FROM deal_outcomes o
+             JOIN quote q ON q.id = o.quote_id
+             WHERE o.id = (
+                 SELECT latest.id FROM deal_outcomes latest
+                 WHERE latest.quote_id = o.quote_id
+                 ORDER BY latest.close_date DESC, latest.created_at DESC, latest.id DESC
+                 LIMIT 1
+             )
+             ORDER BY o.close_date, o.quote_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut outcomes = Vec::with_capacity(rows.len());
+        let mut skipped = 0;
+        for row in rows {
+            let quote_id: String = row.try_get("quote_id")?;
+            let close_date: String = row.try_get("close_date")?;
+            let (Some(quote), Some(close_date)) = (
+                quotes.find_by_id(&QuoteId(quote_id.clone())).await?,
+                parse_close_date(&close_date),
+            ) else {
+                skipped += 1;
+                continue;
+            };
+            let segment: Option<String> = row.try_get("customer_segment")?;
+            let rep: Option<String> = row.try_get("sales_rep_id")?;
+            let outcome: String = row.try_get("outcome")?;
+            outcomes.push(DealOutcome {
+                quote_id,
+                features: QuoteFeatures::from_quote(&quote, &families)
+                    .with_customer_segment(segment.as_deref())
+                    .with_sales_rep(rep.as_deref()),
+                outcome: outcome == "won",
+                final_price: Decimal::from_f64(row.try_get("final_price")?).unwrap_or_default(),
+                close_date,
+            });
+        }
+        Ok((outcomes, skipped))
+    }
+
+    /// Trains a new version on recorded outcomes and stores it as a `candidate`.
+    pub async fn train(&self, config: &TrainingConfig) -> Result<TrainingRun, WinProbabilityError> {
+        let (outcomes, skipped) = self.training_set().await?;
+        let version = self.next_version().await?;
+        let mut model = WinProbabilityModel::new(version.clone());
+        let report =
+            model.train_with_holdout(&outcomes, config).map_err(WinProbabilityError::Training)?;
+
+        let model_json = serde_json::to_string(&model)
+            .map_err(|error| RepositoryError::Decode(error.to_string()))?;
+        let metrics_json = serde_json::to_string(&report)
+            .map_err(|error| RepositoryError::Decode(error.to_string()))?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO win_probability_models
+                (id, version, training_date, accuracy_score, model_path, created_at, status,
+                 model_json, metrics_json, training_samples, holdout_samples, brier_score)
+             VALUES (?, ?, ?, ?, ?, ?, 'candidate', ?, ?, ?, ?, ?)",
+        )
+        .bind(format!("WPM-{version}"))
+        .bind(&version)
+        .bind(model.trained_at.to_rfc3339())
+        .bind(model.accuracy)
+        .bind(format!("db:win_probability_models/{version}"))
+        .bind(&now)
+        .bind(model_json)
+        .bind(metrics_json)
+        .bind(report.training_samples as i64)
+        .bind(report.holdout_samples as i64)
+        .bind(report.calibration.brier_score)
+        .execute(&self.pool)
+        .await?;
+
+        let model = self
+            .find_version(&version)
+            .await?
+            .ok_or(WinProbabilityError::VersionNotFound(version))?;
+        Ok(TrainingRun { model, report, skipped })
+    }
+
+    /// All versions, newest first.
+    pub async fn list_versions(&self) -> Result<Vec<ModelVersionRecord>, WinProbabilityError> {
+        let rows = sqlx::query(&format!("{MODEL_SELECT} ORDER BY created_at DESC, version DESC"))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(model_from_row).collect()
+    }
+
+    pub async fn find_version(
+        &self,
+        version: &str,
+    ) -> Result<Option<ModelVersionRecord>, WinProbabilityError> {
+        let row = sqlx::query(&format!("{MODEL_SELECT} WHERE version = ?"))
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(model_from_row).transpose()
+    }
+
+    pub async fn active_version(&self) -> Result<Option<ModelVersionRecord>, WinProbabilityError> {
+        let row = sqlx::query(&format!("{MODEL_SELECT} WHERE status = 'active'"))
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(model_from_row).transpose()
+    }
+
+    /// Makes `version` the serving model, retiring the current one. Promoting the active
+    /// version again is a no-op.
+    pub async fn promote(&self, version: &str) -> Result<ModelVersionRecord, WinProbabilityError> {
+        let mut tx = self.pool.begin().await?;
+        let status: Option<String> =
+            sqlx::query_scalar("SELECT status FROM win_probability_models WHERE version = ?")
+                .bind(version)
+                .fetch_optional(&mut *tx)
+                .await?;
+        match status.as_deref() {
+            None => return Err(WinProbabilityError::VersionNotFound(version.to_string())),
+            Some("active") => {}
+            Some(_) => {
+                let current: Option<String> = sqlx::query_scalar(
+                    "SELECT version FROM win_probability_models WHERE status = 'active'",
+                )
+                .fetch_optional(&mut *tx)
+                .await?;
+                sqlx::query(
+                    "UPDATE win_probability_models SET status = 'retired' WHERE status = 'active'",
+                )
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(
+                    "UPDATE win_probability_models
+                     SET status = 'active', promoted_at = ?, previous_version = ?
+                     WHERE version = ?",
+                )
+                .bind(Utc::now().to_rfc3339())
+                .bind(current)
+                .bind(version)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+
+        self.find_version(version)
+            .await?
+            .ok_or_else(|| WinProbabilityError::VersionNotFound(version.to_string()))
+    }
+
+    /// Retires the active version and reinstates the one it replaced.
+    pub async fn rollback(&self) -> Result<ModelVersionRecord, WinProbabilityError> {
+        let mut tx = self.pool.begin().await?;
+        let active: Option<(String, Option<String>)> = sqlx::query_as(
+            "SELECT version, previous_version FROM win_probability_models WHERE status = 'active'",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (active, previous) = active.ok_or(WinProbabilityError::NoActiveModel)?;
+        let previous = previous.ok_or(WinProbabilityError::NoPreviousVersion(active.clone()))?;
+
+        sqlx::query("UPDATE win_probability_models SET status = 'retired' WHERE version = ?")
+            .bind(&active)
+            .execute(&mut *tx)
+            .await?;
+        let restored = sqlx::query(
+            "UPDATE win_probability_models SET status = 'active', promoted_at = ? WHERE version = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(&previous)
+        .execute(&mut *tx)
+        .await?;
+        if restored.rows_affected() == 0 {
+            return Err(WinProbabilityError::VersionNotFound(previous));
+        }
+        tx.commit().await?;
+
+        self.find_version(&previous).await?.ok_or(WinProbabilityError::VersionNotFound(previous))
+    }
+
+    /// Scores `quote` with the active model, or returns `None` when no model is active.
+    pub async fn predict(
+        &self,
+        quote: &Quote,
+    ) -> Result<Option<WinPrediction>, WinProbabilityError> {
+        let active: Option<(String, String)> = sqlx::query_as(
+            "SELECT version, model_json FROM win_probability_models WHERE status = 'active'",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((version, model_json)) = active else {
+            return Ok(None);
+        };
+
+        let features = self.features_for(quote).await?;
+        let feature_hash = features.cache_key();
+        let cached: Option<(f64, String)> = sqlx::query_as(
+            "SELECT win_probability, predicted_at FROM prediction_cache
+             WHERE quote_id = ? AND model_version = ? AND feature_hash = ?",
+        )
+        .bind(&quote.id.0)
+        .bind(&version)
+        .bind(&feature_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some((win_probability, predicted_at)) = cached {
+            return Ok(Some(WinPrediction {
+                quote_id: quote.id.0.clone(),
+                model_version: version,
+                win_probability,
+                predicted_at,
+            }));
+        }
+
+        let model: WinProbabilityModel = serde_json::from_str(&model_json).map_err(|error| {
+            WinProbabilityError::CorruptModel {
+                version: version.clone(),
+                reason: error.to_string(),
+            }
+        })?;
+        let win_probability = model.predict_from_features(&features).clamp(0.0, 1.0);
+        let predicted_at = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO prediction_cache
+                (id, quote_id, model_version, win_probability, predicted_at, feature_hash)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(quote_id, model_version) DO UPDATE SET
+                win_probability = excluded.win_probability,
+                predicted_at = excluded.predicted_at,
+                feature_hash = excluded.feature_hash",
+        )
+        .bind(format!("PC-{}-{version}", quote.id.0))
+        .bind(&quote.id.0)
+        .bind(&version)
+        .bind(win_probability)
+        .bind(&predicted_at)
+        .bind(&feature_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Some(WinPrediction {
+            quote_id: quote.id.0.clone(),
+            model_version: version,
+            win_probability,
+            predicted_at,
+        }))
+    }
+
+    /// Features for an open quote. The segment comes from the quote's own outcome if it has
+    /// one, otherwise from the latest outcome recorded for the same account.
+    pub async fn features_for(&self, quote: &Quote) -> Result<QuoteFeatures, WinProbabilityError> {
+        let families = self.product_families().await?;
+        let segment: Option<String> = sqlx::query_scalar(
+            "SELECT o.customer_segment
+             FROM deal_outcomes o
+             JOIN quote q ON q.id = o.quote_id
+             WHERE o.customer_segment IS NOT NULL
+               AND (o.quote_id = ? OR (? IS NOT NULL AND q.account_id = ?))
+             ORDER BY o.quote_id = ? DESC, o.close_date DESC, o.created_at DESC
+             LIMIT 1",
+        )
+        .bind(&quote.id.0)
+        .bind(quote.account_id.as_deref())
+        .bind(quote.account_id.as_deref())
+        .bind(&quote.id.0)
+        .fetch_optional(&self.pool)
+        .await?;
+        let rep: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT COALESCE(created_by_sales_rep_id, created_by) FROM quote WHERE id = ?",
+        )
+        .bind(&quote.id.0)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let features =
+            QuoteFeatures::from_quote(quote, &families).with_customer_segment(segment.as_deref());
+        Ok(match rep.flatten() {
+            Some(rep) => features.with_sales_rep(Some(&rep)),
+            None => features,
+        })
+    }
+
+    async fn product_families(&self) -> Result<ProductFamilies, WinProbabilityError> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, family_id FROM product WHERE family_id IS NOT NULL")
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(product, family)| (ProductId(product), ProductFamilyId(family)))
+            .collect::<HashMap<_, _>>())
+    }
+
+    /// `v<n>`, one past the highest numbered version stored so far.
+    async fn next_version(&self) -> Result<String, WinProbabilityError> {
+        let versions: Vec<String> =
+            sqlx::query_scalar("SELECT version FROM win_probability_models")
+                .fetch_all(&self.pool)
+                .await?;
+        let latest = versions
+            .iter()
+            .filter_map(|version| version.strip_prefix('v')?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        Ok(format!("v{}", latest + 1))
+    }
+}
+
+const MODEL_SELECT: &str = "SELECT version, status, training_date, accuracy_score, brier_score,
+        training_samples, holdout_samples, promoted_at, previous_version, created_at
+     FROM win_probability_models";
+
+fn model_from_row(row: &SqliteRow) -> Result<ModelVersionRecord, WinProbabilityError> {
+    Ok(ModelVersionRecord {
+        version: row.try_get("version")?,
+        status: row.try_get("status")?,
+        training_date: row.try_get("training_date")?,
+        accuracy_score: row.try_get("accuracy_score")?,
+        brier_score: row.try_get("brier_score")?,
+        training_samples: row.try_get("training_samples")?,
+        holdout_samples: row.try_get("holdout_samples")?,
+        promoted_at: row.try_get("promoted_at")?,
+        previous_version: row.try_get("previous_version")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Accepts RFC 3339 timestamps and bare `YYYY-MM-DD` dates.
+fn parse_close_date(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw).map(|date| date.with_timezone(&Utc)).ok().or_else(|| {
+        let date = NaiveDate::parse_from_str(raw.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_core::chrono::Utc;
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use quotey_core::ml::TrainingConfig;
+    use rust_decimal::Decimal;
+
+    use super::{parse_close_date, WinProbabilityError, WinProbabilityService};
+    use crate::repositories::{QuoteRepository, SqlQuoteRepository};
+    use crate::{connect_with_settings, migrations, DbPool};
+
+    async fn setup() -> DbPool {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        for (family, products) in [
+            ("platform", ["plan-ent", "plan-starter"]),
+            ("support", ["support-24x7", "support-basic"]),
+        ] {
+            sqlx::query(
+                "INSERT INTO product_family (id, name, created_at, updated_at)
+                 VALUES (?, ?, '2026-01-01', '2026-01-01')",
+            )
+            .bind(family)
+            .bind(family)
+            .execute(&pool)
+            .await
+            .expect("family");
+            for product in products {
+                sqlx::query(
+                    "INSERT INTO product (id, sku, name, family_id, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, '2026-01-01', '2026-01-01')",
+                )
+                .bind(product)
+                .bind(product)
+                .bind(product)
+                .bind(family)
+                .execute(&pool)
+                .await
+                .expect("product");
+            }
+        }
+        pool
+    }
+
+    fn quote(id: &str, account: &str, won: bool, discount_pct: f64) -> Quote {
+        let now = Utc::now();
+        let (product, term, rep) =
+            if won { ("plan-ent", 36, "rep-a") } else { ("plan-starter", 1, "rep-b") };
+        Quote {
+            id: QuoteId(id.to_string()),
+            version: 1,
+            status: if won { QuoteStatus::Sent } else { QuoteStatus::Finalized },
+            account_id: Some(account.to_string()),
+            deal_id: None,
+            currency: "USD".to_string(),
+            term_months: Some(term),
+            start_date: None,
+            end_date: None,
+            valid_until: None,
+            notes: None,
+            created_by: rep.to_string(),
+            lines: vec![
+                QuoteLine {
+                    product_id: ProductId(product.to_string()),
+                    quantity: 10,
+                    unit_price: Decimal::new(10_000, 2),
+                    discount_pct,
+                    notes: None,
+                },
+                QuoteLine {
+                    product_id: ProductId("support-24x7".to_string()),
+                    quantity: 1,
+                    unit_price: Decimal::new(50_000, 2),
+                    discount_pct: 0.0,
+                    notes: None,
+                },
+            ],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    async fn seed_outcomes(pool: &DbPool, count: usize) {
+        let quotes = SqlQuoteRepository::new(pool.clone());
+        for index in 0..count {
+            let won = index % 2 == 0;
+            let id = format!("Q-HIST-{index:02}");
+            let account = if won { "acct-big" } else { "acct-small" };
+            let discount = if won { 5.0 } else { 35.0 };
+            quotes.save(quote(&id, account, won, discount)).await.expect("save quote");
+            sqlx::query(
+                "INSERT INTO deal_outcomes
+                    (id, quote_id, outcome, final_price, close_date, customer_segment, created_at)
+                 VALUES (?, ?, ?, 1000.0, ?, ?, '2026-03-01T00:00:00Z')",
+            )
+            .bind(format!("do-{id}"))
+            .bind(&id)
+            .bind(if won { "won" } else { "lost" })
+            .bind(format!("2026-02-{:02}", index + 1))
+            .bind(if won { "Enterprise" } else { "SMB" })
+            .execute(pool)
+            .await
+            .expect("seed outcome");
+        }
+    }
+
+    #[tokio::test]
+    async fn trains_versions_from_outcomes_and_serves_cached_predictions_after_promotion() {
+        let pool = setup().await;
+        seed_outcomes(&pool, 20).await;
+        let service = WinProbabilityService::new(pool.clone());
+
+        let (outcomes, skipped) = service.training_set().await.expect("training set");
+        assert_eq!((outcomes.len(), skipped), (20, 0));
+        let won = &outcomes.iter().find(|o| o.outcome).expect("won").features;
+        assert_eq!(won.product_families, vec!["platform".to_string(), "support".to_string()]);
+        assert_eq!(won.customer_segment.as_deref(), Some("enterprise"));
+        assert_eq!(won.sales_rep_id.as_deref(), Some("rep-a"));
+        assert_eq!(won.term_months, Some(36));
+
+        let run = service.train(&TrainingConfig::default()).await.expect("train");
+        assert_eq!(run.model.version, "v1");
+        assert_eq!(run.model.status, "candidate");
+        assert_eq!((run.model.training_samples, run.model.holdout_samples), (16, 4));
+        assert_eq!(run.model.brier_score, Some(run.report.calibration.brier_score));
+        assert!(run.report.holdout.accuracy >= 0.75);
+
+        let open = quote("Q-OPEN", "acct-big", true, 5.0);
+        SqlQuoteRepository::new(pool.clone()).save(open.clone()).await.expect("save open");
+        assert!(service.predict(&open).await.expect("predict").is_none());
+
+        service.promote("v1").await.expect("promote");
+        let first = service.predict(&open).await.expect("predict").expect("prediction");
+        assert_eq!(first.model_version, "v1");
+        assert!(first.win_probability > 0.5, "p = {}", first.win_probability);
+        let cached = service.predict(&open).await.expect("predict").expect("prediction");
+        assert_eq!(cached, first);
+
+        let mut discounted = quote("Q-OPEN", "acct-small", false, 40.0);
+        discounted.created_by = "rep-b".to_string();
+        let rescored = service.predict(&discounted).await.expect("predict").expect("prediction");
+        assert!(rescored.win_probability < first.win_probability);
+        let cache_rows: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM prediction_cache WHERE quote_id = 'Q-OPEN'")
+                .fetch_one(&pool)
+                .await
+                .expect("count");
+        assert_eq!(cache_rows, 1);
+    }
+
+    #[tokio::test]
+    async fn promote_and_rollback_walk_version_lineage() {
+        let pool = setup().await;
+        seed_outcomes(&pool, 12).await;
+        let service = WinProbabilityService::new(pool);
+
+        assert!(matches!(service.rollback().await, Err(WinProbabilityError::NoActiveModel)));
+        assert!(matches!(
+            service.promote("v9").await,
+            Err(WinProbabilityError::VersionNotFound(version)) if version == "v9"
+        ));
+
+        service.train(&TrainingConfig::default()).await.expect("train v1");
+        service.train(&TrainingConfig::default()).await.expect("train v2");
+        service.promote("v1").await.expect("promote v1");
+        let v2 = service.promote("v2").await.expect("promote v2");
+        assert_eq!(v2.previous_version.as_deref(), Some("v1"));
+        assert_eq!(
+            service.promote("v2").await.expect("idempotent").previous_version,
+            v2.previous_version
+        );
+
+        let restored = service.rollback().await.expect("rollback");
+        assert_eq!(restored.version, "v1");
+        assert_eq!(restored.status, "active");
+        let statuses: Vec<(String, String)> = service
+            .list_versions()
+            .await
+            .expect("list")
+            .into_iter()
+            .map(|model| (model.version, model.status))
+            .collect();
+        assert!(statuses.contains(&("v2".to_string(), "retired".to_string())));
+        assert_eq!(service.active_version().await.expect("active").expect("some").version, "v1");
+
+        assert!(matches!(
+            service.rollback().await,
+            Err(WinProbabilityError::NoPreviousVersion(version)) if version == "v1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn training_refuses_thin_history() {
+        let pool = setup().await;
+        seed_outcomes(&pool, 4).await;
+        let service = WinProbabilityService::new(pool);
+        assert!(matches!(
+            service.train(&TrainingConfig::default()).await,
+            Err(WinProbabilityError::Training(message)) if message.contains("at least 10")
+        ));
+        assert!(service.list_versions().await.expect("list").is_empty());
+    }
+
+    #[test]
+    fn close_dates_accept_timestamps_and_plain_dates() {
+        assert_eq!(
+            parse_close_date("2026-02-01").map(|date| date.to_rfc3339()),
+            Some("2026-02-01T00:00:00+00:00".to_string())
+        );
+        assert!(parse_close_date("2026-02-01T10:00:00Z").is_some());
+        assert!(parse_close_date("last week").is_none());
+    }
+}
@@ -400,7 +400,7 @@ async fn g002_full_cpq_evaluation_round_trip() -> TestResult {
     let policy = evaluate_policy_input(&PolicyInput {
         requested_discount_pct: Decimal::new(25, 0),
         deal_value: pricing.total,
-        minimum_margin_pct: Decimal::new(75, 0),
+        minimum_margin_pct: Some(Decimal::new(75, 0)),
     });
 
     assert!(policy.approval_required, "25% discount should require approval");
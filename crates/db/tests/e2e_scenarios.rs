@@ -261,7 +261,7 @@ async fn s001_happy_path_create_price_finalize_deliver() -> TestResult {
     let policy = evaluate_policy_input(&PolicyInput {
         requested_discount_pct: Decimal::ZERO,
         deal_value: pricing.total,
-        minimum_margin_pct: Decimal::new(25, 0),
+        minimum_margin_pct: Some(Decimal::new(25, 0)),
     });
     assert!(!policy.approval_required, "0% discount should not require approval");
 
@@ -350,7 +350,7 @@ async fn s002_approval_path_policy_violation_approve_deliver() -> TestResult {
     let policy = evaluate_policy_input(&PolicyInput {
         requested_discount_pct: Decimal::new(25, 0),
         deal_value: pricing.total,
-        minimum_margin_pct: Decimal::new(75, 0),
+        minimum_margin_pct: Some(Decimal::new(75, 0)),
     });
     assert!(policy.approval_required);
     assert!(policy.violations.iter().any(|v| v.policy_id == "discount-cap"));
@@ -469,7 +469,7 @@ async fn s003_rejection_path_policy_violation_denied() -> TestResult {
     let policy = evaluate_policy_input(&PolicyInput {
         requested_discount_pct: Decimal::new(35, 0),
         deal_value: pricing_total,
-        minimum_margin_pct: Decimal::new(65, 0),
+        minimum_margin_pct: Some(Decimal::new(65, 0)),
     });
     assert!(policy.approval_required);
     assert!(policy
@@ -578,7 +578,7 @@ async fn s004_deterministic_replay_produces_identical_outcomes() -> TestResult {
     let policy_input = PolicyInput {
         requested_discount_pct: Decimal::new(15, 0),
         deal_value: result_1.total,
-        minimum_margin_pct: Decimal::new(30, 0),
+        minimum_margin_pct: Some(Decimal::new(30, 0)),
     };
     let policy_1 = evaluate_policy_input(&policy_input);
     let policy_2 = evaluate_policy_input(&policy_input);
@@ -1513,7 +1513,7 @@ async fn s014_renewal_expansion_prior_context_discount_approval() -> TestResult
     let policy = evaluate_policy_input(&PolicyInput {
         requested_discount_pct: Decimal::new(25, 0),
         deal_value: pricing.total,
-        minimum_margin_pct: Decimal::new(75, 0),
+        minimum_margin_pct: Some(Decimal::new(75, 0)),
     });
     assert!(policy.approval_required, "25% discount on renewal should require approval");
     assert!(
@@ -1,6 +1,11 @@
 //! MCP Authentication and Rate Limiting
 //!
-//! Provides API key authentication and rate limiting for MCP requests.
+//! Provides API key authentication, per-key scopes and rate limiting for MCP
+//! requests. Keys are held by their SHA-256 hash only; keys created through
+//! `quotey api-key` are loaded from the `api_key` table, while keys from the
+//! config file are hashed on load and keep full access unless `grants` is set.
+//! With a key store attached, stored keys are re-read on every request, so a
+//! key revoked or rotated through the CLI stops working without a restart.
 
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
@@ -8,6 +13,10 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
+use quotey_core::domain::auth::{hash_api_key, ApiKeyGrants, ApiKeyRecord, ApiScope};
+use quotey_db::repositories::{ApiKeyRepository, SqlApiKeyRepository};
+use quotey_db::DbPool;
+
 /// API key entry with metadata
 #[derive(Debug, Clone)]
 pub struct ApiKeyEntry {
@@ -21,6 +30,50 @@ pub struct ApiKeyEntry {
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// Whether the key is active
     pub active: bool,
+    /// Scopes and account/team restrictions
+    pub grants: ApiKeyGrants,
+}
+
+/// Key state held by the manager, indexed by key hash.
+#[derive(Debug, Clone)]
+struct StoredKey {
+    /// Database id for keys loaded from the `api_key` table
+    key_id: Option<String>,
+    name: String,
+    requests_per_minute: u32,
+    created_at: chrono::DateTime<chrono::Utc>,
+    active: bool,
+    grants: ApiKeyGrants,
+}
+
+impl StoredKey {
+    fn from_entry(entry: ApiKeyEntry) -> (String, Self) {
+        (
+            hash_api_key(&entry.key),
+            Self {
+                key_id: None,
+                name: entry.name,
+                requests_per_minute: entry.requests_per_minute,
+                created_at: entry.created_at,
+                active: entry.active,
+                grants: entry.grants,
+            },
+        )
+    }
+
+    fn from_record(record: ApiKeyRecord) -> (String, Self) {
+        (
+            record.key_hash,
+            Self {
+                key_id: Some(record.id),
+                name: record.name,
+                requests_per_minute: record.requests_per_minute,
+                created_at: record.created_at,
+                active: record.active,
+                grants: record.grants,
+            },
+        )
+    }
 }
 
 /// Rate limit tracking for a single key
@@ -88,14 +141,16 @@ impl RateLimitEntry {
 /// Authentication and rate limiting manager
 #[derive(Debug, Clone)]
 pub struct AuthManager {
-    /// Valid API keys
-    api_keys: Arc<RwLock<HashMap<String, ApiKeyEntry>>>,
-    /// Rate limit tracking per key
+    /// Valid API keys, keyed by key hash
+    api_keys: Arc<RwLock<HashMap<String, StoredKey>>>,
+    /// Rate limit tracking per key hash
     rate_limits: Arc<RwLock<HashMap<String, RateLimitEntry>>>,
     /// Rate limit window duration (default: 1 minute)
     rate_limit_window: Duration,
     /// Whether authentication is required
     auth_required: bool,
+    /// Database holding `quotey api-key` keys, consulted on every request
+    key_store: Option<DbPool>,
 }
 
 impl AuthManager {
@@ -106,30 +161,43 @@ impl AuthManager {
             rate_limits: Arc::new(RwLock::new(HashMap::new())),
             rate_limit_window: Duration::from_secs(60),
             auth_required: false,
+            key_store: None,
         }
     }
 
     /// Create a new auth manager with the given API keys
     pub fn with_keys(api_keys: Vec<ApiKeyEntry>) -> Self {
-        let keys: HashMap<String, ApiKeyEntry> =
-            api_keys.into_iter().map(|entry| (entry.key.clone(), entry)).collect();
+        let keys: HashMap<String, StoredKey> =
+            api_keys.into_iter().map(StoredKey::from_entry).collect();
 
         Self {
             api_keys: Arc::new(RwLock::new(keys)),
             rate_limits: Arc::new(RwLock::new(HashMap::new())),
             rate_limit_window: Duration::from_secs(60),
             auth_required: true,
+            key_store: None,
         }
     }
 
     /// Create auth manager from configuration
     pub fn from_config(config: &AuthConfig) -> Self {
-        if !config.enabled || config.api_keys.is_empty() {
-            return Self::no_auth();
+        Self::from_sources(config, Vec::new())
+    }
+
+    /// Create auth manager from configuration plus keys stored in the database.
+    ///
+    /// Any stored key (active or revoked) turns authentication on, so revoked
+    /// keys are reported as revoked rather than silently accepted.
+    pub fn from_sources(config: &AuthConfig, stored_keys: Vec<ApiKeyRecord>) -> Self {
+        let config_keys_enabled = config.enabled && !config.api_keys.is_empty();
+        if !config_keys_enabled && stored_keys.is_empty() {
+            let mut manager = Self::no_auth();
+            manager.rate_limit_window = Duration::from_secs(config.rate_limit_window_secs.max(1));
+            return manager;
         }
 
-        let keys: Vec<ApiKeyEntry> = config
-            .api_keys
+        let config_keys = if config_keys_enabled { config.api_keys.as_slice() } else { &[] };
+        let keys: Vec<ApiKeyEntry> = config_keys
             .iter()
             .map(|key_config| ApiKeyEntry {
                 key: key_config.key.clone(),
@@ -145,21 +213,88 @@ impl AuthManager {
                 },
                 created_at: chrono::Utc::now(),
                 active: true,
+                grants: key_config.grants.clone().unwrap_or_else(ApiKeyGrants::unrestricted),
             })
             .collect();
 
         let mut manager = Self::with_keys(keys);
+        {
+            let map = Arc::get_mut(&mut manager.api_keys)
+                .expect("freshly built key map is not shared")
+                .get_mut();
+            map.extend(stored_keys.into_iter().map(StoredKey::from_record));
+        }
         manager.rate_limit_window = Duration::from_secs(config.rate_limit_window_secs.max(1));
         manager
     }
 
+    /// Re-read stored keys from `pool` on every request instead of trusting
+    /// the copy loaded at startup. Keys created after startup are accepted, and
+    /// the first stored key turns authentication on for a server started without any.
+    pub fn with_key_store(mut self, pool: DbPool) -> Self {
+        self.key_store = Some(pool);
+        self
+    }
+
+    /// Current state of the key with `key_hash`. Stored keys come from the key
+    /// store when one is attached; config keys only live in memory.
+    async fn lookup_key(&self, key_hash: &str) -> Result<Option<StoredKey>, String> {
+        let cached = self.api_keys.read().await.get(key_hash).cloned();
+        let Some(pool) = &self.key_store else {
+            return Ok(cached);
+        };
+        if cached.as_ref().is_some_and(|entry| entry.key_id.is_none()) {
+            return Ok(cached);
+        }
+        let record = SqlApiKeyRepository::new(pool.clone())
+            .find_by_hash(key_hash)
+            .await
+            .map_err(|error| error.to_string())?;
+        let mut keys = self.api_keys.write().await;
+        match record {
+            Some(record) => {
+                let (key_hash, stored) = StoredKey::from_record(record);
+                keys.insert(key_hash, stored.clone());
+                Ok(Some(stored))
+            }
+            None => {
+                keys.remove(key_hash);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Whether this request needs a key: always when keys were configured or
+    /// stored at startup, otherwise as soon as the key store holds any key.
+    async fn requires_key(&self) -> Result<bool, String> {
+        if self.auth_required {
+            return Ok(true);
+        }
+        let Some(pool) = &self.key_store else {
+            return Ok(false);
+        };
+        SqlApiKeyRepository::new(pool.clone()).has_keys().await.map_err(|error| error.to_string())
+    }
+
     /// Validate an API key and check rate limits
     pub async fn validate_request(&self, api_key: Option<&str>) -> AuthResult {
+        let required = match self.requires_key().await {
+            Ok(required) => required,
+            Err(error) => {
+                warn!(%error, "API key store lookup failed");
+                return AuthResult::Denied {
+                    reason: "API key store unavailable".to_string(),
+                    retry_after: None,
+                };
+            }
+        };
         // If auth not required, allow all requests
-        if !self.auth_required {
+        if !required {
             return AuthResult::Allowed {
                 key_name: "anonymous".to_string(),
                 remaining_requests: u32::MAX,
+                grants: ApiKeyGrants::unrestricted(),
+                key_id: None,
             };
         }
 
@@ -174,18 +309,24 @@ impl AuthManager {
             }
         };
 
-        // Look up the key
-        let keys = self.api_keys.read().await;
-        let entry = match keys.get(key) {
-            Some(e) => e.clone(),
-            None => {
+        // Look up the key by its hash
+        let key_hash = hash_api_key(key);
+        let entry = match self.lookup_key(&key_hash).await {
+            Ok(Some(e)) => e,
+            Ok(None) => {
                 return AuthResult::Denied {
                     reason: "Invalid API key".to_string(),
                     retry_after: None,
                 };
             }
+            Err(error) => {
+                warn!(%error, "API key store lookup failed");
+                return AuthResult::Denied {
+                    reason: "API key store unavailable".to_string(),
+                    retry_after: None,
+                };
+            }
         };
-        drop(keys);
 
         // Check if key is active
         if !entry.active {
@@ -197,7 +338,7 @@ impl AuthManager {
 
         // Check rate limit
         let mut limits = self.rate_limits.write().await;
-        let limit_entry = limits.entry(key.to_string()).or_insert_with(RateLimitEntry::new);
+        let limit_entry = limits.entry(key_hash).or_insert_with(RateLimitEntry::new);
 
         let limit = entry.requests_per_minute.max(1) as usize;
         let request_count = match limit_entry.record_request(self.rate_limit_window, limit) {
@@ -218,28 +359,35 @@ impl AuthManager {
         let remaining = (limit - request_count) as u32;
         debug!(key_name = %entry.name, remaining = remaining, "Request allowed");
 
-        AuthResult::Allowed { key_name: entry.name.clone(), remaining_requests: remaining }
+        AuthResult::Allowed {
+            key_name: entry.name,
+            remaining_requests: remaining,
+            grants: entry.grants,
+            key_id: entry.key_id,
+        }
     }
 
     /// Add a new API key
     pub async fn add_key(&self, entry: ApiKeyEntry) -> Result<(), String> {
+        let (key_hash, stored) = StoredKey::from_entry(entry);
         let mut keys = self.api_keys.write().await;
-        if keys.contains_key(&entry.key) {
+        if keys.contains_key(&key_hash) {
             return Err("API key already exists".to_string());
         }
-        keys.insert(entry.key.clone(), entry);
+        keys.insert(key_hash, stored);
         Ok(())
     }
 
     /// Revoke an API key
     pub async fn revoke_key(&self, key: &str) -> bool {
+        let key_hash = hash_api_key(key);
         let mut keys = self.api_keys.write().await;
-        let removed = keys.remove(key).is_some();
+        let removed = keys.remove(&key_hash).is_some();
         drop(keys);
 
         if removed {
             let mut limits = self.rate_limits.write().await;
-            limits.remove(key);
+            limits.remove(&key_hash);
         }
 
         removed
@@ -257,20 +405,22 @@ impl AuthManager {
             return Err("new API key is required".to_string());
         }
 
+        let old_hash = hash_api_key(old_key);
+        let (new_hash, stored) = StoredKey::from_entry(new_entry);
         let mut keys = self.api_keys.write().await;
-        if !keys.contains_key(old_key) {
+        if !keys.contains_key(&old_hash) {
             return Err("old API key does not exist".to_string());
         }
-        if old_key != new_entry.key && keys.contains_key(&new_entry.key) {
+        if old_hash != new_hash && keys.contains_key(&new_hash) {
             return Err("new API key already exists".to_string());
         }
-        keys.remove(old_key);
-        keys.insert(new_entry.key.clone(), new_entry.clone());
+        keys.remove(&old_hash);
+        keys.insert(new_hash.clone(), stored);
         drop(keys);
 
         let mut limits = self.rate_limits.write().await;
-        limits.remove(old_key);
-        limits.remove(&new_entry.key);
+        limits.remove(&old_hash);
+        limits.remove(&new_hash);
         Ok(())
     }
 
@@ -283,11 +433,13 @@ impl AuthManager {
                 requests_per_minute: entry.requests_per_minute,
                 created_at: entry.created_at,
                 active: entry.active,
+                grants: entry.grants.clone(),
             })
             .collect()
     }
 
-    /// Check if authentication is required
+    /// Check if authentication is required by the keys known at startup. With a
+    /// key store attached, keys created later can still turn it on per request.
     pub fn is_auth_required(&self) -> bool {
         self.auth_required
     }
@@ -302,6 +454,10 @@ pub enum AuthResult {
         key_name: String,
         /// Remaining requests in the current window
         remaining_requests: u32,
+        /// Scopes and restrictions of the key used
+        grants: ApiKeyGrants,
+        /// Database id of the key, when it was loaded from the `api_key` table
+        key_id: Option<String>,
     },
     /// Request is denied
     Denied {
@@ -326,6 +482,14 @@ impl AuthResult {
         }
     }
 
+    /// Get the key's grants if allowed
+    pub fn grants(&self) -> Option<&ApiKeyGrants> {
+        match self {
+            AuthResult::Allowed { grants, .. } => Some(grants),
+            _ => None,
+        }
+    }
+
     /// Get the denial reason if denied
     pub fn denial_reason(&self) -> Option<&str> {
         match self {
@@ -350,6 +514,7 @@ pub struct ApiKeyInfo {
     pub requests_per_minute: u32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub active: bool,
+    pub grants: ApiKeyGrants,
 }
 
 /// Configuration for authentication
@@ -382,6 +547,9 @@ pub struct ApiKeyConfig {
     /// Maximum requests per minute (default: 60)
     #[serde(default = "default_requests_per_minute")]
     pub requests_per_minute: u32,
+    /// Scopes and restrictions; omitted means full access
+    #[serde(default)]
+    pub grants: Option<ApiKeyGrants>,
 }
 
 fn default_rate_limit_window() -> u64 {
@@ -392,24 +560,99 @@ fn default_requests_per_minute() -> u32 {
     60
 }
 
-/// Generate a new cryptographically secure API key
-pub fn generate_api_key() -> String {
-    use rand::rngs::OsRng;
-    use rand::RngCore;
+/// Permission an MCP operation requires from the presented key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolPermission {
+    pub scope: ApiScope,
+    /// Operation touches account-owned data, so account restrictions apply.
+    pub account_bound: bool,
+    /// Operation touches team-owned data, so team restrictions apply.
+    pub team_bound: bool,
+}
 
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-    const KEY_LEN: usize = 32;
+impl ToolPermission {
+    const fn global(scope: ApiScope) -> Self {
+        Self { scope, account_bound: false, team_bound: false }
+    }
 
-    let mut key = String::with_capacity(KEY_LEN);
-    let mut bytes = vec![0u8; KEY_LEN];
+    const fn account(scope: ApiScope) -> Self {
+        Self { scope, account_bound: true, team_bound: true }
+    }
 
-    // Use OsRng for cryptographically secure randomness
-    OsRng.fill_bytes(&mut bytes);
+    const fn team(scope: ApiScope) -> Self {
+        Self { scope, account_bound: false, team_bound: true }
+    }
+}
 
-    for b in bytes {
-        key.push(CHARSET[b as usize % CHARSET.len()] as char);
+/// Permission required to call an MCP tool.
+///
+/// Unknown tools require `settings:admin` so new tools fail closed until
+/// they are classified here.
+pub fn tool_permission(tool_name: &str) -> ToolPermission {
+    match tool_name {
+        "catalog_search" | "catalog_get" => ToolPermission::global(ApiScope::CatalogRead),
+        "quote_get" | "quote_list" | "quote_explain" | "quote_pdf" | "comment_list"
+        | "quote_lock_status" | "negotiation_status" | "budget_check" | "budget_status" => {
+            ToolPermission::account(ApiScope::QuoteRead)
+        }
+        "quote_create"
+        | "quote_price"
+        | "comment_add"
+        | "quote_lock"
+        | "quote_unlock"
+        | "negotiation_start"
+        | "negotiation_evaluate"
+        | "negotiation_escalate"
+        | "negotiation_counter"
+        | "negotiation_accept"
+        | "negotiation_reject"
+        | "budget_record"
+        | "quote_simulate"
+        | "quote_simulate_promote" => ToolPermission::account(ApiScope::QuoteWrite),
+        "quote_force_unlock" => ToolPermission::account(ApiScope::QuoteAdmin),
+        "approval_status" | "approval_pending" => ToolPermission::account(ApiScope::ApprovalRead),
+        "approval_request" => ToolPermission::account(ApiScope::ApprovalRequest),
+        "anomaly_override" => ToolPermission::account(ApiScope::ApprovalDecide),
+        "rep_get" | "rep_list" | "org_chain" | "org_authority" => {
+            ToolPermission::team(ApiScope::OrgRead)
+        }
+        "rep_upsert" => ToolPermission::team(ApiScope::OrgAdmin),
+        "audit_query" | "cost_summary" | "cost_list" | "analytics_query" => {
+            ToolPermission::global(ApiScope::AuditRead)
+        }
+        "settings_get" | "settings_list" | "integration_list" | "webhook_list"
+        | "webhook_deliveries" => ToolPermission::global(ApiScope::SettingsRead),
+        _ => ToolPermission::global(ApiScope::SettingsAdmin),
     }
-    key
+}
+
+/// Permission required to read an MCP resource.
+pub fn resource_permission(resource: &crate::resources::ResourceUri) -> ToolPermission {
+    use crate::resources::ResourceUri;
+    match resource {
+        ResourceUri::Quote(_) | ResourceUri::QuotePdf(_) => {
+            ToolPermission::account(ApiScope::QuoteRead)
+        }
+        ResourceUri::Approval(_) => ToolPermission::account(ApiScope::ApprovalRead),
+        ResourceUri::CatalogProduct(_) => ToolPermission::global(ApiScope::CatalogRead),
+        ResourceUri::PolicyThresholds => ToolPermission::global(ApiScope::QuoteRead),
+    }
+}
+
+/// Permission required to render an MCP prompt.
+pub fn prompt_permission(prompt_name: &str) -> ToolPermission {
+    match prompt_name {
+        crate::prompts::QUOTE_FROM_EMAIL_PROMPT => ToolPermission::global(ApiScope::QuoteWrite),
+        crate::prompts::DISCOUNT_JUSTIFICATION_PROMPT => {
+            ToolPermission::account(ApiScope::ApprovalRequest)
+        }
+        _ => ToolPermission::global(ApiScope::QuoteRead),
+    }
+}
+
+/// Generate a new cryptographically secure API key
+pub fn generate_api_key() -> String {
+    quotey_core::domain::auth::generate_api_key_secret()
 }
 
 #[cfg(test)]
@@ -433,6 +676,7 @@ mod tests {
             requests_per_minute: 10,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
 
         let auth = AuthManager::with_keys(vec![key]);
@@ -450,6 +694,7 @@ mod tests {
             requests_per_minute: 10,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
 
         let auth = AuthManager::with_keys(vec![key]);
@@ -467,6 +712,7 @@ mod tests {
             requests_per_minute: 10,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
 
         let auth = AuthManager::with_keys(vec![key]);
@@ -483,6 +729,7 @@ mod tests {
             requests_per_minute: 2,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
 
         let auth = AuthManager::with_keys(vec![key]);
@@ -510,6 +757,7 @@ mod tests {
                 key: "zero-rpm".to_string(),
                 name: "Zero RPM".to_string(),
                 requests_per_minute: 0,
+                grants: None,
             }],
         });
 
@@ -528,6 +776,7 @@ mod tests {
             requests_per_minute: 2,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
         let auth = AuthManager::with_keys(vec![old_key]);
 
@@ -540,6 +789,7 @@ mod tests {
             requests_per_minute: 10,
             created_at: chrono::Utc::now(),
             active: true,
+            grants: ApiKeyGrants::unrestricted(),
         };
         auth.rotate_key("old-key", rotated).await.expect("rotate key");
 
@@ -577,4 +827,142 @@ mod tests {
         let remaining = entry.requests.front().expect("remaining request");
         assert!(*remaining > now - window);
     }
+
+    fn stored_record(id: &str, secret: &str, active: bool, grants: ApiKeyGrants) -> ApiKeyRecord {
+        ApiKeyRecord {
+            id: id.to_string(),
+            name: format!("{id}-name"),
+            key_hash: hash_api_key(secret),
+            key_prefix: secret.chars().take(8).collect(),
+            grants,
+            requests_per_minute: 10,
+            active,
+            rotated_from: None,
+            created_at: chrono::Utc::now(),
+            revoked_at: None,
+            last_used_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_keys_enable_auth_and_carry_grants() {
+        let grants = ApiKeyGrants {
+            scopes: vec![ApiScope::QuoteRead],
+            account_ids: vec!["acct-1".to_string()],
+            team_ids: Vec::new(),
+        };
+        let auth = AuthManager::from_sources(
+            &AuthConfig::default(),
+            vec![
+                stored_record("key-1", "stored-secret", true, grants.clone()),
+                stored_record("key-2", "revoked-secret", false, ApiKeyGrants::unrestricted()),
+            ],
+        );
+        assert!(auth.is_auth_required());
+
+        let allowed = auth.validate_request(Some("stored-secret")).await;
+        assert_eq!(allowed.grants(), Some(&grants));
+        assert!(matches!(
+            allowed,
+            AuthResult::Allowed { key_id: Some(ref id), .. } if id == "key-1"
+        ));
+
+        let revoked = auth.validate_request(Some("revoked-secret")).await;
+        assert_eq!(revoked.denial_reason(), Some("API key deactivated"));
+    }
+
+    #[tokio::test]
+    async fn keys_revoked_or_rotated_in_the_store_take_effect_without_a_restart() {
+        let pool =
+            quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let repo = SqlApiKeyRepository::new(pool.clone());
+        let unrestricted = ApiKeyGrants::unrestricted;
+        for (id, secret) in [("key-1", "first-secret"), ("key-2", "second-secret")] {
+            repo.create(&stored_record(id, secret, true, unrestricted())).await.expect("create");
+        }
+        let auth =
+            AuthManager::from_sources(&AuthConfig::default(), repo.list(true).await.expect("list"))
+                .with_key_store(pool.clone());
+        assert!(auth.validate_request(Some("first-secret")).await.is_allowed());
+        assert!(auth.validate_request(Some("second-secret")).await.is_allowed());
+
+        assert!(repo.revoke("key-1", chrono::Utc::now()).await.expect("revoke"));
+        let revoked = auth.validate_request(Some("first-secret")).await;
+        assert_eq!(revoked.denial_reason(), Some("API key deactivated"));
+
+        let replacement = stored_record("key-3", "third-secret", true, unrestricted());
+        assert!(repo.rotate("key-2", &replacement).await.expect("rotate"));
+        let rotated = auth.validate_request(Some("second-secret")).await;
+        assert_eq!(rotated.denial_reason(), Some("API key deactivated"));
+        let fresh = auth.validate_request(Some("third-secret")).await;
+        assert!(matches!(fresh, AuthResult::Allowed { key_id: Some(ref id), .. } if id == "key-3"));
+    }
+
+    #[tokio::test]
+    async fn keys_created_after_startup_are_accepted_and_turn_auth_on() {
+        let pool =
+            quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let repo = SqlApiKeyRepository::new(pool.clone());
+        let unrestricted = ApiKeyGrants::unrestricted;
+
+        let open = AuthManager::from_sources(&AuthConfig::default(), Vec::new())
+            .with_key_store(pool.clone());
+        assert!(!open.is_auth_required());
+        assert!(open.validate_request(None).await.is_allowed());
+
+        let config_only = AuthManager::from_sources(
+            &AuthConfig {
+                enabled: true,
+                rate_limit_window_secs: 60,
+                api_keys: vec![ApiKeyConfig {
+                    key: "config-secret".to_string(),
+                    name: "config".to_string(),
+                    requests_per_minute: 60,
+                    grants: None,
+                }],
+            },
+            Vec::new(),
+        )
+        .with_key_store(pool.clone());
+
+        repo.create(&stored_record("key-1", "late-secret", true, unrestricted()))
+            .await
+            .expect("create");
+        let anonymous = open.validate_request(None).await;
+        assert_eq!(anonymous.denial_reason(), Some("API key required"));
+        assert!(open.validate_request(Some("late-secret")).await.is_allowed());
+        assert!(config_only.validate_request(Some("late-secret")).await.is_allowed());
+        assert!(config_only.validate_request(Some("config-secret")).await.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn config_keys_default_to_full_access() {
+        let auth = AuthManager::from_config(&AuthConfig {
+            enabled: true,
+            rate_limit_window_secs: 60,
+            api_keys: vec![ApiKeyConfig {
+                key: "legacy".to_string(),
+                name: "Legacy".to_string(),
+                requests_per_minute: 5,
+                grants: None,
+            }],
+        });
+        let result = auth.validate_request(Some("legacy")).await;
+        assert!(result.grants().expect("grants").allows_scope(ApiScope::SettingsAdmin));
+    }
+
+    #[test]
+    fn dangerous_tools_require_admin_scopes_and_unknown_tools_fail_closed() {
+        assert_eq!(tool_permission("settings_set").scope, ApiScope::SettingsAdmin);
+        assert_eq!(tool_permission("quote_force_unlock").scope, ApiScope::QuoteAdmin);
+        assert_eq!(tool_permission("rep_upsert").scope, ApiScope::OrgAdmin);
+        assert_eq!(tool_permission("not_a_tool").scope, ApiScope::SettingsAdmin);
+
+        let read = tool_permission("quote_get");
+        assert_eq!(read.scope, ApiScope::QuoteRead);
+        assert!(read.account_bound && read.team_bound);
+        assert!(!tool_permission("catalog_search").account_bound);
+    }
 }
@@ -24,6 +24,16 @@
 //! ### PDF Tools
 //! - `quote_pdf`: Generate PDF for a quote
 //!
+//! ## Resources Available
+//! - `quote://{id}` and `quote://{id}/pdf` (subscribable)
+//! - `catalog://product/{id}`
+//! - `policy://thresholds`
+//! - `approval://{id}`
+//!
+//! ## Prompts Available
+//! - `quote_from_email`: Build a draft quote from a customer email
+//! - `discount_justification`: Prepare a discount justification for approval
+//!
 //! ## Example Usage
 //!
 //! ```no_run
@@ -40,6 +50,8 @@
 
 #[allow(dead_code)]
 mod auth;
+pub mod prompts;
+pub mod resources;
 #[allow(dead_code)]
 pub mod server;
 mod tools;
@@ -17,6 +17,9 @@
 //! # Run with multiple API keys and rate limiting
 //! MCP_API_KEYS='[{"key":"key1","name":"Agent1","requests_per_minute":60}]' quotey-mcp
 //!
+//! # Keys created with `quotey api-key create` are loaded from the database
+//! # automatically and enable authentication on their own.
+//!
 //! # Run with auth from quotey.toml ([mcp.auth] section)
 //! QUOTEY_CONFIG_PATH=config/quotey.dev.toml quotey-mcp
 //!
@@ -28,10 +31,11 @@
 //! ```
 
 use anyhow::{anyhow, bail, Result};
+use quotey_db::repositories::{ApiKeyRepository, RepositoryError, SqlApiKeyRepository};
 use serde::Deserialize;
 use std::env::VarError;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use tracing::{info, warn};
 
 fn redact_database_url(database_url: &str) -> String {
     let Some((scheme, remainder)) = database_url.split_once("://") else {
@@ -131,6 +135,25 @@ fn auth_source_label(path: &Path) -> String {
     format!("config:{}", path.display())
 }
 
+/// Load hashed API keys from the `api_key` table.
+///
+/// Returns `None` for a database that predates the table; any other failure
+/// aborts startup rather than silently falling back to no-auth mode.
+async fn load_stored_api_keys(
+    db_pool: &quotey_db::DbPool,
+) -> Result<Option<Vec<quotey_core::ApiKeyRecord>>> {
+    match SqlApiKeyRepository::new(db_pool.clone()).list(true).await {
+        Ok(keys) => Ok(Some(keys)),
+        Err(RepositoryError::Database(error))
+            if error.to_string().contains("no such table: api_key") =>
+        {
+            warn!("api_key table missing; run `quotey migrate` to enable stored API keys");
+            Ok(None)
+        }
+        Err(error) => Err(anyhow!("failed to load stored API keys: {error}")),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize tracing
@@ -168,6 +191,7 @@ async fn main() -> Result<()> {
                 key: single_key,
                 name: "default".to_string(),
                 requests_per_minute: default_requests_per_minute,
+                grants: None,
             }],
         })
     } else if let Some((path, config)) = load_auth_config_from_file().await? {
@@ -177,23 +201,39 @@ async fn main() -> Result<()> {
         None
     };
 
-    let server = if let Some(auth_config) = auth_config {
-        let auth_manager = quotey_mcp::AuthManager::from_config(&auth_config);
-        if auth_manager.is_auth_required() {
-            info!(
-                source = %auth_source,
-                key_count = auth_config.api_keys.len(),
-                rate_limit_window_secs = auth_config.rate_limit_window_secs,
-                "Loading API key authentication"
-            );
-        } else {
-            info!(source = %auth_source, "Auth configuration resolved to no-auth mode");
-        }
-        quotey_mcp::QuoteyMcpServer::with_auth(db_pool, auth_manager)
+    // Keys created with `quotey api-key create` live (hashed) in the database.
+    let stored_keys = load_stored_api_keys(&db_pool).await?;
+    let key_store_available = stored_keys.is_some();
+    let stored_keys = stored_keys.unwrap_or_default();
+    let stored_key_count = stored_keys.len();
+
+    let auth_config = auth_config.unwrap_or_else(|| quotey_mcp::AuthConfig {
+        rate_limit_window_secs,
+        ..quotey_mcp::AuthConfig::default()
+    });
+    let mut auth_manager = quotey_mcp::AuthManager::from_sources(&auth_config, stored_keys);
+    if key_store_available {
+        // Re-read stored keys per request so keys created, revoked or rotated with the CLI
+        // apply without a restart, including the first key on a server started without any.
+        auth_manager = auth_manager.with_key_store(db_pool.clone());
+    }
+    if auth_manager.is_auth_required() {
+        info!(
+            source = %auth_source,
+            key_count = auth_config.api_keys.len(),
+            stored_key_count,
+            rate_limit_window_secs = auth_config.rate_limit_window_secs,
+            "Loading API key authentication"
+        );
+    } else if key_store_available {
+        info!(
+            source = %auth_source,
+            "No API keys yet; authentication turns on once `quotey api-key create` adds one"
+        );
     } else {
         info!("Running without authentication");
-        quotey_mcp::QuoteyMcpServer::new(db_pool)
-    };
+    }
+    let server = quotey_mcp::QuoteyMcpServer::with_auth(db_pool, auth_manager);
 
     // Run MCP server
     server.run_stdio().await?;
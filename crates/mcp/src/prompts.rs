@@ -0,0 +1,187 @@
+//! MCP Prompts for Quotey
+//!
+//! Server-provided prompt templates for common CPQ workflows. Prompts only
+//! assemble context and instructions; every pricing or policy decision is
+//! still made by the deterministic tools they point the agent at.
+//!
+//! - `quote_from_email`: turn an RFQ email into a priced draft quote
+//! - `discount_justification`: draft an approval justification for a discount
+
+use rmcp::model::{GetPromptResult, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+
+pub const QUOTE_FROM_EMAIL_PROMPT: &str = "quote_from_email";
+pub const DISCOUNT_JUSTIFICATION_PROMPT: &str = "discount_justification";
+
+const MAX_EMAIL_CHARS: usize = 20_000;
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        title: None,
+        description: Some(description.to_string()),
+        required: Some(required),
+    }
+}
+
+/// Prompts advertised by `prompts/list`.
+pub fn prompt_definitions() -> Vec<Prompt> {
+    vec![
+        Prompt::new(
+            QUOTE_FROM_EMAIL_PROMPT,
+            Some("Build a draft quote from a customer email or RFQ"),
+            Some(vec![
+                argument("email_text", "Full text of the customer email", true),
+                argument("account_id", "Account the quote belongs to, if known", false),
+            ]),
+        ),
+        Prompt::new(
+            DISCOUNT_JUSTIFICATION_PROMPT,
+            Some("Prepare a discount justification for an approval request"),
+            Some(vec![
+                argument("quote_id", "Quote the discount applies to", true),
+                argument("requested_discount_pct", "Requested discount percentage (0-100)", true),
+                argument("competitor", "Competitor being matched, if any", false),
+            ]),
+        ),
+    ]
+}
+
+/// Read a string prompt argument, treating blanks as absent.
+pub fn prompt_arg(args: Option<&rmcp::model::JsonObject>, name: &str) -> Option<String> {
+    let value = args?.get(name)?;
+    let text = match value {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+pub fn quote_from_email_prompt(
+    email_text: &str,
+    account_id: Option<&str>,
+) -> Result<GetPromptResult, String> {
+    let email_text = email_text.trim();
+    if email_text.is_empty() {
+        return Err("email_text is required".to_string());
+    }
+    if email_text.chars().count() > MAX_EMAIL_CHARS {
+        return Err(format!("email_text exceeds {MAX_EMAIL_CHARS} characters"));
+    }
+
+    let account_step = match account_id {
+        Some(account) => format!("Use account_id `{account}` for the quote."),
+        None => "Identify the customer account from the email; ask the user if it is ambiguous \
+                 rather than guessing."
+            .to_string(),
+    };
+
+    let instructions = format!(
+        "Build a draft quote from the customer email below.\n\n\
+         1. Extract requested products, quantities, term length, start date and any discount \
+            or budget expectations. Quote numbers verbatim; do not invent values.\n\
+         2. Resolve each product with `catalog_search`, then confirm with `catalog_get` \
+            (or read `catalog://product/{{id}}`).\n\
+         3. {account_step}\n\
+         4. Call `quote_create` with the resolved line items, then `quote_price`.\n\
+         5. Read `policy://thresholds`; if the priced quote needs approval, say which \
+            threshold it crosses and offer to run `approval_request`.\n\
+         6. Summarise the quote (read `quote://{{quote_id}}`) and list anything in the \
+            email you could not map to the catalog.\n\n\
+         Customer email:\n```\n{email_text}\n```"
+    );
+
+    Ok(GetPromptResult {
+        description: Some("Build a draft quote from a customer email".to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, instructions)],
+    })
+}
+
+pub fn discount_justification_prompt(
+    quote_id: &str,
+    requested_discount_pct: f64,
+    competitor: Option<&str>,
+    quote_json: &str,
+    thresholds_json: &str,
+    expected_approver: Option<&str>,
+) -> GetPromptResult {
+    let competitor_line = competitor
+        .map(|name| format!("The rep is matching a competing offer from {name}.\n"))
+        .unwrap_or_default();
+    let routing_line = match expected_approver {
+        Some(role) => format!(
+            "At {requested_discount_pct}% this discount routes to `{role}` for approval; \
+             address that approver's concerns directly."
+        ),
+        None => format!(
+            "At {requested_discount_pct}% this discount is within standard policy; keep the \
+             justification brief."
+        ),
+    };
+
+    let instructions = format!(
+        "Prepare a discount justification for quote `{quote_id}` requesting a \
+         {requested_discount_pct}% discount.\n{competitor_line}{routing_line}\n\n\
+         Write 3-5 short bullet points covering deal size and term, strategic value, \
+         competitive pressure and the margin impact. Cite only figures present in the quote \
+         data below. Finish with a one-sentence justification suitable for the \
+         `justification` field of `approval_request`."
+    );
+
+    GetPromptResult {
+        description: Some(format!("Discount justification for quote {quote_id}")),
+        messages: vec![
+            PromptMessage::new_text(PromptMessageRole::User, instructions),
+            PromptMessage::new_text(
+                PromptMessageRole::User,
+                format!(
+                    "Quote data (quote://{quote_id}):\n```json\n{quote_json}\n```\n\n\
+                     Policy thresholds (policy://thresholds):\n```json\n{thresholds_json}\n```"
+                ),
+            ),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_declare_required_arguments() {
+        let prompts = prompt_definitions();
+        let names: Vec<&str> = prompts.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec![QUOTE_FROM_EMAIL_PROMPT, DISCOUNT_JUSTIFICATION_PROMPT]);
+
+        let email_args = prompts[0].arguments.as_ref().expect("args");
+        assert!(email_args.iter().any(|a| a.name == "email_text" && a.required == Some(true)));
+    }
+
+    #[test]
+    fn quote_from_email_embeds_email_and_account() {
+        let result =
+            quote_from_email_prompt("Need 50 seats of Pro", Some("acct-1")).expect("prompt");
+        let text = serde_json::to_string(&result.messages).expect("json");
+        assert!(text.contains("Need 50 seats of Pro"));
+        assert!(text.contains("acct-1"));
+    }
+
+    #[test]
+    fn quote_from_email_rejects_blank_email() {
+        assert!(quote_from_email_prompt("   ", None).is_err());
+    }
+
+    #[test]
+    fn prompt_arg_accepts_numbers_and_ignores_blanks() {
+        let mut args = rmcp::model::JsonObject::new();
+        args.insert("pct".to_string(), serde_json::json!(25));
+        args.insert("blank".to_string(), serde_json::json!("  "));
+        assert_eq!(prompt_arg(Some(&args), "pct").as_deref(), Some("25"));
+        assert_eq!(prompt_arg(Some(&args), "blank"), None);
+        assert_eq!(prompt_arg(None, "pct"), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! MCP Resources for Quotey
+//!
+//! Exposes read-only context as browsable MCP resources so agents do not
+//! have to call tools repeatedly just to read state:
+//!
+//! - `quote://{id}`: quote header, line items and pricing (JSON)
+//! - `quote://{id}/pdf`: rendered quote document (PDF blob)
+//! - `catalog://product/{id}`: product detail (JSON)
+//! - `policy://thresholds`: active approval policy thresholds (JSON)
+//! - `approval://{id}`: approval request detail (JSON)
+//!
+//! Clients may `resources/subscribe` to quote URIs; the server emits
+//! `notifications/resources/updated` whenever a quote-mutating tool succeeds,
+//! and a background poll of the subscribed quotes catches changes made
+//! anywhere else (Slack, REST, the portal, the CLI or another MCP session).
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use quotey_core::domain::quote::Quote;
+use rmcp::model::{
+    AnnotateAble, ErrorData, RawResource, RawResourceTemplate, ReadResourceResult, Resource,
+    ResourceContents, ResourceTemplate, ResourceUpdatedNotificationParam,
+};
+use rmcp::service::{Peer, RoleServer};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+pub const QUOTE_SCHEME: &str = "quote://";
+pub const CATALOG_PRODUCT_SCHEME: &str = "catalog://product/";
+pub const POLICY_THRESHOLDS_URI: &str = "policy://thresholds";
+pub const APPROVAL_SCHEME: &str = "approval://";
+
+const JSON_MIME: &str = "application/json";
+const PDF_MIME: &str = "application/pdf";
+const MAX_RESOURCE_ID_LEN: usize = 128;
+
+/// Tools whose successful execution changes what `quote://{id}` returns.
+pub const QUOTE_MUTATING_TOOLS: &[&str] = &[
+    "quote_price",
+    "quote_simulate_promote",
+    "approval_request",
+    "anomaly_override",
+    "negotiation_start",
+    "negotiation_evaluate",
+    "negotiation_escalate",
+    "negotiation_counter",
+    "negotiation_accept",
+    "negotiation_reject",
+    "comment_add",
+    "quote_lock",
+    "quote_unlock",
+    "quote_force_unlock",
+];
+
+/// A parsed Quotey resource URI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceUri {
+    Quote(String),
+    QuotePdf(String),
+    CatalogProduct(String),
+    PolicyThresholds,
+    Approval(String),
+}
+
+impl ResourceUri {
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let uri = uri.trim();
+        if uri == POLICY_THRESHOLDS_URI {
+            return Ok(Self::PolicyThresholds);
+        }
+        if let Some(rest) = uri.strip_prefix(CATALOG_PRODUCT_SCHEME) {
+            return Ok(Self::CatalogProduct(parse_resource_id(rest)?));
+        }
+        if let Some(rest) = uri.strip_prefix(APPROVAL_SCHEME) {
+            return Ok(Self::Approval(parse_resource_id(rest)?));
+        }
+        if let Some(rest) = uri.strip_prefix(QUOTE_SCHEME) {
+            if let Some(id) = rest.strip_suffix("/pdf") {
+                return Ok(Self::QuotePdf(parse_resource_id(id)?));
+            }
+            return Ok(Self::Quote(parse_resource_id(rest)?));
+        }
+        Err(format!("unsupported resource URI '{uri}'"))
+    }
+
+    pub fn as_uri(&self) -> String {
+        match self {
+            Self::Quote(id) => format!("{QUOTE_SCHEME}{id}"),
+            Self::QuotePdf(id) => format!("{QUOTE_SCHEME}{id}/pdf"),
+            Self::CatalogProduct(id) => format!("{CATALOG_PRODUCT_SCHEME}{id}"),
+            Self::PolicyThresholds => POLICY_THRESHOLDS_URI.to_string(),
+            Self::Approval(id) => format!("{APPROVAL_SCHEME}{id}"),
+        }
+    }
+
+    /// Quote the resource belongs to, used for audit attribution.
+    pub fn quote_id(&self) -> Option<&str> {
+        match self {
+            Self::Quote(id) | Self::QuotePdf(id) => Some(id.as_str()),
+            _ => None,
+        }
+    }
+}
+
+fn parse_resource_id(raw: &str) -> Result<String, String> {
+    let id = raw.trim();
+    if id.is_empty() {
+        return Err("resource id is required".to_string());
+    }
+    if id.len() > MAX_RESOURCE_ID_LEN {
+        return Err("resource id exceeds maximum length".to_string());
+    }
+    if id.contains('/') {
+        return Err(format!("resource id '{id}' must not contain '/'"));
+    }
+    Ok(id.to_string())
+}
+
+/// URIs whose content changes when the given quote changes.
+pub fn quote_resource_uris(quote_id: &str) -> [String; 2] {
+    [
+        ResourceUri::Quote(quote_id.to_string()).as_uri(),
+        ResourceUri::QuotePdf(quote_id.to_string()).as_uri(),
+    ]
+}
+
+/// Concrete (non-templated) resources advertised by `resources/list`.
+pub fn static_resources() -> Vec<Resource> {
+    let mut thresholds = RawResource::new(POLICY_THRESHOLDS_URI, "policy_thresholds");
+    thresholds.title = Some("Approval policy thresholds".to_string());
+    thresholds.description = Some(
+        "Discount, margin and deal-value thresholds that route quotes to approval".to_string(),
+    );
+    thresholds.mime_type = Some(JSON_MIME.to_string());
+    vec![thresholds.no_annotation()]
+}
+
+/// Parameterised resources advertised by `resources/templates/list`.
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    [
+        (
+            "quote://{id}",
+            "quote",
+            "Quote header, line items and pricing. Subscribers are notified of changes from any \
+             source within a few seconds",
+            JSON_MIME,
+        ),
+        ("quote://{id}/pdf", "quote_pdf", "Rendered quote document", PDF_MIME),
+        ("catalog://product/{id}", "catalog_product", "Catalog product detail", JSON_MIME),
+        ("approval://{id}", "approval", "Approval request detail and decision", JSON_MIME),
+    ]
+    .into_iter()
+    .map(|(uri_template, name, description, mime_type)| {
+        RawResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            mime_type: Some(mime_type.to_string()),
+        }
+        .no_annotation()
+    })
+    .collect()
+}
+
+pub(crate) fn json_contents(uri: &str, body: String) -> ReadResourceResult {
+    ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(JSON_MIME.to_string()),
+            text: body,
+            meta: None,
+        }],
+    }
+}
+
+pub(crate) fn pdf_contents(uri: &str, bytes: &[u8]) -> ReadResourceResult {
+    use base64::Engine;
+    ReadResourceResult {
+        contents: vec![ResourceContents::BlobResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(PDF_MIME.to_string()),
+            blob: base64::engine::general_purpose::STANDARD.encode(bytes),
+            meta: None,
+        }],
+    }
+}
+
+/// Map a tool's JSON output (which may be an error envelope) to resource contents.
+pub(crate) fn tool_output_to_resource(
+    uri: &str,
+    output: String,
+) -> Result<ReadResourceResult, ErrorData> {
+    let parsed: serde_json::Value = serde_json::from_str(&output)
+        .map_err(|e| ErrorData::internal_error(format!("invalid resource payload: {e}"), None))?;
+    if let Some(error) = parsed.get("error") {
+        let code = error.get("code").and_then(serde_json::Value::as_str).unwrap_or_default();
+        let message = error
+            .get("message")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("resource read failed")
+            .to_string();
+        let data = Some(serde_json::json!({ "uri": uri, "code": code }));
+        return Err(match code {
+            "NOT_FOUND" => ErrorData::resource_not_found(message, data),
+            "VALIDATION_ERROR" => ErrorData::invalid_params(message, data),
+            _ => ErrorData::internal_error(message, data),
+        });
+    }
+    Ok(json_contents(uri, output))
+}
+
+#[derive(Default)]
+struct SubscriptionState {
+    uris: BTreeSet<String>,
+    peer: Option<Peer<RoleServer>>,
+    /// Last stored state seen for each subscribed quote, `None` when it did not exist
+    snapshots: HashMap<String, Option<Quote>>,
+    #[cfg(test)]
+    notified: Vec<String>,
+}
+
+/// Resource subscriptions held for the connected MCP client.
+///
+/// Each `QuoteyMcpServer` instance serves a single client session (stdio),
+/// so one peer handle is enough to deliver update notifications.
+#[derive(Clone, Default)]
+pub struct ResourceSubscriptions {
+    state: Arc<Mutex<SubscriptionState>>,
+}
+
+impl std::fmt::Debug for ResourceSubscriptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceSubscriptions").finish_non_exhaustive()
+    }
+}
+
+impl ResourceSubscriptions {
+    pub async fn subscribe(&self, uri: String, peer: Option<Peer<RoleServer>>) {
+        let mut state = self.state.lock().await;
+        state.uris.insert(uri);
+        if peer.is_some() {
+            state.peer = peer;
+        }
+    }
+
+    pub async fn unsubscribe(&self, uri: &str) -> bool {
+        self.state.lock().await.uris.remove(uri)
+    }
+
+    pub async fn subscribed_uris(&self) -> Vec<String> {
+        self.state.lock().await.uris.iter().cloned().collect()
+    }
+
+    /// Quotes behind the subscribed `quote://` URIs. Snapshots of quotes no
+    /// longer subscribed are dropped.
+    pub async fn subscribed_quote_ids(&self) -> BTreeSet<String> {
+        let mut state = self.state.lock().await;
+        let quote_ids: BTreeSet<String> = state
+            .uris
+            .iter()
+            .filter_map(|uri| match ResourceUri::parse(uri) {
+                Ok(ResourceUri::Quote(id) | ResourceUri::QuotePdf(id)) => Some(id),
+                _ => None,
+            })
+            .collect();
+        state.snapshots.retain(|quote_id, _| quote_ids.contains(quote_id));
+        quote_ids
+    }
+
+    /// Remember `snapshot` as the current state of `quote_id`. Returns whether
+    /// it differs from the previous snapshot; the first one is a baseline.
+    pub async fn record_snapshot(&self, quote_id: &str, snapshot: Option<Quote>) -> bool {
+        let mut state = self.state.lock().await;
+        match state.snapshots.insert(quote_id.to_string(), snapshot.clone()) {
+            Some(previous) => previous != snapshot,
+            None => false,
+        }
+    }
+
+    /// URIs notified since the last call, for tests that have no client peer.
+    #[cfg(test)]
+    pub(crate) async fn take_notified(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.lock().await.notified)
+    }
+
+    /// Notify the client about every subscribed URI in `uris`.
+    ///
+    /// Returns the URIs that matched a subscription, whether or not a peer was
+    /// available to receive the notification.
+    pub async fn notify_updated(&self, uris: &[String]) -> Vec<String> {
+        let (matched, peer) = {
+            let state = self.state.lock().await;
+            let matched: Vec<String> =
+                uris.iter().filter(|uri| state.uris.contains(*uri)).cloned().collect();
+            (matched, state.peer.clone())
+        };
+        #[cfg(test)]
+        self.state.lock().await.notified.extend(matched.iter().cloned());
+
+        if let Some(peer) = peer {
+            for uri in &matched {
+                if let Err(error) = peer
+                    .notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+                    .await
+                {
+                    warn!(error = %error, uri = %uri, "failed to send resources/updated notification");
+                } else {
+                    debug!(uri = %uri, "sent resources/updated notification");
+                }
+            }
+        }
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trips_every_resource_kind() {
+        let cases = [
+            ("quote://Q-1", ResourceUri::Quote("Q-1".to_string())),
+            ("quote://Q-1/pdf", ResourceUri::QuotePdf("Q-1".to_string())),
+            ("catalog://product/prod-1", ResourceUri::CatalogProduct("prod-1".to_string())),
+            ("policy://thresholds", ResourceUri::PolicyThresholds),
+            ("approval://APR-9", ResourceUri::Approval("APR-9".to_string())),
+        ];
+        for (uri, expected) in cases {
+            let parsed = ResourceUri::parse(uri).expect("parse");
+            assert_eq!(parsed, expected);
+            assert_eq!(parsed.as_uri(), uri);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_unknown_scheme_and_nested_ids() {
+        assert!(ResourceUri::parse("file:///etc/passwd").is_err());
+        assert!(ResourceUri::parse("quote://").is_err());
+        assert!(ResourceUri::parse("quote://Q-1/lines").is_err());
+        assert!(ResourceUri::parse("policy://other").is_err());
+    }
+
+    #[test]
+    fn tool_error_output_maps_to_resource_not_found() {
+        let output = r#"{"error":{"code":"NOT_FOUND","message":"Quote 'Q-x' not found"}}"#;
+        let err = tool_output_to_resource("quote://Q-x", output.to_string()).unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn notify_updated_only_matches_subscribed_uris() {
+        let subs = ResourceSubscriptions::default();
+        subs.subscribe("quote://Q-1".to_string(), None).await;
+
+        let matched = subs.notify_updated(&quote_resource_uris("Q-1")).await;
+        assert_eq!(matched, vec!["quote://Q-1".to_string()]);
+
+        assert!(subs.unsubscribe("quote://Q-1").await);
+        assert!(subs.notify_updated(&quote_resource_uris("Q-1")).await.is_empty());
+    }
+}
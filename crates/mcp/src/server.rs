@@ -11,25 +11,29 @@ use rmcp::{
 };
 use tracing::{debug, info, warn};
 
-use std::collections::HashMap;
-
 use std::path::PathBuf;
 use std::time::Duration;
-use tera::{Context, Tera};
-use tokio::process::Command;
 
-use crate::auth::{AuthManager, AuthResult};
+use crate::auth::{
+    prompt_permission, resource_permission, tool_permission, AuthManager, AuthResult,
+    ToolPermission,
+};
+use crate::prompts::{self, prompt_arg};
+use crate::resources::{self, ResourceSubscriptions, ResourceUri};
 use quotey_core::domain::quote::Quote;
+use quotey_core::pdf::{render_quote_pdf, PdfBranding, PdfRenderError, PdfTemplate};
 use quotey_core::{
     AuthChannel, AuthContext, AuthError, AuthErrorCode, AuthMethod, AuthPrincipal, AuthStrength,
 };
-use quotey_db::repositories::ApprovalRepository;
+use quotey_db::repositories::{ApiKeyRepository, ApprovalRepository, SqlApiKeyRepository};
 
 const MAX_PAGE_LIMIT: u32 = 100;
 const DEFAULT_PAGE_LIMIT: u32 = 20;
 const MAX_LINE_ITEMS: usize = 500;
 const MAX_QUANTITY: u32 = 1_000_000;
 const PORTAL_PUSH_BRIDGE_URL_ENV: &str = "QUOTEY_PORTAL_PUSH_BRIDGE_URL";
+/// How often subscribed quotes are re-read for changes made outside this session.
+const QUOTE_CHANGE_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Return a tool error response with a redacted message for internal errors.
 /// Logs the detailed error server-side for debugging.
@@ -38,6 +42,20 @@ fn internal_tool_error(error: &dyn std::fmt::Display) -> String {
     tool_error("INTERNAL_ERROR", "Internal server error", None)
 }
 
+fn webhook_tool_error(error: quotey_db::webhooks::WebhookServiceError) -> String {
+    use quotey_db::webhooks::WebhookServiceError;
+    match error {
+        WebhookServiceError::SubscriptionNotFound(_) | WebhookServiceError::DeliveryNotFound(_) => {
+            tool_error("NOT_FOUND", &error.to_string(), None)
+        }
+        WebhookServiceError::SubscriptionDisabled(_) => {
+            tool_error("CONFLICT", &error.to_string(), None)
+        }
+        WebhookServiceError::Invalid(_) => tool_error("VALIDATION_ERROR", &error.to_string(), None),
+        WebhookServiceError::Repository(error) => internal_tool_error(&error),
+    }
+}
+
 fn tool_error(code: &str, message: &str, details: Option<serde_json::Value>) -> String {
     let safe_message = if code == "INTERNAL_ERROR" { "Internal server error" } else { message };
     let payload = serde_json::json!({
@@ -114,64 +132,6 @@ fn normalize_discount(value: f64, field: &str) -> Result<f64, String> {
     Ok(value)
 }
 
-fn register_pdf_quote_filters(tera: &mut Tera) {
-    tera.register_filter("format", pdf_format_filter);
-    tera.register_filter("money", pdf_money_filter);
-}
-
-fn pdf_format_filter(
-    value: &tera::Value,
-    args: &HashMap<String, tera::Value>,
-) -> tera::Result<tera::Value> {
-    let format_str =
-        value.as_str().ok_or_else(|| tera::Error::msg("format filter expects a string input"))?;
-
-    let val = args
-        .get("value")
-        .ok_or_else(|| tera::Error::msg("format filter requires a 'value' argument"))?;
-
-    let num = match val {
-        tera::Value::Number(n) => n.as_f64().unwrap_or(0.0),
-        tera::Value::Null => 0.0,
-        _ => 0.0,
-    };
-
-    let result = if let Some(rest) = format_str.strip_prefix("%.") {
-        if let Some(precision_str) = rest.strip_suffix('f') {
-            let precision: usize = precision_str.parse().unwrap_or(2);
-            format!("{:.*}", precision, num)
-        } else {
-            format!("{}", num)
-        }
-    } else {
-        format!("{}", num)
-    };
-
-    Ok(tera::Value::String(result))
-}
-
-fn pdf_money_filter(
-    value: &tera::Value,
-    _args: &HashMap<String, tera::Value>,
-) -> tera::Result<tera::Value> {
-    let num = match value {
-        tera::Value::Number(n) => n.as_f64().unwrap_or(0.0),
-        tera::Value::Null => 0.0,
-        _ => 0.0,
-    };
-    Ok(tera::Value::String(format!("{:.2}", num)))
-}
-
-fn register_quote_style_partials(tera: &mut Tera) {
-    tera.add_raw_template(
-        "styles/quote-base.css",
-        include_str!("../../../templates/styles/quote-base.css"),
-    )
-    .ok();
-    tera.add_raw_template("styles/quote.css", include_str!("../../../templates/styles/quote.css"))
-        .ok();
-}
-
 fn decimal_to_f64(value: &rust_decimal::Decimal) -> f64 {
     value.to_string().parse::<f64>().unwrap_or_else(|_| {
         tracing::warn!(value = %value, "Failed to parse Decimal to f64, using 0.0");
@@ -209,10 +169,6 @@ fn allowed_pdf_templates() -> &'static [&'static str] {
     &["detailed", "executive_summary", "compact"]
 }
 
-fn template_is_allowed(template: &str) -> bool {
-    allowed_pdf_templates().contains(&template)
-}
-
 fn checksum_of(value: &str) -> String {
     let hash = blake3::hash(value.as_bytes());
     format!("checksum:{:.32}", hash.to_hex())
@@ -430,6 +386,124 @@ fn auth_context_for_denied_mcp_call(presented_key: Option<&str>) -> AuthContext
     }
 }
 
+/// Audit context for a call denied by error `error`.
+///
+/// Authorization denials (valid key, insufficient scope) still identify the key
+/// so audit consumers can see which credential attempted the call.
+fn auth_context_for_denied_error(
+    error: &rmcp::ErrorData,
+    presented_key: Option<&str>,
+) -> AuthContext {
+    let key_name = error
+        .data
+        .as_ref()
+        .and_then(|data| data.get("key_name"))
+        .and_then(serde_json::Value::as_str);
+    match key_name {
+        Some(key_name) => AuthContext {
+            channel: AuthChannel::Mcp,
+            method: AuthMethod::ApiKey,
+            strength: AuthStrength::Possession,
+            principal: AuthPrincipal {
+                actor_id: format!("mcp:key:{key_name}"),
+                display_name: Some(key_name.to_string()),
+            },
+            token_fingerprint: presented_key.map(checksum_of),
+            session_id: None,
+        },
+        None => auth_context_for_denied_mcp_call(presented_key),
+    }
+}
+
+/// Account and team an MCP request targets, used for per-key restrictions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AccessTarget {
+    account_id: Option<String>,
+    team_id: Option<String>,
+}
+
+fn non_blank_argument(
+    arguments: Option<&serde_json::Map<String, serde_json::Value>>,
+    name: &str,
+) -> Option<String> {
+    arguments
+        .and_then(|args| args.get(name))
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Tool-style arguments identifying what a resource read touches.
+fn resource_access_arguments(resource: &ResourceUri) -> serde_json::Map<String, serde_json::Value> {
+    let mut arguments = serde_json::Map::new();
+    match resource {
+        ResourceUri::Quote(id) | ResourceUri::QuotePdf(id) => {
+            arguments.insert("quote_id".to_string(), serde_json::json!(id));
+        }
+        ResourceUri::Approval(id) => {
+            arguments.insert("approval_id".to_string(), serde_json::json!(id));
+        }
+        ResourceUri::CatalogProduct(_) | ResourceUri::PolicyThresholds => {}
+    }
+    arguments
+}
+
+/// Check a key's grants against an operation. Returns the denial message, if any.
+fn authorization_denial(
+    grants: &quotey_core::ApiKeyGrants,
+    permission: ToolPermission,
+    target: &AccessTarget,
+) -> Option<String> {
+    if !grants.allows_scope(permission.scope) {
+        return Some(format!("API key lacks scope '{}'", permission.scope));
+    }
+    if permission.account_bound && grants.is_account_restricted() {
+        match target.account_id.as_deref() {
+            Some(account_id) if grants.allows_account(account_id) => {}
+            Some(account_id) => {
+                return Some(format!("API key is not permitted for account '{account_id}'"));
+            }
+            None => {
+                return Some(
+                    "API key is restricted to specific accounts; request must target one"
+                        .to_string(),
+                );
+            }
+        }
+    }
+    if permission.team_bound && grants.is_team_restricted() {
+        match target.team_id.as_deref() {
+            Some(team_id) if grants.allows_team(team_id) => {}
+            Some(team_id) => {
+                return Some(format!("API key is not permitted for team '{team_id}'"));
+            }
+            None => {
+                return Some(
+                    "API key is restricted to specific teams; request must target one".to_string(),
+                );
+            }
+        }
+    }
+    None
+}
+
+/// Picks the stored owner of a resolved row over the caller's claim. `stored` is `None` when no
+/// row resolved (the claim is used) and `Some(None)` when the row has no owner of this kind.
+fn reconcile_target(
+    stored: Option<Option<String>>,
+    claimed: Option<String>,
+    field: &str,
+) -> Result<Option<String>, String> {
+    match (stored, claimed) {
+        (None, claimed) => Ok(claimed),
+        (Some(Some(stored)), Some(claimed)) if stored != claimed => {
+            Err(format!("{field} '{claimed}' does not match the targeted record"))
+        }
+        (Some(stored), _) => Ok(stored),
+    }
+}
+
 fn actor_from_auth_context(auth_context: &AuthContext) -> String {
     if auth_context.method == AuthMethod::ApiKey {
         if let Some(display_name) = auth_context.principal.display_name.as_deref() {
@@ -519,6 +593,10 @@ pub struct QuoteyMcpServer {
     auth_manager: AuthManager,
     /// MCP protocol version advertised during initialize handshake
     protocol_version: ProtocolVersion,
+    /// Resource URIs the connected client subscribed to
+    subscriptions: ResourceSubscriptions,
+    /// Results of recent `analytics_query` calls
+    analytics_cache: quotey_db::analytics::AnalyticsCache,
 }
 
 impl QuoteyMcpServer {
@@ -528,7 +606,16 @@ impl QuoteyMcpServer {
         let tool_router = Self::tool_router();
         let auth_manager = AuthManager::no_auth();
         let protocol_version = resolve_protocol_version();
-        Self { db_pool, tool_router, auth_manager, protocol_version }
+        let subscriptions = ResourceSubscriptions::default();
+        let analytics_cache = quotey_db::analytics::AnalyticsCache::default();
+        Self {
+            db_pool,
+            tool_router,
+            auth_manager,
+            protocol_version,
+            subscriptions,
+            analytics_cache,
+        }
     }
 
     /// Create a new MCP server with authentication
@@ -536,7 +623,16 @@ impl QuoteyMcpServer {
         info!("Initializing Quotey MCP Server (with auth)");
         let tool_router = Self::tool_router();
         let protocol_version = resolve_protocol_version();
-        Self { db_pool, tool_router, auth_manager, protocol_version }
+        let subscriptions = ResourceSubscriptions::default();
+        let analytics_cache = quotey_db::analytics::AnalyticsCache::default();
+        Self {
+            db_pool,
+            tool_router,
+            auth_manager,
+            protocol_version,
+            subscriptions,
+            analytics_cache,
+        }
     }
 
     /// Run the server with stdio transport
@@ -546,10 +642,20 @@ impl QuoteyMcpServer {
 
         info!("Starting MCP server with stdio transport");
 
+        let watcher = {
+            let server = self.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(QUOTE_CHANGE_POLL_INTERVAL).await;
+                    server.poll_quote_changes().await;
+                }
+            })
+        };
         let service = serve_server(self, (stdin(), stdout())).await?;
 
         // Wait for shutdown
         let _quit = service.waiting().await?;
+        watcher.abort();
 
         info!("MCP server shutdown complete");
         Ok(())
@@ -569,6 +675,241 @@ impl QuoteyMcpServer {
         &self.protocol_version
     }
 
+    /// Get a reference to the resource subscription registry
+    pub fn resource_subscriptions(&self) -> &ResourceSubscriptions {
+        &self.subscriptions
+    }
+
+    /// Emit `resources/updated` for every subscribed URI backed by this quote.
+    pub async fn notify_quote_changed(&self, quote_id: &str) -> Vec<String> {
+        let notified =
+            self.subscriptions.notify_updated(&resources::quote_resource_uris(quote_id)).await;
+        // Move the poll baseline past this change so it is not announced twice.
+        if !notified.is_empty() {
+            self.snapshot_quote(quote_id).await;
+        }
+        notified
+    }
+
+    /// Re-read every subscribed quote and emit `resources/updated` for those
+    /// whose stored state changed since the last look, whichever process
+    /// changed it. Returns the notified URIs.
+    pub async fn poll_quote_changes(&self) -> Vec<String> {
+        let mut notified = Vec::new();
+        for quote_id in self.subscriptions.subscribed_quote_ids().await {
+            if self.snapshot_quote(&quote_id).await {
+                notified.extend(
+                    self.subscriptions
+                        .notify_updated(&resources::quote_resource_uris(&quote_id))
+                        .await,
+                );
+            }
+        }
+        notified
+    }
+
+    /// Record the stored state of `quote_id`, returning whether it changed.
+    async fn snapshot_quote(&self, quote_id: &str) -> bool {
+        use quotey_core::domain::quote::QuoteId;
+        use quotey_db::repositories::QuoteRepository;
+
+        let repo = quotey_db::repositories::SqlQuoteRepository::new(self.db_pool.clone());
+        match repo.find_by_id(&QuoteId(quote_id.to_string())).await {
+            Ok(snapshot) => self.subscriptions.record_snapshot(quote_id, snapshot).await,
+            Err(error) => {
+                warn!(error = %error, quote_id = %quote_id, "quote change poll failed");
+                false
+            }
+        }
+    }
+
+    /// Read a Quotey resource by URI (`quote://`, `catalog://`, `policy://`, `approval://`).
+    pub async fn read_resource_uri(&self, uri: &str) -> Result<ReadResourceResult, ErrorData> {
+        let resource = ResourceUri::parse(uri).map_err(|msg| {
+            ErrorData::invalid_params(msg, Some(serde_json::json!({ "uri": uri })))
+        })?;
+        let canonical_uri = resource.as_uri();
+
+        match &resource {
+            ResourceUri::Quote(quote_id) => {
+                let output = self
+                    .quote_get(Parameters(QuoteGetInput {
+                        quote_id: quote_id.clone(),
+                        include_pricing: true,
+                    }))
+                    .await;
+                resources::tool_output_to_resource(&canonical_uri, output)
+            }
+            ResourceUri::QuotePdf(quote_id) => {
+                self.record_mcp_audit_event(
+                    "resource_read",
+                    Some(quote_id),
+                    serde_json::json!({ "uri": &canonical_uri }),
+                )
+                .await;
+
+                use quotey_core::domain::quote::QuoteId;
+                use quotey_db::repositories::QuoteRepository;
+
+                let quote_repo =
+                    quotey_db::repositories::SqlQuoteRepository::new(self.db_pool.clone());
+                let quote = match quote_repo.find_by_id(&QuoteId(quote_id.clone())).await {
+                    Ok(Some(q)) => q,
+                    Ok(None) => {
+                        return Err(ErrorData::resource_not_found(
+                            format!("Quote '{}' not found", quote_id),
+                            Some(serde_json::json!({ "uri": &canonical_uri })),
+                        ));
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "resource_read: failed to load quote");
+                        return Err(ErrorData::internal_error("Internal server error", None));
+                    }
+                };
+                let payload = build_pdf_quote_payload(&quote);
+                match render_quote_pdf_bytes(&payload, PdfTemplate::Detailed) {
+                    Ok(bytes) => Ok(resources::pdf_contents(&canonical_uri, &bytes)),
+                    Err(err) => {
+                        warn!(error = %err, "resource_read: failed to render quote document");
+                        Err(ErrorData::internal_error("Internal server error", None))
+                    }
+                }
+            }
+            ResourceUri::CatalogProduct(product_id) => {
+                let output = self
+                    .catalog_get(Parameters(CatalogGetInput {
+                        product_id: product_id.clone(),
+                        include_relationships: false,
+                    }))
+                    .await;
+                resources::tool_output_to_resource(&canonical_uri, output)
+            }
+            ResourceUri::PolicyThresholds => {
+                self.record_mcp_audit_event(
+                    "resource_read",
+                    None,
+                    serde_json::json!({ "uri": &canonical_uri }),
+                )
+                .await;
+                let thresholds = load_policy_thresholds(self.db()).await;
+                let body = serde_json::to_string_pretty(&thresholds).unwrap_or_default();
+                Ok(resources::json_contents(&canonical_uri, body))
+            }
+            ResourceUri::Approval(approval_id) => {
+                self.record_mcp_audit_event(
+                    "resource_read",
+                    None,
+                    serde_json::json!({ "uri": &canonical_uri }),
+                )
+                .await;
+
+                use quotey_core::domain::approval::ApprovalId;
+
+                let repo =
+                    quotey_db::repositories::SqlApprovalRepository::new(self.db_pool.clone());
+                match repo.find_by_id(&ApprovalId(approval_id.clone())).await {
+                    Ok(Some(approval)) => {
+                        let body = serde_json::to_string_pretty(&serde_json::json!({
+                            "approval_id": approval.id.0,
+                            "quote_id": approval.quote_id.0,
+                            "status": approval.status.as_str(),
+                            "approval_type": approval.approval_type.as_str(),
+                            "approver_role": approval.approver_role,
+                            "reason": approval.reason,
+                            "justification": approval.justification,
+                            "decision_note": approval.decision_note,
+                            "requested_by": approval.requested_by,
+                            "expires_at": approval.expires_at.map(|t| t.to_rfc3339()),
+                            "created_at": approval.created_at.to_rfc3339(),
+                            "updated_at": approval.updated_at.to_rfc3339(),
+                        }))
+                        .unwrap_or_default();
+                        Ok(resources::json_contents(&canonical_uri, body))
+                    }
+                    Ok(None) => Err(ErrorData::resource_not_found(
+                        format!("Approval '{}' not found", approval_id),
+                        Some(serde_json::json!({ "uri": &canonical_uri })),
+                    )),
+                    Err(e) => {
+                        warn!(error = %e, "resource_read: failed to load approval");
+                        Err(ErrorData::internal_error("Internal server error", None))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Render a server-provided prompt with the supplied arguments.
+    pub async fn render_prompt(
+        &self,
+        name: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        match name {
+            prompts::QUOTE_FROM_EMAIL_PROMPT => {
+                let email_text = prompt_arg(arguments, "email_text").unwrap_or_default();
+                let account_id = prompt_arg(arguments, "account_id");
+                prompts::quote_from_email_prompt(&email_text, account_id.as_deref())
+                    .map_err(|msg| ErrorData::invalid_params(msg, None))
+            }
+            prompts::DISCOUNT_JUSTIFICATION_PROMPT => {
+                let quote_id = prompt_arg(arguments, "quote_id")
+                    .ok_or_else(|| ErrorData::invalid_params("quote_id is required", None))?;
+                let requested_discount_pct = prompt_arg(arguments, "requested_discount_pct")
+                    .and_then(|raw| raw.parse::<f64>().ok())
+                    .ok_or_else(|| {
+                        ErrorData::invalid_params("requested_discount_pct must be a number", None)
+                    })
+                    .and_then(|pct| {
+                        normalize_discount(pct, "requested_discount_pct")
+                            .map_err(|msg| ErrorData::invalid_params(msg, None))
+                    })?;
+                let competitor = prompt_arg(arguments, "competitor");
+
+                let quote =
+                    self.read_resource_uri(&ResourceUri::Quote(quote_id.clone()).as_uri()).await?;
+                let quote_json = match quote.contents.into_iter().next() {
+                    Some(ResourceContents::TextResourceContents { text, .. }) => text,
+                    _ => String::new(),
+                };
+
+                use quotey_core::cpq::policy::{evaluate_policy_with_thresholds, PolicyInput};
+                use rust_decimal::prelude::FromPrimitive;
+                use rust_decimal::Decimal;
+
+                let thresholds = load_policy_thresholds(self.db()).await;
+                let decision = evaluate_policy_with_thresholds(
+                    &PolicyInput {
+                        requested_discount_pct: Decimal::from_f64(requested_discount_pct)
+                            .unwrap_or_default(),
+                        deal_value: Decimal::ZERO,
+                        minimum_margin_pct: None,
+                    },
+                    &thresholds,
+                );
+                let expected_approver = decision
+                    .violations
+                    .iter()
+                    .find(|v| v.policy_id == "discount-cap")
+                    .and_then(|v| v.required_approval.clone());
+                let thresholds_json = serde_json::to_string_pretty(&thresholds).unwrap_or_default();
+
+                Ok(prompts::discount_justification_prompt(
+                    &quote_id,
+                    requested_discount_pct,
+                    competitor.as_deref(),
+                    &quote_json,
+                    &thresholds_json,
+                    expected_approver.as_deref(),
+                ))
+            }
+            other => Err(ErrorData::invalid_params(
+                format!("Unknown prompt '{other}'"),
+                Some(serde_json::json!({ "name": other })),
+            )),
+        }
+    }
+
     async fn sanitize_quote_id_for_audit(&self, quote_id: Option<&str>) -> Option<String> {
         let candidate = quote_id.map(str::trim).filter(|value| !value.is_empty())?;
         match sqlx::query_scalar::<_, i64>("SELECT 1 FROM quote WHERE id = ? LIMIT 1")
@@ -939,6 +1280,80 @@ impl QuoteyMcpServer {
         }
     }
 
+    /// Resolve the account and team a request targets from its arguments.
+    ///
+    /// Stored rows win: the quote (directly or via `approval_id`) supplies the account and its
+    /// creating rep's team, and `rep_id` supplies the rep's team. Explicit `account_id` /
+    /// `team_id` arguments are only used when nothing resolves; when a row does resolve they
+    /// must match it, and a mismatch is returned as the denial message.
+    async fn resolve_access_target(
+        &self,
+        arguments: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<AccessTarget, String> {
+        let claimed_account = non_blank_argument(arguments, "account_id");
+        let claimed_team = non_blank_argument(arguments, "team_id");
+        let mut stored_account = None;
+        let mut stored_team = None;
+
+        let mut quote_id = extract_quote_id_from_arguments(arguments);
+        if quote_id.is_none() {
+            if let Some(approval_id) = non_blank_argument(arguments, "approval_id") {
+                quote_id = sqlx::query_scalar::<_, String>(
+                    "SELECT quote_id FROM approval_request WHERE id = ? LIMIT 1",
+                )
+                .bind(&approval_id)
+                .fetch_optional(&self.db_pool)
+                .await
+                .unwrap_or_else(|error| {
+                    warn!(error = %error, "failed to resolve approval for authorization");
+                    None
+                });
+            }
+        }
+
+        if let Some(quote_id) = quote_id {
+            let row = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+                "SELECT q.account_id, r.team_id FROM quote q \
+                 LEFT JOIN sales_rep r ON r.id = q.created_by_sales_rep_id \
+                 WHERE q.id = ? LIMIT 1",
+            )
+            .bind(&quote_id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .unwrap_or_else(|error| {
+                warn!(error = %error, "failed to resolve quote for authorization");
+                None
+            });
+            if let Some((account_id, team_id)) = row {
+                stored_account = Some(account_id);
+                stored_team = Some(team_id);
+            }
+        }
+
+        if stored_team.as_ref().map_or(true, Option::is_none) {
+            if let Some(rep_id) = non_blank_argument(arguments, "rep_id") {
+                let rep_team = sqlx::query_scalar::<_, Option<String>>(
+                    "SELECT team_id FROM sales_rep WHERE id = ? LIMIT 1",
+                )
+                .bind(&rep_id)
+                .fetch_optional(&self.db_pool)
+                .await
+                .unwrap_or_else(|error| {
+                    warn!(error = %error, "failed to resolve rep for authorization");
+                    None
+                });
+                if rep_team.is_some() {
+                    stored_team = rep_team;
+                }
+            }
+        }
+
+        Ok(AccessTarget {
+            account_id: reconcile_target(stored_account, claimed_account, "account_id")?,
+            team_id: reconcile_target(stored_team, claimed_team, "team_id")?,
+        })
+    }
+
     /// Validate the current request against the auth manager.
     ///
     /// Clients pass their key via the MCP `_meta` field on tool-call requests.
@@ -953,12 +1368,66 @@ impl QuoteyMcpServer {
     ///     "_meta": { "api_key": "your-secret-key" }
     /// }}
     /// ```
-    async fn check_auth(&self, meta: &rmcp::model::Meta) -> Result<AuthResult, rmcp::ErrorData> {
+    ///
+    /// After authentication the key's grants are checked against `permission`;
+    /// account/team restrictions are resolved from `arguments` (`account_id`,
+    /// `quote_id`, `approval_id`, `rep_id`, `team_id`). Scope and restriction
+    /// failures are reported as `unauthorized_scope` (HTTP 403).
+    async fn check_auth(
+        &self,
+        meta: &rmcp::model::Meta,
+        permission: ToolPermission,
+        arguments: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<AuthResult, rmcp::ErrorData> {
         let presented_key = extract_api_key_from_meta(meta); // ubs:ignore (runtime metadata lookup, not a hardcoded secret)
         let result = self.auth_manager.validate_request(presented_key.as_deref()).await;
 
         match &result {
-            AuthResult::Allowed { key_name, remaining_requests } => {
+            AuthResult::Allowed { key_name, remaining_requests, grants, key_id } => {
+                let needs_target = (permission.account_bound && grants.is_account_restricted())
+                    || (permission.team_bound && grants.is_team_restricted());
+                let target = if needs_target {
+                    self.resolve_access_target(arguments).await
+                } else {
+                    Ok(AccessTarget::default())
+                };
+                let denial = match target {
+                    Ok(target) => authorization_denial(grants, permission, &target),
+                    Err(mismatch) => Some(
+                        authorization_denial(
+                            grants,
+                            ToolPermission {
+                                account_bound: false,
+                                team_bound: false,
+                                ..permission
+                            },
+                            &AccessTarget::default(),
+                        )
+                        .unwrap_or(mismatch),
+                    ),
+                };
+                if let Some(message) = denial {
+                    warn!(key_name = %key_name, scope = %permission.scope, "Authorization denied");
+                    let auth_error = AuthError::new(AuthErrorCode::UnauthorizedScope, message);
+                    return Err(rmcp::ErrorData::invalid_request(
+                        format!("Authorization failed: {}", auth_error.message),
+                        Some(serde_json::json!({
+                            "reason": auth_error.message,
+                            "code": auth_error.code.as_str(),
+                            "error_code": "AUTHORIZATION_FAILED",
+                            "http_status": auth_error.http_status(),
+                            "required_scope": permission.scope.as_str(),
+                            "key_name": key_name,
+                        })),
+                    ));
+                }
+
+                if let Some(key_id) = key_id {
+                    let repo = SqlApiKeyRepository::new(self.db_pool.clone());
+                    if let Err(error) = repo.touch_last_used(key_id, chrono::Utc::now()).await {
+                        warn!(error = %error, "failed to record API key last use");
+                    }
+                }
                 debug!(
                     key_name = %key_name,
                     remaining = remaining_requests,
@@ -1005,6 +1474,7 @@ impl ServerHandler for QuoteyMcpServer {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .enable_resources()
+                .enable_resources_subscribe()
                 .enable_prompts()
                 .build(),
             server_info: Implementation {
@@ -1017,7 +1487,10 @@ impl ServerHandler for QuoteyMcpServer {
             instructions: Some(
                 "Quotey MCP Server - CPQ automation for AI agents. \
                  Tools: catalog_search, catalog_get, quote_create, quote_get, quote_price, \
-                 quote_list, approval_request, approval_status, approval_pending, quote_pdf"
+                 quote_list, approval_request, approval_status, approval_pending, quote_pdf. \
+                 Resources: quote://{id}, quote://{id}/pdf, catalog://product/{id}, \
+                 policy://thresholds, approval://{id}. \
+                 Prompts: quote_from_email, discount_justification"
                     .to_string(),
             ),
         }
@@ -1039,10 +1512,12 @@ impl ServerHandler for QuoteyMcpServer {
         // Enforce authentication when configured.
         // Clients pass their API key via `_meta.api_key` on each tool-call request.
         // When auth is not required the check is a no-op (returns Allowed).
-        let auth_result = match self.check_auth(&context.meta).await {
+        let permission = tool_permission(&tool_name);
+        let auth_result = match self.check_auth(&context.meta, permission, arguments.as_ref()).await
+        {
             Ok(result) => result,
             Err(error) => {
-                let auth_context = auth_context_for_denied_mcp_call(presented_key.as_deref());
+                let auth_context = auth_context_for_denied_error(&error, presented_key.as_deref());
                 let envelope = McpInvocationAuditEnvelope {
                     tool_name: tool_name.clone(),
                     quote_id: quote_id_for_audit.clone(),
@@ -1086,6 +1561,14 @@ impl ServerHandler for QuoteyMcpServer {
         let result = self.tool_router.call(tool_call_context).await;
         let (success, outcome_code, error_message) = outcome_from_tool_result(&result);
 
+        // Tools addressed by a negotiation session notify from the handler once it has resolved
+        // the session's quote.
+        if success && resources::QUOTE_MUTATING_TOOLS.contains(&tool_name.as_str()) {
+            if let Some(quote_id) = quote_id_for_audit.as_deref() {
+                self.notify_quote_changed(quote_id).await;
+            }
+        }
+
         self.record_mcp_invocation_outcome(&McpInvocationAuditEnvelope {
             tool_name,
             quote_id: quote_id_for_audit,
@@ -1111,6 +1594,88 @@ impl ServerHandler for QuoteyMcpServer {
     ) -> Result<ListToolsResult, rmcp::ErrorData> {
         Ok(ListToolsResult { tools: self.tool_router.list_all(), next_cursor: None })
     }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::ErrorData> {
+        Ok(ListResourcesResult { resources: resources::static_resources(), next_cursor: None })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, rmcp::ErrorData> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: resources::resource_templates(),
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::ErrorData> {
+        let resource = ResourceUri::parse(&request.uri)
+            .map_err(|msg| rmcp::ErrorData::invalid_params(msg, None))?;
+        let arguments = resource_access_arguments(&resource);
+        self.check_auth(&context.meta, resource_permission(&resource), Some(&arguments)).await?;
+        self.read_resource_uri(&request.uri).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        let resource = ResourceUri::parse(&request.uri)
+            .map_err(|msg| rmcp::ErrorData::invalid_params(msg, None))?;
+        let arguments = resource_access_arguments(&resource);
+        self.check_auth(&context.meta, resource_permission(&resource), Some(&arguments)).await?;
+        debug!(uri = %resource.as_uri(), "resources/subscribe");
+        self.subscriptions.subscribe(resource.as_uri(), Some(context.peer.clone())).await;
+        if let ResourceUri::Quote(quote_id) | ResourceUri::QuotePdf(quote_id) = &resource {
+            self.snapshot_quote(quote_id).await;
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        let uri = ResourceUri::parse(&request.uri)
+            .map(|resource| resource.as_uri())
+            .unwrap_or(request.uri);
+        self.subscriptions.unsubscribe(&uri).await;
+        Ok(())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::ErrorData> {
+        Ok(ListPromptsResult { prompts: prompts::prompt_definitions(), next_cursor: None })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<GetPromptResult, rmcp::ErrorData> {
+        self.check_auth(
+            &context.meta,
+            prompt_permission(&request.name),
+            request.arguments.as_ref(),
+        )
+        .await?;
+        self.render_prompt(&request.name, request.arguments.as_ref()).await
+    }
 }
 
 // ============================================================================
@@ -1295,11 +1860,21 @@ pub struct QuoteInfo {
     pub created_by: String,
 }
 
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WinProbabilityInfo {
+    pub model_version: String,
+    /// 0.0–1.0
+    pub probability: f64,
+    pub predicted_at: String,
+}
+
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct QuoteGetResult {
     pub quote: QuoteInfo,
     pub line_items: Vec<QuoteLineInfo>,
     pub pricing: Option<PricingInfo>,
+    /// Prediction from the active win-probability model; null until one is promoted.
+    pub win_probability: Option<WinProbabilityInfo>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -1309,6 +1884,64 @@ pub struct QuotePriceInput {
     pub requested_discount_pct: f64,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct QuoteExplainInput {
+    pub quote_id: String,
+    /// What to explain: `total` (default), `line` or `policy`.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Line id from `quote_price` (`line_pricing[].line_id`); required when target is `line`.
+    #[serde(default)]
+    pub line_id: Option<String>,
+    /// Quote version to explain; defaults to the current version.
+    #[serde(default)]
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScenarioLineChangeInput {
+    pub product_id: String,
+    /// Seats/units to add (positive) or remove (negative).
+    #[serde(default)]
+    pub quantity_delta: i32,
+    /// Unit price override; required when the product is not on the quote yet.
+    #[serde(default)]
+    pub unit_price: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScenarioVariantInput {
+    /// Unique key for this scenario, e.g. `more-seats`.
+    pub key: String,
+    #[serde(default)]
+    pub line_changes: Vec<ScenarioLineChangeInput>,
+    /// Discount percentage (0-100) to evaluate policy against instead of the baseline.
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+    /// Margin percentage to evaluate policy against instead of the baseline.
+    #[serde(default)]
+    pub margin_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct QuoteSimulateInput {
+    pub quote_id: String,
+    /// Up to three scenarios to compare against the current quote.
+    pub variants: Vec<ScenarioVariantInput>,
+    /// Baseline discount percentage; defaults to the quote's line discounts.
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct QuoteSimulatePromoteInput {
+    pub quote_id: String,
+    /// `run_id` returned by `quote_simulate`.
+    pub run_id: String,
+    /// Variant key (or `variant_id`) to promote.
+    pub variant: String,
+}
+
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct LinePricingInfo {
     pub line_id: String,
@@ -1321,6 +1954,19 @@ pub struct LinePricingInfo {
     pub discount_pct: f64,
     pub discount_amount: f64,
     pub line_total: f64,
+    /// Product cost of the line; `None` when the product has no cost in the quote currency.
+    pub cost: Option<f64>,
+    pub margin_pct: Option<f64>,
+}
+
+/// Margin over product cost. Internal only: never rendered on customer-facing documents.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MarginInfo {
+    pub cost_total: f64,
+    pub margin_total: f64,
+    /// `None` when no line has a cost record.
+    pub margin_pct: Option<f64>,
+    pub uncosted_line_ids: Vec<String>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -1341,6 +1987,7 @@ pub struct QuotePriceResult {
     pub status: String,
     pub pricing: PricingInfo,
     pub line_pricing: Vec<LinePricingInfo>,
+    pub margin: MarginInfo,
     pub approval_required: bool,
     pub policy_violations: Vec<PolicyViolation>,
 }
@@ -1507,6 +2154,53 @@ pub struct NegotiationEscalateInput {
     pub reason: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NegotiationCounterInput {
+    /// The negotiation session ID
+    pub session_id: String,
+    /// Actor recording the counter
+    pub actor_id: String,
+    /// Discount percentage proposed for every quote line
+    pub discount_pct: f64,
+    /// Proposed term in months
+    #[serde(default)]
+    pub term_months: Option<u32>,
+    /// Who proposed the counter: "rep" (default) or "customer" when relaying the customer's ask
+    #[serde(default)]
+    pub party: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Retrying with the same key returns the turn it first recorded
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NegotiationAcceptInput {
+    /// The negotiation session ID
+    pub session_id: String,
+    /// Rep accepting the pending counter
+    pub actor_id: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Retrying with the same key returns the turn it first recorded
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NegotiationRejectInput {
+    /// The negotiation session ID
+    pub session_id: String,
+    /// Rep rejecting the negotiation
+    pub actor_id: String,
+    /// Reason for rejecting
+    pub reason: String,
+    /// Retrying with the same key returns the turn it first recorded
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
 // Sales Rep Types
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RepGetInput {
@@ -1680,6 +2374,90 @@ pub struct IntegrationTestInput {
     pub integration_id: String,
 }
 
+// Analytics Types
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyticsFilterInput {
+    /// Dimension to filter on, e.g. region or sales_rep
+    pub dimension: String,
+    /// Keep rows whose dimension equals any of these values
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyticsQueryInput {
+    /// Metrics: quote_count, win_rate_pct, avg_discount_pct, avg_deal_value,
+    /// approval_cycle_hours, time_to_finalize_hours, anomaly_rate_pct
+    pub metrics: Vec<String>,
+    /// Group by: day, week, month, quarter, customer_segment, industry, region, sales_rep,
+    /// product_family, approval_role
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    /// Days back from today to include, 1-3650 (default: 30)
+    #[serde(default = "default_analytics_lookback_days")]
+    pub lookback_days: u32,
+    /// Only count approved, finalized, sent and accepted quotes (default: false)
+    #[serde(default)]
+    pub include_only_finalized: bool,
+    #[serde(default)]
+    pub filters: Vec<AnalyticsFilterInput>,
+    /// Last creation date counted, YYYY-MM-DD inclusive
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// Row cap, 1-10000 (default: 1000)
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+fn default_analytics_lookback_days() -> u32 {
+    30
+}
+
+// Webhook Types
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookSubscribeInput {
+    /// Absolute http(s) URL that receives signed POST deliveries
+    pub url: String,
+    /// Event types (quote.finalized, quote.accepted, approval.decided, portal.comment_added),
+    /// namespace wildcards such as quote.*, or * for everything
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookListInput {
+    /// If true, also list disabled subscriptions (default: false)
+    #[serde(default)]
+    pub include_disabled: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookDisableInput {
+    pub subscription_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookDeliveriesInput {
+    pub subscription_id: String,
+    /// Return this delivery with every attempt instead of the list
+    #[serde(default)]
+    pub delivery_id: Option<String>,
+    /// Filter by status: pending, retrying, delivered, dead_lettered, cancelled
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebhookRedeliverInput {
+    pub delivery_id: String,
+    #[serde(default)]
+    pub actor: Option<String>,
+}
+
 // Audit Query Types
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct AuditQueryInput {
@@ -1753,11 +2531,6 @@ pub struct QuotePdfResult {
     pub generated_at: String,
 }
 
-enum PdfRenderResult {
-    Pdf(Vec<u8>),
-    Html(String),
-}
-
 fn build_pdf_quote_payload(quote: &Quote) -> serde_json::Value {
     let mut line_rows = Vec::with_capacity(quote.lines.len());
     let mut subtotal = 0.0_f64;
@@ -1826,215 +2599,17 @@ fn build_pdf_quote_payload(quote: &Quote) -> serde_json::Value {
     })
 }
 
-async fn render_quote_pdf_html_to_bytes(
+fn render_quote_pdf_bytes(
     payload: &serde_json::Value,
-    template: &str,
-) -> Result<PdfRenderResult, String> {
-    let mut tera = Tera::default();
-    register_pdf_quote_filters(&mut tera);
-    register_quote_style_partials(&mut tera);
-
-    tera.add_raw_template(
-        "detailed.html.tera",
-        include_str!("../../../templates/quotes/detailed.html.tera"),
-    )
-    .map_err(|e| e.to_string())?;
-    tera.add_raw_template(
-        "executive_summary.html.tera",
-        include_str!("../../../templates/quotes/executive_summary.html.tera"),
-    )
-    .map_err(|e| e.to_string())?;
-    tera.add_raw_template(
-        "compact.html.tera",
-        include_str!("../../../templates/quotes/compact.html.tera"),
-    )
-    .map_err(|e| e.to_string())?;
-
-    let mut context = Context::new();
-    context.insert("quote", &payload);
-    context.insert("account", &payload.get("account").cloned().unwrap_or(serde_json::json!({})));
-    context.insert("lines", &payload.get("lines").cloned().unwrap_or(serde_json::json!([])));
-    context.insert("pricing", &payload.get("pricing").cloned().unwrap_or_else(|| {
-        serde_json::json!({
-            "subtotal": payload.get("subtotal").cloned().unwrap_or(serde_json::json!(0.0)),
-            "discount_total": payload.get("discount_total").cloned().unwrap_or(serde_json::json!(0.0)),
-            "tax_rate": payload.get("tax_rate").cloned().unwrap_or(serde_json::json!(0.0)),
-            "tax": payload.get("tax").cloned().unwrap_or(serde_json::json!(0.0)),
-            "tax_total": payload.get("tax_total").cloned().unwrap_or(serde_json::json!(0.0)),
-            "total": payload.get("total").cloned().unwrap_or(serde_json::json!(0.0)),
-        })
-    }));
-    context
-        .insert("sales_rep", &payload.get("sales_rep").cloned().unwrap_or(serde_json::json!({})));
-
-    let branding = payload.get("branding");
-    let read_string = |key: &str| -> Option<String> {
-        branding
-            .and_then(|value| value.get(key))
-            .or_else(|| payload.get(key))
-            .and_then(serde_json::Value::as_str)
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(str::to_owned)
-    };
-    let read_bool = |key: &str| -> Option<bool> {
-        branding
-            .and_then(|value| value.get(key))
-            .or_else(|| payload.get(key))
-            .and_then(serde_json::Value::as_bool)
-    };
-
-    let company_name = read_string("company_name").unwrap_or_else(|| "Quotey".to_string());
-    let company_logo = read_string("company_logo");
-    let company_address = read_string("company_address");
-    let company_email = read_string("company_email");
-    let support_email = read_string("support_email")
-        .or_else(|| read_string("contact_email"))
-        .or(company_email.clone());
-    let sender_name = read_string("sender_name").or_else(|| read_string("contact_name"));
-    let company_phone = read_string("company_phone");
-    let primary_color = read_string("primary_color").unwrap_or_else(|| "#2563eb".to_string());
-    let secondary_color = read_string("secondary_color").unwrap_or_else(|| "#1e40af".to_string());
-    let accent_color = read_string("accent_color").unwrap_or_else(|| "#3b82f6".to_string());
-    let footer_text = read_string("footer_text");
-    let terms_footer = read_string("terms_footer")
-        .or_else(|| read_string("custom_terms_footer"))
-        .or_else(|| footer_text.clone());
-    let white_label = read_bool("white_label").unwrap_or(false);
-
-    let support_contact_name = sender_name.clone().or_else(|| {
-        payload
-            .get("sales_rep")
-            .and_then(|value| value.get("name"))
-            .and_then(serde_json::Value::as_str)
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(str::to_owned)
-    });
-    let support_contact_email = support_email.clone().or_else(|| {
-        payload
-            .get("sales_rep")
-            .and_then(|value| value.get("email"))
-            .and_then(serde_json::Value::as_str)
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(str::to_owned)
-    });
-
-    context.insert("company_name", &company_name);
-    context.insert("company_logo", &company_logo);
-    context.insert("company_address", &company_address);
-    context.insert("company_email", &company_email);
-    context.insert("support_email", &support_email);
-    context.insert("sender_name", &sender_name);
-    context.insert("company_phone", &company_phone);
-    context.insert("primary_color", &primary_color);
-    context.insert("secondary_color", &secondary_color);
-    context.insert("accent_color", &accent_color);
-    context.insert("footer_text", &footer_text);
-    context.insert("terms_footer", &terms_footer);
-    context.insert(
-        "support_contact_name",
-        &support_contact_name.clone().unwrap_or_else(|| "your sales representative".to_string()),
-    );
-    context.insert(
-        "support_contact_email",
-        &support_contact_email.clone().unwrap_or_else(|| "sales@example.com".to_string()),
-    );
-    context.insert("white_label", &white_label);
-    context.insert(
-        "branding",
-        &serde_json::json!({
-            "company_name": company_name.clone(),
-            "logo_url": company_logo.clone(),
-            "company_logo": company_logo.clone(),
-            "company_address": company_address.clone(),
-            "company_email": company_email.clone(),
-            "contact_email": support_email.clone(),
-            "support_email": support_email.clone(),
-            "company_phone": company_phone.clone(),
-            "primary_color": primary_color.clone(),
-            "secondary_color": secondary_color.clone(),
-            "accent_color": accent_color.clone(),
-            "footer_text": footer_text.clone(),
-            "terms_footer": terms_footer.clone(),
-            "sender_name": sender_name.clone(),
-            "white_label": white_label,
-        }),
-    );
-
-    let template_name = format!("{}.html.tera", template);
-    let html = tera.render(&template_name, &context).map_err(|e| e.to_string())?;
-
-    let temp_dir = std::env::temp_dir();
-    let html_path: PathBuf = temp_dir.join(format!("quotey-mcp-{}.html", uuid::Uuid::new_v4()));
-    let pdf_path: PathBuf = temp_dir.join(format!("quotey-mcp-{}.pdf", uuid::Uuid::new_v4()));
-    tokio::fs::write(&html_path, html.as_bytes()).await.map_err(|e| e.to_string())?;
-
-    let output = Command::new("wkhtmltopdf")
-        .arg("--page-size")
-        .arg("A4")
-        .arg("--margin-top")
-        .arg("10mm")
-        .arg("--margin-bottom")
-        .arg("10mm")
-        .arg("--margin-left")
-        .arg("10mm")
-        .arg("--margin-right")
-        .arg("10mm")
-        .arg("--encoding")
-        .arg("utf-8")
-        .arg("--enable-local-file-access")
-        .arg(&html_path)
-        .arg(&pdf_path)
-        .output()
-        .await;
-
-    async fn cleanup(html_path: &PathBuf, pdf_path: &PathBuf) {
-        let _ = tokio::fs::remove_file(html_path).await;
-        let _ = tokio::fs::remove_file(pdf_path).await;
-    }
-
-    match output {
-        Ok(result) if result.status.success() => {
-            let pdf_bytes = match tokio::fs::read(&pdf_path).await {
-                Ok(bytes) => bytes,
-                Err(read_err) => {
-                    warn!(error = %read_err, "quote_pdf: failed to read generated PDF, returning HTML fallback");
-                    cleanup(&html_path, &pdf_path).await;
-                    return Ok(PdfRenderResult::Html(html));
-                }
-            };
-            cleanup(&html_path, &pdf_path).await;
-            Ok(PdfRenderResult::Pdf(pdf_bytes))
-        }
-        Ok(result) => {
-            let stderr = String::from_utf8_lossy(&result.stderr);
-            warn!(stderr = %stderr, "quote_pdf: wkhtmltopdf returned non-zero status, using HTML fallback");
-            cleanup(&html_path, &pdf_path).await;
-            Ok(PdfRenderResult::Html(html))
-        }
-        Err(err) => {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                warn!("quote_pdf: wkhtmltopdf not found, using HTML fallback");
-            } else {
-                warn!(error = %err, "quote_pdf: wkhtmltopdf command failed, using HTML fallback");
-            }
-            cleanup(&html_path, &pdf_path).await;
-            Ok(PdfRenderResult::Html(html))
-        }
-    }
+    template: PdfTemplate,
+) -> Result<Vec<u8>, PdfRenderError> {
+    render_quote_pdf(payload, template, &PdfBranding::from_payload(payload))
 }
 
-fn payload_to_output_path(quote_id: &str, template: &str, is_pdf: bool) -> (PathBuf, PathBuf) {
+fn payload_to_output_path(quote_id: &str, template: &str) -> (PathBuf, PathBuf) {
     let dir = std::env::temp_dir().join("quotey-mcp").join("quote-pdfs");
-    let extension = if is_pdf { "pdf" } else { "html" };
-    let filename = format!(
-        "quote-{}-{}.{}",
-        sanitize_filename(quote_id),
-        sanitize_filename(template),
-        extension
-    );
+    let filename =
+        format!("quote-{}-{}.pdf", sanitize_filename(quote_id), sanitize_filename(template));
     let path = dir.join(filename);
     (dir, path)
 }
@@ -2478,6 +3053,20 @@ impl QuoteyMcpServer {
                     None
                 };
 
+                use quotey_db::win_probability::WinProbabilityService;
+                let win_probability =
+                    match WinProbabilityService::new(self.db_pool.clone()).predict(&q).await {
+                        Ok(prediction) => prediction.map(|prediction| WinProbabilityInfo {
+                            model_version: prediction.model_version,
+                            probability: prediction.win_probability,
+                            predicted_at: prediction.predicted_at,
+                        }),
+                        Err(e) => {
+                            warn!(error = %e, quote_id = %q.id.0, "win probability not predicted");
+                            None
+                        }
+                    };
+
                 let result = QuoteGetResult {
                     quote: QuoteInfo {
                         id: q.id.0,
@@ -2497,6 +3086,7 @@ impl QuoteyMcpServer {
                     },
                     line_items,
                     pricing,
+                    win_probability,
                 };
 
                 serde_json::to_string_pretty(&result).unwrap_or_default()
@@ -2538,7 +3128,10 @@ impl QuoteyMcpServer {
                 }
             };
 
-        use quotey_core::cpq::policy::{evaluate_policy_with_thresholds, PolicyInput};
+        use quotey_core::cpq::boundary::BoundaryCalculator;
+        use quotey_core::cpq::margin::{apply_category_margin_floors, MarginLine, QuoteMargin};
+        use quotey_core::cpq::policy::PolicyInput;
+        use quotey_core::cpq::policy_rules::{evaluate_policy_with_rule_set, PolicyRuleLine};
         use quotey_core::cpq::pricing::price_quote_with_trace;
         use quotey_core::domain::quote::QuoteId;
         use quotey_db::repositories::QuoteRepository;
@@ -2564,24 +3157,92 @@ impl QuoteyMcpServer {
         // Run deterministic policy engine
         let discount_pct = Decimal::from_f64(requested_discount_pct).unwrap_or(Decimal::ZERO);
         let deal_value_dec = pricing_result.total;
-        // Estimate margin: if no discount requested, margin is 100%; otherwise approximate
-        let margin_pct = if deal_value_dec > Decimal::ZERO {
-            let discount_amount = deal_value_dec * discount_pct / Decimal::from(100);
-            let net = deal_value_dec - discount_amount;
-            (net * Decimal::from(100)) / deal_value_dec
-        } else {
-            Decimal::from(100)
-        };
+        // Margin over product cost of the discounted lines; uncosted lines are left out
+        let margin_lines: Vec<MarginLine> = quote
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let line_discount_pct = if requested_discount_pct > 0.0 {
+                    discount_pct
+                } else {
+                    Decimal::from_f64(line.discount_pct).unwrap_or(Decimal::ZERO)
+                };
+                let line_subtotal = line.unit_price * Decimal::from(line.quantity);
+                MarginLine {
+                    line_id: format!("{}-ql-{}", quote.id.0, i + 1),
+                    product_id: line.product_id.0.clone(),
+                    quantity: line.quantity,
+                    net_amount: line_subtotal
+                        - line_subtotal * line_discount_pct / Decimal::from(100),
+                }
+            })
+            .collect();
+        let unit_costs =
+            match quotey_db::repositories::SqlProductCostRepository::new(self.db_pool.clone())
+                .unit_costs_for_quote(&quote_id, chrono::Utc::now().date_naive())
+                .await
+            {
+                Ok(unit_costs) => unit_costs,
+                Err(e) => {
+                    warn!(error = %e, "quote_price: product costs unavailable");
+                    std::collections::BTreeMap::new()
+                }
+            };
+        let margin = QuoteMargin::compute(&margin_lines, &unit_costs);
 
         let policy_input = PolicyInput {
             requested_discount_pct: discount_pct,
             deal_value: deal_value_dec,
-            minimum_margin_pct: margin_pct,
+            minimum_margin_pct: margin.margin_pct,
         };
 
-        // Load policy thresholds from org_settings, falling back to defaults
-        let thresholds = load_policy_thresholds(&self.db_pool).await;
-        let policy_decision = evaluate_policy_with_thresholds(&policy_input, &thresholds);
+        // Thresholds of the policy version this quote falls under plus the scoped rules of the
+        // newest rule set
+        let (thresholds, policy_version) = match load_quote_policy(&self.db_pool, &quote_id).await {
+            Ok(policy) => policy,
+            Err(e) => {
+                warn!(error = %e, quote_id, "quote_price: quote policy unavailable");
+                return internal_tool_error(&e);
+            }
+        };
+        let rule_lines: Vec<PolicyRuleLine> = quote
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let discount = if requested_discount_pct > 0.0 {
+                    requested_discount_pct
+                } else {
+                    line.discount_pct
+                };
+                PolicyRuleLine {
+                    line_id: format!("{}-ql-{}", quote.id.0, i + 1),
+                    product_id: line.product_id.0.clone(),
+                    discount_pct: Decimal::from_f64(discount).unwrap_or(Decimal::ZERO),
+                }
+            })
+            .collect();
+        let (rule_set, rule_context) = match load_policy_rules(&self.db_pool, &quote_id).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!(error = %e, quote_id, "quote_price: policy rules unavailable");
+                return internal_tool_error(&e);
+            }
+        };
+        let mut policy_decision = evaluate_policy_with_rule_set(
+            &policy_input,
+            &thresholds,
+            &rule_set,
+            &rule_context,
+            &rule_lines,
+        );
+        apply_category_margin_floors(
+            &mut policy_decision,
+            &BoundaryCalculator::default(),
+            &margin,
+            &rule_context.product_families,
+        );
 
         // Build per-line pricing
         let product_repo = quotey_db::repositories::SqlProductRepository::new(self.db_pool.clone());
@@ -2617,6 +3278,8 @@ impl QuoteyMcpServer {
                 discount_pct: effective_discount,
                 discount_amount,
                 line_total: line_subtotal - discount_amount,
+                cost: margin.lines[i].cost.map(|cost| decimal_to_f64(&cost)),
+                margin_pct: margin.lines[i].margin_pct.map(|pct| decimal_to_f64(&pct)),
             });
         }
 
@@ -2655,6 +3318,12 @@ impl QuoteyMcpServer {
                 priced_at: Some(chrono::Utc::now().to_rfc3339()),
             },
             line_pricing,
+            margin: MarginInfo {
+                cost_total: decimal_to_f64(&margin.cost),
+                margin_total: decimal_to_f64(&margin.margin),
+                margin_pct: margin.margin_pct.map(|pct| decimal_to_f64(&pct)),
+                uncosted_line_ids: margin.uncosted_line_ids.clone(),
+            },
             approval_required: policy_decision.approval_required,
             policy_violations,
         };
@@ -2669,9 +3338,231 @@ impl QuoteyMcpServer {
         } else {
             format!("Quote priced at ${:.2}. No policy violations.", result.pricing.total)
         };
-        auto_comment(&self.db_pool, &quote_id, "pricing_rendered", &comment_body).await;
+        auto_comment(&self.db_pool, &quote_id, "pricing_rendered", &comment_body).await;
+        record_pricing_snapshot(
+            &self.db_pool,
+            &quote,
+            &result,
+            &policy_input,
+            &thresholds,
+            &policy_decision,
+            Some(policy_version),
+        )
+        .await;
+
+        serde_json::to_string_pretty(&result).unwrap_or_default()
+    }
+
+    #[tool(
+        description = "Explain why a quote total, a line amount or a policy verdict is what it is, citing the persisted pricing snapshot and policy evaluation"
+    )]
+    pub async fn quote_explain(&self, Parameters(input): Parameters<QuoteExplainInput>) -> String {
+        debug!(quote_id = %input.quote_id, "quote_explain called");
+        let quote_id_for_audit = input.quote_id.trim().to_string();
+        self.record_mcp_audit_event(
+            "quote_explain",
+            if quote_id_for_audit.is_empty() { None } else { Some(quote_id_for_audit.as_str()) },
+            serde_json::json!({
+                "quote_id": &input.quote_id,
+                "target": &input.target,
+                "line_id": &input.line_id,
+                "version": input.version
+            }),
+        )
+        .await;
+
+        let quote_id = match normalize_id(&input.quote_id, "quote_id") {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+
+        use quotey_db::explain::{ExplainError, ExplainQuery, ExplainService, ExplainTarget};
+
+        let Some(target) = ExplainTarget::parse(input.target.as_deref(), input.line_id.as_deref())
+        else {
+            return tool_error(
+                "VALIDATION_ERROR",
+                "target must be one of total, line or policy; line requires line_id",
+                None,
+            );
+        };
+
+        let query = ExplainQuery {
+            quote_id: quotey_core::domain::quote::QuoteId(quote_id.clone()),
+            target,
+            version: input.version,
+            actor_type: "agent".to_string(),
+            actor_id: "mcp".to_string(),
+            thread_id: format!("mcp:{quote_id}"),
+            correlation_id: format!("mcp-explain-{}", uuid::Uuid::new_v4()),
+        };
+
+        match ExplainService::new(self.db_pool.clone()).explain(query).await {
+            Ok(explanation) => serde_json::to_string_pretty(&explanation).unwrap_or_default(),
+            Err(ExplainError::QuoteNotFound(id)) => {
+                tool_error("NOT_FOUND", &format!("Quote '{id}' not found"), None)
+            }
+            Err(error @ ExplainError::LineNotFound { .. }) => {
+                tool_error("NOT_FOUND", &error.to_string(), None)
+            }
+            Err(error @ ExplainError::MissingEvidence(_)) => tool_error(
+                "MISSING_EVIDENCE",
+                &error.to_string(),
+                Some(serde_json::json!({ "hint": "Run quote_price to record a pricing snapshot" })),
+            ),
+            Err(error) => {
+                warn!(error = %error, "quote_explain: explanation failed");
+                internal_tool_error(&error)
+            }
+        }
+    }
+
+    #[tool(
+        description = "Simulate up to three what-if scenarios (seat, price or discount changes) against a quote; runs them through CPQ pricing and policy and stores the ranked comparison"
+    )]
+    pub async fn quote_simulate(
+        &self,
+        Parameters(input): Parameters<QuoteSimulateInput>,
+    ) -> String {
+        debug!(quote_id = %input.quote_id, variants = input.variants.len(), "quote_simulate called");
+        let quote_id_for_audit = input.quote_id.trim().to_string();
+        self.record_mcp_audit_event(
+            "quote_simulate",
+            if quote_id_for_audit.is_empty() { None } else { Some(quote_id_for_audit.as_str()) },
+            serde_json::json!({
+                "quote_id": &input.quote_id,
+                "variant_keys": input.variants.iter().map(|v| v.key.as_str()).collect::<Vec<_>>(),
+                "discount_pct": input.discount_pct
+            }),
+        )
+        .await;
+
+        let quote_id = match normalize_id(&input.quote_id, "quote_id") {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        let variations = match scenario_variations(&input.variants) {
+            Ok(variations) => variations,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        let requested_discount_pct = match input.discount_pct.map(scenario_pct).transpose() {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+
+        use quotey_db::simulate::{SimulateQuery, SimulationError, SimulationService};
+
+        let thresholds = load_policy_thresholds(&self.db_pool).await;
+        let query = SimulateQuery {
+            quote_id: quotey_core::domain::quote::QuoteId(quote_id.clone()),
+            variations,
+            requested_discount_pct,
+            actor_type: "agent".to_string(),
+            actor_id: "mcp".to_string(),
+            thread_id: format!("mcp:{quote_id}"),
+            correlation_id: format!("mcp-simulate-{}", uuid::Uuid::new_v4()),
+        };
+        match SimulationService::new(self.db_pool.clone(), thresholds).simulate(query).await {
+            Ok(run) => serde_json::to_string_pretty(&run).unwrap_or_default(),
+            Err(SimulationError::QuoteNotFound(id)) => {
+                tool_error("NOT_FOUND", &format!("Quote '{id}' not found"), None)
+            }
+            Err(error @ SimulationError::Guardrail { .. }) => tool_error(
+                "GUARDRAIL_REJECTED",
+                &error.to_string(),
+                match &error {
+                    SimulationError::Guardrail { run_id: Some(run_id), .. } => {
+                        Some(serde_json::json!({ "run_id": run_id }))
+                    }
+                    _ => None,
+                },
+            ),
+            Err(error) => {
+                warn!(error = %error, "quote_simulate: simulation failed");
+                internal_tool_error(&error)
+            }
+        }
+    }
+
+    #[tool(
+        description = "Promote one variant of a quote_simulate run into a new quote revision, linked back to the scenario run in the audit trail"
+    )]
+    pub async fn quote_simulate_promote(
+        &self,
+        Parameters(input): Parameters<QuoteSimulatePromoteInput>,
+    ) -> String {
+        debug!(quote_id = %input.quote_id, run_id = %input.run_id, "quote_simulate_promote called");
+        let quote_id_for_audit = input.quote_id.trim().to_string();
+        self.record_mcp_audit_event(
+            "quote_simulate_promote",
+            if quote_id_for_audit.is_empty() { None } else { Some(quote_id_for_audit.as_str()) },
+            serde_json::json!({
+                "quote_id": &input.quote_id,
+                "run_id": &input.run_id,
+                "variant": &input.variant
+            }),
+        )
+        .await;
+
+        let quote_id = match normalize_id(&input.quote_id, "quote_id") {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        let run_id = match normalize_id(&input.run_id, "run_id") {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        let variant = match normalize_id(&input.variant, "variant") {
+            Ok(value) => value,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
 
-        serde_json::to_string_pretty(&result).unwrap_or_default()
+        use quotey_db::simulate::{PromoteQuery, SimulationError, SimulationService};
+
+        let thresholds = load_policy_thresholds(&self.db_pool).await;
+        let query = PromoteQuery {
+            quote_id: quotey_core::domain::quote::QuoteId(quote_id.clone()),
+            run_id,
+            variant,
+            actor_type: "agent".to_string(),
+            actor_id: "mcp".to_string(),
+            correlation_id: format!("mcp-simulate-promote-{}", uuid::Uuid::new_v4()),
+        };
+        match SimulationService::new(self.db_pool.clone(), thresholds).promote(query).await {
+            Ok(promotion) => {
+                auto_comment(
+                    &self.db_pool,
+                    &quote_id,
+                    "simulation_promoted",
+                    &format!(
+                        "Scenario '{}' promoted to quote version {}.",
+                        promotion.variant_key, promotion.version
+                    ),
+                )
+                .await;
+                serde_json::to_string_pretty(&promotion).unwrap_or_default()
+            }
+            Err(
+                error @ (SimulationError::QuoteNotFound(_)
+                | SimulationError::RunNotFound(_)
+                | SimulationError::VariantNotFound { .. }),
+            ) => tool_error("NOT_FOUND", &error.to_string(), None),
+            Err(
+                error @ (SimulationError::NotPromotable { .. }
+                | SimulationError::StaleRun { .. }
+                | SimulationError::QuoteClosed(_)),
+            ) => tool_error(
+                "CONFLICT",
+                &error.to_string(),
+                Some(
+                    serde_json::json!({ "hint": "Run quote_simulate again on the current quote" }),
+                ),
+            ),
+            Err(error) => {
+                warn!(error = %error, "quote_simulate_promote: promotion failed");
+                internal_tool_error(&error)
+            }
+        }
     }
 
     #[tool(description = "List quotes with optional filters")]
@@ -2827,6 +3718,7 @@ impl QuoteyMcpServer {
             quote.status,
             QuoteStatus::Approved
                 | QuoteStatus::Sent
+                | QuoteStatus::Accepted
                 | QuoteStatus::Expired
                 | QuoteStatus::Cancelled
         ) {
@@ -3099,7 +3991,7 @@ impl QuoteyMcpServer {
         } else {
             input.template.trim().to_string()
         };
-        if !template_is_allowed(&template) {
+        let Some(pdf_template) = PdfTemplate::parse(&template) else {
             return tool_error(
                 "VALIDATION_ERROR",
                 &format!(
@@ -3109,7 +4001,7 @@ impl QuoteyMcpServer {
                 ),
                 None,
             );
-        }
+        };
 
         use quotey_core::domain::quote::QuoteId;
         use quotey_db::repositories::QuoteRepository;
@@ -3127,43 +4019,26 @@ impl QuoteyMcpServer {
         };
 
         let payload = build_pdf_quote_payload(&quote);
-        let render_result = match render_quote_pdf_html_to_bytes(&payload, &template).await {
-            Ok(rendered) => rendered,
+        let bytes = match render_quote_pdf_bytes(&payload, pdf_template) {
+            Ok(bytes) => bytes,
             Err(err) => {
-                warn!(error = %err, "quote_pdf: failed to render PDF html");
+                warn!(error = %err, "quote_pdf: failed to render PDF");
                 return internal_tool_error(&err);
             }
         };
 
         let generated_at = chrono::Utc::now().to_rfc3339();
-        let (_dir, file_path, pdf_generated, file_size_bytes, checksum) = match &render_result {
-            PdfRenderResult::Pdf(bytes) => {
-                let (dir, file_path) = payload_to_output_path(&quote_id, &template, true);
-                if let Err(e) = tokio::fs::create_dir_all(&dir).await {
-                    warn!(error = %e, "quote_pdf: failed to create output directory");
-                    return internal_tool_error(&e);
-                }
-                if let Err(e) = tokio::fs::write(&file_path, bytes).await {
-                    warn!(error = %e, "quote_pdf: failed to write artifact");
-                    return internal_tool_error(&e);
-                }
-                let checksum = checksum_of_bytes(bytes);
-                (dir, file_path, true, bytes.len() as u64, checksum)
-            }
-            PdfRenderResult::Html(html) => {
-                let (dir, file_path) = payload_to_output_path(&quote_id, &template, false);
-                if let Err(e) = tokio::fs::create_dir_all(&dir).await {
-                    warn!(error = %e, "quote_pdf: failed to create output directory");
-                    return internal_tool_error(&e);
-                }
-                if let Err(e) = tokio::fs::write(&file_path, html.as_bytes()).await {
-                    warn!(error = %e, "quote_pdf: failed to write artifact");
-                    return internal_tool_error(&e);
-                }
-                let checksum = checksum_of(html);
-                (dir, file_path, false, html.len() as u64, checksum)
-            }
-        };
+        let (dir, file_path) = payload_to_output_path(&quote_id, &template);
+        if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+            warn!(error = %e, "quote_pdf: failed to create output directory");
+            return internal_tool_error(&e);
+        }
+        if let Err(e) = tokio::fs::write(&file_path, &bytes).await {
+            warn!(error = %e, "quote_pdf: failed to write artifact");
+            return internal_tool_error(&e);
+        }
+        let checksum = checksum_of_bytes(&bytes);
+        let file_size_bytes = bytes.len() as u64;
 
         if let Some(parent) = file_path.parent() {
             debug!(event_name = "quote_pdf.output_dir", path = %parent.display(), "PDF output prepared");
@@ -3172,7 +4047,7 @@ impl QuoteyMcpServer {
         let file_path = file_path.to_string_lossy().to_string();
         let result = QuotePdfResult {
             quote_id,
-            pdf_generated,
+            pdf_generated: true,
             file_path,
             file_size_bytes,
             checksum,
@@ -3444,12 +4319,17 @@ impl QuoteyMcpServer {
             &quote_id[quote_id.len().saturating_sub(4)..]
         );
 
+        // Sessions carry the policy version they negotiate under so canary KPIs can be split out.
+        let policy_version = match load_quote_policy(&self.db_pool, &quote_id).await {
+            Ok((_, version)) => version,
+            Err(e) => return internal_tool_error(&e),
+        };
         let session = quotey_core::domain::negotiation::NegotiationSession {
             id: quotey_core::NegotiationSessionId(session_id.clone()),
             quote_id: quote_id.clone(),
             actor_id: actor_id.clone(),
             state: quotey_core::NegotiationState::Draft,
-            policy_version: "policy-v1".to_string(),
+            policy_version: quotey_db::policy_apply::policy_version_label(policy_version),
             pricing_version: "pricing-v1".to_string(),
             idempotency_key,
             max_turns: 20,
@@ -3622,6 +4502,7 @@ impl QuoteyMcpServer {
             },
         });
 
+        self.notify_quote_changed(&session.quote_id).await;
         serde_json::to_string_pretty(&result).unwrap_or_default()
     }
 
@@ -3830,9 +4711,122 @@ impl QuoteyMcpServer {
             "message": "Session escalated to approval_pending. Approval workflow will evaluate the evidence packet.",
         });
 
+        self.notify_quote_changed(&pack.quote_id).await;
         serde_json::to_string_pretty(&result).unwrap_or_default()
     }
 
+    #[tool(
+        description = "Record a counteroffer on a negotiation session. The counter is checked against the concession envelope and the margin/discount boundaries: out-of-bounds counters are recorded as rejected, soft-zone counters send the session to approval."
+    )]
+    pub async fn negotiation_counter(
+        &self,
+        Parameters(input): Parameters<NegotiationCounterInput>,
+    ) -> String {
+        debug!(session_id = %input.session_id, "negotiation_counter called");
+        self.record_mcp_audit_event(
+            "negotiation_counter",
+            None,
+            serde_json::json!({
+                "session_id": &input.session_id,
+                "discount_pct": input.discount_pct,
+                "term_months": input.term_months,
+                "party": &input.party,
+            }),
+        )
+        .await;
+
+        let party = match input.party.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            None => quotey_core::cpq::negotiation_turn::TurnParty::Rep,
+            Some(raw) => match quotey_core::cpq::negotiation_turn::TurnParty::parse_label(raw) {
+                Some(party) => party,
+                None => {
+                    return tool_error(
+                        "VALIDATION_ERROR",
+                        "party must be 'rep' or 'customer'",
+                        None,
+                    )
+                }
+            },
+        };
+        let terms = quotey_core::cpq::negotiation_turn::CounterTerms {
+            discount_pct: input.discount_pct,
+            term_months: input.term_months,
+        };
+        self.record_negotiation_turn(
+            "negotiation_counter",
+            quotey_db::negotiation::RecordTurn {
+                session_id: input.session_id,
+                action: quotey_db::negotiation::TurnAction::Counter(terms),
+                party,
+                actor_id: input.actor_id,
+                note: input.note,
+                idempotency_key: input.idempotency_key,
+                correlation_id: String::new(),
+            },
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Accept the pending counteroffer of a negotiation session. The counter's terms are applied to the quote as a new revision and the session closes as accepted."
+    )]
+    pub async fn negotiation_accept(
+        &self,
+        Parameters(input): Parameters<NegotiationAcceptInput>,
+    ) -> String {
+        debug!(session_id = %input.session_id, "negotiation_accept called");
+        self.record_mcp_audit_event(
+            "negotiation_accept",
+            None,
+            serde_json::json!({ "session_id": &input.session_id }),
+        )
+        .await;
+
+        self.record_negotiation_turn(
+            "negotiation_accept",
+            quotey_db::negotiation::RecordTurn {
+                session_id: input.session_id,
+                action: quotey_db::negotiation::TurnAction::Accept,
+                party: quotey_core::cpq::negotiation_turn::TurnParty::Rep,
+                actor_id: input.actor_id,
+                note: input.note,
+                idempotency_key: input.idempotency_key,
+                correlation_id: String::new(),
+            },
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Reject a negotiation session with a reason. The session closes as rejected."
+    )]
+    pub async fn negotiation_reject(
+        &self,
+        Parameters(input): Parameters<NegotiationRejectInput>,
+    ) -> String {
+        debug!(session_id = %input.session_id, "negotiation_reject called");
+        self.record_mcp_audit_event(
+            "negotiation_reject",
+            None,
+            serde_json::json!({ "session_id": &input.session_id, "reason": &input.reason }),
+        )
+        .await;
+
+        self.record_negotiation_turn(
+            "negotiation_reject",
+            quotey_db::negotiation::RecordTurn {
+                session_id: input.session_id,
+                action: quotey_db::negotiation::TurnAction::Reject { reason: input.reason },
+                party: quotey_core::cpq::negotiation_turn::TurnParty::Rep,
+                actor_id: input.actor_id,
+                note: None,
+                idempotency_key: input.idempotency_key,
+                correlation_id: String::new(),
+            },
+        )
+        .await
+    }
+
     // ── Sales Rep Tools ─────────────────────────────────────────────────
 
     #[tool(description = "Get a sales rep profile by ID or external user reference")]
@@ -4775,6 +5769,219 @@ impl QuoteyMcpServer {
         .unwrap_or_default()
     }
 
+    // -----------------------------------------------------------------------
+    // Analytics tools
+    // -----------------------------------------------------------------------
+
+    #[tool(
+        name = "analytics_query",
+        description = "Aggregate quote metrics (quote_count, win_rate_pct, avg_discount_pct, avg_deal_value, approval_cycle_hours, time_to_finalize_hours, anomaly_rate_pct) grouped by dimensions (day, week, month, quarter, customer_segment, industry, region, sales_rep, product_family, approval_role) over a lookback window, with optional dimension filters and a row limit."
+    )]
+    pub async fn analytics_query(
+        &self,
+        Parameters(input): Parameters<AnalyticsQueryInput>,
+    ) -> String {
+        use quotey_core::{AnalyticsFilter, AnalyticsQuerySpec};
+        use quotey_db::analytics::{AnalyticsService, AnalyticsServiceError};
+
+        let mut spec = match AnalyticsQuerySpec::from_labels(
+            &input.metrics,
+            &input.dimensions,
+            input.lookback_days,
+        ) {
+            Ok(spec) => spec,
+            Err(e) => return tool_error("VALIDATION_ERROR", &e.to_string(), None),
+        };
+        spec.include_only_finalized = input.include_only_finalized;
+        spec.filters = match input
+            .filters
+            .into_iter()
+            .map(|filter| AnalyticsFilter::from_label(&filter.dimension, filter.values))
+            .collect::<Result<_, _>>()
+        {
+            Ok(filters) => filters,
+            Err(e) => return tool_error("VALIDATION_ERROR", &e.to_string(), None),
+        };
+        if let Some(raw) = input.end_date.as_deref().map(str::trim) {
+            match chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+                Ok(date) => spec.end_date = Some(date),
+                Err(_) => {
+                    return tool_error(
+                        "VALIDATION_ERROR",
+                        &format!("end_date '{raw}' must be YYYY-MM-DD"),
+                        None,
+                    );
+                }
+            }
+        }
+        spec.limit = input.limit;
+
+        match AnalyticsService::new(self.db_pool.clone())
+            .with_cache(self.analytics_cache.clone())
+            .run(&spec)
+            .await
+        {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_default(),
+            Err(AnalyticsServiceError::Invalid(e)) => {
+                tool_error("VALIDATION_ERROR", &e.to_string(), None)
+            }
+            Err(e) => internal_tool_error(&e),
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Webhook tools
+    // -----------------------------------------------------------------------
+
+    #[tool(
+        name = "webhook_subscribe",
+        description = "Subscribe a URL to quote lifecycle events (quote.finalized, quote.accepted, approval.decided, portal.comment_added, or wildcards like quote.*). Returns the signing secret once."
+    )]
+    pub async fn webhook_subscribe(
+        &self,
+        Parameters(input): Parameters<WebhookSubscribeInput>,
+    ) -> String {
+        let created_by = input
+            .actor
+            .as_deref()
+            .map(str::trim)
+            .filter(|actor| !actor.is_empty())
+            .unwrap_or("mcp")
+            .to_string();
+        let created = match quotey_db::webhooks::WebhookService::new(self.db_pool.clone())
+            .subscribe(quotey_db::webhooks::NewWebhookSubscription {
+                url: input.url,
+                events: input.events,
+                description: input
+                    .description
+                    .map(|description| description.trim().to_string())
+                    .filter(|description| !description.is_empty()),
+                created_by,
+            })
+            .await
+        {
+            Ok(created) => created,
+            Err(e) => return webhook_tool_error(e),
+        };
+        serde_json::to_string_pretty(&created).unwrap_or_default()
+    }
+
+    #[tool(
+        name = "webhook_list",
+        description = "List webhook subscriptions, newest first. Secrets are never included."
+    )]
+    pub async fn webhook_list(&self, Parameters(input): Parameters<WebhookListInput>) -> String {
+        match quotey_db::webhooks::WebhookService::new(self.db_pool.clone())
+            .list_subscriptions(input.include_disabled)
+            .await
+        {
+            Ok(subscriptions) => serde_json::to_string_pretty(&serde_json::json!({
+                "count": subscriptions.len(),
+                "subscriptions": subscriptions,
+            }))
+            .unwrap_or_default(),
+            Err(e) => webhook_tool_error(e),
+        }
+    }
+
+    #[tool(
+        name = "webhook_disable",
+        description = "Disable a webhook subscription. Queued deliveries for it are cancelled instead of sent."
+    )]
+    pub async fn webhook_disable(
+        &self,
+        Parameters(input): Parameters<WebhookDisableInput>,
+    ) -> String {
+        match quotey_db::webhooks::WebhookService::new(self.db_pool.clone())
+            .disable(&input.subscription_id)
+            .await
+        {
+            Ok(subscription) => serde_json::to_string_pretty(&subscription).unwrap_or_default(),
+            Err(e) => webhook_tool_error(e),
+        }
+    }
+
+    #[tool(
+        name = "webhook_deliveries",
+        description = "Show the delivery log of a webhook subscription, newest first, optionally filtered by status. Pass delivery_id to get one delivery with every attempt (status code, response excerpt, error, duration)."
+    )]
+    pub async fn webhook_deliveries(
+        &self,
+        Parameters(input): Parameters<WebhookDeliveriesInput>,
+    ) -> String {
+        use quotey_db::webhooks::{DeliveryQuery, DeliveryStatus, WebhookServiceError};
+
+        let service = quotey_db::webhooks::WebhookService::new(self.db_pool.clone());
+        let subscription = match service.subscription(&input.subscription_id).await {
+            Ok(subscription) => subscription,
+            Err(e) => return webhook_tool_error(e),
+        };
+        if let Some(delivery_id) = input.delivery_id.as_deref() {
+            return match service.delivery_log(delivery_id).await {
+                Ok(log) if log.delivery.subscription_id == subscription.id => {
+                    serde_json::to_string_pretty(&log).unwrap_or_default()
+                }
+                Ok(_) => webhook_tool_error(WebhookServiceError::DeliveryNotFound(
+                    delivery_id.trim().to_string(),
+                )),
+                Err(e) => webhook_tool_error(e),
+            };
+        }
+        let status = match input.status.as_deref().map(str::trim).filter(|raw| !raw.is_empty()) {
+            Some(raw) => match DeliveryStatus::parse_label(raw) {
+                Some(status) => Some(status),
+                None => {
+                    return tool_error(
+                        "VALIDATION_ERROR",
+                        &format!("Unknown status '{raw}'. Must be: pending, retrying, delivered, dead_lettered, cancelled"),
+                        None,
+                    );
+                }
+            },
+            None => None,
+        };
+        match service
+            .list_deliveries(&DeliveryQuery {
+                subscription_id: subscription.id,
+                status,
+                before: None,
+                limit: normalize_limit(input.limit),
+            })
+            .await
+        {
+            Ok(deliveries) => serde_json::to_string_pretty(&serde_json::json!({
+                "count": deliveries.len(),
+                "deliveries": deliveries,
+            }))
+            .unwrap_or_default(),
+            Err(e) => webhook_tool_error(e),
+        }
+    }
+
+    #[tool(
+        name = "webhook_redeliver",
+        description = "Queue a fresh delivery of a past webhook event, e.g. after a dead letter. The original body is re-signed when sent."
+    )]
+    pub async fn webhook_redeliver(
+        &self,
+        Parameters(input): Parameters<WebhookRedeliverInput>,
+    ) -> String {
+        let actor = input
+            .actor
+            .as_deref()
+            .map(str::trim)
+            .filter(|actor| !actor.is_empty())
+            .unwrap_or("mcp")
+            .to_string();
+        match quotey_db::webhooks::WebhookService::new(self.db_pool.clone())
+            .redeliver(&input.delivery_id, &actor)
+            .await
+        {
+            Ok(delivery) => serde_json::to_string_pretty(&delivery).unwrap_or_default(),
+            Err(e) => webhook_tool_error(e),
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Audit query tools
     // -----------------------------------------------------------------------
@@ -4856,10 +6063,7 @@ impl QuoteyMcpServer {
         name = "budget_check",
         description = "Check whether a proposed discount fits within a sales rep's monthly discount budget. Returns Ok (under 80%), SoftWarn (80-99%), or HardLimit (100%+, needs manager approval)."
     )]
-    pub async fn budget_check(
-        &self,
-        Parameters(input): Parameters<BudgetCheckInput>,
-    ) -> String {
+    pub async fn budget_check(&self, Parameters(input): Parameters<BudgetCheckInput>) -> String {
         use quotey_core::cpq::discount_budget::check_discount_budget;
         use quotey_db::repositories::SalesRepRepository;
 
@@ -4890,12 +6094,16 @@ impl QuoteyMcpServer {
             quotey_core::cpq::discount_budget::BudgetStatus::Ok { used_pct, remaining_cents } => {
                 ("ok", *used_pct, *remaining_cents, 0i64, String::new())
             }
-            quotey_core::cpq::discount_budget::BudgetStatus::SoftWarn { used_pct, remaining_cents, message } => {
-                ("soft_warn", *used_pct, *remaining_cents, 0i64, message.clone())
-            }
-            quotey_core::cpq::discount_budget::BudgetStatus::HardLimit { used_pct, overage_cents, message } => {
-                ("hard_limit", *used_pct, 0i64, *overage_cents, message.clone())
-            }
+            quotey_core::cpq::discount_budget::BudgetStatus::SoftWarn {
+                used_pct,
+                remaining_cents,
+                message,
+            } => ("soft_warn", *used_pct, *remaining_cents, 0i64, message.clone()),
+            quotey_core::cpq::discount_budget::BudgetStatus::HardLimit {
+                used_pct,
+                overage_cents,
+                message,
+            } => ("hard_limit", *used_pct, 0i64, *overage_cents, message.clone()),
         };
 
         serde_json::to_string_pretty(&serde_json::json!({
@@ -4917,10 +6125,7 @@ impl QuoteyMcpServer {
         name = "budget_status",
         description = "Get a sales rep's current discount budget summary: monthly budget, amount spent, remaining, utilization percentage, and status level."
     )]
-    pub async fn budget_status(
-        &self,
-        Parameters(input): Parameters<BudgetStatusInput>,
-    ) -> String {
+    pub async fn budget_status(&self, Parameters(input): Parameters<BudgetStatusInput>) -> String {
         use quotey_core::cpq::discount_budget::budget_summary;
         use quotey_db::repositories::SalesRepRepository;
 
@@ -4960,10 +6165,7 @@ impl QuoteyMcpServer {
         name = "budget_record",
         description = "Record a discount spend against a sales rep's monthly budget. Atomically updates the spent total and returns the new balance."
     )]
-    pub async fn budget_record(
-        &self,
-        Parameters(input): Parameters<BudgetRecordInput>,
-    ) -> String {
+    pub async fn budget_record(&self, Parameters(input): Parameters<BudgetRecordInput>) -> String {
         let rep_id = input.rep_id.trim();
         if rep_id.is_empty() {
             return tool_error("VALIDATION_ERROR", "rep_id is required", None);
@@ -4976,21 +6178,15 @@ impl QuoteyMcpServer {
         let sid = quotey_core::domain::sales_rep::SalesRepId(rep_id.to_string());
 
         match repo.record_discount_spend(&sid, input.discount_cents).await {
-            Ok(Some(new_total)) => {
-                serde_json::to_string_pretty(&serde_json::json!({
-                    "rep_id": rep_id,
-                    "discount_cents_recorded": input.discount_cents,
-                    "new_spent_total_cents": new_total,
-                    "success": true,
-                }))
-                .unwrap_or_default()
-            }
+            Ok(Some(new_total)) => serde_json::to_string_pretty(&serde_json::json!({
+                "rep_id": rep_id,
+                "discount_cents_recorded": input.discount_cents,
+                "new_spent_total_cents": new_total,
+                "success": true,
+            }))
+            .unwrap_or_default(),
             Ok(None) => {
-                tool_error(
-                    "VALIDATION_ERROR",
-                    &format!("Sales rep '{rep_id}' not found"),
-                    None,
-                )
+                tool_error("VALIDATION_ERROR", &format!("Sales rep '{rep_id}' not found"), None)
             }
             Err(e) => internal_tool_error(&e),
         }
@@ -5019,39 +6215,239 @@ async fn auto_comment(pool: &quotey_db::DbPool, quote_id: &str, event: &str, bod
     }
 }
 
-async fn load_policy_thresholds(
+/// Converts tool scenario inputs into simulator variations.
+fn scenario_variations(
+    variants: &[ScenarioVariantInput],
+) -> Result<Vec<quotey_core::cpq::simulator::ScenarioVariation>, String> {
+    use quotey_core::cpq::simulator::{LineVariation, ScenarioVariation};
+    use quotey_core::domain::product::ProductId;
+    use rust_decimal::prelude::FromPrimitive;
+    use rust_decimal::Decimal;
+
+    variants
+        .iter()
+        .map(|variant| {
+            let line_variations = variant
+                .line_changes
+                .iter()
+                .map(|change| {
+                    let unit_price_override = match change.unit_price {
+                        Some(price) if !price.is_finite() || price < 0.0 => {
+                            return Err(format!(
+                                "unit_price for '{}' must be a non-negative number",
+                                change.product_id
+                            ))
+                        }
+                        Some(price) => Decimal::from_f64(price).map(|d| d.round_dp(2)),
+                        None => None,
+                    };
+                    Ok(LineVariation {
+                        product_id: ProductId(change.product_id.trim().to_string()),
+                        quantity_delta: change.quantity_delta,
+                        unit_price_override,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(ScenarioVariation {
+                variant_key: variant.key.trim().to_string(),
+                line_variations,
+                requested_discount_pct_override: variant
+                    .discount_pct
+                    .map(scenario_pct)
+                    .transpose()?,
+                minimum_margin_pct_override: variant.margin_pct.map(scenario_pct).transpose()?,
+                deal_value_override: None,
+            })
+        })
+        .collect()
+}
+
+fn scenario_pct(value: f64) -> Result<rust_decimal::Decimal, String> {
+    use rust_decimal::prelude::FromPrimitive;
+
+    if !(0.0..=100.0).contains(&value) {
+        return Err("percentages must be between 0 and 100".to_string());
+    }
+    rust_decimal::Decimal::from_f64(value)
+        .map(|pct| pct.round_dp(4))
+        .ok_or_else(|| "percentages must be between 0 and 100".to_string())
+}
+
+/// Persist the priced snapshot and policy verdict so `quote_explain` can cite the exact
+/// arithmetic behind this price. Failures are logged but do not block pricing.
+async fn record_pricing_snapshot(
     pool: &quotey_db::DbPool,
-) -> quotey_core::cpq::policy::PolicyThresholds {
-    use quotey_core::cpq::policy::PolicyThresholds;
-    use quotey_db::repositories::OrgSettingsRepository;
+    quote: &quotey_core::domain::quote::Quote,
+    result: &QuotePriceResult,
+    policy_input: &quotey_core::cpq::policy::PolicyInput,
+    thresholds: &quotey_core::cpq::policy::PolicyThresholds,
+    decision: &quotey_core::cpq::policy::PolicyDecision,
+    policy_version: Option<i32>,
+) {
+    use quotey_core::{policy_evaluation_from_decision, pricing_snapshot_from_lines};
     use rust_decimal::prelude::FromPrimitive;
     use rust_decimal::Decimal;
 
-    let repo = quotey_db::repositories::SqlOrgSettingsRepository::new(pool.clone());
-    let mut thresholds = PolicyThresholds::default();
+    let lines = quote
+        .lines
+        .iter()
+        .zip(&result.line_pricing)
+        .map(|(line, priced)| {
+            let gross = line.unit_price * Decimal::from(line.quantity);
+            let discount_percent =
+                Decimal::from_f64(priced.discount_pct).unwrap_or(Decimal::ZERO).round_dp(4);
+            let discount_amount = (gross * discount_percent / Decimal::from(100)).round_dp(2);
+            quotey_core::PricingLineSnapshot {
+                line_id: priced.line_id.clone(),
+                product_id: priced.product_id.clone(),
+                product_name: priced.product_name.clone(),
+                quantity: i32::try_from(line.quantity).unwrap_or(i32::MAX),
+                unit_price: line.unit_price,
+                discount_percent,
+                discount_amount,
+                line_subtotal: gross - discount_amount,
+            }
+        })
+        .collect();
+
+    let version = i32::try_from(quote.version).unwrap_or(i32::MAX);
+    let priced_at =
+        result.pricing.priced_at.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let snapshot = pricing_snapshot_from_lines(
+        &quote.id,
+        version,
+        &quote.currency,
+        lines,
+        Decimal::ZERO,
+        priced_at.clone(),
+    );
+    let policy = policy_evaluation_from_decision(
+        &quote.id,
+        version,
+        policy_input,
+        thresholds,
+        decision,
+        priced_at,
+    );
+
+    let repo =
+        quotey_db::repositories::SqlPricingSnapshotRepository::with_priced_by(pool.clone(), "mcp")
+            .with_policy_version(policy_version);
+    if let Err(e) = repo.record_snapshot(&snapshot, Some(&policy)).await {
+        warn!(error = %e, quote_id = %quote.id.0, "pricing snapshot not recorded (non-blocking)");
+    }
+}
+
+/// Thresholds of the active policy version, falling back to the engine defaults.
+async fn load_policy_thresholds(
+    pool: &quotey_db::DbPool,
+) -> quotey_core::cpq::policy::PolicyThresholds {
+    match quotey_db::policy_apply::PolicyApplyService::new(pool.clone()).active_version().await {
+        Ok(version) => version.thresholds,
+        Err(e) => {
+            warn!(error = %e, "active policy version unavailable; using default thresholds");
+            quotey_core::cpq::policy::PolicyThresholds::default()
+        }
+    }
+}
+
+impl QuoteyMcpServer {
+    /// Records a negotiation turn through the shared service and renders the tool result.
+    async fn record_negotiation_turn(
+        &self,
+        tool: &str,
+        mut request: quotey_db::negotiation::RecordTurn,
+    ) -> String {
+        use quotey_db::negotiation::{NegotiationError, NegotiationService};
 
-    if let Ok(Some(s)) = repo.get("require_manager_approval_above_discount_pct").await {
-        if let Some(v) = s.value_as_f64() {
-            // The setting stores a fraction (0.10 = 10%), convert to percentage for the engine
-            if let Some(d) = Decimal::from_f64(v * 100.0) {
-                thresholds.manager_discount_pct = d;
+        request.session_id = match normalize_id(&request.session_id, "session_id") {
+            Ok(v) => v,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        request.actor_id = match normalize_id(&request.actor_id, "actor_id") {
+            Ok(v) => v,
+            Err(msg) => return tool_error("VALIDATION_ERROR", &msg, None),
+        };
+        request.correlation_id = format!("mcp-{tool}-{}", uuid::Uuid::new_v4());
+        match NegotiationService::new(self.db_pool.clone()).record(request).await {
+            Ok(recorded) => {
+                if let Some(revision) = &recorded.revision {
+                    auto_comment(
+                        &self.db_pool,
+                        &revision.quote_id,
+                        "negotiation_accepted",
+                        &format!(
+                            "Counteroffer accepted; quote revised to version {}.",
+                            revision.version
+                        ),
+                    )
+                    .await;
+                }
+                if !recorded.replayed {
+                    self.notify_quote_changed(&recorded.quote_id).await;
+                }
+                serde_json::to_string_pretty(&recorded).unwrap_or_default()
+            }
+            Err(
+                error @ (NegotiationError::SessionNotFound(_) | NegotiationError::QuoteNotFound(_)),
+            ) => tool_error("NOT_FOUND", &error.to_string(), None),
+            Err(error @ NegotiationError::Invalid(_)) => {
+                tool_error("VALIDATION_ERROR", &error.to_string(), None)
+            }
+            Err(
+                error @ (NegotiationError::InvalidState(_)
+                | NegotiationError::MaxTurnsReached(_)
+                | NegotiationError::SessionExpired(_)
+                | NegotiationError::QuoteClosed(_)),
+            ) => tool_error("INVALID_STATE", &error.to_string(), None),
+            Err(error @ NegotiationError::StaleCounter { .. }) => tool_error(
+                "CONFLICT",
+                &error.to_string(),
+                Some(serde_json::json!({ "hint": "Record a new counter on the current quote" })),
+            ),
+            Err(error) => {
+                warn!(error = %error, tool, "negotiation turn failed");
+                internal_tool_error(&error)
             }
         }
     }
+}
 
-    if let Ok(Some(s)) = repo.get("require_finance_approval_above_deal_value_cents").await {
-        if let Some(v) = s.value_as_i64() {
-            thresholds.finance_deal_value_cents = Some(v);
-        }
-    }
-
-    if let Ok(Some(s)) = repo.get("auto_approve_standard_pricing").await {
-        if let Some(v) = s.value_as_bool() {
-            thresholds.auto_approve_clean = v;
-        }
-    }
+/// Thresholds and policy version a stored quote is evaluated under; quotes whose rep or segment
+/// is enrolled in a monitoring canary get the canary's thresholds.
+///
+/// Errors are returned rather than replaced with defaults, so a tool never prices or negotiates
+/// under thresholds other than the quote's own.
+async fn load_quote_policy(
+    pool: &quotey_db::DbPool,
+    quote_id: &str,
+) -> Result<
+    (quotey_core::cpq::policy::PolicyThresholds, i32),
+    quotey_db::policy_apply::PolicyApplyError,
+> {
+    let resolved = quotey_db::policy_apply::PolicyApplyService::new(pool.clone())
+        .resolve_for_quote(quote_id)
+        .await?;
+    Ok((resolved.thresholds, resolved.version))
+}
 
-    thresholds
+/// Scoped rules of the newest published rule set and the quote context they are checked against.
+/// With no published rule set only the thresholds apply; a failed lookup is an error.
+async fn load_policy_rules(
+    pool: &quotey_db::DbPool,
+    quote_id: &str,
+) -> Result<
+    (
+        quotey_core::cpq::policy_rules::PolicyRuleSet,
+        quotey_core::cpq::policy_rules::PolicyRuleContext,
+    ),
+    quotey_db::policy_rules::PolicyRuleSetError,
+> {
+    let as_of = chrono::Utc::now().date_naive();
+    let service = quotey_db::policy_rules::PolicyRuleSetService::new(pool.clone());
+    let rules = service.latest().await?.map(|version| version.rules).unwrap_or_default();
+    let context = service.context_for_quote(quote_id, as_of).await?;
+    Ok((rules, context))
 }
 
 /// Walk the reports_to chain asynchronously, returning the full chain from start upward.
@@ -5487,8 +6883,12 @@ mod tests {
 
     #[test]
     fn auth_context_for_allowed_mcp_call_maps_api_key_to_possession() {
-        let result =
-            AuthResult::Allowed { key_name: "test-key".to_string(), remaining_requests: 9 };
+        let result = AuthResult::Allowed {
+            key_name: "test-key".to_string(),
+            remaining_requests: 9,
+            grants: quotey_core::ApiKeyGrants::unrestricted(),
+            key_id: None,
+        };
         let context = auth_context_for_allowed_mcp_call(&result, Some("secret-api-key"));
         assert_eq!(context.channel, AuthChannel::Mcp);
         assert_eq!(context.method, AuthMethod::ApiKey);
@@ -5500,8 +6900,12 @@ mod tests {
 
     #[test]
     fn auth_context_for_allowed_anonymous_key_stays_anonymous_even_with_presented_key() {
-        let result =
-            AuthResult::Allowed { key_name: "anonymous".to_string(), remaining_requests: u32::MAX };
+        let result = AuthResult::Allowed {
+            key_name: "anonymous".to_string(),
+            remaining_requests: u32::MAX,
+            grants: quotey_core::ApiKeyGrants::unrestricted(),
+            key_id: None,
+        };
         let context = auth_context_for_allowed_mcp_call(&result, Some("ignored-key"));
         assert_eq!(context.channel, AuthChannel::Mcp);
         assert_eq!(context.method, AuthMethod::None);
@@ -5581,6 +6985,7 @@ mod tests {
                 key: "limited-key".to_string(),
                 name: "limited".to_string(),
                 requests_per_minute: 1,
+                grants: None,
             }],
         });
         let srv = QuoteyMcpServer::with_auth(pool, auth);
@@ -5588,9 +6993,12 @@ mod tests {
         let mut meta = rmcp::model::Meta::new();
         meta.0.insert("api_key".to_string(), serde_json::json!("limited-key"));
 
-        assert!(srv.check_auth(&meta).await.is_ok());
+        assert!(srv.check_auth(&meta, tool_permission("catalog_search"), None).await.is_ok());
 
-        let err = srv.check_auth(&meta).await.expect_err("should be rate limited");
+        let err = srv
+            .check_auth(&meta, tool_permission("catalog_search"), None)
+            .await
+            .expect_err("should be rate limited");
         assert_eq!(err.code, rmcp::model::ErrorCode(429));
         let retry_after = auth_denial_u64(&err, "retry_after");
         assert!(retry_after.is_some());
@@ -5610,12 +7018,16 @@ mod tests {
                 key: "required-key".to_string(),
                 name: "required".to_string(),
                 requests_per_minute: 10,
+                grants: None,
             }],
         });
         let srv = QuoteyMcpServer::with_auth(pool, auth);
 
         let meta = rmcp::model::Meta::new();
-        let err = srv.check_auth(&meta).await.expect_err("missing key should fail");
+        let err = srv
+            .check_auth(&meta, tool_permission("catalog_search"), None)
+            .await
+            .expect_err("missing key should fail");
         assert_eq!(auth_denial_str(&err, "reason"), Some("API key required"));
         assert_eq!(auth_denial_str(&err, "code"), Some("missing_credential"));
         assert_eq!(auth_denial_str(&err, "error_code"), Some("AUTHENTICATION_FAILED"));
@@ -5633,13 +7045,17 @@ mod tests {
                 key: "valid-key".to_string(),
                 name: "valid".to_string(),
                 requests_per_minute: 10,
+                grants: None,
             }],
         });
         let srv = QuoteyMcpServer::with_auth(pool, auth);
 
         let mut meta = rmcp::model::Meta::new();
         meta.0.insert("api_key".to_string(), serde_json::json!("wrong-key"));
-        let err = srv.check_auth(&meta).await.expect_err("invalid key should fail");
+        let err = srv
+            .check_auth(&meta, tool_permission("catalog_search"), None)
+            .await
+            .expect_err("invalid key should fail");
 
         assert_eq!(auth_denial_str(&err, "reason"), Some("Invalid API key"));
         assert_eq!(auth_denial_str(&err, "code"), Some("invalid_credential"));
@@ -5657,12 +7073,16 @@ mod tests {
             requests_per_minute: 10,
             created_at: chrono::Utc::now(),
             active: false,
+            grants: quotey_core::ApiKeyGrants::unrestricted(),
         }]);
         let srv = QuoteyMcpServer::with_auth(pool, auth);
 
         let mut meta = rmcp::model::Meta::new();
         meta.0.insert("api_key".to_string(), serde_json::json!("disabled-key"));
-        let err = srv.check_auth(&meta).await.expect_err("deactivated key should fail");
+        let err = srv
+            .check_auth(&meta, tool_permission("catalog_search"), None)
+            .await
+            .expect_err("deactivated key should fail");
 
         assert_eq!(auth_denial_str(&err, "reason"), Some("API key deactivated"));
         assert_eq!(auth_denial_str(&err, "code"), Some("credential_revoked"));
@@ -5671,6 +7091,141 @@ mod tests {
         assert_eq!(auth_denial_u64(&err, "retry_after"), None);
     }
 
+    fn scoped_key_server(
+        pool: quotey_db::DbPool,
+        grants: quotey_core::ApiKeyGrants,
+    ) -> QuoteyMcpServer {
+        let auth = crate::auth::AuthManager::with_keys(vec![crate::auth::ApiKeyEntry {
+            key: "scoped-key".to_string(),
+            name: "scoped".to_string(),
+            requests_per_minute: 100,
+            created_at: chrono::Utc::now(),
+            active: true,
+            grants,
+        }]);
+        QuoteyMcpServer::with_auth(pool, auth)
+    }
+
+    fn scoped_key_meta() -> rmcp::model::Meta {
+        let mut meta = rmcp::model::Meta::new();
+        meta.0.insert("api_key".to_string(), serde_json::json!("scoped-key"));
+        meta
+    }
+
+    #[tokio::test]
+    async fn check_auth_denies_tool_outside_key_scopes_with_403() {
+        let srv = scoped_key_server(
+            test_db().await,
+            quotey_core::ApiKeyGrants {
+                scopes: vec![quotey_core::ApiScope::QuoteWrite],
+                ..Default::default()
+            },
+        );
+        let meta = scoped_key_meta();
+
+        assert!(srv.check_auth(&meta, tool_permission("quote_get"), None).await.is_ok());
+        let err = srv
+            .check_auth(&meta, tool_permission("settings_set"), None)
+            .await
+            .expect_err("settings_set needs settings:admin");
+        assert_eq!(auth_denial_str(&err, "code"), Some("unauthorized_scope"));
+        assert_eq!(auth_denial_str(&err, "error_code"), Some("AUTHORIZATION_FAILED"));
+        assert_eq!(auth_denial_str(&err, "required_scope"), Some("settings:admin"));
+        assert_eq!(auth_denial_str(&err, "key_name"), Some("scoped"));
+        assert_eq!(auth_denial_u64(&err, "http_status"), Some(403));
+
+        let context = auth_context_for_denied_error(&err, Some("scoped-key"));
+        assert_eq!(context.principal.actor_id, "mcp:key:scoped");
+    }
+
+    #[tokio::test]
+    async fn check_auth_enforces_account_restriction_via_quote_lookup() {
+        let pool = test_db().await;
+        seed_quote(&pool, "Q-ACCT-1").await;
+        sqlx::query("UPDATE quote SET account_id = 'acct-allowed' WHERE id = 'Q-ACCT-1'")
+            .execute(&pool)
+            .await
+            .expect("set account");
+        seed_quote(&pool, "Q-ACCT-2").await;
+        sqlx::query("UPDATE quote SET account_id = 'acct-other' WHERE id = 'Q-ACCT-2'")
+            .execute(&pool)
+            .await
+            .expect("set account");
+
+        let srv = scoped_key_server(
+            pool,
+            quotey_core::ApiKeyGrants {
+                scopes: vec![quotey_core::ApiScope::QuoteRead, quotey_core::ApiScope::CatalogRead],
+                account_ids: vec!["acct-allowed".to_string()],
+                team_ids: Vec::new(),
+            },
+        );
+        let meta = scoped_key_meta();
+        let args = |quote_id: &str| {
+            let mut map = serde_json::Map::new();
+            map.insert("quote_id".to_string(), serde_json::json!(quote_id));
+            map
+        };
+
+        let allowed = args("Q-ACCT-1");
+        assert!(srv.check_auth(&meta, tool_permission("quote_get"), Some(&allowed)).await.is_ok());
+
+        let other = args("Q-ACCT-2");
+        let err = srv
+            .check_auth(&meta, tool_permission("quote_get"), Some(&other))
+            .await
+            .expect_err("other account must be denied");
+        assert_eq!(auth_denial_str(&err, "code"), Some("unauthorized_scope"));
+
+        let err = srv
+            .check_auth(&meta, tool_permission("quote_list"), None)
+            .await
+            .expect_err("unscoped listing must be denied for restricted keys");
+        assert_eq!(auth_denial_u64(&err, "http_status"), Some(403));
+
+        assert!(srv.check_auth(&meta, tool_permission("catalog_search"), None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn check_auth_ignores_caller_account_when_the_quote_resolves() {
+        let pool = test_db().await;
+        seed_quote(&pool, "Q-ACCT-OTHER").await;
+        sqlx::query("UPDATE quote SET account_id = 'acct-other' WHERE id = 'Q-ACCT-OTHER'")
+            .execute(&pool)
+            .await
+            .expect("set account");
+        let srv = scoped_key_server(
+            pool,
+            quotey_core::ApiKeyGrants {
+                scopes: vec![quotey_core::ApiScope::QuoteRead, quotey_core::ApiScope::QuoteWrite],
+                account_ids: vec!["acct-allowed".to_string()],
+                team_ids: Vec::new(),
+            },
+        );
+        let meta = scoped_key_meta();
+        let mut spoofed = serde_json::Map::new();
+        spoofed.insert("quote_id".to_string(), serde_json::json!("Q-ACCT-OTHER"));
+        spoofed.insert("account_id".to_string(), serde_json::json!("acct-allowed"));
+
+        for tool in ["quote_get", "quote_price"] {
+            let err = srv
+                .check_auth(&meta, tool_permission(tool), Some(&spoofed))
+                .await
+                .expect_err("another account's quote must stay denied");
+            assert_eq!(auth_denial_str(&err, "code"), Some("unauthorized_scope"));
+            assert_eq!(auth_denial_u64(&err, "http_status"), Some(403));
+        }
+
+        // With nothing to resolve, the caller's account is what gets checked.
+        let mut unresolved = serde_json::Map::new();
+        unresolved.insert("quote_id".to_string(), serde_json::json!("Q-MISSING"));
+        unresolved.insert("account_id".to_string(), serde_json::json!("acct-allowed"));
+        assert!(srv
+            .check_auth(&meta, tool_permission("quote_get"), Some(&unresolved))
+            .await
+            .is_ok());
+    }
+
     fn auth_denial_str<'a>(err: &'a rmcp::ErrorData, key: &'a str) -> Option<&'a str> {
         err.data.as_ref()?.get(key)?.as_str()
     }
@@ -6050,6 +7605,118 @@ mod tests {
         assert!(v["pricing"]["total"].is_number());
     }
 
+    #[tokio::test]
+    async fn quote_changes_made_outside_mcp_reach_subscribers_on_the_next_poll() {
+        let pool = test_db().await;
+        seed_product(&pool, "PROD-W1", "SKU-W1", "Watch Widget", "50.00").await;
+        let srv = server(pool.clone());
+        let created = parse_output(
+            &srv.quote_create(Parameters(QuoteCreateInput {
+                account_id: "ACC-WATCH".to_string(),
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: Some(12),
+                start_date: None,
+                notes: None,
+                line_items: vec![LineItemInput {
+                    product_id: "PROD-W1".to_string(),
+                    quantity: 2,
+                    discount_pct: 0.0,
+                    attributes: None,
+                    notes: None,
+                }],
+                idempotency_key: None,
+            }))
+            .await,
+        );
+        let quote_id = created["quote_id"].as_str().unwrap().to_string();
+        let pdf_uri = format!("quote://{quote_id}/pdf");
+        srv.resource_subscriptions().subscribe(pdf_uri.clone(), None).await;
+        srv.resource_subscriptions().subscribe("quote://Q-UNRELATED".to_string(), None).await;
+        assert!(srv.poll_quote_changes().await.is_empty(), "first poll only records a baseline");
+
+        // Reads leave nothing to announce.
+        srv.quote_get(Parameters(QuoteGetInput {
+            quote_id: quote_id.clone(),
+            include_pricing: true,
+        }))
+        .await;
+        assert!(srv.poll_quote_changes().await.is_empty());
+
+        // A change written by another process, e.g. the Slack bot or the REST API.
+        sqlx::query("UPDATE quote SET status = 'approved', updated_at = ? WHERE id = ?")
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(&quote_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(srv.poll_quote_changes().await, vec![pdf_uri.clone()]);
+        assert!(srv.poll_quote_changes().await.is_empty());
+
+        sqlx::query("UPDATE quote_line SET quantity = 5 WHERE quote_id = ?")
+            .bind(&quote_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(srv.poll_quote_changes().await, vec![pdf_uri]);
+    }
+
+    #[tokio::test]
+    async fn quote_get_includes_active_model_prediction() {
+        use quotey_core::ml::{FeatureSchema, WinProbabilityModel};
+
+        let pool = test_db().await;
+        seed_product(&pool, "PROD-W1", "SKU-W1", "Win Widget", "50.00").await;
+        let srv = server(pool.clone());
+        let created = parse_output(
+            &srv.quote_create(Parameters(QuoteCreateInput {
+                account_id: "ACC-WIN".to_string(),
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: None,
+                start_date: None,
+                notes: None,
+                line_items: vec![LineItemInput {
+                    product_id: "PROD-W1".to_string(),
+                    quantity: 1,
+                    discount_pct: 0.0,
+                    attributes: None,
+                    notes: None,
+                }],
+                idempotency_key: None,
+            }))
+            .await,
+        );
+        let quote_id = created["quote_id"].as_str().unwrap().to_string();
+        let get = || {
+            srv.quote_get(Parameters(QuoteGetInput {
+                quote_id: quote_id.clone(),
+                include_pricing: false,
+            }))
+        };
+        assert!(parse_output(&get().await)["win_probability"].is_null());
+
+        let mut weights = vec![0.0; WinProbabilityModel::FEATURE_DIM];
+        weights[0] = 2.0;
+        let model = WinProbabilityModel::with_weights("v7", FeatureSchema::default(), weights)
+            .expect("model");
+        sqlx::query(
+            "INSERT INTO win_probability_models
+                (id, version, training_date, accuracy_score, model_path, created_at, status,
+                 model_json)
+             VALUES ('WPM-v7', 'v7', '2026-01-01', 0.8, 'db:v7', '2026-01-01', 'active', ?)",
+        )
+        .bind(serde_json::to_string(&model).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let v = parse_output(&get().await);
+        assert_eq!(v["win_probability"]["model_version"].as_str(), Some("v7"));
+        let probability = v["win_probability"]["probability"].as_f64().unwrap();
+        assert!((probability - 0.8808).abs() < 1e-3, "sigmoid(2) expected, got {probability}");
+    }
+
     #[tokio::test]
     async fn quote_get_empty_id_returns_validation_error() {
         let pool = test_db().await;
@@ -6140,6 +7807,59 @@ mod tests {
         assert_error_envelope(&output, "NOT_FOUND");
     }
 
+    #[tokio::test]
+    async fn quote_price_fails_instead_of_pricing_under_default_policy() {
+        let pool = test_db().await;
+        seed_product(&pool, "PROD-P2", "SKU-P2", "Policy Widget", "150.00").await;
+        let srv = server(pool.clone());
+        let created = parse_output(
+            &srv.quote_create(Parameters(QuoteCreateInput {
+                account_id: "ACC-POLICY".to_string(),
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: None,
+                start_date: None,
+                notes: None,
+                line_items: vec![LineItemInput {
+                    product_id: "PROD-P2".to_string(),
+                    quantity: 2,
+                    discount_pct: 0.0,
+                    attributes: None,
+                    notes: None,
+                }],
+                idempotency_key: Some("policy-error-test".to_string()),
+            }))
+            .await,
+        );
+        let quote_id = created["quote_id"].as_str().unwrap().to_string();
+        let price = |quote_id: String| {
+            srv.quote_price(Parameters(QuotePriceInput { quote_id, requested_discount_pct: 5.0 }))
+        };
+
+        sqlx::query("ALTER TABLE policy_rule_set_version RENAME TO policy_rule_set_version_gone")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_error_envelope(&price(quote_id.clone()).await, "INTERNAL_ERROR");
+        sqlx::query("ALTER TABLE policy_rule_set_version_gone RENAME TO policy_rule_set_version")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("ALTER TABLE policy_set_version RENAME TO policy_set_version_gone")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_error_envelope(&price(quote_id.clone()).await, "INTERNAL_ERROR");
+        sqlx::query("ALTER TABLE policy_set_version_gone RENAME TO policy_set_version")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let priced = parse_output(&price(quote_id).await);
+        assert!(priced["pricing"].is_object(), "{priced}");
+    }
+
     #[tokio::test]
     async fn quote_price_invalid_discount_returns_validation_error() {
         let pool = test_db().await;
@@ -6615,33 +8335,16 @@ mod tests {
             .await;
         let v = parse_output(&output);
 
-        if v["error"].is_object() {
-            // Tera templates use date filters not registered in the MCP render path.
-            // This is a known pre-existing limitation — verify the error is a template
-            // error (not a NOT_FOUND or VALIDATION_ERROR).
-            let code = v["error"]["code"].as_str().unwrap_or("");
-            assert_eq!(
-                code, "INTERNAL_ERROR",
-                "Expected INTERNAL_ERROR from template render, got: {output}"
-            );
-            // The hardening layer sanitizes INTERNAL_ERROR messages to hide
-            // implementation details, so we just verify the code is correct.
-            let msg = v["error"]["message"].as_str().unwrap_or("");
-            assert!(
-                msg.contains("render") || msg.contains("Internal server error"),
-                "Expected template render or sanitized internal error, got: {msg}"
-            );
-        } else {
-            // Full contract verification when template rendering succeeds
-            assert_eq!(v["quote_id"].as_str().unwrap(), quote_id);
-            assert!(v["pdf_generated"].is_boolean());
-            assert!(v["file_path"].is_string());
-            assert!(v["file_size_bytes"].is_number());
-            assert!(v["checksum"].is_string());
-            assert_eq!(v["template_used"].as_str().unwrap(), "compact");
-            assert!(v["generated_at"].is_string());
-            assert!(v["file_size_bytes"].as_u64().unwrap() > 0, "file should have non-zero size");
-        }
+        assert!(v["error"].is_null(), "quote_pdf should render natively: {output}");
+        assert_eq!(v["quote_id"].as_str().unwrap(), quote_id);
+        assert_eq!(v["pdf_generated"], serde_json::json!(true));
+        assert!(v["checksum"].is_string());
+        assert_eq!(v["template_used"].as_str().unwrap(), "compact");
+        assert!(v["generated_at"].is_string());
+        let bytes = std::fs::read(v["file_path"].as_str().unwrap()).expect("pdf artifact");
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert_eq!(v["file_size_bytes"].as_u64().unwrap(), bytes.len() as u64);
+        assert_eq!(v["checksum"].as_str().unwrap(), checksum_of_bytes(&bytes));
     }
 
     // ========================================================================
@@ -6729,11 +8432,11 @@ mod tests {
 
     #[test]
     fn template_allowlist() {
-        assert!(template_is_allowed("detailed"));
-        assert!(template_is_allowed("executive_summary"));
-        assert!(template_is_allowed("compact"));
-        assert!(!template_is_allowed("malicious"));
-        assert!(!template_is_allowed(""));
+        for name in allowed_pdf_templates() {
+            assert_eq!(PdfTemplate::parse(name).map(PdfTemplate::as_str), Some(*name));
+        }
+        assert!(PdfTemplate::parse("malicious").is_none());
+        assert!(PdfTemplate::parse("").is_none());
     }
 
     #[tokio::test]
@@ -7146,6 +8849,94 @@ mod tests {
         assert!(json["concession_deltas"].as_array().is_some());
     }
 
+    #[tokio::test]
+    async fn negotiation_counter_then_accept_revises_quote() {
+        let pool = test_db().await;
+        seed_product(&pool, "PROD-NEG", "SKU-NEG", "Negotiated Product", "100.00").await;
+        let srv = server(pool.clone());
+
+        let created = parse_output(
+            &srv.quote_create(Parameters(QuoteCreateInput {
+                account_id: "ACC-NEG".to_string(),
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: None,
+                start_date: None,
+                notes: None,
+                line_items: vec![LineItemInput {
+                    product_id: "PROD-NEG".to_string(),
+                    quantity: 5,
+                    discount_pct: 0.0,
+                    attributes: None,
+                    notes: None,
+                }],
+                idempotency_key: Some("neg-quote".to_string()),
+            }))
+            .await,
+        );
+        let quote_id = created["quote_id"].as_str().unwrap().to_string();
+        let started = parse_output(
+            &srv.negotiation_start(Parameters(NegotiationStartInput {
+                quote_id: quote_id.clone(),
+                actor_id: "rep-alice".to_string(),
+                idempotency_key: "neg-test".to_string(),
+            }))
+            .await,
+        );
+        let session_id = started["session_id"].as_str().unwrap().to_string();
+
+        let accept_early = parse_output(
+            &srv.negotiation_accept(Parameters(NegotiationAcceptInput {
+                session_id: session_id.clone(),
+                actor_id: "rep-alice".to_string(),
+                note: None,
+                idempotency_key: None,
+            }))
+            .await,
+        );
+        assert_eq!(accept_early["error"]["code"], "INVALID_STATE");
+
+        let countered = parse_output(
+            &srv.negotiation_counter(Parameters(NegotiationCounterInput {
+                session_id: session_id.clone(),
+                actor_id: "rep-alice".to_string(),
+                discount_pct: 10.0,
+                term_months: Some(24),
+                party: Some("customer".to_string()),
+                note: Some("Customer asked on the call".to_string()),
+                idempotency_key: None,
+            }))
+            .await,
+        );
+        assert_eq!(countered["outcome"], "offered");
+        assert_eq!(countered["state"], "counter_pending");
+        assert_eq!(countered["check"]["verdict"], "Offered");
+
+        // The accept input names only the session; the revised quote is still announced.
+        let quote_uri = format!("quote://{quote_id}");
+        srv.resource_subscriptions().subscribe(quote_uri.clone(), None).await;
+        srv.resource_subscriptions().take_notified().await;
+        let accepted = parse_output(
+            &srv.negotiation_accept(Parameters(NegotiationAcceptInput {
+                session_id: session_id.clone(),
+                actor_id: "rep-alice".to_string(),
+                note: None,
+                idempotency_key: None,
+            }))
+            .await,
+        );
+        assert_eq!(accepted["state"], "accepted");
+        assert_eq!(accepted["revision"]["version"], 2);
+        assert_eq!(srv.resource_subscriptions().take_notified().await, vec![quote_uri]);
+        assert!(srv.poll_quote_changes().await.is_empty(), "the accept is announced once");
+
+        let status = parse_output(
+            &srv.negotiation_status(Parameters(NegotiationStatusInput { session_id })).await,
+        );
+        assert_eq!(status["state"], "accepted");
+        assert_eq!(status["turn_count"], 2);
+    }
+
     #[tokio::test]
     async fn negotiation_escalate_rejects_missing_reason() {
         let pool = test_db().await;
@@ -7254,7 +9045,7 @@ mod tests {
             }))
             .await;
         let json = parse_output(&result);
-        assert_eq!(json["locked"].as_bool().unwrap(), true);
+        assert!(json["locked"].as_bool().unwrap());
         assert_eq!(json["locked_by"].as_str().unwrap(), "rep-alice");
 
         // Check status
@@ -7264,7 +9055,7 @@ mod tests {
             }))
             .await;
         let status_json = parse_output(&status);
-        assert_eq!(status_json["locked"].as_bool().unwrap(), true);
+        assert!(status_json["locked"].as_bool().unwrap());
         assert_eq!(status_json["locked_by"].as_str().unwrap(), "rep-alice");
 
         // Unlock
@@ -7275,7 +9066,7 @@ mod tests {
             }))
             .await;
         let unlock_json = parse_output(&unlock);
-        assert_eq!(unlock_json["unlocked"].as_bool().unwrap(), true);
+        assert!(unlock_json["unlocked"].as_bool().unwrap());
 
         // Verify unlocked
         let status2 = srv
@@ -7284,7 +9075,7 @@ mod tests {
             }))
             .await;
         let status2_json = parse_output(&status2);
-        assert_eq!(status2_json["locked"].as_bool().unwrap(), false);
+        assert!(!status2_json["locked"].as_bool().unwrap());
     }
 
     #[tokio::test]
@@ -7336,8 +9127,8 @@ mod tests {
             }))
             .await;
         let json = parse_output(&result);
-        assert_eq!(json["unlocked"].as_bool().unwrap(), true);
-        assert_eq!(json["forced"].as_bool().unwrap(), true);
+        assert!(json["unlocked"].as_bool().unwrap());
+        assert!(json["forced"].as_bool().unwrap());
     }
 
     // ── Settings Tool Tests ────────────────────────────────────────────
@@ -7547,9 +9338,8 @@ mod tests {
             }))
             .await;
         let price_json = parse_output(&price_result);
-        assert_eq!(
+        assert!(
             price_json["approval_required"].as_bool().unwrap(),
-            true,
             "8% discount should trigger approval when threshold is set to 5%"
         );
     }
@@ -8030,6 +9820,155 @@ mod tests {
         assert_error_envelope(&result, "VALIDATION_ERROR");
     }
 
+    // -----------------------------------------------------------------------
+    // Analytics tests
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn analytics_query_groups_seeded_quotes() {
+        let pool = test_db().await;
+        seed_quote(&pool, "Q-ANALYTICS-1").await;
+        seed_quote(&pool, "Q-ANALYTICS-2").await;
+        let srv = server(pool);
+
+        let output = srv
+            .analytics_query(Parameters(AnalyticsQueryInput {
+                metrics: vec!["quote_count".to_string()],
+                dimensions: vec!["day".to_string()],
+                lookback_days: 7,
+                include_only_finalized: false,
+                filters: Vec::new(),
+                end_date: None,
+                limit: Some(10),
+            }))
+            .await;
+        let v = parse_output(&output);
+        assert_eq!(v["columns"][0]["name"], "day");
+        assert_eq!(v["rows"].as_array().unwrap().len(), 1);
+        assert_eq!(v["rows"][0]["quote_count"], 2);
+        assert_eq!(v["truncated"], false);
+
+        let unknown = srv
+            .analytics_query(Parameters(AnalyticsQueryInput {
+                metrics: vec!["quote_count".to_string()],
+                dimensions: Vec::new(),
+                lookback_days: 7,
+                include_only_finalized: false,
+                filters: vec![AnalyticsFilterInput {
+                    dimension: "planet".to_string(),
+                    values: vec!["mars".to_string()],
+                }],
+                end_date: None,
+                limit: None,
+            }))
+            .await;
+        assert_error_envelope(&unknown, "VALIDATION_ERROR");
+    }
+
+    // -----------------------------------------------------------------------
+    // Webhook tests
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn webhook_tools_subscribe_log_and_redeliver() {
+        use quotey_core::webhooks::{WebhookEvent, WebhookEventType};
+
+        let pool = test_db().await;
+        seed_quote(&pool, "Q-WEBHOOK").await;
+        let srv = server(pool.clone());
+
+        let rejected = srv
+            .webhook_subscribe(Parameters(WebhookSubscribeInput {
+                url: "ftp://hooks.example.com".to_string(),
+                events: vec!["quote.*".to_string()],
+                description: None,
+                actor: None,
+            }))
+            .await;
+        assert_error_envelope(&rejected, "VALIDATION_ERROR");
+
+        let created = srv
+            .webhook_subscribe(Parameters(WebhookSubscribeInput {
+                url: "https://203.0.113.10/quotey".to_string(),
+                events: vec!["quote.*".to_string()],
+                description: Some("ERP".to_string()),
+                actor: Some("ops@example.com".to_string()),
+            }))
+            .await;
+        let created: serde_json::Value = serde_json::from_str(&created).unwrap();
+        assert!(created["secret"].as_str().unwrap().starts_with("whsec_"));
+        let subscription_id = created["subscription"]["id"].as_str().unwrap().to_string();
+
+        let listed =
+            srv.webhook_list(Parameters(WebhookListInput { include_disabled: false })).await;
+        let listed: serde_json::Value = serde_json::from_str(&listed).unwrap();
+        assert_eq!(listed["count"], 1);
+        assert!(listed["subscriptions"][0].get("secret").is_none());
+
+        quotey_db::webhooks::WebhookService::new(pool.clone())
+            .publish(&WebhookEvent::new(
+                WebhookEventType::QuoteFinalized,
+                "Q-WEBHOOK",
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+        let deliveries = srv
+            .webhook_deliveries(Parameters(WebhookDeliveriesInput {
+                subscription_id: subscription_id.clone(),
+                delivery_id: None,
+                status: Some("pending".to_string()),
+                limit: 10,
+            }))
+            .await;
+        let deliveries: serde_json::Value = serde_json::from_str(&deliveries).unwrap();
+        assert_eq!(deliveries["count"], 1);
+        let delivery_id = deliveries["deliveries"][0]["id"].as_str().unwrap().to_string();
+
+        let log = srv
+            .webhook_deliveries(Parameters(WebhookDeliveriesInput {
+                subscription_id: subscription_id.clone(),
+                delivery_id: Some(delivery_id.clone()),
+                status: None,
+                limit: 10,
+            }))
+            .await;
+        let log: serde_json::Value = serde_json::from_str(&log).unwrap();
+        assert_eq!(log["delivery"]["event_type"], "quote.finalized");
+        assert_eq!(log["attempts"], serde_json::json!([]));
+
+        let redelivered = srv
+            .webhook_redeliver(Parameters(WebhookRedeliverInput {
+                delivery_id: delivery_id.clone(),
+                actor: None,
+            }))
+            .await;
+        let redelivered: serde_json::Value = serde_json::from_str(&redelivered).unwrap();
+        assert_eq!(redelivered["redelivery_of"], delivery_id.as_str());
+
+        let disabled = srv
+            .webhook_disable(Parameters(WebhookDisableInput {
+                subscription_id: subscription_id.clone(),
+            }))
+            .await;
+        let disabled: serde_json::Value = serde_json::from_str(&disabled).unwrap();
+        assert_eq!(disabled["active"], false);
+        let conflict = srv
+            .webhook_redeliver(Parameters(WebhookRedeliverInput { delivery_id, actor: None }))
+            .await;
+        assert_error_envelope(&conflict, "CONFLICT");
+
+        let missing = srv
+            .webhook_deliveries(Parameters(WebhookDeliveriesInput {
+                subscription_id: "WHS-missing".to_string(),
+                delivery_id: None,
+                status: None,
+                limit: 10,
+            }))
+            .await;
+        assert_error_envelope(&missing, "NOT_FOUND");
+    }
+
     // -----------------------------------------------------------------------
     // Audit query tests
     // -----------------------------------------------------------------------
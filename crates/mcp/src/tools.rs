@@ -12,6 +12,8 @@
 //! - Negotiation: Negotiation autopilot
 //! - Anomaly: Anomaly override management
 //! - Cost: AI usage cost tracking
+//! - Analytics: Aggregated quote metrics
+//! - Webhook: Outbound event subscriptions and delivery logs
 
 // Tool categories for organization
 /// Catalog tools category
@@ -67,7 +69,15 @@ impl ToolCategory for QuoteTools {
         "quote"
     }
     fn tool_names() -> &'static [&'static str] {
-        &["quote_create", "quote_get", "quote_price", "quote_list"]
+        &[
+            "quote_create",
+            "quote_get",
+            "quote_price",
+            "quote_list",
+            "quote_explain",
+            "quote_simulate",
+            "quote_simulate_promote",
+        ]
     }
 }
 
@@ -161,6 +171,12 @@ impl ToolCategory for AuditTools {
 /// Budget tools category
 pub struct BudgetTools;
 
+/// Analytics tools category
+pub struct AnalyticsTools;
+
+/// Webhook tools category
+pub struct WebhookTools;
+
 impl ToolCategory for BudgetTools {
     fn category_name() -> &'static str {
         "budget"
@@ -170,6 +186,30 @@ impl ToolCategory for BudgetTools {
     }
 }
 
+impl ToolCategory for AnalyticsTools {
+    fn category_name() -> &'static str {
+        "analytics"
+    }
+    fn tool_names() -> &'static [&'static str] {
+        &["analytics_query"]
+    }
+}
+
+impl ToolCategory for WebhookTools {
+    fn category_name() -> &'static str {
+        "webhook"
+    }
+    fn tool_names() -> &'static [&'static str] {
+        &[
+            "webhook_subscribe",
+            "webhook_list",
+            "webhook_disable",
+            "webhook_deliveries",
+            "webhook_redeliver",
+        ]
+    }
+}
+
 /// All tool names (does not include negotiation, sales_rep, anomaly tools registered via #[tool_router])
 pub const ALL_TOOL_NAMES: &[&str] = &[
     // Catalog
@@ -180,6 +220,9 @@ pub const ALL_TOOL_NAMES: &[&str] = &[
     "quote_get",
     "quote_price",
     "quote_list",
+    "quote_explain",
+    "quote_simulate",
+    "quote_simulate_promote",
     // Approval
     "approval_request",
     "approval_status",
@@ -214,6 +257,14 @@ pub const ALL_TOOL_NAMES: &[&str] = &[
     "budget_check",
     "budget_status",
     "budget_record",
+    // Analytics
+    "analytics_query",
+    // Webhook
+    "webhook_subscribe",
+    "webhook_list",
+    "webhook_disable",
+    "webhook_deliveries",
+    "webhook_redeliver",
 ];
 
 /// Total number of tools in the registry
@@ -226,7 +277,7 @@ mod tests {
     #[test]
     fn test_tool_counts() {
         assert_eq!(CatalogTools::tool_names().len(), 2);
-        assert_eq!(QuoteTools::tool_names().len(), 4);
+        assert_eq!(QuoteTools::tool_names().len(), 7);
         assert_eq!(ApprovalTools::tool_names().len(), 3);
         assert_eq!(PdfTools::tool_names().len(), 1);
         assert_eq!(CommentTools::tool_names().len(), 2);
@@ -237,6 +288,8 @@ mod tests {
         assert_eq!(IntegrationTools::tool_names().len(), 3);
         assert_eq!(AuditTools::tool_names().len(), 1);
         assert_eq!(BudgetTools::tool_names().len(), 3);
-        assert_eq!(TOTAL_TOOLS, 30);
+        assert_eq!(AnalyticsTools::tool_names().len(), 1);
+        assert_eq!(WebhookTools::tool_names().len(), 5);
+        assert_eq!(TOTAL_TOOLS, 39);
     }
 }
@@ -10,6 +10,7 @@
 //! - Currency mismatch errors
 //! - Audit trail persistence from tool invocations
 //! - Quote PDF tool responses
+//! - Resource reads and prompt rendering
 
 use quotey_mcp::QuoteyMcpServer;
 use rmcp::handler::server::wrapper::Parameters;
@@ -887,16 +888,121 @@ async fn contract_quote_pdf_success_shape() -> TestResult {
     let input = quotey_mcp::server::QuotePdfInput { quote_id, template: "detailed".to_string() };
     let v = parse(&server.quote_pdf(Parameters(input)).await);
 
-    // PDF may fail if wkhtmltopdf is not installed, but the response
-    // should still have the expected shape (either success or graceful error)
-    if v.get("error").is_none() {
-        // Success shape
-        for field in &["file_path", "file_size_bytes", "pdf_generated"] {
-            assert!(v.get(field).is_some(), "quote_pdf success must have '{field}'");
-        }
-        assert!(v["pdf_generated"].is_boolean());
+    assert!(v.get("error").is_none(), "quote_pdf renders in-process: {v}");
+    for field in &["file_path", "file_size_bytes", "pdf_generated", "checksum"] {
+        assert!(v.get(field).is_some(), "quote_pdf success must have '{field}'");
+    }
+    assert_eq!(v["pdf_generated"], serde_json::json!(true));
+
+    Ok(())
+}
+
+// ============================================================================
+// Resources and Prompts
+// ============================================================================
+
+fn resource_text(result: &rmcp::model::ReadResourceResult) -> serde_json::Value {
+    match result.contents.first() {
+        Some(rmcp::model::ResourceContents::TextResourceContents { text, .. }) => parse(text),
+        other => panic!("expected text resource contents, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn contract_quote_resource_matches_quote_get_shape() -> TestResult {
+    let pool = setup_pool().await?;
+    let server = QuoteyMcpServer::new(pool.clone());
+    let quote_id = create_test_quote(&server, &pool, "res-quote").await?;
+
+    let result = server
+        .read_resource_uri(&format!("quote://{quote_id}"))
+        .await
+        .map_err(|e| format!("read quote resource: {e:?}"))?;
+    let v = resource_text(&result);
+    assert_eq!(v["quote"]["id"].as_str(), Some(quote_id.as_str()));
+    assert!(v["line_items"].is_array());
+    assert!(v["pricing"]["total"].is_number(), "quote resource always includes pricing");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn contract_resource_errors_use_mcp_error_codes() -> TestResult {
+    let pool = setup_pool().await?;
+    let server = QuoteyMcpServer::new(pool);
+
+    let missing = server.read_resource_uri("quote://Q-DOES-NOT-EXIST").await.unwrap_err();
+    assert_eq!(missing.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+
+    let missing_approval = server.read_resource_uri("approval://APR-NOPE").await.unwrap_err();
+    assert_eq!(missing_approval.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+
+    let unsupported = server.read_resource_uri("file:///etc/passwd").await.unwrap_err();
+    assert_eq!(unsupported.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn contract_policy_and_catalog_resources_shape() -> TestResult {
+    let pool = setup_pool().await?;
+    let server = QuoteyMcpServer::new(pool.clone());
+    seed_product(&pool, "PROD-RES-1", "SKU-RES-1", "Resource Item", 1000, "USD").await?;
+
+    let thresholds = resource_text(
+        &server
+            .read_resource_uri("policy://thresholds")
+            .await
+            .map_err(|e| format!("read thresholds: {e:?}"))?,
+    );
+    for field in &["manager_discount_pct", "vp_discount_pct", "margin_floor_pct"] {
+        assert!(thresholds.get(field).is_some(), "policy://thresholds must have '{field}'");
     }
-    // If error, it should be INTERNAL_ERROR from missing wkhtmltopdf, not a crash
+
+    let product = resource_text(
+        &server
+            .read_resource_uri("catalog://product/PROD-RES-1")
+            .await
+            .map_err(|e| format!("read product: {e:?}"))?,
+    );
+    assert_eq!(product["sku"].as_str(), Some("SKU-RES-1"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn contract_quote_mutation_notifies_subscribers() -> TestResult {
+    let pool = setup_pool().await?;
+    let server = QuoteyMcpServer::new(pool.clone());
+    let quote_id = create_test_quote(&server, &pool, "res-sub").await?;
+
+    server.resource_subscriptions().subscribe(format!("quote://{quote_id}"), None).await;
+    let notified = server.notify_quote_changed(&quote_id).await;
+    assert_eq!(notified, vec![format!("quote://{quote_id}")]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn contract_discount_justification_prompt_embeds_quote_and_routing() -> TestResult {
+    let pool = setup_pool().await?;
+    let server = QuoteyMcpServer::new(pool.clone());
+    let quote_id = create_test_quote(&server, &pool, "prompt").await?;
+
+    let mut args = rmcp::model::JsonObject::new();
+    args.insert("quote_id".to_string(), serde_json::json!(quote_id));
+    args.insert("requested_discount_pct".to_string(), serde_json::json!(25));
+    let prompt = server
+        .render_prompt("discount_justification", Some(&args))
+        .await
+        .map_err(|e| format!("render prompt: {e:?}"))?;
+
+    let text = serde_json::to_string(&prompt.messages).map_err(|e| e.to_string())?;
+    assert!(text.contains(&quote_id));
+    assert!(text.contains("sales_manager"), "25% routes to the manager threshold");
+
+    let unknown = server.render_prompt("no_such_prompt", None).await.unwrap_err();
+    assert_eq!(unknown.code, rmcp::model::ErrorCode::INVALID_PARAMS);
 
     Ok(())
 }
@@ -31,6 +31,7 @@ async fn server_with_auth(key: &str, rpm: u32) -> QuoteyMcpServer {
             key: key.to_string(),
             name: "test-agent".to_string(),
             requests_per_minute: rpm,
+            grants: None,
         }],
     });
     QuoteyMcpServer::with_auth(test_db().await, auth)
@@ -69,6 +70,7 @@ async fn test_auth_required_rejects_missing_key() {
             key: "secret".to_string(),
             name: "agent".to_string(),
             requests_per_minute: 60,
+            grants: None,
         }],
     });
     let result = auth.validate_request(None).await;
@@ -85,6 +87,7 @@ async fn test_auth_rejects_wrong_key() {
             key: "correct-key".to_string(),
             name: "agent".to_string(),
             requests_per_minute: 60,
+            grants: None,
         }],
     });
     let result = auth.validate_request(Some("wrong-key")).await;
@@ -101,6 +104,7 @@ async fn test_auth_accepts_valid_key() {
             key: "valid-key".to_string(),
             name: "my-agent".to_string(),
             requests_per_minute: 100,
+            grants: None,
         }],
     });
     let result = auth.validate_request(Some("valid-key")).await;
@@ -117,6 +121,7 @@ async fn test_rate_limiting_enforced() {
             key: "limited-key".to_string(),
             name: "rate-limited".to_string(),
             requests_per_minute: 3,
+            grants: None,
         }],
     });
 
@@ -142,6 +147,7 @@ async fn test_disabled_auth_config() {
             key: "ignored".to_string(),
             name: "ignored".to_string(),
             requests_per_minute: 60,
+            grants: None,
         }],
     });
     // Disabled auth → all requests allowed even without key
@@ -783,6 +789,221 @@ async fn test_quote_price_with_discount() -> TestResult {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_quote_explain_cites_recorded_pricing_snapshot() -> TestResult {
+    let pool = setup_pool().await?;
+    seed_product(&pool, "PROD-EXPL", "SKU-EXPL", "Explain Item", 10000).await?;
+
+    let server = QuoteyMcpServer::new(pool.clone());
+
+    let create_input = quotey_mcp::server::QuoteCreateInput {
+        account_id: "ACCT-EXPL".to_string(),
+        deal_id: None,
+        currency: "USD".to_string(),
+        term_months: Some(12),
+        start_date: None,
+        notes: None,
+        line_items: vec![quotey_mcp::server::LineItemInput {
+            product_id: "PROD-EXPL".to_string(),
+            quantity: 4,
+            discount_pct: 0.0,
+            attributes: None,
+            notes: None,
+        }],
+        idempotency_key: Some("explain-test".to_string()),
+    };
+    let create_output =
+        server.quote_create(rmcp::handler::server::wrapper::Parameters(create_input)).await;
+    let quote_id =
+        parse_output(&create_output).get("quote_id").and_then(|v| v.as_str()).unwrap().to_string();
+
+    let explain =
+        |target: Option<&str>, line_id: Option<String>| quotey_mcp::server::QuoteExplainInput {
+            quote_id: quote_id.clone(),
+            target: target.map(str::to_string),
+            line_id,
+            version: None,
+        };
+
+    // Nothing has been priced yet, so there is no evidence to cite.
+    let before =
+        server.quote_explain(rmcp::handler::server::wrapper::Parameters(explain(None, None))).await;
+    assert_eq!(error_code(&parse_output(&before)), Some("MISSING_EVIDENCE"));
+
+    let price_input = quotey_mcp::server::QuotePriceInput {
+        quote_id: quote_id.clone(),
+        requested_discount_pct: 25.0,
+    };
+    let price_output =
+        server.quote_price(rmcp::handler::server::wrapper::Parameters(price_input)).await;
+    let line_id = parse_output(&price_output)["line_pricing"][0]["line_id"]
+        .as_str()
+        .expect("line id")
+        .to_string();
+
+    let total = parse_output(
+        &server
+            .quote_explain(rmcp::handler::server::wrapper::Parameters(explain(None, None)))
+            .await,
+    );
+    assert!(total.get("error").is_none(), "expected success, got: {total:?}");
+    assert_eq!(total["target"].as_str(), Some("total"));
+    assert_eq!(total["response"]["amount"].as_str().and_then(|v| v.parse().ok()), Some(300.0));
+    assert_eq!(total["cached"].as_bool(), Some(false));
+    assert!(total["evidence"].as_array().is_some_and(|links| !links.is_empty()));
+
+    let line = parse_output(
+        &server
+            .quote_explain(rmcp::handler::server::wrapper::Parameters(explain(
+                Some("line"),
+                Some(line_id),
+            )))
+            .await,
+    );
+    assert_eq!(line["response"]["amount"].as_str().and_then(|v| v.parse().ok()), Some(300.0));
+
+    let policy = parse_output(
+        &server
+            .quote_explain(rmcp::handler::server::wrapper::Parameters(explain(
+                Some("policy"),
+                None,
+            )))
+            .await,
+    );
+    let verdicts = policy["response"]["policy_evidence"].as_array().expect("policy evidence");
+    assert!(verdicts.iter().any(|v| v["decision"].as_str() == Some("violated")));
+
+    let again = parse_output(
+        &server
+            .quote_explain(rmcp::handler::server::wrapper::Parameters(explain(None, None)))
+            .await,
+    );
+    assert_eq!(again["cached"].as_bool(), Some(true));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quote_simulate_and_promote_variant() -> TestResult {
+    let pool = setup_pool().await?;
+    seed_product(&pool, "PROD-SIM", "SKU-SIM", "Simulated Item", 10000).await?;
+
+    let server = QuoteyMcpServer::new(pool.clone());
+
+    let create_input = quotey_mcp::server::QuoteCreateInput {
+        account_id: "ACCT-SIM".to_string(),
+        deal_id: None,
+        currency: "USD".to_string(),
+        term_months: Some(12),
+        start_date: None,
+        notes: None,
+        line_items: vec![quotey_mcp::server::LineItemInput {
+            product_id: "PROD-SIM".to_string(),
+            quantity: 4,
+            discount_pct: 0.0,
+            attributes: None,
+            notes: None,
+        }],
+        idempotency_key: Some("simulate-test".to_string()),
+    };
+    let create_output =
+        server.quote_create(rmcp::handler::server::wrapper::Parameters(create_input)).await;
+    let quote_id =
+        parse_output(&create_output).get("quote_id").and_then(|v| v.as_str()).unwrap().to_string();
+
+    let too_many = server
+        .quote_simulate(rmcp::handler::server::wrapper::Parameters(
+            quotey_mcp::server::QuoteSimulateInput {
+                quote_id: quote_id.clone(),
+                variants: Vec::new(),
+                discount_pct: None,
+            },
+        ))
+        .await;
+    assert_eq!(error_code(&parse_output(&too_many)), Some("GUARDRAIL_REJECTED"));
+
+    let simulate_output = server
+        .quote_simulate(rmcp::handler::server::wrapper::Parameters(
+            quotey_mcp::server::QuoteSimulateInput {
+                quote_id: quote_id.clone(),
+                variants: vec![
+                    quotey_mcp::server::ScenarioVariantInput {
+                        key: "more-seats".to_string(),
+                        line_changes: vec![quotey_mcp::server::ScenarioLineChangeInput {
+                            product_id: "PROD-SIM".to_string(),
+                            quantity_delta: 2,
+                            unit_price: None,
+                        }],
+                        discount_pct: None,
+                        margin_pct: None,
+                    },
+                    quotey_mcp::server::ScenarioVariantInput {
+                        key: "deep-discount".to_string(),
+                        line_changes: Vec::new(),
+                        discount_pct: Some(40.0),
+                        margin_pct: None,
+                    },
+                ],
+                discount_pct: None,
+            },
+        ))
+        .await;
+    let run = parse_output(&simulate_output);
+    assert!(run.get("error").is_none(), "expected success, got: {run:?}");
+    let run_id = run["run_id"].as_str().expect("run id").to_string();
+    let variants = run["variants"].as_array().expect("variants");
+    assert_eq!(variants.len(), 2);
+    let deep = variants
+        .iter()
+        .find(|v| v["variant_key"].as_str() == Some("deep-discount"))
+        .expect("deep-discount variant");
+    assert_eq!(deep["outcome"]["approval_required"].as_bool(), Some(true));
+
+    let promote = |variant: &str| quotey_mcp::server::QuoteSimulatePromoteInput {
+        quote_id: quote_id.clone(),
+        run_id: run_id.clone(),
+        variant: variant.to_string(),
+    };
+    let missing = server
+        .quote_simulate_promote(rmcp::handler::server::wrapper::Parameters(promote("nope")))
+        .await;
+    assert_eq!(error_code(&parse_output(&missing)), Some("NOT_FOUND"));
+
+    let promoted = parse_output(
+        &server
+            .quote_simulate_promote(rmcp::handler::server::wrapper::Parameters(promote(
+                "more-seats",
+            )))
+            .await,
+    );
+    assert!(promoted.get("error").is_none(), "expected success, got: {promoted:?}");
+    assert_eq!(promoted["version"].as_u64(), Some(2));
+
+    let quantity: i64 =
+        sqlx::query_scalar("SELECT quantity FROM quote_line WHERE quote_id = ? LIMIT 1")
+            .bind(&quote_id)
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| format!("query: {e}"))?;
+    assert_eq!(quantity, 6);
+
+    let linked: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_event WHERE quote_id = ? AND event_type = 'quote.simulation_promoted'",
+    )
+    .bind(&quote_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("query: {e}"))?;
+    assert_eq!(linked, 1);
+
+    let again = server
+        .quote_simulate_promote(rmcp::handler::server::wrapper::Parameters(promote("more-seats")))
+        .await;
+    assert_eq!(error_code(&parse_output(&again)), Some("CONFLICT"));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_quote_list_with_pagination() -> TestResult {
     let pool = setup_pool().await?;
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::State, Json};
+use quotey_core::chrono::NaiveDate;
+use quotey_core::{AnalyticsContractError, AnalyticsFilter, AnalyticsQuerySpec};
+use quotey_db::analytics::{AnalyticsResult, AnalyticsService, AnalyticsServiceError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::error::{ApiError, ApiResult};
+use super::{ApiJson, ApiState};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyticsFilterInput {
+    /// Dimension to filter on, e.g. `region` or `sales_rep`.
+    pub dimension: String,
+    /// Rows are kept when the dimension equals any of these values.
+    pub values: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyticsQueryRequest {
+    /// Defaults to `analytics_contract.v1`.
+    #[serde(default)]
+    pub schema_version: Option<String>,
+    /// `quote_count`, `win_rate_pct`, `avg_discount_pct`, `avg_deal_value`,
+    /// `approval_cycle_hours`, `time_to_finalize_hours` or `anomaly_rate_pct`.
+    pub metrics: Vec<String>,
+    /// Group by `day`, `week`, `month`, `quarter`, `customer_segment`, `industry`, `region`,
+    /// `sales_rep`, `product_family` or `approval_role`.
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    /// Days back from today to include, 1 to 3650. Defaults to 30.
+    #[serde(default = "default_lookback_days")]
+    pub lookback_days: u32,
+    /// Only count approved, finalized, sent and accepted quotes.
+    #[serde(default)]
+    pub include_only_finalized: bool,
+    #[serde(default)]
+    pub filters: Vec<AnalyticsFilterInput>,
+    /// Last creation date (`YYYY-MM-DD`, inclusive) to count.
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// Row cap, 1 to 10000. Defaults to 1000.
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+fn default_lookback_days() -> u32 {
+    30
+}
+
+impl AnalyticsQueryRequest {
+    pub fn into_spec(self) -> Result<AnalyticsQuerySpec, ApiError> {
+        let mut spec =
+            AnalyticsQuerySpec::from_labels(&self.metrics, &self.dimensions, self.lookback_days)
+                .map_err(invalid)?;
+        if let Some(version) = self.schema_version {
+            spec.schema_version = version;
+        }
+        spec.include_only_finalized = self.include_only_finalized;
+        spec.filters = self
+            .filters
+            .into_iter()
+            .map(|filter| AnalyticsFilter::from_label(&filter.dimension, filter.values))
+            .collect::<Result<_, _>>()
+            .map_err(invalid)?;
+        spec.end_date = match self.end_date.as_deref().map(str::trim) {
+            Some(raw) => Some(NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|_| {
+                ApiError::validation(format!("end_date `{raw}` is not a YYYY-MM-DD date"))
+            })?),
+            None => None,
+        };
+        spec.limit = self.limit;
+        Ok(spec)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyticsColumnResource {
+    pub name: String,
+    /// `dimension` or `metric`.
+    pub kind: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyticsResultResource {
+    pub schema_version: String,
+    /// Dimension columns first, then metrics, in request order.
+    pub columns: Vec<AnalyticsColumnResource>,
+    /// One object per group, keyed by column name. Metrics are `null` when no quote had data.
+    pub rows: Vec<BTreeMap<String, serde_json::Value>>,
+    /// More rows matched than `limit` allowed.
+    pub truncated: bool,
+    pub generated_at: String,
+    /// Served from the short-lived result cache.
+    pub cached: bool,
+}
+
+impl From<AnalyticsResult> for AnalyticsResultResource {
+    fn from(result: AnalyticsResult) -> Self {
+        Self {
+            schema_version: result.schema_version,
+            columns: result
+                .columns
+                .iter()
+                .map(|column| AnalyticsColumnResource {
+                    name: column.name.to_string(),
+                    kind: column.kind.as_str().to_string(),
+                })
+                .collect(),
+            rows: result.rows.into_iter().map(|row| row.into_iter().collect()).collect(),
+            truncated: result.truncated,
+            generated_at: result.generated_at.to_rfc3339(),
+            cached: result.cached,
+        }
+    }
+}
+
+impl From<AnalyticsServiceError> for ApiError {
+    fn from(error: AnalyticsServiceError) -> Self {
+        match error {
+            AnalyticsServiceError::Invalid(_) => Self::validation(error.to_string()),
+            AnalyticsServiceError::Repository(error) => Self::from(error),
+        }
+    }
+}
+
+fn invalid(error: AnalyticsContractError) -> ApiError {
+    ApiError::validation(error.to_string())
+}
+
+pub async fn run_query(
+    State(state): State<ApiState>,
+    ApiJson(body): ApiJson<AnalyticsQueryRequest>,
+) -> ApiResult<Json<AnalyticsResultResource>> {
+    let spec = body.into_spec()?;
+    let result = AnalyticsService::new(state.db_pool.clone())
+        .with_cache(state.analytics_cache.clone())
+        .run(&spec)
+        .await?;
+    Ok(Json(result.into()))
+}
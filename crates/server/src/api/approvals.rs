@@ -0,0 +1,401 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use chrono::{Duration, Utc};
+use quotey_core::domain::approval::{ApprovalId, ApprovalRequest, ApprovalStatus, ApprovalType};
+use quotey_core::domain::quote::QuoteStatus;
+use quotey_core::webhooks::WebhookEventType;
+use quotey_db::repositories::quote::quote_status_as_str;
+use quotey_db::repositories::{
+    ApprovalRepository, QuoteRepository, SqlApprovalRepository, SqlQuoteRepository,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::auth::ApiPrincipal;
+use super::comments::auto_comment;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::pagination::{Cursor, Page, PageRequest};
+use super::quotes::{load_quote, normalize_id, optional_trimmed};
+use super::{ApiJson, ApiQuery, ApiState};
+
+const MAX_JUSTIFICATION_LEN: usize = 2000;
+const DEFAULT_APPROVER_ROLE: &str = "sales_manager";
+const APPROVAL_TTL_HOURS: i64 = 4;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListApprovalsQuery {
+    /// `pending`, `approved`, `rejected`, `escalated` or `revision_requested`.
+    pub status: Option<String>,
+    pub approver_role: Option<String>,
+    pub quote_id: Option<String>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RequestApprovalRequest {
+    pub justification: String,
+    /// Defaults to `sales_manager`.
+    #[serde(default)]
+    pub approver_role: Option<String>,
+    /// Defaults to `discount_override`.
+    #[serde(default)]
+    pub approval_type: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DecideApprovalRequest {
+    /// `approve`, `reject` or `request_revision`.
+    pub decision: String,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalResource {
+    pub id: String,
+    pub quote_id: String,
+    pub approver_role: String,
+    pub approval_type: String,
+    pub reason: String,
+    pub justification: String,
+    pub status: String,
+    pub decision_note: Option<String>,
+    pub requested_by: String,
+    pub expires_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalDecisionResource {
+    pub approval: ApprovalResource,
+    /// Quote status after the decision was applied.
+    pub quote_status: String,
+}
+
+impl From<&ApprovalRequest> for ApprovalResource {
+    fn from(approval: &ApprovalRequest) -> Self {
+        Self {
+            id: approval.id.0.clone(),
+            quote_id: approval.quote_id.0.clone(),
+            approver_role: approval.approver_role.clone(),
+            approval_type: approval.approval_type.as_str().to_string(),
+            reason: approval.reason.clone(),
+            justification: approval.justification.clone(),
+            status: approval.status.as_str().to_string(),
+            decision_note: approval.decision_note.clone(),
+            requested_by: approval.requested_by.clone(),
+            expires_at: approval.expires_at.map(|at| at.to_rfc3339()),
+            created_at: approval.created_at.to_rfc3339(),
+            updated_at: approval.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+pub async fn list_approvals(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiQuery(query): ApiQuery<ListApprovalsQuery>,
+) -> ApiResult<Json<Page<ApprovalResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let status = match optional_trimmed(query.status) {
+        Some(raw) => Some(
+            ApprovalStatus::from_str(&raw)
+                .map_err(|_| ApiError::validation(format!("unknown approval status `{raw}`")))?,
+        ),
+        None => None,
+    };
+    let approver_role = optional_trimmed(query.approver_role);
+    let quote_id = optional_trimmed(query.quote_id);
+
+    let (visibility, visibility_binds) = principal.quote_visibility_filter();
+    let mut sql = String::from(
+        "SELECT a.id, a.created_at
+         FROM approval_request a
+         JOIN quote q ON q.id = a.quote_id
+         LEFT JOIN sales_rep sr ON sr.id = q.created_by_sales_rep_id
+         WHERE 1 = 1",
+    );
+    sql.push_str(&visibility);
+    if status.is_some() {
+        sql.push_str(" AND a.status = ?");
+    }
+    if approver_role.is_some() {
+        sql.push_str(" AND a.approver_role = ?");
+    }
+    if quote_id.is_some() {
+        sql.push_str(" AND a.quote_id = ?");
+    }
+    if page.after.is_some() {
+        sql.push_str(" AND (a.created_at < ? OR (a.created_at = ? AND a.id < ?))");
+    }
+    sql.push_str(" ORDER BY a.created_at DESC, a.id DESC LIMIT ?");
+
+    let mut rows = sqlx::query_as::<_, (String, String)>(&sql);
+    for value in &visibility_binds {
+        rows = rows.bind(value);
+    }
+    if let Some(status) = &status {
+        rows = rows.bind(status.as_str());
+    }
+    if let Some(role) = &approver_role {
+        rows = rows.bind(role);
+    }
+    if let Some(quote_id) = &quote_id {
+        rows = rows.bind(quote_id);
+    }
+    if let Some(after) = &page.after {
+        rows = rows.bind(&after.key).bind(&after.key).bind(&after.id);
+    }
+    let rows = rows.bind(page.fetch_limit()).fetch_all(&state.db_pool).await?;
+    let (rows, next_cursor) = page.finish(rows, |(id, created_at)| Cursor::new(created_at, id));
+
+    let repo = SqlApprovalRepository::new(state.db_pool.clone());
+    let mut data = Vec::with_capacity(rows.len());
+    for (id, _) in rows {
+        if let Some(approval) = repo.find_by_id(&ApprovalId(id)).await? {
+            data.push(ApprovalResource::from(&approval));
+        }
+    }
+    Ok(Json(Page { data, next_cursor }))
+}
+
+pub async fn get_approval(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ApprovalResource>> {
+    let approval = load_approval(&state, &principal, &id).await?;
+    Ok(Json(ApprovalResource::from(&approval)))
+}
+
+pub async fn request_approval(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<RequestApprovalRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "approval.request",
+        Some(&id),
+        &payload,
+        || async {
+            let justification = body.justification.trim().to_string();
+            if justification.is_empty() {
+                return Err(ApiError::validation("justification is required"));
+            }
+            if justification.chars().count() > MAX_JUSTIFICATION_LEN {
+                return Err(ApiError::validation(format!(
+                    "justification must be {MAX_JUSTIFICATION_LEN} characters or fewer"
+                )));
+            }
+            let approval_type = match optional_trimmed(body.approval_type) {
+                Some(raw) => ApprovalType::from_str(&raw)
+                    .map_err(|_| ApiError::validation(format!("unknown approval type `{raw}`")))?,
+                None => ApprovalType::DiscountOverride,
+            };
+            let approver_role = optional_trimmed(body.approver_role)
+                .unwrap_or_else(|| DEFAULT_APPROVER_ROLE.to_string());
+
+            let mut quote = load_quote(&state, &principal, &id).await?;
+            if matches!(
+                quote.status,
+                QuoteStatus::Approved
+                    | QuoteStatus::Sent
+                    | QuoteStatus::Accepted
+                    | QuoteStatus::Expired
+                    | QuoteStatus::Cancelled
+            ) {
+                return Err(ApiError::conflict(format!(
+                    "Quote '{}' is in '{}' state and cannot be submitted for approval",
+                    quote.id.0,
+                    quote_status_as_str(&quote.status)
+                )));
+            }
+
+            let repo = SqlApprovalRepository::new(state.db_pool.clone());
+            if let Some(existing) = repo.find_by_quote_id(&quote.id).await?.into_iter().find(|a| {
+                a.status == ApprovalStatus::Pending
+                    && a.approver_role.eq_ignore_ascii_case(&approver_role)
+            }) {
+                return Err(ApiError::conflict(
+                    "A pending approval already exists for this quote and approver role",
+                )
+                .with_details(serde_json::json!({ "approval_id": existing.id.0 })));
+            }
+
+            let now = Utc::now();
+            let approval = ApprovalRequest {
+                id: ApprovalId(format!("APR-{}", &uuid::Uuid::new_v4().simple().to_string()[..12])),
+                quote_id: quote.id.clone(),
+                approver_role: approver_role.clone(),
+                approval_type,
+                reason: optional_trimmed(body.reason)
+                    .unwrap_or_else(|| format!("Approval requested for quote {}", quote.id.0)),
+                justification,
+                payload_json: "{}".to_string(),
+                status: ApprovalStatus::Pending,
+                decision_note: None,
+                requested_by: principal.actor(),
+                expires_at: Some(now + Duration::hours(APPROVAL_TTL_HOURS)),
+                created_at: now,
+                updated_at: now,
+            };
+            repo.save(approval.clone()).await?;
+
+            // A priced quote enters the approval stage with its first request.
+            if quote.status == QuoteStatus::Priced {
+                quote.transition_to(QuoteStatus::Approval)?;
+                quote.version += 1;
+                quote.updated_at = now;
+                SqlQuoteRepository::new(state.db_pool.clone()).save(quote.clone()).await?;
+            }
+
+            auto_comment(
+                &state.db_pool,
+                &quote.id.0,
+                "approval_submitted",
+                &format!(
+                    "Approval request {} submitted via REST API for role '{approver_role}'.",
+                    approval.id.0
+                ),
+            )
+            .await;
+            Mutation::new(
+                StatusCode::CREATED,
+                quote.id.0.clone(),
+                ApprovalResource::from(&approval),
+            )
+        },
+    )
+    .await
+}
+
+pub async fn decide_approval(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<DecideApprovalRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "approval_id": &id, "body": &body });
+    // The ledger row hangs off the quote, which is only known after loading the approval.
+    let quote_id = match load_approval(&state, &principal, &id).await {
+        Ok(approval) => approval.quote_id.0,
+        Err(error) => return axum::response::IntoResponse::into_response(error),
+    };
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "approval.decide",
+        Some(&quote_id),
+        &payload,
+        || async {
+            let (status, quote_status) = match body.decision.trim().to_ascii_lowercase().as_str() {
+                "approve" => (ApprovalStatus::Approved, QuoteStatus::Approved),
+                "reject" => (ApprovalStatus::Rejected, QuoteStatus::Rejected),
+                "request_revision" => (ApprovalStatus::RevisionRequested, QuoteStatus::Revised),
+                other => {
+                    return Err(ApiError::validation(format!(
+                        "decision must be approve, reject or request_revision (got `{other}`)"
+                    )))
+                }
+            };
+
+            let mut approval = load_approval(&state, &principal, &id).await?;
+            if !matches!(approval.status, ApprovalStatus::Pending | ApprovalStatus::Escalated) {
+                return Err(ApiError::conflict(format!(
+                    "approval is already `{}`",
+                    approval.status.as_str()
+                )));
+            }
+            let now = Utc::now();
+            approval.status = status.clone();
+            approval.decision_note = optional_trimmed(body.note);
+            approval.updated_at = now;
+            let repo = SqlApprovalRepository::new(state.db_pool.clone());
+            repo.save(approval.clone()).await?;
+
+            let mut quote = load_quote(&state, &principal, &approval.quote_id.0).await?;
+            // Approval only completes once no other request on the quote is still open.
+            let still_open = repo.find_by_quote_id(&quote.id).await?.iter().any(|other| {
+                other.id != approval.id
+                    && matches!(other.status, ApprovalStatus::Pending | ApprovalStatus::Escalated)
+            });
+            let advance = quote.status == QuoteStatus::Approval
+                && !(status == ApprovalStatus::Approved && still_open);
+            if advance && quote.can_transition_to(quote_status.clone()) {
+                quote.transition_to(quote_status)?;
+                quote.version += 1;
+                quote.updated_at = now;
+                SqlQuoteRepository::new(state.db_pool.clone()).save(quote.clone()).await?;
+            }
+
+            auto_comment(
+                &state.db_pool,
+                &quote.id.0,
+                "approval_decided",
+                &format!(
+                    "Approval {} marked {} by {} via REST API.",
+                    approval.id.0,
+                    approval.status.as_str(),
+                    principal.actor()
+                ),
+            )
+            .await;
+            crate::webhooks::emit(
+                &state.db_pool,
+                WebhookEventType::ApprovalDecided,
+                &quote.id.0,
+                serde_json::json!({
+                    "approval_id": approval.id.0,
+                    "status": approval.status.as_str(),
+                    "note": approval.decision_note,
+                    "quote_status": quote_status_as_str(&quote.status),
+                    "actor": principal.actor(),
+                    "source": "rest_api",
+                }),
+            )
+            .await;
+            Mutation::new(
+                StatusCode::OK,
+                quote.id.0.clone(),
+                ApprovalDecisionResource {
+                    approval: ApprovalResource::from(&approval),
+                    quote_status: quote_status_as_str(&quote.status).to_string(),
+                },
+            )
+        },
+    )
+    .await
+}
+
+async fn load_approval(
+    state: &ApiState,
+    principal: &ApiPrincipal,
+    id: &str,
+) -> ApiResult<ApprovalRequest> {
+    let id = normalize_id(id, "approval id")?;
+    let approval = SqlApprovalRepository::new(state.db_pool.clone())
+        .find_by_id(&ApprovalId(id.clone()))
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Approval '{id}' not found")))?;
+    principal.ensure_quote_access(&state.db_pool, &approval.quote_id.0).await?;
+    Ok(approval)
+}
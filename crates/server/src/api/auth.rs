@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use quotey_core::{hash_api_key, ApiKeyGrants, ApiScope};
+use quotey_db::repositories::{ApiKeyRepository, SqlApiKeyRepository};
+use quotey_db::DbPool;
+use tracing::warn;
+
+use super::error::{ApiError, ApiResult};
+use super::ApiState;
+
+/// The API key a request was authenticated with, inserted into request extensions.
+#[derive(Clone, Debug)]
+pub struct ApiPrincipal {
+    pub key_id: String,
+    pub key_name: String,
+    pub grants: ApiKeyGrants,
+}
+
+impl ApiPrincipal {
+    /// Actor recorded on rows written through the API.
+    pub fn actor(&self) -> String {
+        format!("api:{}", self.key_name)
+    }
+
+    pub fn ensure_account(&self, account_id: Option<&str>) -> ApiResult<()> {
+        if !self.grants.is_account_restricted() {
+            return Ok(());
+        }
+        match account_id {
+            Some(account_id) if self.grants.allows_account(account_id) => Ok(()),
+            Some(account_id) => Err(ApiError::forbidden(format!(
+                "API key `{}` is not permitted to access account `{account_id}`",
+                self.key_name
+            ))),
+            None => Err(ApiError::forbidden(format!(
+                "API key `{}` is restricted to specific accounts",
+                self.key_name
+            ))),
+        }
+    }
+
+    pub fn ensure_team(&self, team_id: Option<&str>) -> ApiResult<()> {
+        if !self.grants.is_team_restricted() {
+            return Ok(());
+        }
+        match team_id {
+            Some(team_id) if self.grants.allows_team(team_id) => Ok(()),
+            _ => Err(ApiError::forbidden(format!(
+                "API key `{}` is restricted to specific teams",
+                self.key_name
+            ))),
+        }
+    }
+
+    /// Checks account and team restrictions against an existing quote.
+    ///
+    /// Missing quotes pass so the handler can answer with its own `NOT_FOUND`.
+    pub async fn ensure_quote_access(&self, pool: &DbPool, quote_id: &str) -> ApiResult<()> {
+        if !self.grants.is_account_restricted() && !self.grants.is_team_restricted() {
+            return Ok(());
+        }
+        let owner: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT q.account_id, sr.team_id
+             FROM quote q
+             LEFT JOIN sales_rep sr ON sr.id = q.created_by_sales_rep_id
+             WHERE q.id = ?",
+        )
+        .bind(quote_id)
+        .fetch_optional(pool)
+        .await?;
+        match owner {
+            Some((account_id, team_id)) => {
+                self.ensure_account(account_id.as_deref())?;
+                self.ensure_team(team_id.as_deref())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// SQL predicate limiting a `quote q LEFT JOIN sales_rep sr` listing to this key's grants.
+    ///
+    /// Returns the clause (prefixed with ` AND`) and the values to bind, in order.
+    pub fn quote_visibility_filter(&self) -> (String, Vec<String>) {
+        let mut clause = String::new();
+        let mut binds = Vec::new();
+        if self.grants.is_account_restricted() {
+            clause.push_str(&format!(
+                " AND q.account_id IN ({})",
+                placeholders(self.grants.account_ids.len())
+            ));
+            binds.extend(self.grants.account_ids.iter().cloned());
+        }
+        if self.grants.is_team_restricted() {
+            clause.push_str(&format!(
+                " AND sr.team_id IN ({})",
+                placeholders(self.grants.team_ids.len())
+            ));
+            binds.extend(self.grants.team_ids.iter().cloned());
+        }
+        (clause, binds)
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Start of the current window and requests counted in it.
+type RateWindow = (DateTime<Utc>, u32);
+
+/// Fixed one-minute windows per key, enforcing each key's `requests_per_minute`.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    windows: Arc<Mutex<HashMap<String, RateWindow>>>,
+}
+
+impl RateLimiter {
+    fn check(&self, key_id: &str, limit: u32, now: DateTime<Utc>) -> Result<(), i64> {
+        let mut windows = self.windows.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entry = windows.entry(key_id.to_string()).or_insert((now, 0));
+        if now - entry.0 >= Duration::minutes(1) {
+            *entry = (now, 0);
+        }
+        if entry.1 >= limit {
+            let retry_after = (entry.0 + Duration::minutes(1) - now).num_seconds().max(1);
+            return Err(retry_after);
+        }
+        entry.1 += 1;
+        Ok(())
+    }
+}
+
+/// Per-route middleware state: the API state plus the scope the route requires.
+#[derive(Clone)]
+pub struct RouteGuard {
+    pub state: ApiState,
+    pub scope: ApiScope,
+}
+
+pub async fn authenticate(
+    State(guard): State<RouteGuard>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match resolve_principal(&guard.state, request.headers(), guard.scope).await {
+        Ok(principal) => {
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn resolve_principal(
+    state: &ApiState,
+    headers: &HeaderMap,
+    required: ApiScope,
+) -> ApiResult<ApiPrincipal> {
+    let secret = presented_key(headers).ok_or_else(|| {
+        ApiError::unauthenticated("provide an API key via `Authorization: Bearer` or `X-API-Key`")
+    })?;
+
+    let repo = SqlApiKeyRepository::new(state.db_pool.clone());
+    let record = repo
+        .find_by_hash(&hash_api_key(&secret))
+        .await?
+        .filter(|record| record.active && record.revoked_at.is_none())
+        .ok_or_else(|| ApiError::unauthenticated("API key is invalid or has been revoked"))?;
+
+    if !record.grants.allows_scope(required) {
+        return Err(ApiError::forbidden(format!(
+            "API key `{}` lacks the `{required}` scope",
+            record.name
+        ))
+        .with_details(serde_json::json!({ "required_scope": required.as_str() })));
+    }
+
+    let now = Utc::now();
+    if let Err(retry_after) = state.rate_limiter.check(&record.id, record.requests_per_minute, now)
+    {
+        return Err(ApiError::rate_limited(format!(
+            "API key `{}` exceeded {} requests per minute",
+            record.name, record.requests_per_minute
+        ))
+        .with_details(serde_json::json!({ "retry_after_seconds": retry_after })));
+    }
+
+    if let Err(error) = repo.touch_last_used(&record.id, now).await {
+        warn!(error = %error, key_id = %record.id, "failed to record API key usage");
+    }
+
+    Ok(ApiPrincipal { key_id: record.id, key_name: record.name, grants: record.grants })
+}
+
+fn presented_key(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("Bearer "))
+        .map(str::trim);
+    let header_key = headers.get("x-api-key").and_then(|value| value.to_str().ok()).map(str::trim);
+    bearer.or(header_key).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(accounts: &[&str], teams: &[&str]) -> ApiPrincipal {
+        ApiPrincipal {
+            key_id: "KEY-1".to_string(),
+            key_name: "ci".to_string(),
+            grants: ApiKeyGrants {
+                scopes: vec![ApiScope::QuoteRead],
+                account_ids: accounts.iter().map(|value| value.to_string()).collect(),
+                team_ids: teams.iter().map(|value| value.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn account_restrictions_apply_only_to_restricted_keys() {
+        let open = principal(&[], &[]);
+        assert!(open.ensure_account(None).is_ok());
+
+        let scoped = principal(&["acct-1"], &[]);
+        assert!(scoped.ensure_account(Some("acct-1")).is_ok());
+        assert_eq!(
+            scoped.ensure_account(Some("acct-2")).expect_err("denied").code,
+            "AUTHORIZATION_FAILED"
+        );
+        assert!(scoped.ensure_account(None).is_err());
+    }
+
+    #[test]
+    fn visibility_filter_binds_each_grant() {
+        let (clause, binds) = principal(&["a", "b"], &["t"]).quote_visibility_filter();
+        assert_eq!(clause, " AND q.account_id IN (?, ?) AND sr.team_id IN (?)");
+        assert_eq!(binds, vec!["a", "b", "t"]);
+        assert_eq!(principal(&[], &[]).quote_visibility_filter().0, "");
+    }
+
+    #[test]
+    fn bearer_header_wins_over_x_api_key() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "from-header".parse().expect("header"));
+        assert_eq!(presented_key(&headers).as_deref(), Some("from-header"));
+        headers.insert(axum::http::header::AUTHORIZATION, "Bearer tok".parse().expect("header"));
+        assert_eq!(presented_key(&headers).as_deref(), Some("tok"));
+    }
+
+    #[test]
+    fn rate_limiter_resets_each_minute() {
+        let limiter = RateLimiter::default();
+        let start = Utc::now();
+        assert!(limiter.check("k", 2, start).is_ok());
+        assert!(limiter.check("k", 2, start).is_ok());
+        assert!(limiter.check("k", 2, start).is_err());
+        assert!(limiter.check("k", 2, start + Duration::seconds(61)).is_ok());
+    }
+}
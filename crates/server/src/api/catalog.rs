@@ -0,0 +1,453 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{NaiveDate, Utc};
+use quotey_core::domain::product::{CostComponent, Product, ProductCost, ProductId};
+use quotey_db::catalog::{
+    CatalogDocument, CatalogError, CatalogFormat, CatalogService, FamilyChange, FieldChange,
+    ImportReport, ImportRequest, ImportSummary, ProductChange,
+};
+use quotey_db::repositories::{ProductRepository, SqlProductCostRepository, SqlProductRepository};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult};
+use super::pagination::{Cursor, Page, PageRequest};
+use super::quotes::{normalize_id, optional_trimmed};
+use super::{ApiJson, ApiQuery, ApiState};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ProductSearchQuery {
+    /// Full-text search over name, SKU and description (prefix match).
+    pub q: Option<String>,
+    pub family_id: Option<String>,
+    /// Include inactive products when `false` (default `true`).
+    pub active_only: Option<bool>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductResource {
+    pub id: String,
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub product_type: String,
+    pub family_id: Option<String>,
+    /// List price as a decimal string; `null` for products priced by rule only.
+    pub base_price: Option<String>,
+    pub currency: String,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&Product> for ProductResource {
+    fn from(product: &Product) -> Self {
+        Self {
+            id: product.id.0.clone(),
+            sku: product.sku.clone(),
+            name: product.name.clone(),
+            description: product.description.clone(),
+            product_type: product.product_type.as_str().to_string(),
+            family_id: product.family_id.as_ref().map(|family| family.0.clone()),
+            base_price: product.base_price.map(|price| price.to_string()),
+            currency: product.currency.clone(),
+            active: product.active,
+            created_at: product.created_at.to_rfc3339(),
+            updated_at: product.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CostComponentResource {
+    pub name: String,
+    /// Decimal string in the cost currency.
+    pub unit_cost: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecordProductCostRequest {
+    /// Decimal string in `currency`; must equal the sum of `components` when they are given.
+    pub unit_cost: String,
+    /// ISO currency code; the cost only applies to quotes in this currency.
+    pub currency: String,
+    /// First day the cost applies (`YYYY-MM-DD`).
+    pub effective_from: String,
+    /// First day the cost no longer applies (`YYYY-MM-DD`); open-ended when absent.
+    #[serde(default)]
+    pub effective_until: Option<String>,
+    /// Optional COGS breakdown.
+    #[serde(default)]
+    pub components: Vec<CostComponentResource>,
+}
+
+/// A product cost record. Costs are confidential and need `catalog:admin`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductCostResource {
+    pub id: String,
+    pub product_id: String,
+    pub unit_cost: String,
+    pub currency: String,
+    pub effective_from: String,
+    pub effective_until: Option<String>,
+    pub components: Vec<CostComponentResource>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+impl From<&ProductCost> for ProductCostResource {
+    fn from(cost: &ProductCost) -> Self {
+        Self {
+            id: cost.id.clone(),
+            product_id: cost.product_id.0.clone(),
+            unit_cost: cost.unit_cost.to_string(),
+            currency: cost.currency.clone(),
+            effective_from: cost.effective_from.to_string(),
+            effective_until: cost.effective_until.map(|until| until.to_string()),
+            components: cost
+                .components
+                .iter()
+                .map(|component| CostComponentResource {
+                    name: component.name.clone(),
+                    unit_cost: component.unit_cost.to_string(),
+                })
+                .collect(),
+            created_by: cost.created_by.clone(),
+            created_at: cost.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductCostListResource {
+    pub product_id: String,
+    /// Newest `effective_from` first; on any day the newest record covering it applies.
+    pub data: Vec<ProductCostResource>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogExportQuery {
+    /// `json` (default) or `csv`.
+    pub format: Option<String>,
+}
+
+/// The whole catalog, inactive products included. Exactly one of `document` and `csv` is set,
+/// matching `format`; either can be sent back to the import endpoint unchanged.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogExportResource {
+    pub format: String,
+    pub families: usize,
+    pub products: usize,
+    /// `catalog.v1` document with `families` and `products`.
+    pub document: Option<serde_json::Value>,
+    /// One CSV sheet with a `record` column (`family`, `product`, `attribute`, `bundle_member`).
+    pub csv: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportCatalogRequest {
+    /// `catalog.v1` JSON document; give this or `csv`.
+    #[serde(default)]
+    pub document: Option<serde_json::Value>,
+    /// CSV sheet in the export layout; give this or `document`.
+    #[serde(default)]
+    pub csv: Option<String>,
+    /// Only report the diff (default `true`). Imports cover the whole catalog: products missing
+    /// from the file are deactivated when applied.
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FieldChangeResource {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl From<&FieldChange> for FieldChangeResource {
+    fn from(change: &FieldChange) -> Self {
+        Self {
+            field: change.field.clone(),
+            before: change.before.clone(),
+            after: change.after.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FamilyChangeResource {
+    pub family_id: String,
+    /// `create` or `update`.
+    pub kind: String,
+    pub changes: Vec<FieldChangeResource>,
+}
+
+impl From<&FamilyChange> for FamilyChangeResource {
+    fn from(change: &FamilyChange) -> Self {
+        Self {
+            family_id: change.family_id.clone(),
+            kind: change.kind.as_str().to_string(),
+            changes: change.changes.iter().map(FieldChangeResource::from).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProductChangeResource {
+    pub product_id: String,
+    pub sku: String,
+    /// `create`, `update` or `deactivate`.
+    pub kind: String,
+    pub changes: Vec<FieldChangeResource>,
+}
+
+impl From<&ProductChange> for ProductChangeResource {
+    fn from(change: &ProductChange) -> Self {
+        Self {
+            product_id: change.product_id.clone(),
+            sku: change.sku.clone(),
+            kind: change.kind.as_str().to_string(),
+            changes: change.changes.iter().map(FieldChangeResource::from).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ImportSummaryResource {
+    pub families_created: usize,
+    pub families_updated: usize,
+    pub products_created: usize,
+    pub products_updated: usize,
+    pub products_deactivated: usize,
+    pub products_unchanged: usize,
+}
+
+impl From<&ImportSummary> for ImportSummaryResource {
+    fn from(summary: &ImportSummary) -> Self {
+        Self {
+            families_created: summary.families_created,
+            families_updated: summary.families_updated,
+            products_created: summary.products_created,
+            products_updated: summary.products_updated,
+            products_deactivated: summary.products_deactivated,
+            products_unchanged: summary.products_unchanged,
+        }
+    }
+}
+
+/// The diff an import makes (or would make, for dry runs). Unchanged products are only counted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CatalogImportResource {
+    pub dry_run: bool,
+    /// Correlation id of the import's audit events; `null` for dry runs.
+    pub import_id: Option<String>,
+    pub summary: ImportSummaryResource,
+    pub families: Vec<FamilyChangeResource>,
+    pub products: Vec<ProductChangeResource>,
+}
+
+impl From<&ImportReport> for CatalogImportResource {
+    fn from(report: &ImportReport) -> Self {
+        Self {
+            dry_run: report.dry_run,
+            import_id: report.import_id.clone(),
+            summary: ImportSummaryResource::from(&report.diff.summary),
+            families: report.diff.families.iter().map(FamilyChangeResource::from).collect(),
+            products: report.diff.products.iter().map(ProductChangeResource::from).collect(),
+        }
+    }
+}
+
+pub async fn search_products(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<ProductSearchQuery>,
+) -> ApiResult<Json<Page<ProductResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let text = optional_trimmed(query.q).map(|text| text.replace('"', ""));
+    let family_id = optional_trimmed(query.family_id);
+
+    // Ordered by (name, id) rather than FTS rank so the keyset cursor is stable across pages.
+    let mut sql = String::from("SELECT p.id, p.name FROM product p WHERE 1 = 1");
+    if query.active_only.unwrap_or(true) {
+        sql.push_str(" AND p.active = 1");
+    }
+    if text.is_some() {
+        sql.push_str(" AND p.id IN (SELECT product_id FROM product_fts WHERE product_fts MATCH ?)");
+    }
+    if family_id.is_some() {
+        sql.push_str(" AND p.family_id = ?");
+    }
+    if page.after.is_some() {
+        sql.push_str(" AND (p.name > ? OR (p.name = ? AND p.id > ?))");
+    }
+    sql.push_str(" ORDER BY p.name ASC, p.id ASC LIMIT ?");
+
+    let mut rows = sqlx::query_as::<_, (String, String)>(&sql);
+    if let Some(text) = &text {
+        rows = rows.bind(format!("\"{text}\"*"));
+    }
+    if let Some(family_id) = &family_id {
+        rows = rows.bind(family_id);
+    }
+    if let Some(after) = &page.after {
+        rows = rows.bind(&after.key).bind(&after.key).bind(&after.id);
+    }
+    let rows = rows.bind(page.fetch_limit()).fetch_all(&state.db_pool).await?;
+    let (rows, next_cursor) = page.finish(rows, |(id, name)| Cursor::new(name, id));
+
+    let repo = SqlProductRepository::new(state.db_pool.clone());
+    let mut data = Vec::with_capacity(rows.len());
+    for (id, _) in rows {
+        if let Some(product) = repo.find_by_id(&ProductId(id)).await? {
+            data.push(ProductResource::from(&product));
+        }
+    }
+    Ok(Json(Page { data, next_cursor }))
+}
+
+pub async fn get_product(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ProductResource>> {
+    let product = load_product(&state, &id).await?;
+    Ok(Json(ProductResource::from(&product)))
+}
+
+pub async fn list_product_costs(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ProductCostListResource>> {
+    let product = load_product(&state, &id).await?;
+    let costs =
+        SqlProductCostRepository::new(state.db_pool.clone()).list_for_product(&product.id).await?;
+    Ok(Json(ProductCostListResource {
+        product_id: product.id.0,
+        data: costs.iter().map(ProductCostResource::from).collect(),
+    }))
+}
+
+pub async fn record_product_cost(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<RecordProductCostRequest>,
+) -> ApiResult<(StatusCode, Json<ProductCostResource>)> {
+    let product = load_product(&state, &id).await?;
+    let cost = ProductCost {
+        id: format!("COST-{}", Uuid::new_v4().simple()),
+        product_id: product.id,
+        unit_cost: parse_amount(&body.unit_cost, "unit_cost")?,
+        currency: body.currency.trim().to_ascii_uppercase(),
+        effective_from: parse_date(&body.effective_from, "effective_from")?,
+        effective_until: body
+            .effective_until
+            .as_deref()
+            .map(|until| parse_date(until, "effective_until"))
+            .transpose()?,
+        components: body
+            .components
+            .iter()
+            .map(|component| {
+                Ok(CostComponent {
+                    name: component.name.trim().to_string(),
+                    unit_cost: parse_amount(&component.unit_cost, "components[].unit_cost")?,
+                })
+            })
+            .collect::<ApiResult<_>>()?,
+        created_by: principal.actor(),
+        created_at: Utc::now(),
+    };
+    cost.validate().map_err(ApiError::validation)?;
+    SqlProductCostRepository::new(state.db_pool.clone()).record(&cost).await?;
+    Ok((StatusCode::CREATED, Json(ProductCostResource::from(&cost))))
+}
+
+pub async fn export_catalog(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<CatalogExportQuery>,
+) -> ApiResult<Json<CatalogExportResource>> {
+    let format = match optional_trimmed(query.format) {
+        Some(label) => CatalogFormat::parse_label(&label)
+            .ok_or_else(|| ApiError::validation("format must be json or csv"))?,
+        None => CatalogFormat::Json,
+    };
+    let document =
+        CatalogService::new(state.db_pool.clone()).export().await.map_err(catalog_error)?;
+    let (json, csv) = match format {
+        CatalogFormat::Json => (
+            Some(serde_json::to_value(&document).map_err(|error| ApiError::internal(&error))?),
+            None,
+        ),
+        CatalogFormat::Csv => (None, Some(document.render(format))),
+    };
+    Ok(Json(CatalogExportResource {
+        format: format.as_str().to_string(),
+        families: document.families.len(),
+        products: document.products.len(),
+        document: json,
+        csv,
+    }))
+}
+
+pub async fn import_catalog(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiJson(body): ApiJson<ImportCatalogRequest>,
+) -> ApiResult<Json<CatalogImportResource>> {
+    let document = match (body.document, body.csv) {
+        (Some(document), None) => serde_json::from_value::<CatalogDocument>(document)
+            .map_err(|error| ApiError::validation(format!("invalid catalog document: {error}")))?,
+        (None, Some(csv)) => {
+            CatalogDocument::parse(CatalogFormat::Csv, &csv).map_err(catalog_error)?
+        }
+        _ => return Err(ApiError::validation("send exactly one of document and csv")),
+    };
+    let report = CatalogService::new(state.db_pool.clone())
+        .import(ImportRequest {
+            document,
+            dry_run: body.dry_run.unwrap_or(true),
+            actor: principal.actor(),
+            source: "api".to_string(),
+        })
+        .await
+        .map_err(catalog_error)?;
+    Ok(Json(CatalogImportResource::from(&report)))
+}
+
+fn catalog_error(error: CatalogError) -> ApiError {
+    match &error {
+        CatalogError::Parse(_) => ApiError::validation(error.to_string()),
+        CatalogError::Invalid(issues) => ApiError::validation(error.to_string())
+            .with_details(serde_json::json!({ "issues": issues })),
+        CatalogError::Repository(_) => ApiError::internal(&error),
+    }
+}
+
+async fn load_product(state: &ApiState, id: &str) -> ApiResult<Product> {
+    let id = normalize_id(id, "product id")?;
+    SqlProductRepository::new(state.db_pool.clone())
+        .find_by_id(&ProductId(id.clone()))
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Product '{id}' not found")))
+}
+
+fn parse_amount(value: &str, field: &str) -> ApiResult<Decimal> {
+    Decimal::from_str(value.trim())
+        .map_err(|_| ApiError::validation(format!("{field} must be a decimal string")))
+}
+
+fn parse_date(value: &str, field: &str) -> ApiResult<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| ApiError::validation(format!("{field} must be a YYYY-MM-DD date")))
+}
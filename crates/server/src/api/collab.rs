@@ -0,0 +1,637 @@
+//! Live collaborative editing sessions on a quote.
+//!
+//! Participants join with a role that sets the authority of their edits, submit line operations
+//! against the last sequence number they saw, and follow the session over a server-sent event
+//! stream. Every resolved operation is pushed to the session; overridden operations are also
+//! pushed to their author alone.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    Extension, Json,
+};
+use quotey_core::collab::OperationType;
+use quotey_core::domain::product::ProductId;
+use quotey_core::domain::quote::QuoteLine;
+use quotey_db::collab::{
+    CollabError, CollabEvent, CollabService, CollabSession, CommittedRevision, Participant,
+    ResolvedOperation, SessionState, SubmitOperations, SubmitOutcome,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::auth::ApiPrincipal;
+use super::comments::auto_comment;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::quotes::{load_quote, money, normalize_id, QuoteResource};
+use super::{ApiJson, ApiQuery, ApiState};
+
+/// Events buffered per session before a slow subscriber is told to resync.
+const EVENT_BUFFER: usize = 256;
+
+/// Fan-out of session events to the open event streams, one channel per session.
+#[derive(Clone, Default)]
+pub struct CollabHub {
+    channels: Arc<Mutex<HashMap<String, broadcast::Sender<CollabEvent>>>>,
+}
+
+impl CollabHub {
+    fn subscribe(&self, session_id: &str) -> broadcast::Receiver<CollabEvent> {
+        let mut channels = self.channels.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        channels
+            .entry(session_id.to_string())
+            .or_insert_with(|| broadcast::channel(EVENT_BUFFER).0)
+            .subscribe()
+    }
+
+    fn publish(&self, session_id: &str, events: Vec<CollabEvent>) {
+        let mut channels = self.channels.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(sender) = channels.get(session_id) else {
+            return;
+        };
+        let closing = events.iter().any(|event| matches!(event, CollabEvent::Committed { .. }));
+        for event in events {
+            // No receivers just means nobody is streaming right now.
+            let _ = sender.send(event);
+        }
+        if closing || sender.receiver_count() == 0 {
+            channels.remove(session_id);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct JoinSessionRequest {
+    pub user_id: String,
+    /// `sales_rep`, `sales_manager`, `deal_desk`, `finance` or `vp_sales`; higher roles win
+    /// conflicting edits. The role is taken from the user's `sales_rep` record; asking for one
+    /// can only lower it.
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HeartbeatRequest {
+    pub user_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OperationInput {
+    /// Adds a line, or replaces the line for the same product.
+    Insert {
+        product_id: String,
+        quantity: u32,
+        unit_price: f64,
+        #[serde(default)]
+        discount_pct: f64,
+    },
+    Update {
+        product_id: String,
+        #[serde(default)]
+        quantity: Option<u32>,
+        #[serde(default)]
+        unit_price: Option<f64>,
+    },
+    Delete {
+        product_id: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubmitOperationsRequest {
+    pub user_id: String,
+    /// Newest `seq` the client had applied when making these edits; 0 for none.
+    #[serde(default)]
+    pub base_seq: u64,
+    pub operations: Vec<OperationInput>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CommitSessionRequest {
+    pub user_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EventsQuery {
+    /// Participant the stream belongs to; overridden notices are only sent to their author.
+    pub user_id: String,
+    /// Replay operations recorded after this sequence before streaming live events.
+    #[serde(default)]
+    pub after: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParticipantResource {
+    pub user_id: String,
+    pub role: String,
+    pub joined_at: String,
+    pub last_activity: String,
+    /// Seen within the last minute.
+    pub online: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionLineResource {
+    pub product_id: String,
+    pub quantity: u32,
+    pub unit_price: String,
+    pub discount_pct: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvedOperationResource {
+    pub seq: u64,
+    pub operation_id: String,
+    pub actor_user_id: String,
+    pub role: String,
+    /// `applied`, `overridden` or `rejected`.
+    pub status: String,
+    pub reason: String,
+    pub superseded_by: Option<String>,
+    /// `insert`, `update` or `delete`.
+    pub kind: String,
+    pub product_id: String,
+    pub quantity: Option<u32>,
+    pub unit_price: Option<String>,
+    pub recorded_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionResource {
+    pub id: String,
+    pub quote_id: String,
+    /// `active` or `closed`.
+    pub status: String,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+    pub base_version: u32,
+    pub committed_version: Option<u32>,
+    /// Sequence of the newest operation; send it as `base_seq` with the next edits.
+    pub head_seq: u64,
+    /// Working lines: the quote's lines with every applied operation replayed.
+    pub lines: Vec<SessionLineResource>,
+    pub participants: Vec<ParticipantResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SubmitOutcomeResource {
+    pub session_id: String,
+    pub head_seq: u64,
+    pub operations: Vec<ResolvedOperationResource>,
+    pub lines: Vec<SessionLineResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LeaveResource {
+    pub session_id: String,
+    pub user_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CommitResource {
+    pub session_id: String,
+    pub previous_version: u32,
+    pub applied_operations: usize,
+    pub audit_event_id: String,
+    pub quote: QuoteResource,
+}
+
+/// Payload of each server-sent event; the SSE event name matches `type`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabEventResource {
+    Joined {
+        participant: ParticipantResource,
+    },
+    Left {
+        user_id: String,
+    },
+    Operations {
+        head_seq: u64,
+        operations: Vec<ResolvedOperationResource>,
+    },
+    Overridden {
+        operation: ResolvedOperationResource,
+    },
+    Committed {
+        version: u32,
+        audit_event_id: String,
+    },
+    /// Events were dropped for this stream; fetch the session and reconnect with `after`.
+    Resync,
+}
+
+impl From<&Participant> for ParticipantResource {
+    fn from(participant: &Participant) -> Self {
+        Self {
+            user_id: participant.user_id.clone(),
+            role: participant.role.clone(),
+            joined_at: participant.joined_at.clone(),
+            last_activity: participant.last_activity.clone(),
+            online: participant.online,
+        }
+    }
+}
+
+impl From<&QuoteLine> for SessionLineResource {
+    fn from(line: &QuoteLine) -> Self {
+        Self {
+            product_id: line.product_id.0.clone(),
+            quantity: line.quantity,
+            unit_price: money(line.unit_price),
+            discount_pct: line.discount_pct,
+        }
+    }
+}
+
+impl From<&ResolvedOperation> for ResolvedOperationResource {
+    fn from(resolved: &ResolvedOperation) -> Self {
+        let (quantity, unit_price) = match &resolved.operation {
+            OperationType::Insert { line } => (Some(line.quantity), Some(money(line.unit_price))),
+            OperationType::Update { quantity, unit_price, .. } => {
+                (*quantity, unit_price.map(money))
+            }
+            OperationType::Delete { .. } => (None, None),
+        };
+        Self {
+            seq: resolved.seq,
+            operation_id: resolved.operation_id.clone(),
+            actor_user_id: resolved.actor_user_id.clone(),
+            role: resolved.role.clone(),
+            status: resolved.status.as_str().to_string(),
+            reason: resolved.reason.clone(),
+            superseded_by: resolved.superseded_by.clone(),
+            kind: resolved.operation.kind().to_string(),
+            product_id: resolved.operation.target_key(),
+            quantity,
+            unit_price,
+            recorded_at: resolved.recorded_at.clone(),
+        }
+    }
+}
+
+impl From<&SessionState> for SessionResource {
+    fn from(state: &SessionState) -> Self {
+        let CollabSession {
+            id,
+            quote_id,
+            status,
+            created_by,
+            created_at,
+            expires_at,
+            base_version,
+            committed_version,
+        } = state.session.clone();
+        Self {
+            id,
+            quote_id,
+            status,
+            created_by,
+            created_at,
+            expires_at,
+            base_version,
+            committed_version,
+            head_seq: state.head_seq,
+            lines: state.lines.iter().map(SessionLineResource::from).collect(),
+            participants: state.participants.iter().map(ParticipantResource::from).collect(),
+        }
+    }
+}
+
+impl From<&SubmitOutcome> for SubmitOutcomeResource {
+    fn from(outcome: &SubmitOutcome) -> Self {
+        Self {
+            session_id: outcome.session_id.clone(),
+            head_seq: outcome.head_seq,
+            operations: outcome.operations.iter().map(ResolvedOperationResource::from).collect(),
+            lines: outcome.lines.iter().map(SessionLineResource::from).collect(),
+        }
+    }
+}
+
+impl From<&CollabEvent> for CollabEventResource {
+    fn from(event: &CollabEvent) -> Self {
+        match event {
+            CollabEvent::Joined { participant } => {
+                Self::Joined { participant: ParticipantResource::from(participant) }
+            }
+            CollabEvent::Left { user_id } => Self::Left { user_id: user_id.clone() },
+            CollabEvent::Operations { head_seq, operations } => Self::Operations {
+                head_seq: *head_seq,
+                operations: operations.iter().map(ResolvedOperationResource::from).collect(),
+            },
+            CollabEvent::Overridden { operation, .. } => {
+                Self::Overridden { operation: ResolvedOperationResource::from(operation) }
+            }
+            CollabEvent::Committed { revision } => Self::Committed {
+                version: revision.version,
+                audit_event_id: revision.audit_event_id.clone(),
+            },
+        }
+    }
+}
+
+impl From<CollabError> for ApiError {
+    fn from(error: CollabError) -> Self {
+        match error {
+            CollabError::SessionNotFound(_) | CollabError::QuoteNotFound(_) => {
+                Self::not_found(error.to_string())
+            }
+            CollabError::NotParticipant { .. } => Self::forbidden(error.to_string()),
+            CollabError::Invalid(_) => Self::validation(error.to_string()),
+            CollabError::SessionClosed(_)
+            | CollabError::StaleSession { .. }
+            | CollabError::QuoteClosed(_)
+            | CollabError::SequenceConflict => Self::conflict(error.to_string()),
+            CollabError::Repository(_) => Self::internal(&error),
+        }
+    }
+}
+
+pub async fn open_session(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<JoinSessionRequest>,
+) -> ApiResult<Json<SessionResource>> {
+    let quote = load_quote(&state, &principal, &id).await?;
+    let user_id = normalize_id(&body.user_id, "user_id")?;
+    let service = CollabService::new(state.db_pool.clone());
+    let (session, participant) = service.open(&quote.id.0, &user_id, body.role.as_deref()).await?;
+    state.collab_hub.publish(&session.id, vec![CollabEvent::Joined { participant }]);
+    Ok(Json(SessionResource::from(&service.state(&session.id).await?)))
+}
+
+pub async fn get_session(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+) -> ApiResult<Json<SessionResource>> {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = load_session(&state, &principal, &service, &session_id).await?;
+    Ok(Json(SessionResource::from(&service.state(&session.id).await?)))
+}
+
+pub async fn join_session(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+    ApiJson(body): ApiJson<JoinSessionRequest>,
+) -> ApiResult<Json<ParticipantResource>> {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = load_session(&state, &principal, &service, &session_id).await?;
+    let user_id = normalize_id(&body.user_id, "user_id")?;
+    let participant = service.join(&session.id, &user_id, body.role.as_deref()).await?;
+    let resource = ParticipantResource::from(&participant);
+    state.collab_hub.publish(&session.id, vec![CollabEvent::Joined { participant }]);
+    Ok(Json(resource))
+}
+
+pub async fn leave_session(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((session_id, user_id)): Path<(String, String)>,
+) -> ApiResult<Json<LeaveResource>> {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = load_session(&state, &principal, &service, &session_id).await?;
+    let user_id = normalize_id(&user_id, "user_id")?;
+    service.leave(&session.id, &user_id).await?;
+    state.collab_hub.publish(&session.id, vec![CollabEvent::Left { user_id: user_id.clone() }]);
+    Ok(Json(LeaveResource { session_id: session.id, user_id }))
+}
+
+pub async fn heartbeat(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+    ApiJson(body): ApiJson<HeartbeatRequest>,
+) -> ApiResult<Json<ParticipantResource>> {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = load_session(&state, &principal, &service, &session_id).await?;
+    let user_id = normalize_id(&body.user_id, "user_id")?;
+    Ok(Json(ParticipantResource::from(&service.heartbeat(&session.id, &user_id).await?)))
+}
+
+pub async fn submit_operations(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<SubmitOperationsRequest>,
+) -> Response {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = match load_session(&state, &principal, &service, &session_id).await {
+        Ok(session) => session,
+        Err(error) => return axum::response::IntoResponse::into_response(error),
+    };
+    let payload = serde_json::json!({ "session_id": &session.id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.collab.operations",
+        Some(&session.quote_id),
+        &payload,
+        || async {
+            let operations =
+                body.operations.iter().map(operation).collect::<ApiResult<Vec<_>>>()?;
+            let outcome = service
+                .submit(SubmitOperations {
+                    session_id: session.id.clone(),
+                    user_id: normalize_id(&body.user_id, "user_id")?,
+                    base_seq: body.base_seq,
+                    operations,
+                })
+                .await?;
+            state.collab_hub.publish(&session.id, CollabEvent::for_submission(&outcome));
+            Mutation::new(
+                StatusCode::OK,
+                session.quote_id.clone(),
+                SubmitOutcomeResource::from(&outcome),
+            )
+        },
+    )
+    .await
+}
+
+pub async fn commit_session(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<CommitSessionRequest>,
+) -> Response {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = match load_session(&state, &principal, &service, &session_id).await {
+        Ok(session) => session,
+        Err(error) => return axum::response::IntoResponse::into_response(error),
+    };
+    let payload = serde_json::json!({ "session_id": &session.id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.collab.commit",
+        Some(&session.quote_id),
+        &payload,
+        || async {
+            let user_id = normalize_id(&body.user_id, "user_id")?;
+            let revision: CommittedRevision = service
+                .commit(
+                    &session.id,
+                    &user_id,
+                    &format!("api-collab-commit-{}", uuid::Uuid::new_v4()),
+                )
+                .await?;
+            auto_comment(
+                &state.db_pool,
+                &revision.quote_id,
+                "collab_committed",
+                &format!(
+                    "Collaborative session {} committed as quote version {} ({} edits).",
+                    revision.session_id, revision.version, revision.applied_operations
+                ),
+            )
+            .await;
+            state
+                .collab_hub
+                .publish(&session.id, vec![CollabEvent::Committed { revision: revision.clone() }]);
+
+            let quote = load_quote(&state, &principal, &revision.quote_id).await?;
+            Mutation::new(
+                StatusCode::OK,
+                revision.quote_id.clone(),
+                CommitResource {
+                    session_id: revision.session_id,
+                    previous_version: revision.previous_version,
+                    applied_operations: revision.applied_operations,
+                    audit_event_id: revision.audit_event_id,
+                    quote: QuoteResource::from(&quote),
+                },
+            )
+        },
+    )
+    .await
+}
+
+/// Streams session events as `text/event-stream`, replaying operations after `after` first.
+pub async fn stream_events(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(session_id): Path<String>,
+    ApiQuery(query): ApiQuery<EventsQuery>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let service = CollabService::new(state.db_pool.clone());
+    let session = load_session(&state, &principal, &service, &session_id).await?;
+    let user_id = normalize_id(&query.user_id, "user_id")?;
+    service.heartbeat(&session.id, &user_id).await?;
+
+    // Subscribe before reading the backlog so nothing recorded in between is missed.
+    let live = BroadcastStream::new(state.collab_hub.subscribe(&session.id));
+    let backlog = service.operations_since(&session.id, query.after.unwrap_or(0)).await?;
+    let replayed_to = backlog.last().map(|operation| operation.seq).unwrap_or(0);
+
+    let backlog_events = if backlog.is_empty() {
+        Vec::new()
+    } else {
+        CollabEvent::for_operations(replayed_to, backlog)
+    };
+    let backlog_events = backlog_events
+        .into_iter()
+        .filter(|event| addressed_to(event, &user_id))
+        .map(|event| Ok(sse_event(&CollabEventResource::from(&event), event.name())))
+        .collect::<Vec<_>>();
+
+    let live_events = live.filter_map(move |received| match received {
+        Ok(CollabEvent::Operations { head_seq, .. }) if head_seq <= replayed_to => None,
+        Ok(event) if addressed_to(&event, &user_id) => {
+            Some(Ok(sse_event(&CollabEventResource::from(&event), event.name())))
+        }
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(_)) => {
+            Some(Ok(sse_event(&CollabEventResource::Resync, "resync")))
+        }
+    });
+
+    Ok(Sse::new(tokio_stream::iter(backlog_events).chain(live_events))
+        .keep_alive(KeepAlive::default()))
+}
+
+/// Loads a session and checks the key may see its quote.
+async fn load_session(
+    state: &ApiState,
+    principal: &ApiPrincipal,
+    service: &CollabService,
+    session_id: &str,
+) -> ApiResult<CollabSession> {
+    let session = service.load_session(&normalize_id(session_id, "session id")?).await?;
+    principal.ensure_quote_access(&state.db_pool, &session.quote_id).await?;
+    Ok(session)
+}
+
+fn addressed_to(event: &CollabEvent, user_id: &str) -> bool {
+    match event {
+        CollabEvent::Overridden { author, .. } => author == user_id,
+        _ => true,
+    }
+}
+
+fn sse_event(resource: &CollabEventResource, name: &str) -> Event {
+    Event::default()
+        .event(name)
+        .data(serde_json::to_string(resource).unwrap_or_else(|_| "{}".to_string()))
+}
+
+fn operation(input: &OperationInput) -> ApiResult<OperationType> {
+    let product_id =
+        |id: &str| -> ApiResult<ProductId> { Ok(ProductId(normalize_id(id, "product_id")?)) };
+    Ok(match input {
+        OperationInput::Insert { product_id: id, quantity, unit_price, discount_pct } => {
+            if !(0.0..=100.0).contains(discount_pct) {
+                return Err(ApiError::validation("discount_pct must be between 0 and 100"));
+            }
+            OperationType::Insert {
+                line: QuoteLine {
+                    product_id: product_id(id)?,
+                    quantity: *quantity,
+                    unit_price: price(*unit_price)?,
+                    discount_pct: *discount_pct,
+                    notes: None,
+                },
+            }
+        }
+        OperationInput::Update { product_id: id, quantity, unit_price } => OperationType::Update {
+            product_id: product_id(id)?,
+            quantity: *quantity,
+            unit_price: unit_price.map(price).transpose()?,
+        },
+        OperationInput::Delete { product_id: id } => {
+            OperationType::Delete { product_id: product_id(id)? }
+        }
+    })
+}
+
+fn price(value: f64) -> ApiResult<Decimal> {
+    (value.is_finite() && value >= 0.0)
+        .then(|| Decimal::from_f64(value))
+        .flatten()
+        .map(|price| price.round_dp(2))
+        .ok_or_else(|| ApiError::validation("unit_price must be a non-negative number"))
+}
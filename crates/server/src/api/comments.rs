@@ -0,0 +1,176 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use chrono::Utc;
+use quotey_core::domain::quote_comment::{AuthorType, QuoteComment};
+use quotey_db::repositories::{QuoteCommentRepository, SqlQuoteCommentRepository};
+use quotey_db::DbPool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::pagination::{Cursor, Page, PageRequest};
+use super::quotes::load_quote;
+use super::{ApiJson, ApiQuery, ApiState};
+
+const MAX_COMMENT_LEN: usize = 4000;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListCommentsQuery {
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AddCommentRequest {
+    pub body: String,
+    /// Free-form JSON object stored alongside the comment.
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CommentResource {
+    pub id: String,
+    pub quote_id: String,
+    /// `rep`, `manager`, `system`, `ai` or `integration`.
+    pub author_type: String,
+    pub author_id: String,
+    pub body: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+impl From<&QuoteComment> for CommentResource {
+    fn from(comment: &QuoteComment) -> Self {
+        Self {
+            id: comment.id.clone(),
+            quote_id: comment.quote_id.clone(),
+            author_type: comment.author_type.as_str().to_string(),
+            author_id: comment.author_id.clone(),
+            body: comment.body.clone(),
+            metadata: comment
+                .metadata_json
+                .as_deref()
+                .and_then(|raw| serde_json::from_str(raw).ok()),
+            created_at: comment.created_at.to_rfc3339(),
+        }
+    }
+}
+
+type CommentRow = (String, String, String, String, String, Option<String>, String);
+
+pub async fn list_comments(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiQuery(query): ApiQuery<ListCommentsQuery>,
+) -> ApiResult<Json<Page<CommentResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let quote = load_quote(&state, &principal, &id).await?;
+
+    let mut sql = String::from(
+        "SELECT id, quote_id, author_type, author_id, body, metadata_json, created_at
+         FROM quote_comment
+         WHERE quote_id = ?",
+    );
+    if page.after.is_some() {
+        sql.push_str(" AND (created_at > ? OR (created_at = ? AND id > ?))");
+    }
+    sql.push_str(" ORDER BY created_at ASC, id ASC LIMIT ?");
+
+    let mut rows = sqlx::query_as::<_, CommentRow>(&sql).bind(&quote.id.0);
+    if let Some(after) = &page.after {
+        rows = rows.bind(&after.key).bind(&after.key).bind(&after.id);
+    }
+    let rows = rows.bind(page.fetch_limit()).fetch_all(&state.db_pool).await?;
+    let (rows, next_cursor) = page.finish(rows, |row| Cursor::new(&row.6, &row.0));
+
+    let data = rows
+        .into_iter()
+        .map(|(id, quote_id, author_type, author_id, body, metadata_json, created_at)| {
+            CommentResource {
+                id,
+                quote_id,
+                author_type,
+                author_id,
+                body,
+                metadata: metadata_json.as_deref().and_then(|raw| serde_json::from_str(raw).ok()),
+                created_at,
+            }
+        })
+        .collect();
+    Ok(Json(Page { data, next_cursor }))
+}
+
+pub async fn add_comment(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<AddCommentRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.comment.add",
+        Some(&id),
+        &payload,
+        || async {
+            let text = body.body.trim();
+            if text.is_empty() {
+                return Err(ApiError::validation("body is required"));
+            }
+            if text.chars().count() > MAX_COMMENT_LEN {
+                return Err(ApiError::validation(format!(
+                    "body must be {MAX_COMMENT_LEN} characters or fewer"
+                )));
+            }
+            if body.metadata.as_ref().is_some_and(|metadata| !metadata.is_object()) {
+                return Err(ApiError::validation("metadata must be a JSON object"));
+            }
+            let quote = load_quote(&state, &principal, &id).await?;
+
+            let comment = QuoteComment {
+                id: format!("CMT-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]),
+                quote_id: quote.id.0.clone(),
+                author_type: AuthorType::Integration,
+                author_id: principal.actor(),
+                body: text.to_string(),
+                metadata_json: body.metadata.as_ref().map(|metadata| metadata.to_string()),
+                created_at: Utc::now(),
+            };
+            SqlQuoteCommentRepository::new(state.db_pool.clone())
+                .add_comment(comment.clone())
+                .await?;
+            Mutation::new(StatusCode::CREATED, quote.id.0, CommentResource::from(&comment))
+        },
+    )
+    .await
+}
+
+/// Records a system comment for an API-driven change. Failures are logged, never surfaced.
+pub async fn auto_comment(pool: &DbPool, quote_id: &str, event: &str, body: &str) {
+    let comment = QuoteComment {
+        id: format!("CMT-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]),
+        quote_id: quote_id.to_string(),
+        author_type: AuthorType::System,
+        author_id: format!("system:{event}"),
+        body: body.to_string(),
+        metadata_json: Some(
+            serde_json::json!({ "event": event, "auto": true, "source": "rest_api" }).to_string(),
+        ),
+        created_at: Utc::now(),
+    };
+    if let Err(error) = SqlQuoteCommentRepository::new(pool.clone()).add_comment(comment).await {
+        warn!(error = %error, quote_id = %quote_id, event = %event, "auto-comment failed (non-blocking)");
+    }
+}
@@ -0,0 +1,181 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use quotey_core::errors::DomainError;
+use quotey_db::repositories::RepositoryError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// Canonical error envelope shared with the MCP tools: `{"error": {code, message, details}}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorEnvelope {
+    pub error: ErrorBody,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorBody {
+    /// Stable machine-readable code such as `VALIDATION_ERROR` or `NOT_FOUND`.
+    pub code: String,
+    pub message: String,
+    /// Structured context for the failure; `null` when there is none.
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), details: None }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NOT_FOUND", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "CONFLICT", message)
+    }
+
+    pub fn currency_mismatch(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, "CURRENCY_MISMATCH", message)
+    }
+
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "AUTHENTICATION_FAILED", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "AUTHORIZATION_FAILED", message)
+    }
+
+    pub fn rate_limited(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED", message)
+    }
+
+    /// Internal failures are logged with their cause and returned with a redacted message.
+    pub fn internal(cause: &dyn std::fmt::Display) -> Self {
+        error!(error = %cause, "REST API internal error (redacted from response)");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", "Internal server error")
+    }
+
+    pub fn envelope(&self) -> ErrorEnvelope {
+        ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code.to_string(),
+                message: self.message.clone(),
+                details: self.details.clone(),
+            },
+        }
+    }
+
+    /// Rebuilds an error from a stored envelope, e.g. when replaying an idempotent request.
+    pub fn from_envelope(status: StatusCode, envelope: ErrorEnvelope) -> Self {
+        Self {
+            status,
+            code: known_code(&envelope.error.code),
+            message: envelope.error.message,
+            details: envelope.error.details,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.envelope())).into_response()
+    }
+}
+
+impl From<RepositoryError> for ApiError {
+    fn from(error: RepositoryError) -> Self {
+        Self::internal(&error)
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::internal(&error)
+    }
+}
+
+impl From<DomainError> for ApiError {
+    fn from(error: DomainError) -> Self {
+        match error {
+            DomainError::InvalidQuoteTransition { from, to } => Self::conflict(format!(
+                "quote cannot move from `{}` to `{}`",
+                quotey_db::repositories::quote::quote_status_as_str(&from),
+                quotey_db::repositories::quote::quote_status_as_str(&to)
+            )),
+            other => Self::validation(other.to_string()),
+        }
+    }
+}
+
+/// Every code the API can emit; used to restore a `&'static str` from a stored snapshot.
+pub const ERROR_CODES: [&str; 10] = [
+    "VALIDATION_ERROR",
+    "NOT_FOUND",
+    "CONFLICT",
+    "CURRENCY_MISMATCH",
+    "AUTHENTICATION_FAILED",
+    "AUTHORIZATION_FAILED",
+    "RATE_LIMITED",
+    "IDEMPOTENCY_KEY_REUSED",
+    "IDEMPOTENCY_IN_PROGRESS",
+    "INTERNAL_ERROR",
+];
+
+fn known_code(code: &str) -> &'static str {
+    ERROR_CODES.iter().copied().find(|known| *known == code).unwrap_or("INTERNAL_ERROR")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_matches_canonical_shape() {
+        let error =
+            ApiError::conflict("busy").with_details(serde_json::json!({ "quote_id": "Q-1" }));
+        let value = serde_json::to_value(error.envelope()).expect("serialize");
+        assert_eq!(value["error"]["code"], "CONFLICT");
+        assert_eq!(value["error"]["message"], "busy");
+        assert_eq!(value["error"]["details"]["quote_id"], "Q-1");
+
+        let bare = serde_json::to_value(ApiError::not_found("gone").envelope()).expect("serialize");
+        assert!(bare["error"]["details"].is_null());
+    }
+
+    #[test]
+    fn internal_errors_are_redacted() {
+        let error = ApiError::internal(&"disk exploded at /var/lib/quotey");
+        assert_eq!(error.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error.message, "Internal server error");
+    }
+
+    #[test]
+    fn envelope_round_trips_through_snapshot() {
+        let original = ApiError::validation("bad quantity");
+        let restored = ApiError::from_envelope(original.status, original.envelope());
+        assert_eq!(restored, original);
+    }
+}
@@ -0,0 +1,366 @@
+//! `Idempotency-Key` support for mutating REST routes, backed by `execution_idempotency_ledger`.
+//!
+//! The ledger row is keyed per API key (`rest:<key id>:<client key>`) and stores the payload hash
+//! plus a snapshot of the response, so a retried request replays the original outcome instead of
+//! executing twice. Rows reference the quote they mutated, which means a request that fails before
+//! any quote exists (for example a create with an unknown product) is simply not recorded. While a
+//! request runs, its key is held in `rest_idempotency_reservation`.
+
+use std::future::Future;
+
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use quotey_core::domain::execution::{IdempotencyRecord, IdempotencyRecordState, OperationKey};
+use quotey_core::domain::quote::QuoteId;
+use quotey_core::execution_engine::DeterministicExecutionEngine;
+use quotey_db::repositories::{IdempotencyRepository, SqlExecutionQueueRepository};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult, ErrorEnvelope};
+use super::ApiState;
+
+pub const IDEMPOTENCY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+pub const MAX_KEY_LEN: usize = 255;
+
+const COMPONENT: &str = "quotey-server:rest";
+const RETENTION: Duration = Duration::hours(24);
+/// A reservation older than this is assumed abandoned (e.g. the server restarted mid-request).
+const STALE_RUNNING: Duration = Duration::minutes(5);
+
+/// Successful outcome of a mutating handler.
+pub struct Mutation {
+    pub status: StatusCode,
+    /// Quote the mutation touched; the ledger row is attached to it.
+    pub quote_id: String,
+    pub body: serde_json::Value,
+}
+
+impl Mutation {
+    pub fn new(
+        status: StatusCode,
+        quote_id: impl Into<String>,
+        body: impl Serialize,
+    ) -> ApiResult<Self> {
+        let body = serde_json::to_value(body).map_err(|error| ApiError::internal(&error))?;
+        Ok(Self { status, quote_id: quote_id.into(), body })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResultSnapshot {
+    status: u16,
+    body: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ErrorSnapshot {
+    status: u16,
+    envelope: ErrorEnvelope,
+}
+
+/// Reads and validates the optional `Idempotency-Key` header.
+pub fn idempotency_key(headers: &HeaderMap) -> ApiResult<Option<String>> {
+    let Some(raw) = headers.get(IDEMPOTENCY_HEADER) else {
+        return Ok(None);
+    };
+    let key = raw
+        .to_str()
+        .map_err(|_| ApiError::validation("Idempotency-Key must be visible ASCII"))?
+        .trim();
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(ApiError::validation(format!(
+            "Idempotency-Key must be between 1 and {MAX_KEY_LEN} characters"
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Runs `action` at most once per idempotency key and replays its stored outcome on retries.
+///
+/// The key is reserved in `rest_idempotency_reservation` before `action` runs, creates included,
+/// so a concurrent duplicate gets `IDEMPOTENCY_IN_PROGRESS` (or the replay, once the first request
+/// has finished) instead of executing again. `known_quote_id` is the quote addressed by the route,
+/// if any; failures are only recorded against it.
+pub async fn run<F, Fut>(
+    state: &ApiState,
+    principal: &ApiPrincipal,
+    headers: &HeaderMap,
+    operation: &str,
+    known_quote_id: Option<&str>,
+    payload: &serde_json::Value,
+    action: F,
+) -> Response
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = ApiResult<Mutation>>,
+{
+    let key = match idempotency_key(headers) {
+        Ok(Some(key)) => key,
+        Ok(None) => return respond(action().await, None, false),
+        Err(error) => return error.into_response(),
+    };
+
+    let repo = SqlExecutionQueueRepository::new(state.db_pool.clone());
+    let operation_key = OperationKey(format!("rest:{}:{key}", principal.key_id));
+    let payload_hash =
+        DeterministicExecutionEngine::hash_payload(&format!("{operation}\n{payload}"));
+    let now = Utc::now();
+
+    if let Err(response) = settled_or_previous(&repo, &operation_key, &payload_hash, &key).await {
+        return response;
+    }
+    match reserve(state, &operation_key, operation, &payload_hash, now).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return held_elsewhere(state, &repo, &operation_key, &payload_hash, &key).await;
+        }
+        Err(error) => return error.into_response(),
+    }
+
+    // The holder of the key may have finished between the lookup above and the reservation.
+    let previous = match settled_or_previous(&repo, &operation_key, &payload_hash, &key).await {
+        Ok(previous) => previous,
+        Err(response) => {
+            release(state, &operation_key).await;
+            return response;
+        }
+    };
+    let (attempt_count, first_seen_at) = previous
+        .map(|previous| (previous.attempt_count.saturating_add(1), previous.first_seen_at))
+        .unwrap_or((1, now));
+
+    let record = |quote_id: &str, state: IdempotencyRecordState| IdempotencyRecord {
+        operation_key: operation_key.clone(),
+        quote_id: QuoteId(quote_id.to_string()),
+        operation_kind: operation.to_string(),
+        payload_hash: payload_hash.clone(),
+        state,
+        attempt_count,
+        first_seen_at,
+        last_seen_at: Utc::now(),
+        result_snapshot_json: None,
+        error_snapshot_json: None,
+        expires_at: Some(now + RETENTION),
+        correlation_id: key.clone(),
+        created_by_component: COMPONENT.to_string(),
+        updated_by_component: COMPONENT.to_string(),
+    };
+
+    let outcome = action().await;
+
+    let finished = match &outcome {
+        Ok(mutation) => {
+            let mut finished = record(&mutation.quote_id, IdempotencyRecordState::Completed);
+            finished.result_snapshot_json = serde_json::to_string(&ResultSnapshot {
+                status: mutation.status.as_u16(),
+                body: mutation.body.clone(),
+            })
+            .ok();
+            Some(finished)
+        }
+        Err(error) => {
+            let failed_quote = match known_quote_id {
+                Some(quote_id) => match quote_exists(state, quote_id).await {
+                    Ok(true) => Some(quote_id),
+                    Ok(false) => None,
+                    Err(lookup) => {
+                        warn!(error = %lookup.message, operation, "cannot check failed quote");
+                        None
+                    }
+                },
+                None => None,
+            };
+            failed_quote.map(|quote_id| {
+                let state = if error.status.is_server_error() {
+                    IdempotencyRecordState::FailedRetryable
+                } else {
+                    IdempotencyRecordState::FailedTerminal
+                };
+                let mut finished = record(quote_id, state);
+                finished.error_snapshot_json = serde_json::to_string(&ErrorSnapshot {
+                    status: error.status.as_u16(),
+                    envelope: error.envelope(),
+                })
+                .ok();
+                finished
+            })
+        }
+    };
+    if let Some(finished) = finished {
+        if let Err(error) = repo.save_operation(finished).await {
+            warn!(error = %error, operation, "failed to persist idempotency outcome");
+        }
+    }
+    release(state, &operation_key).await;
+
+    respond(outcome, Some(&key), false)
+}
+
+/// Looks up the unexpired ledger row for the key. Settled outcomes and payload mismatches come
+/// back as the response to send; anything else is returned for the attempt count.
+async fn settled_or_previous(
+    repo: &SqlExecutionQueueRepository,
+    operation_key: &OperationKey,
+    payload_hash: &str,
+    key: &str,
+) -> Result<Option<IdempotencyRecord>, Response> {
+    let now = Utc::now();
+    let previous = match repo.find_operation(operation_key).await {
+        Ok(previous) => previous.filter(|record| record.expires_at.map_or(true, |at| at > now)),
+        Err(error) => return Err(ApiError::from(error).into_response()),
+    };
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+    if previous.payload_hash != payload_hash {
+        return Err(key_reused(&previous.operation_kind));
+    }
+    match previous.state {
+        IdempotencyRecordState::Completed => Err(replay_result(&previous, key)),
+        IdempotencyRecordState::FailedTerminal => Err(replay_error(&previous, key)),
+        _ => Ok(Some(previous)),
+    }
+}
+
+/// Claims the key, taking over a reservation abandoned for longer than [`STALE_RUNNING`].
+/// Returns `false` when another request holds it.
+async fn reserve(
+    state: &ApiState,
+    operation_key: &OperationKey,
+    operation: &str,
+    payload_hash: &str,
+    now: DateTime<Utc>,
+) -> ApiResult<bool> {
+    let reserved = sqlx::query(
+        "INSERT INTO rest_idempotency_reservation
+            (operation_key, operation_kind, payload_hash, reserved_at)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT (operation_key) DO UPDATE SET
+            operation_kind = excluded.operation_kind,
+            payload_hash = excluded.payload_hash,
+            reserved_at = excluded.reserved_at
+         WHERE rest_idempotency_reservation.reserved_at < ?",
+    )
+    .bind(&operation_key.0)
+    .bind(operation)
+    .bind(payload_hash)
+    .bind(now.to_rfc3339())
+    .bind((now - STALE_RUNNING).to_rfc3339())
+    .execute(&state.db_pool)
+    .await?;
+    Ok(reserved.rows_affected() == 1)
+}
+
+async fn release(state: &ApiState, operation_key: &OperationKey) {
+    if let Err(error) =
+        sqlx::query("DELETE FROM rest_idempotency_reservation WHERE operation_key = ?")
+            .bind(&operation_key.0)
+            .execute(&state.db_pool)
+            .await
+    {
+        warn!(error = %error, operation_key = %operation_key.0, "failed to release idempotency key");
+    }
+}
+
+/// Response for a key another request holds: its replay if it has finished by now, otherwise
+/// `IDEMPOTENCY_IN_PROGRESS`.
+async fn held_elsewhere(
+    state: &ApiState,
+    repo: &SqlExecutionQueueRepository,
+    operation_key: &OperationKey,
+    payload_hash: &str,
+    key: &str,
+) -> Response {
+    if let Err(response) = settled_or_previous(repo, operation_key, payload_hash, key).await {
+        return response;
+    }
+    let held: Result<Option<(String, String)>, sqlx::Error> = sqlx::query_as(
+        "SELECT operation_kind, payload_hash FROM rest_idempotency_reservation
+         WHERE operation_key = ?",
+    )
+    .bind(&operation_key.0)
+    .fetch_optional(&state.db_pool)
+    .await;
+    match held {
+        Ok(Some((operation_kind, held_hash))) if held_hash != payload_hash => {
+            key_reused(&operation_kind)
+        }
+        Ok(_) => ApiError::new(
+            StatusCode::CONFLICT,
+            "IDEMPOTENCY_IN_PROGRESS",
+            "a request with this Idempotency-Key is still being processed",
+        )
+        .into_response(),
+        Err(error) => ApiError::from(error).into_response(),
+    }
+}
+
+fn key_reused(operation_kind: &str) -> Response {
+    ApiError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "IDEMPOTENCY_KEY_REUSED",
+        "Idempotency-Key was already used with a different request",
+    )
+    .with_details(serde_json::json!({ "operation_kind": operation_kind }))
+    .into_response()
+}
+
+async fn quote_exists(state: &ApiState, quote_id: &str) -> ApiResult<bool> {
+    let found: Option<i64> = sqlx::query_scalar("SELECT 1 FROM quote WHERE id = ?")
+        .bind(quote_id)
+        .fetch_optional(&state.db_pool)
+        .await?;
+    Ok(found.is_some())
+}
+
+fn replay_result(record: &IdempotencyRecord, key: &str) -> Response {
+    let snapshot = record
+        .result_snapshot_json
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<ResultSnapshot>(raw).ok());
+    let outcome = match snapshot {
+        Some(snapshot) => Ok(Mutation {
+            status: StatusCode::from_u16(snapshot.status).unwrap_or(StatusCode::OK),
+            quote_id: record.quote_id.0.clone(),
+            body: snapshot.body,
+        }),
+        None => Err(ApiError::internal(&"idempotency ledger row has no result snapshot")),
+    };
+    respond(outcome, Some(key), true)
+}
+
+fn replay_error(record: &IdempotencyRecord, key: &str) -> Response {
+    let error = record
+        .error_snapshot_json
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<ErrorSnapshot>(raw).ok())
+        .map(|snapshot| {
+            ApiError::from_envelope(
+                StatusCode::from_u16(snapshot.status).unwrap_or(StatusCode::BAD_REQUEST),
+                snapshot.envelope,
+            )
+        })
+        .unwrap_or_else(|| ApiError::internal(&"idempotency ledger row has no error snapshot"));
+    respond(Err(error), Some(key), true)
+}
+
+fn respond(outcome: ApiResult<Mutation>, key: Option<&str>, replayed: bool) -> Response {
+    let mut response = match outcome {
+        Ok(mutation) => (mutation.status, Json(mutation.body)).into_response(),
+        Err(error) => error.into_response(),
+    };
+    if let Some(value) = key.and_then(|key| HeaderValue::from_str(key).ok()) {
+        response.headers_mut().insert(IDEMPOTENCY_HEADER, value);
+    }
+    if replayed {
+        response.headers_mut().insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    }
+    response
+}
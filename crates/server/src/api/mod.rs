@@ -0,0 +1,2262 @@
+//! Versioned public REST API mounted under `/api/v1`.
+//!
+//! Every route is declared once in [`routes`]: the axum router and the OpenAPI document served
+//! at `/api/v1/openapi.json` are both built from that table, so the published contract cannot
+//! drift from the mounted handlers. Requests authenticate with a scoped API key
+//! (`quotey api-key create`), mutating routes accept `Idempotency-Key`, collections use cursor
+//! pagination, and every failure uses the canonical `{error: {code, message, details}}` envelope.
+
+mod analytics;
+mod approvals;
+mod auth;
+mod catalog;
+mod collab;
+mod comments;
+mod error;
+mod idempotency;
+mod openapi;
+mod orders;
+mod pagination;
+mod quotes;
+mod simulations;
+mod templates;
+mod webhooks;
+
+use axum::{
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::request::Parts,
+    middleware,
+    routing::{delete, get, patch, post, MethodRouter},
+    Json, Router,
+};
+use quotey_core::ApiScope;
+use quotey_db::analytics::AnalyticsCache;
+use quotey_db::DbPool;
+use schemars::generate::SchemaGenerator;
+use schemars::Schema;
+
+use error::ApiError;
+
+pub(crate) use quotes::load_quote_policy;
+
+pub const API_VERSION: &str = "v1";
+pub const OPENAPI_PATH: &str = "/api/v1/openapi.json";
+
+#[derive(Clone)]
+pub struct ApiState {
+    db_pool: DbPool,
+    rate_limiter: auth::RateLimiter,
+    analytics_cache: AnalyticsCache,
+    collab_hub: collab::CollabHub,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiMethod {
+    Get,
+    Post,
+    Patch,
+    Delete,
+}
+
+impl ApiMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Patch => "patch",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// One REST operation: its handler plus everything the OpenAPI document says about it.
+pub struct ApiRoute {
+    pub method: ApiMethod,
+    pub path: &'static str,
+    pub operation_id: &'static str,
+    pub summary: &'static str,
+    pub tag: &'static str,
+    /// Scope the calling API key must hold; enforced by middleware before the handler runs.
+    pub scope: ApiScope,
+    pub success_status: u16,
+    /// Accepts `Idempotency-Key`.
+    pub idempotent: bool,
+    /// Honors `If-Match: <quote version>`.
+    pub if_match: bool,
+    pub query: Option<fn() -> Schema>,
+    pub request: Option<fn(&mut SchemaGenerator) -> Schema>,
+    pub response: fn(&mut SchemaGenerator) -> Schema,
+    pub handler: fn() -> MethodRouter<ApiState>,
+}
+
+/// The complete v1 route table.
+pub fn routes() -> Vec<ApiRoute> {
+    use openapi::{query_schema, schema};
+    use pagination::Page;
+    use ApiMethod::*;
+
+    let quote_write = |method, path, operation_id, summary, request, handler| ApiRoute {
+        method,
+        path,
+        operation_id,
+        summary,
+        tag: "quotes",
+        scope: ApiScope::QuoteWrite,
+        success_status: 200,
+        idempotent: true,
+        if_match: true,
+        query: None,
+        request,
+        response: schema::<quotes::QuoteResource>,
+        handler,
+    };
+
+    vec![
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/quotes",
+            operation_id: "listQuotes",
+            summary: "List quotes visible to the API key, newest first",
+            tag: "quotes",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<quotes::ListQuotesQuery>),
+            request: None,
+            response: schema::<Page<quotes::QuoteResource>>,
+            handler: || get(quotes::list_quotes),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/quotes",
+            operation_id: "createQuote",
+            summary: "Create a draft quote from catalog products",
+            tag: "quotes",
+            scope: ApiScope::QuoteWrite,
+            success_status: 201,
+            idempotent: true,
+            if_match: false,
+            query: None,
+            request: Some(schema::<quotes::CreateQuoteRequest>),
+            response: schema::<quotes::QuoteResource>,
+            handler: || post(quotes::create_quote),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/quotes/{id}",
+            operation_id: "getQuote",
+            summary: "Fetch a quote with its lines",
+            tag: "quotes",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<quotes::QuoteResource>,
+            handler: || get(quotes::get_quote),
+        },
+        quote_write(
+            Patch,
+            "/api/v1/quotes/{id}",
+            "updateQuote",
+            "Update header fields of a draft or revised quote",
+            Some(schema::<quotes::UpdateQuoteRequest>),
+            || patch(quotes::update_quote),
+        ),
+        quote_write(
+            Delete,
+            "/api/v1/quotes/{id}",
+            "cancelQuote",
+            "Cancel a quote (quotes are never hard-deleted)",
+            None,
+            || delete(quotes::cancel_quote),
+        ),
+        quote_write(
+            Post,
+            "/api/v1/quotes/{id}/transitions",
+            "transitionQuote",
+            "Move a quote to another lifecycle status",
+            Some(schema::<quotes::TransitionRequest>),
+            || post(quotes::transition_quote),
+        ),
+        ApiRoute {
+            success_status: 201,
+            ..quote_write(
+                Post,
+                "/api/v1/quotes/{id}/lines",
+                "addQuoteLine",
+                "Append a catalog product line",
+                Some(schema::<quotes::LineInput>),
+                || post(quotes::add_line),
+            )
+        },
+        quote_write(
+            Patch,
+            "/api/v1/quotes/{id}/lines/{line_id}",
+            "updateQuoteLine",
+            "Change quantity, discount or notes of a line",
+            Some(schema::<quotes::UpdateLineRequest>),
+            || patch(quotes::update_line),
+        ),
+        quote_write(
+            Delete,
+            "/api/v1/quotes/{id}/lines/{line_id}",
+            "removeQuoteLine",
+            "Remove a line; later line ids shift down by one",
+            None,
+            || delete(quotes::remove_line),
+        ),
+        ApiRoute {
+            if_match: false,
+            response: schema::<quotes::PricingResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/quotes/{id}/price",
+                "priceQuote",
+                "Price a quote and evaluate discount policy",
+                Some(schema::<quotes::PriceQuoteRequest>),
+                || post(quotes::price_quote),
+            )
+        },
+        ApiRoute {
+            success_status: 201,
+            if_match: false,
+            response: schema::<simulations::SimulationResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/quotes/{id}/simulations",
+                "simulateQuote",
+                "Price and policy-check up to three what-if scenarios against a quote",
+                Some(schema::<simulations::SimulateQuoteRequest>),
+                || post(simulations::simulate_quote),
+            )
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/quotes/{id}/simulations/{simulation_id}",
+            operation_id: "getQuoteSimulation",
+            summary: "Fetch a stored simulation run with its ranked scenarios",
+            tag: "quotes",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<simulations::SimulationResource>,
+            handler: || get(simulations::get_simulation),
+        },
+        ApiRoute {
+            if_match: false,
+            response: schema::<simulations::PromotionResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/quotes/{id}/simulations/{simulation_id}/promote",
+                "promoteQuoteSimulation",
+                "Promote a simulated scenario into a new quote revision",
+                Some(schema::<simulations::PromoteScenarioRequest>),
+                || post(simulations::promote_simulation),
+            )
+        },
+        ApiRoute {
+            if_match: false,
+            idempotent: false,
+            tag: "collaboration",
+            response: schema::<collab::SessionResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/quotes/{id}/collab/sessions",
+                "openCollabSession",
+                "Join the quote's live editing session, starting one if none is open",
+                Some(schema::<collab::JoinSessionRequest>),
+                || post(collab::open_session),
+            )
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/collab/sessions/{id}",
+            operation_id: "getCollabSession",
+            summary: "Fetch a live editing session with its working lines and presence",
+            tag: "collaboration",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<collab::SessionResource>,
+            handler: || get(collab::get_session),
+        },
+        ApiRoute {
+            if_match: false,
+            idempotent: false,
+            tag: "collaboration",
+            response: schema::<collab::ParticipantResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/collab/sessions/{id}/participants",
+                "joinCollabSession",
+                "Join a live editing session or change your role in it",
+                Some(schema::<collab::JoinSessionRequest>),
+                || post(collab::join_session),
+            )
+        },
+        ApiRoute {
+            if_match: false,
+            idempotent: false,
+            tag: "collaboration",
+            response: schema::<collab::LeaveResource>,
+            ..quote_write(
+                Delete,
+                "/api/v1/collab/sessions/{id}/participants/{user_id}",
+                "leaveCollabSession",
+                "Leave a live editing session",
+                None,
+                || delete(collab::leave_session),
+            )
+        },
+        ApiRoute {
+            if_match: false,
+            idempotent: false,
+            tag: "collaboration",
+            response: schema::<collab::ParticipantResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/collab/sessions/{id}/heartbeat",
+                "collabHeartbeat",
+                "Keep a participant marked as online",
+                Some(schema::<collab::HeartbeatRequest>),
+                || post(collab::heartbeat),
+            )
+        },
+        ApiRoute {
+            if_match: false,
+            tag: "collaboration",
+            response: schema::<collab::SubmitOutcomeResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/collab/sessions/{id}/operations",
+                "submitCollabOperations",
+                "Submit line edits; conflicts resolve by role authority, then recency",
+                Some(schema::<collab::SubmitOperationsRequest>),
+                || post(collab::submit_operations),
+            )
+        },
+        ApiRoute {
+            if_match: false,
+            tag: "collaboration",
+            response: schema::<collab::CommitResource>,
+            ..quote_write(
+                Post,
+                "/api/v1/collab/sessions/{id}/commit",
+                "commitCollabSession",
+                "Commit the session's converged lines as a new quote revision",
+                Some(schema::<collab::CommitSessionRequest>),
+                || post(collab::commit_session),
+            )
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/collab/sessions/{id}/events",
+            operation_id: "streamCollabEvents",
+            summary: "Server-sent event stream of a session's operations and presence",
+            tag: "collaboration",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<collab::EventsQuery>),
+            request: None,
+            response: schema::<collab::CollabEventResource>,
+            handler: || get(collab::stream_events),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/quotes/{id}/approvals",
+            operation_id: "requestApproval",
+            summary: "Submit a quote for approval",
+            tag: "approvals",
+            scope: ApiScope::ApprovalRequest,
+            success_status: 201,
+            idempotent: true,
+            if_match: false,
+            query: None,
+            request: Some(schema::<approvals::RequestApprovalRequest>),
+            response: schema::<approvals::ApprovalResource>,
+            handler: || post(approvals::request_approval),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/quotes/{id}/comments",
+            operation_id: "listQuoteComments",
+            summary: "List comments on a quote, oldest first",
+            tag: "comments",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<comments::ListCommentsQuery>),
+            request: None,
+            response: schema::<Page<comments::CommentResource>>,
+            handler: || get(comments::list_comments),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/quotes/{id}/comments",
+            operation_id: "addQuoteComment",
+            summary: "Add a comment to a quote",
+            tag: "comments",
+            scope: ApiScope::QuoteWrite,
+            success_status: 201,
+            idempotent: true,
+            if_match: false,
+            query: None,
+            request: Some(schema::<comments::AddCommentRequest>),
+            response: schema::<comments::CommentResource>,
+            handler: || post(comments::add_comment),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/catalog/products",
+            operation_id: "searchProducts",
+            summary: "Search the product catalog",
+            tag: "catalog",
+            scope: ApiScope::CatalogRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<catalog::ProductSearchQuery>),
+            request: None,
+            response: schema::<Page<catalog::ProductResource>>,
+            handler: || get(catalog::search_products),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/catalog/products/{id}",
+            operation_id: "getProduct",
+            summary: "Fetch a catalog product",
+            tag: "catalog",
+            scope: ApiScope::CatalogRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<catalog::ProductResource>,
+            handler: || get(catalog::get_product),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/catalog/products/{id}/costs",
+            operation_id: "listProductCosts",
+            summary: "List a product's cost records, newest effective date first",
+            tag: "catalog",
+            scope: ApiScope::CatalogAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<catalog::ProductCostListResource>,
+            handler: || get(catalog::list_product_costs),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/catalog/products/{id}/costs",
+            operation_id: "recordProductCost",
+            summary:
+                "Record an effective-dated unit cost; it supersedes older costs from its start",
+            tag: "catalog",
+            scope: ApiScope::CatalogAdmin,
+            success_status: 201,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<catalog::RecordProductCostRequest>),
+            response: schema::<catalog::ProductCostResource>,
+            handler: || post(catalog::record_product_cost),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/catalog/export",
+            operation_id: "exportCatalog",
+            summary: "Export the whole catalog as a JSON document or CSV sheet",
+            tag: "catalog",
+            scope: ApiScope::CatalogAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<catalog::CatalogExportQuery>),
+            request: None,
+            response: schema::<catalog::CatalogExportResource>,
+            handler: || get(catalog::export_catalog),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/catalog/imports",
+            operation_id: "importCatalog",
+            summary: "Validate and diff a full catalog; apply it when dry_run is false",
+            tag: "catalog",
+            scope: ApiScope::CatalogAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<catalog::ImportCatalogRequest>),
+            response: schema::<catalog::CatalogImportResource>,
+            handler: || post(catalog::import_catalog),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/quotes/{id}/order-forms",
+            operation_id: "generateOrderForm",
+            summary: "Generate the draft order form of an accepted quote",
+            tag: "order-forms",
+            scope: ApiScope::QuoteWrite,
+            success_status: 201,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<orders::GenerateOrderFormRequest>),
+            response: schema::<orders::OrderFormResource>,
+            handler: || post(orders::generate_order_form),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/order-forms/{id}",
+            operation_id: "getOrderForm",
+            summary: "Fetch an order form with its amendments",
+            tag: "order-forms",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<orders::OrderFormResource>,
+            handler: || get(orders::get_order_form),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/order-forms/{id}/transitions",
+            operation_id: "transitionOrderForm",
+            summary: "Send, sign or countersign an order form",
+            tag: "order-forms",
+            scope: ApiScope::QuoteWrite,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<orders::TransitionOrderFormRequest>),
+            response: schema::<orders::OrderFormResource>,
+            handler: || post(orders::transition_order_form),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/order-forms/{id}/amendments",
+            operation_id: "amendOrderForm",
+            summary: "Amend a countersigned order form from another accepted quote",
+            tag: "order-forms",
+            scope: ApiScope::QuoteWrite,
+            success_status: 201,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<orders::AmendOrderFormRequest>),
+            response: schema::<orders::OrderFormResource>,
+            handler: || post(orders::amend_order_form),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/order-forms/{id}/document",
+            operation_id: "renderOrderForm",
+            summary: "Render an order form PDF from its snapshot",
+            tag: "order-forms",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<orders::OrderFormDocumentResource>,
+            handler: || get(orders::render_order_form),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/quotes/{id}/preview",
+            operation_id: "previewQuoteDocument",
+            summary: "Render a quote with any template version, drafts included",
+            tag: "templates",
+            scope: ApiScope::QuoteRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<templates::PreviewDocumentRequest>),
+            response: schema::<templates::DocumentPreviewResource>,
+            handler: || post(templates::preview_document),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/templates",
+            operation_id: "getQuoteTemplateRegistry",
+            summary: "List quote templates, built-in layouts and default assignments",
+            tag: "templates",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<templates::TemplateRegistryResource>,
+            handler: || get(templates::get_registry),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/templates/{key}/versions",
+            operation_id: "listQuoteTemplateVersions",
+            summary: "List a quote template's versions, newest first",
+            tag: "templates",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<templates::TemplateVersionListResource>,
+            handler: || get(templates::list_versions),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/templates/{key}/versions",
+            operation_id: "saveQuoteTemplateDraft",
+            summary: "Save the template's open draft, starting a new version after a publish",
+            tag: "templates",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<templates::SaveTemplateDraftRequest>),
+            response: schema::<templates::TemplateVersionResource>,
+            handler: || post(templates::save_draft),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/templates/{key}/versions/{version}/publish",
+            operation_id: "publishQuoteTemplateVersion",
+            summary: "Publish a draft version; the previously published version is retired",
+            tag: "templates",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<templates::TemplateVersionResource>,
+            handler: || post(templates::publish_version),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/templates/clauses",
+            operation_id: "listClauseBlocks",
+            summary: "List reusable clause blocks at their newest version",
+            tag: "templates",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<templates::ClauseBlockListResource>,
+            handler: || get(templates::list_clauses),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/templates/clauses",
+            operation_id: "saveClauseBlock",
+            summary: "Store the next version of a clause block",
+            tag: "templates",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 201,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<templates::SaveClauseRequest>),
+            response: schema::<templates::ClauseBlockResource>,
+            handler: || post(templates::save_clause),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/templates/defaults",
+            operation_id: "setDefaultQuoteTemplate",
+            summary: "Set the default template for an account, a segment or the workspace",
+            tag: "templates",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<templates::SetTemplateDefaultRequest>),
+            response: schema::<templates::TemplateDefaultResource>,
+            handler: || post(templates::set_default),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/approvals",
+            operation_id: "listApprovals",
+            summary: "List approval requests, newest first",
+            tag: "approvals",
+            scope: ApiScope::ApprovalRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<approvals::ListApprovalsQuery>),
+            request: None,
+            response: schema::<Page<approvals::ApprovalResource>>,
+            handler: || get(approvals::list_approvals),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/approvals/{id}",
+            operation_id: "getApproval",
+            summary: "Fetch an approval request",
+            tag: "approvals",
+            scope: ApiScope::ApprovalRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<approvals::ApprovalResource>,
+            handler: || get(approvals::get_approval),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/approvals/{id}/decision",
+            operation_id: "decideApproval",
+            summary: "Approve, reject or request revision of a pending approval",
+            tag: "approvals",
+            scope: ApiScope::ApprovalDecide,
+            success_status: 200,
+            idempotent: true,
+            if_match: false,
+            query: None,
+            request: Some(schema::<approvals::DecideApprovalRequest>),
+            response: schema::<approvals::ApprovalDecisionResource>,
+            handler: || post(approvals::decide_approval),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/webhooks",
+            operation_id: "listWebhookSubscriptions",
+            summary: "List webhook subscriptions, newest first",
+            tag: "webhooks",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<webhooks::ListSubscriptionsQuery>),
+            request: None,
+            response: schema::<Page<webhooks::SubscriptionResource>>,
+            handler: || get(webhooks::list_subscriptions),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/webhooks",
+            operation_id: "createWebhookSubscription",
+            summary: "Subscribe a URL to quote lifecycle events; the response carries the secret",
+            tag: "webhooks",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 201,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<webhooks::CreateSubscriptionRequest>),
+            response: schema::<webhooks::SubscriptionResource>,
+            handler: || post(webhooks::create_subscription),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/webhooks/{id}",
+            operation_id: "getWebhookSubscription",
+            summary: "Fetch a webhook subscription",
+            tag: "webhooks",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<webhooks::SubscriptionResource>,
+            handler: || get(webhooks::get_subscription),
+        },
+        ApiRoute {
+            method: Delete,
+            path: "/api/v1/webhooks/{id}",
+            operation_id: "disableWebhookSubscription",
+            summary: "Disable a subscription; its delivery log is kept",
+            tag: "webhooks",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<webhooks::SubscriptionResource>,
+            handler: || delete(webhooks::disable_subscription),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/webhooks/{id}/deliveries",
+            operation_id: "listWebhookDeliveries",
+            summary: "List a subscription's deliveries, newest first",
+            tag: "webhooks",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: Some(query_schema::<webhooks::ListDeliveriesQuery>),
+            request: None,
+            response: schema::<Page<webhooks::DeliveryResource>>,
+            handler: || get(webhooks::list_deliveries),
+        },
+        ApiRoute {
+            method: Get,
+            path: "/api/v1/webhooks/{id}/deliveries/{delivery_id}",
+            operation_id: "getWebhookDelivery",
+            summary: "Fetch a delivery with every HTTP attempt made for it",
+            tag: "webhooks",
+            scope: ApiScope::SettingsRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<webhooks::DeliveryLogResource>,
+            handler: || get(webhooks::get_delivery),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+            operation_id: "redeliverWebhook",
+            summary: "Queue a delivery's event again, e.g. after it was dead-lettered",
+            tag: "webhooks",
+            scope: ApiScope::SettingsAdmin,
+            success_status: 201,
+            idempotent: true,
+            if_match: false,
+            query: None,
+            request: None,
+            response: schema::<webhooks::DeliveryResource>,
+            handler: || post(webhooks::redeliver),
+        },
+        ApiRoute {
+            method: Post,
+            path: "/api/v1/analytics/query",
+            operation_id: "runAnalyticsQuery",
+            summary: "Aggregate quote metrics by dimensions over a lookback window",
+            tag: "analytics",
+            scope: ApiScope::AuditRead,
+            success_status: 200,
+            idempotent: false,
+            if_match: false,
+            query: None,
+            request: Some(schema::<analytics::AnalyticsQueryRequest>),
+            response: schema::<analytics::AnalyticsResultResource>,
+            handler: || post(analytics::run_query),
+        },
+    ]
+}
+
+pub fn router(db_pool: DbPool) -> Router {
+    let state = ApiState {
+        db_pool,
+        rate_limiter: auth::RateLimiter::default(),
+        analytics_cache: AnalyticsCache::default(),
+        collab_hub: collab::CollabHub::default(),
+    };
+    let routes = routes();
+    let document = openapi::document(&routes);
+
+    let mut router = Router::new().route(OPENAPI_PATH, get(move || async move { Json(document) }));
+    for route in routes {
+        let guard = auth::RouteGuard { state: state.clone(), scope: route.scope };
+        router = router.route(
+            route.path,
+            (route.handler)()
+                .route_layer(middleware::from_fn_with_state(guard, auth::authenticate)),
+        );
+    }
+    router.with_state(state)
+}
+
+/// `Json` extractor whose rejections use the canonical error envelope.
+pub struct ApiJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = axum::extract::rejection::JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Json::<T>::from_request(request, state)
+            .await
+            .map(|Json(value)| Self(value))
+            .map_err(|rejection| ApiError::validation(rejection.body_text()))
+    }
+}
+
+/// `Query` extractor whose rejections use the canonical error envelope.
+pub struct ApiQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiQuery<T>
+where
+    Query<T>: FromRequestParts<S, Rejection = axum::extract::rejection::QueryRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Query::<T>::from_request_parts(parts, state)
+            .await
+            .map(|Query(value)| Self(value))
+            .map_err(|rejection| ApiError::validation(rejection.body_text()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Method, Request as HttpRequest, StatusCode};
+    use chrono::Utc;
+    use quotey_core::domain::product::Product;
+    use quotey_core::ml::TrainingConfig;
+    use quotey_core::{hash_api_key, ApiKeyGrants, ApiKeyRecord, ApiScope};
+    use quotey_db::repositories::{
+        ApiKeyRepository, ProductRepository, SqlApiKeyRepository, SqlProductRepository,
+    };
+    use quotey_db::win_probability::WinProbabilityService;
+    use quotey_db::{connect_with_settings, migrations, DbPool};
+    use rust_decimal::Decimal;
+    use serde_json::{json, Value};
+    use tower::util::ServiceExt;
+
+    use super::{router, routes, OPENAPI_PATH};
+
+    const ADMIN_KEY: &str = "admin-secret";
+
+    async fn setup() -> (DbPool, axum::Router) {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        let products = SqlProductRepository::new(pool.clone());
+        for (id, name, price) in [
+            ("PROD-A", "Analytics Seat", 100),
+            ("PROD-B", "Backup Add-on", 40),
+            ("PROD-C", "Compute", 7),
+        ] {
+            let mut product = Product::simple(id, format!("SKU-{id}"), name);
+            product.base_price = Some(Decimal::from(price));
+            products.save(product).await.expect("seed product");
+        }
+        let mut retired = Product::simple("PROD-OLD", "SKU-OLD", "Legacy Seat");
+        retired.active = false;
+        products.save(retired).await.expect("seed inactive product");
+
+        issue_key(&pool, "admin", ADMIN_KEY, ApiKeyGrants::unrestricted()).await;
+        let app = router(pool.clone());
+        (pool, app)
+    }
+
+    async fn issue_key(pool: &DbPool, name: &str, secret: &str, grants: ApiKeyGrants) {
+        SqlApiKeyRepository::new(pool.clone())
+            .create(&ApiKeyRecord {
+                id: format!("KEY-{name}"),
+                name: name.to_string(),
+                key_hash: hash_api_key(secret),
+                key_prefix: secret.chars().take(8).collect(),
+                grants,
+                requests_per_minute: 1000,
+                active: true,
+                rotated_from: None,
+                created_at: Utc::now(),
+                revoked_at: None,
+                last_used_at: None,
+            })
+            .await
+            .expect("create key");
+    }
+
+    async fn call(
+        app: &axum::Router,
+        method: Method,
+        uri: &str,
+        key: Option<&str>,
+        body: Option<Value>,
+        headers: &[(&str, &str)],
+    ) -> (StatusCode, axum::http::HeaderMap, Value) {
+        let mut builder = HttpRequest::builder().method(method).uri(uri);
+        if let Some(key) = key {
+            builder = builder.header("authorization", format!("Bearer {key}"));
+        }
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = match body {
+            Some(body) => builder
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .expect("request");
+        let response = app.clone().oneshot(request).await.expect("response");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.expect("body");
+        let value = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).expect("json")
+        };
+        (status, headers, value)
+    }
+
+    fn create_body(account_id: &str) -> Value {
+        json!({
+            "account_id": account_id,
+            "currency": "usd",
+            "lines": [{ "product_id": "PROD-A", "quantity": 10, "discount_pct": 5.0 }],
+        })
+    }
+
+    #[tokio::test]
+    async fn openapi_document_describes_every_route() {
+        let (_, app) = setup().await;
+        let (status, _, document) = call(&app, Method::GET, OPENAPI_PATH, None, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(document["openapi"], "3.0.3");
+
+        for route in routes() {
+            let operation = &document["paths"][route.path][route.method.as_str()];
+            assert_eq!(operation["operationId"], route.operation_id, "{}", route.path);
+            assert_eq!(operation["x-required-scope"], route.scope.as_str());
+        }
+        let schemas = document["components"]["schemas"].as_object().expect("schemas");
+        assert!(schemas.contains_key("QuoteResource"));
+        assert!(schemas.contains_key("ErrorEnvelope"));
+        let create = &document["paths"]["/api/v1/quotes"]["post"];
+        assert!(create["parameters"]
+            .as_array()
+            .expect("parameters")
+            .iter()
+            .any(|parameter| parameter["name"] == "Idempotency-Key"));
+        assert!(document["paths"]["/api/v1/quotes"]["get"]["parameters"]
+            .as_array()
+            .expect("parameters")
+            .iter()
+            .any(|parameter| parameter["name"] == "cursor" && parameter["in"] == "query"));
+    }
+
+    #[tokio::test]
+    async fn requests_without_valid_key_or_scope_get_canonical_errors() {
+        let (pool, app) = setup().await;
+        let (status, _, body) = call(&app, Method::GET, "/api/v1/quotes", None, None, &[]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "AUTHENTICATION_FAILED");
+
+        let (status, _, _) =
+            call(&app, Method::GET, "/api/v1/quotes", Some("wrong"), None, &[]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        issue_key(
+            &pool,
+            "reader",
+            "reader-secret",
+            ApiKeyGrants { scopes: vec![ApiScope::QuoteRead], ..ApiKeyGrants::default() },
+        )
+        .await;
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/quotes",
+            Some("reader-secret"),
+            Some(create_body("acct-1")),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "AUTHORIZATION_FAILED");
+        assert_eq!(body["error"]["details"]["required_scope"], "quote:write");
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/quotes",
+            Some(ADMIN_KEY),
+            Some(json!({ "account_id": "acct-1" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    }
+
+    #[tokio::test]
+    async fn quote_lifecycle_runs_end_to_end() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+
+        let (status, _, quote) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{quote}");
+        assert_eq!(quote["status"], "draft");
+        assert_eq!(quote["currency"], "USD");
+        assert_eq!(quote["subtotal"], "950.00");
+        let id = quote["id"].as_str().expect("id").to_string();
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/lines"),
+            key,
+            Some(json!({ "product_id": "PROD-OLD", "quantity": 1 })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "CONFLICT");
+
+        let (status, _, quote) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/lines"),
+            key,
+            Some(json!({ "product_id": "PROD-B", "quantity": 2 })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(quote["lines"].as_array().expect("lines").len(), 2);
+        let second_line = quote["lines"][1]["line_id"].as_str().expect("line id").to_string();
+
+        let (status, _, quote) = call(
+            &app,
+            Method::PATCH,
+            &format!("/api/v1/quotes/{id}/lines/{second_line}"),
+            key,
+            Some(json!({ "quantity": 5 })),
+            &[("if-match", "2")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{quote}");
+        assert_eq!(quote["lines"][1]["quantity"], 5);
+        assert_eq!(quote["version"], 3);
+
+        let (status, _, body) = call(
+            &app,
+            Method::PATCH,
+            &format!("/api/v1/quotes/{id}"),
+            key,
+            Some(json!({ "notes": "stale write" })),
+            &[("if-match", "2")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["details"]["current_version"], 3);
+
+        let (status, _, quote) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/transitions"),
+            key,
+            Some(json!({ "status": "validated" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(quote["status"], "validated");
+
+        let (status, _, body) = call(
+            &app,
+            Method::DELETE,
+            &format!("/api/v1/quotes/{id}/lines/{second_line}"),
+            key,
+            None,
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT, "validated quotes are not editable: {body}");
+
+        let (status, _, pricing) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/price"),
+            key,
+            Some(json!({ "requested_discount_pct": 25.0 })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{pricing}");
+        assert_eq!(pricing["status"], "priced");
+        assert_eq!(pricing["subtotal"], "1200.00");
+        assert_eq!(pricing["total"], "900.00");
+        assert_eq!(pricing["approval_required"], true);
+
+        let (status, _, approval) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/approvals"),
+            key,
+            Some(json!({ "justification": "Strategic logo" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{approval}");
+        let approval_id = approval["id"].as_str().expect("approval id").to_string();
+
+        let (_, _, pending) =
+            call(&app, Method::GET, "/api/v1/approvals?status=pending", key, None, &[]).await;
+        assert_eq!(pending["data"][0]["id"], approval_id.as_str());
+
+        let (status, _, decision) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/approvals/{approval_id}/decision"),
+            key,
+            Some(json!({ "decision": "approve", "note": "ok" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{decision}");
+        assert_eq!(decision["approval"]["status"], "approved");
+        assert_eq!(decision["quote_status"], "approved");
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/approvals/{approval_id}/decision"),
+            key,
+            Some(json!({ "decision": "reject" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+
+        let (status, _, quote) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/transitions"),
+            key,
+            Some(json!({ "status": "finalized" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{quote}");
+        let indexed: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM deal_similarity_index WHERE quote_id = ?")
+                .bind(&id)
+                .fetch_one(&pool)
+                .await
+                .expect("index count");
+        assert_eq!(indexed, 1, "finalized quotes enter the similar-deal index");
+
+        let (status, _, _) = call(
+            &app,
+            Method::POST,
+            &format!("/api/v1/quotes/{id}/comments"),
+            key,
+            Some(json!({ "body": "Customer signed off", "metadata": { "channel": "email" } })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let mut events = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let uri = match &cursor {
+                Some(cursor) => format!("/api/v1/quotes/{id}/comments?limit=2&cursor={cursor}"),
+                None => format!("/api/v1/quotes/{id}/comments?limit=2"),
+            };
+            let (status, _, page) = call(&app, Method::GET, &uri, key, None, &[]).await;
+            assert_eq!(status, StatusCode::OK);
+            events.extend(page["data"].as_array().expect("data").iter().cloned());
+            match page["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+        assert!(events.len() >= 5);
+        assert_eq!(events.last().expect("last")["body"], "Customer signed off");
+        assert_eq!(events.last().expect("last")["author_id"], "api:admin");
+
+        let (status, _, quote) =
+            call(&app, Method::DELETE, &format!("/api/v1/quotes/{id}"), key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(quote["status"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn simulations_are_stored_and_promoted_into_a_revision() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let (_, _, quote) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[]).await;
+        let id = quote["id"].as_str().expect("id").to_string();
+        let simulations = format!("/api/v1/quotes/{id}/simulations");
+
+        let (status, _, body) =
+            call(&app, Method::POST, &simulations, key, Some(json!({ "scenarios": [] })), &[])
+                .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+        let request = json!({
+            "scenarios": [
+                {
+                    "key": "more-seats",
+                    "line_changes": [{ "product_id": "PROD-A", "quantity_delta": 5 }],
+                },
+                { "key": "deep-discount", "discount_pct": 40.0 },
+            ],
+        });
+        let (status, _, run) =
+            call(&app, Method::POST, &simulations, key, Some(request), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{run}");
+        assert_eq!(run["base_quote_version"], 1);
+        assert_eq!(run["scenarios"].as_array().map(Vec::len), Some(2));
+        let run_id = run["id"].as_str().expect("run id").to_string();
+
+        let (status, _, stored) =
+            call(&app, Method::GET, &format!("{simulations}/{run_id}"), key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stored, run);
+
+        let promote = format!("{simulations}/{run_id}/promote");
+        let (status, _, body) =
+            call(&app, Method::POST, &promote, key, Some(json!({ "variant": "nope" })), &[]).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+        let (status, _, promoted) =
+            call(&app, Method::POST, &promote, key, Some(json!({ "variant": "more-seats" })), &[])
+                .await;
+        assert_eq!(status, StatusCode::OK, "{promoted}");
+        assert_eq!(promoted["previous_version"], 1);
+        assert_eq!(promoted["quote"]["version"], 2);
+        assert_eq!(promoted["quote"]["lines"][0]["quantity"], 15);
+
+        let linked: String = sqlx::query_scalar(
+            "SELECT metadata_json FROM audit_event WHERE event_type = 'quote.simulation_promoted'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("promotion audit event");
+        assert!(linked.contains(&run_id));
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            &promote,
+            key,
+            Some(json!({ "variant": "deep-discount" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    }
+
+    #[tokio::test]
+    async fn collab_sessions_resolve_edits_stream_events_and_commit() {
+        use tokio_stream::StreamExt;
+
+        let (pool, app) = setup().await;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO sales_rep (id, name, role, status, created_at, updated_at)
+             VALUES ('U-DESK', 'Deal Desk', 'ops', 'active', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("deal desk rep");
+        let key = Some(ADMIN_KEY);
+        let (_, _, quote) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[]).await;
+        let id = quote["id"].as_str().expect("id").to_string();
+
+        let open = format!("/api/v1/quotes/{id}/collab/sessions");
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            &open,
+            key,
+            Some(json!({ "user_id": "U-CUST", "role": "customer" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+        let (status, _, session) = call(
+            &app,
+            Method::POST,
+            &open,
+            key,
+            Some(json!({ "user_id": "U-REP", "role": "sales_rep" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{session}");
+        let session_id = session["id"].as_str().expect("session id").to_string();
+        let base = format!("/api/v1/collab/sessions/{session_id}");
+        let (status, _, desk) = call(
+            &app,
+            Method::POST,
+            &format!("{base}/participants"),
+            key,
+            Some(json!({ "user_id": "U-DESK" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{desk}");
+        assert_eq!(desk["role"], "deal_desk");
+
+        // Re-joining cannot claim more authority than the rep record grants.
+        let (status, _, rejoined) = call(
+            &app,
+            Method::POST,
+            &format!("{base}/participants"),
+            key,
+            Some(json!({ "user_id": "U-REP", "role": "vp_sales" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{rejoined}");
+        assert_eq!(rejoined["role"], "sales_rep");
+
+        let edit = |user: &str, base_seq: u64, quantity: u32| {
+            json!({
+                "user_id": user,
+                "base_seq": base_seq,
+                "operations": [{ "type": "update", "product_id": "PROD-A", "quantity": quantity }],
+            })
+        };
+        let operations = format!("{base}/operations");
+        let (status, _, desk_edit) =
+            call(&app, Method::POST, &operations, key, Some(edit("U-DESK", 0, 30)), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{desk_edit}");
+        let (_, _, rep_edit) =
+            call(&app, Method::POST, &operations, key, Some(edit("U-REP", 0, 12)), &[]).await;
+        assert_eq!(rep_edit["operations"][0]["status"], "overridden");
+        assert_eq!(
+            rep_edit["operations"][0]["superseded_by"],
+            desk_edit["operations"][0]["operation_id"]
+        );
+        assert_eq!(rep_edit["lines"][0]["quantity"], 30);
+
+        // The rep's stream replays the backlog, including the notice that their edit lost.
+        let request = HttpRequest::builder()
+            .uri(format!("{base}/events?user_id=U-REP"))
+            .header("authorization", format!("Bearer {ADMIN_KEY}"))
+            .body(Body::empty())
+            .expect("request");
+        let response = app.clone().oneshot(request).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut stream = response.into_body().into_data_stream();
+        let mut received = String::new();
+        while !received.contains("event: overridden") {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(2), stream.next())
+                .await
+                .expect("event before timeout")
+                .expect("stream open")
+                .expect("chunk");
+            received.push_str(&String::from_utf8_lossy(&chunk));
+        }
+        assert!(received.contains("event: operations"));
+
+        let (status, _, committed) = call(
+            &app,
+            Method::POST,
+            &format!("{base}/commit"),
+            key,
+            Some(json!({ "user_id": "U-DESK" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{committed}");
+        assert_eq!(committed["previous_version"], 1);
+        assert_eq!(committed["quote"]["version"], 2);
+        assert_eq!(committed["quote"]["lines"][0]["quantity"], 30);
+
+        let (status, _, body) =
+            call(&app, Method::POST, &operations, key, Some(edit("U-REP", 2, 5)), &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    }
+
+    #[tokio::test]
+    async fn idempotency_key_replays_and_rejects_mismatched_payloads() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let headers = [("idempotency-key", "create-001")];
+
+        let (status, first_headers, first) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &headers)
+                .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(first_headers.get("idempotent-replayed").is_none());
+
+        let (status, replay_headers, replay) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &headers)
+                .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(replay_headers.get("idempotent-replayed").expect("replayed"), "true");
+        assert_eq!(replay, first);
+
+        let quote_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM quote").fetch_one(&pool).await.expect("count");
+        assert_eq!(quote_count, 1);
+        let ledger_state: String = sqlx::query_scalar(
+            "SELECT state FROM execution_idempotency_ledger WHERE operation_key = ?",
+        )
+        .bind("rest:KEY-admin:create-001")
+        .fetch_one(&pool)
+        .await
+        .expect("ledger row");
+        assert_eq!(ledger_state, "completed");
+
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-2")), &headers)
+                .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "IDEMPOTENCY_KEY_REUSED");
+
+        // Terminal failures on an existing quote are replayed as well.
+        let id = first["id"].as_str().expect("id");
+        let bad_line = [("idempotency-key", "line-001")];
+        let uri = format!("/api/v1/quotes/{id}/lines");
+        let payload = json!({ "product_id": "PROD-MISSING", "quantity": 1 });
+        let (status, _, _) =
+            call(&app, Method::POST, &uri, key, Some(payload.clone()), &bad_line).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, headers, body) =
+            call(&app, Method::POST, &uri, key, Some(payload), &bad_line).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(headers.get("idempotent-replayed").expect("replayed"), "true");
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn concurrent_creates_with_one_idempotency_key_create_one_quote() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let headers = [("idempotency-key", "create-race")];
+
+        let ((first_status, first_headers, first), (second_status, second_headers, second)) = tokio::join!(
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &headers),
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &headers),
+        );
+        let quote_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM quote").fetch_one(&pool).await.expect("count");
+        assert_eq!(quote_count, 1, "{first} / {second}");
+        let outcomes = [
+            (first_status, first_headers.contains_key("idempotent-replayed"), &first),
+            (second_status, second_headers.contains_key("idempotent-replayed"), &second),
+        ];
+        let executed = outcomes
+            .iter()
+            .filter(|(status, replayed, _)| *status == StatusCode::CREATED && !replayed)
+            .count();
+        assert_eq!(executed, 1, "{first} / {second}");
+        for (status, replayed, body) in outcomes {
+            assert!(
+                status == StatusCode::CREATED
+                    || (status == StatusCode::CONFLICT
+                        && !replayed
+                        && body["error"]["code"] == "IDEMPOTENCY_IN_PROGRESS"),
+                "{status}: {body}"
+            );
+        }
+
+        // A key held by a request still in flight is never run a second time.
+        sqlx::query(
+            "INSERT INTO rest_idempotency_reservation
+                (operation_key, operation_kind, payload_hash, reserved_at)
+             SELECT 'rest:KEY-admin:create-held', operation_kind, payload_hash, ?
+             FROM execution_idempotency_ledger WHERE operation_key = 'rest:KEY-admin:create-race'",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .expect("held reservation");
+        let held = [("idempotency-key", "create-held")];
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &held)
+                .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+        assert_eq!(body["error"]["code"], "IDEMPOTENCY_IN_PROGRESS");
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-2")), &held)
+                .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+        let quote_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM quote").fetch_one(&pool).await.expect("count");
+        assert_eq!(quote_count, 1);
+    }
+
+    #[tokio::test]
+    async fn listings_paginate_and_respect_account_restrictions() {
+        let (pool, app) = setup().await;
+        for account in ["acct-1", "acct-1", "acct-1", "acct-2"] {
+            let (status, _, _) = call(
+                &app,
+                Method::POST,
+                "/api/v1/quotes",
+                Some(ADMIN_KEY),
+                Some(create_body(account)),
+                &[],
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let mut seen = Vec::new();
+        let mut uri = "/api/v1/quotes?limit=3".to_string();
+        loop {
+            let (status, _, page) = call(&app, Method::GET, &uri, Some(ADMIN_KEY), None, &[]).await;
+            assert_eq!(status, StatusCode::OK);
+            seen.extend(
+                page["data"].as_array().expect("data").iter().map(|quote| quote["id"].clone()),
+            );
+            match page["next_cursor"].as_str() {
+                Some(next) => uri = format!("/api/v1/quotes?limit=3&cursor={next}"),
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), 4);
+        seen.dedup();
+        assert_eq!(seen.len(), 4);
+
+        let (status, _, body) =
+            call(&app, Method::GET, "/api/v1/quotes?cursor=bogus", Some(ADMIN_KEY), None, &[])
+                .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+        issue_key(
+            &pool,
+            "acct2",
+            "acct2-secret",
+            ApiKeyGrants {
+                scopes: vec![ApiScope::QuoteWrite],
+                account_ids: vec!["acct-2".to_string()],
+                team_ids: Vec::new(),
+            },
+        )
+        .await;
+        let scoped = Some("acct2-secret");
+        let (_, _, page) = call(&app, Method::GET, "/api/v1/quotes", scoped, None, &[]).await;
+        let visible = page["data"].as_array().expect("data");
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0]["account_id"], "acct-2");
+
+        let other: String =
+            sqlx::query_scalar("SELECT id FROM quote WHERE account_id = 'acct-1' LIMIT 1")
+                .fetch_one(&pool)
+                .await
+                .expect("acct-1 quote");
+        let (status, _, _) =
+            call(&app, Method::GET, &format!("/api/v1/quotes/{other}"), scoped, None, &[]).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) =
+            call(&app, Method::POST, "/api/v1/quotes", scoped, Some(create_body("acct-1")), &[])
+                .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn catalog_search_pages_by_name() {
+        let (_, app) = setup().await;
+        let (status, _, page) =
+            call(&app, Method::GET, "/api/v1/catalog/products?limit=2", Some(ADMIN_KEY), None, &[])
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        let names: Vec<&str> = page["data"]
+            .as_array()
+            .expect("data")
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["Analytics Seat", "Backup Add-on"]);
+        let next = page["next_cursor"].as_str().expect("next cursor");
+
+        let (_, _, page) = call(
+            &app,
+            Method::GET,
+            &format!("/api/v1/catalog/products?limit=2&cursor={next}"),
+            Some(ADMIN_KEY),
+            None,
+            &[],
+        )
+        .await;
+        assert_eq!(page["data"][0]["name"], "Compute");
+        assert!(page["next_cursor"].is_null());
+
+        let (_, _, page) = call(
+            &app,
+            Method::GET,
+            "/api/v1/catalog/products?q=backup",
+            Some(ADMIN_KEY),
+            None,
+            &[],
+        )
+        .await;
+        assert_eq!(page["data"][0]["id"], "PROD-B");
+        assert_eq!(page["data"][0]["base_price"], "40");
+
+        let (status, _, body) = call(
+            &app,
+            Method::GET,
+            "/api/v1/catalog/products/PROD-NOPE",
+            Some(ADMIN_KEY),
+            None,
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn catalog_imports_diff_before_applying_and_deactivate_missing_products() {
+        let (_, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let (status, _, export) =
+            call(&app, Method::GET, "/api/v1/catalog/export?format=json", key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(export["products"], 4, "inactive products are exported too");
+        let mut document = export["document"].clone();
+        let products = document["products"].as_array_mut().expect("products");
+        products.retain(|product| product["id"] != "PROD-A");
+        for product in products.iter_mut().filter(|product| product["id"] == "PROD-B") {
+            product["base_price"] = json!("42.50");
+        }
+
+        let (status, _, diff) = call(
+            &app,
+            Method::POST,
+            "/api/v1/catalog/imports",
+            key,
+            Some(json!({ "document": document })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{diff}");
+        assert_eq!(diff["dry_run"], true);
+        assert!(diff["import_id"].is_null());
+        assert_eq!(diff["summary"]["products_updated"], 1);
+        assert_eq!(diff["summary"]["products_deactivated"], 1);
+        let (_, _, product) =
+            call(&app, Method::GET, "/api/v1/catalog/products/PROD-A", key, None, &[]).await;
+        assert_eq!(product["active"], true, "dry runs change nothing");
+
+        let (status, _, applied) = call(
+            &app,
+            Method::POST,
+            "/api/v1/catalog/imports",
+            key,
+            Some(json!({ "document": document, "dry_run": false })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{applied}");
+        assert!(applied["import_id"].as_str().expect("import id").starts_with("CATIMP-"));
+        assert_eq!(applied["products"], diff["products"]);
+        let (_, _, product) =
+            call(&app, Method::GET, "/api/v1/catalog/products/PROD-A", key, None, &[]).await;
+        assert_eq!(product["active"], false);
+
+        let (_, _, export) =
+            call(&app, Method::GET, "/api/v1/catalog/export?format=csv", key, None, &[]).await;
+        let csv = export["csv"].as_str().expect("csv").replace(",USD,", ",US,");
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/catalog/imports",
+            key,
+            Some(json!({ "csv": csv, "dry_run": false })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+        assert_eq!(
+            body["error"]["details"]["issues"][0]["message"],
+            "currency 'US' must be a 3-letter ISO code"
+        );
+    }
+
+    #[tokio::test]
+    async fn quote_view_serves_prediction_from_promoted_model() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let create = |product: &str, quantity: u32, discount: f64| {
+            json!({
+                "account_id": "acct-1",
+                "currency": "USD",
+                "term_months": 12,
+                "lines": [{ "product_id": product, "quantity": quantity, "discount_pct": discount }],
+            })
+        };
+
+        for index in 0..12u32 {
+            let won = index % 2 == 0;
+            let body = if won { create("PROD-A", 40, 5.0) } else { create("PROD-C", 2, 40.0) };
+            let (status, _, quote) =
+                call(&app, Method::POST, "/api/v1/quotes", key, Some(body), &[]).await;
+            assert_eq!(status, StatusCode::CREATED);
+            sqlx::query(
+                "INSERT INTO deal_outcomes
+                    (id, quote_id, outcome, final_price, close_date, created_at)
+                 VALUES (?, ?, ?, 100.0, ?, '2026-03-01T00:00:00Z')",
+            )
+            .bind(format!("do-{index}"))
+            .bind(quote["id"].as_str().expect("id"))
+            .bind(if won { "won" } else { "lost" })
+            .bind(format!("2026-02-{:02}", index + 1))
+            .execute(&pool)
+            .await
+            .expect("seed outcome");
+        }
+
+        let (_, _, open) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create("PROD-A", 40, 5.0)), &[])
+                .await;
+        let uri = format!("/api/v1/quotes/{}", open["id"].as_str().expect("id"));
+        assert!(open.get("win_probability").is_none());
+        let (_, _, quote) = call(&app, Method::GET, &uri, key, None, &[]).await;
+        assert!(quote.get("win_probability").is_none(), "no model is active yet");
+
+        let models = WinProbabilityService::new(pool.clone());
+        let run = models.train(&TrainingConfig::default()).await.expect("train");
+        models.promote(&run.model.version).await.expect("promote");
+
+        let (status, _, quote) = call(&app, Method::GET, &uri, key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(quote["win_probability"]["model_version"], "v1");
+        let probability = quote["win_probability"]["probability"].as_f64().expect("probability");
+        assert!(probability > 0.5, "p = {probability}");
+    }
+
+    #[tokio::test]
+    async fn webhook_subscriptions_log_lifecycle_deliveries_and_redeliver() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        issue_key(
+            &pool,
+            "settings-reader",
+            "settings-reader-secret",
+            ApiKeyGrants { scopes: vec![ApiScope::SettingsRead], ..ApiKeyGrants::default() },
+        )
+        .await;
+        let subscription_body = json!({
+            "url": "https://203.0.113.10/quotey",
+            "events": ["quote.finalized"],
+            "description": "CPQ mirror",
+        });
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/webhooks",
+            Some("settings-reader-secret"),
+            Some(subscription_body.clone()),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["details"]["required_scope"], "settings:admin");
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/webhooks",
+            key,
+            Some(json!({ "url": "https://203.0.113.10", "events": ["quote.deleted"] })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+        let (status, _, subscription) =
+            call(&app, Method::POST, "/api/v1/webhooks", key, Some(subscription_body), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{subscription}");
+        assert!(subscription["secret"].as_str().expect("secret").starts_with("whsec_"));
+        let subscription_id = subscription["id"].as_str().expect("id").to_string();
+
+        let (status, _, listed) =
+            call(&app, Method::GET, "/api/v1/webhooks", Some("settings-reader-secret"), None, &[])
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed["data"][0]["id"], subscription_id.as_str());
+        assert!(listed["data"][0].get("secret").is_none(), "secrets are shown once");
+
+        let (_, _, quote) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[]).await;
+        let id = quote["id"].as_str().expect("id").to_string();
+        let transitions = format!("/api/v1/quotes/{id}/transitions");
+        call(&app, Method::POST, &transitions, key, Some(json!({ "status": "validated" })), &[])
+            .await;
+        call(&app, Method::POST, &format!("/api/v1/quotes/{id}/price"), key, Some(json!({})), &[])
+            .await;
+        let (status, _, quote) = call(
+            &app,
+            Method::POST,
+            &transitions,
+            key,
+            Some(json!({ "status": "finalized" })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{quote}");
+
+        let deliveries_path = format!("/api/v1/webhooks/{subscription_id}/deliveries");
+        let (status, _, deliveries) =
+            call(&app, Method::GET, &deliveries_path, key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK, "{deliveries}");
+        assert_eq!(deliveries["data"].as_array().expect("data").len(), 1);
+        assert_eq!(deliveries["data"][0]["event_type"], "quote.finalized");
+        assert_eq!(deliveries["data"][0]["quote_id"], id.as_str());
+        assert_eq!(deliveries["data"][0]["status"], "pending");
+        let delivery_id = deliveries["data"][0]["id"].as_str().expect("delivery").to_string();
+
+        let (status, _, log) =
+            call(&app, Method::GET, &format!("{deliveries_path}/{delivery_id}"), key, None, &[])
+                .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(log["attempts"], json!([]));
+
+        let redeliver = format!("{deliveries_path}/{delivery_id}/redeliver");
+        let (status, _, again) = call(&app, Method::POST, &redeliver, key, None, &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{again}");
+        assert_eq!(again["redelivery_of"], delivery_id.as_str());
+        assert_eq!(again["event_id"], deliveries["data"][0]["event_id"]);
+
+        let (_, _, filtered) =
+            call(&app, Method::GET, &format!("{deliveries_path}?limit=1"), key, None, &[]).await;
+        assert_eq!(filtered["data"][0]["id"], again["id"]);
+        assert!(filtered["next_cursor"].is_string());
+
+        let (status, _, disabled) = call(
+            &app,
+            Method::DELETE,
+            &format!("/api/v1/webhooks/{subscription_id}"),
+            key,
+            None,
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(disabled["active"], false);
+        let (status, _, body) = call(&app, Method::POST, &redeliver, key, None, &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    }
+
+    #[tokio::test]
+    async fn analytics_query_aggregates_quotes_and_rejects_bad_specs() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        issue_key(
+            &pool,
+            "quote-reader",
+            "quote-reader-secret",
+            ApiKeyGrants { scopes: vec![ApiScope::QuoteRead], ..ApiKeyGrants::default() },
+        )
+        .await;
+        for account in ["acct-1", "acct-2"] {
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body(account)), &[]).await;
+        }
+        let query = json!({
+            "metrics": ["quote_count", "avg_discount_pct"],
+            "dimensions": ["customer_segment"],
+            "filters": [{ "dimension": "customer_segment", "values": ["acct-2"] }],
+        });
+
+        let (status, _, result) =
+            call(&app, Method::POST, "/api/v1/analytics/query", key, Some(query.clone()), &[])
+                .await;
+        assert_eq!(status, StatusCode::OK, "{result}");
+        assert_eq!(
+            result["columns"][0],
+            json!({ "name": "customer_segment", "kind": "dimension" })
+        );
+        assert_eq!(
+            result["rows"],
+            json!([{
+                "customer_segment": "acct-2",
+                "quote_count": 1,
+                "avg_discount_pct": 5.0,
+            }])
+        );
+        assert_eq!(result["truncated"], false);
+        assert_eq!(result["cached"], false);
+
+        let (_, _, again) =
+            call(&app, Method::POST, "/api/v1/analytics/query", key, Some(query.clone()), &[])
+                .await;
+        assert_eq!(again["cached"], true);
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/analytics/query",
+            key,
+            Some(json!({ "metrics": ["revenue"] })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["message"], "unknown metric `revenue`");
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/analytics/query",
+            key,
+            Some(json!({ "metrics": ["quote_count"], "limit": 50000 })),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+        let (status, _, body) = call(
+            &app,
+            Method::POST,
+            "/api/v1/analytics/query",
+            Some("quote-reader-secret"),
+            Some(query),
+            &[],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["details"]["required_scope"], "audit:read");
+    }
+
+    #[tokio::test]
+    async fn quote_templates_publish_assign_defaults_and_preview() {
+        let (_, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let (_, _, quote) =
+            call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[]).await;
+        let id = quote["id"].as_str().expect("id").to_string();
+        let preview = format!("/api/v1/quotes/{id}/preview");
+
+        let (status, _, builtin) =
+            call(&app, Method::POST, &preview, key, Some(json!({})), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{builtin}");
+        assert_eq!(builtin["template_key"], "detailed");
+        assert_eq!(builtin["template_version"], Value::Null);
+        assert_eq!(builtin["sections"].as_array().map(Vec::len), Some(3));
+        assert!(builtin["pdf_base64"].as_str().is_some_and(|pdf| pdf.starts_with("JVBERi")));
+
+        let definition = json!({
+            "layout": "compact",
+            "sections": [
+                { "kind": "clause", "key": "msa", "when": { "min_total": "500" } },
+                { "kind": "terms", "when": { "segments": ["enterprise"] } },
+            ],
+        });
+        let draft_path = "/api/v1/templates/enterprise/versions";
+        let draft = json!({ "label": "Enterprise", "definition": definition });
+        let (status, _, body) =
+            call(&app, Method::POST, draft_path, key, Some(draft.clone()), &[]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "unknown clause: {body}");
+
+        let clause = json!({ "key": "msa", "title": "MSA", "body": "Governed by the MSA." });
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/templates/clauses", key, Some(clause), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+        assert_eq!(body["version"], 1);
+
+        let (status, _, saved) = call(&app, Method::POST, draft_path, key, Some(draft), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{saved}");
+        assert_eq!((saved["version"].clone(), saved["status"].clone()), (json!(1), json!("draft")));
+        assert_eq!(saved["definition"], definition);
+
+        let draft_preview = json!({ "template_key": "enterprise", "version": 1 });
+        let (status, _, rendered) =
+            call(&app, Method::POST, &preview, key, Some(draft_preview), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{rendered}");
+        assert_eq!(rendered["layout"], "compact");
+        assert_eq!(
+            rendered["sections"],
+            json!([{ "kind": "clause", "key": "msa", "version": 1, "title": "MSA" }])
+        );
+
+        let default =
+            json!({ "scope": "account", "value": "acct-1", "template_key": "enterprise" });
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/templates/defaults", key, Some(default.clone()), &[])
+                .await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "drafts cannot be defaults: {body}");
+
+        let publish = format!("{draft_path}/1/publish");
+        let (status, _, published) = call(&app, Method::POST, &publish, key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK, "{published}");
+        assert_eq!(published["status"], "published");
+        let (status, _, _) = call(&app, Method::POST, &publish, key, None, &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, _, body) =
+            call(&app, Method::POST, "/api/v1/templates/defaults", key, Some(default), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let (status, _, rendered) =
+            call(&app, Method::POST, &preview, key, Some(json!({})), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{rendered}");
+        assert_eq!(
+            (rendered["template_key"].clone(), rendered["template_version"].clone()),
+            (json!("enterprise"), json!(1))
+        );
+
+        let (status, _, registry) =
+            call(&app, Method::GET, "/api/v1/templates", key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            registry["builtin_layouts"],
+            json!(["detailed", "executive_summary", "compact"])
+        );
+        assert_eq!(registry["templates"][0]["key"], "enterprise");
+        assert_eq!(registry["defaults"][0]["value"], "acct-1");
+    }
+
+    #[tokio::test]
+    async fn order_forms_are_generated_signed_rendered_and_amended() {
+        let (pool, app) = setup().await;
+        let key = Some(ADMIN_KEY);
+        let mut quote_ids = Vec::new();
+        for _ in 0..2 {
+            let (_, _, quote) =
+                call(&app, Method::POST, "/api/v1/quotes", key, Some(create_body("acct-1")), &[])
+                    .await;
+            quote_ids.push(quote["id"].as_str().expect("id").to_string());
+        }
+        let generate = format!("/api/v1/quotes/{}/order-forms", quote_ids[0]);
+        let body = json!({
+            "billing_frequency": "quarterly",
+            "start_date": "2026-01-01",
+            "term_months": 12,
+            "signatories": [{ "party": "customer", "name": "Dana Reyes", "title": "VP" }],
+        });
+        let (status, _, draft_quote) =
+            call(&app, Method::POST, &generate, key, Some(body.clone()), &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT, "only accepted quotes: {draft_quote}");
+
+        sqlx::query("UPDATE quote SET status = 'accepted'")
+            .execute(&pool)
+            .await
+            .expect("accept quotes");
+        let (status, _, order) =
+            call(&app, Method::POST, &generate, key, Some(body.clone()), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{order}");
+        assert_eq!(order["status"], "draft");
+        assert_eq!(order["allowed_transitions"], json!(["sent"]));
+        assert_eq!(order["title"], "Order Form");
+        assert_eq!(order["billing_schedule"].as_array().map(Vec::len), Some(4));
+        let kinds: Vec<&str> = order["sections"]
+            .as_array()
+            .expect("sections")
+            .iter()
+            .filter_map(|s| s["kind"].as_str())
+            .collect();
+        assert_eq!(kinds, ["assumptions", "notes", "terms", "billing_schedule", "signatures"]);
+        let (status, _, _) = call(&app, Method::POST, &generate, key, Some(body), &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT, "one order form per quote");
+
+        let id = order["id"].as_str().expect("order id").to_string();
+        let transitions = format!("/api/v1/order-forms/{id}/transitions");
+        let amendments = format!("/api/v1/order-forms/{id}/amendments");
+        let amend = json!({ "quote_id": quote_ids[1], "billing_frequency": "monthly" });
+        let (status, _, _) =
+            call(&app, Method::POST, &amendments, key, Some(amend.clone()), &[]).await;
+        assert_eq!(status, StatusCode::CONFLICT, "drafts cannot be amended");
+
+        let (status, _, skipped) =
+            call(&app, Method::POST, &transitions, key, Some(json!({ "status": "signed" })), &[])
+                .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{skipped}");
+        for step in ["sent", "signed"] {
+            let (status, _, moved) =
+                call(&app, Method::POST, &transitions, key, Some(json!({ "status": step })), &[])
+                    .await;
+            assert_eq!((status, moved["status"].as_str()), (StatusCode::OK, Some(step)), "{moved}");
+        }
+        let countersign = json!({
+            "status": "countersigned",
+            "signer": { "party": "provider", "name": "Sam Ortiz" },
+        });
+        let (status, _, signed) =
+            call(&app, Method::POST, &transitions, key, Some(countersign), &[]).await;
+        assert_eq!(status, StatusCode::OK, "{signed}");
+        assert_eq!(signed["allowed_transitions"], json!([]));
+        assert!(signed["signatories"][1]["signed_at"].is_string());
+
+        let (status, _, document) =
+            call(&app, Method::GET, &format!("/api/v1/order-forms/{id}/document"), key, None, &[])
+                .await;
+        assert_eq!(status, StatusCode::OK, "{document}");
+        assert!(document["pdf_base64"].as_str().is_some_and(|pdf| pdf.starts_with("JVBERi")));
+
+        let (status, _, amendment) =
+            call(&app, Method::POST, &amendments, key, Some(amend), &[]).await;
+        assert_eq!(status, StatusCode::CREATED, "{amendment}");
+        assert_eq!(amendment["amends_order_id"], id);
+        assert_eq!(amendment["amendment_number"], 1);
+        let (status, _, original) =
+            call(&app, Method::GET, &format!("/api/v1/order-forms/{id}"), key, None, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(original["amendments"], json!([amendment["id"]]));
+
+        issue_key(
+            &pool,
+            "west",
+            "west-secret",
+            ApiKeyGrants {
+                scopes: vec![ApiScope::QuoteRead, ApiScope::QuoteWrite],
+                account_ids: vec!["acct-1".to_string()],
+                team_ids: vec!["team-west".to_string()],
+            },
+        )
+        .await;
+        let west = Some("west-secret");
+        let order_uri = format!("/api/v1/order-forms/{id}");
+        for (method, uri) in
+            [(Method::GET, order_uri.clone()), (Method::GET, format!("{order_uri}/document"))]
+        {
+            let (status, _, body) = call(&app, method, &uri, west, None, &[]).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{uri}: {body}");
+        }
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO sales_rep (id, name, role, status, team_id, created_at, updated_at)
+             VALUES ('U-WEST', 'West Rep', 'ae', 'active', 'team-west', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("west rep");
+        sqlx::query("UPDATE quote SET created_by_sales_rep_id = 'U-WEST' WHERE id = ?")
+            .bind(&quote_ids[0])
+            .execute(&pool)
+            .await
+            .expect("assign quote");
+        let (status, _, body) = call(&app, Method::GET, &order_uri, west, None, &[]).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+}
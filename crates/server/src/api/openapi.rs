@@ -0,0 +1,169 @@
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::{JsonSchema, Schema};
+use serde_json::{json, Map, Value};
+
+use super::error::ErrorEnvelope;
+use super::idempotency::MAX_KEY_LEN;
+use super::{ApiRoute, API_VERSION};
+
+/// Schema reference for a request or response body, registered under `#/components/schemas`.
+pub fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+/// Fully inlined schema of a query-string struct; each property becomes a query parameter.
+pub fn query_schema<T: JsonSchema>() -> Schema {
+    SchemaSettings::openapi3()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// Builds the OpenAPI 3.0 document for the given route table.
+pub fn document(routes: &[ApiRoute]) -> Value {
+    let mut generator = SchemaSettings::openapi3().into_generator();
+    let error_schema = schema::<ErrorEnvelope>(&mut generator).to_value();
+
+    let mut paths = Map::new();
+    for route in routes {
+        let mut parameters: Vec<Value> = path_parameters(route.path)
+            .map(|name| {
+                json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+            })
+            .collect();
+        if let Some(query) = route.query {
+            parameters.extend(query_parameters(&query()));
+        }
+        if route.idempotent {
+            parameters.push(json!({
+                "name": "Idempotency-Key",
+                "in": "header",
+                "required": false,
+                "description": format!(
+                    "Replays the stored response when the same key is retried with the same body; \
+                     keys live for 24 hours and are scoped to the calling API key (max {MAX_KEY_LEN} chars)."
+                ),
+                "schema": { "type": "string", "maxLength": MAX_KEY_LEN },
+            }));
+        }
+        if route.if_match {
+            parameters.push(json!({
+                "name": "If-Match",
+                "in": "header",
+                "required": false,
+                "description": "Expected quote `version`; the request fails with CONFLICT when it differs.",
+                "schema": { "type": "integer", "minimum": 1 },
+            }));
+        }
+
+        let mut operation = json!({
+            "operationId": route.operation_id,
+            "summary": route.summary,
+            "tags": [route.tag],
+            "security": [{ "bearerAuth": [] }, { "apiKeyHeader": [] }],
+            "x-required-scope": route.scope.as_str(),
+            "parameters": parameters,
+            "responses": {
+                route.success_status.to_string(): {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": (route.response)(&mut generator).to_value() } },
+                },
+                "4XX": error_response(&error_schema),
+                "5XX": error_response(&error_schema),
+            },
+        });
+        if let Some(request) = route.request {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": request(&mut generator).to_value() } },
+            });
+        }
+
+        // axum and OpenAPI share the `{name}` placeholder syntax, so paths carry over verbatim.
+        let entry = paths.entry(route.path).or_insert_with(|| Value::Object(Map::new()));
+        entry[route.method.as_str()] = operation;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Quotey REST API",
+            "version": API_VERSION,
+            "description": "Quote lifecycle, line editing, pricing, catalog, approvals and comments. \
+                            Errors use the canonical `{error: {code, message, details}}` envelope; \
+                            collections are cursor-paginated via `cursor` and `next_cursor`.",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": generator.take_definitions(true),
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+                "apiKeyHeader": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+            },
+        },
+    })
+}
+
+fn error_response(schema: &Value) -> Value {
+    json!({
+        "description": "Error envelope",
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+fn query_parameters(schema: &Schema) -> Vec<Value> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, property)| {
+                    let mut property = property.clone();
+                    let description =
+                        property.as_object_mut().and_then(|object| object.remove("description"));
+                    let mut parameter = json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required.contains(&name.as_str()),
+                        "schema": property,
+                    });
+                    if let Some(description) = description {
+                        parameter["description"] = description;
+                    }
+                    parameter
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_parameters_are_extracted_in_order() {
+        let names: Vec<&str> = path_parameters("/api/v1/quotes/{id}/lines/{line_id}").collect();
+        assert_eq!(names, vec!["id", "line_id"]);
+    }
+
+    #[test]
+    fn query_struct_properties_become_parameters() {
+        let parameters = query_parameters(&query_schema::<super::super::quotes::ListQuotesQuery>());
+        let names: Vec<&str> =
+            parameters.iter().filter_map(|parameter| parameter["name"].as_str()).collect();
+        assert!(names.contains(&"cursor"));
+        assert!(names.contains(&"limit"));
+        assert!(parameters.iter().all(|parameter| parameter["in"] == "query"));
+    }
+}
@@ -0,0 +1,427 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use base64::Engine as _;
+use quotey_core::chrono::NaiveDate;
+use quotey_core::domain::order_form::{
+    BillingFrequency, OrderFormStatus, Signatory, SignatoryParty,
+};
+use quotey_core::esign::document_sha256;
+use quotey_db::order_forms::{
+    ClauseSelection, GenerateOrderForm, OrderForm, OrderFormService, OrderFormServiceError,
+};
+use quotey_db::quote_templates::TemplateSelection;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult};
+use super::quotes::{load_quote, normalize_id, optional_trimmed};
+use super::templates::{template_error, PreviewSectionResource};
+use super::{ApiJson, ApiState};
+use crate::pdf::PdfGenerator;
+use crate::portal::BrandingConfig;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SignatoryResource {
+    /// `customer` or `provider`.
+    pub party: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Set when the party signs; ignored on input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_at: Option<String>,
+}
+
+impl From<&Signatory> for SignatoryResource {
+    fn from(signatory: &Signatory) -> Self {
+        Self {
+            party: signatory.party.as_str().to_string(),
+            name: signatory.name.clone(),
+            title: signatory.title.clone(),
+            email: signatory.email.clone(),
+            signed_at: signatory.signed_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
+
+impl TryFrom<SignatoryResource> for Signatory {
+    type Error = ApiError;
+
+    fn try_from(resource: SignatoryResource) -> ApiResult<Self> {
+        let party = match resource.party.trim() {
+            "customer" => SignatoryParty::Customer,
+            "provider" => SignatoryParty::Provider,
+            other => {
+                return Err(ApiError::validation(format!(
+                    "unknown signatory party `{other}`; expected customer or provider"
+                )))
+            }
+        };
+        Ok(Self {
+            party,
+            name: resource.name.trim().to_string(),
+            title: optional_trimmed(resource.title),
+            email: optional_trimmed(resource.email),
+            signed_at: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OrderLineResource {
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    pub name: String,
+    pub quantity: u32,
+    pub unit_price: String,
+    pub discount_pct: String,
+    pub subtotal: String,
+    pub total: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BillingInstallmentResource {
+    pub sequence: u32,
+    pub description: String,
+    pub due_date: String,
+    /// Decimal string.
+    pub amount: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OrderFormResource {
+    pub id: String,
+    pub quote_id: String,
+    pub quote_version: u32,
+    pub account_id: Option<String>,
+    /// The original order form this one amends.
+    pub amends_order_id: Option<String>,
+    /// `0` for an original order form; amendments are numbered from 1.
+    pub amendment_number: u32,
+    /// `draft`, `sent`, `signed` or `countersigned`.
+    pub status: String,
+    pub allowed_transitions: Vec<String>,
+    pub title: Option<String>,
+    pub currency: String,
+    pub lines: Vec<OrderLineResource>,
+    pub subtotal: String,
+    pub discount_total: String,
+    pub total: String,
+    pub payment_terms: String,
+    pub billing_frequency: String,
+    pub term_months: u32,
+    pub start_date: String,
+    pub billing_schedule: Vec<BillingInstallmentResource>,
+    pub template_key: String,
+    pub template_version: Option<i32>,
+    /// Sections printed after the pricing, in order, including selected clauses.
+    pub sections: Vec<PreviewSectionResource>,
+    pub signatories: Vec<SignatoryResource>,
+    /// SHA-256 of the content snapshot taken from the quote.
+    pub content_sha256: String,
+    /// Newest quote ledger entry when the order form was generated, normally the acceptance.
+    pub quote_ledger_entry_id: Option<String>,
+    /// Ids of the amendments of this order form, oldest first.
+    pub amendments: Vec<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub sent_at: Option<String>,
+    pub signed_at: Option<String>,
+    pub countersigned_at: Option<String>,
+}
+
+impl OrderFormResource {
+    fn new(order: OrderForm, amendments: &[OrderForm]) -> Self {
+        let content = order.content;
+        let allowed_transitions = [
+            OrderFormStatus::Draft,
+            OrderFormStatus::Sent,
+            OrderFormStatus::Signed,
+            OrderFormStatus::Countersigned,
+        ]
+        .into_iter()
+        .filter(|next| order.status.can_transition_to(*next))
+        .map(|next| next.as_str().to_string())
+        .collect();
+        Self {
+            id: order.id.0,
+            quote_id: order.quote_id,
+            quote_version: content.quote_version,
+            account_id: order.account_id,
+            amends_order_id: order.amends_order_id.map(|id| id.0),
+            amendment_number: order.amendment_number,
+            status: order.status.as_str().to_string(),
+            allowed_transitions,
+            title: content.document.title.clone(),
+            currency: content.currency,
+            lines: content
+                .lines
+                .into_iter()
+                .map(|line| OrderLineResource {
+                    product_id: line.product_id,
+                    sku: line.sku,
+                    name: line.name,
+                    quantity: line.quantity,
+                    unit_price: line.unit_price.to_string(),
+                    discount_pct: line.discount_pct.to_string(),
+                    subtotal: line.subtotal.to_string(),
+                    total: line.total.to_string(),
+                })
+                .collect(),
+            subtotal: content.subtotal.to_string(),
+            discount_total: content.discount_total.to_string(),
+            total: content.total.to_string(),
+            payment_terms: content.payment_terms,
+            billing_frequency: content.billing_frequency.as_str().to_string(),
+            term_months: content.term_months,
+            start_date: content.start_date.to_string(),
+            billing_schedule: content
+                .billing_schedule
+                .into_iter()
+                .map(|installment| BillingInstallmentResource {
+                    sequence: installment.sequence,
+                    description: installment.description,
+                    due_date: installment.due_date.to_string(),
+                    amount: installment.amount.to_string(),
+                })
+                .collect(),
+            sections: content.document.sections.iter().map(PreviewSectionResource::from).collect(),
+            template_key: content.document.template_key,
+            template_version: content.document.template_version,
+            signatories: order.signatories.iter().map(SignatoryResource::from).collect(),
+            content_sha256: order.content_sha256,
+            quote_ledger_entry_id: order.quote_ledger_entry_id,
+            amendments: amendments.iter().map(|amendment| amendment.id.0.clone()).collect(),
+            created_by: order.created_by,
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+            sent_at: order.sent_at,
+            signed_at: order.signed_at,
+            countersigned_at: order.countersigned_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClauseSelectionResource {
+    pub key: String,
+    /// Pins a clause version; the newest is used when absent.
+    #[serde(default)]
+    pub version: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateOrderFormRequest {
+    /// Template to render with; the quote's default template when absent.
+    #[serde(default)]
+    pub template_key: Option<String>,
+    #[serde(default)]
+    pub template_version: Option<i32>,
+    /// `upfront`, `annual` (default), `quarterly` or `monthly`.
+    #[serde(default)]
+    pub billing_frequency: Option<String>,
+    /// `YYYY-MM-DD`; the quote's start date, else today, when absent.
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// The quote's term, else 12 months, when absent.
+    #[serde(default)]
+    pub term_months: Option<u32>,
+    #[serde(default)]
+    pub signatories: Vec<SignatoryResource>,
+    /// Clause blocks printed after the template's sections.
+    #[serde(default)]
+    pub clauses: Vec<ClauseSelectionResource>,
+}
+
+impl GenerateOrderFormRequest {
+    fn into_request(self, quote_id: String, actor: String) -> ApiResult<GenerateOrderForm> {
+        let billing_frequency = match optional_trimmed(self.billing_frequency) {
+            Some(frequency) => BillingFrequency::parse_label(&frequency).ok_or_else(|| {
+                ApiError::validation(format!(
+                    "unknown billing_frequency `{frequency}`; expected upfront, annual, \
+                     quarterly or monthly"
+                ))
+            })?,
+            None => BillingFrequency::default(),
+        };
+        let start_date = optional_trimmed(self.start_date)
+            .map(|raw| {
+                raw.parse::<NaiveDate>()
+                    .map_err(|_| ApiError::validation("start_date must be formatted YYYY-MM-DD"))
+            })
+            .transpose()?;
+        if self.term_months == Some(0) {
+            return Err(ApiError::validation("term_months must be greater than 0"));
+        }
+        Ok(GenerateOrderForm {
+            quote_id,
+            template: TemplateSelection {
+                key: optional_trimmed(self.template_key),
+                version: self.template_version,
+            },
+            billing_frequency,
+            start_date,
+            term_months: self.term_months,
+            signatories: self
+                .signatories
+                .into_iter()
+                .map(Signatory::try_from)
+                .collect::<ApiResult<_>>()?,
+            clauses: self
+                .clauses
+                .into_iter()
+                .map(|clause| ClauseSelection { key: clause.key, version: clause.version })
+                .collect(),
+            actor,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmendOrderFormRequest {
+    /// The accepted quote with the amended terms.
+    pub quote_id: String,
+    #[serde(flatten)]
+    pub order_form: GenerateOrderFormRequest,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TransitionOrderFormRequest {
+    /// `sent`, `signed` or `countersigned`; one step at a time.
+    pub status: String,
+    /// Replaces the signing party's block when signing or countersigning.
+    #[serde(default)]
+    pub signer: Option<SignatoryResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OrderFormDocumentResource {
+    pub order_id: String,
+    pub status: String,
+    pub content_type: String,
+    pub sha256: String,
+    pub pdf_base64: String,
+}
+
+/// Generates the draft order form of an accepted quote.
+pub async fn generate_order_form(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<GenerateOrderFormRequest>,
+) -> ApiResult<(StatusCode, Json<OrderFormResource>)> {
+    let quote = load_quote(&state, &principal, &id).await?;
+    let request = body.into_request(quote.id.0, principal.actor())?;
+    let order = OrderFormService::new(state.db_pool.clone())
+        .generate(request)
+        .await
+        .map_err(order_form_error)?;
+    Ok((StatusCode::CREATED, Json(OrderFormResource::new(order, &[]))))
+}
+
+pub async fn get_order_form(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<OrderFormResource>> {
+    let service = OrderFormService::new(state.db_pool.clone());
+    let order = load_order_form(&state, &principal, &service, &id).await?;
+    let amendments = service.amendments(&order.id.0).await.map_err(order_form_error)?;
+    Ok(Json(OrderFormResource::new(order, &amendments)))
+}
+
+pub async fn transition_order_form(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<TransitionOrderFormRequest>,
+) -> ApiResult<Json<OrderFormResource>> {
+    let status = OrderFormStatus::parse_label(body.status.trim()).ok_or_else(|| {
+        ApiError::validation(format!(
+            "unknown status `{}`; expected sent, signed or countersigned",
+            body.status.trim()
+        ))
+    })?;
+    let signer = body.signer.map(Signatory::try_from).transpose()?;
+    let service = OrderFormService::new(state.db_pool.clone());
+    let order = load_order_form(&state, &principal, &service, &id).await?;
+    let order = service.transition(&order.id.0, status, signer).await.map_err(order_form_error)?;
+    let amendments = service.amendments(&order.id.0).await.map_err(order_form_error)?;
+    Ok(Json(OrderFormResource::new(order, &amendments)))
+}
+
+/// Generates an amendment of a countersigned order form from another accepted quote.
+pub async fn amend_order_form(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<AmendOrderFormRequest>,
+) -> ApiResult<(StatusCode, Json<OrderFormResource>)> {
+    let service = OrderFormService::new(state.db_pool.clone());
+    let original = load_order_form(&state, &principal, &service, &id).await?;
+    let quote = load_quote(&state, &principal, &body.quote_id).await?;
+    let request = body.order_form.into_request(quote.id.0, principal.actor())?;
+    let amendment = service.amend(&original.id.0, request).await.map_err(order_form_error)?;
+    Ok((StatusCode::CREATED, Json(OrderFormResource::new(amendment, &[]))))
+}
+
+/// Renders the order form from its snapshot through the quote template layouts.
+pub async fn render_order_form(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<OrderFormDocumentResource>> {
+    let service = OrderFormService::new(state.db_pool.clone());
+    let order = load_order_form(&state, &principal, &service, &id).await?;
+    let company_name = BrandingConfig::from_env().company_name;
+    let payload =
+        order.content.render_payload(&order.id, order.status, &order.signatories, &company_name);
+    let pdf = PdfGenerator::with_embedded_templates()
+        .generate_document_pdf(&payload, &order.content.document)
+        .map_err(|error| ApiError::internal(&error))?;
+    Ok(Json(OrderFormDocumentResource {
+        order_id: order.id.0,
+        status: order.status.as_str().to_string(),
+        content_type: "application/pdf".to_string(),
+        sha256: document_sha256(&pdf.0),
+        pdf_base64: base64::engine::general_purpose::STANDARD.encode(&pdf.0),
+    }))
+}
+
+/// Loads an order form and checks the key may see its account and quote.
+async fn load_order_form(
+    state: &ApiState,
+    principal: &ApiPrincipal,
+    service: &OrderFormService,
+    id: &str,
+) -> ApiResult<OrderForm> {
+    let id = normalize_id(id, "order form id")?;
+    let order = service
+        .find(&id)
+        .await
+        .map_err(order_form_error)?
+        .ok_or_else(|| ApiError::not_found(format!("Order form '{id}' not found")))?;
+    principal.ensure_account(order.account_id.as_deref())?;
+    principal.ensure_quote_access(&state.db_pool, &order.quote_id).await?;
+    Ok(order)
+}
+
+fn order_form_error(error: OrderFormServiceError) -> ApiError {
+    match error {
+        OrderFormServiceError::Template(error) => template_error(error),
+        error => match error.code() {
+            "not_found" => ApiError::not_found(error.to_string()),
+            "conflict" => ApiError::conflict(error.to_string()),
+            "invalid_order_form" => ApiError::validation(error.to_string()),
+            _ => ApiError::internal(&error),
+        },
+    }
+}
@@ -0,0 +1,115 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::error::{ApiError, ApiResult};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 25;
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Keyset position of the last row on a page: the sort key plus the row id as a tiebreaker.
+///
+/// Clients treat the encoded form as opaque; it is only meaningful for the listing that issued it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "k")]
+    pub key: String,
+    #[serde(rename = "i")]
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn new(key: impl Into<String>, id: impl Into<String>) -> Self {
+        Self { key: key.into(), id: id.into() }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(raw: &str) -> ApiResult<Self> {
+        URL_SAFE_NO_PAD
+            .decode(raw.trim())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| ApiError::validation("cursor is malformed or expired"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageRequest {
+    pub limit: u32,
+    pub after: Option<Cursor>,
+}
+
+impl PageRequest {
+    pub fn parse(limit: Option<u32>, cursor: Option<&str>) -> ApiResult<Self> {
+        let limit = match limit {
+            None => DEFAULT_PAGE_SIZE,
+            Some(0) => return Err(ApiError::validation("limit must be greater than zero")),
+            Some(value) => value.min(MAX_PAGE_SIZE),
+        };
+        let after = match cursor.map(str::trim).filter(|value| !value.is_empty()) {
+            Some(raw) => Some(Cursor::decode(raw)?),
+            None => None,
+        };
+        Ok(Self { limit, after })
+    }
+
+    /// Rows to fetch: one extra row tells us whether another page exists.
+    pub fn fetch_limit(&self) -> i64 {
+        i64::from(self.limit) + 1
+    }
+
+    /// Trims the look-ahead row and derives the cursor for the following page.
+    pub fn finish<R>(
+        &self,
+        mut rows: Vec<R>,
+        cursor_of: impl Fn(&R) -> Cursor,
+    ) -> (Vec<R>, Option<String>) {
+        if rows.len() > self.limit as usize {
+            rows.truncate(self.limit as usize);
+            let next = rows.last().map(|row| cursor_of(row).encode());
+            (rows, next)
+        } else {
+            (rows, None)
+        }
+    }
+}
+
+/// One page of a cursor-paginated collection.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// Pass as `cursor` to fetch the next page; `null` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = Cursor::new("2026-01-01T00:00:00+00:00", "Q-1");
+        assert_eq!(Cursor::decode(&cursor.encode()).expect("decode"), cursor);
+        assert_eq!(Cursor::decode("not a cursor").expect_err("garbage").code, "VALIDATION_ERROR");
+    }
+
+    #[test]
+    fn page_request_clamps_limit_and_emits_next_cursor() {
+        let page = PageRequest::parse(Some(500), None).expect("parse");
+        assert_eq!(page.limit, MAX_PAGE_SIZE);
+        assert!(PageRequest::parse(Some(0), None).is_err());
+
+        let page = PageRequest::parse(Some(2), None).expect("parse");
+        let (rows, next) = page.finish(vec!["a", "b", "c"], |row| Cursor::new(*row, *row));
+        assert_eq!(rows, vec!["a", "b"]);
+        assert_eq!(Cursor::decode(&next.expect("next")).expect("decode").id, "b");
+
+        let (rows, next) = page.finish(vec!["a"], |row| Cursor::new(*row, *row));
+        assert_eq!(rows.len(), 1);
+        assert!(next.is_none());
+    }
+}
@@ -0,0 +1,1154 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use chrono::Utc;
+use quotey_core::cpq::boundary::BoundaryCalculator;
+use quotey_core::cpq::margin::{apply_category_margin_floors, MarginLine, QuoteMargin};
+use quotey_core::cpq::policy::{PolicyDecision, PolicyInput, PolicyThresholds};
+use quotey_core::cpq::policy_rules::{
+    evaluate_policy_with_rule_set, PolicyRuleContext, PolicyRuleLine, PolicyRuleSet,
+};
+use quotey_core::domain::product::ProductId;
+use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+use quotey_core::execution_engine::DeterministicExecutionEngine;
+use quotey_core::webhooks::WebhookEventType;
+use quotey_core::{
+    policy_evaluation_from_decision, pricing_snapshot_from_lines, PolicyEvaluation,
+    PricingLineSnapshot, PricingSnapshot,
+};
+use quotey_db::policy_apply::PolicyApplyService;
+use quotey_db::policy_rules::PolicyRuleSetService;
+use quotey_db::repositories::quote::{parse_quote_status, quote_status_as_str};
+use quotey_db::repositories::{
+    ProductRepository, QuoteRepository, SqlPricingSnapshotRepository, SqlProductCostRepository,
+    SqlProductRepository, SqlQuoteRepository,
+};
+use quotey_db::similarity::DealSimilarityService;
+use quotey_db::win_probability::WinProbabilityService;
+use quotey_db::DbPool;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::auth::ApiPrincipal;
+use super::comments::auto_comment;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::pagination::{Cursor, Page, PageRequest};
+use super::{ApiJson, ApiQuery, ApiState};
+
+pub const MAX_LINE_ITEMS: usize = 500;
+pub const MAX_QUANTITY: u32 = 1_000_000;
+const MAX_ID_LEN: usize = 64;
+
+const ALL_STATUSES: [QuoteStatus; 12] = [
+    QuoteStatus::Draft,
+    QuoteStatus::Validated,
+    QuoteStatus::Priced,
+    QuoteStatus::Approval,
+    QuoteStatus::Approved,
+    QuoteStatus::Rejected,
+    QuoteStatus::Finalized,
+    QuoteStatus::Sent,
+    QuoteStatus::Accepted,
+    QuoteStatus::Expired,
+    QuoteStatus::Cancelled,
+    QuoteStatus::Revised,
+];
+
+// ---------------------------------------------------------------------------
+// Request / response types
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListQuotesQuery {
+    pub account_id: Option<String>,
+    /// Lifecycle status, e.g. `draft` or `priced`.
+    pub status: Option<String>,
+    /// Page size (default 25, max 100).
+    pub limit: Option<u32>,
+    /// Opaque `next_cursor` from the previous page.
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LineInput {
+    pub product_id: String,
+    pub quantity: u32,
+    /// Line discount percentage, 0–100.
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateQuoteRequest {
+    pub account_id: String,
+    /// ISO currency code; every line's product must be priced in it.
+    pub currency: String,
+    #[serde(default)]
+    pub deal_id: Option<String>,
+    #[serde(default)]
+    pub term_months: Option<u32>,
+    #[serde(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+    #[serde(default)]
+    pub valid_until: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Sales rep id or external user ref recorded as the quote owner.
+    /// Required for team-restricted API keys so the quote lands in an allowed team.
+    #[serde(default)]
+    pub owner_rep_id: Option<String>,
+    pub lines: Vec<LineInput>,
+}
+
+/// Partial update of quote header fields; omitted fields are left unchanged.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateQuoteRequest {
+    #[serde(default)]
+    pub deal_id: Option<String>,
+    #[serde(default)]
+    pub term_months: Option<u32>,
+    #[serde(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+    #[serde(default)]
+    pub valid_until: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Partial update of one line; omitted fields are left unchanged.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateLineRequest {
+    #[serde(default)]
+    pub quantity: Option<u32>,
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TransitionRequest {
+    /// Target lifecycle status; see `allowed_transitions` on the quote.
+    pub status: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PriceQuoteRequest {
+    /// Deal-level discount to evaluate against policy; overrides line discounts when set.
+    #[serde(default)]
+    pub requested_discount_pct: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct QuoteLineResource {
+    /// Positional id (`<quote id>-ql-<n>`); ids after a removed line shift down by one.
+    pub line_id: String,
+    pub product_id: String,
+    pub quantity: u32,
+    /// Decimal string in the quote currency.
+    pub unit_price: String,
+    pub discount_pct: f64,
+    /// Line total after the line discount, as a decimal string.
+    pub line_total: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct QuoteResource {
+    pub id: String,
+    /// Incremented on every change; send it as `If-Match` to guard against lost updates.
+    pub version: u32,
+    pub status: String,
+    pub allowed_transitions: Vec<String>,
+    pub account_id: Option<String>,
+    pub deal_id: Option<String>,
+    pub currency: String,
+    pub term_months: Option<u32>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub valid_until: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: String,
+    pub lines: Vec<QuoteLineResource>,
+    /// Sum of line totals, as a decimal string.
+    pub subtotal: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Chance of winning from the active win-probability model; absent until one is promoted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub win_probability: Option<WinProbabilityResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WinProbabilityResource {
+    pub model_version: String,
+    /// 0.0–1.0
+    pub probability: f64,
+    pub predicted_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LinePricingResource {
+    pub line_id: String,
+    pub product_id: String,
+    pub quantity: u32,
+    pub unit_price: String,
+    pub discount_pct: f64,
+    pub subtotal: String,
+    pub discount_amount: String,
+    pub line_total: String,
+    /// Product cost of the line; `null` when the product has no cost in the quote currency.
+    pub cost: Option<String>,
+    pub margin_pct: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PolicyViolationResource {
+    pub policy_id: String,
+    pub reason: String,
+    pub required_approval: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PricingResource {
+    pub quote_id: String,
+    pub version: u32,
+    pub status: String,
+    pub currency: String,
+    pub subtotal: String,
+    pub discount_total: String,
+    pub total: String,
+    pub approval_required: bool,
+    pub lines: Vec<LinePricingResource>,
+    pub margin: MarginResource,
+    pub policy_violations: Vec<PolicyViolationResource>,
+    pub priced_at: String,
+}
+
+/// Internal margin view of a priced quote; customer-facing portal pages and PDFs never show it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MarginResource {
+    /// Total product cost of the costed lines.
+    pub cost_total: String,
+    pub margin_total: String,
+    /// Margin over the costed lines' revenue; `null` when no line has a cost record.
+    pub margin_pct: Option<f64>,
+    /// Lines left out of the margin because their product has no cost in the quote currency.
+    pub uncosted_line_ids: Vec<String>,
+}
+
+impl From<&QuoteMargin> for MarginResource {
+    fn from(margin: &QuoteMargin) -> Self {
+        Self {
+            cost_total: money(margin.cost),
+            margin_total: money(margin.margin),
+            margin_pct: margin.margin_pct.and_then(|pct| pct.to_f64()),
+            uncosted_line_ids: margin.uncosted_line_ids.clone(),
+        }
+    }
+}
+
+impl From<&Quote> for QuoteResource {
+    fn from(quote: &Quote) -> Self {
+        let lines: Vec<QuoteLineResource> = quote
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| QuoteLineResource {
+                line_id: line_id(&quote.id.0, index),
+                product_id: line.product_id.0.clone(),
+                quantity: line.quantity,
+                unit_price: line.unit_price.to_string(),
+                discount_pct: line.discount_pct,
+                line_total: money(discounted(line)),
+                notes: line.notes.clone(),
+            })
+            .collect();
+        let subtotal: Decimal = quote.lines.iter().map(discounted).sum();
+        Self {
+            id: quote.id.0.clone(),
+            version: quote.version,
+            status: quote_status_as_str(&quote.status).to_string(),
+            allowed_transitions: ALL_STATUSES
+                .iter()
+                .filter(|next| **next != quote.status && quote.can_transition_to((*next).clone()))
+                .map(|next| quote_status_as_str(next).to_string())
+                .collect(),
+            account_id: quote.account_id.clone(),
+            deal_id: quote.deal_id.clone(),
+            currency: quote.currency.clone(),
+            term_months: quote.term_months,
+            start_date: quote.start_date.clone(),
+            end_date: quote.end_date.clone(),
+            valid_until: quote.valid_until.clone(),
+            notes: quote.notes.clone(),
+            created_by: quote.created_by.clone(),
+            lines,
+            subtotal: money(subtotal),
+            created_at: quote.created_at.to_rfc3339(),
+            updated_at: quote.updated_at.to_rfc3339(),
+            win_probability: None,
+        }
+    }
+}
+
+fn line_id(quote_id: &str, index: usize) -> String {
+    format!("{quote_id}-ql-{}", index + 1)
+}
+
+fn discount_rate(discount_pct: f64) -> Decimal {
+    Decimal::from_f64(discount_pct).unwrap_or(Decimal::ZERO) / Decimal::from(100)
+}
+
+fn discounted(line: &QuoteLine) -> Decimal {
+    let subtotal = line.unit_price * Decimal::from(line.quantity);
+    subtotal - subtotal * discount_rate(line.discount_pct)
+}
+
+/// Formats an amount as a two-decimal string (`950.00`), the representation used on every resource.
+pub(super) fn money(amount: Decimal) -> String {
+    let mut amount = amount.round_dp(2);
+    amount.rescale(2);
+    amount.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+pub async fn list_quotes(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiQuery(query): ApiQuery<ListQuotesQuery>,
+) -> ApiResult<Json<Page<QuoteResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let account_id = optional_trimmed(query.account_id);
+    if account_id.is_some() {
+        principal.ensure_account(account_id.as_deref())?;
+    }
+    let status = match optional_trimmed(query.status) {
+        Some(raw) => Some(
+            parse_quote_status(&raw)
+                .map(|status| quote_status_as_str(&status))
+                .map_err(|_| ApiError::validation(format!("unknown quote status `{raw}`")))?,
+        ),
+        None => None,
+    };
+
+    let (visibility, visibility_binds) = principal.quote_visibility_filter();
+    let mut sql = String::from(
+        "SELECT q.id, q.created_at
+         FROM quote q
+         LEFT JOIN sales_rep sr ON sr.id = q.created_by_sales_rep_id
+         WHERE 1 = 1",
+    );
+    sql.push_str(&visibility);
+    if account_id.is_some() {
+        sql.push_str(" AND q.account_id = ?");
+    }
+    if status.is_some() {
+        sql.push_str(" AND q.status = ?");
+    }
+    if page.after.is_some() {
+        sql.push_str(" AND (q.created_at < ? OR (q.created_at = ? AND q.id < ?))");
+    }
+    sql.push_str(" ORDER BY q.created_at DESC, q.id DESC LIMIT ?");
+
+    let mut rows = sqlx::query_as::<_, (String, String)>(&sql);
+    for value in &visibility_binds {
+        rows = rows.bind(value);
+    }
+    if let Some(account_id) = &account_id {
+        rows = rows.bind(account_id);
+    }
+    if let Some(status) = status {
+        rows = rows.bind(status);
+    }
+    if let Some(after) = &page.after {
+        rows = rows.bind(&after.key).bind(&after.key).bind(&after.id);
+    }
+    let rows = rows.bind(page.fetch_limit()).fetch_all(&state.db_pool).await?;
+    let (rows, next_cursor) = page.finish(rows, |(id, created_at)| Cursor::new(created_at, id));
+
+    let repo = SqlQuoteRepository::new(state.db_pool.clone());
+    let mut data = Vec::with_capacity(rows.len());
+    for (id, _) in rows {
+        if let Some(quote) = repo.find_by_id(&QuoteId(id)).await? {
+            data.push(QuoteResource::from(&quote));
+        }
+    }
+    Ok(Json(Page { data, next_cursor }))
+}
+
+pub async fn create_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<CreateQuoteRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "body": &body });
+    let quote_id = match idempotency::idempotency_key(&headers) {
+        Ok(Some(key)) => {
+            let hash = DeterministicExecutionEngine::hash_payload(&format!(
+                "rest:{}:{key}",
+                principal.key_id
+            ));
+            format!("Q-{}", &hash[..16])
+        }
+        Ok(None) => format!("Q-{}", &uuid::Uuid::new_v4().simple().to_string()[..16]),
+        Err(error) => return axum::response::IntoResponse::into_response(error),
+    };
+
+    idempotency::run(&state, &principal, &headers, "quote.create", None, &payload, || async {
+        let quote = build_new_quote(&state, &principal, quote_id, body).await?;
+        SqlQuoteRepository::new(state.db_pool.clone()).save(quote.clone()).await?;
+        auto_comment(
+            &state.db_pool,
+            &quote.id.0,
+            "quote_created",
+            &format!(
+                "Quote created via REST API with {} line item(s) in {}.",
+                quote.lines.len(),
+                quote.currency
+            ),
+        )
+        .await;
+        Mutation::new(StatusCode::CREATED, quote.id.0.clone(), QuoteResource::from(&quote))
+    })
+    .await
+}
+
+pub async fn get_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<QuoteResource>> {
+    let quote = load_quote(&state, &principal, &id).await?;
+    let mut resource = QuoteResource::from(&quote);
+    resource.win_probability = predict_win_probability(&state.db_pool, &quote).await;
+    Ok(Json(resource))
+}
+
+/// Scores the quote with the active model. A failed prediction never fails the read.
+async fn predict_win_probability(pool: &DbPool, quote: &Quote) -> Option<WinProbabilityResource> {
+    match WinProbabilityService::new(pool.clone()).predict(quote).await {
+        Ok(prediction) => prediction.map(|prediction| WinProbabilityResource {
+            model_version: prediction.model_version,
+            probability: prediction.win_probability,
+            predicted_at: prediction.predicted_at,
+        }),
+        Err(error) => {
+            warn!(%error, quote_id = %quote.id.0, "win probability not predicted");
+            None
+        }
+    }
+}
+
+pub async fn update_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<UpdateQuoteRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(&state, &principal, &headers, "quote.update", Some(&id), &payload, || async {
+        let mut quote = load_quote(&state, &principal, &id).await?;
+        ensure_version(&headers, &quote)?;
+        ensure_editable(&quote)?;
+        if let Some(months) = body.term_months {
+            if months == 0 {
+                return Err(ApiError::validation("term_months must be greater than 0"));
+            }
+            quote.term_months = Some(months);
+        }
+        if let Some(deal_id) = body.deal_id {
+            quote.deal_id = optional_trimmed(Some(deal_id));
+        }
+        if body.start_date.is_some() {
+            quote.start_date = body.start_date;
+        }
+        if body.end_date.is_some() {
+            quote.end_date = body.end_date;
+        }
+        if body.valid_until.is_some() {
+            quote.valid_until = body.valid_until;
+        }
+        if body.notes.is_some() {
+            quote.notes = body.notes;
+        }
+        save_revision(&state, quote).await
+    })
+    .await
+}
+
+pub async fn cancel_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id });
+    idempotency::run(&state, &principal, &headers, "quote.cancel", Some(&id), &payload, || async {
+        let mut quote = load_quote(&state, &principal, &id).await?;
+        ensure_version(&headers, &quote)?;
+        quote.transition_to(QuoteStatus::Cancelled)?;
+        let mutation = save_revision(&state, quote).await?;
+        auto_comment(&state.db_pool, &id, "quote_cancelled", "Quote cancelled via REST API.").await;
+        Ok(mutation)
+    })
+    .await
+}
+
+pub async fn transition_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<TransitionRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.transition",
+        Some(&id),
+        &payload,
+        || async {
+            let target = parse_quote_status(&body.status).map_err(|_| {
+                ApiError::validation(format!("unknown quote status `{}`", body.status))
+            })?;
+            let mut quote = load_quote(&state, &principal, &id).await?;
+            ensure_version(&headers, &quote)?;
+            let from = quote_status_as_str(&quote.status);
+            if target == QuoteStatus::Validated && quote.lines.is_empty() {
+                return Err(ApiError::conflict("a quote needs at least one line to be validated"));
+            }
+            let reopened = matches!(
+                quote.status,
+                QuoteStatus::Finalized | QuoteStatus::Sent | QuoteStatus::Accepted
+            );
+            quote.transition_to(target.clone())?;
+            let mutation = save_revision(&state, quote.clone()).await?;
+            index_similar_deal(&state.db_pool, &quote, reopened).await;
+            auto_comment(
+                &state.db_pool,
+                &id,
+                "quote_transitioned",
+                &format!(
+                    "Quote moved from {from} to {} via REST API.",
+                    quote_status_as_str(&target)
+                ),
+            )
+            .await;
+            if target == QuoteStatus::Finalized {
+                crate::webhooks::emit(
+                    &state.db_pool,
+                    WebhookEventType::QuoteFinalized,
+                    &id,
+                    serde_json::json!({
+                        "previous_status": from,
+                        "version": quote.version + 1,
+                        "actor": principal.actor(),
+                        "source": "rest_api",
+                    }),
+                )
+                .await;
+            }
+            Ok(mutation)
+        },
+    )
+    .await
+}
+
+pub async fn add_line(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<LineInput>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.line.add",
+        Some(&id),
+        &payload,
+        || async {
+            let mut quote = load_quote(&state, &principal, &id).await?;
+            ensure_version(&headers, &quote)?;
+            ensure_editable(&quote)?;
+            if quote.lines.len() >= MAX_LINE_ITEMS {
+                return Err(ApiError::validation(format!(
+                    "Too many line items (max {MAX_LINE_ITEMS})"
+                )));
+            }
+            let line =
+                build_line(&state.db_pool, &quote.currency, &body, quote.lines.len()).await?;
+            quote.lines.push(line);
+            let mut mutation = save_revision(&state, quote).await?;
+            mutation.status = StatusCode::CREATED;
+            Ok(mutation)
+        },
+    )
+    .await
+}
+
+pub async fn update_line(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((id, line)): Path<(String, String)>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<UpdateLineRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "line_id": &line, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.line.update",
+        Some(&id),
+        &payload,
+        || async {
+            let mut quote = load_quote(&state, &principal, &id).await?;
+            ensure_version(&headers, &quote)?;
+            ensure_editable(&quote)?;
+            let index = line_index(&quote, &line)?;
+            let target = &mut quote.lines[index];
+            if let Some(quantity) = body.quantity {
+                target.quantity = validate_quantity(quantity, "quantity")?;
+            }
+            if let Some(discount_pct) = body.discount_pct {
+                target.discount_pct = validate_discount(discount_pct, "discount_pct")?;
+            }
+            if body.notes.is_some() {
+                target.notes = body.notes;
+            }
+            save_revision(&state, quote).await
+        },
+    )
+    .await
+}
+
+pub async fn remove_line(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((id, line)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "line_id": &line });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.line.remove",
+        Some(&id),
+        &payload,
+        || async {
+            let mut quote = load_quote(&state, &principal, &id).await?;
+            ensure_version(&headers, &quote)?;
+            ensure_editable(&quote)?;
+            let index = line_index(&quote, &line)?;
+            quote.lines.remove(index);
+            save_revision(&state, quote).await
+        },
+    )
+    .await
+}
+
+pub async fn price_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<PriceQuoteRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(&state, &principal, &headers, "quote.price", Some(&id), &payload, || async {
+        let requested = match body.requested_discount_pct {
+            Some(value) => Some(validate_discount(value, "requested_discount_pct")?),
+            None => None,
+        };
+        let mut quote = load_quote(&state, &principal, &id).await?;
+        if quote.lines.is_empty() {
+            return Err(ApiError::conflict("a quote needs at least one line to be priced"));
+        }
+        // Pricing a validated quote advances it to `priced`; other states are priced read-only.
+        if quote.status == QuoteStatus::Validated {
+            quote.transition_to(QuoteStatus::Priced)?;
+            quote.version += 1;
+            quote.updated_at = Utc::now();
+            SqlQuoteRepository::new(state.db_pool.clone()).save(quote.clone()).await?;
+        }
+
+        let quote_policy = load_quote_policy(&state.db_pool, &id).await;
+        let Priced { resource: pricing, snapshot, policy } =
+            price(&quote, requested, &quote_policy);
+        // Recorded so explanations can cite exactly this arithmetic; never blocks pricing.
+        if let Err(error) =
+            SqlPricingSnapshotRepository::with_priced_by(state.db_pool.clone(), "rest_api")
+                .with_policy_version(quote_policy.version)
+                .record_snapshot(&snapshot, Some(&policy))
+                .await
+        {
+            warn!(%error, quote_id = %id, "pricing snapshot not recorded");
+        }
+        auto_comment(
+            &state.db_pool,
+            &id,
+            "pricing_rendered",
+            &format!(
+                "Quote priced at {} {} via REST API ({} policy violation(s)).",
+                pricing.total,
+                pricing.currency,
+                pricing.policy_violations.len()
+            ),
+        )
+        .await;
+        Mutation::new(StatusCode::OK, id.clone(), pricing)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Loads a quote after checking the key may see it. Unknown ids are `NOT_FOUND`.
+pub async fn load_quote(state: &ApiState, principal: &ApiPrincipal, id: &str) -> ApiResult<Quote> {
+    let id = normalize_id(id, "quote id")?;
+    principal.ensure_quote_access(&state.db_pool, &id).await?;
+    SqlQuoteRepository::new(state.db_pool.clone())
+        .find_by_id(&QuoteId(id.clone()))
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Quote '{id}' not found")))
+}
+
+async fn build_new_quote(
+    state: &ApiState,
+    principal: &ApiPrincipal,
+    quote_id: String,
+    body: CreateQuoteRequest,
+) -> ApiResult<Quote> {
+    let account_id = normalize_id(&body.account_id, "account_id")?;
+    principal.ensure_account(Some(&account_id))?;
+    let currency = normalize_currency(&body.currency)?;
+    if body.lines.is_empty() {
+        return Err(ApiError::validation("At least one line item is required"));
+    }
+    if body.lines.len() > MAX_LINE_ITEMS {
+        return Err(ApiError::validation(format!("Too many line items (max {MAX_LINE_ITEMS})")));
+    }
+    if body.term_months == Some(0) {
+        return Err(ApiError::validation("term_months must be greater than 0"));
+    }
+
+    let created_by = match optional_trimmed(body.owner_rep_id) {
+        Some(owner) => {
+            let team_id: Option<Option<String>> = sqlx::query_scalar(
+                "SELECT team_id FROM sales_rep WHERE id = ? OR external_user_ref = ? LIMIT 1",
+            )
+            .bind(&owner)
+            .bind(&owner)
+            .fetch_optional(&state.db_pool)
+            .await?;
+            let team_id = team_id
+                .ok_or_else(|| ApiError::not_found(format!("Sales rep '{owner}' not found")))?;
+            principal.ensure_team(team_id.as_deref())?;
+            owner
+        }
+        None => {
+            principal.ensure_team(None)?;
+            principal.actor()
+        }
+    };
+
+    let mut lines = Vec::with_capacity(body.lines.len());
+    for (index, input) in body.lines.iter().enumerate() {
+        lines.push(build_line(&state.db_pool, &currency, input, index).await?);
+    }
+
+    let now = Utc::now();
+    Ok(Quote {
+        id: QuoteId(quote_id),
+        version: 1,
+        status: QuoteStatus::Draft,
+        account_id: Some(account_id),
+        deal_id: optional_trimmed(body.deal_id),
+        currency,
+        term_months: body.term_months,
+        start_date: body.start_date,
+        end_date: body.end_date,
+        valid_until: body.valid_until,
+        notes: body.notes,
+        created_by,
+        lines,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Resolves a catalog product into a priced line, enforcing the same rules as MCP `quote_create`.
+async fn build_line(
+    pool: &DbPool,
+    currency: &str,
+    input: &LineInput,
+    index: usize,
+) -> ApiResult<QuoteLine> {
+    let product_id = normalize_id(&input.product_id, "product_id")?;
+    let quantity = validate_quantity(input.quantity, &format!("lines[{index}].quantity"))?;
+    let discount_pct = validate_discount(
+        input.discount_pct.unwrap_or(0.0),
+        &format!("lines[{index}].discount_pct"),
+    )?;
+
+    let product = SqlProductRepository::new(pool.clone())
+        .find_by_id(&ProductId(product_id.clone()))
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Product '{product_id}' not found")))?;
+    if !product.active {
+        return Err(ApiError::conflict(format!("Product '{product_id}' is inactive")));
+    }
+    if !product.currency.eq_ignore_ascii_case(currency) {
+        return Err(ApiError::currency_mismatch(format!(
+            "Product '{product_id}' currency '{}' does not match quote currency '{currency}'",
+            product.currency
+        )));
+    }
+
+    Ok(QuoteLine {
+        product_id: ProductId(product_id),
+        quantity,
+        unit_price: product.base_price.unwrap_or(Decimal::ZERO),
+        discount_pct,
+        notes: input.notes.clone(),
+    })
+}
+
+async fn save_revision(state: &ApiState, mut quote: Quote) -> ApiResult<Mutation> {
+    quote.version += 1;
+    quote.updated_at = Utc::now();
+    SqlQuoteRepository::new(state.db_pool.clone()).save(quote.clone()).await?;
+    Mutation::new(StatusCode::OK, quote.id.0.clone(), QuoteResource::from(&quote))
+}
+
+/// Keeps the similar-deal index current as quotes close or reopen; failures only log.
+async fn index_similar_deal(pool: &DbPool, quote: &Quote, reopened: bool) {
+    let service = DealSimilarityService::new(pool.clone());
+    let result = match quote.status {
+        QuoteStatus::Finalized | QuoteStatus::Sent | QuoteStatus::Accepted => {
+            service.on_quote_closed(quote, None).await
+        }
+        QuoteStatus::Revised if reopened => service.on_quote_reopened_or_modified(quote).await,
+        _ => return,
+    };
+    if let Err(error) = result {
+        warn!(%error, quote_id = %quote.id.0, "similar-deal index not updated");
+    }
+}
+
+/// Honors `If-Match: <version>` for optimistic concurrency.
+fn ensure_version(headers: &HeaderMap, quote: &Quote) -> ApiResult<()> {
+    let Some(raw) = headers.get(axum::http::header::IF_MATCH) else {
+        return Ok(());
+    };
+    let expected = raw
+        .to_str()
+        .ok()
+        .map(|value| value.trim().trim_matches('"'))
+        .and_then(|value| value.parse::<u32>().ok())
+        .ok_or_else(|| ApiError::validation("If-Match must be a quote version number"))?;
+    if expected != quote.version {
+        return Err(ApiError::conflict(format!(
+            "quote version is {}, not {expected}",
+            quote.version
+        ))
+        .with_details(serde_json::json!({ "current_version": quote.version })));
+    }
+    Ok(())
+}
+
+fn ensure_editable(quote: &Quote) -> ApiResult<()> {
+    if matches!(quote.status, QuoteStatus::Draft | QuoteStatus::Revised) {
+        return Ok(());
+    }
+    Err(ApiError::conflict(format!(
+        "quote is `{}`; move it to `revised` before editing",
+        quote_status_as_str(&quote.status)
+    )))
+}
+
+fn line_index(quote: &Quote, line: &str) -> ApiResult<usize> {
+    line.trim()
+        .strip_prefix(&format!("{}-ql-", quote.id.0))
+        .and_then(|position| position.parse::<usize>().ok())
+        .filter(|position| (1..=quote.lines.len()).contains(position))
+        .map(|position| position - 1)
+        .ok_or_else(|| ApiError::not_found(format!("Line '{line}' not found on quote")))
+}
+
+/// Pricing output plus the snapshot and policy verdict persisted for explanations.
+struct Priced {
+    resource: PricingResource,
+    snapshot: PricingSnapshot,
+    policy: PolicyEvaluation,
+}
+
+fn price(quote: &Quote, requested: Option<f64>, quote_policy: &QuotePolicy) -> Priced {
+    let mut lines = Vec::with_capacity(quote.lines.len());
+    let mut snapshot_lines = Vec::with_capacity(quote.lines.len());
+    let mut margin_lines = Vec::with_capacity(quote.lines.len());
+    let mut subtotal = Decimal::ZERO;
+    let mut discount_total = Decimal::ZERO;
+    for (index, line) in quote.lines.iter().enumerate() {
+        let discount_pct = requested.unwrap_or(line.discount_pct);
+        let line_subtotal = line.unit_price * Decimal::from(line.quantity);
+        let discount_amount = (line_subtotal * discount_rate(discount_pct)).round_dp(2);
+        subtotal += line_subtotal;
+        discount_total += discount_amount;
+        snapshot_lines.push(PricingLineSnapshot {
+            line_id: line_id(&quote.id.0, index),
+            product_id: line.product_id.0.clone(),
+            product_name: line.product_id.0.clone(),
+            quantity: i32::try_from(line.quantity).unwrap_or(i32::MAX),
+            unit_price: line.unit_price,
+            discount_percent: Decimal::from_f64(discount_pct).unwrap_or(Decimal::ZERO),
+            discount_amount,
+            line_subtotal: line_subtotal - discount_amount,
+        });
+        margin_lines.push(MarginLine {
+            line_id: line_id(&quote.id.0, index),
+            product_id: line.product_id.0.clone(),
+            quantity: line.quantity,
+            net_amount: line_subtotal - discount_amount,
+        });
+        lines.push(LinePricingResource {
+            line_id: line_id(&quote.id.0, index),
+            product_id: line.product_id.0.clone(),
+            quantity: line.quantity,
+            unit_price: line.unit_price.to_string(),
+            discount_pct,
+            subtotal: money(line_subtotal),
+            discount_amount: discount_amount.to_string(),
+            line_total: money(line_subtotal - discount_amount),
+            cost: None,
+            margin_pct: None,
+        });
+    }
+    let total = subtotal - discount_total;
+    let margin = quote_policy.margin(&margin_lines);
+    for (resource, line) in lines.iter_mut().zip(&margin.lines) {
+        resource.cost = line.cost.map(money);
+        resource.margin_pct = line.margin_pct.and_then(|pct| pct.to_f64());
+    }
+
+    let effective_discount_pct = if subtotal > Decimal::ZERO {
+        discount_total * Decimal::from(100) / subtotal
+    } else {
+        Decimal::ZERO
+    };
+    let policy_input = PolicyInput {
+        requested_discount_pct: effective_discount_pct.round_dp(4),
+        deal_value: subtotal,
+        minimum_margin_pct: margin.margin_pct,
+    };
+    let rule_lines: Vec<PolicyRuleLine> = snapshot_lines
+        .iter()
+        .map(|line| PolicyRuleLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            discount_pct: line.discount_percent,
+        })
+        .collect();
+    let decision = quote_policy.evaluate(&policy_input, &rule_lines, &margin);
+    let priced_at = Utc::now().to_rfc3339();
+    let version = i32::try_from(quote.version).unwrap_or(i32::MAX);
+    let snapshot = pricing_snapshot_from_lines(
+        &quote.id,
+        version,
+        &quote.currency,
+        snapshot_lines,
+        Decimal::ZERO,
+        priced_at.clone(),
+    );
+    let policy = policy_evaluation_from_decision(
+        &quote.id,
+        version,
+        &policy_input,
+        &quote_policy.thresholds,
+        &decision,
+        priced_at.clone(),
+    );
+
+    let resource = PricingResource {
+        quote_id: quote.id.0.clone(),
+        version: quote.version,
+        status: quote_status_as_str(&quote.status).to_string(),
+        currency: quote.currency.clone(),
+        subtotal: money(subtotal),
+        discount_total: money(discount_total),
+        total: money(total),
+        approval_required: decision.approval_required,
+        lines,
+        margin: MarginResource::from(&margin),
+        policy_violations: decision
+            .violations
+            .into_iter()
+            .map(|violation| PolicyViolationResource {
+                policy_id: violation.policy_id,
+                reason: violation.reason,
+                required_approval: violation.required_approval,
+            })
+            .collect(),
+        priced_at,
+    };
+    Priced { resource, snapshot, policy }
+}
+
+/// Thresholds of the active policy version, falling back to the engine defaults.
+pub(crate) async fn load_policy_thresholds(pool: &DbPool) -> PolicyThresholds {
+    match PolicyApplyService::new(pool.clone()).active_version().await {
+        Ok(version) => version.thresholds,
+        Err(error) => {
+            warn!(%error, "active policy version unavailable; using default thresholds");
+            PolicyThresholds::default()
+        }
+    }
+}
+
+/// Everything a stored quote's policy verdict depends on.
+pub(crate) struct QuotePolicy {
+    pub(crate) thresholds: PolicyThresholds,
+    /// Policy set version the thresholds belong to; `None` when it could not be resolved.
+    pub(crate) version: Option<i32>,
+    /// Scoped rules of the newest published rule set.
+    pub(crate) rules: PolicyRuleSet,
+    pub(crate) context: PolicyRuleContext,
+    /// Unit costs of the quote's products in the quote currency, keyed by product id.
+    pub(crate) unit_costs: BTreeMap<String, Decimal>,
+}
+
+impl QuotePolicy {
+    pub(crate) fn margin(&self, lines: &[MarginLine]) -> QuoteMargin {
+        QuoteMargin::compute(lines, &self.unit_costs)
+    }
+
+    /// Thresholds, scoped rules, and the margin floors of each line's product family.
+    pub(crate) fn evaluate(
+        &self,
+        input: &PolicyInput,
+        lines: &[PolicyRuleLine],
+        margin: &QuoteMargin,
+    ) -> PolicyDecision {
+        let mut decision = evaluate_policy_with_rule_set(
+            input,
+            &self.thresholds,
+            &self.rules,
+            &self.context,
+            lines,
+        );
+        apply_category_margin_floors(
+            &mut decision,
+            &BoundaryCalculator::default(),
+            margin,
+            &self.context.product_families,
+        );
+        decision
+    }
+}
+
+/// Resolves the policy a stored quote is priced under. Quotes whose rep or segment is enrolled in
+/// a monitoring canary get the canary's thresholds; scoped rules come from the newest rule set.
+pub(crate) async fn load_quote_policy(pool: &DbPool, quote_id: &str) -> QuotePolicy {
+    let (thresholds, version) =
+        match PolicyApplyService::new(pool.clone()).resolve_for_quote(quote_id).await {
+            Ok(resolved) => (resolved.thresholds, Some(resolved.version)),
+            Err(error) => {
+                warn!(%error, %quote_id, "quote policy unavailable; using default thresholds");
+                (PolicyThresholds::default(), None)
+            }
+        };
+    let as_of = Utc::now().date_naive();
+    let rule_sets = PolicyRuleSetService::new(pool.clone());
+    let rules = match rule_sets.latest().await {
+        Ok(latest) => latest.map(|version| version.rules).unwrap_or_default(),
+        Err(error) => {
+            warn!(%error, %quote_id, "policy rule set unavailable; evaluating thresholds only");
+            PolicyRuleSet::default()
+        }
+    };
+    let context = match rule_sets.context_for_quote(quote_id, as_of).await {
+        Ok(context) => context,
+        Err(error) => {
+            warn!(%error, %quote_id, "policy rule context unavailable");
+            PolicyRuleContext::new(as_of)
+        }
+    };
+    let unit_costs = match SqlProductCostRepository::new(pool.clone())
+        .unit_costs_for_quote(quote_id, as_of)
+        .await
+    {
+        Ok(unit_costs) => unit_costs,
+        Err(error) => {
+            warn!(%error, %quote_id, "product costs unavailable; margin is not evaluated");
+            BTreeMap::new()
+        }
+    };
+    QuotePolicy { thresholds, version, rules, context, unit_costs }
+}
+
+pub fn normalize_id(value: &str, field: &str) -> ApiResult<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(ApiError::validation(format!("{field} is required")));
+    }
+    if trimmed.len() > MAX_ID_LEN {
+        return Err(ApiError::validation(format!("{field} exceeds maximum length")));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn normalize_currency(value: &str) -> ApiResult<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.len() > 8 || !trimmed.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return Err(ApiError::validation("currency must be alphabetic and <= 8 chars"));
+    }
+    Ok(trimmed.to_ascii_uppercase())
+}
+
+fn validate_quantity(quantity: u32, field: &str) -> ApiResult<u32> {
+    if quantity == 0 || quantity > MAX_QUANTITY {
+        return Err(ApiError::validation(format!("{field} must be between 1 and {MAX_QUANTITY}")));
+    }
+    Ok(quantity)
+}
+
+fn validate_discount(value: f64, field: &str) -> ApiResult<f64> {
+    if !value.is_finite() || !(0.0..=100.0).contains(&value) {
+        return Err(ApiError::validation(format!("{field} must be between 0 and 100")));
+    }
+    Ok(value)
+}
+
+pub fn optional_trimmed(value: Option<String>) -> Option<String> {
+    value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
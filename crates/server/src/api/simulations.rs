@@ -0,0 +1,339 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use quotey_core::cpq::simulator::{LineVariation, ScenarioVariation};
+use quotey_core::domain::product::ProductId;
+use quotey_db::simulate::{
+    PromoteQuery, ScenarioOutcome, SimulateQuery, SimulatedVariant, SimulationError, SimulationRun,
+    SimulationService, MAX_SIMULATION_VARIANTS,
+};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::auth::ApiPrincipal;
+use super::comments::auto_comment;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::quotes::{load_policy_thresholds, load_quote, money, QuoteResource};
+use super::{ApiJson, ApiState};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioLineChange {
+    pub product_id: String,
+    /// Units to add (positive) or remove (negative).
+    #[serde(default)]
+    pub quantity_delta: i32,
+    /// Unit price override; required when the product is not on the quote yet.
+    #[serde(default)]
+    pub unit_price: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioInput {
+    /// Unique key for the scenario, e.g. `more-seats`.
+    pub key: String,
+    #[serde(default)]
+    pub line_changes: Vec<ScenarioLineChange>,
+    /// Discount to evaluate policy against instead of the baseline.
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+    /// Margin to evaluate policy against instead of the baseline.
+    #[serde(default)]
+    pub margin_pct: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SimulateQuoteRequest {
+    /// One to three scenarios compared against the current quote.
+    pub scenarios: Vec<ScenarioInput>,
+    /// Baseline discount; defaults to the quote's line discounts.
+    #[serde(default)]
+    pub discount_pct: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PromoteScenarioRequest {
+    /// Scenario key or variant id from the simulation run.
+    pub variant: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioOutcomeResource {
+    pub subtotal: String,
+    pub discount_total: String,
+    pub total: String,
+    pub approval_required: bool,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioLineResource {
+    pub product_id: String,
+    pub quantity: u32,
+    pub unit_price: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioVariantResource {
+    pub id: String,
+    pub key: String,
+    /// 1 is the best-ranked scenario.
+    pub rank: i32,
+    pub selected_for_promotion: bool,
+    pub outcome: ScenarioOutcomeResource,
+    /// Price, policy, approval and configuration deltas against the baseline.
+    pub delta: serde_json::Value,
+    pub lines: Vec<ScenarioLineResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SimulationResource {
+    pub id: String,
+    pub quote_id: String,
+    /// Quote version the scenarios were forked from; promotion fails once the quote moves on.
+    pub base_quote_version: i32,
+    pub status: String,
+    pub baseline: ScenarioOutcomeResource,
+    pub scenarios: Vec<ScenarioVariantResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PromotionResource {
+    pub simulation_id: String,
+    pub variant_id: String,
+    pub variant_key: String,
+    pub previous_version: u32,
+    /// Audit event linking the new revision to the simulation run.
+    pub audit_event_id: String,
+    pub quote: QuoteResource,
+}
+
+impl From<&ScenarioOutcome> for ScenarioOutcomeResource {
+    fn from(outcome: &ScenarioOutcome) -> Self {
+        Self {
+            subtotal: money(outcome.subtotal),
+            discount_total: money(outcome.discount_total),
+            total: money(outcome.total),
+            approval_required: outcome.approval_required,
+            reasons: outcome.reasons.clone(),
+        }
+    }
+}
+
+impl From<&SimulatedVariant> for ScenarioVariantResource {
+    fn from(variant: &SimulatedVariant) -> Self {
+        Self {
+            id: variant.variant_id.clone(),
+            key: variant.variant_key.clone(),
+            rank: variant.rank_order,
+            selected_for_promotion: variant.selected_for_promotion,
+            outcome: ScenarioOutcomeResource::from(&variant.outcome),
+            delta: serde_json::to_value(&variant.delta).unwrap_or_default(),
+            lines: variant
+                .lines
+                .iter()
+                .map(|line| ScenarioLineResource {
+                    product_id: line.product_id.0.clone(),
+                    quantity: line.quantity,
+                    unit_price: money(line.unit_price),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&SimulationRun> for SimulationResource {
+    fn from(run: &SimulationRun) -> Self {
+        Self {
+            id: run.run_id.clone(),
+            quote_id: run.quote_id.clone(),
+            base_quote_version: run.base_quote_version,
+            status: run.status.to_string(),
+            baseline: ScenarioOutcomeResource::from(&run.baseline),
+            scenarios: run.variants.iter().map(ScenarioVariantResource::from).collect(),
+        }
+    }
+}
+
+impl From<SimulationError> for ApiError {
+    fn from(error: SimulationError) -> Self {
+        match error {
+            SimulationError::QuoteNotFound(_)
+            | SimulationError::RunNotFound(_)
+            | SimulationError::VariantNotFound { .. } => Self::not_found(error.to_string()),
+            SimulationError::Guardrail { run_id: Some(ref run_id), .. } => {
+                let details = serde_json::json!({ "simulation_id": run_id });
+                Self::validation(error.to_string()).with_details(details)
+            }
+            SimulationError::Guardrail { run_id: None, .. } => Self::validation(error.to_string()),
+            SimulationError::NotPromotable { .. }
+            | SimulationError::StaleRun { .. }
+            | SimulationError::QuoteClosed(_) => Self::conflict(error.to_string()),
+            SimulationError::Failed(_) | SimulationError::Repository(_) => Self::internal(&error),
+        }
+    }
+}
+
+pub async fn simulate_quote(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<SimulateQuoteRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.simulate",
+        Some(&id),
+        &payload,
+        || async {
+            if body.scenarios.len() > MAX_SIMULATION_VARIANTS {
+                return Err(ApiError::validation(format!(
+                    "at most {MAX_SIMULATION_VARIANTS} scenarios can be simulated at once"
+                )));
+            }
+            let variations = body.scenarios.iter().map(variation).collect::<ApiResult<Vec<_>>>()?;
+            let requested_discount_pct = body.discount_pct.map(percentage).transpose()?;
+            let quote = load_quote(&state, &principal, &id).await?;
+
+            let thresholds = load_policy_thresholds(&state.db_pool).await;
+            let run = SimulationService::new(state.db_pool.clone(), thresholds)
+                .simulate(SimulateQuery {
+                    quote_id: quote.id.clone(),
+                    variations,
+                    requested_discount_pct,
+                    actor_type: "system".to_string(),
+                    actor_id: principal.actor(),
+                    thread_id: principal.actor(),
+                    correlation_id: format!("api-simulate-{}", uuid::Uuid::new_v4()),
+                })
+                .await?;
+            Mutation::new(StatusCode::CREATED, quote.id.0, SimulationResource::from(&run))
+        },
+    )
+    .await
+}
+
+pub async fn get_simulation(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((id, run_id)): Path<(String, String)>,
+) -> ApiResult<Json<SimulationResource>> {
+    let quote = load_quote(&state, &principal, &id).await?;
+    let thresholds = load_policy_thresholds(&state.db_pool).await;
+    let run = SimulationService::new(state.db_pool.clone(), thresholds).load(run_id.trim()).await?;
+    if run.quote_id != quote.id.0 {
+        return Err(ApiError::not_found(format!("Simulation '{run_id}' not found on quote")));
+    }
+    Ok(Json(SimulationResource::from(&run)))
+}
+
+pub async fn promote_simulation(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((id, run_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<PromoteScenarioRequest>,
+) -> Response {
+    let payload = serde_json::json!({ "quote_id": &id, "simulation_id": &run_id, "body": &body });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "quote.simulation.promote",
+        Some(&id),
+        &payload,
+        || async {
+            let variant = body.variant.trim();
+            if variant.is_empty() {
+                return Err(ApiError::validation("variant is required"));
+            }
+            let quote = load_quote(&state, &principal, &id).await?;
+
+            let thresholds = load_policy_thresholds(&state.db_pool).await;
+            let promotion = SimulationService::new(state.db_pool.clone(), thresholds)
+                .promote(PromoteQuery {
+                    quote_id: quote.id.clone(),
+                    run_id: run_id.trim().to_string(),
+                    variant: variant.to_string(),
+                    actor_type: "system".to_string(),
+                    actor_id: principal.actor(),
+                    correlation_id: format!("api-simulate-promote-{}", uuid::Uuid::new_v4()),
+                })
+                .await?;
+            auto_comment(
+                &state.db_pool,
+                &quote.id.0,
+                "simulation_promoted",
+                &format!(
+                    "Scenario '{}' promoted to quote version {} via REST API.",
+                    promotion.variant_key, promotion.version
+                ),
+            )
+            .await;
+
+            let revised = load_quote(&state, &principal, &id).await?;
+            Mutation::new(
+                StatusCode::OK,
+                quote.id.0,
+                PromotionResource {
+                    simulation_id: promotion.run_id,
+                    variant_id: promotion.variant_id,
+                    variant_key: promotion.variant_key,
+                    previous_version: promotion.previous_version,
+                    audit_event_id: promotion.audit_event_id,
+                    quote: QuoteResource::from(&revised),
+                },
+            )
+        },
+    )
+    .await
+}
+
+fn variation(input: &ScenarioInput) -> ApiResult<ScenarioVariation> {
+    let line_variations = input
+        .line_changes
+        .iter()
+        .map(|change| {
+            let unit_price_override = match change.unit_price {
+                Some(price) if !price.is_finite() || price < 0.0 => {
+                    return Err(ApiError::validation(format!(
+                        "unit_price for '{}' must be a non-negative number",
+                        change.product_id
+                    )))
+                }
+                Some(price) => Decimal::from_f64(price).map(|price| price.round_dp(2)),
+                None => None,
+            };
+            Ok(LineVariation {
+                product_id: ProductId(change.product_id.trim().to_string()),
+                quantity_delta: change.quantity_delta,
+                unit_price_override,
+            })
+        })
+        .collect::<ApiResult<Vec<_>>>()?;
+    Ok(ScenarioVariation {
+        variant_key: input.key.trim().to_string(),
+        line_variations,
+        requested_discount_pct_override: input.discount_pct.map(percentage).transpose()?,
+        minimum_margin_pct_override: input.margin_pct.map(percentage).transpose()?,
+        deal_value_override: None,
+    })
+}
+
+fn percentage(value: f64) -> ApiResult<Decimal> {
+    (0.0..=100.0)
+        .contains(&value)
+        .then(|| Decimal::from_f64(value))
+        .flatten()
+        .map(|pct| pct.round_dp(4))
+        .ok_or_else(|| ApiError::validation("percentages must be between 0 and 100"))
+}
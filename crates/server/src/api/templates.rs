@@ -0,0 +1,549 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use base64::Engine as _;
+use quotey_core::esign::document_sha256;
+use quotey_core::pdf::template::{
+    DocumentSection, QuoteDocumentTemplate, SectionCondition, SectionContent, TemplateSection,
+};
+use quotey_core::pdf::PdfTemplate;
+use quotey_db::quote_templates::{
+    ClauseBlockVersion, QuoteTemplateError, QuoteTemplateService, QuoteTemplateVersion, SaveClause,
+    SaveTemplateDraft, TemplateDefault, TemplateScope, TemplateSelection,
+};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult};
+use super::quotes::{load_quote, optional_trimmed};
+use super::{ApiJson, ApiState};
+use crate::pdf::PdfGenerator;
+use crate::portal::{quote_pdf_payload, BrandingConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutResource {
+    Detailed,
+    ExecutiveSummary,
+    Compact,
+}
+
+impl From<PdfTemplate> for LayoutResource {
+    fn from(layout: PdfTemplate) -> Self {
+        match layout {
+            PdfTemplate::Detailed => Self::Detailed,
+            PdfTemplate::ExecutiveSummary => Self::ExecutiveSummary,
+            PdfTemplate::Compact => Self::Compact,
+        }
+    }
+}
+
+impl From<LayoutResource> for PdfTemplate {
+    fn from(layout: LayoutResource) -> Self {
+        match layout {
+            LayoutResource::Detailed => Self::Detailed,
+            LayoutResource::ExecutiveSummary => Self::ExecutiveSummary,
+            LayoutResource::Compact => Self::Compact,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKindResource {
+    Assumptions,
+    Notes,
+    Terms,
+    /// Order form billing schedule; empty on quotes.
+    BillingSchedule,
+    /// Order form signature blocks; empty on quotes.
+    Signatures,
+    Clause,
+}
+
+/// When a section is rendered. Every non-empty criterion must match; lists match any entry.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SectionConditionResource {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    /// Minimum quote total as a decimal string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_total: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_term_months: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateSectionResource {
+    pub kind: SectionKindResource,
+    /// Clause block key; required for `clause` sections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Pins a clause version; the newest is used when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<SectionConditionResource>,
+}
+
+/// Base layout plus the ordered sections rendered after the pricing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateDefinitionResource {
+    pub layout: LayoutResource,
+    #[serde(default)]
+    pub sections: Vec<TemplateSectionResource>,
+}
+
+impl From<&QuoteDocumentTemplate> for TemplateDefinitionResource {
+    fn from(definition: &QuoteDocumentTemplate) -> Self {
+        let sections = definition
+            .sections
+            .iter()
+            .map(|section| {
+                let (kind, key, version) = match &section.content {
+                    SectionContent::Assumptions => (SectionKindResource::Assumptions, None, None),
+                    SectionContent::Notes => (SectionKindResource::Notes, None, None),
+                    SectionContent::Terms => (SectionKindResource::Terms, None, None),
+                    SectionContent::BillingSchedule => {
+                        (SectionKindResource::BillingSchedule, None, None)
+                    }
+                    SectionContent::Signatures => (SectionKindResource::Signatures, None, None),
+                    SectionContent::Clause { key, version } => {
+                        (SectionKindResource::Clause, Some(key.clone()), *version)
+                    }
+                };
+                let when = &section.when;
+                TemplateSectionResource {
+                    kind,
+                    key,
+                    version,
+                    when: (!when.is_always()).then(|| SectionConditionResource {
+                        segments: when.segments.clone(),
+                        regions: when.regions.clone(),
+                        min_total: when.min_total.map(|total| total.to_string()),
+                        min_term_months: when.min_term_months,
+                    }),
+                }
+            })
+            .collect();
+        Self { layout: definition.layout.into(), sections }
+    }
+}
+
+impl TryFrom<TemplateDefinitionResource> for QuoteDocumentTemplate {
+    type Error = ApiError;
+
+    fn try_from(resource: TemplateDefinitionResource) -> ApiResult<Self> {
+        let mut sections = Vec::with_capacity(resource.sections.len());
+        for (index, section) in resource.sections.into_iter().enumerate() {
+            let content = match (section.kind, optional_trimmed(section.key)) {
+                (SectionKindResource::Clause, Some(key)) => {
+                    SectionContent::Clause { key, version: section.version }
+                }
+                (SectionKindResource::Clause, None) => {
+                    return Err(ApiError::validation(format!(
+                        "sections[{index}].key is required for clause sections"
+                    )));
+                }
+                (_, Some(_)) => {
+                    return Err(ApiError::validation(format!(
+                        "sections[{index}].key is only allowed on clause sections"
+                    )));
+                }
+                (SectionKindResource::Assumptions, None) => SectionContent::Assumptions,
+                (SectionKindResource::Notes, None) => SectionContent::Notes,
+                (SectionKindResource::Terms, None) => SectionContent::Terms,
+                (SectionKindResource::BillingSchedule, None) => SectionContent::BillingSchedule,
+                (SectionKindResource::Signatures, None) => SectionContent::Signatures,
+            };
+            let when = section.when.unwrap_or_default();
+            let min_total = when
+                .min_total
+                .as_deref()
+                .map(|total| {
+                    Decimal::from_str(total.trim()).map_err(|_| {
+                        ApiError::validation(format!(
+                            "sections[{index}].when.min_total must be a decimal string"
+                        ))
+                    })
+                })
+                .transpose()?;
+            sections.push(TemplateSection {
+                content,
+                when: SectionCondition {
+                    segments: when.segments,
+                    regions: when.regions,
+                    min_total,
+                    min_term_months: when.min_term_months,
+                },
+            });
+        }
+        Ok(Self { layout: resource.layout.into(), sections })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateVersionResource {
+    pub key: String,
+    pub version: i32,
+    pub label: String,
+    /// `draft`, `published` or `retired`.
+    pub status: String,
+    pub definition: TemplateDefinitionResource,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub published_by: Option<String>,
+    pub published_at: Option<String>,
+}
+
+impl From<&QuoteTemplateVersion> for TemplateVersionResource {
+    fn from(version: &QuoteTemplateVersion) -> Self {
+        Self {
+            key: version.key.clone(),
+            version: version.version,
+            label: version.label.clone(),
+            status: version.status.as_str().to_string(),
+            definition: TemplateDefinitionResource::from(&version.definition),
+            created_by: version.created_by.clone(),
+            created_at: version.created_at.clone(),
+            updated_at: version.updated_at.clone(),
+            published_by: version.published_by.clone(),
+            published_at: version.published_at.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateDefaultResource {
+    /// `global`, `segment` or `account`.
+    pub scope: String,
+    /// Segment name or account id; `null` for the global default.
+    pub value: Option<String>,
+    pub template_key: String,
+    pub updated_by: String,
+    pub updated_at: String,
+}
+
+impl From<&TemplateDefault> for TemplateDefaultResource {
+    fn from(default: &TemplateDefault) -> Self {
+        let (scope, value) = match &default.scope {
+            TemplateScope::Global => ("global", None),
+            TemplateScope::Segment(segment) => ("segment", Some(segment.clone())),
+            TemplateScope::Account(account) => ("account", Some(account.clone())),
+        };
+        Self {
+            scope: scope.to_string(),
+            value,
+            template_key: default.template_key.clone(),
+            updated_by: default.updated_by.clone(),
+            updated_at: default.updated_at.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateRegistryResource {
+    /// Always-available layouts, usable as template keys without publishing anything.
+    pub builtin_layouts: Vec<LayoutResource>,
+    /// Newest version of every registered template.
+    pub templates: Vec<TemplateVersionResource>,
+    pub defaults: Vec<TemplateDefaultResource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateVersionListResource {
+    pub key: String,
+    /// Newest version first.
+    pub data: Vec<TemplateVersionResource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SaveTemplateDraftRequest {
+    pub label: String,
+    pub definition: TemplateDefinitionResource,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SaveClauseRequest {
+    pub key: String,
+    pub title: String,
+    /// Plain text; blank lines separate paragraphs.
+    pub body: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClauseBlockResource {
+    pub key: String,
+    pub version: i32,
+    pub title: String,
+    pub body: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+impl From<&ClauseBlockVersion> for ClauseBlockResource {
+    fn from(version: &ClauseBlockVersion) -> Self {
+        Self {
+            key: version.clause.key.clone(),
+            version: version.clause.version,
+            title: version.clause.title.clone(),
+            body: version.clause.body.clone(),
+            created_by: version.created_by.clone(),
+            created_at: version.created_at.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClauseBlockListResource {
+    /// Newest version of every clause block.
+    pub data: Vec<ClauseBlockResource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetTemplateDefaultRequest {
+    /// `global`, `segment` or `account`.
+    pub scope: String,
+    /// Segment name or account id; omitted for `global`.
+    #[serde(default)]
+    pub value: Option<String>,
+    /// A published template key or a built-in layout name.
+    pub template_key: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PreviewDocumentRequest {
+    /// Template to render with; the quote's default template when absent.
+    #[serde(default)]
+    pub template_key: Option<String>,
+    /// Any version, including drafts; the published version when absent.
+    #[serde(default)]
+    pub version: Option<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PreviewSectionResource {
+    pub kind: SectionKindResource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl From<&DocumentSection> for PreviewSectionResource {
+    fn from(section: &DocumentSection) -> Self {
+        let kind = match section {
+            DocumentSection::Assumptions => SectionKindResource::Assumptions,
+            DocumentSection::Notes => SectionKindResource::Notes,
+            DocumentSection::Terms => SectionKindResource::Terms,
+            DocumentSection::BillingSchedule => SectionKindResource::BillingSchedule,
+            DocumentSection::Signatures => SectionKindResource::Signatures,
+            DocumentSection::Clause { key, version, title, .. } => {
+                return Self {
+                    kind: SectionKindResource::Clause,
+                    key: Some(key.clone()),
+                    version: Some(*version),
+                    title: Some(title.clone()),
+                }
+            }
+        };
+        Self { kind, key: None, version: None, title: None }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentPreviewResource {
+    pub quote_id: String,
+    pub template_key: String,
+    /// `null` for built-in layouts.
+    pub template_version: Option<i32>,
+    pub layout: LayoutResource,
+    /// Sections whose conditions matched this quote, in render order.
+    pub sections: Vec<PreviewSectionResource>,
+    pub content_type: String,
+    pub sha256: String,
+    pub pdf_base64: String,
+}
+
+pub async fn get_registry(
+    State(state): State<ApiState>,
+) -> ApiResult<Json<TemplateRegistryResource>> {
+    let service = QuoteTemplateService::new(state.db_pool.clone());
+    let templates = service.list_templates().await.map_err(template_error)?;
+    let defaults = service.list_defaults().await.map_err(template_error)?;
+    Ok(Json(TemplateRegistryResource {
+        builtin_layouts: PdfTemplate::ALL.into_iter().map(LayoutResource::from).collect(),
+        templates: templates.iter().map(TemplateVersionResource::from).collect(),
+        defaults: defaults.iter().map(TemplateDefaultResource::from).collect(),
+    }))
+}
+
+pub async fn list_versions(
+    State(state): State<ApiState>,
+    Path(key): Path<String>,
+) -> ApiResult<Json<TemplateVersionListResource>> {
+    let versions = QuoteTemplateService::new(state.db_pool.clone())
+        .list_versions(&key)
+        .await
+        .map_err(template_error)?;
+    if versions.is_empty() {
+        return Err(ApiError::not_found(format!("Quote template '{}' not found", key.trim())));
+    }
+    Ok(Json(TemplateVersionListResource {
+        key: key.trim().to_string(),
+        data: versions.iter().map(TemplateVersionResource::from).collect(),
+    }))
+}
+
+pub async fn save_draft(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(key): Path<String>,
+    ApiJson(body): ApiJson<SaveTemplateDraftRequest>,
+) -> ApiResult<Json<TemplateVersionResource>> {
+    let version = QuoteTemplateService::new(state.db_pool.clone())
+        .save_draft(SaveTemplateDraft {
+            key,
+            label: body.label,
+            definition: body.definition.try_into()?,
+            actor: principal.actor(),
+        })
+        .await
+        .map_err(template_error)?;
+    Ok(Json(TemplateVersionResource::from(&version)))
+}
+
+pub async fn publish_version(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((key, version)): Path<(String, i32)>,
+) -> ApiResult<Json<TemplateVersionResource>> {
+    let version = QuoteTemplateService::new(state.db_pool.clone())
+        .publish(&key, version, &principal.actor())
+        .await
+        .map_err(template_error)?;
+    Ok(Json(TemplateVersionResource::from(&version)))
+}
+
+pub async fn list_clauses(
+    State(state): State<ApiState>,
+) -> ApiResult<Json<ClauseBlockListResource>> {
+    let clauses = QuoteTemplateService::new(state.db_pool.clone())
+        .list_clauses()
+        .await
+        .map_err(template_error)?;
+    Ok(Json(ClauseBlockListResource {
+        data: clauses.iter().map(ClauseBlockResource::from).collect(),
+    }))
+}
+
+pub async fn save_clause(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiJson(body): ApiJson<SaveClauseRequest>,
+) -> ApiResult<(StatusCode, Json<ClauseBlockResource>)> {
+    let clause = QuoteTemplateService::new(state.db_pool.clone())
+        .save_clause(SaveClause {
+            key: body.key,
+            title: body.title,
+            body: body.body,
+            actor: principal.actor(),
+        })
+        .await
+        .map_err(template_error)?;
+    Ok((StatusCode::CREATED, Json(ClauseBlockResource::from(&clause))))
+}
+
+pub async fn set_default(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiJson(body): ApiJson<SetTemplateDefaultRequest>,
+) -> ApiResult<Json<TemplateDefaultResource>> {
+    let value = optional_trimmed(body.value);
+    let scope = match (body.scope.trim(), value) {
+        ("global", None) => TemplateScope::Global,
+        ("segment", Some(segment)) => TemplateScope::Segment(segment),
+        ("account", Some(account)) => {
+            principal.ensure_account(Some(&account))?;
+            TemplateScope::Account(account)
+        }
+        ("global", Some(_)) => {
+            return Err(ApiError::validation("value must be omitted for the global default"))
+        }
+        ("segment" | "account", None) => {
+            return Err(ApiError::validation("value is required for segment and account defaults"))
+        }
+        (other, _) => {
+            return Err(ApiError::validation(format!(
+                "unknown scope `{other}`; expected global, segment or account"
+            )))
+        }
+    };
+    let service = QuoteTemplateService::new(state.db_pool.clone());
+    service
+        .set_default(&scope, &body.template_key, &principal.actor())
+        .await
+        .map_err(template_error)?;
+    let defaults = service.list_defaults().await.map_err(template_error)?;
+    defaults
+        .iter()
+        .find(|default| default.scope == scope)
+        .map(|default| Json(TemplateDefaultResource::from(default)))
+        .ok_or_else(|| ApiError::internal(&"template default vanished after saving"))
+}
+
+/// Renders a quote with any template version, drafts included, without recording anything.
+pub async fn preview_document(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path(id): Path<String>,
+    ApiJson(body): ApiJson<PreviewDocumentRequest>,
+) -> ApiResult<Json<DocumentPreviewResource>> {
+    let quote = load_quote(&state, &principal, &id).await?;
+    let quote_id = quote.id.0;
+    let document = QuoteTemplateService::new(state.db_pool.clone())
+        .resolve_for_quote(
+            &quote_id,
+            TemplateSelection { key: optional_trimmed(body.template_key), version: body.version },
+        )
+        .await
+        .map_err(template_error)?;
+    let company_name = BrandingConfig::from_env().company_name;
+    let payload = quote_pdf_payload(&state.db_pool, &quote_id, &company_name)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Quote '{quote_id}' not found")))?;
+    let pdf = PdfGenerator::with_embedded_templates()
+        .generate_document_pdf(&payload, &document)
+        .map_err(|error| ApiError::internal(&error))?;
+
+    Ok(Json(DocumentPreviewResource {
+        quote_id,
+        sections: document.sections.iter().map(PreviewSectionResource::from).collect(),
+        template_key: document.template_key,
+        template_version: document.template_version,
+        layout: document.layout.into(),
+        content_type: "application/pdf".to_string(),
+        sha256: document_sha256(&pdf.0),
+        pdf_base64: base64::engine::general_purpose::STANDARD.encode(&pdf.0),
+    }))
+}
+
+pub(super) fn template_error(error: QuoteTemplateError) -> ApiError {
+    match error.code() {
+        "not_found" => ApiError::not_found(error.to_string()),
+        "not_draft" => ApiError::conflict(error.to_string()),
+        "invalid_template" => ApiError::validation(error.to_string()),
+        _ => ApiError::internal(&error),
+    }
+}
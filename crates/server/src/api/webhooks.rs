@@ -0,0 +1,295 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use quotey_db::webhooks::{
+    DeliveryQuery, DeliveryStatus, NewWebhookSubscription, WebhookDelivery, WebhookDeliveryAttempt,
+    WebhookService, WebhookServiceError, WebhookSubscription,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::auth::ApiPrincipal;
+use super::error::{ApiError, ApiResult};
+use super::idempotency::{self, Mutation};
+use super::pagination::{Cursor, Page, PageRequest};
+use super::quotes::optional_trimmed;
+use super::{ApiJson, ApiQuery, ApiState};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListSubscriptionsQuery {
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+    /// Also list disabled subscriptions.
+    #[serde(default)]
+    pub include_disabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateSubscriptionRequest {
+    /// Absolute http(s) URL that receives `POST` deliveries.
+    pub url: String,
+    /// Event types (`quote.finalized`, `quote.accepted`, `approval.decided`,
+    /// `portal.comment_added`), `namespace.*` wildcards, or `*`.
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SubscriptionResource {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub description: Option<String>,
+    pub active: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Signing secret; only returned when the subscription is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<&WebhookSubscription> for SubscriptionResource {
+    fn from(subscription: &WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id.clone(),
+            url: subscription.url.clone(),
+            events: subscription.events.clone(),
+            description: subscription.description.clone(),
+            active: subscription.active,
+            created_by: subscription.created_by.clone(),
+            created_at: subscription.created_at.to_rfc3339(),
+            updated_at: subscription.updated_at.to_rfc3339(),
+            secret: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListDeliveriesQuery {
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+    /// `pending`, `retrying`, `delivered`, `dead_lettered` or `cancelled`.
+    pub status: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DeliveryResource {
+    pub id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub quote_id: String,
+    /// Delivery this one re-sends, for manual redeliveries.
+    pub redelivery_of: Option<String>,
+    /// `pending`, `retrying`, `delivered`, `dead_lettered` or `cancelled`.
+    pub status: String,
+    pub attempt_count: u32,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub last_attempt_at: Option<String>,
+    pub delivered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<&WebhookDelivery> for DeliveryResource {
+    fn from(delivery: &WebhookDelivery) -> Self {
+        Self {
+            id: delivery.id.clone(),
+            subscription_id: delivery.subscription_id.clone(),
+            event_id: delivery.event_id.clone(),
+            event_type: delivery.event_type.clone(),
+            quote_id: delivery.quote_id.clone(),
+            redelivery_of: delivery.redelivery_of.clone(),
+            status: delivery.status.as_str().to_string(),
+            attempt_count: delivery.attempt_count,
+            last_status_code: delivery.last_status_code,
+            last_error: delivery.last_error.clone(),
+            last_attempt_at: delivery.last_attempt_at.map(|at| at.to_rfc3339()),
+            delivered_at: delivery.delivered_at.map(|at| at.to_rfc3339()),
+            created_at: delivery.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AttemptResource {
+    pub attempt: u32,
+    pub status_code: Option<u16>,
+    /// First 512 characters of the subscriber's response body.
+    pub response_excerpt: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: String,
+}
+
+impl From<&WebhookDeliveryAttempt> for AttemptResource {
+    fn from(attempt: &WebhookDeliveryAttempt) -> Self {
+        Self {
+            attempt: attempt.attempt,
+            status_code: attempt.status_code,
+            response_excerpt: attempt.response_excerpt.clone(),
+            error: attempt.error.clone(),
+            duration_ms: attempt.duration_ms,
+            attempted_at: attempt.attempted_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DeliveryLogResource {
+    #[serde(flatten)]
+    pub delivery: DeliveryResource,
+    pub attempts: Vec<AttemptResource>,
+}
+
+impl From<WebhookServiceError> for ApiError {
+    fn from(error: WebhookServiceError) -> Self {
+        match error {
+            WebhookServiceError::SubscriptionNotFound(_)
+            | WebhookServiceError::DeliveryNotFound(_) => Self::not_found(error.to_string()),
+            WebhookServiceError::SubscriptionDisabled(_) => Self::conflict(error.to_string()),
+            WebhookServiceError::Invalid(_) => Self::validation(error.to_string()),
+            WebhookServiceError::Repository(error) => Self::from(error),
+        }
+    }
+}
+
+pub async fn list_subscriptions(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<ListSubscriptionsQuery>,
+) -> ApiResult<Json<Page<SubscriptionResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let subscriptions = WebhookService::new(state.db_pool.clone())
+        .list_subscriptions(query.include_disabled)
+        .await?;
+    // Subscriptions are few, so the keyset is applied after loading them newest first.
+    let rows: Vec<SubscriptionResource> = subscriptions
+        .iter()
+        .map(SubscriptionResource::from)
+        .filter(|row| {
+            page.after.as_ref().map_or(true, |after| {
+                (row.created_at.as_str(), row.id.as_str()) < (after.key.as_str(), after.id.as_str())
+            })
+        })
+        .take(page.fetch_limit() as usize)
+        .collect();
+    let (data, next_cursor) = page.finish(rows, |row| Cursor::new(&row.created_at, &row.id));
+    Ok(Json(Page { data, next_cursor }))
+}
+
+pub async fn create_subscription(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    ApiJson(body): ApiJson<CreateSubscriptionRequest>,
+) -> ApiResult<(StatusCode, Json<SubscriptionResource>)> {
+    let created = WebhookService::new(state.db_pool.clone())
+        .subscribe(NewWebhookSubscription {
+            url: body.url,
+            events: body.events,
+            description: optional_trimmed(body.description),
+            created_by: principal.actor(),
+        })
+        .await?;
+    let mut resource = SubscriptionResource::from(&created.subscription);
+    resource.secret = Some(created.secret);
+    Ok((StatusCode::CREATED, Json(resource)))
+}
+
+pub async fn get_subscription(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<SubscriptionResource>> {
+    let subscription = WebhookService::new(state.db_pool.clone()).subscription(&id).await?;
+    Ok(Json(SubscriptionResource::from(&subscription)))
+}
+
+pub async fn disable_subscription(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<SubscriptionResource>> {
+    let subscription = WebhookService::new(state.db_pool.clone()).disable(&id).await?;
+    Ok(Json(SubscriptionResource::from(&subscription)))
+}
+
+pub async fn list_deliveries(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    ApiQuery(query): ApiQuery<ListDeliveriesQuery>,
+) -> ApiResult<Json<Page<DeliveryResource>>> {
+    let page = PageRequest::parse(query.limit, query.cursor.as_deref())?;
+    let status = match query.status.as_deref().map(str::trim).filter(|raw| !raw.is_empty()) {
+        Some(raw) => Some(
+            DeliveryStatus::parse_label(raw)
+                .ok_or_else(|| ApiError::validation(format!("unknown delivery status `{raw}`")))?,
+        ),
+        None => None,
+    };
+    let service = WebhookService::new(state.db_pool.clone());
+    let subscription = service.subscription(&id).await?;
+    let rows = service
+        .list_deliveries(&DeliveryQuery {
+            subscription_id: subscription.id,
+            status,
+            before: page.after.as_ref().map(|after| (after.key.clone(), after.id.clone())),
+            limit: page.limit + 1,
+        })
+        .await?;
+    let (rows, next_cursor) =
+        page.finish(rows, |row| Cursor::new(row.created_at.to_rfc3339(), &row.id));
+    Ok(Json(Page { data: rows.iter().map(DeliveryResource::from).collect(), next_cursor }))
+}
+
+pub async fn get_delivery(
+    State(state): State<ApiState>,
+    Path((id, delivery_id)): Path<(String, String)>,
+) -> ApiResult<Json<DeliveryLogResource>> {
+    let log = WebhookService::new(state.db_pool.clone()).delivery_log(&delivery_id).await?;
+    if log.delivery.subscription_id != id.trim() {
+        return Err(WebhookServiceError::DeliveryNotFound(delivery_id).into());
+    }
+    Ok(Json(DeliveryLogResource {
+        delivery: DeliveryResource::from(&log.delivery),
+        attempts: log.attempts.iter().map(AttemptResource::from).collect(),
+    }))
+}
+
+pub async fn redeliver(
+    State(state): State<ApiState>,
+    Extension(principal): Extension<ApiPrincipal>,
+    Path((id, delivery_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    let service = WebhookService::new(state.db_pool.clone());
+    // The idempotency ledger row hangs off the quote the event belongs to.
+    let original = match service.delivery(&delivery_id).await {
+        Ok(delivery) if delivery.subscription_id == id.trim() => delivery,
+        Ok(_) => {
+            let error = WebhookServiceError::DeliveryNotFound(delivery_id);
+            return axum::response::IntoResponse::into_response(ApiError::from(error));
+        }
+        Err(error) => return axum::response::IntoResponse::into_response(ApiError::from(error)),
+    };
+    let payload = serde_json::json!({ "subscription_id": &id, "delivery_id": &delivery_id });
+    idempotency::run(
+        &state,
+        &principal,
+        &headers,
+        "webhook.redeliver",
+        Some(&original.quote_id),
+        &payload,
+        || async {
+            let delivery = service.redeliver(&original.id, &principal.actor()).await?;
+            Mutation::new(
+                StatusCode::CREATED,
+                delivery.quote_id.clone(),
+                DeliveryResource::from(&delivery),
+            )
+        },
+    )
+    .await
+}
@@ -0,0 +1,107 @@
+//! Scheduled refresh of the pricing baselines used for anomaly detection.
+//!
+//! The worker checks every few minutes whether the newest baseline version is older than the
+//! refresh interval and, if so, rebuilds baselines from finalized quote history. Pricing runs pick
+//! up the new version the next time they record a snapshot. Three org settings tune it:
+//! `anomaly_baseline_method` (`"mean_std_dev"` or `"median_mad"`),
+//! `anomaly_baseline_window_days` and `anomaly_baseline_refresh_hours`.
+
+use std::time::Duration;
+
+use quotey_core::cpq::anomaly::BaselineMethod;
+use quotey_db::repositories::{
+    BaselineRefreshConfig, OrgSettingsRepository, SqlOrgSettingsRepository,
+    SqlPricingBaselineRepository,
+};
+use quotey_db::DbPool;
+use tracing::{info, warn};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+const DEFAULT_REFRESH_HOURS: i64 = 24;
+
+/// Effective schedule: how to build baselines and how old they may get.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BaselineSchedule {
+    config: BaselineRefreshConfig,
+    max_age: chrono::Duration,
+}
+
+async fn load_schedule(pool: &DbPool) -> BaselineSchedule {
+    let repo = SqlOrgSettingsRepository::new(pool.clone());
+    let mut schedule = BaselineSchedule {
+        config: BaselineRefreshConfig::default(),
+        max_age: chrono::Duration::hours(DEFAULT_REFRESH_HOURS),
+    };
+
+    if let Ok(Some(setting)) = repo.get("anomaly_baseline_method").await {
+        match serde_json::from_str::<BaselineMethod>(&setting.value_json) {
+            Ok(method) => schedule.config.method = method,
+            Err(error) => warn!(%error, "ignoring invalid anomaly_baseline_method setting"),
+        }
+    }
+    if let Ok(Some(setting)) = repo.get("anomaly_baseline_window_days").await {
+        if let Some(days) = setting.value_as_i64().and_then(|days| u32::try_from(days).ok()) {
+            schedule.config.window_days = days.max(1);
+        }
+    }
+    if let Ok(Some(setting)) = repo.get("anomaly_baseline_refresh_hours").await {
+        if let Some(hours) = setting.value_as_i64().filter(|hours| *hours > 0) {
+            schedule.max_age = chrono::Duration::hours(hours);
+        }
+    }
+
+    schedule
+}
+
+/// Rebuilds baselines whenever they go stale, until the process exits.
+pub fn spawn(pool: DbPool) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let schedule = load_schedule(&pool).await;
+            match SqlPricingBaselineRepository::new(pool.clone())
+                .refresh_if_stale(schedule.config, schedule.max_age)
+                .await
+            {
+                Ok(Some(version)) => info!(
+                    version = version.version,
+                    method = version.method.as_str(),
+                    samples = version.sample_count,
+                    baselines = version.baseline_count,
+                    "pricing baselines refreshed"
+                ),
+                Ok(None) => {}
+                Err(error) => warn!(%error, "pricing baseline refresh failed"),
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_db::{connect_with_settings, migrations};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn schedule_reads_org_settings_and_falls_back_to_defaults() {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+        assert_eq!(
+            load_schedule(&pool).await,
+            BaselineSchedule {
+                config: BaselineRefreshConfig::default(),
+                max_age: chrono::Duration::hours(24),
+            }
+        );
+
+        let settings = SqlOrgSettingsRepository::new(pool.clone());
+        settings.set("anomaly_baseline_method", "\"median_mad\"", Some("test")).await.unwrap();
+        settings.set("anomaly_baseline_window_days", "90", Some("test")).await.unwrap();
+        settings.set("anomaly_baseline_refresh_hours", "6", Some("test")).await.unwrap();
+        let schedule = load_schedule(&pool).await;
+        assert_eq!(schedule.config.method, BaselineMethod::MedianMad);
+        assert_eq!(schedule.config.window_days, 90);
+        assert_eq!(schedule.max_age, chrono::Duration::hours(6));
+    }
+}
@@ -3,15 +3,25 @@ use std::sync::Arc;
 use chrono::Utc;
 use quotey_agent::{guardrails::GuardrailPolicy, runtime::AgentRuntime};
 use quotey_core::config::{AppConfig, ConfigError, LoadOptions};
+use quotey_core::domain::quote::QuoteId;
 use quotey_core::suggestions::{SuggestionFeedback, SuggestionFeedbackEvent};
+use quotey_db::explain::{ExplainError, ExplainQuery, ExplainService, ExplainTarget, Explanation};
+use quotey_db::ghost::{GhostAction, GhostDraft, GhostError, GhostPipeline, ObservedMessage};
 use quotey_db::repositories::{SqlSuggestionFeedbackRepository, SuggestionFeedbackRepository};
 use quotey_db::{connect_with_settings, migrations, DbPool};
-use quotey_slack::commands::NoopQuoteCommandService;
+use quotey_slack::blocks::{
+    self, ExplanationEvidenceView, ExplanationView, GhostQuoteView, MessageTemplate,
+};
+use quotey_slack::commands::{
+    CommandEnvelope, ExplainRequest, ExplainSubject, NoopQuoteCommandService,
+};
 use quotey_slack::events::{
-    BlockActionHandler, EventDispatcher, EventHandlerError, NoopBlockActionService,
-    NoopReactionApprovalService, NoopThreadMessageService, ReactionAddedHandler,
-    SlashCommandHandler, SuggestionFeedbackRecorder, SuggestionShownRecord,
-    SuggestionShownRecorder, ThreadMessageHandler,
+    BlockActionHandler, BuyingSignalObserver, EventContext, EventDispatcher, EventHandlerError,
+    ExplainingThreadMessageService, GhostQuoteAction, GhostQuoteActionService,
+    GhostQuoteBlockActionService, NoopBlockActionService, NoopReactionApprovalService,
+    NoopThreadMessageService, QuoteExplainer, ReactionAddedHandler,
+    SignalObservingThreadMessageService, SlashCommandHandler, SuggestionFeedbackRecorder,
+    SuggestionShownRecord, SuggestionShownRecorder, ThreadMessageEvent, ThreadMessageHandler,
 };
 use quotey_slack::socket::{NoopSocketTransport, ReconnectPolicy, SocketModeRunner};
 use thiserror::Error;
@@ -113,6 +123,180 @@ impl SuggestionShownRecorder for DbSuggestionFeedbackRecorder {
     }
 }
 
+/// Answers `/quote explain` and thread "why is this line $X?" questions from the persisted
+/// pricing snapshots via [`ExplainService`].
+#[derive(Clone)]
+struct DbQuoteExplainer {
+    pool: DbPool,
+}
+
+#[async_trait::async_trait]
+impl QuoteExplainer for DbQuoteExplainer {
+    async fn explain(
+        &self,
+        request: ExplainRequest,
+        envelope: &CommandEnvelope,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        let Some(quote_id) = request.quote_id else {
+            return Ok(Some(blocks::error_message(
+                "Which quote should I explain? Use `/quote explain <quote_id> [line <n>|policy]`.",
+                &envelope.request_id,
+            )));
+        };
+        let target = match request.subject {
+            ExplainSubject::Total => ExplainTarget::Total,
+            ExplainSubject::Line(line_id) => ExplainTarget::Line(line_id),
+            ExplainSubject::Policy => ExplainTarget::Policy,
+        };
+        let query = ExplainQuery {
+            quote_id: QuoteId(quote_id.clone()),
+            target,
+            version: None,
+            actor_type: "user".to_owned(),
+            actor_id: envelope.user_id.clone(),
+            thread_id: format!("{}:{}", envelope.channel_id, envelope.trigger_ts),
+            correlation_id: envelope.request_id.clone(),
+        };
+
+        match ExplainService::new(self.pool.clone()).explain(query).await {
+            Ok(explanation) => Ok(Some(blocks::explanation_message(&explanation_view(
+                &explanation,
+                &envelope.request_id,
+            )))),
+            Err(ExplainError::MissingEvidence(_)) => Ok(Some(blocks::error_message(
+                &format!(
+                    "No pricing snapshot is recorded for `{quote_id}` yet. Price the quote, then ask again."
+                ),
+                &envelope.request_id,
+            ))),
+            Err(error @ (ExplainError::QuoteNotFound(_) | ExplainError::LineNotFound { .. })) => {
+                Ok(Some(blocks::error_message(&error.to_string(), &envelope.request_id)))
+            }
+            Err(error) => Err(EventHandlerError::Explain(error.to_string())),
+        }
+    }
+}
+
+fn explanation_view(explanation: &Explanation, request_id: &str) -> ExplanationView {
+    let response = &explanation.response;
+    let subject = match explanation.line_id.as_deref() {
+        Some(line_id) => format!("Line {line_id} · {}", response.amount_description),
+        None if explanation.target == "policy" => "Policy verdict".to_owned(),
+        None => "Quote total".to_owned(),
+    };
+    ExplanationView {
+        quote_id: explanation.quote_id.clone(),
+        quote_version: explanation.quote_version,
+        subject,
+        amount: format!("{:.2}", response.amount),
+        summary: response.user_summary.clone(),
+        arithmetic: response
+            .arithmetic_chain
+            .iter()
+            .map(|step| format!("{} = {:.2}", step.description, step.result))
+            .collect(),
+        policy: response
+            .policy_evidence
+            .iter()
+            .map(|policy| {
+                let threshold = policy
+                    .threshold_value
+                    .as_deref()
+                    .map(|threshold| format!(", threshold {threshold}"))
+                    .unwrap_or_default();
+                format!(
+                    "{}: {} (actual {}{threshold})",
+                    policy.policy_name, policy.decision, policy.actual_value
+                )
+            })
+            .collect(),
+        evidence: explanation
+            .evidence
+            .iter()
+            .map(|link| ExplanationEvidenceView {
+                label: link.key.clone(),
+                source_reference: link.source_reference.clone(),
+            })
+            .collect(),
+        cached: explanation.cached,
+        explanation_id: explanation.request_id.clone(),
+        request_id: request_id.to_owned(),
+    }
+}
+
+/// Runs thread messages through the buying-signal detector and posts ghost drafts for rep
+/// review; the card buttons come back through [`GhostQuoteActionService`].
+#[derive(Clone)]
+struct DbGhostQuotes {
+    pool: DbPool,
+}
+
+#[async_trait::async_trait]
+impl BuyingSignalObserver for DbGhostQuotes {
+    async fn observe(
+        &self,
+        event: &ThreadMessageEvent,
+        _ctx: &EventContext,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        let pipeline = GhostPipeline::new(self.pool.clone());
+        let message = ObservedMessage {
+            channel_id: event.channel_id.clone(),
+            message_id: event.message_ts.clone(),
+            author_id: event.user_id.clone(),
+            text: event.text.clone(),
+        };
+        let thread_error = |error: GhostError| EventHandlerError::ThreadMessage(error.to_string());
+        let Some(draft) = pipeline.observe(&message).await.map_err(thread_error)? else {
+            return Ok(None);
+        };
+        pipeline.mark_notified(&draft.ghost_id).await.map_err(thread_error)?;
+        Ok(Some(blocks::ghost_quote_message(&ghost_quote_view(&draft))))
+    }
+}
+
+#[async_trait::async_trait]
+impl GhostQuoteActionService for DbGhostQuotes {
+    async fn resolve(
+        &self,
+        ghost_id: &str,
+        action: GhostQuoteAction,
+        user_id: &str,
+        ctx: &EventContext,
+    ) -> Result<MessageTemplate, EventHandlerError> {
+        let action = match action {
+            GhostQuoteAction::Accept => GhostAction::Accept,
+            GhostQuoteAction::Dismiss => GhostAction::Dismiss,
+            GhostQuoteAction::Convert => GhostAction::Convert,
+        };
+        match GhostPipeline::new(self.pool.clone()).resolve(ghost_id, action, user_id).await {
+            Ok(resolution) => Ok(blocks::ghost_quote_resolved_message(
+                &resolution.ghost_id,
+                resolution.action.as_str(),
+                resolution.quote_id.as_deref(),
+            )),
+            Err(error @ (GhostError::NotFound(_) | GhostError::AlreadyResolved { .. })) => {
+                Ok(blocks::error_message(&error.to_string(), &ctx.correlation_id))
+            }
+            Err(error) => Err(EventHandlerError::BlockAction(error.to_string())),
+        }
+    }
+}
+
+fn ghost_quote_view(draft: &GhostDraft) -> GhostQuoteView {
+    GhostQuoteView {
+        ghost_id: draft.ghost_id.clone(),
+        quote_id: draft.quote_id.clone(),
+        company: draft.company.clone(),
+        signal_type: draft.signal_type.clone(),
+        intent: draft.intent.clone(),
+        confidence: draft.confidence,
+        suggested_discount_pct: draft.suggested_discount_pct,
+        total: format!("{} {:.2}", draft.currency, draft.total),
+        line_count: draft.line_count,
+        similar_quote_id: draft.similar_quote_id.clone(),
+    }
+}
+
 /// Bootstrap with a pre-loaded config - avoids double config loading
 pub async fn bootstrap_with_config(config: AppConfig) -> Result<Application, BootstrapError> {
     bootstrap_from_config(config).await
@@ -160,7 +344,9 @@ async fn bootstrap_from_config(config: AppConfig) -> Result<Application, Bootstr
     );
 
     let feedback_recorder = DbSuggestionFeedbackRecorder { pool: db_pool.clone() };
-    let dispatcher = build_slack_dispatcher(feedback_recorder);
+    let explainer = DbQuoteExplainer { pool: db_pool.clone() };
+    let ghost_quotes = DbGhostQuotes { pool: db_pool.clone() };
+    let dispatcher = build_slack_dispatcher(feedback_recorder, explainer, ghost_quotes);
     let slack_runner = SocketModeRunner::new(
         Arc::new(NoopSocketTransport),
         dispatcher,
@@ -175,16 +361,27 @@ async fn bootstrap_from_config(config: AppConfig) -> Result<Application, Bootstr
     })
 }
 
-fn build_slack_dispatcher(feedback_recorder: DbSuggestionFeedbackRecorder) -> EventDispatcher {
+fn build_slack_dispatcher(
+    feedback_recorder: DbSuggestionFeedbackRecorder,
+    explainer: DbQuoteExplainer,
+    ghost_quotes: DbGhostQuotes,
+) -> EventDispatcher {
     let mut dispatcher = EventDispatcher::new();
-    dispatcher.register(SlashCommandHandler::with_shown_recorder(
-        NoopQuoteCommandService,
-        feedback_recorder.clone(),
-    ));
-    dispatcher.register(ThreadMessageHandler::new(NoopThreadMessageService::new()));
+    dispatcher.register(
+        SlashCommandHandler::with_shown_recorder(
+            NoopQuoteCommandService,
+            feedback_recorder.clone(),
+        )
+        .with_explainer(explainer.clone()),
+    );
+    dispatcher.register(ThreadMessageHandler::new(SignalObservingThreadMessageService::new(
+        ghost_quotes.clone(),
+        ExplainingThreadMessageService::new(explainer, NoopThreadMessageService::new()),
+    )));
     dispatcher.register(ReactionAddedHandler::new(NoopReactionApprovalService));
-    dispatcher.register(BlockActionHandler::new(NoopBlockActionService::with_feedback_recorder(
-        feedback_recorder,
+    dispatcher.register(BlockActionHandler::new(GhostQuoteBlockActionService::new(
+        ghost_quotes,
+        NoopBlockActionService::with_feedback_recorder(feedback_recorder),
     )));
     dispatcher
 }
@@ -206,7 +403,98 @@ mod tests {
     };
     use rust_decimal::Decimal;
 
-    use crate::bootstrap::bootstrap;
+    use crate::bootstrap::{bootstrap, DbGhostQuotes, DbQuoteExplainer};
+
+    #[tokio::test]
+    async fn quote_explainer_answers_from_recorded_pricing_snapshot() {
+        use quotey_core::{cpq::policy::PolicyThresholds, PricingLineSnapshot};
+        use quotey_core::{policy_evaluation_from_decision, pricing_snapshot_from_lines};
+        use quotey_db::repositories::{
+            QuoteRepository, SqlPricingSnapshotRepository, SqlQuoteRepository,
+        };
+        use quotey_slack::commands::{CommandEnvelope, ExplainRequest, ExplainSubject};
+        use quotey_slack::events::QuoteExplainer;
+
+        let pool =
+            quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let quote = quote_fixture();
+        SqlQuoteRepository::new(pool.clone()).save(quote.clone()).await.expect("save quote");
+
+        let explainer = DbQuoteExplainer { pool: pool.clone() };
+        let envelope = CommandEnvelope {
+            command: "quote".to_owned(),
+            verb: "explain".to_owned(),
+            quote_id: Some(quote.id.0.clone()),
+            account_hint: None,
+            freeform_args: quote.id.0.clone(),
+            channel_id: "C1".to_owned(),
+            user_id: "U1".to_owned(),
+            trigger_ts: "1".to_owned(),
+            request_id: "req-explain".to_owned(),
+        };
+        let request = |subject| ExplainRequest {
+            quote_id: Some(quote.id.0.clone()),
+            subject,
+            raw_args: String::new(),
+        };
+
+        let missing = explainer
+            .explain(request(ExplainSubject::Total), &envelope)
+            .await
+            .expect("explain")
+            .expect("message");
+        assert!(missing.fallback_text.starts_with("No pricing snapshot is recorded"));
+
+        let snapshot = pricing_snapshot_from_lines(
+            &quote.id,
+            1,
+            "USD",
+            vec![PricingLineSnapshot {
+                line_id: "Q-INT-0001-ql-1".to_owned(),
+                product_id: "plan-pro".to_owned(),
+                product_name: "Pro Plan".to_owned(),
+                quantity: 2,
+                unit_price: Decimal::new(25_000, 2),
+                discount_percent: Decimal::ZERO,
+                discount_amount: Decimal::ZERO,
+                line_subtotal: Decimal::new(50_000, 2),
+            }],
+            Decimal::ZERO,
+            Utc::now().to_rfc3339(),
+        );
+        let input = PolicyInput {
+            requested_discount_pct: Decimal::ZERO,
+            deal_value: Decimal::new(500, 0),
+            minimum_margin_pct: Some(Decimal::new(100, 0)),
+        };
+        let thresholds = PolicyThresholds::default();
+        let decision =
+            quotey_core::cpq::policy::evaluate_policy_with_thresholds(&input, &thresholds);
+        let policy = policy_evaluation_from_decision(
+            &quote.id,
+            1,
+            &input,
+            &thresholds,
+            &decision,
+            Utc::now().to_rfc3339(),
+        );
+        SqlPricingSnapshotRepository::new(pool.clone())
+            .record_snapshot(&snapshot, Some(&policy))
+            .await
+            .expect("record snapshot");
+
+        let answered = explainer
+            .explain(request(ExplainSubject::Line("Q-INT-0001-ql-1".to_owned())), &envelope)
+            .await
+            .expect("explain")
+            .expect("message");
+        assert_eq!(
+            answered.fallback_text,
+            "Line Q-INT-0001-ql-1 · Pro Plan (plan-pro) for Q-INT-0001: 500.00"
+        );
+        assert!(format!("{:?}", answered.blocks).contains("quote_pricing_snapshot:psnap-"));
+    }
 
     #[tokio::test]
     async fn bootstrap_fails_fast_without_required_slack_tokens() {
@@ -262,7 +550,7 @@ mod tests {
             PolicyInput {
                 requested_discount_pct: Decimal::new(1500, 2),
                 deal_value: Decimal::new(250_000, 2),
-                minimum_margin_pct: Decimal::new(4000, 2),
+                minimum_margin_pct: Some(Decimal::new(4000, 2)),
             },
         );
         assert!(cpq_evaluation.constraints.valid, "quote fixture should pass constraints");
@@ -304,6 +592,61 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn thread_chatter_posts_ghost_card_and_convert_hands_quote_to_rep() {
+        use quotey_db::repositories::{QuoteRepository, SqlQuoteRepository};
+        use quotey_slack::events::{
+            BuyingSignalObserver, EventContext, GhostQuoteAction, GhostQuoteActionService,
+            ThreadMessageEvent,
+        };
+
+        let pool =
+            quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let mut history = quote_fixture();
+        history.id = QuoteId("Q-ACME-0001".to_string());
+        history.account_id = Some("acme".to_string());
+        history.created_by = "U-REP".to_string();
+        SqlQuoteRepository::new(pool.clone()).save(history).await.expect("seed history");
+
+        let ghost_quotes = DbGhostQuotes { pool: pool.clone() };
+        let event = ThreadMessageEvent {
+            channel_id: "C-DEALS".to_owned(),
+            thread_ts: "1700000000.0001".to_owned(),
+            message_ts: "1700000000.0002".to_owned(),
+            user_id: "U-REP".to_owned(),
+            text: "Acme Corp plans to expand operations next quarter and is evaluating Salesforce pricing."
+                .to_owned(),
+        };
+        let card = ghost_quotes
+            .observe(&event, &EventContext::default())
+            .await
+            .expect("observe")
+            .expect("ghost card");
+        assert!(card.fallback_text.starts_with("Ghost quote drafted for Acme Corp"));
+        let card_json = serde_json::to_string(&card.blocks).expect("serialize card");
+        assert!(card_json.contains("ghost.convert.v1"));
+
+        let pending = quotey_db::ghost::GhostPipeline::new(pool.clone())
+            .pending(10)
+            .await
+            .expect("pending drafts");
+        assert_eq!(pending.len(), 1);
+        assert!(pending[0].rep_notified_at.is_some());
+
+        let resolved = ghost_quotes
+            .resolve(&pending[0].id, GhostQuoteAction::Convert, "U-REP", &EventContext::default())
+            .await
+            .expect("convert");
+        assert!(resolved.fallback_text.contains("converted into `Q-"));
+
+        let again = ghost_quotes
+            .resolve(&pending[0].id, GhostQuoteAction::Dismiss, "U-REP", &EventContext::default())
+            .await
+            .expect("second response is reported, not raised");
+        assert!(again.fallback_text.contains("already"));
+    }
+
     fn quote_fixture() -> Quote {
         let now = Utc::now();
         Quote {
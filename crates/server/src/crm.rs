@@ -3,13 +3,16 @@
 //! Current implementation provides:
 //! - OAuth start + callback wiring for Salesforce and HubSpot
 //! - provider connection upsert and status query
-//! - quote → CRM sync event capture and replay
-//! - CRM → quote inbound webhook ingest
+//! - quote → CRM sync: Salesforce Opportunity / HubSpot Deal and line item upserts
+//!   (see [`remote`]), recorded as replayable sync events
+//! - CRM → quote inbound webhook ingest with conflict resolution against the
+//!   last Quotey push
 //! - field mapping configuration APIs
 //! - sync history + retry support
 //!
-//! This implementation intentionally focuses on deterministic auditability and
-//! a safe, debuggable API surface before full bidirectional remote writes.
+//! Quotey is the source of truth for the fields it pushes. An inbound change
+//! older than the last push is skipped as stale, and one that races unsynced
+//! local edits only applies CRM-owned identity fields (account and deal ids).
 
 use std::collections::{HashMap, HashSet};
 
@@ -34,9 +37,11 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::Row;
-use tracing::{error, info, warn};
+use tracing::{error, warn};
 use uuid::Uuid;
 
+mod remote;
+
 const MAX_SYNC_ATTEMPTS: i32 = 5;
 const CRM_SYNC_BASE_RETRY_DELAY_SECONDS: i64 = 30;
 const CRM_SYNC_MAX_RETRY_DELAY_SECONDS: i64 = 3600;
@@ -46,6 +51,8 @@ const CRM_SYNC_WORKER_ID: &str = "crm-worker";
 const CRM_SYNC_ALERT_SAMPLE_LIMIT: i64 = 5;
 const CRM_SYNC_FAILED_CRITICAL_THRESHOLD: i64 = 10;
 const CRM_SYNC_STALE_RETRY_MINUTES: i64 = 30;
+const DEFAULT_SALESFORCE_LOGIN_URL: &str = "https://login.salesforce.com";
+const DEFAULT_HUBSPOT_API_BASE_URL: &str = "https://api.hubapi.com";
 
 #[derive(Clone, Debug)]
 struct CrmRuntimeConfig {
@@ -56,6 +63,8 @@ struct CrmRuntimeConfig {
     salesforce_client_secret: Option<String>,
     hubspot_client_id: Option<String>,
     hubspot_client_secret: Option<String>,
+    salesforce_login_url: Option<String>,
+    hubspot_api_base_url: Option<String>,
 }
 
 impl From<&CrmConfig> for CrmRuntimeConfig {
@@ -68,10 +77,29 @@ impl From<&CrmConfig> for CrmRuntimeConfig {
             salesforce_client_secret: config.salesforce_client_secret.clone(),
             hubspot_client_id: config.hubspot_client_id.clone(),
             hubspot_client_secret: config.hubspot_client_secret.clone(),
+            salesforce_login_url: config.salesforce_login_url.clone(),
+            hubspot_api_base_url: config.hubspot_api_base_url.clone(),
         }
     }
 }
 
+impl CrmRuntimeConfig {
+    fn salesforce_login_url(&self) -> &str {
+        configured_base_url(&self.salesforce_login_url).unwrap_or(DEFAULT_SALESFORCE_LOGIN_URL)
+    }
+
+    fn hubspot_api_base_url(&self) -> &str {
+        configured_base_url(&self.hubspot_api_base_url).unwrap_or(DEFAULT_HUBSPOT_API_BASE_URL)
+    }
+}
+
+fn configured_base_url(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(|value| value.trim().trim_end_matches('/'))
+        .filter(|value| !value.is_empty())
+}
+
 #[derive(Clone)]
 pub struct CrmState {
     db_pool: DbPool,
@@ -151,28 +179,34 @@ struct CrmError {
     error: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct ProviderConfig {
     provider: &'static str,
-    authorize_url: &'static str,
-    token_url: &'static str,
+    authorize_url: String,
+    token_url: String,
+    userinfo_url: String,
     default_scope: &'static str,
 }
 
 impl ProviderConfig {
-    fn from_provider(provider: CrmProvider) -> Self {
+    fn from_provider(provider: CrmProvider, config: &CrmRuntimeConfig) -> Self {
         match provider {
-            CrmProvider::Salesforce => Self {
-                provider: "salesforce",
-                authorize_url: "https://login.salesforce.com/services/oauth2/authorize",
-                token_url: "https://login.salesforce.com/services/oauth2/token",
-                default_scope: "api refresh_token",
-            },
+            CrmProvider::Salesforce => {
+                let login = config.salesforce_login_url();
+                Self {
+                    provider: "salesforce",
+                    authorize_url: format!("{login}/services/oauth2/authorize"),
+                    token_url: format!("{login}/services/oauth2/token"),
+                    userinfo_url: format!("{login}/services/oauth2/userinfo"),
+                    default_scope: "api refresh_token",
+                }
+            }
             CrmProvider::Hubspot => Self {
                 provider: "hubspot",
-                authorize_url: "https://app.hubspot.com/oauth/authorize",
-                token_url: "https://api.hubapi.com/oauth/v1/token",
-                default_scope: "crm.objects.line_items.read crm.objects.line_items.write crm.objects.contacts.read crm.objects.contacts.write",
+                authorize_url: "https://app.hubspot.com/oauth/authorize".to_string(),
+                token_url: format!("{}/oauth/v1/token", config.hubspot_api_base_url()),
+                userinfo_url: format!("{}/oauth/v3/userinfo", config.hubspot_api_base_url()),
+                default_scope: "crm.objects.line_items.read crm.objects.line_items.write crm.objects.contacts.read crm.objects.contacts.write crm.objects.deals.read crm.objects.deals.write",
             },
         }
     }
@@ -869,6 +903,176 @@ impl InboundQuoteUpdate {
     }
 }
 
+/// Outcome of comparing an inbound CRM change with the last Quotey push.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InboundConflict {
+    /// No newer Quotey state competes with the change; apply it.
+    Apply,
+    /// The change predates what Quotey last pushed or applied; skip it.
+    StaleRemote,
+    /// The quote changed locally after the last push; keep Quotey's status and notes.
+    QuoteyWins,
+}
+
+impl InboundConflict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Apply => "applied",
+            Self::StaleRemote => "stale_remote",
+            Self::QuoteyWins => "quotey_wins",
+        }
+    }
+}
+
+const REMOTE_MODIFIED_AT_FIELDS: &[&str] = &[
+    "remote_modified_at",
+    "LastModifiedDate",
+    "SystemModstamp",
+    "hs_lastmodifieddate",
+    "updatedAt",
+    "occurredAt",
+];
+
+/// Remote modification time carried by a webhook, from Salesforce
+/// `LastModifiedDate`-style fields or HubSpot `hs_lastmodifieddate`/`occurredAt`.
+fn inbound_remote_modified_at(payload: &WebhookPayload) -> Option<DateTime<Utc>> {
+    let properties = payload.payload.get("properties");
+    REMOTE_MODIFIED_AT_FIELDS.iter().find_map(|field| {
+        payload
+            .payload
+            .get(*field)
+            .or_else(|| properties.and_then(|properties| properties.get(*field)))
+            .and_then(parse_remote_timestamp)
+    })
+}
+
+fn parse_remote_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    let raw = webhook_string_field(value)?;
+    if let Ok(millis) = raw.parse::<i64>() {
+        // HubSpot sends epoch milliseconds; tolerate epoch seconds too.
+        let millis = if millis < 100_000_000_000 { millis.saturating_mul(1000) } else { millis };
+        return DateTime::from_timestamp_millis(millis);
+    }
+    DateTime::parse_from_rfc3339(&raw)
+        .or_else(|_| DateTime::parse_from_str(&raw, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .ok()
+        .map(|value| value.with_timezone(&Utc))
+}
+
+fn parse_stored_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|value| value.with_timezone(&Utc))
+}
+
+async fn resolve_inbound_conflict(
+    state: &CrmState,
+    provider: CrmProvider,
+    quote_id: &str,
+    remote_modified_at: Option<DateTime<Utc>>,
+) -> Result<InboundConflict, (StatusCode, Json<CrmError>)> {
+    let Some(remote_modified_at) = remote_modified_at else {
+        return Ok(InboundConflict::Apply);
+    };
+    let Some(link) = remote::parent_link(state, provider, quote_id).await.map_err(db_error)? else {
+        return Ok(InboundConflict::Apply);
+    };
+
+    let last_applied = parse_stored_timestamp(link.remote_modified_at.as_deref());
+    let last_pushed = parse_stored_timestamp(link.last_pushed_at.as_deref());
+    if last_applied.is_some_and(|applied| remote_modified_at <= applied)
+        || last_pushed.is_some_and(|pushed| remote_modified_at <= pushed)
+    {
+        return Ok(InboundConflict::StaleRemote);
+    }
+
+    // Pushes and applied inbound changes both touch the link, so a quote updated after
+    // that has local edits the CRM has not seen yet.
+    if let Some(last_synced) = parse_stored_timestamp(Some(link.updated_at.as_str())) {
+        let quote_updated_at: Option<String> =
+            sqlx::query_scalar("SELECT updated_at FROM quote WHERE id = ?")
+                .bind(quote_id)
+                .fetch_optional(&state.db_pool)
+                .await
+                .map_err(db_error)?;
+        if parse_stored_timestamp(quote_updated_at.as_deref())
+            .is_some_and(|local| local > last_synced)
+        {
+            return Ok(InboundConflict::QuoteyWins);
+        }
+    }
+    Ok(InboundConflict::Apply)
+}
+
+#[derive(Debug)]
+struct AppliedInboundUpdate {
+    conflict: InboundConflict,
+    status: &'static str,
+    error_message: Option<String>,
+}
+
+/// Resolves conflicts for an inbound change to `quote_id` and applies what survives.
+async fn apply_inbound_update(
+    state: &CrmState,
+    provider: CrmProvider,
+    quote_id: &str,
+    mut update: InboundQuoteUpdate,
+    remote_modified_at: Option<DateTime<Utc>>,
+) -> Result<AppliedInboundUpdate, (StatusCode, Json<CrmError>)> {
+    let conflict = resolve_inbound_conflict(state, provider, quote_id, remote_modified_at).await?;
+    if conflict != InboundConflict::Apply {
+        record_audit(&state.db_pool, quote_id, "crm.conflict_resolved", conflict.as_str()).await;
+    }
+    let skipped = |message: &str| AppliedInboundUpdate {
+        conflict,
+        status: "skipped",
+        error_message: Some(message.to_string()),
+    };
+
+    match conflict {
+        InboundConflict::StaleRemote => {
+            return Ok(skipped("remote change predates the last quotey sync"));
+        }
+        InboundConflict::QuoteyWins => {
+            update.status = None;
+            update.notes = None;
+            if update.account_id.is_none() && update.deal_id.is_none() {
+                return Ok(skipped("quote changed locally since the last sync; quotey wins"));
+            }
+        }
+        InboundConflict::Apply => {}
+    }
+
+    if let Err((_, err)) = apply_crm_update_to_quote(
+        state,
+        quote_id,
+        update.account_id.as_deref(),
+        update.deal_id.as_deref(),
+        update.status.as_deref(),
+        update.notes.as_deref(),
+    )
+    .await
+    {
+        return Ok(AppliedInboundUpdate {
+            conflict,
+            status: "failed",
+            error_message: Some(err.0.error.clone()),
+        });
+    }
+    let remote_modified_at = remote_modified_at.map(|value| value.to_rfc3339());
+    remote::mark_inbound_applied(state, provider, quote_id, remote_modified_at.as_deref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(AppliedInboundUpdate {
+        conflict,
+        status: "success",
+        error_message: (conflict == InboundConflict::QuoteyWins).then(|| {
+            "quote changed locally since the last sync; kept quotey status and notes".to_string()
+        }),
+    })
+}
+
 fn infer_crm_object_identity(
     quote_id: Option<&str>,
     account_id: Option<&str>,
@@ -1036,30 +1240,20 @@ fn extract_inbound_quote_update(
         }
 
         match quotey_field.as_str() {
-            "quote_id" => {
-                if updates.quote_id.is_none() {
-                    updates.quote_id = Some(value);
-                }
+            "quote_id" if updates.quote_id.is_none() => {
+                updates.quote_id = Some(value);
             }
-            "account_id" => {
-                if updates.account_id.is_none() {
-                    updates.account_id = Some(value);
-                }
+            "account_id" if updates.account_id.is_none() => {
+                updates.account_id = Some(value);
             }
-            "deal_id" => {
-                if updates.deal_id.is_none() {
-                    updates.deal_id = Some(value);
-                }
+            "deal_id" if updates.deal_id.is_none() => {
+                updates.deal_id = Some(value);
             }
-            "status" => {
-                if updates.status.is_none() {
-                    updates.status = Some(value);
-                }
+            "status" if updates.status.is_none() => {
+                updates.status = Some(value);
             }
-            "notes" => {
-                if updates.notes.is_none() {
-                    updates.notes = Some(value);
-                }
+            "notes" if updates.notes.is_none() => {
+                updates.notes = Some(value);
             }
             _ => {}
         }
@@ -1239,6 +1433,10 @@ fn outbound_sync_semantics_for_status(status: Option<&str>) -> OutboundSyncSeman
             event_type: "quote_expired".to_string(),
             sync_action: "update_stage".to_string(),
         },
+        "accepted" => OutboundSyncSemantics {
+            event_type: "quote_accepted".to_string(),
+            sync_action: "close_won".to_string(),
+        },
         _ => OutboundSyncSemantics {
             event_type: "quote_updated".to_string(),
             sync_action: "update_quote".to_string(),
@@ -1375,7 +1573,7 @@ async fn start_oauth(
     let provider = parse_provider(&provider_raw)?;
     crm_state_guard(&HeaderMap::new(), &state, Some(provider), false).await?;
 
-    let provider_config = ProviderConfig::from_provider(provider);
+    let provider_config = ProviderConfig::from_provider(provider, &state.config);
     let state_token = Uuid::new_v4().simple().to_string();
     let now = Utc::now();
     let expires_at = now + Duration::minutes(10);
@@ -1441,7 +1639,7 @@ async fn oauth_callback(
     })?;
 
     let state_row = fetch_and_reserve_oauth_state(&state, provider, &query.state).await?;
-    let provider_config = ProviderConfig::from_provider(provider);
+    let provider_config = ProviderConfig::from_provider(provider, &state.config);
     let (client_id, client_secret) = provider.credentials(&state.config).ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
@@ -1463,7 +1661,7 @@ async fn oauth_callback(
         scope: Some(&state_row.scope),
     };
 
-    let token = exchange_token(&state, provider_config.token_url, token_request).await?;
+    let token = exchange_token(&state, &provider_config.token_url, token_request).await?;
     let discovered_account_id = discover_provider_account_id(
         &state,
         provider,
@@ -1667,7 +1865,7 @@ async fn run_crm_sync_task(
     )
     .await?;
 
-    let attempt = simulate_crm_sync_attempt(integration, payload, direction).await;
+    let attempt = attempt_crm_sync(state, integration, payload, direction).await;
     let result = match attempt {
         Ok(message) => {
             let completed = execution_engine
@@ -2533,6 +2731,7 @@ async fn retry_sync_event(
                 extract_inbound_quote_update(&webhook_payload, provider, &mappings);
             let quote_id = resolve_webhook_quote_id(
                 &state,
+                provider,
                 inbound_update.quote_id.as_deref().or(event.quote_id.as_deref()),
                 inbound_update.account_id.as_deref(),
                 inbound_update.deal_id.as_deref(),
@@ -2549,28 +2748,23 @@ async fn retry_sync_event(
                 )
                 .await?;
             } else if let Some(ref quote_id) = quote_id {
-                if let Err((_code, err)) = apply_crm_update_to_quote(
+                let remote_modified_at = inbound_remote_modified_at(&webhook_payload);
+                let applied = apply_inbound_update(
                     &state,
+                    provider,
                     quote_id,
-                    inbound_update.account_id.as_deref(),
-                    inbound_update.deal_id.as_deref(),
-                    inbound_update.status.as_deref(),
-                    inbound_update.notes.as_deref(),
+                    inbound_update,
+                    remote_modified_at,
                 )
-                .await
-                {
-                    update_sync_event_status(
-                        &state,
-                        &event_id,
-                        "failed",
-                        next_attempt,
-                        Some(err.0.error.clone()),
-                    )
-                    .await?;
-                } else {
-                    update_sync_event_status(&state, &event_id, "success", next_attempt, None)
-                        .await?;
-                }
+                .await?;
+                update_sync_event_status(
+                    &state,
+                    &event_id,
+                    applied.status,
+                    next_attempt,
+                    applied.error_message,
+                )
+                .await?;
             } else {
                 update_sync_event_status(
                     &state,
@@ -3125,6 +3319,7 @@ async fn webhook_ingest(
     let inbound_update = extract_inbound_quote_update(&payload, provider, &mappings);
     let quote_id = resolve_webhook_quote_id(
         &state,
+        provider,
         inbound_update.quote_id.as_deref(),
         inbound_update.account_id.as_deref(),
         inbound_update.deal_id.as_deref(),
@@ -3140,8 +3335,8 @@ async fn webhook_ingest(
         )
     })?;
 
-    let mut error_msg = None;
-    let mut status = "success";
+    let error_msg;
+    let status;
     if let Some(ref quote_id) = quote_id {
         event_payload["quote_id"] = json!(quote_id);
     }
@@ -3162,14 +3357,17 @@ async fn webhook_ingest(
         inbound_update.account_id.as_deref(),
         inbound_update.deal_id.as_deref(),
     );
-    let duplicate_event = inbound_event_is_duplicate(
-        &state,
-        provider,
-        event_type,
-        crm_object_type.as_deref(),
-        crm_object_id.as_deref(),
-    )
-    .await?;
+    // Timestamped changes are ordered by conflict resolution instead of the dedupe window.
+    let remote_modified_at = inbound_remote_modified_at(&payload);
+    let duplicate_event = remote_modified_at.is_none()
+        && inbound_event_is_duplicate(
+            &state,
+            provider,
+            event_type,
+            crm_object_type.as_deref(),
+            crm_object_id.as_deref(),
+        )
+        .await?;
 
     if inbound_update.is_empty() {
         status = "skipped";
@@ -3178,19 +3376,12 @@ async fn webhook_ingest(
         status = "skipped";
         error_msg = Some("duplicate crm object event ignored".to_string());
     } else if let Some(ref quote_id) = quote_id {
-        if let Err((_, err)) = apply_crm_update_to_quote(
-            &state,
-            quote_id,
-            inbound_update.account_id.as_deref(),
-            inbound_update.deal_id.as_deref(),
-            inbound_update.status.as_deref(),
-            inbound_update.notes.as_deref(),
-        )
-        .await
-        {
-            status = "failed";
-            error_msg = Some(err.0.error.clone());
-        }
+        let applied =
+            apply_inbound_update(&state, provider, quote_id, inbound_update, remote_modified_at)
+                .await?;
+        event_payload["conflict_outcome"] = json!(applied.conflict.as_str());
+        status = applied.status;
+        error_msg = applied.error_message;
     } else {
         status = "skipped";
         error_msg = Some("webhook could not resolve quote_id".to_string());
@@ -3235,6 +3426,7 @@ async fn webhook_ingest(
 
 async fn resolve_webhook_quote_id(
     state: &CrmState,
+    provider: CrmProvider,
     explicit_quote_id: Option<&str>,
     account_id: Option<&str>,
     deal_id: Option<&str>,
@@ -3248,6 +3440,15 @@ async fn resolve_webhook_quote_id(
         return Ok(found);
     }
 
+    // A deal id Quotey created itself identifies its quote exactly.
+    if let Some(raw_deal_id) = deal_id.map(str::trim).filter(|value| !value.is_empty()) {
+        let found =
+            remote::quote_for_remote_id(state, provider, raw_deal_id).await.map_err(db_error)?;
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+
     if let Some(raw_account_id) = account_id.map(str::trim).filter(|value| !value.is_empty()) {
         let found = sqlx::query_scalar(
             "SELECT id FROM quote WHERE account_id = ? ORDER BY COALESCE(updated_at, created_at) DESC LIMIT 1",
@@ -3301,7 +3502,7 @@ async fn fetch_quote_payload(
     quote_id: &str,
 ) -> Result<Value, (StatusCode, Json<CrmError>)> {
     let row = sqlx::query(
-        "SELECT id, status, currency, created_by, account_id, deal_id, notes, version,\n         term_months,\n         (SELECT COALESCE(SUM(subtotal), 0) FROM quote_line WHERE quote_line.quote_id = quote.id) AS total_amount\n         FROM quote\n         WHERE id = ?",
+        "SELECT id, status, currency, created_by, account_id, deal_id, notes, version,\n         term_months, valid_until,\n         (SELECT COALESCE(SUM(subtotal), 0) FROM quote_line WHERE quote_line.quote_id = quote.id) AS total_amount\n         FROM quote\n         WHERE id = ?",
     )
     .bind(quote_id)
     .fetch_optional(&state.db_pool)
@@ -3326,6 +3527,7 @@ async fn fetch_quote_payload(
         "notes": row.try_get::<String, _>("notes").ok(),
         "version": row.try_get::<i64, _>("version").unwrap_or(1),
         "term_months": row.try_get::<i64, _>("term_months").ok(),
+        "valid_until": row.try_get::<String, _>("valid_until").ok(),
         "total_amount": row.try_get::<f64, _>("total_amount").unwrap_or(0.0),
     }))
 }
@@ -3335,7 +3537,7 @@ async fn fetch_quote_lines_payload(
     quote_id: &str,
 ) -> Result<Value, (StatusCode, Json<CrmError>)> {
     let lines = sqlx::query(
-        "SELECT id, product_id, quantity, unit_price, discount_pct, subtotal, notes\n         FROM quote_line\n         WHERE quote_id = ?\n         ORDER BY created_at ASC",
+        "SELECT quote_line.id, quote_line.product_id, quote_line.quantity, quote_line.unit_price,\n         quote_line.discount_pct, quote_line.subtotal, quote_line.notes,\n         COALESCE(product.sku, quote_line.product_id) AS product_code\n         FROM quote_line\n         LEFT JOIN product ON product.id = quote_line.product_id\n         WHERE quote_line.quote_id = ?\n         ORDER BY quote_line.created_at ASC",
     )
     .bind(quote_id)
     .fetch_all(&state.db_pool)
@@ -3348,6 +3550,7 @@ async fn fetch_quote_lines_payload(
             json!({
                 "id": row.try_get::<String, _>("id").unwrap_or_default(),
                 "product_id": row.try_get::<String, _>("product_id").unwrap_or_default(),
+                "product_code": row.try_get::<String, _>("product_code").unwrap_or_default(),
                 "quantity": row.try_get::<i64, _>("quantity").unwrap_or(0),
                 "unit_price": row.try_get::<f64, _>("unit_price").unwrap_or(0.0),
                 "discount_pct": row.try_get::<f64, _>("discount_pct").unwrap_or(0.0),
@@ -3479,17 +3682,7 @@ async fn discover_provider_account_id(
 ) -> Option<String> {
     let token_type = if token_type.trim().is_empty() { "Bearer" } else { token_type };
     let auth = format!("{token_type} {}", access_token);
-    let userinfo_url = match provider {
-        CrmProvider::Salesforce => {
-            Some("https://login.salesforce.com/services/oauth2/userinfo".to_string())
-        }
-        CrmProvider::Hubspot => Some("https://api.hubapi.com/oauth/v3/userinfo".to_string()),
-    };
-
-    let userinfo_url = match userinfo_url {
-        Some(url) => url,
-        None => return None,
-    };
+    let userinfo_url = ProviderConfig::from_provider(provider, &state.config).userinfo_url;
 
     let response =
         state.client.get(&userinfo_url).header("Authorization", auth).send().await.ok()?;
@@ -3655,29 +3848,30 @@ fn map_quote_payload_for_provider(
             continue;
         }
 
-        let quotey_field =
-            normalize_quotey_field_for_direction(&mapping.quotey_field, CrmDirection::QuoteyToCrm);
-        if !is_supported_quotey_field(&quotey_field, CrmDirection::QuoteyToCrm) {
-            continue;
-        }
-        let value = if let Some(expression) = mapping.expression.as_deref() {
-            match extract_template_value(expression, payload) {
-                Some(rendered) => Value::String(rendered),
-                None => resolve_payload_value(payload, &quotey_field).unwrap_or(Value::Null),
-            }
-        } else {
-            resolve_payload_value(payload, &quotey_field).unwrap_or(Value::Null)
-        };
-
-        if value.is_null() {
-            continue;
+        if let Some(value) = mapped_field_value(payload, mapping) {
+            set_nested_value(&mut mapped, &mapping.crm_field, value);
         }
-        set_nested_value(&mut mapped, &mapping.crm_field, value);
     }
 
     mapped
 }
 
+/// Value an outbound mapping writes to its `crm_field`, or `None` when the
+/// field is unsupported or resolves to null.
+fn mapped_field_value(payload: &Value, mapping: &CrmFieldMapping) -> Option<Value> {
+    let quotey_field =
+        normalize_quotey_field_for_direction(&mapping.quotey_field, CrmDirection::QuoteyToCrm);
+    if !is_supported_quotey_field(&quotey_field, CrmDirection::QuoteyToCrm) {
+        return None;
+    }
+    let value = match mapping.expression.as_deref().and_then(|e| extract_template_value(e, payload))
+    {
+        Some(rendered) => Value::String(rendered),
+        None => resolve_payload_value(payload, &quotey_field)?,
+    };
+    (!value.is_null()).then_some(value)
+}
+
 fn has_expired_token(token_expires_at: &Option<String>) -> bool {
     let Some(expires_at) = token_expires_at else {
         return false;
@@ -3688,7 +3882,8 @@ fn has_expired_token(token_expires_at: &Option<String>) -> bool {
     }
 }
 
-async fn simulate_crm_sync_attempt(
+async fn attempt_crm_sync(
+    state: &CrmState,
     integration: &CrmIntegration,
     payload: &Value,
     direction: CrmDirection,
@@ -3718,7 +3913,7 @@ async fn simulate_crm_sync_attempt(
     if has_expired_token(&integration.token_expires_at) && integration.refresh_token.is_none() {
         return Err(SyncAttemptResult {
             kind: SyncAttemptResultKind::TerminalFailure,
-            message: "provider integration access token expired".to_string(),
+            message: "access token expired and refresh token is missing".to_string(),
             error_class: Some("access_token_expired".to_string()),
         });
     }
@@ -3733,29 +3928,7 @@ async fn simulate_crm_sync_attempt(
         );
     }
 
-    if has_expired_token(&integration.token_expires_at) {
-        if let Some(refresh_token) = &integration.refresh_token {
-            info!(
-                provider = integration.provider.as_str(),
-                refresh_token_available = !refresh_token.is_empty(),
-                "crm token is expired; best effort sync"
-            );
-            return Err(SyncAttemptResult {
-                kind: SyncAttemptResultKind::RetryableFailure,
-                message:
-                    "access token expired; refresh token available for background worker retry"
-                        .to_string(),
-                error_class: Some("access_token_expired".to_string()),
-            });
-        }
-        return Err(SyncAttemptResult {
-            kind: SyncAttemptResultKind::TerminalFailure,
-            message: "access token expired and refresh token is missing".to_string(),
-            error_class: Some("access_token_expired".to_string()),
-        });
-    }
-
-    Ok("sync payload validated successfully".to_string())
+    remote::push_quote(state, integration, payload).await
 }
 
 fn db_error(error: sqlx::Error) -> (StatusCode, Json<CrmError>) {
@@ -4261,10 +4434,13 @@ fn encode_query(value: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     use axum::extract::{Path, Query, State};
-    use axum::http::StatusCode;
+    use axum::http::{HeaderMap, Method, StatusCode, Uri};
+    use axum::response::{IntoResponse, Response};
     use axum::Json;
     use chrono::{Duration, Utc};
     use quotey_core::config::CrmConfig;
@@ -4282,7 +4458,129 @@ mod tests {
         WebhookPayload,
     };
 
+    /// Local stand-in for the Salesforce REST and HubSpot CRM v3 APIs. Records every request
+    /// and answers with provider-shaped responses; `fail_next` forces the next API response.
+    #[derive(Clone, Default)]
+    struct CrmStub {
+        base_url: String,
+        requests: Arc<Mutex<Vec<StubRequest>>>,
+        failures: Arc<Mutex<VecDeque<StatusCode>>>,
+        next_id: Arc<AtomicUsize>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct StubRequest {
+        method: Method,
+        path: String,
+        authorization: Option<String>,
+        body: Value,
+    }
+
+    impl CrmStub {
+        async fn spawn() -> Self {
+            let listener =
+                tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind crm stub");
+            let base_url = format!("http://{}", listener.local_addr().expect("crm stub address"));
+            let stub = Self { base_url, ..Self::default() };
+            let app = axum::Router::new().fallback(crm_stub_handler).with_state(stub.clone());
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.expect("serve crm stub");
+            });
+            stub
+        }
+
+        fn fail_next(&self, status: StatusCode) {
+            self.failures.lock().expect("stub failures").push_back(status);
+        }
+
+        fn requests(&self) -> Vec<StubRequest> {
+            self.requests.lock().expect("stub requests").clone()
+        }
+
+        fn take_requests(&self) -> Vec<StubRequest> {
+            std::mem::take(&mut *self.requests.lock().expect("stub requests"))
+        }
+    }
+
+    async fn crm_stub_handler(
+        State(stub): State<CrmStub>,
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: String,
+    ) -> Response {
+        let path = uri.path().to_string();
+        let query = uri.query().unwrap_or_default().to_string();
+        stub.requests.lock().expect("stub requests").push(StubRequest {
+            method: method.clone(),
+            path: path.clone(),
+            authorization: headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            body: serde_json::from_str(&body).unwrap_or(Value::String(body)),
+        });
+
+        let json = |status: StatusCode, body: Value| (status, Json(body)).into_response();
+        if path.ends_with("/oauth2/token") || path.ends_with("/oauth/v1/token") {
+            return json(
+                StatusCode::OK,
+                serde_json::json!({
+                    "access_token": "refreshed-token",
+                    "refresh_token": "refresh-2",
+                    "token_type": "Bearer",
+                    "expires_in": 3600,
+                }),
+            );
+        }
+        if let Some(status) = stub.failures.lock().expect("stub failures").pop_front() {
+            return json(status, serde_json::json!({ "message": "stub failure" }));
+        }
+
+        let id = stub.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let salesforce = path.starts_with("/services/data/");
+        match method {
+            Method::GET if path.ends_with("/query") => {
+                let records = if query.contains("plan-pro") {
+                    serde_json::json!([{
+                        "Id": "01uSTUB",
+                        "Pricebook2Id": "01sSTANDARD",
+                        "ProductCode": "plan-pro",
+                    }])
+                } else {
+                    serde_json::json!([])
+                };
+                json(StatusCode::OK, serde_json::json!({ "done": true, "records": records }))
+            }
+            Method::POST if path.ends_with("/sobjects/Opportunity") => json(
+                StatusCode::CREATED,
+                serde_json::json!({ "id": format!("006STUB{id}"), "success": true }),
+            ),
+            Method::POST if path.ends_with("/sobjects/OpportunityLineItem") => json(
+                StatusCode::CREATED,
+                serde_json::json!({ "id": format!("00kSTUB{id}"), "success": true }),
+            ),
+            Method::POST if path.starts_with("/crm/v3/objects/") => {
+                json(StatusCode::CREATED, serde_json::json!({ "id": format!("{}", 9000 + id) }))
+            }
+            Method::PATCH if salesforce => StatusCode::NO_CONTENT.into_response(),
+            Method::PATCH => {
+                let id = path.rsplit('/').next().unwrap_or_default();
+                json(StatusCode::OK, serde_json::json!({ "id": id }))
+            }
+            Method::DELETE => StatusCode::NO_CONTENT.into_response(),
+            _ => json(StatusCode::NOT_FOUND, serde_json::json!({ "message": "not found" })),
+        }
+    }
+
     async fn setup_state() -> CrmState {
+        setup_state_with_stub().await.0
+    }
+
+    /// Salesforce API calls go to the integration's `instance_url`, which
+    /// `seed_connected_integration` points at the stub; HubSpot uses the configured base URL.
+    async fn setup_state_with_stub() -> (CrmState, CrmStub) {
+        let stub = CrmStub::spawn().await;
         let db_pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 5)
             .await
             .expect("connect sqlite memory pool");
@@ -4296,8 +4594,15 @@ mod tests {
             salesforce_client_secret: Some("sf-client-secret".to_string()),
             hubspot_client_id: Some("hs-client-id".to_string()),
             hubspot_client_secret: Some("hs-client-secret".to_string()),
+            salesforce_login_url: None,
+            hubspot_api_base_url: Some(stub.base_url.clone()),
+        };
+        let state = CrmState {
+            db_pool,
+            config: CrmRuntimeConfig::from(&crm),
+            client: reqwest::Client::new(),
         };
-        CrmState { db_pool, config: CrmRuntimeConfig::from(&crm), client: reqwest::Client::new() }
+        (state, stub)
     }
 
     async fn seed_connected_integration(state: &CrmState, provider: &str) {
@@ -4307,11 +4612,12 @@ mod tests {
             "INSERT INTO crm_integration
                 (id, provider, status, crm_account_id, instance_url, access_token, refresh_token, token_type, scope, token_expires_at, last_error, created_at, updated_at)
              VALUES
-                (?, ?, 'connected', ?, 'https://example.crm.local', 'token-123', NULL, 'Bearer', NULL, ?, NULL, ?, ?)",
+                (?, ?, 'connected', ?, ?, 'token-123', NULL, 'Bearer', NULL, ?, NULL, ?, ?)",
         )
         .bind(format!("CRMINT-{provider}-test"))
         .bind(provider)
         .bind(format!("acct-{provider}"))
+        .bind(state.config.hubspot_api_base_url())
         .bind(&expires_at)
         .bind(&now)
         .bind(&now)
@@ -4580,6 +4886,10 @@ mod tests {
         let expired = outbound_sync_semantics_for_status(Some("expired"));
         assert_eq!(expired.event_type, "quote_expired");
         assert_eq!(expired.sync_action, "update_stage");
+
+        let accepted = outbound_sync_semantics_for_status(Some("accepted"));
+        assert_eq!(accepted.event_type, "quote_accepted");
+        assert_eq!(accepted.sync_action, "close_won");
     }
 
     #[tokio::test]
@@ -5282,4 +5592,349 @@ mod tests {
 
         state.db_pool.close().await;
     }
+
+    async fn seed_outbound_mapping(
+        state: &CrmState,
+        provider: &str,
+        quotey_field: &str,
+        crm_field: &str,
+    ) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO crm_field_mapping
+                (id, provider, direction, quotey_field, crm_field, is_active, created_at, updated_at)
+             VALUES (?, ?, 'quotey_to_crm', ?, ?, 1, ?, ?)",
+        )
+        .bind(format!("MAP-{provider}-{quotey_field}"))
+        .bind(provider)
+        .bind(quotey_field)
+        .bind(crm_field)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db_pool)
+        .await
+        .expect("seed outbound mapping");
+    }
+
+    async fn sync_provider(state: &CrmState, quote_id: &str, provider: &str) -> super::SyncAttempt {
+        let Json(mut response) = sync_quote_to_crm(
+            Path(quote_id.to_string()),
+            State(state.clone()),
+            Query(SyncEventPayloadRequest {
+                direction: None,
+                provider: Some(provider.to_string()),
+                event_type: None,
+            }),
+        )
+        .await
+        .expect("outbound sync should run");
+        response.provider_results.remove(0)
+    }
+
+    async fn remote_id(state: &CrmState, object_type: &str, local_id: &str) -> Option<String> {
+        sqlx::query_scalar(
+            "SELECT remote_id FROM crm_remote_object WHERE object_type = ? AND local_id = ?",
+        )
+        .bind(object_type)
+        .bind(local_id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .expect("fetch remote object link")
+    }
+
+    fn find_request<'a>(
+        requests: &'a [StubRequest],
+        method: Method,
+        path_suffix: &str,
+    ) -> &'a StubRequest {
+        requests
+            .iter()
+            .find(|request| request.method == method && request.path.ends_with(path_suffix))
+            .unwrap_or_else(|| panic!("expected {method} …{path_suffix} in {requests:#?}"))
+    }
+
+    #[tokio::test]
+    async fn salesforce_sync_upserts_opportunity_and_line_items_with_mappings() {
+        let (state, stub) = setup_state_with_stub().await;
+        seed_connected_integration(&state, "salesforce").await;
+        seed_quote_with_line(&state, "Q-CRM-SF-001", "draft").await;
+        seed_outbound_mapping(&state, "salesforce", "term_months", "Term_Months__c").await;
+
+        let created = sync_provider(&state, "Q-CRM-SF-001", "salesforce").await;
+        assert_eq!(created.status, "success", "{}", created.message);
+
+        let opportunity_id =
+            remote_id(&state, "opportunity", "Q-CRM-SF-001").await.expect("opportunity link");
+        let first_line_id = remote_id(&state, "opportunity_line_item", "QL-Q-CRM-SF-001")
+            .await
+            .expect("line item link");
+        let requests = stub.take_requests();
+        let query = find_request(&requests, Method::GET, "/query");
+        assert!(query.path.starts_with("/services/data/v59.0/"));
+        let opportunity = find_request(&requests, Method::POST, "/sobjects/Opportunity");
+        assert_eq!(opportunity.authorization.as_deref(), Some("Bearer token-123"));
+        assert_eq!(opportunity.body["Name"], "Quote Q-CRM-SF-001");
+        assert_eq!(opportunity.body["StageName"], "Prospecting");
+        assert_eq!(opportunity.body["Amount"], 200.0);
+        assert_eq!(opportunity.body["Description"], "seed quote");
+        assert_eq!(opportunity.body["Term_Months__c"], 12);
+        assert_eq!(opportunity.body["Pricebook2Id"], "01sSTANDARD");
+        let line = find_request(&requests, Method::POST, "/sobjects/OpportunityLineItem");
+        assert_eq!(line.body["OpportunityId"], opportunity_id.as_str());
+        assert_eq!(line.body["PricebookEntryId"], "01uSTUB");
+        assert_eq!(line.body["Quantity"], 2);
+        assert_eq!(line.body["UnitPrice"], 100.0);
+        assert_eq!(line.body["Description"], "line note");
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query("UPDATE quote SET status = 'accepted', updated_at = ? WHERE id = ?")
+            .bind(&now)
+            .bind("Q-CRM-SF-001")
+            .execute(&state.db_pool)
+            .await
+            .expect("accept quote");
+        sqlx::query("DELETE FROM quote_line WHERE id = 'QL-Q-CRM-SF-001'")
+            .execute(&state.db_pool)
+            .await
+            .expect("remove original line");
+        sqlx::query(
+            "INSERT INTO quote_line
+                (id, quote_id, product_id, quantity, unit_price, subtotal, discount_pct, notes, created_at, updated_at)
+             VALUES ('QL-SF-NEW', 'Q-CRM-SF-001', 'plan-pro', 5, 90.0, 450.0, 10.0, NULL, ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db_pool)
+        .await
+        .expect("add replacement line");
+
+        let updated = sync_provider(&state, "Q-CRM-SF-001", "salesforce").await;
+        assert_eq!(updated.status, "success", "{}", updated.message);
+        assert!(updated.message.contains("1 line items created, 0 updated, 1 removed"));
+
+        let requests = stub.take_requests();
+        let patch = find_request(
+            &requests,
+            Method::PATCH,
+            &format!("/sobjects/Opportunity/{opportunity_id}"),
+        );
+        assert_eq!(patch.body["StageName"], "Closed Won");
+        assert_eq!(patch.body["Amount"], 450.0);
+        assert!(patch.body.get("Pricebook2Id").is_none(), "pricebook is only set on create");
+        let new_line = find_request(&requests, Method::POST, "/sobjects/OpportunityLineItem");
+        assert_eq!(new_line.body["Discount"], 10.0);
+        find_request(
+            &requests,
+            Method::DELETE,
+            &format!("/sobjects/OpportunityLineItem/{first_line_id}"),
+        );
+
+        let links: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM crm_remote_object WHERE quote_id = ?")
+                .bind("Q-CRM-SF-001")
+                .fetch_one(&state.db_pool)
+                .await
+                .expect("count links");
+        assert_eq!(links, 2, "opportunity plus the replacement line item");
+        assert!(remote_id(&state, "opportunity_line_item", "QL-Q-CRM-SF-001").await.is_none());
+
+        state.db_pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn hubspot_sync_refreshes_expired_token_before_writing_deal_and_line_items() {
+        let (state, stub) = setup_state_with_stub().await;
+        seed_connected_integration(&state, "hubspot").await;
+        sqlx::query(
+            "UPDATE crm_integration
+             SET access_token = 'expired-token', refresh_token = 'refresh-1', token_expires_at = ?
+             WHERE provider = 'hubspot'",
+        )
+        .bind((Utc::now() - Duration::minutes(5)).to_rfc3339())
+        .execute(&state.db_pool)
+        .await
+        .expect("expire hubspot token");
+        seed_quote_with_line(&state, "Q-CRM-HS-001", "sent").await;
+
+        let attempt = sync_provider(&state, "Q-CRM-HS-001", "hubspot").await;
+        assert_eq!(attempt.status, "success", "{}", attempt.message);
+
+        let requests = stub.requests();
+        assert_eq!(requests[0].path, "/oauth/v1/token");
+        let form = requests[0].body.as_str().expect("token refresh is form encoded");
+        assert!(form.contains("grant_type=refresh_token"));
+        assert!(form.contains("refresh_token=refresh-1"));
+        assert!(form.contains("client_id=hs-client-id"));
+
+        let deal = find_request(&requests, Method::POST, "/crm/v3/objects/deals");
+        assert_eq!(deal.authorization.as_deref(), Some("Bearer refreshed-token"));
+        assert_eq!(deal.body["properties"]["dealname"], "Quote Q-CRM-HS-001");
+        assert_eq!(deal.body["properties"]["dealstage"], "contractsent");
+        assert_eq!(deal.body["properties"]["amount"], "200.0");
+        let deal_id = remote_id(&state, "deal", "Q-CRM-HS-001").await.expect("deal link");
+        let line = find_request(&requests, Method::POST, "/crm/v3/objects/line_items");
+        assert_eq!(line.body["properties"]["hs_sku"], "plan-pro");
+        assert_eq!(line.body["properties"]["quantity"], "2");
+        assert_eq!(line.body["associations"][0]["to"]["id"], deal_id.as_str());
+        assert_eq!(line.body["associations"][0]["types"][0]["associationTypeId"], 20);
+
+        let row = sqlx::query(
+            "SELECT access_token, refresh_token, token_expires_at FROM crm_integration
+             WHERE provider = 'hubspot'",
+        )
+        .fetch_one(&state.db_pool)
+        .await
+        .expect("fetch refreshed integration");
+        assert_eq!(row.try_get::<String, _>("access_token").expect("token"), "refreshed-token");
+        assert_eq!(row.try_get::<String, _>("refresh_token").expect("refresh"), "refresh-2");
+        let expires_at: String = row.try_get("token_expires_at").expect("expiry");
+        assert!(!super::has_expired_token(&Some(expires_at)));
+
+        state.db_pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn crm_sync_refreshes_on_unauthorized_and_classifies_provider_failures() {
+        let (mut state, stub) = setup_state_with_stub().await;
+        state.config.salesforce_login_url = Some(stub.base_url.clone());
+        seed_connected_integration(&state, "salesforce").await;
+        sqlx::query("UPDATE crm_integration SET refresh_token = 'refresh-1'")
+            .execute(&state.db_pool)
+            .await
+            .expect("add refresh token");
+        seed_quote_with_line(&state, "Q-CRM-SF-002", "draft").await;
+
+        stub.fail_next(StatusCode::UNAUTHORIZED);
+        let refreshed = sync_provider(&state, "Q-CRM-SF-002", "salesforce").await;
+        assert_eq!(refreshed.status, "success", "{}", refreshed.message);
+        let requests = stub.take_requests();
+        assert_eq!(requests[0].authorization.as_deref(), Some("Bearer token-123"));
+        assert_eq!(requests[1].path, "/services/oauth2/token");
+        assert!(requests[2..]
+            .iter()
+            .all(|request| request.authorization.as_deref() == Some("Bearer refreshed-token")));
+
+        stub.fail_next(StatusCode::TOO_MANY_REQUESTS);
+        let throttled = sync_provider(&state, "Q-CRM-SF-002", "salesforce").await;
+        assert_eq!(throttled.status, "retrying");
+        assert!(throttled.message.contains("429"), "{}", throttled.message);
+
+        stub.fail_next(StatusCode::BAD_REQUEST);
+        let rejected = sync_provider(&state, "Q-CRM-SF-002", "salesforce").await;
+        assert_eq!(rejected.status, "failed");
+        assert!(rejected.message.contains("400 Bad Request"), "{}", rejected.message);
+        let integration_status: String =
+            sqlx::query_scalar("SELECT status FROM crm_integration WHERE provider = 'salesforce'")
+                .fetch_one(&state.db_pool)
+                .await
+                .expect("integration status");
+        assert_eq!(integration_status, "error");
+
+        state.db_pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn webhook_conflicts_skip_stale_changes_and_keep_unsynced_quote_edits() {
+        let state = setup_state().await;
+        seed_connected_integration(&state, "hubspot").await;
+        seed_quote_with_line(&state, "Q-CRM-HS-002", "sent").await;
+        let pushed = sync_provider(&state, "Q-CRM-HS-002", "hubspot").await;
+        assert_eq!(pushed.status, "success", "{}", pushed.message);
+        let deal_id = remote_id(&state, "deal", "Q-CRM-HS-002").await.expect("deal link");
+
+        let change = |stage: &str, account_id: Option<&str>, modified: chrono::DateTime<Utc>| {
+            WebhookPayload {
+                quote_id: None,
+                account_id: account_id.map(str::to_string),
+                deal_id: Some(deal_id.clone()),
+                stage: Some(stage.to_string()),
+                status: None,
+                notes: None,
+                contact_id: None,
+                contact_email: None,
+                contact_name: None,
+                event_type: Some("deal.propertyChange".to_string()),
+                payload: HashMap::from([(
+                    "hs_lastmodifieddate".to_string(),
+                    Value::String(modified.timestamp_millis().to_string()),
+                )]),
+            }
+        };
+        let ingest = |payload: WebhookPayload| {
+            webhook_ingest(
+                Path("hubspot".to_string()),
+                State(state.clone()),
+                HeaderMap::new(),
+                Json(payload),
+            )
+        };
+        let quote_field = |field: &'static str| {
+            let pool = state.db_pool.clone();
+            async move {
+                sqlx::query_scalar::<_, String>(&format!(
+                    "SELECT {field} FROM quote WHERE id = 'Q-CRM-HS-002'"
+                ))
+                .fetch_one(&pool)
+                .await
+                .expect("fetch quote field")
+            }
+        };
+
+        let (_, Json(stale)) = ingest(change("rejected", None, Utc::now() - Duration::hours(1)))
+            .await
+            .expect("stale webhook");
+        assert_eq!(stale.quote_id.as_deref(), Some("Q-CRM-HS-002"));
+        assert_eq!(stale.status, "skipped");
+        assert_eq!(
+            stale.error_message.as_deref(),
+            Some("remote change predates the last quotey sync")
+        );
+        assert_eq!(quote_field("status").await, "sent");
+
+        sqlx::query("UPDATE quote SET notes = 'local edit', updated_at = ? WHERE id = ?")
+            .bind((Utc::now() + Duration::seconds(5)).to_rfc3339())
+            .bind("Q-CRM-HS-002")
+            .execute(&state.db_pool)
+            .await
+            .expect("edit quote locally");
+        let (_, Json(raced)) =
+            ingest(change("rejected", Some("A-HS-NEW"), Utc::now() + Duration::seconds(10)))
+                .await
+                .expect("conflicting webhook");
+        assert_eq!(raced.status, "success");
+        assert!(raced.error_message.as_deref().is_some_and(|m| m.contains("kept quotey status")));
+        assert_eq!(quote_field("status").await, "sent");
+        assert_eq!(quote_field("notes").await, "local edit");
+        assert_eq!(quote_field("account_id").await, "A-HS-NEW");
+
+        let newer = Utc::now() + Duration::seconds(20);
+        let (_, Json(applied)) =
+            ingest(change("rejected", None, newer)).await.expect("newer webhook");
+        assert_eq!(applied.status, "success");
+        assert_eq!(quote_field("status").await, "rejected");
+
+        let (_, Json(redelivered)) =
+            ingest(change("approved", None, newer)).await.expect("redelivered webhook");
+        assert_eq!(redelivered.status, "skipped");
+        assert_eq!(quote_field("status").await, "rejected");
+
+        let outcome: String = sqlx::query_scalar(
+            "SELECT json_extract(payload_json, '$.conflict_outcome') FROM crm_sync_event WHERE id = ?",
+        )
+        .bind(&raced.id)
+        .fetch_one(&state.db_pool)
+        .await
+        .expect("conflict outcome");
+        assert_eq!(outcome, "quotey_wins");
+        let resolved: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM audit_event
+             WHERE quote_id = 'Q-CRM-HS-002' AND event_type = 'crm.conflict_resolved'",
+        )
+        .fetch_one(&state.db_pool)
+        .await
+        .expect("count conflict audits");
+        assert_eq!(resolved, 3);
+
+        state.db_pool.close().await;
+    }
 }
@@ -0,0 +1,1003 @@
+//! Salesforce REST and HubSpot CRM v3 write clients.
+//!
+//! A quote is pushed as a Salesforce Opportunity or HubSpot Deal plus one line item per quote
+//! line. Provider defaults (name, stage, close date, amount) are applied first and the active
+//! `quotey_to_crm` rows of `crm_field_mapping` are layered on top. Remote ids are kept in
+//! `crm_remote_object`, so later syncs update the same objects and delete line items whose quote
+//! line was removed.
+//!
+//! Expired access tokens are refreshed before the first request, and a 401 triggers one refresh
+//! per attempt; refreshed tokens are persisted on `crm_integration`. HTTP failures are classified
+//! for the execution queue: transport errors, 408, 429 and 5xx are retryable, other 4xx are
+//! terminal.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Map, Value};
+use sqlx::Row;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+    fetch_mappings, has_expired_token, mapped_field_value, normalize_quotey_field_for_direction,
+    set_nested_value, CrmDirection, CrmFieldMapping, CrmIntegration, CrmProvider, CrmState,
+    OAuthTokenResponse, ProviderConfig, SyncAttemptResult, SyncAttemptResultKind,
+};
+
+const SALESFORCE_API_VERSION: &str = "v59.0";
+/// HubSpot-defined association type id for line item → deal.
+const HUBSPOT_LINE_ITEM_TO_DEAL_ASSOCIATION_TYPE: u32 = 20;
+const DEFAULT_CLOSE_DATE_DAYS: i64 = 30;
+const MAX_ERROR_BODY_CHARS: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RemoteObject {
+    Opportunity,
+    OpportunityLineItem,
+    Deal,
+    LineItem,
+}
+
+impl RemoteObject {
+    pub(super) fn parent(provider: CrmProvider) -> Self {
+        match provider {
+            CrmProvider::Salesforce => Self::Opportunity,
+            CrmProvider::Hubspot => Self::Deal,
+        }
+    }
+
+    fn line(provider: CrmProvider) -> Self {
+        match provider {
+            CrmProvider::Salesforce => Self::OpportunityLineItem,
+            CrmProvider::Hubspot => Self::LineItem,
+        }
+    }
+
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Opportunity => "opportunity",
+            Self::OpportunityLineItem => "opportunity_line_item",
+            Self::Deal => "deal",
+            Self::LineItem => "line_item",
+        }
+    }
+
+    fn path(&self, id: Option<&str>) -> String {
+        let collection = match self {
+            Self::Opportunity => {
+                format!("/services/data/{SALESFORCE_API_VERSION}/sobjects/Opportunity")
+            }
+            Self::OpportunityLineItem => {
+                format!("/services/data/{SALESFORCE_API_VERSION}/sobjects/OpportunityLineItem")
+            }
+            Self::Deal => "/crm/v3/objects/deals".to_string(),
+            Self::LineItem => "/crm/v3/objects/line_items".to_string(),
+        };
+        match id {
+            Some(id) => format!("{collection}/{id}"),
+            None => collection,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct RemoteLink {
+    pub(super) object_type: String,
+    pub(super) local_id: String,
+    pub(super) remote_id: String,
+    pub(super) last_pushed_at: Option<String>,
+    pub(super) remote_modified_at: Option<String>,
+    pub(super) updated_at: String,
+}
+
+#[derive(Debug, Default)]
+struct PushSummary {
+    created: usize,
+    updated: usize,
+    removed: usize,
+}
+
+/// Creates or updates the remote Opportunity/Deal and line items for an outbound sync payload.
+pub(super) async fn push_quote(
+    state: &CrmState,
+    integration: &CrmIntegration,
+    payload: &Value,
+) -> Result<String, SyncAttemptResult> {
+    let provider = integration.provider;
+    let quote_id = payload.get("quote_id").and_then(Value::as_str).unwrap_or_default();
+    let mappings = fetch_mappings(state, CrmDirection::QuoteyToCrm, Some(provider))
+        .await
+        .map_err(|(_, error)| retryable(error.0.error, "crm_mapping_lookup_failed"))?;
+    let links = load_links(state, provider, quote_id).await.map_err(link_store_error)?;
+
+    let mut session = RemoteSession { state, integration: integration.clone(), refreshed: false };
+    if has_expired_token(&integration.token_expires_at) {
+        session.refresh().await?;
+    }
+
+    let parent = RemoteObject::parent(provider);
+    let line_object = RemoteObject::line(provider);
+    let lines: Vec<&Value> = payload
+        .get("lines")
+        .and_then(Value::as_array)
+        .map(|lines| lines.iter().collect())
+        .unwrap_or_default();
+    let parent_link = links.iter().find(|link| link.object_type == parent.as_str());
+    let mut line_links: HashMap<&str, &RemoteLink> = links
+        .iter()
+        .filter(|link| link.object_type == line_object.as_str())
+        .map(|link| (link.local_id.as_str(), link))
+        .collect();
+
+    let mut pricebook = PricebookEntries::default();
+    if provider == CrmProvider::Salesforce {
+        let missing: Vec<String> = lines
+            .iter()
+            .filter(|line| !line_links.contains_key(line_local_id(line)))
+            .map(|line| line_product_code(line).to_string())
+            .collect();
+        pricebook.resolve(&mut session, &missing).await?;
+    }
+
+    let fields = parent_fields(provider, payload, &mappings);
+    let mut create_body = wrap_fields(provider, fields.clone());
+    if let Some(pricebook_id) = pricebook.pricebook_id() {
+        create_body["Pricebook2Id"] = json!(pricebook_id);
+    }
+    let (parent_id, parent_created) = upsert_object(
+        &mut session,
+        parent,
+        parent_link,
+        &wrap_fields(provider, fields),
+        Some(&create_body),
+    )
+    .await?;
+    let version = payload.get("version").and_then(Value::as_i64);
+    save_link(state, provider, parent, quote_id, quote_id, &parent_id, version)
+        .await
+        .map_err(link_store_error)?;
+    if parent_created {
+        // Line items belonged to the previous remote parent; recreate them under the new one.
+        for (local_id, _) in line_links.drain() {
+            delete_link(state, provider, line_object, local_id).await.map_err(link_store_error)?;
+        }
+    }
+
+    let mut summary = PushSummary::default();
+    for line in &lines {
+        let local_id = line_local_id(line);
+        let fields = line_fields(provider, line);
+        let update_body = wrap_fields(provider, fields.clone());
+        let updated = match line_links.remove(local_id) {
+            Some(link) => {
+                match upsert_object(&mut session, line_object, Some(link), &update_body, None).await
+                {
+                    Ok(result) => Some(result),
+                    Err(failure) if failure.error_class.as_deref() == Some("crm_not_found") => None,
+                    Err(failure) => return Err(failure),
+                }
+            }
+            None => None,
+        };
+        let (remote_id, created) = match updated {
+            Some(result) => result,
+            None => {
+                if provider == CrmProvider::Salesforce {
+                    let code = line_product_code(line).to_string();
+                    pricebook.resolve(&mut session, &[code]).await?;
+                }
+                let create_body =
+                    line_create_body(provider, &fields, &parent_id, &pricebook, line)?;
+                upsert_object(&mut session, line_object, None, &update_body, Some(&create_body))
+                    .await?
+            }
+        };
+        save_link(state, provider, line_object, quote_id, local_id, &remote_id, version)
+            .await
+            .map_err(link_store_error)?;
+        if created {
+            summary.created += 1;
+        } else {
+            summary.updated += 1;
+        }
+    }
+
+    for stale in line_links.into_values() {
+        match session
+            .send(Method::DELETE, &line_object.path(Some(&stale.remote_id)), &[], None)
+            .await
+        {
+            Ok(_) => {}
+            Err(failure) if failure.error_class.as_deref() == Some("crm_not_found") => {}
+            Err(failure) => return Err(failure),
+        }
+        delete_link(state, provider, line_object, &stale.local_id)
+            .await
+            .map_err(link_store_error)?;
+        summary.removed += 1;
+    }
+
+    Ok(format!(
+        "{provider} {} `{parent_id}` {}: {} line items created, {} updated, {} removed",
+        parent.as_str(),
+        if parent_created { "created" } else { "updated" },
+        summary.created,
+        summary.updated,
+        summary.removed,
+    ))
+}
+
+struct RemoteSession<'a> {
+    state: &'a CrmState,
+    integration: CrmIntegration,
+    refreshed: bool,
+}
+
+impl RemoteSession<'_> {
+    fn base_url(&self) -> Result<String, SyncAttemptResult> {
+        match self.integration.provider {
+            CrmProvider::Salesforce => self
+                .integration
+                .instance_url
+                .as_deref()
+                .map(|url| url.trim().trim_end_matches('/'))
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    terminal(
+                        "salesforce integration has no instance_url; reconnect the integration",
+                        "crm_missing_instance_url",
+                    )
+                }),
+            CrmProvider::Hubspot => Ok(self.state.config.hubspot_api_base_url().to_string()),
+        }
+    }
+
+    async fn send(
+        &mut self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<Option<Value>, SyncAttemptResult> {
+        let provider = self.integration.provider;
+        loop {
+            let token_type = match self.integration.token_type.trim() {
+                "" => "Bearer",
+                value => value,
+            };
+            let mut request = self
+                .state
+                .client
+                .request(method.clone(), format!("{}{path}", self.base_url()?))
+                .header("Authorization", format!("{token_type} {}", self.integration.access_token));
+            if !query.is_empty() {
+                request = request.query(query);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let response = request.send().await.map_err(|error| {
+                retryable(
+                    format!("{provider} {method} {path} failed: {error}"),
+                    "crm_transport_error",
+                )
+            })?;
+            let status = response.status();
+            if status == StatusCode::UNAUTHORIZED
+                && !self.refreshed
+                && self.integration.refresh_token.is_some()
+            {
+                self.refresh().await?;
+                continue;
+            }
+
+            let text = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                return Err(classify_http_failure(provider, &method, path, status, &text));
+            }
+            if text.trim().is_empty() {
+                return Ok(None);
+            }
+            return serde_json::from_str(&text).map(Some).map_err(|error| {
+                terminal(
+                    format!("{provider} {method} {path} returned invalid JSON: {error}"),
+                    "crm_invalid_response",
+                )
+            });
+        }
+    }
+
+    async fn refresh(&mut self) -> Result<(), SyncAttemptResult> {
+        refresh_access_token(self.state, &mut self.integration).await?;
+        self.refreshed = true;
+        Ok(())
+    }
+}
+
+async fn refresh_access_token(
+    state: &CrmState,
+    integration: &mut CrmIntegration,
+) -> Result<(), SyncAttemptResult> {
+    let provider = integration.provider;
+    let refresh_token = integration
+        .refresh_token
+        .clone()
+        .filter(|token| !token.trim().is_empty())
+        .ok_or_else(|| {
+            terminal("access token expired and refresh token is missing", "access_token_expired")
+        })?;
+    let (client_id, client_secret) = provider.credentials(&state.config).ok_or_else(|| {
+        terminal(
+            format!("crm credentials not configured for provider `{provider}`"),
+            "crm_missing_credentials",
+        )
+    })?;
+
+    let token_url = ProviderConfig::from_provider(provider, &state.config).token_url;
+    let response = state
+        .client
+        .post(&token_url)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+        ])
+        .send()
+        .await
+        .map_err(|error| {
+            retryable(
+                format!("{provider} token refresh failed: {error}"),
+                "crm_token_refresh_failed",
+            )
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = truncate_body(&response.text().await.unwrap_or_default());
+        let message = format!("{provider} token refresh returned {status}: {body}");
+        return Err(if is_retryable_status(status) {
+            retryable(message, "crm_token_refresh_failed")
+        } else {
+            terminal(format!("{message}; reconnect the integration"), "crm_refresh_rejected")
+        });
+    }
+
+    let token: OAuthTokenResponse = response.json().await.map_err(|error| {
+        terminal(
+            format!("failed to decode {provider} token refresh response: {error}"),
+            "crm_invalid_response",
+        )
+    })?;
+    if token.access_token.is_empty() {
+        return Err(terminal(
+            format!("{provider} token refresh returned an empty access token"),
+            "crm_invalid_response",
+        ));
+    }
+
+    let now = Utc::now();
+    let token_expires_at = token.expires_in.and_then(|seconds| {
+        now.checked_add_signed(Duration::seconds(seconds)).map(|value| value.to_rfc3339())
+    });
+    sqlx::query(
+        "UPDATE crm_integration
+         SET access_token = ?,
+             refresh_token = COALESCE(?, refresh_token),
+             instance_url = COALESCE(?, instance_url),
+             token_type = COALESCE(?, token_type),
+             token_expires_at = ?,
+             updated_at = ?
+         WHERE provider = ?",
+    )
+    .bind(&token.access_token)
+    .bind(token.refresh_token.as_deref())
+    .bind(token.instance_url.as_deref())
+    .bind(token.token_type.as_deref())
+    .bind(token_expires_at.as_deref())
+    .bind(now.to_rfc3339())
+    .bind(provider.as_str())
+    .execute(&state.db_pool)
+    .await
+    .map_err(link_store_error)?;
+
+    integration.access_token = token.access_token;
+    if let Some(refresh_token) = token.refresh_token {
+        integration.refresh_token = Some(refresh_token);
+    }
+    if let Some(instance_url) = token.instance_url {
+        integration.instance_url = Some(instance_url);
+    }
+    if let Some(token_type) = token.token_type {
+        integration.token_type = token_type;
+    }
+    integration.token_expires_at = token_expires_at;
+    info!(provider = provider.as_str(), "refreshed crm access token");
+    Ok(())
+}
+
+/// Salesforce line items must reference a PricebookEntry; entries are looked up in the standard
+/// pricebook by product code (the catalog SKU, or the product id when the product is unknown).
+#[derive(Debug, Default)]
+struct PricebookEntries {
+    by_code: HashMap<String, (String, String)>,
+}
+
+impl PricebookEntries {
+    async fn resolve(
+        &mut self,
+        session: &mut RemoteSession<'_>,
+        codes: &[String],
+    ) -> Result<(), SyncAttemptResult> {
+        let mut missing: Vec<&str> = codes
+            .iter()
+            .map(String::as_str)
+            .filter(|code| !self.by_code.contains_key(*code))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let literals: Vec<String> = missing.iter().map(|code| soql_literal(code)).collect();
+        let soql = format!(
+            "SELECT Id, Pricebook2Id, ProductCode FROM PricebookEntry \
+             WHERE IsActive = true AND Pricebook2.IsStandard = true AND ProductCode IN ({})",
+            literals.join(", ")
+        );
+        let path = format!("/services/data/{SALESFORCE_API_VERSION}/query");
+        let response = session.send(Method::GET, &path, &[("q", soql.as_str())], None).await?;
+        let records = response
+            .as_ref()
+            .and_then(|body| body.get("records"))
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for record in records {
+            let field = |name: &str| record.get(name).and_then(Value::as_str).map(str::to_string);
+            if let (Some(code), Some(id), Some(pricebook_id)) =
+                (field("ProductCode"), field("Id"), field("Pricebook2Id"))
+            {
+                self.by_code.entry(code).or_insert((id, pricebook_id));
+            }
+        }
+
+        match missing.iter().find(|code| !self.by_code.contains_key(**code)) {
+            Some(code) => Err(terminal(
+                format!(
+                    "salesforce has no active standard PricebookEntry for product code `{code}`"
+                ),
+                "crm_missing_pricebook_entry",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn entry_id(&self, code: &str) -> Option<&str> {
+        self.by_code.get(code).map(|(id, _)| id.as_str())
+    }
+
+    fn pricebook_id(&self) -> Option<&str> {
+        self.by_code.values().next().map(|(_, pricebook_id)| pricebook_id.as_str())
+    }
+}
+
+/// Updates the linked object, or creates it when there is no link or the linked object is gone.
+/// Without a `create_body` a missing object is reported as `crm_not_found` instead.
+/// Returns the remote id and whether the object was created.
+async fn upsert_object(
+    session: &mut RemoteSession<'_>,
+    object: RemoteObject,
+    existing: Option<&RemoteLink>,
+    update_body: &Value,
+    create_body: Option<&Value>,
+) -> Result<(String, bool), SyncAttemptResult> {
+    let provider = session.integration.provider;
+    if let Some(link) = existing {
+        match session
+            .send(Method::PATCH, &object.path(Some(&link.remote_id)), &[], Some(update_body))
+            .await
+        {
+            Ok(_) => return Ok((link.remote_id.clone(), false)),
+            Err(failure)
+                if failure.error_class.as_deref() == Some("crm_not_found")
+                    && create_body.is_some() =>
+            {
+                warn!(
+                    provider = provider.as_str(),
+                    object = object.as_str(),
+                    remote_id = %link.remote_id,
+                    "linked crm object no longer exists; recreating"
+                );
+            }
+            Err(failure) => return Err(failure),
+        }
+    }
+
+    let Some(create_body) = create_body else {
+        return Err(terminal(
+            format!("{provider} has no {} to update", object.as_str()),
+            "crm_not_found",
+        ));
+    };
+    let created = session.send(Method::POST, &object.path(None), &[], Some(create_body)).await?;
+    let remote_id = created
+        .as_ref()
+        .and_then(|body| body.get("id"))
+        .and_then(|id| match id {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| {
+            terminal(
+                format!("{provider} did not return an id for the created {}", object.as_str()),
+                "crm_invalid_response",
+            )
+        })?;
+    Ok((remote_id, true))
+}
+
+fn parent_fields(provider: CrmProvider, payload: &Value, mappings: &[CrmFieldMapping]) -> Value {
+    let text = |field: &str| payload.get(field).and_then(Value::as_str).filter(|v| !v.is_empty());
+    let quote_id = text("quote_id").unwrap_or_default();
+    let status = text("status").unwrap_or_default();
+    let amount = payload.get("total_amount").cloned().unwrap_or(json!(0.0));
+    let close_date = close_date(text("valid_until"));
+
+    let mut fields = match provider {
+        CrmProvider::Salesforce => json!({
+            "Name": format!("Quote {quote_id}"),
+            "StageName": stage_for_status(provider, status),
+            "CloseDate": close_date,
+            "Amount": amount,
+        }),
+        CrmProvider::Hubspot => json!({
+            "dealname": format!("Quote {quote_id}"),
+            "dealstage": stage_for_status(provider, status),
+            "pipeline": "default",
+            "closedate": format!("{close_date}T00:00:00Z"),
+            "amount": amount,
+        }),
+    };
+    if let Some(notes) = text("notes") {
+        let key = match provider {
+            CrmProvider::Salesforce => "Description",
+            CrmProvider::Hubspot => "description",
+        };
+        fields[key] = json!(notes);
+    }
+
+    for mapping in mappings {
+        if mapping.provider != provider || !mapping.is_active {
+            continue;
+        }
+        // Remote identity comes from crm_remote_object, never from the request body.
+        if mapping.crm_field.eq_ignore_ascii_case("id")
+            || mapping.crm_field.eq_ignore_ascii_case("hs_object_id")
+        {
+            continue;
+        }
+        let quotey_field =
+            normalize_quotey_field_for_direction(&mapping.quotey_field, CrmDirection::QuoteyToCrm);
+        // A plain status mapping carries the provider stage, not Quotey's lifecycle name.
+        let value = if quotey_field == "status" && mapping.expression.is_none() {
+            Some(json!(stage_for_status(provider, status)))
+        } else {
+            mapped_field_value(payload, mapping)
+        };
+        if let Some(value) = value {
+            set_nested_value(&mut fields, &mapping.crm_field, value);
+        }
+    }
+    fields
+}
+
+fn line_fields(provider: CrmProvider, line: &Value) -> Value {
+    let number = |field: &str| line.get(field).cloned().unwrap_or(json!(0));
+    let code = line_product_code(line);
+    let mut fields = match provider {
+        CrmProvider::Salesforce => json!({
+            "Quantity": number("quantity"),
+            "UnitPrice": number("unit_price"),
+            "Discount": number("discount_pct"),
+        }),
+        CrmProvider::Hubspot => json!({
+            "name": code,
+            "hs_sku": code,
+            "quantity": number("quantity"),
+            "price": number("unit_price"),
+            "hs_discount_percentage": number("discount_pct"),
+        }),
+    };
+    if let Some(notes) = line.get("notes").and_then(Value::as_str).filter(|v| !v.is_empty()) {
+        let key = match provider {
+            CrmProvider::Salesforce => "Description",
+            CrmProvider::Hubspot => "description",
+        };
+        fields[key] = json!(notes);
+    }
+    fields
+}
+
+fn line_create_body(
+    provider: CrmProvider,
+    fields: &Value,
+    parent_id: &str,
+    pricebook: &PricebookEntries,
+    line: &Value,
+) -> Result<Value, SyncAttemptResult> {
+    match provider {
+        CrmProvider::Salesforce => {
+            let code = line_product_code(line);
+            let entry_id = pricebook.entry_id(code).ok_or_else(|| {
+                terminal(
+                    format!("salesforce has no active standard PricebookEntry for product code `{code}`"),
+                    "crm_missing_pricebook_entry",
+                )
+            })?;
+            let mut body = fields.clone();
+            body["OpportunityId"] = json!(parent_id);
+            body["PricebookEntryId"] = json!(entry_id);
+            Ok(body)
+        }
+        CrmProvider::Hubspot => {
+            let mut body = wrap_fields(provider, fields.clone());
+            body["associations"] = json!([{
+                "to": { "id": parent_id },
+                "types": [{
+                    "associationCategory": "HUBSPOT_DEFINED",
+                    "associationTypeId": HUBSPOT_LINE_ITEM_TO_DEAL_ASSOCIATION_TYPE,
+                }],
+            }]);
+            Ok(body)
+        }
+    }
+}
+
+/// Salesforce sObject bodies are the fields themselves; HubSpot expects string `properties`.
+fn wrap_fields(provider: CrmProvider, fields: Value) -> Value {
+    match provider {
+        CrmProvider::Salesforce => fields,
+        CrmProvider::Hubspot => {
+            let properties: Map<String, Value> = fields
+                .as_object()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|(key, value)| {
+                            let value = match value {
+                                Value::Number(number) => Value::String(number.to_string()),
+                                Value::Bool(flag) => Value::String(flag.to_string()),
+                                other => other.clone(),
+                            };
+                            (key.clone(), value)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            json!({ "properties": properties })
+        }
+    }
+}
+
+fn stage_for_status(provider: CrmProvider, status: &str) -> &'static str {
+    let status = status.trim().to_ascii_lowercase();
+    match (provider, status.as_str()) {
+        (CrmProvider::Salesforce, "accepted") => "Closed Won",
+        (CrmProvider::Salesforce, "rejected" | "expired" | "cancelled") => "Closed Lost",
+        (CrmProvider::Salesforce, "sent") => "Negotiation/Review",
+        (CrmProvider::Salesforce, "approval" | "approved" | "finalized") => "Proposal/Price Quote",
+        (CrmProvider::Salesforce, _) => "Prospecting",
+        (CrmProvider::Hubspot, "accepted") => "closedwon",
+        (CrmProvider::Hubspot, "rejected" | "expired" | "cancelled") => "closedlost",
+        (CrmProvider::Hubspot, "sent") => "contractsent",
+        (CrmProvider::Hubspot, "approval" | "approved" | "finalized") => "presentationscheduled",
+        (CrmProvider::Hubspot, _) => "appointmentscheduled",
+    }
+}
+
+fn close_date(valid_until: Option<&str>) -> String {
+    valid_until
+        .and_then(|value| value.get(..10))
+        .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| (Utc::now() + Duration::days(DEFAULT_CLOSE_DATE_DAYS)).date_naive())
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn line_local_id(line: &Value) -> &str {
+    line.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+fn line_product_code(line: &Value) -> &str {
+    line.get("product_code")
+        .and_then(Value::as_str)
+        .filter(|code| !code.is_empty())
+        .or_else(|| line.get("product_id").and_then(Value::as_str))
+        .unwrap_or_default()
+}
+
+fn soql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+fn classify_http_failure(
+    provider: CrmProvider,
+    method: &Method,
+    path: &str,
+    status: StatusCode,
+    body: &str,
+) -> SyncAttemptResult {
+    let message = format!("{provider} {method} {path} returned {status}: {}", truncate_body(body));
+    match status {
+        StatusCode::UNAUTHORIZED => retryable(message, "crm_unauthorized"),
+        StatusCode::NOT_FOUND => terminal(message, "crm_not_found"),
+        StatusCode::TOO_MANY_REQUESTS => retryable(message, "crm_rate_limited"),
+        status if is_retryable_status(status) => retryable(message, "crm_server_error"),
+        _ => terminal(message, "crm_rejected"),
+    }
+}
+
+fn truncate_body(body: &str) -> String {
+    let body = body.trim();
+    match body.char_indices().nth(MAX_ERROR_BODY_CHARS) {
+        Some((index, _)) => format!("{}…", &body[..index]),
+        None => body.to_string(),
+    }
+}
+
+fn retryable(message: impl Into<String>, error_class: &str) -> SyncAttemptResult {
+    SyncAttemptResult {
+        kind: SyncAttemptResultKind::RetryableFailure,
+        message: message.into(),
+        error_class: Some(error_class.to_string()),
+    }
+}
+
+fn terminal(message: impl Into<String>, error_class: &str) -> SyncAttemptResult {
+    SyncAttemptResult {
+        kind: SyncAttemptResultKind::TerminalFailure,
+        message: message.into(),
+        error_class: Some(error_class.to_string()),
+    }
+}
+
+fn link_store_error(error: sqlx::Error) -> SyncAttemptResult {
+    retryable(format!("crm remote object store error: {error}"), "crm_link_store_error")
+}
+
+pub(super) async fn load_links(
+    state: &CrmState,
+    provider: CrmProvider,
+    quote_id: &str,
+) -> Result<Vec<RemoteLink>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT object_type, local_id, remote_id, last_pushed_at, remote_modified_at, updated_at
+         FROM crm_remote_object
+         WHERE provider = ? AND quote_id = ?
+         ORDER BY object_type, local_id",
+    )
+    .bind(provider.as_str())
+    .bind(quote_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RemoteLink {
+            object_type: row.try_get("object_type").unwrap_or_default(),
+            local_id: row.try_get("local_id").unwrap_or_default(),
+            remote_id: row.try_get("remote_id").unwrap_or_default(),
+            last_pushed_at: row.try_get("last_pushed_at").ok().flatten(),
+            remote_modified_at: row.try_get("remote_modified_at").ok().flatten(),
+            updated_at: row.try_get("updated_at").unwrap_or_default(),
+        })
+        .collect())
+}
+
+pub(super) async fn parent_link(
+    state: &CrmState,
+    provider: CrmProvider,
+    quote_id: &str,
+) -> Result<Option<RemoteLink>, sqlx::Error> {
+    let parent = RemoteObject::parent(provider).as_str();
+    Ok(load_links(state, provider, quote_id)
+        .await?
+        .into_iter()
+        .find(|link| link.object_type == parent))
+}
+
+/// Resolves the quote that owns a remote Opportunity/Deal id written by a previous push.
+pub(super) async fn quote_for_remote_id(
+    state: &CrmState,
+    provider: CrmProvider,
+    remote_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT quote_id FROM crm_remote_object
+         WHERE provider = ? AND object_type = ? AND remote_id = ?
+         ORDER BY updated_at DESC
+         LIMIT 1",
+    )
+    .bind(provider.as_str())
+    .bind(RemoteObject::parent(provider).as_str())
+    .bind(remote_id)
+    .fetch_optional(&state.db_pool)
+    .await
+}
+
+/// Records that an inbound change was applied to the quote linked to a remote parent object.
+pub(super) async fn mark_inbound_applied(
+    state: &CrmState,
+    provider: CrmProvider,
+    quote_id: &str,
+    remote_modified_at: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE crm_remote_object
+         SET remote_modified_at = COALESCE(?, remote_modified_at), updated_at = ?
+         WHERE provider = ? AND object_type = ? AND local_id = ?",
+    )
+    .bind(remote_modified_at)
+    .bind(Utc::now().to_rfc3339())
+    .bind(provider.as_str())
+    .bind(RemoteObject::parent(provider).as_str())
+    .bind(quote_id)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(())
+}
+
+async fn save_link(
+    state: &CrmState,
+    provider: CrmProvider,
+    object: RemoteObject,
+    quote_id: &str,
+    local_id: &str,
+    remote_id: &str,
+    pushed_version: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO crm_remote_object (
+            id, provider, object_type, quote_id, local_id, remote_id,
+            last_pushed_at, last_pushed_version, created_at, updated_at
+         )
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(provider, object_type, local_id) DO UPDATE SET
+            remote_id = excluded.remote_id,
+            last_pushed_at = excluded.last_pushed_at,
+            last_pushed_version = excluded.last_pushed_version,
+            updated_at = excluded.updated_at",
+    )
+    .bind(format!("CRMOBJ-{}", Uuid::new_v4().simple()))
+    .bind(provider.as_str())
+    .bind(object.as_str())
+    .bind(quote_id)
+    .bind(local_id)
+    .bind(remote_id)
+    .bind(&now)
+    .bind(pushed_version)
+    .bind(&now)
+    .bind(&now)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(())
+}
+
+async fn delete_link(
+    state: &CrmState,
+    provider: CrmProvider,
+    object: RemoteObject,
+    local_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM crm_remote_object WHERE provider = ? AND object_type = ? AND local_id = ?",
+    )
+    .bind(provider.as_str())
+    .bind(object.as_str())
+    .bind(local_id)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::{
+        classify_http_failure, close_date, parent_fields, soql_literal, stage_for_status,
+        wrap_fields,
+    };
+    use crate::crm::{CrmFieldMapping, CrmProvider, SyncAttemptResultKind};
+
+    #[test]
+    fn http_failures_split_into_retryable_and_terminal() {
+        let classify = |status| {
+            classify_http_failure(CrmProvider::Hubspot, &Method::PATCH, "/x", status, "nope")
+        };
+
+        for status in [
+            StatusCode::UNAUTHORIZED,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::REQUEST_TIMEOUT,
+            StatusCode::BAD_GATEWAY,
+        ] {
+            assert!(
+                matches!(classify(status).kind, SyncAttemptResultKind::RetryableFailure),
+                "{status} should be retryable"
+            );
+        }
+        let rejected = classify(StatusCode::BAD_REQUEST);
+        assert!(matches!(rejected.kind, SyncAttemptResultKind::TerminalFailure));
+        assert_eq!(rejected.error_class.as_deref(), Some("crm_rejected"));
+        assert!(rejected.message.contains("400 Bad Request: nope"));
+        assert_eq!(classify(StatusCode::NOT_FOUND).error_class.as_deref(), Some("crm_not_found"));
+    }
+
+    #[test]
+    fn parent_fields_layer_mappings_over_provider_defaults() {
+        let payload = json!({
+            "quote_id": "Q-9",
+            "status": "accepted",
+            "total_amount": 1200.5,
+            "term_months": 24,
+            "valid_until": "2026-12-31T00:00:00Z",
+        });
+        let mapping =
+            |quotey_field: &str, crm_field: &str, expression: Option<&str>| CrmFieldMapping {
+                provider: CrmProvider::Salesforce,
+                id: crm_field.to_string(),
+                quotey_field: quotey_field.to_string(),
+                crm_field: crm_field.to_string(),
+                description: None,
+                expression: expression.map(str::to_string),
+                is_active: true,
+            };
+        let mappings = vec![
+            mapping("term_months", "Term_Months__c", None),
+            mapping("status", "Quote_Stage__c", None),
+            mapping("quote_id", "Id", None),
+            mapping("quote_id", "Name", Some("Quotey ${quote_id}")),
+        ];
+
+        let fields = parent_fields(CrmProvider::Salesforce, &payload, &mappings);
+        assert_eq!(fields["Name"], "Quotey Q-9");
+        assert_eq!(fields["StageName"], "Closed Won");
+        assert_eq!(fields["Quote_Stage__c"], "Closed Won");
+        assert_eq!(fields["CloseDate"], "2026-12-31");
+        assert_eq!(fields["Amount"], 1200.5);
+        assert_eq!(fields["Term_Months__c"], 24);
+        assert!(fields.get("Id").is_none(), "identity fields are never written");
+
+        let deal =
+            wrap_fields(CrmProvider::Hubspot, parent_fields(CrmProvider::Hubspot, &payload, &[]));
+        assert_eq!(deal["properties"]["dealstage"], "closedwon");
+        assert_eq!(deal["properties"]["amount"], "1200.5");
+        assert_eq!(deal["properties"]["closedate"], "2026-12-31T00:00:00Z");
+    }
+
+    #[test]
+    fn stages_close_dates_and_soql_literals_are_normalized() {
+        assert_eq!(stage_for_status(CrmProvider::Salesforce, "draft"), "Prospecting");
+        assert_eq!(stage_for_status(CrmProvider::Hubspot, "Expired"), "closedlost");
+        assert_eq!(close_date(Some("2027-01-15")), "2027-01-15");
+        assert_eq!(close_date(Some("not a date")).len(), 10);
+        assert_eq!(soql_literal("o'brien\\x"), "'o\\'brien\\\\x'");
+    }
+}
@@ -0,0 +1,302 @@
+//! Data for the analytics dashboard (`templates/dashboard/analytics.html`).
+//!
+//! Every figure comes from [`AnalyticsService`] specs, so the dashboard shows the same numbers as
+//! `POST /api/v1/analytics/query`, the `analytics_query` MCP tool and `quotey analytics`. Summary
+//! deltas compare the selected window with the window of equal length just before it.
+
+use chrono::{Duration, NaiveDate, Utc};
+use quotey_core::{AnalyticsFilter, AnalyticsQuerySpec, DimensionKind, MetricKind};
+use quotey_db::analytics::{
+    AnalyticsCache, AnalyticsResult, AnalyticsService, AnalyticsServiceError,
+};
+use quotey_db::DbPool;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const DEFAULT_DAYS: u32 = 30;
+const TOP_ROWS: usize = 10;
+/// Approval turnaround that scores 0% on the approver performance bar.
+const APPROVAL_SLA_HOURS: f64 = 48.0;
+
+/// Filters sent by the dashboard; `all` (or nothing) means unfiltered.
+#[derive(Debug, Default, Deserialize)]
+pub struct DashboardQuery {
+    pub days: Option<u32>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub segment: Option<String>,
+    pub region: Option<String>,
+    pub rep: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DashboardError {
+    #[error("{0}")]
+    InvalidFilter(String),
+    #[error(transparent)]
+    Analytics(#[from] AnalyticsServiceError),
+}
+
+/// Resolved reporting window: `lookback_days` back from today, optionally ending early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Window {
+    lookback_days: u32,
+    end_date: Option<NaiveDate>,
+    span_days: u32,
+}
+
+impl DashboardQuery {
+    fn window(&self, today: NaiveDate) -> Result<Window, DashboardError> {
+        let parse = |field: &str, raw: &str| {
+            NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").map_err(|_| {
+                DashboardError::InvalidFilter(format!("{field} `{raw}` must be YYYY-MM-DD"))
+            })
+        };
+        match (self.start.as_deref(), self.end.as_deref()) {
+            (Some(start), Some(end)) => {
+                let (start, end) = (parse("start", start)?, parse("end", end)?);
+                if end < start || start > today {
+                    return Err(DashboardError::InvalidFilter(
+                        "start must not be after end or today".to_string(),
+                    ));
+                }
+                let lookback_days = (today - start).num_days() as u32 + 1;
+                let span_days = (end.min(today) - start).num_days() as u32 + 1;
+                Ok(Window { lookback_days, end_date: Some(end), span_days })
+            }
+            (None, None) => {
+                let days = self.days.unwrap_or(DEFAULT_DAYS);
+                Ok(Window { lookback_days: days, end_date: None, span_days: days })
+            }
+            _ => Err(DashboardError::InvalidFilter("start and end go together".to_string())),
+        }
+    }
+
+    fn filters(&self) -> Vec<AnalyticsFilter> {
+        [
+            (DimensionKind::CustomerSegment, &self.segment),
+            (DimensionKind::Region, &self.region),
+            (DimensionKind::SalesRep, &self.rep),
+        ]
+        .into_iter()
+        .filter_map(|(dimension, value)| {
+            let value = value.as_deref()?.trim();
+            (!value.is_empty() && value != "all")
+                .then(|| AnalyticsFilter { dimension, values: vec![value.to_string()] })
+        })
+        .collect()
+    }
+}
+
+struct Runner {
+    service: AnalyticsService,
+    window: Window,
+    filters: Vec<AnalyticsFilter>,
+}
+
+impl Runner {
+    async fn run(
+        &self,
+        metrics: Vec<MetricKind>,
+        dimensions: Vec<DimensionKind>,
+        window: Window,
+    ) -> Result<AnalyticsResult, DashboardError> {
+        let mut spec = AnalyticsQuerySpec::new(metrics, dimensions, window.lookback_days);
+        spec.end_date = window.end_date;
+        spec.filters = self.filters.clone();
+        Ok(self.service.run(&spec).await?)
+    }
+
+    /// The window of the same length ending the day before this one starts.
+    fn previous_window(&self, today: NaiveDate) -> Window {
+        let end = self.window.end_date.unwrap_or(today).min(today);
+        let start = end - Duration::days(i64::from(self.window.span_days) - 1);
+        let previous_end = start - Duration::days(1);
+        Window {
+            lookback_days: (today - previous_end).num_days() as u32
+                + self.window.span_days.saturating_sub(1),
+            end_date: Some(previous_end),
+            span_days: self.window.span_days,
+        }
+    }
+}
+
+/// Dashboard payload in the shape `analytics.html` renders and `updateDashboard()` consumes.
+pub async fn dashboard_data(
+    pool: &DbPool,
+    cache: &AnalyticsCache,
+    query: &DashboardQuery,
+) -> Result<Value, DashboardError> {
+    use DimensionKind::*;
+    use MetricKind::*;
+
+    let today = Utc::now().date_naive();
+    let window = query.window(today)?;
+    let runner = Runner {
+        service: AnalyticsService::new(pool.clone()).with_cache(cache.clone()),
+        window,
+        filters: query.filters(),
+    };
+
+    let summary_metrics = vec![QuoteCount, AvgDiscountPct, WinRatePct, TimeToFinalizeHours];
+    let summary = runner.run(summary_metrics.clone(), vec![], window).await?;
+    let previous = runner.run(summary_metrics, vec![], runner.previous_window(today)).await?;
+    let approvals = runner.run(vec![ApprovalCycleHours], vec![], window).await?;
+    let current = summary.rows.first();
+    let prior = previous.rows.first();
+    let metric = |row: Option<&serde_json::Map<String, Value>>, metric: MetricKind| {
+        row.and_then(|row| row.get(metric.as_str())).and_then(Value::as_f64).unwrap_or(0.0)
+    };
+    let change_pct = |metric_kind: MetricKind| {
+        let (now, before) = (metric(current, metric_kind), metric(prior, metric_kind));
+        if before == 0.0 {
+            0.0
+        } else {
+            round1((now - before) / before * 100.0)
+        }
+    };
+    let change_points =
+        |metric_kind: MetricKind| round1(metric(current, metric_kind) - metric(prior, metric_kind));
+
+    let pricing = runner.run(vec![AvgDiscountPct], vec![Week], window).await?;
+    let velocity = runner.run(vec![TimeToFinalizeHours], vec![Month], window).await?;
+    let segments = runner.run(vec![WinRatePct, QuoteCount], vec![CustomerSegment], window).await?;
+    let families = runner.run(vec![QuoteCount, AvgDealValue], vec![ProductFamily], window).await?;
+    let roles =
+        runner.run(vec![ApprovalCycleHours, QuoteCount], vec![ApprovalRole], window).await?;
+
+    let mut top_products: Vec<Value> = families
+        .rows
+        .iter()
+        .map(|row| {
+            json!({
+                "name": row[ProductFamily.as_str()],
+                "category": "Product family",
+                "quote_count": row[QuoteCount.as_str()],
+                "avg_deal_value": row[AvgDealValue.as_str()],
+                // No revenue or trend metric exists yet; the template reads both keys.
+                "revenue": Value::Null,
+                "trend": "flat",
+            })
+        })
+        .collect();
+    top_products.sort_by_key(|row| std::cmp::Reverse(row["quote_count"].as_i64().unwrap_or(0)));
+    top_products.truncate(TOP_ROWS);
+
+    let approver_stats: Vec<Value> = roles
+        .rows
+        .iter()
+        .filter(|row| row[ApprovalRole.as_str()] != "none")
+        .filter_map(|row| {
+            let hours = row[ApprovalCycleHours.as_str()].as_f64()?;
+            Some(json!({
+                "name": row[ApprovalRole.as_str()],
+                "role": "Approver role",
+                "avg_hours": hours,
+                "quote_count": row[QuoteCount.as_str()],
+                "pending": Value::Null,
+                "performance_pct":
+                    round1(((APPROVAL_SLA_HOURS - hours) / APPROVAL_SLA_HOURS * 100.0).clamp(0.0, 100.0)),
+            }))
+        })
+        .collect();
+
+    let truncated = [&summary, &pricing, &velocity, &segments, &families, &roles]
+        .iter()
+        .any(|result| result.truncated);
+
+    Ok(json!({
+        "metrics": {
+            "total_quotes": metric(current, QuoteCount),
+            "quotes_change_pct": change_pct(QuoteCount),
+            "avg_discount": metric(current, AvgDiscountPct),
+            "discount_change": change_points(AvgDiscountPct),
+            "win_rate": metric(current, WinRatePct),
+            "win_rate_change": change_points(WinRatePct),
+            "avg_close_days": round1(metric(current, TimeToFinalizeHours) / 24.0),
+            "close_time_change": change_pct(TimeToFinalizeHours),
+            "approval_wait_avg": metric(approvals.rows.first(), ApprovalCycleHours),
+        },
+        "pricing_history": {
+            "labels": column(&pricing, Week),
+            "discounts": metric_column(&pricing, AvgDiscountPct, 1.0),
+            "margins": [],
+        },
+        "velocity": {
+            "labels": column(&velocity, Month),
+            "days": metric_column(&velocity, TimeToFinalizeHours, 24.0),
+        },
+        "win_rate_by_segment": {
+            "labels": column(&segments, CustomerSegment),
+            "win_rates": metric_column(&segments, WinRatePct, 1.0),
+        },
+        "top_products": top_products,
+        "approver_stats": approver_stats,
+        "truncated": truncated,
+    }))
+}
+
+fn column(result: &AnalyticsResult, dimension: DimensionKind) -> Vec<Value> {
+    result.rows.iter().map(|row| row[dimension.as_str()].clone()).collect()
+}
+
+fn metric_column(result: &AnalyticsResult, metric: MetricKind, divisor: f64) -> Vec<Value> {
+    result
+        .rows
+        .iter()
+        .map(|row| {
+            row[metric.as_str()]
+                .as_f64()
+                .map_or(Value::Null, |value| round1(value / divisor).into())
+        })
+        .collect()
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_cover_presets_and_custom_ranges() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let preset = DashboardQuery { days: Some(7), ..DashboardQuery::default() };
+        assert_eq!(
+            preset.window(today).unwrap(),
+            Window { lookback_days: 7, end_date: None, span_days: 7 }
+        );
+
+        let custom = DashboardQuery {
+            start: Some("2026-09-01".to_string()),
+            end: Some("2026-09-30".to_string()),
+            ..DashboardQuery::default()
+        };
+        let window = custom.window(today).unwrap();
+        assert_eq!(window.lookback_days, 48);
+        assert_eq!(window.end_date, NaiveDate::from_ymd_opt(2026, 9, 30));
+        assert_eq!(window.span_days, 30);
+
+        let half = DashboardQuery { start: Some("2026-09-01".into()), ..DashboardQuery::default() };
+        assert!(half.window(today).is_err());
+    }
+
+    #[test]
+    fn all_means_unfiltered() {
+        let query = DashboardQuery {
+            segment: Some("all".to_string()),
+            region: Some("emea".to_string()),
+            rep: Some(" ".to_string()),
+            ..DashboardQuery::default()
+        };
+        assert_eq!(
+            query.filters(),
+            vec![AnalyticsFilter {
+                dimension: DimensionKind::Region,
+                values: vec!["emea".to_string()]
+            }]
+        );
+    }
+}
@@ -0,0 +1,1203 @@
+//! Email integration adapter: SMTP delivery and inbound email-to-quote.
+//!
+//! An `integration_config` row with `adapter_type = "email"` carries the adapter settings as
+//! JSON in `adapter_config` (see [`EmailAdapterConfig`]). The adapter:
+//! - delivers queued `email.send` execution tasks as MIME mail over SMTP (STARTTLS, implicit
+//!   TLS, or plaintext for local relays), with optional Tera wrappers for the text and HTML
+//!   bodies and the file at `attachment_path` attached; `email.signature_code` tasks get their
+//!   one-time code minted and rendered at send time, so it is never stored
+//! - polls an IMAP mailbox or a local maildir for unseen messages (see [`mailbox`]) and hands
+//!   them to [`intake`], which runs requirement extraction on RFQ emails and opens draft quotes
+//!   using the LLM provider from the `[llm]` config; without a usable provider the inbound
+//!   mailbox is left alone and the integration's status message says so
+//!
+//! Sent and received messages are recorded in `email_message` by Message-ID, so a customer
+//! reply to a quote email joins that quote's thread instead of opening a new draft.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use quotey_agent::llm::LlmClient;
+use quotey_core::domain::execution::{
+    ExecutionTask, ExecutionTaskState, IdempotencyRecord, IdempotencyRecordState,
+};
+use quotey_core::domain::integration::{AdapterTestResult, AdapterType, IntegrationConfig};
+use quotey_core::domain::outbox::OutboxOperation;
+use quotey_core::services::{AdapterError, AdapterPayload, AdapterResult, IntegrationAdapter};
+use quotey_core::{
+    DeterministicExecutionEngine, ExecutionEngineConfig, ExecutionTaskId, RetryPolicy,
+};
+use quotey_db::esign::{QuoteSignatureService, SignatureServiceError};
+use quotey_db::repositories::{
+    ExecutionQueueRepository, IdempotencyRepository, IntegrationConfigRepository, RepositoryError,
+    SqlExecutionQueueRepository, SqlIntegrationConfigRepository,
+};
+use quotey_db::DbPool;
+use serde::Deserialize;
+use serde_json::json;
+use tera::{Context, Tera};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+mod intake;
+mod mailbox;
+
+pub use intake::{IntakeOutcome, IntakeStatus};
+use mailbox::InboundEmail;
+
+const EMAIL_SEND_OPERATION_KIND: &str = "email.send";
+const SIGNATURE_CODE_OPERATION_KIND: &str = "email.signature_code";
+const EMAIL_WORKER_ID: &str = "email-worker";
+const DEFAULT_SMTP_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 60;
+const DELIVERY_BATCH_SIZE: i64 = 25;
+const INBOUND_BATCH_SIZE: usize = 25;
+const INBOUND_DISABLED_MESSAGE: &str =
+    "inbound email intake is disabled: no LLM client is available for requirement extraction";
+
+/// Settings stored as JSON in `integration_config.adapter_config` for the email adapter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailAdapterConfig {
+    /// Sender mailbox, e.g. `"Quotey <quotes@example.com>"`.
+    pub from: String,
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    pub smtp: SmtpSettings,
+    #[serde(default)]
+    pub templates: EmailTemplates,
+    /// Mailbox polled for RFQ emails; outbound-only when absent.
+    #[serde(default)]
+    pub inbound: Option<InboundSource>,
+    /// Words that mark an inbound email as a request for quote; defaults to
+    /// [`intake::DEFAULT_RFQ_KEYWORDS`].
+    #[serde(default)]
+    pub rfq_keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub poll_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// Plain connection upgraded with STARTTLS (SMTP 587, IMAP 143).
+    #[default]
+    Starttls,
+    /// TLS from the first byte (SMTP 465, IMAP 993).
+    Tls,
+    /// No TLS; only for local relays and test servers.
+    None,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: TlsMode,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Environment variable holding the password, preferred over storing it in the row.
+    #[serde(default)]
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl SmtpSettings {
+    fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            TlsMode::Starttls => 587,
+            TlsMode::Tls => 465,
+            TlsMode::None => 25,
+        })
+    }
+}
+
+/// Tera templates wrapped around outgoing bodies. Both see `subject`, `to`, `body_text` and
+/// `body_html`; the HTML template is autoescaped, so use `{{ body_html | safe }}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailTemplates {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub html: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InboundSource {
+    Imap(ImapSettings),
+    Maildir { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImapSettings {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default = "default_imap_tls")]
+    pub tls: TlsMode,
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub password_env: Option<String>,
+    #[serde(default = "default_imap_mailbox")]
+    pub mailbox: String,
+}
+
+impl ImapSettings {
+    fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            TlsMode::Tls => 993,
+            TlsMode::Starttls | TlsMode::None => 143,
+        })
+    }
+}
+
+fn default_imap_tls() -> TlsMode {
+    TlsMode::Tls
+}
+
+fn default_imap_mailbox() -> String {
+    "INBOX".to_string()
+}
+
+impl EmailAdapterConfig {
+    pub fn parse(config: &IntegrationConfig) -> Result<Self, AdapterError> {
+        if config.adapter_type != AdapterType::Email {
+            return Err(AdapterError::NotConfigured(format!(
+                "integration `{}` uses adapter `{}`, not email",
+                config.id,
+                config.adapter_type.as_str()
+            )));
+        }
+        let parsed: Self = serde_json::from_str(&config.adapter_config).map_err(|error| {
+            AdapterError::NotConfigured(format!("invalid email adapter_config: {error}"))
+        })?;
+        parse_mailbox(&parsed.from)?;
+        if let Some(reply_to) = &parsed.reply_to {
+            parse_mailbox(reply_to)?;
+        }
+        if parsed.smtp.host.trim().is_empty() {
+            return Err(AdapterError::NotConfigured("smtp.host is required".to_string()));
+        }
+        Ok(parsed)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs.unwrap_or(DEFAULT_POLL_INTERVAL_SECS).max(5))
+    }
+}
+
+/// Resolves a password given inline or through an environment variable.
+fn resolve_password(
+    password: Option<&str>,
+    password_env: Option<&str>,
+) -> Result<Option<String>, AdapterError> {
+    if let Some(name) = password_env {
+        return std::env::var(name)
+            .map(Some)
+            .map_err(|_| AdapterError::NotConfigured(format!("password_env `{name}` is not set")));
+    }
+    Ok(password.map(str::to_string))
+}
+
+fn parse_mailbox(value: &str) -> Result<Mailbox, AdapterError> {
+    value
+        .parse::<Mailbox>()
+        .map_err(|error| AdapterError::NotConfigured(format!("invalid mailbox `{value}`: {error}")))
+}
+
+/// The `IntegrationAdapter` for `adapter_type = "email"`.
+pub struct EmailAdapter;
+
+#[async_trait]
+impl IntegrationAdapter for EmailAdapter {
+    fn name(&self) -> &str {
+        "email-smtp"
+    }
+
+    async fn send(
+        &self,
+        config: &IntegrationConfig,
+        payload: &AdapterPayload,
+    ) -> Result<AdapterResult, AdapterError> {
+        let settings = EmailAdapterConfig::parse(config)?;
+        let operation: OutboxOperation =
+            serde_json::from_str(&payload.data_json).map_err(|error| {
+                AdapterError::OperationFailed(format!("invalid email payload: {error}"))
+            })?;
+        let OutboxOperation::EmailSend { to, subject, body_text, body_html, attachment_path } =
+            operation
+        else {
+            return Err(AdapterError::Unsupported(format!(
+                "email adapter cannot run `{}`",
+                operation.kind()
+            )));
+        };
+
+        let message_id = new_message_id(&settings.from);
+        let message = build_message(
+            &settings,
+            &message_id,
+            &to,
+            &subject,
+            &body_text,
+            body_html.as_deref(),
+            attachment_path.as_deref().map(Path::new),
+        )
+        .await?;
+
+        let transport = smtp_transport(&settings.smtp)?;
+        transport.send(message).await.map_err(smtp_error)?;
+
+        Ok(AdapterResult {
+            result_json: json!({
+                "status": "sent",
+                "message_id": message_id,
+                "from": settings.from,
+                "to": to,
+                "subject": subject,
+            })
+            .to_string(),
+        })
+    }
+
+    async fn receive(
+        &self,
+        config: &IntegrationConfig,
+        limit: usize,
+    ) -> Result<Vec<String>, AdapterError> {
+        let settings = EmailAdapterConfig::parse(config)?;
+        let Some(source) = &settings.inbound else {
+            return Ok(vec![]);
+        };
+
+        let mut received = Vec::new();
+        for raw in mailbox::fetch_unseen(source, limit).await? {
+            match mailbox::parse_inbound(&raw) {
+                Some(email) => received.push(serde_json::to_string(&email).map_err(|error| {
+                    AdapterError::OperationFailed(format!("cannot encode inbound email: {error}"))
+                })?),
+                None => warn!(
+                    integration_id = %config.id,
+                    bytes = raw.len(),
+                    "skipping inbound email that is not valid MIME"
+                ),
+            }
+        }
+        Ok(received)
+    }
+
+    async fn test(&self, config: &IntegrationConfig) -> Result<AdapterTestResult, AdapterError> {
+        let settings = EmailAdapterConfig::parse(config)?;
+        let transport = smtp_transport(&settings.smtp)?;
+        let started = Instant::now();
+        let ok = transport.test_connection().await.map_err(smtp_error)?;
+        Ok(AdapterTestResult {
+            ok,
+            latency_ms: started.elapsed().as_millis() as u64,
+            message: if ok {
+                format!(
+                    "SMTP server {}:{} accepted the connection",
+                    settings.smtp.host,
+                    settings.smtp.port()
+                )
+            } else {
+                format!(
+                    "SMTP server {}:{} did not respond",
+                    settings.smtp.host,
+                    settings.smtp.port()
+                )
+            },
+        })
+    }
+}
+
+fn smtp_transport(
+    settings: &SmtpSettings,
+) -> Result<AsyncSmtpTransport<Tokio1Executor>, AdapterError> {
+    let builder = match settings.tls {
+        TlsMode::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host),
+        TlsMode::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host),
+        TlsMode::None => {
+            Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.host.as_str()))
+        }
+    }
+    .map_err(|error| AdapterError::NotConfigured(format!("invalid smtp host: {error}")))?;
+
+    let mut builder = builder.port(settings.port()).timeout(Some(Duration::from_secs(
+        settings.timeout_secs.unwrap_or(DEFAULT_SMTP_TIMEOUT_SECS),
+    )));
+    if let Some(username) = &settings.username {
+        let password =
+            resolve_password(settings.password.as_deref(), settings.password_env.as_deref())?
+                .unwrap_or_default();
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+/// Permanent SMTP replies (5xx) will not succeed on retry; everything else (4xx, connection
+/// and TLS failures, timeouts) might.
+fn smtp_error(error: lettre::transport::smtp::Error) -> AdapterError {
+    if error.is_permanent() {
+        AdapterError::OperationFailed(format!("smtp rejected message: {error}"))
+    } else {
+        AdapterError::ConnectionFailed(format!("smtp delivery failed: {error}"))
+    }
+}
+
+fn new_message_id(from: &str) -> String {
+    let domain = from
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim_end_matches('>').trim())
+        .filter(|domain| !domain.is_empty())
+        .unwrap_or("quotey.local");
+    format!("{}@{domain}", Uuid::new_v4().simple())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_message(
+    settings: &EmailAdapterConfig,
+    message_id: &str,
+    to: &[String],
+    subject: &str,
+    body_text: &str,
+    body_html: Option<&str>,
+    attachment_path: Option<&Path>,
+) -> Result<Message, AdapterError> {
+    if to.is_empty() {
+        return Err(AdapterError::OperationFailed("email has no recipients".to_string()));
+    }
+
+    let mut builder = Message::builder()
+        .from(parse_mailbox(&settings.from)?)
+        .subject(subject)
+        .message_id(Some(format!("<{message_id}>")));
+    if let Some(reply_to) = &settings.reply_to {
+        builder = builder.reply_to(parse_mailbox(reply_to)?);
+    }
+    for recipient in to {
+        let mailbox = recipient.parse::<Mailbox>().map_err(|error| {
+            AdapterError::OperationFailed(format!("invalid recipient `{recipient}`: {error}"))
+        })?;
+        builder = builder.to(mailbox);
+    }
+
+    let (text, html) = render_bodies(&settings.templates, to, subject, body_text, body_html)?;
+    let body = match html {
+        Some(html) => MultiPart::alternative_plain_html(text, html),
+        None => MultiPart::mixed().singlepart(SinglePart::plain(text)),
+    };
+    let body = match attachment_path {
+        Some(path) => MultiPart::mixed().multipart(body).singlepart(load_attachment(path).await?),
+        None => body,
+    };
+
+    builder.multipart(body).map_err(|error| {
+        AdapterError::OperationFailed(format!("cannot build email message: {error}"))
+    })
+}
+
+fn render_bodies(
+    templates: &EmailTemplates,
+    to: &[String],
+    subject: &str,
+    body_text: &str,
+    body_html: Option<&str>,
+) -> Result<(String, Option<String>), AdapterError> {
+    let mut context = Context::new();
+    context.insert("subject", subject);
+    context.insert("to", to);
+    context.insert("body_text", body_text);
+    context.insert("body_html", body_html.unwrap_or_default());
+
+    let render = |template: &str, autoescape: bool| {
+        Tera::one_off(template, &context, autoescape).map_err(|error| {
+            AdapterError::NotConfigured(format!("email template failed to render: {error}"))
+        })
+    };
+    let text = match &templates.text {
+        Some(template) => render(template, false)?,
+        None => body_text.to_string(),
+    };
+    let html = match &templates.html {
+        Some(template) => Some(render(template, true)?),
+        None => body_html.map(str::to_string),
+    };
+    Ok((text, html))
+}
+
+async fn load_attachment(path: &Path) -> Result<SinglePart, AdapterError> {
+    let bytes = tokio::fs::read(path).await.map_err(|error| {
+        AdapterError::OperationFailed(format!(
+            "cannot read attachment `{}`: {error}",
+            path.display()
+        ))
+    })?;
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attachment".to_string());
+    let content_type = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("pdf") => "application/pdf",
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => "text/csv",
+        Some(ext) if ext.eq_ignore_ascii_case("txt") => "text/plain",
+        _ => "application/octet-stream",
+    };
+    let content_type = ContentType::parse(content_type).map_err(|error| {
+        AdapterError::OperationFailed(format!("invalid attachment content type: {error}"))
+    })?;
+    Ok(Attachment::new(filename).body(bytes, content_type))
+}
+
+/// Outcome of one queued `email.send` task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailDelivery {
+    pub task_id: ExecutionTaskId,
+    pub quote_id: String,
+    pub state: ExecutionTaskState,
+    pub message: String,
+}
+
+/// Sends every due `email.send` and `email.signature_code` task through `integration`, moving each through the execution
+/// engine so retries, backoff and terminal failures match the rest of the queue.
+pub async fn deliver_queued_emails(
+    pool: &DbPool,
+    integration: &IntegrationConfig,
+) -> Result<Vec<EmailDelivery>, RepositoryError> {
+    let task_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM execution_queue_task
+         WHERE operation_kind IN (?, ?) AND state IN ('queued', 'retryable_failed')
+           AND available_at <= ?
+         ORDER BY available_at ASC, created_at ASC
+         LIMIT ?",
+    )
+    .bind(EMAIL_SEND_OPERATION_KIND)
+    .bind(SIGNATURE_CODE_OPERATION_KIND)
+    .bind(Utc::now().to_rfc3339())
+    .bind(DELIVERY_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let repository = SqlExecutionQueueRepository::new(pool.clone());
+    let mut deliveries = Vec::with_capacity(task_ids.len());
+    for task_id in task_ids {
+        let Some(task) = repository.find_task_by_id(&ExecutionTaskId(task_id)).await? else {
+            continue;
+        };
+        deliveries.push(deliver_task(pool, &repository, integration, task).await?);
+    }
+    Ok(deliveries)
+}
+
+async fn deliver_task(
+    pool: &DbPool,
+    repository: &SqlExecutionQueueRepository,
+    integration: &IntegrationConfig,
+    task: ExecutionTask,
+) -> Result<EmailDelivery, RepositoryError> {
+    let policy = serde_json::from_str::<OutboxOperation>(&task.payload_json)
+        .map(|operation| operation.retry_policy())
+        .unwrap_or_default();
+    let engine = DeterministicExecutionEngine::with_config(ExecutionEngineConfig {
+        retry_base_delay_seconds: policy.base_delay_secs,
+        ..ExecutionEngineConfig::default()
+    });
+
+    let mut idempotency_record = match repository.find_operation(&task.idempotency_key).await? {
+        Some(record) => record,
+        None => new_idempotency_record(&task),
+    };
+    let claimed = match engine.claim_task(task.clone(), EMAIL_WORKER_ID, &mut idempotency_record) {
+        Ok(claimed) => claimed,
+        Err(error) => {
+            return Ok(EmailDelivery {
+                task_id: task.id,
+                quote_id: task.quote_id.0,
+                state: task.state,
+                message: error.to_string(),
+            });
+        }
+    };
+    repository.save_task(claimed.task.clone()).await?;
+    repository.append_transition(claimed.transition).await?;
+    repository.save_operation(idempotency_record.clone()).await?;
+    let task = claimed.task;
+
+    let sent = match render_payload(pool, &task.payload_json).await {
+        Ok(data_json) => {
+            let payload =
+                AdapterPayload { data_json, idempotency_key: Some(task.idempotency_key.0.clone()) };
+            EmailAdapter.send(integration, &payload).await
+        }
+        Err(error) => Err(error),
+    };
+    let transition = match sent {
+        Ok(result) => {
+            record_outbound(pool, integration, &task.quote_id.0, &result.result_json).await?;
+            let fingerprint = DeterministicExecutionEngine::hash_payload(&result.result_json);
+            engine.complete_task(task, fingerprint, &mut idempotency_record)
+        }
+        Err(error) => {
+            let (policy, error_class) = match &error {
+                AdapterError::ConnectionFailed(_) => {
+                    (RetryPolicy::Retry, "email_connection_failed")
+                }
+                AdapterError::OperationFailed(_) => (RetryPolicy::FailTerminal, "email_rejected"),
+                AdapterError::NotConfigured(_) => {
+                    (RetryPolicy::FailTerminal, "email_not_configured")
+                }
+                AdapterError::Unsupported(_) => (RetryPolicy::FailTerminal, "email_unsupported"),
+            };
+            warn!(task_id = %task.id.0, %error, error_class, "email delivery failed");
+            engine.fail_task(task, error.to_string(), error_class, policy, &mut idempotency_record)
+        }
+    }
+    .map_err(|error| RepositoryError::Decode(format!("email task transition failed: {error}")))?;
+
+    repository.append_transition(transition.transition.clone()).await?;
+    repository.save_task(transition.task.clone()).await?;
+    repository.save_operation(idempotency_record).await?;
+
+    let task = transition.task;
+    Ok(EmailDelivery {
+        message: task.last_error.clone().unwrap_or_else(|| "sent".to_string()),
+        task_id: task.id,
+        quote_id: task.quote_id.0,
+        state: task.state,
+    })
+}
+
+/// The email to send for a queued task. A signing-code task only names its signature request:
+/// the code is minted here and lives in the returned payload, which is sent and dropped.
+async fn render_payload(pool: &DbPool, payload_json: &str) -> Result<String, AdapterError> {
+    let Ok(OutboxOperation::SignatureCodeSend { signature_request_id, .. }) =
+        serde_json::from_str::<OutboxOperation>(payload_json)
+    else {
+        return Ok(payload_json.to_string());
+    };
+    let issued = QuoteSignatureService::new(pool.clone())
+        .issue_code(&signature_request_id)
+        .await
+        .map_err(|error| match error {
+        SignatureServiceError::Repository(error) => {
+            AdapterError::ConnectionFailed(format!("cannot issue signing code: {error}"))
+        }
+        error => AdapterError::OperationFailed(error.to_string()),
+    })?;
+    serde_json::to_string(&issued.email()).map_err(|error| {
+        AdapterError::OperationFailed(format!("cannot encode signing code email: {error}"))
+    })
+}
+
+/// Tasks queued straight into `execution_queue_task` (signing codes, for one) have no
+/// idempotency row yet; the first claim creates it.
+fn new_idempotency_record(task: &ExecutionTask) -> IdempotencyRecord {
+    let now = Utc::now();
+    IdempotencyRecord {
+        operation_key: task.idempotency_key.clone(),
+        quote_id: task.quote_id.clone(),
+        operation_kind: task.operation_kind.clone(),
+        payload_hash: DeterministicExecutionEngine::hash_payload(&task.payload_json),
+        state: IdempotencyRecordState::Reserved,
+        attempt_count: task.retry_count + 1,
+        first_seen_at: now,
+        last_seen_at: now,
+        result_snapshot_json: None,
+        error_snapshot_json: None,
+        expires_at: None,
+        correlation_id: task.id.0.clone(),
+        created_by_component: EMAIL_WORKER_ID.to_string(),
+        updated_by_component: EMAIL_WORKER_ID.to_string(),
+    }
+}
+
+async fn record_outbound(
+    pool: &DbPool,
+    integration: &IntegrationConfig,
+    quote_id: &str,
+    result_json: &str,
+) -> Result<(), RepositoryError> {
+    let result: serde_json::Value = serde_json::from_str(result_json)
+        .map_err(|error| RepositoryError::Decode(format!("invalid send result: {error}")))?;
+    let message_id = result["message_id"].as_str().unwrap_or_default();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO email_message
+            (id, integration_id, direction, message_id, thread_id, quote_id, from_address,
+             to_addresses_json, subject, status, occurred_at, created_at)
+         VALUES (?, ?, 'outbound', ?, ?, (SELECT id FROM quote WHERE id = ?), ?, ?, ?, 'sent', ?, ?)
+         ON CONFLICT(direction, message_id) DO NOTHING",
+    )
+    .bind(format!("EMAIL-{}", Uuid::new_v4().simple()))
+    .bind(&integration.id)
+    .bind(message_id)
+    .bind(message_id)
+    .bind(quote_id)
+    .bind(result["from"].as_str().unwrap_or_default())
+    .bind(result["to"].to_string())
+    .bind(result["subject"].as_str().unwrap_or_default())
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Polls the integration's inbound mailbox and runs each new message through intake.
+pub async fn poll_inbound(
+    pool: &DbPool,
+    integration: &IntegrationConfig,
+    llm: &dyn LlmClient,
+) -> Result<Vec<IntakeOutcome>, AdapterError> {
+    let settings = EmailAdapterConfig::parse(integration)?;
+    let intake = intake::EmailIntake::new(
+        pool,
+        llm,
+        &integration.id,
+        settings.rfq_keywords.clone().unwrap_or_else(|| {
+            intake::DEFAULT_RFQ_KEYWORDS.iter().map(|keyword| keyword.to_string()).collect()
+        }),
+    );
+
+    let mut outcomes = Vec::new();
+    for payload in EmailAdapter.receive(integration, INBOUND_BATCH_SIZE).await? {
+        let email: InboundEmail = serde_json::from_str(&payload).map_err(|error| {
+            AdapterError::OperationFailed(format!("invalid inbound email payload: {error}"))
+        })?;
+        match intake.process(&email).await {
+            Ok(Some(outcome)) => outcomes.push(outcome),
+            Ok(None) => {}
+            Err(error) => {
+                return Err(AdapterError::OperationFailed(format!(
+                    "cannot record inbound email `{}`: {error}",
+                    email.message_id
+                )))
+            }
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Runs delivery and inbound polling for every active email integration until the process
+/// exits. Inbound intake needs an LLM client for requirement extraction and is skipped
+/// without one.
+pub fn spawn(pool: DbPool, llm: Option<Arc<dyn LlmClient>>) -> tokio::task::JoinHandle<()> {
+    if llm.is_none() {
+        warn!("email worker started without an LLM client; inbound email intake is disabled");
+    }
+    tokio::spawn(async move {
+        loop {
+            let interval = run_once(&pool, llm.as_deref()).await;
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
+
+async fn run_once(pool: &DbPool, llm: Option<&dyn LlmClient>) -> Duration {
+    let repository = SqlIntegrationConfigRepository::new(pool.clone());
+    let integrations = match repository.list_all(true).await {
+        Ok(integrations) => integrations,
+        Err(error) => {
+            warn!(%error, "cannot list email integrations");
+            return Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS);
+        }
+    };
+
+    let mut interval = Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS);
+    for integration in integrations.iter().filter(|row| row.adapter_type == AdapterType::Email) {
+        let settings = match EmailAdapterConfig::parse(integration) {
+            Ok(settings) => settings,
+            Err(error) => {
+                let _ = repository
+                    .update_status(&integration.id, "error", Some(&error.to_string()))
+                    .await;
+                continue;
+            }
+        };
+        interval = interval.min(settings.poll_interval());
+
+        match deliver_queued_emails(pool, integration).await {
+            Ok(deliveries) if !deliveries.is_empty() => info!(
+                integration_id = %integration.id,
+                delivered = deliveries
+                    .iter()
+                    .filter(|delivery| delivery.state == ExecutionTaskState::Completed)
+                    .count(),
+                attempted = deliveries.len(),
+                "email delivery pass finished"
+            ),
+            Ok(_) => {}
+            Err(error) => {
+                warn!(integration_id = %integration.id, %error, "email delivery pass failed")
+            }
+        }
+
+        if settings.inbound.is_none() {
+            continue;
+        }
+        report_inbound_availability(&repository, integration, llm.is_some()).await;
+        if let Some(llm) = llm {
+            match poll_inbound(pool, integration, llm).await {
+                Ok(outcomes) if !outcomes.is_empty() => info!(
+                    integration_id = %integration.id,
+                    received = outcomes.len(),
+                    drafts = outcomes
+                        .iter()
+                        .filter(|outcome| outcome.status == IntakeStatus::DraftCreated)
+                        .count(),
+                    "inbound email pass finished"
+                ),
+                Ok(_) => {}
+                Err(error) => {
+                    warn!(integration_id = %integration.id, %error, "inbound email pass failed")
+                }
+            }
+        }
+    }
+    interval
+}
+
+/// Keeps the integration's status message in step with whether inbound intake can run, so an
+/// operator sees why a configured mailbox is not being read.
+async fn report_inbound_availability(
+    repository: &SqlIntegrationConfigRepository,
+    integration: &IntegrationConfig,
+    available: bool,
+) {
+    let current = integration.status_message.as_deref();
+    let wanted = match (available, current) {
+        (false, Some(INBOUND_DISABLED_MESSAGE)) | (true, None) => return,
+        (false, _) => Some(INBOUND_DISABLED_MESSAGE),
+        (true, Some(INBOUND_DISABLED_MESSAGE)) => None,
+        (true, Some(_)) => return,
+    };
+    if let Err(error) =
+        repository.update_status(&integration.id, integration.status.as_str(), wanted).await
+    {
+        warn!(integration_id = %integration.id, %error, "cannot record inbound intake status");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+    use quotey_core::domain::execution::ExecutionTaskState;
+    use quotey_core::domain::integration::{
+        AdapterStatus, AdapterType, IntegrationConfig, IntegrationType,
+    };
+    use quotey_core::domain::outbox::OutboxOperation;
+    use quotey_core::domain::quote::QuoteId;
+    use quotey_core::esign::SignerIdentity;
+    use quotey_core::services::{AdapterError, AdapterPayload, IntegrationAdapter};
+    use quotey_db::esign::{QuoteSignatureService, SignatureStart};
+    use quotey_db::repositories::{
+        ExecutionQueueRepository, IntegrationConfigRepository, SqlExecutionQueueRepository,
+        SqlIntegrationConfigRepository,
+    };
+    use quotey_db::DbPool;
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::{deliver_queued_emails, run_once, EmailAdapter, INBOUND_DISABLED_MESSAGE};
+
+    /// Minimal SMTP server: accepts every command, records each DATA payload, and answers
+    /// `RCPT TO` with `reject_rcpt` when set.
+    pub(crate) struct SmtpStandIn {
+        pub port: u16,
+        pub messages: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SmtpStandIn {
+        pub(crate) async fn start(reject_rcpt: Option<&'static str>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind smtp");
+            let port = listener.local_addr().expect("addr").port();
+            let messages = Arc::new(Mutex::new(Vec::new()));
+            let captured = messages.clone();
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    let captured = captured.clone();
+                    tokio::spawn(async move {
+                        let (read, mut write) = socket.into_split();
+                        let mut lines = BufReader::new(read);
+                        let _ = write.write_all(b"220 standin ESMTP\r\n").await;
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            if lines.read_line(&mut line).await.unwrap_or(0) == 0 {
+                                break;
+                            }
+                            let command = line.trim_end().to_ascii_uppercase();
+                            let reply: &[u8] = if command.starts_with("EHLO") {
+                                b"250-standin\r\n250 8BITMIME\r\n"
+                            } else if command.starts_with("RCPT") && reject_rcpt.is_some() {
+                                reject_rcpt.expect("reply").as_bytes()
+                            } else if command == "DATA" {
+                                let _ = write.write_all(b"354 go ahead\r\n").await;
+                                let mut data = String::new();
+                                loop {
+                                    line.clear();
+                                    if lines.read_line(&mut line).await.unwrap_or(0) == 0
+                                        || line == ".\r\n"
+                                    {
+                                        break;
+                                    }
+                                    data.push_str(&line);
+                                }
+                                captured.lock().expect("lock").push(data);
+                                b"250 queued\r\n"
+                            } else if command == "QUIT" {
+                                let _ = write.write_all(b"221 bye\r\n").await;
+                                break;
+                            } else {
+                                b"250 ok\r\n"
+                            };
+                            let _ = write.write_all(reply).await;
+                        }
+                    });
+                }
+            });
+            Self { port, messages }
+        }
+    }
+
+    pub(crate) fn email_integration(adapter_config: Value) -> IntegrationConfig {
+        IntegrationConfig {
+            id: "INT-EMAIL".to_string(),
+            integration_type: IntegrationType::Notification,
+            adapter_type: AdapterType::Email,
+            name: "email".to_string(),
+            adapter_config: adapter_config.to_string(),
+            status: AdapterStatus::Active,
+            status_message: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn smtp_config(port: u16) -> Value {
+        json!({
+            "from": "Quotey Sales <quotes@example.com>",
+            "reply_to": "sales@example.com",
+            "smtp": { "host": "127.0.0.1", "port": port, "tls": "none" },
+            "templates": {
+                "text": "{{ body_text }}\n--\nSent for {{ to | join(sep=\", \") }}",
+                "html": "<div class=\"quote\">{{ body_html | safe }}</div>",
+            },
+        })
+    }
+
+    fn email_payload(operation: &OutboxOperation) -> AdapterPayload {
+        AdapterPayload {
+            data_json: serde_json::to_string(operation).expect("encode"),
+            idempotency_key: None,
+        }
+    }
+
+    async fn test_pool() -> DbPool {
+        let pool = quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("pool");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        pool
+    }
+
+    async fn seed_quote(pool: &DbPool, quote_id: &str) {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
+             VALUES (?, 'sent', 'USD', 'rep', ?, ?)",
+        )
+        .bind(quote_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .expect("quote");
+    }
+
+    #[tokio::test]
+    async fn send_delivers_templated_multipart_mail_with_pdf_attachment() {
+        let smtp = SmtpStandIn::start(None).await;
+        let dir = std::env::temp_dir().join(format!("quotey-email-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("dir");
+        let pdf = dir.join("Q-2026-0042.pdf");
+        std::fs::write(&pdf, b"%PDF-1.7 quote").expect("pdf");
+
+        let result = EmailAdapter
+            .send(
+                &email_integration(smtp_config(smtp.port)),
+                &email_payload(&OutboxOperation::EmailSend {
+                    to: vec!["buyer@acme.test".to_string(), "Finance <ap@acme.test>".to_string()],
+                    subject: "Your quote Q-2026-0042".to_string(),
+                    body_text: "Please find your quote attached.".to_string(),
+                    body_html: Some("<p>Please find your quote attached.</p>".to_string()),
+                    attachment_path: Some(pdf.display().to_string()),
+                }),
+            )
+            .await
+            .expect("send");
+        std::fs::remove_dir_all(&dir).ok();
+
+        let result: Value = serde_json::from_str(&result.result_json).expect("result");
+        assert_eq!(result["status"], "sent");
+        let message_id = result["message_id"].as_str().expect("message id");
+        assert!(message_id.ends_with("@example.com"));
+
+        let messages = smtp.messages.lock().expect("lock").clone();
+        assert_eq!(messages.len(), 1);
+        let raw = &messages[0];
+        assert!(raw.contains(&format!("Message-ID: <{message_id}>")));
+        assert!(raw.contains("From: \"Quotey Sales\" <quotes@example.com>"));
+        assert!(raw.contains("Reply-To: sales@example.com"));
+        assert!(raw.contains("Subject: Your quote Q-2026-0042"));
+        assert!(raw.contains("multipart/alternative"));
+        assert!(raw.contains("Sent for buyer@acme.test, Finance <ap@acme.test>"));
+        assert!(raw.contains("<div class=\"quote\"><p>Please find your quote attached.</p></div>"));
+        assert!(raw.contains("Content-Type: application/pdf"));
+        assert!(raw.contains("filename=\"Q-2026-0042.pdf\""));
+    }
+
+    #[tokio::test]
+    async fn send_rejects_other_operations_and_classifies_smtp_replies() {
+        let integration = email_integration(smtp_config(1));
+        let unsupported = EmailAdapter
+            .send(
+                &integration,
+                &email_payload(&OutboxOperation::PdfGenerate {
+                    quote_id: QuoteId("Q-1".to_string()),
+                    template: "standard".to_string(),
+                }),
+            )
+            .await
+            .expect_err("pdf is not an email");
+        assert!(matches!(unsupported, AdapterError::Unsupported(_)));
+
+        let operation = OutboxOperation::EmailSend {
+            to: vec!["buyer@acme.test".to_string()],
+            subject: "Quote".to_string(),
+            body_text: "Hi".to_string(),
+            body_html: None,
+            attachment_path: None,
+        };
+        let permanent = SmtpStandIn::start(Some("550 no such user\r\n")).await;
+        let error = EmailAdapter
+            .send(&email_integration(smtp_config(permanent.port)), &email_payload(&operation))
+            .await
+            .expect_err("rejected recipient");
+        assert!(matches!(error, AdapterError::OperationFailed(_)), "{error}");
+
+        let transient = SmtpStandIn::start(Some("451 try again later\r\n")).await;
+        let error = EmailAdapter
+            .send(&email_integration(smtp_config(transient.port)), &email_payload(&operation))
+            .await
+            .expect_err("greylisted");
+        assert!(matches!(error, AdapterError::ConnectionFailed(_)), "{error}");
+
+        let health = EmailAdapter
+            .test(&email_integration(smtp_config(transient.port)))
+            .await
+            .expect("health");
+        assert!(health.ok);
+    }
+
+    #[tokio::test]
+    async fn queued_email_tasks_are_delivered_and_recorded_for_threading() {
+        let pool = test_pool().await;
+        let smtp = SmtpStandIn::start(None).await;
+        let integration = email_integration(smtp_config(smtp.port));
+        let now = Utc::now().to_rfc3339();
+        seed_quote(&pool, "Q-MAIL-1").await;
+
+        let operation = OutboxOperation::EmailSend {
+            to: vec!["buyer@acme.test".to_string()],
+            subject: "Your signing code".to_string(),
+            body_text: "Use code 123456.".to_string(),
+            body_html: None,
+            attachment_path: None,
+        };
+        let quote_id = QuoteId("Q-MAIL-1".to_string());
+        sqlx::query(
+            "INSERT INTO execution_queue_task
+                (id, quote_id, operation_kind, payload_json, idempotency_key, state,
+                 retry_count, max_retries, available_at, state_version, created_at, updated_at)
+             VALUES ('email-1', 'Q-MAIL-1', 'email.send', ?, ?, 'queued', 0, 5, ?, 1, ?, ?)",
+        )
+        .bind(serde_json::to_string(&operation).expect("encode"))
+        .bind(operation.idempotency_key(&quote_id).0)
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("task");
+
+        let deliveries = deliver_queued_emails(&pool, &integration).await.expect("deliver");
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].state, ExecutionTaskState::Completed);
+        assert_eq!(smtp.messages.lock().expect("lock").len(), 1);
+
+        let task = SqlExecutionQueueRepository::new(pool.clone())
+            .find_task_by_id(&deliveries[0].task_id)
+            .await
+            .expect("find")
+            .expect("task");
+        assert_eq!(task.state, ExecutionTaskState::Completed);
+
+        let (direction, quote, status): (String, Option<String>, String) = sqlx::query_as(
+            "SELECT direction, quote_id, status FROM email_message WHERE integration_id = ?",
+        )
+        .bind(&integration.id)
+        .fetch_one(&pool)
+        .await
+        .expect("email row");
+        assert_eq!(direction, "outbound");
+        assert_eq!(quote.as_deref(), Some("Q-MAIL-1"));
+        assert_eq!(status, "sent");
+
+        assert!(deliver_queued_emails(&pool, &integration).await.expect("again").is_empty());
+    }
+
+    #[tokio::test]
+    async fn signing_codes_are_minted_at_delivery_and_never_queued() {
+        let pool = test_pool().await;
+        let smtp = SmtpStandIn::start(None).await;
+        let integration = email_integration(smtp_config(smtp.port));
+        seed_quote(&pool, "Q-MAIL-SIGN").await;
+
+        let service = QuoteSignatureService::new(pool.clone());
+        let start = || SignatureStart {
+            quote_id: "Q-MAIL-SIGN".to_string(),
+            template: "detailed".to_string(),
+            document_sha256: "ab".repeat(32),
+            signer: SignerIdentity::new("Dana Reyes", "VP Procurement", "dana@northwind.example")
+                .expect("signer"),
+            ip_address: "203.0.113.7".to_string(),
+            user_agent: "Mozilla/5.0".to_string(),
+        };
+        let superseded = service.start(start()).await.expect("first request");
+        let issued = service.start(start()).await.expect("second request");
+
+        let deliveries = deliver_queued_emails(&pool, &integration).await.expect("deliver");
+        let state_of = |task_id: &str| {
+            deliveries
+                .iter()
+                .find(|delivery| delivery.task_id.0 == task_id)
+                .expect("delivery")
+                .state
+                .clone()
+        };
+        assert_eq!(state_of(&superseded.email_task_id), ExecutionTaskState::FailedTerminal);
+        assert_eq!(state_of(&issued.email_task_id), ExecutionTaskState::Completed);
+
+        let messages = smtp.messages.lock().expect("lock").clone();
+        assert_eq!(messages.len(), 1);
+        let code = messages[0]
+            .split("Use code ")
+            .nth(1)
+            .map(|rest| rest[..6].to_string())
+            .expect("code in email body");
+        let payload: String =
+            sqlx::query_scalar("SELECT payload_json FROM execution_queue_task WHERE id = ?")
+                .bind(&issued.email_task_id)
+                .fetch_one(&pool)
+                .await
+                .expect("task payload");
+        assert!(!payload.contains(&code), "{payload}");
+        service.verify_code("Q-MAIL-SIGN", &issued.request.id, &code).await.expect("code works");
+    }
+
+    struct EmptyLlm;
+
+    #[async_trait::async_trait]
+    impl quotey_agent::llm::LlmClient for EmptyLlm {
+        async fn complete(&self, _prompt: &str) -> anyhow::Result<String> {
+            Ok("{}".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn configured_inbound_without_an_llm_is_reported_on_the_integration() {
+        let pool = test_pool().await;
+        let root = std::env::temp_dir().join(format!("quotey-inbound-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("new")).expect("maildir");
+        let repository = SqlIntegrationConfigRepository::new(pool.clone());
+        repository
+            .save(email_integration(json!({
+                "from": "quotes@example.com",
+                "smtp": { "host": "127.0.0.1", "tls": "none" },
+                "inbound": { "kind": "maildir", "path": root },
+            })))
+            .await
+            .expect("save integration");
+
+        run_once(&pool, None).await;
+        let disabled = repository.find_by_id("INT-EMAIL").await.expect("find").expect("row");
+        assert_eq!(disabled.status, AdapterStatus::Active, "outbound delivery keeps running");
+        assert_eq!(disabled.status_message.as_deref(), Some(INBOUND_DISABLED_MESSAGE));
+
+        run_once(&pool, Some(&EmptyLlm)).await;
+        let enabled = repository.find_by_id("INT-EMAIL").await.expect("find").expect("row");
+        assert_eq!(enabled.status_message, None);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn unreachable_smtp_leaves_task_retryable() {
+        let pool = test_pool().await;
+        let unused = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let port = unused.local_addr().expect("addr").port();
+        drop(unused);
+
+        seed_quote(&pool, "Q-MAIL-2").await;
+        let now = Utc::now().to_rfc3339();
+        let operation = OutboxOperation::EmailSend {
+            to: vec!["buyer@acme.test".to_string()],
+            subject: "Quote".to_string(),
+            body_text: "Hi".to_string(),
+            body_html: None,
+            attachment_path: None,
+        };
+        sqlx::query(
+            "INSERT INTO execution_queue_task
+                (id, quote_id, operation_kind, payload_json, idempotency_key, state,
+                 retry_count, max_retries, available_at, state_version, created_at, updated_at)
+             VALUES ('email-2', 'Q-MAIL-2', 'email.send', ?, 'key-2', 'queued', 0, 5, ?, 1, ?, ?)",
+        )
+        .bind(serde_json::to_string(&operation).expect("encode"))
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("task");
+
+        let deliveries = deliver_queued_emails(&pool, &email_integration(smtp_config(port)))
+            .await
+            .expect("deliver");
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].state, ExecutionTaskState::RetryableFailed);
+        let (retry_count, available_at): (i64, String) = sqlx::query_as(
+            "SELECT retry_count, available_at FROM execution_queue_task WHERE id = 'email-2'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("task");
+        assert_eq!(retry_count, 1);
+        assert!(available_at > now);
+    }
+}
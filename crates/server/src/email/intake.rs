@@ -0,0 +1,502 @@
+//! Inbound email-to-quote intake.
+//!
+//! Each polled message is recorded once in `email_message`. A reply to a message already linked
+//! to a quote joins that quote's thread and gets a comment on the quote. Otherwise RFQ-looking
+//! emails go through requirement extraction, the catalog matcher and the draft quote builder; a
+//! draft is saved when at least one requirement matched a product. Every other outcome (not an
+//! RFQ, nothing matched, extraction failed) is recorded with the reason so the mailbox can be
+//! audited.
+
+use chrono::Utc;
+use quotey_agent::extraction::parse_email_requirements;
+use quotey_agent::llm::LlmClient;
+use quotey_core::domain::quote_comment::{AuthorType, QuoteComment};
+use quotey_core::{DraftQuoteBuildRequest, DraftQuoteBuilder, ProductMatcher};
+use quotey_db::repositories::{
+    ProductRepository, QuoteCommentRepository, QuoteRepository, RepositoryError,
+    SqlProductRepository, SqlQuoteCommentRepository, SqlQuoteRepository,
+};
+use quotey_db::DbPool;
+use serde_json::json;
+use sqlx::Row;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::mailbox::InboundEmail;
+
+/// Subject/body words that mark an email as a request for quote.
+pub const DEFAULT_RFQ_KEYWORDS: &[&str] = &[
+    "quote",
+    "quotation",
+    "rfq",
+    "pricing",
+    "price",
+    "proposal",
+    "licenses",
+    "licences",
+    "seats",
+    "purchase",
+];
+const CATALOG_LIMIT: u32 = 1000;
+const DEFAULT_CURRENCY: &str = "USD";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntakeStatus {
+    DraftCreated,
+    Linked,
+    NotRfq,
+    NoMatch,
+    Failed,
+}
+
+impl IntakeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DraftCreated => "draft_created",
+            Self::Linked => "linked",
+            Self::NotRfq => "not_rfq",
+            Self::NoMatch => "no_match",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// What intake did with one inbound message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntakeOutcome {
+    pub message_id: String,
+    pub thread_id: String,
+    pub status: IntakeStatus,
+    pub quote_id: Option<String>,
+    pub detail: Option<String>,
+}
+
+pub(super) struct EmailIntake<'a> {
+    pool: &'a DbPool,
+    llm: &'a dyn LlmClient,
+    integration_id: &'a str,
+    rfq_keywords: Vec<String>,
+}
+
+impl<'a> EmailIntake<'a> {
+    pub(super) fn new(
+        pool: &'a DbPool,
+        llm: &'a dyn LlmClient,
+        integration_id: &'a str,
+        rfq_keywords: Vec<String>,
+    ) -> Self {
+        let rfq_keywords = rfq_keywords.into_iter().map(|word| word.to_lowercase()).collect();
+        Self { pool, llm, integration_id, rfq_keywords }
+    }
+
+    /// Processes one message; `None` when it was already recorded by an earlier poll.
+    pub(super) async fn process(
+        &self,
+        email: &InboundEmail,
+    ) -> Result<Option<IntakeOutcome>, RepositoryError> {
+        let known: Option<i64> = sqlx::query_scalar(
+            "SELECT 1 FROM email_message WHERE direction = 'inbound' AND message_id = ?",
+        )
+        .bind(&email.message_id)
+        .fetch_optional(self.pool)
+        .await?;
+        if known.is_some() {
+            return Ok(None);
+        }
+
+        let thread = self.find_thread(email).await?;
+        let thread_id = thread
+            .as_ref()
+            .map(|(thread_id, _)| thread_id.clone())
+            .or_else(|| email.references.first().cloned())
+            .unwrap_or_else(|| email.message_id.clone());
+
+        let (status, quote_id, detail) = match thread.and_then(|(_, quote_id)| quote_id) {
+            Some(quote_id) => {
+                self.comment(
+                    &quote_id,
+                    "email_reply",
+                    &format!("Email reply from {}: {}", email.from, email.subject),
+                    email,
+                )
+                .await;
+                (IntakeStatus::Linked, Some(quote_id), None)
+            }
+            None if !self.looks_like_rfq(email) => {
+                (IntakeStatus::NotRfq, None, Some("no RFQ keywords found".to_string()))
+            }
+            None => self.draft_from_email(email).await?,
+        };
+
+        let outcome = IntakeOutcome {
+            message_id: email.message_id.clone(),
+            thread_id,
+            status,
+            quote_id,
+            detail,
+        };
+        self.record(email, &outcome).await?;
+        Ok(Some(outcome))
+    }
+
+    /// The thread this message continues, preferring a thread message linked to a quote.
+    async fn find_thread(
+        &self,
+        email: &InboundEmail,
+    ) -> Result<Option<(String, Option<String>)>, RepositoryError> {
+        let ancestors: Vec<&String> = email.ancestors().collect();
+        if ancestors.is_empty() {
+            return Ok(None);
+        }
+        let placeholders = vec!["?"; ancestors.len()].join(", ");
+        let sql = format!(
+            "SELECT thread_id,
+                    (SELECT quote_id FROM email_message linked
+                     WHERE linked.thread_id = email_message.thread_id
+                       AND linked.quote_id IS NOT NULL
+                     ORDER BY linked.created_at DESC LIMIT 1) AS quote_id
+             FROM email_message
+             WHERE message_id IN ({placeholders})
+             ORDER BY quote_id IS NULL, created_at DESC
+             LIMIT 1"
+        );
+        let mut query = sqlx::query(&sql);
+        for id in ancestors {
+            query = query.bind(id);
+        }
+        let row = query.fetch_optional(self.pool).await?;
+        row.map(|row| Ok((row.try_get("thread_id")?, row.try_get("quote_id")?))).transpose()
+    }
+
+    fn looks_like_rfq(&self, email: &InboundEmail) -> bool {
+        let text = format!("{}\n{}", email.subject, email.body_text).to_lowercase();
+        let words: Vec<&str> =
+            text.split(|ch: char| !ch.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+        self.rfq_keywords.iter().any(|keyword| {
+            if keyword.contains(' ') {
+                text.contains(keyword.as_str())
+            } else {
+                words.contains(&keyword.as_str())
+            }
+        })
+    }
+
+    async fn draft_from_email(
+        &self,
+        email: &InboundEmail,
+    ) -> Result<(IntakeStatus, Option<String>, Option<String>), RepositoryError> {
+        let source_text =
+            format!("From: {}\nSubject: {}\n\n{}", email.from, email.subject, email.body_text);
+        let extracted = match parse_email_requirements(self.llm, &source_text).await {
+            Ok(extracted) => extracted,
+            Err(error) => {
+                warn!(
+                    message_id = %email.message_id,
+                    error = %format!("{error:#}"),
+                    "email requirement extraction failed"
+                );
+                return Ok((
+                    IntakeStatus::Failed,
+                    None,
+                    Some(format!("requirement extraction failed: {error:#}")),
+                ));
+            }
+        };
+
+        let catalog =
+            SqlProductRepository::new(self.pool.clone()).search("", true, CATALOG_LIMIT).await?;
+        let matches = ProductMatcher.match_requirements(&extracted, &catalog);
+        let currency = matches
+            .matches
+            .first()
+            .and_then(|matched| catalog.iter().find(|product| product.id.0 == matched.product_id))
+            .map(|product| product.currency.clone())
+            .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+        let request = DraftQuoteBuildRequest {
+            quote_id: format!("Q-{}", &Uuid::new_v4().simple().to_string()[..16]),
+            created_by: format!("email:{}", email.from),
+            account_id: None,
+            deal_id: None,
+            currency,
+        };
+        let built =
+            match DraftQuoteBuilder.build_from_matches(&request, &extracted, &matches, &catalog) {
+                Ok(built) => built,
+                Err(error) => return Ok((IntakeStatus::Failed, None, Some(error.to_string()))),
+            };
+        let detail = (!built.warnings.is_empty()).then(|| built.warnings.join("; "));
+        let Some(quote) = built.quote else {
+            return Ok((IntakeStatus::NoMatch, None, detail));
+        };
+
+        let quote_id = quote.id.0.clone();
+        let line_count = quote.lines.len();
+        SqlQuoteRepository::new(self.pool.clone()).save(quote).await?;
+        self.comment(
+            &quote_id,
+            "quote_created_from_email",
+            &format!(
+                "Draft created from email \"{}\" from {} with {line_count} matched line item(s).",
+                email.subject, email.from
+            ),
+            email,
+        )
+        .await;
+        Ok((IntakeStatus::DraftCreated, Some(quote_id), detail))
+    }
+
+    async fn comment(&self, quote_id: &str, event: &str, body: &str, email: &InboundEmail) {
+        let comment = QuoteComment {
+            id: format!("CMT-{}", &Uuid::new_v4().simple().to_string()[..12]),
+            quote_id: quote_id.to_string(),
+            author_type: AuthorType::System,
+            author_id: format!("system:{event}"),
+            body: body.to_string(),
+            metadata_json: Some(
+                json!({
+                    "event": event,
+                    "auto": true,
+                    "source": "email",
+                    "message_id": email.message_id,
+                })
+                .to_string(),
+            ),
+            created_at: Utc::now(),
+        };
+        if let Err(error) =
+            SqlQuoteCommentRepository::new(self.pool.clone()).add_comment(comment).await
+        {
+            warn!(%error, quote_id, event, "email intake comment failed (non-blocking)");
+        }
+    }
+
+    async fn record(
+        &self,
+        email: &InboundEmail,
+        outcome: &IntakeOutcome,
+    ) -> Result<(), RepositoryError> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO email_message
+                (id, integration_id, direction, message_id, thread_id, quote_id, from_address,
+                 to_addresses_json, subject, status, detail, occurred_at, created_at)
+             VALUES (?, ?, 'inbound', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(direction, message_id) DO NOTHING",
+        )
+        .bind(format!("EMAIL-{}", Uuid::new_v4().simple()))
+        .bind(self.integration_id)
+        .bind(&outcome.message_id)
+        .bind(&outcome.thread_id)
+        .bind(&outcome.quote_id)
+        .bind(&email.from)
+        .bind(json!(email.to).to_string())
+        .bind(&email.subject)
+        .bind(outcome.status.as_str())
+        .bind(&outcome.detail)
+        .bind(email.date.as_deref().unwrap_or(&now))
+        .bind(&now)
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use quotey_agent::llm::LlmClient;
+    use quotey_core::domain::quote::QuoteId;
+    use quotey_db::repositories::{QuoteRepository, SqlQuoteRepository};
+    use quotey_db::DbPool;
+    use serde_json::json;
+
+    use super::IntakeStatus;
+    use crate::email::mailbox::tests::ImapStandIn;
+    use crate::email::poll_inbound;
+    use crate::email::tests::email_integration;
+
+    struct ScriptedLlm {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for ScriptedLlm {
+        async fn complete(&self, prompt: &str) -> Result<String> {
+            self.prompts.lock().expect("lock").push(prompt.to_string());
+            Ok(json!({
+                "schema_version": "requirement_extraction.v1",
+                "source_type": "email",
+                "sender_hint": "dana@acme.test",
+                "requirements": [
+                    {
+                        "requirement_type": "product",
+                        "name": "Pro Plan",
+                        "quantity": 40,
+                        "confidence": 0.92
+                    }
+                ],
+            })
+            .to_string())
+        }
+    }
+
+    async fn seeded_pool() -> DbPool {
+        let pool = quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("pool");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO product
+                (id, sku, name, base_price, currency, active, created_at, updated_at)
+             VALUES ('plan-pro', 'PRO-001', 'Pro Plan', 49.0, 'USD', 1, ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("product");
+        pool
+    }
+
+    fn message(id: &str, subject: &str, body: &str, in_reply_to: Option<&str>) -> String {
+        let reply = in_reply_to
+            .map(|parent| format!("In-Reply-To: <{parent}>\r\nReferences: <{parent}>\r\n"))
+            .unwrap_or_default();
+        format!(
+            "From: Dana Buyer <dana@acme.test>\r\nTo: rfq@example.com\r\nSubject: {subject}\r\n\
+             Message-ID: <{id}>\r\n{reply}\r\n{body}\r\n"
+        )
+    }
+
+    #[tokio::test]
+    async fn rfq_emails_open_drafts_and_replies_join_the_quote_thread() {
+        let pool = seeded_pool().await;
+        let imap = ImapStandIn::start(vec![
+            message("rfq-1@acme.test", "Quote request", "We need 40 Pro Plan seats.", None),
+            message("news@vendor.test", "Our newsletter", "Hello from the team.", None),
+            message("rfq-2@acme.test", "Re: Quote request", "Any update?", Some("rfq-1@acme.test")),
+        ])
+        .await;
+        let integration = email_integration(json!({
+            "from": "quotes@example.com",
+            "smtp": { "host": "127.0.0.1", "port": 1, "tls": "none" },
+            "inbound": {
+                "kind": "imap",
+                "host": "127.0.0.1",
+                "port": imap.port,
+                "tls": "none",
+                "username": "rfq@example.com",
+                "password": "secret",
+            },
+        }));
+        let llm = ScriptedLlm { prompts: Mutex::new(Vec::new()) };
+
+        let outcomes = poll_inbound(&pool, &integration, &llm).await.expect("poll");
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(
+            outcomes.iter().map(|outcome| outcome.status).collect::<Vec<_>>(),
+            vec![IntakeStatus::DraftCreated, IntakeStatus::NotRfq, IntakeStatus::Linked]
+        );
+        assert_eq!(llm.prompts.lock().expect("lock").len(), 1, "only the RFQ is extracted");
+        assert!(llm.prompts.lock().expect("lock")[0].contains("We need 40 Pro Plan seats."));
+
+        let quote_id = outcomes[0].quote_id.clone().expect("draft quote");
+        assert_eq!(outcomes[2].quote_id.as_deref(), Some(quote_id.as_str()));
+        assert_eq!(outcomes[2].thread_id, "rfq-1@acme.test");
+
+        let quote = SqlQuoteRepository::new(pool.clone())
+            .find_by_id(&QuoteId(quote_id.clone()))
+            .await
+            .expect("find")
+            .expect("quote");
+        assert_eq!(quote.created_by, "email:dana@acme.test");
+        assert_eq!(quote.lines.len(), 1);
+        assert_eq!(quote.lines[0].product_id.0, "plan-pro");
+        assert_eq!(quote.lines[0].quantity, 40);
+
+        let comments: Vec<String> = sqlx::query_scalar(
+            "SELECT author_id FROM quote_comment WHERE quote_id = ? ORDER BY created_at",
+        )
+        .bind(&quote_id)
+        .fetch_all(&pool)
+        .await
+        .expect("comments");
+        assert_eq!(
+            comments,
+            vec!["system:quote_created_from_email".to_string(), "system:email_reply".to_string()]
+        );
+
+        let thread: Vec<(String, String)> = sqlx::query_as(
+            "SELECT message_id, status FROM email_message WHERE thread_id = ? ORDER BY message_id",
+        )
+        .bind("rfq-1@acme.test")
+        .fetch_all(&pool)
+        .await
+        .expect("thread");
+        assert_eq!(
+            thread,
+            vec![
+                ("rfq-1@acme.test".to_string(), "draft_created".to_string()),
+                ("rfq-2@acme.test".to_string(), "linked".to_string()),
+            ]
+        );
+
+        assert!(poll_inbound(&pool, &integration, &llm).await.expect("poll again").is_empty());
+    }
+
+    #[tokio::test]
+    async fn replies_to_sent_quote_emails_link_back_to_the_quote() {
+        let pool = seeded_pool().await;
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
+             VALUES ('Q-SENT-1', 'sent', 'USD', 'rep', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("quote");
+        sqlx::query(
+            "INSERT INTO email_message
+                (id, integration_id, direction, message_id, thread_id, quote_id, from_address,
+                 subject, status, occurred_at, created_at)
+             VALUES ('EMAIL-1', 'INT-EMAIL', 'outbound', 'out-1@example.com', 'out-1@example.com',
+                     'Q-SENT-1', 'quotes@example.com', 'Your quote', 'sent', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("outbound");
+
+        let root = std::env::temp_dir().join(format!("quotey-intake-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("new")).expect("maildir");
+        std::fs::write(
+            root.join("new/1.reply"),
+            message(
+                "reply-1@acme.test",
+                "Re: Your quote",
+                "Can you add 5 seats?",
+                Some("out-1@example.com"),
+            ),
+        )
+        .expect("write");
+        let integration = email_integration(json!({
+            "from": "quotes@example.com",
+            "smtp": { "host": "127.0.0.1", "tls": "none" },
+            "inbound": { "kind": "maildir", "path": root },
+        }));
+        let llm = ScriptedLlm { prompts: Mutex::new(Vec::new()) };
+
+        let outcomes = poll_inbound(&pool, &integration, &llm).await.expect("poll");
+        std::fs::remove_dir_all(&root).ok();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].status, IntakeStatus::Linked);
+        assert_eq!(outcomes[0].quote_id.as_deref(), Some("Q-SENT-1"));
+        assert_eq!(outcomes[0].thread_id, "out-1@example.com");
+        assert!(llm.prompts.lock().expect("lock").is_empty(), "replies skip extraction");
+    }
+}
@@ -0,0 +1,533 @@
+//! Inbound mailbox readers for the email adapter.
+//!
+//! IMAP support is a small client covering what polling needs: LOGIN, SELECT,
+//! `UID SEARCH UNSEEN`, `UID FETCH … BODY.PEEK[]` and marking fetched messages `\Seen`. A maildir
+//! source reads `new/` and moves each delivered file to `cur/` with the `S` flag. Either way a
+//! message is only handed out once; intake additionally dedupes by Message-ID.
+
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use mail_parser::{HeaderValue, MessageParser};
+use quotey_core::services::AdapterError;
+use quotey_core::DeterministicExecutionEngine;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use super::{resolve_password, ImapSettings, InboundSource, TlsMode};
+
+const IMAP_TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest message accepted from the mailbox; bigger literals abort the poll.
+const MAX_MESSAGE_BYTES: usize = 25 * 1024 * 1024;
+const MAX_BODY_CHARS: usize = 20_000;
+
+/// A received email reduced to what intake needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboundEmail {
+    /// Message-ID without angle brackets.
+    pub message_id: String,
+    #[serde(default)]
+    pub in_reply_to: Vec<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+    pub from: String,
+    #[serde(default)]
+    pub from_name: Option<String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    pub subject: String,
+    #[serde(default)]
+    pub date: Option<String>,
+    pub body_text: String,
+}
+
+impl InboundEmail {
+    /// Every earlier message this one answers, oldest reference first.
+    pub fn ancestors(&self) -> impl Iterator<Item = &String> {
+        self.references.iter().chain(self.in_reply_to.iter())
+    }
+}
+
+/// Parses a raw RFC 5322 message. Messages without a Message-ID get a stable one derived from
+/// their content so repeated polls still dedupe.
+pub fn parse_inbound(raw: &[u8]) -> Option<InboundEmail> {
+    let message = MessageParser::default().parse(raw)?;
+    let sender = message.from().and_then(|from| from.first())?;
+    let from = sender.address.as_deref()?.trim().to_ascii_lowercase();
+
+    let message_id = message.message_id().map(str::to_string).unwrap_or_else(|| {
+        format!(
+            "generated-{}@quotey.local",
+            DeterministicExecutionEngine::hash_payload(&String::from_utf8_lossy(raw))
+        )
+    });
+    let mut body_text = message.body_text(0).map(|body| body.into_owned()).unwrap_or_default();
+    if body_text.chars().count() > MAX_BODY_CHARS {
+        body_text = body_text.chars().take(MAX_BODY_CHARS).collect();
+    }
+
+    Some(InboundEmail {
+        message_id,
+        in_reply_to: header_ids(message.in_reply_to()),
+        references: header_ids(message.references()),
+        from_name: sender.name.as_deref().map(str::to_string),
+        from,
+        to: message
+            .to()
+            .map(|to| {
+                to.iter().filter_map(|addr| addr.address.as_deref().map(str::to_string)).collect()
+            })
+            .unwrap_or_default(),
+        subject: message.subject().unwrap_or_default().trim().to_string(),
+        date: message.date().map(|date| date.to_rfc3339()),
+        body_text,
+    })
+}
+
+fn header_ids(value: &HeaderValue<'_>) -> Vec<String> {
+    value.as_text_list().unwrap_or_default().into_iter().map(str::to_string).collect()
+}
+
+/// Fetches up to `limit` unseen messages and marks them seen.
+pub async fn fetch_unseen(
+    source: &InboundSource,
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, AdapterError> {
+    match source {
+        InboundSource::Imap(settings) => fetch_imap(settings, limit).await,
+        InboundSource::Maildir { path } => fetch_maildir(path, limit).await,
+    }
+}
+
+async fn fetch_maildir(root: &Path, limit: usize) -> Result<Vec<Vec<u8>>, AdapterError> {
+    let maildir_error = |action: &str, error: std::io::Error| {
+        AdapterError::ConnectionFailed(format!(
+            "cannot {action} maildir `{}`: {error}",
+            root.display()
+        ))
+    };
+    let new_dir = root.join("new");
+    let cur_dir = root.join("cur");
+    tokio::fs::create_dir_all(&cur_dir).await.map_err(|error| maildir_error("prepare", error))?;
+
+    let mut names = Vec::new();
+    let mut entries =
+        tokio::fs::read_dir(&new_dir).await.map_err(|error| maildir_error("read", error))?;
+    while let Some(entry) =
+        entries.next_entry().await.map_err(|error| maildir_error("read", error))?
+    {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with('.') {
+            names.push(name);
+        }
+    }
+    // Maildir names start with the delivery timestamp, so name order is arrival order.
+    names.sort();
+
+    let mut messages = Vec::new();
+    for name in names.into_iter().take(limit) {
+        let path = new_dir.join(&name);
+        let raw = tokio::fs::read(&path).await.map_err(|error| maildir_error("read", error))?;
+        tokio::fs::rename(&path, cur_dir.join(format!("{name}:2,S")))
+            .await
+            .map_err(|error| maildir_error("update", error))?;
+        messages.push(raw);
+    }
+    Ok(messages)
+}
+
+async fn fetch_imap(settings: &ImapSettings, limit: usize) -> Result<Vec<Vec<u8>>, AdapterError> {
+    let password =
+        resolve_password(settings.password.as_deref(), settings.password_env.as_deref())?
+            .unwrap_or_default();
+    let mut session = ImapSession::connect(settings).await?;
+    session.command(&format!("LOGIN {} {}", quoted(&settings.username), quoted(&password))).await?;
+    session.command(&format!("SELECT {}", quoted(&settings.mailbox))).await?;
+
+    let search = session.command("UID SEARCH UNSEEN").await?;
+    let uids: Vec<u64> = search
+        .lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("* SEARCH"))
+        .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+        .take(limit)
+        .collect();
+
+    let mut messages = Vec::with_capacity(uids.len());
+    for uid in uids {
+        let fetched = session.command(&format!("UID FETCH {uid} (BODY.PEEK[])")).await?;
+        if let Some(raw) = fetched.literals.into_iter().next() {
+            messages.push(raw);
+            session.command(&format!("UID STORE {uid} +FLAGS.SILENT (\\Seen)")).await?;
+        }
+    }
+    let _ = session.command("LOGOUT").await;
+    Ok(messages)
+}
+
+/// IMAP quoted string.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+enum ImapStream {
+    Plain(TcpStream),
+    Tls(Box<tokio_rustls::client::TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for ImapStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ImapStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ImapResponse {
+    lines: Vec<String>,
+    literals: Vec<Vec<u8>>,
+}
+
+struct ImapSession {
+    stream: BufReader<ImapStream>,
+    next_tag: u32,
+}
+
+impl ImapSession {
+    async fn connect(settings: &ImapSettings) -> Result<Self, AdapterError> {
+        let address = (settings.host.as_str(), settings.port());
+        let tcp = tokio::time::timeout(IMAP_TIMEOUT, TcpStream::connect(address))
+            .await
+            .map_err(|_| imap_connection_error("connection timed out"))?
+            .map_err(|error| imap_connection_error(&error.to_string()))?;
+
+        let stream = match settings.tls {
+            TlsMode::Tls => ImapStream::Tls(Box::new(tls_handshake(&settings.host, tcp).await?)),
+            TlsMode::Starttls | TlsMode::None => ImapStream::Plain(tcp),
+        };
+        let mut session = Self { stream: BufReader::new(stream), next_tag: 1 };
+        let greeting = session.read_line().await?;
+        if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+            return Err(imap_connection_error(&format!("unexpected greeting `{greeting}`")));
+        }
+
+        if settings.tls == TlsMode::Starttls {
+            session.command("STARTTLS").await?;
+            let ImapStream::Plain(tcp) = session.stream.into_inner() else {
+                unreachable!("STARTTLS is only issued on a plain connection");
+            };
+            let tls = tls_handshake(&settings.host, tcp).await?;
+            session = Self { stream: BufReader::new(ImapStream::Tls(Box::new(tls))), ..session };
+        }
+        Ok(session)
+    }
+
+    async fn command(&mut self, command: &str) -> Result<ImapResponse, AdapterError> {
+        let tag = format!("A{:04}", self.next_tag);
+        self.next_tag += 1;
+        let verb = command.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+
+        let io = |error: std::io::Error| imap_connection_error(&error.to_string());
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{tag} {command}\r\n").as_bytes()).await.map_err(io)?;
+        stream.flush().await.map_err(io)?;
+
+        let mut response = ImapResponse::default();
+        loop {
+            let line = self.read_line().await?;
+            if let Some(status) = line.strip_prefix(&format!("{tag} ")) {
+                if status.starts_with("OK") {
+                    return Ok(response);
+                }
+                // The LOGIN line carries the password, so only the verb is echoed back.
+                let error = format!("IMAP {verb} failed: {status}");
+                return Err(if verb == "LOGIN" || status.starts_with("NO") {
+                    AdapterError::OperationFailed(error)
+                } else {
+                    AdapterError::ConnectionFailed(error)
+                });
+            }
+            if let Some(size) = literal_size(&line) {
+                if size > MAX_MESSAGE_BYTES {
+                    return Err(AdapterError::OperationFailed(format!(
+                        "IMAP message of {size} bytes exceeds the {MAX_MESSAGE_BYTES} byte limit"
+                    )));
+                }
+                let mut literal = vec![0; size];
+                tokio::time::timeout(IMAP_TIMEOUT, self.stream.read_exact(&mut literal))
+                    .await
+                    .map_err(|_| imap_connection_error("read timed out"))?
+                    .map_err(io)?;
+                response.literals.push(literal);
+            }
+            response.lines.push(line);
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, AdapterError> {
+        let mut line = Vec::new();
+        let read = tokio::time::timeout(IMAP_TIMEOUT, self.stream.read_until(b'\n', &mut line))
+            .await
+            .map_err(|_| imap_connection_error("read timed out"))?
+            .map_err(|error| imap_connection_error(&error.to_string()))?;
+        if read == 0 {
+            return Err(imap_connection_error("server closed the connection"));
+        }
+        Ok(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+/// Size of a `{N}` literal announced at the end of a response line.
+fn literal_size(line: &str) -> Option<usize> {
+    let open = line.strip_suffix('}')?.rfind('{')?;
+    line[open + 1..line.len() - 1].parse().ok()
+}
+
+async fn tls_handshake(
+    host: &str,
+    tcp: TcpStream,
+) -> Result<tokio_rustls::client::TlsStream<TcpStream>, AdapterError> {
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let config = ClientConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|error| imap_connection_error(&error.to_string()))?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|_| AdapterError::NotConfigured(format!("invalid IMAP host `{host}`")))?;
+    TlsConnector::from(Arc::new(config))
+        .connect(server_name, tcp)
+        .await
+        .map_err(|error| imap_connection_error(&format!("TLS handshake failed: {error}")))
+}
+
+fn imap_connection_error(detail: &str) -> AdapterError {
+    AdapterError::ConnectionFailed(format!("IMAP connection failed: {detail}"))
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::{fetch_unseen, literal_size, parse_inbound, quoted};
+    use crate::email::{ImapSettings, InboundSource, TlsMode};
+
+    /// IMAP server holding `messages` in memory. It tracks `\Seen` and records each command.
+    pub(crate) struct ImapStandIn {
+        pub port: u16,
+        pub commands: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ImapStandIn {
+        pub(crate) async fn start(messages: Vec<String>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind imap");
+            let port = listener.local_addr().expect("addr").port();
+            let commands = Arc::new(Mutex::new(Vec::new()));
+            let seen = Arc::new(Mutex::new(vec![false; messages.len()]));
+            let recorded = commands.clone();
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    let (messages, seen, recorded) =
+                        (messages.clone(), seen.clone(), recorded.clone());
+                    tokio::spawn(async move {
+                        let (read, mut write) = socket.into_split();
+                        let mut lines = BufReader::new(read);
+                        let _ = write.write_all(b"* OK standin IMAP4rev1 ready\r\n").await;
+                        let mut line = String::new();
+                        while lines.read_line(&mut line).await.unwrap_or(0) > 0 {
+                            let (tag, command) =
+                                line.trim_end().split_once(' ').expect("tagged command");
+                            let (tag, command) = (tag.to_string(), command.to_string());
+                            line.clear();
+                            recorded.lock().expect("lock").push(command.clone());
+                            let mut reply = String::new();
+                            if command.starts_with("LOGIN") && !command.contains("\"secret\"") {
+                                reply.push_str(&format!("{tag} NO invalid credentials\r\n"));
+                            } else if command == "UID SEARCH UNSEEN" {
+                                let unseen = seen
+                                    .lock()
+                                    .expect("lock")
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, seen)| !**seen)
+                                    .map(|(index, _)| format!(" {}", index + 1))
+                                    .collect::<String>();
+                                reply.push_str(&format!("* SEARCH{unseen}\r\n{tag} OK done\r\n"));
+                            } else if let Some(uid) = command
+                                .strip_prefix("UID FETCH ")
+                                .and_then(|rest| rest.split(' ').next())
+                            {
+                                let uid: usize = uid.parse().expect("uid");
+                                let body = &messages[uid - 1];
+                                reply.push_str(&format!(
+                                    "* {uid} FETCH (UID {uid} BODY[] {{{}}}\r\n{body})\r\n\
+                                     {tag} OK done\r\n",
+                                    body.len()
+                                ));
+                            } else if let Some(uid) = command
+                                .strip_prefix("UID STORE ")
+                                .and_then(|rest| rest.split(' ').next())
+                            {
+                                let uid: usize = uid.parse().expect("uid");
+                                seen.lock().expect("lock")[uid - 1] = true;
+                                reply.push_str(&format!("{tag} OK stored\r\n"));
+                            } else if command == "LOGOUT" {
+                                let _ = write
+                                    .write_all(format!("* BYE\r\n{tag} OK bye\r\n").as_bytes())
+                                    .await;
+                                break;
+                            } else {
+                                reply.push_str(&format!("{tag} OK done\r\n"));
+                            }
+                            let _ = write.write_all(reply.as_bytes()).await;
+                        }
+                    });
+                }
+            });
+            Self { port, commands }
+        }
+
+        pub(crate) fn source(&self, password: &str) -> InboundSource {
+            InboundSource::Imap(ImapSettings {
+                host: "127.0.0.1".to_string(),
+                port: Some(self.port),
+                tls: TlsMode::None,
+                username: "rfq@example.com".to_string(),
+                password: Some(password.to_string()),
+                password_env: None,
+                mailbox: "INBOX".to_string(),
+            })
+        }
+    }
+
+    fn raw_email(message_id: &str, subject: &str) -> String {
+        format!(
+            "From: \"Dana Buyer\" <Dana@Acme.test>\r\nTo: rfq@example.com\r\n\
+             Subject: {subject}\r\nMessage-ID: <{message_id}>\r\n\
+             In-Reply-To: <root@example.com>\r\n\
+             References: <root@example.com> <mid@example.com>\r\n\
+             Date: Mon, 12 Oct 2026 09:30:00 +0000\r\n\r\nWe need 40 seats.\r\n"
+        )
+    }
+
+    #[test]
+    fn parse_inbound_extracts_thread_headers_and_sender() {
+        let email =
+            parse_inbound(raw_email("m1@acme.test", "RFQ: seats").as_bytes()).expect("parsed");
+        assert_eq!(email.message_id, "m1@acme.test");
+        assert_eq!(email.from, "dana@acme.test");
+        assert_eq!(email.from_name.as_deref(), Some("Dana Buyer"));
+        assert_eq!(email.to, vec!["rfq@example.com".to_string()]);
+        assert_eq!(email.subject, "RFQ: seats");
+        assert_eq!(email.in_reply_to, vec!["root@example.com".to_string()]);
+        assert_eq!(
+            email.references,
+            vec!["root@example.com".to_string(), "mid@example.com".to_string()]
+        );
+        assert_eq!(email.date.as_deref(), Some("2026-10-12T09:30:00Z"));
+        assert_eq!(email.body_text.trim(), "We need 40 seats.");
+
+        let without_id = "From: a@b.test\r\nSubject: hi\r\n\r\nbody\r\n";
+        let first = parse_inbound(without_id.as_bytes()).expect("parsed");
+        let second = parse_inbound(without_id.as_bytes()).expect("parsed");
+        assert!(first.message_id.starts_with("generated-"));
+        assert_eq!(first.message_id, second.message_id);
+    }
+
+    #[test]
+    fn imap_helpers_quote_strings_and_find_literals() {
+        assert_eq!(quoted(r#"pa"ss\word"#), r#""pa\"ss\\word""#);
+        assert_eq!(literal_size("* 1 FETCH (UID 1 BODY[] {342}"), Some(342));
+        assert_eq!(literal_size("* SEARCH 1 2"), None);
+    }
+
+    #[tokio::test]
+    async fn imap_fetch_returns_unseen_messages_once_and_marks_them_seen() {
+        let imap = ImapStandIn::start(vec![
+            raw_email("m1@acme.test", "first"),
+            raw_email("m2@acme.test", "second"),
+        ])
+        .await;
+
+        let first = fetch_unseen(&imap.source("secret"), 1).await.expect("fetch");
+        assert_eq!(first.len(), 1);
+        assert_eq!(parse_inbound(&first[0]).expect("parse").message_id, "m1@acme.test");
+
+        let rest = fetch_unseen(&imap.source("secret"), 10).await.expect("fetch");
+        assert_eq!(rest.len(), 1);
+        assert_eq!(parse_inbound(&rest[0]).expect("parse").subject, "second");
+        assert!(fetch_unseen(&imap.source("secret"), 10).await.expect("fetch").is_empty());
+
+        let commands = imap.commands.lock().expect("lock").clone();
+        assert!(commands.contains(&"SELECT \"INBOX\"".to_string()));
+        assert!(commands.contains(&"UID FETCH 1 (BODY.PEEK[])".to_string()));
+        assert!(commands.contains(&"UID STORE 2 +FLAGS.SILENT (\\Seen)".to_string()));
+
+        let error = fetch_unseen(&imap.source("wrong"), 10).await.expect_err("bad login");
+        assert!(error.to_string().contains("IMAP LOGIN"), "{error}");
+        assert!(!error.to_string().contains("wrong"));
+    }
+
+    #[tokio::test]
+    async fn maildir_fetch_moves_new_messages_to_cur_as_seen() {
+        let root = std::env::temp_dir().join(format!("quotey-maildir-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("new")).expect("new");
+        std::fs::write(root.join("new/1700000001.a.host"), raw_email("a@acme.test", "a"))
+            .expect("write");
+        std::fs::write(root.join("new/1700000002.b.host"), raw_email("b@acme.test", "b"))
+            .expect("write");
+
+        let source = InboundSource::Maildir { path: root.clone() };
+        let fetched = fetch_unseen(&source, 10).await.expect("fetch");
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(parse_inbound(&fetched[0]).expect("parse").message_id, "a@acme.test");
+        assert!(root.join("cur/1700000001.a.host:2,S").exists());
+        assert!(fetch_unseen(&source, 10).await.expect("fetch").is_empty());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -6,7 +6,13 @@ use axum::{
 };
 use chrono::Utc;
 use quotey_core::config::CrmConfig;
+use quotey_core::dna::SimilarityFilter;
+use quotey_db::similarity::{
+    outcome_as_str, parse_outcome, DealSimilarityService, SimilarDealsQuery as IndexQuery,
+    SimilarityIndexError,
+};
 use quotey_db::DbPool;
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
@@ -33,6 +39,13 @@ pub struct HealthResponse {
 pub struct SimilarDealsQuery {
     pub limit: Option<u32>,
     pub min_similarity: Option<f64>,
+    /// Customer segment of the matched deal, case-insensitive.
+    pub segment: Option<String>,
+    /// `won`, `lost` or `pending`.
+    pub outcome: Option<String>,
+    /// Inclusive `YYYY-MM-DD` bounds on the matched deal's close date.
+    pub closed_after: Option<String>,
+    pub closed_before: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -50,21 +63,12 @@ pub struct ApiErrorResponse {
     pub error: String,
 }
 
-#[derive(Debug, sqlx::FromRow)]
-struct SimilarDealRow {
-    quote_id: String,
-    customer_name: String,
-    similarity_score: f64,
-    outcome: String,
-    final_price: f64,
-    close_date: Option<String>,
-}
-
 pub fn router(db_pool: DbPool, crm_config: CrmConfig) -> Router {
     Router::new()
         .route("/health", get(health))
         .route("/api/v1/quotes/{id}/similar-deals", get(similar_deals))
         .with_state(HealthState { db_pool: db_pool.clone() })
+        .merge(crate::api::router(db_pool.clone()))
         .merge(crate::portal::router(db_pool.clone()))
         .merge(crate::crm::router(db_pool, crm_config))
 }
@@ -126,70 +130,57 @@ pub async fn similar_deals(
     Query(params): Query<SimilarDealsQuery>,
     State(state): State<HealthState>,
 ) -> Result<Json<Vec<SimilarDealResponse>>, (StatusCode, Json<ApiErrorResponse>)> {
-    let limit = params.limit.unwrap_or(5).clamp(1, 20) as i64;
-    let min_similarity = params.min_similarity.unwrap_or(0.7).clamp(0.0, 1.0);
+    let outcome = params
+        .outcome
+        .as_deref()
+        .map(parse_outcome)
+        .transpose()
+        .map_err(|_| bad_request("outcome must be one of won, lost or pending"))?;
+    let query = IndexQuery {
+        min_similarity: params.min_similarity.unwrap_or(0.7).clamp(0.0, 1.0) as f32,
+        limit: params.limit.unwrap_or(5).clamp(1, 20) as usize,
+        filter: SimilarityFilter {
+            customer_segment: non_blank(params.segment),
+            outcome,
+            closed_from: non_blank(params.closed_after),
+            closed_to: non_blank(params.closed_before),
+        },
+    };
 
-    let quote_exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM quote WHERE id = ?")
-        .bind(&quote_id)
-        .fetch_optional(&state.db_pool)
+    let matches = DealSimilarityService::new(state.db_pool.clone())
+        .find_similar_to_quote(&quote_id, &query)
         .await
-        .map_err(internal_api_error)?;
-
-    if quote_exists.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ApiErrorResponse { error: format!("quote `{quote_id}` not found") }),
-        ));
-    }
-
-    let rows: Vec<SimilarDealRow> = sqlx::query_as(
-        r#"
-        WITH source AS (
-            SELECT id
-            FROM configuration_fingerprints
-            WHERE quote_id = ?
-            ORDER BY created_at DESC
-            LIMIT 1
-        )
-        SELECT
-            candidate.quote_id AS quote_id,
-            COALESCE(candidate_quote.created_by, 'unknown') AS customer_name,
-            sc.similarity_score AS similarity_score,
-            COALESCE(deal_outcome.outcome, candidate.outcome_status, 'pending') AS outcome,
-            COALESCE(deal_outcome.final_price, candidate.final_price) AS final_price,
-            COALESCE(deal_outcome.close_date, candidate.close_date) AS close_date
-        FROM source
-        JOIN similarity_cache sc ON sc.source_fingerprint_id = source.id
-        JOIN configuration_fingerprints candidate ON candidate.id = sc.candidate_fingerprint_id
-        LEFT JOIN deal_outcomes deal_outcome ON deal_outcome.quote_id = candidate.quote_id
-        LEFT JOIN quote candidate_quote ON candidate_quote.id = candidate.quote_id
-        WHERE sc.similarity_score >= ?
-        ORDER BY sc.similarity_score DESC
-        LIMIT ?
-        "#,
-    )
-    .bind(&quote_id)
-    .bind(min_similarity)
-    .bind(limit)
-    .fetch_all(&state.db_pool)
-    .await
-    .map_err(internal_api_error)?;
-
-    let deals = rows
+        .map_err(|error| match error {
+            SimilarityIndexError::QuoteNotFound(_) => (
+                StatusCode::NOT_FOUND,
+                Json(ApiErrorResponse { error: format!("quote `{quote_id}` not found") }),
+            ),
+            error => internal_api_error(error),
+        })?;
+
+    let deals = matches
         .into_iter()
-        .map(|row| SimilarDealResponse {
-            quote_id: row.quote_id,
-            customer_name: row.customer_name,
-            similarity_score: row.similarity_score,
-            outcome: row.outcome,
-            final_price: row.final_price,
-            close_date: row.close_date.unwrap_or_default(),
+        .map(|found| SimilarDealResponse {
+            quote_id: found.deal.outcome.quote_id,
+            customer_name: found.customer_name,
+            similarity_score: f64::from(found.deal.similarity_score),
+            outcome: outcome_as_str(&found.deal.outcome.outcome_status).to_string(),
+            final_price: found.deal.outcome.final_price.to_f64().unwrap_or_default(),
+            close_date: found.deal.outcome.close_date.unwrap_or_default(),
         })
         .collect();
 
     Ok(Json(deals))
 }
 
+fn non_blank(value: Option<String>) -> Option<String> {
+    value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+fn bad_request(message: &str) -> (StatusCode, Json<ApiErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ApiErrorResponse { error: message.to_string() }))
+}
+
 async fn database_check(pool: &DbPool) -> HealthCheck {
     match sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(pool).await {
         Ok(_) => HealthCheck { status: "ready", detail: "database query succeeded".to_string() },
@@ -200,7 +191,7 @@ async fn database_check(pool: &DbPool) -> HealthCheck {
     }
 }
 
-fn internal_api_error(error: sqlx::Error) -> (StatusCode, Json<ApiErrorResponse>) {
+fn internal_api_error(error: SimilarityIndexError) -> (StatusCode, Json<ApiErrorResponse>) {
     error!(error = %error, "similar_deals database query failed");
     (
         StatusCode::INTERNAL_SERVER_ERROR,
@@ -218,6 +209,12 @@ mod tests {
     use chrono::Utc;
     use quotey_db::{connect_with_settings, migrations};
 
+    use quotey_core::domain::product::ProductId;
+    use quotey_core::domain::quote::{Quote, QuoteId, QuoteLine, QuoteStatus};
+    use quotey_db::repositories::{QuoteRepository, SqlQuoteRepository};
+    use quotey_db::similarity::DealSimilarityService;
+    use rust_decimal::Decimal;
+
     use crate::health::{health, similar_deals, HealthState, SimilarDealsQuery};
 
     #[tokio::test]
@@ -287,7 +284,11 @@ mod tests {
 
         let Json(deals) = similar_deals(
             Path("Q-2026-0001".to_string()),
-            Query(SimilarDealsQuery { limit: Some(1), min_similarity: Some(0.6) }),
+            Query(SimilarDealsQuery {
+                limit: Some(1),
+                min_similarity: Some(0.6),
+                ..SimilarDealsQuery::default()
+            }),
             State(HealthState { db_pool: pool.clone() }),
         )
         .await
@@ -299,6 +300,53 @@ mod tests {
         pool.close().await;
     }
 
+    #[tokio::test]
+    async fn similar_deals_filters_by_segment_outcome_and_close_date() {
+        let pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 5)
+            .await
+            .expect("pool should connect");
+        migrations::run_pending(&pool).await.expect("migrations should run");
+        seed_similarity_fixture(&pool).await;
+
+        let lookup = |query: SimilarDealsQuery| {
+            similar_deals(
+                Path("Q-2026-0001".to_string()),
+                Query(query),
+                State(HealthState { db_pool: pool.clone() }),
+            )
+        };
+        let all = SimilarDealsQuery { min_similarity: Some(0.0), ..SimilarDealsQuery::default() };
+
+        let Json(deals) = lookup(all.clone()).await.expect("unfiltered");
+        assert_eq!(deals.len(), 2);
+
+        let Json(deals) = lookup(SimilarDealsQuery {
+            segment: Some("ENTERPRISE".to_string()),
+            outcome: Some("won".to_string()),
+            closed_after: Some("2026-01-01".to_string()),
+            ..all.clone()
+        })
+        .await
+        .expect("filtered");
+        assert_eq!(deals.len(), 1);
+        assert_eq!(deals[0].quote_id, "Q-2026-0002");
+
+        let Json(deals) = lookup(SimilarDealsQuery {
+            closed_before: Some("2026-01-31".to_string()),
+            ..all.clone()
+        })
+        .await
+        .expect("date filtered");
+        assert!(deals.is_empty());
+
+        let (status, _) = lookup(SimilarDealsQuery { outcome: Some("maybe".to_string()), ..all })
+            .await
+            .expect_err("unknown outcome");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn similar_deals_returns_not_found_for_missing_quote() {
         let pool = connect_with_settings("sqlite::memory:?cache=shared", 1, 5)
@@ -321,107 +369,45 @@ mod tests {
     }
 
     async fn seed_similarity_fixture(pool: &sqlx::SqlitePool) {
-        let now = Utc::now().to_rfc3339();
-
-        for (id, created_by) in [
-            ("Q-2026-0001", "Source Customer"),
-            ("Q-2026-0002", "Acme Corp"),
-            ("Q-2026-0003", "Globex"),
+        let bundle: &[(&str, u32)] =
+            &[("plan-pro", 50), ("support-premium", 1), ("addon-sso", 50), ("addon-audit", 50)];
+        let repo = SqlQuoteRepository::new(pool.clone());
+        for (id, created_by, status, lines) in [
+            ("Q-2026-0001", "Source Customer", QuoteStatus::Draft, bundle),
+            ("Q-2026-0002", "Acme Corp", QuoteStatus::Sent, bundle),
+            ("Q-2026-0003", "Globex", QuoteStatus::Finalized, &[("starter", 3)][..]),
         ] {
-            sqlx::query(
-                "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
-                 VALUES (?, 'draft', 'USD', ?, ?, ?)",
-            )
-            .bind(id)
-            .bind(created_by)
-            .bind(&now)
-            .bind(&now)
-            .execute(pool)
+            let now = Utc::now();
+            repo.save(Quote {
+                id: QuoteId(id.to_string()),
+                version: 1,
+                status,
+                account_id: None,
+                deal_id: None,
+                currency: "USD".to_string(),
+                term_months: None,
+                start_date: None,
+                end_date: None,
+                valid_until: None,
+                notes: None,
+                created_by: created_by.to_string(),
+                lines: lines
+                    .iter()
+                    .map(|(product, quantity)| QuoteLine {
+                        product_id: ProductId((*product).to_string()),
+                        quantity: *quantity,
+                        unit_price: Decimal::new(25_000, 2),
+                        discount_pct: 0.0,
+                        notes: None,
+                    })
+                    .collect(),
+                created_at: now,
+                updated_at: now,
+            })
             .await
             .expect("seed quote");
         }
 
-        sqlx::query(
-            "INSERT INTO configuration_fingerprints
-             (id, quote_id, fingerprint_hash, configuration_vector, outcome_status, final_price, close_date, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind("fp-source")
-        .bind("Q-2026-0001")
-        .bind("hash-source")
-        .bind(vec![1_u8, 2, 3])
-        .bind("pending")
-        .bind(50_000.0)
-        .bind("2026-01-01")
-        .bind(&now)
-        .execute(pool)
-        .await
-        .expect("seed source fingerprint");
-
-        sqlx::query(
-            "INSERT INTO configuration_fingerprints
-             (id, quote_id, fingerprint_hash, configuration_vector, outcome_status, final_price, close_date, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind("fp-candidate-1")
-        .bind("Q-2026-0002")
-        .bind("hash-candidate-1")
-        .bind(vec![2_u8, 3, 4])
-        .bind("won")
-        .bind(45_000.0)
-        .bind("2026-01-15")
-        .bind(&now)
-        .execute(pool)
-        .await
-        .expect("seed first candidate fingerprint");
-
-        sqlx::query(
-            "INSERT INTO configuration_fingerprints
-             (id, quote_id, fingerprint_hash, configuration_vector, outcome_status, final_price, close_date, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind("fp-candidate-2")
-        .bind("Q-2026-0003")
-        .bind("hash-candidate-2")
-        .bind(vec![9_u8, 8, 7])
-        .bind("lost")
-        .bind(30_000.0)
-        .bind("2026-01-20")
-        .bind(&now)
-        .execute(pool)
-        .await
-        .expect("seed second candidate fingerprint");
-
-        sqlx::query(
-            "INSERT INTO similarity_cache
-             (id, source_fingerprint_id, candidate_fingerprint_id, similarity_score, algorithm_version, computed_at)
-             VALUES (?, ?, ?, ?, ?, ?)",
-        )
-        .bind("sim-1")
-        .bind("fp-source")
-        .bind("fp-candidate-1")
-        .bind(0.91_f64)
-        .bind("v1")
-        .bind(&now)
-        .execute(pool)
-        .await
-        .expect("seed first similarity row");
-
-        sqlx::query(
-            "INSERT INTO similarity_cache
-             (id, source_fingerprint_id, candidate_fingerprint_id, similarity_score, algorithm_version, computed_at)
-             VALUES (?, ?, ?, ?, ?, ?)",
-        )
-        .bind("sim-2")
-        .bind("fp-source")
-        .bind("fp-candidate-2")
-        .bind(0.65_f64)
-        .bind("v1")
-        .bind(&now)
-        .execute(pool)
-        .await
-        .expect("seed second similarity row");
-
         sqlx::query(
             "INSERT INTO deal_outcomes
              (id, quote_id, outcome, final_price, close_date, customer_segment, product_mix_json, sales_cycle_days, created_at)
@@ -435,9 +421,12 @@ mod tests {
         .bind("enterprise")
         .bind("[]")
         .bind(35_i64)
-        .bind(&now)
+        .bind(Utc::now().to_rfc3339())
         .execute(pool)
         .await
         .expect("seed deal outcome");
+
+        let report = DealSimilarityService::new(pool.clone()).rebuild().await.expect("rebuild");
+        assert_eq!(report.indexed, 2);
     }
 }
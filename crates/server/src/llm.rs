@@ -0,0 +1,276 @@
+//! HTTP [`LlmClient`] for the provider configured under `[llm]`.
+//!
+//! The server uses it to extract requirements from inbound RFQ emails. OpenAI and Anthropic are
+//! called through their hosted chat APIs (or `llm.base_url` when set), Ollama through
+//! `/api/generate` on `llm.base_url`. Transport errors, `429` and `5xx` responses are retried up
+//! to `llm.max_retries` times with exponential backoff; any other error status fails at once.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use quotey_agent::llm::LlmClient;
+use quotey_core::config::{LlmConfig, LlmProvider};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com";
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Extraction replies are small JSON documents; this leaves ample room.
+const MAX_OUTPUT_TOKENS: u32 = 4096;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+pub struct HttpLlmClient {
+    http: Client,
+    provider: LlmProvider,
+    api_key: Option<SecretString>,
+    base_url: String,
+    model: String,
+    max_retries: u32,
+}
+
+impl HttpLlmClient {
+    pub fn from_config(config: &LlmConfig) -> Result<Self> {
+        let base_url = match (&config.provider, config.base_url.as_deref().map(str::trim)) {
+            (_, Some(url)) if !url.is_empty() => url.trim_end_matches('/').to_string(),
+            (LlmProvider::OpenAi, _) => OPENAI_BASE_URL.to_string(),
+            (LlmProvider::Anthropic, _) => ANTHROPIC_BASE_URL.to_string(),
+            (LlmProvider::Ollama, _) => bail!("llm.base_url is required for the ollama provider"),
+        };
+        if !matches!(config.provider, LlmProvider::Ollama) && config.api_key.is_none() {
+            bail!("llm.api_key is required for the openai and anthropic providers");
+        }
+        let http = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()
+            .context("cannot build LLM HTTP client")?;
+        Ok(Self {
+            http,
+            provider: config.provider,
+            api_key: config.api_key.clone(),
+            base_url,
+            model: config.model.clone(),
+            max_retries: config.max_retries,
+        })
+    }
+
+    fn request(&self, prompt: &str) -> RequestBuilder {
+        let api_key = self.api_key.as_ref().map(|key| key.expose_secret().to_string());
+        match self.provider {
+            LlmProvider::OpenAi => self
+                .http
+                .post(format!("{}/v1/chat/completions", self.base_url))
+                .bearer_auth(api_key.unwrap_or_default())
+                .json(&json!({
+                    "model": self.model,
+                    "temperature": 0,
+                    "messages": [{ "role": "user", "content": prompt }],
+                })),
+            LlmProvider::Anthropic => self
+                .http
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", api_key.unwrap_or_default())
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&json!({
+                    "model": self.model,
+                    "max_tokens": MAX_OUTPUT_TOKENS,
+                    "temperature": 0,
+                    "messages": [{ "role": "user", "content": prompt }],
+                })),
+            LlmProvider::Ollama => {
+                self.http.post(format!("{}/api/generate", self.base_url)).json(&json!({
+                    "model": self.model,
+                    "prompt": prompt,
+                    "stream": false,
+                    "options": { "temperature": 0 },
+                }))
+            }
+        }
+    }
+
+    /// Pulls the completion text out of a provider response body.
+    fn completion_text(&self, body: &Value) -> Option<String> {
+        match self.provider {
+            LlmProvider::OpenAi => {
+                body.pointer("/choices/0/message/content")?.as_str().map(str::to_string)
+            }
+            LlmProvider::Anthropic => {
+                let text: String = body
+                    .get("content")?
+                    .as_array()?
+                    .iter()
+                    .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                    .filter_map(|block| block.get("text").and_then(Value::as_str))
+                    .collect();
+                (!text.is_empty()).then_some(text)
+            }
+            LlmProvider::Ollama => body.get("response")?.as_str().map(str::to_string),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for HttpLlmClient {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            let retryable = match self.request(prompt).send().await {
+                Ok(response) if response.status().is_success() => {
+                    let body: Value =
+                        response.json().await.context("LLM response is not valid JSON")?;
+                    return self
+                        .completion_text(&body)
+                        .ok_or_else(|| anyhow!("LLM response carries no completion text"));
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let error = anyhow!("LLM provider returned HTTP {status}");
+                    if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(error) => anyhow!(error).context("LLM request failed"),
+            };
+            if attempt >= self.max_retries {
+                return Err(retryable);
+            }
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.saturating_pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use quotey_agent::llm::LlmClient;
+    use quotey_core::config::{LlmConfig, LlmProvider};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::HttpLlmClient;
+
+    type Seen = Arc<Mutex<Vec<(String, Option<String>, Value)>>>;
+
+    /// Answers each provider's endpoint with a canned reply, failing the first `failures`
+    /// requests with `503`.
+    async fn provider_stub(failures: usize) -> (String, Seen) {
+        let seen: Seen = Arc::default();
+        let reply = |path: &'static str, body: Value| {
+            post(move |State(seen): State<Seen>, headers: HeaderMap, Json(request): Json<Value>| {
+                let body = body.clone();
+                async move {
+                    let auth = headers
+                        .get("authorization")
+                        .or_else(|| headers.get("x-api-key"))
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    let mut seen = seen.lock().expect("lock");
+                    seen.push((path.to_string(), auth, request));
+                    if seen.len() <= failures {
+                        return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({})));
+                    }
+                    (StatusCode::OK, Json(body))
+                }
+            })
+        };
+        let app = Router::new()
+            .route(
+                "/v1/chat/completions",
+                reply(
+                    "openai",
+                    json!({ "choices": [{ "message": { "role": "assistant", "content": "{}" } }] }),
+                ),
+            )
+            .route(
+                "/v1/messages",
+                reply(
+                    "anthropic",
+                    json!({ "content": [{ "type": "text", "text": "{\"from\":\"claude\"}" }] }),
+                ),
+            )
+            .route("/api/generate", reply("ollama", json!({ "response": "{\"ok\":true}" })))
+            .with_state(seen.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("http://{}", listener.local_addr().expect("addr"));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, seen)
+    }
+
+    fn config(provider: LlmProvider, base_url: &str, max_retries: u32) -> LlmConfig {
+        LlmConfig {
+            provider,
+            api_key: (provider != LlmProvider::Ollama).then(|| "sk-test".to_string().into()),
+            base_url: Some(format!("{base_url}/")),
+            model: "test-model".to_string(),
+            timeout_secs: 5,
+            max_retries,
+        }
+    }
+
+    #[tokio::test]
+    async fn each_provider_is_called_with_its_own_request_shape() {
+        let (url, seen) = provider_stub(0).await;
+        for (provider, expected) in [
+            (LlmProvider::OpenAi, "{}"),
+            (LlmProvider::Anthropic, "{\"from\":\"claude\"}"),
+            (LlmProvider::Ollama, "{\"ok\":true}"),
+        ] {
+            let client = HttpLlmClient::from_config(&config(provider, &url, 0)).expect("client");
+            let completion = client.complete("extract this").await.expect("completion");
+            assert_eq!(completion, expected, "{provider:?}");
+        }
+
+        let seen = seen.lock().expect("lock");
+        assert_eq!(seen[0].0, "openai");
+        assert_eq!(seen[0].1.as_deref(), Some("Bearer sk-test"));
+        assert_eq!(seen[0].2["messages"][0]["content"], "extract this");
+        assert_eq!(seen[1].0, "anthropic");
+        assert_eq!(seen[1].1.as_deref(), Some("sk-test"));
+        assert_eq!(seen[1].2["model"], "test-model");
+        assert_eq!(seen[2].0, "ollama");
+        assert_eq!(seen[2].1, None);
+        assert_eq!(seen[2].2["prompt"], "extract this");
+        assert_eq!(seen[2].2["stream"], false);
+    }
+
+    #[tokio::test]
+    async fn unavailable_providers_are_retried_up_to_the_configured_limit() {
+        let (url, seen) = provider_stub(2).await;
+        let client =
+            HttpLlmClient::from_config(&config(LlmProvider::Ollama, &url, 2)).expect("client");
+        assert_eq!(client.complete("retry me").await.expect("completion"), "{\"ok\":true}");
+        assert_eq!(seen.lock().expect("lock").len(), 3);
+
+        let (url, seen) = provider_stub(5).await;
+        let client =
+            HttpLlmClient::from_config(&config(LlmProvider::Ollama, &url, 1)).expect("client");
+        let error = client.complete("give up").await.expect_err("retries exhausted");
+        assert!(error.to_string().contains("503"), "{error}");
+        assert_eq!(seen.lock().expect("lock").len(), 2);
+    }
+
+    #[test]
+    fn hosted_providers_need_a_key_and_ollama_needs_a_url() {
+        let mut openai = config(LlmProvider::OpenAi, "http://127.0.0.1:9", 0);
+        openai.api_key = None;
+        assert!(HttpLlmClient::from_config(&openai).is_err());
+
+        let mut ollama = config(LlmProvider::Ollama, "", 0);
+        ollama.base_url = None;
+        assert!(HttpLlmClient::from_config(&ollama).is_err());
+
+        let mut anthropic = config(LlmProvider::Anthropic, "", 0);
+        anthropic.base_url = None;
+        let client = HttpLlmClient::from_config(&anthropic).expect("client");
+        assert_eq!(client.base_url, "https://api.anthropic.com");
+    }
+}
@@ -1,11 +1,21 @@
+mod api;
+mod baselines;
 mod bootstrap;
 mod crm;
+mod dashboard;
+mod email;
 mod health;
+mod llm;
 mod pdf;
+mod policy_canary;
 pub mod portal;
 mod web;
+mod webhooks;
+
+use std::sync::Arc;
 
 use anyhow::Result;
+use quotey_agent::llm::LlmClient;
 use quotey_core::config::{AppConfig, LoadOptions};
 
 fn init_logging(config: &AppConfig) {
@@ -55,6 +65,19 @@ pub async fn run() -> Result<()> {
     )
     .await?;
 
+    // Inbound RFQ emails are turned into draft quotes with the configured LLM provider.
+    let llm: Option<Arc<dyn LlmClient>> = match llm::HttpLlmClient::from_config(&app.config.llm) {
+        Ok(client) => Some(Arc::new(client)),
+        Err(error) => {
+            tracing::warn!(%error, "cannot build the LLM client; inbound email intake is disabled");
+            None
+        }
+    };
+    let _email_worker = email::spawn(app.db_pool.clone(), llm);
+    let _webhook_worker = webhooks::spawn(app.db_pool.clone());
+    let _baseline_worker = baselines::spawn(app.db_pool.clone());
+    let _policy_canary_worker = policy_canary::spawn(app.db_pool.clone());
+
     tracing::info!(
         event_name = "system.server.slack_transport_mode",
         transport_mode = if app.slack_runner.is_noop_transport() { "noop" } else { "socket" },
@@ -1,18 +1,22 @@
 //! PDF Generation for Quotes
 //!
-//! This module handles PDF generation for quotes using HTML templates
-//! and conversion via external tools (wkhtmltopdf) or browser rendering.
+//! Quote PDFs are rendered in-process by `quotey_core::pdf`, so downloads are real PDFs with
+//! embedded fonts regardless of what is installed on the host. The Tera quote templates are
+//! still used for the browser print view.
 
 use axum::{
     body::Body,
     http::{header, StatusCode},
     response::Response,
 };
+use quotey_core::esign::AcceptanceCertificate;
+use quotey_core::pdf::template::ResolvedDocument;
+use quotey_core::pdf::{
+    render_quote_pdf, render_signed_quote_pdf, PdfBranding, PdfRenderError, PdfTemplate,
+};
 use std::collections::HashMap;
-use std::process::Stdio;
 use tera::{Context, Tera};
-use tokio::process::Command;
-use tracing::{error, info, warn};
+use tracing::info;
 
 #[derive(Clone, Debug, PartialEq)]
 struct TemplateBranding {
@@ -117,6 +121,23 @@ impl TemplateBranding {
         self.terms_footer.clone().or_else(|| self.footer_text.clone())
     }
 
+    fn to_pdf_branding(&self, quote_data: &serde_json::Value) -> PdfBranding {
+        PdfBranding {
+            company_name: self.company_name.clone(),
+            company_logo: self.company_logo.clone(),
+            company_address: self.company_address.clone(),
+            company_email: self.company_email.clone(),
+            company_phone: self.company_phone.clone(),
+            primary_color: self.primary_color.clone(),
+            secondary_color: self.secondary_color.clone(),
+            accent_color: self.accent_color.clone(),
+            support_contact_name: self.support_contact_name(quote_data),
+            support_contact_email: self.support_contact_email(quote_data),
+            terms_footer: self.resolved_terms_footer(),
+            white_label: self.white_label,
+        }
+    }
+
     fn insert_into_context(&self, context: &mut Context, quote_data: &serde_json::Value) {
         let support_contact_name = self.support_contact_name(quote_data);
         let support_contact_email = self.support_contact_email(quote_data);
@@ -257,20 +278,14 @@ fn register_quote_style_partials(tera: &mut Tera) {
 pub enum PdfError {
     #[error("template error: {0}")]
     Template(String),
-    #[error("conversion error: {0}")]
-    Conversion(String),
-    #[error("wkhtmltopdf not found")]
-    #[allow(dead_code)]
-    WkhtmltopdfNotFound,
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    #[error("render error: {0}")]
+    Render(#[from] PdfRenderError),
 }
 
 /// PDF generator configuration
 #[derive(Clone, Debug)]
 pub struct PdfGenerator {
     tera: Tera,
-    wkhtmltopdf_path: Option<String>,
 }
 
 impl PdfGenerator {
@@ -282,17 +297,7 @@ impl PdfGenerator {
         register_template_filters(&mut tera);
         register_quote_style_partials(&mut tera);
 
-        // Check for wkhtmltopdf
-        let wkhtmltopdf_path =
-            which::which("wkhtmltopdf").ok().map(|p| p.to_string_lossy().to_string());
-
-        if let Some(ref path) = wkhtmltopdf_path {
-            info!(path = %path, "wkhtmltopdf found");
-        } else {
-            warn!("wkhtmltopdf not found in PATH - PDF generation will use browser rendering");
-        }
-
-        Ok(Self { tera, wkhtmltopdf_path })
+        Ok(Self { tera })
     }
 
     /// Create a new PDF generator with embedded templates (for testing)
@@ -320,11 +325,7 @@ impl PdfGenerator {
         )
         .expect("Failed to load compact.html.tera template");
 
-        // Check for wkhtmltopdf
-        let wkhtmltopdf_path =
-            which::which("wkhtmltopdf").ok().map(|p| p.to_string_lossy().to_string());
-
-        Self { tera, wkhtmltopdf_path }
+        Self { tera }
     }
 
     /// Generate a PDF for a quote
@@ -334,126 +335,54 @@ impl PdfGenerator {
     /// * `template` - The template name to use (detailed, executive_summary, compact)
     ///
     /// # Returns
-    /// Either a PDF bytes or HTML bytes depending on wkhtmltopdf availability
-    pub async fn generate_quote_pdf(
+    /// The rendered PDF. Output is byte-for-byte identical for identical quote data, so its
+    /// hash can be recorded against the quote version.
+    pub fn generate_quote_pdf(
         &self,
         quote_data: &serde_json::Value,
         template: &str,
-    ) -> Result<PdfResult, PdfError> {
-        let branding = TemplateBranding::from_quote_data(quote_data);
-        // Build context
-        let mut context = Context::new();
-        context.insert("quote", quote_data);
-        context.insert(
-            "account",
-            &quote_data.get("account").cloned().unwrap_or(serde_json::json!({})),
-        );
-        context.insert("lines", &quote_data.get("lines").cloned().unwrap_or(serde_json::json!([])));
-        context.insert(
-            "pricing",
-            &quote_data.get("pricing").cloned().unwrap_or(serde_json::json!({})),
-        );
-        context.insert(
-            "sales_rep",
-            &quote_data.get("sales_rep").cloned().unwrap_or(serde_json::json!({})),
-        );
-        branding.insert_into_context(&mut context, quote_data);
+    ) -> Result<RenderedPdf, PdfError> {
+        let template = PdfTemplate::parse(template)
+            .ok_or_else(|| PdfError::Template(format!("unknown quote template `{template}`")))?;
+        let branding = TemplateBranding::from_quote_data(quote_data).to_pdf_branding(quote_data);
+        let bytes = render_quote_pdf(quote_data, template, &branding)?;
+        info!(size = bytes.len(), template = template.as_str(), "PDF generated successfully");
+        Ok(RenderedPdf(bytes))
+    }
 
-        // Assumption tracking for F-003
-        context.insert(
-            "assumptions",
-            &quote_data.get("assumptions").cloned().unwrap_or(serde_json::json!([])),
-        );
-        context.insert(
-            "has_assumptions",
-            &quote_data.get("has_assumptions").cloned().unwrap_or(serde_json::json!(false)),
-        );
-        context.insert(
-            "currency_explicit",
-            &quote_data.get("currency_explicit").cloned().unwrap_or(serde_json::json!(false)),
-        );
-        context.insert(
-            "tax_rate_explicit",
-            &quote_data.get("tax_rate_explicit").cloned().unwrap_or(serde_json::json!(false)),
-        );
-        context.insert(
-            "payment_terms_explicit",
-            &quote_data.get("payment_terms_explicit").cloned().unwrap_or(serde_json::json!(false)),
+    /// Generate a PDF using a template resolved from the registry: its layout, plus the
+    /// conditional sections and clauses that apply to this quote.
+    pub fn generate_document_pdf(
+        &self,
+        quote_data: &serde_json::Value,
+        document: &ResolvedDocument,
+    ) -> Result<RenderedPdf, PdfError> {
+        let mut payload = quote_data.clone();
+        document.insert_into(&mut payload);
+        let branding = TemplateBranding::from_quote_data(&payload).to_pdf_branding(&payload);
+        let bytes = render_quote_pdf(&payload, document.layout, &branding)?;
+        info!(
+            size = bytes.len(),
+            template = %document.template_key,
+            template_version = ?document.template_version,
+            "PDF generated successfully"
         );
-
-        // Render HTML
-        let template_name = format!("{}.html.tera", template);
-        let html = self
-            .tera
-            .render(&template_name, &context)
-            .map_err(|e| PdfError::Template(e.to_string()))?;
-
-        // If wkhtmltopdf is available, convert to PDF
-        if let Some(ref wkhtmltopdf) = self.wkhtmltopdf_path {
-            match self.convert_html_to_pdf(&html, wkhtmltopdf).await {
-                Ok(pdf_bytes) => Ok(PdfResult::Pdf(pdf_bytes)),
-                Err(e) => {
-                    warn!(error = %e, "PDF conversion failed, falling back to HTML");
-                    Ok(PdfResult::Html(html))
-                }
-            }
-        } else {
-            // Return HTML for browser rendering
-            Ok(PdfResult::Html(html))
-        }
+        Ok(RenderedPdf(bytes))
     }
 
-    /// Convert HTML to PDF using wkhtmltopdf
-    async fn convert_html_to_pdf(
+    /// Generate the quote PDF with a certificate-of-acceptance page appended
+    pub fn generate_signed_quote_pdf(
         &self,
-        html: &str,
-        wkhtmltopdf_path: &str,
-    ) -> Result<Vec<u8>, PdfError> {
-        // Write HTML to temp file
-        let temp_dir = std::env::temp_dir();
-        let html_path = temp_dir.join(format!("quote_{}.html", uuid::Uuid::new_v4()));
-        let pdf_path = temp_dir.join(format!("quote_{}.pdf", uuid::Uuid::new_v4()));
-
-        tokio::fs::write(&html_path, html).await?;
-
-        // Run wkhtmltopdf
-        let output = Command::new(wkhtmltopdf_path)
-            .arg("--page-size")
-            .arg("A4")
-            .arg("--margin-top")
-            .arg("10mm")
-            .arg("--margin-bottom")
-            .arg("10mm")
-            .arg("--margin-left")
-            .arg("10mm")
-            .arg("--margin-right")
-            .arg("10mm")
-            .arg("--encoding")
-            .arg("utf-8")
-            .arg("--enable-local-file-access")
-            .arg(&html_path)
-            .arg(&pdf_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error!(stderr = %stderr, "wkhtmltopdf failed");
-            return Err(PdfError::Conversion(stderr.to_string()));
-        }
-
-        // Read PDF bytes
-        let pdf_bytes = tokio::fs::read(&pdf_path).await?;
-
-        // Cleanup temp files
-        let _ = tokio::fs::remove_file(&html_path).await;
-        let _ = tokio::fs::remove_file(&pdf_path).await;
-
-        info!(size = pdf_bytes.len(), "PDF generated successfully");
-
-        Ok(pdf_bytes)
+        quote_data: &serde_json::Value,
+        template: &str,
+        certificate: &AcceptanceCertificate,
+    ) -> Result<RenderedPdf, PdfError> {
+        let template = PdfTemplate::parse(template)
+            .ok_or_else(|| PdfError::Template(format!("unknown quote template `{template}`")))?;
+        let branding = TemplateBranding::from_quote_data(quote_data).to_pdf_branding(quote_data);
+        let bytes = render_signed_quote_pdf(quote_data, template, &branding, certificate)?;
+        info!(size = bytes.len(), template = template.as_str(), "signed PDF generated");
+        Ok(RenderedPdf(bytes))
     }
 
     /// Generate HTML for browser printing
@@ -492,50 +421,26 @@ impl PdfGenerator {
     }
 }
 
-/// Result of PDF generation
-pub enum PdfResult {
-    Pdf(Vec<u8>),
-    Html(String),
-}
+/// A rendered quote PDF.
+pub struct RenderedPdf(pub Vec<u8>);
 
-impl PdfResult {
+impl RenderedPdf {
     /// Convert to an Axum response
     pub fn into_response(self, filename: &str) -> Response {
-        match self {
-            PdfResult::Pdf(bytes) => Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/pdf")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    format!("attachment; filename=\"{}\"", filename),
-                )
-                .body(Body::from(bytes))
-                .unwrap_or_else(|_| {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(Body::from("Failed to build PDF response"))
-                        .expect("static error response")
-                }),
-            PdfResult::Html(html) => Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
-                .body(Body::from(html))
-                .unwrap_or_else(|_| {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(Body::from("Failed to build HTML response"))
-                        .expect("static error response")
-                }),
-        }
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/pdf")
+            .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
+            .body(Body::from(self.0))
+            .unwrap_or_else(|_| {
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from("Failed to build PDF response"))
+                    .expect("static error response")
+            })
     }
 }
 
-/// Check if wkhtmltopdf is available
-#[allow(dead_code)]
-pub fn is_wkhtmltopdf_available() -> bool {
-    which::which("wkhtmltopdf").is_ok()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -603,23 +508,13 @@ mod tests {
         assert_eq!(branding.support_email.as_deref(), Some("quotes@fallback.example"));
     }
 
-    #[test]
-    fn pdf_generator_with_embedded_templates() {
-        let generator = PdfGenerator::with_embedded_templates();
-        assert!(generator.wkhtmltopdf_path.is_some() || generator.wkhtmltopdf_path.is_none());
-    }
-
-    #[tokio::test]
-    async fn generate_html_when_wkhtmltopdf_not_available() {
-        let mut generator = PdfGenerator::with_embedded_templates();
-        generator.wkhtmltopdf_path = None; // Force HTML mode
-
-        let quote_data = serde_json::json!({
+    fn sample_quote_data() -> serde_json::Value {
+        serde_json::json!({
             "id": "Q-TEST-001",
             "status": "sent",
-            "total": 1000.00,
             "created_at": "2024-01-15T10:00:00Z",
             "valid_until": "2024-02-15T23:59:59Z",
+            "currency": "USD",
             "account": {
                 "id": "ACC-001",
                 "name": "Test Account",
@@ -637,10 +532,8 @@ mod tests {
             ],
             "pricing": {
                 "subtotal": 1000.00,
-                "total_discount": 0.00,
                 "discount_total": 0.00,
                 "tax_rate": 0.08,
-                "tax": 80.00,
                 "tax_total": 80.00,
                 "total": 1080.00,
             },
@@ -650,26 +543,68 @@ mod tests {
                 "name": "Test Rep",
                 "email": "rep@example.com",
             },
-        });
+        })
+    }
 
-        let result = generator.generate_quote_pdf(&quote_data, "compact").await;
+    #[test]
+    fn generate_quote_pdf_renders_a_deterministic_pdf() {
+        let generator = PdfGenerator::with_embedded_templates();
+        let quote_data = sample_quote_data();
 
-        match result {
-            Ok(PdfResult::Html(html)) => {
-                assert!(html.contains("Q-TEST-001"));
-                assert!(html.contains("Test Account"));
-            }
-            Ok(PdfResult::Pdf(_)) => {
-                panic!("Expected HTML result when wkhtmltopdf is not available")
-            }
-            Err(e) => {
-                eprintln!("PDF generation error: {}", e);
-                // For debugging, let's just verify the template loads and the generator works
-                // The actual rendering error might be due to Tera date filter limitations
-                // For now, we'll accept that HTML generation works conceptually
-                // Test passes if we get this far — error is expected due to Tera date filter limitations
-            }
-        }
+        let first = generator.generate_quote_pdf(&quote_data, "compact").expect("render");
+        let second = generator.generate_quote_pdf(&quote_data, "compact").expect("render");
+        assert!(first.0.starts_with(b"%PDF-"));
+        assert_eq!(first.0, second.0);
+
+        let response = first.into_response("Quote_Q-TEST-001.pdf");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/pdf");
+    }
+
+    #[test]
+    fn generate_document_pdf_uses_the_resolved_layout() {
+        use quotey_core::pdf::template::{DocumentSection, QuoteDocumentTemplate};
+
+        let generator = PdfGenerator::with_embedded_templates();
+        let quote_data = sample_quote_data();
+        let compact = QuoteDocumentTemplate::builtin(PdfTemplate::Compact)
+            .resolve("compact", None, &Default::default(), &[])
+            .expect("resolve");
+        let rendered = generator.generate_document_pdf(&quote_data, &compact).expect("render");
+        let named = generator.generate_quote_pdf(&quote_data, "compact").expect("render");
+        assert_eq!(rendered.0, named.0, "built-in templates match the named layouts");
+
+        let with_clause = ResolvedDocument {
+            sections: vec![DocumentSection::Clause {
+                key: "msa".into(),
+                version: 1,
+                title: "Master Services Agreement".into(),
+                body: "Governed by the master services agreement.".into(),
+            }],
+            ..compact
+        };
+        let rendered = generator.generate_document_pdf(&quote_data, &with_clause).expect("render");
+        assert_ne!(rendered.0, named.0);
+    }
+
+    #[test]
+    fn generate_quote_pdf_rejects_unknown_templates() {
+        let generator = PdfGenerator::with_embedded_templates();
+        let result = generator.generate_quote_pdf(&sample_quote_data(), "invoice");
+        assert!(matches!(result, Err(PdfError::Template(_))));
+    }
+
+    #[test]
+    fn template_branding_maps_to_pdf_branding() {
+        let quote_data = serde_json::json!({
+            "sales_rep": {"name": "Default Rep", "email": "rep@example.com"},
+            "branding": {"accent_color": "#556677", "footer_text": "Footer terms."}
+        });
+
+        let branding = TemplateBranding::from_quote_data(&quote_data).to_pdf_branding(&quote_data);
+        assert_eq!(branding.accent_color, "#556677");
+        assert_eq!(branding.support_contact_name, "Default Rep");
+        assert_eq!(branding.support_contact_email, "rep@example.com");
+        assert_eq!(branding.terms_footer.as_deref(), Some("Footer terms."));
     }
 
     #[test]
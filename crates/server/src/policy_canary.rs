@@ -0,0 +1,79 @@
+//! Scheduled evaluation of the monitoring policy canary.
+//!
+//! Every few minutes the worker scores the canary cohort's negotiation KPIs and pricing
+//! anomalies, promoting the canary once the rollout gate is ready and rolling it back on a
+//! no-go. Rollbacks are signed with `QUOTEY_POLICY_SIGNING_KEY`; without it a failing canary is
+//! left monitoring and a warning is logged until an operator rolls it back.
+
+use std::time::Duration;
+
+use quotey_db::policy_apply::{CanaryDecision, PolicyApplyError, PolicyApplyService, PolicySigner};
+use quotey_db::DbPool;
+use tracing::{info, warn};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// One evaluation pass; returns the decision when a canary was monitoring.
+async fn evaluate_once(pool: &DbPool, signer: Option<&PolicySigner>) -> Option<CanaryDecision> {
+    match PolicyApplyService::new(pool.clone()).evaluate_canary(signer).await {
+        Ok(Some(evaluation)) => {
+            let canary = &evaluation.canary;
+            match evaluation.decision {
+                CanaryDecision::Monitoring => {}
+                CanaryDecision::Promoted => info!(
+                    canary_id = %canary.id,
+                    version = canary.canary_version,
+                    reason = canary.decision_reason.as_deref().unwrap_or_default(),
+                    "policy canary promoted"
+                ),
+                CanaryDecision::RolledBack => warn!(
+                    canary_id = %canary.id,
+                    version = canary.canary_version,
+                    reason = canary.decision_reason.as_deref().unwrap_or_default(),
+                    "policy canary rolled back"
+                ),
+            }
+            Some(evaluation.decision)
+        }
+        Ok(None) => None,
+        Err(PolicyApplyError::SignerRequired(canary_id)) => {
+            warn!(
+                %canary_id,
+                "policy canary failed its rollout gate; set QUOTEY_POLICY_SIGNING_KEY or roll it back manually"
+            );
+            None
+        }
+        Err(error) => {
+            warn!(%error, "policy canary evaluation failed");
+            None
+        }
+    }
+}
+
+/// Evaluates the monitoring canary on a fixed interval, until the process exits.
+pub fn spawn(pool: DbPool) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let signer = PolicySigner::from_env();
+        loop {
+            evaluate_once(&pool, signer.as_ref()).await;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quotey_db::{connect_with_settings, migrations};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn evaluation_is_a_no_op_without_a_monitoring_canary() {
+        let pool = connect_with_settings("sqlite::memory:", 1, 30).await.expect("connect");
+        migrations::run_pending(&pool).await.expect("migrations");
+
+        assert_eq!(evaluate_once(&pool, None).await, None);
+        let service = PolicyApplyService::new(pool.clone());
+        assert!(service.list_canaries(10).await.expect("canaries").is_empty());
+    }
+}
@@ -28,7 +28,10 @@
 //! - `POST /api/v1/portal/push/subscribe`       — register browser push subscription
 //! - `POST /api/v1/portal/push/unsubscribe`     — revoke browser push subscription
 
-use crate::pdf::PdfGenerator;
+use crate::api::load_quote_policy;
+use crate::dashboard::{self, DashboardError, DashboardQuery};
+use crate::pdf::{PdfGenerator, RenderedPdf};
+use crate::webhooks;
 use axum::{
     extract::{Path, Query, Request, State},
     http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
@@ -38,8 +41,34 @@ use axum::{
     Json, Router,
 };
 use chrono::{Datelike, Duration, Timelike, Utc};
+use quotey_core::cpq::margin::{MarginLine, QuoteMargin};
+use quotey_core::cpq::negotiation_turn::{CounterTerms, CounterVerdict, TurnParty};
+use quotey_core::cpq::policy::PolicyInput;
+use quotey_core::cpq::policy_rules::PolicyRuleLine;
+use quotey_core::dna::ClosedDealOutcome;
+use quotey_core::domain::quote::QuoteId;
+use quotey_core::esign::{
+    document_sha256, mask_email, AcceptanceCertificate, SignatureCapture, SignatureError,
+    SignerIdentity, SigningEvidence,
+};
+use quotey_core::webhooks::WebhookEventType;
+use quotey_core::{
+    policy_evaluation_from_decision, pricing_snapshot_from_lines, PricingLineSnapshot,
+};
 use quotey_core::{AuthChannel, AuthContext, AuthMethod, AuthPrincipal, AuthStrength};
+use quotey_db::analytics::AnalyticsCache;
+use quotey_db::esign::{QuoteSignatureService, SignatureServiceError, SignatureStart};
+use quotey_db::explain::{ExplainError, ExplainQuery, ExplainService, ExplainTarget, Explanation};
+use quotey_db::negotiation::{NegotiationError, NegotiationService, RecordTurn, TurnAction};
+use quotey_db::policy_apply::policy_version_label;
+use quotey_db::quote_templates::{QuoteTemplateService, TemplateSelection, FALLBACK_TEMPLATE};
+use quotey_db::repositories::{
+    QuoteRepository, SqlPricingSnapshotRepository, SqlProductCostRepository, SqlQuoteRepository,
+};
+use quotey_db::similarity::DealSimilarityService;
 use quotey_db::DbPool;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{QueryBuilder, Row};
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -132,6 +161,10 @@ pub struct PortalState {
     pdf_generator: Option<Arc<PdfGenerator>>,
     branding: BrandingConfig,
     rep_notifications: PortalRepNotificationConfig,
+    /// HMAC key for quote ledger entries; customer signing is unavailable without it.
+    ledger_signing_key: Option<Arc<str>>,
+    /// Shared with the dashboard feed so repeat loads within the TTL skip the database.
+    analytics_cache: AnalyticsCache,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -218,6 +251,47 @@ pub struct RejectRequest {
     pub fallback_password: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CounterRequest {
+    #[serde(rename = "discountPct")]
+    pub discount_pct: f64,
+    #[serde(rename = "termMonths", default)]
+    pub term_months: Option<u32>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(rename = "idempotencyKey", default)]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CounterResponse {
+    pub success: bool,
+    pub message: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub status: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignStartRequest {
+    #[serde(rename = "signerName")]
+    pub signer_name: String,
+    #[serde(rename = "signerTitle")]
+    pub signer_title: String,
+    #[serde(rename = "signerEmail")]
+    pub signer_email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignCompleteRequest {
+    #[serde(rename = "signatureRequestId")]
+    pub signature_request_id: String,
+    pub code: String,
+    pub signature: SignatureCapture,
+    #[serde(default)]
+    pub consent: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ApprovalActionAuth {
     None,
@@ -408,6 +482,28 @@ pub struct LiveApprovalItem {
     pub latest_comment_at: Option<String>,
 }
 
+/// A signing code was sent; the code itself is only delivered by email.
+#[derive(Debug, Serialize)]
+pub struct SignStartResponse {
+    pub success: bool,
+    pub signature_request_id: String,
+    pub code_sent_to: String,
+    pub expires_at: String,
+    /// SHA-256 of the unsigned PDF the signer is accepting.
+    pub document_sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignCompleteResponse {
+    pub success: bool,
+    pub message: String,
+    pub quote_id: String,
+    pub signed_pdf_url: String,
+    pub signed_pdf_sha256: String,
+    pub ledger_entry_id: String,
+    pub signed_at: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct PortalResponse {
     pub success: bool,
@@ -465,7 +561,6 @@ impl PortalError {
         }
     }
 
-    #[allow(dead_code)]
     fn rate_limited(retry_after: u32) -> Self {
         Self {
             error: "Too many requests".to_string(),
@@ -578,6 +673,11 @@ fn init_templates() -> Arc<Tera> {
         include_str!("../../../templates/portal/approval_detail.html"),
     )
     .ok();
+    tera.add_raw_template(
+        "dashboard/analytics.html",
+        include_str!("../../../templates/dashboard/analytics.html"),
+    )
+    .ok();
 
     Arc::new(tera)
 }
@@ -601,10 +701,12 @@ pub fn router(db_pool: DbPool) -> Router {
         // HTML routes
         .route("/quote/{token}", get(view_quote_page))
         .route("/quote/{token}/download", get(download_quote_pdf))
+        .route("/quote/{token}/signed.pdf", get(download_signed_quote_pdf))
         .route("/portal", get(portal_index_page))
         .route("/approvals", get(approvals_index_page))
         .route("/approvals/{id}", get(approval_detail_page))
         .route("/settings", get(approvals_settings_page))
+        .route("/analytics", get(analytics_dashboard_page))
         .route("/manifest.webmanifest", get(portal_manifest))
         .route("/sw.js", get(portal_service_worker))
         .route("/shell.js", get(portal_shell_script))
@@ -616,8 +718,12 @@ pub fn router(db_pool: DbPool) -> Router {
         // JSON API routes
         .route("/quote/{token}/approve", post(approve_quote))
         .route("/quote/{token}/reject", post(reject_quote))
+        .route("/quote/{token}/counter", post(propose_counter))
+        .route("/quote/{token}/sign/start", post(start_quote_signature))
+        .route("/quote/{token}/sign/complete", post(complete_quote_signature))
         .route("/quote/{token}/comment", post(add_comment))
         .route("/quote/{token}/comments", get(list_comments))
+        .route("/quote/{token}/explain", get(explain_quote_number))
         .route("/quote/{token}/line/{line_id}/comment", post(add_line_comment))
         .route("/quote/{token}/assumptions", post(update_assumptions))
         .route("/api/v1/portal/links", post(create_link))
@@ -630,6 +736,7 @@ pub fn router(db_pool: DbPool) -> Router {
         .route("/api/v1/portal/push/unsubscribe", post(unsubscribe_push))
         .route("/api/v1/portal/export/quotes", get(export_quotes_csv))
         .route("/api/analytics/export", get(export_quotes_csv))
+        .route("/api/analytics/dashboard", get(analytics_dashboard_data))
         .route("/api/v1/portal/analytics/digest-schedule", get(get_digest_schedule))
         .route("/api/v1/portal/analytics/digest-schedule", post(upsert_digest_schedule))
         .route("/api/v1/portal/analytics/digest-dispatch/run", post(run_digest_dispatch))
@@ -640,6 +747,12 @@ pub fn router(db_pool: DbPool) -> Router {
             pdf_generator,
             branding: BrandingConfig::from_env(),
             rep_notifications: PortalRepNotificationConfig::from_env(),
+            ledger_signing_key: std::env::var("QUOTEY_LEDGER_SIGNING_KEY")
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .map(Arc::from),
+            analytics_cache: AnalyticsCache::default(),
         })
 }
 
@@ -664,6 +777,7 @@ fn canonical_quote_status(raw_status: &str) -> &'static str {
         "expired" => "expired",
         "approved" => "approved",
         "sent" => "sent",
+        "accepted" => "accepted",
         _ => "pending",
     }
 }
@@ -679,6 +793,7 @@ fn normalize_status_filter(raw_status: Option<&str>) -> Option<Vec<String>> {
         Some("sent") => Some(vec!["sent".to_string()]),
         Some("approved") => Some(vec!["approved".to_string()]),
         Some("expired") => Some(vec!["expired".to_string()]),
+        Some("accepted") => Some(vec!["accepted".to_string()]),
         Some(other) => Some(vec![other.to_string()]),
     }
 }
@@ -1158,6 +1273,7 @@ async fn view_quote_page(
             let line_total = subtotal * (1.0 - discount_pct / 100.0);
 
             serde_json::json!({
+                "id": row.try_get::<String, _>("id").unwrap_or_default(),
                 "product_name": row.try_get::<String, _>("product_name").unwrap_or_default(),
                 "quantity": quantity,
                 "unit_price": format_price(unit_price),
@@ -1416,6 +1532,30 @@ async fn view_quote_page(
         has_snapshot,
     );
 
+    let acceptance = if display_status == "accepted" {
+        sqlx::query(
+            "SELECT signer_name, signer_title, signed_at, signed_pdf_sha256
+             FROM quote_signature_request
+             WHERE quote_id = ? AND status = 'signed'
+             ORDER BY signed_at DESC LIMIT 1",
+        )
+        .bind(&quote_id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(redacted_db_error)?
+        .map(|row| {
+            serde_json::json!({
+                "signer_name": row.try_get::<String, _>("signer_name").unwrap_or_default(),
+                "signer_title": row.try_get::<String, _>("signer_title").unwrap_or_default(),
+                "signed_at": row.try_get::<String, _>("signed_at").unwrap_or_default(),
+                "signed_pdf_sha256":
+                    row.try_get::<String, _>("signed_pdf_sha256").unwrap_or_default(),
+            })
+        })
+    } else {
+        None
+    };
+
     context.insert("quote", &serde_json::json!({
         "quote_id": quote_id,
         "token": token,
@@ -1449,6 +1589,7 @@ async fn view_quote_page(
         "payment_terms_explicit": payment_terms_explicit,
         "billing_country_explicit": billing_country_explicit,
         "pricing_rationale": pricing_rationale,
+        "acceptance": acceptance,
     }));
 
     context.insert(
@@ -1556,9 +1697,20 @@ async fn download_quote_pdf(
     let quote_data =
         fetch_quote_for_pdf(&state.db_pool, &quote_id, &state.branding.company_name).await?;
 
-    // Generate PDF (or HTML if wkhtmltopdf not available)
+    // The account's default template (or `detailed`); a broken template must not block downloads.
+    let rendered = match QuoteTemplateService::new(state.db_pool.clone())
+        .resolve_for_quote(&quote_id, TemplateSelection::default())
+        .await
+    {
+        Ok(document) => pdf_generator.generate_document_pdf(&quote_data, &document),
+        Err(e) => {
+            warn!(error = %e, quote_id = %quote_id, "quote template unavailable, using default");
+            pdf_generator.generate_quote_pdf(&quote_data, FALLBACK_TEMPLATE)
+        }
+    };
+
     let filename = format!("Quote_{}.pdf", quote_id);
-    match pdf_generator.generate_quote_pdf(&quote_data, "detailed").await {
+    match rendered {
         Ok(result) => {
             info!(
                 event_name = "portal.pdf.generated",
@@ -1594,8 +1746,27 @@ async fn fetch_quote_for_pdf(
     quote_id: &str,
     company_name: &str,
 ) -> Result<serde_json::Value, (StatusCode, Json<PortalError>)> {
+    match quote_pdf_payload(pool, quote_id, company_name).await {
+        Ok(Some(quote_data)) => Ok(quote_data),
+        Ok(None) => Err((StatusCode::NOT_FOUND, Json(PortalError::not_found("quote")))),
+        Err(e) => {
+            error!(error = %e, "Failed to load quote for PDF");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(PortalError::service_unavailable("database")),
+            ))
+        }
+    }
+}
+
+/// The JSON payload quote documents are rendered from; `None` when the quote does not exist.
+pub(crate) async fn quote_pdf_payload(
+    pool: &DbPool,
+    quote_id: &str,
+    company_name: &str,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
     // Fetch quote basic info
-    let quote_row = sqlx::query(
+    let Some(quote_row) = sqlx::query(
         r#"SELECT
             q.id, q.status, q.created_at, q.valid_until, q.currency,
             q.account_id
@@ -1603,14 +1774,11 @@ async fn fetch_quote_for_pdf(
          WHERE q.id = ?"#,
     )
     .bind(quote_id)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| match e {
-        sqlx::Error::RowNotFound => (StatusCode::NOT_FOUND, Json(PortalError::not_found("quote"))),
-        _ => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(PortalError::service_unavailable("database")))
-        }
-    })?;
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
 
     // Fetch quote lines
     let account_id: Option<String> =
@@ -1628,14 +1796,7 @@ async fn fetch_quote_for_pdf(
     )
     .bind(quote_id)
     .fetch_all(pool)
-    .await
-    .map_err(|e| {
-        error!(error = %e, "Failed to fetch quote lines");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(PortalError::service_unavailable("database")),
-        )
-    })?;
+    .await?;
 
     // Calculate pricing summary using fold to properly accumulate totals
     let (subtotal, total_discount, lines): (f64, f64, Vec<serde_json::Value>) = lines.iter().fold(
@@ -1708,7 +1869,7 @@ async fn fetch_quote_for_pdf(
         "status_text": canonical_quote_status(&raw_status),
     });
 
-    Ok(quote_data)
+    Ok(Some(quote_data))
 }
 
 /// Render the portal index page (list of quotes).
@@ -2263,8 +2424,8 @@ async fn approval_detail_page(
 
     // Fetch quote lines with product names
     let line_rows = sqlx::query(
-        r#"SELECT ql.id AS line_id, ql.quantity, ql.unit_price, ql.subtotal, ql.discount_pct,
-                  p.name AS product_name, p.sku AS product_sku
+        r#"SELECT ql.id AS line_id, ql.product_id, ql.quantity, ql.unit_price, ql.subtotal,
+                  ql.discount_pct, p.name AS product_name, p.sku AS product_sku
            FROM quote_line ql
            LEFT JOIN product p ON p.id = ql.product_id
            WHERE ql.quote_id = ?
@@ -2275,10 +2436,20 @@ async fn approval_detail_page(
     .await
     .map_err(db_err)?;
 
+    // Approvers see margin over product cost; the customer-facing quote page never does.
+    let unit_costs = SqlProductCostRepository::new(state.db_pool.clone())
+        .unit_costs_for_quote(&quote_id, Utc::now().date_naive())
+        .await
+        .unwrap_or_else(|error| {
+            warn!(%error, %quote_id, "approval_detail product costs unavailable");
+            BTreeMap::new()
+        });
+
     // Build line items and compute totals
     let mut subtotal = 0.0_f64;
     let mut discount_total = 0.0_f64;
-    let lines: Vec<serde_json::Value> = line_rows
+    let mut margin_lines = Vec::with_capacity(line_rows.len());
+    let mut lines: Vec<serde_json::Value> = line_rows
         .iter()
         .map(|r| {
             let qty: i64 = r.try_get("quantity").unwrap_or(0);
@@ -2294,9 +2465,16 @@ async fn approval_detail_page(
 
             subtotal += base_subtotal;
             discount_total += discount_amount;
+            let line_id = r.try_get::<String, _>("line_id").unwrap_or_default();
+            margin_lines.push(MarginLine {
+                line_id: line_id.clone(),
+                product_id: r.try_get::<String, _>("product_id").unwrap_or_default(),
+                quantity: u32::try_from(qty).unwrap_or(0),
+                net_amount: money_decimal(total_price),
+            });
 
             serde_json::json!({
-                "id": r.try_get::<String, _>("line_id").unwrap_or_default(),
+                "id": line_id,
                 "product_name": r.try_get::<String, _>("product_name").unwrap_or_default(),
                 "sku": r.try_get::<String, _>("product_sku").unwrap_or_default(),
                 "quantity": qty,
@@ -2310,6 +2488,10 @@ async fn approval_detail_page(
     let total = subtotal - discount_total;
     let discount_pct =
         if subtotal > 0.0 { ((discount_total / subtotal) * 100.0).clamp(0.0, 100.0) } else { 0.0 };
+    let margin = QuoteMargin::compute(&margin_lines, &unit_costs);
+    for (line, line_margin) in lines.iter_mut().zip(&margin.lines) {
+        line["margin_pct"] = serde_json::json!(line_margin.margin_pct.and_then(|pct| pct.to_f64()));
+    }
 
     let comment_rows = sqlx::query(
         r#"SELECT
@@ -2524,6 +2706,9 @@ async fn approval_detail_page(
             "discount_total": format_price(discount_total),
             "discount_pct": (discount_pct * 10.0).round() / 10.0,
             "total": format_price(total),
+            "margin_pct": margin.margin_pct.and_then(|pct| pct.to_f64()),
+            "cost_total": format_price(margin.cost.to_f64().unwrap_or(0.0)),
+            "uncosted_line_count": margin.uncosted_line_ids.len(),
         }),
     );
 
@@ -2559,6 +2744,70 @@ async fn approvals_settings_page(
     Ok(Html(html))
 }
 
+async fn analytics_dashboard_page(
+    Query(params): Query<DashboardQuery>,
+    State(state): State<PortalState>,
+) -> Result<Html<String>, (StatusCode, Html<String>)> {
+    let reps: Vec<serde_json::Value> =
+        sqlx::query_as::<_, (String, String)>("SELECT id, name FROM sales_rep ORDER BY name")
+            .fetch_all(&state.db_pool)
+            .await
+            .map_err(redacted_db_error)?
+            .into_iter()
+            .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+            .collect();
+    let data = match dashboard::dashboard_data(&state.db_pool, &state.analytics_cache, &params)
+        .await
+    {
+        Ok(data) => data,
+        Err(DashboardError::InvalidFilter(reason)) => {
+            return Err((StatusCode::BAD_REQUEST, Html(format!("<p>{reason}</p>"))));
+        }
+        Err(DashboardError::Analytics(error)) => {
+            warn!(error = %error, "analytics dashboard query failed (redacted from user response)");
+            return Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                Html("<h1>Service Unavailable</h1><p>Analytics are temporarily unavailable. Please try again later.</p>".to_string()),
+            ));
+        }
+    };
+
+    let mut context = Context::new();
+    context.insert("branding", &state.branding);
+    context.insert("reps", &reps);
+    context.insert("metrics", &data["metrics"]);
+    context.insert("top_products", &data["top_products"]);
+    context.insert("approver_stats", &data["approver_stats"]);
+
+    let html = state
+        .templates
+        .render("dashboard/analytics.html", &context)
+        .map_err(redacted_template_error)?;
+
+    Ok(Html(html))
+}
+
+async fn analytics_dashboard_data(
+    Query(params): Query<DashboardQuery>,
+    State(state): State<PortalState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<PortalError>)> {
+    dashboard::dashboard_data(&state.db_pool, &state.analytics_cache, &params)
+        .await
+        .map(Json)
+        .map_err(|error| match error {
+            DashboardError::InvalidFilter(reason) => {
+                (StatusCode::BAD_REQUEST, Json(PortalError::validation("date range", &reason)))
+            }
+            DashboardError::Analytics(error) => {
+                warn!(error = %error, "analytics dashboard query failed");
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(PortalError::service_unavailable("Analytics")),
+                )
+            }
+        })
+}
+
 /// Format a price for display
 fn format_price(amount: f64) -> String {
     format!("${:.2}", amount)
@@ -2963,6 +3212,21 @@ async fn approve_quote(
         "quote approved via web portal"
     );
 
+    webhooks::emit(
+        &state.db_pool,
+        WebhookEventType::ApprovalDecided,
+        &quote_id,
+        serde_json::json!({
+            "approval_id": approval_id,
+            "status": "approved",
+            "note": body.comments,
+            "quote_status": "approved",
+            "actor": format!("portal:{approver_email}"),
+            "source": "portal",
+        }),
+    )
+    .await;
+
     Ok(Json(PortalResponse {
         success: true,
         message: format!(
@@ -3053,12 +3317,487 @@ async fn reject_quote(
         "quote rejected via web portal"
     );
 
+    webhooks::emit(
+        &state.db_pool,
+        WebhookEventType::ApprovalDecided,
+        &quote_id,
+        serde_json::json!({
+            "approval_id": rejection_id,
+            "status": "rejected",
+            "note": reason,
+            "quote_status": "rejected",
+            "actor": "portal:customer",
+            "source": "portal",
+        }),
+    )
+    .await;
+
     Ok(Json(PortalResponse {
         success: true,
         message: "Quote declined. Your sales rep has been notified.".to_string(),
     }))
 }
 
+/// Records a customer counteroffer on the quote's negotiation session, opening one if needed.
+/// The reply only says whether the counter went to the rep or needs approval; the margin and
+/// boundary details behind that verdict stay internal.
+async fn propose_counter(
+    Path(token): Path<String>,
+    State(state): State<PortalState>,
+    Json(body): Json<CounterRequest>,
+) -> Result<Json<CounterResponse>, (StatusCode, Json<PortalError>)> {
+    let quote_id = resolve_quote_by_token(&state.db_pool, &token).await?;
+
+    let terms = CounterTerms { discount_pct: body.discount_pct, term_months: body.term_months };
+    if let Err(reason) = terms.validate() {
+        return Err((StatusCode::BAD_REQUEST, Json(PortalError::validation("counter", &reason))));
+    }
+    let note = body.note.as_deref().map(str::trim).filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.len() > 2000) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(PortalError::validation("note", "must be 2000 characters or fewer")),
+        ));
+    }
+
+    let policy_version = load_quote_policy(&state.db_pool, &quote_id).await.version;
+    let service = NegotiationService::new(state.db_pool.clone());
+    let session = service
+        .open_session(
+            &quote_id,
+            "portal:customer",
+            &policy_version_label(policy_version.unwrap_or(1)),
+        )
+        .await
+        .map_err(negotiation_error)?;
+    let correlation_id = format!("portal-counter-{}", uuid_v4());
+    let recorded = service
+        .record(RecordTurn {
+            session_id: session.id.0.clone(),
+            action: TurnAction::Counter(terms.clone()),
+            party: TurnParty::Customer,
+            actor_id: "portal:customer".to_string(),
+            note: note.map(str::to_string),
+            idempotency_key: body
+                .idempotency_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string),
+            correlation_id: correlation_id.clone(),
+        })
+        .await
+        .map_err(negotiation_error)?;
+
+    let verdict = recorded.check.as_ref().map(|check| &check.verdict);
+    let (status, message) = match verdict {
+        Some(CounterVerdict::Blocked) => (
+            "declined",
+            "This counteroffer is outside what we can offer. Try a smaller discount or contact your sales rep.",
+        ),
+        Some(CounterVerdict::NeedsApproval) => (
+            "pending_approval",
+            "Counteroffer received. It needs internal approval; your sales rep will follow up.",
+        ),
+        _ => ("submitted", "Counteroffer sent. Your sales rep has been notified."),
+    };
+
+    if !recorded.replayed {
+        let term_label =
+            terms.term_months.map(|months| format!(", {months} months")).unwrap_or_default();
+        record_audit_event(
+            &state.db_pool,
+            Some(&quote_id),
+            "portal.negotiation.counter",
+            &format!(
+                "Customer countered at {:.2}% discount{term_label} via web portal ({status})",
+                terms.discount_pct
+            ),
+        )
+        .await;
+    }
+
+    info!(
+        event_name = "portal.negotiation.counter",
+        correlation_id = %correlation_id,
+        quote_id = %quote_id,
+        session_id = %recorded.session_id,
+        status,
+        "customer counteroffer recorded via web portal"
+    );
+
+    Ok(Json(CounterResponse {
+        success: !matches!(verdict, Some(CounterVerdict::Blocked)),
+        message: message.to_string(),
+        session_id: recorded.session_id,
+        status,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Customer e-signature
+// ---------------------------------------------------------------------------
+
+const SIGNING_TEMPLATE: &str = "detailed";
+
+/// Sends a one-time code to the signer after pinning the hash of the PDF they are accepting.
+async fn start_quote_signature(
+    Path(token): Path<String>,
+    State(state): State<PortalState>,
+    headers: HeaderMap,
+    Json(body): Json<SignStartRequest>,
+) -> Result<Json<SignStartResponse>, (StatusCode, Json<PortalError>)> {
+    let quote_id = resolve_quote_by_token(&state.db_pool, &token).await?;
+    signing_key(&state)?;
+    let signer = SignerIdentity::new(&body.signer_name, &body.signer_title, &body.signer_email)
+        .map_err(signature_validation_error)?;
+    let service = QuoteSignatureService::new(state.db_pool.clone());
+    // Refuse throttled starts before paying for a PDF render.
+    service.check_start_allowed(&quote_id, &signer.email).await.map_err(signature_service_error)?;
+    let (_, unsigned_pdf) = render_signing_document(&state, &quote_id).await?;
+
+    let issued = service
+        .start(SignatureStart {
+            quote_id: quote_id.clone(),
+            template: SIGNING_TEMPLATE.to_string(),
+            document_sha256: document_sha256(&unsigned_pdf),
+            signer,
+            ip_address: extract_requester_ip(&headers),
+            user_agent: extract_user_agent(&headers),
+        })
+        .await
+        .map_err(signature_service_error)?;
+    let request = issued.request;
+
+    record_audit_event(
+        &state.db_pool,
+        Some(&quote_id),
+        "portal.signature.started",
+        &format!(
+            "Signing code sent to {} for {} ({}) [request={}, ip={}]",
+            mask_email(&request.signer.email),
+            request.signer.name,
+            request.signer.title,
+            request.id,
+            request.requested_ip
+        ),
+    )
+    .await;
+    info!(
+        event_name = "portal.signature.started",
+        quote_id = %quote_id,
+        signature_request_id = %request.id,
+        email_task_id = %issued.email_task_id,
+        "signing code queued for delivery"
+    );
+
+    Ok(Json(SignStartResponse {
+        success: true,
+        signature_request_id: request.id,
+        code_sent_to: mask_email(&request.signer.email),
+        expires_at: request.code_expires_at.to_rfc3339(),
+        document_sha256: request.document_sha256,
+    }))
+}
+
+/// Confirms the code, stamps the certificate page onto the quote PDF and closes the deal.
+async fn complete_quote_signature(
+    Path(token): Path<String>,
+    State(state): State<PortalState>,
+    headers: HeaderMap,
+    Json(body): Json<SignCompleteRequest>,
+) -> Result<Json<SignCompleteResponse>, (StatusCode, Json<PortalError>)> {
+    let quote_id = resolve_quote_by_token(&state.db_pool, &token).await?;
+    let ledger_key = signing_key(&state)?;
+    if !body.consent {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(PortalError::validation("consent", "you must agree to sign electronically")),
+        ));
+    }
+    body.signature.validate().map_err(signature_validation_error)?;
+
+    let service = QuoteSignatureService::new(state.db_pool.clone());
+    let verified = service
+        .verify_code(&quote_id, body.signature_request_id.trim(), &body.code)
+        .await
+        .map_err(signature_service_error)?;
+    let request = verified.request().clone();
+
+    let (quote_data, unsigned_pdf) = render_signing_document(&state, &quote_id).await?;
+    if document_sha256(&unsigned_pdf) != request.document_sha256 {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(PortalError {
+                error: "This quote changed after you started signing".to_string(),
+                category: Some(PortalErrorCategory::ValidationError),
+                recovery_hint: Some(
+                    "Reload the page to review the latest version, then sign again.".to_string(),
+                ),
+                retry_after_seconds: None,
+            }),
+        ));
+    }
+
+    let certificate = AcceptanceCertificate {
+        request_id: request.id.clone(),
+        quote_id: quote_id.clone(),
+        quote_version: request.quote_version,
+        document_sha256: request.document_sha256.clone(),
+        signer: request.signer.clone(),
+        signature: body.signature,
+        evidence: SigningEvidence {
+            code_sent_at: request.created_at,
+            requested_ip: request.requested_ip.clone(),
+            requested_user_agent: request.requested_user_agent.clone(),
+            signed_at: Utc::now(),
+            signed_ip: extract_requester_ip(&headers),
+            signed_user_agent: extract_user_agent(&headers),
+        },
+    };
+    let signed_pdf = state
+        .pdf_generator
+        .as_ref()
+        .ok_or_else(pdf_generator_unavailable)?
+        .generate_signed_quote_pdf(&quote_data, &request.template, &certificate)
+        .map_err(|e| {
+            error!(error = %e, quote_id = %quote_id, "signed PDF generation failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(PortalError::service_unavailable("PDF generator")),
+            )
+        })?;
+    let acceptance = service
+        .record_acceptance(verified, &certificate, &signed_pdf.0, ledger_key.as_bytes())
+        .await
+        .map_err(signature_service_error)?;
+
+    close_won_deal(&state.db_pool, &quote_id, &acceptance.signed_at.date_naive().to_string()).await;
+    record_audit_event(
+        &state.db_pool,
+        Some(&quote_id),
+        "portal.signature.completed",
+        &format!(
+            "Quote accepted by {} ({}, {}) via e-signature [request={}, signature={}, \
+             ip={}, document={}, ledger={}]",
+            request.signer.name,
+            request.signer.title,
+            mask_email(&request.signer.email),
+            request.id,
+            certificate.signature.kind(),
+            certificate.evidence.signed_ip,
+            acceptance.signed_pdf_sha256,
+            acceptance.ledger_entry_id
+        ),
+    )
+    .await;
+    let funnel_actor = format!("portal:{}", request.signer.email);
+    record_funnel_event(
+        &state.db_pool,
+        quotey_core::audit::funnel::QUOTE_ACCEPTED,
+        Some(&quote_id),
+        &funnel_actor,
+        "success",
+        &[("signature_kind", certificate.signature.kind())],
+    )
+    .await;
+    info!(
+        event_name = "portal.quote.accepted",
+        quote_id = %quote_id,
+        signature_request_id = %request.id,
+        ledger_entry_id = %acceptance.ledger_entry_id,
+        signed_pdf_sha256 = %acceptance.signed_pdf_sha256,
+        "quote accepted via e-signature"
+    );
+
+    webhooks::emit(
+        &state.db_pool,
+        WebhookEventType::QuoteAccepted,
+        &quote_id,
+        serde_json::json!({
+            "signature_request_id": request.id,
+            "quote_version": request.quote_version,
+            "signer": request.signer,
+            "signed_pdf_sha256": acceptance.signed_pdf_sha256,
+            "ledger_entry_id": acceptance.ledger_entry_id,
+            "signed_at": acceptance.signed_at,
+        }),
+    )
+    .await;
+
+    Ok(Json(SignCompleteResponse {
+        success: true,
+        message: format!("Quote {quote_id} accepted. A signed copy is ready to download."),
+        signed_pdf_url: format!("/quote/{token}/signed.pdf"),
+        quote_id,
+        signed_pdf_sha256: acceptance.signed_pdf_sha256,
+        ledger_entry_id: acceptance.ledger_entry_id,
+        signed_at: acceptance.signed_at.to_rfc3339(),
+    }))
+}
+
+async fn download_signed_quote_pdf(
+    Path(token): Path<String>,
+    State(state): State<PortalState>,
+) -> Result<Response, (StatusCode, Json<PortalError>)> {
+    let quote_id = resolve_quote_by_token(&state.db_pool, &token).await?;
+    let (_, pdf) = QuoteSignatureService::new(state.db_pool.clone())
+        .signed_pdf(&quote_id)
+        .await
+        .map_err(signature_service_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(PortalError::not_found("signed quote"))))?;
+    Ok(RenderedPdf(pdf).into_response(&format!("Quote_{quote_id}_signed.pdf")))
+}
+
+fn signing_key(state: &PortalState) -> Result<&str, (StatusCode, Json<PortalError>)> {
+    state.ledger_signing_key.as_deref().ok_or_else(|| {
+        warn!("QUOTEY_LEDGER_SIGNING_KEY is not set; customer signing is disabled");
+        (StatusCode::SERVICE_UNAVAILABLE, Json(PortalError::service_unavailable("E-signature")))
+    })
+}
+
+fn pdf_generator_unavailable() -> (StatusCode, Json<PortalError>) {
+    error!("PDF generator not initialized");
+    (StatusCode::SERVICE_UNAVAILABLE, Json(PortalError::service_unavailable("PDF generator")))
+}
+
+/// The quote data and unsigned PDF exactly as the signer sees them.
+async fn render_signing_document(
+    state: &PortalState,
+    quote_id: &str,
+) -> Result<(serde_json::Value, Vec<u8>), (StatusCode, Json<PortalError>)> {
+    let pdf_generator = state.pdf_generator.as_ref().ok_or_else(pdf_generator_unavailable)?;
+    let quote_data =
+        fetch_quote_for_pdf(&state.db_pool, quote_id, &state.branding.company_name).await?;
+    let pdf = pdf_generator.generate_quote_pdf(&quote_data, SIGNING_TEMPLATE).map_err(|e| {
+        error!(error = %e, quote_id = %quote_id, "PDF generation failed");
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(PortalError::service_unavailable("PDF generator")))
+    })?;
+    Ok((quote_data, pdf.0))
+}
+
+/// Feeds the accepted quote to deal DNA as closed-won; failures only log.
+async fn close_won_deal(pool: &DbPool, quote_id: &str, close_date: &str) {
+    let quote =
+        match SqlQuoteRepository::new(pool.clone()).find_by_id(&QuoteId(quote_id.into())).await {
+            Ok(Some(quote)) => quote,
+            Ok(None) => return,
+            Err(error) => {
+                warn!(%error, quote_id = %quote_id, "accepted quote not loaded for deal DNA");
+                return;
+            }
+        };
+    let service = DealSimilarityService::new(pool.clone());
+    if let Err(error) = service.on_quote_closed(&quote, Some(close_date.to_string())).await {
+        warn!(%error, quote_id = %quote_id, "similar-deal index not updated");
+    }
+    if let Err(error) =
+        service.record_outcome(&quote, ClosedDealOutcome::Won, close_date.to_string()).await
+    {
+        warn!(%error, quote_id = %quote_id, "closed-won outcome not recorded");
+    }
+}
+
+fn signature_validation_error(error: SignatureError) -> (StatusCode, Json<PortalError>) {
+    let (field, reason) = match &error {
+        SignatureError::InvalidSigner { field, reason } => (*field, reason.clone()),
+        SignatureError::InvalidSignature(reason) => ("signature", reason.clone()),
+    };
+    (StatusCode::BAD_REQUEST, Json(PortalError::validation(field, &reason)))
+}
+
+fn negotiation_error(error: NegotiationError) -> (StatusCode, Json<PortalError>) {
+    let closed = |error: &str| PortalError {
+        error: error.to_string(),
+        category: Some(PortalErrorCategory::PermissionDenied),
+        recovery_hint: Some("Contact your sales rep to continue the negotiation.".to_string()),
+        retry_after_seconds: None,
+    };
+    match error {
+        NegotiationError::QuoteNotFound(_) | NegotiationError::SessionNotFound(_) => {
+            (StatusCode::NOT_FOUND, Json(PortalError::not_found("quote")))
+        }
+        NegotiationError::Invalid(reason) => {
+            (StatusCode::BAD_REQUEST, Json(PortalError::validation("counter", &reason)))
+        }
+        NegotiationError::QuoteClosed(_) => (
+            StatusCode::CONFLICT,
+            Json(closed("This quote is closed and can no longer be countered")),
+        ),
+        NegotiationError::InvalidState(_) | NegotiationError::StaleCounter { .. } => {
+            (StatusCode::CONFLICT, Json(closed("This quote cannot take a counteroffer right now")))
+        }
+        NegotiationError::MaxTurnsReached(_) | NegotiationError::SessionExpired(_) => {
+            (StatusCode::CONFLICT, Json(closed("This negotiation has ended")))
+        }
+        NegotiationError::Repository(error) => {
+            error!(error = %error, "portal negotiation failed");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(PortalError::service_unavailable("database")))
+        }
+    }
+}
+
+fn signature_service_error(error: SignatureServiceError) -> (StatusCode, Json<PortalError>) {
+    let closed = |error: &str, hint: &str| PortalError {
+        error: error.to_string(),
+        category: Some(PortalErrorCategory::PermissionDenied),
+        recovery_hint: Some(hint.to_string()),
+        retry_after_seconds: None,
+    };
+    match error {
+        SignatureServiceError::QuoteNotFound(_) => {
+            (StatusCode::NOT_FOUND, Json(PortalError::not_found("quote")))
+        }
+        SignatureServiceError::RequestNotFound(_) => {
+            (StatusCode::NOT_FOUND, Json(PortalError::not_found("signature request")))
+        }
+        SignatureServiceError::NotSignable { status, .. } => (
+            StatusCode::CONFLICT,
+            Json(closed(
+                &format!("This quote is {status} and can no longer be signed"),
+                "Contact your sales rep for an updated quote.",
+            )),
+        ),
+        SignatureServiceError::RequestClosed { .. } => (
+            StatusCode::CONFLICT,
+            Json(closed(
+                "This signing session is no longer active",
+                "Start signing again to receive a new code.",
+            )),
+        ),
+        SignatureServiceError::CodeExpired(_) => (
+            StatusCode::GONE,
+            Json(closed("Your signing code has expired", "Request a new code and try again.")),
+        ),
+        SignatureServiceError::Locked(_) => (
+            StatusCode::FORBIDDEN,
+            Json(closed(
+                "Too many incorrect codes were entered",
+                "Start signing again to receive a new code.",
+            )),
+        ),
+        SignatureServiceError::TooManyStarts { retry_after_secs } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(PortalError::rate_limited(u32::try_from(retry_after_secs).unwrap_or(u32::MAX))),
+        ),
+        SignatureServiceError::InvalidCode { remaining } => (
+            StatusCode::BAD_REQUEST,
+            Json(PortalError::validation(
+                "code",
+                &format!("the code is incorrect ({remaining} attempts left)"),
+            )),
+        ),
+        SignatureServiceError::Signature(error) => signature_validation_error(error),
+        error => {
+            error!(error = %error, "portal signature failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(PortalError::service_unavailable("E-signature")),
+            )
+        }
+    }
+}
+
 async fn add_comment(
     Path(token): Path<String>,
     State(state): State<PortalState>,
@@ -3133,6 +3872,20 @@ async fn add_comment(
         "customer comment added via web portal"
     );
 
+    webhooks::emit(
+        &state.db_pool,
+        WebhookEventType::PortalCommentAdded,
+        &quote_id,
+        serde_json::json!({
+            "comment_id": id,
+            "line_id": null,
+            "parent_id": body.parent_id,
+            "author_name": author_name,
+            "author_email": author_email,
+            "body": text,
+        }),
+    )
+    .await;
     notify_rep_about_comment(&state, &quote_id, "overall", None, &author_name, &author_email, text)
         .await;
 
@@ -3197,6 +3950,140 @@ async fn list_comments(
     })))
 }
 
+/// Query parameters for the explanation drill-down.
+#[derive(Debug, Deserialize, Default)]
+pub struct ExplainDrilldownQuery {
+    /// `total` (default) or `line`.
+    pub target: Option<String>,
+    /// Portal line id (`quote_line.id`) or positional `<quote>-ql-<n>` id.
+    pub line_id: Option<String>,
+}
+
+/// Customer-facing explanation: the arithmetic behind an amount and nothing else. Policy
+/// evidence, approval thresholds, margin and source references stay with the sales team.
+#[derive(Debug, Serialize)]
+pub struct PortalExplanation {
+    pub quote_id: String,
+    pub quote_version: i32,
+    pub target: &'static str,
+    pub line_id: Option<String>,
+    pub amount: Decimal,
+    pub amount_description: String,
+    pub steps: Vec<PortalExplanationStep>,
+}
+
+/// One arithmetic step of a [`PortalExplanation`].
+#[derive(Debug, Serialize)]
+pub struct PortalExplanationStep {
+    pub step_order: i32,
+    pub description: String,
+    pub result: Decimal,
+}
+
+impl From<Explanation> for PortalExplanation {
+    fn from(explanation: Explanation) -> Self {
+        Self {
+            quote_id: explanation.quote_id,
+            quote_version: explanation.quote_version,
+            target: explanation.target,
+            line_id: explanation.line_id,
+            amount: explanation.response.amount,
+            amount_description: explanation.response.amount_description,
+            steps: explanation
+                .response
+                .arithmetic_chain
+                .into_iter()
+                .map(|step| PortalExplanationStep {
+                    step_order: step.step_order,
+                    description: step.description,
+                    result: step.result,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Explains the quote total or a line from the recorded pricing snapshot.
+async fn explain_quote_number(
+    Path(token): Path<String>,
+    Query(params): Query<ExplainDrilldownQuery>,
+    State(state): State<PortalState>,
+) -> Result<Json<PortalExplanation>, (StatusCode, Json<PortalError>)> {
+    let quote_id = resolve_quote_by_token(&state.db_pool, &token).await?;
+
+    let line_id = match params.line_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(line_id) => Some(snapshot_line_id(&state.db_pool, &quote_id, line_id).await?),
+        None => None,
+    };
+    let target = ExplainTarget::parse(params.target.as_deref(), line_id.as_deref())
+        .filter(|target| !matches!(target, ExplainTarget::Policy))
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(PortalError::validation("target", "must be total or line (with line_id)")),
+            )
+        })?;
+
+    let query = ExplainQuery {
+        quote_id: quotey_core::domain::quote::QuoteId(quote_id.clone()),
+        target,
+        version: None,
+        actor_type: "user".to_string(),
+        actor_id: "portal:customer".to_string(),
+        thread_id: format!("portal:{token}"),
+        correlation_id: format!("portal-explain-{}", &uuid_v4()[..12]),
+    };
+    match ExplainService::new(state.db_pool.clone()).explain(query).await {
+        Ok(explanation) => Ok(Json(explanation.into())),
+        Err(ExplainError::QuoteNotFound(_)) => {
+            Err((StatusCode::NOT_FOUND, Json(PortalError::not_found("quote"))))
+        }
+        Err(ExplainError::LineNotFound { .. }) => {
+            Err((StatusCode::NOT_FOUND, Json(PortalError::not_found("quote line"))))
+        }
+        Err(ExplainError::MissingEvidence(_)) => Err((
+            StatusCode::CONFLICT,
+            Json(PortalError {
+                error: "This quote has not been priced yet".to_string(),
+                category: Some(PortalErrorCategory::NotFound),
+                recovery_hint: Some(
+                    "Ask your sales rep to re-price the quote, then try again.".to_string(),
+                ),
+                retry_after_seconds: None,
+            }),
+        )),
+        Err(error) => {
+            error!(error = %error, quote_id = %quote_id, "portal explanation failed");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(PortalError::service_unavailable("explanation")),
+            ))
+        }
+    }
+}
+
+/// Maps a portal `quote_line.id` onto the positional line id used in pricing snapshots.
+async fn snapshot_line_id(
+    pool: &DbPool,
+    quote_id: &str,
+    line_id: &str,
+) -> Result<String, (StatusCode, Json<PortalError>)> {
+    if line_id.starts_with(&format!("{quote_id}-ql-")) {
+        return Ok(line_id.to_string());
+    }
+    let ids: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM quote_line WHERE quote_id = ? ORDER BY created_at ASC, id ASC",
+    )
+    .bind(quote_id)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+    ids.iter()
+        .position(|id| id == line_id)
+        .map(|index| format!("{quote_id}-ql-{}", index + 1))
+        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(PortalError::not_found("quote line"))))
+}
+
 async fn list_live_approvals(
     State(state): State<PortalState>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<PortalError>)> {
@@ -3392,6 +4279,21 @@ async fn add_line_comment(
     )
     .await;
 
+    webhooks::emit(
+        &state.db_pool,
+        WebhookEventType::PortalCommentAdded,
+        &quote_id,
+        serde_json::json!({
+            "comment_id": id,
+            "line_id": line_id,
+            "parent_id": body.parent_id,
+            "author_name": author_name,
+            "author_email": author_email,
+            "body": text,
+        }),
+    )
+    .await;
+
     notify_rep_about_comment(
         &state,
         &quote_id,
@@ -3491,80 +4393,113 @@ async fn update_assumptions(
     .await
     .map_err(db_error)?;
 
-    // Fetch quote lines to recalculate totals
+    // Fetch quote lines (in pricing order) to recalculate totals
     let lines = sqlx::query(
-        "SELECT quantity, unit_price, subtotal, discount_pct
-         FROM quote_line WHERE quote_id = ?",
-    )
-    .bind(&quote_id)
-    .fetch_all(&state.db_pool)
-    .await
-    .map_err(db_error)?;
-
-    // Recalculate totals
-    let mut subtotal = 0.0_f64;
-    let mut discount_total = 0.0_f64;
-    for row in &lines {
-        let qty: i64 = row.try_get("quantity").unwrap_or(0);
-        let unit_price: f64 = row.try_get("unit_price").unwrap_or(0.0);
-        let line_subtotal = match row.try_get::<Option<f64>, _>("subtotal") {
-            Ok(Some(value)) => value,
-            _ => unit_price * qty as f64,
-        };
-        let discount_pct: f64 =
-            row.try_get::<f64, _>("discount_pct").unwrap_or(0.0).clamp(0.0, 100.0);
-        subtotal += line_subtotal;
-        discount_total += line_subtotal * discount_pct / 100.0;
-    }
-
-    let discounted_subtotal = subtotal - discount_total;
-    let tax_amount = discounted_subtotal * new_tax_rate;
-    let total = discounted_subtotal + tax_amount;
-
-    // Update or create pricing snapshot with new totals
-    let snapshot_id = format!("PSNAP-{}", &uuid_v4()[..12]);
-    let version: i64 = current.try_get("version").unwrap_or(1);
-
-    // Delete old snapshot for this version (assumption updates create new pricing state)
-    sqlx::query("DELETE FROM quote_pricing_snapshot WHERE quote_id = ? AND version = ?")
-        .bind(&quote_id)
-        .bind(version)
-        .execute(&state.db_pool)
-        .await
-        .map_err(db_error)?;
-
-    // Create new snapshot
-    let pricing_trace = serde_json::json!({
-        "quote_id": &quote_id,
-        "version": version,
-        "priced_at": now.to_rfc3339(),
-        "priced_by": "portal_assumption_update",
-        "reason": "User updated assumptions via portal",
-        "lines": lines.len(),
-        "tax_rate": new_tax_rate,
-        "payment_terms": &new_payment_terms,
-    });
-
-    sqlx::query(
-        "INSERT INTO quote_pricing_snapshot
-            (id, quote_id, version, subtotal, discount_total, tax_total, total, currency, pricing_trace_json, priced_at, priced_by)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        "SELECT product_id, quantity, unit_price, subtotal, discount_pct
+         FROM quote_line WHERE quote_id = ?
+         ORDER BY created_at ASC, id ASC",
     )
-    .bind(&snapshot_id)
     .bind(&quote_id)
-    .bind(version)
-    .bind(subtotal)
-    .bind(discount_total)
-    .bind(tax_amount)
-    .bind(total)
-    .bind(&new_currency)
-    .bind(pricing_trace.to_string())
-    .bind(now.to_rfc3339())
-    .bind("portal")
-    .execute(&state.db_pool)
+    .fetch_all(&state.db_pool)
     .await
     .map_err(db_error)?;
 
+    // Recalculate totals
+    let mut line_items = Vec::with_capacity(lines.len());
+    for (index, row) in lines.iter().enumerate() {
+        let qty: i64 = row.try_get("quantity").unwrap_or(0);
+        let unit_price: f64 = row.try_get("unit_price").unwrap_or(0.0);
+        let line_subtotal = match row.try_get::<Option<f64>, _>("subtotal") {
+            Ok(Some(value)) => value,
+            _ => unit_price * qty as f64,
+        };
+        let discount_pct: f64 =
+            row.try_get::<f64, _>("discount_pct").unwrap_or(0.0).clamp(0.0, 100.0);
+        let gross = money_decimal(line_subtotal);
+        let discount_amount = money_decimal(line_subtotal * discount_pct / 100.0);
+        let product_id: String = row.try_get("product_id").unwrap_or_default();
+        line_items.push(PricingLineSnapshot {
+            line_id: format!("{quote_id}-ql-{}", index + 1),
+            product_name: product_id.clone(),
+            product_id,
+            quantity: i32::try_from(qty).unwrap_or(i32::MAX),
+            unit_price: money_decimal(unit_price),
+            discount_percent: Decimal::from_f64(discount_pct).unwrap_or(Decimal::ZERO),
+            discount_amount,
+            line_subtotal: gross - discount_amount,
+        });
+    }
+
+    let discounted_subtotal: Decimal = line_items.iter().map(|line| line.line_subtotal).sum();
+    let tax_amount = (discounted_subtotal
+        * Decimal::from_f64(new_tax_rate).unwrap_or(Decimal::ZERO))
+    .round_dp(2);
+    let version: i64 = current.try_get("version").unwrap_or(1);
+    let version = i32::try_from(version).unwrap_or(1);
+    let core_quote_id = quotey_core::domain::quote::QuoteId(quote_id.clone());
+
+    // Assumption updates re-price the current version in place, so the policy verdict is
+    // re-evaluated alongside the snapshot to keep explanations answerable.
+    let snapshot = pricing_snapshot_from_lines(
+        &core_quote_id,
+        version,
+        &new_currency,
+        line_items,
+        tax_amount,
+        now.to_rfc3339(),
+    );
+    let effective_discount_pct = if snapshot.subtotal > Decimal::ZERO {
+        snapshot.discount_total * Decimal::from(100) / snapshot.subtotal
+    } else {
+        Decimal::ZERO
+    };
+    let quote_policy = load_quote_policy(&state.db_pool, &quote_id).await;
+    // Margin only drives the policy verdict here; the customer-facing response never carries it.
+    let margin_lines: Vec<MarginLine> = snapshot
+        .line_items
+        .iter()
+        .map(|line| MarginLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            quantity: u32::try_from(line.quantity).unwrap_or(0),
+            net_amount: line.line_subtotal,
+        })
+        .collect();
+    let margin = quote_policy.margin(&margin_lines);
+    let policy_input = PolicyInput {
+        requested_discount_pct: effective_discount_pct.round_dp(4),
+        deal_value: snapshot.subtotal,
+        minimum_margin_pct: margin.margin_pct,
+    };
+    let rule_lines: Vec<PolicyRuleLine> = snapshot
+        .line_items
+        .iter()
+        .map(|line| PolicyRuleLine {
+            line_id: line.line_id.clone(),
+            product_id: line.product_id.clone(),
+            discount_pct: line.discount_percent,
+        })
+        .collect();
+    let decision = quote_policy.evaluate(&policy_input, &rule_lines, &margin);
+    let policy = policy_evaluation_from_decision(
+        &core_quote_id,
+        version,
+        &policy_input,
+        &quote_policy.thresholds,
+        &decision,
+        now.to_rfc3339(),
+    );
+    let snapshots = SqlPricingSnapshotRepository::with_priced_by(state.db_pool.clone(), "portal")
+        .with_policy_version(quote_policy.version);
+    snapshots.record_snapshot(&snapshot, Some(&policy)).await.map_err(|error| {
+        error!(error = %error, quote_id = %quote_id, "portal pricing snapshot write failed");
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(PortalError::service_unavailable("database")))
+    })?;
+    let subtotal = snapshot.subtotal.to_f64().unwrap_or(0.0);
+    let discount_total = snapshot.discount_total.to_f64().unwrap_or(0.0);
+    let tax_amount = snapshot.tax_total.to_f64().unwrap_or(0.0);
+    let total = snapshot.total.to_f64().unwrap_or(0.0);
+
     // Record audit event
     let changes = serde_json::json!({
         "tax_rate": new_tax_rate,
@@ -4862,6 +5797,10 @@ fn redact_token(token: &str) -> String {
     format!("{}***", &token[..keep])
 }
 
+fn money_decimal(amount: f64) -> Decimal {
+    Decimal::from_f64(amount).unwrap_or(Decimal::ZERO).round_dp(2)
+}
+
 fn db_error(error: sqlx::Error) -> (StatusCode, Json<PortalError>) {
     error!(error = %error, "portal database error");
     (StatusCode::INTERNAL_SERVER_ERROR, Json(PortalError::service_unavailable("database")))
@@ -4936,6 +5875,15 @@ async fn ensure_digest_delivery_table(pool: &DbPool) -> Result<(), sqlx::Error>
     Ok(())
 }
 
+fn extract_user_agent(headers: &HeaderMap) -> String {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().chars().take(512).collect())
+        .filter(|value: &String| !value.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
 fn extract_requester_ip(headers: &HeaderMap) -> String {
     let from_forwarded = headers
         .get("x-forwarded-for")
@@ -5056,6 +6004,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         })
     }
 
@@ -5066,6 +6016,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         })
     }
 
@@ -5181,6 +6133,202 @@ mod tests {
         assert_portal_security_headers(shell_css_response.headers());
     }
 
+    fn signing_state(pool: sqlx::SqlitePool) -> PortalState {
+        let State(mut state) = state(pool);
+        state.pdf_generator = Some(Arc::new(PdfGenerator::with_embedded_templates()));
+        state.ledger_signing_key = Some(Arc::from("test-ledger-key"));
+        state
+    }
+
+    fn signing_headers(ip: &str) -> HeaderMap {
+        let mut headers = forwarded_headers(ip);
+        headers.insert(header::USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Test)"));
+        headers
+    }
+
+    async fn start_signing(state: &PortalState, token: &str) -> (String, String) {
+        let started = start_quote_signature(
+            axum::extract::Path(token.to_string()),
+            State(state.clone()),
+            signing_headers("198.51.100.4"),
+            Json(SignStartRequest {
+                signer_name: "Dana Reyes".to_string(),
+                signer_title: "VP Procurement".to_string(),
+                signer_email: "Dana@Northwind.example".to_string(),
+            }),
+        )
+        .await
+        .expect("start signing")
+        .0;
+        assert_eq!(started.code_sent_to, "d***@northwind.example");
+
+        // The email worker mints the code at delivery; do the same here without sending.
+        let code = QuoteSignatureService::new(state.db_pool.clone())
+            .issue_code(&started.signature_request_id)
+            .await
+            .expect("signing code")
+            .code;
+        (started.signature_request_id, code)
+    }
+
+    #[tokio::test]
+    async fn customer_signature_accepts_quote_and_records_signed_pdf_in_ledger() {
+        let (pool, quote_id, token) = setup().await;
+        let state = signing_state(pool.clone());
+        let (request_id, code) = start_signing(&state, &token).await;
+
+        let complete = |code: String, signature: SignatureCapture| {
+            complete_quote_signature(
+                axum::extract::Path(token.clone()),
+                State(state.clone()),
+                signing_headers("198.51.100.9"),
+                Json(SignCompleteRequest {
+                    signature_request_id: request_id.clone(),
+                    code,
+                    signature,
+                    consent: true,
+                }),
+            )
+        };
+        let drawn = SignatureCapture::Drawn {
+            strokes: vec![vec![[0.1, 0.7], [0.3, 0.2], [0.6, 0.8], [0.9, 0.3]]],
+        };
+        let wrong_code = if code == "000000" { "111111" } else { "000000" };
+        let (status, Json(error)) =
+            complete(wrong_code.to_string(), drawn.clone()).await.expect_err("wrong code");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error.error.contains("4 attempts left"), "{}", error.error);
+
+        let accepted = complete(code, drawn).await.expect("complete signing").0;
+        assert!(accepted.success);
+        assert_eq!(accepted.signed_pdf_url, format!("/quote/{token}/signed.pdf"));
+
+        let status: String = sqlx::query_scalar("SELECT status FROM quote WHERE id = ?")
+            .bind(&quote_id)
+            .fetch_one(&pool)
+            .await
+            .expect("quote status");
+        assert_eq!(status, "accepted");
+        let (content_hash, action): (String, String) =
+            sqlx::query_as("SELECT content_hash, action_type FROM quote_ledger WHERE entry_id = ?")
+                .bind(&accepted.ledger_entry_id)
+                .fetch_one(&pool)
+                .await
+                .expect("ledger entry");
+        assert_eq!(
+            (content_hash.as_str(), action.as_str()),
+            (accepted.signed_pdf_sha256.as_str(), "accept")
+        );
+        let evidence: String =
+            sqlx::query_scalar("SELECT evidence_json FROM quote_signature_request WHERE id = ?")
+                .bind(&request_id)
+                .fetch_one(&pool)
+                .await
+                .expect("evidence");
+        assert!(evidence.contains("198.51.100.4") && evidence.contains("198.51.100.9"));
+        assert!(evidence.contains("Mozilla/5.0 (Test)"));
+        let completed: String = sqlx::query_scalar(
+            "SELECT payload_json FROM audit_event
+             WHERE quote_id = ? AND event_type = 'portal.signature.completed'",
+        )
+        .bind(&quote_id)
+        .fetch_one(&pool)
+        .await
+        .expect("completed audit event");
+        assert!(completed.contains("d***@northwind.example"), "{completed}");
+        assert!(!completed.to_lowercase().contains("dana@northwind.example"), "{completed}");
+
+        let funnel_events: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM audit_event WHERE quote_id = ? AND event_type = ?",
+        )
+        .bind(&quote_id)
+        .bind(quotey_core::audit::funnel::QUOTE_ACCEPTED)
+        .fetch_one(&pool)
+        .await
+        .expect("funnel events");
+        assert_eq!(funnel_events, 1);
+
+        let response = download_signed_quote_pdf(axum::extract::Path(token.clone()), State(state))
+            .await
+            .expect("signed pdf");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.expect("body");
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert_eq!(document_sha256(&bytes), accepted.signed_pdf_sha256);
+    }
+
+    #[tokio::test]
+    async fn customer_signature_rejects_quotes_changed_mid_signing() {
+        let (pool, quote_id, token) = setup().await;
+        let state = signing_state(pool.clone());
+        let (request_id, code) = start_signing(&state, &token).await;
+        sqlx::query("UPDATE quote SET currency = 'EUR' WHERE id = ?")
+            .bind(&quote_id)
+            .execute(&pool)
+            .await
+            .expect("edit quote");
+
+        let (status, _) = complete_quote_signature(
+            axum::extract::Path(token.clone()),
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(SignCompleteRequest {
+                signature_request_id: request_id,
+                code,
+                signature: SignatureCapture::Typed { text: "Dana Reyes".to_string() },
+                consent: true,
+            }),
+        )
+        .await
+        .expect_err("document changed");
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let State(unconfigured) = self::state(pool);
+        let (status, _) = start_quote_signature(
+            axum::extract::Path(token),
+            State(unconfigured),
+            HeaderMap::new(),
+            Json(SignStartRequest {
+                signer_name: "Dana Reyes".to_string(),
+                signer_title: "VP Procurement".to_string(),
+                signer_email: "dana@northwind.example".to_string(),
+            }),
+        )
+        .await
+        .expect_err("no ledger key");
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn signature_starts_are_throttled_before_sending_more_codes() {
+        let (pool, _, token) = setup().await;
+        let state = signing_state(pool.clone());
+        for _ in 0..quotey_core::esign::MAX_STARTS_PER_WINDOW {
+            start_signing(&state, &token).await;
+        }
+        let (status, Json(error)) = start_quote_signature(
+            axum::extract::Path(token),
+            State(state),
+            signing_headers("198.51.100.4"),
+            Json(SignStartRequest {
+                signer_name: "Dana Reyes".to_string(),
+                signer_title: "VP Procurement".to_string(),
+                signer_email: "dana@northwind.example".to_string(),
+            }),
+        )
+        .await
+        .expect_err("throttled");
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(error.retry_after_seconds.is_some_and(|secs| secs > 0));
+        let queued: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM execution_queue_task WHERE operation_kind = 'email.signature_code'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("count codes");
+        assert_eq!(queued, quotey_core::esign::MAX_STARTS_PER_WINDOW);
+    }
+
     #[tokio::test]
     async fn approve_quote_records_approval_and_updates_status() {
         let (pool, quote_id, token) = setup().await;
@@ -5526,6 +6674,65 @@ mod tests {
         assert_eq!(status, StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn propose_counter_records_customer_turn_without_margin_details() {
+        let (pool, quote_id, token) = setup().await;
+
+        let Json(response) = propose_counter(
+            axum::extract::Path(token.clone()),
+            state(pool.clone()),
+            Json(CounterRequest {
+                discount_pct: 10.0,
+                term_months: Some(24),
+                note: Some("Budget is fixed this year".to_string()),
+                idempotency_key: Some("portal-counter-1".to_string()),
+            }),
+        )
+        .await
+        .expect("counter recorded");
+
+        assert!(response.success);
+        assert_eq!(response.status, "submitted");
+        let body = serde_json::to_value(&response).expect("serialize");
+        assert!(body.get("check").is_none());
+        assert!(!body.to_string().contains("margin"));
+
+        let (party, state_label): (String, String) = sqlx::query_as(
+            "SELECT json_extract(t.request_payload, '$.party'), s.state
+             FROM negotiation_turn t JOIN negotiation_session s ON s.id = t.session_id
+             WHERE s.quote_id = ?",
+        )
+        .bind(&quote_id)
+        .fetch_one(&pool)
+        .await
+        .expect("turn row");
+        assert_eq!(party, "Customer");
+        assert_eq!(state_label, "counter_pending");
+
+        let audits: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM audit_event
+             WHERE quote_id = ? AND event_type = 'portal.negotiation.counter'",
+        )
+        .bind(&quote_id)
+        .fetch_one(&pool)
+        .await
+        .expect("audit count");
+        assert_eq!(audits, 1);
+
+        let rejected = propose_counter(
+            axum::extract::Path(token),
+            state(pool),
+            Json(CounterRequest {
+                discount_pct: 150.0,
+                term_months: None,
+                note: None,
+                idempotency_key: None,
+            }),
+        )
+        .await;
+        assert_eq!(rejected.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn reject_quote_allows_legacy_payload_without_auth_method() {
         let (pool, quote_id, token) = setup().await;
@@ -6026,6 +7233,54 @@ mod tests {
         assert!(html.contains("Notification Settings"));
     }
 
+    #[tokio::test]
+    async fn analytics_dashboard_feed_reports_seeded_quotes_and_filters() {
+        let (pool, _quote_id, _token) = setup().await;
+
+        let Json(data) = analytics_dashboard_data(
+            Query(DashboardQuery { days: Some(30), ..DashboardQuery::default() }),
+            state(pool.clone()),
+        )
+        .await
+        .expect("dashboard data");
+        assert_eq!(data["metrics"]["total_quotes"], 1.0);
+        assert_eq!(data["metrics"]["win_rate"], 100.0);
+        assert_eq!(data["win_rate_by_segment"]["win_rates"], serde_json::json!([100.0]));
+        assert_eq!(data["truncated"], false);
+
+        let Json(filtered) = analytics_dashboard_data(
+            Query(DashboardQuery {
+                rep: Some("someone-else".to_string()),
+                ..DashboardQuery::default()
+            }),
+            state(pool.clone()),
+        )
+        .await
+        .expect("filtered dashboard data");
+        assert_eq!(filtered["metrics"]["total_quotes"], 0.0);
+
+        let (status, Json(error)) = analytics_dashboard_data(
+            Query(DashboardQuery {
+                start: Some("2026-01-01".to_string()),
+                ..DashboardQuery::default()
+            }),
+            state(pool.clone()),
+        )
+        .await
+        .expect_err("half a custom range is rejected");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error.error.contains("start and end"));
+
+        let html = analytics_dashboard_page(
+            Query(DashboardQuery::default()),
+            state_with_real_templates(pool),
+        )
+        .await
+        .expect("render analytics dashboard")
+        .0;
+        assert!(html.contains("id=\"totalQuotes\">1<"));
+    }
+
     // -----------------------------------------------------------------------
     // Link management tests
     // -----------------------------------------------------------------------
@@ -7742,6 +8997,8 @@ mod tests {
                 pdf_generator: None,
                 branding: BrandingConfig::default(),
                 rep_notifications: PortalRepNotificationConfig::default(),
+                ledger_signing_key: None,
+                analytics_cache: AnalyticsCache::default(),
             }),
         )
         .await
@@ -7792,6 +9049,8 @@ mod tests {
                 pdf_generator: None,
                 branding: BrandingConfig::default(),
                 rep_notifications: PortalRepNotificationConfig::default(),
+                ledger_signing_key: None,
+                analytics_cache: AnalyticsCache::default(),
             }),
         )
         .await
@@ -7825,6 +9084,8 @@ mod tests {
                 pdf_generator: None,
                 branding: BrandingConfig::default(),
                 rep_notifications: PortalRepNotificationConfig::default(),
+                ledger_signing_key: None,
+                analytics_cache: AnalyticsCache::default(),
             }),
         )
         .await;
@@ -7844,6 +9105,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         };
 
         let initial = get_digest_schedule(State(state.clone())).await.expect("get digest").0;
@@ -7884,6 +9147,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         };
 
         let invalid_time = upsert_digest_schedule(
@@ -7926,6 +9191,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         };
 
         let today = weekday_name(Utc::now().weekday()).to_string();
@@ -7966,6 +9233,8 @@ mod tests {
             pdf_generator: None,
             branding: BrandingConfig::default(),
             rep_notifications: PortalRepNotificationConfig::default(),
+            ledger_signing_key: None,
+            analytics_cache: AnalyticsCache::default(),
         };
 
         let today = weekday_name(Utc::now().weekday()).to_string();
@@ -8005,4 +9274,176 @@ mod tests {
                 .expect("count digest deliveries");
         assert_eq!(delivery_count, 1);
     }
+
+    #[tokio::test]
+    async fn explain_responses_never_carry_margin_or_cost() {
+        let (pool, quote_id, token) = setup().await;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO product (id, sku, name, base_price, created_at, updated_at)
+             VALUES ('PROD-GADGET', 'SKU-GADGET', 'Gadget', '100.00', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("seed product");
+        sqlx::query(
+            "INSERT INTO product_cost (id, product_id, unit_cost, currency, effective_from, created_by, created_at)
+             VALUES ('PC-GADGET', 'PROD-GADGET', '80.00', 'USD', '2000-01-01', 'test', ?)",
+        )
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("seed cost");
+        sqlx::query(
+            "INSERT INTO quote_line (id, quote_id, product_id, quantity, unit_price, subtotal, discount_pct, created_at, updated_at)
+             VALUES ('QL-GADGET-1', ?, 'PROD-GADGET', 3, 100.0, 300.0, 10.0, ?, ?)",
+        )
+        .bind(&quote_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("seed quote line");
+        let _ = update_assumptions(
+            axum::extract::Path(token.clone()),
+            state(pool.clone()),
+            Json(UpdateAssumptionsRequest {
+                tax_rate: Some(0.0),
+                payment_terms: None,
+                billing_country: None,
+                currency: None,
+            }),
+        )
+        .await
+        .expect("update assumptions");
+
+        // Internally the explanation does carry the margin evaluation (270 net over 240 cost).
+        let internal = ExplainService::new(pool.clone())
+            .explain(ExplainQuery {
+                quote_id: QuoteId(quote_id.clone()),
+                target: ExplainTarget::Total,
+                version: None,
+                actor_type: "user".to_string(),
+                actor_id: "test".to_string(),
+                thread_id: "test".to_string(),
+                correlation_id: "test".to_string(),
+            })
+            .await
+            .expect("internal explanation");
+        let internal = serde_json::to_string(&internal).expect("serialize").to_lowercase();
+        assert!(internal.contains("margin-floor"), "{internal}");
+
+        for target in [None, Some("line")] {
+            let explanation = explain_quote_number(
+                axum::extract::Path(token.clone()),
+                Query(ExplainDrilldownQuery {
+                    target: target.map(str::to_string),
+                    line_id: target.map(|_| "QL-GADGET-1".to_string()),
+                }),
+                state(pool.clone()),
+            )
+            .await
+            .expect("explain")
+            .0;
+            let body = serde_json::to_string(&explanation).expect("serialize").to_lowercase();
+            for internal in ["margin", "cost", "240", "11.1", "threshold", "actual_value"] {
+                assert!(!body.contains(internal), "`{internal}` leaked: {body}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn explain_drilldown_uses_snapshot_recorded_by_assumption_update() {
+        let (pool, quote_id, token) = setup().await;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote_line (id, quote_id, product_id, quantity, unit_price, subtotal, discount_pct, created_at, updated_at)
+             VALUES ('QL-EXPLAIN-1', ?, 'PROD-EXPLAIN', 3, 100.0, 300.0, 10.0, ?, ?)",
+        )
+        .bind(&quote_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("seed quote line");
+
+        let unpriced = explain_quote_number(
+            axum::extract::Path(token.clone()),
+            Query(ExplainDrilldownQuery::default()),
+            state(pool.clone()),
+        )
+        .await
+        .expect_err("no snapshot yet");
+        assert_eq!(unpriced.0, StatusCode::CONFLICT);
+
+        let updated = update_assumptions(
+            axum::extract::Path(token.clone()),
+            state(pool.clone()),
+            Json(UpdateAssumptionsRequest {
+                tax_rate: Some(0.1),
+                payment_terms: None,
+                billing_country: Some("US".to_string()),
+                currency: None,
+            }),
+        )
+        .await
+        .expect("update assumptions");
+        assert_eq!(updated.0.totals["total_raw"], serde_json::json!(297.0));
+
+        let total = explain_quote_number(
+            axum::extract::Path(token.clone()),
+            Query(ExplainDrilldownQuery::default()),
+            state(pool.clone()),
+        )
+        .await
+        .expect("explain total")
+        .0;
+        assert_eq!(total.target, "total");
+        assert_eq!(total.amount, Decimal::new(297, 0));
+        assert!(!total.steps.is_empty());
+
+        let line = explain_quote_number(
+            axum::extract::Path(token.clone()),
+            Query(ExplainDrilldownQuery {
+                target: Some("line".to_string()),
+                line_id: Some("QL-EXPLAIN-1".to_string()),
+            }),
+            state(pool.clone()),
+        )
+        .await
+        .expect("explain line")
+        .0;
+        assert_eq!(line.line_id.as_deref(), Some("Q-TEST-001-ql-1"));
+        assert_eq!(line.amount, Decimal::new(270, 0));
+
+        // Approval thresholds, margin and cost never reach the customer.
+        for explanation in [&total, &line] {
+            let body = serde_json::to_string(explanation).expect("serialize").to_lowercase();
+            for internal in ["policy", "threshold", "actual_value", "margin", "cost", "source"] {
+                assert!(!body.contains(internal), "`{internal}` leaked: {body}");
+            }
+        }
+        let policy = explain_quote_number(
+            axum::extract::Path(token.clone()),
+            Query(ExplainDrilldownQuery { target: Some("policy".to_string()), line_id: None }),
+            state(pool.clone()),
+        )
+        .await
+        .expect_err("policy target is internal");
+        assert_eq!(policy.0, StatusCode::BAD_REQUEST);
+
+        let unknown = explain_quote_number(
+            axum::extract::Path(token),
+            Query(ExplainDrilldownQuery {
+                target: Some("line".to_string()),
+                line_id: Some("QL-MISSING".to_string()),
+            }),
+            state(pool),
+        )
+        .await
+        .expect_err("unknown line");
+        assert_eq!(unknown.0, StatusCode::NOT_FOUND);
+    }
 }
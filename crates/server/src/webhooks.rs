@@ -0,0 +1,548 @@
+//! Signed delivery of quote lifecycle events to webhook subscribers.
+//!
+//! Handlers call [`emit`] after a lifecycle change commits; [`quotey_db::webhooks`] fans the
+//! event out into one `webhook.call` execution task per matching subscription. The worker started
+//! by [`spawn`] sends those tasks, signing each attempt with the subscription secret (see
+//! [`quotey_core::webhooks`]), and moves them through the execution engine so retries use the
+//! `webhook.call` backoff policy. Any 2xx response completes a delivery, `410 Gone` fails it
+//! immediately, and everything else is retried until the task is dead-lettered. The target host
+//! is resolved again before every attempt; a URL that now points at a loopback, link-local or
+//! private address fails without being sent, and one that does not resolve is retried. The
+//! worker's client ([`delivery_client`]) connects only to addresses its own resolver has vetted,
+//! so a DNS answer that changes between the check and the request cannot reach a private host,
+//! and redirects are never followed.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use quotey_core::domain::execution::{
+    ExecutionTask, ExecutionTaskId, IdempotencyRecord, IdempotencyRecordState,
+};
+use quotey_core::domain::outbox::OutboxOperation;
+use quotey_core::webhooks::{
+    sign_payload, TargetPolicy, WebhookError, WebhookEvent, WebhookEventType, SIGNATURE_HEADER,
+};
+use quotey_core::{DeterministicExecutionEngine, ExecutionEngineConfig, RetryPolicy};
+use quotey_db::repositories::{
+    ExecutionQueueRepository, IdempotencyRepository, RepositoryError, SqlExecutionQueueRepository,
+};
+use quotey_db::webhooks::{
+    check_resolved_target, resolve_public, DeliveryStatus, WebhookDelivery, WebhookDeliveryAttempt,
+    WebhookService, WebhookServiceError,
+};
+use quotey_db::DbPool;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, Method};
+use serde_json::Value;
+use tracing::{info, warn};
+
+const WEBHOOK_WORKER_ID: &str = "webhook-worker";
+const USER_AGENT: &str = concat!("quotey-webhooks/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Publishes a lifecycle event to every matching subscription. Failures are logged, never
+/// surfaced, so a broken subscription cannot block the change that raised the event.
+pub async fn emit(pool: &DbPool, event_type: WebhookEventType, quote_id: &str, data: Value) {
+    let event = WebhookEvent::new(event_type, quote_id, data);
+    match WebhookService::new(pool.clone()).publish(&event).await {
+        Ok(deliveries) if !deliveries.is_empty() => info!(
+            event_name = "webhook.event.published",
+            event_id = %event.id,
+            event_type = event_type.as_str(),
+            quote_id = %quote_id,
+            deliveries = deliveries.len(),
+            "webhook event queued"
+        ),
+        Ok(_) => {}
+        Err(error) => warn!(
+            %error,
+            event_type = event_type.as_str(),
+            quote_id = %quote_id,
+            "webhook event could not be queued (non-blocking)"
+        ),
+    }
+}
+
+/// Sends every due webhook delivery once, returning the deliveries as they stand afterwards.
+pub async fn deliver_due_webhooks(
+    pool: &DbPool,
+    client: &Client,
+    target_policy: TargetPolicy,
+) -> Result<Vec<WebhookDelivery>, WebhookServiceError> {
+    let task_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT t.id FROM execution_queue_task t
+         JOIN webhook_delivery d ON d.task_id = t.id
+         WHERE t.state IN ('queued', 'retryable_failed') AND t.available_at <= ?
+         ORDER BY t.available_at ASC, t.created_at ASC
+         LIMIT ?",
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(DELIVERY_BATCH_SIZE)
+    .fetch_all(pool)
+    .await
+    .map_err(RepositoryError::from)?;
+
+    let service = WebhookService::new(pool.clone());
+    let repository = SqlExecutionQueueRepository::new(pool.clone());
+    let mut deliveries = Vec::with_capacity(task_ids.len());
+    for task_id in task_ids {
+        let Some(task) = repository.find_task_by_id(&ExecutionTaskId(task_id)).await? else {
+            continue;
+        };
+        if let Some(delivery) =
+            deliver_task(&service, &repository, client, target_policy, task).await?
+        {
+            deliveries.push(delivery);
+        }
+    }
+    Ok(deliveries)
+}
+
+async fn deliver_task(
+    service: &WebhookService,
+    repository: &SqlExecutionQueueRepository,
+    client: &Client,
+    target_policy: TargetPolicy,
+    task: ExecutionTask,
+) -> Result<Option<WebhookDelivery>, WebhookServiceError> {
+    let Some(target) = service.target_for_task(&task.id.0).await? else {
+        return Ok(None);
+    };
+    let operation = serde_json::from_str::<OutboxOperation>(&task.payload_json).ok();
+    let policy = operation.as_ref().map(OutboxOperation::retry_policy).unwrap_or_default();
+    let engine = DeterministicExecutionEngine::with_config(ExecutionEngineConfig {
+        retry_base_delay_seconds: policy.base_delay_secs,
+        ..ExecutionEngineConfig::default()
+    });
+
+    let mut idempotency_record = match repository.find_operation(&task.idempotency_key).await? {
+        Some(record) => record,
+        None => new_idempotency_record(&task),
+    };
+    let claimed = match engine.claim_task(task, WEBHOOK_WORKER_ID, &mut idempotency_record) {
+        Ok(claimed) => claimed,
+        Err(error) => {
+            warn!(delivery_id = %target.delivery.id, %error, "webhook task could not be claimed");
+            return Ok(Some(target.delivery));
+        }
+    };
+    repository.save_task(claimed.task.clone()).await?;
+    repository.append_transition(claimed.transition).await?;
+    repository.save_operation(idempotency_record.clone()).await?;
+    let task = claimed.task;
+
+    if !target.subscription_active {
+        let transition = engine
+            .fail_task(
+                task,
+                "subscription disabled",
+                "webhook_subscription_disabled",
+                RetryPolicy::FailTerminal,
+                &mut idempotency_record,
+            )
+            .map_err(transition_error)?;
+        repository.append_transition(transition.transition).await?;
+        repository.save_task(transition.task).await?;
+        repository.save_operation(idempotency_record).await?;
+        return service.cancel(&target.delivery.id).await.map(Some);
+    }
+
+    let Some(OutboxOperation::WebhookCall { url, method, headers, body }) = operation else {
+        return Err(RepositoryError::Decode(format!(
+            "task `{}` does not carry a webhook call",
+            task.id.0
+        ))
+        .into());
+    };
+    let attempted_at = Utc::now();
+    let started = Instant::now();
+    let mut request = client
+        .request(Method::from_bytes(method.as_bytes()).unwrap_or(Method::POST), &url)
+        .timeout(REQUEST_TIMEOUT)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .header(SIGNATURE_HEADER, sign_payload(&target.secret, attempted_at.timestamp(), &body));
+    for (name, value) in &headers {
+        request = request.header(name, value);
+    }
+    let blocked = check_resolved_target(&url, target_policy).await.err();
+    let outcome = match &blocked {
+        Some(error) => Err(error.to_string()),
+        None => match request.body(body).send().await {
+            Ok(response) => {
+                let status = response.status();
+                let excerpt = response.text().await.ok().filter(|text| !text.is_empty());
+                Ok((status, excerpt))
+            }
+            Err(error) => Err(error.to_string()),
+        },
+    };
+    let mut attempt = WebhookDeliveryAttempt {
+        attempt: target.delivery.attempt_count + 1,
+        status_code: None,
+        response_excerpt: None,
+        error: None,
+        duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        attempted_at,
+    };
+
+    let transition = match outcome {
+        Ok((status, excerpt)) => {
+            attempt.status_code = Some(status.as_u16());
+            attempt.response_excerpt = excerpt;
+            if status.is_success() {
+                let fingerprint = DeterministicExecutionEngine::hash_payload(&status.to_string());
+                engine.complete_task(task, fingerprint, &mut idempotency_record)
+            } else {
+                let error = format!("subscriber answered HTTP {}", status.as_u16());
+                let policy = if status == reqwest::StatusCode::GONE {
+                    RetryPolicy::FailTerminal
+                } else {
+                    RetryPolicy::Retry
+                };
+                attempt.error = Some(error.clone());
+                engine.fail_task(
+                    task,
+                    error,
+                    "webhook_http_status",
+                    policy,
+                    &mut idempotency_record,
+                )
+            }
+        }
+        Err(error) => {
+            attempt.error = Some(error.clone());
+            let (error_class, policy) = match blocked {
+                Some(WebhookError::UnresolvedTarget(_)) => {
+                    ("webhook_target_unresolved", RetryPolicy::Retry)
+                }
+                Some(_) => ("webhook_target_blocked", RetryPolicy::FailTerminal),
+                None => ("webhook_connection_failed", RetryPolicy::Retry),
+            };
+            engine.fail_task(task, error, error_class, policy, &mut idempotency_record)
+        }
+    }
+    .map_err(transition_error)?;
+
+    repository.append_transition(transition.transition.clone()).await?;
+    repository.save_task(transition.task.clone()).await?;
+    repository.save_operation(idempotency_record).await?;
+    let delivery = service.record_attempt(&target.delivery.id, &attempt, &transition.task).await?;
+    if delivery.status != DeliveryStatus::Delivered {
+        warn!(
+            delivery_id = %delivery.id,
+            subscription_id = %delivery.subscription_id,
+            status = delivery.status.as_str(),
+            error = attempt.error.as_deref().unwrap_or_default(),
+            "webhook delivery attempt failed"
+        );
+    }
+    Ok(Some(delivery))
+}
+
+fn transition_error(error: quotey_core::ExecutionError) -> WebhookServiceError {
+    RepositoryError::Decode(format!("webhook task transition failed: {error}")).into()
+}
+
+/// Webhook tasks are queued straight into `execution_queue_task`; the first claim creates the
+/// idempotency row.
+fn new_idempotency_record(task: &ExecutionTask) -> IdempotencyRecord {
+    let now = Utc::now();
+    IdempotencyRecord {
+        operation_key: task.idempotency_key.clone(),
+        quote_id: task.quote_id.clone(),
+        operation_kind: task.operation_kind.clone(),
+        payload_hash: DeterministicExecutionEngine::hash_payload(&task.payload_json),
+        state: IdempotencyRecordState::Reserved,
+        attempt_count: task.retry_count + 1,
+        first_seen_at: now,
+        last_seen_at: now,
+        result_snapshot_json: None,
+        error_snapshot_json: None,
+        expires_at: None,
+        correlation_id: task.id.0.clone(),
+        created_by_component: WEBHOOK_WORKER_ID.to_string(),
+        updated_by_component: WEBHOOK_WORKER_ID.to_string(),
+    }
+}
+
+/// Resolver that hands reqwest public addresses only, so connections go to vetted hosts.
+struct PublicAddressResolver;
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = resolve_public(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// HTTP client for webhook deliveries. Redirects are never followed, and under
+/// [`TargetPolicy::PublicOnly`] host names resolve through [`PublicAddressResolver`].
+pub fn delivery_client(target_policy: TargetPolicy) -> Client {
+    let builder = Client::builder().redirect(reqwest::redirect::Policy::none());
+    let builder = match target_policy {
+        TargetPolicy::PublicOnly => builder.dns_resolver(Arc::new(PublicAddressResolver)),
+        TargetPolicy::AllowPrivate => builder,
+    };
+    builder.build().expect("webhook http client builds")
+}
+
+/// Delivers due webhooks every few seconds until the process exits.
+pub fn spawn(pool: DbPool) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = delivery_client(TargetPolicy::PublicOnly);
+        loop {
+            match deliver_due_webhooks(&pool, &client, TargetPolicy::PublicOnly).await {
+                Ok(deliveries) if !deliveries.is_empty() => info!(
+                    delivered = deliveries
+                        .iter()
+                        .filter(|delivery| delivery.status == DeliveryStatus::Delivered)
+                        .count(),
+                    attempted = deliveries.len(),
+                    "webhook delivery pass finished"
+                ),
+                Ok(_) => {}
+                Err(error) => warn!(%error, "webhook delivery pass failed"),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use chrono::Utc;
+    use quotey_core::domain::execution::ExecutionTaskState;
+    use quotey_core::webhooks::{
+        verify_signature, TargetPolicy, WebhookEvent, WebhookEventType, DELIVERY_ID_HEADER,
+        SIGNATURE_HEADER, SIGNATURE_TOLERANCE_SECS,
+    };
+    use quotey_db::webhooks::{DeliveryStatus, NewWebhookSubscription, WebhookService};
+    use quotey_db::DbPool;
+    use reqwest::Client;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::{deliver_due_webhooks, delivery_client, emit};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    /// Subscriber endpoint that records every request and answers with `status`.
+    async fn receiver(status: StatusCode) -> (String, Received) {
+        let received: Received = Arc::default();
+        let app =
+            Router::new()
+                .route(
+                    "/hook",
+                    post(
+                        move |State(received): State<Received>,
+                              headers: HeaderMap,
+                              body: String| async move {
+                            received.lock().expect("lock").push((headers, body));
+                            (status, "ack")
+                        },
+                    ),
+                )
+                .with_state(received.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("http://{}/hook", listener.local_addr().expect("addr"));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    async fn test_pool() -> DbPool {
+        let pool = quotey_db::connect_with_settings("sqlite::memory:", 1, 30).await.expect("pool");
+        quotey_db::migrations::run_pending(&pool).await.expect("migrations");
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO quote (id, status, currency, created_by, created_at, updated_at)
+             VALUES ('Q-HOOK', 'finalized', 'USD', 'rep', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("quote");
+        pool
+    }
+
+    async fn subscribe(pool: &DbPool, url: &str) -> (String, String) {
+        let created = WebhookService::new(pool.clone())
+            .with_target_policy(TargetPolicy::AllowPrivate)
+            .subscribe(NewWebhookSubscription {
+                url: url.to_string(),
+                events: vec!["quote.*".to_string()],
+                description: None,
+                created_by: "test".to_string(),
+            })
+            .await
+            .expect("subscribe");
+        (created.subscription.id, created.secret)
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_signed_and_verifiable_by_the_subscriber() {
+        let pool = test_pool().await;
+        let (url, received) = receiver(StatusCode::NO_CONTENT).await;
+        let (_, secret) = subscribe(&pool, &url).await;
+
+        emit(&pool, WebhookEventType::QuoteFinalized, "Q-HOOK", json!({ "version": 3 })).await;
+        emit(&pool, WebhookEventType::ApprovalDecided, "Q-HOOK", json!({})).await;
+        let deliveries = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+            .await
+            .expect("deliver");
+
+        assert_eq!(deliveries.len(), 1, "only the quote.* event matches");
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].last_status_code, Some(204));
+        let received = received.lock().expect("lock").clone();
+        let (headers, body) = &received[0];
+        let signature = headers[SIGNATURE_HEADER].to_str().expect("ascii");
+        verify_signature(&secret, signature, body, Utc::now(), SIGNATURE_TOLERANCE_SECS)
+            .expect("signature verifies");
+        assert_eq!(headers[DELIVERY_ID_HEADER], deliveries[0].id.as_str());
+        let event: WebhookEvent = serde_json::from_str(body).expect("event body");
+        assert_eq!(event.event_type, WebhookEventType::QuoteFinalized);
+        assert_eq!(event.data["version"], 3);
+
+        assert!(deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+            .await
+            .expect("idle")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn failing_subscribers_are_retried_then_dead_lettered_and_can_be_redelivered() {
+        let pool = test_pool().await;
+        let (url, received) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        subscribe(&pool, &url).await;
+        emit(&pool, WebhookEventType::QuoteAccepted, "Q-HOOK", json!({})).await;
+
+        let first = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+            .await
+            .expect("deliver");
+        assert_eq!(first[0].status, DeliveryStatus::Retrying);
+        let (state, available_at): (String, String) =
+            sqlx::query_as("SELECT state, available_at FROM execution_queue_task WHERE id = ?")
+                .bind(&first[0].task_id)
+                .fetch_one(&pool)
+                .await
+                .expect("task");
+        assert_eq!(state, "retryable_failed");
+        assert!(available_at > Utc::now().to_rfc3339(), "retry waits for backoff");
+
+        // Fast-forward through the remaining retries.
+        let mut last = first[0].clone();
+        for _ in 0..8 {
+            sqlx::query("UPDATE execution_queue_task SET available_at = ? WHERE id = ?")
+                .bind(Utc::now().to_rfc3339())
+                .bind(&last.task_id)
+                .execute(&pool)
+                .await
+                .expect("rewind");
+            last = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+                .await
+                .expect("retry")
+                .remove(0);
+        }
+        assert_eq!(last.status, DeliveryStatus::DeadLettered);
+        assert_eq!(last.attempt_count, 9);
+        assert_eq!(received.lock().expect("lock").len(), 9);
+        let dead_letters: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM outbox_dead_letter WHERE operation_kind = 'webhook.call'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("dead letters");
+        assert_eq!(dead_letters, 1);
+        let task_state = quotey_db::repositories::SqlExecutionQueueRepository::new(pool.clone());
+        let task = quotey_db::repositories::ExecutionQueueRepository::find_task_by_id(
+            &task_state,
+            &quotey_core::domain::execution::ExecutionTaskId(last.task_id.clone()),
+        )
+        .await
+        .expect("find")
+        .expect("task");
+        assert_eq!(task.state, ExecutionTaskState::FailedTerminal);
+
+        let service = WebhookService::new(pool.clone());
+        let again = service.redeliver(&last.id, "test").await.expect("redeliver");
+        let retried = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+            .await
+            .expect("deliver");
+        assert_eq!(retried[0].id, again.id);
+        let log = service.delivery_log(&last.id).await.expect("log");
+        assert_eq!(log.attempts.len(), 9);
+        assert_eq!(log.attempts[8].status_code, Some(500));
+    }
+
+    #[tokio::test]
+    async fn deliveries_for_disabled_subscriptions_are_cancelled() {
+        let pool = test_pool().await;
+        let (url, received) = receiver(StatusCode::OK).await;
+        let (subscription_id, _) = subscribe(&pool, &url).await;
+        emit(&pool, WebhookEventType::QuoteFinalized, "Q-HOOK", json!({})).await;
+        WebhookService::new(pool.clone()).disable(&subscription_id).await.expect("disable");
+
+        let deliveries = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::AllowPrivate)
+            .await
+            .expect("deliver");
+        assert_eq!(deliveries[0].status, DeliveryStatus::Cancelled);
+        assert!(received.lock().expect("lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn deliveries_to_private_addresses_fail_without_being_sent() {
+        let pool = test_pool().await;
+        let (url, received) = receiver(StatusCode::OK).await;
+        // Registered while allowed, e.g. before a DNS record was repointed at a private host.
+        subscribe(&pool, &url).await;
+        emit(&pool, WebhookEventType::QuoteFinalized, "Q-HOOK", json!({})).await;
+
+        let deliveries = deliver_due_webhooks(&pool, &Client::new(), TargetPolicy::PublicOnly)
+            .await
+            .expect("deliver");
+        assert_eq!(deliveries[0].status, DeliveryStatus::DeadLettered);
+        assert!(received.lock().expect("lock").is_empty());
+        let error_class: Option<String> = sqlx::query_scalar(
+            "SELECT error_class FROM execution_queue_transition_audit
+             WHERE task_id = ? AND to_state = 'failed_terminal'",
+        )
+        .bind(&deliveries[0].task_id)
+        .fetch_one(&pool)
+        .await
+        .expect("transition");
+        assert_eq!(error_class.as_deref(), Some("webhook_target_blocked"));
+        let log =
+            WebhookService::new(pool.clone()).delivery_log(&deliveries[0].id).await.expect("log");
+        assert!(log.attempts[0].error.as_deref().is_some_and(|error| error.contains("127.0.0.1")));
+    }
+
+    #[tokio::test]
+    async fn the_delivery_client_never_connects_to_names_resolving_to_private_addresses() {
+        let (url, received) = receiver(StatusCode::OK).await;
+        let port = url.rsplit(':').next().and_then(|rest| rest.split('/').next()).expect("port");
+        let by_name = format!("http://localhost:{port}/hook");
+
+        let error = delivery_client(TargetPolicy::PublicOnly)
+            .post(&by_name)
+            .send()
+            .await
+            .expect_err("localhost resolves to loopback");
+        assert!(error.is_connect(), "{error:?}");
+        assert!(received.lock().expect("lock").is_empty());
+
+        delivery_client(TargetPolicy::AllowPrivate).post(&by_name).send().await.expect("allowed");
+        assert_eq!(received.lock().expect("lock").len(), 1);
+    }
+}
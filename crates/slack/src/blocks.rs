@@ -1182,6 +1182,170 @@ pub fn crm_field_mapping_message(view: &CrmFieldMappingSummaryView) -> MessageTe
         .build()
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplanationEvidenceView {
+    pub label: String,
+    pub source_reference: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplanationView {
+    pub quote_id: String,
+    pub quote_version: i32,
+    /// What was explained, e.g. "Quote total" or "Line Q-2026-0001-ql-2".
+    pub subject: String,
+    pub amount: String,
+    pub summary: String,
+    pub arithmetic: Vec<String>,
+    pub policy: Vec<String>,
+    pub evidence: Vec<ExplanationEvidenceView>,
+    pub cached: bool,
+    pub explanation_id: String,
+    pub request_id: String,
+}
+
+pub fn explanation_message(view: &ExplanationView) -> MessageTemplate {
+    let bullet_list = |items: &[String], empty: &str| -> String {
+        if items.is_empty() {
+            return format!("• _{empty}_");
+        }
+        items.iter().map(|item| format!("• {item}")).collect::<Vec<_>>().join("\n")
+    };
+    let evidence = if view.evidence.is_empty() {
+        "• _No evidence recorded_".to_owned()
+    } else {
+        view.evidence
+            .iter()
+            .map(|link| format!("• {} · `{}`", link.label, link.source_reference))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    MessageBuilder::new(format!("{} for {}: {}", view.subject, view.quote_id, view.amount))
+        .section("quote.explain.header.v1", |section| {
+            section.mrkdwn(format!(
+                ":mag: *{}* on `{}` (v{}) is *{}*\n{}",
+                view.subject, view.quote_id, view.quote_version, view.amount, view.summary
+            ));
+        })
+        .section("quote.explain.arithmetic.v1", |section| {
+            section.mrkdwn(format!(
+                "*How it adds up*\n{}",
+                bullet_list(&view.arithmetic, "No arithmetic for this explanation")
+            ));
+        })
+        .section("quote.explain.policy.v1", |section| {
+            section.mrkdwn(format!(
+                "*Policy checks*\n{}",
+                bullet_list(&view.policy, "No policy checks recorded")
+            ));
+        })
+        .section("quote.explain.evidence.v1", |section| {
+            section.mrkdwn(format!("*Evidence*\n{evidence}"));
+        })
+        .context("quote.explain.context.v1", |context| {
+            context.plain(format!("Explanation ID: {}", view.explanation_id));
+            context.plain(format!("Request ID: {}", view.request_id));
+            if view.cached {
+                context.plain("Served from cache for this pricing snapshot.");
+            }
+        })
+        .build()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GhostQuoteView {
+    pub ghost_id: String,
+    pub quote_id: String,
+    pub company: String,
+    /// e.g. `expansion` or `competitive_evaluation`.
+    pub signal_type: String,
+    pub intent: String,
+    pub confidence: u8,
+    pub suggested_discount_pct: u8,
+    /// Formatted draft total, e.g. "USD 10800.00".
+    pub total: String,
+    pub line_count: usize,
+    pub similar_quote_id: Option<String>,
+}
+
+pub fn ghost_quote_action_value(ghost_id: &str) -> String {
+    format!("ghost={}", encode_action_value_component(ghost_id))
+}
+
+pub fn ghost_quote_message(view: &GhostQuoteView) -> MessageTemplate {
+    let value = ghost_quote_action_value(&view.ghost_id);
+    let intent =
+        if view.intent.is_empty() { "buying intent".to_owned() } else { view.intent.clone() };
+
+    MessageBuilder::new(format!("Ghost quote drafted for {} ({})", view.company, view.total))
+        .section("ghost.quote.header.v1", |section| {
+            section.mrkdwn(format!(
+                ":ghost: *Possible deal: {}*\nSignal `{}` ({}% confidence): {}",
+                view.company,
+                view.signal_type.replace('_', " "),
+                view.confidence,
+                intent
+            ));
+        })
+        .section("ghost.quote.draft.v1", |section| {
+            section.mrkdwn(format!(
+                "Draft `{}`: {} line{} totalling *{}* with a suggested {}% discount.",
+                view.quote_id,
+                view.line_count,
+                if view.line_count == 1 { "" } else { "s" },
+                view.total,
+                view.suggested_discount_pct
+            ));
+        })
+        .actions("ghost.quote.actions.v1", |actions| {
+            actions
+                .button(
+                    ButtonElement::new("ghost.convert.v1", "Convert to quote")
+                        .style(ButtonStyle::Primary)
+                        .value(value.clone()),
+                )
+                .button(ButtonElement::new("ghost.accept.v1", "Keep draft").value(value.clone()))
+                .button(
+                    ButtonElement::new("ghost.dismiss.v1", "Dismiss")
+                        .style(ButtonStyle::Danger)
+                        .value(value.clone()),
+                );
+        })
+        .context("ghost.quote.context.v1", |context| {
+            context.plain(format!("Ghost quote ID: {}", view.ghost_id));
+            if let Some(similar) = &view.similar_quote_id {
+                context.plain(format!("Modelled on {similar}"));
+            }
+            context.plain("Dismissals make future ghost quotes more conservative.");
+        })
+        .build()
+}
+
+/// Confirmation replacing a ghost quote card once a rep has responded.
+pub fn ghost_quote_resolved_message(
+    ghost_id: &str,
+    action: &str,
+    quote_id: Option<&str>,
+) -> MessageTemplate {
+    let summary = match (action, quote_id) {
+        ("convert", Some(quote_id)) => {
+            format!(":white_check_mark: Ghost quote converted into `{quote_id}`; it's yours now.")
+        }
+        ("accept", _) => ":pushpin: Ghost draft kept for follow-up.".to_owned(),
+        ("dismiss", _) => ":wastebasket: Ghost quote dismissed.".to_owned(),
+        (other, _) => format!("Ghost quote {other}."),
+    };
+    MessageBuilder::new(summary.clone())
+        .section("ghost.quote.resolved.v1", |section| {
+            section.mrkdwn(summary);
+        })
+        .context("ghost.quote.resolved.context.v1", |context| {
+            context.plain(format!("Ghost quote ID: {ghost_id}"));
+        })
+        .build()
+}
+
 pub fn help_message() -> MessageTemplate {
     MessageBuilder::new("Quotey command guide")
         .section("quote.help.hero.v1", |section| {
@@ -1203,6 +1367,7 @@ pub fn help_message() -> MessageTemplate {
                 "*Core command matrix*\n\
 • `/quote help` · open this command card\n\
 • `/quote list [mine|open|all]` · locate candidate quotes\n\
+• `/quote explain <quote_id> [line <n>|policy]` · show why a total, line or verdict is what it is\n\
 • `/quote new [for <customer>]` · create a new quote draft\n\
 • `/quote edit <quote_id> ...` · mutate quote configuration intent\n\
 • `/quote add-line <quote_id> <sku>:<delta>` · apply deterministic line adjustments\n\
@@ -2103,14 +2268,38 @@ pub fn new_quote_started_message(_thread_ts: &str) -> MessageTemplate {
 mod tests {
     use super::{
         approval_request_message, error_message, execution_task_progress_message,
-        policy_approval_packet_action_value, policy_approval_packet_message, preview_mode_message,
-        quote_status_message, session_expired_recovery_message, session_resume_prompt,
-        simulation_comparison_message, simulation_promotion_action_value, Block, ButtonStyle,
-        DealDnaCard, DealDnaSimilarDeal, ExecutionTaskStatus, MessageBuilder,
-        PolicyApprovalDecisionKind, PolicyApprovalPacketView, SimulationComparisonView,
-        SimulationVariantView, TextObject,
+        ghost_quote_message, policy_approval_packet_action_value, policy_approval_packet_message,
+        preview_mode_message, quote_status_message, session_expired_recovery_message,
+        session_resume_prompt, simulation_comparison_message, simulation_promotion_action_value,
+        Block, ButtonStyle, DealDnaCard, DealDnaSimilarDeal, ExecutionTaskStatus, GhostQuoteView,
+        MessageBuilder, PolicyApprovalDecisionKind, PolicyApprovalPacketView,
+        SimulationComparisonView, SimulationVariantView, TextObject,
     };
 
+    #[test]
+    fn ghost_quote_message_offers_convert_keep_and_dismiss() {
+        let message = ghost_quote_message(&GhostQuoteView {
+            ghost_id: "GQ-1 a".to_owned(),
+            quote_id: "Q-GHOST-1".to_owned(),
+            company: "Acme Corp".to_owned(),
+            signal_type: "competitive_evaluation".to_owned(),
+            intent: "expand · vs salesforce".to_owned(),
+            confidence: 92,
+            suggested_discount_pct: 8,
+            total: "USD 1104.00".to_owned(),
+            line_count: 1,
+            similar_quote_id: None,
+        });
+
+        assert!(message.fallback_text.contains("Acme Corp"));
+        let Some(Block::Actions { elements, .. }) = message.blocks.get(2) else {
+            panic!("expected ghost actions block");
+        };
+        let actions: Vec<_> = elements.iter().map(|button| button.action_id.as_str()).collect();
+        assert_eq!(actions, ["ghost.convert.v1", "ghost.accept.v1", "ghost.dismiss.v1"]);
+        assert!(elements.iter().all(|button| button.value.as_deref() == Some("ghost=GQ-1%20a")));
+    }
+
     #[test]
     fn message_builder_creates_typed_block_structure() {
         let message = MessageBuilder::new("fallback")
@@ -9,12 +9,13 @@ use quotey_core::cpq::anomaly::{
     AnomalyDetector, AnomalyRuleEvaluationInput, AnomalyRuleKind, AnomalySeverity,
 };
 
-const SUPPORTED_QUOTE_VERBS: [&str; 15] = [
+const SUPPORTED_QUOTE_VERBS: [&str; 16] = [
     "help",
     "new",
     "status",
     "list",
     "audit",
+    "explain",
     "edit",
     "add-line",
     "discount",
@@ -100,6 +101,7 @@ pub enum QuoteCommand {
     Status { quote_id: Option<String>, freeform_args: String },
     List { filter: Option<String> },
     Audit { quote_id: Option<String>, freeform_args: String },
+    Explain { request: ExplainRequest },
     Edit { quote_id: Option<String>, freeform_args: String },
     AddLine { quote_id: Option<String>, freeform_args: String },
     Discount { quote_id: Option<String>, freeform_args: String },
@@ -141,6 +143,21 @@ pub struct SimulationPromotionAction {
     pub action: String,
 }
 
+/// Which number on a quote `/quote explain` should account for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExplainSubject {
+    Total,
+    Line(String),
+    Policy,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplainRequest {
+    pub quote_id: Option<String>,
+    pub subject: ExplainSubject,
+    pub raw_args: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FinalizeRequest {
     pub quote_id: Option<String>,
@@ -248,6 +265,19 @@ pub fn infer_thread_quote_command(input: &str) -> Option<String> {
         return Some("help".to_owned());
     }
 
+    if is_explain_request(&normalized) {
+        let subject = explain_subject_hint(&normalized);
+        let args = [quote_id.as_str(), subject.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if args.is_empty() {
+            return Some("explain".to_owned());
+        }
+        return Some(format!("explain {args}"));
+    }
+
     if is_status_request(&normalized) {
         if quote_id.is_empty() {
             return Some("status".to_owned());
@@ -422,6 +452,45 @@ fn is_list_request(normalized: &str) -> bool {
         || normalized.contains("check my quotes")
 }
 
+fn is_explain_request(normalized: &str) -> bool {
+    normalized.starts_with("explain")
+        || normalized.starts_with("why is")
+        || normalized.starts_with("why's")
+        || normalized.starts_with("why does")
+        || normalized.starts_with("why do")
+        || normalized.starts_with("why did")
+        || normalized.starts_with("how did we get")
+        || normalized.starts_with("how was this")
+        || normalized.contains("break down the price")
+        || normalized.contains("breakdown of the price")
+}
+
+/// Picks out `line <n>` or a policy reference from thread phrasing such as
+/// "why is line 2 $900?" or "why does this need approval?".
+fn explain_subject_hint(normalized: &str) -> String {
+    let tokens: Vec<&str> = normalized
+        .split_whitespace()
+        .map(|token| token.trim_matches(|ch: char| !ch.is_ascii_alphanumeric() && ch != '-'))
+        .collect();
+    if let Some(line_id) = tokens.iter().find(|token| token.contains("-ql-")) {
+        return (*line_id).to_owned();
+    }
+    let line_number = tokens
+        .iter()
+        .position(|token| *token == "line")
+        .and_then(|index| tokens.get(index + 1))
+        .and_then(|token| token.trim_start_matches('#').parse::<u32>().ok());
+    if let Some(number) = line_number {
+        return format!("line {number}");
+    }
+    if tokens.iter().any(|token| {
+        matches!(*token, "policy" | "approval" | "approve" | "approved" | "violation" | "blocked")
+    }) {
+        return "policy".to_owned();
+    }
+    String::new()
+}
+
 fn is_audit_request(normalized: &str) -> bool {
     let has_quote = token_matches(normalized, "quote") || token_matches(normalized, "quotes");
     normalized.starts_with("audit")
@@ -579,6 +648,7 @@ where
             QuoteCommand::Audit { quote_id, freeform_args } => {
                 self.service.audit_quote(quote_id, freeform_args, &envelope)
             }
+            QuoteCommand::Explain { request } => self.service.explain_quote(request, &envelope),
             QuoteCommand::Edit { quote_id, freeform_args } => {
                 self.service.edit_quote(quote_id, freeform_args, &envelope)
             }
@@ -673,6 +743,12 @@ pub trait QuoteCommandService: Send + Sync {
         envelope: &CommandEnvelope,
     ) -> Result<MessageTemplate, CommandRouteError>;
 
+    fn explain_quote(
+        &self,
+        request: ExplainRequest,
+        envelope: &CommandEnvelope,
+    ) -> Result<MessageTemplate, CommandRouteError>;
+
     fn edit_quote(
         &self,
         quote_id: Option<String>,
@@ -832,6 +908,25 @@ impl QuoteCommandService for NoopQuoteCommandService {
         ))
     }
 
+    fn explain_quote(
+        &self,
+        request: ExplainRequest,
+        envelope: &CommandEnvelope,
+    ) -> Result<MessageTemplate, CommandRouteError> {
+        let quote_id = request.quote_id.unwrap_or_else(|| "unknown".to_owned());
+        let subject = match request.subject {
+            ExplainSubject::Total => "quote total".to_owned(),
+            ExplainSubject::Line(line_id) => format!("line {line_id}"),
+            ExplainSubject::Policy => "policy verdict".to_owned(),
+        };
+        Ok(blocks::preview_mode_message(
+            "/quote explain",
+            Some(&quote_id),
+            &format!("explanation request captured · {subject}"),
+            &envelope.request_id,
+        ))
+    }
+
     fn edit_quote(
         &self,
         quote_id: Option<String>,
@@ -1203,6 +1298,9 @@ fn classify_quote_command(verb: &str, freeform_args: String) -> QuoteCommand {
             quote_id: freeform_args.split_whitespace().find_map(parse_quote_id_token),
             freeform_args,
         },
+        "explain" | "why" => {
+            QuoteCommand::Explain { request: parse_explain_request(freeform_args) }
+        }
         "edit" => QuoteCommand::Edit {
             quote_id: freeform_args.split_whitespace().find_map(parse_quote_id_token),
             freeform_args,
@@ -1242,6 +1340,66 @@ fn classify_quote_command(verb: &str, freeform_args: String) -> QuoteCommand {
     }
 }
 
+/// Returns the explain request carried by a normalized `/quote explain` envelope.
+pub fn explain_request(envelope: &CommandEnvelope) -> Option<ExplainRequest> {
+    if envelope.command.eq_ignore_ascii_case("quotey") {
+        return None;
+    }
+    match classify_quote_command(&envelope.verb, envelope.freeform_args.clone()) {
+        QuoteCommand::Explain { mut request } => {
+            request.quote_id = request.quote_id.or_else(|| envelope.quote_id.clone());
+            Some(request)
+        }
+        _ => None,
+    }
+}
+
+/// Parses `/quote explain <quote_id> [total | policy | line <n|line_id>]`.
+///
+/// A bare line number resolves to the `<quote_id>-ql-<n>` id used in pricing output.
+fn parse_explain_request(raw_args: String) -> ExplainRequest {
+    let quote_id = raw_args.split_whitespace().find_map(parse_quote_id_token);
+    let tokens: Vec<String> = raw_args
+        .split_whitespace()
+        .map(|token| {
+            token.trim_matches(|ch: char| !ch.is_ascii_alphanumeric() && ch != '-').to_owned()
+        })
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let explicit_line = tokens.iter().find(|token| token.to_ascii_lowercase().contains("-ql-"));
+    let numbered_line = tokens
+        .iter()
+        .position(|token| token.eq_ignore_ascii_case("line") || token.eq_ignore_ascii_case("ql"))
+        .and_then(|index| tokens.get(index + 1))
+        .and_then(|token| token.trim_start_matches('#').parse::<u32>().ok())
+        .filter(|number| *number > 0);
+    let mentions_policy = tokens.iter().any(|token| {
+        matches!(
+            token.to_ascii_lowercase().as_str(),
+            "policy" | "approval" | "approvals" | "verdict" | "violation" | "violations"
+        )
+    });
+
+    let subject = if let Some(line_id) = explicit_line {
+        let (quote_part, line_part) =
+            line_id.split_at(line_id.to_ascii_lowercase().find("-ql-").unwrap_or(0));
+        ExplainSubject::Line(format!(
+            "{}{}",
+            quote_part.to_ascii_uppercase(),
+            line_part.to_ascii_lowercase()
+        ))
+    } else if let (Some(number), Some(quote_id)) = (numbered_line, quote_id.as_deref()) {
+        ExplainSubject::Line(format!("{quote_id}-ql-{number}"))
+    } else if mentions_policy {
+        ExplainSubject::Policy
+    } else {
+        ExplainSubject::Total
+    };
+
+    ExplainRequest { quote_id, subject, raw_args }
+}
+
 fn classify_quotey_command(verb: &str, freeform_args: String) -> QuoteCommand {
     match verb {
         "branding" => QuoteCommand::Branding { freeform_args },
@@ -2400,9 +2558,9 @@ mod tests {
         action_quote_id, action_value_pairs, build_simulation_promotion_value, handle_block_action,
         handle_simulation_promotion_action, infer_thread_quote_command, normalize_quote_command,
         parse_quote_command, parse_quote_id_token, parse_simulation_promotion_value,
-        suggest_supported_verb, CommandEnvelope, CommandRouteError, CommandRouter, FinalizeRequest,
-        NoopQuoteCommandService, QuoteCommand, QuoteCommandService, SimulationRequest,
-        SlashCommandPayload,
+        suggest_supported_verb, CommandEnvelope, CommandRouteError, CommandRouter, ExplainSubject,
+        FinalizeRequest, NoopQuoteCommandService, QuoteCommand, QuoteCommandService,
+        SimulationRequest, SlashCommandPayload,
     };
     use crate::blocks::MessageTemplate;
 
@@ -3051,6 +3209,36 @@ mod tests {
         assert!(!pairs.contains_key("bad"));
     }
 
+    #[test]
+    fn explain_command_parses_total_line_and_policy_subjects() {
+        let subject = |args: &str| match parse_quote_command(&format!("explain {args}")) {
+            QuoteCommand::Explain { request } => request.subject,
+            other => panic!("expected explain command, got {other:?}"),
+        };
+
+        assert_eq!(subject("Q-2026-0001"), ExplainSubject::Total);
+        assert_eq!(
+            subject("Q-2026-0001 line 2"),
+            ExplainSubject::Line("Q-2026-0001-ql-2".to_owned())
+        );
+        assert_eq!(
+            subject("q-2026-0001-QL-4"),
+            ExplainSubject::Line("Q-2026-0001-ql-4".to_owned())
+        );
+        assert_eq!(subject("Q-2026-0001 policy"), ExplainSubject::Policy);
+        assert_eq!(subject("Q-2026-0001 why approval"), ExplainSubject::Policy);
+
+        assert_eq!(
+            infer_thread_quote_command("Why is line 2 on Q-2026-0001 $900?").as_deref(),
+            Some("explain Q-2026-0001 line 2")
+        );
+        assert_eq!(
+            infer_thread_quote_command("why does Q-2026-0001 need approval?").as_deref(),
+            Some("explain Q-2026-0001 policy")
+        );
+        assert_eq!(infer_thread_quote_command("explain this").as_deref(), Some("explain"));
+    }
+
     #[test]
     fn parse_quote_id_token_accepts_lowercase_quote_id() {
         assert_eq!(parse_quote_id_token("q-2026-0420"), Some("Q-2026-0420".to_string()));
@@ -3148,6 +3336,15 @@ mod tests {
                 Ok(crate::blocks::help_message())
             }
 
+            fn explain_quote(
+                &self,
+                _request: super::ExplainRequest,
+                _envelope: &CommandEnvelope,
+            ) -> Result<MessageTemplate, CommandRouteError> {
+                self.calls.lock().expect("lock").push("explain");
+                Ok(crate::blocks::help_message())
+            }
+
             fn edit_quote(
                 &self,
                 _quote_id: Option<String>,
@@ -3271,6 +3468,7 @@ mod tests {
             ("list", "mine"),
             ("simulate", "Q-2026-1111 variant=v1 plan-pro:+1"),
             ("audit", "Q-2026-1111"),
+            ("explain", "Q-2026-1111 line 2"),
             ("edit", "Q-2026-1111 term change"),
             ("add-line", "Q-2026-1111 addon:+1"),
             ("discount", "Q-2026-1111 25"),
@@ -3305,6 +3503,7 @@ mod tests {
                 "list",
                 "simulate",
                 "audit",
+                "explain",
                 "edit",
                 "add-line",
                 "discount",
@@ -3357,6 +3556,13 @@ mod tests {
             ) -> Result<MessageTemplate, CommandRouteError> {
                 Ok(crate::blocks::help_message())
             }
+            fn explain_quote(
+                &self,
+                _request: super::ExplainRequest,
+                _envelope: &CommandEnvelope,
+            ) -> Result<MessageTemplate, CommandRouteError> {
+                Ok(crate::blocks::help_message())
+            }
             fn edit_quote(
                 &self,
                 _quote_id: Option<String>,
@@ -3518,6 +3724,13 @@ mod tests {
             ) -> Result<MessageTemplate, CommandRouteError> {
                 Ok(crate::blocks::help_message())
             }
+            fn explain_quote(
+                &self,
+                _request: super::ExplainRequest,
+                _envelope: &CommandEnvelope,
+            ) -> Result<MessageTemplate, CommandRouteError> {
+                Ok(crate::blocks::help_message())
+            }
             fn edit_quote(
                 &self,
                 _quote_id: Option<String>,
@@ -6,9 +6,10 @@ use thiserror::Error;
 use crate::{
     blocks::{Block, MessageTemplate},
     commands::{
-        action_quote_id, action_value_pairs, extract_suggestion_feedback,
-        infer_thread_quote_command, normalize_quote_command, CommandParseError, CommandRouteError,
-        CommandRouter, NoopQuoteCommandService, QuoteCommandService, SlashCommandPayload,
+        action_quote_id, action_value_pairs, explain_request, extract_suggestion_feedback,
+        infer_thread_quote_command, normalize_quote_command, CommandEnvelope, CommandParseError,
+        CommandRouteError, CommandRouter, ExplainRequest, NoopQuoteCommandService,
+        QuoteCommandService, SlashCommandPayload,
     },
 };
 use quotey_core::domain::dialogue::SlackQuoteState;
@@ -54,6 +55,8 @@ pub enum SlackEventType {
 pub struct ThreadMessageEvent {
     pub channel_id: String,
     pub thread_ts: String,
+    /// Slack `ts` of this message; equals `thread_ts` for the thread root.
+    pub message_ts: String,
     pub user_id: String,
     pub text: String,
 }
@@ -111,6 +114,8 @@ pub enum EventHandlerError {
     BlockAction(String),
     #[error("reaction approval handler failure: {0}")]
     ReactionApproval(String),
+    #[error("explanation handler failure: {0}")]
+    Explain(String),
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -212,9 +217,36 @@ impl SuggestionShownRecorder for NoopSuggestionShownRecorder {
     }
 }
 
-pub struct SlashCommandHandler<S, R = NoopSuggestionShownRecorder> {
+/// Answers `/quote explain` from persisted pricing evidence.
+///
+/// Returning `None` leaves the command to the [`QuoteCommandService`] preview.
+#[async_trait]
+pub trait QuoteExplainer: Send + Sync {
+    async fn explain(
+        &self,
+        request: ExplainRequest,
+        envelope: &CommandEnvelope,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError>;
+}
+
+#[derive(Default)]
+pub struct NoopQuoteExplainer;
+
+#[async_trait]
+impl QuoteExplainer for NoopQuoteExplainer {
+    async fn explain(
+        &self,
+        _request: ExplainRequest,
+        _envelope: &CommandEnvelope,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        Ok(None)
+    }
+}
+
+pub struct SlashCommandHandler<S, R = NoopSuggestionShownRecorder, E = NoopQuoteExplainer> {
     router: CommandRouter<S>,
     shown_recorder: R,
+    explainer: E,
 }
 
 impl<S> SlashCommandHandler<S, NoopSuggestionShownRecorder>
@@ -222,7 +254,11 @@ where
     S: QuoteCommandService,
 {
     pub fn new(service: S) -> Self {
-        Self { router: CommandRouter::new(service), shown_recorder: NoopSuggestionShownRecorder }
+        Self {
+            router: CommandRouter::new(service),
+            shown_recorder: NoopSuggestionShownRecorder,
+            explainer: NoopQuoteExplainer,
+        }
     }
 }
 
@@ -232,15 +268,27 @@ where
     R: SuggestionShownRecorder,
 {
     pub fn with_shown_recorder(service: S, shown_recorder: R) -> Self {
-        Self { router: CommandRouter::new(service), shown_recorder }
+        Self { router: CommandRouter::new(service), shown_recorder, explainer: NoopQuoteExplainer }
+    }
+}
+
+impl<S, R, E> SlashCommandHandler<S, R, E>
+where
+    S: QuoteCommandService,
+    R: SuggestionShownRecorder,
+    E: QuoteExplainer,
+{
+    pub fn with_explainer<X: QuoteExplainer>(self, explainer: X) -> SlashCommandHandler<S, R, X> {
+        SlashCommandHandler { router: self.router, shown_recorder: self.shown_recorder, explainer }
     }
 }
 
 #[async_trait]
-impl<S, R> EventHandler for SlashCommandHandler<S, R>
+impl<S, R, E> EventHandler for SlashCommandHandler<S, R, E>
 where
     S: QuoteCommandService + 'static,
     R: SuggestionShownRecorder + 'static,
+    E: QuoteExplainer + 'static,
 {
     fn event_type(&self) -> SlackEventType {
         SlackEventType::SlashCommand
@@ -257,6 +305,11 @@ where
 
         let normalized = normalize_quote_command(payload.clone())?;
         let request_id = normalized.request_id.clone();
+        if let Some(request) = explain_request(&normalized) {
+            if let Some(message) = self.explainer.explain(request, &normalized).await? {
+                return Ok(HandlerResult::Responded(message));
+            }
+        }
         let message = self.router.route(normalized)?;
 
         let shown_records = extract_suggestion_shown_records(&message, &request_id);
@@ -536,6 +589,201 @@ where
     }
 }
 
+/// A ThreadMessageService that answers "why is this line $X?"-style questions with the
+/// explainer and delegates everything else to the inner service.
+pub struct ExplainingThreadMessageService<E, S>
+where
+    E: QuoteExplainer,
+    S: ThreadMessageService,
+{
+    explainer: E,
+    inner: S,
+}
+
+impl<E, S> ExplainingThreadMessageService<E, S>
+where
+    E: QuoteExplainer,
+    S: ThreadMessageService,
+{
+    pub fn new(explainer: E, inner: S) -> Self {
+        Self { explainer, inner }
+    }
+}
+
+#[async_trait]
+impl<E, S> ThreadMessageService for ExplainingThreadMessageService<E, S>
+where
+    E: QuoteExplainer + 'static,
+    S: ThreadMessageService + 'static,
+{
+    async fn handle_thread_message(
+        &self,
+        event: &ThreadMessageEvent,
+        ctx: &EventContext,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        if let Some(text) = infer_thread_quote_command(&event.text) {
+            let payload = SlashCommandPayload {
+                command: "/quote".to_owned(),
+                text,
+                channel_id: event.channel_id.clone(),
+                user_id: event.user_id.clone(),
+                trigger_ts: event.thread_ts.clone(),
+                request_id: format!("thread-{}", event.thread_ts),
+            };
+            let normalized = normalize_quote_command(payload)?;
+            if let Some(request) = explain_request(&normalized) {
+                if let Some(message) = self.explainer.explain(request, &normalized).await? {
+                    return Ok(Some(message));
+                }
+            }
+        }
+
+        self.inner.handle_thread_message(event, ctx).await
+    }
+}
+
+/// Watches thread traffic for buying signals (ghost quotes).
+///
+/// Returns a message when the observation produced something a rep should review.
+#[async_trait]
+pub trait BuyingSignalObserver: Send + Sync {
+    async fn observe(
+        &self,
+        event: &ThreadMessageEvent,
+        ctx: &EventContext,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError>;
+}
+
+/// A ThreadMessageService that runs every message past the buying-signal observer before
+/// delegating. Observer failures are logged and never block the thread reply; a reply from
+/// the inner service takes precedence over the observer's message.
+pub struct SignalObservingThreadMessageService<O, S>
+where
+    O: BuyingSignalObserver,
+    S: ThreadMessageService,
+{
+    observer: O,
+    inner: S,
+}
+
+impl<O, S> SignalObservingThreadMessageService<O, S>
+where
+    O: BuyingSignalObserver,
+    S: ThreadMessageService,
+{
+    pub fn new(observer: O, inner: S) -> Self {
+        Self { observer, inner }
+    }
+}
+
+#[async_trait]
+impl<O, S> ThreadMessageService for SignalObservingThreadMessageService<O, S>
+where
+    O: BuyingSignalObserver + 'static,
+    S: ThreadMessageService + 'static,
+{
+    async fn handle_thread_message(
+        &self,
+        event: &ThreadMessageEvent,
+        ctx: &EventContext,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        let observed = match self.observer.observe(event, ctx).await {
+            Ok(message) => message,
+            Err(error) => {
+                tracing::warn!(
+                    event_name = "ingress.slack.buying_signal.observe_failed",
+                    correlation_id = %ctx.correlation_id,
+                    thread_id = %event.thread_ts,
+                    error = %error,
+                    "failed to observe thread message for buying signals"
+                );
+                None
+            }
+        };
+
+        match self.inner.handle_thread_message(event, ctx).await? {
+            Some(message) => Ok(Some(message)),
+            None => Ok(observed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GhostQuoteAction {
+    Accept,
+    Dismiss,
+    Convert,
+}
+
+impl GhostQuoteAction {
+    pub fn from_action_id(action_id: &str) -> Option<Self> {
+        match action_id {
+            "ghost.accept.v1" => Some(Self::Accept),
+            "ghost.dismiss.v1" => Some(Self::Dismiss),
+            "ghost.convert.v1" => Some(Self::Convert),
+            _ => None,
+        }
+    }
+}
+
+/// Applies a rep's response to a ghost quote card.
+#[async_trait]
+pub trait GhostQuoteActionService: Send + Sync {
+    async fn resolve(
+        &self,
+        ghost_id: &str,
+        action: GhostQuoteAction,
+        user_id: &str,
+        ctx: &EventContext,
+    ) -> Result<MessageTemplate, EventHandlerError>;
+}
+
+/// A BlockActionService that handles ghost quote buttons and delegates everything else.
+pub struct GhostQuoteBlockActionService<G, S>
+where
+    G: GhostQuoteActionService,
+    S: BlockActionService,
+{
+    ghost: G,
+    inner: S,
+}
+
+impl<G, S> GhostQuoteBlockActionService<G, S>
+where
+    G: GhostQuoteActionService,
+    S: BlockActionService,
+{
+    pub fn new(ghost: G, inner: S) -> Self {
+        Self { ghost, inner }
+    }
+}
+
+#[async_trait]
+impl<G, S> BlockActionService for GhostQuoteBlockActionService<G, S>
+where
+    G: GhostQuoteActionService + 'static,
+    S: BlockActionService + 'static,
+{
+    async fn handle_block_action(
+        &self,
+        event: &BlockActionEvent,
+        ctx: &EventContext,
+    ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+        let Some(action) = GhostQuoteAction::from_action_id(&event.action_id) else {
+            return self.inner.handle_block_action(event, ctx).await;
+        };
+        let ghost_id = action_value_pairs(event.value.as_deref())
+            .and_then(|pairs| pairs.get("ghost").map(|value| value.trim().to_owned()))
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| {
+                EventHandlerError::BlockAction(
+                    "ghost quote action is missing its ghost id".to_owned(),
+                )
+            })?;
+        self.ghost.resolve(&ghost_id, action, &event.user_id, ctx).await.map(Some)
+    }
+}
+
 #[async_trait]
 pub trait BlockActionService: Send + Sync {
     async fn handle_block_action(
@@ -900,14 +1148,21 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     use super::{
-        async_trait, default_dispatcher, BlockActionEvent, BlockActionService, EventContext,
-        EventDispatcher, EventHandler, EventHandlerError, HandlerResult, NoopBlockActionService,
-        NoopSessionLookup, ReactionAddedEvent, ReactionApprovalAction, ResumableSessionInfo,
-        ResumableThreadMessageService, SessionLookup, SlackEnvelope, SlackEvent,
-        SlashCommandHandler, SuggestionFeedbackRecorder, SuggestionShownRecord,
-        SuggestionShownRecorder, ThreadMessageEvent, ThreadMessageService,
+        async_trait, default_dispatcher, BlockActionEvent, BlockActionService,
+        BuyingSignalObserver, EventContext, EventDispatcher, EventHandler, EventHandlerError,
+        ExplainingThreadMessageService, GhostQuoteAction, GhostQuoteActionService,
+        GhostQuoteBlockActionService, HandlerResult, NoopBlockActionService, NoopSessionLookup,
+        NoopThreadMessageService, QuoteExplainer, ReactionAddedEvent, ReactionApprovalAction,
+        ResumableSessionInfo, ResumableThreadMessageService, SessionLookup,
+        SignalObservingThreadMessageService, SlackEnvelope, SlackEvent, SlashCommandHandler,
+        SuggestionFeedbackRecorder, SuggestionShownRecord, SuggestionShownRecorder,
+        ThreadMessageEvent, ThreadMessageService,
+    };
+    use crate::blocks::MessageTemplate;
+    use crate::commands::{
+        CommandEnvelope, ExplainRequest, ExplainSubject, NoopQuoteCommandService,
+        SlashCommandPayload,
     };
-    use crate::commands::{NoopQuoteCommandService, SlashCommandPayload};
     use quotey_core::domain::dialogue::SlackQuoteState;
     use quotey_core::suggestions::SuggestionFeedbackEvent;
 
@@ -943,6 +1198,26 @@ mod tests {
         }
     }
 
+    #[derive(Clone, Default)]
+    struct RecordingExplainer {
+        requests: Arc<Mutex<Vec<ExplainRequest>>>,
+    }
+
+    #[async_trait]
+    impl QuoteExplainer for RecordingExplainer {
+        async fn explain(
+            &self,
+            request: ExplainRequest,
+            envelope: &CommandEnvelope,
+        ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+            self.requests.lock().expect("lock explain requests").push(request);
+            Ok(Some(MessageTemplate {
+                fallback_text: format!("explained:{}", envelope.request_id),
+                blocks: vec![],
+            }))
+        }
+    }
+
     /// qa-tag: fake-in-memory-critical-path (bd-3vp2.1)
     #[tokio::test]
     async fn dispatcher_routes_slash_commands() {
@@ -997,6 +1272,201 @@ mod tests {
         assert!(records.iter().all(|record| record.score.is_some()));
     }
 
+    #[tokio::test]
+    async fn slash_command_handler_answers_explain_with_explainer() {
+        let explainer = RecordingExplainer::default();
+        let captured = explainer.requests.clone();
+        let handler = SlashCommandHandler::new(NoopQuoteCommandService).with_explainer(explainer);
+
+        let envelope = SlackEnvelope {
+            envelope_id: "env-explain-1".to_owned(),
+            event: SlackEvent::SlashCommand(SlashCommandPayload {
+                command: "/quote".to_owned(),
+                text: "explain Q-2026-0501 line 2".to_owned(),
+                channel_id: "C1".to_owned(),
+                user_id: "U1".to_owned(),
+                trigger_ts: "1".to_owned(),
+                request_id: "req-explain-1".to_owned(),
+            }),
+        };
+
+        let result = handler.handle(&envelope, &EventContext::default()).await.expect("handle");
+        let HandlerResult::Responded(message) = result else {
+            panic!("explain should respond");
+        };
+        assert_eq!(message.fallback_text, "explained:req-explain-1");
+        let requests = captured.lock().expect("lock explain requests");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].quote_id.as_deref(), Some("Q-2026-0501"));
+        assert_eq!(requests[0].subject, ExplainSubject::Line("Q-2026-0501-ql-2".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn explaining_thread_service_answers_why_questions_and_delegates_the_rest() {
+        let explainer = RecordingExplainer::default();
+        let captured = explainer.requests.clone();
+        let service =
+            ExplainingThreadMessageService::new(explainer, NoopThreadMessageService::new());
+        let event = |text: &str| ThreadMessageEvent {
+            channel_id: "C1".to_owned(),
+            thread_ts: "T-explain".to_owned(),
+            message_ts: "T-explain".to_owned(),
+            user_id: "U1".to_owned(),
+            text: text.to_owned(),
+        };
+
+        let answered = service
+            .handle_thread_message(
+                &event("why is line 3 on Q-2026-0501 so expensive?"),
+                &EventContext::default(),
+            )
+            .await
+            .expect("thread message")
+            .expect("response");
+        assert_eq!(answered.fallback_text, "explained:thread-T-explain");
+
+        let delegated = service
+            .handle_thread_message(&event("check status for Q-2026-0501"), &EventContext::default())
+            .await
+            .expect("thread message")
+            .expect("response");
+        assert_ne!(delegated.fallback_text, "explained:thread-T-explain");
+
+        let requests = captured.lock().expect("lock explain requests");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].subject, ExplainSubject::Line("Q-2026-0501-ql-3".to_owned()));
+    }
+
+    struct FixedObserver(Result<Option<&'static str>, &'static str>);
+
+    #[async_trait]
+    impl BuyingSignalObserver for FixedObserver {
+        async fn observe(
+            &self,
+            _event: &ThreadMessageEvent,
+            _ctx: &EventContext,
+        ) -> Result<Option<MessageTemplate>, EventHandlerError> {
+            self.0
+                .map(|text| {
+                    text.map(|text| MessageTemplate {
+                        fallback_text: text.to_owned(),
+                        blocks: Vec::new(),
+                    })
+                })
+                .map_err(|error| EventHandlerError::ThreadMessage(error.to_owned()))
+        }
+    }
+
+    #[tokio::test]
+    async fn signal_observer_sees_every_message_without_blocking_thread_replies() {
+        let event = |text: &str| ThreadMessageEvent {
+            channel_id: "C1".to_owned(),
+            thread_ts: "T-ghost".to_owned(),
+            message_ts: "T-ghost.2".to_owned(),
+            user_id: "U1".to_owned(),
+            text: text.to_owned(),
+        };
+        let chatter = event("Acme Corp wants to expand next quarter");
+        let command = event("check status for Q-2026-0501");
+
+        let drafted = SignalObservingThreadMessageService::new(
+            FixedObserver(Ok(Some("ghost drafted"))),
+            NoopThreadMessageService::new(),
+        );
+        let reply = drafted
+            .handle_thread_message(&chatter, &EventContext::default())
+            .await
+            .expect("thread message")
+            .expect("observer message");
+        assert_eq!(reply.fallback_text, "ghost drafted");
+        let reply = drafted
+            .handle_thread_message(&command, &EventContext::default())
+            .await
+            .expect("thread message")
+            .expect("inner reply");
+        assert_ne!(reply.fallback_text, "ghost drafted");
+
+        let failing = SignalObservingThreadMessageService::new(
+            FixedObserver(Err("db down")),
+            NoopThreadMessageService::new(),
+        );
+        let reply = failing
+            .handle_thread_message(&chatter, &EventContext::default())
+            .await
+            .expect("observer failures are not surfaced");
+        assert!(reply.is_none());
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordingGhostActions {
+        calls: Arc<Mutex<Vec<(String, GhostQuoteAction, String)>>>,
+    }
+
+    #[async_trait]
+    impl GhostQuoteActionService for RecordingGhostActions {
+        async fn resolve(
+            &self,
+            ghost_id: &str,
+            action: GhostQuoteAction,
+            user_id: &str,
+            _ctx: &EventContext,
+        ) -> Result<MessageTemplate, EventHandlerError> {
+            self.calls.lock().expect("lock ghost calls").push((
+                ghost_id.to_owned(),
+                action,
+                user_id.to_owned(),
+            ));
+            Ok(MessageTemplate {
+                fallback_text: format!("resolved:{ghost_id}"),
+                blocks: Vec::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn ghost_buttons_resolve_through_ghost_service_and_other_actions_delegate() {
+        let ghost = RecordingGhostActions::default();
+        let calls = ghost.calls.clone();
+        let service = GhostQuoteBlockActionService::new(ghost, NoopBlockActionService::new());
+        let action = |action_id: &str, value: Option<&str>| BlockActionEvent {
+            channel_id: "C1".to_owned(),
+            message_ts: "1.0".to_owned(),
+            thread_ts: None,
+            user_id: "U-REP".to_owned(),
+            action_id: action_id.to_owned(),
+            value: value.map(str::to_owned),
+            quote_id: None,
+            request_id: None,
+        };
+
+        let resolved = service
+            .handle_block_action(
+                &action("ghost.convert.v1", Some("ghost=GQ-1%20a")),
+                &EventContext::default(),
+            )
+            .await
+            .expect("ghost action")
+            .expect("response");
+        assert_eq!(resolved.fallback_text, "resolved:GQ-1 a");
+        assert_eq!(
+            calls.lock().expect("lock ghost calls").as_slice(),
+            [("GQ-1 a".to_owned(), GhostQuoteAction::Convert, "U-REP".to_owned())]
+        );
+
+        let missing = service
+            .handle_block_action(&action("ghost.dismiss.v1", None), &EventContext::default())
+            .await;
+        assert!(matches!(missing, Err(EventHandlerError::BlockAction(_))));
+
+        let delegated = service
+            .handle_block_action(&action("quote.help.v1", None), &EventContext::default())
+            .await
+            .expect("help action")
+            .expect("help response");
+        assert_eq!(delegated, crate::blocks::help_message());
+        assert_eq!(calls.lock().expect("lock ghost calls").len(), 1);
+    }
+
     #[tokio::test]
     async fn dispatcher_routes_quotey_branding_slash_command() {
         let dispatcher = default_dispatcher();
@@ -1025,6 +1495,7 @@ mod tests {
             event: SlackEvent::ThreadMessage(ThreadMessageEvent {
                 channel_id: "C1".to_owned(),
                 thread_ts: "T1".to_owned(),
+                message_ts: "T1".to_owned(),
                 user_id: "U2".to_owned(),
                 text: "hello".to_owned(),
             }),
@@ -1253,6 +1724,7 @@ mod tests {
             event: SlackEvent::ThreadMessage(ThreadMessageEvent {
                 channel_id: "C1".to_owned(),
                 thread_ts: "1730000001.0000".to_owned(),
+                message_ts: "1730000001.0000".to_owned(),
                 user_id: "U8".to_owned(),
                 text: "random thread banter".to_owned(),
             }),
@@ -1369,6 +1841,7 @@ mod tests {
         let event = ThreadMessageEvent {
             channel_id: "C1".to_owned(),
             thread_ts: "thread-resume".to_owned(),
+            message_ts: "thread-resume".to_owned(),
             user_id: "U1".to_owned(),
             text: "any message".to_owned(),
         };
@@ -1400,6 +1873,7 @@ mod tests {
         let event = ThreadMessageEvent {
             channel_id: "C1".to_owned(),
             thread_ts: "thread-expired".to_owned(),
+            message_ts: "thread-expired".to_owned(),
             user_id: "U1".to_owned(),
             text: "any message".to_owned(),
         };
@@ -1422,6 +1896,7 @@ mod tests {
         let event = ThreadMessageEvent {
             channel_id: "C1".to_owned(),
             thread_ts: "thread-new".to_owned(),
+            message_ts: "thread-new".to_owned(),
             user_id: "U1".to_owned(),
             text: "quote for Acme Corp".to_owned(),
         };
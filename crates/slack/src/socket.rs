@@ -450,6 +450,7 @@ mod tests {
             event: SlackEvent::ThreadMessage(crate::events::ThreadMessageEvent {
                 channel_id: "C1".to_owned(),
                 thread_ts: "1730000000.1000".to_owned(),
+                message_ts: "1730000000.1000".to_owned(),
                 user_id: "U1".to_owned(),
                 text: "status Q-2026-0032".to_owned(),
             }),
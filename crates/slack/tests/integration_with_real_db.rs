@@ -26,8 +26,8 @@ use quotey_db::repositories::{
 use quotey_slack::blocks::MessageTemplate;
 use quotey_slack::commands::{
     infer_thread_quote_command, normalize_quote_command, parse_quote_command, CommandEnvelope,
-    CommandRouteError, CommandRouter, NoopQuoteCommandService, QuoteCommand, QuoteCommandService,
-    SlashCommandPayload,
+    CommandRouteError, CommandRouter, ExplainRequest, NoopQuoteCommandService, QuoteCommand,
+    QuoteCommandService, SlashCommandPayload,
 };
 use quotey_slack::events::{
     BlockActionEvent, BlockActionService, EventContext, EventHandler, EventHandlerError,
@@ -254,6 +254,15 @@ impl QuoteCommandService for DbBackedQuoteCommandService {
         Ok(MessageTemplate { fallback_text: format!("db:audit_quote:id={qid}"), blocks: vec![] })
     }
 
+    fn explain_quote(
+        &self,
+        request: ExplainRequest,
+        _envelope: &CommandEnvelope,
+    ) -> Result<MessageTemplate, CommandRouteError> {
+        let qid = request.quote_id.unwrap_or_else(|| "none".to_string());
+        Ok(MessageTemplate { fallback_text: format!("db:explain_quote:id={qid}"), blocks: vec![] })
+    }
+
     fn edit_quote(
         &self,
         quote_id: Option<String>,
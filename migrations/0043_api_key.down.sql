@@ -0,0 +1,4 @@
+-- Reverse migration: 0043_api_key
+DROP INDEX IF EXISTS idx_api_key_active;
+DROP INDEX IF EXISTS idx_api_key_name;
+DROP TABLE IF EXISTS api_key;
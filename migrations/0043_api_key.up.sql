@@ -0,0 +1,22 @@
+-- Migration: 0043_api_key
+-- Description: Hashed MCP API keys with scopes and account/team restrictions
+-- Only the SHA-256 hash of each key secret is stored; the secret is shown once at creation.
+
+CREATE TABLE api_key (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    key_prefix TEXT NOT NULL,
+    scopes_json TEXT NOT NULL DEFAULT '[]',
+    account_ids_json TEXT NOT NULL DEFAULT '[]',
+    team_ids_json TEXT NOT NULL DEFAULT '[]',
+    requests_per_minute INTEGER NOT NULL DEFAULT 60 CHECK (requests_per_minute > 0),
+    active INTEGER NOT NULL DEFAULT 1 CHECK (active IN (0, 1)),
+    rotated_from TEXT REFERENCES api_key(id),
+    created_at TEXT NOT NULL,
+    revoked_at TEXT,
+    last_used_at TEXT
+);
+
+CREATE INDEX idx_api_key_name ON api_key(name);
+CREATE INDEX idx_api_key_active ON api_key(active);
@@ -0,0 +1,11 @@
+-- Reverse migration: 0044_deal_similarity_index
+DROP INDEX IF EXISTS idx_deal_similarity_index_filters;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_7;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_6;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_5;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_4;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_3;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_2;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_1;
+DROP INDEX IF EXISTS idx_deal_similarity_index_chunk_0;
+DROP TABLE IF EXISTS deal_similarity_index;
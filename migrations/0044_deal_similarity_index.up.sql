@@ -0,0 +1,35 @@
+-- Migration: 0044_deal_similarity_index
+-- Description: Persistent multi-index hash over closed-deal configuration fingerprints
+-- The 128-bit fingerprint is split into eight 16-bit chunks, each indexed separately. Any deal
+-- within Hamming distance r of a query matches it exactly on some chunk within r / 8 bits, so
+-- lookups probe a handful of chunk values instead of scanning every fingerprint.
+
+CREATE TABLE deal_similarity_index (
+    quote_id TEXT PRIMARY KEY,
+    fingerprint BLOB NOT NULL CHECK (length(fingerprint) = 16),
+    chunk_0 INTEGER NOT NULL,
+    chunk_1 INTEGER NOT NULL,
+    chunk_2 INTEGER NOT NULL,
+    chunk_3 INTEGER NOT NULL,
+    chunk_4 INTEGER NOT NULL,
+    chunk_5 INTEGER NOT NULL,
+    chunk_6 INTEGER NOT NULL,
+    chunk_7 INTEGER NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('won', 'lost', 'pending')),
+    customer_segment TEXT,
+    final_price REAL NOT NULL,
+    close_date TEXT,
+    indexed_at TEXT NOT NULL,
+    FOREIGN KEY (quote_id) REFERENCES quote(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_deal_similarity_index_chunk_0 ON deal_similarity_index(chunk_0);
+CREATE INDEX idx_deal_similarity_index_chunk_1 ON deal_similarity_index(chunk_1);
+CREATE INDEX idx_deal_similarity_index_chunk_2 ON deal_similarity_index(chunk_2);
+CREATE INDEX idx_deal_similarity_index_chunk_3 ON deal_similarity_index(chunk_3);
+CREATE INDEX idx_deal_similarity_index_chunk_4 ON deal_similarity_index(chunk_4);
+CREATE INDEX idx_deal_similarity_index_chunk_5 ON deal_similarity_index(chunk_5);
+CREATE INDEX idx_deal_similarity_index_chunk_6 ON deal_similarity_index(chunk_6);
+CREATE INDEX idx_deal_similarity_index_chunk_7 ON deal_similarity_index(chunk_7);
+CREATE INDEX idx_deal_similarity_index_filters
+    ON deal_similarity_index(outcome, customer_segment, close_date);
@@ -0,0 +1,13 @@
+-- Reverse migration: 0045_win_probability_registry
+DROP INDEX IF EXISTS idx_win_probability_models_active;
+
+ALTER TABLE prediction_cache DROP COLUMN feature_hash;
+
+ALTER TABLE win_probability_models DROP COLUMN previous_version;
+ALTER TABLE win_probability_models DROP COLUMN promoted_at;
+ALTER TABLE win_probability_models DROP COLUMN brier_score;
+ALTER TABLE win_probability_models DROP COLUMN holdout_samples;
+ALTER TABLE win_probability_models DROP COLUMN training_samples;
+ALTER TABLE win_probability_models DROP COLUMN metrics_json;
+ALTER TABLE win_probability_models DROP COLUMN model_json;
+ALTER TABLE win_probability_models DROP COLUMN status;
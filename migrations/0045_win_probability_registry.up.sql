@@ -0,0 +1,21 @@
+-- Migration: 0045_win_probability_registry
+-- Description: Versioned win-probability models stored in the database with an
+-- active/candidate/retired lifecycle, holdout metrics and promote/rollback lineage.
+-- Prediction cache rows record the feature hash they were scored from so edits to a
+-- quote invalidate its cached prediction.
+
+ALTER TABLE win_probability_models ADD COLUMN status TEXT NOT NULL DEFAULT 'candidate'
+    CHECK (status IN ('candidate', 'active', 'retired'));
+ALTER TABLE win_probability_models ADD COLUMN model_json TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE win_probability_models ADD COLUMN metrics_json TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE win_probability_models ADD COLUMN training_samples INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE win_probability_models ADD COLUMN holdout_samples INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE win_probability_models ADD COLUMN brier_score REAL;
+ALTER TABLE win_probability_models ADD COLUMN promoted_at TEXT;
+ALTER TABLE win_probability_models ADD COLUMN previous_version TEXT;
+
+-- At most one model serves predictions at a time.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_win_probability_models_active
+    ON win_probability_models(status) WHERE status = 'active';
+
+ALTER TABLE prediction_cache ADD COLUMN feature_hash TEXT NOT NULL DEFAULT '';
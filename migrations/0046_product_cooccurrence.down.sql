@@ -0,0 +1,6 @@
+-- Reverse migration: 0046_product_cooccurrence
+DROP INDEX IF EXISTS idx_product_cooccurrence_pair_b;
+DROP TABLE IF EXISTS product_cooccurrence_pair;
+DROP TABLE IF EXISTS product_cooccurrence_item;
+DROP INDEX IF EXISTS idx_product_cooccurrence_basket_account;
+DROP TABLE IF EXISTS product_cooccurrence_basket;
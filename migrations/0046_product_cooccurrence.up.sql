@@ -0,0 +1,34 @@
+-- Migration: 0046_product_cooccurrence
+-- Description: Incrementally maintained product co-occurrence counts for suggestions
+-- Each quote is stored as a basket of distinct product ids together with a marker of the quote
+-- and line timestamps it was read from. A refresh only re-reads quotes whose marker changed and
+-- applies the difference between the old and new basket to the item and pair counts, from which
+-- support, confidence and lift are derived at read time. Baskets deliberately have no foreign
+-- key to quote: a deleted quote's basket must still be subtracted before it is dropped.
+
+CREATE TABLE product_cooccurrence_basket (
+    quote_id TEXT PRIMARY KEY,
+    account_id TEXT,
+    product_ids_json TEXT NOT NULL DEFAULT '[]',
+    source_marker TEXT NOT NULL,
+    quoted_at TEXT NOT NULL,
+    indexed_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_product_cooccurrence_basket_account
+    ON product_cooccurrence_basket(account_id);
+
+CREATE TABLE product_cooccurrence_item (
+    product_id TEXT PRIMARY KEY,
+    basket_count INTEGER NOT NULL CHECK (basket_count >= 0)
+);
+
+CREATE TABLE product_cooccurrence_pair (
+    product_a TEXT NOT NULL,
+    product_b TEXT NOT NULL,
+    pair_count INTEGER NOT NULL CHECK (pair_count >= 0),
+    PRIMARY KEY (product_a, product_b),
+    CHECK (product_a < product_b)
+);
+
+CREATE INDEX idx_product_cooccurrence_pair_b ON product_cooccurrence_pair(product_b);
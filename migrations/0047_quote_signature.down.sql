@@ -0,0 +1,3 @@
+-- Reverse migration: 0047_quote_signature
+DROP INDEX IF EXISTS idx_quote_signature_request_quote;
+DROP TABLE IF EXISTS quote_signature_request;
@@ -0,0 +1,35 @@
+-- Migration: 0047_quote_signature
+-- Description: Customer e-signature requests and the signed acceptances they produce
+-- A request is opened when a signer enters their name, title and email in the portal; only a
+-- salted hash of the one-time code sent to that email is kept. Completing the request stores the
+-- signature, the signing evidence and the signed PDF, whose SHA-256 is also written to
+-- quote_ledger. Starting a new request supersedes any pending one for the same quote.
+
+CREATE TABLE quote_signature_request (
+    id TEXT PRIMARY KEY,
+    quote_id TEXT NOT NULL,
+    quote_version INTEGER NOT NULL CHECK (quote_version >= 1),
+    template TEXT NOT NULL,
+    document_sha256 TEXT NOT NULL,
+    signer_name TEXT NOT NULL,
+    signer_title TEXT NOT NULL,
+    signer_email TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    code_expires_at TEXT NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0 CHECK (failed_attempts >= 0),
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'signed', 'locked', 'superseded')),
+    requested_ip TEXT NOT NULL,
+    requested_user_agent TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    signature_json TEXT,
+    evidence_json TEXT,
+    signed_pdf BLOB,
+    signed_pdf_sha256 TEXT,
+    ledger_entry_id TEXT,
+    signed_at TEXT,
+    FOREIGN KEY (quote_id) REFERENCES quote(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_quote_signature_request_quote
+    ON quote_signature_request(quote_id, status);
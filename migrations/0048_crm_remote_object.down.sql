@@ -0,0 +1,4 @@
+-- Reverse migration: 0048_crm_remote_object
+DROP INDEX IF EXISTS idx_crm_remote_object_remote_id;
+DROP INDEX IF EXISTS idx_crm_remote_object_quote;
+DROP TABLE IF EXISTS crm_remote_object;
@@ -0,0 +1,27 @@
+-- Migration: 0048_crm_remote_object
+-- Description: Links between Quotey records and the CRM objects written for them
+-- One row per remote object Quotey has created: the Opportunity/Deal for a quote (local_id is the
+-- quote id) and one OpportunityLineItem/line item per quote line (local_id is the quote_line id).
+-- last_pushed_at records when Quotey last wrote the object; remote_modified_at records the newest
+-- CRM-side change applied back to the quote. Inbound webhooks compare against both to decide
+-- whether a remote change is a stale echo, a conflict Quotey wins, or an update to apply.
+
+CREATE TABLE crm_remote_object (
+    id TEXT PRIMARY KEY NOT NULL,
+    provider TEXT NOT NULL CHECK (provider IN ('salesforce', 'hubspot')),
+    object_type TEXT NOT NULL
+        CHECK (object_type IN ('opportunity', 'opportunity_line_item', 'deal', 'line_item')),
+    quote_id TEXT NOT NULL,
+    local_id TEXT NOT NULL,
+    remote_id TEXT NOT NULL,
+    last_pushed_at TEXT,
+    last_pushed_version INTEGER,
+    remote_modified_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (provider, object_type, local_id),
+    FOREIGN KEY (quote_id) REFERENCES quote(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_crm_remote_object_quote ON crm_remote_object(quote_id, provider);
+CREATE INDEX idx_crm_remote_object_remote_id ON crm_remote_object(provider, remote_id);
@@ -0,0 +1,4 @@
+-- Reverse migration: 0049_email_message
+DROP INDEX IF EXISTS idx_email_message_quote;
+DROP INDEX IF EXISTS idx_email_message_thread;
+DROP TABLE IF EXISTS email_message;
@@ -0,0 +1,29 @@
+-- Migration: 0049_email_message
+-- Description: Email sent and received through the email integration adapter
+-- Every delivered `email.send` task and every polled inbound message gets one row. thread_id is
+-- the Message-ID of the first message in the conversation, so a customer reply that names any
+-- earlier message in In-Reply-To/References joins the same thread and the quote linked to it.
+-- status records what inbound intake did with the message: opened a draft quote, attached it to
+-- an existing quote thread, skipped it as not an RFQ, found no catalog match, or failed.
+
+CREATE TABLE email_message (
+    id TEXT PRIMARY KEY NOT NULL,
+    integration_id TEXT NOT NULL,
+    direction TEXT NOT NULL CHECK (direction IN ('inbound', 'outbound')),
+    message_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    quote_id TEXT,
+    from_address TEXT NOT NULL,
+    to_addresses_json TEXT NOT NULL DEFAULT '[]',
+    subject TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL
+        CHECK (status IN ('sent', 'draft_created', 'linked', 'not_rfq', 'no_match', 'failed')),
+    detail TEXT,
+    occurred_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (direction, message_id),
+    FOREIGN KEY (quote_id) REFERENCES quote(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_email_message_thread ON email_message(thread_id);
+CREATE INDEX idx_email_message_quote ON email_message(quote_id);
@@ -0,0 +1,7 @@
+-- Reverse migration: 0050_webhook_subscription
+DROP INDEX IF EXISTS idx_webhook_delivery_attempt_delivery;
+DROP TABLE IF EXISTS webhook_delivery_attempt;
+DROP INDEX IF EXISTS idx_webhook_delivery_event;
+DROP INDEX IF EXISTS idx_webhook_delivery_subscription;
+DROP TABLE IF EXISTS webhook_delivery;
+DROP TABLE IF EXISTS webhook_subscription;
@@ -0,0 +1,60 @@
+-- Migration: 0050_webhook_subscription
+-- Description: Customer webhook subscriptions and their delivery log
+-- A subscription receives every quote lifecycle event whose type matches one of the patterns in
+-- events_json (`quote.finalized`, `quote.*`, `*`). The secret signs each delivery with
+-- HMAC-SHA256, so it is stored as issued; it is only returned when the subscription is created.
+-- Each matching event gets one webhook_delivery row and one `webhook.call` execution task; every
+-- HTTP attempt appends to webhook_delivery_attempt. Redelivery opens a new delivery pointing at
+-- the original through redelivery_of, so the log keeps every attempt.
+
+CREATE TABLE webhook_subscription (
+    id TEXT PRIMARY KEY NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events_json TEXT NOT NULL DEFAULT '[]',
+    description TEXT,
+    active INTEGER NOT NULL DEFAULT 1 CHECK (active IN (0, 1)),
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE webhook_delivery (
+    id TEXT PRIMARY KEY NOT NULL,
+    subscription_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    quote_id TEXT NOT NULL,
+    task_id TEXT NOT NULL UNIQUE,
+    redelivery_of TEXT,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'retrying', 'delivered', 'dead_lettered', 'cancelled')),
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    last_status_code INTEGER,
+    last_error TEXT,
+    last_attempt_at TEXT,
+    delivered_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (subscription_id) REFERENCES webhook_subscription(id) ON DELETE CASCADE,
+    FOREIGN KEY (quote_id) REFERENCES quote(id) ON DELETE CASCADE,
+    FOREIGN KEY (redelivery_of) REFERENCES webhook_delivery(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_webhook_delivery_subscription
+    ON webhook_delivery(subscription_id, created_at);
+CREATE INDEX idx_webhook_delivery_event ON webhook_delivery(event_id);
+
+CREATE TABLE webhook_delivery_attempt (
+    id TEXT PRIMARY KEY NOT NULL,
+    delivery_id TEXT NOT NULL,
+    attempt INTEGER NOT NULL CHECK (attempt >= 1),
+    status_code INTEGER,
+    response_excerpt TEXT,
+    error TEXT,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    attempted_at TEXT NOT NULL,
+    FOREIGN KEY (delivery_id) REFERENCES webhook_delivery(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_delivery_attempt_delivery
+    ON webhook_delivery_attempt(delivery_id, attempt);
@@ -0,0 +1,6 @@
+-- Reverse migration: 0051_pricing_baseline
+ALTER TABLE quote_pricing_snapshot DROP COLUMN baseline_version;
+ALTER TABLE quote_pricing_snapshot DROP COLUMN anomaly_json;
+DROP INDEX IF EXISTS idx_pricing_baseline_product;
+DROP TABLE IF EXISTS pricing_baseline;
+DROP TABLE IF EXISTS pricing_baseline_version;
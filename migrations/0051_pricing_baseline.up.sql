@@ -0,0 +1,30 @@
+-- Migration: 0051_pricing_baseline
+-- Description: Versioned pricing baselines and per-snapshot anomaly scores
+-- Each refresh aggregates finalized quote lines from a trailing window into one baseline per
+-- product (segment_key 'all') and one per product and customer segment, written under a new
+-- pricing_baseline_version row. Older versions are kept for audit until pruned by the refresh.
+-- Every recorded pricing snapshot is scored against the newest version; the AnomalyScore and the
+-- version it used are stored on the snapshot row.
+
+CREATE TABLE pricing_baseline_version (
+    version INTEGER PRIMARY KEY AUTOINCREMENT,
+    method TEXT NOT NULL CHECK (method IN ('mean_std_dev', 'median_mad')),
+    window_days INTEGER NOT NULL CHECK (window_days >= 1),
+    sample_count INTEGER NOT NULL DEFAULT 0,
+    baseline_count INTEGER NOT NULL DEFAULT 0,
+    computed_at TEXT NOT NULL
+);
+
+CREATE TABLE pricing_baseline (
+    version INTEGER NOT NULL,
+    product_id TEXT NOT NULL,
+    segment_key TEXT NOT NULL DEFAULT 'all',
+    baseline_json TEXT NOT NULL,
+    PRIMARY KEY (version, product_id, segment_key),
+    FOREIGN KEY (version) REFERENCES pricing_baseline_version(version) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pricing_baseline_product ON pricing_baseline(product_id, segment_key);
+
+ALTER TABLE quote_pricing_snapshot ADD COLUMN anomaly_json TEXT;
+ALTER TABLE quote_pricing_snapshot ADD COLUMN baseline_version INTEGER;
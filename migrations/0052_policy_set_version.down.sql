@@ -0,0 +1,7 @@
+-- Reverse migration: 0052_policy_set_version
+DROP INDEX IF EXISTS idx_quote_pricing_snapshot_policy_version;
+ALTER TABLE quote_pricing_snapshot DROP COLUMN policy_version;
+DROP INDEX IF EXISTS idx_policy_canary_monitoring;
+DROP TABLE IF EXISTS policy_canary;
+DROP INDEX IF EXISTS idx_policy_set_version_active;
+DROP TABLE IF EXISTS policy_set_version;
@@ -0,0 +1,59 @@
+-- Migration: 0052_policy_set_version
+-- Description: Versioned live policy thresholds, canary rollouts and per-snapshot policy version
+-- Every distinct set of live PolicyThresholds gets a policy_set_version row. Exactly one version
+-- is 'active'; edits to the threshold org settings are folded into a new active version the next
+-- time thresholds are read. An approved optimizer candidate is applied as a 'canary' version that
+-- only its policy_canary cohort (reps and customer segments) evaluates against, until the canary
+-- is promoted to active or rolled back. Pricing snapshots record the version they were evaluated
+-- against.
+
+CREATE TABLE policy_set_version (
+    version INTEGER PRIMARY KEY CHECK (version >= 1),
+    status TEXT NOT NULL CHECK (status IN ('active', 'canary', 'superseded', 'rolled_back')),
+    source TEXT NOT NULL CHECK (source IN ('org_settings', 'candidate')),
+    thresholds_json TEXT NOT NULL,
+    -- Raw value_json of the threshold org settings this version was built from or promoted to.
+    org_settings_json TEXT NOT NULL DEFAULT '{}',
+    candidate_id TEXT,
+    parent_version INTEGER,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    activated_at TEXT,
+    retired_at TEXT
+);
+
+CREATE UNIQUE INDEX idx_policy_set_version_active
+    ON policy_set_version(status) WHERE status = 'active';
+
+CREATE TABLE policy_canary (
+    id TEXT PRIMARY KEY,
+    candidate_id TEXT NOT NULL,
+    apply_record_id TEXT NOT NULL,
+    base_version INTEGER NOT NULL,
+    canary_version INTEGER NOT NULL,
+    rep_ids_json TEXT NOT NULL DEFAULT '[]',
+    segment_keys_json TEXT NOT NULL DEFAULT '[]',
+    min_sessions INTEGER NOT NULL CHECK (min_sessions >= 1),
+    status TEXT NOT NULL CHECK (status IN ('monitoring', 'promoted', 'rolled_back')),
+    packet_json TEXT NOT NULL,
+    action_json TEXT NOT NULL,
+    signature_key_id TEXT NOT NULL,
+    kpi_report_json TEXT,
+    readiness_json TEXT,
+    decision_reason TEXT,
+    decided_by TEXT,
+    started_at TEXT NOT NULL,
+    last_evaluated_at TEXT,
+    decided_at TEXT,
+    FOREIGN KEY (apply_record_id) REFERENCES policy_apply_record(id) ON DELETE RESTRICT,
+    FOREIGN KEY (base_version) REFERENCES policy_set_version(version),
+    FOREIGN KEY (canary_version) REFERENCES policy_set_version(version)
+);
+
+CREATE UNIQUE INDEX idx_policy_canary_monitoring
+    ON policy_canary(status) WHERE status = 'monitoring';
+
+ALTER TABLE quote_pricing_snapshot ADD COLUMN policy_version INTEGER;
+
+CREATE INDEX idx_quote_pricing_snapshot_policy_version
+    ON quote_pricing_snapshot(policy_version);
@@ -0,0 +1,4 @@
+-- Reverse migration: 0053_policy_rule_set
+DROP TRIGGER IF EXISTS trg_policy_rule_set_version_no_delete;
+DROP TRIGGER IF EXISTS trg_policy_rule_set_version_no_update;
+DROP TABLE IF EXISTS policy_rule_set_version;
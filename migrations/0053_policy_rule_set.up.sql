@@ -0,0 +1,29 @@
+-- Migration: 0053_policy_rule_set
+-- Description: Immutable, versioned policy rule sets authored as visual rules
+-- Each published rule set is a new version holding the authored visual rules and the scoped,
+-- effective-dated policy rules compiled from them. The newest version is the one quotes are
+-- evaluated against; rows are never updated or deleted so past decisions stay reproducible.
+
+CREATE TABLE policy_rule_set_version (
+    version INTEGER PRIMARY KEY CHECK (version >= 1),
+    name TEXT NOT NULL,
+    visual_rules_json TEXT NOT NULL,
+    rules_json TEXT NOT NULL,
+    -- sha256 of rules_json, so identical republishes are easy to spot.
+    checksum TEXT NOT NULL,
+    parent_version INTEGER REFERENCES policy_rule_set_version(version),
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TRIGGER trg_policy_rule_set_version_no_update
+    BEFORE UPDATE ON policy_rule_set_version
+BEGIN
+    SELECT RAISE(ABORT, 'policy rule set versions are immutable');
+END;
+
+CREATE TRIGGER trg_policy_rule_set_version_no_delete
+    BEFORE DELETE ON policy_rule_set_version
+BEGIN
+    SELECT RAISE(ABORT, 'policy rule set versions are immutable');
+END;
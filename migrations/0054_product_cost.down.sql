@@ -0,0 +1,3 @@
+-- Reverse migration: 0054_product_cost
+DROP INDEX IF EXISTS idx_product_cost_lookup;
+DROP TABLE IF EXISTS product_cost;
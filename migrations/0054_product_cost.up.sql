@@ -0,0 +1,23 @@
+-- Migration: 0054_product_cost
+-- Description: Effective-dated product unit costs for margin computation
+-- A product may carry several cost records per currency. For a given day the record with the
+-- latest effective_from covering that day applies; effective_until is exclusive. Costs are
+-- internal and never rendered on customer-facing portal pages or PDFs.
+
+CREATE TABLE product_cost (
+    id TEXT PRIMARY KEY,
+    product_id TEXT NOT NULL REFERENCES product(id) ON DELETE CASCADE,
+    -- Decimal string in `currency`.
+    unit_cost TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    effective_from TEXT NOT NULL,
+    effective_until TEXT,
+    -- JSON array of {name, unit_cost}; empty when no COGS breakdown was given.
+    components_json TEXT NOT NULL DEFAULT '[]',
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    CHECK (effective_until IS NULL OR effective_until > effective_from)
+);
+
+CREATE INDEX idx_product_cost_lookup
+    ON product_cost(product_id, currency, effective_from DESC);
@@ -0,0 +1,13 @@
+-- Migration: 0055_collab_session_state (rollback)
+-- Description: Remove collaborative operation sequencing
+
+DROP INDEX IF EXISTS idx_session_operations_seq;
+
+ALTER TABLE session_operations DROP COLUMN superseded_by;
+ALTER TABLE session_operations DROP COLUMN reason;
+ALTER TABLE session_operations DROP COLUMN status;
+ALTER TABLE session_operations DROP COLUMN seq;
+
+ALTER TABLE quote_sessions DROP COLUMN closed_at;
+ALTER TABLE quote_sessions DROP COLUMN committed_version;
+ALTER TABLE quote_sessions DROP COLUMN base_version;
@@ -0,0 +1,20 @@
+-- Migration: 0055_collab_session_state
+-- Description: Sequence and resolve collaborative editing operations
+-- Operations get a per-session sequence number and the outcome of the transform that
+-- resolved them; sessions remember the quote version they forked from and the revision
+-- their converged state was committed as.
+-- Author: Quotey Team
+-- Date: 2026-10-18
+
+ALTER TABLE quote_sessions ADD COLUMN base_version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE quote_sessions ADD COLUMN committed_version INTEGER;
+ALTER TABLE quote_sessions ADD COLUMN closed_at TEXT;
+
+ALTER TABLE session_operations ADD COLUMN seq INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE session_operations ADD COLUMN status TEXT NOT NULL DEFAULT 'applied'
+    CHECK (status IN ('applied', 'overridden', 'rejected'));
+ALTER TABLE session_operations ADD COLUMN reason TEXT NOT NULL DEFAULT '';
+ALTER TABLE session_operations ADD COLUMN superseded_by TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_session_operations_seq
+    ON session_operations(session_id, seq);
@@ -0,0 +1,9 @@
+-- Reverse migration: 0056_quote_document_template
+DROP TABLE IF EXISTS quote_template_default;
+DROP TRIGGER IF EXISTS trg_quote_clause_block_no_delete;
+DROP TRIGGER IF EXISTS trg_quote_clause_block_no_update;
+DROP TABLE IF EXISTS quote_clause_block;
+DROP TRIGGER IF EXISTS trg_quote_template_version_no_delete;
+DROP TRIGGER IF EXISTS trg_quote_template_version_frozen;
+DROP INDEX IF EXISTS idx_quote_template_version_published;
+DROP TABLE IF EXISTS quote_template_version;
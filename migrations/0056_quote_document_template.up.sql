@@ -0,0 +1,77 @@
+-- Migration: 0056_quote_document_template
+-- Description: Versioned quote document templates, reusable clause blocks and default templates
+-- Template versions start as drafts that can be edited in place. Publishing freezes a version
+-- and retires the previously published one, so exactly one version per template is live.
+-- Clause blocks are immutable versions; templates reference them by key. Defaults map an
+-- account, a customer segment or the whole workspace to a template key.
+
+CREATE TABLE quote_template_version (
+    template_key TEXT NOT NULL,
+    version INTEGER NOT NULL CHECK (version >= 1),
+    label TEXT NOT NULL,
+    layout TEXT NOT NULL CHECK (layout IN ('detailed', 'executive_summary', 'compact')),
+    -- QuoteDocumentTemplate JSON: layout plus ordered, conditional sections.
+    definition_json TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('draft', 'published', 'retired')),
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    published_by TEXT,
+    published_at TEXT,
+    PRIMARY KEY (template_key, version)
+);
+
+CREATE UNIQUE INDEX idx_quote_template_version_published
+    ON quote_template_version(template_key)
+    WHERE status = 'published';
+
+-- Once published, only the status may move on (to retired); the content is frozen.
+CREATE TRIGGER trg_quote_template_version_frozen
+    BEFORE UPDATE ON quote_template_version
+    WHEN OLD.status <> 'draft'
+        AND (NEW.definition_json IS NOT OLD.definition_json
+             OR NEW.label IS NOT OLD.label
+             OR NEW.layout IS NOT OLD.layout
+             OR NEW.status = 'draft')
+BEGIN
+    SELECT RAISE(ABORT, 'published quote template versions are immutable');
+END;
+
+CREATE TRIGGER trg_quote_template_version_no_delete
+    BEFORE DELETE ON quote_template_version
+    WHEN OLD.status <> 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'published quote template versions are immutable');
+END;
+
+CREATE TABLE quote_clause_block (
+    clause_key TEXT NOT NULL,
+    version INTEGER NOT NULL CHECK (version >= 1),
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (clause_key, version)
+);
+
+CREATE TRIGGER trg_quote_clause_block_no_update
+    BEFORE UPDATE ON quote_clause_block
+BEGIN
+    SELECT RAISE(ABORT, 'quote clause blocks are immutable');
+END;
+
+CREATE TRIGGER trg_quote_clause_block_no_delete
+    BEFORE DELETE ON quote_clause_block
+BEGIN
+    SELECT RAISE(ABORT, 'quote clause blocks are immutable');
+END;
+
+CREATE TABLE quote_template_default (
+    scope_type TEXT NOT NULL CHECK (scope_type IN ('global', 'segment', 'account')),
+    -- Account id or segment name; empty for the global default.
+    scope_value TEXT NOT NULL,
+    template_key TEXT NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (scope_type, scope_value)
+);
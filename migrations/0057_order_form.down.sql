@@ -0,0 +1,6 @@
+-- Reverse migration: 0057_order_form
+DROP TRIGGER IF EXISTS trg_order_form_no_delete;
+DROP TRIGGER IF EXISTS trg_order_form_frozen;
+DROP INDEX IF EXISTS idx_order_form_amendment;
+DROP INDEX IF EXISTS idx_order_form_account;
+DROP TABLE IF EXISTS order_form;
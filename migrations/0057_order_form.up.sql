@@ -0,0 +1,57 @@
+-- Migration: 0057_order_form
+-- Description: Order forms generated from accepted quotes, with their own signing lifecycle
+-- content_json is the OrderFormContent snapshot (lines, pricing, billing schedule and the
+-- resolved template sections with clause text inlined); it is frozen once the order form leaves
+-- draft. quote_ledger_entry_id is the newest ledger entry of the quote when the order form was
+-- generated, normally its `accept` entry. Amendments point at the original order form and are
+-- numbered from 1.
+
+CREATE TABLE order_form (
+    id TEXT PRIMARY KEY,
+    quote_id TEXT NOT NULL UNIQUE,
+    account_id TEXT,
+    amends_order_id TEXT,
+    amendment_number INTEGER NOT NULL DEFAULT 0 CHECK (amendment_number >= 0),
+    status TEXT NOT NULL DEFAULT 'draft'
+        CHECK (status IN ('draft', 'sent', 'signed', 'countersigned')),
+    content_json TEXT NOT NULL,
+    content_sha256 TEXT NOT NULL,
+    -- Signatory blocks; signed_at is filled in as each party signs.
+    signatories_json TEXT NOT NULL DEFAULT '[]',
+    template_key TEXT NOT NULL,
+    template_version INTEGER,
+    quote_ledger_entry_id TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sent_at TEXT,
+    signed_at TEXT,
+    countersigned_at TEXT,
+    FOREIGN KEY (quote_id) REFERENCES quote(id),
+    FOREIGN KEY (amends_order_id) REFERENCES order_form(id),
+    FOREIGN KEY (quote_ledger_entry_id) REFERENCES quote_ledger(entry_id),
+    CHECK ((amends_order_id IS NULL) = (amendment_number = 0))
+);
+
+CREATE INDEX idx_order_form_account ON order_form(account_id);
+CREATE UNIQUE INDEX idx_order_form_amendment
+    ON order_form(amends_order_id, amendment_number)
+    WHERE amends_order_id IS NOT NULL;
+
+CREATE TRIGGER trg_order_form_frozen
+    BEFORE UPDATE ON order_form
+    WHEN OLD.status <> 'draft'
+        AND (NEW.content_json IS NOT OLD.content_json
+             OR NEW.content_sha256 IS NOT OLD.content_sha256
+             OR NEW.quote_id IS NOT OLD.quote_id
+             OR NEW.amends_order_id IS NOT OLD.amends_order_id)
+BEGIN
+    SELECT RAISE(ABORT, 'order form content is frozen once sent');
+END;
+
+CREATE TRIGGER trg_order_form_no_delete
+    BEFORE DELETE ON order_form
+    WHEN OLD.status <> 'draft'
+BEGIN
+    SELECT RAISE(ABORT, 'sent order forms cannot be deleted');
+END;
@@ -0,0 +1,2 @@
+-- Reverse migration: 0058_rest_idempotency_reservation
+DROP TABLE IF EXISTS rest_idempotency_reservation;
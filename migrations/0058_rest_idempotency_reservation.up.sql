@@ -0,0 +1,12 @@
+-- Migration: 0058_rest_idempotency_reservation
+-- Description: Claims on REST Idempotency-Keys, taken before the handler runs
+-- A row exists while a request holding the key is executing; the outcome itself is recorded in
+-- execution_idempotency_ledger. The table has no quote reference so creates can reserve a key
+-- before their quote exists.
+
+CREATE TABLE rest_idempotency_reservation (
+    operation_key TEXT PRIMARY KEY,
+    operation_kind TEXT NOT NULL,
+    payload_hash TEXT NOT NULL,
+    reserved_at TEXT NOT NULL
+);
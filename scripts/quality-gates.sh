@@ -15,6 +15,7 @@
 #   QUOTEY_SKIP_FMT=1        # Skip fmt gate
 #   QUOTEY_SKIP_CLIPPY=1     # Skip clippy gate
 #   QUOTEY_SKIP_TESTS=1      # Skip tests gate
+#   QUOTEY_SKIP_REPLAY=1     # Skip replay drift gate
 #   QUOTEY_SKIP_QA=1         # Skip QA threshold gate
 #   QUOTEY_SKIP_DENY=1       # Skip deny gate
 #   QUOTEY_SKIP_DOC=1        # Skip doc gate
@@ -26,6 +27,8 @@
 #   QUOTEY_THRESHOLD_E2E_PASS_PCT=100               # Minimum E2E pass rate
 #   QUOTEY_THRESHOLD_LOG_VALIDATOR_CASES_MIN=5      # Minimum log-validator cases
 #   QUOTEY_QA_REPORT_DIR=.planning/qa/reports       # QA dashboard/report artifact output dir
+#   QUOTEY_REPLAY_FIXTURES=path/to/fixtures.json    # `quotey replay export` output; gate skips when unset
+#   QUOTEY_REPLAY_ARGS="--default-policy"           # Extra `quotey replay run` arguments
 
 set -euo pipefail
 
@@ -130,6 +133,7 @@ while [[ $# -gt 0 ]]; do
       echo "  fmt     Check code formatting"
       echo "  clippy  Run clippy lints"
       echo "  tests   Run all tests"
+      echo "  replay  Replay recorded fixtures and fail on outcome drift"
       echo "  qa      Enforce QA threshold checks"
       echo "  deny    Run cargo-deny security checks"
       echo "  doc     Build documentation"
@@ -143,6 +147,7 @@ while [[ $# -gt 0 ]]; do
       echo "  QUOTEY_THRESHOLD_CRITICAL_PATH_GAPS_MAX=0"
       echo "  QUOTEY_THRESHOLD_E2E_PASS_PCT=100"
       echo "  QUOTEY_THRESHOLD_LOG_VALIDATOR_CASES_MIN=5"
+      echo "  QUOTEY_REPLAY_FIXTURES=path  Fixture file for the replay gate"
       echo ""
       echo "Examples:"
       echo "  $0                  # Run all gates"
@@ -151,14 +156,14 @@ while [[ $# -gt 0 ]]; do
       exit 0
       ;;
     --list)
-      echo "Available gates: build fmt clippy tests qa deny doc"
+      echo "Available gates: build fmt clippy tests replay qa deny doc"
       exit 0
       ;;
     --verbose|-v)
       export QUOTEY_VERBOSE=1
       shift
       ;;
-    build|fmt|clippy|tests|qa|deny|doc)
+    build|fmt|clippy|tests|replay|qa|deny|doc)
       RUN_ALL=false
       GATES_TO_RUN+=("$1")
       shift
@@ -256,6 +261,26 @@ run_tests() {
   run_gate "tests" cargo test --workspace
 }
 
+replay_fixture_check() {
+  local fixtures="${QUOTEY_REPLAY_FIXTURES:-}"
+  local -a extra_args=()
+  read -r -a extra_args <<< "${QUOTEY_REPLAY_ARGS:-}"
+  # `replay run` exits 7 when a recorded outcome drifted, failing the gate.
+  cargo run --quiet -p quotey-cli -- replay run --fixtures "$fixtures" ${extra_args[@]+"${extra_args[@]}"}
+}
+
+run_replay() {
+  if [[ -z "${QUOTEY_REPLAY_FIXTURES:-}" ]]; then
+    log "Skipping replay (QUOTEY_REPLAY_FIXTURES is not set)"
+    return 0
+  fi
+  if [[ ! -f "$QUOTEY_REPLAY_FIXTURES" ]]; then
+    log_error "replay fixtures not found: $QUOTEY_REPLAY_FIXTURES"
+    return 1
+  fi
+  run_gate "replay" replay_fixture_check
+}
+
 qa_threshold_checks() {
   local inventory_json
   inventory_json="$(mktemp "$TMPDIR/qa-thresholds.inventory.XXXXXX.json")"
@@ -825,6 +850,11 @@ if should_run_gate "tests"; then
   run_tests || FAILED=1
 fi
 
+# Replay drift (needs build)
+if should_run_gate "replay"; then
+  run_replay || FAILED=1
+fi
+
 # QA thresholds (depends on test inventory + E2E suite)
 if should_run_gate "qa"; then
   run_qa || FAILED=1